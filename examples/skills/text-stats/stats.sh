@@ -0,0 +1,15 @@
+#!/bin/sh
+# Arguments arrive as SKILL_ARG_<NAME> environment variables; whatever the
+# script prints is returned to the agent.
+text="$SKILL_ARG_TEXT"
+top="${SKILL_ARG_TOP:-3}"
+
+printf '%s\n' "$text" | wc -lw | awk '{ print "lines: " $1; print "words: " $2 }'
+echo "top words:"
+printf '%s\n' "$text" |
+    tr -cs '[:alnum:]' '\n' |
+    tr '[:upper:]' '[:lower:]' |
+    grep -v '^$' |
+    sort | uniq -c | sort -k1,1nr -k2 |
+    head -n "$top" |
+    awk '{ print "  " $2 " (" $1 ")" }'
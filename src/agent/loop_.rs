@@ -6,32 +6,65 @@ use crate::providers::traits::{
 };
 use crate::providers::{self, Provider};
 use crate::runtime;
+use crate::security::approval::{self, ApprovalDecision, ApprovalRequest};
+use crate::security::audit::AuditDecision;
+use crate::security::redact::redact;
 use crate::security::SecurityPolicy;
+use crate::sessions::Transcript;
 use crate::tools::{self, Tool};
+use crate::usage::ledger::UsageLog;
+use crate::usage::{TurnUsage, UsageMeter};
 use crate::util::truncate_with_ellipsis;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::fmt::Write;
 use std::sync::Arc;
 use std::time::Instant;
+use tokio_util::sync::CancellationToken;
 
 /// Build context preamble by searching memory for relevant entries
-async fn build_context(mem: &dyn Memory, user_msg: &str) -> String {
+pub(crate) async fn build_context(mem: &dyn Memory, user_msg: &str) -> String {
     let mut context = String::new();
 
     // Pull relevant memories for this message
-    if let Ok(entries) = mem.recall(user_msg, 5).await {
-        if !entries.is_empty() {
-            context.push_str("[Memory context]\n");
-            for entry in &entries {
-                let _ = writeln!(context, "- {}: {}", entry.key, entry.content);
-            }
-            context.push('\n');
+    if let Ok(entries) = mem.recall(user_msg, 5, &[]).await
+        && !entries.is_empty()
+    {
+        context.push_str("[Memory context]\n");
+        for entry in &entries {
+            let _ = writeln!(context, "- {}: {}", entry.key, entry.content);
         }
+        context.push('\n');
     }
 
     context
 }
 
+/// Refusal markers in tool errors and the reason logged for each. Only the
+/// fixed reason goes into the audit log, never the error text itself.
+const AUDIT_DENIALS: &[(&str, &str)] = &[
+    ("not allowed by security policy", "security policy"),
+    ("not permitted by policy", "security policy"),
+    ("escapes workspace", "workspace escape"),
+    ("denied by the user", "denied by user"),
+    ("no approval received", "approval timed out"),
+    ("Confirmation required", "destructive command not confirmed"),
+    ("操作被阻止", "security policy"),
+];
+
+/// Whether a tool result was a policy refusal, for the audit log.
+fn audit_decision(tool_result: &str) -> (AuditDecision, Option<&'static str>) {
+    tool_result
+        .strip_prefix("Error: ")
+        .and_then(|error| {
+            AUDIT_DENIALS
+                .iter()
+                .find(|(marker, _)| error.contains(marker))
+        })
+        .map_or((AuditDecision::Allowed, None), |(_, reason)| {
+            (AuditDecision::Denied, Some(*reason))
+        })
+}
+
 /// Execute a list of tool calls against the tool registry.
 ///
 /// Returns a `ChatMessage::Tool` for each call (success or error).
@@ -60,7 +93,7 @@ pub async fn execute_tool_calls(
         };
 
         // Rate limit check
-        if !security.record_action() {
+        if !security.admit_tool_call(tool_name, &tc.function.arguments) {
             tracing::warn!(tool = tool_name, "工具调用超出速率限制");
             results.push(ChatMessage::Tool {
                 tool_call_id: tc.id.clone(),
@@ -90,6 +123,10 @@ pub async fn execute_tool_calls(
         if !quiet {
             tracing::info!(tool = tool_name, "正在执行工具");
         }
+        observer.record_event(&ObserverEvent::ToolStart {
+            tool: tool_name.clone(),
+            arguments: redact(&tc.function.arguments),
+        });
         let tool_result = match tool.execute(args).await {
             Ok(result) => {
                 if result.success {
@@ -106,11 +143,23 @@ pub async fn execute_tool_calls(
 
         let duration = tool_start.elapsed();
         let success = !tool_result.starts_with("Error:");
+        let (decision, reason) = audit_decision(&tool_result);
+        security.audit.record_execution(
+            tool_name,
+            &tc.function.arguments,
+            decision,
+            reason,
+            success,
+            duration,
+        );
 
         observer.record_event(&ObserverEvent::ToolCall {
             tool: tool_name.clone(),
             duration,
             success,
+            error: tool_result
+                .strip_prefix("Error: ")
+                .map(|e| truncate_with_ellipsis(&redact(e), 200)),
         });
 
         if !quiet {
@@ -131,12 +180,31 @@ pub async fn execute_tool_calls(
     results
 }
 
-/// Trim conversation history to keep at most `max_turns` User turns.
+/// Header of the synthetic System message (index 1) holding summarized older turns.
+pub const COMPACT_SUMMARY_HEADER: &str = "[Summary of earlier conversation]";
+
+/// User turns kept verbatim by a manual `/compact`.
+pub const MANUAL_COMPACT_KEEP_TURNS: usize = 2;
+
+const COMPACT_PROMPT: &str = "You compress conversation history. Summarize the following \
+turns into a compact context note for the assistant continuing this conversation. Keep \
+facts, decisions, user preferences, names, paths, commands and open tasks; drop small talk \
+and tool output details. Reply with the note only.";
+
+/// Max chars of a single message included in the text sent for summarizing.
+const COMPACT_MESSAGE_CHARS: usize = 1500;
+
+fn is_compact_summary(msg: &ChatMessage) -> bool {
+    matches!(msg, ChatMessage::System { content } if content.starts_with(COMPACT_SUMMARY_HEADER))
+}
+
+/// Messages to drop so that at most `max_turns` User turns remain.
 ///
-/// System message (index 0) is always preserved. `max_turns == 0` means no limit.
-pub fn trim_history(history: &mut Vec<ChatMessage>, max_turns: usize) {
+/// Never includes the System message (index 0) or a compacted summary at
+/// index 1. `None` when nothing needs to go (or `max_turns == 0`).
+fn trim_range(history: &[ChatMessage], max_turns: usize) -> Option<std::ops::Range<usize>> {
     if max_turns == 0 {
-        return;
+        return None;
     }
 
     // Count User messages
@@ -146,25 +214,183 @@ pub fn trim_history(history: &mut Vec<ChatMessage>, max_turns: usize) {
         .count();
 
     if user_count <= max_turns {
-        return;
+        return None;
     }
 
-    // Find the cut point: skip the first (user_count - max_turns) User messages,
-    // then drain everything between index 1 and the start of the kept portion.
+    // Find the cut point: skip the first (user_count - max_turns) User messages;
+    // everything between the preserved head and the kept portion goes.
+    let start = if history.get(1).is_some_and(is_compact_summary) {
+        2
+    } else {
+        1
+    };
     let skip = user_count - max_turns;
     let mut user_seen = 0;
-    let mut cut_index = 1; // start after System message
-    for (i, msg) in history.iter().enumerate().skip(1) {
-        if matches!(msg, ChatMessage::User { .. }) {
-            user_seen += 1;
-            if user_seen > skip {
-                cut_index = i;
-                break;
+    let cut_index = history
+        .iter()
+        .enumerate()
+        .skip(start)
+        .find(|(_, msg)| {
+            if matches!(msg, ChatMessage::User { .. }) {
+                user_seen += 1;
             }
+            user_seen > skip
+        })
+        .map(|(i, _)| i)?;
+
+    (cut_index > start).then_some(start..cut_index)
+}
+
+/// Trim conversation history to keep at most `max_turns` User turns.
+///
+/// System message (index 0) and any compacted summary are always preserved.
+/// `max_turns == 0` means no limit.
+pub fn trim_history(history: &mut Vec<ChatMessage>, max_turns: usize) {
+    if let Some(range) = trim_range(history, max_turns) {
+        history.drain(range);
+    }
+}
+
+/// Like [`trim_history`], but the turns that would be dropped are first
+/// summarized by the provider. The summary lives in a System message at
+/// index 1; later compactions append to it rather than summarizing it again.
+///
+/// Returns the number of messages compacted (0 when within the limit). On
+/// error the history is left unchanged.
+pub async fn compact_history(
+    provider: &dyn Provider,
+    history: &mut Vec<ChatMessage>,
+    max_turns: usize,
+    model: &str,
+) -> Result<usize> {
+    let Some(range) = trim_range(history, max_turns) else {
+        return Ok(0);
+    };
+
+    let transcript = compact_transcript(&history[range.clone()]);
+    let summary = provider
+        .chat_with_system(Some(COMPACT_PROMPT), &transcript, model, 0.2)
+        .await
+        .context("总结对话历史失败")?;
+    let summary = summary.trim();
+    anyhow::ensure!(!summary.is_empty(), "模型返回的对话摘要为空");
+
+    let compacted = range.len();
+    history.drain(range);
+    match history.get_mut(1) {
+        Some(ChatMessage::System { content }) if content.starts_with(COMPACT_SUMMARY_HEADER) => {
+            content.push_str("\n\n");
+            content.push_str(summary);
         }
+        _ => history.insert(
+            1,
+            ChatMessage::System {
+                content: format!("{COMPACT_SUMMARY_HEADER}\n{summary}"),
+            },
+        ),
     }
+    Ok(compacted)
+}
 
-    history.drain(1..cut_index);
+/// Keep `history` within `max_turns` before a new turn: compacted when
+/// `compact` is set (falling back to trimming if summarizing fails),
+/// trimmed otherwise.
+pub async fn limit_history(
+    provider: &dyn Provider,
+    history: &mut Vec<ChatMessage>,
+    max_turns: usize,
+    model: &str,
+    compact: bool,
+) {
+    if compact {
+        match compact_history(provider, history, max_turns, model).await {
+            Ok(_) => return,
+            Err(e) => tracing::warn!("压缩对话历史失败，改为直接裁剪: {e:#}"),
+        }
+    }
+    trim_history(history, max_turns);
+}
+
+/// Plain-text rendering of `messages` for the summarization request.
+fn compact_transcript(messages: &[ChatMessage]) -> String {
+    let mut transcript = String::new();
+    for msg in messages {
+        match msg {
+            ChatMessage::System { content } => {
+                let _ = writeln!(
+                    transcript,
+                    "System: {}",
+                    truncate_with_ellipsis(content, COMPACT_MESSAGE_CHARS)
+                );
+            }
+            ChatMessage::User { content } => {
+                let _ = writeln!(
+                    transcript,
+                    "User: {}",
+                    truncate_with_ellipsis(content, COMPACT_MESSAGE_CHARS)
+                );
+            }
+            ChatMessage::Assistant {
+                content,
+                tool_calls,
+            } => {
+                if let Some(text) = content.as_deref().filter(|t| !t.trim().is_empty()) {
+                    let _ = writeln!(
+                        transcript,
+                        "Assistant: {}",
+                        truncate_with_ellipsis(text, COMPACT_MESSAGE_CHARS)
+                    );
+                }
+                for call in tool_calls.iter().flatten() {
+                    let _ = writeln!(
+                        transcript,
+                        "Assistant called {}: {}",
+                        call.function.name,
+                        truncate_with_ellipsis(&call.function.arguments, 200)
+                    );
+                }
+            }
+            ChatMessage::Tool { content, .. } => {
+                let _ = writeln!(
+                    transcript,
+                    "Tool result: {}",
+                    truncate_with_ellipsis(content, 300)
+                );
+            }
+        }
+    }
+    transcript
+}
+
+/// The error [`run_tool_loop`] returns when its token is cancelled. The
+/// history is left ready for the next turn.
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("本轮对话已取消")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Told to the model in place of the answer of a cancelled turn.
+const CANCELLED_NOTE: &str = "[The user cancelled this turn before it finished.]";
+
+/// End a cancelled turn: close it with a note, so the history never ends
+/// on a user message or on tool calls without results.
+fn cancel_turn(
+    history: &mut Vec<ChatMessage>,
+    transcript: &Transcript,
+    model: &str,
+) -> anyhow::Error {
+    transcript.assistant(CANCELLED_NOTE, model);
+    history.push(ChatMessage::Assistant {
+        content: Some(CANCELLED_NOTE.to_string()),
+        tool_calls: None,
+    });
+    Cancelled.into()
 }
 
 /// Run the tool-calling loop: send messages → parse `tool_calls` → execute → feedback → repeat.
@@ -177,7 +403,12 @@ pub fn trim_history(history: &mut Vec<ChatMessage>, max_turns: usize) {
 /// Returns the final text response from the model.
 ///
 /// When `quiet` is true, suppresses all stdout/stderr output (for TUI mode).
-#[allow(clippy::too_many_arguments)]
+/// Assistant replies and tool calls/results are appended to `transcript`; the
+/// caller records the user's message (before memory context is injected).
+///
+/// Cancelling `cancel` stops the turn at once, dropping the provider call or
+/// tools in flight, and returns [`Cancelled`].
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
 pub async fn run_tool_loop(
     provider: &dyn Provider,
     history: &mut Vec<ChatMessage>,
@@ -188,15 +419,20 @@ pub async fn run_tool_loop(
     max_iterations: usize,
     security: &SecurityPolicy,
     observer: &dyn Observer,
+    transcript: &Transcript,
     quiet: bool,
+    cancel: &CancellationToken,
 ) -> Result<String> {
     for iteration in 0..max_iterations {
-        let response = provider
-            .chat_with_tools(history, tool_definitions, model, temperature)
-            .await?;
+        let response = tokio::select! {
+            biased;
+            () = cancel.cancelled() => return Err(cancel_turn(history, transcript, model)),
+            response = provider.chat_with_tools(history, tool_definitions, model, temperature, None) => response?,
+        };
 
         match response {
             ChatResponse::Text(text) => {
+                transcript.assistant(&text, model);
                 // Append the assistant's final text to history so subsequent calls see it
                 history.push(ChatMessage::Assistant {
                     content: Some(text.clone()),
@@ -218,6 +454,13 @@ pub async fn run_tool_loop(
                     }
                 }
 
+                if let Some(text) = assistant_text.as_deref().filter(|t| !t.trim().is_empty()) {
+                    transcript.assistant(text, model);
+                }
+                for call in &tool_calls {
+                    transcript.tool_call(call);
+                }
+
                 // Append assistant message with tool_calls
                 history.push(ChatMessage::Assistant {
                     content: assistant_text,
@@ -225,8 +468,28 @@ pub async fn run_tool_loop(
                 });
 
                 // Execute all tool calls
-                let tool_results =
-                    execute_tool_calls(&tool_calls, tools, security, observer, quiet).await;
+                let tool_results = tokio::select! {
+                    biased;
+                    () = cancel.cancelled() => {
+                        history.extend(tool_calls.iter().map(|call| ChatMessage::Tool {
+                            tool_call_id: call.id.clone(),
+                            content: "Error: cancelled by the user before it finished".into(),
+                        }));
+                        return Err(cancel_turn(history, transcript, model));
+                    }
+                    results = execute_tool_calls(&tool_calls, tools, security, observer, quiet) => results,
+                };
+
+                for result in &tool_results {
+                    if let ChatMessage::Tool {
+                        content,
+                        tool_call_id,
+                    } = result
+                        && let Some(call) = tool_calls.iter().find(|tc| tc.id == *tool_call_id)
+                    {
+                        transcript.tool_result(call, content);
+                    }
+                }
 
                 // Print tool results for user visibility (skip in TUI mode)
                 if !quiet {
@@ -254,35 +517,35 @@ pub async fn run_tool_loop(
 
     // Max iterations reached — ask for a final text response without tools
     tracing::warn!(max_iterations, "工具循环已达最大迭代次数，正在请求最终响应");
+    let wrap_up = "你已达到工具调用的最大迭代次数。请根据目前收集的信息，立即给出最终回答。";
+    transcript.user(wrap_up);
     history.push(ChatMessage::User {
-        content: "你已达到工具调用的最大迭代次数。请根据目前收集的信息，立即给出最终回答。"
-            .to_string(),
+        content: wrap_up.to_string(),
     });
 
-    let final_response = provider
-        .chat_with_tools(history, &[], model, temperature)
-        .await?;
+    let final_response = tokio::select! {
+        biased;
+        () = cancel.cancelled() => return Err(cancel_turn(history, transcript, model)),
+        response = provider.chat_with_tools(history, &[], model, temperature, None) => response?,
+    };
 
-    match final_response {
-        ChatResponse::Text(text) => {
-            history.push(ChatMessage::Assistant {
-                content: Some(text.clone()),
-                tool_calls: None,
-            });
-            Ok(text)
-        }
+    let final_text = match final_response {
+        ChatResponse::Text(text) => text,
         ChatResponse::ToolUse { text, .. } => {
-            let final_text =
-                text.unwrap_or_else(|| "在迭代次数限制内未能给出最终回答。".to_string());
-            history.push(ChatMessage::Assistant {
-                content: Some(final_text.clone()),
-                tool_calls: None,
-            });
-            Ok(final_text)
+            text.unwrap_or_else(|| "在迭代次数限制内未能给出最终回答。".to_string())
         }
-    }
+    };
+    transcript.assistant(&final_text, model);
+    history.push(ChatMessage::Assistant {
+        content: Some(final_text.clone()),
+        tool_calls: None,
+    });
+    Ok(final_text)
 }
 
+/// Answer `message`, or chat interactively when it is `None`. Returns the
+/// final response in single-message mode. `origin` (cli, heartbeat, cron, …)
+/// tags the audit log and the session transcript.
 #[allow(clippy::too_many_lines)]
 pub async fn run(
     config: Config,
@@ -290,15 +553,19 @@ pub async fn run(
     provider_override: Option<String>,
     model_override: Option<String>,
     temperature: f64,
-) -> Result<()> {
+    origin: &str,
+) -> Result<Option<String>> {
     // ── Wire up agnostic subsystems ──────────────────────────────
     let observer: Arc<dyn Observer> =
         Arc::from(observability::create_observer(&config.observability));
-    let _runtime = runtime::create_runtime(&config.runtime)?;
-    let security = Arc::new(SecurityPolicy::from_config(
-        &config.autonomy,
+    let runtime: Arc<dyn runtime::RuntimeAdapter> = Arc::from(runtime::create_runtime(
+        &config.runtime,
         &config.workspace_dir,
-    ));
+    )?);
+    let security = Arc::new(
+        SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir).with_origin(origin),
+    );
+    let transcript = Transcript::start(&config.workspace_dir, origin);
 
     // ── Memory (the brain) ────────────────────────────────────────
     let mem: Arc<dyn Memory> = Arc::from(memory::create_memory(
@@ -314,12 +581,19 @@ pub async fn run(
     } else {
         None
     };
+    let skills = crate::skills::load_skills(&config.workspace_dir);
     let tools = tools::all_tools(
         &security,
         mem.clone(),
         composio_key,
         &config.browser,
         &config.brave_search,
+        &config.git,
+        &config.web_fetch,
+        &config.tools,
+        &config.secrets.named,
+        &skills,
+        runtime,
     );
 
     // Build tool definitions for the API
@@ -343,7 +617,9 @@ pub async fn run(
         provider_name,
         config.api_key.as_deref(),
         &config.reliability,
+        observer.clone(),
     )?;
+    let usage_log = UsageLog::new(&config.workspace_dir, provider_name, &transcript);
 
     observer.record_event(&ObserverEvent::AgentStart {
         provider: provider_name.to_string(),
@@ -351,7 +627,6 @@ pub async fn run(
     });
 
     // ── Build system prompt from workspace MD files (OpenClaw framework) ──
-    let skills = crate::skills::load_skills(&config.workspace_dir);
     let mut tool_descs: Vec<(&str, &str)> = vec![
         (
             "shell",
@@ -363,7 +638,11 @@ pub async fn run(
         ),
         (
             "file_write",
-            "Write file contents. Use when: applying focused edits, scaffolding files, updating docs/code. Don't use when: side effects are unclear or file ownership is uncertain.",
+            "Write a whole file. Use when: creating files or replacing most of a file's content. Don't use when: changing part of an existing file (use file_edit), or side effects are unclear or file ownership is uncertain.",
+        ),
+        (
+            "file_edit",
+            "Replace exact text in an existing file (old_text/new_text pairs or diff hunks); each old_text must match once. Use when: changing part of an existing file; prefer it over file_write there. Don't use when: creating a new file.",
         ),
         (
             "memory_store",
@@ -377,6 +656,18 @@ pub async fn run(
             "memory_forget",
             "Delete a memory entry. Use when: memory is incorrect/stale or explicitly requested for removal. Don't use when: impact is uncertain.",
         ),
+        (
+            "task_add",
+            "Add a task to the user's task list, optionally with a due time. Use when: the user asks to be reminded or an open loop needs tracking. Don't use when: it is a fact to remember (use memory_store).",
+        ),
+        (
+            "task_list",
+            "List open tasks with ids and due times. Use when: checking what is pending or overdue. Don't use when: the task list was just listed.",
+        ),
+        (
+            "task_complete",
+            "Mark a task done by id. Use when: a tracked task is finished. Don't use when: the task should be dropped without being done (ask the user).",
+        ),
     ];
     if config.browser.enabled {
         tool_descs.push((
@@ -384,10 +675,51 @@ pub async fn run(
             "Open approved HTTPS URLs in Brave Browser (allowlist-only, no scraping)",
         ));
     }
-    if config.brave_search.enabled {
+    if crate::tools::web_search::provider_from_config(
+        &config.tools.web_search,
+        &config.brave_search,
+    )
+    .is_some()
+    {
         tool_descs.push((
             "web_search",
-            "Search the web using Brave Search. Use when: you need current information, facts, documentation, or any knowledge beyond your training data.",
+            "Search the web. Use when: you need current information, facts, documentation, or any knowledge beyond your training data.",
+        ));
+    }
+    if config.web_fetch.enabled {
+        tool_descs.push((
+            "web_fetch",
+            "Fetch a URL and return its readable text. Use when: reading a specific page, e.g. a web_search result or a link the user shared. Don't use when: the URL is local/private or a search would answer the question.",
+        ));
+    }
+    if config.tools.http.enabled {
+        tool_descs.push((
+            "http_request",
+            "Call a JSON API on an allowed host (method, url, headers, body). Use when: the user wants you to query or control a service they configured, e.g. Home Assistant. Pass credentials as secret:<name>, never inline.",
+        ));
+    }
+    if config.tools.clipboard.enabled {
+        tool_descs.push((
+            "clipboard",
+            "Read (get) or replace (set) the user's clipboard text. Use when: the user refers to what they copied or wants text to paste elsewhere. Don't use when: they haven't mentioned the clipboard; it may hold passwords.",
+        ));
+    }
+    if config.tools.reminders.enabled {
+        tool_descs.push((
+            "reminders",
+            "Add, list or complete to-dos in the user's iCalendar (.ics) reminders file. Use when: the user wants a reminder their calendar app can see, or asks what is due (list with due_before). Don't use when: a plain task_add is enough.",
+        ));
+    }
+    if config.tools.python.enabled {
+        tool_descs.push((
+            "python",
+            "Run a Python program in the workspace and return its printed output. Use when: calculating, parsing or analysing data (CSV, JSON, logs) where a few lines of Python beat a shell one-liner. Don't use when: a single shell command does it. Print what you need; only output is returned.",
+        ));
+    }
+    if config.git.enabled {
+        tool_descs.push((
+            "git",
+            "Inspect and commit repository changes (status, diff, log, add, commit, branch). Use when: reviewing or committing work in the workspace repo. Don't use when: the user hasn't asked for a commit.",
         ));
     }
     let system_prompt = crate::channels::build_system_prompt(
@@ -402,8 +734,12 @@ pub async fn run(
 
     // ── Execute ──────────────────────────────────────────────────
     let start = Instant::now();
+    let mut usage = UsageMeter::default();
+    let mut final_response = None;
 
     if let Some(msg) = message {
+        transcript.user(&msg);
+
         // Auto-save user message to memory
         if config.memory.auto_save {
             let _ = mem
@@ -437,9 +773,14 @@ pub async fn run(
             max_iterations,
             &security,
             observer.as_ref(),
+            &transcript,
             false,
+            &CancellationToken::new(),
         )
         .await?;
+        let turn_usage = TurnUsage::of_turn(&history, 1, &tool_definitions, model_name);
+        usage_log.record(model_name, &turn_usage);
+        usage.add(&turn_usage);
         println!("{response}");
 
         // Auto-save assistant response to daily log
@@ -449,9 +790,10 @@ pub async fn run(
                 .store("assistant_resp", &summary, MemoryCategory::Daily)
                 .await;
         }
+        final_response = Some(response);
     } else {
         println!("🤖 Jarvis 交互模式");
-        println!("输入 /quit 退出。\n");
+        println!("输入 /quit 退出，/compact 压缩较早的对话历史；回复过程中按 Ctrl+C 取消本轮。\n");
 
         let (tx, mut rx) = tokio::sync::mpsc::channel(32);
         let cli = crate::channels::CliChannel::new();
@@ -465,8 +807,21 @@ pub async fn run(
         let mut history = vec![ChatMessage::System {
             content: system_prompt.clone(),
         }];
+        // Shell commands off the allowlist are confirmed on the next input line
+        let mut approvals = security.approvals.attach();
+
+        // Ctrl+C cancels the turn in progress, and quits at the prompt
+        while let Some(msg) = tokio::select! {
+            msg = rx.recv() => msg,
+            _ = tokio::signal::ctrl_c() => None,
+        } {
+            if msg.content.trim() == "/compact" {
+                compact_command(provider.as_ref(), &mut history, model_name).await;
+                continue;
+            }
+
+            transcript.user(&msg.content);
 
-        while let Some(msg) = rx.recv().await {
             // Auto-save conversation turns
             if config.memory.auto_save {
                 let _ = mem
@@ -482,11 +837,20 @@ pub async fn run(
                 format!("{context}{}", msg.content)
             };
 
-            // Trim history before adding new turn
-            trim_history(&mut history, max_history_turns);
+            // Trim (or compact) history before adding new turn
+            limit_history(
+                provider.as_ref(),
+                &mut history,
+                max_history_turns,
+                model_name,
+                config.autonomy.compact_history,
+            )
+            .await;
+            let turn_start = history.len();
             history.push(ChatMessage::User { content: enriched });
 
-            let response = run_tool_loop(
+            let cancel = CancellationToken::new();
+            let turn = run_tool_loop(
                 provider.as_ref(),
                 &mut history,
                 &tools,
@@ -496,9 +860,25 @@ pub async fn run(
                 max_iterations,
                 &security,
                 observer.as_ref(),
+                &transcript,
                 false,
-            )
-            .await?;
+                &cancel,
+            );
+            let result =
+                answer_approvals_during(turn, &mut approvals, &mut rx, &security, &config, &cancel)
+                    .await;
+            let turn_usage =
+                TurnUsage::of_turn(&history, turn_start, &tool_definitions, model_name);
+            usage_log.record(model_name, &turn_usage);
+            usage.add(&turn_usage);
+            let response = match result {
+                Ok(response) => response,
+                Err(e) if e.is::<Cancelled>() => {
+                    println!("\n⏹  已取消本轮回复。\n");
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
             println!("\n{response}\n");
 
             if config.memory.auto_save {
@@ -512,13 +892,88 @@ pub async fn run(
         listen_handle.abort();
     }
 
-    let duration = start.elapsed();
-    observer.record_event(&ObserverEvent::AgentEnd {
+    observer.record_event(&agent_end(start.elapsed(), &usage));
+    observer.flush();
+
+    Ok(final_response)
+}
+
+/// The `AgentEnd` event of a run that took `duration` and used `usage`.
+pub(crate) fn agent_end(duration: std::time::Duration, usage: &UsageMeter) -> ObserverEvent {
+    let tokens = usage.tokens.prompt_tokens + usage.tokens.completion_tokens;
+    ObserverEvent::AgentEnd {
         duration,
-        tokens_used: None,
-    });
+        tokens_used: (tokens > 0).then_some(tokens),
+        cost_usd: (tokens > 0 && !usage.unpriced).then_some(usage.cost_usd),
+    }
+}
+
+/// Drive one interactive turn, answering shell approval requests with the
+/// user's next input line. Ctrl+C cancels the turn through `cancel`.
+async fn answer_approvals_during(
+    turn: impl std::future::Future<Output = Result<String>>,
+    approvals: &mut tokio::sync::mpsc::UnboundedReceiver<ApprovalRequest>,
+    input: &mut tokio::sync::mpsc::Receiver<crate::channels::traits::ChannelMessage>,
+    security: &SecurityPolicy,
+    config: &Config,
+    cancel: &CancellationToken,
+) -> Result<String> {
+    tokio::pin!(turn);
+    loop {
+        let request = tokio::select! {
+            result = &mut turn => return result,
+            Some(request) = approvals.recv() => request,
+            _ = tokio::signal::ctrl_c(), if !cancel.is_cancelled() => {
+                cancel.cancel();
+                continue;
+            }
+        };
+
+        // The turn is blocked on this answer, so its own timeout can't fire
+        // while we wait here; apply the same limit to the input instead.
+        match (&request.danger, request.unlisted.is_empty()) {
+            (Some(danger), true) => println!(
+                "\n⚠️  `{}` 可能具有破坏性（{danger}），是否执行？[y/N]",
+                request.command
+            ),
+            (Some(danger), false) => println!(
+                "\n⚠️  `{}` 可能具有破坏性（{danger}），是否允许？[y/N/always]",
+                request.command
+            ),
+            (None, _) => println!("\n🔐 是否允许执行 `{}`？[y/N/always]", request.command),
+        }
+        let reply = tokio::select! {
+            reply = tokio::time::timeout(security.approvals.timeout(), input.recv()) => reply,
+            _ = tokio::signal::ctrl_c() => {
+                request.respond(ApprovalDecision::Denied);
+                cancel.cancel();
+                continue;
+            }
+        };
+        let unlisted = request.unlisted.join(", ");
+        let decision = if let Ok(Some(msg)) = reply {
+            approval::answer(request, &msg.content, config)
+        } else {
+            request.respond(ApprovalDecision::TimedOut);
+            ApprovalDecision::TimedOut
+        };
+        match decision {
+            ApprovalDecision::Approved => println!("✅ 已允许。"),
+            ApprovalDecision::Always if unlisted.is_empty() => println!("✅ 已允许。"),
+            ApprovalDecision::Always => println!("✅ 已允许，本次会话不再询问: {unlisted}"),
+            ApprovalDecision::Denied => println!("🚫 已拒绝。"),
+            ApprovalDecision::TimedOut => println!("⌛ 等待确认超时，命令未执行。"),
+        }
+    }
+}
 
-    Ok(())
+/// `/compact` in interactive mode: summarize all but the latest turns.
+async fn compact_command(provider: &dyn Provider, history: &mut Vec<ChatMessage>, model: &str) {
+    match compact_history(provider, history, MANUAL_COMPACT_KEEP_TURNS, model).await {
+        Ok(0) => println!("没有需要压缩的历史。\n"),
+        Ok(n) => println!("🗜  已将 {n} 条较早的消息压缩为摘要。\n"),
+        Err(e) => println!("⚠️  压缩失败: {e:#}\n"),
+    }
 }
 
 #[cfg(test)]
@@ -550,6 +1005,7 @@ mod tests {
             _tools: &[ToolDefinition],
             _model: &str,
             _temperature: f64,
+            _response_format: Option<&providers::ResponseFormat>,
         ) -> anyhow::Result<ChatResponse> {
             let idx = self
                 .call_count
@@ -630,7 +1086,9 @@ mod tests {
             10,
             &security,
             &observer,
+            &Transcript::default(),
             true,
+            &CancellationToken::new(),
         )
         .await
         .unwrap();
@@ -684,7 +1142,9 @@ mod tests {
             10,
             &security,
             &observer,
+            &Transcript::default(),
             true,
+            &CancellationToken::new(),
         )
         .await
         .unwrap();
@@ -728,7 +1188,9 @@ mod tests {
             10,
             &security,
             &observer,
+            &Transcript::default(),
             true,
+            &CancellationToken::new(),
         )
         .await
         .unwrap();
@@ -780,7 +1242,9 @@ mod tests {
             3, // only 3 iterations
             &security,
             &observer,
+            &Transcript::default(),
             true,
+            &CancellationToken::new(),
         )
         .await
         .unwrap();
@@ -795,8 +1259,152 @@ mod tests {
         );
     }
 
+    /// Keeps asking for `echo`, and cancels `cancel` on call `cancel_on`.
+    struct CancellingProvider {
+        cancel: CancellationToken,
+        cancel_on: usize,
+        call_count: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for CancellingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("fallback".into())
+        }
+
+        async fn chat_with_tools(
+            &self,
+            _messages: &[ChatMessage],
+            _tools: &[ToolDefinition],
+            _model: &str,
+            _temperature: f64,
+            _response_format: Option<&providers::ResponseFormat>,
+        ) -> anyhow::Result<ChatResponse> {
+            let n = 1 + self
+                .call_count
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if n == self.cancel_on {
+                self.cancel.cancel();
+            }
+            Ok(ChatResponse::ToolUse {
+                tool_calls: vec![ToolCall {
+                    id: format!("call_{n}"),
+                    function: FunctionCall {
+                        name: "echo".into(),
+                        arguments: r#"{"text":"again"}"#.into(),
+                    },
+                }],
+                text: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn cancelled_tool_loop_stops_calling_the_provider() {
+        let tool = make_echo_tool();
+        let tool_defs = vec![tool_spec_to_definition(&tool.spec())];
+        let security = SecurityPolicy {
+            max_actions_per_hour: 100,
+            ..SecurityPolicy::default()
+        };
+        let provider = CancellingProvider {
+            cancel: CancellationToken::new(),
+            cancel_on: 2,
+            call_count: std::sync::atomic::AtomicUsize::new(0),
+        };
+
+        let mut history = make_history("system", "loop forever");
+        let err = run_tool_loop(
+            &provider,
+            &mut history,
+            &[tool],
+            &tool_defs,
+            "model",
+            0.7,
+            10,
+            &security,
+            &crate::observability::NoopObserver,
+            &Transcript::default(),
+            true,
+            &provider.cancel,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.is::<Cancelled>());
+        assert_eq!(
+            provider
+                .call_count
+                .load(std::sync::atomic::Ordering::SeqCst),
+            2
+        );
+        // The cancelled call still gets a result, and a note ends the turn
+        assert_eq!(history.len(), 7);
+        assert!(matches!(
+            &history[5],
+            ChatMessage::Tool { tool_call_id, content }
+                if tool_call_id == "call_2" && content.contains("cancelled")
+        ));
+        assert!(matches!(
+            &history[6],
+            ChatMessage::Assistant { content: Some(note), tool_calls: None } if note == CANCELLED_NOTE
+        ));
+
+        // Already cancelled: not a single call
+        let mut history = make_history("system", "hello");
+        assert!(run_tool_loop(
+            &provider,
+            &mut history,
+            &[],
+            &[],
+            "model",
+            0.7,
+            10,
+            &security,
+            &crate::observability::NoopObserver,
+            &Transcript::default(),
+            true,
+            &provider.cancel,
+        )
+        .await
+        .is_err());
+        assert_eq!(
+            provider
+                .call_count
+                .load(std::sync::atomic::Ordering::SeqCst),
+            2
+        );
+        assert_eq!(history.len(), 3);
+    }
+
     // ── trim_history tests ──────────────────────────────────────
 
+    #[test]
+    fn audit_decision_spots_policy_refusals() {
+        assert_eq!(
+            audit_decision("file contents"),
+            (AuditDecision::Allowed, None)
+        );
+        assert_eq!(
+            audit_decision("Error: No such file or directory"),
+            (AuditDecision::Allowed, None)
+        );
+        assert_eq!(
+            audit_decision("Error: Command not permitted by policy: rm -rf / (`rm -rf /` does not match autonomy.allowed_commands)"),
+            (AuditDecision::Denied, Some("security policy"))
+        );
+        assert_eq!(
+            audit_decision("Error: Command denied by the user: curl x"),
+            (AuditDecision::Denied, Some("denied by user"))
+        );
+    }
+
     #[test]
     fn trim_history_keeps_system_message() {
         let mut history = vec![
@@ -919,6 +1527,144 @@ mod tests {
         assert_eq!(history.len(), original_len);
     }
 
+    // ── compact_history tests ───────────────────────────────────
+
+    /// Returns "summary N" for the Nth summarization request and records the
+    /// transcripts it was sent; fails when `fail` is set.
+    struct SummaryProvider {
+        transcripts: std::sync::Mutex<Vec<String>>,
+        fail: bool,
+    }
+
+    impl SummaryProvider {
+        fn new(fail: bool) -> Self {
+            Self {
+                transcripts: std::sync::Mutex::new(Vec::new()),
+                fail,
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for SummaryProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            anyhow::ensure!(!self.fail, "provider down");
+            let mut transcripts = self.transcripts.lock().unwrap();
+            transcripts.push(message.to_string());
+            Ok(format!("summary {}", transcripts.len()))
+        }
+    }
+
+    fn conversation(turns: &[&str]) -> Vec<ChatMessage> {
+        let mut history = vec![ChatMessage::System {
+            content: "sys".into(),
+        }];
+        for turn in turns {
+            history.push(ChatMessage::User {
+                content: (*turn).to_string(),
+            });
+            history.push(ChatMessage::Assistant {
+                content: Some(format!("re: {turn}")),
+                tool_calls: None,
+            });
+        }
+        history
+    }
+
+    fn summary_text(history: &[ChatMessage]) -> &str {
+        match &history[1] {
+            ChatMessage::System { content } => content
+                .strip_prefix(COMPACT_SUMMARY_HEADER)
+                .expect("index 1 should be the compacted summary"),
+            other => panic!("expected summary at index 1, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn compact_history_inserts_summary_at_index_1() {
+        let provider = SummaryProvider::new(false);
+        let mut history = conversation(&["msg1", "msg2", "msg3"]);
+
+        let compacted = compact_history(&provider, &mut history, 1, "model")
+            .await
+            .unwrap();
+
+        assert_eq!(compacted, 4);
+        assert!(matches!(&history[0], ChatMessage::System { content } if content == "sys"));
+        assert_eq!(summary_text(&history).trim(), "summary 1");
+        assert!(matches!(&history[2], ChatMessage::User { content } if content == "msg3"));
+        assert_eq!(history.len(), 4);
+
+        let transcripts = provider.transcripts.lock().unwrap();
+        assert!(transcripts[0].contains("User: msg1"));
+        assert!(transcripts[0].contains("Assistant: re: msg2"));
+        assert!(!transcripts[0].contains("msg3"));
+    }
+
+    #[tokio::test]
+    async fn compact_history_does_not_resummarize_summary() {
+        let provider = SummaryProvider::new(false);
+        let mut history = conversation(&["msg1", "msg2"]);
+        compact_history(&provider, &mut history, 1, "model")
+            .await
+            .unwrap();
+
+        history.extend(conversation(&["msg3"]).into_iter().skip(1));
+        compact_history(&provider, &mut history, 1, "model")
+            .await
+            .unwrap();
+
+        let transcripts = provider.transcripts.lock().unwrap();
+        assert_eq!(transcripts.len(), 2);
+        assert!(!transcripts[1].contains(COMPACT_SUMMARY_HEADER));
+        assert!(!transcripts[1].contains("summary 1"));
+        assert!(transcripts[1].contains("User: msg2"));
+
+        // Still a single summary message, now holding both notes
+        assert_eq!(summary_text(&history).trim(), "summary 1\n\nsummary 2");
+        assert!(matches!(&history[2], ChatMessage::User { content } if content == "msg3"));
+        assert_eq!(history.len(), 4);
+
+        // Plain trimming keeps the summary too
+        trim_history(&mut history, 1);
+        assert_eq!(history.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn compact_history_within_limit_is_noop() {
+        let provider = SummaryProvider::new(false);
+        let mut history = conversation(&["msg1"]);
+        assert_eq!(
+            compact_history(&provider, &mut history, 2, "model")
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(history.len(), 3);
+        assert!(provider.transcripts.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn limit_history_falls_back_to_trimming() {
+        let provider = SummaryProvider::new(true);
+        let mut history = conversation(&["msg1", "msg2", "msg3"]);
+
+        assert!(compact_history(&provider, &mut history, 1, "model")
+            .await
+            .is_err());
+        assert_eq!(history.len(), 7, "failed compaction leaves history intact");
+
+        limit_history(&provider, &mut history, 1, "model", true).await;
+        assert!(matches!(&history[1], ChatMessage::User { content } if content == "msg3"));
+        assert_eq!(history.len(), 3);
+    }
+
     #[tokio::test]
     async fn conversation_history_accumulates_across_calls() {
         let provider = MockToolProvider {
@@ -950,7 +1696,9 @@ mod tests {
             10,
             &security,
             &observer,
+            &Transcript::default(),
             true,
+            &CancellationToken::new(),
         )
         .await
         .unwrap();
@@ -972,7 +1720,9 @@ mod tests {
             10,
             &security,
             &observer,
+            &Transcript::default(),
             true,
+            &CancellationToken::new(),
         )
         .await
         .unwrap();
@@ -1030,6 +1780,77 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn denied_command_is_audited() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("audit.log");
+        let security = Arc::new(SecurityPolicy {
+            workspace_dir: tmp.path().to_path_buf(),
+            audit: crate::security::audit::AuditLog::new(path.clone(), "cli"),
+            ..SecurityPolicy::default()
+        });
+        let shell: Box<dyn Tool> = Box::new(crate::tools::ShellTool::new(security.clone()));
+        let calls = vec![crate::providers::ToolCall {
+            id: "call_1".into(),
+            function: FunctionCall {
+                name: "shell".into(),
+                arguments: r#"{"command":"rm -rf /"}"#.into(),
+            },
+        }];
+
+        let observer = crate::observability::NoopObserver;
+        execute_tool_calls(&calls, &[shell], &security, &observer, true).await;
+
+        let entries = crate::security::audit::read_entries(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].tool, "shell");
+        assert_eq!(entries[0].decision, AuditDecision::Denied);
+        assert_eq!(entries[0].reason.as_deref(), Some("security policy"));
+        assert_eq!(entries[0].success, Some(false));
+        assert!(entries[0].args.as_deref().unwrap().contains("rm -rf /"));
+    }
+
+    #[tokio::test]
+    async fn tool_arguments_are_redacted_before_events_and_audit() {
+        struct Recorder(std::sync::Mutex<Vec<String>>);
+
+        impl Observer for Recorder {
+            fn record_event(&self, event: &ObserverEvent) {
+                if let ObserverEvent::ToolStart { arguments, .. } = event {
+                    self.0.lock().unwrap().push(arguments.clone());
+                }
+            }
+            fn record_metric(&self, _metric: &crate::observability::traits::ObserverMetric) {}
+            fn name(&self) -> &str {
+                "recorder"
+            }
+        }
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("audit.log");
+        let security = SecurityPolicy {
+            audit: crate::security::audit::AuditLog::new(path.clone(), "cli"),
+            ..SecurityPolicy::default()
+        };
+        let calls = vec![crate::providers::ToolCall {
+            id: "call_1".into(),
+            function: FunctionCall {
+                name: "echo".into(),
+                arguments: r#"{"text":"use sk-live-4f9a8b7c6d5e"}"#.into(),
+            },
+        }];
+        let observer = Recorder(std::sync::Mutex::new(Vec::new()));
+        execute_tool_calls(&calls, &[make_echo_tool()], &security, &observer, true).await;
+
+        let started = observer.0.lock().unwrap();
+        assert_eq!(started.as_slice(), [r#"{"text":"use sk-****"}"#]);
+        let entries = crate::security::audit::read_entries(&path).unwrap();
+        assert_eq!(
+            entries[0].args.as_deref(),
+            Some(r#"{"text":"use sk-****"}"#)
+        );
+    }
+
     #[tokio::test]
     async fn execute_tool_calls_bad_arguments() {
         let tool = make_echo_tool();
@@ -0,0 +1,461 @@
+//! Sender allowlists: `jarvis channel allow|deny <channel> <user>`, and the
+//! report of senders an allowlist turned away.
+//!
+//! With `[channels_config] report_rejected_senders` on, channels hand every
+//! rejected sender to a [`RejectionSink`]; the reporter writes each attempt
+//! to the audit log and tells the owner through `[notify]` (once per sender
+//! and run) with the exact `jarvis channel allow …` command to admit them.
+
+use crate::config::{ChannelsConfig, Config};
+use crate::notify::{Notification, Notifier};
+use crate::security::audit::{AuditDecision, AuditLog};
+use anyhow::{bail, Result};
+use std::collections::HashSet;
+use tokio::sync::mpsc;
+
+/// Channels whose allowlist the CLI can edit.
+pub const CHANNELS: &[&str] = &["telegram", "discord", "slack", "matrix", "whatsapp", "irc"];
+
+/// The allowlist of `channel`, with the config key it is stored under.
+fn allowlist_mut<'a>(
+    config: &'a mut ChannelsConfig,
+    channel: &str,
+) -> Result<(&'a mut Vec<String>, &'static str)> {
+    let missing =
+        || anyhow::anyhow!("未配置 [channels_config.{channel}]，请先运行 `jarvis onboard`");
+    Ok(match channel {
+        "telegram" => (
+            &mut config.telegram.as_mut().ok_or_else(missing)?.allowed_users,
+            "allowed_users",
+        ),
+        "discord" => (
+            &mut config.discord.as_mut().ok_or_else(missing)?.allowed_users,
+            "allowed_users",
+        ),
+        "slack" => (
+            &mut config.slack.as_mut().ok_or_else(missing)?.allowed_users,
+            "allowed_users",
+        ),
+        "matrix" => (
+            &mut config.matrix.as_mut().ok_or_else(missing)?.allowed_users,
+            "allowed_users",
+        ),
+        "whatsapp" => (
+            &mut config
+                .whatsapp
+                .as_mut()
+                .ok_or_else(missing)?
+                .allowed_numbers,
+            "allowed_numbers",
+        ),
+        "irc" => (
+            &mut config.irc.as_mut().ok_or_else(missing)?.allowed_users,
+            "allowed_users",
+        ),
+        other => bail!("不支持的通道「{other}」（可选 {}）", CHANNELS.join("、")),
+    })
+}
+
+/// Check `user` against the identifier format of `channel` and return it
+/// the way the channel compares it: Telegram usernames without '@',
+/// `WhatsApp` numbers as `+` and digits. `*` admits everyone.
+pub fn normalize_user(channel: &str, user: &str) -> Result<String> {
+    let user = user.trim();
+    if user == "*" {
+        return Ok(user.to_string());
+    }
+    let valid = match channel {
+        "telegram" => {
+            let name = user.strip_prefix('@').unwrap_or(user);
+            if name.bytes().all(|b| b.is_ascii_digit()) && !name.is_empty() {
+                return Ok(name.to_string());
+            }
+            if (5..=32).contains(&name.len())
+                && name.starts_with(|c: char| c.is_ascii_alphabetic())
+                && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+            {
+                return Ok(name.to_string());
+            }
+            "@用户名（5-32 位字母、数字或下划线）或数字用户 ID"
+        }
+        "discord" => {
+            if (17..=20).contains(&user.len()) && user.bytes().all(|b| b.is_ascii_digit()) {
+                return Ok(user.to_string());
+            }
+            "数字用户 ID（17-20 位，在开发者模式下右键用户 → 复制用户 ID）"
+        }
+        "slack" => {
+            if (9..=21).contains(&user.len())
+                && user.starts_with(['U', 'W'])
+                && user
+                    .bytes()
+                    .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+            {
+                return Ok(user.to_string());
+            }
+            "成员 ID（以 U 或 W 开头，例如 U01AB2CD3EF）"
+        }
+        "matrix" => {
+            let valid = user.strip_prefix('@').and_then(|rest| rest.split_once(':'));
+            if let Some((local, server)) = valid
+                && !local.is_empty()
+                && !server.is_empty()
+                && !user.chars().any(char::is_whitespace)
+            {
+                return Ok(user.to_string());
+            }
+            "MXID（@用户名:服务器，例如 @alice:matrix.org）"
+        }
+        "whatsapp" => {
+            let digits: String = user
+                .chars()
+                .filter(|c| !matches!(c, ' ' | '-' | '(' | ')'))
+                .collect();
+            let digits = digits.strip_prefix('+').unwrap_or(&digits);
+            if (7..=15).contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Ok(format!("+{digits}"));
+            }
+            "E.164 号码（+ 加国家代码和号码，例如 +8613800138000）"
+        }
+        "irc" => {
+            let special = |c: char| "[]\\`_^{|}".contains(c);
+            if (1..=30).contains(&user.len())
+                && user.starts_with(|c: char| c.is_ascii_alphabetic() || special(c))
+                && user
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || special(c) || c == '-')
+            {
+                return Ok(user.to_string());
+            }
+            "昵称（字母或 []\\`_^{|} 开头，最多 30 个字符）"
+        }
+        other => bail!("不支持的通道「{other}」（可选 {}）", CHANNELS.join("、")),
+    };
+    bail!("「{user}」不是有效的 {channel} 标识，应为{valid}")
+}
+
+/// Whether two allowlist entries name the same sender; Matrix and IRC
+/// compare case-insensitively, as the channels do.
+fn same_user(channel: &str, a: &str, b: &str) -> bool {
+    match channel {
+        "matrix" | "irc" => a.eq_ignore_ascii_case(b),
+        _ => a == b,
+    }
+}
+
+/// What `allow`/`deny` did to an allowlist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// The identifier as stored
+    pub user: String,
+    /// `false` when the allowlist already was as requested
+    pub changed: bool,
+    /// Entries left afterwards
+    pub remaining: usize,
+}
+
+/// Add `user` to the allowlist of `channel`.
+pub fn allow(config: &mut ChannelsConfig, channel: &str, user: &str) -> Result<Change> {
+    let user = normalize_user(channel, user)?;
+    let (list, _) = allowlist_mut(config, channel)?;
+    let changed = !list.iter().any(|u| same_user(channel, u, &user));
+    if changed {
+        list.push(user.clone());
+    }
+    Ok(Change {
+        user,
+        changed,
+        remaining: list.len(),
+    })
+}
+
+/// Remove `user` from the allowlist of `channel`.
+pub fn deny(config: &mut ChannelsConfig, channel: &str, user: &str) -> Result<Change> {
+    let user = normalize_user(channel, user)?;
+    let (list, _) = allowlist_mut(config, channel)?;
+    let before = list.len();
+    list.retain(|u| !same_user(channel, u, &user));
+    Ok(Change {
+        user,
+        changed: list.len() != before,
+        remaining: list.len(),
+    })
+}
+
+/// Handle `jarvis channel allow|deny`: edit the allowlist and save the
+/// config.
+pub fn handle_change(config: &Config, channel: &str, user: &str, add: bool) -> Result<()> {
+    let channel = channel.trim().to_ascii_lowercase();
+    let (change, key) = Config::update(&config.config_path, |updated| {
+        let change = if add {
+            allow(&mut updated.channels_config, &channel, user)?
+        } else {
+            deny(&mut updated.channels_config, &channel, user)?
+        };
+        let (_, key) = allowlist_mut(&mut updated.channels_config, &channel)?;
+        Ok((change, key))
+    })?
+    .0;
+    let section = format!("[channels_config.{channel}] {key}");
+    if !change.changed {
+        if add {
+            println!("ℹ️  {} 已在 {section} 中", change.user);
+        } else {
+            println!("ℹ️  {} 不在 {section} 中", change.user);
+        }
+        return Ok(());
+    }
+    if add {
+        println!("✅ 已将 {} 添加到 {section}", change.user);
+        if change.user == "*" {
+            println!("⚠️  '*' 允许任何人向 Jarvis 发消息，仅建议临时测试时使用。");
+        }
+    } else {
+        println!("✅ 已从 {section} 中移除 {}", change.user);
+        if change.remaining == 0 {
+            println!("⚠️  {section} 现在为空 — {channel} 将拒绝所有入站消息。");
+        }
+    }
+    println!("   重启通道（jarvis channel start 或守护进程）后生效。");
+    Ok(())
+}
+
+/// The command that admits `sender` on `channel`.
+pub fn allow_command(channel: &str, sender: &str) -> String {
+    format!("jarvis channel allow {channel} {sender}")
+}
+
+/// A sender an allowlist turned away.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rejection {
+    pub channel: &'static str,
+    /// The identifier to put on the allowlist
+    pub sender: String,
+}
+
+/// Where channels report rejected senders. Cheap to clone; reporting never
+/// blocks the channel.
+#[derive(Debug, Clone)]
+pub struct RejectionSink(mpsc::UnboundedSender<Rejection>);
+
+impl RejectionSink {
+    pub fn report(&self, channel: &'static str, sender: &str) {
+        let _ = self.0.send(Rejection {
+            channel,
+            sender: sender.to_string(),
+        });
+    }
+}
+
+/// Start reporting rejected senders if `report_rejected_senders` is on.
+pub fn spawn_reporter(config: &Config) -> Option<RejectionSink> {
+    if !config.channels_config.report_rejected_senders {
+        return None;
+    }
+    let audit = AuditLog::new(AuditLog::default_path(&config.workspace_dir), "channel");
+    let notifier = match Notifier::from_notify(config) {
+        Ok(Some(notifier)) => Some(notifier),
+        Ok(None) => {
+            tracing::info!("未配置 [notify]，未授权的发送者只记录到审计日志");
+            None
+        }
+        Err(e) => {
+            tracing::warn!("[notify] 配置无效，未授权的发送者只记录到审计日志: {e:#}");
+            None
+        }
+    };
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut seen = HashSet::new();
+        while let Some(rejection) = rx.recv().await {
+            record(&audit, &rejection);
+            let Some(notifier) = &notifier else {
+                continue;
+            };
+            if !seen.insert(rejection.clone()) {
+                continue;
+            }
+            if let Err(e) = notifier.send(&notification(&rejection)).await {
+                tracing::warn!("发送未授权发送者通知失败: {e:#}");
+            }
+        }
+    });
+    Some(RejectionSink(tx))
+}
+
+fn record(audit: &AuditLog, rejection: &Rejection) {
+    let arguments = serde_json::json!({ "sender": rejection.sender }).to_string();
+    audit.record(
+        rejection.channel,
+        &arguments,
+        AuditDecision::Denied,
+        Some("sender not in allowlist"),
+    );
+}
+
+fn notification(rejection: &Rejection) -> Notification {
+    Notification {
+        source: "channel",
+        title: format!("{} 拒绝了未授权的发送者", rejection.channel),
+        body: format!(
+            "发送者：{}\n如需允许，请运行：\n{}",
+            rejection.sender,
+            allow_command(rejection.channel, &rejection.sender)
+        ),
+        failed: false,
+        actionable: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{MatrixConfig, TelegramConfig};
+    use crate::security::audit::read_entries;
+
+    fn channels() -> ChannelsConfig {
+        ChannelsConfig {
+            telegram: Some(TelegramConfig {
+                bot_token: "123:ABC".into(),
+                allowed_users: vec!["alice_bot".into()],
+            }),
+            matrix: Some(MatrixConfig {
+                homeserver: "https://matrix.org".into(),
+                access_token: "tok".into(),
+                room_id: "!room:matrix.org".into(),
+                allowed_users: vec!["@alice:matrix.org".into()],
+            }),
+            ..ChannelsConfig::default()
+        }
+    }
+
+    #[test]
+    fn identifiers_are_validated_per_channel() {
+        let ok = |channel, user| normalize_user(channel, user).unwrap();
+        assert_eq!(ok("telegram", "@Alice_99"), "Alice_99");
+        assert_eq!(ok("telegram", "123456789"), "123456789");
+        assert_eq!(ok("discord", "123456789012345678"), "123456789012345678");
+        assert_eq!(ok("slack", "U01AB2CD3EF"), "U01AB2CD3EF");
+        assert_eq!(ok("matrix", "@bob:example.org"), "@bob:example.org");
+        assert_eq!(ok("whatsapp", "+86 138-0013-8000"), "+8613800138000");
+        assert_eq!(ok("whatsapp", "14155550123"), "+14155550123");
+        assert_eq!(ok("irc", "[bob]-2"), "[bob]-2");
+        assert_eq!(ok("slack", " * "), "*");
+
+        for (channel, user) in [
+            ("telegram", "ab"),
+            ("telegram", "has space"),
+            ("discord", "alice"),
+            ("discord", "1234"),
+            ("slack", "u01ab2cd3ef"),
+            ("slack", "C01AB2CD3EF"),
+            ("matrix", "bob:example.org"),
+            ("matrix", "@bob"),
+            ("whatsapp", "+12ab"),
+            ("whatsapp", "12345"),
+            ("irc", "9lives"),
+        ] {
+            assert!(normalize_user(channel, user).is_err(), "{channel} {user}");
+        }
+        let err = normalize_user("email", "a@b.c").unwrap_err();
+        assert!(err.to_string().contains("telegram"));
+    }
+
+    #[test]
+    fn allow_skips_duplicates_and_deny_reports_what_is_left() {
+        let mut config = channels();
+        let change = allow(&mut config, "telegram", "@bob_smith").unwrap();
+        assert_eq!((change.changed, change.remaining), (true, 2));
+        let change = allow(&mut config, "telegram", "bob_smith").unwrap();
+        assert_eq!((change.changed, change.remaining), (false, 2));
+        assert_eq!(
+            config.telegram.as_ref().unwrap().allowed_users,
+            ["alice_bot", "bob_smith"]
+        );
+
+        // Matrix IDs compare case-insensitively, like the channel does
+        assert!(
+            !allow(&mut config, "matrix", "@Alice:Matrix.org")
+                .unwrap()
+                .changed
+        );
+        let change = deny(&mut config, "matrix", "@ALICE:matrix.org").unwrap();
+        assert_eq!((change.changed, change.remaining), (true, 0));
+        assert!(
+            !deny(&mut config, "matrix", "@alice:matrix.org")
+                .unwrap()
+                .changed
+        );
+
+        let err = allow(&mut config, "discord", "123456789012345678").unwrap_err();
+        assert!(err.to_string().contains("[channels_config.discord]"));
+    }
+
+    #[test]
+    fn cli_change_is_saved() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut config = Config {
+            config_path: tmp.path().join("config.toml"),
+            channels_config: channels(),
+            ..Config::default()
+        };
+        config.save().unwrap();
+
+        handle_change(&config, "Telegram", "987654321", true).unwrap();
+        config = Config::load_from(&config.config_path).unwrap();
+        assert_eq!(
+            config
+                .channels_config
+                .telegram
+                .as_ref()
+                .unwrap()
+                .allowed_users,
+            ["alice_bot", "987654321"]
+        );
+        handle_change(&config, "telegram", "@alice_bot", false).unwrap();
+        assert!(handle_change(&config, "telegram", "no", true).is_err());
+        config = Config::load_from(&config.config_path).unwrap();
+        assert_eq!(
+            config.channels_config.telegram.unwrap().allowed_users,
+            ["987654321"]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejections_are_audited() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut config = Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..Config::default()
+        };
+        assert!(spawn_reporter(&config).is_none());
+
+        config.channels_config.report_rejected_senders = true;
+        let sink = spawn_reporter(&config).unwrap();
+        sink.report("telegram", "555");
+        sink.report("telegram", "555");
+        drop(sink);
+
+        let path = AuditLog::default_path(tmp.path());
+        let mut entries = Vec::new();
+        for _ in 0..50 {
+            entries = read_entries(&path).unwrap_or_default();
+            if entries.len() == 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].tool, "telegram");
+        assert_eq!(entries[0].origin, "channel");
+        assert_eq!(entries[0].decision, AuditDecision::Denied);
+        assert!(entries[0].args.as_deref().unwrap().contains("555"));
+
+        let body = notification(&Rejection {
+            channel: "telegram",
+            sender: "555".into(),
+        })
+        .body;
+        assert!(body.ends_with("jarvis channel allow telegram 555"));
+    }
+}
@@ -0,0 +1,244 @@
+//! Splitting long replies into messages that fit a channel's size limit.
+//!
+//! Lengths are counted in UTF-16 code units, as Telegram and Discord do.
+
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// Split a reply into messages of at most `max` UTF-16 units, breaking
+/// between paragraphs and keeping fenced code blocks whole. A code block
+/// too long for one message is cut between lines and each part re-fenced.
+pub fn split_message(text: &str, max: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for block in blocks(text) {
+        for piece in split_block(&block, max) {
+            if !current.is_empty() && utf16_len(&current) + 2 + utf16_len(&piece) > max {
+                chunks.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push_str("\n\n");
+            }
+            current.push_str(&piece);
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Whether `line` opens or closes a fenced code block.
+pub fn is_fence(line: &str) -> bool {
+    line.trim_start().starts_with("```")
+}
+
+/// Paragraphs and whole fenced code blocks, in order.
+fn blocks(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        let fence = is_fence(line);
+        if !in_fence && (fence || line.trim().is_empty()) && !current.is_empty() {
+            blocks.push(current.join("\n"));
+            current.clear();
+        }
+        if !in_fence && !fence && line.trim().is_empty() {
+            continue;
+        }
+        current.push(line);
+        if fence {
+            in_fence = !in_fence;
+            if !in_fence {
+                blocks.push(current.join("\n"));
+                current.clear();
+            }
+        }
+    }
+    if !current.is_empty() {
+        blocks.push(current.join("\n"));
+    }
+    blocks
+}
+
+fn split_block(block: &str, max: usize) -> Vec<String> {
+    if utf16_len(block) <= max {
+        return vec![block.to_string()];
+    }
+    let mut lines: Vec<&str> = block.lines().collect();
+    if lines.len() < 2 || !is_fence(lines[0]) {
+        return split_lines(&lines, max);
+    }
+    let opener = lines.remove(0).trim_start();
+    if lines.last().is_some_and(|line| is_fence(line)) {
+        lines.pop();
+    }
+    // Room left for code once the opening and closing fences are added.
+    let budget = max.saturating_sub(utf16_len(opener) + 5).max(1);
+    split_lines(&lines, budget)
+        .into_iter()
+        .map(|code| format!("{opener}\n{code}\n```"))
+        .collect()
+}
+
+/// Pack lines into pieces of at most `max` units, cutting over-long lines
+/// at the last space that fits (or mid-word if there is none).
+fn split_lines(lines: &[&str], max: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    for &line in lines {
+        let mut line = line;
+        while utf16_len(line) > max {
+            if !current.is_empty() {
+                pieces.push(std::mem::take(&mut current));
+            }
+            let (cut, skip) = cut_point(line, max);
+            pieces.push(line[..cut].to_string());
+            line = &line[cut + skip..];
+        }
+        if !current.is_empty() && utf16_len(&current) + 1 + utf16_len(line) > max {
+            pieces.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+/// Byte index to cut `line` at so the head fits in `max` units, and how
+/// many bytes of separator to drop after it.
+fn cut_point(line: &str, max: usize) -> (usize, usize) {
+    let mut units = 0;
+    let mut end = 0;
+    let mut space = None;
+    for (i, c) in line.char_indices() {
+        if c == ' ' && i > 0 {
+            space = Some(i);
+        }
+        units += c.len_utf16();
+        if units > max {
+            break;
+        }
+        end = i + c.len_utf8();
+    }
+    match space {
+        Some(i) => (i, 1),
+        // Always make progress, even if one character is wider than `max`.
+        None if end == 0 => (line.chars().next().map_or(0, char::len_utf8), 0),
+        None => (end, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fences(chunk: &str) -> usize {
+        chunk.lines().filter(|line| is_fence(line)).count()
+    }
+
+    #[test]
+    fn split_keeps_short_replies_whole() {
+        assert_eq!(split_message("hello\n\nworld", 4096), ["hello\n\nworld"]);
+        assert!(split_message("", 4096).is_empty());
+    }
+
+    #[test]
+    fn split_breaks_between_paragraphs() {
+        let para = "word ".repeat(30);
+        let text = [para.trim(), para.trim(), para.trim()].join("\n\n");
+        let chunks = split_message(&text, 320);
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks.iter().all(|c| utf16_len(c) <= 320));
+        assert_eq!(chunks.join("\n\n"), text);
+    }
+
+    #[test]
+    fn split_never_breaks_inside_a_code_fence() {
+        let code = "```rust\nfn main() {\n\n    println!(\"hi\");\n}\n```";
+        let text = format!("{}\n\n{code}\n\nDone.", "intro ".repeat(10).trim());
+        for max in [60, 80, 200] {
+            let chunks = split_message(&text, max);
+            assert!(chunks.iter().all(|c| utf16_len(c) <= max), "{chunks:?}");
+            assert!(
+                chunks.iter().all(|c| fences(c).is_multiple_of(2)),
+                "{chunks:?}"
+            );
+            assert!(chunks.iter().any(|c| c.contains(code)), "{chunks:?}");
+        }
+    }
+
+    #[test]
+    fn split_refences_code_blocks_longer_than_a_message() {
+        let body: Vec<String> = (0..50).map(|i| format!("let x{i} = {i};")).collect();
+        let text = format!("```rust\n{}\n```", body.join("\n"));
+        let chunks = split_message(&text, 200);
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(utf16_len(chunk) <= 200);
+            assert!(chunk.starts_with("```rust\n") && chunk.ends_with("\n```"));
+        }
+        let code: Vec<&str> = chunks
+            .iter()
+            .flat_map(|c| c.lines().filter(|line| !is_fence(line)))
+            .collect();
+        assert_eq!(code, body);
+    }
+
+    #[test]
+    fn split_cuts_long_lines_by_utf16_length() {
+        let chunks = split_message(&"😀".repeat(10), 8);
+        assert_eq!(chunks, ["😀😀😀😀", "😀😀😀😀", "😀😀"]);
+
+        let chunks = split_message("aaaa bbbb cccc", 9);
+        assert_eq!(chunks, ["aaaa bbbb", "cccc"]);
+    }
+
+    #[test]
+    fn split_at_exact_discord_limit() {
+        const MAX: usize = 2000;
+        assert_eq!(split_message(&"a".repeat(MAX), MAX).len(), 1);
+        let chunks = split_message(&"a".repeat(MAX + 1), MAX);
+        assert_eq!(chunks.iter().map(String::len).collect::<Vec<_>>(), [MAX, 1]);
+
+        // Two paragraphs joined by "\n\n" landing exactly on the limit
+        let fits = format!("{}\n\n{}", "a".repeat(999), "b".repeat(999));
+        assert_eq!(split_message(&fits, MAX), vec![fits]);
+        let over = format!("{}\n\n{}", "a".repeat(1000), "b".repeat(999));
+        assert_eq!(
+            split_message(&over, MAX),
+            ["a".repeat(1000), "b".repeat(999)]
+        );
+
+        // Surrogate pairs count twice
+        assert_eq!(split_message(&"😀".repeat(MAX / 2), MAX).len(), 1);
+        assert_eq!(split_message(&"😀".repeat(MAX / 2 + 1), MAX).len(), 2);
+    }
+
+    #[test]
+    fn split_code_block_at_exact_discord_limit() {
+        const MAX: usize = 2000;
+        // "```rust\n" + code + "\n```" is 12 units of fencing
+        let whole = format!("```rust\n{}\n```", "x".repeat(MAX - 12));
+        assert_eq!(utf16_len(&whole), MAX);
+        assert_eq!(split_message(&whole, MAX), vec![whole]);
+
+        let line = "y".repeat(99);
+        let code = vec![line.as_str(); 20].join("\n");
+        let long = format!("```rust\n{code}\n```");
+        assert_eq!(utf16_len(&long), MAX + 11);
+        let chunks = split_message(&long, MAX);
+        assert_eq!(chunks.len(), 2);
+        for chunk in &chunks {
+            assert!(utf16_len(chunk) <= MAX);
+            assert!(chunk.starts_with("```rust\n") && chunk.ends_with("\n```"));
+        }
+    }
+}
@@ -1,14 +1,77 @@
+use super::line_editor::{LineEditor, ReadOutcome};
 use super::traits::{Channel, ChannelMessage};
 use async_trait::async_trait;
+use std::io::IsTerminal;
+use std::path::PathBuf;
 use tokio::io::{self, AsyncBufReadExt, BufReader};
 use uuid::Uuid;
 
-/// CLI channel — stdin/stdout, always available, zero deps
-pub struct CliChannel;
+/// A line typed at the CLI prompt.
+#[derive(Debug, PartialEq, Eq)]
+enum Input {
+    Blank,
+    /// `/quit` or `/exit`
+    Quit,
+    Message(String),
+}
+
+/// CLI channel — stdin/stdout, always available, zero deps.
+///
+/// On a terminal, input goes through [`LineEditor`] (history in
+/// `~/.jarvis/cli_history`, per profile); piped stdin is read line by line.
+pub struct CliChannel {
+    history_path: Option<PathBuf>,
+}
 
 impl CliChannel {
     pub fn new() -> Self {
-        Self
+        Self {
+            history_path: crate::config::profile::config_dir()
+                .ok()
+                .map(|dir| dir.join("cli_history")),
+        }
+    }
+
+    fn message(content: String) -> ChannelMessage {
+        ChannelMessage {
+            id: Uuid::new_v4().to_string(),
+            sender: "user".to_string(),
+            content,
+            channel: "cli".to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+
+    fn parse_input(line: &str) -> Input {
+        match line.trim() {
+            "" => Input::Blank,
+            "/quit" | "/exit" => Input::Quit,
+            line => Input::Message(line.to_string()),
+        }
+    }
+
+    async fn listen_piped(
+        &self,
+        tx: tokio::sync::mpsc::Sender<ChannelMessage>,
+    ) -> anyhow::Result<()> {
+        let stdin = io::stdin();
+        let reader = BufReader::new(stdin);
+        let mut lines = reader.lines();
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            let content = match Self::parse_input(&line) {
+                Input::Blank => continue,
+                Input::Quit => break,
+                Input::Message(content) => content,
+            };
+            if tx.send(Self::message(content)).await.is_err() {
+                break;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -24,35 +87,31 @@ impl Channel for CliChannel {
     }
 
     async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
-        let stdin = io::stdin();
-        let reader = BufReader::new(stdin);
-        let mut lines = reader.lines();
+        if !std::io::stdin().is_terminal() {
+            return self.listen_piped(tx).await;
+        }
 
-        while let Ok(Some(line)) = lines.next_line().await {
-            let line = line.trim().to_string();
-            if line.is_empty() {
-                continue;
+        // The editor blocks on terminal input, so it runs on a blocking thread
+        let history_path = self.history_path.clone();
+        tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+            let mut editor = LineEditor::new(history_path.as_deref());
+            loop {
+                let content = match editor.read_line()? {
+                    ReadOutcome::Eof => break,
+                    ReadOutcome::Interrupted => continue,
+                    ReadOutcome::Line(line) => match Self::parse_input(&line) {
+                        Input::Blank => continue,
+                        Input::Quit => break,
+                        Input::Message(content) => content,
+                    },
+                };
+                if tx.blocking_send(Self::message(content)).is_err() {
+                    break;
+                }
             }
-            if line == "/quit" || line == "/exit" {
-                break;
-            }
-
-            let msg = ChannelMessage {
-                id: Uuid::new_v4().to_string(),
-                sender: "user".to_string(),
-                content: line,
-                channel: "cli".to_string(),
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs(),
-            };
-
-            if tx.send(msg).await.is_err() {
-                break;
-            }
-        }
-        Ok(())
+            Ok(())
+        })
+        .await?
     }
 }
 
@@ -85,6 +144,28 @@ mod tests {
         assert!(ch.health_check().await);
     }
 
+    #[test]
+    fn parse_input_trims_skips_blank_and_detects_quit() {
+        assert_eq!(
+            CliChannel::parse_input("  hello  "),
+            Input::Message("hello".into())
+        );
+        assert_eq!(CliChannel::parse_input("   "), Input::Blank);
+        assert_eq!(CliChannel::parse_input("/quit"), Input::Quit);
+        assert_eq!(CliChannel::parse_input(" /exit "), Input::Quit);
+        assert_eq!(
+            CliChannel::parse_input("line one\nline two"),
+            Input::Message("line one\nline two".into())
+        );
+    }
+
+    #[test]
+    fn history_lives_in_jarvis_dir() {
+        if let Some(path) = CliChannel::new().history_path {
+            assert!(path.ends_with(".jarvis/cli_history"));
+        }
+    }
+
     #[test]
     fn channel_message_struct() {
         let msg = ChannelMessage {
@@ -0,0 +1,329 @@
+//! Conversation history for the channel daemon.
+//!
+//! Each chat — a channel plus the sender id the reply goes to — keeps its
+//! own bounded history, so the agent remembers what was said a few messages
+//! ago without users sharing context. Histories are saved to
+//! `state/channel_conversations.json` so a restart doesn't forget active
+//! chats, and dropped after `[channels_config] conversation_idle_secs`.
+
+use crate::agent::loop_::trim_history;
+use crate::providers::{ChatMessage, ChatResponse, Provider};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The chat command that clears the sender's conversation.
+pub const RESET_COMMAND: &str = "/reset";
+
+pub const RESET_REPLY: &str =
+    "Conversation history cleared. I'll start fresh from your next message.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Conversation {
+    /// Turns so far, without the system prompt (it is rebuilt on start)
+    history: Vec<ChatMessage>,
+    /// Unix seconds of the last message
+    last_active: u64,
+}
+
+/// Conversation histories by chat, see the module docs.
+#[derive(Debug)]
+pub struct ConversationStore {
+    path: PathBuf,
+    idle_ttl: Duration,
+    max_turns: usize,
+    conversations: HashMap<String, Conversation>,
+    /// Changed since the last save
+    dirty: bool,
+}
+
+impl ConversationStore {
+    /// Load the saved histories of `workspace_dir`. A missing or unreadable
+    /// file starts empty; expired conversations are dropped.
+    pub fn load(workspace_dir: &Path, idle_ttl: Duration, max_turns: usize) -> Self {
+        let path = workspace_dir
+            .join("state")
+            .join("channel_conversations.json");
+        let conversations = match std::fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                tracing::warn!("会话历史文件无法解析，已忽略: {}: {e}", path.display());
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        let mut store = Self {
+            path,
+            idle_ttl,
+            max_turns,
+            conversations,
+            dirty: false,
+        };
+        store.sweep();
+        store
+    }
+
+    /// The key of the chat `sender` on `channel`.
+    pub fn key(channel: &str, sender: &str) -> String {
+        format!("{channel}:{sender}")
+    }
+
+    /// The messages for the next turn of `key`: the system prompt, the
+    /// conversation so far, then `user`.
+    pub fn begin(&mut self, key: &str, system_prompt: &str, user: &str) -> Vec<ChatMessage> {
+        self.sweep();
+        let mut messages = vec![ChatMessage::System {
+            content: system_prompt.to_string(),
+        }];
+        if let Some(conversation) = self.conversations.get(key) {
+            messages.extend(conversation.history.iter().cloned());
+        }
+        messages.push(ChatMessage::User {
+            content: user.to_string(),
+        });
+        messages
+    }
+
+    /// Keep `messages` (from [`Self::begin`], plus the reply) as the history
+    /// of `key`, trimmed to the configured number of turns.
+    pub fn finish(&mut self, key: &str, mut messages: Vec<ChatMessage>) {
+        trim_history(&mut messages, self.max_turns);
+        messages.remove(0);
+        self.conversations.insert(
+            key.to_string(),
+            Conversation {
+                history: messages,
+                last_active: now_secs(),
+            },
+        );
+        self.dirty = true;
+    }
+
+    /// Forget the conversation of `key`; `false` if there was none.
+    pub fn reset(&mut self, key: &str) -> bool {
+        let existed = self.conversations.remove(key).is_some();
+        self.dirty |= existed;
+        existed
+    }
+
+    /// Drop conversations idle for longer than the TTL.
+    pub fn sweep(&mut self) {
+        if self.idle_ttl.is_zero() {
+            return;
+        }
+        let cutoff = now_secs().saturating_sub(self.idle_ttl.as_secs());
+        let before = self.conversations.len();
+        self.conversations.retain(|_, c| c.last_active >= cutoff);
+        self.dirty |= self.conversations.len() != before;
+    }
+
+    /// Write the histories if they changed since the last save.
+    pub fn save(&mut self) -> Result<()> {
+        let path = &self.path;
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+        }
+        let json = serde_json::to_string(&self.conversations)?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json).with_context(|| format!("写入失败: {}", tmp.display()))?;
+        std::fs::rename(&tmp, path).with_context(|| format!("写入失败: {}", path.display()))?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// Whether `content` is the `/reset` command, also in Telegram's
+/// `/reset@botname` form.
+pub fn is_reset_command(content: &str) -> bool {
+    let command = content.trim();
+    let command = command.split_once('@').map_or(command, |(name, _)| name);
+    command.eq_ignore_ascii_case(RESET_COMMAND)
+}
+
+/// Answer `content` as the next turn of the conversation `key`.
+pub async fn respond(
+    provider: &dyn Provider,
+    store: &mut ConversationStore,
+    key: &str,
+    system_prompt: &str,
+    content: &str,
+    model: &str,
+    temperature: f64,
+) -> Result<String> {
+    let mut messages = store.begin(key, system_prompt, content);
+    let reply = match provider
+        .chat_with_tools(&messages, &[], model, temperature, None)
+        .await?
+    {
+        ChatResponse::Text(text) => text,
+        ChatResponse::ToolUse { text, .. } => text.unwrap_or_default(),
+    };
+    messages.push(ChatMessage::Assistant {
+        content: Some(reply.clone()),
+        tool_calls: None,
+    });
+    store.finish(key, messages);
+    Ok(reply)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{ResponseFormat, ToolDefinition};
+    use tempfile::TempDir;
+
+    /// Replies with the user messages it was sent, oldest first.
+    struct RecallProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for RecallProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> Result<String> {
+            unreachable!("respond sends the whole history")
+        }
+
+        async fn chat_with_tools(
+            &self,
+            messages: &[ChatMessage],
+            _tools: &[ToolDefinition],
+            _model: &str,
+            _temperature: f64,
+            _response_format: Option<&ResponseFormat>,
+        ) -> Result<ChatResponse> {
+            let seen: Vec<&str> = messages
+                .iter()
+                .filter_map(|m| match m {
+                    ChatMessage::User { content } => Some(content.as_str()),
+                    _ => None,
+                })
+                .collect();
+            Ok(ChatResponse::Text(seen.join(" | ")))
+        }
+    }
+
+    async fn say(store: &mut ConversationStore, sender: &str, content: &str) -> String {
+        let key = ConversationStore::key("telegram", sender);
+        respond(&RecallProvider, store, &key, "system", content, "m", 0.7)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn telegram_users_do_not_share_context() {
+        let tmp = TempDir::new().unwrap();
+        let mut store = ConversationStore::load(tmp.path(), Duration::from_hours(1), 10);
+
+        assert_eq!(
+            say(&mut store, "111", "my name is Ada").await,
+            "my name is Ada"
+        );
+        assert_eq!(say(&mut store, "222", "who am I?").await, "who am I?");
+        assert_eq!(
+            say(&mut store, "111", "who am I?").await,
+            "my name is Ada | who am I?"
+        );
+        assert_eq!(
+            say(&mut store, "222", "still there?").await,
+            "who am I? | still there?"
+        );
+
+        // The same sender on another channel is another conversation
+        let key = ConversationStore::key("slack", "111");
+        let reply = respond(&RecallProvider, &mut store, &key, "system", "hi", "m", 0.7)
+            .await
+            .unwrap();
+        assert_eq!(reply, "hi");
+    }
+
+    #[tokio::test]
+    async fn history_is_trimmed_reset_and_survives_a_restart() {
+        let tmp = TempDir::new().unwrap();
+        let mut store = ConversationStore::load(tmp.path(), Duration::from_hours(1), 2);
+        for message in ["one", "two", "three"] {
+            say(&mut store, "111", message).await;
+        }
+        // Two earlier turns are kept, plus the new message
+        assert_eq!(say(&mut store, "111", "four").await, "two | three | four");
+        say(&mut store, "222", "other").await;
+        store.save().unwrap();
+
+        let mut restarted = ConversationStore::load(tmp.path(), Duration::from_hours(1), 2);
+        assert_eq!(
+            say(&mut restarted, "111", "five").await,
+            "three | four | five"
+        );
+
+        assert!(restarted.reset("telegram:111"));
+        assert!(!restarted.reset("telegram:111"));
+        assert_eq!(say(&mut restarted, "111", "six").await, "six");
+        assert_eq!(say(&mut restarted, "222", "again").await, "other | again");
+    }
+
+    #[tokio::test]
+    async fn idle_conversations_expire() {
+        let tmp = TempDir::new().unwrap();
+        let mut store = ConversationStore::load(tmp.path(), Duration::from_mins(1), 10);
+        say(&mut store, "111", "old").await;
+        say(&mut store, "222", "recent").await;
+        store
+            .conversations
+            .get_mut("telegram:111")
+            .unwrap()
+            .last_active -= 120;
+        store.save().unwrap();
+
+        let mut reloaded = ConversationStore::load(tmp.path(), Duration::from_mins(1), 10);
+        assert_eq!(reloaded.conversations.len(), 1);
+        assert_eq!(say(&mut reloaded, "111", "new").await, "new");
+        assert_eq!(say(&mut reloaded, "222", "more").await, "recent | more");
+
+        // A zero TTL never expires anything
+        let mut forever = ConversationStore::load(tmp.path(), Duration::ZERO, 10);
+        forever
+            .conversations
+            .get_mut("telegram:222")
+            .unwrap()
+            .last_active = 0;
+        forever.sweep();
+        assert_eq!(forever.conversations.len(), 2);
+    }
+
+    #[test]
+    fn corrupt_state_starts_empty() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("state").join("channel_conversations.json");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "not json").unwrap();
+        let mut store = ConversationStore::load(tmp.path(), Duration::ZERO, 10);
+        assert!(store.conversations.is_empty());
+        // Nothing changed, so the file is left for the user to inspect
+        store.save().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not json");
+    }
+
+    #[test]
+    fn reset_command_forms() {
+        assert!(is_reset_command("/reset"));
+        assert!(is_reset_command("  /RESET \n"));
+        assert!(is_reset_command("/reset@jarvis_bot"));
+        assert!(!is_reset_command("/resets"));
+        assert!(!is_reset_command("please /reset"));
+    }
+}
@@ -0,0 +1,251 @@
+//! De-duplication of inbound channel messages.
+//!
+//! Channels sometimes deliver a message twice: a Telegram long poll that
+//! overlaps a restart, a Matrix re-sync, a retried webhook. Each message's
+//! provider id is remembered per channel for `[channels_config]
+//! dedup_ttl_secs`, at most `dedup_max_entries` of them, and a message whose
+//! id was already seen is dropped before it reaches the agent. The ids are
+//! saved to `state/channel_seen_messages.json`, so this holds across
+//! restarts too.
+
+use super::traits::ChannelMessage;
+use anyhow::{Context, Result};
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::{Receiver, Sender};
+
+/// How often new ids are written to disk.
+const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Recently seen message ids, see the module docs.
+#[derive(Debug)]
+pub struct SeenMessages {
+    path: PathBuf,
+    ttl: Duration,
+    capacity: usize,
+    /// `channel:id` and the Unix seconds it was first seen, oldest first
+    entries: VecDeque<(String, u64)>,
+    keys: HashSet<String>,
+    /// Changed since the last save
+    dirty: bool,
+}
+
+impl SeenMessages {
+    /// Load the ids seen in `workspace_dir`. A missing or unreadable file
+    /// starts empty; expired ids are dropped.
+    pub fn load(workspace_dir: &Path, ttl: Duration, capacity: usize) -> Self {
+        let path = workspace_dir
+            .join("state")
+            .join("channel_seen_messages.json");
+        let entries: VecDeque<(String, u64)> = match std::fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                tracing::warn!("消息去重记录无法解析，已忽略: {}: {e}", path.display());
+                VecDeque::new()
+            }),
+            Err(_) => VecDeque::new(),
+        };
+        let mut seen = Self {
+            path,
+            ttl,
+            capacity,
+            keys: entries.iter().map(|(key, _)| key.clone()).collect(),
+            entries,
+            dirty: false,
+        };
+        seen.sweep();
+        seen.evict();
+        seen
+    }
+
+    /// Record a message of `channel`; `false` if its `id` was seen before.
+    /// Messages without an id, and every message when de-duplication is
+    /// off, are always new.
+    pub fn first_delivery(&mut self, channel: &str, id: &str) -> bool {
+        if self.capacity == 0 || id.is_empty() {
+            return true;
+        }
+        self.sweep();
+        let key = format!("{channel}:{id}");
+        if self.keys.contains(&key) {
+            return false;
+        }
+        self.keys.insert(key.clone());
+        self.entries.push_back((key, now_secs()));
+        self.evict();
+        self.dirty = true;
+        true
+    }
+
+    /// Drop ids older than the TTL.
+    pub fn sweep(&mut self) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let cutoff = now_secs().saturating_sub(self.ttl.as_secs());
+        while let Some((key, seen_at)) = self.entries.front() {
+            if *seen_at >= cutoff {
+                break;
+            }
+            self.keys.remove(key);
+            self.entries.pop_front();
+            self.dirty = true;
+        }
+    }
+
+    /// Drop the oldest ids beyond the capacity.
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            if let Some((key, _)) = self.entries.pop_front() {
+                self.keys.remove(&key);
+            }
+            self.dirty = true;
+        }
+    }
+
+    /// Write the ids if they changed since the last save.
+    pub fn save(&mut self) -> Result<()> {
+        let path = &self.path;
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+        }
+        let json = serde_json::to_string(&self.entries)?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json).with_context(|| format!("写入失败: {}", tmp.display()))?;
+        std::fs::rename(&tmp, path).with_context(|| format!("写入失败: {}", path.display()))?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// Pass the messages from `inbound` on to `dispatch`, dropping those seen
+/// before. Runs until either side closes, saving the seen ids periodically
+/// and on the way out.
+pub async fn forward_first_deliveries(
+    mut seen: SeenMessages,
+    mut inbound: Receiver<ChannelMessage>,
+    dispatch: Sender<ChannelMessage>,
+) {
+    let mut save = tokio::time::interval(SAVE_INTERVAL);
+    loop {
+        let msg = tokio::select! {
+            msg = inbound.recv() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            _ = save.tick() => {
+                seen.sweep();
+                if let Err(e) = seen.save() {
+                    tracing::warn!("保存消息去重记录失败: {e:#}");
+                }
+                continue;
+            }
+        };
+        if !seen.first_delivery(&msg.channel, &msg.id) {
+            tracing::info!(channel = %msg.channel, id = %msg.id, "忽略重复投递的消息");
+            continue;
+        }
+        if dispatch.send(msg).await.is_err() {
+            break;
+        }
+    }
+    if let Err(e) = seen.save() {
+        tracing::warn!("保存消息去重记录失败: {e:#}");
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use tokio::sync::mpsc;
+
+    fn message(channel: &str, id: &str, content: &str) -> ChannelMessage {
+        ChannelMessage {
+            id: id.into(),
+            sender: "chat-1".into(),
+            content: content.into(),
+            channel: channel.into(),
+            timestamp: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn a_redelivered_message_is_dispatched_once() {
+        let tmp = TempDir::new().unwrap();
+        let seen = SeenMessages::load(tmp.path(), Duration::from_mins(1), 100);
+        let (tx, inbound) = mpsc::channel(10);
+        let (dispatch, mut rx) = mpsc::channel(10);
+        let forwarder = tokio::spawn(forward_first_deliveries(seen, inbound, dispatch));
+
+        tx.send(message("telegram", "42:7", "hello")).await.unwrap();
+        tx.send(message("telegram", "42:7", "hello")).await.unwrap();
+        // The same id on another channel is another message
+        tx.send(message("matrix", "42:7", "hi")).await.unwrap();
+        tx.send(message("telegram", "", "no id")).await.unwrap();
+        tx.send(message("telegram", "", "no id")).await.unwrap();
+        drop(tx);
+        forwarder.await.unwrap();
+
+        let mut dispatched = Vec::new();
+        while let Some(msg) = rx.recv().await {
+            dispatched.push(format!("{}:{}", msg.channel, msg.content));
+        }
+        assert_eq!(
+            dispatched,
+            [
+                "telegram:hello",
+                "matrix:hi",
+                "telegram:no id",
+                "telegram:no id"
+            ]
+        );
+
+        // Still a duplicate after a restart
+        let mut seen = SeenMessages::load(tmp.path(), Duration::from_mins(1), 100);
+        assert!(!seen.first_delivery("telegram", "42:7"));
+        assert!(seen.first_delivery("telegram", "42:8"));
+    }
+
+    #[test]
+    fn oldest_ids_are_evicted_beyond_capacity() {
+        let tmp = TempDir::new().unwrap();
+        let mut seen = SeenMessages::load(tmp.path(), Duration::ZERO, 2);
+        assert!(seen.first_delivery("slack", "1"));
+        assert!(seen.first_delivery("slack", "2"));
+        assert!(seen.first_delivery("slack", "3"));
+        assert!(!seen.first_delivery("slack", "3"));
+        assert!(seen.first_delivery("slack", "1"));
+
+        let mut off = SeenMessages::load(tmp.path(), Duration::ZERO, 0);
+        assert!(off.first_delivery("slack", "1"));
+        assert!(off.first_delivery("slack", "1"));
+    }
+
+    #[test]
+    fn expired_ids_are_forgotten() {
+        let tmp = TempDir::new().unwrap();
+        let stale = now_secs() - 120;
+        std::fs::create_dir_all(tmp.path().join("state")).unwrap();
+        std::fs::write(
+            tmp.path().join("state").join("channel_seen_messages.json"),
+            serde_json::to_string(&[("telegram:1", stale), ("telegram:2", now_secs())]).unwrap(),
+        )
+        .unwrap();
+
+        let mut seen = SeenMessages::load(tmp.path(), Duration::from_mins(1), 100);
+        assert!(seen.first_delivery("telegram", "1"));
+        assert!(!seen.first_delivery("telegram", "2"));
+    }
+}
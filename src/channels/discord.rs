@@ -1,16 +1,66 @@
+use super::allowlist::RejectionSink;
+use super::chunk::split_message;
 use super::traits::{Channel, ChannelMessage};
+use crate::config::DiscordConfig;
+use anyhow::Context;
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
-use serde_json::json;
+use reqwest::multipart::{Form, Part};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
 use tokio_tungstenite::tungstenite::Message;
-use uuid::Uuid;
+
+const API: &str = "https://discord.com/api/v10";
+
+/// Discord's limit on a message's content.
+const MAX_MESSAGE_LEN: usize = 2000;
+
+/// Replies that would take more messages than this go out as their first
+/// message with the whole text attached as a file.
+const MAX_REPLY_MESSAGES: usize = 3;
+
+/// File name long replies are attached under.
+const REPLY_FILE_NAME: &str = "reply.txt";
+
+/// Largest inbound attachment downloaded for the agent.
+const MAX_ATTACHMENT_BYTES: u64 = 25 * 1024 * 1024;
+
+/// Name of the slash command users invoke the agent with.
+const COMMAND_NAME: &str = "jarvis";
+
+/// Recipient prefix that routes a reply to a pending `/jarvis` interaction.
+const INTERACTION_PREFIX: &str = "interaction:";
+
+/// Message flag: only the invoking user sees it.
+const EPHEMERAL: u64 = 1 << 6;
 
 /// Discord channel — connects via Gateway WebSocket for real-time messages
 pub struct DiscordChannel {
     bot_token: String,
     guild_id: Option<String>,
     allowed_users: Vec<String>,
+    /// Where senders the allowlist turns away are reported
+    rejections: Option<RejectionSink>,
+    reply_in_thread: bool,
+    respond_to_mentions_only: bool,
     client: reqwest::Client,
+    /// Reply thread per `channel_id:user_id`.
+    threads: Mutex<HashMap<String, String>>,
+    /// Deferred `/jarvis` interactions awaiting their answer, by interaction id.
+    interactions: Mutex<HashMap<String, Interaction>>,
+    commands_registered: AtomicBool,
+    /// Where inbound attachments are saved (under `inbox/discord/`); without
+    /// one the agent only sees their names and links.
+    workspace_dir: Option<PathBuf>,
+}
+
+/// A `/jarvis` invocation whose "thinking…" reply is still to be edited.
+struct Interaction {
+    application_id: String,
+    token: String,
 }
 
 impl DiscordChannel {
@@ -19,10 +69,41 @@ impl DiscordChannel {
             bot_token,
             guild_id,
             allowed_users,
+            rejections: None,
+            reply_in_thread: false,
+            respond_to_mentions_only: false,
             client: reqwest::Client::new(),
+            threads: Mutex::new(HashMap::new()),
+            interactions: Mutex::new(HashMap::new()),
+            commands_registered: AtomicBool::new(false),
+            workspace_dir: None,
+        }
+    }
+
+    pub fn from_config(config: &DiscordConfig) -> Self {
+        Self {
+            reply_in_thread: config.reply_in_thread,
+            respond_to_mentions_only: config.respond_to_mentions_only,
+            ..Self::new(
+                config.bot_token.clone(),
+                config.guild_id.clone(),
+                config.allowed_users.clone(),
+            )
         }
     }
 
+    /// Save inbound attachments into `workspace_dir` for the agent to read.
+    pub fn with_workspace(mut self, workspace_dir: &Path) -> Self {
+        self.workspace_dir = Some(workspace_dir.to_path_buf());
+        self
+    }
+
+    /// Report senders the allowlist turns away to `sink`.
+    pub fn with_rejections(mut self, sink: Option<RejectionSink>) -> Self {
+        self.rejections = sink;
+        self
+    }
+
     /// Check if a Discord user ID is in the allowlist.
     /// Empty list means deny everyone until explicitly configured.
     /// `"*"` means allow everyone.
@@ -30,11 +111,647 @@ impl DiscordChannel {
         self.allowed_users.iter().any(|u| u == "*" || u == user_id)
     }
 
+    /// Whether `guild` passes the `guild_id` filter (DMs have no guild).
+    fn is_guild_allowed(&self, guild: Option<&str>) -> bool {
+        self.guild_id
+            .as_deref()
+            .is_none_or(|gid| guild == Some(gid))
+    }
+
     fn bot_user_id_from_token(token: &str) -> Option<String> {
         // Discord bot tokens are base64(bot_user_id).timestamp.hmac
         let part = token.split('.').next()?;
         base64_decode(part)
     }
+
+    fn authorized(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        request.header("Authorization", format!("Bot {}", self.bot_token))
+    }
+
+    async fn post_message(
+        &self,
+        channel_id: &str,
+        content: &str,
+        file: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let request = self.authorized(
+            self.client
+                .post(format!("{API}/channels/{channel_id}/messages")),
+        );
+        let resp = with_body(request, json!({ "content": content }), file)?
+            .send()
+            .await?;
+        check(resp, "发送消息").await.map(|_| ())
+    }
+
+    /// The id of the bot's DM channel with `user_id`, opening it if needed.
+    pub async fn dm_channel(&self, user_id: &str) -> anyhow::Result<String> {
+        let resp = self
+            .authorized(self.client.post(format!("{API}/users/@me/channels")))
+            .json(&json!({ "recipient_id": user_id }))
+            .send()
+            .await?;
+        let channel = check(resp, "打开私信").await?;
+        channel
+            .get("id")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .context("私信通道中缺少 id")
+    }
+
+    /// The bot's application id, which commands are registered under.
+    async fn application_id(&self) -> anyhow::Result<String> {
+        let resp = self
+            .authorized(self.client.get(format!("{API}/oauth2/applications/@me")))
+            .send()
+            .await?;
+        let app = check(resp, "读取应用信息").await?;
+        app.get("id")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .context("应用信息中缺少 id")
+    }
+
+    /// Commands on the configured guild show up at once; global ones are
+    /// used when no guild is set.
+    fn commands_url(&self, application_id: &str) -> String {
+        match &self.guild_id {
+            Some(guild) => format!("{API}/applications/{application_id}/guilds/{guild}/commands"),
+            None => format!("{API}/applications/{application_id}/commands"),
+        }
+    }
+
+    /// Create or update the `/jarvis <prompt>` command.
+    async fn register_commands(&self) -> anyhow::Result<()> {
+        let application_id = self.application_id().await?;
+        let resp = self
+            .authorized(self.client.post(self.commands_url(&application_id)))
+            .json(&jarvis_command())
+            .send()
+            .await?;
+        check(resp, "注册 /jarvis 命令").await.map(|_| ())
+    }
+
+    /// For `jarvis channel doctor`: fail if the bot lacks the
+    /// `applications.commands` scope in the configured guild or `/jarvis`
+    /// is not registered.
+    pub async fn check_commands(&self) -> anyhow::Result<()> {
+        let application_id = self.application_id().await?;
+        let resp = self
+            .authorized(self.client.get(self.commands_url(&application_id)))
+            .send()
+            .await?;
+        if resp.status() == reqwest::StatusCode::FORBIDDEN {
+            anyhow::bail!(
+                "机器人缺少 applications.commands 权限范围，请用包含该 scope 的邀请链接重新邀请"
+            );
+        }
+        let commands = check(resp, "读取命令列表").await?;
+        let registered = commands.as_array().is_some_and(|commands| {
+            commands
+                .iter()
+                .any(|c| c.get("name").and_then(Value::as_str) == Some(COMMAND_NAME))
+        });
+        if !registered {
+            anyhow::bail!("/jarvis 命令尚未注册（通道启动时自动注册，失败原因见日志）");
+        }
+        Ok(())
+    }
+
+    /// Register `/jarvis` once per process; a failure is logged and shown as
+    /// a degraded channel, and retried on the next reconnect.
+    async fn ensure_commands(&self) {
+        if self.commands_registered.load(Ordering::Relaxed) {
+            return;
+        }
+        match self.register_commands().await {
+            Ok(()) => {
+                self.commands_registered.store(true, Ordering::Relaxed);
+                tracing::info!("Discord: 已注册 /jarvis 命令");
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Discord: 注册 /jarvis 命令失败（检查 applications.commands 权限范围）: {e}"
+                );
+                crate::health::mark_component_degraded(
+                    "channel:discord",
+                    format!("/jarvis 命令注册失败: {e}"),
+                );
+            }
+        }
+    }
+
+    fn is_own_thread(&self, channel_id: &str) -> bool {
+        self.threads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .any(|thread| thread == channel_id)
+    }
+
+    /// Where to answer a server message: a reply thread when
+    /// `reply_in_thread` is set, else the message's channel.
+    async fn reply_target(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+        author_id: &str,
+        content: &str,
+    ) -> String {
+        if !self.reply_in_thread || self.is_own_thread(channel_id) {
+            return channel_id.to_string();
+        }
+        let key = format!("{channel_id}:{author_id}");
+        if let Some(thread) = self
+            .threads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+        {
+            return thread.clone();
+        }
+        match self.start_thread(channel_id, message_id, content).await {
+            Ok(thread) => {
+                self.threads
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .insert(key, thread.clone());
+                thread
+            }
+            Err(e) => {
+                // e.g. the message is already in a thread someone else started
+                tracing::warn!("Discord: 创建回复线程失败，改为直接回复: {e}");
+                channel_id.to_string()
+            }
+        }
+    }
+
+    async fn start_thread(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+        content: &str,
+    ) -> anyhow::Result<String> {
+        let resp = self
+            .authorized(self.client.post(format!(
+                "{API}/channels/{channel_id}/messages/{message_id}/threads"
+            )))
+            .json(&json!({
+                "name": thread_name(content),
+                "auto_archive_duration": 1440
+            }))
+            .send()
+            .await?;
+        let thread = check(resp, "创建线程").await?;
+        thread
+            .get("id")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .context("线程响应中缺少 id")
+    }
+
+    /// Filter a `MESSAGE_CREATE` payload and pick where to reply.
+    async fn on_message(&self, d: &Value, bot_user_id: &str) -> Option<ChannelMessage> {
+        // Skip messages from the bot itself
+        let author = d.get("author");
+        let author_id = author
+            .and_then(|a| a.get("id"))
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        if author_id == bot_user_id {
+            return None;
+        }
+
+        // Skip bot messages
+        if author
+            .and_then(|a| a.get("bot"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+        {
+            return None;
+        }
+
+        // Sender validation
+        if !self.is_user_allowed(author_id) {
+            tracing::warn!("Discord: 忽略未授权用户的消息: {author_id}");
+            if let Some(sink) = &self.rejections {
+                sink.report("discord", author_id);
+            }
+            return None;
+        }
+
+        // Guild filter
+        let guild = d.get("guild_id").and_then(Value::as_str);
+        if !self.is_guild_allowed(guild) {
+            return None;
+        }
+
+        let channel_id = d.get("channel_id").and_then(Value::as_str).unwrap_or("");
+        let mut content = d
+            .get("content")
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_string();
+        if guild.is_some() {
+            if self.respond_to_mentions_only
+                && !mentions(d, bot_user_id)
+                && !self.is_own_thread(channel_id)
+            {
+                return None;
+            }
+            content = strip_mention(&content, bot_user_id);
+        }
+        for note in self.receive_attachments(d).await {
+            if !content.is_empty() {
+                content.push('\n');
+            }
+            content.push_str(&note);
+        }
+        if content.is_empty() {
+            return None;
+        }
+
+        let message_id = d.get("id").and_then(Value::as_str).unwrap_or("");
+        let sender = if guild.is_some() && !message_id.is_empty() {
+            self.reply_target(channel_id, message_id, author_id, &content)
+                .await
+        } else {
+            channel_id.to_string()
+        };
+        Some(channel_message(message_id, sender, content))
+    }
+
+    /// Acknowledge a `/jarvis` invocation with an ephemeral "thinking…"
+    /// reply (Discord allows 3 seconds) and queue its prompt.
+    async fn on_interaction(&self, d: &Value) -> Option<ChannelMessage> {
+        let prompt = slash_prompt(d)?;
+        let id = d.get("id").and_then(Value::as_str)?;
+        let token = d.get("token").and_then(Value::as_str)?;
+        let application_id = d.get("application_id").and_then(Value::as_str)?;
+        let user_id = interaction_user_id(d).unwrap_or("");
+
+        let allowed = self.is_user_allowed(user_id)
+            && self.is_guild_allowed(d.get("guild_id").and_then(Value::as_str));
+        let callback = if allowed {
+            json!({ "type": 5, "data": { "flags": EPHEMERAL } })
+        } else {
+            tracing::warn!("Discord: 忽略未授权用户的 /jarvis 命令: {user_id}");
+            if let Some(sink) = &self.rejections
+                && !user_id.is_empty()
+                && !self.is_user_allowed(user_id)
+            {
+                sink.report("discord", user_id);
+            }
+            json!({
+                "type": 4,
+                "data": { "content": "You are not allowed to use Jarvis here.", "flags": EPHEMERAL }
+            })
+        };
+        let resp = self
+            .client
+            .post(format!("{API}/interactions/{id}/{token}/callback"))
+            .json(&callback)
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url);
+        let acknowledged = match resp {
+            Ok(resp) => check(resp, "响应 /jarvis").await.map(|_| ()),
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = acknowledged {
+            tracing::warn!("Discord: {e}");
+            return None;
+        }
+        if !allowed {
+            return None;
+        }
+
+        self.interactions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                id.to_string(),
+                Interaction {
+                    application_id: application_id.to_string(),
+                    token: token.to_string(),
+                },
+            );
+        Some(channel_message(
+            id,
+            format!("{INTERACTION_PREFIX}{id}"),
+            prompt,
+        ))
+    }
+
+    /// Replace the "thinking…" reply with the first message; the rest
+    /// follow as ephemeral follow-up messages.
+    async fn answer_interaction(
+        &self,
+        interaction: &Interaction,
+        reply: &Reply,
+    ) -> anyhow::Result<()> {
+        let webhook = format!(
+            "{API}/webhooks/{}/{}",
+            interaction.application_id, interaction.token
+        );
+        for (i, content) in reply.messages.iter().enumerate() {
+            let file = reply.file_for(i);
+            let request = if i == 0 {
+                let request = self.client.patch(format!("{webhook}/messages/@original"));
+                with_body(request, json!({ "content": content }), file)?
+            } else {
+                let payload = json!({ "content": content, "flags": EPHEMERAL });
+                with_body(self.client.post(&webhook), payload, file)?
+            };
+            // The URL embeds the interaction token; keep it out of errors
+            let resp = request.send().await.map_err(reqwest::Error::without_url)?;
+            check(resp, "回复 /jarvis").await?;
+        }
+        Ok(())
+    }
+
+    /// One line per attachment of a `MESSAGE_CREATE`, saving each file under
+    /// `inbox/discord/<attachment id>/` when a workspace is set.
+    async fn receive_attachments(&self, d: &Value) -> Vec<String> {
+        let mut notes = Vec::new();
+        for attachment in attachments(d) {
+            let status = match &self.workspace_dir {
+                None => format!("at {}", attachment.url),
+                Some(_) if attachment.size > MAX_ATTACHMENT_BYTES => format!(
+                    "not downloaded: larger than {}",
+                    format_size(MAX_ATTACHMENT_BYTES)
+                ),
+                Some(workspace_dir) => match self.download(&attachment, workspace_dir).await {
+                    Ok(relative) => format!("saved to {relative}"),
+                    Err(e) => {
+                        tracing::warn!("Discord: 下载附件 {} 失败: {e:#}", attachment.filename);
+                        "could not be downloaded".to_string()
+                    }
+                },
+            };
+            notes.push(format!("[Attachment: {} {status}]", attachment.describe()));
+        }
+        notes
+    }
+
+    /// Fetch an attachment into the workspace; returns its path relative
+    /// to the workspace.
+    async fn download(
+        &self,
+        attachment: &Attachment,
+        workspace_dir: &Path,
+    ) -> anyhow::Result<String> {
+        let relative = Path::new("inbox")
+            .join("discord")
+            .join(safe_file_name(&attachment.id))
+            .join(safe_file_name(&attachment.filename));
+        let resp = self
+            .client
+            .get(&attachment.url)
+            .send()
+            .await?
+            .error_for_status()?;
+        let bytes = resp.bytes().await?;
+        if bytes.len() as u64 > MAX_ATTACHMENT_BYTES {
+            anyhow::bail!("附件超过 {}", format_size(MAX_ATTACHMENT_BYTES));
+        }
+        let path = workspace_dir.join(&relative);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, &bytes).await?;
+        Ok(relative.to_string_lossy().replace('\\', "/"))
+    }
+}
+
+/// Fail on a non-2xx response, else return its JSON body (`null` if none).
+async fn check(resp: reqwest::Response, action: &str) -> anyhow::Result<Value> {
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let err = resp
+            .text()
+            .await
+            .unwrap_or_else(|e| format!("<无法读取响应体: {e}>"));
+        anyhow::bail!("Discord {action}失败 ({status}): {err}");
+    }
+    Ok(resp.json().await.unwrap_or_default())
+}
+
+/// How a reply goes out: the messages to post and, for a reply too long to
+/// read as a run of messages, its full text attached to the last of them.
+#[derive(Debug, PartialEq)]
+struct Reply {
+    messages: Vec<String>,
+    file: Option<String>,
+}
+
+impl Reply {
+    fn plan(text: &str) -> Self {
+        let mut messages = split_message(text, MAX_MESSAGE_LEN);
+        if messages.len() <= MAX_REPLY_MESSAGES {
+            return Self {
+                messages,
+                file: None,
+            };
+        }
+        messages.truncate(1);
+        Self {
+            messages,
+            file: Some(text.to_string()),
+        }
+    }
+
+    /// The file to attach to message `index`, if any.
+    fn file_for(&self, index: usize) -> Option<&str> {
+        self.file
+            .as_deref()
+            .filter(|_| index + 1 == self.messages.len())
+    }
+}
+
+/// Send `payload` as JSON, or as multipart with `file` attached as
+/// [`REPLY_FILE_NAME`].
+fn with_body(
+    request: reqwest::RequestBuilder,
+    mut payload: Value,
+    file: Option<&str>,
+) -> anyhow::Result<reqwest::RequestBuilder> {
+    let Some(text) = file else {
+        return Ok(request.json(&payload));
+    };
+    payload["attachments"] = json!([{ "id": 0, "filename": REPLY_FILE_NAME }]);
+    let part = Part::bytes(text.as_bytes().to_vec())
+        .file_name(REPLY_FILE_NAME)
+        .mime_str("text/plain; charset=utf-8")?;
+    let form = Form::new()
+        .text("payload_json", payload.to_string())
+        .part("files[0]", part);
+    Ok(request.multipart(form))
+}
+
+/// A file attached to an inbound message.
+#[derive(Debug)]
+struct Attachment {
+    id: String,
+    filename: String,
+    url: String,
+    size: u64,
+    content_type: Option<String>,
+}
+
+impl Attachment {
+    /// `notes.pdf (application/pdf, 12 KB)`
+    fn describe(&self) -> String {
+        match &self.content_type {
+            Some(kind) => format!("{} ({kind}, {})", self.filename, format_size(self.size)),
+            None => format!("{} ({})", self.filename, format_size(self.size)),
+        }
+    }
+}
+
+fn attachments(d: &Value) -> Vec<Attachment> {
+    d.get("attachments")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|a| {
+            Some(Attachment {
+                id: a.get("id")?.as_str()?.to_string(),
+                filename: a
+                    .get("filename")
+                    .and_then(Value::as_str)
+                    .unwrap_or("attachment")
+                    .to_string(),
+                url: a.get("url")?.as_str()?.to_string(),
+                size: a.get("size").and_then(Value::as_u64).unwrap_or(0),
+                content_type: a
+                    .get("content_type")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// `name` reduced to characters that are safe in a path component.
+fn safe_file_name(name: &str) -> String {
+    let safe: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let safe = safe.trim_start_matches('.');
+    if safe.is_empty() {
+        "attachment".to_string()
+    } else {
+        safe.to_string()
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = 1024 * KB;
+    if bytes < KB {
+        format!("{bytes} B")
+    } else if bytes < MB {
+        format!("{} KB", bytes.div_ceil(KB))
+    } else {
+        let tenths = bytes * 10 / MB;
+        format!("{}.{} MB", tenths / 10, tenths % 10)
+    }
+}
+
+fn channel_message(id: &str, sender: String, content: String) -> ChannelMessage {
+    ChannelMessage {
+        id: id.to_string(),
+        sender,
+        content,
+        channel: "discord".to_string(),
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    }
+}
+
+/// The `/jarvis <prompt>` command definition.
+fn jarvis_command() -> Value {
+    json!({
+        "name": COMMAND_NAME,
+        "type": 1,
+        "description": "Ask Jarvis",
+        "options": [{
+            "type": 3,
+            "name": "prompt",
+            "description": "What to ask",
+            "required": true
+        }]
+    })
+}
+
+/// The prompt of a `/jarvis` interaction; `None` for anything else.
+fn slash_prompt(d: &Value) -> Option<String> {
+    // Type 2 is an application command
+    if d.get("type").and_then(Value::as_u64) != Some(2) {
+        return None;
+    }
+    let data = d.get("data")?;
+    if data.get("name").and_then(Value::as_str) != Some(COMMAND_NAME) {
+        return None;
+    }
+    let prompt = data
+        .get("options")?
+        .as_array()?
+        .iter()
+        .find(|o| o.get("name").and_then(Value::as_str) == Some("prompt"))?
+        .get("value")?
+        .as_str()?
+        .trim();
+    (!prompt.is_empty()).then(|| prompt.to_string())
+}
+
+/// The invoking user: `member.user` in servers, `user` in DMs.
+fn interaction_user_id(d: &Value) -> Option<&str> {
+    d.get("member")
+        .and_then(|m| m.get("user"))
+        .or_else(|| d.get("user"))?
+        .get("id")?
+        .as_str()
+}
+
+fn mentions(d: &Value, user_id: &str) -> bool {
+    d.get("mentions")
+        .and_then(Value::as_array)
+        .is_some_and(|users| {
+            users
+                .iter()
+                .any(|u| u.get("id").and_then(Value::as_str) == Some(user_id))
+        })
+}
+
+fn strip_mention(content: &str, user_id: &str) -> String {
+    if user_id.is_empty() {
+        return content.trim().to_string();
+    }
+    content
+        .replace(&format!("<@{user_id}>"), "")
+        .replace(&format!("<@!{user_id}>"), "")
+        .trim()
+        .to_string()
+}
+
+/// Thread title from the first line of the prompt (Discord allows 100).
+fn thread_name(content: &str) -> String {
+    let line = content.lines().next().unwrap_or("").trim();
+    if line.is_empty() {
+        return "Jarvis".to_string();
+    }
+    crate::util::truncate_with_ellipsis(line, 80)
 }
 
 const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
@@ -83,27 +800,21 @@ impl Channel for DiscordChannel {
         "discord"
     }
 
-    async fn send(&self, message: &str, channel_id: &str) -> anyhow::Result<()> {
-        let url = format!("https://discord.com/api/v10/channels/{channel_id}/messages");
-        let body = json!({ "content": message });
-
-        let resp = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bot {}", self.bot_token))
-            .json(&body)
-            .send()
-            .await?;
-
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let err = resp
-                .text()
-                .await
-                .unwrap_or_else(|e| format!("<无法读取响应体: {e}>"));
-            anyhow::bail!("Discord 发送消息失败 ({status}): {err}");
+    async fn send(&self, message: &str, recipient: &str) -> anyhow::Result<()> {
+        let reply = Reply::plan(message);
+        if let Some(id) = recipient.strip_prefix(INTERACTION_PREFIX) {
+            let interaction = self
+                .interactions
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(id)
+                .context("/jarvis 交互不存在或已回复")?;
+            return self.answer_interaction(&interaction, &reply).await;
+        }
+        for (i, content) in reply.messages.iter().enumerate() {
+            self.post_message(recipient, content, reply.file_for(i))
+                .await?;
         }
-
         Ok(())
     }
 
@@ -111,11 +822,11 @@ impl Channel for DiscordChannel {
     async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
         let bot_user_id = Self::bot_user_id_from_token(&self.bot_token).unwrap_or_default();
 
+        self.ensure_commands().await;
+
         // Get Gateway URL
         let gw_resp: serde_json::Value = self
-            .client
-            .get("https://discord.com/api/v10/gateway/bot")
-            .header("Authorization", format!("Bot {}", self.bot_token))
+            .authorized(self.client.get(format!("{API}/gateway/bot")))
             .send()
             .await?
             .json()
@@ -179,8 +890,6 @@ impl Channel for DiscordChannel {
             }
         });
 
-        let guild_filter = self.guild_id.clone();
-
         loop {
             tokio::select! {
                 _ = hb_rx.recv() => {
@@ -232,57 +941,17 @@ impl Channel for DiscordChannel {
                         _ => {}
                     }
 
-                    // Only handle MESSAGE_CREATE (opcode 0, type "MESSAGE_CREATE")
-                    let event_type = event.get("t").and_then(|t| t.as_str()).unwrap_or("");
-                    if event_type != "MESSAGE_CREATE" {
-                        continue;
-                    }
-
                     let Some(d) = event.get("d") else {
                         continue;
                     };
-
-                    // Skip messages from the bot itself
-                    let author_id = d.get("author").and_then(|a| a.get("id")).and_then(|i| i.as_str()).unwrap_or("");
-                    if author_id == bot_user_id {
-                        continue;
-                    }
-
-                    // Skip bot messages
-                    if d.get("author").and_then(|a| a.get("bot")).and_then(serde_json::Value::as_bool).unwrap_or(false) {
-                        continue;
-                    }
-
-                    // Sender validation
-                    if !self.is_user_allowed(author_id) {
-                        tracing::warn!("Discord: 忽略未授权用户的消息: {author_id}");
-                        continue;
-                    }
-
-                    // Guild filter
-                    if let Some(ref gid) = guild_filter {
-                        let msg_guild = d.get("guild_id").and_then(serde_json::Value::as_str).unwrap_or("");
-                        if msg_guild != gid {
-                            continue;
-                        }
-                    }
-
-                    let content = d.get("content").and_then(|c| c.as_str()).unwrap_or("");
-                    if content.is_empty() {
+                    let event_type = event.get("t").and_then(|t| t.as_str()).unwrap_or("");
+                    let channel_msg = match event_type {
+                        "MESSAGE_CREATE" => self.on_message(d, &bot_user_id).await,
+                        "INTERACTION_CREATE" => self.on_interaction(d).await,
+                        _ => None,
+                    };
+                    let Some(channel_msg) = channel_msg else {
                         continue;
-                    }
-
-                    let channel_id = d.get("channel_id").and_then(|c| c.as_str()).unwrap_or("").to_string();
-
-                    let channel_msg = ChannelMessage {
-                        id: Uuid::new_v4().to_string(),
-                        sender: channel_id,
-                        content: content.to_string(),
-                        channel: "discord".to_string(),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_secs(),
                     };
 
                     if tx.send(channel_msg).await.is_err() {
@@ -296,13 +965,10 @@ impl Channel for DiscordChannel {
     }
 
     async fn health_check(&self) -> bool {
-        self.client
-            .get("https://discord.com/api/v10/users/@me")
-            .header("Authorization", format!("Bot {}", self.bot_token))
+        self.authorized(self.client.get(format!("{API}/users/@me")))
             .send()
             .await
-            .map(|r| r.status().is_success())
-            .unwrap_or(false)
+            .is_ok_and(|r| r.status().is_success())
     }
 }
 
@@ -400,4 +1066,256 @@ mod tests {
         let id = DiscordChannel::bot_user_id_from_token("");
         assert_eq!(id, Some(String::new()));
     }
+
+    fn config(reply_in_thread: bool, respond_to_mentions_only: bool) -> DiscordConfig {
+        DiscordConfig {
+            bot_token: "MTIzNDU2.fake.hmac".into(),
+            guild_id: None,
+            allowed_users: vec!["42".into()],
+            reply_in_thread,
+            respond_to_mentions_only,
+        }
+    }
+
+    fn dm(content: &str) -> Value {
+        json!({
+            "id": "m1",
+            "channel_id": "dm-channel",
+            "author": { "id": "42" },
+            "content": content
+        })
+    }
+
+    #[test]
+    fn commands_register_on_the_configured_guild() {
+        let ch = DiscordChannel::new("fake".into(), Some("g1".into()), vec![]);
+        assert_eq!(
+            ch.commands_url("app"),
+            "https://discord.com/api/v10/applications/app/guilds/g1/commands"
+        );
+        let ch = DiscordChannel::new("fake".into(), None, vec![]);
+        assert_eq!(
+            ch.commands_url("app"),
+            "https://discord.com/api/v10/applications/app/commands"
+        );
+
+        let command = jarvis_command();
+        assert_eq!(command["name"], "jarvis");
+        assert_eq!(command["options"][0]["name"], "prompt");
+        assert_eq!(command["options"][0]["required"], true);
+    }
+
+    #[test]
+    fn slash_prompt_reads_only_jarvis_commands() {
+        let interaction = |name: &str, prompt: &str| {
+            json!({
+                "type": 2,
+                "data": { "name": name, "options": [{ "name": "prompt", "type": 3, "value": prompt }] }
+            })
+        };
+        assert_eq!(
+            slash_prompt(&interaction("jarvis", " what's up? ")).as_deref(),
+            Some("what's up?")
+        );
+        assert_eq!(slash_prompt(&interaction("other", "hi")), None);
+        assert_eq!(slash_prompt(&interaction("jarvis", "  ")), None);
+        assert_eq!(
+            slash_prompt(&json!({ "type": 3, "data": { "name": "jarvis" } })),
+            None
+        );
+    }
+
+    #[test]
+    fn interaction_user_comes_from_member_or_user() {
+        let in_guild = json!({ "member": { "user": { "id": "1" } }, "guild_id": "g" });
+        let in_dm = json!({ "user": { "id": "2" } });
+        assert_eq!(interaction_user_id(&in_guild), Some("1"));
+        assert_eq!(interaction_user_id(&in_dm), Some("2"));
+        assert_eq!(interaction_user_id(&json!({})), None);
+    }
+
+    #[test]
+    fn mentions_are_detected_and_stripped() {
+        let d = json!({ "mentions": [{ "id": "7" }, { "id": "123456" }] });
+        assert!(mentions(&d, "123456"));
+        assert!(!mentions(&d, "999"));
+        assert!(!mentions(&json!({}), "123456"));
+
+        assert_eq!(strip_mention("<@123456> hello", "123456"), "hello");
+        assert_eq!(strip_mention("hey <@!123456>", "123456"), "hey");
+        assert_eq!(strip_mention("<@7> hi", "123456"), "<@7> hi");
+    }
+
+    #[test]
+    fn thread_names_use_the_first_line() {
+        assert_eq!(thread_name("Fix the build\nplease"), "Fix the build");
+        assert_eq!(thread_name("  \n"), "Jarvis");
+        assert!(thread_name(&"x".repeat(200)).chars().count() <= 100);
+    }
+
+    #[tokio::test]
+    async fn dms_are_answered_in_place_even_with_mentions_only() {
+        let ch = DiscordChannel::from_config(&config(true, true));
+        let msg = ch.on_message(&dm("hello"), "123456").await.unwrap();
+        assert_eq!(msg.sender, "dm-channel");
+        assert_eq!(msg.content, "hello");
+        assert_eq!(msg.channel, "discord");
+    }
+
+    #[tokio::test]
+    async fn mentions_only_skips_unmentioned_server_messages() {
+        let ch = DiscordChannel::from_config(&config(false, true));
+        let mut d = dm("hello everyone");
+        d["guild_id"] = json!("g1");
+        d["channel_id"] = json!("general");
+        assert!(ch.on_message(&d, "123456").await.is_none());
+
+        d["content"] = json!("<@123456> hello");
+        d["mentions"] = json!([{ "id": "123456" }]);
+        let msg = ch.on_message(&d, "123456").await.unwrap();
+        assert_eq!(msg.content, "hello");
+        assert_eq!(msg.sender, "general");
+    }
+
+    #[tokio::test]
+    async fn messages_in_own_threads_need_no_mention() {
+        let ch = DiscordChannel::from_config(&config(true, true));
+        ch.threads
+            .lock()
+            .unwrap()
+            .insert("general:42".into(), "thread-1".into());
+
+        let mut d = dm("follow-up");
+        d["guild_id"] = json!("g1");
+        d["channel_id"] = json!("thread-1");
+        assert_eq!(
+            ch.on_message(&d, "123456").await.unwrap().sender,
+            "thread-1"
+        );
+
+        // A new message in the channel goes to the user's existing thread
+        d["channel_id"] = json!("general");
+        d["mentions"] = json!([{ "id": "123456" }]);
+        assert_eq!(
+            ch.on_message(&d, "123456").await.unwrap().sender,
+            "thread-1"
+        );
+    }
+
+    #[test]
+    fn long_replies_become_a_preview_and_a_file() {
+        let paragraph = "word ".repeat(300);
+        let text = |paragraphs: usize| vec![paragraph.trim(); paragraphs].join("\n\n");
+
+        let short = Reply::plan("hello");
+        assert_eq!(short.messages, ["hello"]);
+        assert_eq!(short.file_for(0), None);
+
+        // Each paragraph needs a message of its own
+        let most = Reply::plan(&text(MAX_REPLY_MESSAGES));
+        assert_eq!(most.messages.len(), MAX_REPLY_MESSAGES);
+        assert!(most.file.is_none());
+
+        let long = text(MAX_REPLY_MESSAGES + 1);
+        let reply = Reply::plan(&long);
+        assert_eq!(reply.messages, [paragraph.trim()]);
+        assert_eq!(reply.file_for(0), Some(long.as_str()));
+        assert_eq!(reply.file_for(1), None);
+    }
+
+    #[test]
+    fn file_replies_are_sent_as_multipart() {
+        let client = reqwest::Client::new();
+        let plain = with_body(
+            client.post("http://localhost/"),
+            json!({ "content": "hi" }),
+            None,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        assert_eq!(plain.headers()["content-type"], "application/json");
+
+        let upload = with_body(
+            client.post("http://localhost/"),
+            json!({ "content": "hi" }),
+            Some("full text"),
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        let content_type = upload.headers()["content-type"].to_str().unwrap();
+        assert!(content_type.starts_with("multipart/form-data"));
+    }
+
+    #[test]
+    fn attachment_names_and_sizes() {
+        assert_eq!(safe_file_name("report 2026.pdf"), "report_2026.pdf");
+        assert_eq!(safe_file_name("../../etc/passwd"), "_.._etc_passwd");
+        assert_eq!(safe_file_name("..."), "attachment");
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1500), "2 KB");
+        assert_eq!(format_size(MAX_ATTACHMENT_BYTES), "25.0 MB");
+    }
+
+    #[tokio::test]
+    async fn attachments_are_saved_for_the_agent() {
+        let app = axum::Router::new().route(
+            "/files/notes.txt",
+            axum::routing::get(|| async { "meeting notes" }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let attachment = |id: &str, path: &str, size: u64| {
+            json!({
+                "id": id,
+                "filename": "notes.txt",
+                "url": format!("http://{addr}{path}"),
+                "size": size,
+                "content_type": "text/plain"
+            })
+        };
+        let mut d = dm("");
+        d["attachments"] = json!([
+            attachment("a1", "/files/notes.txt", 13),
+            attachment("a2", "/files/missing.txt", 13),
+            attachment("a3", "/files/notes.txt", MAX_ATTACHMENT_BYTES + 1),
+        ]);
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let ch = DiscordChannel::from_config(&config(false, false)).with_workspace(tmp.path());
+        let msg = ch.on_message(&d, "123456").await.unwrap();
+        let lines: Vec<&str> = msg.content.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "[Attachment: notes.txt (text/plain, 13 B) saved to inbox/discord/a1/notes.txt]",
+                "[Attachment: notes.txt (text/plain, 13 B) could not be downloaded]",
+                "[Attachment: notes.txt (text/plain, 25.0 MB) not downloaded: larger than 25.0 MB]",
+            ]
+        );
+        let saved = tmp.path().join("inbox/discord/a1/notes.txt");
+        assert_eq!(std::fs::read_to_string(saved).unwrap(), "meeting notes");
+
+        // Without a workspace the agent gets the link
+        d["content"] = json!("see this");
+        d["attachments"] = json!([attachment("a4", "/files/notes.txt", 13)]);
+        let ch = DiscordChannel::from_config(&config(false, false));
+        let msg = ch.on_message(&d, "123456").await.unwrap();
+        assert_eq!(
+            msg.content,
+            format!(
+                "see this\n[Attachment: notes.txt (text/plain, 13 B) at http://{addr}/files/notes.txt]"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn replies_to_unknown_interactions_fail() {
+        let ch = DiscordChannel::new("fake".into(), None, vec![]);
+        let err = ch.send("hi", "interaction:missing").await.unwrap_err();
+        assert!(err.to_string().contains("/jarvis"));
+    }
 }
@@ -1,3 +1,4 @@
+use super::allowlist::RejectionSink;
 use crate::channels::traits::{Channel, ChannelMessage};
 use async_trait::async_trait;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -27,6 +28,8 @@ pub struct IrcChannel {
     username: String,
     channels: Vec<String>,
     allowed_users: Vec<String>,
+    /// Where senders the allowlist turns away are reported
+    rejections: Option<RejectionSink>,
     server_password: Option<String>,
     nickserv_password: Option<String>,
     sasl_password: Option<String>,
@@ -242,6 +245,7 @@ impl IrcChannel {
             username,
             channels,
             allowed_users,
+            rejections: None,
             server_password,
             nickserv_password,
             sasl_password,
@@ -250,6 +254,12 @@ impl IrcChannel {
         }
     }
 
+    /// Report senders the allowlist turns away to `sink`.
+    pub fn with_rejections(mut self, sink: Option<RejectionSink>) -> Self {
+        self.rejections = sink;
+        self
+    }
+
     fn is_user_allowed(&self, nick: &str) -> bool {
         if self.allowed_users.iter().any(|u| u == "*") {
             return true;
@@ -526,6 +536,9 @@ impl Channel for IrcChannel {
                     }
 
                     if !self.is_user_allowed(sender_nick) {
+                        if let Some(sink) = &self.rejections {
+                            sink.report("irc", sender_nick);
+                        }
                         continue;
                     }
 
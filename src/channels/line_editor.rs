@@ -0,0 +1,510 @@
+//! Readline-style line editing for the interactive CLI channel.
+//!
+//! Supports cursor movement, Up/Down history (persisted between runs),
+//! Ctrl+R reverse search, Ctrl+C to drop the current line, Ctrl+D to quit and
+//! continuation lines ending in `\`. Only keyboard input is switched to raw
+//! mode; output processing stays on so the agent's replies print normally
+//! while the editor waits for the next line.
+
+use crossterm::cursor::MoveToColumn;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::QueueableCommand;
+use std::io::{self, Write};
+use std::path::Path;
+use unicode_width::UnicodeWidthStr;
+
+use crate::tui::history::InputHistory;
+
+const PROMPT: &str = "> ";
+const CONTINUATION_PROMPT: &str = ". ";
+
+/// Result of reading one line.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReadOutcome {
+    /// A submitted line; continuation lines are joined with `\n`.
+    Line(String),
+    /// Ctrl+C: the current line was discarded.
+    Interrupted,
+    /// Ctrl+D on an empty line.
+    Eof,
+}
+
+/// Active Ctrl+R search.
+#[derive(Debug)]
+struct Search {
+    query: String,
+    /// History index of the current match
+    found: Option<usize>,
+    /// Line to restore if the search is cancelled
+    saved_line: String,
+}
+
+/// In-progress input, independent of the terminal so key handling is testable.
+#[derive(Debug, Default)]
+struct EditState {
+    /// Lines already ended with `\`
+    committed: Vec<String>,
+    line: String,
+    /// Byte offset into `line`
+    cursor: usize,
+    search: Option<Search>,
+}
+
+enum Step {
+    Continue,
+    Done(ReadOutcome),
+}
+
+impl EditState {
+    fn set_line(&mut self, line: String) {
+        self.cursor = line.len();
+        self.line = line;
+    }
+
+    fn prev_boundary(&self) -> usize {
+        self.line[..self.cursor]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.line[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+
+    /// Start of the word before the cursor (Ctrl+W).
+    fn word_start(&self) -> usize {
+        let before = self.line[..self.cursor].trim_end();
+        before
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8())
+    }
+}
+
+pub struct LineEditor {
+    history: InputHistory,
+}
+
+impl LineEditor {
+    /// Editor whose history is loaded from and saved to `history_path`.
+    pub fn new(history_path: Option<&Path>) -> Self {
+        Self {
+            history: history_path.map(InputHistory::load).unwrap_or_default(),
+        }
+    }
+
+    /// Read one line from the terminal. The prompt is drawn on the first
+    /// keypress, so replies printed while waiting don't land after a stale prompt.
+    pub fn read_line(&mut self) -> io::Result<ReadOutcome> {
+        let _mode = input_mode::enable()?;
+        let mut state = EditState::default();
+        loop {
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            let Step::Done(outcome) = self.handle_key(&mut state, key) else {
+                render(&state, &self.history)?;
+                continue;
+            };
+            if outcome == ReadOutcome::Interrupted {
+                print!("^C");
+            }
+            println!();
+            return Ok(outcome);
+        }
+    }
+
+    fn handle_key(&mut self, state: &mut EditState, key: KeyEvent) -> Step {
+        if state.search.is_some() {
+            return self.handle_search_key(state, key);
+        }
+
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter => return self.enter(state),
+            KeyCode::Char('c') if ctrl => {
+                self.history.reset();
+                return Step::Done(ReadOutcome::Interrupted);
+            }
+            KeyCode::Char('d') if ctrl => {
+                if state.line.is_empty() && state.committed.is_empty() {
+                    return Step::Done(ReadOutcome::Eof);
+                }
+                let end = state.next_boundary();
+                state.line.replace_range(state.cursor..end, "");
+            }
+            KeyCode::Char('r') if ctrl => {
+                state.search = Some(Search {
+                    query: String::new(),
+                    found: None,
+                    saved_line: state.line.clone(),
+                });
+            }
+            KeyCode::Char('a') if ctrl => state.cursor = 0,
+            KeyCode::Char('e') if ctrl => state.cursor = state.line.len(),
+            KeyCode::Char('u') if ctrl => {
+                state.line.replace_range(..state.cursor, "");
+                state.cursor = 0;
+            }
+            KeyCode::Char('k') if ctrl => state.line.truncate(state.cursor),
+            KeyCode::Char('w') if ctrl => {
+                let start = state.word_start();
+                state.line.replace_range(start..state.cursor, "");
+                state.cursor = start;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                state.line.insert(state.cursor, c);
+                state.cursor += c.len_utf8();
+            }
+            KeyCode::Backspace => {
+                let start = state.prev_boundary();
+                state.line.replace_range(start..state.cursor, "");
+                state.cursor = start;
+            }
+            KeyCode::Delete => {
+                let end = state.next_boundary();
+                state.line.replace_range(state.cursor..end, "");
+            }
+            KeyCode::Left => state.cursor = state.prev_boundary(),
+            KeyCode::Right => state.cursor = state.next_boundary(),
+            KeyCode::Home => state.cursor = 0,
+            KeyCode::End => state.cursor = state.line.len(),
+            KeyCode::Up => {
+                if let Some(entry) = self.history.older(&state.line) {
+                    state.set_line(entry);
+                }
+            }
+            KeyCode::Down => {
+                if let Some(entry) = self.history.newer() {
+                    state.set_line(entry);
+                }
+            }
+            _ => {}
+        }
+        Step::Continue
+    }
+
+    fn handle_search_key(&mut self, state: &mut EditState, key: KeyEvent) -> Step {
+        let Some(search) = state.search.as_mut() else {
+            return Step::Continue;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let cancel =
+            key.code == KeyCode::Esc || (ctrl && matches!(key.code, KeyCode::Char('g' | 'c')));
+        if cancel {
+            let saved = std::mem::take(&mut search.saved_line);
+            state.search = None;
+            state.set_line(saved);
+            return Step::Continue;
+        }
+
+        match key.code {
+            KeyCode::Char('r') if ctrl => {
+                let before = search.found.unwrap_or(self.history.entries().len());
+                if let Some(found) = self.history.search(&search.query, before) {
+                    search.found = Some(found);
+                }
+                return Step::Continue;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                search.query.push(c);
+                search.found = self
+                    .history
+                    .search(&search.query, self.history.entries().len());
+                return Step::Continue;
+            }
+            KeyCode::Backspace => {
+                search.query.pop();
+                search.found = self
+                    .history
+                    .search(&search.query, self.history.entries().len());
+                return Step::Continue;
+            }
+            _ => {}
+        }
+
+        // Any other key accepts the match into the line; Enter also submits it
+        let accepted = search
+            .found
+            .and_then(|i| self.history.entries().get(i).cloned())
+            .unwrap_or_else(|| std::mem::take(&mut search.saved_line));
+        state.search = None;
+        state.set_line(accepted);
+        if key.code == KeyCode::Enter {
+            self.enter(state)
+        } else {
+            Step::Continue
+        }
+    }
+
+    fn enter(&mut self, state: &mut EditState) -> Step {
+        if let Some(continued) = state.line.strip_suffix('\\') {
+            state.committed.push(continued.to_string());
+            state.set_line(String::new());
+            println!();
+            return Step::Continue;
+        }
+
+        let mut lines = std::mem::take(&mut state.committed);
+        lines.push(std::mem::take(&mut state.line));
+        let text = lines.join("\n");
+        self.history.push(&text);
+        Step::Done(ReadOutcome::Line(text))
+    }
+}
+
+/// Redraw the current row: prompt and line, or the search status.
+fn render(state: &EditState, history: &InputHistory) -> io::Result<()> {
+    let prompt = if state.committed.is_empty() {
+        PROMPT
+    } else {
+        CONTINUATION_PROMPT
+    };
+    let (text, cursor_col) = if let Some(search) = &state.search {
+        let found = search
+            .found
+            .and_then(|i| history.entries().get(i))
+            .map_or("", String::as_str);
+        let text = format!("(reverse-i-search)`{}': {found}", search.query);
+        let col = text.width();
+        (text, col)
+    } else {
+        let text = format!("{prompt}{}", state.line);
+        let col = prompt.width() + state.line[..state.cursor].width();
+        (text, col)
+    };
+
+    let mut out = io::stdout();
+    out.queue(MoveToColumn(0))?;
+    out.queue(Clear(ClearType::UntilNewLine))?;
+    write!(out, "{text}")?;
+    out.queue(MoveToColumn(u16::try_from(cursor_col).unwrap_or(u16::MAX)))?;
+    out.flush()
+}
+
+/// Raw keyboard input with output processing left on, restored on drop.
+#[cfg(unix)]
+mod input_mode {
+    use std::io;
+    use std::sync::OnceLock;
+
+    /// Terminal settings before the first `enable`, restored at process exit
+    /// too in case the agent exits while a read is pending.
+    static ORIGINAL: OnceLock<libc::termios> = OnceLock::new();
+
+    pub struct Guard(libc::termios);
+
+    extern "C" fn restore_at_exit() {
+        if let Some(original) = ORIGINAL.get() {
+            set(original);
+        }
+    }
+
+    fn set(termios: &libc::termios) -> bool {
+        // SAFETY: `termios` is a valid, initialized struct obtained from tcgetattr.
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, termios) == 0 }
+    }
+
+    pub fn enable() -> io::Result<Guard> {
+        // SAFETY: tcgetattr fully initializes the zeroed struct on success;
+        // the result is only used when it returns 0.
+        let mut original: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &raw mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if ORIGINAL.set(original).is_ok() {
+            // SAFETY: registering a plain `extern "C" fn()` with atexit.
+            unsafe { libc::atexit(restore_at_exit) };
+        }
+
+        let mut raw = original;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+        raw.c_iflag &= !(libc::IXON | libc::ICRNL);
+        raw.c_cc[libc::VMIN] = 1;
+        raw.c_cc[libc::VTIME] = 0;
+        if !set(&raw) {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Guard(original))
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            set(&self.0);
+        }
+    }
+}
+
+/// Other platforms don't translate `\n` in raw mode, so crossterm's raw mode is fine.
+#[cfg(not(unix))]
+mod input_mode {
+    use std::io;
+
+    pub struct Guard;
+
+    pub fn enable() -> io::Result<Guard> {
+        crossterm::terminal::enable_raw_mode()?;
+        Ok(Guard)
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            let _ = crossterm::terminal::disable_raw_mode();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn editor(entries: &[&str]) -> LineEditor {
+        let mut editor = LineEditor::new(None);
+        for entry in entries {
+            editor.history.push(entry);
+        }
+        editor
+    }
+
+    /// Feed keys until the editor finishes a line.
+    fn run(editor: &mut LineEditor, keys: &[KeyEvent]) -> Option<ReadOutcome> {
+        let mut state = EditState::default();
+        for &k in keys {
+            if let Step::Done(outcome) = editor.handle_key(&mut state, k) {
+                return Some(outcome);
+            }
+        }
+        None
+    }
+
+    fn typed(text: &str) -> Vec<KeyEvent> {
+        text.chars().map(|c| key(KeyCode::Char(c))).collect()
+    }
+
+    #[test]
+    fn edits_in_the_middle_of_a_line() {
+        let mut ed = editor(&[]);
+        let mut keys = typed("helo");
+        keys.extend([
+            key(KeyCode::Left),
+            key(KeyCode::Char('l')),
+            key(KeyCode::End),
+        ]);
+        keys.extend(typed(" wörld"));
+        keys.extend([key(KeyCode::Backspace), key(KeyCode::Enter)]);
+        assert_eq!(
+            run(&mut ed, &keys),
+            Some(ReadOutcome::Line("hello wörl".into()))
+        );
+        assert_eq!(ed.history.entries(), ["hello wörl"]);
+    }
+
+    #[test]
+    fn kill_commands() {
+        let mut ed = editor(&[]);
+        let mut keys = typed("git commit -m msg");
+        keys.extend([ctrl('w'), ctrl('w'), key(KeyCode::Enter)]);
+        assert_eq!(
+            run(&mut ed, &keys),
+            Some(ReadOutcome::Line("git commit ".into()))
+        );
+
+        let mut keys = typed("abc def");
+        keys.extend([ctrl('a'), ctrl('k'), key(KeyCode::Enter)]);
+        assert_eq!(run(&mut ed, &keys), Some(ReadOutcome::Line(String::new())));
+    }
+
+    #[test]
+    fn up_down_recall_history() {
+        let mut ed = editor(&["first", "second"]);
+        let keys = [
+            key(KeyCode::Up),
+            key(KeyCode::Up),
+            key(KeyCode::Down),
+            key(KeyCode::Enter),
+        ];
+        assert_eq!(
+            run(&mut ed, &keys),
+            Some(ReadOutcome::Line("second".into()))
+        );
+    }
+
+    #[test]
+    fn ctrl_c_discards_line_and_ctrl_d_quits_when_empty() {
+        let mut ed = editor(&[]);
+        let mut keys = typed("half typed");
+        keys.push(ctrl('c'));
+        assert_eq!(run(&mut ed, &keys), Some(ReadOutcome::Interrupted));
+        assert!(ed.history.entries().is_empty());
+
+        // Ctrl+D deletes forward on a non-empty line instead of quitting
+        let mut keys = typed("ab");
+        keys.extend([key(KeyCode::Home), ctrl('d'), key(KeyCode::Enter)]);
+        assert_eq!(run(&mut ed, &keys), Some(ReadOutcome::Line("b".into())));
+
+        assert_eq!(run(&mut ed, &[ctrl('d')]), Some(ReadOutcome::Eof));
+    }
+
+    #[test]
+    fn backslash_continues_onto_next_line() {
+        let mut ed = editor(&[]);
+        let mut keys = typed("first \\");
+        keys.push(key(KeyCode::Enter));
+        keys.extend(typed("second"));
+        keys.push(key(KeyCode::Enter));
+        assert_eq!(
+            run(&mut ed, &keys),
+            Some(ReadOutcome::Line("first \nsecond".into()))
+        );
+    }
+
+    #[test]
+    fn reverse_search_finds_and_accepts_matches() {
+        let mut ed = editor(&["cargo build", "ls", "cargo test"]);
+        let mut keys = vec![ctrl('r')];
+        keys.extend(typed("cargo"));
+        keys.extend([ctrl('r'), key(KeyCode::Enter)]);
+        assert_eq!(
+            run(&mut ed, &keys),
+            Some(ReadOutcome::Line("cargo build".into()))
+        );
+
+        // Accept without submitting, then keep editing
+        let mut keys = vec![ctrl('r')];
+        keys.extend(typed("ls"));
+        keys.push(key(KeyCode::End));
+        keys.extend(typed(" -la"));
+        keys.push(key(KeyCode::Enter));
+        assert_eq!(
+            run(&mut ed, &keys),
+            Some(ReadOutcome::Line("ls -la".into()))
+        );
+    }
+
+    #[test]
+    fn cancelled_search_restores_line() {
+        let mut ed = editor(&["cargo test"]);
+        let mut keys = typed("draft");
+        keys.push(ctrl('r'));
+        keys.extend(typed("cargo"));
+        keys.extend([key(KeyCode::Esc), key(KeyCode::Enter)]);
+        assert_eq!(run(&mut ed, &keys), Some(ReadOutcome::Line("draft".into())));
+    }
+}
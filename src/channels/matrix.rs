@@ -1,9 +1,16 @@
+use super::allowlist::RejectionSink;
 use crate::channels::traits::{Channel, ChannelMessage};
+use crate::util::truncate_with_ellipsis;
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
+/// Health component updated on every successful sync.
+const COMPONENT: &str = "channel:matrix";
+
 /// Matrix channel using the Client-Server API (no SDK needed).
 /// Connects to any Matrix homeserver (Element, Synapse, etc.).
 #[derive(Clone)]
@@ -12,9 +19,23 @@ pub struct MatrixChannel {
     access_token: String,
     room_id: String,
     allowed_users: Vec<String>,
+    /// Where senders the allowlist turns away are reported
+    rejections: Option<RejectionSink>,
+    /// First pause after a failed sync; doubles up to `max_backoff`
+    initial_backoff: Duration,
+    max_backoff: Duration,
     client: Client,
 }
 
+/// Why a request to the homeserver failed.
+#[derive(Debug)]
+enum SyncError {
+    /// The access token was rejected (expired, revoked or logged out)
+    Unauthorized(String),
+    /// Worth retrying: network errors, 5xx, rate limits, odd responses
+    Transient(anyhow::Error),
+}
+
 #[derive(Debug, Deserialize)]
 struct SyncResponse {
     next_batch: String,
@@ -44,6 +65,8 @@ struct Timeline {
 struct TimelineEvent {
     #[serde(rename = "type")]
     event_type: String,
+    #[serde(default)]
+    event_id: String,
     sender: String,
     #[serde(default)]
     content: EventContent,
@@ -79,10 +102,26 @@ impl MatrixChannel {
             access_token,
             room_id,
             allowed_users,
+            rejections: None,
+            initial_backoff: Duration::from_secs(super::DEFAULT_CHANNEL_INITIAL_BACKOFF_SECS),
+            max_backoff: Duration::from_secs(super::DEFAULT_CHANNEL_MAX_BACKOFF_SECS),
             client: Client::new(),
         }
     }
 
+    /// Pause between sync retries: `initial`, doubling up to `max`.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Report senders the allowlist turns away to `sink`.
+    pub fn with_rejections(mut self, sink: Option<RejectionSink>) -> Self {
+        self.rejections = sink;
+        self
+    }
+
     fn is_user_allowed(&self, sender: &str) -> bool {
         if self.allowed_users.iter().any(|u| u == "*") {
             return true;
@@ -92,22 +131,121 @@ impl MatrixChannel {
             .any(|u| u.eq_ignore_ascii_case(sender))
     }
 
-    async fn get_my_user_id(&self) -> anyhow::Result<String> {
-        let url = format!("{}/_matrix/client/v3/account/whoami", self.homeserver);
+    /// GET a client-server API endpoint as JSON.
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, SyncError> {
         let resp = self
             .client
-            .get(&url)
+            .get(url)
             .header("Authorization", format!("Bearer {}", self.access_token))
             .send()
-            .await?;
+            .await
+            .map_err(|e| SyncError::Transient(e.into()))?;
+
+        let status = resp.status();
+        if status == StatusCode::UNAUTHORIZED {
+            let body: serde_json::Value = resp.json().await.unwrap_or_default();
+            let errcode = body
+                .get("errcode")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("M_UNKNOWN_TOKEN");
+            return Err(SyncError::Unauthorized(errcode.to_string()));
+        }
+        if !status.is_success() {
+            let err = resp.text().await.unwrap_or_default();
+            return Err(SyncError::Transient(anyhow::anyhow!(
+                "HTTP {status}: {}",
+                truncate_with_ellipsis(&err, 200)
+            )));
+        }
+        resp.json()
+            .await
+            .map_err(|e| SyncError::Transient(anyhow::anyhow!("响应无法解析: {e}")))
+    }
 
-        if !resp.status().is_success() {
-            let err = resp.text().await?;
-            anyhow::bail!("Matrix whoami 失败: {err}");
+    /// One step of the sync loop: look up our user id if still unknown, then
+    /// sync once and forward new messages. The first sync only fetches the
+    /// `since` token, so history isn't replayed. Returns `false` once the
+    /// receiver is gone.
+    async fn sync_once(
+        &self,
+        my_user_id: &mut Option<String>,
+        since: &mut Option<String>,
+        tx: &mpsc::Sender<ChannelMessage>,
+    ) -> Result<bool, SyncError> {
+        if my_user_id.is_none() {
+            let url = format!("{}/_matrix/client/v3/account/whoami", self.homeserver);
+            let who: WhoAmIResponse = self.get_json(&url).await?;
+            *my_user_id = Some(who.user_id);
+        }
+
+        let url = match since {
+            None => format!(
+                "{}/_matrix/client/v3/sync?timeout=30000&filter={{\"room\":{{\"timeline\":{{\"limit\":1}}}}}}",
+                self.homeserver
+            ),
+            Some(since) => format!(
+                "{}/_matrix/client/v3/sync?since={since}&timeout=30000",
+                self.homeserver
+            ),
+        };
+        let sync: SyncResponse = self.get_json(&url).await?;
+        let initial = since.replace(sync.next_batch.clone()).is_none();
+        if initial {
+            return Ok(true);
+        }
+
+        for msg in self.messages(&sync, my_user_id.as_deref().unwrap_or_default()) {
+            if tx.send(msg).await.is_err() {
+                return Ok(false);
+            }
         }
+        Ok(true)
+    }
 
-        let who: WhoAmIResponse = resp.json().await?;
-        Ok(who.user_id)
+    /// Text messages from allowed users in our room.
+    fn messages(&self, sync: &SyncResponse, my_user_id: &str) -> Vec<ChannelMessage> {
+        let Some(room) = sync.rooms.join.get(&self.room_id) else {
+            return Vec::new();
+        };
+        let mut messages = Vec::new();
+        for event in &room.timeline.events {
+            // Skip our own messages
+            if event.sender == my_user_id {
+                continue;
+            }
+
+            // Only process text messages
+            if event.event_type != "m.room.message" {
+                continue;
+            }
+
+            if event.content.msgtype.as_deref() != Some("m.text") {
+                continue;
+            }
+
+            let Some(ref body) = event.content.body else {
+                continue;
+            };
+
+            if !self.is_user_allowed(&event.sender) {
+                if let Some(sink) = &self.rejections {
+                    sink.report("matrix", &event.sender);
+                }
+                continue;
+            }
+
+            messages.push(ChannelMessage {
+                id: event.event_id.clone(),
+                sender: event.sender.clone(),
+                content: body.clone(),
+                channel: "matrix".to_string(),
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+            });
+        }
+        messages
     }
 }
 
@@ -145,102 +283,41 @@ impl Channel for MatrixChannel {
         Ok(())
     }
 
+    /// Sync until the receiver is gone. Transient failures (network errors,
+    /// 5xx, rate limits) are retried in place with backoff, keeping the other
+    /// channels untouched; a rejected access token stops syncing, since
+    /// retrying with it can't succeed.
     async fn listen(&self, tx: mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
         tracing::info!("Matrix 通道正在监听房间 {}...", self.room_id);
 
-        let my_user_id = self.get_my_user_id().await?;
-
-        // Initial sync to get the since token
-        let url = format!(
-            "{}/_matrix/client/v3/sync?timeout=30000&filter={{\"room\":{{\"timeline\":{{\"limit\":1}}}}}}",
-            self.homeserver
-        );
-
-        let resp = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.access_token))
-            .send()
-            .await?;
-
-        if !resp.status().is_success() {
-            let err = resp.text().await?;
-            anyhow::bail!("Matrix 初始同步失败: {err}");
-        }
-
-        let sync: SyncResponse = resp.json().await?;
-        let mut since = sync.next_batch;
-
-        // Long-poll loop
+        let mut my_user_id = None;
+        let mut since = None;
+        let mut backoff = self.initial_backoff;
         loop {
-            let url = format!(
-                "{}/_matrix/client/v3/sync?since={}&timeout=30000",
-                self.homeserver, since
-            );
-
-            let resp = self
-                .client
-                .get(&url)
-                .header("Authorization", format!("Bearer {}", self.access_token))
-                .send()
-                .await;
-
-            let resp = match resp {
-                Ok(r) => r,
-                Err(e) => {
-                    tracing::warn!("Matrix 同步出错: {e}，正在重试...");
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                    continue;
+            match self.sync_once(&mut my_user_id, &mut since, &tx).await {
+                Ok(true) => {
+                    crate::health::mark_component_ok(COMPONENT);
+                    backoff = self.initial_backoff;
                 }
-            };
-
-            if !resp.status().is_success() {
-                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                continue;
-            }
-
-            let sync: SyncResponse = resp.json().await?;
-            since = sync.next_batch;
-
-            // Process events from our room
-            if let Some(room) = sync.rooms.join.get(&self.room_id) {
-                for event in &room.timeline.events {
-                    // Skip our own messages
-                    if event.sender == my_user_id {
-                        continue;
-                    }
-
-                    // Only process text messages
-                    if event.event_type != "m.room.message" {
-                        continue;
-                    }
-
-                    if event.content.msgtype.as_deref() != Some("m.text") {
-                        continue;
-                    }
-
-                    let Some(ref body) = event.content.body else {
-                        continue;
-                    };
-
-                    if !self.is_user_allowed(&event.sender) {
-                        continue;
-                    }
-
-                    let msg = ChannelMessage {
-                        id: format!("mx_{}", chrono::Utc::now().timestamp_millis()),
-                        sender: event.sender.clone(),
-                        content: body.clone(),
-                        channel: "matrix".to_string(),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_secs(),
-                    };
-
-                    if tx.send(msg).await.is_err() {
-                        return Ok(());
+                Ok(false) => return Ok(()),
+                Err(SyncError::Unauthorized(errcode)) => {
+                    let message = format!(
+                        "Matrix access token 无效或已过期（{errcode}），\
+                         请运行 `jarvis onboard --channels-only` 重新配置 Matrix 后重启通道"
+                    );
+                    tracing::error!("{message}");
+                    crate::health::mark_component_error(COMPONENT, &message);
+                    tx.closed().await;
+                    return Ok(());
+                }
+                Err(SyncError::Transient(e)) => {
+                    tracing::warn!("Matrix 同步出错: {e}，{}毫秒后重试", backoff.as_millis());
+                    crate::health::mark_component_degraded(COMPONENT, format!("同步重试中: {e}"));
+                    tokio::select! {
+                        () = tokio::time::sleep(backoff) => {}
+                        () = tx.closed() => return Ok(()),
                     }
+                    backoff = (backoff * 2).min(self.max_backoff);
                 }
             }
         }
@@ -265,6 +342,8 @@ impl Channel for MatrixChannel {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
 
     fn make_channel() -> MatrixChannel {
         MatrixChannel::new(
@@ -464,4 +543,117 @@ mod tests {
         let resp: SyncResponse = serde_json::from_str(json).unwrap();
         assert!(resp.rooms.join.is_empty());
     }
+
+    /// A homeserver whose whoami answers `whoami_status` and whose sync
+    /// fails with a 502 on the second call, then delivers one message.
+    async fn mock_homeserver(whoami_status: u16) -> (String, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        use axum::http::StatusCode as HttpStatus;
+        use axum::routing::get;
+        use axum::{Json, Router};
+        use serde_json::json;
+
+        let whoami_calls = Arc::new(AtomicUsize::new(0));
+        let sync_calls = Arc::new(AtomicUsize::new(0));
+        let whoami = {
+            let calls = whoami_calls.clone();
+            move || async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                let status = HttpStatus::from_u16(whoami_status).unwrap();
+                if status.is_success() {
+                    (status, Json(json!({ "user_id": "@jarvis:m.org" })))
+                } else {
+                    let error = json!({ "errcode": "M_UNKNOWN_TOKEN", "error": "Invalid token" });
+                    (status, Json(error))
+                }
+            }
+        };
+        let sync = {
+            let calls = sync_calls.clone();
+            move || async move {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 => (HttpStatus::OK, Json(json!({ "next_batch": "s1" }))),
+                    1 => (HttpStatus::BAD_GATEWAY, Json(json!({}))),
+                    _ => (
+                        HttpStatus::OK,
+                        Json(json!({
+                            "next_batch": "s2",
+                            "rooms": { "join": { "!r:m.org": { "timeline": { "events": [
+                                { "type": "m.room.message", "sender": "@jarvis:m.org",
+                                  "content": { "msgtype": "m.text", "body": "echo" } },
+                                { "type": "m.room.message", "sender": "@alice:m.org",
+                                  "content": { "msgtype": "m.text", "body": "hello" } }
+                            ] } } } }
+                        })),
+                    ),
+                }
+            }
+        };
+        let app = Router::new()
+            .route("/_matrix/client/v3/account/whoami", get(whoami))
+            .route("/_matrix/client/v3/sync", get(sync));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{addr}"), whoami_calls, sync_calls)
+    }
+
+    fn mock_channel(homeserver: String) -> Arc<MatrixChannel> {
+        Arc::new(
+            MatrixChannel::new(
+                homeserver,
+                "tok".into(),
+                "!r:m.org".into(),
+                vec!["@alice:m.org".into()],
+            )
+            .with_backoff(Duration::from_millis(10), Duration::from_millis(40)),
+        )
+    }
+
+    #[tokio::test]
+    async fn sync_recovers_in_place_after_a_failure() {
+        let (homeserver, _, sync_calls) = mock_homeserver(200).await;
+        let ch = mock_channel(homeserver);
+        let (tx, mut rx) = mpsc::channel(4);
+        let listener = tokio::spawn({
+            let ch = ch.clone();
+            async move { ch.listen(tx).await }
+        });
+
+        let msg = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (msg.sender.as_str(), msg.content.as_str()),
+            ("@alice:m.org", "hello")
+        );
+        assert!(sync_calls.load(Ordering::SeqCst) >= 3);
+        assert!(!listener.is_finished());
+        let health = crate::health::snapshot();
+        assert!(health.components[COMPONENT].last_ok.is_some());
+
+        drop(rx);
+        listener.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn expired_token_stops_syncing() {
+        let (homeserver, whoami_calls, sync_calls) = mock_homeserver(401).await;
+        let ch = mock_channel(homeserver);
+        let (tx, rx) = mpsc::channel(4);
+        let listener = tokio::spawn({
+            let ch = ch.clone();
+            async move { ch.listen(tx).await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(whoami_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(sync_calls.load(Ordering::SeqCst), 0);
+        assert!(!listener.is_finished());
+
+        drop(rx);
+        listener.await.unwrap().unwrap();
+    }
 }
@@ -1,15 +1,23 @@
+pub mod allowlist;
+pub mod chunk;
 pub mod cli;
+pub mod conversations;
+pub mod dedup;
 pub mod discord;
 pub mod email_channel;
 pub mod imessage;
 pub mod irc;
+pub mod line_editor;
 pub mod matrix;
+pub mod probe;
 pub mod slack;
 pub mod telegram;
+pub mod test_send;
 pub mod traits;
 pub mod whatsapp;
 
 pub use cli::CliChannel;
+pub use conversations::ConversationStore;
 pub use discord::DiscordChannel;
 pub use imessage::IMessageChannel;
 pub use irc::IrcChannel;
@@ -33,6 +41,12 @@ const BOOTSTRAP_MAX_CHARS: usize = 20_000;
 const DEFAULT_CHANNEL_INITIAL_BACKOFF_SECS: u64 = 2;
 const DEFAULT_CHANNEL_MAX_BACKOFF_SECS: u64 = 60;
 
+/// How often the daemon writes conversation histories to disk.
+const CONVERSATION_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the typing indicator is refreshed (Telegram clears it after 5s).
+const TYPING_REFRESH: Duration = Duration::from_secs(4);
+
 fn spawn_supervised_listener(
     ch: Arc<dyn Channel>,
     tx: tokio::sync::mpsc::Sender<traits::ChannelMessage>,
@@ -73,6 +87,19 @@ fn spawn_supervised_listener(
     })
 }
 
+/// Keep a typing indicator up in `recipient`'s chat until the task is aborted.
+fn spawn_typing_indicator(ch: Arc<dyn Channel>, recipient: String) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = ch.start_typing(&recipient).await {
+                tracing::debug!("通道 {} 发送输入状态失败: {e}", ch.name());
+                return;
+            }
+            tokio::time::sleep(TYPING_REFRESH).await;
+        }
+    })
+}
+
 /// Load workspace identity files and build a system prompt.
 ///
 /// Follows the `OpenClaw` framework structure:
@@ -227,6 +254,24 @@ fn inject_workspace_file(prompt: &mut String, workspace_dir: &std::path::Path, f
     }
 }
 
+/// Check an inbound message against the `max_inbound_chars` cap.
+///
+/// Returns the content unchanged when it fits, or a polite notice to send back
+/// to the sender when it is too long. A cap of `0` disables the check.
+pub fn check_inbound_size(content: &str, max_chars: usize) -> std::result::Result<&str, String> {
+    if max_chars == 0 {
+        return Ok(content);
+    }
+    let chars = content.chars().count();
+    if chars <= max_chars {
+        Ok(content)
+    } else {
+        Err(format!(
+            "⚠️ 消息过长（{chars} 个字符，上限 {max_chars}）。请精简后分段发送。"
+        ))
+    }
+}
+
 pub fn handle_command(command: crate::ChannelCommands, config: &Config) -> Result<()> {
     match command {
         crate::ChannelCommands::Start => {
@@ -235,6 +280,9 @@ pub fn handle_command(command: crate::ChannelCommands, config: &Config) -> Resul
         crate::ChannelCommands::Doctor => {
             anyhow::bail!("Doctor 必须在 main.rs 中处理（需要异步运行时）")
         }
+        crate::ChannelCommands::Test { .. } => {
+            anyhow::bail!("Test 必须在 main.rs 中处理（需要异步运行时）")
+        }
         crate::ChannelCommands::List => {
             println!("通道列表:");
             println!("  ✅ CLI（始终可用）");
@@ -252,6 +300,7 @@ pub fn handle_command(command: crate::ChannelCommands, config: &Config) -> Resul
             }
             println!("\n启动通道:       jarvis channel start");
             println!("检查健康状态:   jarvis channel doctor");
+            println!("发送测试消息:   jarvis channel test <通道>");
             println!("配置通道:       jarvis onboard");
             Ok(())
         }
@@ -264,6 +313,12 @@ pub fn handle_command(command: crate::ChannelCommands, config: &Config) -> Resul
         crate::ChannelCommands::Remove { name } => {
             anyhow::bail!("移除通道「{name}」— 请直接编辑 ~/.jarvis/config.toml");
         }
+        crate::ChannelCommands::Allow { channel, user } => {
+            allowlist::handle_change(config, &channel, &user, true)
+        }
+        crate::ChannelCommands::Deny { channel, user } => {
+            allowlist::handle_change(config, &channel, &user, false)
+        }
     }
 }
 
@@ -300,25 +355,11 @@ pub async fn doctor_channels(config: Config) -> Result<()> {
     }
 
     if let Some(ref dc) = config.channels_config.discord {
-        channels.push((
-            "Discord",
-            Arc::new(DiscordChannel::new(
-                dc.bot_token.clone(),
-                dc.guild_id.clone(),
-                dc.allowed_users.clone(),
-            )),
-        ));
+        channels.push(("Discord", Arc::new(DiscordChannel::from_config(dc))));
     }
 
     if let Some(ref sl) = config.channels_config.slack {
-        channels.push((
-            "Slack",
-            Arc::new(SlackChannel::new(
-                sl.bot_token.clone(),
-                sl.channel_id.clone(),
-                sl.allowed_users.clone(),
-            )),
-        ));
+        channels.push(("Slack", Arc::new(SlackChannel::from_config(sl))));
     }
 
     if let Some(ref im) = config.channels_config.imessage {
@@ -402,6 +443,15 @@ pub async fn doctor_channels(config: Config) -> Result<()> {
         }
     }
 
+    if let Some(ref dc) = config.channels_config.discord {
+        let discord = DiscordChannel::from_config(dc);
+        match tokio::time::timeout(Duration::from_secs(10), discord.check_commands()).await {
+            Ok(Ok(())) => println!("  ✅ Discord   /jarvis 命令已注册"),
+            Ok(Err(e)) => println!("  ⚠️  Discord   /jarvis 命令：{e}"),
+            Err(_) => println!("  ⏱️  Discord   检查 /jarvis 命令超时（>10秒）"),
+        }
+    }
+
     if config.channels_config.webhook.is_some() {
         println!("  ℹ️  Webhook   请通过 `jarvis gateway` 启动后 GET /health 检查");
     }
@@ -414,10 +464,13 @@ pub async fn doctor_channels(config: Config) -> Result<()> {
 /// Start all configured channels and route messages to the agent
 #[allow(clippy::too_many_lines)]
 pub async fn start_channels(config: Config) -> Result<()> {
+    let observer: Arc<dyn crate::observability::Observer> =
+        Arc::from(crate::observability::create_observer(&config.observability));
     let provider: Arc<dyn Provider> = Arc::from(providers::create_resilient_provider(
         config.default_provider.as_deref().unwrap_or("openrouter"),
         config.api_key.as_deref(),
         &config.reliability,
+        Arc::clone(&observer),
     )?);
 
     // Warm up the provider connection pool (TLS handshake, DNS, HTTP/2 setup)
@@ -453,7 +506,11 @@ pub async fn start_channels(config: Config) -> Result<()> {
         ),
         (
             "file_write",
-            "Write file contents. Use when: applying focused edits, scaffolding files, updating docs/code. Don't use when: side effects are unclear or file ownership is uncertain.",
+            "Write a whole file. Use when: creating files or replacing most of a file's content. Don't use when: changing part of an existing file (use file_edit), or side effects are unclear or file ownership is uncertain.",
+        ),
+        (
+            "file_edit",
+            "Replace exact text in an existing file (old_text/new_text pairs or diff hunks); each old_text must match once. Use when: changing part of an existing file; prefer it over file_write there. Don't use when: creating a new file.",
         ),
         (
             "memory_store",
@@ -467,6 +524,18 @@ pub async fn start_channels(config: Config) -> Result<()> {
             "memory_forget",
             "Delete a memory entry. Use when: memory is incorrect/stale or explicitly requested for removal. Don't use when: impact is uncertain.",
         ),
+        (
+            "task_add",
+            "Add a task to the user's task list, optionally with a due time. Use when: the user asks to be reminded or an open loop needs tracking. Don't use when: it is a fact to remember (use memory_store).",
+        ),
+        (
+            "task_list",
+            "List open tasks with ids and due times. Use when: checking what is pending or overdue. Don't use when: the task list was just listed.",
+        ),
+        (
+            "task_complete",
+            "Mark a task done by id. Use when: a tracked task is finished. Don't use when: the task should be dropped without being done (ask the user).",
+        ),
     ];
 
     if config.browser.enabled {
@@ -489,30 +558,38 @@ pub async fn start_channels(config: Config) -> Result<()> {
         );
     }
 
+    let initial_backoff_secs = config
+        .reliability
+        .channel_initial_backoff_secs
+        .max(DEFAULT_CHANNEL_INITIAL_BACKOFF_SECS);
+    let max_backoff_secs = config
+        .reliability
+        .channel_max_backoff_secs
+        .max(DEFAULT_CHANNEL_MAX_BACKOFF_SECS);
+
     // Collect active channels
     let mut channels: Vec<Arc<dyn Channel>> = Vec::new();
+    let rejections = allowlist::spawn_reporter(&config);
 
     if let Some(ref tg) = config.channels_config.telegram {
-        channels.push(Arc::new(TelegramChannel::new(
-            tg.bot_token.clone(),
-            tg.allowed_users.clone(),
-        )));
+        channels.push(Arc::new(
+            TelegramChannel::new(tg.bot_token.clone(), tg.allowed_users.clone())
+                .with_rejections(rejections.clone()),
+        ));
     }
 
     if let Some(ref dc) = config.channels_config.discord {
-        channels.push(Arc::new(DiscordChannel::new(
-            dc.bot_token.clone(),
-            dc.guild_id.clone(),
-            dc.allowed_users.clone(),
-        )));
+        channels.push(Arc::new(
+            DiscordChannel::from_config(dc)
+                .with_workspace(&config.workspace_dir)
+                .with_rejections(rejections.clone()),
+        ));
     }
 
     if let Some(ref sl) = config.channels_config.slack {
-        channels.push(Arc::new(SlackChannel::new(
-            sl.bot_token.clone(),
-            sl.channel_id.clone(),
-            sl.allowed_users.clone(),
-        )));
+        channels.push(Arc::new(
+            SlackChannel::from_config(sl).with_rejections(rejections.clone()),
+        ));
     }
 
     if let Some(ref im) = config.channels_config.imessage {
@@ -520,12 +597,19 @@ pub async fn start_channels(config: Config) -> Result<()> {
     }
 
     if let Some(ref mx) = config.channels_config.matrix {
-        channels.push(Arc::new(MatrixChannel::new(
-            mx.homeserver.clone(),
-            mx.access_token.clone(),
-            mx.room_id.clone(),
-            mx.allowed_users.clone(),
-        )));
+        channels.push(Arc::new(
+            MatrixChannel::new(
+                mx.homeserver.clone(),
+                mx.access_token.clone(),
+                mx.room_id.clone(),
+                mx.allowed_users.clone(),
+            )
+            .with_backoff(
+                Duration::from_secs(initial_backoff_secs),
+                Duration::from_secs(max_backoff_secs),
+            )
+            .with_rejections(rejections.clone()),
+        ));
     }
 
     if let Some(ref wa) = config.channels_config.whatsapp {
@@ -538,18 +622,21 @@ pub async fn start_channels(config: Config) -> Result<()> {
     }
 
     if let Some(ref irc) = config.channels_config.irc {
-        channels.push(Arc::new(IrcChannel::new(
-            irc.server.clone(),
-            irc.port,
-            irc.nickname.clone(),
-            irc.username.clone(),
-            irc.channels.clone(),
-            irc.allowed_users.clone(),
-            irc.server_password.clone(),
-            irc.nickserv_password.clone(),
-            irc.sasl_password.clone(),
-            irc.verify_tls.unwrap_or(true),
-        )));
+        channels.push(Arc::new(
+            IrcChannel::new(
+                irc.server.clone(),
+                irc.port,
+                irc.nickname.clone(),
+                irc.username.clone(),
+                irc.channels.clone(),
+                irc.allowed_users.clone(),
+                irc.server_password.clone(),
+                irc.nickserv_password.clone(),
+                irc.sasl_password.clone(),
+                irc.verify_tls.unwrap_or(true),
+            )
+            .with_rejections(rejections),
+        ));
     }
 
     if channels.is_empty() {
@@ -582,17 +669,24 @@ pub async fn start_channels(config: Config) -> Result<()> {
 
     crate::health::mark_component_ok("channels");
 
-    let initial_backoff_secs = config
-        .reliability
-        .channel_initial_backoff_secs
-        .max(DEFAULT_CHANNEL_INITIAL_BACKOFF_SECS);
-    let max_backoff_secs = config
-        .reliability
-        .channel_max_backoff_secs
-        .max(DEFAULT_CHANNEL_MAX_BACKOFF_SECS);
+    let max_inbound_chars = config.channels_config.max_inbound_chars;
+    let mut histories = ConversationStore::load(
+        &config.workspace_dir,
+        Duration::from_secs(config.channels_config.conversation_idle_secs),
+        config.autonomy.max_history_turns,
+    );
+    let mut save_histories = tokio::time::interval(CONVERSATION_SAVE_INTERVAL);
 
-    // Single message bus — all channels send messages here
-    let (tx, mut rx) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(100);
+    // Single message bus — all channels send messages here, and only
+    // messages not delivered before go on to the agent
+    let (tx, inbound) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(100);
+    let (dispatch, mut rx) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(100);
+    let seen = dedup::SeenMessages::load(
+        &config.workspace_dir,
+        Duration::from_secs(config.channels_config.dedup_ttl_secs),
+        config.channels_config.dedup_max_entries,
+    );
+    let dedup = tokio::spawn(dedup::forward_first_deliveries(seen, inbound, dispatch));
 
     // Spawn a listener for each channel
     let mut handles = Vec::new();
@@ -607,7 +701,24 @@ pub async fn start_channels(config: Config) -> Result<()> {
     drop(tx); // Drop our copy so rx closes when all channels stop
 
     // Process incoming messages — call the LLM and reply
-    while let Some(msg) = rx.recv().await {
+    loop {
+        let msg = tokio::select! {
+            msg = rx.recv() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            _ = save_histories.tick() => {
+                histories.sweep();
+                if let Err(e) = histories.save() {
+                    tracing::warn!("保存通道会话历史失败: {e:#}");
+                }
+                continue;
+            }
+        };
+        observer.record_event(&crate::observability::ObserverEvent::ChannelMessage {
+            channel: msg.channel.clone(),
+            direction: "inbound".into(),
+        });
         println!(
             "  💬 [{}] 来自 {}: {}",
             msg.channel,
@@ -615,6 +726,28 @@ pub async fn start_channels(config: Config) -> Result<()> {
             truncate_with_ellipsis(&msg.content, 80)
         );
 
+        // Reject oversized messages before they reach memory or the LLM
+        if let Err(notice) = check_inbound_size(&msg.content, max_inbound_chars) {
+            tracing::warn!(
+                channel = %msg.channel,
+                sender = %msg.sender,
+                "入站消息超出长度上限，已拒绝"
+            );
+            if let Some(ch) = channels.iter().find(|ch| ch.name() == msg.channel) {
+                let _ = ch.send(&notice, &msg.sender).await;
+            }
+            continue;
+        }
+
+        let conversation = ConversationStore::key(&msg.channel, &msg.sender);
+        if conversations::is_reset_command(&msg.content) {
+            histories.reset(&conversation);
+            if let Some(ch) = channels.iter().find(|ch| ch.name() == msg.channel) {
+                let _ = ch.send(conversations::RESET_REPLY, &msg.sender).await;
+            }
+            continue;
+        }
+
         // Auto-save to memory
         if config.memory.auto_save {
             let _ = mem
@@ -626,18 +759,48 @@ pub async fn start_channels(config: Config) -> Result<()> {
                 .await;
         }
 
-        // Call the LLM with system prompt (identity + soul + tools)
-        match provider
-            .chat_with_system(Some(&system_prompt), &msg.content, &model, temperature)
-            .await
-        {
+        let transcript = crate::sessions::Transcript::resume(
+            &config.workspace_dir,
+            &msg.channel,
+            &format!("{}-{}", msg.channel, msg.sender),
+        );
+        transcript.user(&msg.content);
+
+        let typing = channels
+            .iter()
+            .find(|ch| ch.name() == msg.channel)
+            .map(|ch| spawn_typing_indicator(Arc::clone(ch), msg.sender.clone()));
+
+        // Call the LLM with system prompt (identity + soul + tools) and the
+        // conversation so far
+        let reply = conversations::respond(
+            provider.as_ref(),
+            &mut histories,
+            &conversation,
+            &system_prompt,
+            &msg.content,
+            &model,
+            temperature,
+        )
+        .await;
+        if let Some(typing) = typing {
+            typing.abort();
+        }
+        match reply {
             Ok(response) => {
+                transcript.assistant(&response, &model);
                 println!("  🤖 回复: {}", truncate_with_ellipsis(&response, 80));
                 // Find the channel that sent this message and reply
                 for ch in &channels {
                     if ch.name() == msg.channel {
-                        if let Err(e) = ch.send(&response, &msg.sender).await {
-                            eprintln!("  ❌ 在 {} 上回复失败: {e}", ch.name());
+                        match ch.send(&response, &msg.sender).await {
+                            Ok(()) => observer.record_event(
+                                &crate::observability::ObserverEvent::ChannelMessage {
+                                    channel: msg.channel.clone(),
+                                    direction: "outbound".into(),
+                                },
+                            ),
+                            Err(e) => eprintln!("  ❌ 在 {} 上回复失败: {e}", ch.name()),
                         }
                         break;
                     }
@@ -655,10 +818,15 @@ pub async fn start_channels(config: Config) -> Result<()> {
         }
     }
 
+    if let Err(e) = histories.save() {
+        tracing::warn!("保存通道会话历史失败: {e:#}");
+    }
+
     // Wait for all channel tasks
     for h in handles {
         let _ = h.await;
     }
+    let _ = dedup.await;
 
     Ok(())
 }
@@ -828,8 +996,10 @@ mod tests {
             version: "1.0.0".into(),
             author: None,
             tags: vec![],
+            required_tools: vec![],
             tools: vec![],
             prompts: vec!["Long prompt content that should NOT appear in system prompt".into()],
+            entrypoint: None,
             location: None,
         }];
 
@@ -888,6 +1058,34 @@ mod tests {
         assert!(prompt.contains(&format!("Working directory: `{}`", ws.path().display())));
     }
 
+    #[test]
+    fn inbound_under_limit_passes_through_unchanged() {
+        let msg = "hello jarvis";
+        assert_eq!(check_inbound_size(msg, 100), Ok(msg));
+        assert_eq!(check_inbound_size(msg, msg.len()), Ok(msg));
+    }
+
+    #[test]
+    fn inbound_over_limit_is_rejected_with_notice() {
+        let msg = "x".repeat(101);
+        let notice = check_inbound_size(&msg, 100).unwrap_err();
+        assert!(notice.contains("101"));
+        assert!(notice.contains("100"));
+    }
+
+    #[test]
+    fn inbound_limit_counts_chars_not_bytes() {
+        // 4 CJK chars = 12 bytes, still within a 4-char cap
+        assert!(check_inbound_size("你好世界", 4).is_ok());
+        assert!(check_inbound_size("你好世界!", 4).is_err());
+    }
+
+    #[test]
+    fn inbound_limit_zero_disables_check() {
+        let msg = "y".repeat(100_000);
+        assert!(check_inbound_size(&msg, 0).is_ok());
+    }
+
     #[test]
     fn classify_health_ok_true() {
         let state = classify_health_result(&Ok(true));
@@ -935,6 +1133,48 @@ mod tests {
         }
     }
 
+    struct TypingChannel {
+        typing: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Channel for TypingChannel {
+        fn name(&self) -> &str {
+            "test-typing"
+        }
+
+        async fn send(&self, _message: &str, _recipient: &str) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn listen(
+            &self,
+            _tx: tokio::sync::mpsc::Sender<traits::ChannelMessage>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn start_typing(&self, recipient: &str) -> anyhow::Result<()> {
+            assert_eq!(recipient, "42");
+            self.typing.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn typing_indicator_starts_at_once_and_stops_when_aborted() {
+        let typing = Arc::new(AtomicUsize::new(0));
+        let channel: Arc<dyn Channel> = Arc::new(TypingChannel {
+            typing: Arc::clone(&typing),
+        });
+
+        let handle = spawn_typing_indicator(channel, "42".into());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle.abort();
+        assert!(handle.await.unwrap_err().is_cancelled());
+        assert_eq!(typing.load(Ordering::SeqCst), 1);
+    }
+
     #[tokio::test]
     async fn supervised_listener_marks_error_and_restarts_on_failures() {
         let calls = Arc::new(AtomicUsize::new(0));
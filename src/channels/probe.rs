@@ -0,0 +1,141 @@
+//! Credential checks against each channel's "who am I" endpoint (Telegram
+//! `getMe`, Slack `auth.test`, …), shared by `jarvis onboard` and
+//! `jarvis doctor --deep`. Blocking; run them off the async runtime.
+
+use anyhow::Result;
+use std::time::Duration;
+
+/// Upper bound for one check, so an offline machine fails fast
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn client() -> reqwest::blocking::Client {
+    reqwest::blocking::Client::builder()
+        .timeout(PROBE_TIMEOUT)
+        .connect_timeout(Duration::from_secs(5))
+        .build()
+        .unwrap_or_else(|_| reqwest::blocking::Client::new())
+}
+
+/// Telegram `getMe`; returns the bot username.
+pub fn check_telegram(token: &str) -> Result<String> {
+    let token = token.trim();
+    if token.is_empty() {
+        anyhow::bail!("Token 为空");
+    }
+    let resp = client()
+        .get(format!("https://api.telegram.org/bot{token}/getMe"))
+        .send()
+        // The URL embeds the token; keep it out of error messages
+        .map_err(reqwest::Error::without_url)?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Telegram 返回 {}", resp.status());
+    }
+    let data: serde_json::Value = resp.json().unwrap_or_default();
+    Ok(data
+        .get("result")
+        .and_then(|r| r.get("username"))
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("unknown")
+        .to_string())
+}
+
+/// Discord `users/@me`; returns the bot username.
+pub fn check_discord(token: &str) -> Result<String> {
+    let token = token.trim();
+    if token.is_empty() {
+        anyhow::bail!("Token 为空");
+    }
+    let resp = client()
+        .get("https://discord.com/api/v10/users/@me")
+        .header("Authorization", format!("Bot {token}"))
+        .send()?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Discord 返回 {}", resp.status());
+    }
+    let data: serde_json::Value = resp.json().unwrap_or_default();
+    Ok(data
+        .get("username")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("unknown")
+        .to_string())
+}
+
+/// Slack `auth.test`; returns the workspace name.
+pub fn check_slack(token: &str) -> Result<String> {
+    let token = token.trim();
+    if token.is_empty() {
+        anyhow::bail!("Token 为空");
+    }
+    let resp = client()
+        .get("https://slack.com/api/auth.test")
+        .bearer_auth(token)
+        .send()?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Slack 返回 {}", resp.status());
+    }
+    let data: serde_json::Value = resp.json().unwrap_or_default();
+    if !data
+        .get("ok")
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false)
+    {
+        let err = data
+            .get("error")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("unknown error");
+        anyhow::bail!("Slack 错误：{err}");
+    }
+    Ok(data
+        .get("team")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("unknown")
+        .to_string())
+}
+
+/// Matrix `account/whoami`; returns the user ID.
+pub fn check_matrix(homeserver: &str, access_token: &str) -> Result<String> {
+    let hs = homeserver.trim_end_matches('/');
+    let resp = client()
+        .get(format!("{hs}/_matrix/client/v3/account/whoami"))
+        .header("Authorization", format!("Bearer {access_token}"))
+        .send()?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Matrix 返回 {}", resp.status());
+    }
+    let data: serde_json::Value = resp.json().unwrap_or_default();
+    Ok(data
+        .get("user_id")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("unknown")
+        .to_string())
+}
+
+/// Graph API lookup of the `WhatsApp` phone number ID.
+pub fn check_whatsapp(access_token: &str, phone_number_id: &str) -> Result<()> {
+    let resp = client()
+        .get(format!(
+            "https://graph.facebook.com/v18.0/{}",
+            phone_number_id.trim()
+        ))
+        .header("Authorization", format!("Bearer {}", access_token.trim()))
+        .send()?;
+    if !resp.status().is_success() {
+        anyhow::bail!("WhatsApp 返回 {}", resp.status());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_tokens_fail_without_a_request() {
+        assert!(check_telegram("  ")
+            .unwrap_err()
+            .to_string()
+            .contains("Token 为空"));
+        assert!(check_discord("").is_err());
+        assert!(check_slack("").is_err());
+    }
+}
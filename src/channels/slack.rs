@@ -1,25 +1,78 @@
+use super::allowlist::RejectionSink;
 use super::traits::{Channel, ChannelMessage};
+use crate::config::SlackConfig;
 use async_trait::async_trait;
-use uuid::Uuid;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use tokio_tungstenite::tungstenite::Message;
 
-/// Slack channel — polls conversations.history via Web API
+const API: &str = "https://slack.com/api";
+
+/// Longest pause between Socket Mode reconnect attempts.
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_mins(1);
+
+/// How many recent `channel:ts` keys are remembered; a mention arrives both
+/// as `message` and `app_mention`, and unacked envelopes are redelivered.
+const SEEN_CAPACITY: usize = 256;
+
+/// Slack channel — Socket Mode when an app token is configured, otherwise
+/// polls conversations.history via Web API
 pub struct SlackChannel {
     bot_token: String,
+    app_token: Option<String>,
     channel_id: Option<String>,
     allowed_users: Vec<String>,
+    /// Where senders the allowlist turns away are reported
+    rejections: Option<RejectionSink>,
     client: reqwest::Client,
 }
 
+/// Why a Socket Mode session ended without an error.
+#[derive(Debug, PartialEq)]
+enum SessionEnd {
+    /// The message receiver is gone; stop listening.
+    ReceiverClosed,
+    /// Slack asked us to reconnect (e.g. `refresh_requested`) or closed the socket.
+    Disconnected(String),
+}
+
 impl SlackChannel {
     pub fn new(bot_token: String, channel_id: Option<String>, allowed_users: Vec<String>) -> Self {
         Self {
             bot_token,
+            app_token: None,
             channel_id,
             allowed_users,
+            rejections: None,
             client: reqwest::Client::new(),
         }
     }
 
+    pub fn from_config(config: &SlackConfig) -> Self {
+        Self {
+            app_token: config
+                .app_token
+                .as_deref()
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(String::from),
+            ..Self::new(
+                config.bot_token.clone(),
+                config.channel_id.clone(),
+                config.allowed_users.clone(),
+            )
+        }
+    }
+
+    /// Report senders the allowlist turns away to `sink`.
+    pub fn with_rejections(mut self, sink: Option<RejectionSink>) -> Self {
+        self.rejections = sink;
+        self
+    }
+
     /// Check if a Slack user ID is in the allowlist.
     /// Empty list means deny everyone until explicitly configured.
     /// `"*"` means allow everyone.
@@ -31,7 +84,7 @@ impl SlackChannel {
     async fn get_bot_user_id(&self) -> Option<String> {
         let resp: serde_json::Value = self
             .client
-            .get("https://slack.com/api/auth.test")
+            .get(format!("{API}/auth.test"))
             .bearer_auth(&self.bot_token)
             .send()
             .await
@@ -44,56 +97,205 @@ impl SlackChannel {
             .and_then(|u| u.as_str())
             .map(String::from)
     }
-}
-
-#[async_trait]
-impl Channel for SlackChannel {
-    fn name(&self) -> &str {
-        "slack"
-    }
 
-    async fn send(&self, message: &str, channel: &str) -> anyhow::Result<()> {
-        let body = serde_json::json!({
-            "channel": channel,
-            "text": message
-        });
-
-        let resp = self
+    /// Ask Slack for a fresh Socket Mode WebSocket URL.
+    async fn open_socket_url(&self, app_token: &str) -> anyhow::Result<String> {
+        let resp: Value = self
             .client
-            .post("https://slack.com/api/chat.postMessage")
-            .bearer_auth(&self.bot_token)
-            .json(&body)
+            .post(format!("{API}/apps.connections.open"))
+            .bearer_auth(app_token)
             .send()
+            .await?
+            .json()
             .await?;
 
-        let status = resp.status();
-        let body = resp
-            .text()
-            .await
-            .unwrap_or_else(|e| format!("<无法读取响应体: {e}>"));
+        if resp.get("ok") != Some(&Value::Bool(true)) {
+            let err = resp
+                .get("error")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown");
+            anyhow::bail!("Slack apps.connections.open 失败: {err}");
+        }
+        resp.get("url")
+            .and_then(Value::as_str)
+            .map(String::from)
+            .ok_or_else(|| anyhow::anyhow!("Slack apps.connections.open 未返回 url"))
+    }
 
-        if !status.is_success() {
-            anyhow::bail!("Slack chat.postMessage 失败 ({status}): {body}");
+    /// Receive events over Socket Mode, reconnecting with backoff. The
+    /// channel shows as degraded while it is reconnecting.
+    async fn listen_socket_mode(
+        &self,
+        app_token: &str,
+        tx: &Sender<ChannelMessage>,
+    ) -> anyhow::Result<()> {
+        let bot_user_id = self.get_bot_user_id().await.unwrap_or_default();
+        let mut seen = VecDeque::new();
+        let mut backoff = Duration::from_secs(1);
+
+        loop {
+            let session = match self.open_socket_url(app_token).await {
+                Ok(url) => {
+                    self.run_socket_session(&url, &bot_user_id, tx, &mut seen)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+
+            match session {
+                Ok(SessionEnd::ReceiverClosed) => return Ok(()),
+                Ok(SessionEnd::Disconnected(reason)) => {
+                    // A requested reconnect is routine: go again right away
+                    tracing::info!("Slack: Socket Mode 连接断开（{reason}），正在重连");
+                    crate::health::mark_component_degraded(
+                        "channel:slack",
+                        format!("Socket Mode 重连中: {reason}"),
+                    );
+                    backoff = Duration::from_secs(1);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Slack: Socket Mode 连接出错: {e}，{}秒后重连",
+                        backoff.as_secs()
+                    );
+                    crate::health::mark_component_degraded(
+                        "channel:slack",
+                        format!("Socket Mode 重连中: {e}"),
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+                }
+            }
         }
+    }
 
-        // Slack returns 200 for most app-level errors; check JSON "ok" field
-        let parsed: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
-        if parsed.get("ok") == Some(&serde_json::Value::Bool(false)) {
-            let err = parsed
-                .get("error")
-                .and_then(|e| e.as_str())
-                .unwrap_or("unknown");
-            anyhow::bail!("Slack chat.postMessage 失败: {err}");
+    /// One Socket Mode connection: ack every envelope as soon as it arrives
+    /// (Slack redelivers anything not acked within 3 seconds), then dispatch.
+    async fn run_socket_session(
+        &self,
+        url: &str,
+        bot_user_id: &str,
+        tx: &Sender<ChannelMessage>,
+        seen: &mut VecDeque<String>,
+    ) -> anyhow::Result<SessionEnd> {
+        let (ws_stream, _) = tokio_tungstenite::connect_async(url).await?;
+        let (mut write, mut read) = ws_stream.split();
+
+        while let Some(msg) = read.next().await {
+            let text = match msg? {
+                Message::Text(t) => t,
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            let Ok(envelope) = serde_json::from_str::<Value>(&text) else {
+                continue;
+            };
+
+            if let Some(id) = envelope.get("envelope_id").and_then(Value::as_str) {
+                let ack = json!({ "envelope_id": id });
+                write.send(Message::Text(ack.to_string())).await?;
+            }
+
+            match envelope.get("type").and_then(Value::as_str) {
+                Some("hello") => {
+                    tracing::info!("Slack: 已通过 Socket Mode 连接");
+                    crate::health::mark_component_ok("channel:slack");
+                }
+                Some("disconnect") => {
+                    let reason = envelope
+                        .get("reason")
+                        .and_then(Value::as_str)
+                        .unwrap_or("unknown");
+                    return Ok(SessionEnd::Disconnected(reason.to_string()));
+                }
+                Some("events_api") => {
+                    let Some(event) = envelope.pointer("/payload/event") else {
+                        continue;
+                    };
+                    if let Some(message) = self.event_message(event, bot_user_id, seen)
+                        && tx.send(message).await.is_err()
+                    {
+                        return Ok(SessionEnd::ReceiverClosed);
+                    }
+                }
+                _ => {}
+            }
         }
 
-        Ok(())
+        Ok(SessionEnd::Disconnected("连接已关闭".to_string()))
+    }
+
+    /// Turn a `message` or `app_mention` event into a channel message, or
+    /// `None` if it's from a bot, an edit/join/etc., outside `channel_id`,
+    /// from a user not on the allowlist, or already seen.
+    fn event_message(
+        &self,
+        event: &Value,
+        bot_user_id: &str,
+        seen: &mut VecDeque<String>,
+    ) -> Option<ChannelMessage> {
+        let field = |key: &str| event.get(key).and_then(Value::as_str);
+
+        if !matches!(field("type"), Some("message" | "app_mention"))
+            || field("subtype").is_some()
+            || field("bot_id").is_some()
+        {
+            return None;
+        }
+        let user = field("user")?;
+        let channel = field("channel")?;
+        let ts = field("ts")?;
+        if user == bot_user_id {
+            return None;
+        }
+        if let Some(only) = &self.channel_id
+            && channel != only
+        {
+            return None;
+        }
+        if !self.is_user_allowed(user) {
+            tracing::warn!("Slack: 忽略未授权用户的消息: {user}");
+            if let Some(sink) = &self.rejections {
+                sink.report("slack", user);
+            }
+            return None;
+        }
+
+        let key = format!("{channel}:{ts}");
+        if seen.contains(&key) {
+            return None;
+        }
+        if seen.len() == SEEN_CAPACITY {
+            seen.pop_front();
+        }
+        seen.push_back(key.clone());
+
+        let mut text = field("text").unwrap_or("").to_string();
+        if !bot_user_id.is_empty() {
+            text = text.replace(&format!("<@{bot_user_id}>"), "");
+        }
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+
+        Some(ChannelMessage {
+            id: key,
+            sender: channel.to_string(),
+            content: text.to_string(),
+            channel: "slack".to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
     }
 
-    async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
-        let channel_id = self
-            .channel_id
-            .clone()
-            .ok_or_else(|| anyhow::anyhow!("Slack 监听需要 channel_id"))?;
+    /// Poll `channel_id`'s history every few seconds (no app token).
+    async fn poll_history(&self, tx: &Sender<ChannelMessage>) -> anyhow::Result<()> {
+        let channel_id = self.channel_id.clone().ok_or_else(|| {
+            anyhow::anyhow!("Slack 轮询需要 channel_id（或配置 app_token 使用 Socket Mode）")
+        })?;
 
         let bot_user_id = self.get_bot_user_id().await.unwrap_or_default();
         let mut last_ts = String::new();
@@ -110,7 +312,7 @@ impl Channel for SlackChannel {
 
             let resp = match self
                 .client
-                .get("https://slack.com/api/conversations.history")
+                .get(format!("{API}/conversations.history"))
                 .bearer_auth(&self.bot_token)
                 .query(&params)
                 .send()
@@ -149,6 +351,9 @@ impl Channel for SlackChannel {
                     // Sender validation
                     if !self.is_user_allowed(user) {
                         tracing::warn!("Slack: 忽略未授权用户的消息: {user}");
+                        if let Some(sink) = &self.rejections {
+                            sink.report("slack", user);
+                        }
                         continue;
                     }
 
@@ -160,7 +365,7 @@ impl Channel for SlackChannel {
                     last_ts = ts.to_string();
 
                     let channel_msg = ChannelMessage {
-                        id: Uuid::new_v4().to_string(),
+                        id: format!("{channel_id}:{ts}"),
                         sender: channel_id.clone(),
                         content: text.to_string(),
                         channel: "slack".to_string(),
@@ -177,10 +382,61 @@ impl Channel for SlackChannel {
             }
         }
     }
+}
+
+#[async_trait]
+impl Channel for SlackChannel {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    async fn send(&self, message: &str, channel: &str) -> anyhow::Result<()> {
+        let body = serde_json::json!({
+            "channel": channel,
+            "text": message
+        });
+
+        let resp = self
+            .client
+            .post(format!("{API}/chat.postMessage"))
+            .bearer_auth(&self.bot_token)
+            .json(&body)
+            .send()
+            .await?;
+
+        let status = resp.status();
+        let body = resp
+            .text()
+            .await
+            .unwrap_or_else(|e| format!("<无法读取响应体: {e}>"));
+
+        if !status.is_success() {
+            anyhow::bail!("Slack chat.postMessage 失败 ({status}): {body}");
+        }
+
+        // Slack returns 200 for most app-level errors; check JSON "ok" field
+        let parsed: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+        if parsed.get("ok") == Some(&serde_json::Value::Bool(false)) {
+            let err = parsed
+                .get("error")
+                .and_then(|e| e.as_str())
+                .unwrap_or("unknown");
+            anyhow::bail!("Slack chat.postMessage 失败: {err}");
+        }
+
+        Ok(())
+    }
+
+    async fn listen(&self, tx: Sender<ChannelMessage>) -> anyhow::Result<()> {
+        match &self.app_token {
+            Some(app_token) => self.listen_socket_mode(app_token, &tx).await,
+            None => self.poll_history(&tx).await,
+        }
+    }
 
     async fn health_check(&self) -> bool {
         self.client
-            .get("https://slack.com/api/auth.test")
+            .get(format!("{API}/auth.test"))
             .bearer_auth(&self.bot_token)
             .send()
             .await
@@ -252,4 +508,153 @@ mod tests {
         assert!(ch.is_user_allowed("U111"));
         assert!(ch.is_user_allowed("anyone"));
     }
+
+    fn event(kind: &str, user: &str, channel: &str, ts: &str, text: &str) -> Value {
+        json!({ "type": kind, "user": user, "channel": channel, "ts": ts, "text": text })
+    }
+
+    #[test]
+    fn from_config_ignores_blank_app_token() {
+        let mut config = SlackConfig {
+            bot_token: "xoxb-fake".into(),
+            app_token: Some("  ".into()),
+            channel_id: None,
+            allowed_users: vec![],
+        };
+        assert_eq!(SlackChannel::from_config(&config).app_token, None);
+        config.app_token = Some("xapp-1".into());
+        assert_eq!(
+            SlackChannel::from_config(&config).app_token.as_deref(),
+            Some("xapp-1")
+        );
+    }
+
+    #[test]
+    fn events_are_filtered_deduplicated_and_stripped() {
+        let ch = SlackChannel::new("xoxb-fake".into(), Some("C1".into()), vec!["U111".into()]);
+        let mut seen = VecDeque::new();
+
+        let msg = ch
+            .event_message(
+                &event("app_mention", "U111", "C1", "1.1", "<@UBOT> hello"),
+                "UBOT",
+                &mut seen,
+            )
+            .unwrap();
+        assert_eq!(msg.sender, "C1");
+        assert_eq!(msg.content, "hello");
+        assert_eq!(msg.channel, "slack");
+
+        // Same message delivered again as a plain `message` event
+        let dup = event("message", "U111", "C1", "1.1", "<@UBOT> hello");
+        assert!(ch.event_message(&dup, "UBOT", &mut seen).is_none());
+
+        for skipped in [
+            event("message", "U999", "C1", "1.2", "hi"),
+            event("message", "U111", "C2", "1.3", "hi"),
+            event("message", "UBOT", "C1", "1.4", "hi"),
+            event("message", "U111", "C1", "1.5", "<@UBOT>"),
+            event("reaction_added", "U111", "C1", "1.6", "hi"),
+            json!({ "type": "message", "subtype": "message_changed", "user": "U111",
+                    "channel": "C1", "ts": "1.7", "text": "hi" }),
+            json!({ "type": "message", "bot_id": "B1", "user": "U111",
+                    "channel": "C1", "ts": "1.8", "text": "hi" }),
+        ] {
+            assert!(ch.event_message(&skipped, "UBOT", &mut seen).is_none());
+        }
+    }
+
+    #[test]
+    fn seen_keys_are_bounded() {
+        let ch = SlackChannel::new("xoxb-fake".into(), None, vec!["*".into()]);
+        let mut seen = VecDeque::new();
+        for i in 0..SEEN_CAPACITY + 10 {
+            let ev = event("message", "U1", "C1", &format!("{i}.0"), "hi");
+            assert!(ch.event_message(&ev, "UBOT", &mut seen).is_some());
+        }
+        assert_eq!(seen.len(), SEEN_CAPACITY);
+        assert_eq!(seen.front().map(String::as_str), Some("C1:10.0"));
+    }
+
+    #[tokio::test]
+    async fn socket_session_acks_envelopes_and_dispatches_messages() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            ws.send(Message::Text(json!({ "type": "hello" }).to_string()))
+                .await
+                .unwrap();
+
+            let events = [
+                event("message", "U111", "C1", "1.1", "first"),
+                event("message", "U999", "C1", "1.2", "intruder"),
+                event("app_mention", "U111", "C1", "1.1", "<@UBOT> first"),
+                event("message", "U111", "C1", "1.3", "second"),
+            ];
+            let mut acks = Vec::new();
+            for (i, ev) in events.into_iter().enumerate() {
+                let envelope = json!({
+                    "envelope_id": format!("env-{i}"),
+                    "type": "events_api",
+                    "payload": { "event": ev },
+                });
+                ws.send(Message::Text(envelope.to_string())).await.unwrap();
+                let ack = ws.next().await.unwrap().unwrap();
+                let ack: Value = serde_json::from_str(ack.to_text().unwrap()).unwrap();
+                acks.push(ack["envelope_id"].as_str().unwrap().to_string());
+            }
+
+            let disconnect = json!({ "type": "disconnect", "reason": "refresh_requested" });
+            ws.send(Message::Text(disconnect.to_string()))
+                .await
+                .unwrap();
+            acks
+        });
+
+        let ch = SlackChannel::new("xoxb-fake".into(), None, vec!["U111".into()]);
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        let mut seen = VecDeque::new();
+        let end = ch
+            .run_socket_session(&url, "UBOT", &tx, &mut seen)
+            .await
+            .unwrap();
+
+        assert_eq!(end, SessionEnd::Disconnected("refresh_requested".into()));
+        assert_eq!(server.await.unwrap(), ["env-0", "env-1", "env-2", "env-3"]);
+        let received: Vec<String> = std::iter::from_fn(|| rx.try_recv().ok())
+            .map(|m| m.content)
+            .collect();
+        assert_eq!(received, ["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn socket_session_stops_when_receiver_is_gone() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let envelope = json!({
+                "envelope_id": "env-0",
+                "type": "events_api",
+                "payload": { "event": event("message", "U111", "C1", "1.1", "hi") },
+            });
+            ws.send(Message::Text(envelope.to_string())).await.unwrap();
+            // Keep the socket open until the client hangs up
+            while ws.next().await.is_some() {}
+        });
+
+        let ch = SlackChannel::new("xoxb-fake".into(), None, vec!["*".into()]);
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        drop(rx);
+        let end = ch
+            .run_socket_session(&url, "UBOT", &tx, &mut VecDeque::new())
+            .await
+            .unwrap();
+        assert_eq!(end, SessionEnd::ReceiverClosed);
+    }
 }
@@ -1,13 +1,19 @@
+use super::allowlist::RejectionSink;
+use super::chunk::{is_fence, split_message};
 use super::traits::{Channel, ChannelMessage};
 use async_trait::async_trait;
 use reqwest::multipart::{Form, Part};
 use std::path::Path;
-use uuid::Uuid;
+
+/// Telegram's limit on a message's text, in UTF-16 code units.
+const MAX_MESSAGE_LEN: usize = 4096;
 
 /// Telegram channel — long-polls the Bot API for updates
 pub struct TelegramChannel {
     bot_token: String,
     allowed_users: Vec<String>,
+    /// Where senders the allowlist turns away are reported
+    rejections: Option<RejectionSink>,
     client: reqwest::Client,
 }
 
@@ -16,10 +22,17 @@ impl TelegramChannel {
         Self {
             bot_token,
             allowed_users,
+            rejections: None,
             client: reqwest::Client::new(),
         }
     }
 
+    /// Report senders the allowlist turns away to `sink`.
+    pub fn with_rejections(mut self, sink: Option<RejectionSink>) -> Self {
+        self.rejections = sink;
+        self
+    }
+
     fn api_url(&self, method: &str) -> String {
         format!("https://api.telegram.org/bot{}/{method}", self.bot_token)
     }
@@ -35,6 +48,27 @@ impl TelegramChannel {
         identities.into_iter().any(|id| self.is_user_allowed(id))
     }
 
+    async fn send_message(
+        &self,
+        chat_id: &str,
+        text: &str,
+        parse_mode: Option<&str>,
+    ) -> anyhow::Result<reqwest::Response> {
+        let mut body = serde_json::json!({
+            "chat_id": chat_id,
+            "text": text,
+        });
+        if let Some(mode) = parse_mode {
+            body["parse_mode"] = mode.into();
+        }
+        Ok(self
+            .client
+            .post(self.api_url("sendMessage"))
+            .json(&body)
+            .send()
+            .await?)
+    }
+
     /// Send a document/file to a Telegram chat
     pub async fn send_document(
         &self,
@@ -363,6 +397,154 @@ impl TelegramChannel {
     }
 }
 
+/// Convert the model's markdown to Telegram `MarkdownV2`: `**bold**`,
+/// `*italic*`/`_italic_`, `~~strike~~`, inline code, code fences and links
+/// are kept, headings become bold and list markers become bullets.
+/// Everything else has `MarkdownV2`'s reserved characters escaped, so
+/// `snake_case` and URLs come through unchanged.
+fn to_markdown_v2(text: &str) -> String {
+    let mut lines = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        if is_fence(line) {
+            if in_fence {
+                lines.push("```".to_string());
+            } else {
+                let lang: String = line.trim_start()[3..]
+                    .trim()
+                    .chars()
+                    .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '_' | '#'))
+                    .collect();
+                lines.push(format!("```{lang}"));
+            }
+            in_fence = !in_fence;
+        } else if in_fence {
+            lines.push(escape_code(line));
+        } else {
+            lines.push(convert_line(line));
+        }
+    }
+    if in_fence {
+        lines.push("```".to_string());
+    }
+    lines.join("\n")
+}
+
+fn convert_line(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&hashes)
+        && let Some(title) = trimmed[hashes..].strip_prefix(' ')
+    {
+        return format!("*{}*", inline(title.trim()));
+    }
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(item) = trimmed.strip_prefix(bullet) {
+            return format!("{indent}• {}", inline(item));
+        }
+    }
+    format!("{indent}{}", inline(trimmed))
+}
+
+/// Convert inline markdown; anything that is not a complete span is escaped.
+fn inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut prev = None;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some((converted, used)) = span(rest, prev) {
+            out.push_str(&converted);
+            prev = rest[..used].chars().next_back();
+            rest = &rest[used..];
+            continue;
+        }
+        push_escaped(&mut out, c, "_*[]()~`>#+-=|{}.!\\");
+        prev = Some(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// A markdown span at the start of `s`: its `MarkdownV2` form and the bytes
+/// it covers. `prev` is the character before it, so that `snake_case` and
+/// `2*3*4` are not read as emphasis.
+fn span(s: &str, prev: Option<char>) -> Option<(String, usize)> {
+    let after_word = prev.is_some_and(char::is_alphanumeric);
+    if let Some(body) = s.strip_prefix('`') {
+        let end = body.find('`').filter(|&end| end > 0)?;
+        return Some((format!("`{}`", escape_code(&body[..end])), end + 2));
+    }
+    if let Some(body) = s.strip_prefix('[') {
+        let label_end = body.find("](")?;
+        let label = &body[..label_end];
+        let url_start = label_end + 2;
+        let url_len = closing_paren(&body[url_start..])?;
+        let url = &body[url_start..url_start + url_len];
+        if label.is_empty() || url.is_empty() {
+            return None;
+        }
+        let url = url.replace('\\', "\\\\").replace(')', "\\)");
+        return Some((
+            format!("[{}]({url})", inline(label)),
+            1 + url_start + url_len + 1,
+        ));
+    }
+    for (marker, entity) in [
+        ("**", '*'),
+        ("__", '*'),
+        ("~~", '~'),
+        ("*", '_'),
+        ("_", '_'),
+    ] {
+        let Some(body) = s.strip_prefix(marker) else {
+            continue;
+        };
+        if after_word || body.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let end = body.match_indices(marker).map(|(i, _)| i).find(|&i| {
+            i > 0
+                && !body[..i].ends_with(char::is_whitespace)
+                && !body[i + marker.len()..].starts_with(char::is_alphanumeric)
+        })?;
+        let converted = format!("{entity}{}{entity}", inline(&body[..end]));
+        return Some((converted, end + 2 * marker.len()));
+    }
+    None
+}
+
+/// Length of a link target up to its closing `)`, allowing balanced
+/// parentheses inside (as in Wikipedia URLs).
+fn closing_paren(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            ' ' | '\n' => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn escape_code(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        push_escaped(&mut out, c, "`\\");
+    }
+    out
+}
+
+fn push_escaped(out: &mut String, c: char, reserved: &str) {
+    if reserved.contains(c) {
+        out.push('\\');
+    }
+    out.push(c);
+}
+
 #[async_trait]
 impl Channel for TelegramChannel {
     fn name(&self) -> &str {
@@ -370,28 +552,47 @@ impl Channel for TelegramChannel {
     }
 
     async fn send(&self, message: &str, chat_id: &str) -> anyhow::Result<()> {
+        for chunk in split_message(message, MAX_MESSAGE_LEN) {
+            let mut resp = self
+                .send_message(chat_id, &to_markdown_v2(&chunk), Some("MarkdownV2"))
+                .await?;
+
+            if resp.status() == reqwest::StatusCode::BAD_REQUEST {
+                let err = resp.text().await.unwrap_or_default();
+                if !err.contains("can't parse entities") {
+                    anyhow::bail!("Telegram sendMessage 失败 (400 Bad Request): {err}");
+                }
+                tracing::debug!("Telegram 无法解析 MarkdownV2，改为纯文本发送: {err}");
+                resp = self.send_message(chat_id, &chunk, None).await?;
+            }
+
+            if !resp.status().is_success() {
+                let status = resp.status();
+                let err = resp
+                    .text()
+                    .await
+                    .unwrap_or_else(|e| format!("<无法读取响应体: {e}>"));
+                anyhow::bail!("Telegram sendMessage 失败 ({status}): {err}");
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn start_typing(&self, chat_id: &str) -> anyhow::Result<()> {
         let body = serde_json::json!({
             "chat_id": chat_id,
-            "text": message,
-            "parse_mode": "Markdown"
+            "action": "typing"
         });
-
         let resp = self
             .client
-            .post(self.api_url("sendMessage"))
+            .post(self.api_url("sendChatAction"))
             .json(&body)
             .send()
             .await?;
-
         if !resp.status().is_success() {
-            let status = resp.status();
-            let err = resp
-                .text()
-                .await
-                .unwrap_or_else(|e| format!("<无法读取响应体: {e}>"));
-            anyhow::bail!("Telegram sendMessage 失败 ({status}): {err}");
+            anyhow::bail!("Telegram sendChatAction 失败 ({})", resp.status());
         }
-
         Ok(())
     }
 
@@ -464,6 +665,9 @@ impl Channel for TelegramChannel {
 请将 Telegram @username 或数字用户 ID 添加到白名单，然后运行 `jarvis onboard --channels-only`。",
                             user_id_str.as_deref().unwrap_or("unknown")
                         );
+                        if let Some(sink) = &self.rejections {
+                            sink.report("telegram", user_id_str.as_deref().unwrap_or(username));
+                        }
                         continue;
                     }
 
@@ -474,8 +678,15 @@ impl Channel for TelegramChannel {
                         .map(|id| id.to_string())
                         .unwrap_or_default();
 
+                    // Message ids are only unique within a chat
+                    let message_id = message
+                        .get("message_id")
+                        .and_then(serde_json::Value::as_i64)
+                        .map(|id| format!("{chat_id}:{id}"))
+                        .unwrap_or_default();
+
                     let msg = ChannelMessage {
-                        id: Uuid::new_v4().to_string(),
+                        id: message_id,
                         sender: chat_id,
                         content: text.to_string(),
                         channel: "telegram".to_string(),
@@ -828,4 +1039,48 @@ mod tests {
         // Should not panic
         assert!(result.is_err());
     }
+
+    #[test]
+    fn markdown_v2_escapes_reserved_characters() {
+        assert_eq!(
+            to_markdown_v2("Done! Cost: $1.50 (approx) - see #3 + {x} = [y] | >z"),
+            r"Done\! Cost: $1\.50 \(approx\) \- see \#3 \+ \{x\} \= \[y\] \| \>z"
+        );
+        assert_eq!(to_markdown_v2(r"C:\temp"), r"C:\\temp");
+    }
+
+    #[test]
+    fn markdown_v2_leaves_underscores_in_urls_and_words_alone() {
+        assert_eq!(
+            to_markdown_v2("see https://example.com/a_b_c?x=1 and snake_case_name"),
+            r"see https://example\.com/a\_b\_c?x\=1 and snake\_case\_name"
+        );
+        assert_eq!(
+            to_markdown_v2("[the docs](https://x.io/my_page_(v2)) now"),
+            r"[the docs](https://x.io/my_page_(v2\)) now"
+        );
+        assert_eq!(to_markdown_v2("2*3*4 = 24"), r"2\*3\*4 \= 24");
+    }
+
+    #[test]
+    fn markdown_v2_converts_emphasis_code_and_structure() {
+        assert_eq!(
+            to_markdown_v2("**Note**: use `my_var!` or *this* or _that_ ~~not~~"),
+            r"*Note*: use `my_var!` or _this_ or _that_ ~not~"
+        );
+        assert_eq!(
+            to_markdown_v2("## Steps\n- first.\n  * nested"),
+            "*Steps*\n• first\\.\n  • nested"
+        );
+        assert_eq!(to_markdown_v2("**unclosed"), r"\*\*unclosed");
+    }
+
+    #[test]
+    fn markdown_v2_escapes_only_backticks_and_backslashes_in_code() {
+        assert_eq!(
+            to_markdown_v2("```python\nprint(\"a_b\\n\", `x`) # 1.0!\n```"),
+            "```python\nprint(\"a_b\\\\n\", \\`x\\`) # 1.0!\n```"
+        );
+        assert_eq!(to_markdown_v2("```\nopen"), "```\nopen\n```");
+    }
 }
@@ -0,0 +1,287 @@
+//! `jarvis channel test <name>`: check one channel's credentials and send a
+//! canned message through its outbound path, without starting the daemon.
+//!
+//! The message goes to the channel's default target: the Slack `channel_id`
+//! or Matrix room when set, otherwise the first entry of the allowlist (the
+//! owner, as `jarvis onboard` writes it).
+
+use super::{
+    probe, Channel, DiscordChannel, IMessageChannel, MatrixChannel, SlackChannel, TelegramChannel,
+    WhatsAppChannel,
+};
+use crate::config::{ChannelsConfig, Config};
+use anyhow::{bail, Context, Result};
+use std::time::Duration;
+
+/// What the test sends.
+pub const TEST_MESSAGE: &str = "hello from jarvis";
+
+/// Channels `jarvis channel test` knows about.
+pub const CHANNELS: &[&str] = &[
+    "telegram", "discord", "slack", "matrix", "whatsapp", "imessage", "irc", "webhook",
+];
+
+/// Upper bound for the send itself.
+const SEND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The first allowlist entry that names someone, skipping `*`.
+fn owner(allowed: &[String]) -> Option<&str> {
+    allowed
+        .iter()
+        .map(|u| u.trim())
+        .find(|u| !u.is_empty() && *u != "*")
+}
+
+/// Where the test message of `channel` goes, or why it can't be sent.
+pub fn recipient(config: &ChannelsConfig, channel: &str) -> Result<String> {
+    let missing =
+        || anyhow::anyhow!("未配置 [channels_config.{channel}]，请先运行 `jarvis onboard`");
+    let no_owner = |key: &str| {
+        anyhow::anyhow!(
+            "[channels_config.{channel}] {key} 中没有可发送的用户，\
+             请先运行 `jarvis channel allow {channel} <用户>`"
+        )
+    };
+    let target = match channel {
+        "telegram" => {
+            let tg = config.telegram.as_ref().ok_or_else(missing)?;
+            // Bots can message a user by numeric id only, and only after
+            // the user has started a chat with them
+            tg.allowed_users
+                .iter()
+                .map(|u| u.trim())
+                .find(|u| !u.is_empty() && u.bytes().all(|b| b.is_ascii_digit()))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Telegram 机器人只能主动发消息给数字用户 ID，\
+                         请先运行 `jarvis channel allow telegram <用户 ID>`"
+                    )
+                })?
+        }
+        "discord" => {
+            let dc = config.discord.as_ref().ok_or_else(missing)?;
+            owner(&dc.allowed_users).ok_or_else(|| no_owner("allowed_users"))?
+        }
+        "slack" => {
+            let sl = config.slack.as_ref().ok_or_else(missing)?;
+            match sl.channel_id.as_deref().map(str::trim) {
+                Some(id) if !id.is_empty() => id,
+                _ => owner(&sl.allowed_users).ok_or_else(|| no_owner("allowed_users"))?,
+            }
+        }
+        "matrix" => config.matrix.as_ref().ok_or_else(missing)?.room_id.trim(),
+        "whatsapp" => {
+            let wa = config.whatsapp.as_ref().ok_or_else(missing)?;
+            owner(&wa.allowed_numbers).ok_or_else(|| no_owner("allowed_numbers"))?
+        }
+        "imessage" => {
+            let im = config.imessage.as_ref().ok_or_else(missing)?;
+            owner(&im.allowed_contacts).ok_or_else(|| no_owner("allowed_contacts"))?
+        }
+        "irc" => {
+            config.irc.as_ref().ok_or_else(missing)?;
+            bail!("IRC 只能在保持连接时发送消息，请用 `jarvis channel start` 验证");
+        }
+        "webhook" => {
+            config.webhook.as_ref().ok_or_else(missing)?;
+            bail!("Webhook 通道只接收消息，没有可测试的发送路径");
+        }
+        other => bail!("不支持的通道「{other}」（可选 {}）", CHANNELS.join("、")),
+    };
+    if target.is_empty() {
+        bail!("[channels_config.{channel}] 没有可发送的目标");
+    }
+    Ok(target.to_string())
+}
+
+/// The channel's credential check from the onboarding wizard; `None` for
+/// channels without one. Blocking.
+fn check_credentials(config: &ChannelsConfig, channel: &str) -> Option<Result<String>> {
+    match channel {
+        "telegram" => config
+            .telegram
+            .as_ref()
+            .map(|tg| probe::check_telegram(&tg.bot_token).map(|name| format!("@{name}"))),
+        "discord" => config
+            .discord
+            .as_ref()
+            .map(|dc| probe::check_discord(&dc.bot_token)),
+        "slack" => config
+            .slack
+            .as_ref()
+            .map(|sl| probe::check_slack(&sl.bot_token)),
+        "matrix" => config
+            .matrix
+            .as_ref()
+            .map(|mx| probe::check_matrix(&mx.homeserver, &mx.access_token)),
+        "whatsapp" => config.whatsapp.as_ref().map(|wa| {
+            probe::check_whatsapp(&wa.access_token, &wa.phone_number_id)
+                .map(|()| wa.phone_number_id.clone())
+        }),
+        _ => None,
+    }
+}
+
+/// Send [`TEST_MESSAGE`] to `recipient` through `channel`.
+async fn send(config: &ChannelsConfig, channel: &str, recipient: &str) -> Result<()> {
+    let ch: Box<dyn Channel> = match channel {
+        "telegram" => {
+            let tg = config.telegram.as_ref().context("未配置 Telegram")?;
+            Box::new(TelegramChannel::new(
+                tg.bot_token.clone(),
+                tg.allowed_users.clone(),
+            ))
+        }
+        "discord" => {
+            let dc = config.discord.as_ref().context("未配置 Discord")?;
+            let discord = DiscordChannel::from_config(dc);
+            // Discord posts to channels; the owner is reached in their DMs
+            let dm = discord.dm_channel(recipient).await?;
+            return discord.send(TEST_MESSAGE, &dm).await;
+        }
+        "slack" => Box::new(SlackChannel::from_config(
+            config.slack.as_ref().context("未配置 Slack")?,
+        )),
+        "matrix" => {
+            let mx = config.matrix.as_ref().context("未配置 Matrix")?;
+            Box::new(MatrixChannel::new(
+                mx.homeserver.clone(),
+                mx.access_token.clone(),
+                mx.room_id.clone(),
+                mx.allowed_users.clone(),
+            ))
+        }
+        "whatsapp" => {
+            let wa = config.whatsapp.as_ref().context("未配置 WhatsApp")?;
+            Box::new(WhatsAppChannel::new(
+                wa.access_token.clone(),
+                wa.phone_number_id.clone(),
+                wa.verify_token.clone(),
+                wa.allowed_numbers.clone(),
+            ))
+        }
+        "imessage" => {
+            let im = config.imessage.as_ref().context("未配置 iMessage")?;
+            Box::new(IMessageChannel::new(im.allowed_contacts.clone()))
+        }
+        other => bail!("通道「{other}」不支持发送测试消息"),
+    };
+    ch.send(TEST_MESSAGE, recipient).await
+}
+
+/// `jarvis channel test <name>`.
+pub async fn run(config: &Config, name: &str) -> Result<()> {
+    let channel = name.trim().to_ascii_lowercase();
+    let recipient = recipient(&config.channels_config, &channel)?;
+
+    let channels = config.channels_config.clone();
+    let probe_channel = channel.clone();
+    let checked = tokio::task::spawn_blocking(move || check_credentials(&channels, &probe_channel))
+        .await
+        .context("凭据校验任务异常退出")?;
+    match checked {
+        Some(Ok(identity)) => println!("✅ {channel} 凭据有效（{identity}）"),
+        Some(Err(e)) => bail!("{channel} 凭据校验失败: {e:#}"),
+        None => {}
+    }
+
+    println!("📤 正在通过 {channel} 发送测试消息到 {recipient}...");
+    match tokio::time::timeout(
+        SEND_TIMEOUT,
+        send(&config.channels_config, &channel, &recipient),
+    )
+    .await
+    {
+        Ok(Ok(())) => {
+            println!("✅ 已发送「{TEST_MESSAGE}」，请在 {channel} 中查看");
+            Ok(())
+        }
+        Ok(Err(e)) => bail!("{channel} 发送失败: {e:#}"),
+        Err(_) => bail!("{channel} 发送超时（{} 秒）", SEND_TIMEOUT.as_secs()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::schema::{IrcConfig, WhatsAppConfig};
+    use crate::config::{MatrixConfig, SlackConfig, TelegramConfig};
+
+    fn error(config: &ChannelsConfig, channel: &str) -> String {
+        recipient(config, channel).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn the_default_target_is_the_configured_room_or_the_owner() {
+        let mut config = ChannelsConfig {
+            telegram: Some(TelegramConfig {
+                bot_token: "t".into(),
+                allowed_users: vec!["*".into(), "alice_bot".into(), "123456".into()],
+            }),
+            ..ChannelsConfig::default()
+        };
+        config.slack = Some(SlackConfig {
+            bot_token: "xoxb".into(),
+            app_token: None,
+            channel_id: None,
+            allowed_users: vec!["*".into(), "U024BE7LH".into()],
+        });
+        config.matrix = Some(MatrixConfig {
+            homeserver: "https://matrix.org".into(),
+            access_token: "syt".into(),
+            room_id: "!room:matrix.org".into(),
+            allowed_users: vec![],
+        });
+        config.whatsapp = Some(WhatsAppConfig {
+            access_token: "EAAB".into(),
+            phone_number_id: "123".into(),
+            verify_token: "v".into(),
+            app_secret: None,
+            allowed_numbers: vec!["+15551234567".into()],
+        });
+
+        assert_eq!(recipient(&config, "telegram").unwrap(), "123456");
+        assert_eq!(recipient(&config, "slack").unwrap(), "U024BE7LH");
+        assert_eq!(recipient(&config, "matrix").unwrap(), "!room:matrix.org");
+        assert_eq!(recipient(&config, "whatsapp").unwrap(), "+15551234567");
+
+        config.slack.as_mut().unwrap().channel_id = Some("C024BE91L".into());
+        assert_eq!(recipient(&config, "slack").unwrap(), "C024BE91L");
+    }
+
+    #[test]
+    fn unusable_channels_are_explained() {
+        let mut config = ChannelsConfig::default();
+        assert!(error(&config, "discord").contains("未配置 [channels_config.discord]"));
+        assert!(error(&config, "signal").contains("不支持的通道"));
+
+        config.telegram = Some(TelegramConfig {
+            bot_token: "t".into(),
+            allowed_users: vec!["alice_bot".into()],
+        });
+        assert!(error(&config, "telegram").contains("数字用户 ID"));
+
+        config.whatsapp = Some(WhatsAppConfig {
+            access_token: "EAAB".into(),
+            phone_number_id: "123".into(),
+            verify_token: "v".into(),
+            app_secret: None,
+            allowed_numbers: vec!["*".into()],
+        });
+        assert!(error(&config, "whatsapp").contains("jarvis channel allow whatsapp"));
+
+        config.irc = Some(IrcConfig {
+            server: "irc.libera.chat".into(),
+            port: 6697,
+            nickname: "jarvis".into(),
+            username: None,
+            channels: vec!["#jarvis".into()],
+            allowed_users: vec!["alice".into()],
+            server_password: None,
+            nickserv_password: None,
+            sasl_password: None,
+            verify_tls: None,
+        });
+        assert!(error(&config, "irc").contains("jarvis channel start"));
+    }
+}
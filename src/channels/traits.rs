@@ -22,6 +22,12 @@ pub trait Channel: Send + Sync {
     /// Start listening for incoming messages (long-running)
     async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()>;
 
+    /// Show a "typing…" indicator to `recipient` while a reply is prepared.
+    /// Indicators expire on their own, so callers repeat this every few seconds.
+    async fn start_typing(&self, _recipient: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     /// Check if channel is healthy
     async fn health_check(&self) -> bool {
         true
@@ -1,6 +1,33 @@
+use super::allowlist::RejectionSink;
 use super::traits::{Channel, ChannelMessage};
 use async_trait::async_trait;
-use uuid::Uuid;
+
+const GRAPH_API: &str = "https://graph.facebook.com/v18.0";
+
+/// Message types dropped without a reply (a reply to a reaction would be noise).
+const SILENT_TYPES: &[&str] = &["reaction", "system"];
+
+/// A message from an allowed number, as delivered by the webhook.
+#[derive(Debug, Clone)]
+pub enum Inbound {
+    Text(ChannelMessage),
+    /// Image, audio, location, …: answered with a notice instead of the agent
+    Unsupported {
+        id: String,
+        sender: String,
+        kind: String,
+    },
+}
+
+impl Inbound {
+    /// The `WhatsApp` message id (`wamid.…`), empty if the payload had none.
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Text(msg) => &msg.id,
+            Self::Unsupported { id, .. } => id,
+        }
+    }
+}
 
 /// `WhatsApp` channel — uses `WhatsApp` Business Cloud API
 ///
@@ -13,7 +40,10 @@ pub struct WhatsAppChannel {
     phone_number_id: String,
     verify_token: String,
     allowed_numbers: Vec<String>,
+    /// Where senders the allowlist turns away are reported
+    rejections: Option<RejectionSink>,
     client: reqwest::Client,
+    api_base: String,
 }
 
 impl WhatsAppChannel {
@@ -28,10 +58,25 @@ impl WhatsAppChannel {
             phone_number_id,
             verify_token,
             allowed_numbers,
+            rejections: None,
             client: reqwest::Client::new(),
+            api_base: GRAPH_API.to_string(),
         }
     }
 
+    /// Report senders the allowlist turns away to `sink`.
+    pub fn with_rejections(mut self, sink: Option<RejectionSink>) -> Self {
+        self.rejections = sink;
+        self
+    }
+
+    /// Point the Graph API calls at a mock server.
+    #[cfg(test)]
+    pub(crate) fn with_api_base(mut self, api_base: &str) -> Self {
+        self.api_base = api_base.trim_end_matches('/').to_string();
+        self
+    }
+
     /// Check if a phone number is allowed (E.164 format: +1234567890)
     fn is_number_allowed(&self, phone: &str) -> bool {
         self.allowed_numbers.iter().any(|n| n == "*" || n == phone)
@@ -42,8 +87,20 @@ impl WhatsAppChannel {
         &self.verify_token
     }
 
-    /// Parse an incoming webhook payload from Meta and extract messages
+    /// Parse an incoming webhook payload from Meta and extract text messages
     pub fn parse_webhook_payload(&self, payload: &serde_json::Value) -> Vec<ChannelMessage> {
+        self.parse_inbound(payload)
+            .into_iter()
+            .filter_map(|inbound| match inbound {
+                Inbound::Text(msg) => Some(msg),
+                Inbound::Unsupported { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Parse an incoming webhook payload from Meta: text messages, and the
+    /// senders of message types the agent can't read
+    pub fn parse_inbound(&self, payload: &serde_json::Value) -> Vec<Inbound> {
         let mut messages = Vec::new();
 
         // WhatsApp Cloud API webhook structure:
@@ -85,9 +142,18 @@ impl WhatsAppChannel {
                             "WhatsApp: 忽略未授权号码的消息: {normalized_from}。\
                             请在 config.toml 中添加到 allowed_numbers，然后运行 `jarvis onboard --channels-only`。"
                         );
+                        if let Some(sink) = &self.rejections {
+                            sink.report("whatsapp", &normalized_from);
+                        }
                         continue;
                     }
 
+                    let id = msg
+                        .get("id")
+                        .and_then(|i| i.as_str())
+                        .unwrap_or("")
+                        .to_string();
+
                     // Extract text content (support text messages only for now)
                     let content = if let Some(text_obj) = msg.get("text") {
                         text_obj
@@ -96,8 +162,19 @@ impl WhatsAppChannel {
                             .unwrap_or("")
                             .to_string()
                     } else {
-                        // Could be image, audio, etc. — skip for now
-                        tracing::debug!("WhatsApp: 跳过来自 {from} 的非文本消息");
+                        // Image, audio, etc. — the sender gets a notice
+                        let kind = msg
+                            .get("type")
+                            .and_then(|t| t.as_str())
+                            .unwrap_or("unknown");
+                        tracing::debug!("WhatsApp: 收到来自 {from} 的非文本消息（{kind}）");
+                        if !SILENT_TYPES.contains(&kind) {
+                            messages.push(Inbound::Unsupported {
+                                id,
+                                sender: normalized_from,
+                                kind: kind.to_string(),
+                            });
+                        }
                         continue;
                     };
 
@@ -117,13 +194,13 @@ impl WhatsAppChannel {
                                 .as_secs()
                         });
 
-                    messages.push(ChannelMessage {
-                        id: Uuid::new_v4().to_string(),
+                    messages.push(Inbound::Text(ChannelMessage {
+                        id,
                         sender: normalized_from,
                         content,
                         channel: "whatsapp".to_string(),
                         timestamp,
-                    });
+                    }));
                 }
             }
         }
@@ -140,10 +217,7 @@ impl Channel for WhatsAppChannel {
 
     async fn send(&self, message: &str, recipient: &str) -> anyhow::Result<()> {
         // WhatsApp Cloud API: POST to /v18.0/{phone_number_id}/messages
-        let url = format!(
-            "https://graph.facebook.com/v18.0/{}/messages",
-            self.phone_number_id
-        );
+        let url = format!("{}/{}/messages", self.api_base, self.phone_number_id);
 
         // Normalize recipient (remove leading + if present for API)
         let to = recipient.strip_prefix('+').unwrap_or(recipient);
@@ -195,7 +269,7 @@ impl Channel for WhatsAppChannel {
 
     async fn health_check(&self) -> bool {
         // Check if we can reach the WhatsApp API
-        let url = format!("https://graph.facebook.com/v18.0/{}", self.phone_number_id);
+        let url = format!("{}/{}", self.api_base, self.phone_number_id);
 
         self.client
             .get(&url)
@@ -854,6 +928,36 @@ mod tests {
         assert!(msgs.is_empty());
     }
 
+    #[test]
+    fn whatsapp_inbound_reports_unsupported_types_from_allowed_numbers() {
+        let ch = make_channel();
+        let message = |from: &str, kind: &str| serde_json::json!({ "from": from, "timestamp": "1", "type": kind, kind: {} });
+        let payload = serde_json::json!({
+            "entry": [{
+                "changes": [{
+                    "value": {
+                        "messages": [
+                            message("1234567890", "image"),
+                            message("1234567890", "reaction"),
+                            message("9999999999", "audio"),
+                            { "from": "1234567890", "timestamp": "1", "type": "text",
+                              "text": { "body": "hi" } },
+                        ]
+                    }
+                }]
+            }]
+        });
+
+        let inbound = ch.parse_inbound(&payload);
+        assert_eq!(inbound.len(), 2);
+        assert!(matches!(
+            &inbound[0],
+            Inbound::Unsupported { sender, kind, .. } if sender == "+1234567890" && kind == "image"
+        ));
+        assert!(matches!(&inbound[1], Inbound::Text(msg) if msg.content == "hi"));
+        assert_eq!(ch.parse_webhook_payload(&payload).len(), 1);
+    }
+
     #[test]
     fn whatsapp_parse_mixed_authorized_unauthorized() {
         let ch = WhatsAppChannel::new(
@@ -0,0 +1,323 @@
+//! `jarvis completions <shell>`: shell completion scripts.
+//!
+//! `clap_complete` writes the static part (subcommands, flags, fixed values).
+//! Arguments naming things that only exist at runtime — configured channels,
+//! installed skills, cron jobs — are completed by a snippet added for bash,
+//! zsh, fish and PowerShell that runs the hidden `jarvis __complete <kind>`
+//! and offers what it prints, one value per line.
+
+use crate::config::Config;
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use clap_complete::Shell;
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// What `jarvis __complete` lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Kind {
+    /// Configured channels
+    Channels,
+    /// Installed skills
+    Skills,
+    /// Cron job ids
+    CronJobs,
+}
+
+impl Kind {
+    fn arg(self) -> &'static str {
+        match self {
+            Self::Channels => "channels",
+            Self::Skills => "skills",
+            Self::CronJobs => "cron-jobs",
+        }
+    }
+}
+
+/// Subcommands whose first argument is completed with `__complete`.
+const DYNAMIC: &[(&str, &str, Kind)] = &[
+    ("channel", "remove", Kind::Channels),
+    ("skills", "remove", Kind::Skills),
+    ("cron", "remove", Kind::CronJobs),
+];
+
+/// Write the completion script for `shell` to `out`. `cmd` is the full CLI.
+pub fn write(cmd: &mut clap::Command, shell: Shell, out: &mut dyn Write) -> Result<()> {
+    let name = cmd.get_name().to_string();
+    let mut script = Vec::new();
+    clap_complete::generate(shell, cmd, &name, &mut script);
+    let mut script = String::from_utf8(script)?;
+
+    // Global options taking a value, skipped when finding the subcommand
+    let valued: Vec<String> = cmd
+        .get_arguments()
+        .filter(|a| a.is_global_set() && a.get_action().takes_values())
+        .filter_map(|a| a.get_long().map(|l| format!("--{l}")))
+        .collect();
+    match shell {
+        Shell::Bash => script.push_str(&bash(&name, &valued)),
+        Shell::Zsh => script = zsh(&script, &name, &valued)?,
+        Shell::Fish => script.push_str(&fish(&name)),
+        Shell::PowerShell => script = powershell(&script, &name)?,
+        _ => {}
+    }
+    out.write_all(script.as_bytes())?;
+    Ok(())
+}
+
+/// `case` arms mapping the subcommand path to its kind, in sh syntax.
+fn case_arms(indent: &str) -> String {
+    let mut arms = String::new();
+    for (cmd, sub, kind) in DYNAMIC {
+        let _ = writeln!(arms, "{indent}\"{cmd} {sub}\") kind={} ;;", kind.arg());
+    }
+    arms
+}
+
+/// Wraps the generated `_<name>` function and registers the wrapper.
+fn bash(name: &str, valued: &[String]) -> String {
+    format!(
+        r#"
+_{name}_dynamic() {{
+    local i word skip=0 profile="" path="" kind=""
+    for (( i = 1; i < COMP_CWORD; i++ )); do
+        word="${{COMP_WORDS[i]}}"
+        if (( skip )); then
+            [[ "${{COMP_WORDS[i-1]}}" == --profile ]] && profile="$word"
+            skip=0
+            continue
+        fi
+        case "$word" in
+            {valued}) skip=1 ;;
+            -*) ;;
+            *) path="${{path:+$path }}$word" ;;
+        esac
+    done
+    case "$path" in
+{arms}    esac
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    if [[ -n "$kind" && "$cur" != -* ]]; then
+        COMPREPLY=( $(compgen -W "$("$1" ${{profile:+--profile "$profile"}} __complete "$kind" 2>/dev/null)" -- "$cur") )
+        return 0
+    fi
+    _{name} "$@"
+}}
+complete -F _{name}_dynamic -o bashdefault -o default {name}
+"#,
+        valued = valued.join("|"),
+        arms = case_arms("        "),
+    )
+}
+
+/// Replaces the generated registration with one for a wrapper around
+/// `_<name>`, also when the file is autoloaded from `fpath`.
+fn zsh(script: &str, name: &str, valued: &[String]) -> Result<String> {
+    let tail = format!(
+        "if [ \"$funcstack[1]\" = \"_{name}\" ]; then\n    _{name} \"$@\"\nelse\n    compdef _{name} {name}\nfi\n"
+    );
+    let Some(head) = script.strip_suffix(&tail) else {
+        bail!("zsh 补全脚本的格式与预期不符");
+    };
+    Ok(format!(
+        r#"{head}_{name}_dynamic() {{
+    local word skip=0 profile kind
+    local -a path_
+    for word in "${{(@)words[2,CURRENT-1]}}"; do
+        if (( skip )); then
+            profile=$word
+            skip=0
+            continue
+        fi
+        case $word in
+            {valued}) skip=1 ;;
+            -*) ;;
+            *) path_+=("$word") ;;
+        esac
+    done
+    case "${{path_[*]}}" in
+{arms}    esac
+    if [[ -n $kind && $PREFIX != -* ]]; then
+        local -a values
+        values=(${{(f)"$($words[1] ${{profile:+--profile $profile}} __complete $kind 2>/dev/null)"}})
+        compadd -a values
+        return
+    fi
+    _{name} "$@"
+}}
+
+compdef _{name}_dynamic {name}
+if [ "$funcstack[1]" = "_{name}" ]; then
+    _{name}_dynamic "$@"
+fi
+"#,
+        valued = valued.join("|"),
+        arms = case_arms("        "),
+    ))
+}
+
+/// Fish merges completions, so extra `complete` lines are enough.
+fn fish(name: &str) -> String {
+    let mut lines = String::from("\n");
+    for (cmd, sub, kind) in DYNAMIC {
+        let _ = writeln!(
+            lines,
+            "complete -c {name} -n \"__fish_seen_subcommand_from {cmd}; and __fish_seen_subcommand_from {sub}\" -f -a \"({name} __complete {} 2>/dev/null)\"",
+            kind.arg()
+        );
+    }
+    lines
+}
+
+/// Adds the values to the generated `switch` arm of each subcommand.
+fn powershell(script: &str, name: &str) -> Result<String> {
+    let mut script = script.to_string();
+    for (cmd, sub, kind) in DYNAMIC {
+        let arm = format!("'{name};{cmd};{sub}' {{\n");
+        let Some(at) = script.find(&arm) else {
+            bail!("PowerShell 补全脚本中没有 {cmd} {sub}");
+        };
+        script.insert_str(
+            at + arm.len(),
+            &format!(
+                "            & '{name}' __complete {} 2>$null | ForEach-Object {{\n                [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $_)\n            }}\n",
+                kind.arg()
+            ),
+        );
+    }
+    Ok(script)
+}
+
+/// The current values of `kind`, for `jarvis __complete`.
+pub fn values(kind: Kind, config: &Config) -> Vec<String> {
+    match kind {
+        Kind::Channels => {
+            let c = &config.channels_config;
+            [
+                ("telegram", c.telegram.is_some()),
+                ("discord", c.discord.is_some()),
+                ("slack", c.slack.is_some()),
+                ("webhook", c.webhook.is_some()),
+                ("imessage", c.imessage.is_some()),
+                ("matrix", c.matrix.is_some()),
+                ("whatsapp", c.whatsapp.is_some()),
+                ("irc", c.irc.is_some()),
+            ]
+            .into_iter()
+            .filter(|(_, configured)| *configured)
+            .map(|(name, _)| name.to_string())
+            .collect()
+        }
+        Kind::Skills => {
+            let dir = crate::skills::skills_dir(&config.workspace_dir);
+            let mut names: Vec<String> = std::fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .filter(|e| e.path().is_dir())
+                .filter_map(|e| e.file_name().into_string().ok())
+                .filter(|n| !n.starts_with('.'))
+                .collect();
+            names.sort();
+            names
+        }
+        Kind::CronJobs => {
+            // Don't create the database just to complete a command line
+            if !config.workspace_dir.join("cron/jobs.db").exists() {
+                return Vec::new();
+            }
+            crate::cron::list_jobs(config)
+                .map(|jobs| jobs.into_iter().map(|j| j.id).collect())
+                .unwrap_or_default()
+        }
+    }
+}
+
+/// `jarvis __complete <kind>`: print the values, or nothing when the
+/// config can't be loaded; a completion must never print an error.
+pub fn print_values(kind: Kind) {
+    let Ok(path) = Config::default_path() else {
+        return;
+    };
+    if !path.exists() {
+        return;
+    }
+    let Ok(config) = Config::load_from(&path) else {
+        return;
+    };
+    for value in values(kind, &config) {
+        println!("{value}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, ArgAction, Command};
+
+    /// A stand-in with the same shape as the real CLI.
+    fn cli() -> Command {
+        let sub = |name: &'static str, arg: &'static str| {
+            Command::new(name).subcommand(Command::new("remove").arg(Arg::new(arg).required(true)))
+        };
+        Command::new("jarvis")
+            .arg(
+                Arg::new("profile")
+                    .long("profile")
+                    .global(true)
+                    .action(ArgAction::Set),
+            )
+            .subcommand(sub("channel", "name"))
+            .subcommand(sub("skills", "name"))
+            .subcommand(sub("cron", "id"))
+    }
+
+    fn script(shell: Shell) -> String {
+        let mut out = Vec::new();
+        write(&mut cli(), shell, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn every_shell_gets_a_script_with_the_dynamic_values() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let script = script(shell);
+            assert!(script.contains(" __complete "), "{shell}");
+            for kind in ["channels", "skills", "cron-jobs"] {
+                assert!(script.contains(kind), "{shell}: {kind}");
+            }
+        }
+        let bash = script(Shell::Bash);
+        assert!(bash.contains("\"channel remove\") kind=channels ;;"));
+        assert!(bash.contains("--profile) skip=1 ;;"));
+        assert!(bash.ends_with("complete -F _jarvis_dynamic -o bashdefault -o default jarvis\n"));
+        let zsh = script(Shell::Zsh);
+        assert!(zsh.starts_with("#compdef jarvis"));
+        assert!(zsh.contains("compdef _jarvis_dynamic jarvis"));
+        assert!(!zsh.contains("compdef _jarvis jarvis"));
+        assert!(script(Shell::PowerShell)
+            .contains("'jarvis;cron;remove' {\n            & 'jarvis' __complete cron-jobs"));
+    }
+
+    #[test]
+    fn values_list_configured_channels_and_installed_skills() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut config = Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..Config::default()
+        };
+        config.channels_config.telegram = Some(crate::config::TelegramConfig {
+            bot_token: "t".into(),
+            allowed_users: vec![],
+        });
+        assert_eq!(values(Kind::Channels, &config), ["telegram"]);
+
+        for dir in ["weather", "git-helper", ".hidden"] {
+            std::fs::create_dir_all(tmp.path().join("skills").join(dir)).unwrap();
+        }
+        std::fs::write(tmp.path().join("skills/README.md"), "").unwrap();
+        assert_eq!(values(Kind::Skills, &config), ["git-helper", "weather"]);
+
+        assert!(values(Kind::CronJobs, &config).is_empty());
+        assert!(!tmp.path().join("cron").exists());
+    }
+}
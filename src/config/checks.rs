@@ -0,0 +1,605 @@
+//! Semantic checks on a loaded [`Config`]: values that deserialize fine but
+//! name a provider, backend or tunnel that doesn't exist, or contradict each
+//! other. Shared by `jarvis config validate`, [`Config::load_or_init`] and
+//! `jarvis doctor`.
+
+use super::Config;
+use crate::security::commands::CommandPattern;
+use std::collections::HashSet;
+
+/// Memory backends `memory.backend` accepts.
+pub const MEMORY_BACKENDS: &[&str] = &["sqlite", "markdown", "postgres", "none"];
+
+/// Tunnel providers `tunnel.provider` accepts.
+pub const TUNNEL_PROVIDERS: &[&str] = &["none", "cloudflare", "tailscale", "ngrok", "custom"];
+
+/// Whether a [`Problem`] stops the config from working as written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One problem found in a config. `path` is the dotted key it concerns, or
+/// empty for problems with the whole file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub path: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Problem {
+    pub fn error(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    pub fn warning(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl Config {
+    /// Check the values in this config against what the rest of jarvis
+    /// accepts. Errors would make a command fail or silently behave
+    /// differently from what the file says; warnings are likely mistakes.
+    pub fn check(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        check_providers(self, &mut problems);
+        check_memory(self, &mut problems);
+        check_observability(self, &mut problems);
+        if let Err(e) = crate::runtime::validate(&self.runtime) {
+            let path = match self.runtime.kind.as_str() {
+                "docker" => "runtime.docker",
+                "sandbox" => "runtime.sandbox",
+                _ => "runtime.kind",
+            };
+            problems.push(Problem::error(path, e.to_string()));
+        }
+        check_workspace(self, &mut problems);
+        check_channels(self, &mut problems);
+        check_tunnel(self, &mut problems);
+        check_command_patterns(self, &mut problems);
+        problems
+    }
+}
+
+fn check_providers(config: &Config, problems: &mut Vec<Problem>) {
+    if let Some(name) = &config.default_provider {
+        check_provider_name("default_provider", name, problems);
+    }
+    if let Some(model) = &config.default_model
+        && model.trim().is_empty()
+    {
+        problems.push(Problem::error(
+            "default_model",
+            "不能为空；删除这一项以使用默认模型",
+        ));
+    }
+
+    let reliability = &config.reliability;
+    for (i, name) in reliability.fallback_providers.iter().enumerate() {
+        check_provider_name(
+            &format!("reliability.fallback_providers[{i}]"),
+            name,
+            problems,
+        );
+    }
+    let mut unused: Vec<_> = reliability
+        .fallback_models
+        .keys()
+        .filter(|name| !reliability.fallback_providers.contains(name))
+        .collect();
+    unused.sort();
+    for name in unused {
+        problems.push(Problem::warning(
+            format!("reliability.fallback_models.{name}"),
+            "不在 reliability.fallback_providers 中，不会被使用",
+        ));
+    }
+}
+
+fn check_provider_name(path: &str, name: &str, problems: &mut Vec<Problem>) {
+    if name.trim().is_empty() {
+        problems.push(Problem::error(path, "Provider 名称不能为空"));
+        return;
+    }
+    for prefix in ["custom:", "anthropic-custom:"] {
+        if let Some(url) = name.strip_prefix(prefix)
+            && !url.is_empty()
+        {
+            match reqwest::Url::parse(url) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                _ => problems.push(Problem::error(
+                    path,
+                    format!("「{url}」不是有效的 http(s) URL"),
+                )),
+            }
+            return;
+        }
+    }
+    if let Err(e) = crate::providers::create_provider(name, None) {
+        let message = e.to_string();
+        problems.push(Problem::error(
+            path,
+            message.lines().next().unwrap_or_default(),
+        ));
+    }
+}
+
+fn check_memory(config: &Config, problems: &mut Vec<Problem>) {
+    let memory = &config.memory;
+    if !MEMORY_BACKENDS.contains(&memory.backend.as_str()) {
+        problems.push(Problem::error(
+            "memory.backend",
+            format!(
+                "未知的记忆后端「{}」，可选值: {}",
+                memory.backend,
+                MEMORY_BACKENDS.join(", ")
+            ),
+        ));
+    } else if memory.backend == "postgres" {
+        if !cfg!(feature = "postgres") {
+            problems.push(Problem::error(
+                "memory.backend",
+                "postgres 后端需要使用 `cargo build --features postgres` 编译的 jarvis",
+            ));
+        }
+        if memory
+            .postgres_url
+            .as_deref()
+            .is_none_or(|url| url.trim().is_empty())
+        {
+            problems.push(Problem::error(
+                "memory.postgres_url",
+                "memory.backend = \"postgres\" 时必须设置",
+            ));
+        }
+    }
+    check_embeddings(memory, problems);
+}
+
+fn check_embeddings(memory: &crate::config::MemoryConfig, problems: &mut Vec<Problem>) {
+    use crate::memory::embeddings::{EMBEDDING_PROVIDERS, HASHED_MODEL};
+
+    let provider = memory.embedding_provider.as_str();
+    let url = ["custom:", "local:"]
+        .iter()
+        .find_map(|prefix| provider.strip_prefix(prefix));
+    if let Some(url) = url {
+        if !matches!(reqwest::Url::parse(url), Ok(url) if matches!(url.scheme(), "http" | "https"))
+        {
+            problems.push(Problem::error(
+                "memory.embedding_provider",
+                format!("「{url}」不是有效的 http(s) URL"),
+            ));
+        }
+    } else if !EMBEDDING_PROVIDERS.contains(&provider) {
+        problems.push(Problem::error(
+            "memory.embedding_provider",
+            format!(
+                "未知的 embedding 来源「{provider}」，可选值: {}、local:URL、custom:URL",
+                EMBEDDING_PROVIDERS.join(", ")
+            ),
+        ));
+        return;
+    }
+    if matches!(provider, "none" | "noop") {
+        return;
+    }
+    if memory.embedding_dimensions == 0 && provider != "hashed" {
+        problems.push(Problem::error(
+            "memory.embedding_dimensions",
+            "必须大于 0，且与模型输出的向量维度一致",
+        ));
+    }
+    if provider == "hashed" && memory.embedding_model != HASHED_MODEL {
+        problems.push(Problem::warning(
+            "memory.embedding_model",
+            format!(
+                "hashed 只有模型「{HASHED_MODEL}」，「{}」将被忽略",
+                memory.embedding_model
+            ),
+        ));
+    }
+    if (provider == "local" || provider.starts_with("local:"))
+        && memory.embedding_model.starts_with("text-embedding-")
+    {
+        problems.push(Problem::warning(
+            "memory.embedding_model",
+            format!(
+                "「{}」是 OpenAI 的模型；local 使用 Ollama 中的模型名，如 nomic-embed-text",
+                memory.embedding_model
+            ),
+        ));
+    }
+}
+
+fn check_observability(config: &Config, problems: &mut Vec<Problem>) {
+    let observability = &config.observability;
+    if observability.backend == "otel" && !cfg!(feature = "otel") {
+        problems.push(Problem::error(
+            "observability.backend",
+            "otel 后端需要使用 `cargo build --features otel` 编译的 jarvis",
+        ));
+    }
+    if let Some(endpoint) = &observability.otel_endpoint
+        && !matches!(reqwest::Url::parse(endpoint.trim()), Ok(url) if matches!(url.scheme(), "http" | "https"))
+    {
+        problems.push(Problem::error(
+            "observability.otel_endpoint",
+            format!("「{endpoint}」不是有效的 http(s) URL（如 http://localhost:4318）"),
+        ));
+    }
+}
+
+fn check_workspace(config: &Config, problems: &mut Vec<Problem>) {
+    let dir = &config.workspace_dir;
+    match std::fs::metadata(dir) {
+        Ok(meta) if !meta.is_dir() => problems.push(Problem::error(
+            "workspace_dir",
+            format!("{} 不是目录", dir.display()),
+        )),
+        Ok(meta) if meta.permissions().readonly() => problems.push(Problem::error(
+            "workspace_dir",
+            format!("{} 是只读的", dir.display()),
+        )),
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            // Created on first use, as long as some ancestor can hold it
+            let creatable = dir
+                .ancestors()
+                .skip(1)
+                .find(|a| a.exists())
+                .is_some_and(std::path::Path::is_dir);
+            if creatable {
+                problems.push(Problem::warning(
+                    "workspace_dir",
+                    format!("{} 不存在，首次使用时会创建", dir.display()),
+                ));
+            } else {
+                problems.push(Problem::error(
+                    "workspace_dir",
+                    format!("{} 不存在且无法创建", dir.display()),
+                ));
+            }
+        }
+        Err(e) => problems.push(Problem::error(
+            "workspace_dir",
+            format!("无法访问 {}：{e}", dir.display()),
+        )),
+    }
+}
+
+fn check_channels(config: &Config, problems: &mut Vec<Problem>) {
+    let channels = &config.channels_config;
+    let mut lists: Vec<(&str, &[String])> = Vec::new();
+    if let Some(c) = &channels.telegram {
+        lists.push(("telegram.allowed_users", &c.allowed_users));
+    }
+    if let Some(c) = &channels.discord {
+        lists.push(("discord.allowed_users", &c.allowed_users));
+    }
+    if let Some(c) = &channels.slack {
+        lists.push(("slack.allowed_users", &c.allowed_users));
+    }
+    if let Some(c) = &channels.imessage {
+        lists.push(("imessage.allowed_contacts", &c.allowed_contacts));
+    }
+    if let Some(c) = &channels.matrix {
+        lists.push(("matrix.allowed_users", &c.allowed_users));
+    }
+    if let Some(c) = &channels.whatsapp {
+        lists.push(("whatsapp.allowed_numbers", &c.allowed_numbers));
+    }
+    if let Some(c) = &channels.irc {
+        lists.push(("irc.allowed_users", &c.allowed_users));
+    }
+
+    for (key, entries) in lists {
+        let path = format!("channels_config.{key}");
+        if entries.is_empty() {
+            problems.push(Problem::warning(&path, "为空，这个通道会拒绝所有消息"));
+            continue;
+        }
+        if entries.len() > 1 && entries.iter().any(|e| e == "*") {
+            problems.push(Problem::warning(
+                &path,
+                "已包含 \"*\"（允许所有人），其余条目没有作用",
+            ));
+        }
+        let mut seen = HashSet::new();
+        for (i, entry) in entries.iter().enumerate() {
+            let entry_path = format!("{path}[{i}]");
+            if let Some(message) = allowlist_entry_problem(key, entry) {
+                problems.push(Problem::error(entry_path, message));
+            } else if !seen.insert(entry) {
+                problems.push(Problem::warning(entry_path, format!("「{entry}」重复")));
+            }
+        }
+    }
+}
+
+/// Why `entry` can never match a sender on the channel behind `key`, if it can't.
+fn allowlist_entry_problem(key: &str, entry: &str) -> Option<String> {
+    if entry.trim().is_empty() {
+        return Some("条目不能为空".into());
+    }
+    if entry.trim() != entry {
+        return Some(format!("「{entry}」包含首尾空白，不会匹配任何发送者"));
+    }
+    if entry == "*" {
+        return None;
+    }
+    if key.starts_with("telegram.") && entry.starts_with('@') {
+        return Some(format!(
+            "「{entry}」不要带 @，请写成「{}」",
+            entry.trim_start_matches('@')
+        ));
+    }
+    if key.starts_with("whatsapp.") {
+        let digits = entry.strip_prefix('+').unwrap_or("");
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Some(format!(
+                "「{entry}」不是 E.164 格式的号码（如 +8613800138000）"
+            ));
+        }
+    }
+    None
+}
+
+fn check_tunnel(config: &Config, problems: &mut Vec<Problem>) {
+    let tunnel = &config.tunnel;
+    let provider = tunnel.provider.as_str();
+    if !provider.is_empty() && !TUNNEL_PROVIDERS.contains(&provider) {
+        problems.push(Problem::error(
+            "tunnel.provider",
+            format!(
+                "未知的隧道类型「{provider}」，可选值: {}",
+                TUNNEL_PROVIDERS.join(", ")
+            ),
+        ));
+        return;
+    }
+
+    let sections = [
+        ("cloudflare", tunnel.cloudflare.is_some()),
+        ("tailscale", tunnel.tailscale.is_some()),
+        ("ngrok", tunnel.ngrok.is_some()),
+        ("custom", tunnel.custom.is_some()),
+    ];
+    for (name, present) in sections {
+        if present && name != provider {
+            problems.push(Problem::warning(
+                format!("tunnel.{name}"),
+                format!("已配置，但 tunnel.provider 为「{provider}」，不会被使用"),
+            ));
+        }
+    }
+
+    let missing = |name: &str| {
+        Problem::error(
+            format!("tunnel.{name}"),
+            format!("tunnel.provider = \"{name}\" 时必须配置 [tunnel.{name}]"),
+        )
+    };
+    match provider {
+        "cloudflare" => match &tunnel.cloudflare {
+            None => problems.push(missing("cloudflare")),
+            Some(cf) if cf.token.trim().is_empty() => {
+                problems.push(Problem::error("tunnel.cloudflare.token", "不能为空"));
+            }
+            Some(_) => {}
+        },
+        "ngrok" => match &tunnel.ngrok {
+            None => problems.push(missing("ngrok")),
+            Some(ng) if ng.auth_token.trim().is_empty() => {
+                problems.push(Problem::error("tunnel.ngrok.auth_token", "不能为空"));
+            }
+            Some(_) => {}
+        },
+        "custom" => match &tunnel.custom {
+            None => problems.push(missing("custom")),
+            Some(custom) if custom.start_command.trim().is_empty() => {
+                problems.push(Problem::error("tunnel.custom.start_command", "不能为空"));
+            }
+            Some(_) => {}
+        },
+        _ => {}
+    }
+}
+
+fn check_command_patterns(config: &Config, problems: &mut Vec<Problem>) {
+    let autonomy = &config.autonomy;
+    for (field, patterns) in [
+        ("allowed_commands", &autonomy.allowed_commands),
+        ("denied_commands", &autonomy.denied_commands),
+        ("destructive_commands", &autonomy.destructive_commands),
+    ] {
+        for (i, pattern) in patterns.iter().enumerate() {
+            if let Err(e) = CommandPattern::parse(pattern) {
+                problems.push(Problem::error(
+                    format!("autonomy.{field}[{i}]"),
+                    format!("{e:#}"),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::schema::{CloudflareTunnelConfig, TelegramConfig, WhatsAppConfig};
+
+    fn config_in(dir: &std::path::Path) -> Config {
+        Config {
+            workspace_dir: dir.to_path_buf(),
+            ..Config::default()
+        }
+    }
+
+    fn paths(problems: &[Problem], severity: Severity) -> Vec<&str> {
+        problems
+            .iter()
+            .filter(|p| p.severity == severity)
+            .map(|p| p.path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn default_config_has_no_problems() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert_eq!(config_in(tmp.path()).check(), []);
+    }
+
+    #[test]
+    fn reports_unknown_names_and_empty_values() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut config = config_in(tmp.path());
+        config.default_provider = Some("openrouterr".into());
+        config.default_model = Some("  ".into());
+        config.reliability.fallback_providers = vec!["anthropic".into(), "custom:ftp://x".into()];
+        config
+            .reliability
+            .fallback_models
+            .insert("openai".into(), "gpt-4o".into());
+        config.memory.backend = "redis".into();
+        config.memory.embedding_provider = "hashed".into();
+        config.observability.otel_endpoint = Some("localhost:4318".into());
+        config.runtime.kind = "cloudflare".into();
+
+        let problems = config.check();
+        assert_eq!(
+            paths(&problems, Severity::Error),
+            [
+                "default_provider",
+                "default_model",
+                "reliability.fallback_providers[1]",
+                "memory.backend",
+                "observability.otel_endpoint",
+                "runtime.kind",
+            ]
+        );
+        assert_eq!(
+            paths(&problems, Severity::Warning),
+            [
+                "reliability.fallback_models.openai",
+                "memory.embedding_model"
+            ]
+        );
+        assert!(problems[0].message.contains("openrouterr"));
+    }
+
+    #[test]
+    fn reports_unknown_and_misconfigured_embedding_providers() {
+        let tmp = tempfile::tempdir().unwrap();
+        let check = |provider: &str, model: &str, dims: usize| {
+            let mut config = config_in(tmp.path());
+            config.memory.embedding_provider = provider.into();
+            config.memory.embedding_model = model.into();
+            config.memory.embedding_dimensions = dims;
+            config
+                .check()
+                .into_iter()
+                .map(|p| format!("{:?} {}", p.severity, p.path))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            check("local", "nomic-embed-text", 768),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            check("local:http://gpu-box:11434", "bge-m3", 1024),
+            Vec::<String>::new()
+        );
+        assert_eq!(check("noop", "", 0), Vec::<String>::new());
+        assert_eq!(
+            check("fastembed", "nomic-embed-text", 768),
+            ["Error memory.embedding_provider"]
+        );
+        assert_eq!(
+            check("local:gpu-box", "nomic-embed-text", 768),
+            ["Error memory.embedding_provider"]
+        );
+        assert_eq!(
+            check("local", "text-embedding-3-small", 0),
+            [
+                "Error memory.embedding_dimensions",
+                "Warning memory.embedding_model"
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_malformed_channel_allowlists() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut config = config_in(tmp.path());
+        config.channels_config.telegram = Some(TelegramConfig {
+            bot_token: "t".into(),
+            allowed_users: vec!["@alice".into(), "bob".into(), "bob".into(), " ".into()],
+        });
+        config.channels_config.whatsapp = Some(WhatsAppConfig {
+            access_token: "t".into(),
+            phone_number_id: "1".into(),
+            verify_token: "v".into(),
+            app_secret: None,
+            allowed_numbers: vec!["*".into(), "+1 555".into()],
+        });
+
+        let problems = config.check();
+        assert_eq!(
+            paths(&problems, Severity::Error),
+            [
+                "channels_config.telegram.allowed_users[0]",
+                "channels_config.telegram.allowed_users[3]",
+                "channels_config.whatsapp.allowed_numbers[1]",
+            ]
+        );
+        assert_eq!(
+            paths(&problems, Severity::Warning),
+            [
+                "channels_config.telegram.allowed_users[2]",
+                "channels_config.whatsapp.allowed_numbers",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_inconsistent_tunnel_and_workspace() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        let mut config = config_in(&file);
+        config.tunnel.provider = "ngrok".into();
+        config.tunnel.cloudflare = Some(CloudflareTunnelConfig {
+            token: "abc".into(),
+        });
+
+        let problems = config.check();
+        assert_eq!(
+            paths(&problems, Severity::Error),
+            ["workspace_dir", "tunnel.ngrok"]
+        );
+        assert_eq!(paths(&problems, Severity::Warning), ["tunnel.cloudflare"]);
+
+        config.workspace_dir = tmp.path().join("later");
+        config.tunnel.provider = "frp".into();
+        let problems = config.check();
+        assert_eq!(paths(&problems, Severity::Error), ["tunnel.provider"]);
+        assert_eq!(paths(&problems, Severity::Warning), ["workspace_dir"]);
+    }
+}
@@ -0,0 +1,613 @@
+//! `jarvis config get|set|unset|validate|restore` — read and edit config.toml
+//! by dotted path (e.g. `autonomy.max_tool_iterations`) without an editor.
+
+use super::env;
+use super::secrets::{self, SECRET_FIELDS};
+use super::{store, unknown, Config};
+use crate::security::SecretStore;
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// Shown in place of a secret unless `--reveal` is passed.
+const MASK: &str = "********";
+
+/// Upper bound on the field errors `validate` collects; each one drops the
+/// offending key and deserializes again.
+const MAX_PROBLEMS: usize = 100;
+
+pub use super::checks::Problem;
+
+/// Handle `jarvis config ...`. Works on `config_path` directly rather than a
+/// loaded config, so a file that fails to load can still be repaired.
+pub fn handle_command(command: crate::ConfigCommands, config_path: &Path) -> Result<()> {
+    match command {
+        crate::ConfigCommands::Get { key, reveal } => {
+            let mut config = load(config_path)?;
+            config.apply_env_overrides();
+            let mut value = get(&config, &key)?;
+            let masked = !reveal && mask_secrets(&mut value, &key, &config) > 0;
+            println!("{}", display(&value));
+            if masked {
+                eprintln!("（密钥已隐藏，使用 --reveal 显示）");
+            }
+            if let Some(o) = config.env.overrides.get(&key) {
+                eprintln!("（来自环境变量 {}）", o.var);
+            } else if let Some(p) = config.env.placeholders.get(&key) {
+                eprintln!("（来自 {}）", p.template);
+            }
+            Ok(())
+        }
+        crate::ConfigCommands::Set { key, value } => {
+            let (updated, backup) = edit(config_path, |config| set(config, &key, &value))?;
+            let mut shown = get(&updated, &key)?;
+            mask_secrets(&mut shown, &key, &updated);
+            println!("✅ {key} = {}", display(&shown));
+            print_backup(backup.as_deref());
+            warn_if_overridden(&updated, &key);
+            Ok(())
+        }
+        crate::ConfigCommands::Unset { key } => {
+            let (updated, backup) = edit(config_path, |config| unset(config, &key))?;
+            match get(&updated, &key) {
+                Ok(mut value) => {
+                    mask_secrets(&mut value, &key, &updated);
+                    println!("✅ 已将 {key} 恢复为默认值：{}", display(&value));
+                }
+                Err(_) => println!("✅ 已删除 {key}"),
+            }
+            print_backup(backup.as_deref());
+            warn_if_overridden(&updated, &key);
+            Ok(())
+        }
+        crate::ConfigCommands::Validate => {
+            let problems = validate(config_path)?;
+            if problems.is_empty() {
+                println!("✅ {} 没有问题", config_path.display());
+                return Ok(());
+            }
+            let (errors, warnings): (Vec<_>, Vec<_>) = problems.iter().partition(|p| p.is_error());
+            for (icon, label, group) in [("❌", "错误", &errors), ("⚠️ ", "警告", &warnings)]
+            {
+                if group.is_empty() {
+                    continue;
+                }
+                println!(
+                    "{icon} {} 中有 {} 个{label}：",
+                    config_path.display(),
+                    group.len()
+                );
+                for p in group {
+                    if p.path.is_empty() {
+                        println!("  - {}", p.message);
+                    } else {
+                        println!("  - {}：{}", p.path, p.message);
+                    }
+                }
+            }
+            if errors.is_empty() {
+                return Ok(());
+            }
+            bail!("配置校验未通过")
+        }
+        crate::ConfigCommands::Restore { backup, list } => {
+            restore(config_path, backup.as_deref(), list)
+        }
+    }
+}
+
+/// `jarvis config restore`: list the backups, or put one back.
+fn restore(config_path: &Path, backup: Option<&str>, list: bool) -> Result<()> {
+    if list {
+        if backup.is_some() {
+            bail!("--list 不能和备份名一起使用");
+        }
+        let backups = store::list(config_path)?;
+        if backups.is_empty() {
+            println!(
+                "{} 中没有配置备份",
+                store::backups_dir(config_path).display()
+            );
+            return Ok(());
+        }
+        println!(
+            "{} 中的配置备份（最新的在前）：",
+            store::backups_dir(config_path).display()
+        );
+        for (i, b) in backups.iter().enumerate() {
+            let version = b
+                .version
+                .map_or_else(|| "无法解析".to_string(), |v| format!("config_version {v}"));
+            println!(
+                "  {:>2}. {}  {}  {version}",
+                i + 1,
+                b.taken.format("%Y-%m-%d %H:%M:%S"),
+                b.file_name()
+            );
+        }
+        println!("使用 `jarvis config restore <序号或文件名>` 恢复");
+        return Ok(());
+    }
+    let (restored, previous) = store::restore(config_path, backup)?;
+    println!(
+        "✅ 已从 {}（{}）恢复 {}",
+        restored.file_name(),
+        restored.taken.format("%Y-%m-%d %H:%M:%S"),
+        config_path.display()
+    );
+    if let Some(previous) = previous {
+        println!("   恢复前的文件已备份到 {}", previous.display());
+    }
+    println!("   正在运行的守护进程需要重启后才会使用恢复的配置");
+    Ok(())
+}
+
+fn load(config_path: &Path) -> Result<Config> {
+    require(config_path)?;
+    Config::load_from(config_path)
+}
+
+fn require(config_path: &Path) -> Result<()> {
+    if !config_path.exists() {
+        bail!(
+            "{} 不存在，请先运行 `jarvis onboard`",
+            config_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Replace the config with `change(config)` under the write lock (see
+/// [`Config::update`]), returning the saved config and the backup.
+fn edit(
+    config_path: &Path,
+    change: impl FnOnce(&Config) -> Result<Config>,
+) -> Result<(Config, Option<std::path::PathBuf>)> {
+    require(config_path)?;
+    Config::update(config_path, |config| {
+        *config = change(config)?;
+        Ok(config.clone())
+    })
+}
+
+fn print_backup(backup: Option<&Path>) {
+    match backup {
+        Some(backup) => println!("   原文件已备份到 {}", backup.display()),
+        None => println!("   值未改变，文件未改动"),
+    }
+}
+
+/// The value at `key`, read from the config as it would be saved.
+fn get(config: &Config, key: &str) -> Result<toml::Value> {
+    let root = toml::Value::try_from(config).context("序列化配置失败")?;
+    env::value_at(&root, key)
+        .cloned()
+        .with_context(|| format!("{key} 未设置或不是有效的配置项"))
+}
+
+/// A copy of `config` with `key` set to `value`, parsed as the field's type.
+fn set(config: &Config, key: &str, value: &str) -> Result<Config> {
+    let mut root = toml::Value::try_from(config).context("序列化配置失败")?;
+    let current = env::value_at(&root, key);
+    if let Some(toml::Value::Table(_)) = current {
+        bail!("{key} 是一个表，请指定其中的字段");
+    }
+    let parsed = match current {
+        Some(current) => parse_as(current, value).with_context(|| format!("{key} 的值无效"))?,
+        None => parse_literal(value),
+    };
+
+    let mut candidate = root.clone();
+    env::insert_at(&mut candidate, key, parsed.clone())?;
+    match rebuild(config, &candidate, key) {
+        Ok(updated) => Ok(updated),
+        // An unset optional field has no current type to go by; a literal
+        // like `42` may still be meant as a string
+        Err(e) if current.is_none() && !parsed.is_str() => {
+            env::insert_at(&mut root, key, toml::Value::String(value.to_string()))?;
+            rebuild(config, &root, key).map_err(|_| e)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// A copy of `config` with `key` back at its default, or removed if the
+/// default config doesn't have it.
+fn unset(config: &Config, key: &str) -> Result<Config> {
+    let mut root = toml::Value::try_from(config).context("序列化配置失败")?;
+    if env::remove_at(&mut root, key).is_none() {
+        bail!("{key} 未设置或不是有效的配置项");
+    }
+    let defaults = toml::Value::try_from(Config::default()).context("序列化配置失败")?;
+    if let Some(default) = env::value_at(&defaults, key) {
+        env::insert_at(&mut root, key, default.clone())?;
+    }
+    rebuild(config, &root, key)
+}
+
+/// Deserialize an edited config, keeping `config`'s computed fields and
+/// rejecting keys the schema doesn't know.
+fn rebuild(config: &Config, root: &toml::Value, key: &str) -> Result<Config> {
+    let mut updated: Config = root
+        .clone()
+        .try_into()
+        .with_context(|| format!("无法更新 {key}"))?;
+    if let Some(unknown) = unknown::find(root, &updated, None)?
+        .into_iter()
+        .find(|k| k.path == key)
+    {
+        bail!("{key}：{unknown}");
+    }
+    updated.config_path.clone_from(&config.config_path);
+    updated.workspace_dir.clone_from(&config.workspace_dir);
+    updated.env = config.env.clone();
+    Ok(updated)
+}
+
+/// Parse `input` as the same TOML type as `current`.
+fn parse_as(current: &toml::Value, input: &str) -> Result<toml::Value> {
+    Ok(match current {
+        toml::Value::String(_) => toml::Value::String(input.to_string()),
+        toml::Value::Integer(_) => toml::Value::Integer(
+            input
+                .trim()
+                .parse()
+                .with_context(|| format!("需要整数，收到 \"{input}\""))?,
+        ),
+        toml::Value::Float(_) => toml::Value::Float(
+            input
+                .trim()
+                .parse()
+                .with_context(|| format!("需要数字，收到 \"{input}\""))?,
+        ),
+        toml::Value::Boolean(_) => toml::Value::Boolean(
+            env::parse_bool(input)
+                .with_context(|| format!("需要 true 或 false，收到 \"{input}\""))?,
+        ),
+        _ => parse_literal(input),
+    })
+}
+
+/// A TOML literal (`42`, `true`, `["a", "b"]`, `{ x = 1 }`), or the input as
+/// a plain string.
+fn parse_literal(input: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("v = {input}"))
+        .ok()
+        .and_then(|mut table| table.remove("v"))
+        .unwrap_or_else(|| toml::Value::String(input.to_string()))
+}
+
+/// Replace the secrets of `config` at or under `key` with [`MASK`],
+/// returning how many were hidden.
+fn mask_secrets(value: &mut toml::Value, key: &str, config: &Config) -> usize {
+    let fields = toml::Value::try_from(config).map_or_else(
+        |_| SECRET_FIELDS.iter().map(ToString::to_string).collect(),
+        |root| secrets::secret_paths(&root),
+    );
+    let mut masked = 0;
+    for field in &fields {
+        let target = if field == key {
+            Some(&mut *value)
+        } else {
+            field
+                .strip_prefix(key)
+                .and_then(|rest| rest.strip_prefix('.'))
+                .and_then(|rest| env::value_at_mut(value, rest))
+        };
+        if let Some(v) = target.filter(|v| v.as_str().is_some_and(|s| !s.is_empty())) {
+            *v = toml::Value::String(MASK.to_string());
+            masked += 1;
+        }
+    }
+    masked
+}
+
+fn display(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        toml::Value::Table(_) => toml::to_string_pretty(value)
+            .unwrap_or_default()
+            .trim_end()
+            .to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn warn_if_overridden(config: &Config, key: &str) {
+    let mut effective = config.clone();
+    effective.apply_env_overrides();
+    if let Some(o) = effective.env.overrides.get(key) {
+        eprintln!("⚠️  环境变量 {} 已设置，运行时会覆盖这个值", o.var);
+    }
+}
+
+/// Check `config_path` strictly: TOML syntax, secret decryption, `${VAR}`
+/// references, field types, keys the schema doesn't know (which a normal
+/// load silently ignores) and then the values themselves ([`Config::check`]).
+/// Returns every problem found.
+pub fn validate(config_path: &Path) -> Result<Vec<Problem>> {
+    let contents = fs::read_to_string(config_path)
+        .with_context(|| format!("读取 {} 失败", config_path.display()))?;
+    let mut root: toml::Value = match toml::from_str(&contents) {
+        Ok(root) => root,
+        Err(e) => {
+            return Ok(vec![Problem::error(
+                String::new(),
+                e.to_string().trim_end(),
+            )])
+        }
+    };
+
+    let mut problems = Vec::new();
+    let jarvis_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+    if let Err(e) = secrets::decrypt_fields(&mut root, &SecretStore::new(jarvis_dir, true)) {
+        problems.push(Problem::error(String::new(), format!("{e:#}")));
+    }
+    let (_, unresolved) = env::interpolate_all(&mut root);
+    problems.extend(
+        unresolved
+            .into_iter()
+            .map(|(path, message)| Problem::error(path, message)),
+    );
+
+    // serde stops at the first bad field: note it, drop it and try again
+    let config = loop {
+        let error = match root.clone().try_into::<Config>() {
+            Ok(config) => break Some(config),
+            Err(e) => e.to_string(),
+        };
+        let (message, path) = split_error(&error);
+        match path {
+            Some(path)
+                if problems.len() < MAX_PROBLEMS && env::remove_at(&mut root, &path).is_some() =>
+            {
+                // A failed `${VAR}` leaves its template behind; don't report
+                // the same field twice
+                if !problems.iter().any(|p| p.path == path) {
+                    problems.push(Problem::error(path, message));
+                }
+            }
+            path => {
+                problems.push(Problem::error(path.unwrap_or_default(), message));
+                break None;
+            }
+        }
+    };
+
+    if let Some(mut config) = config {
+        for key in unknown::find(&root, &config, Some(&contents))? {
+            problems.push(Problem::error(key.path.clone(), key.to_string()));
+        }
+        config.workspace_dir = jarvis_dir.join("workspace");
+        problems.extend(config.check());
+    }
+    Ok(problems)
+}
+
+/// Split a toml deserialization error into its message and the key path it
+/// names (the trailing ``in `a.b` `` line), if any.
+fn split_error(error: &str) -> (String, Option<String>) {
+    match error.trim_end().rsplit_once("\nin `") {
+        Some((message, path)) => (
+            message.trim().to_string(),
+            Some(path.trim_end_matches('`').to_string()),
+        ),
+        None => (error.trim().to_string(), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::checks::Severity;
+
+    fn write_config(dir: &Path, extra: &str) -> std::path::PathBuf {
+        fs::create_dir_all(dir.join("workspace")).unwrap();
+        let path = dir.join("config.toml");
+        fs::write(
+            &path,
+            format!("default_temperature = 0.7\n{extra}\n[secrets]\nencrypt = false\n"),
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn set_parses_values_by_field_type() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = Config::load_from(&write_config(tmp.path(), "")).unwrap();
+
+        let updated = set(&config, "heartbeat.interval_minutes", "15").unwrap();
+        assert_eq!(updated.heartbeat.interval_minutes, 15);
+        let updated = set(&config, "gateway.require_pairing", "off").unwrap();
+        assert!(!updated.gateway.require_pairing);
+        let updated = set(&config, "autonomy.max_tool_iterations", "40").unwrap();
+        assert_eq!(updated.autonomy.max_tool_iterations, 40);
+        assert_eq!(updated.config_path, config.config_path);
+
+        let err = format!("{:#}", set(&config, "gateway.port", "abc").unwrap_err());
+        assert!(err.contains("需要整数"), "{err}");
+        let err = format!("{:#}", set(&config, "gateway.port", "70000").unwrap_err());
+        assert!(err.contains("gateway.port"), "{err}");
+        let err = set(&config, "heartbeat.intervl", "5")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("未知配置项"), "{err}");
+        assert!(set(&config, "gateway", "1").is_err());
+    }
+
+    #[test]
+    fn set_fills_unset_optional_fields() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = Config::load_from(&write_config(tmp.path(), "")).unwrap();
+
+        let updated = set(&config, "api_key", "12345").unwrap();
+        assert_eq!(updated.api_key.as_deref(), Some("12345"));
+        let updated = set(&config, "autonomy.allowed_commands", r#"["git", "ls"]"#).unwrap();
+        assert_eq!(updated.autonomy.allowed_commands, ["git", "ls"]);
+    }
+
+    #[test]
+    fn set_and_unset_save_with_backup() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            "[heartbeat]\nenabled = false\ninterval_minutes = 45",
+        );
+
+        let (_, backup) = edit(&path, |c| set(c, "heartbeat.interval_minutes", "15")).unwrap();
+        let backup = backup.unwrap();
+        assert!(fs::read_to_string(&backup).unwrap().contains("= 45"));
+        let reloaded = Config::load_from(&path).unwrap();
+        assert_eq!(reloaded.heartbeat.interval_minutes, 15);
+        assert!(backup.starts_with(store::backups_dir(&path)));
+        assert!(fs::read_to_string(&path).unwrap().contains(&format!(
+            "config_version = {}",
+            crate::config::CONFIG_VERSION
+        )));
+
+        let reset = unset(&reloaded, "heartbeat.interval_minutes").unwrap();
+        assert_eq!(
+            reset.heartbeat.interval_minutes,
+            crate::config::HeartbeatConfig::default().interval_minutes
+        );
+        let reset = unset(&reloaded, "default_temperature").unwrap();
+        assert!((reset.default_temperature - 0.7).abs() < f64::EPSILON);
+        assert!(unset(&reloaded, "no.such.key").is_err());
+    }
+
+    #[test]
+    fn get_masks_secrets() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            "api_key = \"sk-secret\"\n[channels_config]\ncli = true\n[channels_config.telegram]\nbot_token = \"123:ABC\"\nallowed_users = []\n[secrets.named]\nha_token = \"ha-long-lived\"",
+        );
+        let config = Config::load_from(&path).unwrap();
+
+        let mut value = get(&config, "api_key").unwrap();
+        assert_eq!(mask_secrets(&mut value, "api_key", &config), 1);
+        assert_eq!(display(&value), MASK);
+
+        let mut table = get(&config, "channels_config").unwrap();
+        assert_eq!(mask_secrets(&mut table, "channels_config", &config), 1);
+        let shown = display(&table);
+        assert!(!shown.contains("123:ABC"), "{shown}");
+        assert!(shown.contains("allowed_users"), "{shown}");
+
+        let mut table = get(&config, "secrets").unwrap();
+        assert_eq!(mask_secrets(&mut table, "secrets", &config), 1);
+        assert!(!display(&table).contains("ha-long-lived"));
+
+        assert!(get(&config, "gateway.nope").is_err());
+    }
+
+    #[test]
+    fn validate_reports_every_problem_with_its_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"default_modle = "typo"
+api_key = "${JARVIS_TEST_CONFIG_CLI_UNSET}"
+[gateway]
+port = "abc"
+require_pairing = "yes"
+[heartbeat]
+enabled = false
+interval_minutes = 30
+enabeld = true"#,
+        );
+
+        let problems = validate(&path).unwrap();
+        let mut paths: Vec<&str> = problems.iter().map(|p| p.path.as_str()).collect();
+        paths.sort_unstable();
+        assert_eq!(
+            paths,
+            [
+                "api_key",
+                "default_modle",
+                "gateway.port",
+                "gateway.require_pairing",
+                "heartbeat.enabeld"
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_reports_malformed_command_patterns() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.autonomy.allowed_commands = vec!["git *".into(), "^ls(".into()];
+        let path = tmp.path().join("config.toml");
+        fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+        fs::create_dir(tmp.path().join("workspace")).unwrap();
+
+        let problems = validate(&path).unwrap();
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert_eq!(problems[0].path, "autonomy.allowed_commands[1]");
+        assert!(problems[0].message.contains("^ls("));
+    }
+
+    #[test]
+    fn validate_accepts_a_clean_file_and_reports_syntax_errors() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(tmp.path(), "[gateway]\nport = 8080");
+        assert!(validate(&path).unwrap().is_empty());
+
+        fs::write(&path, "default_temperature = ").unwrap();
+        let problems = validate(&path).unwrap();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].path.is_empty());
+    }
+
+    #[test]
+    fn validate_checks_values_with_their_severity() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = write_config(
+            tmp.path(),
+            r#"default_provider = "nope"
+default_model = ""
+[memory]
+backend = "redis"
+auto_save = true
+[tunnel]
+provider = "cloudflare"
+[channels_config]
+cli = true
+[channels_config.telegram]
+bot_token = "t"
+allowed_users = []"#,
+        );
+
+        let problems = validate(&path).unwrap();
+        let errors: Vec<&str> = problems
+            .iter()
+            .filter(|p| p.is_error())
+            .map(|p| p.path.as_str())
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "default_provider",
+                "default_model",
+                "memory.backend",
+                "tunnel.cloudflare"
+            ]
+        );
+        let warnings: Vec<&str> = problems
+            .iter()
+            .filter(|p| p.severity == Severity::Warning)
+            .map(|p| p.path.as_str())
+            .collect();
+        assert_eq!(warnings, ["channels_config.telegram.allowed_users"]);
+    }
+
+    #[test]
+    fn split_error_extracts_key_path() {
+        let (message, path) =
+            split_error("invalid type: string \"abc\", expected u16\nin `gateway.port`\n");
+        assert_eq!(message, "invalid type: string \"abc\", expected u16");
+        assert_eq!(path.as_deref(), Some("gateway.port"));
+        assert_eq!(split_error("missing field `x`").1, None);
+    }
+}
@@ -0,0 +1,409 @@
+//! Config values taken from the environment.
+//!
+//! Two mechanisms, applied in this order by `Config::load_or_init`:
+//!
+//! 1. `${VAR}` interpolation: any string in config.toml may reference
+//!    environment variables, e.g. `api_key = "${OPENROUTER_API_KEY}"`. An
+//!    unset variable is a load error. Placeholders are never encrypted.
+//! 2. `JARVIS_*` overrides, applied on top of the file:
+//!
+//! | Variable | Config value |
+//! |---|---|
+//! | `JARVIS_API_KEY`, `API_KEY` | `api_key` |
+//! | `JARVIS_DEFAULT_PROVIDER`, `JARVIS_PROVIDER`, `PROVIDER` | `default_provider` |
+//! | `JARVIS_DEFAULT_MODEL`, `JARVIS_MODEL` | `default_model` |
+//! | `JARVIS_DEFAULT_TEMPERATURE`, `JARVIS_TEMPERATURE` | `default_temperature` |
+//! | `JARVIS_WORKSPACE` | workspace directory |
+//! | `JARVIS_GATEWAY_HOST`, `HOST` | `gateway.host` |
+//! | `JARVIS_GATEWAY_PORT`, `PORT` | `gateway.port` |
+//! | `JARVIS_HEARTBEAT_ENABLED` | `heartbeat.enabled` |
+//! | `JARVIS_HEARTBEAT_INTERVAL_MINUTES` | `heartbeat.interval_minutes` |
+//! | `JARVIS_MEMORY_BACKEND` | `memory.backend` |
+//!
+//! Both are recorded in [`EnvProvenance`] so `jarvis status` can show them
+//! and `Config::save` writes the placeholder or the file's own value back
+//! instead of whatever the environment supplied.
+
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+
+/// A `${VAR}` template and the value it resolved to at load.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placeholder {
+    pub template: String,
+    pub resolved: String,
+}
+
+/// A `JARVIS_*` variable applied over the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    /// Variable the value came from
+    pub var: String,
+    /// Value in the file before the override (`None` if it was absent)
+    file_value: Option<toml::Value>,
+    /// Value after the override
+    value: Option<toml::Value>,
+}
+
+/// Which config values came from the environment, by dotted path.
+#[derive(Debug, Clone, Default)]
+pub struct EnvProvenance {
+    pub placeholders: BTreeMap<String, Placeholder>,
+    pub overrides: BTreeMap<String, Override>,
+}
+
+impl EnvProvenance {
+    pub fn is_empty(&self) -> bool {
+        self.placeholders.is_empty() && self.overrides.is_empty()
+    }
+
+    /// Record overrides given the serialized config before and after they
+    /// were applied.
+    pub fn record_overrides(
+        &mut self,
+        before: Option<&toml::Value>,
+        after: Option<&toml::Value>,
+        applied: Vec<(&str, String)>,
+    ) {
+        for (path, var) in applied {
+            let file_value = before.and_then(|root| value_at(root, path)).cloned();
+            let value = after.and_then(|root| value_at(root, path)).cloned();
+            // A second load keeps the file's original value
+            let file_value = match self.overrides.remove(path) {
+                Some(previous) => previous.file_value,
+                None => file_value,
+            };
+            self.overrides.insert(
+                path.to_string(),
+                Override {
+                    var,
+                    file_value,
+                    value,
+                },
+            );
+        }
+    }
+
+    /// Undo environment-supplied values in a serialized config before it is
+    /// written: overrides go back to the file's value, resolved placeholders
+    /// back to their `${VAR}` template. Values changed since load are kept.
+    pub fn restore(&self, root: &mut toml::Value) {
+        for (path, o) in &self.overrides {
+            if o.value.is_none() || value_at(root, path) != o.value.as_ref() {
+                continue;
+            }
+            match &o.file_value {
+                Some(file_value) => {
+                    if let Some(v) = value_at_mut(root, path) {
+                        *v = file_value.clone();
+                    }
+                }
+                None => {
+                    remove_at(root, path);
+                }
+            }
+        }
+        for (path, p) in &self.placeholders {
+            if let Some(v) =
+                value_at_mut(root, path).filter(|v| v.as_str() == Some(p.resolved.as_str()))
+            {
+                *v = toml::Value::String(p.template.clone());
+            }
+        }
+    }
+}
+
+/// Whether a config string holds a `${VAR}` reference.
+pub fn is_placeholder(value: &str) -> bool {
+    value.contains("${")
+}
+
+/// First of `vars` that is set to a non-empty value, as `(name, value)`.
+pub fn first_set(vars: &[&str]) -> Option<(String, String)> {
+    vars.iter().find_map(|var| {
+        std::env::var(var)
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(|v| ((*var).to_string(), v))
+    })
+}
+
+/// First of `vars` that is set to a value `parse` accepts.
+pub fn first_parsed<T>(vars: &[&str], parse: impl Fn(&str) -> Option<T>) -> Option<(String, T)> {
+    vars.iter().find_map(|var| {
+        let value = parse(&std::env::var(var).ok()?)?;
+        Some(((*var).to_string(), value))
+    })
+}
+
+/// Parse a boolean override (`true/false`, `1/0`, `yes/no`, `on/off`).
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Resolve every `${VAR}` in the strings of `root` in place.
+pub fn interpolate(root: &mut toml::Value) -> Result<BTreeMap<String, Placeholder>> {
+    let (placeholders, errors) = interpolate_all(root);
+    if let Some((path, e)) = errors.into_iter().next() {
+        bail!("config.toml 中的 {path}：{e}");
+    }
+    Ok(placeholders)
+}
+
+/// Resolve what can be resolved, returning every failure as `(path, error)`
+/// instead of stopping at the first. Used by `jarvis config validate`.
+pub fn interpolate_all(
+    root: &mut toml::Value,
+) -> (BTreeMap<String, Placeholder>, Vec<(String, String)>) {
+    let mut placeholders = BTreeMap::new();
+    let mut errors = Vec::new();
+    walk(root, String::new(), &mut placeholders, &mut errors);
+    (placeholders, errors)
+}
+
+fn walk(
+    value: &mut toml::Value,
+    path: String,
+    out: &mut BTreeMap<String, Placeholder>,
+    errors: &mut Vec<(String, String)>,
+) {
+    match value {
+        toml::Value::String(s) if is_placeholder(s) => match expand(s) {
+            Ok(resolved) => {
+                let template = std::mem::replace(s, resolved.clone());
+                out.insert(path, Placeholder { template, resolved });
+            }
+            Err(e) => errors.push((path, e.to_string())),
+        },
+        toml::Value::Table(table) => {
+            for (key, child) in table.iter_mut() {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                walk(child, child_path, out, errors);
+            }
+        }
+        toml::Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                walk(item, format!("{path}.{i}"), out, errors);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Substitute the `${VAR}` references in one string.
+fn expand(template: &str) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            bail!("未闭合的环境变量引用 \"{}\"", &rest[start..]);
+        };
+        let name = &after[..end];
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            bail!("无效的环境变量名 \"{name}\"");
+        }
+        match std::env::var(name) {
+            Ok(value) => out.push_str(&value),
+            Err(_) => bail!("引用的环境变量 {name} 未设置"),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+pub(crate) fn value_at<'a>(root: &'a toml::Value, path: &str) -> Option<&'a toml::Value> {
+    path.split('.').try_fold(root, |v, part| match v {
+        toml::Value::Array(items) => items.get(part.parse::<usize>().ok()?),
+        _ => v.get(part),
+    })
+}
+
+pub(crate) fn value_at_mut<'a>(
+    root: &'a mut toml::Value,
+    path: &str,
+) -> Option<&'a mut toml::Value> {
+    path.split('.').try_fold(root, |v, part| match v {
+        toml::Value::Array(items) => items.get_mut(part.parse::<usize>().ok()?),
+        _ => v.get_mut(part),
+    })
+}
+
+/// Remove the value at `path`, returning it if it was there.
+pub(crate) fn remove_at(root: &mut toml::Value, path: &str) -> Option<toml::Value> {
+    let (parent, key) = match path.rsplit_once('.') {
+        Some((parent, key)) => (value_at_mut(root, parent), key),
+        None => (Some(root), path),
+    };
+    match parent {
+        Some(toml::Value::Table(table)) => table.remove(key),
+        _ => None,
+    }
+}
+
+/// Set the value at `path`, creating missing tables on the way.
+pub(crate) fn insert_at(root: &mut toml::Value, path: &str, value: toml::Value) -> Result<()> {
+    let mut current = root;
+    let mut parts = path.split('.').peekable();
+    while let Some(part) = parts.next() {
+        let toml::Value::Table(table) = current else {
+            bail!("{path} 的上级不是表");
+        };
+        if parts.peek().is_none() {
+            table.insert(part.to_string(), value);
+            return Ok(());
+        }
+        current = table
+            .entry(part)
+            .or_insert_with(|| toml::Value::Table(toml::map::Map::new()));
+    }
+    bail!("配置项路径为空")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> toml::Value {
+        toml::from_str(raw).unwrap()
+    }
+
+    #[test]
+    fn interpolates_and_restores_placeholders() {
+        unsafe { std::env::set_var("JARVIS_TEST_INTERP_TOKEN", "123:SECRET") };
+        let mut root = parse(
+            r#"
+api_key = "plain"
+[channels_config.telegram]
+bot_token = "${JARVIS_TEST_INTERP_TOKEN}"
+allowed_users = ["bot-${JARVIS_TEST_INTERP_TOKEN}"]
+"#,
+        );
+        let placeholders = interpolate(&mut root).unwrap();
+        assert_eq!(placeholders.len(), 2);
+        assert_eq!(
+            value_at(&root, "channels_config.telegram.bot_token")
+                .unwrap()
+                .as_str(),
+            Some("123:SECRET")
+        );
+        assert_eq!(
+            value_at(&root, "channels_config.telegram.allowed_users.0")
+                .unwrap()
+                .as_str(),
+            Some("bot-123:SECRET")
+        );
+
+        let provenance = EnvProvenance {
+            placeholders,
+            ..EnvProvenance::default()
+        };
+        provenance.restore(&mut root);
+        assert_eq!(
+            value_at(&root, "channels_config.telegram.bot_token")
+                .unwrap()
+                .as_str(),
+            Some("${JARVIS_TEST_INTERP_TOKEN}")
+        );
+        assert_eq!(value_at(&root, "api_key").unwrap().as_str(), Some("plain"));
+        unsafe { std::env::remove_var("JARVIS_TEST_INTERP_TOKEN") };
+    }
+
+    #[test]
+    fn unset_or_malformed_references_are_errors() {
+        let mut root = parse(r#"api_key = "${JARVIS_TEST_INTERP_UNSET}""#);
+        let err = interpolate(&mut root).unwrap_err().to_string();
+        assert!(err.contains("api_key"), "{err}");
+        assert!(err.contains("JARVIS_TEST_INTERP_UNSET 未设置"), "{err}");
+
+        let mut root = parse(r#"api_key = "${OPEN""#);
+        assert!(interpolate(&mut root)
+            .unwrap_err()
+            .to_string()
+            .contains("未闭合"));
+        let mut root = parse(r#"api_key = "${BAD NAME}""#);
+        assert!(interpolate(&mut root).is_err());
+    }
+
+    #[test]
+    fn interpolate_all_reports_every_failure() {
+        let mut root = parse(
+            r#"
+api_key = "${JARVIS_TEST_INTERP_UNSET_A}"
+[gateway]
+host = "${JARVIS_TEST_INTERP_UNSET_B}"
+"#,
+        );
+        let (_, errors) = interpolate_all(&mut root);
+        let paths: Vec<&str> = errors.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, ["api_key", "gateway.host"]);
+    }
+
+    #[test]
+    fn insert_at_creates_missing_tables() {
+        let mut root = parse("[gateway]\nport = 3000\n");
+        insert_at(&mut root, "heartbeat.interval_minutes", 15.into()).unwrap();
+        assert_eq!(
+            value_at(&root, "heartbeat.interval_minutes")
+                .unwrap()
+                .as_integer(),
+            Some(15)
+        );
+        assert!(insert_at(&mut root, "gateway.port.inner", 1.into()).is_err());
+        assert_eq!(
+            remove_at(&mut root, "gateway.port").unwrap().as_integer(),
+            Some(3000)
+        );
+        assert!(remove_at(&mut root, "gateway.port").is_none());
+    }
+
+    #[test]
+    fn restore_puts_back_file_values_unless_changed() {
+        let before = parse("default_model = \"file-model\"\n[gateway]\nport = 3000\n");
+        let after =
+            parse("default_model = \"env-model\"\napi_key = \"env-key\"\n[gateway]\nport = 8080\n");
+        let mut provenance = EnvProvenance::default();
+        provenance.record_overrides(
+            Some(&before),
+            Some(&after),
+            vec![
+                ("default_model", "JARVIS_DEFAULT_MODEL".into()),
+                ("api_key", "JARVIS_API_KEY".into()),
+                ("gateway.port", "PORT".into()),
+            ],
+        );
+
+        let mut saved = after.clone();
+        saved
+            .as_table_mut()
+            .unwrap()
+            .insert("default_model".into(), "picked-in-tui".into());
+        provenance.restore(&mut saved);
+        assert_eq!(
+            value_at(&saved, "default_model").unwrap().as_str(),
+            Some("picked-in-tui")
+        );
+        assert!(value_at(&saved, "api_key").is_none());
+        assert_eq!(
+            value_at(&saved, "gateway.port").unwrap().as_integer(),
+            Some(3000)
+        );
+    }
+
+    #[test]
+    fn parses_bool_overrides() {
+        assert_eq!(parse_bool("TRUE"), Some(true));
+        assert_eq!(parse_bool("off"), Some(false));
+        assert_eq!(parse_bool("maybe"), None);
+    }
+}
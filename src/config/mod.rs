@@ -1,8 +1,20 @@
+pub mod checks;
+pub mod cli;
+pub mod env;
+pub mod profile;
 pub mod schema;
+pub mod secrets;
+pub mod store;
+pub mod unknown;
+
+pub use env::EnvProvenance;
 
 pub use schema::{
-    AutonomyConfig, BraveSearchConfig, BrowserConfig, ChannelsConfig, ComposioConfig, Config,
-    DiscordConfig, GatewayConfig, HeartbeatConfig, IMessageConfig, IdentityConfig, MatrixConfig,
-    MemoryConfig, ObservabilityConfig, ReliabilityConfig, RuntimeConfig, SecretsConfig,
-    SlackConfig, TelegramConfig, TunnelConfig, WebhookConfig,
+    AutonomyConfig, BraveSearchConfig, BrowserConfig, ChannelsConfig, ClipboardConfig,
+    ComposioConfig, Config, DiscordConfig, DockerRuntimeConfig, FileEditConfig, GatewayConfig,
+    GitConfig, HeartbeatConfig, HttpRequestConfig, IMessageConfig, IdentityConfig, LogFormat,
+    LoggingConfig, MatrixConfig, MemoryConfig, NotifyConfig, NotifyThreshold, ObservabilityConfig,
+    PythonConfig, RateLimitsConfig, ReliabilityConfig, RemindersConfig, RetryOn, RuntimeConfig,
+    SandboxRuntimeConfig, SecretsConfig, SlackConfig, TelegramConfig, ToolsConfig, TunnelConfig,
+    WebFetchConfig, WebSearchConfig, WebhookConfig, CONFIG_VERSION,
 };
@@ -0,0 +1,201 @@
+//! Named profiles: separate config, workspace, daemon and logs per profile.
+//!
+//! Without a profile everything lives in `~/.jarvis`; `--profile work` (or
+//! `JARVIS_PROFILE=work`) moves the whole root to `~/.jarvis/profiles/work`.
+//! The daemon's PID, state and log files sit in that root, so daemons of
+//! different profiles run side by side.
+
+use super::Config;
+use anyhow::{Context, Result};
+use directories::UserDirs;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable selecting the profile when `--profile` is not given.
+pub const PROFILE_ENV: &str = "JARVIS_PROFILE";
+
+/// Set once from `--profile` at startup.
+static SELECTED: OnceLock<String> = OnceLock::new();
+
+/// Use `name` for the rest of this process (the `--profile` flag).
+pub fn select(name: &str) -> Result<()> {
+    validate_name(name)?;
+    let _ = SELECTED.set(name.to_string());
+    Ok(())
+}
+
+/// The active profile: `--profile`, else `JARVIS_PROFILE`, else none.
+pub fn active() -> Option<String> {
+    SELECTED.get().cloned().or_else(|| {
+        std::env::var(PROFILE_ENV)
+            .ok()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+    })
+}
+
+/// Profile names become directory names: letters, digits, `-` and `_`.
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if !valid {
+        anyhow::bail!(
+            "无效的 profile 名称「{name}」：只能包含字母、数字、- 和 _（最多 64 个字符）"
+        );
+    }
+    Ok(())
+}
+
+/// `~/.jarvis`, the root without a profile.
+pub fn base_dir() -> Result<PathBuf> {
+    let home = UserDirs::new()
+        .map(|u| u.home_dir().to_path_buf())
+        .context("无法找到用户主目录")?;
+    Ok(home.join(".jarvis"))
+}
+
+/// Root for `profile` under `base`.
+pub fn dir_in(base: &Path, profile: Option<&str>) -> PathBuf {
+    match profile {
+        Some(name) => base.join("profiles").join(name),
+        None => base.to_path_buf(),
+    }
+}
+
+/// Root of the active profile: holds `config.toml`, `workspace/`, `logs/`
+/// and the daemon's PID and state files.
+pub fn config_dir() -> Result<PathBuf> {
+    let profile = active();
+    if let Some(name) = &profile {
+        validate_name(name).with_context(|| format!("检查 {PROFILE_ENV} 失败"))?;
+    }
+    Ok(dir_in(&base_dir()?, profile.as_deref()))
+}
+
+/// Profiles under `base`, sorted by name.
+pub fn list_in(base: &Path) -> Result<Vec<String>> {
+    let dir = base.join("profiles");
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut names: Vec<String> = fs::read_dir(&dir)
+        .with_context(|| format!("读取 {} 失败", dir.display()))?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| validate_name(name).is_ok())
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Create profile `name` under `base` with a default config. Fails if it
+/// already exists.
+pub fn create_in(base: &Path, name: &str) -> Result<PathBuf> {
+    validate_name(name)?;
+    let dir = dir_in(base, Some(name));
+    if dir.exists() {
+        anyhow::bail!("profile「{name}」已存在：{}", dir.display());
+    }
+    let workspace_dir = dir.join("workspace");
+    fs::create_dir_all(&workspace_dir)
+        .with_context(|| format!("创建 {} 失败", workspace_dir.display()))?;
+    let config = Config {
+        config_path: dir.join("config.toml"),
+        workspace_dir,
+        ..Config::default()
+    };
+    config.save()?;
+    Ok(dir)
+}
+
+/// Handle `jarvis profile ...`.
+pub fn handle_command(command: crate::ProfileCommands) -> Result<()> {
+    let base = base_dir()?;
+    let active = active();
+    match command {
+        crate::ProfileCommands::List => {
+            let marker = |selected: bool| if selected { "*" } else { " " };
+            println!("👤 Profiles:");
+            println!("  {} default  {}", marker(active.is_none()), base.display());
+            for name in list_in(&base)? {
+                println!(
+                    "  {} {name}  {}",
+                    marker(active.as_deref() == Some(name.as_str())),
+                    dir_in(&base, Some(&name)).display()
+                );
+            }
+            println!();
+            println!("使用 --profile <name> 或 {PROFILE_ENV}=<name> 切换。");
+            Ok(())
+        }
+        crate::ProfileCommands::Create { name } => {
+            let dir = create_in(&base, &name)?;
+            println!("✅ 已创建 profile「{name}」：{}", dir.display());
+            println!("   配置：jarvis --profile {name} onboard");
+            println!("   如需与其他 profile 的守护进程同时运行，请为其设置不同的 gateway 端口。");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn profiles_get_separate_roots() {
+        let base = Path::new("/home/alice/.jarvis");
+        assert_eq!(dir_in(base, None), base);
+        assert_eq!(
+            dir_in(base, Some("work")),
+            Path::new("/home/alice/.jarvis/profiles/work")
+        );
+        assert_ne!(dir_in(base, Some("work")), dir_in(base, Some("personal")));
+    }
+
+    #[test]
+    fn names_must_be_directory_safe() {
+        for ok in ["work", "home-2", "a_b"] {
+            assert!(validate_name(ok).is_ok(), "{ok}");
+        }
+        for bad in [
+            "",
+            "../etc",
+            "a/b",
+            "with space",
+            ".hidden",
+            &"x".repeat(65),
+        ] {
+            assert!(validate_name(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn create_writes_an_isolated_config_and_lists_it() {
+        let tmp = TempDir::new().unwrap();
+        let dir = create_in(tmp.path(), "work").unwrap();
+        create_in(tmp.path(), "personal").unwrap();
+
+        let config = Config::load_from(&dir.join("config.toml")).unwrap();
+        assert_eq!(config.workspace_dir, dir.join("workspace"));
+        assert!(config.workspace_dir.is_dir());
+        assert_eq!(
+            crate::daemon::pid_file_path(&config),
+            dir.join("daemon.pid")
+        );
+        assert_eq!(
+            crate::daemon::state_file_path(&config),
+            dir.join("daemon_state.json")
+        );
+
+        assert_eq!(list_in(tmp.path()).unwrap(), ["personal", "work"]);
+        assert!(create_in(tmp.path(), "work").is_err());
+        assert!(!tmp.path().join("config.toml").exists());
+    }
+}
@@ -1,12 +1,20 @@
-use crate::security::AutonomyLevel;
+use super::env::{self, EnvProvenance};
+use super::secrets;
+use super::store;
+use super::unknown::{self, UnknownKey};
+use crate::security::{AutonomyLevel, CommandPolicyMode, SecretStore};
 use anyhow::{Context, Result};
-use directories::UserDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 // ── Top-level config ──────────────────────────────────────────────
 
+/// The `config_version` [`Config::save`] writes. Bump it with a migration
+/// whenever a change needs existing files rewritten.
+pub const CONFIG_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     /// Workspace directory - computed from home, not serialized
@@ -15,14 +23,30 @@ pub struct Config {
     /// Path to config.toml - computed from home, not serialized
     #[serde(skip)]
     pub config_path: PathBuf,
+    /// Values taken from the environment - recorded at load, not serialized
+    #[serde(skip)]
+    pub env: EnvProvenance,
+    /// Keys in the file the schema ignored - recorded at load, not serialized
+    #[serde(skip)]
+    pub unknown_keys: Vec<UnknownKey>,
+    /// Schema version the file was written with, 0 for files older than
+    /// versioning
+    #[serde(default)]
+    pub config_version: u32,
     pub api_key: Option<String>,
     pub default_provider: Option<String>,
     pub default_model: Option<String>,
     pub default_temperature: f64,
+    /// Fail to load on unknown keys instead of warning about them
+    #[serde(default)]
+    pub strict_config: bool,
 
     #[serde(default)]
     pub observability: ObservabilityConfig,
 
+    #[serde(default)]
+    pub logging: LoggingConfig,
+
     #[serde(default)]
     pub autonomy: AutonomyConfig,
 
@@ -61,6 +85,19 @@ pub struct Config {
 
     #[serde(default)]
     pub brave_search: BraveSearchConfig,
+
+    #[serde(default)]
+    pub git: GitConfig,
+
+    #[serde(default)]
+    pub web_fetch: WebFetchConfig,
+
+    #[serde(default)]
+    pub tools: ToolsConfig,
+
+    /// Where heartbeat and cron results are sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify: Option<NotifyConfig>,
 }
 
 // ── Identity (AIEOS / OpenClaw format) ──────────────────────────
@@ -108,9 +145,21 @@ pub struct GatewayConfig {
     /// Allow binding to non-localhost without a tunnel (default: false)
     #[serde(default)]
     pub allow_public_bind: bool,
+    /// Skip pairing when bound to a loopback address with no tunnel (default: false)
+    #[serde(default)]
+    pub trust_loopback: bool,
     /// Paired bearer tokens (managed automatically, not user-edited)
     #[serde(default)]
     pub paired_tokens: Vec<String>,
+    /// Maximum request body size in bytes (default: 65536)
+    #[serde(default = "default_gateway_max_body_bytes")]
+    pub max_body_bytes: usize,
+    /// Forget a `/ws/chat` session after this many idle seconds (default: 1800, 0 = never)
+    #[serde(default = "default_chat_session_idle_secs")]
+    pub chat_session_idle_secs: u64,
+    /// Concurrent `POST /v1/agent` runs before answering 429 (default: 4, 0 = unlimited)
+    #[serde(default = "default_max_concurrent_runs")]
+    pub max_concurrent_runs: usize,
 }
 
 fn default_gateway_port() -> u16 {
@@ -121,6 +170,18 @@ fn default_gateway_host() -> String {
     "127.0.0.1".into()
 }
 
+fn default_gateway_max_body_bytes() -> usize {
+    65_536
+}
+
+fn default_chat_session_idle_secs() -> u64 {
+    1800
+}
+
+fn default_max_concurrent_runs() -> usize {
+    4
+}
+
 fn default_true() -> bool {
     true
 }
@@ -132,7 +193,11 @@ impl Default for GatewayConfig {
             host: default_gateway_host(),
             require_pairing: true,
             allow_public_bind: false,
+            trust_loopback: false,
             paired_tokens: Vec::new(),
+            max_body_bytes: default_gateway_max_body_bytes(),
+            chat_session_idle_secs: default_chat_session_idle_secs(),
+            max_concurrent_runs: default_max_concurrent_runs(),
         }
     }
 }
@@ -173,11 +238,18 @@ pub struct SecretsConfig {
     /// Enable encryption for API keys and tokens in config.toml
     #[serde(default = "default_true")]
     pub encrypt: bool,
+    /// Credentials tools reference by name (`secret:<name>`) so the value
+    /// never appears in model-visible arguments. Encrypted like the rest.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub named: BTreeMap<String, String>,
 }
 
 impl Default for SecretsConfig {
     fn default() -> Self {
-        Self { encrypt: true }
+        Self {
+            encrypt: true,
+            named: BTreeMap::new(),
+        }
     }
 }
 
@@ -225,27 +297,315 @@ impl Default for BraveSearchConfig {
     }
 }
 
+// ── Git ──────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GitConfig {
+    /// Enable the `git` tool (status, diff, log, add, commit, branch)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Also allow `push` and `reset`, which can discard or publish work
+    #[serde(default)]
+    pub allow_write: bool,
+}
+
+// ── Web fetch ────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebFetchConfig {
+    /// Enable the `web_fetch` tool (download a URL as readable text)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Largest response body read, in bytes; anything past it is dropped
+    #[serde(default = "default_web_fetch_max_bytes")]
+    pub max_bytes: usize,
+    /// Whole-request timeout, in seconds
+    #[serde(default = "default_web_fetch_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Token budget for the extracted text handed back to the model
+    #[serde(default = "default_web_fetch_max_tokens")]
+    pub max_tokens: usize,
+    /// Allow loopback and private-network hosts. Only honoured when
+    /// `autonomy.workspace_only` is off.
+    #[serde(default)]
+    pub allow_private_hosts: bool,
+}
+
+fn default_web_fetch_max_bytes() -> usize {
+    2 * 1024 * 1024
+}
+
+fn default_web_fetch_timeout_secs() -> u64 {
+    20
+}
+
+fn default_web_fetch_max_tokens() -> usize {
+    4000
+}
+
+impl Default for WebFetchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_bytes: default_web_fetch_max_bytes(),
+            timeout_secs: default_web_fetch_timeout_secs(),
+            max_tokens: default_web_fetch_max_tokens(),
+            allow_private_hosts: false,
+        }
+    }
+}
+
+// ── Tools ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ToolsConfig {
+    /// The `http_request` tool
+    #[serde(default)]
+    pub http: HttpRequestConfig,
+    /// The `file_edit` tool
+    #[serde(default)]
+    pub file_edit: FileEditConfig,
+    /// The `clipboard` tool
+    #[serde(default)]
+    pub clipboard: ClipboardConfig,
+    /// The `reminders` tool
+    #[serde(default)]
+    pub reminders: RemindersConfig,
+    /// Which search engine backs the `web_search` tool
+    #[serde(default)]
+    pub web_search: WebSearchConfig,
+    /// The `python` tool
+    #[serde(default)]
+    pub python: PythonConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebSearchConfig {
+    /// `brave` (the default, configured under `[brave_search]`) or `searxng`
+    #[serde(default = "default_web_search_provider")]
+    pub provider: String,
+    #[serde(default)]
+    pub searxng: SearxngConfig,
+}
+
+fn default_web_search_provider() -> String {
+    "brave".into()
+}
+
+impl Default for WebSearchConfig {
+    fn default() -> Self {
+        Self {
+            provider: default_web_search_provider(),
+            searxng: SearxngConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearxngConfig {
+    /// Base URL of the searxng instance, e.g. `https://searx.example.org`.
+    /// The instance must allow the `json` output format.
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Default number of results per search (1-20)
+    #[serde(default = "default_brave_search_count")]
+    pub default_count: u8,
+}
+
+impl Default for SearxngConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            default_count: default_brave_search_count(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardConfig {
+    /// Enable the `clipboard` tool. The agent can then read whatever you
+    /// last copied, passwords included, so it is off by default.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Longest clipboard text returned to the model, in characters
+    #[serde(default = "default_clipboard_max_chars")]
+    pub max_chars: usize,
+}
+
+fn default_clipboard_max_chars() -> usize {
+    20_000
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_chars: default_clipboard_max_chars(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemindersConfig {
+    /// Enable the `reminders` tool, which keeps to-dos as VTODO entries in
+    /// an iCalendar file that calendar apps can import
+    #[serde(default)]
+    pub enabled: bool,
+    /// The `.ics` file, relative to the workspace
+    #[serde(default = "default_reminders_path")]
+    pub path: String,
+}
+
+fn default_reminders_path() -> String {
+    "reminders.ics".into()
+}
+
+impl Default for RemindersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: default_reminders_path(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythonConfig {
+    /// Enable the `python` tool, which runs code the model writes through
+    /// the configured runtime
+    #[serde(default)]
+    pub enabled: bool,
+    /// Interpreter to run, a name on PATH or an absolute path. Ignored when
+    /// `venv` is set.
+    #[serde(default = "default_python_interpreter")]
+    pub interpreter: String,
+    /// Virtual environment whose `bin/python` runs the code, relative to
+    /// the workspace or absolute
+    #[serde(default)]
+    pub venv: Option<String>,
+    /// Seconds a run may take when the call sets no `timeout_seconds`
+    #[serde(default = "default_python_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Largest output returned to the model, in bytes
+    #[serde(default = "default_python_max_output_bytes")]
+    pub max_output_bytes: usize,
+}
+
+fn default_python_interpreter() -> String {
+    "python3".into()
+}
+
+fn default_python_timeout_secs() -> u64 {
+    60
+}
+
+fn default_python_max_output_bytes() -> usize {
+    64 * 1024
+}
+
+impl Default for PythonConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interpreter: default_python_interpreter(),
+            venv: None,
+            timeout_secs: default_python_timeout_secs(),
+            max_output_bytes: default_python_max_output_bytes(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEditConfig {
+    /// Backups kept per file under `state/backups/` (0 = no backups)
+    #[serde(default = "default_file_edit_keep_backups")]
+    pub keep_backups: usize,
+}
+
+fn default_file_edit_keep_backups() -> usize {
+    10
+}
+
+impl Default for FileEditConfig {
+    fn default() -> Self {
+        Self {
+            keep_backups: default_file_edit_keep_backups(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpRequestConfig {
+    /// Enable the `http_request` tool (call JSON APIs on allowed hosts)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Hosts that may be called: exact names, or `*.example.com` for
+    /// subdomains. Nothing else is reachable.
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    /// HTTP methods the model may use
+    #[serde(default = "default_http_allowed_methods")]
+    pub allowed_methods: Vec<String>,
+    /// Largest response body read and returned, in bytes
+    #[serde(default = "default_http_max_response_bytes")]
+    pub max_response_bytes: usize,
+    /// Whole-request timeout, in seconds
+    #[serde(default = "default_http_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_http_allowed_methods() -> Vec<String> {
+    vec!["GET".into(), "POST".into()]
+}
+
+fn default_http_max_response_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_http_timeout_secs() -> u64 {
+    30
+}
+
+impl Default for HttpRequestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allowed_hosts: Vec::new(),
+            allowed_methods: default_http_allowed_methods(),
+            max_response_bytes: default_http_max_response_bytes(),
+            timeout_secs: default_http_timeout_secs(),
+        }
+    }
+}
+
 // ── Memory ───────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryConfig {
-    /// "sqlite" | "markdown" | "none"
+    /// "sqlite" | "markdown" | "postgres" | "none"
     pub backend: String,
     /// Auto-save conversation context to memory
     pub auto_save: bool,
     /// Run memory/session hygiene (archiving + retention cleanup)
     #[serde(default = "default_hygiene_enabled")]
     pub hygiene_enabled: bool,
-    /// Archive daily/session files older than this many days
+    /// Archive daily/session files and daily/conversation rows older than this many days
     #[serde(default = "default_archive_after_days")]
     pub archive_after_days: u32,
-    /// Purge archived files older than this many days
+    /// Purge archived files and rows older than this many days
     #[serde(default = "default_purge_after_days")]
     pub purge_after_days: u32,
     /// For sqlite backend: prune conversation rows older than this many days
     #[serde(default = "default_conversation_retention_days")]
     pub conversation_retention_days: u32,
-    /// Embedding provider: "none" | "openai" | "custom:URL"
+    /// Delete tasks completed more than this many days ago (0 keeps them)
+    #[serde(default = "default_task_retention_days")]
+    pub task_retention_days: u32,
+    /// Delete conversation transcripts (`sessions/YYYY-MM-DD/`) older than
+    /// this many days (0 keeps them)
+    #[serde(default = "default_transcript_retention_days")]
+    pub transcript_retention_days: u32,
+    /// Embedding provider: "none" | "local" | "local:URL" | "openai" | "custom:URL" | "hashed"
     #[serde(default = "default_embedding_provider")]
     pub embedding_provider: String,
     /// Embedding model name (e.g. "text-embedding-3-small")
@@ -266,6 +626,9 @@ pub struct MemoryConfig {
     /// Max tokens per chunk for document splitting
     #[serde(default = "default_chunk_size")]
     pub chunk_max_tokens: usize,
+    /// Connection string for the postgres backend (requires the `postgres` feature)
+    #[serde(default)]
+    pub postgres_url: Option<String>,
 }
 
 fn default_embedding_provider() -> String {
@@ -283,6 +646,12 @@ fn default_purge_after_days() -> u32 {
 fn default_conversation_retention_days() -> u32 {
     30
 }
+fn default_task_retention_days() -> u32 {
+    30
+}
+fn default_transcript_retention_days() -> u32 {
+    30
+}
 fn default_embedding_model() -> String {
     "text-embedding-3-small".into()
 }
@@ -311,6 +680,8 @@ impl Default for MemoryConfig {
             archive_after_days: default_archive_after_days(),
             purge_after_days: default_purge_after_days(),
             conversation_retention_days: default_conversation_retention_days(),
+            task_retention_days: default_task_retention_days(),
+            transcript_retention_days: default_transcript_retention_days(),
             embedding_provider: default_embedding_provider(),
             embedding_model: default_embedding_model(),
             embedding_dimensions: default_embedding_dims(),
@@ -318,6 +689,7 @@ impl Default for MemoryConfig {
             keyword_weight: default_keyword_weight(),
             embedding_cache_size: default_cache_size(),
             chunk_max_tokens: default_chunk_size(),
+            postgres_url: None,
         }
     }
 }
@@ -328,23 +700,104 @@ impl Default for MemoryConfig {
 pub struct ObservabilityConfig {
     /// "none" | "log" | "prometheus" | "otel"
     pub backend: String,
+    /// Extra `host:port` serving `/metrics` without gateway auth (prometheus
+    /// backend only); the gateway's own `/metrics` is always available
+    #[serde(default)]
+    pub prometheus_listen: Option<String>,
+    /// OTLP/HTTP collector the otel backend sends traces to (default:
+    /// `http://localhost:4318`; `/v1/traces` is appended)
+    #[serde(default)]
+    pub otel_endpoint: Option<String>,
+    /// Headers sent with every export, e.g. an API key for a hosted backend
+    #[serde(default)]
+    pub otel_headers: BTreeMap<String, String>,
 }
 
 impl Default for ObservabilityConfig {
     fn default() -> Self {
         Self {
             backend: "none".into(),
+            prometheus_listen: None,
+            otel_endpoint: None,
+            otel_headers: BTreeMap::new(),
         }
     }
 }
 
+// ── Logging ──────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    /// Default level: "error" | "warn" | "info" | "debug" | "trace"
+    #[serde(default = "default_log_level")]
+    pub level: String,
+    /// Per-target overrides, e.g. `jarvis::channels=debug`
+    #[serde(default)]
+    pub targets: Vec<String>,
+    #[serde(default)]
+    pub format: LogFormat,
+}
+
+fn default_log_level() -> String {
+    "info".into()
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: default_log_level(),
+            targets: Vec::new(),
+            format: LogFormat::default(),
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// Filter directives: `cli_level` (`--log-level`) or `level`, then the
+    /// per-target overrides. `RUST_LOG` replaces all of this when set.
+    pub fn directives(&self, cli_level: Option<&str>) -> String {
+        let level = cli_level.unwrap_or(&self.level).trim();
+        std::iter::once(if level.is_empty() { "info" } else { level })
+            .chain(
+                self.targets
+                    .iter()
+                    .map(|t| t.trim())
+                    .filter(|t| !t.is_empty()),
+            )
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Compact human-readable lines
+    #[default]
+    Pretty,
+    /// One JSON object per line (Loki, Vector, jq)
+    Json,
+}
+
 // ── Autonomy / Security ──────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutonomyConfig {
     pub level: AutonomyLevel,
     pub workspace_only: bool,
+    /// Names, globs (`git *`) or regexes (`^ls( |$)`) of commands that may run
     pub allowed_commands: Vec<String>,
+    /// `allow` runs only `allowed_commands`; `deny` runs anything not
+    /// matching `denied_commands`
+    #[serde(default)]
+    pub command_policy_mode: CommandPolicyMode,
+    /// Commands refused in `deny` mode, in the same syntax as `allowed_commands`
+    #[serde(default)]
+    pub denied_commands: Vec<String>,
+    /// Commands that need confirmation in supervised mode even when
+    /// permitted, in the same syntax as `allowed_commands`
+    #[serde(default = "default_destructive_commands")]
+    pub destructive_commands: Vec<String>,
     pub forbidden_paths: Vec<String>,
     pub max_actions_per_hour: u32,
     pub max_cost_per_day_cents: u32,
@@ -354,6 +807,56 @@ pub struct AutonomyConfig {
     /// Maximum conversation history turns to keep (0 = unlimited).
     #[serde(default = "default_max_history_turns")]
     pub max_history_turns: usize,
+    /// Summarize turns beyond `max_history_turns` instead of dropping them.
+    #[serde(default)]
+    pub compact_history: bool,
+    /// Seconds to wait for a shell command approval before giving up.
+    #[serde(default = "default_approval_timeout_secs")]
+    pub approval_timeout_secs: u64,
+    /// Save commands approved with "always" to `allowed_commands` in this file.
+    #[serde(default)]
+    pub persist_approvals: bool,
+    /// Seconds a shell command may run when the call sets no `timeout_seconds`.
+    #[serde(default = "default_shell_timeout_secs")]
+    pub shell_timeout_secs: u64,
+    /// Upper bound on a shell call's `timeout_seconds`.
+    #[serde(default = "default_max_shell_timeout_secs")]
+    pub max_shell_timeout_secs: u64,
+    /// Per-category action budgets.
+    #[serde(default)]
+    pub rate_limits: RateLimitsConfig,
+}
+
+/// Per-category hourly action limits (`[autonomy.rate_limits]`).
+///
+/// A category with a non-zero limit gets its own sliding one-hour budget and
+/// no longer counts against `max_actions_per_hour`; 0 keeps it on the shared
+/// budget.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitsConfig {
+    #[serde(default)]
+    pub shell: u32,
+    #[serde(default)]
+    pub file: u32,
+    #[serde(default = "default_memory_rate_limit")]
+    pub memory: u32,
+    #[serde(default)]
+    pub network: u32,
+}
+
+fn default_memory_rate_limit() -> u32 {
+    120
+}
+
+impl Default for RateLimitsConfig {
+    fn default() -> Self {
+        Self {
+            shell: 0,
+            file: 0,
+            memory: default_memory_rate_limit(),
+            network: 0,
+        }
+    }
 }
 
 fn default_max_tool_iterations() -> usize {
@@ -364,6 +867,25 @@ fn default_max_history_turns() -> usize {
     20
 }
 
+fn default_destructive_commands() -> Vec<String> {
+    crate::security::commands::DEFAULT_DESTRUCTIVE_COMMANDS
+        .iter()
+        .map(|p| (*p).to_string())
+        .collect()
+}
+
+fn default_approval_timeout_secs() -> u64 {
+    crate::security::approval::DEFAULT_APPROVAL_TIMEOUT_SECS
+}
+
+fn default_shell_timeout_secs() -> u64 {
+    crate::tools::shell::DEFAULT_TIMEOUT_SECS
+}
+
+fn default_max_shell_timeout_secs() -> u64 {
+    crate::tools::shell::DEFAULT_MAX_TIMEOUT_SECS
+}
+
 impl Default for AutonomyConfig {
     fn default() -> Self {
         Self {
@@ -384,6 +906,9 @@ impl Default for AutonomyConfig {
                 "tail".into(),
                 "date".into(),
             ],
+            command_policy_mode: CommandPolicyMode::Allow,
+            denied_commands: Vec::new(),
+            destructive_commands: default_destructive_commands(),
             forbidden_paths: vec![
                 "/etc".into(),
                 "/root".into(),
@@ -408,6 +933,12 @@ impl Default for AutonomyConfig {
             max_cost_per_day_cents: 500,
             max_tool_iterations: default_max_tool_iterations(),
             max_history_turns: default_max_history_turns(),
+            compact_history: false,
+            approval_timeout_secs: default_approval_timeout_secs(),
+            persist_approvals: false,
+            shell_timeout_secs: default_shell_timeout_secs(),
+            max_shell_timeout_secs: default_max_shell_timeout_secs(),
+            rate_limits: RateLimitsConfig::default(),
         }
     }
 }
@@ -416,16 +947,98 @@ impl Default for AutonomyConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RuntimeConfig {
-    /// Runtime kind (currently supported: "native").
+    /// Runtime kind: "native", "docker" or "sandbox".
     ///
-    /// Reserved values (not implemented yet): "docker", "cloudflare".
+    /// Reserved value (not implemented yet): "cloudflare".
     pub kind: String,
+    /// The container shell commands run in with `kind = "docker"`
+    #[serde(default)]
+    pub docker: DockerRuntimeConfig,
+    /// The bwrap/firejail sandbox shell commands run in with
+    /// `kind = "sandbox"` (Linux only)
+    #[serde(default)]
+    pub sandbox: SandboxRuntimeConfig,
 }
 
 impl Default for RuntimeConfig {
     fn default() -> Self {
         Self {
             kind: "native".into(),
+            docker: DockerRuntimeConfig::default(),
+            sandbox: SandboxRuntimeConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxRuntimeConfig {
+    /// "bwrap", "firejail", or "auto" (the default): bwrap when installed,
+    /// otherwise firejail
+    #[serde(default = "default_sandbox_backend")]
+    pub backend: String,
+    /// Let commands reach the network (default: no network)
+    #[serde(default)]
+    pub network: bool,
+    /// More absolute paths commands may write to besides the workspace
+    #[serde(default)]
+    pub writable_paths: Vec<String>,
+}
+
+fn default_sandbox_backend() -> String {
+    "auto".into()
+}
+
+impl Default for SandboxRuntimeConfig {
+    fn default() -> Self {
+        Self {
+            backend: default_sandbox_backend(),
+            network: false,
+            writable_paths: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DockerRuntimeConfig {
+    /// Image the container is created from; it needs `sh` and `timeout`
+    #[serde(default = "default_docker_image")]
+    pub image: String,
+    /// Container name (default: `jarvis-` and a hash of the workspace path,
+    /// so each workspace gets its own container)
+    #[serde(default)]
+    pub container_name: Option<String>,
+    /// More bind mounts in `docker run -v` syntax, e.g. `/data:/data:ro`.
+    /// The workspace is always mounted read-write at its own path.
+    #[serde(default)]
+    pub extra_mounts: Vec<String>,
+    /// `docker run --network`: `bridge`, `none`, `host` or a network name
+    #[serde(default = "default_docker_network")]
+    pub network: String,
+    /// `docker run --memory`, e.g. `512m` or `2g` (default: no limit)
+    #[serde(default)]
+    pub memory: Option<String>,
+    /// `docker run --cpus`, e.g. `1.5` (default: no limit)
+    #[serde(default)]
+    pub cpus: Option<f64>,
+}
+
+fn default_docker_image() -> String {
+    "buildpack-deps:bookworm-scm".into()
+}
+
+fn default_docker_network() -> String {
+    "bridge".into()
+}
+
+impl Default for DockerRuntimeConfig {
+    fn default() -> Self {
+        Self {
+            image: default_docker_image(),
+            container_name: None,
+            extra_mounts: Vec::new(),
+            network: default_docker_network(),
+            memory: None,
+            cpus: None,
         }
     }
 }
@@ -435,20 +1048,48 @@ impl Default for RuntimeConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReliabilityConfig {
     /// Retries per provider before failing over.
-    #[serde(default = "default_provider_retries")]
-    pub provider_retries: u32,
-    /// Base backoff (ms) for provider retry delay.
-    #[serde(default = "default_provider_backoff_ms")]
-    pub provider_backoff_ms: u64,
-    /// Fallback provider chain (e.g. `["anthropic", "openai"]`).
+    #[serde(default = "default_provider_retries", alias = "provider_retries")]
+    pub provider_max_retries: u32,
+    /// Delay (ms) before the first retry; doubles on each further retry,
+    /// with up to a quarter taken off at random.
+    #[serde(default = "default_provider_backoff_ms", alias = "provider_backoff_ms")]
+    pub provider_initial_backoff_ms: u64,
+    /// Upper bound (ms) on a single retry delay, including `Retry-After`.
+    #[serde(default = "default_provider_max_backoff_ms")]
+    pub provider_max_backoff_ms: u64,
+    /// Failures worth retrying on the same provider: `timeout` (timeouts and
+    /// connection errors), `429` and `5xx`. Other retryable failures go
+    /// straight to the next fallback provider.
+    #[serde(default = "default_provider_retry_on")]
+    pub provider_retry_on: Vec<RetryOn>,
+    /// Fallback provider chain (e.g. `["anthropic", "openai"]`), tried in order
+    /// when the primary fails with a retryable error (429/5xx/timeout).
     #[serde(default)]
     pub fallback_providers: Vec<String>,
+    /// Model to request from each fallback provider (e.g. `anthropic = "claude-sonnet-4-20250514"`).
+    /// Without an entry, a matching vendor prefix is stripped (`anthropic/x` → `x`).
+    #[serde(default)]
+    pub fallback_models: HashMap<String, String>,
     /// Initial backoff for channel/daemon restarts.
     #[serde(default = "default_channel_backoff_secs")]
     pub channel_initial_backoff_secs: u64,
     /// Max backoff for channel/daemon restarts.
     #[serde(default = "default_channel_backoff_max_secs")]
     pub channel_max_backoff_secs: u64,
+    /// Daemon components failing more than this many times within
+    /// `circuit_breaker_window_secs` stop being restarted (0 = never).
+    #[serde(default = "default_circuit_breaker_failures")]
+    pub circuit_breaker_failures: u32,
+    /// Window for counting daemon component failures.
+    #[serde(default = "default_circuit_breaker_window_secs")]
+    pub circuit_breaker_window_secs: u64,
+    /// Roll a background daemon log (`logs/daemon.*.log`) once it exceeds
+    /// this many bytes (0 = never rotate).
+    #[serde(default = "default_log_max_bytes")]
+    pub log_max_bytes: u64,
+    /// Rotated daemon logs to keep (`.1` is the newest); older ones are deleted.
+    #[serde(default = "default_log_backups")]
+    pub log_backups: usize,
     /// Scheduler polling cadence in seconds.
     #[serde(default = "default_scheduler_poll_secs")]
     pub scheduler_poll_secs: u64,
@@ -465,6 +1106,14 @@ fn default_provider_backoff_ms() -> u64 {
     500
 }
 
+fn default_provider_max_backoff_ms() -> u64 {
+    60_000
+}
+
+fn default_provider_retry_on() -> Vec<RetryOn> {
+    vec![RetryOn::Timeout, RetryOn::RateLimited, RetryOn::ServerError]
+}
+
 fn default_channel_backoff_secs() -> u64 {
     2
 }
@@ -473,6 +1122,22 @@ fn default_channel_backoff_max_secs() -> u64 {
     60
 }
 
+fn default_circuit_breaker_failures() -> u32 {
+    5
+}
+
+fn default_circuit_breaker_window_secs() -> u64 {
+    600
+}
+
+fn default_log_max_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_log_backups() -> usize {
+    5
+}
+
 fn default_scheduler_poll_secs() -> u64 {
     15
 }
@@ -484,23 +1149,64 @@ fn default_scheduler_retries() -> u32 {
 impl Default for ReliabilityConfig {
     fn default() -> Self {
         Self {
-            provider_retries: default_provider_retries(),
-            provider_backoff_ms: default_provider_backoff_ms(),
+            provider_max_retries: default_provider_retries(),
+            provider_initial_backoff_ms: default_provider_backoff_ms(),
+            provider_max_backoff_ms: default_provider_max_backoff_ms(),
+            provider_retry_on: default_provider_retry_on(),
             fallback_providers: Vec::new(),
+            fallback_models: HashMap::new(),
             channel_initial_backoff_secs: default_channel_backoff_secs(),
             channel_max_backoff_secs: default_channel_backoff_max_secs(),
+            circuit_breaker_failures: default_circuit_breaker_failures(),
+            circuit_breaker_window_secs: default_circuit_breaker_window_secs(),
+            log_max_bytes: default_log_max_bytes(),
+            log_backups: default_log_backups(),
             scheduler_poll_secs: default_scheduler_poll_secs(),
             scheduler_retries: default_scheduler_retries(),
         }
     }
 }
 
+/// Provider failures that `[reliability] provider_retry_on` can select.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RetryOn {
+    /// Timeouts (including HTTP 408) and connection errors
+    #[serde(rename = "timeout")]
+    Timeout,
+    #[serde(rename = "429")]
+    RateLimited,
+    #[serde(rename = "5xx")]
+    ServerError,
+}
+
 // ── Heartbeat ────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HeartbeatConfig {
     pub enabled: bool,
+    /// How often tasks without a schedule directive run, and how soon a
+    /// failed scheduled task is retried
     pub interval_minutes: u32,
+    /// A task still running after this long is cancelled and reported
+    #[serde(default = "default_heartbeat_task_timeout_minutes")]
+    pub task_timeout_minutes: u32,
+    /// How many heartbeat tasks run at the same time
+    #[serde(default = "default_heartbeat_max_parallel_tasks")]
+    pub max_parallel_tasks: usize,
+    /// Add a reminder of overdue tasks from the task list to the due tasks
+    #[serde(default)]
+    pub overdue_tasks: bool,
+    /// Where task results are sent; without it they only reach the daemon log
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify_channel: Option<HeartbeatNotifyConfig>,
+}
+
+fn default_heartbeat_task_timeout_minutes() -> u32 {
+    10
+}
+
+fn default_heartbeat_max_parallel_tasks() -> usize {
+    2
 }
 
 impl Default for HeartbeatConfig {
@@ -508,10 +1214,64 @@ impl Default for HeartbeatConfig {
         Self {
             enabled: false,
             interval_minutes: 30,
+            task_timeout_minutes: default_heartbeat_task_timeout_minutes(),
+            max_parallel_tasks: default_heartbeat_max_parallel_tasks(),
+            overdue_tasks: false,
+            notify_channel: None,
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatNotifyConfig {
+    /// "telegram", "slack", "discord" or "webhook"
+    pub channel: String,
+    /// Chat/channel id to post to, or the URL for "webhook"
+    pub target: String,
+    /// Drop responses the agent marks as having nothing to report
+    #[serde(default = "default_true")]
+    pub quiet: bool,
+}
+
+impl From<&HeartbeatNotifyConfig> for NotifyConfig {
+    fn from(heartbeat: &HeartbeatNotifyConfig) -> Self {
+        Self {
+            channel: heartbeat.channel.clone(),
+            target: heartbeat.target.clone(),
+            threshold: if heartbeat.quiet {
+                NotifyThreshold::Actionable
+            } else {
+                NotifyThreshold::All
+            },
+        }
+    }
+}
+
+// ── Notify ───────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotifyConfig {
+    /// "telegram", "slack", "discord" or "webhook"
+    pub channel: String,
+    /// Chat/channel id to post to, or the URL for "webhook"
+    pub target: String,
+    /// Which results are worth a message
+    #[serde(default)]
+    pub threshold: NotifyThreshold,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyThreshold {
+    /// Every result
+    All,
+    /// Failures, and results with something for the user to act on
+    #[default]
+    Actionable,
+    /// Only failures
+    Failures,
+}
+
 // ── Tunnel ──────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -591,6 +1351,41 @@ pub struct ChannelsConfig {
     pub matrix: Option<MatrixConfig>,
     pub whatsapp: Option<WhatsAppConfig>,
     pub irc: Option<IrcConfig>,
+    /// Reject inbound messages longer than this many characters (0 = unlimited)
+    #[serde(default = "default_max_inbound_chars")]
+    pub max_inbound_chars: usize,
+    /// Forget a chat's conversation history after this many idle seconds
+    /// (default: 86400, 0 = never)
+    #[serde(default = "default_conversation_idle_secs")]
+    pub conversation_idle_secs: u64,
+    /// Write senders an allowlist rejects to the audit log and tell the
+    /// owner through `[notify]`, with the command that admits them
+    #[serde(default)]
+    pub report_rejected_senders: bool,
+    /// Remember inbound message ids this many seconds, dropping messages a
+    /// channel delivers again (default: 86400, 0 = until evicted by size)
+    #[serde(default = "default_dedup_ttl_secs")]
+    pub dedup_ttl_secs: u64,
+    /// Most message ids remembered, oldest dropped first (default: 10000,
+    /// 0 = no de-duplication)
+    #[serde(default = "default_dedup_max_entries")]
+    pub dedup_max_entries: usize,
+}
+
+fn default_max_inbound_chars() -> usize {
+    32_000
+}
+
+fn default_conversation_idle_secs() -> u64 {
+    86_400
+}
+
+fn default_dedup_ttl_secs() -> u64 {
+    86_400
+}
+
+fn default_dedup_max_entries() -> usize {
+    10_000
 }
 
 impl Default for ChannelsConfig {
@@ -605,6 +1400,11 @@ impl Default for ChannelsConfig {
             matrix: None,
             whatsapp: None,
             irc: None,
+            max_inbound_chars: default_max_inbound_chars(),
+            conversation_idle_secs: default_conversation_idle_secs(),
+            report_rejected_senders: false,
+            dedup_ttl_secs: default_dedup_ttl_secs(),
+            dedup_max_entries: default_dedup_max_entries(),
         }
     }
 }
@@ -621,6 +1421,14 @@ pub struct DiscordConfig {
     pub guild_id: Option<String>,
     #[serde(default)]
     pub allowed_users: Vec<String>,
+    /// Answer server messages in a thread started from the user's message,
+    /// reused for that user's later messages in the same channel.
+    #[serde(default)]
+    pub reply_in_thread: bool,
+    /// In servers, only answer messages that @mention the bot (and messages
+    /// in its own threads). DMs and `/jarvis` are always answered.
+    #[serde(default)]
+    pub respond_to_mentions_only: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -702,18 +1510,21 @@ fn default_irc_port() -> u16 {
 
 impl Default for Config {
     fn default() -> Self {
-        let home =
-            UserDirs::new().map_or_else(|| PathBuf::from("."), |u| u.home_dir().to_path_buf());
-        let jarvis_dir = home.join(".jarvis");
+        let jarvis_dir = super::profile::config_dir().unwrap_or_else(|_| PathBuf::from(".jarvis"));
 
         Self {
             workspace_dir: jarvis_dir.join("workspace"),
             config_path: jarvis_dir.join("config.toml"),
+            env: EnvProvenance::default(),
+            unknown_keys: Vec::new(),
+            config_version: CONFIG_VERSION,
             api_key: None,
             default_provider: Some("openrouter".to_string()),
             default_model: Some("anthropic/claude-sonnet-4-20250514".to_string()),
             default_temperature: 0.7,
+            strict_config: false,
             observability: ObservabilityConfig::default(),
+            logging: LoggingConfig::default(),
             autonomy: AutonomyConfig::default(),
             runtime: RuntimeConfig::default(),
             reliability: ReliabilityConfig::default(),
@@ -727,30 +1538,45 @@ impl Default for Config {
             browser: BrowserConfig::default(),
             identity: IdentityConfig::default(),
             brave_search: BraveSearchConfig::default(),
+            git: GitConfig::default(),
+            web_fetch: WebFetchConfig::default(),
+            tools: ToolsConfig::default(),
+            notify: None,
         }
     }
 }
 
 impl Config {
+    /// `~/.jarvis/config.toml`, or the active profile's
+    /// (`~/.jarvis/profiles/<name>/config.toml`).
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(super::profile::config_dir()?.join("config.toml"))
+    }
+
     pub fn load_or_init() -> Result<Self> {
-        let home = UserDirs::new()
-            .map(|u| u.home_dir().to_path_buf())
-            .context("无法找到用户主目录")?;
-        let jarvis_dir = home.join(".jarvis");
-        let config_path = jarvis_dir.join("config.toml");
+        let config_path = Self::default_path()?;
+        let jarvis_dir = config_path
+            .parent()
+            .context("无法确定配置目录")?
+            .to_path_buf();
+
+        // Profiles are created explicitly, so a typo doesn't start a blank one
+        if let Some(name) = super::profile::active()
+            && !jarvis_dir.exists()
+        {
+            anyhow::bail!(
+                "profile「{name}」不存在（{}）。运行 `jarvis profile create {name}` 创建",
+                jarvis_dir.display()
+            );
+        }
 
         if !jarvis_dir.exists() {
             fs::create_dir_all(&jarvis_dir).context("创建 .jarvis 目录失败")?;
             fs::create_dir_all(jarvis_dir.join("workspace")).context("创建 workspace 目录失败")?;
         }
 
-        if config_path.exists() {
-            let contents = fs::read_to_string(&config_path).context("读取配置文件失败")?;
-            let mut config: Config = toml::from_str(&contents).context("解析配置文件失败")?;
-            // Set computed paths that are skipped during serialization
-            config.config_path.clone_from(&config_path);
-            config.workspace_dir = jarvis_dir.join("workspace");
-            Ok(config)
+        let mut config = if config_path.exists() {
+            Self::load_from(&config_path)?
         } else {
             let config = Config {
                 config_path: config_path.clone(),
@@ -758,72 +1584,210 @@ impl Config {
                 ..Config::default()
             };
             config.save()?;
-            Ok(config)
+            config
+        };
+        config.apply_env_overrides();
+        for key in &config.unknown_keys {
+            tracing::warn!(
+                "config.toml 中的 {}：{key}，已忽略。以后的版本将拒绝加载含未知配置项的文件，\
+                 设置 strict_config = true 可立即启用",
+                key.path
+            );
+        }
+        for problem in config.check() {
+            if problem.is_error() {
+                tracing::warn!(
+                    "配置问题 {}：{}（运行 `jarvis config validate` 查看全部）",
+                    problem.path,
+                    problem.message
+                );
+            } else {
+                tracing::debug!("配置警告 {}：{}", problem.path, problem.message);
+            }
+        }
+        Ok(config)
+    }
+
+    /// Load an existing config file, decrypting its secrets with the key
+    /// file next to it. Plaintext secrets found while `secrets.encrypt` is on
+    /// are encrypted and the file rewritten.
+    pub fn load_from(config_path: &Path) -> Result<Self> {
+        let (config, needs_encryption) = Self::read(config_path)?;
+        if config.secrets.encrypt && needs_encryption > 0 {
+            config.save()?;
+            tracing::info!("已加密 config.toml 中的 {needs_encryption} 个明文密钥");
+        }
+        Ok(config)
+    }
+
+    /// Parse the config file, returning it with the number of plaintext
+    /// secrets that should be encrypted.
+    fn read(config_path: &Path) -> Result<(Self, usize)> {
+        let jarvis_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+        let contents = fs::read_to_string(config_path).context("读取配置文件失败")?;
+        let mut raw: toml::Value = toml::from_str(&contents).context("解析配置文件失败")?;
+        let store = SecretStore::new(jarvis_dir, true);
+        let outcome = secrets::decrypt_fields(&mut raw, &store)
+            .with_context(|| format!("无法解密 {} 中的密钥", config_path.display()))?;
+        let placeholders = env::interpolate(&mut raw)?;
+
+        let mut config: Config = raw.clone().try_into().context("解析配置文件失败")?;
+        if config.config_version > CONFIG_VERSION {
+            tracing::warn!(
+                "{} 由更新版本的 jarvis 写入（config_version = {}，当前支持 {CONFIG_VERSION}），部分配置可能不会生效",
+                config_path.display(),
+                config.config_version
+            );
+        }
+        config.env.placeholders = placeholders;
+        config.unknown_keys = unknown::find(&raw, &config, Some(&contents))?;
+        if config.strict_config && !config.unknown_keys.is_empty() {
+            let listed: Vec<String> = config
+                .unknown_keys
+                .iter()
+                .map(|key| format!("  {}：{key}", key.path))
+                .collect();
+            anyhow::bail!(
+                "{} 中有未知配置项（strict_config = true）：\n{}",
+                config_path.display(),
+                listed.join("\n")
+            );
+        }
+        // Set computed paths that are skipped during serialization
+        config.config_path = config_path.to_path_buf();
+        config.workspace_dir = jarvis_dir.join("workspace");
+        Ok((config, outcome.needs_encryption))
+    }
+
+    /// Read-modify-write the config file at `config_path`: reload it, apply
+    /// `change` and save it if anything changed, all under the write lock,
+    /// so a change another process saves meanwhile is never overwritten.
+    /// Returns what `change` returned and the backup of the replaced file.
+    ///
+    /// Waits for the lock, so async code should call it via `spawn_blocking`.
+    pub fn update<T>(
+        config_path: &Path,
+        change: impl FnOnce(&mut Config) -> Result<T>,
+    ) -> Result<(T, Option<PathBuf>)> {
+        let lock = store::lock(config_path)?;
+        let (mut config, needs_encryption) = Self::read(config_path)?;
+        let before = toml::Value::try_from(&config).context("序列化配置失败")?;
+        let out = change(&mut config)?;
+        let after = toml::Value::try_from(&config).context("序列化配置失败")?;
+        if before == after && !(config.secrets.encrypt && needs_encryption > 0) {
+            return Ok((out, None));
         }
+        let backup = config.write_locked(&lock)?;
+        Ok((out, backup))
     }
 
-    /// Apply environment variable overrides to config
+    /// Apply `JARVIS_*` environment variable overrides to config (the full
+    /// list is in [`super::env`]). Applied values are recorded in `self.env`
+    /// so `save` doesn't write them into the file.
     pub fn apply_env_overrides(&mut self) {
-        // API Key: JARVIS_API_KEY or API_KEY
-        if let Ok(key) = std::env::var("JARVIS_API_KEY").or_else(|_| std::env::var("API_KEY")) {
-            if !key.is_empty() {
-                self.api_key = Some(key);
-            }
+        let before = toml::Value::try_from(&*self).ok();
+        let mut applied: Vec<(&str, String)> = Vec::new();
+
+        if let Some((var, key)) = env::first_set(&["JARVIS_API_KEY", "API_KEY"]) {
+            self.api_key = Some(key);
+            applied.push(("api_key", var));
         }
 
-        // Provider: JARVIS_PROVIDER or PROVIDER
-        if let Ok(provider) =
-            std::env::var("JARVIS_PROVIDER").or_else(|_| std::env::var("PROVIDER"))
+        if let Some((var, provider)) =
+            env::first_set(&["JARVIS_DEFAULT_PROVIDER", "JARVIS_PROVIDER", "PROVIDER"])
         {
-            if !provider.is_empty() {
-                self.default_provider = Some(provider);
-            }
+            self.default_provider = Some(provider);
+            applied.push(("default_provider", var));
         }
 
-        // Model: JARVIS_MODEL
-        if let Ok(model) = std::env::var("JARVIS_MODEL") {
-            if !model.is_empty() {
-                self.default_model = Some(model);
-            }
+        if let Some((var, model)) = env::first_set(&["JARVIS_DEFAULT_MODEL", "JARVIS_MODEL"]) {
+            self.default_model = Some(model);
+            applied.push(("default_model", var));
         }
 
-        // Workspace directory: JARVIS_WORKSPACE
-        if let Ok(workspace) = std::env::var("JARVIS_WORKSPACE") {
-            if !workspace.is_empty() {
-                self.workspace_dir = PathBuf::from(workspace);
-            }
+        if let Some((var, temp)) =
+            env::first_parsed(&["JARVIS_DEFAULT_TEMPERATURE", "JARVIS_TEMPERATURE"], |v| {
+                v.parse::<f64>()
+                    .ok()
+                    .filter(|temp| (0.0..=2.0).contains(temp))
+            })
+        {
+            self.default_temperature = temp;
+            applied.push(("default_temperature", var));
         }
 
-        // Gateway port: JARVIS_GATEWAY_PORT or PORT
-        if let Ok(port_str) =
-            std::env::var("JARVIS_GATEWAY_PORT").or_else(|_| std::env::var("PORT"))
+        if let Some((var, workspace)) = env::first_set(&["JARVIS_WORKSPACE"]) {
+            self.workspace_dir = PathBuf::from(workspace);
+            applied.push(("workspace_dir", var));
+        }
+
+        if let Some((var, port)) =
+            env::first_parsed(&["JARVIS_GATEWAY_PORT", "PORT"], |v| v.parse::<u16>().ok())
         {
-            if let Ok(port) = port_str.parse::<u16>() {
-                self.gateway.port = port;
-            }
+            self.gateway.port = port;
+            applied.push(("gateway.port", var));
         }
 
-        // Gateway host: JARVIS_GATEWAY_HOST or HOST
-        if let Ok(host) = std::env::var("JARVIS_GATEWAY_HOST").or_else(|_| std::env::var("HOST")) {
-            if !host.is_empty() {
-                self.gateway.host = host;
-            }
+        if let Some((var, host)) = env::first_set(&["JARVIS_GATEWAY_HOST", "HOST"]) {
+            self.gateway.host = host;
+            applied.push(("gateway.host", var));
         }
 
-        // Temperature: JARVIS_TEMPERATURE
-        if let Ok(temp_str) = std::env::var("JARVIS_TEMPERATURE") {
-            if let Ok(temp) = temp_str.parse::<f64>() {
-                if (0.0..=2.0).contains(&temp) {
-                    self.default_temperature = temp;
-                }
-            }
+        if let Some((var, enabled)) =
+            env::first_parsed(&["JARVIS_HEARTBEAT_ENABLED"], env::parse_bool)
+        {
+            self.heartbeat.enabled = enabled;
+            applied.push(("heartbeat.enabled", var));
+        }
+
+        if let Some((var, minutes)) =
+            env::first_parsed(&["JARVIS_HEARTBEAT_INTERVAL_MINUTES"], |v| {
+                v.parse::<u32>().ok()
+            })
+        {
+            self.heartbeat.interval_minutes = minutes;
+            applied.push(("heartbeat.interval_minutes", var));
+        }
+
+        if let Some((var, backend)) = env::first_set(&["JARVIS_MEMORY_BACKEND"]) {
+            self.memory.backend = backend;
+            applied.push(("memory.backend", var));
+        }
+
+        if !applied.is_empty() {
+            let after = toml::Value::try_from(&*self).ok();
+            self.env
+                .record_overrides(before.as_ref(), after.as_ref(), applied);
         }
     }
 
+    /// Write the config file, encrypting secrets when `secrets.encrypt` is on.
+    /// The write is locked and atomic, and the file it replaces is kept in
+    /// `backups/` (see [`store`]).
     pub fn save(&self) -> Result<()> {
-        let toml_str = toml::to_string_pretty(self).context("序列化配置失败")?;
-        fs::write(&self.config_path, toml_str).context("写入配置文件失败")?;
-        Ok(())
+        self.write().map(|_| ())
+    }
+
+    fn write(&self) -> Result<Option<PathBuf>> {
+        let lock = store::lock(&self.config_path)?;
+        self.write_locked(&lock)
+    }
+
+    fn write_locked(&self, lock: &store::ConfigLock) -> Result<Option<PathBuf>> {
+        let mut raw = toml::Value::try_from(self).context("序列化配置失败")?;
+        if let Some(table) = raw.as_table_mut() {
+            table.insert("config_version".into(), CONFIG_VERSION.into());
+        }
+        self.env.restore(&mut raw);
+        if self.secrets.encrypt {
+            let jarvis_dir = self.config_path.parent().unwrap_or_else(|| Path::new("."));
+            secrets::encrypt_fields(&mut raw, &SecretStore::new(jarvis_dir, true))?;
+        }
+        let toml_str = toml::to_string_pretty(&raw).context("序列化配置失败")?;
+
+        let backup = store::backup(&self.config_path, lock)?;
+        store::replace(&self.config_path, &toml_str, lock)?;
+        Ok(backup)
     }
 }
 
@@ -851,6 +1815,27 @@ mod tests {
         assert_eq!(o.backend, "none");
     }
 
+    #[test]
+    fn logging_directives_prefer_the_cli_level() {
+        let logging: LoggingConfig = toml::from_str(
+            r#"
+level = "warn"
+targets = ["jarvis::channels::telegram=debug", " "]
+format = "json"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            logging.directives(None),
+            "warn,jarvis::channels::telegram=debug"
+        );
+        assert_eq!(
+            logging.directives(Some("trace")),
+            "trace,jarvis::channels::telegram=debug"
+        );
+        assert!(toml::from_str::<LoggingConfig>("format = \"xml\"").is_err());
+    }
+
     #[test]
     fn autonomy_config_default() {
         let a = AutonomyConfig::default();
@@ -867,6 +1852,8 @@ mod tests {
     fn runtime_config_default() {
         let r = RuntimeConfig::default();
         assert_eq!(r.kind, "native");
+        assert_eq!(r.docker.image, "buildpack-deps:bookworm-scm");
+        assert_eq!(r.docker.network, "bridge");
     }
 
     #[test]
@@ -874,6 +1861,58 @@ mod tests {
         let h = HeartbeatConfig::default();
         assert!(!h.enabled);
         assert_eq!(h.interval_minutes, 30);
+        assert_eq!(h.task_timeout_minutes, 10);
+        assert_eq!(h.max_parallel_tasks, 2);
+        assert!(h.notify_channel.is_none());
+    }
+
+    #[test]
+    fn heartbeat_notify_channel_defaults_to_quiet() {
+        let h: HeartbeatConfig = toml::from_str(
+            r#"
+enabled = true
+interval_minutes = 30
+
+[notify_channel]
+channel = "telegram"
+target = "123456789"
+"#,
+        )
+        .unwrap();
+        assert_eq!(h.task_timeout_minutes, 10);
+        let notify = h.notify_channel.unwrap();
+        assert_eq!(notify.channel, "telegram");
+        assert_eq!(notify.target, "123456789");
+        assert!(notify.quiet);
+    }
+
+    #[test]
+    fn notify_block_defaults_to_actionable_results() {
+        let config: Config = toml::from_str(
+            r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[notify]
+channel = "telegram"
+target = "123456789"
+"#,
+        )
+        .unwrap();
+        let notify = config.notify.unwrap();
+        assert_eq!(notify.threshold, NotifyThreshold::Actionable);
+
+        let loud = HeartbeatNotifyConfig {
+            channel: "webhook".into(),
+            target: "https://example.com/hook".into(),
+            quiet: false,
+        };
+        assert_eq!(NotifyConfig::from(&loud).threshold, NotifyThreshold::All);
+        let failures: NotifyConfig =
+            toml::from_str("channel = \"slack\"\ntarget = \"C1\"\nthreshold = \"failures\"")
+                .unwrap();
+        assert_eq!(failures.threshold, NotifyThreshold::Failures);
     }
 
     #[test]
@@ -898,34 +1937,66 @@ mod tests {
     // ── Serde round-trip ─────────────────────────────────────
 
     #[test]
+    #[allow(clippy::too_many_lines)]
     fn config_toml_roundtrip() {
         let config = Config {
             workspace_dir: PathBuf::from("/tmp/test/workspace"),
             config_path: PathBuf::from("/tmp/test/config.toml"),
+            env: EnvProvenance::default(),
+            unknown_keys: Vec::new(),
+            config_version: CONFIG_VERSION,
             api_key: Some("sk-test-key".into()),
             default_provider: Some("openrouter".into()),
             default_model: Some("gpt-4o".into()),
             default_temperature: 0.5,
+            strict_config: false,
             observability: ObservabilityConfig {
                 backend: "log".into(),
+                ..ObservabilityConfig::default()
+            },
+            logging: LoggingConfig {
+                level: "warn".into(),
+                targets: vec!["jarvis::channels=debug".into()],
+                format: LogFormat::Json,
             },
             autonomy: AutonomyConfig {
                 level: AutonomyLevel::Full,
                 workspace_only: false,
                 allowed_commands: vec!["docker".into()],
+                command_policy_mode: CommandPolicyMode::Deny,
+                denied_commands: vec!["rm *".into()],
+                destructive_commands: vec!["dd".into()],
                 forbidden_paths: vec!["/secret".into()],
                 max_actions_per_hour: 50,
                 max_cost_per_day_cents: 1000,
                 max_tool_iterations: 25,
                 max_history_turns: 20,
+                compact_history: false,
+                approval_timeout_secs: 120,
+                persist_approvals: false,
+                shell_timeout_secs: 120,
+                max_shell_timeout_secs: 600,
+                rate_limits: RateLimitsConfig::default(),
             },
             runtime: RuntimeConfig {
                 kind: "docker".into(),
+                docker: DockerRuntimeConfig {
+                    memory: Some("2g".into()),
+                    ..DockerRuntimeConfig::default()
+                },
+                sandbox: SandboxRuntimeConfig {
+                    network: true,
+                    ..SandboxRuntimeConfig::default()
+                },
             },
             reliability: ReliabilityConfig::default(),
             heartbeat: HeartbeatConfig {
                 enabled: true,
                 interval_minutes: 15,
+                task_timeout_minutes: 10,
+                max_parallel_tasks: 2,
+                overdue_tasks: false,
+                notify_channel: None,
             },
             channels_config: ChannelsConfig {
                 cli: true,
@@ -940,6 +2011,11 @@ mod tests {
                 matrix: None,
                 whatsapp: None,
                 irc: None,
+                max_inbound_chars: 32_000,
+                conversation_idle_secs: 86_400,
+                report_rejected_senders: false,
+                dedup_ttl_secs: 86_400,
+                dedup_max_entries: 10_000,
             },
             memory: MemoryConfig::default(),
             tunnel: TunnelConfig::default(),
@@ -949,6 +2025,10 @@ mod tests {
             browser: BrowserConfig::default(),
             identity: IdentityConfig::default(),
             brave_search: BraveSearchConfig::default(),
+            git: GitConfig::default(),
+            web_fetch: WebFetchConfig::default(),
+            tools: ToolsConfig::default(),
+            notify: None,
         };
 
         let toml_str = toml::to_string_pretty(&config).unwrap();
@@ -959,9 +2039,16 @@ mod tests {
         assert_eq!(parsed.default_model, config.default_model);
         assert!((parsed.default_temperature - config.default_temperature).abs() < f64::EPSILON);
         assert_eq!(parsed.observability.backend, "log");
+        assert_eq!(parsed.logging.format, LogFormat::Json);
+        assert_eq!(
+            parsed.logging.directives(None),
+            "warn,jarvis::channels=debug"
+        );
         assert_eq!(parsed.autonomy.level, AutonomyLevel::Full);
         assert!(!parsed.autonomy.workspace_only);
         assert_eq!(parsed.runtime.kind, "docker");
+        assert_eq!(parsed.runtime.docker.memory.as_deref(), Some("2g"));
+        assert!(parsed.runtime.sandbox.network);
         assert!(parsed.heartbeat.enabled);
         assert_eq!(parsed.heartbeat.interval_minutes, 15);
         assert!(parsed.channels_config.telegram.is_some());
@@ -982,6 +2069,8 @@ default_temperature = 0.7
         assert!(parsed.api_key.is_none());
         assert!(parsed.default_provider.is_none());
         assert_eq!(parsed.observability.backend, "none");
+        assert_eq!(parsed.logging.directives(None), "info");
+        assert_eq!(parsed.logging.format, LogFormat::Pretty);
         assert_eq!(parsed.autonomy.level, AutonomyLevel::Supervised);
         assert_eq!(parsed.runtime.kind, "native");
         assert!(!parsed.heartbeat.enabled);
@@ -1002,11 +2091,16 @@ default_temperature = 0.7
         let config = Config {
             workspace_dir: dir.join("workspace"),
             config_path: config_path.clone(),
+            env: EnvProvenance::default(),
+            unknown_keys: Vec::new(),
+            config_version: CONFIG_VERSION,
             api_key: Some("sk-roundtrip".into()),
             default_provider: Some("openrouter".into()),
             default_model: Some("test-model".into()),
             default_temperature: 0.9,
+            strict_config: false,
             observability: ObservabilityConfig::default(),
+            logging: LoggingConfig::default(),
             autonomy: AutonomyConfig::default(),
             runtime: RuntimeConfig::default(),
             reliability: ReliabilityConfig::default(),
@@ -1020,13 +2114,21 @@ default_temperature = 0.7
             browser: BrowserConfig::default(),
             identity: IdentityConfig::default(),
             brave_search: BraveSearchConfig::default(),
+            git: GitConfig::default(),
+            web_fetch: WebFetchConfig::default(),
+            tools: ToolsConfig::default(),
+            notify: None,
         };
 
         config.save().unwrap();
         assert!(config_path.exists());
 
         let contents = fs::read_to_string(&config_path).unwrap();
-        let loaded: Config = toml::from_str(&contents).unwrap();
+        assert!(
+            !contents.contains("sk-roundtrip"),
+            "secrets are encrypted at rest"
+        );
+        let loaded = Config::load_from(&config_path).unwrap();
         assert_eq!(loaded.api_key.as_deref(), Some("sk-roundtrip"));
         assert_eq!(loaded.default_model.as_deref(), Some("test-model"));
         assert!((loaded.default_temperature - 0.9).abs() < f64::EPSILON);
@@ -1034,6 +2136,117 @@ default_temperature = 0.7
         let _ = fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn save_keeps_env_placeholders_and_file_values() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config_path = dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            r#"
+api_key = "${JARVIS_TEST_SCHEMA_KEY}"
+default_model = "file-model"
+default_temperature = 0.7
+"#,
+        )
+        .unwrap();
+
+        unsafe { std::env::set_var("JARVIS_TEST_SCHEMA_KEY", "sk-from-env") };
+        let mut config = Config::load_from(&config_path).unwrap();
+        unsafe { std::env::remove_var("JARVIS_TEST_SCHEMA_KEY") };
+        assert_eq!(config.api_key.as_deref(), Some("sk-from-env"));
+        assert!(config.env.placeholders.contains_key("api_key"));
+
+        let before = toml::Value::try_from(&config).unwrap();
+        config.default_model = Some("env-model".into());
+        let after = toml::Value::try_from(&config).unwrap();
+        config.env.record_overrides(
+            Some(&before),
+            Some(&after),
+            vec![("default_model", "JARVIS_DEFAULT_MODEL".into())],
+        );
+        config.default_temperature = 0.2;
+        config.save().unwrap();
+
+        let contents = fs::read_to_string(&config_path).unwrap();
+        assert!(contents.contains("${JARVIS_TEST_SCHEMA_KEY}"), "{contents}");
+        assert!(!contents.contains("sk-from-env"));
+        assert!(contents.contains("file-model"));
+        assert!(contents.contains("0.2"));
+    }
+
+    #[test]
+    fn update_applies_the_change_to_the_file_as_saved_meanwhile() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config_path = dir.path().join("config.toml");
+        fs::write(&config_path, "default_temperature = 0.7\n").unwrap();
+
+        // Another writer saves its change after this one loaded the file
+        let stale = Config::load_from(&config_path).unwrap();
+        Config::update(&config_path, |config| {
+            config.default_model = Some("other-writer".into());
+            Ok(())
+        })
+        .unwrap();
+        assert!(stale.default_model.is_none());
+
+        let (previous, backup) = Config::update(&config_path, |config| {
+            Ok(std::mem::replace(&mut config.default_temperature, 0.3))
+        })
+        .unwrap();
+        assert!((previous - 0.7).abs() < f64::EPSILON);
+        assert!(backup.is_some());
+        let loaded = Config::load_from(&config_path).unwrap();
+        assert_eq!(loaded.default_model.as_deref(), Some("other-writer"));
+        assert!((loaded.default_temperature - 0.3).abs() < f64::EPSILON);
+
+        let ((), backup) = Config::update(&config_path, |_| Ok(())).unwrap();
+        assert!(backup.is_none(), "nothing changed, nothing written");
+    }
+
+    #[test]
+    fn load_encrypts_plaintext_channel_tokens_in_place() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config_path = dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            r#"
+default_temperature = 0.7
+
+[channels_config]
+cli = true
+
+[channels_config.telegram]
+bot_token = "123:PLAIN"
+allowed_users = []
+"#,
+        )
+        .unwrap();
+
+        let loaded = Config::load_from(&config_path).unwrap();
+        assert_eq!(
+            loaded.channels_config.telegram.unwrap().bot_token,
+            "123:PLAIN"
+        );
+        let contents = fs::read_to_string(&config_path).unwrap();
+        assert!(!contents.contains("123:PLAIN"), "{contents}");
+        assert!(contents.contains("enc2:"));
+
+        // Encryption off: plaintext is left alone
+        let plain_path = dir.path().join("plain.toml");
+        fs::write(
+            &plain_path,
+            "default_temperature = 0.7\napi_key = \"sk-plain\"\n[secrets]\nencrypt = false\n",
+        )
+        .unwrap();
+        assert_eq!(
+            Config::load_from(&plain_path).unwrap().api_key.as_deref(),
+            Some("sk-plain")
+        );
+        assert!(fs::read_to_string(&plain_path)
+            .unwrap()
+            .contains("sk-plain"));
+    }
+
     // ── Telegram / Discord config ────────────────────────────
 
     #[test]
@@ -1054,11 +2267,21 @@ default_temperature = 0.7
             bot_token: "discord-token".into(),
             guild_id: Some("12345".into()),
             allowed_users: vec![],
+            reply_in_thread: true,
+            respond_to_mentions_only: false,
         };
         let json = serde_json::to_string(&dc).unwrap();
         let parsed: DiscordConfig = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.bot_token, "discord-token");
         assert_eq!(parsed.guild_id.as_deref(), Some("12345"));
+        assert!(parsed.reply_in_thread);
+    }
+
+    #[test]
+    fn discord_config_reply_options_default_off() {
+        let parsed: DiscordConfig = toml::from_str("bot_token = \"tok\"").unwrap();
+        assert!(!parsed.reply_in_thread);
+        assert!(!parsed.respond_to_mentions_only);
     }
 
     #[test]
@@ -1067,6 +2290,8 @@ default_temperature = 0.7
             bot_token: "tok".into(),
             guild_id: None,
             allowed_users: vec![],
+            reply_in_thread: false,
+            respond_to_mentions_only: false,
         };
         let json = serde_json::to_string(&dc).unwrap();
         let parsed: DiscordConfig = serde_json::from_str(&json).unwrap();
@@ -1155,6 +2380,11 @@ default_temperature = 0.7
             }),
             whatsapp: None,
             irc: None,
+            max_inbound_chars: 32_000,
+            conversation_idle_secs: 86_400,
+            report_rejected_senders: false,
+            dedup_ttl_secs: 86_400,
+            dedup_max_entries: 10_000,
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -1312,6 +2542,11 @@ channel_id = "C123"
                 allowed_numbers: vec!["+1".into()],
             }),
             irc: None,
+            max_inbound_chars: 32_000,
+            conversation_idle_secs: 86_400,
+            report_rejected_senders: false,
+            dedup_ttl_secs: 86_400,
+            dedup_max_entries: 10_000,
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -1327,6 +2562,22 @@ channel_id = "C123"
         assert!(c.whatsapp.is_none());
     }
 
+    #[test]
+    fn channels_config_default_caps_inbound_chars() {
+        let c = ChannelsConfig::default();
+        assert_eq!(c.max_inbound_chars, 32_000);
+    }
+
+    #[test]
+    fn channels_config_missing_max_inbound_chars_uses_default() {
+        let parsed: ChannelsConfig = toml::from_str("cli = true").unwrap();
+        assert_eq!(parsed.max_inbound_chars, 32_000);
+        assert_eq!(parsed.conversation_idle_secs, 86_400);
+        assert!(!parsed.report_rejected_senders);
+        assert_eq!(parsed.dedup_ttl_secs, 86_400);
+        assert_eq!(parsed.dedup_max_entries, 10_000);
+    }
+
     // ══════════════════════════════════════════════════════════
     // SECURITY CHECKLIST TESTS — Gateway config
     // ══════════════════════════════════════════════════════════
@@ -1377,7 +2628,11 @@ channel_id = "C123"
             host: "127.0.0.1".into(),
             require_pairing: true,
             allow_public_bind: false,
+            trust_loopback: false,
             paired_tokens: vec!["zc_test_token".into()],
+            max_body_bytes: 65_536,
+            chat_session_idle_secs: 1800,
+            max_concurrent_runs: 4,
         };
         let toml_str = toml::to_string(&g).unwrap();
         let parsed: GatewayConfig = toml::from_str(&toml_str).unwrap();
@@ -1487,7 +2742,10 @@ enabled = true
 
     #[test]
     fn secrets_config_serde_roundtrip() {
-        let s = SecretsConfig { encrypt: false };
+        let s = SecretsConfig {
+            encrypt: false,
+            ..SecretsConfig::default()
+        };
         let toml_str = toml::to_string(&s).unwrap();
         let parsed: SecretsConfig = toml::from_str(&toml_str).unwrap();
         assert!(!parsed.encrypt);
@@ -1557,68 +2815,68 @@ default_temperature = 0.7
         let mut config = Config::default();
         assert!(config.api_key.is_none());
 
-        std::env::set_var("JARVIS_API_KEY", "sk-test-env-key");
+        unsafe { std::env::set_var("JARVIS_API_KEY", "sk-test-env-key") };
         config.apply_env_overrides();
         assert_eq!(config.api_key.as_deref(), Some("sk-test-env-key"));
 
-        std::env::remove_var("JARVIS_API_KEY");
+        unsafe { std::env::remove_var("JARVIS_API_KEY") };
     }
 
     #[test]
     fn env_override_api_key_fallback() {
         let mut config = Config::default();
 
-        std::env::remove_var("JARVIS_API_KEY");
-        std::env::set_var("API_KEY", "sk-fallback-key");
+        unsafe { std::env::remove_var("JARVIS_API_KEY") };
+        unsafe { std::env::set_var("API_KEY", "sk-fallback-key") };
         config.apply_env_overrides();
         assert_eq!(config.api_key.as_deref(), Some("sk-fallback-key"));
 
-        std::env::remove_var("API_KEY");
+        unsafe { std::env::remove_var("API_KEY") };
     }
 
     #[test]
     fn env_override_provider() {
         let mut config = Config::default();
 
-        std::env::set_var("JARVIS_PROVIDER", "anthropic");
+        unsafe { std::env::set_var("JARVIS_PROVIDER", "anthropic") };
         config.apply_env_overrides();
         assert_eq!(config.default_provider.as_deref(), Some("anthropic"));
 
-        std::env::remove_var("JARVIS_PROVIDER");
+        unsafe { std::env::remove_var("JARVIS_PROVIDER") };
     }
 
     #[test]
     fn env_override_provider_fallback() {
         let mut config = Config::default();
 
-        std::env::remove_var("JARVIS_PROVIDER");
-        std::env::set_var("PROVIDER", "openai");
+        unsafe { std::env::remove_var("JARVIS_PROVIDER") };
+        unsafe { std::env::set_var("PROVIDER", "openai") };
         config.apply_env_overrides();
         assert_eq!(config.default_provider.as_deref(), Some("openai"));
 
-        std::env::remove_var("PROVIDER");
+        unsafe { std::env::remove_var("PROVIDER") };
     }
 
     #[test]
     fn env_override_model() {
         let mut config = Config::default();
 
-        std::env::set_var("JARVIS_MODEL", "gpt-4o");
+        unsafe { std::env::set_var("JARVIS_MODEL", "gpt-4o") };
         config.apply_env_overrides();
         assert_eq!(config.default_model.as_deref(), Some("gpt-4o"));
 
-        std::env::remove_var("JARVIS_MODEL");
+        unsafe { std::env::remove_var("JARVIS_MODEL") };
     }
 
     #[test]
     fn env_override_workspace() {
         let mut config = Config::default();
 
-        std::env::set_var("JARVIS_WORKSPACE", "/custom/workspace");
+        unsafe { std::env::set_var("JARVIS_WORKSPACE", "/custom/workspace") };
         config.apply_env_overrides();
         assert_eq!(config.workspace_dir, PathBuf::from("/custom/workspace"));
 
-        std::env::remove_var("JARVIS_WORKSPACE");
+        unsafe { std::env::remove_var("JARVIS_WORKSPACE") };
     }
 
     #[test]
@@ -1626,11 +2884,33 @@ default_temperature = 0.7
         let mut config = Config::default();
         let original_provider = config.default_provider.clone();
 
-        std::env::set_var("JARVIS_PROVIDER", "");
+        unsafe { std::env::set_var("JARVIS_PROVIDER", "") };
         config.apply_env_overrides();
         assert_eq!(config.default_provider, original_provider);
 
-        std::env::remove_var("JARVIS_PROVIDER");
+        unsafe { std::env::remove_var("JARVIS_PROVIDER") };
+    }
+
+    #[test]
+    fn env_override_records_provenance() {
+        let mut config = Config::default();
+
+        unsafe { std::env::set_var("JARVIS_HEARTBEAT_ENABLED", "yes") };
+        unsafe { std::env::set_var("JARVIS_MEMORY_BACKEND", "markdown") };
+        config.apply_env_overrides();
+        assert!(config.heartbeat.enabled);
+        assert_eq!(config.memory.backend, "markdown");
+        assert_eq!(
+            config.env.overrides["heartbeat.enabled"].var,
+            "JARVIS_HEARTBEAT_ENABLED"
+        );
+        assert_eq!(
+            config.env.overrides["memory.backend"].var,
+            "JARVIS_MEMORY_BACKEND"
+        );
+
+        unsafe { std::env::remove_var("JARVIS_HEARTBEAT_ENABLED") };
+        unsafe { std::env::remove_var("JARVIS_MEMORY_BACKEND") };
     }
 
     #[test]
@@ -1638,23 +2918,23 @@ default_temperature = 0.7
         let mut config = Config::default();
         assert_eq!(config.gateway.port, 3000);
 
-        std::env::set_var("JARVIS_GATEWAY_PORT", "8080");
+        unsafe { std::env::set_var("JARVIS_GATEWAY_PORT", "8080") };
         config.apply_env_overrides();
         assert_eq!(config.gateway.port, 8080);
 
-        std::env::remove_var("JARVIS_GATEWAY_PORT");
+        unsafe { std::env::remove_var("JARVIS_GATEWAY_PORT") };
     }
 
     #[test]
     fn env_override_port_fallback() {
         let mut config = Config::default();
 
-        std::env::remove_var("JARVIS_GATEWAY_PORT");
-        std::env::set_var("PORT", "9000");
+        unsafe { std::env::remove_var("JARVIS_GATEWAY_PORT") };
+        unsafe { std::env::set_var("PORT", "9000") };
         config.apply_env_overrides();
         assert_eq!(config.gateway.port, 9000);
 
-        std::env::remove_var("PORT");
+        unsafe { std::env::remove_var("PORT") };
     }
 
     #[test]
@@ -1662,53 +2942,53 @@ default_temperature = 0.7
         let mut config = Config::default();
         assert_eq!(config.gateway.host, "127.0.0.1");
 
-        std::env::set_var("JARVIS_GATEWAY_HOST", "0.0.0.0");
+        unsafe { std::env::set_var("JARVIS_GATEWAY_HOST", "0.0.0.0") };
         config.apply_env_overrides();
         assert_eq!(config.gateway.host, "0.0.0.0");
 
-        std::env::remove_var("JARVIS_GATEWAY_HOST");
+        unsafe { std::env::remove_var("JARVIS_GATEWAY_HOST") };
     }
 
     #[test]
     fn env_override_host_fallback() {
         let mut config = Config::default();
 
-        std::env::remove_var("JARVIS_GATEWAY_HOST");
-        std::env::set_var("HOST", "0.0.0.0");
+        unsafe { std::env::remove_var("JARVIS_GATEWAY_HOST") };
+        unsafe { std::env::set_var("HOST", "0.0.0.0") };
         config.apply_env_overrides();
         assert_eq!(config.gateway.host, "0.0.0.0");
 
-        std::env::remove_var("HOST");
+        unsafe { std::env::remove_var("HOST") };
     }
 
     #[test]
     fn env_override_temperature() {
         let mut config = Config::default();
 
-        std::env::set_var("JARVIS_TEMPERATURE", "0.5");
+        unsafe { std::env::set_var("JARVIS_TEMPERATURE", "0.5") };
         config.apply_env_overrides();
         assert!((config.default_temperature - 0.5).abs() < f64::EPSILON);
 
-        std::env::remove_var("JARVIS_TEMPERATURE");
+        unsafe { std::env::remove_var("JARVIS_TEMPERATURE") };
     }
 
     #[test]
     fn env_override_temperature_out_of_range_ignored() {
         // Clean up any leftover env vars from other tests
-        std::env::remove_var("JARVIS_TEMPERATURE");
+        unsafe { std::env::remove_var("JARVIS_TEMPERATURE") };
 
         let mut config = Config::default();
         let original_temp = config.default_temperature;
 
         // Temperature > 2.0 should be ignored
-        std::env::set_var("JARVIS_TEMPERATURE", "3.0");
+        unsafe { std::env::set_var("JARVIS_TEMPERATURE", "3.0") };
         config.apply_env_overrides();
         assert!(
             (config.default_temperature - original_temp).abs() < f64::EPSILON,
             "Temperature 3.0 should be ignored (out of range)"
         );
 
-        std::env::remove_var("JARVIS_TEMPERATURE");
+        unsafe { std::env::remove_var("JARVIS_TEMPERATURE") };
     }
 
     #[test]
@@ -1716,11 +2996,11 @@ default_temperature = 0.7
         let mut config = Config::default();
         let original_port = config.gateway.port;
 
-        std::env::set_var("PORT", "not_a_number");
+        unsafe { std::env::set_var("PORT", "not_a_number") };
         config.apply_env_overrides();
         assert_eq!(config.gateway.port, original_port);
 
-        std::env::remove_var("PORT");
+        unsafe { std::env::remove_var("PORT") };
     }
 
     #[test]
@@ -1730,6 +3010,10 @@ default_temperature = 0.7
         assert_eq!(g.host, "127.0.0.1");
         assert!(g.require_pairing);
         assert!(!g.allow_public_bind);
+        assert!(!g.trust_loopback);
         assert!(g.paired_tokens.is_empty());
+        assert_eq!(g.max_body_bytes, 65_536);
+        assert_eq!(g.chat_session_idle_secs, 1800);
+        assert_eq!(g.max_concurrent_runs, 4);
     }
 }
@@ -0,0 +1,527 @@
+//! Encryption at rest for the credentials in config.toml.
+//!
+//! `Config::save` encrypts every field in [`SECRET_FIELDS`], every named
+//! secret under `[secrets.named]` and every OTLP export header, with the local
+//! key file (`~/.jarvis/.secret_key`) when `secrets.encrypt` is on, and
+//! `Config::load_from` decrypts them again. Loading a file that still holds
+//! plaintext secrets rewrites it encrypted. `${VAR}` placeholders are left
+//! as they are.
+
+use super::env;
+use crate::security::SecretStore;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// Dotted TOML paths of every credential in config.toml. Array-valued
+/// entries (`gateway.paired_tokens`) are redacted from migration archives
+/// but not encrypted.
+pub const SECRET_FIELDS: &[&str] = &[
+    "api_key",
+    "composio.api_key",
+    "memory.postgres_url",
+    "brave_search.api_key",
+    "tunnel.cloudflare.token",
+    "tunnel.ngrok.auth_token",
+    "channels_config.telegram.bot_token",
+    "channels_config.discord.bot_token",
+    "channels_config.slack.bot_token",
+    "channels_config.slack.app_token",
+    "channels_config.webhook.secret",
+    "channels_config.matrix.access_token",
+    "channels_config.whatsapp.access_token",
+    "channels_config.whatsapp.verify_token",
+    "channels_config.whatsapp.app_secret",
+    "channels_config.irc.server_password",
+    "channels_config.irc.nickserv_password",
+    "channels_config.irc.sasl_password",
+    "gateway.paired_tokens",
+];
+
+/// Table of credentials referenced by name (`secret:<name>`); every value in
+/// it is a secret.
+pub const NAMED_SECRETS: &str = "secrets.named";
+
+/// Table of headers sent with every OTLP export; hosted backends take their
+/// API key in one, so every value is treated as a secret.
+pub const OTEL_HEADERS: &str = "observability.otel_headers";
+
+/// Dotted paths of every credential in `root`: [`SECRET_FIELDS`] plus one
+/// per entry of [`NAMED_SECRETS`] and [`OTEL_HEADERS`]. Keys containing dots
+/// can't be addressed by path and are left out.
+pub fn secret_paths(root: &toml::Value) -> Vec<String> {
+    let mut paths: Vec<String> = SECRET_FIELDS.iter().map(ToString::to_string).collect();
+    for table in [NAMED_SECRETS, OTEL_HEADERS] {
+        if let Some(entries) = env::value_at(root, table).and_then(toml::Value::as_table) {
+            paths.extend(
+                entries
+                    .keys()
+                    .filter(|key| !key.contains('.'))
+                    .map(|key| format!("{table}.{key}")),
+            );
+        }
+    }
+    paths
+}
+
+/// Where the fingerprint of the key that encrypted the file is recorded.
+pub(crate) const FINGERPRINT_FIELD: &str = "key_fingerprint";
+
+fn field_mut<'a>(root: &'a mut toml::Value, path: &str) -> Option<&'a mut String> {
+    let mut current = root;
+    for part in path.split('.') {
+        current = current.get_mut(part)?;
+    }
+    match current {
+        toml::Value::String(s) if !s.is_empty() => Some(s),
+        _ => None,
+    }
+}
+
+fn recorded_fingerprint(root: &toml::Value) -> Option<&str> {
+    root.get("secrets")?.get(FINGERPRINT_FIELD)?.as_str()
+}
+
+/// Record the fingerprint of `store`'s key under `[secrets]`.
+pub fn set_fingerprint(root: &mut toml::Value, store: &SecretStore) -> Result<()> {
+    let fingerprint = store.fingerprint()?;
+    if let toml::Value::Table(table) = root {
+        let secrets = table
+            .entry("secrets")
+            .or_insert_with(|| toml::Value::Table(toml::map::Map::new()));
+        if let toml::Value::Table(secrets) = secrets {
+            secrets.insert(
+                FINGERPRINT_FIELD.to_string(),
+                toml::Value::String(fingerprint),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Encrypt every plaintext secret in `root` in place (values that are
+/// already encrypted are kept). Returns how many values were encrypted.
+pub fn encrypt_fields(root: &mut toml::Value, store: &SecretStore) -> Result<usize> {
+    let paths = secret_paths(root);
+    let mut encrypted = 0;
+    for path in &paths {
+        if let Some(value) = field_mut(root, path)
+            .filter(|v| !SecretStore::is_encrypted(v) && !env::is_placeholder(v))
+        {
+            *value = store
+                .encrypt(value)
+                .with_context(|| format!("加密 {path} 失败"))?;
+            encrypted += 1;
+        }
+    }
+    let any_encrypted = paths
+        .iter()
+        .any(|path| field_mut(root, path).is_some_and(|v| SecretStore::is_encrypted(v)));
+    if any_encrypted {
+        set_fingerprint(root, store)?;
+    }
+    Ok(encrypted)
+}
+
+/// Outcome of [`decrypt_fields`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Decrypted {
+    /// Secrets that were decrypted
+    pub decrypted: usize,
+    /// Secrets still stored in plaintext (or in the legacy `enc:` format)
+    pub needs_encryption: usize,
+}
+
+/// Decrypt every secret in `root` in place.
+///
+/// Failures say whether the key is wrong (key file missing, fingerprint
+/// mismatch, or no value decrypts) or a particular value is corrupt.
+pub fn decrypt_fields(root: &mut toml::Value, store: &SecretStore) -> Result<Decrypted> {
+    let paths = secret_paths(root);
+    let encrypted: Vec<&str> = paths
+        .iter()
+        .map(String::as_str)
+        .filter(|path| field_mut(root, path).is_some_and(|v| SecretStore::is_encrypted(v)))
+        .collect();
+    let mut outcome = Decrypted {
+        needs_encryption: paths
+            .iter()
+            .filter(|path| {
+                field_mut(root, path).is_some_and(|v| {
+                    !SecretStore::is_secure_encrypted(v) && !env::is_placeholder(v)
+                })
+            })
+            .count(),
+        ..Decrypted::default()
+    };
+    if encrypted.is_empty() {
+        return Ok(outcome);
+    }
+
+    anyhow::ensure!(
+        store.key_exists(),
+        "缺少密钥文件 {}：config.toml 中有 {} 个加密值无法解密。\
+         请恢复该文件，或在 config.toml 中重新填写明文密钥",
+        store.key_path().display(),
+        encrypted.len()
+    );
+    let fingerprint = store.fingerprint()?;
+    let recorded = recorded_fingerprint(root).map(str::to_string);
+    if let Some(recorded) = &recorded {
+        anyhow::ensure!(
+            *recorded == fingerprint,
+            "密钥错误：{} 不是加密 config.toml 时使用的密钥（记录的指纹 {recorded}，当前 {fingerprint}）",
+            store.key_path().display()
+        );
+    }
+
+    let mut failed = Vec::new();
+    for path in &encrypted {
+        let Some(value) = field_mut(root, path) else {
+            continue;
+        };
+        SecretStore::check_ciphertext(value).with_context(|| format!("{path} 的加密值已损坏"))?;
+        match store.decrypt(value) {
+            Ok(plaintext) => {
+                *value = plaintext;
+                outcome.decrypted += 1;
+            }
+            Err(_) => failed.push(*path),
+        }
+    }
+
+    if let Some(first) = failed.first() {
+        // With a matching fingerprint, or other values decrypting fine, the
+        // key is right and these values were damaged.
+        if recorded.is_some() || outcome.decrypted > 0 {
+            anyhow::bail!(
+                "{} 的加密值已损坏（密钥正确，但校验失败）：{}。请在 config.toml 中重新填写这些密钥",
+                first,
+                failed.join("、")
+            );
+        }
+        anyhow::bail!(
+            "密钥错误：无法用 {} 解密 config.toml 中的任何密钥（{}）",
+            store.key_path().display(),
+            failed.join("、")
+        );
+    }
+    Ok(outcome)
+}
+
+/// The plaintext value of every secret set in `config` (see
+/// [`secret_paths`]), for masking them in logs.
+pub fn secret_values(config: &super::Config) -> Vec<String> {
+    let Ok(root) = toml::Value::try_from(config) else {
+        return Vec::new();
+    };
+    secret_paths(&root)
+        .iter()
+        .filter_map(|path| env::value_at(&root, path)?.as_str())
+        .filter(|v| !v.is_empty() && !SecretStore::is_encrypted(v))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Re-encrypt every secret in the config file at `config_path` under a
+/// freshly generated key. The old key is kept until the rewritten file is on
+/// disk, and restored if anything fails. Returns how many values were
+/// re-encrypted.
+pub fn rotate_key(config_path: &Path) -> Result<usize> {
+    let jarvis_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+    let contents = fs::read_to_string(config_path).context("读取配置文件失败")?;
+    let mut root: toml::Value = toml::from_str(&contents).context("解析配置文件失败")?;
+    let enabled = root
+        .get("secrets")
+        .and_then(|s| s.get("encrypt"))
+        .and_then(toml::Value::as_bool)
+        .unwrap_or(true);
+    anyhow::ensure!(
+        enabled,
+        "secrets.encrypt = false，配置中的密钥未加密，无需轮换"
+    );
+
+    let store = SecretStore::new(jarvis_dir, true);
+    decrypt_fields(&mut root, &store)?;
+
+    let old_key = store.key_path().with_extension("old");
+    let had_key = store.key_exists();
+    if had_key {
+        fs::rename(store.key_path(), &old_key).context("备份旧密钥文件失败")?;
+    }
+    let rewritten = encrypt_fields(&mut root, &store).and_then(|count| {
+        let toml_str = toml::to_string_pretty(&root).context("序列化配置失败")?;
+        fs::write(config_path, toml_str).context("写入配置文件失败")?;
+        Ok(count)
+    });
+    match rewritten {
+        Ok(count) => {
+            if had_key {
+                fs::remove_file(&old_key).context("删除旧密钥文件失败")?;
+            }
+            Ok(count)
+        }
+        Err(e) => {
+            if had_key {
+                fs::rename(&old_key, store.key_path()).context("恢复旧密钥文件失败")?;
+            }
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample() -> toml::Value {
+        toml::from_str(
+            r#"
+api_key = "sk-provider"
+default_temperature = 0.7
+
+[composio]
+enabled = true
+api_key = "comp-key"
+
+[channels_config]
+cli = true
+
+[channels_config.telegram]
+bot_token = "123:ABC"
+allowed_users = ["alice"]
+
+[channels_config.slack]
+bot_token = "xoxb-1"
+app_token = ""
+
+[tunnel]
+provider = "ngrok"
+
+[tunnel.ngrok]
+auth_token = "ngrok-tok"
+"#,
+        )
+        .unwrap()
+    }
+
+    fn get<'a>(root: &'a toml::Value, path: &str) -> &'a str {
+        path.split('.')
+            .fold(root, |v, part| v.get(part).unwrap())
+            .as_str()
+            .unwrap()
+    }
+
+    #[test]
+    fn encrypts_and_decrypts_every_secret_field() {
+        let tmp = TempDir::new().unwrap();
+        let store = SecretStore::new(tmp.path(), true);
+        let mut root = sample();
+
+        assert_eq!(encrypt_fields(&mut root, &store).unwrap(), 5);
+        for path in [
+            "api_key",
+            "composio.api_key",
+            "channels_config.telegram.bot_token",
+            "channels_config.slack.bot_token",
+            "tunnel.ngrok.auth_token",
+        ] {
+            assert!(SecretStore::is_secure_encrypted(get(&root, path)), "{path}");
+        }
+        // Empty values and non-secrets are left alone
+        assert_eq!(get(&root, "channels_config.slack.app_token"), "");
+        assert_eq!(get(&root, "tunnel.provider"), "ngrok");
+        assert_eq!(
+            recorded_fingerprint(&root),
+            Some(store.fingerprint().unwrap().as_str())
+        );
+
+        // Already encrypted values are not encrypted twice
+        assert_eq!(encrypt_fields(&mut root, &store).unwrap(), 0);
+
+        let outcome = decrypt_fields(&mut root, &store).unwrap();
+        assert_eq!(
+            outcome,
+            Decrypted {
+                decrypted: 5,
+                needs_encryption: 0
+            }
+        );
+        assert_eq!(get(&root, "channels_config.telegram.bot_token"), "123:ABC");
+        assert_eq!(get(&root, "tunnel.ngrok.auth_token"), "ngrok-tok");
+    }
+
+    #[test]
+    fn named_secrets_are_encrypted_and_masked() {
+        let tmp = TempDir::new().unwrap();
+        let store = SecretStore::new(tmp.path(), true);
+        let mut root: toml::Value = toml::from_str(
+            r#"
+[secrets.named]
+ha_token = "eyJhbGciOiJIUzI1NiJ9.home"
+"dotted.name" = "skipped-value"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            secret_paths(&root)[SECRET_FIELDS.len()..],
+            ["secrets.named.ha_token".to_string()]
+        );
+
+        assert_eq!(encrypt_fields(&mut root, &store).unwrap(), 1);
+        assert!(SecretStore::is_secure_encrypted(get(
+            &root,
+            "secrets.named.ha_token"
+        )));
+        assert_eq!(decrypt_fields(&mut root, &store).unwrap().decrypted, 1);
+        assert_eq!(
+            get(&root, "secrets.named.ha_token"),
+            "eyJhbGciOiJIUzI1NiJ9.home"
+        );
+
+        let mut config = crate::config::Config::default();
+        config
+            .secrets
+            .named
+            .insert("ha_token".into(), "eyJhbGciOiJIUzI1NiJ9.home".into());
+        assert_eq!(secret_values(&config), ["eyJhbGciOiJIUzI1NiJ9.home"]);
+    }
+
+    #[test]
+    fn otel_headers_are_encrypted_and_masked() {
+        let tmp = TempDir::new().unwrap();
+        let store = SecretStore::new(tmp.path(), true);
+        let mut root: toml::Value = toml::from_str(
+            r#"
+[observability]
+backend = "otel"
+otel_endpoint = "https://otlp.example.com"
+
+[observability.otel_headers]
+x-honeycomb-team = "hc-api-key"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            secret_paths(&root)[SECRET_FIELDS.len()..],
+            ["observability.otel_headers.x-honeycomb-team".to_string()]
+        );
+
+        assert_eq!(encrypt_fields(&mut root, &store).unwrap(), 1);
+        assert!(SecretStore::is_secure_encrypted(get(
+            &root,
+            "observability.otel_headers.x-honeycomb-team"
+        )));
+        assert_eq!(
+            get(&root, "observability.otel_endpoint"),
+            "https://otlp.example.com"
+        );
+        assert_eq!(decrypt_fields(&mut root, &store).unwrap().decrypted, 1);
+        assert_eq!(
+            get(&root, "observability.otel_headers.x-honeycomb-team"),
+            "hc-api-key"
+        );
+
+        let mut config = crate::config::Config::default();
+        config
+            .observability
+            .otel_headers
+            .insert("x-honeycomb-team".into(), "hc-api-key".into());
+        assert_eq!(secret_values(&config), ["hc-api-key"]);
+    }
+
+    #[test]
+    fn reports_plaintext_that_needs_encryption() {
+        let tmp = TempDir::new().unwrap();
+        let store = SecretStore::new(tmp.path(), true);
+        let mut root = sample();
+        let outcome = decrypt_fields(&mut root, &store).unwrap();
+        assert_eq!(outcome.decrypted, 0);
+        assert_eq!(outcome.needs_encryption, 5);
+        assert!(
+            !store.key_exists(),
+            "no key is created just to read plaintext"
+        );
+    }
+
+    #[test]
+    fn distinguishes_wrong_key_from_corrupt_value() {
+        let (a, b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let store = SecretStore::new(a.path(), true);
+        let mut encrypted = sample();
+        encrypt_fields(&mut encrypted, &store).unwrap();
+
+        // Missing key file
+        let err =
+            decrypt_fields(&mut encrypted.clone(), &SecretStore::new(b.path(), true)).unwrap_err();
+        assert!(err.to_string().contains("缺少密钥文件"), "{err}");
+
+        // Another key, with and without the recorded fingerprint
+        let other = SecretStore::new(b.path(), true);
+        other.fingerprint().unwrap();
+        let err = decrypt_fields(&mut encrypted.clone(), &other).unwrap_err();
+        assert!(err.to_string().contains("密钥错误"), "{err}");
+        let mut unfingerprinted = encrypted.clone();
+        unfingerprinted
+            .get_mut("secrets")
+            .unwrap()
+            .as_table_mut()
+            .unwrap()
+            .remove(FINGERPRINT_FIELD);
+        let err = decrypt_fields(&mut unfingerprinted, &other).unwrap_err();
+        assert!(err.to_string().contains("密钥错误"), "{err}");
+
+        // Right key, one tampered value
+        let mut tampered = encrypted.clone();
+        let value = field_mut(&mut tampered, "composio.api_key").unwrap();
+        let last = value.pop().unwrap();
+        value.push(if last == '0' { '1' } else { '0' });
+        let err = decrypt_fields(&mut tampered, &store).unwrap_err();
+        assert!(
+            err.to_string().contains("composio.api_key 的加密值已损坏"),
+            "{err}"
+        );
+
+        // Not even valid hex
+        let mut garbled = encrypted;
+        *field_mut(&mut garbled, "api_key").unwrap() = "enc2:zz".into();
+        let err = decrypt_fields(&mut garbled, &store).unwrap_err();
+        assert!(err.to_string().contains("api_key 的加密值已损坏"), "{err}");
+    }
+
+    #[test]
+    fn rotate_key_reencrypts_under_a_new_key() {
+        let tmp = TempDir::new().unwrap();
+        let config_path = tmp.path().join("config.toml");
+        let store = SecretStore::new(tmp.path(), true);
+        let mut root = sample();
+        encrypt_fields(&mut root, &store).unwrap();
+        fs::write(&config_path, toml::to_string_pretty(&root).unwrap()).unwrap();
+        let old_fingerprint = store.fingerprint().unwrap();
+
+        assert_eq!(rotate_key(&config_path).unwrap(), 5);
+        assert_ne!(store.fingerprint().unwrap(), old_fingerprint);
+        assert!(!store.key_path().with_extension("old").exists());
+
+        let mut rotated: toml::Value =
+            toml::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(decrypt_fields(&mut rotated, &store).unwrap().decrypted, 5);
+        assert_eq!(get(&rotated, "api_key"), "sk-provider");
+    }
+
+    #[test]
+    fn rotate_key_keeps_old_key_when_decryption_fails() {
+        let tmp = TempDir::new().unwrap();
+        let config_path = tmp.path().join("config.toml");
+        let store = SecretStore::new(tmp.path(), true);
+        let mut root = sample();
+        encrypt_fields(&mut root, &store).unwrap();
+        *field_mut(&mut root, "api_key").unwrap() = "enc2:zz".into();
+        fs::write(&config_path, toml::to_string_pretty(&root).unwrap()).unwrap();
+        let fingerprint = store.fingerprint().unwrap();
+
+        assert!(rotate_key(&config_path).is_err());
+        assert_eq!(store.fingerprint().unwrap(), fingerprint);
+    }
+}
@@ -0,0 +1,306 @@
+//! Writing config.toml safely. Every write takes an advisory lock on
+//! `config.toml.lock`, which [`Config::update`](super::Config::update) holds
+//! across the whole read-modify-write, so two processes (say the daemon
+//! persisting an approval and `jarvis config set`) never interleave; copies
+//! the current file to `backups/` next to it, keeping the newest
+//! [`KEEP_BACKUPS`]; and replaces the file with a synced temp file renamed over it, so a crash
+//! leaves either the old or the new config, never half of one.
+
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDateTime;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Backups kept in `backups/`; older ones are deleted.
+pub const KEEP_BACKUPS: usize = 10;
+/// How long a writer waits for another one to finish.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const LOCK_POLL: Duration = Duration::from_millis(50);
+/// `config-<timestamp>.toml`; sorts oldest first.
+const BACKUP_PREFIX: &str = "config-";
+const BACKUP_TIMESTAMP: &str = "%Y%m%d-%H%M%S%.6f";
+
+/// Held while writing config.toml. The lock is released when dropped.
+pub struct ConfigLock {
+    _file: File,
+}
+
+/// Take the write lock for `config_path`, waiting up to [`LOCK_TIMEOUT`]
+/// for another writer. Blocks the thread while it waits.
+pub fn lock(config_path: &Path) -> Result<ConfigLock> {
+    let path = config_path.with_extension("toml.lock");
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("创建目录失败：{}", dir.display()))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("打开锁文件失败：{}", path.display()))?;
+    let started = Instant::now();
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(ConfigLock { _file: file }),
+            Err(TryLockError::WouldBlock) if started.elapsed() < LOCK_TIMEOUT => {
+                std::thread::sleep(LOCK_POLL);
+            }
+            Err(TryLockError::WouldBlock) => bail!(
+                "另一个进程正在写入 {}（{} 秒后仍未完成），请稍后重试",
+                config_path.display(),
+                LOCK_TIMEOUT.as_secs()
+            ),
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("锁定 {} 失败", path.display()));
+            }
+        }
+    }
+}
+
+/// Replace `config_path` with `contents`: write a temp file beside it,
+/// sync it, and rename it over the original, keeping its permissions.
+pub fn replace(config_path: &Path, contents: &str, _lock: &ConfigLock) -> Result<()> {
+    let tmp = config_path.with_extension(format!("toml.{}.tmp", std::process::id()));
+    let written = (|| {
+        let mut file = File::create(&tmp)?;
+        file.write_all(contents.as_bytes())?;
+        if let Ok(meta) = fs::metadata(config_path) {
+            file.set_permissions(meta.permissions())?;
+        }
+        file.sync_all()
+    })();
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp);
+        return Err(e).context("写入配置文件失败");
+    }
+    if let Err(e) = fs::rename(&tmp, config_path) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e).context("写入配置文件失败");
+    }
+    // Persist the rename itself
+    #[cfg(unix)]
+    if let Some(dir) = config_path.parent() {
+        File::open(dir).and_then(|d| d.sync_all()).ok();
+    }
+    Ok(())
+}
+
+/// Where the backups of `config_path` go.
+pub fn backups_dir(config_path: &Path) -> PathBuf {
+    config_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("backups")
+}
+
+/// A copy of config.toml in [`backups_dir`].
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// When it was taken, from the file name
+    pub taken: NaiveDateTime,
+    /// Its `config_version`; `None` when the file doesn't parse
+    pub version: Option<u32>,
+}
+
+impl Backup {
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// Copy the current `config_path` into [`backups_dir`] and delete all but
+/// the newest [`KEEP_BACKUPS`]. Returns the copy, or `None` when there is
+/// no file yet. A file identical to the newest backup isn't copied again.
+pub fn backup(config_path: &Path, _lock: &ConfigLock) -> Result<Option<PathBuf>> {
+    let Ok(current) = fs::read(config_path) else {
+        return Ok(None);
+    };
+    let dir = backups_dir(config_path);
+    let existing = list(config_path)?;
+    if let Some(newest) = existing.first()
+        && fs::read(&newest.path).is_ok_and(|b| b == current)
+    {
+        return Ok(Some(newest.path.clone()));
+    }
+
+    create_private_dir(&dir)?;
+    let stamp = chrono::Local::now().format(BACKUP_TIMESTAMP);
+    let path = dir.join(format!("{BACKUP_PREFIX}{stamp}.toml"));
+    fs::copy(config_path, &path)
+        .with_context(|| format!("备份配置文件失败：{}", path.display()))?;
+
+    for old in existing.iter().skip(KEEP_BACKUPS.saturating_sub(1)) {
+        if let Err(e) = fs::remove_file(&old.path) {
+            tracing::warn!("删除旧配置备份 {} 失败: {e}", old.path.display());
+        }
+    }
+    Ok(Some(path))
+}
+
+fn create_private_dir(dir: &Path) -> Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder
+        .create(dir)
+        .with_context(|| format!("创建目录失败：{}", dir.display()))
+}
+
+/// The backups of `config_path`, newest first.
+pub fn list(config_path: &Path) -> Result<Vec<Backup>> {
+    let dir = backups_dir(config_path);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("读取 {} 失败", dir.display())),
+    };
+    let mut backups: Vec<Backup> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_name()?.to_str()?;
+            let stamp = name.strip_prefix(BACKUP_PREFIX)?.strip_suffix(".toml")?;
+            let taken = NaiveDateTime::parse_from_str(stamp, BACKUP_TIMESTAMP).ok()?;
+            let version = fs::read_to_string(&path)
+                .ok()
+                .and_then(|contents| toml::from_str::<toml::Table>(&contents).ok())
+                .map(|table| {
+                    table
+                        .get("config_version")
+                        .and_then(toml::Value::as_integer)
+                        .and_then(|v| u32::try_from(v).ok())
+                        .unwrap_or(0)
+                });
+            Some(Backup {
+                path,
+                taken,
+                version,
+            })
+        })
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.taken));
+    Ok(backups)
+}
+
+/// Put backup `which` (a file name or its number in [`list`], 1 = newest;
+/// the newest when `None`) back as `config_path`. The file it replaces is
+/// backed up first, so a restore can itself be undone. Returns the restored
+/// backup and that new backup.
+pub fn restore(config_path: &Path, which: Option<&str>) -> Result<(Backup, Option<PathBuf>)> {
+    let lock = lock(config_path)?;
+    let backups = list(config_path)?;
+    if backups.is_empty() {
+        bail!("{} 中没有配置备份", backups_dir(config_path).display());
+    }
+    let chosen = match which.map(str::trim) {
+        None | Some("") => backups[0].clone(),
+        Some(which) => {
+            let by_number = which
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|i| backups.get(i));
+            by_number
+                .or_else(|| backups.iter().find(|b| b.file_name() == which))
+                .cloned()
+                .with_context(|| {
+                    format!("没有名为「{which}」的备份，运行 `jarvis config restore --list` 查看")
+                })?
+        }
+    };
+    let contents = fs::read_to_string(&chosen.path)
+        .with_context(|| format!("读取 {} 失败", chosen.path.display()))?;
+    toml::from_str::<toml::Table>(&contents)
+        .with_context(|| format!("{} 不是有效的 TOML，未恢复", chosen.file_name()))?;
+
+    let previous = backup(config_path, &lock)?;
+    replace(config_path, &contents, &lock)?;
+    Ok((chosen, previous))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(config_path: &Path) -> Vec<String> {
+        list(config_path)
+            .unwrap()
+            .iter()
+            .map(Backup::file_name)
+            .collect()
+    }
+
+    #[test]
+    fn backups_are_pruned_deduplicated_and_restorable() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.toml");
+        let lock = lock(&path).unwrap();
+        assert!(backup(&path, &lock).unwrap().is_none());
+
+        for i in 0..=KEEP_BACKUPS {
+            replace(&path, &format!("config_version = 1\nn = {i}\n"), &lock).unwrap();
+            backup(&path, &lock).unwrap();
+        }
+        let first = names(&path);
+        assert_eq!(first.len(), KEEP_BACKUPS);
+        // Backing up an unchanged file reuses the newest copy
+        assert_eq!(
+            backup(&path, &lock).unwrap().unwrap(),
+            list(&path).unwrap()[0].path
+        );
+        assert_eq!(names(&path), first);
+        let newest = &list(&path).unwrap()[0];
+        assert_eq!(newest.version, Some(1));
+        assert!(fs::read_to_string(&newest.path).unwrap().contains("n = 10"));
+        drop(lock);
+
+        fs::write(&path, "n = 99\n").unwrap();
+        let (restored, previous) = restore(&path, Some("2")).unwrap();
+        assert_eq!(restored.file_name(), first[1]);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "config_version = 1\nn = 9\n"
+        );
+        let previous = previous.unwrap();
+        assert_eq!(fs::read_to_string(&previous).unwrap(), "n = 99\n");
+        assert_eq!(list(&path).unwrap()[0].version, Some(0));
+
+        assert!(restore(&path, Some("config-nope.toml")).is_err());
+        let leftovers: Vec<_> = fs::read_dir(tmp.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .filter(|n| Path::new(n).extension().is_some_and(|ext| ext == "tmp"))
+            .collect();
+        assert!(leftovers.is_empty(), "{leftovers:?}");
+    }
+
+    #[test]
+    fn a_second_writer_waits_for_the_lock() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.toml");
+        let held = lock(&path).unwrap();
+
+        let waiter = std::thread::spawn({
+            let path = path.clone();
+            move || {
+                let lock = lock(&path).unwrap();
+                replace(&path, "second\n", &lock).unwrap();
+            }
+        });
+        std::thread::sleep(Duration::from_millis(200));
+        replace(&path, "first\n", &held).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\n");
+        drop(held);
+        waiter.join().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n");
+    }
+}
@@ -0,0 +1,277 @@
+//! Keys in config.toml the schema doesn't know. serde skips them, so a typo
+//! such as `max_paralel_tasks` would silently leave the default in place.
+//! Loading warns about each one (and fails with `strict_config = true`);
+//! `jarvis doctor` and `jarvis config validate` list them with their line
+//! and the closest known key.
+
+use super::secrets::FINGERPRINT_FIELD;
+use super::Config;
+use crate::util::edit_distance;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Old key names serde still accepts, as dotted paths.
+const ALIASES: &[&str] = &[
+    "reliability.provider_retries",
+    "reliability.provider_backoff_ms",
+];
+
+/// A key the schema ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownKey {
+    /// Dotted path, with `[i]` for entries of an array of tables
+    pub path: String,
+    /// 1-based line in config.toml, when it could be found
+    pub line: Option<usize>,
+    /// The known key at the same level with the closest name
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for UnknownKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "未知配置项")?;
+        if let Some(line) = self.line {
+            write!(f, "（第 {line} 行）")?;
+        }
+        match &self.suggestion {
+            Some(suggestion) => write!(f, "，你是不是要写 {suggestion}？"),
+            None => write!(f, "（拼写错误？）"),
+        }
+    }
+}
+
+/// The keys in `root` that `config` doesn't serialize back, i.e. that the
+/// schema ignored. `contents`, the file `root` was parsed from, supplies
+/// line numbers.
+pub fn find(
+    root: &toml::Value,
+    config: &Config,
+    contents: Option<&str>,
+) -> Result<Vec<UnknownKey>> {
+    // JSON keeps unset optional fields (as null), so they can be suggested
+    let known = serde_json::to_value(config).context("序列化配置失败")?;
+    let mut unknown = Vec::new();
+    collect(root, &known, "", &mut unknown);
+    if let Some(contents) = contents {
+        for key in &mut unknown {
+            key.line = line_of(contents, &key.path);
+        }
+        unknown.sort_by_key(|key| key.line.unwrap_or(usize::MAX));
+    }
+    Ok(unknown)
+}
+
+fn collect(value: &toml::Value, known: &serde_json::Value, path: &str, out: &mut Vec<UnknownKey>) {
+    match (value, known) {
+        (toml::Value::Table(table), serde_json::Value::Object(known)) => {
+            for (key, child) in table {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                // Written by `Config::save`, not part of the schema
+                if path == "secrets" && key == FINGERPRINT_FIELD {
+                    continue;
+                }
+                match known.get(key) {
+                    Some(known_child) => collect(child, known_child, &child_path, out),
+                    // Empty tables (e.g. `[secrets.named]`) aren't serialized back
+                    None if child.as_table().is_some_and(toml::Table::is_empty) => {}
+                    None if ALIASES.contains(&child_path.as_str()) => {}
+                    None => out.push(UnknownKey {
+                        suggestion: closest(key, known.keys()),
+                        path: child_path,
+                        line: None,
+                    }),
+                }
+            }
+        }
+        (toml::Value::Array(items), serde_json::Value::Array(known)) => {
+            for (i, (item, known_item)) in items.iter().zip(known).enumerate() {
+                collect(item, known_item, &format!("{path}[{i}]"), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The candidate closest to `key`, if it is close enough to be a typo.
+fn closest<'a>(key: &str, candidates: impl Iterator<Item = &'a String>) -> Option<String> {
+    let len = key.chars().count();
+    candidates
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance <= (len / 3).max(2) && *distance < len)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.clone())
+}
+
+/// The dotted path of a `[table]` header or the key of a `key = value`
+/// line, quotes removed.
+fn dotted(raw: &str) -> String {
+    raw.split('.')
+        .map(|part| part.trim().trim_matches(['"', '\'']))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Whether `text` opens or closes a multi-line string.
+fn toggles_multiline(text: &str) -> bool {
+    (text.matches("\"\"\"").count() + text.matches("'''").count()) % 2 == 1
+}
+
+/// The line that defines `path`: its `key = …` line, or the header of the
+/// table it names or contains. Headers of arrays of tables count their
+/// entries, so `a[1].b` is found under the second `[[a]]`.
+fn line_of(contents: &str, path: &str) -> Option<usize> {
+    let mut table = String::new();
+    let mut entries: HashMap<String, usize> = HashMap::new();
+    let mut in_multiline = false;
+    let within = |candidate: &str| {
+        candidate == path
+            || path
+                .strip_prefix(candidate)
+                .is_some_and(|rest| rest.starts_with('.'))
+    };
+
+    for (i, line) in contents.lines().enumerate() {
+        let trimmed = line.trim();
+        if in_multiline {
+            in_multiline = !toggles_multiline(line);
+            continue;
+        }
+        if trimmed.starts_with('#') || trimmed.is_empty() {
+            continue;
+        }
+        if let Some(header) = trimmed.strip_prefix("[[").and_then(|h| h.split_once("]]")) {
+            let name = dotted(header.0);
+            let index = entries.entry(name.clone()).or_insert(0);
+            table = format!("{name}[{index}]");
+            *index += 1;
+        } else if let Some(header) = trimmed.strip_prefix('[').and_then(|h| h.split_once(']')) {
+            table = dotted(header.0);
+        } else if let Some((key, value)) = trimmed.split_once('=') {
+            in_multiline = toggles_multiline(value);
+            let key = dotted(key);
+            let full = if table.is_empty() {
+                key
+            } else {
+                format!("{table}.{key}")
+            };
+            if within(&full) {
+                return Some(i + 1);
+            }
+            continue;
+        } else {
+            continue;
+        }
+        // The key is this table, or one of its parents
+        if table == path || table.starts_with(&format!("{path}.")) {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unknown(toml: &str) -> Vec<UnknownKey> {
+        let root: toml::Value = toml::from_str(toml).unwrap();
+        let config: Config = root.clone().try_into().unwrap();
+        find(&root, &config, Some(toml)).unwrap()
+    }
+
+    #[test]
+    fn typos_in_nested_tables_are_found_with_line_and_suggestion() {
+        let found = unknown(
+            "default_temperature = 0.7\n\
+             defualt_model = \"gpt-4o\"\n\
+             \n\
+             [heartbeat]\n\
+             enabled = true\n\
+             interval_minutes = 15\n\
+             max_paralel_tasks = 4\n\
+             \n\
+             [channels_config]\n\
+             cli = true\n\
+             \n\
+             [channels_config.telegram]\n\
+             bot_token = \"t\"\n\
+             allowed_users = []\n\
+             alowed_chats = [\"1\"]\n\
+             \n\
+             [reliability]\n\
+             provider_retries = 3\n\
+             \n\
+             [secrets.named]\n\
+             \n\
+             [heartbeet]\n\
+             enabled = true\n",
+        );
+        let described: Vec<String> = found.iter().map(|k| format!("{} {}", k.path, k)).collect();
+        assert_eq!(
+            described,
+            [
+                "defualt_model 未知配置项（第 2 行），你是不是要写 default_model？",
+                "heartbeat.max_paralel_tasks 未知配置项（第 7 行），你是不是要写 max_parallel_tasks？",
+                "channels_config.telegram.alowed_chats 未知配置项（第 15 行）（拼写错误？）",
+                "heartbeet 未知配置项（第 22 行），你是不是要写 heartbeat？",
+            ]
+        );
+    }
+
+    #[test]
+    fn strict_config_turns_unknown_keys_into_a_load_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.toml");
+        let body = "default_temperature = 0.7\n[heartbeat]\nenabled = true\n\
+                    interval_minutes = 15\nmax_paralel_tasks = 4\n[secrets]\nencrypt = false\n";
+        std::fs::write(&path, body).unwrap();
+        let config = Config::load_from(&path).unwrap();
+        assert_eq!(config.unknown_keys.len(), 1);
+        assert_eq!(config.heartbeat.max_parallel_tasks, 2);
+
+        std::fs::write(&path, format!("strict_config = true\n{body}")).unwrap();
+        let err = format!("{:#}", Config::load_from(&path).unwrap_err());
+        assert!(
+            err.contains(
+                "heartbeat.max_paralel_tasks：未知配置项（第 6 行），你是不是要写 max_parallel_tasks？"
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn entries_of_arrays_of_tables_are_checked_one_by_one() {
+        let root: toml::Value =
+            toml::from_str("[[routes]]\nname = \"a\"\n\n[[routes]]\nname = \"b\"\nnmae = \"c\"\n")
+                .unwrap();
+        let known = serde_json::json!({ "routes": [{ "name": "a" }, { "name": "b" }] });
+        let mut found = Vec::new();
+        collect(&root, &known, "", &mut found);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "routes[1].nmae");
+        assert_eq!(found[0].suggestion.as_deref(), Some("name"));
+
+        let contents = "[[routes]]\nname = \"a\"\n\n[[routes]]\nname = \"b\"\nnmae = \"c\"\n";
+        assert_eq!(line_of(contents, "routes[1].nmae"), Some(6));
+        assert_eq!(line_of(contents, "routes[0].nmae"), None);
+    }
+
+    #[test]
+    fn lines_are_found_past_multiline_strings_and_dotted_keys() {
+        let contents = "[identity]\n\
+                        aieos_inline = \"\"\"\n\
+                        [fake]\n\
+                        x = 1\n\
+                        \"\"\"\n\
+                        \"quoted\" = 1\n\
+                        web_fetch.max_bytez = 2\n";
+        assert_eq!(line_of(contents, "identity.quoted"), Some(6));
+        assert_eq!(line_of(contents, "fake"), None);
+        assert_eq!(line_of(contents, "identity.web_fetch.max_bytez"), Some(7));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::config::Config;
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Local, Utc};
 use cron::Schedule;
 use rusqlite::{params, Connection};
 use std::str::FromStr;
@@ -8,6 +8,11 @@ use uuid::Uuid;
 
 pub mod scheduler;
 
+/// Number of upcoming runs previewed after `cron add`
+const ADD_PREVIEW_COUNT: usize = 3;
+/// Most fire times `cron validate --count` will list
+pub const MAX_PREVIEW_COUNT: usize = 100;
+
 #[derive(Debug, Clone)]
 pub struct CronJob {
     pub id: String,
@@ -57,9 +62,59 @@ pub fn handle_command(command: crate::CronCommands, config: &Config) -> Result<(
             println!("  表达式: {}", job.expression);
             println!("  下次执行: {}", job.next_run.to_rfc3339());
             println!("  命令:     {}", job.command);
+            print_run_preview(&preview_runs(
+                &job.expression,
+                Utc::now(),
+                ADD_PREVIEW_COUNT,
+            )?);
             Ok(())
         }
         crate::CronCommands::Remove { id } => remove_job(config, &id),
+        crate::CronCommands::Validate { expression, count } => {
+            let runs = preview_runs(&expression, Utc::now(), count)?;
+            println!("✅ 表达式有效: {}", expression.trim());
+            print_run_preview(&runs);
+            Ok(())
+        }
+    }
+}
+
+/// Compute the next `count` fire times for `expression` after `from`, at
+/// most [`MAX_PREVIEW_COUNT`].
+pub fn preview_runs(
+    expression: &str,
+    from: DateTime<Utc>,
+    count: usize,
+) -> Result<Vec<DateTime<Utc>>> {
+    if count > MAX_PREVIEW_COUNT {
+        anyhow::bail!("预览次数最多为 {MAX_PREVIEW_COUNT}（收到 {count}）");
+    }
+    let mut runs = Vec::with_capacity(count);
+    let mut cursor = from;
+    for _ in 0..count {
+        let next = match next_run_for(expression, cursor) {
+            Ok(next) => next,
+            // Year-bounded schedules can run out of fire times
+            Err(_) if !runs.is_empty() => break,
+            Err(e) => return Err(e),
+        };
+        runs.push(next);
+        cursor = next;
+    }
+    Ok(runs)
+}
+
+fn print_run_preview(runs: &[DateTime<Utc>]) {
+    if runs.is_empty() {
+        return;
+    }
+    println!("  接下来 {} 次执行:", runs.len());
+    for run in runs {
+        println!(
+            "    {}  (本地 {})",
+            run.format("%Y-%m-%d %H:%M:%S UTC"),
+            run.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S %Z")
+        );
     }
 }
 
@@ -214,11 +269,15 @@ pub fn reschedule_after_run(
     })
 }
 
-fn next_run_for(expression: &str, from: DateTime<Utc>) -> Result<DateTime<Utc>> {
+/// Parse a 5-field crontab expression, or the crate's 6/7-field syntax with
+/// seconds (and year).
+pub fn parse_expression(expression: &str) -> Result<Schedule> {
     let normalized = normalize_expression(expression)?;
-    let schedule = Schedule::from_str(&normalized)
-        .with_context(|| format!("无效的 cron 表达式: {expression}"))?;
-    schedule
+    Schedule::from_str(&normalized).with_context(|| format!("无效的 cron 表达式: {expression}"))
+}
+
+fn next_run_for(expression: &str, from: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    parse_expression(expression)?
         .after(&from)
         .next()
         .ok_or_else(|| anyhow::anyhow!("表达式无未来执行时间: {expression}"))
@@ -310,6 +369,70 @@ mod tests {
         assert!(err.to_string().contains("期望 5、6 或 7 个字段"));
     }
 
+    #[test]
+    fn preview_runs_five_field_expression() {
+        let from = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let runs = preview_runs("*/15 * * * *", from, 3).unwrap();
+        let expected: Vec<String> = runs.iter().map(DateTime::to_rfc3339).collect();
+        assert_eq!(
+            expected,
+            vec![
+                "2026-01-01T00:15:00+00:00",
+                "2026-01-01T00:30:00+00:00",
+                "2026-01-01T00:45:00+00:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn preview_runs_six_field_expression_with_seconds() {
+        let from = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let runs = preview_runs("30 0 9 * * *", from, 2).unwrap();
+        assert_eq!(runs[0].to_rfc3339(), "2026-01-01T09:00:30+00:00");
+        assert_eq!(runs[1].to_rfc3339(), "2026-01-02T09:00:30+00:00");
+    }
+
+    #[test]
+    fn preview_runs_seven_field_expression_with_year() {
+        let from = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        // Only one fire time exists — the preview stops instead of erroring
+        let runs = preview_runs("0 0 12 1 6 * 2027", from, 3).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].to_rfc3339(), "2027-06-01T12:00:00+00:00");
+
+        let err = preview_runs("0 0 12 1 6 * 2027", runs[0], 1).unwrap_err();
+        assert!(err.to_string().contains("无未来执行时间"));
+    }
+
+    #[test]
+    fn preview_runs_rejects_invalid_expression() {
+        let err = preview_runs("61 * * * *", Utc::now(), 1).unwrap_err();
+        assert!(err.to_string().contains("无效的 cron 表达式"));
+
+        let err = preview_runs("* * *", Utc::now(), 1).unwrap_err();
+        assert!(err.to_string().contains("期望 5、6 或 7 个字段"));
+    }
+
+    #[test]
+    fn preview_runs_zero_count_is_empty() {
+        assert!(preview_runs("* * * * *", Utc::now(), 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn preview_runs_rejects_counts_above_the_maximum() {
+        let runs = preview_runs("* * * * *", Utc::now(), MAX_PREVIEW_COUNT).unwrap();
+        assert_eq!(runs.len(), MAX_PREVIEW_COUNT);
+
+        let err = preview_runs("* * * * *", Utc::now(), usize::MAX).unwrap_err();
+        assert!(err.to_string().contains("预览次数最多为 100"));
+    }
+
     #[test]
     fn add_list_remove_roundtrip() {
         let tmp = TempDir::new().unwrap();
@@ -1,5 +1,7 @@
 use crate::config::Config;
 use crate::cron::{due_jobs, reschedule_after_run, CronJob};
+use crate::notify::{Notification, Notifier};
+use crate::security::audit::AuditDecision;
 use crate::security::SecurityPolicy;
 use anyhow::Result;
 use chrono::Utc;
@@ -11,7 +13,14 @@ const MIN_POLL_SECONDS: u64 = 5;
 pub async fn run(config: Config) -> Result<()> {
     let poll_secs = config.reliability.scheduler_poll_secs.max(MIN_POLL_SECONDS);
     let mut interval = time::interval(Duration::from_secs(poll_secs));
-    let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+    let security =
+        SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir).with_origin("cron");
+
+    // A bad notify config only loses notifications; jobs still run
+    let notifier = Notifier::from_notify(&config).unwrap_or_else(|e| {
+        tracing::warn!("通知配置无效，定时任务结果不会推送：{e:#}");
+        None
+    });
 
     crate::health::mark_component_ok("scheduler");
 
@@ -39,6 +48,14 @@ pub async fn run(config: Config) -> Result<()> {
                 crate::health::mark_component_error("scheduler", e.to_string());
                 tracing::warn!("持久化调度器运行结果失败: {e}");
             }
+
+            if let Some(notifier) = &notifier
+                && let Err(e) = notifier
+                    .deliver(&notification(&job, success, &output))
+                    .await
+            {
+                tracing::warn!("定时任务结果通知失败: {e:#}");
+            }
         }
     }
 }
@@ -50,7 +67,7 @@ async fn execute_job_with_retry(
 ) -> (bool, String) {
     let mut last_output = String::new();
     let retries = config.reliability.scheduler_retries;
-    let mut backoff_ms = config.reliability.provider_backoff_ms.max(200);
+    let mut backoff_ms = config.reliability.provider_initial_backoff_ms.max(200);
 
     for attempt in 0..=retries {
         let (success, output) = run_job_command(config, security, job).await;
@@ -75,6 +92,29 @@ async fn execute_job_with_retry(
     (false, last_output)
 }
 
+/// A finished job as a notification; a successful run is actionable only
+/// when it printed something.
+fn notification(job: &CronJob, success: bool, output: &str) -> Notification {
+    Notification {
+        source: "cron",
+        title: job.command.clone(),
+        body: output.to_string(),
+        failed: !success,
+        actionable: !success || !job_stdout(output).is_empty(),
+    }
+}
+
+/// The stdout section of [`run_job_command`]'s output.
+fn job_stdout(output: &str) -> &str {
+    output
+        .split_once("\nstdout:\n")
+        .map_or("", |(_, rest)| {
+            rest.rsplit_once("\nstderr:\n")
+                .map_or(rest, |(stdout, _)| stdout)
+        })
+        .trim()
+}
+
 fn is_env_assignment(word: &str) -> bool {
     word.contains('=')
         && word
@@ -139,6 +179,7 @@ async fn run_job_command(
     job: &CronJob,
 ) -> (bool, String) {
     if !security.is_command_allowed(&job.command) {
+        security.audit_denial("shell", &job.command, "security policy");
         return (
             false,
             format!(
@@ -149,11 +190,15 @@ async fn run_job_command(
     }
 
     if let Some(path) = forbidden_path_argument(security, &job.command) {
+        security.audit_denial("shell", &job.command, "forbidden path");
         return (
             false,
             format!("blocked by security policy: forbidden path argument: {path}"),
         );
     }
+    security
+        .audit
+        .record("shell", &job.command, AuditDecision::Allowed, None);
 
     let output = Command::new("sh")
         .arg("-lc")
@@ -232,6 +277,27 @@ mod tests {
         assert!(output.contains("status=exit status:"));
     }
 
+    #[tokio::test]
+    async fn only_failures_and_printed_output_are_actionable() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+
+        let job = test_job("echo disk at 91%");
+        let (success, output) = run_job_command(&config, &security, &job).await;
+        let printed = notification(&job, success, &output);
+        assert!(printed.actionable && !printed.failed);
+        assert_eq!(job_stdout(&output), "disk at 91%");
+
+        let job = test_job("echo");
+        let (success, output) = run_job_command(&config, &security, &job).await;
+        assert!(success);
+        assert!(!notification(&job, success, &output).actionable);
+
+        let failed = notification(&job, false, "spawn error: no such file");
+        assert!(failed.actionable && failed.failed);
+    }
+
     #[tokio::test]
     async fn run_job_command_blocks_disallowed_command() {
         let tmp = TempDir::new().unwrap();
@@ -266,7 +332,7 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let mut config = test_config(&tmp);
         config.reliability.scheduler_retries = 1;
-        config.reliability.provider_backoff_ms = 1;
+        config.reliability.provider_initial_backoff_ms = 1;
         config.autonomy.allowed_commands = vec!["sh".into()];
         let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
 
@@ -287,7 +353,7 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let mut config = test_config(&tmp);
         config.reliability.scheduler_retries = 1;
-        config.reliability.provider_backoff_ms = 1;
+        config.reliability.provider_initial_backoff_ms = 1;
         let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
 
         let job = test_job("ls always_missing_for_retry_test");
@@ -0,0 +1,252 @@
+//! Size-based rotation for the background daemon's log files.
+//!
+//! `jarvis daemon` (and the launchd service) send the daemon's stdout and
+//! stderr to `~/.jarvis/logs/daemon.stdout.log` and `daemon.stderr.log`.
+//! Once a file grows past `[reliability] log_max_bytes` it is rolled to
+//! `<name>.1` next to it, older backups shift up (`.2`, `.3`, …) and those
+//! beyond `[reliability] log_backups` are deleted.
+//!
+//! Before the background daemon starts, files are rotated by rename. While it
+//! runs it keeps them open in append mode, so they are copied to `.1` and
+//! truncated in place instead.
+
+use crate::config::Config;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+pub const STDOUT_LOG: &str = "daemon.stdout.log";
+pub const STDERR_LOG: &str = "daemon.stderr.log";
+const CHECK_SECONDS: u64 = 60;
+
+/// Log directory: ~/.jarvis/logs
+pub fn logs_dir(config: &Config) -> PathBuf {
+    config
+        .config_path
+        .parent()
+        .map_or_else(|| PathBuf::from("."), PathBuf::from)
+        .join("logs")
+}
+
+/// `daemon.stdout.log` → `daemon.stdout.log.<n>`
+pub(crate) fn backup_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+/// Numbers of the `<path>.<n>` backups present on disk.
+fn existing_backups(path: &Path) -> Vec<usize> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.", name.to_string_lossy());
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            entry
+                .ok()?
+                .file_name()
+                .to_str()?
+                .strip_prefix(&prefix)?
+                .parse()
+                .ok()
+        })
+        .collect()
+}
+
+/// Roll `path` once it is larger than `max_bytes`, keeping `backups` old
+/// copies. `copy_truncate` is for files another process still has open.
+/// Returns whether the file was rotated.
+pub fn rotate_if_needed(
+    path: &Path,
+    max_bytes: u64,
+    backups: usize,
+    copy_truncate: bool,
+) -> Result<bool> {
+    if max_bytes == 0 {
+        return Ok(false);
+    }
+    let Ok(meta) = fs::metadata(path) else {
+        return Ok(false);
+    };
+    if meta.len() <= max_bytes {
+        return Ok(false);
+    }
+
+    // Drop backups that would end up past the retention count (including
+    // leftovers from a previously higher `log_backups`), then shift the rest
+    for stale in existing_backups(path)
+        .into_iter()
+        .filter(|&n| n >= backups.max(1))
+    {
+        let stale = backup_path(path, stale);
+        fs::remove_file(&stale).with_context(|| format!("删除旧日志失败：{}", stale.display()))?;
+    }
+    for n in (1..backups).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            fs::rename(&from, backup_path(path, n + 1))
+                .with_context(|| format!("轮转日志失败：{}", from.display()))?;
+        }
+    }
+
+    if backups > 0 {
+        let first = backup_path(path, 1);
+        if copy_truncate {
+            fs::copy(path, &first).with_context(|| format!("复制日志失败：{}", path.display()))?;
+        } else {
+            fs::rename(path, &first)
+                .with_context(|| format!("轮转日志失败：{}", path.display()))?;
+            return Ok(true);
+        }
+    }
+    fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_len(0))
+        .with_context(|| format!("截断日志失败：{}", path.display()))?;
+    Ok(true)
+}
+
+/// Rotate both daemon logs according to `[reliability]`.
+pub fn rotate_daemon_logs(config: &Config, copy_truncate: bool) -> Result<()> {
+    let dir = logs_dir(config);
+    for name in [STDOUT_LOG, STDERR_LOG] {
+        let path = dir.join(name);
+        if rotate_if_needed(
+            &path,
+            config.reliability.log_max_bytes,
+            config.reliability.log_backups,
+            copy_truncate,
+        )? {
+            tracing::info!("日志已轮转：{}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Check the daemon logs every minute while the daemon runs.
+pub(super) fn spawn_log_rotator(config: Config) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        if config.reliability.log_max_bytes == 0 {
+            return;
+        }
+        let mut interval = tokio::time::interval(Duration::from_secs(CHECK_SECONDS));
+        loop {
+            interval.tick().await;
+            let cfg = config.clone();
+            match tokio::task::spawn_blocking(move || rotate_daemon_logs(&cfg, true)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => tracing::warn!("日志轮转失败：{e:#}"),
+                Err(e) => tracing::warn!("日志轮转任务异常：{e}"),
+            }
+        }
+    })
+}
+
+/// `jarvis daemon --logs` — print the last `lines` lines of each daemon log.
+pub fn print_tail(config: &Config, lines: usize) {
+    let dir = logs_dir(config);
+    let mut found = false;
+    for name in [STDOUT_LOG, STDERR_LOG] {
+        let path = dir.join(name);
+        let Ok(bytes) = fs::read(&path) else {
+            continue;
+        };
+        found = true;
+        println!("==> {} <==", path.display());
+        let text = String::from_utf8_lossy(&bytes);
+        for line in tail(&text, lines) {
+            println!("{line}");
+        }
+        println!();
+    }
+    if !found {
+        println!("暂无守护进程日志（{}）", dir.display());
+        println!("后台启动：jarvis daemon");
+    }
+}
+
+fn tail(text: &str, lines: usize) -> Vec<&str> {
+    let all: Vec<&str> = text.lines().collect();
+    all[all.len().saturating_sub(lines)..].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, content: &str) {
+        fs::write(path, content).unwrap();
+    }
+
+    fn read(path: &Path) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn small_or_missing_logs_are_left_alone() {
+        let tmp = TempDir::new().unwrap();
+        let log = tmp.path().join(STDOUT_LOG);
+        assert!(!rotate_if_needed(&log, 10, 3, false).unwrap());
+
+        write(&log, "short");
+        assert!(!rotate_if_needed(&log, 10, 3, false).unwrap());
+        assert!(!rotate_if_needed(&log, 0, 3, false).unwrap(), "0 disables");
+        assert_eq!(read(&log), "short");
+    }
+
+    #[test]
+    fn rename_rotation_shifts_and_prunes_backups() {
+        let tmp = TempDir::new().unwrap();
+        let log = tmp.path().join(STDOUT_LOG);
+
+        for generation in 1..=4 {
+            write(&log, &format!("generation {generation} ..........."));
+            assert!(rotate_if_needed(&log, 10, 2, false).unwrap());
+            assert!(!log.exists());
+        }
+
+        assert_eq!(read(&backup_path(&log, 1)), "generation 4 ...........");
+        assert_eq!(read(&backup_path(&log, 2)), "generation 3 ...........");
+        assert!(!backup_path(&log, 3).exists());
+    }
+
+    #[test]
+    fn copy_truncate_keeps_file_in_place() {
+        let tmp = TempDir::new().unwrap();
+        let log = tmp.path().join(STDERR_LOG);
+        write(&log, "0123456789abcdef");
+        write(&backup_path(&log, 1), "older");
+        write(&backup_path(&log, 5), "left over from a larger log_backups");
+
+        assert!(rotate_if_needed(&log, 10, 3, true).unwrap());
+        assert_eq!(read(&log), "");
+        assert_eq!(read(&backup_path(&log, 1)), "0123456789abcdef");
+        assert_eq!(read(&backup_path(&log, 2)), "older");
+        assert!(!backup_path(&log, 5).exists());
+    }
+
+    #[test]
+    fn zero_backups_just_truncates() {
+        let tmp = TempDir::new().unwrap();
+        let log = tmp.path().join(STDOUT_LOG);
+        write(&log, "0123456789abcdef");
+
+        assert!(rotate_if_needed(&log, 10, 0, false).unwrap());
+        assert_eq!(read(&log), "");
+        assert!(!backup_path(&log, 1).exists());
+    }
+
+    #[test]
+    fn tail_returns_last_lines() {
+        assert_eq!(tail("a\nb\nc\n", 2), vec!["b", "c"]);
+        assert_eq!(tail("a\nb", 5), vec!["a", "b"]);
+        assert!(tail("", 3).is_empty());
+    }
+}
@@ -1,12 +1,24 @@
 use crate::config::Config;
+use crate::tunnel::Tunnel;
 use anyhow::{Context, Result};
 use chrono::Utc;
+use std::collections::VecDeque;
 use std::future::Future;
 use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::watch;
 use tokio::task::JoinHandle;
-use tokio::time::Duration;
+use tokio::time::{Duration, Instant};
+
+pub mod logs;
+#[cfg(windows)]
+pub(crate) mod windows;
 
 const STATUS_FLUSH_SECONDS: u64 = 5;
+const HYGIENE_CHECK_MINUTES: u64 = 10;
+const HEARTBEAT_POLL_SECS: u64 = 60;
+/// Health component name for the memory hygiene worker
+pub const HYGIENE_COMPONENT: &str = "memory-hygiene";
 
 /// PID 文件路径：~/.jarvis/daemon.pid
 pub fn pid_file_path(config: &Config) -> PathBuf {
@@ -42,14 +54,25 @@ pub fn is_daemon_running(config: &Config) -> Option<u32> {
         }
     }
 
-    #[cfg(not(unix))]
+    // Windows：通过守护进程创建的命名停止事件确认进程存活
+    #[cfg(windows)]
+    {
+        if windows::is_running(pid) {
+            Some(pid)
+        } else {
+            let _ = std::fs::remove_file(&path);
+            None
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
     {
-        // 非 Unix 平台：仅检查 PID 文件存在
+        // 其他平台：仅检查 PID 文件存在
         Some(pid)
     }
 }
 
-/// 停止运行中的 daemon（发送 SIGTERM）
+/// 停止运行中的 daemon（Unix 发送 SIGTERM，Windows 设置停止事件），超时后强制终止
 pub fn stop_daemon(config: &Config) -> Result<()> {
     let pid_path = pid_file_path(config);
     let Some(pid) = is_daemon_running(config) else {
@@ -68,9 +91,12 @@ pub fn stop_daemon(config: &Config) -> Result<()> {
         }
     }
 
-    #[cfg(not(unix))]
+    #[cfg(windows)]
+    windows::request_stop(pid)?;
+
+    #[cfg(not(any(unix, windows)))]
     {
-        anyhow::bail!("停止守护进程仅支持 Unix 平台");
+        anyhow::bail!("停止守护进程仅支持 Unix 和 Windows 平台");
     }
 
     // 等待进程退出（最多 10 秒）
@@ -88,6 +114,10 @@ pub fn stop_daemon(config: &Config) -> Result<()> {
     {
         let _ = unsafe { libc::kill(pid_to_native(pid), libc::SIGKILL) };
     }
+    #[cfg(windows)]
+    if let Err(e) = windows::terminate(pid) {
+        eprintln!("⚠️  {e:#}");
+    }
     let _ = std::fs::remove_file(&pid_path);
     println!("⚠️  守护进程已强制终止（PID {pid}）");
     Ok(())
@@ -108,14 +138,72 @@ fn remove_pid_file(config: &Config) {
     let _ = std::fs::remove_file(pid_file_path(config));
 }
 
+/// 重置已熔断的组件（向 daemon 发送 SIGHUP）
+pub fn reset_circuits(config: &Config) -> Result<()> {
+    let Some(pid) = is_daemon_running(config) else {
+        println!("守护进程未运行");
+        return Ok(());
+    };
+
+    #[cfg(unix)]
+    {
+        let result = unsafe { libc::kill(pid_to_native(pid), libc::SIGHUP) };
+        if result != 0 {
+            anyhow::bail!(
+                "发送 SIGHUP 到进程 {pid} 失败: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+        println!("🔌 已通知守护进程（PID {pid}）重启熔断的组件");
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    {
+        anyhow::bail!("重置熔断仅支持 Unix 平台，请重启守护进程（PID {pid}）");
+    }
+}
+
+/// How `spawn_component_supervisor` restarts a component: exponential
+/// backoff, and a crash-loop breaker that stops restarting after
+/// `max_failures` failures within `failure_window` until `reset` changes.
+#[derive(Clone)]
+struct SupervisorPolicy {
+    initial_backoff_secs: u64,
+    max_backoff_secs: u64,
+    /// 0 disables the breaker
+    max_failures: u32,
+    failure_window: Duration,
+    reset: watch::Receiver<u64>,
+}
+
+impl SupervisorPolicy {
+    fn from_config(config: &Config, reset: watch::Receiver<u64>) -> Self {
+        let reliability = &config.reliability;
+        let initial_backoff_secs = reliability.channel_initial_backoff_secs.max(1);
+        Self {
+            initial_backoff_secs,
+            max_backoff_secs: reliability
+                .channel_max_backoff_secs
+                .max(initial_backoff_secs),
+            max_failures: reliability.circuit_breaker_failures,
+            failure_window: Duration::from_secs(reliability.circuit_breaker_window_secs.max(1)),
+            reset,
+        }
+    }
+}
+
+#[allow(clippy::too_many_lines)]
 pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
     write_pid_file(&config)?;
+    // `jarvis daemon --stop` and the Windows service set this to shut down
+    #[cfg(windows)]
+    let stop_event = windows::StopEvent::create()?;
 
-    let initial_backoff = config.reliability.channel_initial_backoff_secs.max(1);
-    let max_backoff = config
-        .reliability
-        .channel_max_backoff_secs
-        .max(initial_backoff);
+    // SIGHUP (`jarvis daemon --reset`) bumps this to restart tripped components
+    let (reset_tx, reset_rx) = watch::channel(0_u64);
+    let reset_tx = Arc::new(reset_tx);
+    let policy = SupervisorPolicy::from_config(&config, reset_rx);
 
     crate::health::mark_component_ok("daemon");
 
@@ -125,19 +213,47 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
                 .await;
     }
 
-    let mut handles: Vec<JoinHandle<()>> = vec![spawn_state_writer(config.clone())];
+    let (config, metrics) = start_metrics_listener(config).await;
+    let mut handles: Vec<JoinHandle<()>> = vec![
+        spawn_state_writer(config.clone()),
+        logs::spawn_log_rotator(config.clone()),
+    ];
+    handles.extend(metrics);
+    #[cfg(unix)]
+    handles.push(spawn_reset_listener(Arc::clone(&reset_tx)));
 
     {
         let gateway_cfg = config.clone();
         let gateway_host = host.clone();
         handles.push(spawn_component_supervisor(
             "gateway",
-            initial_backoff,
-            max_backoff,
+            policy.clone(),
             move || {
                 let cfg = gateway_cfg.clone();
                 let host = gateway_host.clone();
-                async move { crate::gateway::run_gateway(&host, port, cfg).await }
+                async move { crate::gateway::run_gateway_without_tunnel(&host, port, cfg).await }
+            },
+        ));
+    }
+
+    let tunnel: Option<Arc<dyn Tunnel>> = match crate::tunnel::create_tunnel(&config.tunnel) {
+        Ok(tunnel) => tunnel.map(Arc::from),
+        Err(e) => {
+            crate::health::mark_component_error(crate::tunnel::TUNNEL_COMPONENT, &e);
+            tracing::error!("隧道配置无效：{e}");
+            None
+        }
+    };
+    if let Some(tunnel) = &tunnel {
+        let tunnel = Arc::clone(tunnel);
+        let tunnel_host = host.clone();
+        handles.push(spawn_component_supervisor(
+            crate::tunnel::TUNNEL_COMPONENT,
+            policy.clone(),
+            move || {
+                let tunnel = Arc::clone(&tunnel);
+                let host = tunnel_host.clone();
+                async move { crate::tunnel::run_tunnel(tunnel.as_ref(), &host, port).await }
             },
         ));
     }
@@ -147,8 +263,7 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
             let channels_cfg = config.clone();
             handles.push(spawn_component_supervisor(
                 "channels",
-                initial_backoff,
-                max_backoff,
+                policy.clone(),
                 move || {
                     let cfg = channels_cfg.clone();
                     async move { crate::channels::start_channels(cfg).await }
@@ -164,8 +279,7 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
         let heartbeat_cfg = config.clone();
         handles.push(spawn_component_supervisor(
             "heartbeat",
-            initial_backoff,
-            max_backoff,
+            policy.clone(),
             move || {
                 let cfg = heartbeat_cfg.clone();
                 async move { run_heartbeat_worker(cfg).await }
@@ -177,8 +291,7 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
         let scheduler_cfg = config.clone();
         handles.push(spawn_component_supervisor(
             "scheduler",
-            initial_backoff,
-            max_backoff,
+            policy.clone(),
             move || {
                 let cfg = scheduler_cfg.clone();
                 async move { crate::cron::scheduler::run(cfg).await }
@@ -186,11 +299,39 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
         ));
     }
 
+    if config.memory.hygiene_enabled {
+        let hygiene_cfg = config.clone();
+        handles.push(spawn_component_supervisor(
+            HYGIENE_COMPONENT,
+            policy.clone(),
+            move || {
+                let cfg = hygiene_cfg.clone();
+                async move { run_hygiene_worker(cfg).await }
+            },
+        ));
+    }
+
     println!("🧠 Jarvis 守护进程已启动");
     println!("   Gateway：http://{host}:{port}");
     println!("   组件：gateway, channels, heartbeat, scheduler");
+    if config.observability.backend == "prometheus" {
+        let listen = config.observability.prometheus_listen.as_deref();
+        println!(
+            "   指标：http://{}/metrics",
+            listen.map_or_else(|| format!("{host}:{port}"), str::to_string)
+        );
+    }
+    if let Some(tunnel) = &tunnel {
+        println!("   隧道：{}（公网地址见 jarvis status）", tunnel.name());
+    }
     println!("   按 Ctrl+C 停止");
 
+    #[cfg(windows)]
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result?,
+        () = stop_event.wait() => {}
+    }
+    #[cfg(not(windows))]
     tokio::signal::ctrl_c().await?;
     crate::health::mark_component_error("daemon", "shutdown requested");
 
@@ -200,6 +341,12 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
     for handle in handles {
         let _ = handle.await;
     }
+    if let Some(tunnel) = tunnel {
+        tunnel
+            .stop()
+            .await
+            .unwrap_or_else(|e| tracing::warn!("关闭 {} 隧道失败：{e}", tunnel.name()));
+    }
 
     remove_pid_file(&config);
     // 清理状态文件
@@ -239,10 +386,25 @@ fn spawn_state_writer(config: Config) -> JoinHandle<()> {
     })
 }
 
+/// Bump the reset counter on every SIGHUP.
+#[cfg(unix)]
+fn spawn_reset_listener(reset: Arc<watch::Sender<u64>>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let Ok(mut hangup) = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        else {
+            tracing::warn!("无法监听 SIGHUP，熔断的组件只能通过重启守护进程恢复");
+            return;
+        };
+        while hangup.recv().await.is_some() {
+            tracing::info!("收到 SIGHUP，重启已熔断的组件");
+            reset.send_modify(|generation| *generation = generation.wrapping_add(1));
+        }
+    })
+}
+
 fn spawn_component_supervisor<F, Fut>(
     name: &'static str,
-    initial_backoff_secs: u64,
-    max_backoff_secs: u64,
+    policy: SupervisorPolicy,
     mut run_component: F,
 ) -> JoinHandle<()>
 where
@@ -250,23 +412,64 @@ where
     Fut: Future<Output = Result<()>> + Send + 'static,
 {
     tokio::spawn(async move {
+        let SupervisorPolicy {
+            initial_backoff_secs,
+            max_backoff_secs,
+            max_failures,
+            failure_window,
+            mut reset,
+        } = policy;
         let mut backoff = initial_backoff_secs.max(1);
         let max_backoff = max_backoff_secs.max(backoff);
+        let mut failures: VecDeque<Instant> = VecDeque::new();
 
         loop {
             crate::health::mark_component_ok(name);
-            match run_component().await {
+            let error = match run_component().await {
                 Ok(()) => {
-                    crate::health::mark_component_error(name, "component exited unexpectedly");
                     tracing::warn!("守护进程组件「{name}」意外退出");
                     // Clean exit — reset backoff since the component ran successfully
                     backoff = initial_backoff_secs.max(1);
+                    "component exited unexpectedly".to_string()
                 }
                 Err(e) => {
-                    crate::health::mark_component_error(name, e.to_string());
                     tracing::error!("守护进程组件「{name}」失败：{e}");
+                    e.to_string()
                 }
+            };
+
+            let now = Instant::now();
+            failures.push_back(now);
+            while failures
+                .front()
+                .is_some_and(|t| now.duration_since(*t) > failure_window)
+            {
+                failures.pop_front();
+            }
+            if max_failures > 0 && failures.len() > max_failures as usize {
+                crate::health::mark_component_circuit_open(
+                    name,
+                    format!(
+                        "circuit open: failed {} times within {}s, no longer restarting (last error: {error})",
+                        failures.len(),
+                        failure_window.as_secs()
+                    ),
+                );
+                tracing::error!(
+                    "守护进程组件「{name}」{} 秒内失败 {} 次，已熔断并停止重启；修复后运行 `jarvis daemon --reset`",
+                    failure_window.as_secs(),
+                    failures.len()
+                );
+                reset.borrow_and_update();
+                if reset.changed().await.is_err() {
+                    return;
+                }
+                tracing::info!("守护进程组件「{name}」熔断已重置，正在重启");
+                failures.clear();
+                backoff = initial_backoff_secs.max(1);
+                continue;
             }
+            crate::health::mark_component_error(name, error);
 
             crate::health::bump_component_restart(name);
             tokio::time::sleep(Duration::from_secs(backoff)).await;
@@ -282,34 +485,292 @@ async fn run_heartbeat_worker(config: Config) -> Result<()> {
     let engine = crate::heartbeat::engine::HeartbeatEngine::new(
         config.heartbeat.clone(),
         config.workspace_dir.clone(),
-        observer,
+        observer.clone(),
     );
+    // A bad notify config degrades the heartbeat instead of stopping it
+    let notifier = crate::heartbeat::notify::notifier(&config).map_err(|e| format!("{e:#}"));
+    if let Err(e) = &notifier {
+        tracing::warn!("心跳通知配置无效，结果只写入日志：{e}");
+    }
 
-    let interval_mins = config.heartbeat.interval_minutes.max(5);
-    let mut interval = tokio::time::interval(Duration::from_secs(u64::from(interval_mins) * 60));
+    // Tasks keep their own cadence, so the file is checked every minute
+    let mut poll = tokio::time::interval(Duration::from_secs(HEARTBEAT_POLL_SECS));
+    let runner = std::sync::Arc::new(HeartbeatRunner {
+        task_timeout: Duration::from_secs(
+            u64::from(config.heartbeat.task_timeout_minutes.max(1)) * 60,
+        ),
+        permits: std::sync::Arc::new(tokio::sync::Semaphore::new(
+            config.heartbeat.max_parallel_tasks.max(1),
+        )),
+        running: std::sync::Mutex::new(std::collections::HashSet::new()),
+        config,
+        engine,
+        notifier,
+        observer,
+    });
 
     loop {
-        interval.tick().await;
+        poll.tick().await;
 
-        let tasks = engine.collect_tasks().await?;
+        let mut tasks = runner.engine.collect_tasks().await?;
+        {
+            let mut running = runner
+                .running
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            tasks.retain(|task| {
+                let idle = running.insert(task.key().to_string());
+                if !idle {
+                    tracing::warn!("心跳任务上一次仍在执行，本次跳过：{}", task.text);
+                    crate::health::mark_component_degraded(
+                        "heartbeat",
+                        format!("心跳任务上一次仍在执行，已跳过：{}", task.text),
+                    );
+                }
+                idle
+            });
+        }
         if tasks.is_empty() {
             continue;
         }
+        tokio::spawn(run_heartbeat_tick(runner.clone(), tasks));
+    }
+}
 
-        for task in tasks {
-            let prompt = format!("[Heartbeat Task] {task}");
-            let temp = config.default_temperature;
-            if let Err(e) = crate::agent::run(config.clone(), Some(prompt), None, None, temp).await
-            {
-                crate::health::mark_component_error("heartbeat", e.to_string());
+/// Everything a heartbeat task needs, shared by the tasks of a tick.
+struct HeartbeatRunner {
+    config: Config,
+    engine: crate::heartbeat::engine::HeartbeatEngine,
+    notifier: std::result::Result<Option<crate::notify::Notifier>, String>,
+    observer: std::sync::Arc<dyn crate::observability::Observer>,
+    task_timeout: Duration,
+    /// `max_parallel_tasks`, shared by every batch of due tasks
+    permits: std::sync::Arc<tokio::sync::Semaphore>,
+    /// Keys of the tasks in flight, which are not started twice
+    running: std::sync::Mutex<std::collections::HashSet<String>>,
+}
+
+/// What became of one heartbeat task.
+#[derive(Debug)]
+enum HeartbeatOutcome {
+    Done,
+    Failed(String),
+    Undelivered(String),
+    TimedOut(String),
+}
+
+impl HeartbeatRunner {
+    async fn run_task(&self, task: crate::heartbeat::engine::HeartbeatTask) -> HeartbeatOutcome {
+        use crate::heartbeat::notify;
+
+        let prompt = notify::prompt(self.notifier.as_ref().ok().and_then(Option::as_ref), &task);
+        let started = std::time::Instant::now();
+        let result = tokio::time::timeout(
+            self.task_timeout,
+            crate::agent::run(
+                self.config.clone(),
+                Some(prompt),
+                None,
+                None,
+                self.config.default_temperature,
+                "heartbeat",
+            ),
+        )
+        .await;
+        self.observer
+            .record_event(&crate::observability::ObserverEvent::HeartbeatTask {
+                task: task.text.clone(),
+                duration: started.elapsed(),
+                success: matches!(result, Ok(Ok(_))),
+                timed_out: result.is_err(),
+            });
+
+        let response = match result {
+            Ok(Ok(response)) => response.unwrap_or_default(),
+            Ok(Err(e)) => {
                 tracing::warn!("Heartbeat 任务失败：{e}");
-            } else {
-                crate::health::mark_component_ok("heartbeat");
+                let _ = self
+                    .notify(&notify::failure(&task, &format!("{e:#}")))
+                    .await;
+                return HeartbeatOutcome::Failed(e.to_string());
+            }
+            Err(_) => {
+                let message = format!(
+                    "心跳任务超过 {} 分钟未完成，已取消：{}",
+                    self.task_timeout.as_secs() / 60,
+                    task.text
+                );
+                tracing::warn!("{message}");
+                let _ = self.notify(&notify::failure(&task, &message)).await;
+                return HeartbeatOutcome::TimedOut(message);
             }
+        };
+        if let Err(e) = self.engine.record_success(&task).await {
+            tracing::warn!("记录心跳任务状态失败：{e}");
+        }
+
+        match self.notify(&notify::result(&task, &response)).await {
+            Ok(()) => HeartbeatOutcome::Done,
+            Err(e) => HeartbeatOutcome::Undelivered(e),
+        }
+    }
+
+    /// Send `notification` if a notifier is configured and it clears the
+    /// threshold; failures are logged and returned.
+    async fn notify(&self, notification: &crate::notify::Notification) -> Result<(), String> {
+        let delivered = match &self.notifier {
+            Ok(None) => Ok(()),
+            Ok(Some(notifier)) => notifier.deliver(notification).await.map(|_| ()),
+            Err(e) => Err(anyhow::anyhow!("心跳通知配置无效：{e}")),
+        };
+        delivered.map_err(|e| {
+            tracing::warn!("心跳结果投递失败：{e:#}");
+            format!("{e:#}")
+        })
+    }
+}
+
+/// Run a batch of due tasks, at most `max_parallel_tasks` at a time across
+/// batches, then report the batch to health.
+async fn run_heartbeat_tick(
+    runner: std::sync::Arc<HeartbeatRunner>,
+    tasks: Vec<crate::heartbeat::engine::HeartbeatTask>,
+) {
+    let mut running = tokio::task::JoinSet::new();
+    for task in tasks {
+        let runner = runner.clone();
+        running.spawn(async move {
+            let _permit = runner.permits.clone().acquire_owned().await;
+            let key = task.key().to_string();
+            let outcome = runner.run_task(task).await;
+            runner
+                .running
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .remove(&key);
+            outcome
+        });
+    }
+
+    let mut outcomes = Vec::new();
+    while let Some(joined) = running.join_next().await {
+        outcomes.push(
+            joined.unwrap_or_else(|e| HeartbeatOutcome::Failed(format!("心跳任务异常退出：{e}"))),
+        );
+    }
+    report_heartbeat_tick("heartbeat", &outcomes);
+}
+
+/// A failed task marks the component as errored and an undelivered result
+/// degrades it; a timeout is only noted, since the worker itself is fine.
+fn report_heartbeat_tick(component: &str, outcomes: &[HeartbeatOutcome]) {
+    let last = |pick: fn(&HeartbeatOutcome) -> Option<&String>| {
+        outcomes.iter().filter_map(pick).next_back()
+    };
+    let failed = last(|o| match o {
+        HeartbeatOutcome::Failed(e) => Some(e),
+        _ => None,
+    });
+    let undelivered = last(|o| match o {
+        HeartbeatOutcome::Undelivered(e) => Some(e),
+        _ => None,
+    });
+    let timed_out = last(|o| match o {
+        HeartbeatOutcome::TimedOut(e) => Some(e),
+        _ => None,
+    });
+
+    if let Some(e) = failed {
+        crate::health::mark_component_error(component, e);
+    } else if let Some(e) = undelivered {
+        crate::health::mark_component_degraded(component, e);
+    } else {
+        crate::health::mark_component_ok(component);
+    }
+    if let Some(e) = timed_out {
+        crate::health::record_component_error(component, e);
+    }
+}
+
+/// Bind `[observability] prometheus_listen` for the prometheus backend. When
+/// it can't be bound the daemon boots with the log observer instead.
+async fn start_metrics_listener(mut config: Config) -> (Config, Option<JoinHandle<()>>) {
+    if config.observability.backend != "prometheus" {
+        return (config, None);
+    }
+    let Some(listen) = config.observability.prometheus_listen.clone() else {
+        return (config, None);
+    };
+    let host = listen
+        .rsplit_once(':')
+        .map_or(listen.as_str(), |(host, _)| host);
+    let result =
+        if crate::security::pairing::is_public_bind(host) && !config.gateway.allow_public_bind {
+            Err(anyhow::anyhow!(
+            "{listen} 不是本机地址，指标端点没有认证（需要 [gateway] allow_public_bind = true）"
+        ))
+        } else {
+            crate::observability::prometheus::serve(&listen).await
+        };
+    match result {
+        Ok(handle) => (config, Some(handle)),
+        Err(e) => {
+            tracing::warn!("Prometheus 指标端点启动失败，改用日志观测：{e:#}");
+            config.observability.backend = "log".into();
+            (config, None)
         }
     }
 }
 
+/// Run memory hygiene once on start, then daily (later passes are throttled by the
+/// state file), and publish the latest summary into the health snapshot.
+async fn run_hygiene_worker(config: Config) -> Result<()> {
+    let observer = crate::observability::create_observer(&config.observability);
+    let mut interval = tokio::time::interval(Duration::from_secs(HYGIENE_CHECK_MINUTES * 60));
+    let mut startup_pass = true;
+
+    loop {
+        interval.tick().await;
+        run_hygiene_pass(
+            &config,
+            observer.as_ref(),
+            std::mem::take(&mut startup_pass),
+        )
+        .await?;
+    }
+}
+
+/// One pass of the hygiene worker; `force` skips the cadence gate.
+async fn run_hygiene_pass(
+    config: &Config,
+    observer: &dyn crate::observability::Observer,
+    force: bool,
+) -> Result<()> {
+    let memory_cfg = config.memory.clone();
+    let workspace_dir = config.workspace_dir.clone();
+    let report = tokio::task::spawn_blocking(move || {
+        if force {
+            crate::memory::hygiene::run_now(&memory_cfg, &workspace_dir, false).map(Some)
+        } else {
+            crate::memory::hygiene::run_if_due(&memory_cfg, &workspace_dir)
+        }
+    })
+    .await??;
+
+    if let Some(report) = report {
+        observer.record_event(&crate::observability::ObserverEvent::MemoryHygiene {
+            archived: report.archived(),
+            purged: report.purged(),
+        });
+    }
+
+    if let Some(summary) = crate::memory::hygiene::status_summary(&config.workspace_dir) {
+        crate::health::set_component_detail(HYGIENE_COMPONENT, summary);
+    }
+    crate::health::mark_component_ok(HYGIENE_COMPONENT);
+    Ok(())
+}
+
 fn has_supervised_channels(config: &Config) -> bool {
     config.channels_config.telegram.is_some()
         || config.channels_config.discord.is_some()
@@ -333,6 +794,29 @@ mod tests {
         config
     }
 
+    #[tokio::test]
+    async fn metrics_listener_degrades_to_log_when_it_cannot_bind() {
+        let tmp = TempDir::new().unwrap();
+        let taken = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = test_config(&tmp);
+        config.observability.backend = "prometheus".into();
+        config.observability.prometheus_listen = Some(taken.local_addr().unwrap().to_string());
+
+        let (degraded, handle) = start_metrics_listener(config.clone()).await;
+        assert!(handle.is_none());
+        assert_eq!(degraded.observability.backend, "log");
+
+        config.observability.prometheus_listen = Some("0.0.0.0:0".into());
+        let (refused, handle) = start_metrics_listener(config.clone()).await;
+        assert!(handle.is_none());
+        assert_eq!(refused.observability.backend, "log");
+
+        config.observability.prometheus_listen = Some("127.0.0.1:0".into());
+        let (started, handle) = start_metrics_listener(config).await;
+        assert_eq!(started.observability.backend, "prometheus");
+        handle.unwrap().abort();
+    }
+
     #[test]
     fn state_file_path_uses_config_directory() {
         let tmp = TempDir::new().unwrap();
@@ -342,9 +826,49 @@ mod tests {
         assert_eq!(path, tmp.path().join("daemon_state.json"));
     }
 
+    #[test]
+    fn heartbeat_timeouts_are_noted_but_not_failures() {
+        report_heartbeat_tick(
+            "daemon-test-heartbeat",
+            &[
+                HeartbeatOutcome::Done,
+                HeartbeatOutcome::TimedOut("slow task".into()),
+            ],
+        );
+        let snapshot = crate::health::snapshot();
+        let component = &snapshot.components["daemon-test-heartbeat"];
+        assert_eq!(component.status, "ok");
+        assert_eq!(component.last_error.as_deref(), Some("slow task"));
+
+        report_heartbeat_tick(
+            "daemon-test-heartbeat",
+            &[
+                HeartbeatOutcome::Undelivered("webhook down".into()),
+                HeartbeatOutcome::Failed("provider error".into()),
+            ],
+        );
+        let snapshot = crate::health::snapshot();
+        let component = &snapshot.components["daemon-test-heartbeat"];
+        assert_eq!(component.status, "error");
+        assert_eq!(component.last_error.as_deref(), Some("provider error"));
+    }
+
+    fn test_policy(max_failures: u32) -> (SupervisorPolicy, watch::Sender<u64>) {
+        let (reset_tx, reset) = watch::channel(0);
+        let policy = SupervisorPolicy {
+            initial_backoff_secs: 1,
+            max_backoff_secs: 1,
+            max_failures,
+            failure_window: Duration::from_mins(1),
+            reset,
+        };
+        (policy, reset_tx)
+    }
+
     #[tokio::test]
     async fn supervisor_marks_error_and_restart_on_failure() {
-        let handle = spawn_component_supervisor("daemon-test-fail", 1, 1, || async {
+        let (policy, _reset) = test_policy(5);
+        let handle = spawn_component_supervisor("daemon-test-fail", policy, || async {
             anyhow::bail!("boom")
         });
 
@@ -364,7 +888,8 @@ mod tests {
 
     #[tokio::test]
     async fn supervisor_marks_unexpected_exit_as_error() {
-        let handle = spawn_component_supervisor("daemon-test-exit", 1, 1, || async { Ok(()) });
+        let (policy, _reset) = test_policy(5);
+        let handle = spawn_component_supervisor("daemon-test-exit", policy, || async { Ok(()) });
 
         tokio::time::sleep(Duration::from_millis(50)).await;
         handle.abort();
@@ -380,6 +905,119 @@ mod tests {
             .contains("component exited unexpectedly"));
     }
 
+    #[tokio::test]
+    async fn supervisor_opens_circuit_after_repeated_failures() {
+        let attempts = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let (policy, reset) = test_policy(1);
+        let counter = Arc::clone(&attempts);
+        let handle = spawn_component_supervisor("daemon-test-circuit", policy, move || {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async { anyhow::bail!("token revoked") }
+        });
+
+        // Two failures one backoff apart trip a breaker allowing one
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        let component = &crate::health::snapshot_json()["components"]["daemon-test-circuit"];
+        assert_eq!(component["status"], "error");
+        assert_eq!(component["circuit_open"], true);
+        let error = component["last_error"].as_str().unwrap_or("");
+        assert!(error.contains("circuit open"), "{error}");
+        assert!(error.contains("token revoked"), "{error}");
+
+        // Tripped: no further restarts
+        let tripped_at = attempts.load(std::sync::atomic::Ordering::SeqCst);
+        assert_eq!(tripped_at, 2);
+        tokio::time::sleep(Duration::from_millis(1200)).await;
+        assert_eq!(
+            attempts.load(std::sync::atomic::Ordering::SeqCst),
+            tripped_at
+        );
+
+        // A reset restarts it with a fresh failure count
+        reset.send_modify(|generation| *generation += 1);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(
+            attempts.load(std::sync::atomic::Ordering::SeqCst),
+            tripped_at + 1
+        );
+        let component = &crate::health::snapshot_json()["components"]["daemon-test-circuit"];
+        assert_eq!(component["circuit_open"], false);
+
+        handle.abort();
+        let _ = handle.await;
+    }
+
+    #[tokio::test]
+    async fn hygiene_pass_publishes_summary_detail() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+
+        run_hygiene_pass(&config, &crate::observability::NoopObserver, true)
+            .await
+            .unwrap();
+
+        let snapshot = crate::health::snapshot_json();
+        let component = &snapshot["components"][HYGIENE_COMPONENT];
+        assert_eq!(component["status"], "ok");
+        assert!(component["detail"]
+            .as_str()
+            .unwrap_or("")
+            .starts_with("archived 0, purged 0, last run"));
+    }
+
+    #[tokio::test]
+    async fn forced_hygiene_pass_runs_despite_recent_state() {
+        use crate::memory::{Memory, MemoryCategory, SqliteMemory};
+
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let workspace = config.workspace_dir.clone();
+
+        // A pass just ran, so the cadence gate alone would skip this one
+        crate::memory::hygiene::run_now(&config.memory, &workspace, false).unwrap();
+
+        let mem = SqliteMemory::new(&workspace).unwrap();
+        mem.store(
+            "old_chat",
+            "asked about tokio",
+            MemoryCategory::Conversation,
+        )
+        .await
+        .unwrap();
+        mem.store("old_standup", "shipped it", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        drop(mem);
+        let conn = rusqlite::Connection::open(workspace.join("memory").join("brain.db")).unwrap();
+        for (key, days) in [("old_chat", 45), ("old_standup", 10)] {
+            let ts = (chrono::Local::now() - chrono::Duration::days(days)).to_rfc3339();
+            conn.execute(
+                "UPDATE memories SET created_at = ?1, updated_at = ?1 WHERE key = ?2",
+                rusqlite::params![ts, key],
+            )
+            .unwrap();
+        }
+
+        // The worker's first pass is forced
+        run_hygiene_pass(&config, &crate::observability::NoopObserver, true)
+            .await
+            .unwrap();
+
+        let live: i64 = conn
+            .query_row("SELECT COUNT(*) FROM memories", [], |row| row.get(0))
+            .unwrap();
+        let archived: i64 = conn
+            .query_row("SELECT COUNT(*) FROM memories_archive", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(live, 0);
+        assert_eq!(
+            archived, 1,
+            "only the daily row is archived; the chat is purged"
+        );
+    }
+
     #[test]
     fn pid_file_path_uses_config_directory() {
         let tmp = TempDir::new().unwrap();
@@ -0,0 +1,187 @@
+//! Windows replacement for the signals the Unix daemon relies on.
+//!
+//! Every daemon process creates a named event, `JarvisDaemonStop-<pid>`.
+//! `jarvis daemon --stop` (and the Windows service's stop handler) set it to
+//! ask for a clean shutdown, and `is_daemon_running` opens it to check that
+//! the PID in `daemon.pid` still belongs to a live daemon. The event is
+//! created in the `Global\` namespace when possible, so a daemon running as
+//! a service in session 0 is visible to other sessions, and falls back to
+//! `Local\` without the privilege to do so.
+//!
+//! A daemon that ignores the event is ended with `TerminateProcess`, the
+//! counterpart of `SIGKILL`.
+
+use anyhow::{Context, Result};
+use std::io;
+use tokio::time::Duration;
+use windows_sys::Win32::Foundation::{
+    CloseHandle, GetLastError, ERROR_ACCESS_DENIED, HANDLE, WAIT_OBJECT_0,
+};
+use windows_sys::Win32::System::Threading::{
+    CreateEventW, OpenEventW, OpenProcess, SetEvent, TerminateProcess, WaitForSingleObject,
+    CREATE_NEW_PROCESS_GROUP, DETACHED_PROCESS, EVENT_MODIFY_STATE, PROCESS_TERMINATE,
+    SYNCHRONIZATION_SYNCHRONIZE,
+};
+
+/// How often the daemon checks its stop event
+const POLL_MILLIS: u64 = 500;
+
+fn event_names(pid: u32) -> [String; 2] {
+    [
+        format!("Global\\JarvisDaemonStop-{pid}"),
+        format!("Local\\JarvisDaemonStop-{pid}"),
+    ]
+}
+
+fn wide(name: &str) -> Vec<u16> {
+    name.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// The stop event of the running daemon, closed on drop.
+pub(super) struct StopEvent(HANDLE);
+
+// The handle is only waited on and closed, both of which are thread-safe
+unsafe impl Send for StopEvent {}
+unsafe impl Sync for StopEvent {}
+
+impl StopEvent {
+    pub(super) fn create() -> Result<Self> {
+        for name in event_names(std::process::id()) {
+            let name = wide(&name);
+            // Manual reset, initially unset
+            let handle = unsafe { CreateEventW(std::ptr::null(), 1, 0, name.as_ptr()) };
+            if !handle.is_null() {
+                return Ok(Self(handle));
+            }
+        }
+        Err(io::Error::last_os_error()).context("创建守护进程停止事件失败")
+    }
+
+    /// Resolves once someone sets the event.
+    pub(super) async fn wait(&self) {
+        let mut interval = tokio::time::interval(Duration::from_millis(POLL_MILLIS));
+        loop {
+            interval.tick().await;
+            if unsafe { WaitForSingleObject(self.0, 0) } == WAIT_OBJECT_0 {
+                return;
+            }
+        }
+    }
+}
+
+impl Drop for StopEvent {
+    fn drop(&mut self) {
+        unsafe { CloseHandle(self.0) };
+    }
+}
+
+enum Lookup {
+    Found(HANDLE),
+    /// The event exists but belongs to another account (e.g. the service)
+    Denied,
+    Missing,
+}
+
+fn open_event(pid: u32, access: u32) -> Lookup {
+    let mut denied = false;
+    for name in event_names(pid) {
+        let name = wide(&name);
+        let handle = unsafe { OpenEventW(access, 0, name.as_ptr()) };
+        if !handle.is_null() {
+            return Lookup::Found(handle);
+        }
+        denied |= unsafe { GetLastError() } == ERROR_ACCESS_DENIED;
+    }
+    if denied {
+        Lookup::Denied
+    } else {
+        Lookup::Missing
+    }
+}
+
+/// Whether the daemon with this PID is still running.
+pub(super) fn is_running(pid: u32) -> bool {
+    match open_event(pid, SYNCHRONIZATION_SYNCHRONIZE) {
+        Lookup::Found(handle) => {
+            unsafe { CloseHandle(handle) };
+            true
+        }
+        Lookup::Denied => true,
+        Lookup::Missing => false,
+    }
+}
+
+/// Ask the daemon with this PID to shut down.
+pub(crate) fn request_stop(pid: u32) -> Result<()> {
+    match open_event(pid, EVENT_MODIFY_STATE) {
+        Lookup::Found(handle) => {
+            let set = unsafe { SetEvent(handle) };
+            let err = io::Error::last_os_error();
+            unsafe { CloseHandle(handle) };
+            if set == 0 {
+                return Err(err).with_context(|| format!("通知守护进程（PID {pid}）停止失败"));
+            }
+            Ok(())
+        }
+        Lookup::Denied => anyhow::bail!(
+            "无权停止守护进程（PID {pid}）— 它可能作为 Windows 服务运行，请在管理员终端执行 jarvis service stop"
+        ),
+        Lookup::Missing => anyhow::bail!("守护进程（PID {pid}）已不在运行"),
+    }
+}
+
+/// Kill the process with this PID outright.
+pub(super) fn terminate(pid: u32) -> Result<()> {
+    let handle = unsafe { OpenProcess(PROCESS_TERMINATE, 0, pid) };
+    if handle.is_null() {
+        return Err(io::Error::last_os_error()).with_context(|| format!("打开进程 {pid} 失败"));
+    }
+    let ended = unsafe { TerminateProcess(handle, 1) };
+    let err = io::Error::last_os_error();
+    unsafe { CloseHandle(handle) };
+    if ended == 0 {
+        return Err(err).with_context(|| format!("终止进程 {pid} 失败"));
+    }
+    Ok(())
+}
+
+/// Start the background daemon without a console and outside the caller's
+/// process group, so closing the terminal or Ctrl+C in it doesn't reach it.
+pub(crate) fn detach(cmd: &mut std::process::Command) {
+    use std::os::windows::process::CommandExt;
+    cmd.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stop_event_round_trip() {
+        let pid = std::process::id();
+        assert!(!is_running(pid));
+
+        let event = StopEvent::create().unwrap();
+        assert!(is_running(pid));
+
+        request_stop(pid).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), event.wait())
+            .await
+            .expect("stop event was set");
+
+        drop(event);
+        assert!(!is_running(pid));
+    }
+
+    #[test]
+    fn terminate_ends_a_detached_process() {
+        let mut cmd = std::process::Command::new("ping");
+        cmd.args(["-n", "30", "127.0.0.1"])
+            .stdout(std::process::Stdio::null());
+        detach(&mut cmd);
+        let mut child = cmd.spawn().unwrap();
+
+        terminate(child.id()).unwrap();
+        assert_eq!(child.wait().unwrap().code(), Some(1));
+    }
+}
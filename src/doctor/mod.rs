@@ -1,45 +1,240 @@
-use crate::config::Config;
+use crate::channels::probe;
+use crate::config::{ChannelsConfig, Config};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 const DAEMON_STALE_SECONDS: i64 = 30;
 const SCHEDULER_STALE_SECONDS: i64 = 120;
 const CHANNEL_STALE_SECONDS: i64 = 300;
+const HYGIENE_STALE_SECONDS: i64 = 1800;
 
-pub fn run(config: &Config) -> Result<()> {
+/// How bad a finding is; any [`Level::Error`] makes `jarvis doctor` exit
+/// non-zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Ok,
+    Info,
+    Error,
+}
+
+/// One line of the doctor report.
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    /// What was checked: `daemon`, `scheduler`, `channel:telegram`, …
+    pub check: String,
+    pub level: Level,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    /// Round trip of a `--deep` network check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+}
+
+impl Finding {
+    fn new(check: &str, level: Level, message: impl Into<String>) -> Self {
+        Self {
+            check: check.to_string(),
+            level,
+            message: message.into(),
+            hint: None,
+            latency_ms: None,
+        }
+    }
+
+    fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+/// How `jarvis doctor` reports its findings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    /// The full report
+    Human,
+    /// Nothing; only the exit code
+    Quiet,
+    /// The findings as JSON
+    Json,
+}
+
+/// Print the report and return whether everything is healthy. `deep` adds
+/// live checks of the provider and channel credentials.
+pub async fn run(config: &Config, output: Output, deep: bool) -> Result<bool> {
     let state_file = crate::daemon::state_file_path(config);
-    if !state_file.exists() {
-        println!("🩺 Jarvis 诊断");
-        println!("  ❌ 守护进程状态文件未找到: {}", state_file.display());
-        println!("  💡 启动守护进程: jarvis daemon");
-        return Ok(());
+    let mut findings = check_config(config);
+    findings.extend(check_runtime(config));
+    findings.extend(diagnose(config, &state_file));
+    if deep {
+        findings.extend(probe_connectivity(config).await);
     }
+    let problems = findings
+        .iter()
+        .filter(|finding| finding.level == Level::Error)
+        .count();
 
-    let raw = std::fs::read_to_string(&state_file)
-        .with_context(|| format!("读取失败 {}", state_file.display()))?;
-    let snapshot: serde_json::Value =
-        serde_json::from_str(&raw).with_context(|| format!("解析失败 {}", state_file.display()))?;
+    match output {
+        Output::Quiet => {}
+        Output::Json => {
+            let report = serde_json::json!({
+                "healthy": problems == 0,
+                "state_file": state_file.display().to_string(),
+                "findings": findings,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        Output::Human => {
+            println!("🩺 Jarvis 诊断");
+            if state_file.exists() {
+                println!("  状态文件: {}", state_file.display());
+            }
+            for finding in &findings {
+                let icon = match finding.level {
+                    Level::Ok => "✅",
+                    Level::Info => "ℹ️",
+                    Level::Error => "❌",
+                };
+                println!("  {icon} {}", finding.message);
+                if let Some(hint) = &finding.hint {
+                    println!("     💡 {hint}");
+                }
+            }
+            if problems > 0 {
+                println!();
+                println!("发现 {problems} 个问题");
+            }
+        }
+    }
+    Ok(problems == 0)
+}
+
+/// The keys config.toml has that the schema ignored and the problems
+/// [`Config::check`] finds, or one finding saying there are none.
+fn check_config(config: &Config) -> Vec<Finding> {
+    let problems = config.check();
+    if problems.is_empty() && config.unknown_keys.is_empty() {
+        return vec![Finding::new("config", Level::Ok, "配置校验通过")];
+    }
+    let unknown = config.unknown_keys.iter().map(|key| {
+        Finding::new("config", Level::Error, format!("{}：{key}", key.path))
+            .hint("该项不会生效，请修正或删除；设置 strict_config = true 可让含未知配置项的文件直接加载失败")
+    });
+    unknown
+        .chain(problems.into_iter().map(|problem| {
+            let level = if problem.is_error() {
+                Level::Error
+            } else {
+                Level::Info
+            };
+            Finding::new(
+                "config",
+                level,
+                format!("{}：{}", problem.path, problem.message),
+            )
+            .hint("运行 jarvis config validate 查看全部配置问题")
+        }))
+        .collect()
+}
+
+/// The container shell commands run in, when `runtime.kind` is docker.
+fn check_runtime(config: &Config) -> Option<Finding> {
+    use crate::runtime::docker::{container_name, container_status, ContainerStatus};
 
-    println!("🩺 Jarvis 诊断");
-    println!("  状态文件: {}", state_file.display());
+    if config.runtime.kind == "sandbox" {
+        return Some(
+            match crate::runtime::sandbox::locate(&config.runtime.sandbox) {
+                Ok((backend, program)) => Finding::new(
+                    "runtime",
+                    Level::Ok,
+                    format!(
+                        "shell 命令在 {} 沙箱中运行（{}，网络{}）",
+                        backend.program(),
+                        program.display(),
+                        if config.runtime.sandbox.network {
+                            "开启"
+                        } else {
+                            "关闭"
+                        }
+                    ),
+                ),
+                Err(e) => Finding::new("runtime", Level::Error, format!("{e:#}")),
+            },
+        );
+    }
+    if config.runtime.kind != "docker" {
+        return None;
+    }
+    let docker = &config.runtime.docker;
+    let name = container_name(docker, &config.workspace_dir);
+    Some(match container_status(docker, &config.workspace_dir) {
+        Ok(ContainerStatus::Running { image }) => Finding::new(
+            "runtime",
+            Level::Ok,
+            format!("Docker 容器 {name} 运行中（镜像 {image}）"),
+        ),
+        Ok(ContainerStatus::Stopped { state }) => Finding::new(
+            "runtime",
+            Level::Error,
+            format!("Docker 容器 {name} 未运行（状态 {state}）"),
+        )
+        .hint(format!(
+            "下次启动 agent 时会自动启动，也可以运行 docker start {name}"
+        )),
+        Ok(ContainerStatus::Missing) => Finding::new(
+            "runtime",
+            Level::Info,
+            format!("Docker 容器 {name} 尚未创建"),
+        )
+        .hint("首次启动 agent 时会自动创建"),
+        Err(e) => Finding::new("runtime", Level::Error, format!("{e:#}")),
+    })
+}
 
+/// Check the daemon's state file and the tunnel.
+fn diagnose(config: &Config, state_file: &Path) -> Vec<Finding> {
+    let snapshot = match read_snapshot(state_file) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            let mut findings = vec![Finding::new("daemon", Level::Error, format!("{e:#}"))
+                .hint("启动守护进程: jarvis daemon")];
+            check_tunnel(config, None, &mut findings);
+            return findings;
+        }
+    };
+
+    let mut findings = Vec::new();
     let updated_at = snapshot
         .get("updated_at")
         .and_then(serde_json::Value::as_str)
         .unwrap_or("");
-
-    if let Ok(ts) = DateTime::parse_from_rfc3339(updated_at) {
-        let age = Utc::now()
-            .signed_duration_since(ts.with_timezone(&Utc))
-            .num_seconds();
-        if age <= DAEMON_STALE_SECONDS {
-            println!("  ✅ 守护进程心跳正常（{age}秒前）");
-        } else {
-            println!("  ❌ 守护进程心跳过期（{age}秒前）");
+    findings.push(match parse_rfc3339(updated_at) {
+        Some(ts) => {
+            let age = Utc::now().signed_duration_since(ts).num_seconds();
+            if age <= DAEMON_STALE_SECONDS {
+                Finding::new(
+                    "daemon",
+                    Level::Ok,
+                    format!("守护进程心跳正常（{age}秒前）"),
+                )
+            } else {
+                Finding::new(
+                    "daemon",
+                    Level::Error,
+                    format!("守护进程心跳过期（{age}秒前）"),
+                )
+            }
         }
-    } else {
-        println!("  ❌ 守护进程时间戳无效: {updated_at}");
-    }
+        None => Finding::new(
+            "daemon",
+            Level::Error,
+            format!("守护进程时间戳无效: {updated_at}"),
+        ),
+    });
 
     let mut channel_count = 0_u32;
     let mut stale_channels = 0_u32;
@@ -49,28 +244,26 @@ pub fn run(config: &Config) -> Result<()> {
         .and_then(serde_json::Value::as_object)
     {
         if let Some(scheduler) = components.get("scheduler") {
-            let scheduler_ok = scheduler
-                .get("status")
-                .and_then(serde_json::Value::as_str)
-                .is_some_and(|s| s == "ok");
-
-            let scheduler_last_ok = scheduler
-                .get("last_ok")
-                .and_then(serde_json::Value::as_str)
-                .and_then(parse_rfc3339)
-                .map_or(i64::MAX, |dt| {
-                    Utc::now().signed_duration_since(dt).num_seconds()
-                });
-
-            if scheduler_ok && scheduler_last_ok <= SCHEDULER_STALE_SECONDS {
-                println!("  ✅ 调度器健康（上次正常 {scheduler_last_ok}秒前）");
-            } else {
-                println!(
-                    "  ❌ 调度器异常/过期（status_ok={scheduler_ok}, age={scheduler_last_ok}s）"
-                );
-            }
+            let (scheduler_ok, scheduler_last_ok) = component_state(scheduler);
+            findings.push(
+                if scheduler_ok && scheduler_last_ok <= SCHEDULER_STALE_SECONDS {
+                    Finding::new(
+                        "scheduler",
+                        Level::Ok,
+                        format!("调度器健康（上次正常 {scheduler_last_ok}秒前）"),
+                    )
+                } else {
+                    Finding::new(
+                        "scheduler",
+                        Level::Error,
+                        format!(
+                            "调度器异常/过期（status_ok={scheduler_ok}, age={scheduler_last_ok}s）"
+                        ),
+                    )
+                },
+            );
         } else {
-            println!("  ❌ 调度器组件缺失");
+            findings.push(Finding::new("scheduler", Level::Error, "调度器组件缺失"));
         }
 
         for (name, component) in components {
@@ -79,34 +272,299 @@ pub fn run(config: &Config) -> Result<()> {
             }
 
             channel_count += 1;
-            let status_ok = component
-                .get("status")
-                .and_then(serde_json::Value::as_str)
-                .is_some_and(|s| s == "ok");
-            let age = component
-                .get("last_ok")
-                .and_then(serde_json::Value::as_str)
-                .and_then(parse_rfc3339)
-                .map_or(i64::MAX, |dt| {
-                    Utc::now().signed_duration_since(dt).num_seconds()
-                });
-
+            let (status_ok, age) = component_state(component);
             if status_ok && age <= CHANNEL_STALE_SECONDS {
-                println!("  ✅ {name} 正常（上次正常 {age}秒前）");
+                findings.push(Finding::new(
+                    name,
+                    Level::Ok,
+                    format!("{name} 正常（上次正常 {age}秒前）"),
+                ));
             } else {
                 stale_channels += 1;
-                println!("  ❌ {name} 过期/异常（status_ok={status_ok}, age={age}s）");
+                findings.push(Finding::new(
+                    name,
+                    Level::Error,
+                    format!("{name} 过期/异常（status_ok={status_ok}, age={age}s）"),
+                ));
             }
         }
     }
 
-    if channel_count == 0 {
-        println!("  ℹ️ 状态中尚未跟踪任何通道组件");
+    findings.push(if channel_count == 0 {
+        Finding::new("channels", Level::Info, "状态中尚未跟踪任何通道组件")
+    } else {
+        Finding::new(
+            "channels",
+            Level::Info,
+            format!("通道汇总: 共 {channel_count} 个，{stale_channels} 个已过期"),
+        )
+    });
+
+    check_open_circuits(&snapshot, &mut findings);
+    check_memory_hygiene(config, &snapshot, &mut findings);
+    check_tunnel(config, Some(&snapshot), &mut findings);
+    findings
+}
+
+/// A blocking credential check for one channel.
+type ChannelCheck = Box<dyn FnOnce() -> Result<String> + Send>;
+
+/// `--deep`: ping the provider and check each channel's credentials, all at
+/// once so the slowest check bounds the wait.
+async fn probe_connectivity(config: &Config) -> Vec<Finding> {
+    let provider_name = config
+        .default_provider
+        .clone()
+        .unwrap_or_else(|| "openrouter".into());
+    let provider_probe = async {
+        let started = Instant::now();
+        let result =
+            match crate::providers::create_provider(&provider_name, config.api_key.as_deref()) {
+                Ok(provider) => tokio::time::timeout(probe::PROBE_TIMEOUT, provider.ping())
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(anyhow::anyhow!(
+                            "超时（>{}秒）",
+                            probe::PROBE_TIMEOUT.as_secs()
+                        ))
+                    }),
+                Err(e) => Err(e),
+            };
+        probe_finding(
+            "probe:provider",
+            &format!("Provider {provider_name}"),
+            started.elapsed(),
+            result.map(|()| String::new()),
+        )
+    };
+
+    let channel_probes =
+        channel_checks(&config.channels_config)
+            .into_iter()
+            .map(|(name, check)| async move {
+                let started = Instant::now();
+                let result = tokio::task::spawn_blocking(check)
+                    .await
+                    .unwrap_or_else(|e| Err(anyhow::anyhow!("检查中断：{e}")));
+                probe_finding(
+                    &format!("probe:{}", name.to_lowercase()),
+                    name,
+                    started.elapsed(),
+                    result,
+                )
+            });
+
+    let (provider, channels) = tokio::join!(
+        provider_probe,
+        futures_util::future::join_all(channel_probes)
+    );
+    std::iter::once(provider).chain(channels).collect()
+}
+
+/// The wizard's connection test for every configured channel that has one.
+fn channel_checks(config: &ChannelsConfig) -> Vec<(&'static str, ChannelCheck)> {
+    let mut checks: Vec<(&'static str, ChannelCheck)> = Vec::new();
+    if let Some(telegram) = &config.telegram {
+        let token = telegram.bot_token.clone();
+        checks.push((
+            "Telegram",
+            Box::new(move || probe::check_telegram(&token).map(|bot| format!("@{bot}"))),
+        ));
+    }
+    if let Some(discord) = &config.discord {
+        let token = discord.bot_token.clone();
+        checks.push(("Discord", Box::new(move || probe::check_discord(&token))));
+    }
+    if let Some(slack) = &config.slack {
+        let token = slack.bot_token.clone();
+        checks.push(("Slack", Box::new(move || probe::check_slack(&token))));
+    }
+    if let Some(matrix) = &config.matrix {
+        let (homeserver, token) = (matrix.homeserver.clone(), matrix.access_token.clone());
+        checks.push((
+            "Matrix",
+            Box::new(move || probe::check_matrix(&homeserver, &token)),
+        ));
+    }
+    if let Some(whatsapp) = &config.whatsapp {
+        let (token, phone) = (
+            whatsapp.access_token.clone(),
+            whatsapp.phone_number_id.clone(),
+        );
+        checks.push((
+            "WhatsApp",
+            Box::new(move || probe::check_whatsapp(&token, &phone).map(|()| String::new())),
+        ));
+    }
+    checks
+}
+
+fn probe_finding(check: &str, label: &str, elapsed: Duration, result: Result<String>) -> Finding {
+    let ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+    let mut finding = match result {
+        Ok(detail) if detail.is_empty() => {
+            Finding::new(check, Level::Ok, format!("{label} 连接正常（{ms}ms）"))
+        }
+        Ok(detail) => Finding::new(
+            check,
+            Level::Ok,
+            format!("{label} 连接正常：{detail}（{ms}ms）"),
+        ),
+        Err(e) => Finding::new(
+            check,
+            Level::Error,
+            format!("{label} 连接失败（{ms}ms）：{e:#}"),
+        ),
+    };
+    finding.latency_ms = Some(ms);
+    finding
+}
+
+fn read_snapshot(state_file: &Path) -> Result<serde_json::Value> {
+    if !state_file.exists() {
+        anyhow::bail!("守护进程状态文件未找到: {}", state_file.display());
+    }
+    let raw = std::fs::read_to_string(state_file)
+        .with_context(|| format!("读取失败 {}", state_file.display()))?;
+    serde_json::from_str(&raw).with_context(|| format!("解析失败 {}", state_file.display()))
+}
+
+/// Whether a component reports `ok`, and how many seconds ago it last did.
+fn component_state(component: &serde_json::Value) -> (bool, i64) {
+    let status_ok = component
+        .get("status")
+        .and_then(serde_json::Value::as_str)
+        .is_some_and(|s| s == "ok");
+    let age = component
+        .get("last_ok")
+        .and_then(serde_json::Value::as_str)
+        .and_then(parse_rfc3339)
+        .map_or(i64::MAX, |dt| {
+            Utc::now().signed_duration_since(dt).num_seconds()
+        });
+    (status_ok, age)
+}
+
+/// Components the daemon gave up restarting, as opposed to ones failing
+/// transiently between restarts.
+fn check_open_circuits(snapshot: &serde_json::Value, findings: &mut Vec<Finding>) {
+    let Some(components) = snapshot
+        .get("components")
+        .and_then(serde_json::Value::as_object)
+    else {
+        return;
+    };
+    for (name, component) in components {
+        let circuit_open = component
+            .get("circuit_open")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        if !circuit_open {
+            continue;
+        }
+        let error = component
+            .get("last_error")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("未知错误");
+        findings.push(
+            Finding::new(
+                name,
+                Level::Error,
+                format!("🔌 {name} 已熔断，不再自动重启：{error}"),
+            )
+            .hint("修复后运行 jarvis daemon --reset（或重启守护进程）"),
+        );
+    }
+}
+
+fn check_tunnel(
+    config: &Config,
+    snapshot: Option<&serde_json::Value>,
+    findings: &mut Vec<Finding>,
+) {
+    let check = crate::tunnel::TUNNEL_COMPONENT;
+    match config.tunnel.provider.as_str() {
+        "none" | "" => return,
+        "tailscale" => findings.push(match crate::tunnel::check_tailscale_ready() {
+            Ok(hostname) => {
+                Finding::new(check, Level::Ok, format!("tailscale 已登录（{hostname}）"))
+            }
+            Err(e) => Finding::new(check, Level::Error, format!("tailscale 不可用：{e}")),
+        }),
+        _ => {}
+    }
+
+    let Some(tunnel) = snapshot
+        .and_then(|s| s.get("components"))
+        .and_then(|c| c.get(check))
+    else {
+        return;
+    };
+    let status_ok = tunnel
+        .get("status")
+        .and_then(serde_json::Value::as_str)
+        .is_some_and(|s| s == "ok");
+    let detail = tunnel
+        .get("detail")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("");
+    if status_ok {
+        findings.push(Finding::new(
+            check,
+            Level::Ok,
+            format!("隧道正常（{detail}）"),
+        ));
     } else {
-        println!("  通道汇总: 共 {channel_count} 个，{stale_channels} 个已过期");
+        let error = tunnel
+            .get("last_error")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("未知错误");
+        findings.push(Finding::new(
+            check,
+            Level::Error,
+            format!("隧道异常：{error}"),
+        ));
     }
+}
+
+fn check_memory_hygiene(
+    config: &Config,
+    snapshot: &serde_json::Value,
+    findings: &mut Vec<Finding>,
+) {
+    let check = crate::daemon::HYGIENE_COMPONENT;
+    let Some(hygiene) = snapshot.get("components").and_then(|c| c.get(check)) else {
+        findings.push(if config.memory.hygiene_enabled {
+            Finding::new(
+                check,
+                Level::Error,
+                "记忆清理组件缺失（hygiene_enabled = true）",
+            )
+        } else {
+            Finding::new(
+                check,
+                Level::Info,
+                "记忆清理已关闭（memory.hygiene_enabled = false）",
+            )
+        });
+        return;
+    };
+
+    let (status_ok, age) = component_state(hygiene);
+    let detail = hygiene
+        .get("detail")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("尚未完成清理");
 
-    Ok(())
+    findings.push(if status_ok && age <= HYGIENE_STALE_SECONDS {
+        Finding::new(check, Level::Ok, format!("记忆清理正常（{detail}）"))
+    } else {
+        Finding::new(
+            check,
+            Level::Error,
+            format!("记忆清理过期/异常（status_ok={status_ok}, age={age}s, {detail}）"),
+        )
+    });
 }
 
 fn parse_rfc3339(raw: &str) -> Option<DateTime<Utc>> {
@@ -114,3 +572,119 @@ fn parse_rfc3339(raw: &str) -> Option<DateTime<Utc>> {
         .ok()
         .map(|dt| dt.with_timezone(&Utc))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_state(dir: &Path, state: &serde_json::Value) -> std::path::PathBuf {
+        let path = dir.join("daemon_state.json");
+        std::fs::write(&path, state.to_string()).unwrap();
+        path
+    }
+
+    fn errors(findings: &[Finding]) -> Vec<&str> {
+        findings
+            .iter()
+            .filter(|finding| finding.level == Level::Error)
+            .map(|finding| finding.check.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn deep_probe_reports_unreachable_provider_as_error() {
+        let config = Config {
+            default_provider: Some("ollama".into()),
+            ..Config::default()
+        };
+        // Nothing listens on Ollama's port here; either way the ping must
+        // come back within the probe timeout with a latency.
+        let findings = tokio::time::timeout(
+            probe::PROBE_TIMEOUT + Duration::from_secs(5),
+            probe_connectivity(&config),
+        )
+        .await
+        .expect("deep probes are bounded");
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].check, "probe:provider");
+        assert!(findings[0].latency_ms.is_some());
+    }
+
+    #[test]
+    fn config_problems_are_findings() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut config = Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..Config::default()
+        };
+        let findings = check_config(&config);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].level, Level::Ok);
+
+        config.memory.backend = "redis".into();
+        config.workspace_dir = tmp.path().join("later");
+        let findings = check_config(&config);
+        assert_eq!(errors(&findings), ["config"]);
+        assert!(findings[0].message.starts_with("memory.backend："));
+        assert_eq!(findings[1].level, Level::Info);
+
+        config.memory.backend = "sqlite".into();
+        config.unknown_keys = vec![crate::config::unknown::UnknownKey {
+            path: "heartbeat.max_paralel_tasks".into(),
+            line: Some(12),
+            suggestion: Some("max_parallel_tasks".into()),
+        }];
+        let findings = check_config(&config);
+        assert_eq!(
+            findings[0].message,
+            "heartbeat.max_paralel_tasks：未知配置项（第 12 行），你是不是要写 max_parallel_tasks？"
+        );
+    }
+
+    #[test]
+    fn missing_state_file_is_an_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let findings = diagnose(&Config::default(), &tmp.path().join("daemon_state.json"));
+        assert_eq!(errors(&findings), ["daemon"]);
+    }
+
+    #[test]
+    fn fresh_snapshot_is_healthy() {
+        let tmp = tempfile::tempdir().unwrap();
+        let now = Utc::now().to_rfc3339();
+        let ok = serde_json::json!({"status": "ok", "last_ok": now});
+        let state = write_state(
+            tmp.path(),
+            &serde_json::json!({
+                "updated_at": now,
+                "components": {
+                    "scheduler": ok,
+                    "channel:telegram": ok,
+                    crate::daemon::HYGIENE_COMPONENT: ok,
+                },
+            }),
+        );
+        let findings = diagnose(&Config::default(), &state);
+        assert!(errors(&findings).is_empty(), "{findings:?}");
+    }
+
+    #[test]
+    fn stale_daemon_and_failing_channel_are_errors() {
+        let tmp = tempfile::tempdir().unwrap();
+        let now = Utc::now();
+        let heartbeat = (now - chrono::Duration::minutes(10)).to_rfc3339();
+        let state = write_state(
+            tmp.path(),
+            &serde_json::json!({
+                "updated_at": heartbeat,
+                "components": {
+                    "scheduler": {"status": "ok", "last_ok": now.to_rfc3339()},
+                    "channel:slack": {"status": "error", "last_ok": null},
+                    crate::daemon::HYGIENE_COMPONENT: {"status": "ok", "last_ok": now.to_rfc3339()},
+                },
+            }),
+        );
+        let findings = diagnose(&Config::default(), &state);
+        assert_eq!(errors(&findings), ["daemon", "channel:slack"]);
+    }
+}
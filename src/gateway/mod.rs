@@ -3,35 +3,62 @@
 //! This module replaces the raw TCP implementation with axum for:
 //! - Proper HTTP/1.1 parsing and compliance
 //! - Content-Length validation (handled by hyper)
-//! - Request body size limits (64KB max)
+//! - Request body size limits (64KB default, `[gateway] max_body_bytes`)
 //! - Request timeouts (30s) to prevent slow-loris attacks
 //! - Header sanitization (handled by axum/hyper)
-
+//! - Bearer token auth on every route except `/health`, `/pair` and
+//!   `/whatsapp` when `[gateway] require_pairing` is on
+//! - WebSocket chat at `/ws/chat` (see [`ws`])
+//! - One-shot agent runs at `/v1/agent` and `/v1/runs/:id` (see [`runs`])
+//! - Prometheus metrics at `/metrics` with the `prometheus` observability backend
+
+mod runs;
+mod ws;
+
+use crate::agent::loop_::build_context;
+use crate::channels::dedup::SeenMessages;
+use crate::channels::whatsapp::Inbound;
 use crate::channels::{Channel, WhatsAppChannel};
 use crate::config::Config;
 use crate::memory::{self, Memory, MemoryCategory};
+use crate::observability::Observer;
 use crate::providers::{self, Provider};
-use crate::security::pairing::{constant_time_eq, is_public_bind, PairingGuard};
+use crate::security::devices::DeviceStore;
+use crate::security::pairing::{
+    constant_time_eq, gateway_token_path, is_public_bind, load_or_create_gateway_token,
+    rotate_gateway_token, PairingGuard,
+};
 use crate::util::truncate_with_ellipsis;
 use anyhow::Result;
 use axum::{
     body::Bytes,
-    extract::{Query, State},
+    extract::{Query, Request, State},
     http::{header, HeaderMap, StatusCode},
-    response::{IntoResponse, Json},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::Duration;
 use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::timeout::TimeoutLayer;
 
-/// Maximum request body size (64KB) — prevents memory exhaustion
+/// Default maximum request body size (64KB) — prevents memory exhaustion
 pub const MAX_BODY_SIZE: usize = 65_536;
 /// Request timeout (30s) — prevents slow-loris attacks
 pub const REQUEST_TIMEOUT_SECS: u64 = 30;
+/// Routes served without a bearer token: the health probe, the pairing
+/// exchange, and the `WhatsApp` webhook (which Meta signs instead)
+const PUBLIC_PATHS: &[&str] = &["/health", "/pair", "/whatsapp"];
+/// Health component for the `WhatsApp` webhook channel
+const WHATSAPP_COMPONENT: &str = "channel:whatsapp";
+/// How often the Graph API is probed (well inside doctor's staleness window)
+const WHATSAPP_HEALTH_INTERVAL: Duration = Duration::from_mins(1);
+/// Reply to images, voice notes, locations, …
+const WHATSAPP_UNSUPPORTED_REPLY: &str =
+    "Sorry, I can only read text messages for now. Please send your request as text.";
 
 /// Shared state for all axum handlers
 #[derive(Clone)]
@@ -43,14 +70,37 @@ pub struct AppState {
     pub auto_save: bool,
     pub webhook_secret: Option<Arc<str>>,
     pub pairing: Arc<PairingGuard>,
+    /// Skip bearer auth (`[gateway] trust_loopback` on a loopback bind with no tunnel)
+    pub trust_loopback: bool,
     pub whatsapp: Option<Arc<WhatsAppChannel>>,
     /// `WhatsApp` app secret for webhook signature verification (`X-Hub-Signature-256`)
     pub whatsapp_app_secret: Option<Arc<str>>,
+    /// Inbound message character cap (`[channels_config] max_inbound_chars`, 0 = unlimited)
+    pub max_inbound_chars: usize,
+    /// `WhatsApp` message ids already handled, so Meta's webhook retries
+    /// aren't answered twice
+    pub seen_messages: Arc<Mutex<SeenMessages>>,
+    /// Tools, prompt and per-session history for `/ws/chat`
+    pub chat: Arc<ws::ChatContext>,
+    /// Concurrency cap and records for `/v1/agent` runs
+    pub runs: Arc<runs::RunRegistry>,
+    /// Serve `/metrics` (`[observability] backend = "prometheus"`)
+    pub metrics: bool,
 }
 
 /// Run the HTTP gateway using axum with proper HTTP/1.1 compliance.
-#[allow(clippy::too_many_lines)]
 pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
+    serve(host, port, config, true).await
+}
+
+/// Run the gateway under the daemon, which supervises the tunnel as its own
+/// component (see [`crate::tunnel::run_tunnel`]).
+pub async fn run_gateway_without_tunnel(host: &str, port: u16, config: Config) -> Result<()> {
+    serve(host, port, config, false).await
+}
+
+#[allow(clippy::too_many_lines)]
+async fn serve(host: &str, port: u16, config: Config, start_tunnel: bool) -> Result<()> {
     // ── Security: refuse public bind without tunnel or explicit opt-in ──
     if is_public_bind(host) && config.tunnel.provider == "none" && !config.gateway.allow_public_bind
     {
@@ -66,10 +116,13 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     let actual_port = listener.local_addr()?.port();
     let display_addr = format!("{host}:{actual_port}");
 
+    let observer: Arc<dyn Observer> =
+        Arc::from(crate::observability::create_observer(&config.observability));
     let provider: Arc<dyn Provider> = Arc::from(providers::create_resilient_provider(
         config.default_provider.as_deref().unwrap_or("openrouter"),
         config.api_key.as_deref(),
         &config.reliability,
+        Arc::clone(&observer),
     )?);
     let model = config
         .default_model
@@ -81,6 +134,16 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         &config.workspace_dir,
         config.api_key.as_deref(),
     )?);
+    let runtime: Arc<dyn crate::runtime::RuntimeAdapter> = Arc::from(
+        crate::runtime::create_runtime(&config.runtime, &config.workspace_dir)?,
+    );
+    let chat = Arc::new(ws::ChatContext::from_config(
+        &config,
+        &model,
+        Arc::clone(&mem),
+        observer,
+        runtime,
+    ));
 
     // Extract webhook secret for authentication
     let webhook_secret: Option<Arc<str>> = config
@@ -93,12 +156,15 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     // WhatsApp channel (if configured)
     let whatsapp_channel: Option<Arc<WhatsAppChannel>> =
         config.channels_config.whatsapp.as_ref().map(|wa| {
-            Arc::new(WhatsAppChannel::new(
-                wa.access_token.clone(),
-                wa.phone_number_id.clone(),
-                wa.verify_token.clone(),
-                wa.allowed_numbers.clone(),
-            ))
+            Arc::new(
+                WhatsAppChannel::new(
+                    wa.access_token.clone(),
+                    wa.phone_number_id.clone(),
+                    wa.verify_token.clone(),
+                    wa.allowed_numbers.clone(),
+                )
+                .with_rejections(crate::channels::allowlist::spawn_reporter(&config)),
+            )
         });
 
     // WhatsApp app secret for webhook signature verification
@@ -121,13 +187,27 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         .map(Arc::from);
 
     // ── Pairing guard ──────────────────────────────────────
-    let pairing = Arc::new(PairingGuard::new(
-        config.gateway.require_pairing,
-        &config.gateway.paired_tokens,
-    ));
+    let pairing = Arc::new(
+        PairingGuard::new(
+            config.gateway.require_pairing,
+            &config.gateway.paired_tokens,
+        )
+        .with_devices(DeviceStore::new(&config.workspace_dir)),
+    );
+    if pairing.require_pairing() {
+        pairing.accept_token(&load_or_create_gateway_token(&config.workspace_dir)?);
+    }
+    // A tunnel forwards public traffic to the loopback address, so only a
+    // bare loopback bind can be trusted
+    let trust_loopback =
+        config.gateway.trust_loopback && !is_public_bind(host) && config.tunnel.provider == "none";
 
     // ── Tunnel ────────────────────────────────────────────────
-    let tunnel = crate::tunnel::create_tunnel(&config.tunnel)?;
+    let tunnel = if start_tunnel {
+        crate::tunnel::create_tunnel(&config.tunnel)?
+    } else {
+        None
+    };
     let mut tunnel_url: Option<String> = None;
 
     if let Some(ref tun) = tunnel {
@@ -147,32 +227,48 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     println!("🤖 Jarvis Gateway 正在监听 http://{display_addr}");
     if let Some(ref url) = tunnel_url {
         println!("  🌐 公网地址：{url}");
+    } else if !start_tunnel && config.tunnel.provider != "none" {
+        println!("  🌐 隧道：由守护进程管理（jarvis status 查看公网地址）");
     }
-    println!("  POST /pair      — 配对新客户端（X-Pairing-Code 请求头）");
+    println!("  POST /pair      — 配对新客户端（X-Pairing-Code、X-Device-Name 请求头）");
     println!("  POST /webhook   — {{\"message\": \"你的提示\"}}");
+    println!("  GET  /ws/chat   — WebSocket 聊天（{{\"type\": \"message\", \"content\": ...}}）");
+    println!("  POST /v1/agent  — 运行 agent（{{\"message\": ..., \"async\": false}}）");
+    println!("  GET  /v1/runs/:id — 查询异步运行结果");
     if whatsapp_channel.is_some() {
         println!("  GET  /whatsapp  — Meta webhook 验证");
         println!("  POST /whatsapp  — WhatsApp 消息 webhook");
     }
     println!("  GET  /health    — 健康检查");
+    if config.observability.backend == "prometheus" {
+        println!("  GET  /metrics   — Prometheus 指标");
+    }
     if let Some(code) = pairing.pairing_code() {
         println!();
         println!("  🔐 需要配对 — 请使用此一次性配对码：");
         println!("     ┌──────────────┐");
         println!("     │  {code}  │");
         println!("     └──────────────┘");
-        println!("     发送：POST /pair，请求头 X-Pairing-Code: {code}");
+        println!("     发送：POST /pair，请求头 X-Pairing-Code: {code}（10 分钟内有效）");
+    } else if trust_loopback {
+        println!("  🔓 配对：仅监听本机，已信任 loopback（trust_loopback = true）");
     } else if pairing.require_pairing() {
         println!("  🔒 配对：已启用（需要 bearer token）");
     } else {
         println!("  ⚠️  配对：已禁用（接受所有请求）");
     }
+    if pairing.require_pairing() && !trust_loopback {
+        println!("  🔑 新设备配对码：运行 `jarvis gateway pair` 生成");
+    }
     if webhook_secret.is_some() {
         println!("  🔒 Webhook secret：已启用");
     }
     println!("  按 Ctrl+C 停止。\n");
 
     crate::health::mark_component_ok("gateway");
+    if let Some(ref wa) = whatsapp_channel {
+        tokio::spawn(monitor_whatsapp(Arc::clone(wa)));
+    }
 
     // Build shared state
     let state = AppState {
@@ -183,30 +279,199 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         auto_save: config.memory.auto_save,
         webhook_secret,
         pairing,
+        trust_loopback,
         whatsapp: whatsapp_channel,
         whatsapp_app_secret,
+        max_inbound_chars: config.channels_config.max_inbound_chars,
+        seen_messages: Arc::new(Mutex::new(SeenMessages::load(
+            &config.workspace_dir,
+            Duration::from_secs(config.channels_config.dedup_ttl_secs),
+            config.channels_config.dedup_max_entries,
+        ))),
+        chat,
+        runs: Arc::new(runs::RunRegistry::new(config.gateway.max_concurrent_runs)),
+        metrics: config.observability.backend == "prometheus",
     };
 
-    // Build router with middleware
-    let app = Router::new()
+    // Run the server
+    axum::serve(listener, router(state, config.gateway.max_body_bytes)).await?;
+
+    Ok(())
+}
+
+/// Build the router with its middleware.
+fn router(state: AppState, max_body_bytes: usize) -> Router {
+    Router::new()
         .route("/health", get(handle_health))
         .route("/pair", post(handle_pair))
         .route("/webhook", post(handle_webhook))
+        .route("/ws/chat", get(ws::handle_ws_chat))
+        .route("/v1/agent", post(runs::handle_agent_run))
+        .route("/v1/runs/:id", get(runs::handle_get_run))
+        .route("/metrics", get(handle_metrics))
         .route("/whatsapp", get(handle_whatsapp_verify))
         .route("/whatsapp", post(handle_whatsapp_message))
+        .layer(middleware::from_fn_with_state(state.clone(), require_auth))
         .with_state(state)
-        .layer(RequestBodyLimitLayer::new(MAX_BODY_SIZE))
+        .layer(RequestBodyLimitLayer::new(max_body_bytes))
         .layer(TimeoutLayer::with_status_code(
             StatusCode::REQUEST_TIMEOUT,
             Duration::from_secs(REQUEST_TIMEOUT_SECS),
-        ));
+        ))
+}
 
-    // Run the server
-    axum::serve(listener, app).await?;
+/// `jarvis gateway pair` — issue a one-time pairing code and the URL a new
+/// device redeems it at.
+pub fn pair(config: &Config, url: Option<&str>, host: &str, port: u16) -> Result<()> {
+    let store = DeviceStore::new(&config.workspace_dir);
+    let issued = store.issue_code()?;
+    let base = url.map_or_else(
+        || pair_base_url(config, host, port),
+        |url| url.trim_end_matches('/').to_string(),
+    );
+
+    println!("🔐 配对码：{}", issued.code);
+    println!("   配对链接：{base}/pair?code={}", issued.code);
+    println!(
+        "   有效期至 {}（10 分钟，仅可使用一次）",
+        issued
+            .expires_at
+            .with_timezone(&chrono::Local)
+            .format("%H:%M:%S")
+    );
+    println!(
+        "   新设备发送 POST /pair（请求头 X-Pairing-Code，可选 X-Device-Name）换取 bearer token"
+    );
+    if !config.gateway.require_pairing {
+        eprintln!("⚠️  [gateway] require_pairing = false，gateway 当前不校验 token。");
+    }
+    Ok(())
+}
+
+/// Where clients reach the gateway: the daemon's tunnel when one is up,
+/// otherwise the local listen address.
+fn pair_base_url(config: &Config, host: &str, port: u16) -> String {
+    let tunnel_url = std::fs::read_to_string(crate::daemon::state_file_path(config))
+        .ok()
+        .and_then(|raw| serde_json::from_str::<serde_json::Value>(&raw).ok())
+        .and_then(|state| {
+            state
+                .pointer(&format!(
+                    "/components/{}/detail",
+                    crate::tunnel::TUNNEL_COMPONENT
+                ))
+                .and_then(serde_json::Value::as_str)
+                .filter(|detail| detail.starts_with("http"))
+                .map(|url| url.trim_end_matches('/').to_string())
+        });
+    tunnel_url.unwrap_or_else(|| {
+        let host = match host {
+            "0.0.0.0" | "::" | "[::]" => hostname::get()
+                .ok()
+                .and_then(|name| name.into_string().ok())
+                .unwrap_or_else(|| host.to_string()),
+            _ => host.to_string(),
+        };
+        format!("http://{host}:{port}")
+    })
+}
+
+/// `jarvis gateway pair --token` — print the gateway's own bearer token, or
+/// replace it with `--rotate`.
+pub fn show_token(config: &Config, rotate: bool) -> Result<()> {
+    let token = if rotate {
+        rotate_gateway_token(&config.workspace_dir)?
+    } else {
+        load_or_create_gateway_token(&config.workspace_dir)?
+    };
+    println!("{token}");
+    eprintln!(
+        "已保存在 {}。请求时附带 Authorization: Bearer <token>（webhook 也可用 ?token=<token>）。",
+        gateway_token_path(&config.workspace_dir).display()
+    );
+    if rotate {
+        eprintln!("旧 token 已失效；正在运行的 gateway/守护进程需重启后生效。");
+    }
+    if !config.gateway.require_pairing {
+        eprintln!("⚠️  [gateway] require_pairing = false，gateway 当前不校验 token。");
+    }
+    Ok(())
+}
+
+/// `jarvis gateway devices` — list paired devices.
+pub fn list_devices(config: &Config) -> Result<()> {
+    let devices = DeviceStore::new(&config.workspace_dir).devices()?;
+    if devices.is_empty() {
+        println!("暂无已配对设备（jarvis gateway pair 生成配对码）");
+        return Ok(());
+    }
+    let local = |at: chrono::DateTime<chrono::Utc>| {
+        at.with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+    println!("{:<10} {:<20} {:<18} 最近使用", "ID", "名称", "配对时间");
+    for device in devices {
+        println!(
+            "{:<10} {:<20} {:<18} {}",
+            device.id,
+            device.name,
+            local(device.paired_at),
+            device.last_seen.map_or_else(|| "从未".to_string(), local)
+        );
+    }
+    Ok(())
+}
 
+/// `jarvis gateway revoke <device>` — invalidate a device's token.
+pub fn revoke_device(config: &Config, device: &str) -> Result<()> {
+    let revoked = DeviceStore::new(&config.workspace_dir).revoke(device)?;
+    println!(
+        "✅ 已撤销设备 {}（{}），其 token 立即失效",
+        revoked.name, revoked.id
+    );
     Ok(())
 }
 
+/// The token from `Authorization: Bearer <token>`, or from `?token=` for
+/// webhook senders that can't set headers.
+fn request_token(req: &Request) -> Option<String> {
+    let bearer = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|auth| auth.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+    bearer.or_else(|| {
+        Query::<std::collections::HashMap<String, String>>::try_from_uri(req.uri())
+            .ok()?
+            .0
+            .remove("token")
+    })
+}
+
+/// Reject requests without a paired bearer token with 401.
+async fn require_auth(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    if !state.pairing.require_pairing()
+        || state.trust_loopback
+        || PUBLIC_PATHS.contains(&req.uri().path())
+    {
+        return next.run(req).await;
+    }
+    if request_token(&req).is_some_and(|token| state.pairing.is_authenticated(&token)) {
+        return next.run(req).await;
+    }
+
+    tracing::warn!(
+        "Gateway：已拒绝 {} — 未配对或 bearer token 无效",
+        req.uri().path()
+    );
+    let err = serde_json::json!({
+        "error": "Unauthorized — send Authorization: Bearer <token> (see `jarvis gateway pair`, or pair via POST /pair)"
+    });
+    (StatusCode::UNAUTHORIZED, Json(err)).into_response()
+}
+
 // ══════════════════════════════════════════════════════════════════════════════
 // AXUM HANDLERS
 // ══════════════════════════════════════════════════════════════════════════════
@@ -221,16 +486,46 @@ async fn handle_health(State(state): State<AppState>) -> impl IntoResponse {
     Json(body)
 }
 
+/// GET /metrics — Prometheus scrape, 404 unless the prometheus backend is on
+async fn handle_metrics(State(state): State<AppState>) -> Response {
+    if !state.metrics {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    (
+        [(
+            header::CONTENT_TYPE,
+            crate::observability::prometheus::CONTENT_TYPE,
+        )],
+        crate::observability::prometheus::global().render(),
+    )
+        .into_response()
+}
+
+/// Query parameters accepted by `POST /pair` (the `jarvis gateway pair` link)
+#[derive(serde::Deserialize)]
+pub struct PairQuery {
+    pub code: Option<String>,
+    pub device: Option<String>,
+}
+
 /// POST /pair — exchange one-time code for bearer token
-async fn handle_pair(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
-    let code = headers
-        .get("X-Pairing-Code")
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("");
+async fn handle_pair(
+    State(state): State<AppState>,
+    Query(query): Query<PairQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(ToOwned::to_owned)
+    };
+    let code = header("X-Pairing-Code").or(query.code).unwrap_or_default();
+    let device = header("X-Device-Name").or(query.device).unwrap_or_default();
 
-    match state.pairing.try_pair(code) {
+    match state.pairing.try_pair(&code, &device) {
         Ok(Some(token)) => {
-            tracing::info!("🔐 新客户端配对成功");
+            tracing::info!("🔐 新客户端配对成功（{device}）");
             let body = serde_json::json!({
                 "paired": true,
                 "token": token,
@@ -254,6 +549,28 @@ async fn handle_pair(State(state): State<AppState>, headers: HeaderMap) -> impl
     }
 }
 
+/// Reject with 401 unless `X-Webhook-Secret` matches the configured
+/// `[channels_config.webhook] secret` (no-op without one).
+fn check_webhook_secret(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let Some(ref secret) = state.webhook_secret else {
+        return Ok(());
+    };
+    let header_val = headers
+        .get("X-Webhook-Secret")
+        .and_then(|v| v.to_str().ok());
+    match header_val {
+        Some(val) if constant_time_eq(val, secret.as_ref()) => Ok(()),
+        _ => {
+            tracing::warn!("Webhook：已拒绝请求 — X-Webhook-Secret 无效或缺失");
+            let err = serde_json::json!({"error": "Unauthorized — invalid or missing X-Webhook-Secret header"});
+            Err((StatusCode::UNAUTHORIZED, Json(err)))
+        }
+    }
+}
+
 /// Webhook request body
 #[derive(serde::Deserialize)]
 pub struct WebhookBody {
@@ -266,40 +583,23 @@ async fn handle_webhook(
     headers: HeaderMap,
     body: Result<Json<WebhookBody>, axum::extract::rejection::JsonRejection>,
 ) -> impl IntoResponse {
-    // ── Bearer token auth (pairing) ──
-    if state.pairing.require_pairing() {
-        let auth = headers
-            .get(header::AUTHORIZATION)
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
-        let token = auth.strip_prefix("Bearer ").unwrap_or("");
-        if !state.pairing.is_authenticated(token) {
-            tracing::warn!("Webhook：已拒绝 — 未配对或 bearer token 无效");
-            let err = serde_json::json!({
-                "error": "Unauthorized — pair first via POST /pair, then send Authorization: Bearer <token>"
-            });
-            return (StatusCode::UNAUTHORIZED, Json(err));
-        }
-    }
+    // Bearer token auth (pairing) is enforced by `require_auth`
 
     // ── Webhook secret auth (optional, additional layer) ──
-    if let Some(ref secret) = state.webhook_secret {
-        let header_val = headers
-            .get("X-Webhook-Secret")
-            .and_then(|v| v.to_str().ok());
-        match header_val {
-            Some(val) if constant_time_eq(val, secret.as_ref()) => {}
-            _ => {
-                tracing::warn!("Webhook：已拒绝请求 — X-Webhook-Secret 无效或缺失");
-                let err = serde_json::json!({"error": "Unauthorized — invalid or missing X-Webhook-Secret header"});
-                return (StatusCode::UNAUTHORIZED, Json(err));
-            }
-        }
+    if let Err(rejection) = check_webhook_secret(&state, &headers) {
+        return rejection;
     }
 
     // ── Parse body ──
     let Json(webhook_body) = match body {
         Ok(b) => b,
+        Err(e) if e.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+            tracing::warn!("Webhook：已拒绝 — 请求体超出大小上限");
+            let err = serde_json::json!({
+                "error": "Request body too large — please shorten your message and try again"
+            });
+            return (StatusCode::PAYLOAD_TOO_LARGE, Json(err));
+        }
         Err(e) => {
             let err = serde_json::json!({
                 "error": format!("Invalid JSON: {e}. Expected: {{\"message\": \"...\"}}")
@@ -308,7 +608,15 @@ async fn handle_webhook(
         }
     };
 
-    let message = &webhook_body.message;
+    let message =
+        match crate::channels::check_inbound_size(&webhook_body.message, state.max_inbound_chars) {
+            Ok(message) => message,
+            Err(notice) => {
+                tracing::warn!("Webhook：已拒绝 — 消息超出长度上限");
+                let err = serde_json::json!({ "error": notice });
+                return (StatusCode::PAYLOAD_TOO_LARGE, Json(err));
+            }
+        };
 
     if state.auto_save {
         let _ = state
@@ -317,12 +625,15 @@ async fn handle_webhook(
             .await;
     }
 
+    let transcript = state.chat.resume_transcript("webhook", "webhook");
+    transcript.user(message);
     match state
         .provider
         .chat(message, &state.model, state.temperature)
         .await
     {
         Ok(response) => {
+            transcript.assistant(&response, &state.model);
             let body = serde_json::json!({"response": response, "model": state.model});
             (StatusCode::OK, Json(body))
         }
@@ -445,55 +756,136 @@ async fn handle_whatsapp_message(
         );
     };
 
-    // Parse messages from the webhook payload
-    let messages = wa.parse_webhook_payload(&payload);
+    // Meta reached us with a well-formed (and, if configured, signed) event
+    crate::health::mark_component_ok(WHATSAPP_COMPONENT);
 
-    if messages.is_empty() {
-        // Acknowledge the webhook even if no messages (could be status updates)
-        return (StatusCode::OK, Json(serde_json::json!({"status": "ok"})));
+    let mut inbound = wa.parse_inbound(&payload);
+    {
+        let mut seen = state
+            .seen_messages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        inbound.retain(|message| seen.first_delivery("whatsapp", message.id()));
+        if let Err(e) = seen.save() {
+            tracing::warn!("保存消息去重记录失败: {e:#}");
+        }
+    }
+    if !inbound.is_empty() {
+        // Answer in the background: Meta redelivers webhooks that aren't
+        // acknowledged quickly, and an agent turn can outlast the request timeout
+        let wa = Arc::clone(wa);
+        tokio::spawn(async move {
+            for message in inbound {
+                handle_whatsapp_inbound(&state, &wa, message).await;
+            }
+        });
     }
 
-    // Process each message
-    for msg in &messages {
-        tracing::info!(
-            "收到来自 {} 的 WhatsApp 消息：{}",
-            msg.sender,
-            truncate_with_ellipsis(&msg.content, 50)
-        );
+    // Acknowledge the webhook (status updates carry no messages)
+    (StatusCode::OK, Json(serde_json::json!({"status": "ok"})))
+}
 
-        // Auto-save to memory
-        if state.auto_save {
-            let _ = state
-                .mem
-                .store(
-                    &format!("whatsapp_{}", msg.sender),
-                    &msg.content,
-                    MemoryCategory::Conversation,
-                )
-                .await;
+/// Run the agent on a text message, or tell the sender that other message
+/// types aren't supported, and send the reply.
+async fn handle_whatsapp_inbound(state: &AppState, wa: &WhatsAppChannel, inbound: Inbound) {
+    let msg = match inbound {
+        Inbound::Text(msg) => msg,
+        Inbound::Unsupported { sender, kind, .. } => {
+            tracing::info!("收到来自 {sender} 的 WhatsApp {kind} 消息，暂不支持");
+            send_whatsapp_reply(wa, WHATSAPP_UNSUPPORTED_REPLY, &sender).await;
+            return;
         }
+    };
+    tracing::info!(
+        "收到来自 {} 的 WhatsApp 消息：{}",
+        msg.sender,
+        truncate_with_ellipsis(&msg.content, 50)
+    );
 
-        // Call the LLM
-        match state
-            .provider
-            .chat(&msg.content, &state.model, state.temperature)
-            .await
-        {
-            Ok(response) => {
-                // Send reply via WhatsApp
-                if let Err(e) = wa.send(&response, &msg.sender).await {
-                    tracing::error!("发送 WhatsApp 回复失败：{e}");
-                }
-            }
-            Err(e) => {
-                tracing::error!("WhatsApp 消息的 LLM 调用出错：{e:#}");
-                let _ = wa.send("抱歉，暂时无法处理您的消息。", &msg.sender).await;
-            }
+    if let Err(notice) = crate::channels::check_inbound_size(&msg.content, state.max_inbound_chars)
+    {
+        tracing::warn!("WhatsApp 消息超出长度上限，已拒绝");
+        send_whatsapp_reply(wa, &notice, &msg.sender).await;
+        return;
+    }
+
+    // Auto-save to memory
+    if state.auto_save {
+        let _ = state
+            .mem
+            .store(
+                &format!("whatsapp_{}", msg.sender),
+                &msg.content,
+                MemoryCategory::Conversation,
+            )
+            .await;
+    }
+
+    let transcript = state
+        .chat
+        .resume_transcript("whatsapp", &format!("whatsapp-{}", msg.sender));
+    transcript.user(&msg.content);
+
+    let context = build_context(state.mem.as_ref(), &msg.content).await;
+    let enriched = if context.is_empty() {
+        msg.content.clone()
+    } else {
+        format!("{context}{}", msg.content)
+    };
+    let reply = state
+        .chat
+        .run_once(
+            state.provider.as_ref(),
+            &state.model,
+            state.temperature,
+            enriched,
+            state.chat.observer().as_ref(),
+            &transcript,
+        )
+        .await;
+    match reply {
+        Ok((response, _)) => send_whatsapp_reply(wa, &response, &msg.sender).await,
+        Err(e) => {
+            tracing::error!("WhatsApp 消息的 agent 运行出错：{e:#}");
+            send_whatsapp_reply(
+                wa,
+                "Sorry, I couldn't process your message right now.",
+                &msg.sender,
+            )
+            .await;
         }
     }
+}
 
-    // Acknowledge the webhook
-    (StatusCode::OK, Json(serde_json::json!({"status": "ok"})))
+/// Send via the Graph API; a failure leaves the channel degraded until the
+/// next successful send or health probe.
+async fn send_whatsapp_reply(wa: &WhatsAppChannel, text: &str, recipient: &str) {
+    match wa.send(text, recipient).await {
+        Ok(()) => crate::health::mark_component_ok(WHATSAPP_COMPONENT),
+        Err(e) => {
+            tracing::error!("发送 WhatsApp 回复失败：{e}");
+            crate::health::mark_component_degraded(
+                WHATSAPP_COMPONENT,
+                format!("回复发送失败: {e}"),
+            );
+        }
+    }
+}
+
+/// Probe the Graph API periodically so `channel:whatsapp` stays fresh in
+/// `jarvis doctor` even when no messages arrive.
+async fn monitor_whatsapp(wa: Arc<WhatsAppChannel>) {
+    loop {
+        match tokio::time::timeout(Duration::from_secs(10), wa.health_check()).await {
+            Ok(true) => crate::health::mark_component_ok(WHATSAPP_COMPONENT),
+            Ok(false) => crate::health::mark_component_error(
+                WHATSAPP_COMPONENT,
+                "Graph API 检查失败（检查 access_token 和 phone_number_id）",
+            ),
+            Err(_) => crate::health::mark_component_error(WHATSAPP_COMPONENT, "Graph API 检查超时"),
+        }
+        tokio::time::sleep(WHATSAPP_HEALTH_INTERVAL).await;
+    }
 }
 
 #[cfg(test)]
@@ -505,6 +897,12 @@ mod tests {
         assert_eq!(MAX_BODY_SIZE, 65_536);
     }
 
+    #[test]
+    fn config_default_body_limit_matches_constant() {
+        let g = crate::config::GatewayConfig::default();
+        assert_eq!(g.max_body_bytes, MAX_BODY_SIZE);
+    }
+
     #[test]
     fn security_timeout_is_30_seconds() {
         assert_eq!(REQUEST_TIMEOUT_SECS, 30);
@@ -728,4 +1126,903 @@ mod tests {
             &signature_header
         ));
     }
+
+    // ── Bearer auth ──────────────────────────────────────────
+
+    struct EchoProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for EchoProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok(format!("echo: {message}"))
+        }
+    }
+
+    fn chat_context(
+        tools: Vec<Box<dyn crate::tools::Tool>>,
+        max_history_turns: usize,
+    ) -> ws::ChatContext {
+        ws::ChatContext::new(
+            tools,
+            "system".into(),
+            Arc::new(crate::security::SecurityPolicy::default()),
+            Arc::new(crate::observability::NoopObserver),
+            ws::ChatLimits {
+                max_tool_iterations: 5,
+                max_history_turns,
+                compact_history: false,
+                idle_timeout: Duration::ZERO,
+            },
+        )
+    }
+
+    fn test_state(
+        workspace: &std::path::Path,
+        require_pairing: bool,
+        trust_loopback: bool,
+        provider: Arc<dyn Provider>,
+        chat: ws::ChatContext,
+    ) -> (AppState, String) {
+        let pairing = Arc::new(
+            PairingGuard::new(require_pairing, &[]).with_devices(DeviceStore::new(workspace)),
+        );
+        let token = load_or_create_gateway_token(workspace).unwrap();
+        pairing.accept_token(&token);
+        let state = AppState {
+            provider,
+            model: "test-model".into(),
+            temperature: 0.0,
+            mem: Arc::new(crate::memory::MarkdownMemory::new(workspace)),
+            auto_save: false,
+            webhook_secret: None,
+            pairing,
+            trust_loopback,
+            whatsapp: None,
+            whatsapp_app_secret: None,
+            max_inbound_chars: 0,
+            seen_messages: Arc::new(Mutex::new(SeenMessages::load(
+                workspace,
+                Duration::from_mins(1),
+                100,
+            ))),
+            chat: Arc::new(chat),
+            runs: Arc::new(runs::RunRegistry::new(4)),
+            metrics: false,
+        };
+        (state, token)
+    }
+
+    fn auth_router(
+        workspace: &std::path::Path,
+        require_pairing: bool,
+        trust_loopback: bool,
+    ) -> (Router, String) {
+        let (state, token) = test_state(
+            workspace,
+            require_pairing,
+            trust_loopback,
+            Arc::new(EchoProvider),
+            chat_context(Vec::new(), 20),
+        );
+        (router(state, MAX_BODY_SIZE), token)
+    }
+
+    async fn post_webhook(app: &Router, uri: &str, auth: Option<&str>) -> StatusCode {
+        use tower::ServiceExt;
+
+        let mut req =
+            axum::http::Request::post(uri).header(header::CONTENT_TYPE, "application/json");
+        if let Some(auth) = auth {
+            req = req.header(header::AUTHORIZATION, auth);
+        }
+        let req = req
+            .body(axum::body::Body::from(r#"{"message": "hi"}"#))
+            .unwrap();
+        app.clone().oneshot(req).await.unwrap().status()
+    }
+
+    /// A text and an image from an allowed number, and a text from a stranger.
+    fn whatsapp_payload() -> String {
+        serde_json::json!({
+            "entry": [{
+                "changes": [{
+                    "value": {
+                        "messages": [
+                            { "id": "wamid.a", "from": "15550001", "timestamp": "1",
+                              "type": "text", "text": { "body": "hello" } },
+                            { "id": "wamid.b", "from": "15550001", "timestamp": "2",
+                              "type": "image", "image": { "id": "img" } },
+                            { "id": "wamid.c", "from": "15559999", "timestamp": "3",
+                              "type": "text", "text": { "body": "spam" } },
+                        ]
+                    }
+                }]
+            }]
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn whatsapp_webhook_verifies_answers_and_tracks_health() {
+        use tower::ServiceExt;
+
+        // Mock Graph API recording every message sent
+        let sent = Arc::new(std::sync::Mutex::new(Vec::<serde_json::Value>::new()));
+        let recorder = Arc::clone(&sent);
+        let graph = Router::new().route(
+            "/:phone/messages",
+            post(move |Json(body): Json<serde_json::Value>| async move {
+                recorder.lock().unwrap().push(body);
+                Json(serde_json::json!({ "messages": [{ "id": "wamid.1" }] }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, graph).await.unwrap() });
+
+        let tmp = tempfile::tempdir().unwrap();
+        let (mut state, _) = test_state(
+            tmp.path(),
+            true,
+            false,
+            Arc::new(EchoProvider),
+            chat_context(Vec::new(), 20),
+        );
+        let wa = WhatsAppChannel::new(
+            "tok".into(),
+            "123".into(),
+            "verify-me".into(),
+            vec!["+15550001".into()],
+        );
+        state.whatsapp = Some(Arc::new(wa.with_api_base(&base)));
+        state.whatsapp_app_secret = Some(Arc::from("app-secret"));
+        let app = router(state, MAX_BODY_SIZE);
+
+        let verify = |token: &str| {
+            let uri =
+                format!("/whatsapp?hub.mode=subscribe&hub.verify_token={token}&hub.challenge=4242");
+            let req = axum::http::Request::get(uri)
+                .body(axum::body::Body::empty())
+                .unwrap();
+            app.clone().oneshot(req)
+        };
+        let res = verify("verify-me").await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"4242");
+        assert_eq!(
+            verify("wrong").await.unwrap().status(),
+            StatusCode::FORBIDDEN
+        );
+
+        let payload = whatsapp_payload();
+        let deliver = |signature: Option<String>| {
+            let mut req = axum::http::Request::post("/whatsapp")
+                .header(header::CONTENT_TYPE, "application/json");
+            if let Some(signature) = signature {
+                req = req.header("X-Hub-Signature-256", signature);
+            }
+            let req = req.body(axum::body::Body::from(payload.clone())).unwrap();
+            app.clone().oneshot(req)
+        };
+        assert_eq!(
+            deliver(None).await.unwrap().status(),
+            StatusCode::UNAUTHORIZED
+        );
+        let signature = compute_whatsapp_signature_header("app-secret", payload.as_bytes());
+        assert_eq!(
+            deliver(Some(signature.clone())).await.unwrap().status(),
+            StatusCode::OK
+        );
+        // Meta retrying the webhook doesn't get the messages answered again
+        assert_eq!(
+            deliver(Some(signature)).await.unwrap().status(),
+            StatusCode::OK
+        );
+
+        // Replies are sent in the background
+        for _ in 0..200 {
+            if sent.lock().unwrap().len() >= 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let sent = sent.lock().unwrap().clone();
+        assert_eq!(sent.len(), 2, "{sent:?}");
+        assert_eq!(sent[0]["to"], "15550001");
+        let reply = sent[0]["text"]["body"].as_str().unwrap();
+        assert!(
+            reply.starts_with("echo: ") && reply.ends_with("hello"),
+            "{reply}"
+        );
+        assert_eq!(sent[1]["to"], "15550001");
+        assert_eq!(sent[1]["text"]["body"], WHATSAPP_UNSUPPORTED_REPLY);
+
+        let health = crate::health::snapshot_json();
+        assert_eq!(health["components"][WHATSAPP_COMPONENT]["status"], "ok");
+        assert!(health["components"][WHATSAPP_COMPONENT]["last_ok"].is_string());
+    }
+
+    #[tokio::test]
+    async fn webhook_requires_bearer_token() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (app, token) = auth_router(tmp.path(), true, false);
+
+        assert_eq!(
+            post_webhook(&app, "/webhook", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            post_webhook(&app, "/webhook", Some("Bearer zc_wrong")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            post_webhook(&app, "/webhook", Some(&format!("Bearer {token}"))).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn webhook_accepts_token_query_param() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (app, token) = auth_router(tmp.path(), true, false);
+
+        assert_eq!(
+            post_webhook(&app, "/webhook?token=zc_wrong", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            post_webhook(&app, &format!("/webhook?token={token}"), None).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn rotated_token_replaces_old_one() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, old) = auth_router(tmp.path(), true, false);
+        rotate_gateway_token(tmp.path()).unwrap();
+        let (app, new) = auth_router(tmp.path(), true, false);
+
+        assert_eq!(
+            post_webhook(&app, "/webhook", Some(&format!("Bearer {old}"))).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            post_webhook(&app, "/webhook", Some(&format!("Bearer {new}"))).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn pair_endpoint_issues_revocable_device_tokens() {
+        use tower::ServiceExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let (app, _) = auth_router(tmp.path(), true, false);
+        let store = DeviceStore::new(tmp.path());
+        let issued = store.issue_code().unwrap();
+
+        let pair = |uri: String| {
+            let req = axum::http::Request::post(uri)
+                .header("X-Device-Name", "phone")
+                .body(axum::body::Body::empty())
+                .unwrap();
+            app.clone().oneshot(req)
+        };
+        let res = pair(format!("/pair?code={}", issued.code)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let token = body["token"].as_str().unwrap().to_string();
+
+        // Single-use
+        let res = pair(format!("/pair?code={}", issued.code)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let bearer = format!("Bearer {token}");
+        assert_eq!(
+            post_webhook(&app, "/webhook", Some(&bearer)).await,
+            StatusCode::OK
+        );
+        assert_eq!(store.devices().unwrap()[0].name, "phone");
+
+        store.revoke("phone").unwrap();
+        assert_eq!(
+            post_webhook(&app, "/webhook", Some(&bearer)).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn public_paths_and_exemptions_skip_auth() {
+        use tower::ServiceExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let (app, _) = auth_router(tmp.path(), true, false);
+        let req = axum::http::Request::get("/health")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        assert_eq!(app.oneshot(req).await.unwrap().status(), StatusCode::OK);
+
+        let (trusted, _) = auth_router(tmp.path(), true, true);
+        assert_eq!(
+            post_webhook(&trusted, "/webhook", None).await,
+            StatusCode::OK
+        );
+        let (open, _) = auth_router(tmp.path(), false, false);
+        assert_eq!(post_webhook(&open, "/webhook", None).await, StatusCode::OK);
+    }
+
+    /// Counts the messages that reach it, answering like [`EchoProvider`].
+    struct CountingProvider(Arc<std::sync::atomic::AtomicUsize>);
+
+    #[async_trait::async_trait]
+    impl Provider for CountingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(format!("echo: {message}"))
+        }
+    }
+
+    /// An open gateway capping bodies at 256 bytes and messages at 10
+    /// characters, and the count of messages its provider received.
+    fn size_capped_router(
+        workspace: &std::path::Path,
+    ) -> (Router, Arc<std::sync::atomic::AtomicUsize>) {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let (mut state, _) = test_state(
+            workspace,
+            false,
+            false,
+            Arc::new(CountingProvider(Arc::clone(&calls))),
+            chat_context(Vec::new(), 20),
+        );
+        state.max_inbound_chars = 10;
+        (router(state, 256), calls)
+    }
+
+    async fn post_message(app: &Router, message: &str) -> (StatusCode, serde_json::Value) {
+        use tower::ServiceExt;
+
+        let body = serde_json::json!({ "message": message }).to_string();
+        let req = axum::http::Request::post("/webhook")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(body))
+            .unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        let status = res.status();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn webhook_rejects_body_over_max_body_bytes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (app, calls) = size_capped_router(tmp.path());
+
+        let (status, body) = post_message(&app, &"x".repeat(300)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(
+            body["error"]
+                .as_str()
+                .unwrap()
+                .contains("Request body too large"),
+            "{body}"
+        );
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn webhook_rejects_message_over_max_inbound_chars() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (app, calls) = size_capped_router(tmp.path());
+
+        let (status, body) = post_message(&app, "eleven char").await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(
+            body["error"],
+            crate::channels::check_inbound_size("eleven char", 10).unwrap_err()
+        );
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn webhook_passes_message_under_the_limits_unchanged() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (app, calls) = size_capped_router(tmp.path());
+
+        let (status, body) = post_message(&app, "ten chars!").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["response"], "echo: ten chars!");
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn metrics_require_auth_and_the_prometheus_backend() {
+        use tower::ServiceExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let get = |auth: Option<String>| {
+            let mut req = axum::http::Request::get("/metrics");
+            if let Some(auth) = auth {
+                req = req.header(header::AUTHORIZATION, auth);
+            }
+            req.body(axum::body::Body::empty()).unwrap()
+        };
+
+        let (app, token) = auth_router(tmp.path(), true, false);
+        let res = app.oneshot(get(Some(format!("Bearer {token}")))).await;
+        assert_eq!(res.unwrap().status(), StatusCode::NOT_FOUND);
+
+        let (mut state, token) = test_state(
+            tmp.path(),
+            true,
+            false,
+            Arc::new(EchoProvider),
+            chat_context(Vec::new(), 20),
+        );
+        state.metrics = true;
+        let app = router(state, MAX_BODY_SIZE);
+        let res = app.clone().oneshot(get(None)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = app.oneshot(get(Some(format!("Bearer {token}")))).await;
+        let res = res.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("# TYPE jarvis_tool_calls_total counter"));
+    }
+
+    // ── WebSocket chat ───────────────────────────────────────
+
+    /// Calls the `echo` tool with each new user message, then replies with
+    /// the number of user turns it can see and the tool output.
+    struct ScriptedToolProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for ScriptedToolProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("fallback".into())
+        }
+
+        async fn chat_with_tools(
+            &self,
+            messages: &[crate::providers::traits::ChatMessage],
+            _tools: &[crate::providers::traits::ToolDefinition],
+            _model: &str,
+            _temperature: f64,
+            _response_format: Option<&providers::ResponseFormat>,
+        ) -> anyhow::Result<crate::providers::traits::ChatResponse> {
+            use crate::providers::traits::{ChatMessage, ChatResponse, FunctionCall, ToolCall};
+
+            match messages.last() {
+                Some(ChatMessage::User { content }) => Ok(ChatResponse::ToolUse {
+                    tool_calls: vec![ToolCall {
+                        id: "call_1".into(),
+                        function: FunctionCall {
+                            name: "echo".into(),
+                            arguments: serde_json::json!({ "text": content }).to_string(),
+                        },
+                    }],
+                    text: None,
+                }),
+                Some(ChatMessage::Tool { content, .. }) => {
+                    let turns = messages
+                        .iter()
+                        .filter(|m| matches!(m, ChatMessage::User { .. }))
+                        .count();
+                    Ok(ChatResponse::Text(format!(
+                        "turns: {turns}; echo: {content}"
+                    )))
+                }
+                other => anyhow::bail!("unexpected last message: {other:?}"),
+            }
+        }
+    }
+
+    struct EchoTool;
+
+    #[async_trait::async_trait]
+    impl crate::tools::Tool for EchoTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+        fn description(&self) -> &str {
+            "Echo back the input"
+        }
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({
+                "type": "object",
+                "properties": { "text": { "type": "string" } },
+                "required": ["text"]
+            })
+        }
+        async fn execute(
+            &self,
+            args: serde_json::Value,
+        ) -> anyhow::Result<crate::tools::ToolResult> {
+            Ok(crate::tools::ToolResult {
+                success: true,
+                output: args["text"].as_str().unwrap_or_default().to_string(),
+                error: None,
+            })
+        }
+    }
+
+    async fn spawn_gateway(app: Router) -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        addr
+    }
+
+    type ClientSocket = tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >;
+
+    async fn send_frame(socket: &mut ClientSocket, frame: serde_json::Value) {
+        use futures_util::SinkExt;
+
+        socket
+            .send(tokio_tungstenite::tungstenite::Message::Text(
+                frame.to_string(),
+            ))
+            .await
+            .unwrap();
+    }
+
+    async fn next_frame(socket: &mut ClientSocket) -> serde_json::Value {
+        use futures_util::StreamExt;
+
+        loop {
+            let msg = tokio::time::timeout(Duration::from_secs(5), socket.next())
+                .await
+                .expect("timed out waiting for a frame")
+                .expect("socket closed")
+                .unwrap();
+            if let tokio_tungstenite::tungstenite::Message::Text(text) = msg {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn ws_chat_requires_token() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (app, _) = auth_router(tmp.path(), true, false);
+        let addr = spawn_gateway(app).await;
+
+        let err = tokio_tungstenite::connect_async(format!("ws://{addr}/ws/chat?token=zc_wrong"))
+            .await
+            .unwrap_err();
+        match err {
+            tokio_tungstenite::tungstenite::Error::Http(resp) => {
+                assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+            }
+            other => panic!("expected HTTP 401, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn ws_chat_round_trip_keeps_session_history() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (state, token) = test_state(
+            tmp.path(),
+            true,
+            false,
+            Arc::new(ScriptedToolProvider),
+            chat_context(vec![Box::new(EchoTool)], 2),
+        );
+        let addr = spawn_gateway(router(state, MAX_BODY_SIZE)).await;
+        let (mut socket, _) =
+            tokio_tungstenite::connect_async(format!("ws://{addr}/ws/chat?token={token}"))
+                .await
+                .unwrap();
+
+        // max_history_turns = 2 keeps two earlier turns plus the new one
+        for (turn, seen) in [(1, 1), (2, 2), (3, 3), (4, 3)] {
+            let content = format!("msg {turn}");
+            send_frame(
+                &mut socket,
+                serde_json::json!({"type": "message", "content": content, "session_id": "s1"}),
+            )
+            .await;
+
+            let start = next_frame(&mut socket).await;
+            assert_eq!(start["type"], "tool");
+            assert_eq!(start["phase"], "start");
+            assert_eq!(start["tool"], "echo");
+            let end = next_frame(&mut socket).await;
+            assert_eq!(end["phase"], "end");
+            assert_eq!(end["success"], true);
+
+            let reply = next_frame(&mut socket).await;
+            assert_eq!(reply["type"], "token");
+            assert_eq!(reply["session_id"], "s1");
+            assert_eq!(reply["content"], format!("turns: {seen}; echo: {content}"));
+            assert_eq!(
+                next_frame(&mut socket).await,
+                serde_json::json!({"type": "done", "session_id": "s1"})
+            );
+        }
+
+        // A different session starts from scratch
+        send_frame(
+            &mut socket,
+            serde_json::json!({"type": "message", "content": "hello", "session_id": "s2"}),
+        )
+        .await;
+        next_frame(&mut socket).await;
+        next_frame(&mut socket).await;
+        let reply = next_frame(&mut socket).await;
+        assert_eq!(reply["content"], "turns: 1; echo: hello");
+        next_frame(&mut socket).await;
+
+        send_frame(&mut socket, serde_json::json!({"type": "bogus"})).await;
+        let err = next_frame(&mut socket).await;
+        assert_eq!(err["type"], "error");
+        assert!(err["message"].as_str().unwrap().contains("Invalid frame"));
+    }
+
+    /// Asks for approval to run its `text`, and returns the decision.
+    struct ApprovalTool(Arc<crate::security::SecurityPolicy>);
+
+    #[async_trait::async_trait]
+    impl crate::tools::Tool for ApprovalTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+        fn description(&self) -> &str {
+            "Ask before echoing the input"
+        }
+        fn parameters_schema(&self) -> serde_json::Value {
+            EchoTool.parameters_schema()
+        }
+        async fn execute(
+            &self,
+            args: serde_json::Value,
+        ) -> anyhow::Result<crate::tools::ToolResult> {
+            let command = args["text"].as_str().unwrap_or_default();
+            let decision = self.0.request_approval(command, None).await;
+            Ok(crate::tools::ToolResult {
+                success: true,
+                output: format!("{decision:?}"),
+                error: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn ws_chat_asks_the_session_for_approval() {
+        let tmp = tempfile::tempdir().unwrap();
+        let security = Arc::new(crate::security::SecurityPolicy {
+            approvals: crate::security::approval::ApprovalGate::new(Duration::from_secs(1)),
+            ..crate::security::SecurityPolicy::default()
+        });
+        let chat = ws::ChatContext::new(
+            vec![Box::new(ApprovalTool(Arc::clone(&security)))],
+            "system".into(),
+            security,
+            Arc::new(crate::observability::NoopObserver),
+            ws::ChatLimits {
+                max_tool_iterations: 5,
+                max_history_turns: 20,
+                compact_history: false,
+                idle_timeout: Duration::ZERO,
+            },
+        );
+        let (state, token) = test_state(
+            tmp.path(),
+            true,
+            false,
+            Arc::new(ScriptedToolProvider),
+            chat,
+        );
+        let addr = spawn_gateway(router(state, MAX_BODY_SIZE)).await;
+        let (mut socket, _) =
+            tokio_tungstenite::connect_async(format!("ws://{addr}/ws/chat?token={token}"))
+                .await
+                .unwrap();
+
+        for (turn, reply, decision) in [
+            (1, Some("yes"), "Some(Approved)"),
+            (2, Some("no"), "Some(Denied)"),
+            (3, None, "Some(TimedOut)"),
+        ] {
+            let command = format!("rm build-{turn}");
+            send_frame(
+                &mut socket,
+                serde_json::json!({"type": "message", "content": command, "session_id": "s1"}),
+            )
+            .await;
+            assert_eq!(next_frame(&mut socket).await["phase"], "start");
+
+            let ask = next_frame(&mut socket).await;
+            assert_eq!(ask["type"], "approval");
+            assert_eq!(ask["session_id"], "s1");
+            assert_eq!(ask["command"], command);
+            assert_eq!(ask["prompt"], format!("Allow `{command}`? [y/N/always]"));
+            assert_eq!(ask["timeout_secs"], 1);
+            if let Some(reply) = reply {
+                send_frame(
+                    &mut socket,
+                    serde_json::json!({"type": "message", "content": reply}),
+                )
+                .await;
+            }
+
+            assert_eq!(next_frame(&mut socket).await["phase"], "end");
+            let answer = next_frame(&mut socket).await;
+            assert_eq!(
+                answer["content"],
+                format!("turns: {turn}; echo: {decision}")
+            );
+            assert_eq!(next_frame(&mut socket).await["type"], "done");
+        }
+    }
+
+    // ── Agent runs ───────────────────────────────────────────
+
+    fn run_state(workspace: &std::path::Path) -> (AppState, String) {
+        test_state(
+            workspace,
+            true,
+            false,
+            Arc::new(ScriptedToolProvider),
+            chat_context(vec![Box::new(EchoTool)], 20),
+        )
+    }
+
+    async fn call(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        token: &str,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        use tower::ServiceExt;
+
+        let req = axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.map_or_else(axum::body::Body::empty, |b| {
+                axum::body::Body::from(b.to_string())
+            }))
+            .unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        let status = resp.status();
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn agent_run_returns_reply_and_tool_calls() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (state, token) = run_state(tmp.path());
+        let app = router(state, MAX_BODY_SIZE);
+
+        let (status, _) = call(&app, "POST", "/v1/agent", "zc_wrong", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, run) = call(
+            &app,
+            "POST",
+            "/v1/agent",
+            &token,
+            Some(serde_json::json!({"message": "hello", "model": "other-model"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(run["status"], "succeeded");
+        assert_eq!(run["model"], "other-model");
+        assert_eq!(run["response"], "turns: 1; echo: hello");
+        assert_eq!(run["tool_calls"][0]["tool"], "echo");
+        assert_eq!(run["tool_calls"][0]["success"], true);
+
+        let (status, err) = call(
+            &app,
+            "POST",
+            "/v1/agent",
+            &token,
+            Some(serde_json::json!({"message": "hi", "temperature": 5.0})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(err["error"].as_str().unwrap().contains("temperature"));
+    }
+
+    #[tokio::test]
+    async fn async_agent_run_can_be_polled() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (state, token) = run_state(tmp.path());
+        let app = router(state, MAX_BODY_SIZE);
+
+        let (status, accepted) = call(
+            &app,
+            "POST",
+            "/v1/agent",
+            &token,
+            Some(serde_json::json!({"message": "later", "async": true})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let poll = accepted["poll"].as_str().unwrap().to_string();
+        assert_eq!(
+            poll,
+            format!("/v1/runs/{}", accepted["id"].as_str().unwrap())
+        );
+
+        let mut run = serde_json::Value::Null;
+        for _ in 0..50 {
+            let (status, body) = call(&app, "GET", &poll, &token, None).await;
+            assert_eq!(status, StatusCode::OK);
+            run = body;
+            if run["status"] != "running" {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(run["status"], "succeeded");
+        assert_eq!(run["response"], "turns: 1; echo: later");
+        assert!(run["finished_at"].is_string());
+
+        let (status, _) = call(&app, "GET", "/v1/runs/run_missing", &token, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn agent_runs_over_the_limit_get_429() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (mut state, token) = run_state(tmp.path());
+        state.runs = Arc::new(runs::RunRegistry::new(1));
+        let busy = state.runs.try_start("test-model").unwrap();
+        let app = router(state.clone(), MAX_BODY_SIZE);
+
+        let body = serde_json::json!({"message": "hi"});
+        let (status, err) = call(&app, "POST", "/v1/agent", &token, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(err["limit"], 1);
+
+        drop(busy);
+        let (status, _) = call(&app, "POST", "/v1/agent", &token, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+
+        state.webhook_secret = Some(Arc::from("s3cret"));
+        let app = router(state, MAX_BODY_SIZE);
+        let (status, _) = call(&app, "POST", "/v1/agent", &token, Some(body)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
 }
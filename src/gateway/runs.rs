@@ -0,0 +1,492 @@
+//! `POST /v1/agent` and `GET /v1/runs/:id` — one-shot agent runs for
+//! automations (Shortcuts, n8n, curl).
+//!
+//! A run answers a single message with the same tools, security policy and
+//! memory auto-save as `/ws/chat`, but without session history. By default
+//! the request waits for the final text (and is subject to the 30s request
+//! timeout); with `"async": true` it returns a run id straight away, to be
+//! polled at `/v1/runs/:id`. At most `[gateway] max_concurrent_runs` run at
+//! once, beyond which requests get 429. Recent runs also show up in the
+//! health snapshot.
+
+use super::{check_webhook_secret, AppState};
+use crate::agent::loop_::build_context;
+use crate::memory::MemoryCategory;
+use crate::observability::traits::ObserverMetric;
+use crate::observability::{Observer, ObserverEvent};
+use crate::usage::TurnUsage;
+use crate::util::truncate_with_ellipsis;
+use axum::{
+    extract::{rejection::JsonRejection, Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Finished runs kept for polling before the oldest are dropped
+const MAX_RUN_RECORDS: usize = 100;
+
+/// `POST /v1/agent` request body
+#[derive(Debug, serde::Deserialize)]
+pub struct AgentRunBody {
+    pub message: String,
+    /// Defaults to the gateway's model
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Defaults to the gateway's temperature
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    /// Return a run id immediately instead of waiting for the reply
+    #[serde(default, rename = "async")]
+    pub run_async: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl RunStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// One tool call made during a run.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ToolCallSummary {
+    pub tool: String,
+    pub success: bool,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What `GET /v1/runs/:id` (and a synchronous `POST /v1/agent`) returns.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RunRecord {
+    pub id: String,
+    pub status: RunStatus,
+    pub model: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub response: Option<String>,
+    pub tool_calls: Vec<ToolCallSummary>,
+    pub error: Option<String>,
+    /// Prompt plus completion tokens of a finished run (estimated)
+    pub tokens_used: Option<u64>,
+    /// Estimated cost in USD, when the model's price is known
+    pub cost_usd: Option<f64>,
+}
+
+impl RunRecord {
+    fn health(&self) -> crate::health::RunHealth {
+        crate::health::RunHealth {
+            id: self.id.clone(),
+            status: self.status.as_str().into(),
+            model: self.model.clone(),
+            started_at: self.started_at.clone(),
+            finished_at: self.finished_at.clone(),
+            tokens_used: self.tokens_used,
+        }
+    }
+}
+
+/// A started run; holds its concurrency slot until dropped.
+pub struct RunTicket {
+    pub id: String,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+/// Concurrency cap and records of recent runs.
+pub struct RunRegistry {
+    limit: usize,
+    permits: Option<Arc<Semaphore>>,
+    records: Mutex<VecDeque<RunRecord>>,
+}
+
+impl RunRegistry {
+    /// `max_concurrent` of 0 means unlimited.
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            limit: max_concurrent,
+            permits: (max_concurrent > 0).then(|| Arc::new(Semaphore::new(max_concurrent))),
+            records: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Record a new running run, or `None` when all slots are taken.
+    pub fn try_start(&self, model: &str) -> Option<RunTicket> {
+        let permit = match &self.permits {
+            Some(permits) => Some(Arc::clone(permits).try_acquire_owned().ok()?),
+            None => None,
+        };
+        let record = RunRecord {
+            id: format!("run_{}", uuid::Uuid::new_v4().simple()),
+            status: RunStatus::Running,
+            model: model.to_string(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+            response: None,
+            tool_calls: Vec::new(),
+            error: None,
+            tokens_used: None,
+            cost_usd: None,
+        };
+        crate::health::record_run(record.health());
+        let id = record.id.clone();
+
+        let mut records = self.records.lock().unwrap_or_else(PoisonError::into_inner);
+        records.push_back(record);
+        // Drop the oldest finished runs; running ones are never evicted
+        while records.len() > MAX_RUN_RECORDS {
+            let Some(oldest) = records.iter().position(|r| r.status != RunStatus::Running) else {
+                break;
+            };
+            records.remove(oldest);
+        }
+
+        Some(RunTicket {
+            id,
+            _permit: permit,
+        })
+    }
+
+    /// Store the outcome of a run and return its final record.
+    pub fn finish(
+        &self,
+        id: &str,
+        result: Result<(String, TurnUsage), String>,
+        tool_calls: Vec<ToolCallSummary>,
+    ) -> Option<RunRecord> {
+        let mut records = self.records.lock().unwrap_or_else(PoisonError::into_inner);
+        let record = records.iter_mut().find(|r| r.id == id)?;
+        record.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        record.tool_calls = tool_calls;
+        match result {
+            Ok((response, usage)) => {
+                record.status = RunStatus::Succeeded;
+                record.response = Some(response);
+                record.tokens_used =
+                    Some(usage.tokens.prompt_tokens + usage.tokens.completion_tokens);
+                record.cost_usd = usage.cost_usd;
+            }
+            Err(error) => {
+                record.status = RunStatus::Failed;
+                record.error = Some(error);
+            }
+        }
+        crate::health::record_run(record.health());
+        Some(record.clone())
+    }
+
+    pub fn get(&self, id: &str) -> Option<RunRecord> {
+        self.records
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .find(|r| r.id == id)
+            .cloned()
+    }
+}
+
+/// Collects the tool calls of one run.
+struct RunObserver {
+    inner: Arc<dyn Observer>,
+    calls: Mutex<Vec<ToolCallSummary>>,
+}
+
+impl Observer for RunObserver {
+    fn record_event(&self, event: &ObserverEvent) {
+        self.inner.record_event(event);
+        if let ObserverEvent::ToolCall {
+            tool,
+            duration,
+            success,
+            error,
+        } = event
+        {
+            self.calls
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(ToolCallSummary {
+                    tool: tool.clone(),
+                    success: *success,
+                    duration_ms: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+                    error: error.clone(),
+                });
+        }
+    }
+
+    fn record_metric(&self, metric: &ObserverMetric) {
+        self.inner.record_metric(metric);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+
+    fn name(&self) -> &str {
+        "gateway-run"
+    }
+}
+
+fn error_response(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(serde_json::json!({ "error": message.into() }))).into_response()
+}
+
+/// POST /v1/agent — run the agent on one message
+pub(super) async fn handle_agent_run(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Result<Json<AgentRunBody>, JsonRejection>,
+) -> Response {
+    // Bearer token auth (pairing) is enforced by `require_auth`
+    if let Err(rejection) = check_webhook_secret(&state, &headers) {
+        return rejection.into_response();
+    }
+
+    let Json(body) = match body {
+        Ok(body) => body,
+        Err(e) if e.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+            return error_response(StatusCode::PAYLOAD_TOO_LARGE, "Request body too large");
+        }
+        Err(e) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Invalid JSON: {e}. Expected: {{\"message\": \"...\", \"model\": \"...\", \"temperature\": 0.7, \"async\": false}}"
+                ),
+            );
+        }
+    };
+
+    let message = match crate::channels::check_inbound_size(&body.message, state.max_inbound_chars)
+    {
+        Ok(message) if !message.trim().is_empty() => message.to_string(),
+        Ok(_) => return error_response(StatusCode::BAD_REQUEST, "Empty message"),
+        Err(notice) => {
+            tracing::warn!("Agent run：已拒绝 — 消息超出长度上限");
+            return error_response(StatusCode::PAYLOAD_TOO_LARGE, notice);
+        }
+    };
+    let temperature = body.temperature.unwrap_or(state.temperature);
+    if !(0.0..=2.0).contains(&temperature) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "temperature must be between 0.0 and 2.0",
+        );
+    }
+    let model = body
+        .model
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .unwrap_or_else(|| state.model.clone());
+
+    let Some(ticket) = state.runs.try_start(&model) else {
+        tracing::warn!("Agent run：已拒绝 — 并发运行数已达上限");
+        let err = serde_json::json!({
+            "error": format!(
+                "Too many concurrent agent runs (limit {}). Try again later.",
+                state.runs.limit
+            ),
+            "limit": state.runs.limit,
+        });
+        return (StatusCode::TOO_MANY_REQUESTS, Json(err)).into_response();
+    };
+    let id = ticket.id.clone();
+
+    // The run continues even if a synchronous caller hangs up or times out
+    let run = tokio::spawn(execute_run(state, ticket, model, temperature, message));
+
+    if body.run_async {
+        let body = serde_json::json!({
+            "id": id,
+            "status": RunStatus::Running,
+            "poll": format!("/v1/runs/{id}"),
+        });
+        return (StatusCode::ACCEPTED, Json(body)).into_response();
+    }
+    match run.await {
+        Ok(Some(record)) if record.status == RunStatus::Succeeded => {
+            (StatusCode::OK, Json(record)).into_response()
+        }
+        Ok(Some(record)) => (StatusCode::INTERNAL_SERVER_ERROR, Json(record)).into_response(),
+        Ok(None) | Err(_) => error_response(StatusCode::INTERNAL_SERVER_ERROR, "Agent run failed"),
+    }
+}
+
+async fn execute_run(
+    state: AppState,
+    ticket: RunTicket,
+    model: String,
+    temperature: f64,
+    message: String,
+) -> Option<RunRecord> {
+    let transcript = state.chat.start_transcript("api");
+    transcript.user(&message);
+    if state.auto_save {
+        let _ = state
+            .mem
+            .store("agent_run_msg", &message, MemoryCategory::Conversation)
+            .await;
+    }
+    let context = build_context(state.mem.as_ref(), &message).await;
+    let enriched = if context.is_empty() {
+        message
+    } else {
+        format!("{context}{message}")
+    };
+
+    let observer = RunObserver {
+        inner: Arc::clone(state.chat.observer()),
+        calls: Mutex::new(Vec::new()),
+    };
+    let result = state
+        .chat
+        .run_once(
+            state.provider.as_ref(),
+            &model,
+            temperature,
+            enriched,
+            &observer,
+            &transcript,
+        )
+        .await;
+
+    let result = match result {
+        Ok((response, usage)) => {
+            if state.auto_save {
+                let summary = truncate_with_ellipsis(&response, 100);
+                let _ = state
+                    .mem
+                    .store("assistant_resp", &summary, MemoryCategory::Daily)
+                    .await;
+            }
+            Ok((response, usage))
+        }
+        Err(e) => {
+            tracing::error!(
+                "Agent run provider 错误：{}",
+                crate::providers::sanitize_api_error(&e.to_string())
+            );
+            Err("LLM request failed".to_string())
+        }
+    };
+    let calls = observer
+        .calls
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner);
+    state.runs.finish(&ticket.id, result, calls)
+}
+
+/// GET /v1/runs/:id — poll a run started with `"async": true`
+pub(super) async fn handle_get_run(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    if let Err(rejection) = check_webhook_secret(&state, &headers) {
+        return rejection.into_response();
+    }
+    match state.runs.get(&id) {
+        Some(record) => Json(record).into_response(),
+        None => error_response(StatusCode::NOT_FOUND, format!("Unknown run: {id}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage::TokenUsage;
+
+    #[test]
+    fn registry_caps_concurrent_runs() {
+        let runs = RunRegistry::new(1);
+        let first = runs.try_start("m").unwrap();
+        assert!(runs.try_start("m").is_none());
+        drop(first);
+        assert!(runs.try_start("m").is_some());
+
+        let unlimited = RunRegistry::new(0);
+        let tickets: Vec<_> = (0..10).filter_map(|_| unlimited.try_start("m")).collect();
+        assert_eq!(tickets.len(), 10);
+    }
+
+    #[test]
+    fn finished_runs_are_recorded_in_health() {
+        let runs = RunRegistry::new(0);
+        let ticket = runs.try_start("test-model").unwrap();
+        assert_eq!(runs.get(&ticket.id).unwrap().status, RunStatus::Running);
+
+        let usage = TurnUsage {
+            tokens: TokenUsage {
+                prompt_tokens: 120,
+                completion_tokens: 30,
+            },
+            cost_usd: Some(0.002),
+            ..TurnUsage::default()
+        };
+        let record = runs
+            .finish(&ticket.id, Ok(("done".into(), usage)), Vec::new())
+            .unwrap();
+        assert_eq!(record.status, RunStatus::Succeeded);
+        assert_eq!(record.response.as_deref(), Some("done"));
+        assert_eq!(record.tokens_used, Some(150));
+        assert_eq!(record.cost_usd, Some(0.002));
+        assert!(record.finished_at.is_some());
+
+        let snapshot = crate::health::snapshot_json();
+        let health = snapshot["runs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|r| r["id"] == ticket.id.as_str())
+            .expect("run in health snapshot")
+            .clone();
+        assert_eq!(health["status"], "succeeded");
+        assert_eq!(health["model"], "test-model");
+        assert_eq!(health["tokens_used"], 150);
+    }
+
+    #[test]
+    fn oldest_finished_runs_are_evicted() {
+        let runs = RunRegistry::new(0);
+        let running = runs.try_start("m").unwrap();
+        let first_done = runs.try_start("m").unwrap();
+        runs.finish(&first_done.id, Err("boom".into()), Vec::new());
+        for _ in 0..MAX_RUN_RECORDS {
+            let ticket = runs.try_start("m").unwrap();
+            runs.finish(
+                &ticket.id,
+                Ok((String::new(), TurnUsage::default())),
+                Vec::new(),
+            );
+        }
+
+        assert!(runs.get(&running.id).is_some(), "running runs are kept");
+        assert!(runs.get(&first_done.id).is_none());
+    }
+
+    #[test]
+    fn body_accepts_async_flag() {
+        let body: AgentRunBody =
+            serde_json::from_str(r#"{"message": "hi", "async": true, "temperature": 0.2}"#)
+                .unwrap();
+        assert!(body.run_async);
+        assert_eq!(body.temperature, Some(0.2));
+        assert!(body.model.is_none());
+
+        let body: AgentRunBody = serde_json::from_str(r#"{"message": "hi"}"#).unwrap();
+        assert!(!body.run_async);
+    }
+}
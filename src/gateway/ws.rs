@@ -0,0 +1,841 @@
+//! `GET /ws/chat` — WebSocket chat API for custom frontends.
+//!
+//! Clients send `{"type": "message", "content": "...", "session_id": "..."}`
+//! text frames. Each message runs the same tool loop as `jarvis agent`; the
+//! server answers with `tool` frames as tools start and finish, the reply as
+//! a `token` frame, then `done` — or a single `error` frame. Providers return
+//! whole responses today, so a reply arrives as one `token` frame.
+//!
+//! History is kept in memory per `session_id`, trimmed (or compacted) to
+//! `[autonomy] max_history_turns`, and dropped after
+//! `[gateway] chat_session_idle_secs` without messages.
+//!
+//! In supervised mode, a shell command that needs approval (off the
+//! allowlist, or destructive) is put to the client as an `approval` frame,
+//! and the turn waits up to `[autonomy] approval_timeout_secs` for the next
+//! `message` frame to answer it (`yes`, `always`; anything else is "no").
+//! "always" allows the command for the rest of that session only.
+//! Sending `/approve-destructive` lets destructive commands run without
+//! asking for the rest of the session (undone by `/revoke-destructive`).
+
+use super::AppState;
+use crate::agent::loop_::{build_context, limit_history, run_tool_loop};
+use crate::config::Config;
+use crate::memory::{Memory, MemoryCategory};
+use crate::observability::traits::ObserverMetric;
+use crate::observability::{Observer, ObserverEvent};
+use crate::providers::traits::{tool_spec_to_definition, ChatMessage, ToolDefinition};
+use crate::providers::Provider;
+use crate::runtime::RuntimeAdapter;
+use crate::security::approval::{
+    self, ApprovalDecision, ApprovalRequest, SessionApproval, APPROVE_DESTRUCTIVE_COMMAND,
+    REVOKE_DESTRUCTIVE_COMMAND,
+};
+use crate::security::SecurityPolicy;
+use crate::sessions::Transcript;
+use crate::tools::{self, Tool};
+use crate::usage::ledger::UsageLog;
+use crate::usage::TurnUsage;
+use crate::util::truncate_with_ellipsis;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Frames sent by the client.
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientFrame {
+    Message {
+        content: String,
+        /// Defaults to a session private to this connection
+        #[serde(default)]
+        session_id: Option<String>,
+    },
+}
+
+/// Frames sent to the client. Every frame names the session it belongs to.
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerFrame {
+    Token {
+        session_id: String,
+        content: String,
+    },
+    Tool {
+        session_id: String,
+        tool: String,
+        /// `start` or `end`
+        phase: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        arguments: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        success: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        duration_ms: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// A shell command waits for the client's answer in the next `message`
+    Approval {
+        session_id: String,
+        command: String,
+        prompt: String,
+        timeout_secs: u64,
+    },
+    Done {
+        session_id: String,
+    },
+    Error {
+        session_id: Option<String>,
+        message: String,
+    },
+}
+
+/// Turn and session limits, from `[autonomy]` and `[gateway]`.
+#[derive(Debug, Clone, Copy)]
+pub struct ChatLimits {
+    pub max_tool_iterations: usize,
+    pub max_history_turns: usize,
+    pub compact_history: bool,
+    /// Drop a session after this long without messages (zero = keep forever)
+    pub idle_timeout: Duration,
+}
+
+impl ChatLimits {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_tool_iterations: config.autonomy.max_tool_iterations,
+            max_history_turns: config.autonomy.max_history_turns,
+            compact_history: config.autonomy.compact_history,
+            idle_timeout: Duration::from_secs(config.gateway.chat_session_idle_secs),
+        }
+    }
+}
+
+/// One conversation, shared by every connection using its `session_id`.
+struct ChatSession {
+    history: tokio::sync::Mutex<Vec<ChatMessage>>,
+    last_active: Mutex<Instant>,
+    /// Set by `/approve-destructive` (destructive commands run without an
+    /// `approval` frame) and by "always" answers, for this session only
+    approval: SessionApproval,
+    transcript: Transcript,
+}
+
+impl ChatSession {
+    fn touch(&self) {
+        *self
+            .last_active
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Instant::now();
+    }
+
+    fn idle_for(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(
+            *self
+                .last_active
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        )
+    }
+}
+
+/// Tools, prompt and sessions behind the chat endpoint.
+pub struct ChatContext {
+    tools: Vec<Box<dyn Tool>>,
+    tool_definitions: Vec<ToolDefinition>,
+    security: Arc<SecurityPolicy>,
+    observer: Arc<dyn Observer>,
+    system_prompt: String,
+    limits: ChatLimits,
+    sessions: Mutex<HashMap<String, Arc<ChatSession>>>,
+    /// Where transcripts and the daily cost log are written (`None` records nothing)
+    workspace_dir: Option<PathBuf>,
+    /// Provider name recorded in the daily cost log
+    provider_name: String,
+}
+
+impl ChatContext {
+    pub fn new(
+        tools: Vec<Box<dyn Tool>>,
+        system_prompt: String,
+        security: Arc<SecurityPolicy>,
+        observer: Arc<dyn Observer>,
+        limits: ChatLimits,
+    ) -> Self {
+        let tool_definitions = tools
+            .iter()
+            .map(|t| tool_spec_to_definition(&t.spec()))
+            .collect();
+        Self {
+            tools,
+            tool_definitions,
+            security,
+            observer,
+            system_prompt,
+            limits,
+            sessions: Mutex::new(HashMap::new()),
+            workspace_dir: None,
+            provider_name: String::new(),
+        }
+    }
+
+    /// Build the same tool set and system prompt as the CLI agent.
+    pub fn from_config(
+        config: &Config,
+        model: &str,
+        mem: Arc<dyn Memory>,
+        observer: Arc<dyn Observer>,
+        runtime: Arc<dyn RuntimeAdapter>,
+    ) -> Self {
+        let security = Arc::new(
+            SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir)
+                .with_origin("gateway"),
+        );
+        let composio_key = if config.composio.enabled {
+            config.composio.api_key.as_deref()
+        } else {
+            None
+        };
+        let skills = crate::skills::load_skills(&config.workspace_dir);
+        let tools = tools::all_tools(
+            &security,
+            mem,
+            composio_key,
+            &config.browser,
+            &config.brave_search,
+            &config.git,
+            &config.web_fetch,
+            &config.tools,
+            &config.secrets.named,
+            &skills,
+            runtime,
+        );
+
+        let mut tool_descs: Vec<(&str, &str)> = vec![
+            ("shell", "Execute terminal commands."),
+            ("file_read", "Read file contents."),
+            ("file_write", "Write file contents."),
+            (
+                "file_edit",
+                "Replace exact text in an existing file; prefer it over file_write for edits.",
+            ),
+            ("memory_store", "Save to memory."),
+            ("memory_recall", "Search memory."),
+            ("memory_forget", "Delete a memory entry."),
+            ("task_add", "Add a task to the task list."),
+            ("task_list", "List open tasks."),
+            ("task_complete", "Mark a task done."),
+        ];
+        if config.browser.enabled {
+            tool_descs.push(("browser_open", "Open approved HTTPS URLs in Brave Browser."));
+        }
+        if crate::tools::web_search::provider_from_config(
+            &config.tools.web_search,
+            &config.brave_search,
+        )
+        .is_some()
+        {
+            tool_descs.push(("web_search", "Search the web."));
+        }
+        if config.web_fetch.enabled {
+            tool_descs.push(("web_fetch", "Fetch a URL and return its readable text."));
+        }
+        if config.tools.http.enabled {
+            tool_descs.push(("http_request", "Call a JSON API on an allowed host."));
+        }
+        if config.tools.clipboard.enabled {
+            tool_descs.push(("clipboard", "Read or set the user's clipboard text."));
+        }
+        if config.tools.reminders.enabled {
+            tool_descs.push((
+                "reminders",
+                "Add, list or complete reminders in the .ics file.",
+            ));
+        }
+        if config.tools.python.enabled {
+            tool_descs.push(("python", "Run a Python program in the workspace."));
+        }
+        if config.git.enabled {
+            tool_descs.push(("git", "Inspect and commit repository changes."));
+        }
+        let system_prompt = crate::channels::build_system_prompt(
+            &config.workspace_dir,
+            model,
+            &tool_descs,
+            &skills,
+        );
+
+        Self {
+            workspace_dir: Some(config.workspace_dir.clone()),
+            provider_name: config
+                .default_provider
+                .clone()
+                .unwrap_or_else(|| "openrouter".into()),
+            ..Self::new(
+                tools,
+                system_prompt,
+                security,
+                observer,
+                ChatLimits::from_config(config),
+            )
+        }
+    }
+
+    /// A transcript for a new conversation from `origin`.
+    pub(super) fn start_transcript(&self, origin: &str) -> Transcript {
+        self.workspace_dir
+            .as_deref()
+            .map(|dir| Transcript::start(dir, origin))
+            .unwrap_or_default()
+    }
+
+    /// Today's transcript for the conversation identified by `key`.
+    pub(super) fn resume_transcript(&self, origin: &str, key: &str) -> Transcript {
+        self.workspace_dir
+            .as_deref()
+            .map(|dir| Transcript::resume(dir, origin, key))
+            .unwrap_or_default()
+    }
+
+    /// The daily cost log for turns of `transcript`'s session.
+    fn usage_log(&self, transcript: &Transcript) -> UsageLog {
+        self.workspace_dir
+            .as_deref()
+            .map(|dir| UsageLog::new(dir, &self.provider_name, transcript))
+            .unwrap_or_default()
+    }
+
+    pub(super) fn observer(&self) -> &Arc<dyn Observer> {
+        &self.observer
+    }
+
+    /// Answer `message` in a fresh conversation, outside any session (used
+    /// by `POST /v1/agent`), returning the reply and the turn's usage. The
+    /// caller records the user message on `transcript`.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) async fn run_once(
+        &self,
+        provider: &dyn Provider,
+        model: &str,
+        temperature: f64,
+        message: String,
+        observer: &dyn Observer,
+        transcript: &Transcript,
+    ) -> anyhow::Result<(String, TurnUsage)> {
+        let mut history = vec![
+            ChatMessage::System {
+                content: self.system_prompt.clone(),
+            },
+            ChatMessage::User { content: message },
+        ];
+        let response = run_tool_loop(
+            provider,
+            &mut history,
+            &self.tools,
+            &self.tool_definitions,
+            model,
+            temperature,
+            self.limits.max_tool_iterations,
+            &self.security,
+            observer,
+            transcript,
+            true,
+            &CancellationToken::new(),
+        )
+        .await?;
+        let usage = TurnUsage::of_turn(&history, 1, &self.tool_definitions, model);
+        self.usage_log(transcript).record(model, &usage);
+        Ok((response, usage))
+    }
+
+    /// The session for `id`, started fresh if missing or expired. Expired
+    /// sessions are swept on every call.
+    fn session(&self, id: &str) -> Arc<ChatSession> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(PoisonError::into_inner);
+        if !self.limits.idle_timeout.is_zero() {
+            let now = Instant::now();
+            sessions.retain(|_, session| session.idle_for(now) < self.limits.idle_timeout);
+        }
+        let session = sessions
+            .entry(id.to_string())
+            .or_insert_with(|| {
+                Arc::new(ChatSession {
+                    history: tokio::sync::Mutex::new(vec![ChatMessage::System {
+                        content: self.system_prompt.clone(),
+                    }]),
+                    last_active: Mutex::new(Instant::now()),
+                    approval: SessionApproval::default(),
+                    transcript: self.start_transcript("gateway"),
+                })
+            })
+            .clone();
+        session.touch();
+        session
+    }
+
+    /// Answer `/approve-destructive` and `/revoke-destructive`; `None` for
+    /// any other message.
+    fn session_command(&self, session_id: &str, message: &str) -> Option<&'static str> {
+        let message = message.trim();
+        if message == APPROVE_DESTRUCTIVE_COMMAND {
+            self.session(session_id).approval.grant();
+            Some(
+                "Destructive shell commands will run without confirmation in this session until you send /revoke-destructive.",
+            )
+        } else if message == REVOKE_DESTRUCTIVE_COMMAND {
+            self.session(session_id).approval.revoke();
+            Some("Destructive shell commands are refused again in this session.")
+        } else {
+            None
+        }
+    }
+
+    #[cfg(test)]
+    fn session_count(&self) -> usize {
+        self.sessions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+}
+
+/// Forwards tool activity of one turn to the socket as `tool` frames.
+struct ChatObserver {
+    inner: Arc<dyn Observer>,
+    session_id: String,
+    tx: mpsc::UnboundedSender<ServerFrame>,
+}
+
+impl Observer for ChatObserver {
+    fn record_event(&self, event: &ObserverEvent) {
+        self.inner.record_event(event);
+        let frame = match event {
+            ObserverEvent::ToolStart { tool, arguments } => ServerFrame::Tool {
+                session_id: self.session_id.clone(),
+                tool: tool.clone(),
+                phase: "start",
+                arguments: Some(arguments.clone()),
+                success: None,
+                duration_ms: None,
+                error: None,
+            },
+            ObserverEvent::ToolCall {
+                tool,
+                duration,
+                success,
+                error,
+            } => ServerFrame::Tool {
+                session_id: self.session_id.clone(),
+                tool: tool.clone(),
+                phase: "end",
+                arguments: None,
+                success: Some(*success),
+                duration_ms: Some(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)),
+                error: error.clone(),
+            },
+            _ => return,
+        };
+        let _ = self.tx.send(frame);
+    }
+
+    fn record_metric(&self, metric: &ObserverMetric) {
+        self.inner.record_metric(metric);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+
+    fn name(&self) -> &str {
+        "gateway-chat"
+    }
+}
+
+/// GET /ws/chat — upgrade to a chat socket (bearer auth via `require_auth`)
+pub(super) async fn handle_ws_chat(
+    State(state): State<AppState>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    ws.on_upgrade(move |socket| chat_socket(state, socket))
+}
+
+async fn chat_socket(state: AppState, mut socket: WebSocket) {
+    let connection_session = format!("ws-{}", uuid::Uuid::new_v4());
+
+    while let Some(Ok(msg)) = socket.recv().await {
+        let text = match msg {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            // Pings are answered by axum
+            Message::Ping(_) | Message::Pong(_) => continue,
+            Message::Binary(_) => {
+                let frame = ServerFrame::Error {
+                    session_id: None,
+                    message: "Binary frames are not supported — send JSON text frames".into(),
+                };
+                if send(&mut socket, &frame).await.is_err() {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        let sent = match serde_json::from_str::<ClientFrame>(&text) {
+            Ok(ClientFrame::Message {
+                content,
+                session_id,
+            }) => {
+                let session_id = session_id
+                    .filter(|id| !id.trim().is_empty())
+                    .unwrap_or_else(|| connection_session.clone());
+                chat_turn(&state, &mut socket, session_id, &content).await
+            }
+            Err(e) => {
+                let frame = ServerFrame::Error {
+                    session_id: None,
+                    message: format!(
+                        "Invalid frame: {e}. Expected: {{\"type\": \"message\", \"content\": \"...\", \"session_id\": \"...\"}}"
+                    ),
+                };
+                send(&mut socket, &frame).await
+            }
+        };
+        if sent.is_err() {
+            // Client went away; an unfinished turn is dropped with the socket
+            break;
+        }
+    }
+}
+
+/// Run one message through the tool loop, streaming frames as it goes.
+async fn chat_turn(
+    state: &AppState,
+    socket: &mut WebSocket,
+    session_id: String,
+    text: &str,
+) -> Result<(), axum::Error> {
+    let message = match crate::channels::check_inbound_size(text, state.max_inbound_chars) {
+        Ok(message) if !message.trim().is_empty() => message,
+        Ok(_) => {
+            let frame = ServerFrame::Error {
+                session_id: Some(session_id),
+                message: "Empty message".into(),
+            };
+            return send(socket, &frame).await;
+        }
+        Err(notice) => {
+            tracing::warn!("WebSocket 聊天：已拒绝 — 消息超出长度上限");
+            let frame = ServerFrame::Error {
+                session_id: Some(session_id),
+                message: notice,
+            };
+            return send(socket, &frame).await;
+        }
+    };
+
+    if let Some(reply) = state.chat.session_command(&session_id, message) {
+        tracing::info!(session = %session_id, "WebSocket 聊天：{}", message.trim());
+        send(
+            socket,
+            &ServerFrame::Token {
+                session_id: session_id.clone(),
+                content: reply.to_string(),
+            },
+        )
+        .await?;
+        return send(socket, &ServerFrame::Done { session_id }).await;
+    }
+
+    if state.auto_save {
+        let _ = state
+            .mem
+            .store("ws_chat_msg", message, MemoryCategory::Conversation)
+            .await;
+    }
+    let context = build_context(state.mem.as_ref(), message).await;
+    let enriched = if context.is_empty() {
+        message.to_string()
+    } else {
+        format!("{context}{message}")
+    };
+
+    match stream_turn(state, socket, &session_id, message, enriched).await? {
+        Ok(response) => {
+            if state.auto_save {
+                let summary = truncate_with_ellipsis(&response, 100);
+                let _ = state
+                    .mem
+                    .store("assistant_resp", &summary, MemoryCategory::Daily)
+                    .await;
+            }
+            send(
+                socket,
+                &ServerFrame::Token {
+                    session_id: session_id.clone(),
+                    content: response,
+                },
+            )
+            .await?;
+            send(socket, &ServerFrame::Done { session_id }).await
+        }
+        Err(e) => {
+            tracing::error!(
+                "WebSocket 聊天 provider 错误：{}",
+                crate::providers::sanitize_api_error(&e.to_string())
+            );
+            let frame = ServerFrame::Error {
+                session_id: Some(session_id),
+                message: "LLM request failed".into(),
+            };
+            send(socket, &frame).await
+        }
+    }
+}
+
+/// Run the tool loop for one user message on its session, forwarding tool
+/// frames while it runs. `enriched` is `message` with memory context. The
+/// outer error means the socket failed.
+async fn stream_turn(
+    state: &AppState,
+    socket: &mut WebSocket,
+    session_id: &str,
+    message: &str,
+    enriched: String,
+) -> Result<anyhow::Result<String>, axum::Error> {
+    let chat = &state.chat;
+    let session = chat.session(session_id);
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let observer = ChatObserver {
+        inner: Arc::clone(&chat.observer),
+        session_id: session_id.to_string(),
+        tx,
+    };
+
+    let turn = session.approval.scope(async {
+        // Turns on the same session queue up here
+        let mut history = session.history.lock().await;
+        limit_history(
+            state.provider.as_ref(),
+            &mut history,
+            chat.limits.max_history_turns,
+            &state.model,
+            chat.limits.compact_history,
+        )
+        .await;
+        session.transcript.user(message);
+        let turn_start = history.len();
+        history.push(ChatMessage::User { content: enriched });
+        let response = run_tool_loop(
+            state.provider.as_ref(),
+            &mut history,
+            &chat.tools,
+            &chat.tool_definitions,
+            &state.model,
+            state.temperature,
+            chat.limits.max_tool_iterations,
+            &chat.security,
+            &observer,
+            &session.transcript,
+            true,
+            &CancellationToken::new(),
+        )
+        .await?;
+        chat.usage_log(&session.transcript).record(
+            &state.model,
+            &TurnUsage::of_turn(&history, turn_start, &chat.tool_definitions, &state.model),
+        );
+        Ok(response)
+    });
+    // Approval requests of the turn go to this socket
+    let (approval_tx, mut approvals) = mpsc::unbounded_channel();
+    let turn = approval::route_requests(approval_tx, turn);
+    tokio::pin!(turn);
+    let mut pending = None;
+    let result = loop {
+        tokio::select! {
+            // Tool frames go out before the approval frame of the same tool
+            biased;
+            result = &mut turn => break result,
+            Some(frame) = rx.recv() => send(socket, &frame).await?,
+            Some(request) = approvals.recv() => {
+                let frame = ServerFrame::Approval {
+                    session_id: session_id.to_string(),
+                    command: request.command.clone(),
+                    prompt: request.prompt(),
+                    timeout_secs: chat.security.approvals.timeout().as_secs(),
+                };
+                send(socket, &frame).await?;
+                pending = Some(request);
+            }
+            msg = socket.recv(), if pending.is_some() => {
+                answer_approval(socket, session_id, &mut pending, msg).await?;
+            }
+        }
+    };
+    while let Ok(frame) = rx.try_recv() {
+        send(socket, &frame).await?;
+    }
+    session.touch();
+    Ok(result)
+}
+
+/// Answer the pending approval request with the client's next `message`.
+/// A closed socket drops the request, which counts as "no".
+async fn answer_approval(
+    socket: &mut WebSocket,
+    session_id: &str,
+    pending: &mut Option<ApprovalRequest>,
+    msg: Option<Result<Message, axum::Error>>,
+) -> Result<(), axum::Error> {
+    let text = match msg {
+        Some(Ok(Message::Text(text))) => text,
+        Some(Ok(Message::Ping(_) | Message::Pong(_))) => return Ok(()),
+        Some(Ok(Message::Binary(_))) => {
+            let frame = ServerFrame::Error {
+                session_id: Some(session_id.to_string()),
+                message: "Binary frames are not supported — answer the approval prompt with a message frame".into(),
+            };
+            return send(socket, &frame).await;
+        }
+        Some(Ok(Message::Close(_))) | None => {
+            pending.take();
+            return Ok(());
+        }
+        Some(Err(e)) => return Err(e),
+    };
+    let content = match serde_json::from_str::<ClientFrame>(&text) {
+        Ok(ClientFrame::Message { content, .. }) => content,
+        Err(e) => {
+            let frame = ServerFrame::Error {
+                session_id: Some(session_id.to_string()),
+                message: format!(
+                    "Invalid frame: {e}. Answer the approval prompt with {{\"type\": \"message\", \"content\": \"yes\"}}"
+                ),
+            };
+            return send(socket, &frame).await;
+        }
+    };
+    let Some(request) = pending.take() else {
+        return Ok(());
+    };
+    if request.is_cancelled() {
+        let frame = ServerFrame::Error {
+            session_id: Some(session_id.to_string()),
+            message: format!(
+                "The approval request timed out; `{}` was not run",
+                request.command
+            ),
+        };
+        return send(socket, &frame).await;
+    }
+    let decision = ApprovalDecision::from_reply(&content);
+    tracing::info!(session = %session_id, "WebSocket 聊天：`{}` 审批结果 {decision:?}", request.command);
+    request.respond(decision);
+    Ok(())
+}
+
+async fn send(socket: &mut WebSocket, frame: &ServerFrame) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(frame).map_err(axum::Error::new)?;
+    socket.send(Message::Text(text)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::NoopObserver;
+
+    fn context(max_history_turns: usize, idle_timeout: Duration) -> ChatContext {
+        ChatContext::new(
+            Vec::new(),
+            "system".into(),
+            Arc::new(SecurityPolicy::default()),
+            Arc::new(NoopObserver),
+            ChatLimits {
+                max_tool_iterations: 3,
+                max_history_turns,
+                compact_history: false,
+                idle_timeout,
+            },
+        )
+    }
+
+    #[test]
+    fn client_frame_session_id_is_optional() {
+        let frame: ClientFrame =
+            serde_json::from_str(r#"{"type": "message", "content": "hi"}"#).unwrap();
+        let ClientFrame::Message {
+            content,
+            session_id,
+        } = frame;
+        assert_eq!(content, "hi");
+        assert!(session_id.is_none());
+
+        assert!(serde_json::from_str::<ClientFrame>(r#"{"type": "nope"}"#).is_err());
+    }
+
+    #[test]
+    fn server_frames_are_tagged() {
+        let frame = serde_json::to_value(ServerFrame::Tool {
+            session_id: "s".into(),
+            tool: "shell".into(),
+            phase: "start",
+            arguments: Some("{}".into()),
+            success: None,
+            duration_ms: None,
+            error: None,
+        })
+        .unwrap();
+        assert_eq!(frame["type"], "tool");
+        assert_eq!(frame["phase"], "start");
+        assert!(frame.get("success").is_none());
+
+        let done = serde_json::to_value(ServerFrame::Done {
+            session_id: "s".into(),
+        })
+        .unwrap();
+        assert_eq!(done, serde_json::json!({"type": "done", "session_id": "s"}));
+    }
+
+    #[test]
+    fn sessions_are_reused_until_idle() {
+        let chat = context(20, Duration::from_millis(50));
+        let first = chat.session("a");
+        assert!(Arc::ptr_eq(&first, &chat.session("a")));
+        chat.session("b");
+        assert_eq!(chat.session_count(), 2);
+
+        std::thread::sleep(Duration::from_millis(80));
+        let fresh = chat.session("a");
+        assert!(!Arc::ptr_eq(&first, &fresh));
+        assert_eq!(chat.session_count(), 1, "idle session b is swept");
+    }
+
+    #[test]
+    fn destructive_approval_is_per_session() {
+        let chat = context(20, Duration::ZERO);
+        assert!(chat.session_command("a", "rm -rf build").is_none());
+        assert!(chat
+            .session_command("a", " /approve-destructive ")
+            .is_some());
+        assert!(chat.session("a").approval.is_granted());
+        assert!(!chat.session("b").approval.is_granted());
+
+        assert!(chat.session_command("a", "/revoke-destructive").is_some());
+        assert!(!chat.session("a").approval.is_granted());
+    }
+
+    #[test]
+    fn zero_idle_timeout_keeps_sessions() {
+        let chat = context(20, Duration::ZERO);
+        let first = chat.session("a");
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(Arc::ptr_eq(&first, &chat.session("a")));
+    }
+}
@@ -1,6 +1,6 @@
 use chrono::Utc;
 use serde::Serialize;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::sync::{Mutex, OnceLock};
 use std::time::Instant;
 
@@ -11,6 +11,23 @@ pub struct ComponentHealth {
     pub last_ok: Option<String>,
     pub last_error: Option<String>,
     pub restart_count: u64,
+    /// Free-form status line reported by the component (e.g. last hygiene pass)
+    pub detail: Option<String>,
+    /// Set when the daemon stopped restarting the component after a crash loop
+    pub circuit_open: bool,
+}
+
+/// An agent run started through the gateway's `POST /v1/agent`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunHealth {
+    pub id: String,
+    /// `running`, `succeeded` or `failed`
+    pub status: String,
+    pub model: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    /// Not reported by providers yet
+    pub tokens_used: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -19,11 +36,17 @@ pub struct HealthSnapshot {
     pub updated_at: String,
     pub uptime_seconds: u64,
     pub components: BTreeMap<String, ComponentHealth>,
+    /// The most recent gateway agent runs, oldest first
+    pub runs: Vec<RunHealth>,
 }
 
+/// How many gateway runs the snapshot keeps
+const RECENT_RUNS: usize = 20;
+
 struct HealthRegistry {
     started_at: Instant,
     components: Mutex<BTreeMap<String, ComponentHealth>>,
+    runs: Mutex<VecDeque<RunHealth>>,
 }
 
 static REGISTRY: OnceLock<HealthRegistry> = OnceLock::new();
@@ -32,6 +55,7 @@ fn registry() -> &'static HealthRegistry {
     REGISTRY.get_or_init(|| HealthRegistry {
         started_at: Instant::now(),
         components: Mutex::new(BTreeMap::new()),
+        runs: Mutex::new(VecDeque::new()),
     })
 }
 
@@ -53,6 +77,8 @@ where
                 last_ok: None,
                 last_error: None,
                 restart_count: 0,
+                detail: None,
+                circuit_open: false,
             });
         update(entry);
         entry.updated_at = now;
@@ -64,6 +90,7 @@ pub fn mark_component_ok(component: &str) {
         entry.status = "ok".into();
         entry.last_ok = Some(now_rfc3339());
         entry.last_error = None;
+        entry.circuit_open = false;
     });
 }
 
@@ -76,23 +103,82 @@ pub fn mark_component_error(component: &str, error: impl ToString) {
     });
 }
 
+/// Mark a component as running but partly failing (e.g. results that could
+/// not be delivered); the next `mark_component_ok` clears it.
+#[allow(clippy::needless_pass_by_value)]
+pub fn mark_component_degraded(component: &str, error: impl ToString) {
+    let err = error.to_string();
+    upsert_component(component, move |entry| {
+        entry.status = "degraded".into();
+        entry.last_error = Some(err);
+    });
+}
+
+/// Note an error without changing the component's status, for problems that
+/// aren't failures of the component itself (e.g. a heartbeat task timing out).
+#[allow(clippy::needless_pass_by_value)]
+pub fn record_component_error(component: &str, error: impl ToString) {
+    let err = error.to_string();
+    upsert_component(component, move |entry| {
+        entry.last_error = Some(err);
+    });
+}
+
+/// Mark a component as no longer being restarted until its circuit is reset.
+#[allow(clippy::needless_pass_by_value)]
+pub fn mark_component_circuit_open(component: &str, error: impl ToString) {
+    let err = error.to_string();
+    upsert_component(component, move |entry| {
+        entry.status = "error".into();
+        entry.last_error = Some(err);
+        entry.circuit_open = true;
+    });
+}
+
 pub fn bump_component_restart(component: &str) {
     upsert_component(component, |entry| {
         entry.restart_count = entry.restart_count.saturating_add(1);
     });
 }
 
+#[allow(clippy::needless_pass_by_value)]
+pub fn set_component_detail(component: &str, detail: impl ToString) {
+    let detail = detail.to_string();
+    upsert_component(component, move |entry| {
+        entry.detail = Some(detail);
+    });
+}
+
+/// Add or update a gateway run, dropping the oldest beyond [`RECENT_RUNS`].
+pub fn record_run(run: RunHealth) {
+    if let Ok(mut runs) = registry().runs.lock() {
+        if let Some(existing) = runs.iter_mut().find(|r| r.id == run.id) {
+            *existing = run;
+            return;
+        }
+        runs.push_back(run);
+        while runs.len() > RECENT_RUNS {
+            runs.pop_front();
+        }
+    }
+}
+
 pub fn snapshot() -> HealthSnapshot {
     let components = registry()
         .components
         .lock()
         .map_or_else(|_| BTreeMap::new(), |map| map.clone());
+    let runs = registry()
+        .runs
+        .lock()
+        .map_or_else(|_| Vec::new(), |runs| runs.iter().cloned().collect());
 
     HealthSnapshot {
         pid: std::process::id(),
         updated_at: now_rfc3339(),
         uptime_seconds: registry().started_at.elapsed().as_secs(),
         components,
+        runs,
     }
 }
 
@@ -1,16 +1,55 @@
+use super::schedule::{HeartbeatState, Schedule};
 use crate::config::HeartbeatConfig;
 use crate::observability::{Observer, ObserverEvent};
 use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta, Utc};
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 use tokio::time::{self, Duration};
 use tracing::{info, warn};
 
+/// A task line from HEARTBEAT.md.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeartbeatTask {
+    /// What the agent is asked to do, without checkbox or annotation
+    pub text: String,
+    /// `None` runs every `interval_minutes`
+    pub schedule: Option<Schedule>,
+    /// The line after any checkbox; identifies the task in the state file
+    key: String,
+}
+
+impl HeartbeatTask {
+    /// Identifies the task across reads of HEARTBEAT.md.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+#[cfg(test)]
+impl HeartbeatTask {
+    pub(crate) fn new(text: &str) -> Self {
+        Self {
+            text: text.into(),
+            schedule: None,
+            key: text.into(),
+        }
+    }
+}
+
+/// Identifies the overdue-tasks reminder, whose text changes with the list
+const OVERDUE_TASKS_KEY: &str = "overdue tasks";
+
 /// Heartbeat engine — reads HEARTBEAT.md and executes tasks periodically
 pub struct HeartbeatEngine {
     config: HeartbeatConfig,
     workspace_dir: std::path::PathBuf,
     observer: Arc<dyn Observer>,
+    /// Serializes state file updates from tasks finishing concurrently
+    state_lock: tokio::sync::Mutex<()>,
+    /// When each task was last handed out, successful or not
+    attempts: std::sync::Mutex<HashMap<String, NaiveDateTime>>,
 }
 
 impl HeartbeatEngine {
@@ -23,6 +62,8 @@ impl HeartbeatEngine {
             config,
             workspace_dir,
             observer,
+            state_lock: tokio::sync::Mutex::new(()),
+            attempts: std::sync::Mutex::new(HashMap::new()),
         }
     }
 
@@ -59,32 +100,161 @@ impl HeartbeatEngine {
         }
     }
 
-    /// Single heartbeat tick — read HEARTBEAT.md and return task count
+    /// Single heartbeat tick — read HEARTBEAT.md and return the due task count
     async fn tick(&self) -> Result<usize> {
         Ok(self.collect_tasks().await?.len())
     }
 
-    /// Read HEARTBEAT.md and return all parsed tasks.
-    pub async fn collect_tasks(&self) -> Result<Vec<String>> {
+    /// Read HEARTBEAT.md and return the tasks due now, noting them as
+    /// started. The file itself is never rewritten.
+    pub async fn collect_tasks(&self) -> Result<Vec<HeartbeatTask>> {
+        self.collect_due(Local::now().naive_local()).await
+    }
+
+    /// The tasks due at `now`:
+    ///
+    /// - without a directive: every `interval_minutes` since the last start
+    /// - `every`: once the interval has passed since the last start or
+    ///   success, whichever is later
+    /// - daily, weekly, cron: when a slot has come up since the last
+    ///   success; after a failure, again at the next slot or once
+    ///   `interval_minutes` have passed, whichever is sooner
+    ///
+    /// With `overdue_tasks`, a reminder of the task list's overdue tasks is
+    /// added like a task without a directive.
+    async fn collect_due(&self, now: NaiveDateTime) -> Result<Vec<HeartbeatTask>> {
         let heartbeat_path = self.workspace_dir.join("HEARTBEAT.md");
-        if !heartbeat_path.exists() {
-            return Ok(Vec::new());
+        let mut tasks = if heartbeat_path.exists() {
+            Self::parse_tasks(&tokio::fs::read_to_string(&heartbeat_path).await?)
+        } else {
+            Vec::new()
+        };
+        if self.config.overdue_tasks {
+            tasks.extend(self.overdue_reminder());
+        }
+        if tasks.is_empty() {
+            return Ok(tasks);
+        }
+        let state = if tasks.iter().any(|task| task.schedule.is_some()) {
+            HeartbeatState::load(&self.workspace_dir).await
+        } else {
+            HeartbeatState::default()
+        };
+
+        let retry = TimeDelta::minutes(i64::from(self.config.interval_minutes.max(5)));
+        let mut attempts = self
+            .attempts
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let due: Vec<HeartbeatTask> = tasks
+            .into_iter()
+            .filter(|task| {
+                let started = attempts.get(&task.key).copied();
+                let succeeded = state.last_run.get(&task.key).map(DateTime::naive_local);
+                match &task.schedule {
+                    None => started.is_none_or(|at| now - at >= retry),
+                    Some(schedule @ Schedule::Every { .. }) => {
+                        schedule.is_due(started.max(succeeded), now)
+                    }
+                    Some(schedule) => {
+                        schedule.is_due(succeeded, now)
+                            && started.is_none_or(|at| {
+                                now - at >= retry
+                                    || schedule.last_slot(now).is_some_and(|slot| at < slot)
+                            })
+                    }
+                }
+            })
+            .collect();
+        for task in &due {
+            attempts.insert(task.key.clone(), now);
+        }
+        Ok(due)
+    }
+
+    /// The overdue-tasks reminder, if the task list has any. A broken task
+    /// store is logged rather than failing the whole tick.
+    fn overdue_reminder(&self) -> Option<HeartbeatTask> {
+        let overdue = crate::tasks::overdue_tasks(&self.workspace_dir, Utc::now())
+            .map_err(|e| warn!("💓 读取逾期任务失败：{e:#}"))
+            .ok()?;
+        Some(HeartbeatTask {
+            text: crate::tasks::overdue_prompt(&overdue)?,
+            schedule: None,
+            key: OVERDUE_TASKS_KEY.into(),
+        })
+    }
+
+    /// Note that a task finished, so a scheduled one waits for its next slot.
+    pub async fn record_success(&self, task: &HeartbeatTask) -> Result<()> {
+        if task.schedule.is_none() {
+            return Ok(());
         }
-        let content = tokio::fs::read_to_string(&heartbeat_path).await?;
-        Ok(Self::parse_tasks(&content))
+        let _guard = self.state_lock.lock().await;
+        let mut state = HeartbeatState::load(&self.workspace_dir).await;
+        state.last_run.insert(task.key.clone(), Local::now());
+        state.save(&self.workspace_dir).await
     }
 
-    /// Parse tasks from HEARTBEAT.md (lines starting with `- `)
-    fn parse_tasks(content: &str) -> Vec<String> {
+    /// Parse tasks from HEARTBEAT.md (lines starting with `- `), skipping
+    /// checked boxes and lines with a malformed schedule.
+    fn parse_tasks(content: &str) -> Vec<HeartbeatTask> {
         content
             .lines()
-            .filter_map(|line| {
-                let trimmed = line.trim();
-                trimmed.strip_prefix("- ").map(ToString::to_string)
+            .filter_map(|line| match Self::parse_line(line) {
+                Ok(task) => task,
+                Err(e) => {
+                    warn!("💓 已跳过 HEARTBEAT.md 中的任务 {:?}：{e}", line.trim());
+                    None
+                }
             })
             .collect()
     }
 
+    /// One line: `- [ ] [weekly mon 09:00] Review budget`. Both the checkbox
+    /// and the schedule directive are optional.
+    fn parse_line(line: &str) -> Result<Option<HeartbeatTask>> {
+        let Some(rest) = line.trim().strip_prefix("- ") else {
+            return Ok(None);
+        };
+        let rest = rest.trim_start();
+        let rest = if let Some(open) = rest.strip_prefix("[ ]") {
+            open.trim_start()
+        } else if rest.starts_with("[x]") || rest.starts_with("[X]") {
+            return Ok(None);
+        } else {
+            rest
+        };
+        if rest.is_empty() {
+            return Ok(None);
+        }
+
+        let mut task = HeartbeatTask {
+            text: rest.to_string(),
+            schedule: None,
+            key: rest.to_string(),
+        };
+        let Some(inner) = rest.strip_prefix('[') else {
+            return Ok(Some(task));
+        };
+        let Some(end) = inner.find(']') else {
+            // `[daily 09:00 Check email` is a typo, `[draft idea` is just text
+            if !matches!(Schedule::parse(inner), Ok(None)) {
+                anyhow::bail!("计划标注缺少 ]");
+            }
+            return Ok(Some(task));
+        };
+        if let Some(schedule) = Schedule::parse(&inner[..end])? {
+            let text = inner[end + 1..].trim();
+            if text.is_empty() {
+                anyhow::bail!("计划标注后缺少任务内容");
+            }
+            task.text = text.to_string();
+            task.schedule = Some(schedule);
+        }
+        Ok(Some(task))
+    }
+
     /// Create a default HEARTBEAT.md if it doesn't exist
     pub async fn ensure_heartbeat_file(workspace_dir: &Path) -> Result<()> {
         let path = workspace_dir.join("HEARTBEAT.md");
@@ -92,11 +262,16 @@ impl HeartbeatEngine {
             let default = "# Periodic Tasks\n\n\
                            # Add tasks below (one per line, starting with `- `)\n\
                            # The agent will check this file on each heartbeat tick.\n\
+                           # Tasks run every `interval_minutes` unless prefixed with a schedule:\n\
+                           # [daily HH:MM], [weekly mon HH:MM], [every: 2h] or [cron: 0 9 * * MON].\n\
+                           # `- [x]` marks a task done so it is skipped.\n\
                            #\n\
                            # Examples:\n\
                            # - Check my email for important messages\n\
-                           # - Review my calendar for upcoming events\n\
-                           # - Check the weather forecast\n";
+                           # - [daily 09:00] Review my calendar for upcoming events\n\
+                           # - [weekly mon] Review my budget\n\
+                           # - [every: 4h] Check the build status\n\
+                           # - [ ] Check the weather forecast\n";
             tokio::fs::write(&path, default).await?;
         }
         Ok(())
@@ -107,10 +282,17 @@ impl HeartbeatEngine {
 mod tests {
     use super::*;
 
+    fn texts(content: &str) -> Vec<String> {
+        HeartbeatEngine::parse_tasks(content)
+            .into_iter()
+            .map(|task| task.text)
+            .collect()
+    }
+
     #[test]
     fn parse_tasks_basic() {
         let content = "# Tasks\n\n- Check email\n- Review calendar\nNot a task\n- Third task";
-        let tasks = HeartbeatEngine::parse_tasks(content);
+        let tasks = texts(content);
         assert_eq!(tasks.len(), 3);
         assert_eq!(tasks[0], "Check email");
         assert_eq!(tasks[1], "Review calendar");
@@ -119,19 +301,19 @@ mod tests {
 
     #[test]
     fn parse_tasks_empty_content() {
-        assert!(HeartbeatEngine::parse_tasks("").is_empty());
+        assert!(texts("").is_empty());
     }
 
     #[test]
     fn parse_tasks_only_comments() {
-        let tasks = HeartbeatEngine::parse_tasks("# No tasks here\n\nJust comments\n# Another");
+        let tasks = texts("# No tasks here\n\nJust comments\n# Another");
         assert!(tasks.is_empty());
     }
 
     #[test]
     fn parse_tasks_with_leading_whitespace() {
         let content = "  - Indented task\n\t- Tab indented";
-        let tasks = HeartbeatEngine::parse_tasks(content);
+        let tasks = texts(content);
         assert_eq!(tasks.len(), 2);
         assert_eq!(tasks[0], "Indented task");
         assert_eq!(tasks[1], "Tab indented");
@@ -140,7 +322,7 @@ mod tests {
     #[test]
     fn parse_tasks_dash_without_space_ignored() {
         let content = "- Real task\n-\n- Another";
-        let tasks = HeartbeatEngine::parse_tasks(content);
+        let tasks = texts(content);
         // "-" trimmed = "-", does NOT start with "- " => skipped
         // "- Real task" => "Real task"
         // "- Another" => "Another"
@@ -154,7 +336,7 @@ mod tests {
         // "- " trimmed becomes "-" (trim removes trailing space)
         // "-" does NOT start with "- " => skipped
         let content = "- ";
-        let tasks = HeartbeatEngine::parse_tasks(content);
+        let tasks = texts(content);
         assert_eq!(tasks.len(), 0);
     }
 
@@ -162,7 +344,7 @@ mod tests {
     fn parse_tasks_bullet_with_content_after_spaces() {
         // "- hello  " trimmed becomes "- hello" => starts_with "- " => "hello"
         let content = "- hello  ";
-        let tasks = HeartbeatEngine::parse_tasks(content);
+        let tasks = texts(content);
         assert_eq!(tasks.len(), 1);
         assert_eq!(tasks[0], "hello");
     }
@@ -170,7 +352,7 @@ mod tests {
     #[test]
     fn parse_tasks_unicode() {
         let content = "- Check email 📧\n- Review calendar 📅\n- 日本語タスク";
-        let tasks = HeartbeatEngine::parse_tasks(content);
+        let tasks = texts(content);
         assert_eq!(tasks.len(), 3);
         assert!(tasks[0].contains("📧"));
         assert!(tasks[2].contains("日本語"));
@@ -179,7 +361,7 @@ mod tests {
     #[test]
     fn parse_tasks_mixed_markdown() {
         let content = "# Periodic Tasks\n\n## Quick\n- Task A\n\n## Long\n- Task B\n\n* Not a dash bullet\n1. Not numbered";
-        let tasks = HeartbeatEngine::parse_tasks(content);
+        let tasks = texts(content);
         assert_eq!(tasks.len(), 2);
         assert_eq!(tasks[0], "Task A");
         assert_eq!(tasks[1], "Task B");
@@ -187,7 +369,7 @@ mod tests {
 
     #[test]
     fn parse_tasks_single_task() {
-        let tasks = HeartbeatEngine::parse_tasks("- Only one");
+        let tasks = texts("- Only one");
         assert_eq!(tasks.len(), 1);
         assert_eq!(tasks[0], "Only one");
     }
@@ -199,7 +381,7 @@ mod tests {
             let _ = writeln!(s, "- Task {i}");
             s
         });
-        let tasks = HeartbeatEngine::parse_tasks(&content);
+        let tasks = texts(&content);
         assert_eq!(tasks.len(), 100);
         assert_eq!(tasks[99], "Task 99");
     }
@@ -248,6 +430,10 @@ mod tests {
             HeartbeatConfig {
                 enabled: true,
                 interval_minutes: 30,
+                task_timeout_minutes: 10,
+                max_parallel_tasks: 2,
+                overdue_tasks: false,
+                notify_channel: None,
             },
             dir.clone(),
             observer,
@@ -273,6 +459,10 @@ mod tests {
             HeartbeatConfig {
                 enabled: true,
                 interval_minutes: 30,
+                task_timeout_minutes: 10,
+                max_parallel_tasks: 2,
+                overdue_tasks: false,
+                notify_channel: None,
             },
             dir.clone(),
             observer,
@@ -290,6 +480,10 @@ mod tests {
             HeartbeatConfig {
                 enabled: false,
                 interval_minutes: 30,
+                task_timeout_minutes: 10,
+                max_parallel_tasks: 2,
+                overdue_tasks: false,
+                notify_channel: None,
             },
             std::env::temp_dir(),
             observer,
@@ -298,4 +492,206 @@ mod tests {
         let result = engine.run().await;
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn parse_tasks_checkboxes() {
+        let content =
+            "- [ ] Open task\n- [x] Done task\n- [X] Also done\n- [ ]\n- [ ] [daily] Daily open";
+        let tasks = HeartbeatEngine::parse_tasks(content);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].text, "Open task");
+        assert!(tasks[0].schedule.is_none());
+        assert_eq!(tasks[1].text, "Daily open");
+        assert!(tasks[1].schedule.is_some());
+    }
+
+    #[test]
+    fn parse_tasks_schedule_annotations() {
+        let tasks = HeartbeatEngine::parse_tasks(
+            "- [daily 09:00] Check my email\n- [weekly mon] Review budget\n- [URGENT] Call back\n\
+             - [every: 30m] Check the build\n- [cron: 0 9 * * MON-FRI] Standup notes",
+        );
+        assert_eq!(tasks.len(), 5);
+        assert_eq!(tasks[0].text, "Check my email");
+        assert_eq!(
+            tasks[0].schedule.as_ref().unwrap().to_string(),
+            "daily 09:00"
+        );
+        assert_eq!(tasks[1].text, "Review budget");
+        assert_eq!(
+            tasks[1].schedule.as_ref().unwrap().to_string(),
+            "weekly mon 00:00"
+        );
+        // Brackets that aren't a schedule stay part of the task
+        assert_eq!(tasks[2].text, "[URGENT] Call back");
+        assert!(tasks[2].schedule.is_none());
+        assert_eq!(tasks[3].text, "Check the build");
+        assert_eq!(tasks[3].schedule, Some(Schedule::Every { minutes: 30 }));
+        assert_eq!(tasks[4].text, "Standup notes");
+        assert_eq!(
+            tasks[4].schedule.as_ref().unwrap().to_string(),
+            "cron 0 9 * * MON-FRI"
+        );
+    }
+
+    #[test]
+    fn parse_tasks_skips_malformed_annotations() {
+        for bad in [
+            "- [daily 9am] Check email",
+            "- [daily 25:00] Check email",
+            "- [weekly] Review budget",
+            "- [weekly someday] Review budget",
+            "- [weekly mon 09:00 10:00] Review budget",
+            "- [daily 09:00 Check email",
+            "- [daily 09:00]",
+            "- [ ] [weekly] Review budget",
+            "- [every: soon] Check the build",
+            "- [every: 0m] Check the build",
+            "- [cron: 0 9 * *] Standup notes",
+            "- [cron: every monday] Standup notes",
+            "- [every: 2h]",
+        ] {
+            assert!(
+                HeartbeatEngine::parse_line(bad).is_err(),
+                "{bad} should be rejected"
+            );
+        }
+        let tasks = texts("- [daily 9am] Broken\n- Fine\n- [draft idea");
+        assert_eq!(tasks, vec!["Fine", "[draft idea"]);
+    }
+
+    fn engine(dir: &Path) -> HeartbeatEngine {
+        let observer: Arc<dyn Observer> = Arc::new(crate::observability::NoopObserver);
+        HeartbeatEngine::new(
+            HeartbeatConfig {
+                enabled: true,
+                interval_minutes: 30,
+                task_timeout_minutes: 10,
+                max_parallel_tasks: 2,
+                overdue_tasks: false,
+                notify_channel: None,
+            },
+            dir.to_path_buf(),
+            observer,
+        )
+    }
+
+    #[tokio::test]
+    async fn scheduled_tasks_wait_for_their_next_slot() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = "- Every tick\n- [daily] Once a day\n- [ ] [weekly mon] Once a week";
+        tokio::fs::write(dir.path().join("HEARTBEAT.md"), content)
+            .await
+            .unwrap();
+
+        let engine = engine(dir.path());
+        let due = engine.collect_tasks().await.unwrap();
+        assert_eq!(due.len(), 3, "scheduled tasks that never ran are due");
+
+        for task in &due {
+            engine.record_success(task).await.unwrap();
+        }
+        assert!(engine.collect_tasks().await.unwrap().is_empty());
+        assert!(HeartbeatState::path(dir.path()).exists());
+
+        // The engine never rewrites HEARTBEAT.md
+        let after = tokio::fs::read_to_string(dir.path().join("HEARTBEAT.md"))
+            .await
+            .unwrap();
+        assert_eq!(after, content);
+    }
+
+    #[tokio::test]
+    async fn each_task_keeps_its_own_cadence() {
+        let dir = tempfile::tempdir().unwrap();
+        // Feb 29 midnight never comes up during the test
+        let content = "- Default interval\n- [every: 10m] Often\n- [every: 2h] Rarely\n\
+                       - [cron: 0 0 29 2 *] Leap day";
+        tokio::fs::write(dir.path().join("HEARTBEAT.md"), content)
+            .await
+            .unwrap();
+        let engine = engine(dir.path());
+        let texts_at = |minutes: i64| {
+            let engine = &engine;
+            async move {
+                let now = Local::now().naive_local() + TimeDelta::minutes(minutes);
+                engine
+                    .collect_due(now)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|task| task.text)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        let first = texts_at(0).await;
+        assert_eq!(first.len(), 4, "everything runs on the first pass");
+        let leap_day = HeartbeatEngine::parse_tasks(content).pop().unwrap();
+        engine.record_success(&leap_day).await.unwrap();
+
+        assert!(texts_at(5).await.is_empty());
+        assert_eq!(texts_at(10).await, ["Often"]);
+        assert_eq!(texts_at(20).await, ["Often"]);
+        // The global interval (30 minutes) applies to the undirected task
+        assert_eq!(texts_at(30).await, ["Default interval", "Often"]);
+        assert_eq!(texts_at(120).await, ["Default interval", "Often", "Rarely"]);
+    }
+
+    #[tokio::test]
+    async fn overdue_tasks_are_reminded_every_interval() {
+        let dir = tempfile::tempdir().unwrap();
+        let late = Utc::now() - TimeDelta::days(1);
+        crate::tasks::add_task(dir.path(), "File taxes", Some(late), None, "cli").unwrap();
+        crate::tasks::add_task(dir.path(), "Pay rent", Some(late), None, "cli").unwrap();
+
+        assert!(
+            engine(dir.path()).collect_tasks().await.unwrap().is_empty(),
+            "off by default"
+        );
+
+        let mut engine = engine(dir.path());
+        engine.config.overdue_tasks = true;
+        let now = Local::now().naive_local();
+        let due = engine.collect_due(now).await.unwrap();
+        assert_eq!(due.len(), 1, "no HEARTBEAT.md needed");
+        assert_eq!(due[0].key(), OVERDUE_TASKS_KEY);
+        assert!(due[0].text.starts_with("You have 2 overdue tasks:"));
+
+        assert!(engine.collect_due(now).await.unwrap().is_empty());
+        let later = now + TimeDelta::minutes(30);
+        assert_eq!(engine.collect_due(later).await.unwrap().len(), 1);
+
+        for task in crate::tasks::list_tasks(dir.path(), false).unwrap() {
+            crate::tasks::complete_task(dir.path(), task.id).unwrap();
+        }
+        let much_later = now + TimeDelta::minutes(60);
+        assert!(engine.collect_due(much_later).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_scheduled_tasks_retry_after_the_global_interval() {
+        let dir = tempfile::tempdir().unwrap();
+        tokio::fs::write(dir.path().join("HEARTBEAT.md"), "- [weekly mon] Review")
+            .await
+            .unwrap();
+        let engine = engine(dir.path());
+        let now = Local::now().naive_local();
+
+        assert_eq!(engine.collect_due(now).await.unwrap().len(), 1);
+        // Never recorded as a success, so it's retried — but not at once
+        assert!(engine
+            .collect_due(now + TimeDelta::minutes(10))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            engine
+                .collect_due(now + TimeDelta::minutes(30))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
 }
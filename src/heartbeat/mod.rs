@@ -1 +1,3 @@
 pub mod engine;
+pub mod notify;
+pub mod schedule;
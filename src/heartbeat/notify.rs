@@ -0,0 +1,180 @@
+//! Delivery of heartbeat task results through [`crate::notify`], to
+//! `heartbeat.notify_channel` or else the shared `[notify]` block.
+//!
+//! Unless every result is wanted, the task prompt asks the agent to answer
+//! with [`QUIET_MARKER`] when there is nothing actionable, and such answers
+//! fall below the notification threshold.
+
+use super::engine::HeartbeatTask;
+use crate::config::{Config, NotifyThreshold};
+use crate::notify::{Notification, Notifier};
+use anyhow::Result;
+
+/// What the agent answers when a task turns up nothing worth sending.
+pub const QUIET_MARKER: &str = "HEARTBEAT_OK";
+
+/// The notifier for heartbeat results: `[heartbeat.notify_channel]` when
+/// set, otherwise `[notify]`.
+pub fn notifier(config: &Config) -> Result<Option<Notifier>> {
+    match &config.heartbeat.notify_channel {
+        Some(notify) => Notifier::from_config(
+            &notify.into(),
+            &config.channels_config,
+            "heartbeat.notify_channel",
+        )
+        .map(Some),
+        None => Notifier::from_notify(config),
+    }
+}
+
+/// The prompt for `task`, asking for the quiet marker unless every result
+/// is sent.
+pub fn prompt(notifier: Option<&Notifier>, task: &HeartbeatTask) -> String {
+    let prompt = format!("[Heartbeat Task] {}", task.text);
+    if notifier.is_some_and(|n| n.threshold() != NotifyThreshold::All) {
+        format!(
+            "{prompt}\n\nIf there is nothing that needs the user's attention, reply with exactly {QUIET_MARKER} and nothing else."
+        )
+    } else {
+        prompt
+    }
+}
+
+/// A task's final response; quiet answers are not actionable.
+pub fn result(task: &HeartbeatTask, response: &str) -> Notification {
+    Notification {
+        source: "heartbeat",
+        title: task.text.clone(),
+        body: response.to_string(),
+        failed: false,
+        actionable: !is_quiet(response),
+    }
+}
+
+/// A task that errored or timed out.
+pub fn failure(task: &HeartbeatTask, error: &str) -> Notification {
+    Notification {
+        source: "heartbeat",
+        title: task.text.clone(),
+        body: error.to_string(),
+        failed: true,
+        actionable: true,
+    }
+}
+
+/// Whether a response only says there is nothing to report: empty, or just
+/// the marker (possibly wrapped in markdown or followed by punctuation).
+pub fn is_quiet(response: &str) -> bool {
+    let stripped = response.trim().trim_matches(|c: char| {
+        c.is_whitespace() || matches!(c, '`' | '*' | '_' | '.' | '!' | '。' | '！')
+    });
+    stripped.is_empty() || stripped.eq_ignore_ascii_case(QUIET_MARKER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::schema::HeartbeatNotifyConfig;
+    use crate::config::NotifyConfig;
+
+    fn webhook(quiet: bool) -> HeartbeatNotifyConfig {
+        HeartbeatNotifyConfig {
+            channel: "webhook".into(),
+            target: "http://127.0.0.1:9/hook".into(),
+            quiet,
+        }
+    }
+
+    #[test]
+    fn quiet_marker_detection() {
+        for quiet in [
+            "HEARTBEAT_OK",
+            "  `HEARTBEAT_OK`\n",
+            "**HEARTBEAT_OK**.",
+            "",
+        ] {
+            assert!(is_quiet(quiet), "{quiet:?} should be quiet");
+        }
+        for loud in [
+            "3 important emails from Alice",
+            "HEARTBEAT_OK, but also: the build is red",
+        ] {
+            assert!(!is_quiet(loud), "{loud:?} should be delivered");
+        }
+    }
+
+    #[test]
+    fn heartbeat_block_overrides_shared_notify() {
+        let mut config = Config {
+            notify: Some(NotifyConfig {
+                channel: "telegram".into(),
+                target: "123".into(),
+                threshold: NotifyThreshold::Failures,
+            }),
+            ..Config::default()
+        };
+        // [notify] names telegram, which has no credentials
+        let err = notifier(&config).err().unwrap();
+        assert!(err.to_string().starts_with("notify 使用 telegram"), "{err}");
+
+        config.heartbeat.notify_channel = Some(webhook(false));
+        let chosen = notifier(&config).unwrap().unwrap();
+        assert_eq!(chosen.threshold(), NotifyThreshold::All);
+
+        config.notify = None;
+        config.heartbeat.notify_channel = None;
+        assert!(notifier(&config).unwrap().is_none());
+    }
+
+    #[test]
+    fn prompt_asks_for_marker_unless_everything_is_sent() {
+        let task = HeartbeatTask::new("Check my email");
+        assert_eq!(prompt(None, &task), "[Heartbeat Task] Check my email");
+
+        let config = Config {
+            heartbeat: crate::config::HeartbeatConfig {
+                notify_channel: Some(webhook(true)),
+                ..crate::config::HeartbeatConfig::default()
+            },
+            ..Config::default()
+        };
+        let quiet = notifier(&config).unwrap();
+        assert!(prompt(quiet.as_ref(), &task).contains(QUIET_MARKER));
+
+        let config = Config {
+            heartbeat: crate::config::HeartbeatConfig {
+                notify_channel: Some(webhook(false)),
+                ..crate::config::HeartbeatConfig::default()
+            },
+            ..Config::default()
+        };
+        let loud = notifier(&config).unwrap();
+        assert!(!prompt(loud.as_ref(), &task).contains(QUIET_MARKER));
+    }
+
+    #[tokio::test]
+    async fn quiet_responses_are_not_sent() {
+        let config = Config {
+            heartbeat: crate::config::HeartbeatConfig {
+                notify_channel: Some(webhook(true)),
+                ..crate::config::HeartbeatConfig::default()
+            },
+            ..Config::default()
+        };
+        // Port 9 (discard) is never reached because nothing is sent
+        let notifier = notifier(&config).unwrap().unwrap();
+        let task = HeartbeatTask::new("Check my email");
+        assert!(!notifier
+            .deliver(&result(&task, "HEARTBEAT_OK"))
+            .await
+            .unwrap());
+        assert!(notifier
+            .deliver(&result(&task, "2 new emails"))
+            .await
+            .is_err());
+        assert!(notifier
+            .deliver(&failure(&task, "timed out"))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,402 @@
+//! Per-task schedules for HEARTBEAT.md and the record of when each scheduled
+//! task last ran (`workspace/state/heartbeat_state.json`).
+//!
+//! A task line may start with a directive:
+//!
+//! - `[daily]` / `[daily 09:00]` — once a day, at or after the given time
+//! - `[weekly mon]` / `[weekly mon 09:00]` — once a week on that day
+//! - `[every: 30m]` — at that interval (`m`, `h` or `d`)
+//! - `[cron: 0 9 * * MON]` — at each time the cron expression matches
+//!
+//! The colon after the kind is optional. Times are local. A daily, weekly or
+//! cron task is due when its most recent slot is later than its last
+//! successful run (or it never ran); an `every` task once the interval has
+//! passed since it last ran. Tasks without a directive run every
+//! `interval_minutes`.
+
+use anyhow::{Context, Result};
+use chrono::{
+    DateTime, Datelike, Days, Local, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Weekday,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// When a heartbeat task should run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    Daily {
+        at: NaiveTime,
+    },
+    Weekly {
+        day: Weekday,
+        at: NaiveTime,
+    },
+    Every {
+        minutes: u32,
+    },
+    /// Checked by [`crate::cron::parse_expression`] when parsed
+    Cron {
+        expression: String,
+    },
+}
+
+impl Schedule {
+    /// Parse the inside of a directive (`daily 09:00`, `every: 30m`).
+    /// `Ok(None)` means it isn't a schedule at all (e.g. `[URGENT]`), so the
+    /// bracket is left as part of the task text.
+    pub fn parse(annotation: &str) -> Result<Option<Self>> {
+        let annotation = annotation.trim();
+        let (kind, rest) = match annotation.find(|c: char| c == ':' || c.is_whitespace()) {
+            Some(end) => {
+                let rest = annotation[end..].trim_start();
+                (&annotation[..end], rest.strip_prefix(':').unwrap_or(rest))
+            }
+            None => (annotation, ""),
+        };
+        let rest: Vec<&str> = rest.split_whitespace().collect();
+        let schedule = match kind.to_ascii_lowercase().as_str() {
+            "daily" => match rest.as_slice() {
+                [] => Self::Daily { at: NaiveTime::MIN },
+                [at] => Self::Daily {
+                    at: parse_time(at)?,
+                },
+                _ => anyhow::bail!("daily 只接受一个时间（如 [daily 09:00]）"),
+            },
+            "weekly" => match rest.as_slice() {
+                [day] => Self::Weekly {
+                    day: parse_weekday(day)?,
+                    at: NaiveTime::MIN,
+                },
+                [day, at] => Self::Weekly {
+                    day: parse_weekday(day)?,
+                    at: parse_time(at)?,
+                },
+                _ => anyhow::bail!("weekly 需要星期几和可选的时间（如 [weekly mon 09:00]）"),
+            },
+            "every" => match rest.as_slice() {
+                [interval] => Self::Every {
+                    minutes: parse_interval(interval)?,
+                },
+                _ => anyhow::bail!("every 需要一个间隔（如 [every: 30m]）"),
+            },
+            "cron" => {
+                if rest.is_empty() {
+                    anyhow::bail!("cron 需要一个表达式（如 [cron: 0 9 * * MON]）");
+                }
+                let expression = rest.join(" ");
+                crate::cron::parse_expression(&expression)?;
+                Self::Cron { expression }
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(schedule))
+    }
+
+    /// The latest slot at or before `now`; `None` for `every`, which has no
+    /// fixed slots, and for a cron expression that never matched.
+    pub fn last_slot(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        match self {
+            Self::Daily { at } => {
+                let today = now.date().and_time(*at);
+                Some(if today <= now {
+                    today
+                } else {
+                    today - Days::new(1)
+                })
+            }
+            Self::Weekly { day, at } => {
+                let back =
+                    (7 + now.weekday().num_days_from_monday() - day.num_days_from_monday()) % 7;
+                let slot = (now.date() - Days::new(u64::from(back))).and_time(*at);
+                Some(if slot <= now {
+                    slot
+                } else {
+                    slot - Days::new(7)
+                })
+            }
+            Self::Every { .. } => None,
+            Self::Cron { expression } => {
+                let schedule = crate::cron::parse_expression(expression).ok()?;
+                let now = Local.from_local_datetime(&now).earliest()?;
+                // Iterating backwards from `now` yields the previous match
+                let slot = schedule.after(&now).next_back()?.naive_local();
+                Some(slot)
+            }
+        }
+    }
+
+    /// Whether a task last run at `last_run` (if ever) is due at `now`.
+    pub fn is_due(&self, last_run: Option<NaiveDateTime>, now: NaiveDateTime) -> bool {
+        let Some(last) = last_run else {
+            return true;
+        };
+        match self {
+            Self::Every { minutes } => now - last >= TimeDelta::minutes(i64::from(*minutes)),
+            _ => self.last_slot(now).is_some_and(|slot| last < slot),
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Daily { at } => write!(f, "daily {}", at.format("%H:%M")),
+            Self::Weekly { day, at } => write!(
+                f,
+                "weekly {} {}",
+                day.to_string().to_lowercase(),
+                at.format("%H:%M")
+            ),
+            Self::Every { minutes } if minutes % (24 * 60) == 0 => {
+                write!(f, "every {}d", minutes / (24 * 60))
+            }
+            Self::Every { minutes } if minutes % 60 == 0 => write!(f, "every {}h", minutes / 60),
+            Self::Every { minutes } => write!(f, "every {minutes}m"),
+            Self::Cron { expression } => write!(f, "cron {expression}"),
+        }
+    }
+}
+
+fn parse_time(raw: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(raw, "%H:%M")
+        .with_context(|| format!("无效的时间 {raw}（应为 HH:MM，如 09:00）"))
+}
+
+fn parse_weekday(raw: &str) -> Result<Weekday> {
+    raw.parse::<Weekday>()
+        .map_err(|_| anyhow::anyhow!("无效的星期 {raw}（应为 mon…sun）"))
+}
+
+/// `30m`, `2h` or `1d`, in minutes.
+fn parse_interval(raw: &str) -> Result<u32> {
+    let invalid = || anyhow::anyhow!("无效的间隔 {raw}（应为正整数加单位 m、h 或 d，如 30m）");
+    let Some((split, unit)) = raw.char_indices().next_back() else {
+        return Err(invalid());
+    };
+    let count: u32 = raw[..split].parse().map_err(|_| invalid())?;
+    let scale = match unit.to_ascii_lowercase() {
+        'm' => 1,
+        'h' => 60,
+        'd' => 24 * 60,
+        _ => return Err(invalid()),
+    };
+    match count.checked_mul(scale) {
+        Some(minutes) if minutes > 0 => Ok(minutes),
+        _ => Err(invalid()),
+    }
+}
+
+/// When each scheduled task last completed, keyed by its HEARTBEAT.md line.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HeartbeatState {
+    #[serde(default)]
+    pub last_run: BTreeMap<String, DateTime<Local>>,
+}
+
+impl HeartbeatState {
+    pub fn path(workspace_dir: &Path) -> PathBuf {
+        workspace_dir.join("state").join("heartbeat_state.json")
+    }
+
+    /// The saved state; missing or unreadable state counts as "never ran".
+    pub async fn load(workspace_dir: &Path) -> Self {
+        let path = Self::path(workspace_dir);
+        match tokio::fs::read_to_string(&path).await {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                tracing::warn!("心跳状态文件无效，已忽略 {}：{e}", path.display());
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub async fn save(&self, workspace_dir: &Path) -> Result<()> {
+        let path = Self::path(workspace_dir);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, serde_json::to_vec_pretty(self)?)
+            .await
+            .with_context(|| format!("写入失败 {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(date: (i32, u32, u32), time: (u32, u32)) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(date.0, date.1, date.2)
+            .unwrap()
+            .and_hms_opt(time.0, time.1, 0)
+            .unwrap()
+    }
+
+    fn hm(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn parses_daily_and_weekly() {
+        assert_eq!(
+            Schedule::parse("daily 09:00").unwrap(),
+            Some(Schedule::Daily { at: hm(9, 0) })
+        );
+        assert_eq!(
+            Schedule::parse("daily").unwrap(),
+            Some(Schedule::Daily { at: hm(0, 0) })
+        );
+        assert_eq!(
+            Schedule::parse("Weekly Monday 18:30").unwrap(),
+            Some(Schedule::Weekly {
+                day: Weekday::Mon,
+                at: hm(18, 30)
+            })
+        );
+        assert_eq!(
+            Schedule::parse("weekly fri").unwrap(),
+            Some(Schedule::Weekly {
+                day: Weekday::Fri,
+                at: hm(0, 0)
+            })
+        );
+    }
+
+    #[test]
+    fn parses_every_and_cron_directives() {
+        assert_eq!(
+            Schedule::parse("every: 30m").unwrap(),
+            Some(Schedule::Every { minutes: 30 })
+        );
+        assert_eq!(
+            Schedule::parse("every 2h").unwrap(),
+            Some(Schedule::Every { minutes: 120 })
+        );
+        assert_eq!(
+            Schedule::parse("Every:1d").unwrap(),
+            Some(Schedule::Every { minutes: 1440 })
+        );
+        assert_eq!(
+            Schedule::parse("cron: 0 9 * * MON").unwrap(),
+            Some(Schedule::Cron {
+                expression: "0 9 * * MON".into()
+            })
+        );
+        // The colon works for the older kinds too
+        assert_eq!(
+            Schedule::parse("daily: 09:00").unwrap(),
+            Some(Schedule::Daily { at: hm(9, 0) })
+        );
+        assert_eq!(
+            Schedule::parse("every: 90m").unwrap().unwrap().to_string(),
+            "every 90m"
+        );
+        assert_eq!(
+            Schedule::parse("every: 48h").unwrap().unwrap().to_string(),
+            "every 2d"
+        );
+    }
+
+    #[test]
+    fn other_brackets_are_not_schedules() {
+        assert_eq!(Schedule::parse("URGENT").unwrap(), None);
+        assert_eq!(Schedule::parse("").unwrap(), None);
+        assert_eq!(Schedule::parse("monthly 1").unwrap(), None);
+        assert_eq!(Schedule::parse("note: call Bob").unwrap(), None);
+    }
+
+    #[test]
+    fn malformed_annotations_are_errors() {
+        for bad in [
+            "daily 25:00",
+            "daily 9am",
+            "daily 09:00 18:00",
+            "weekly",
+            "weekly funday",
+            "weekly mon 7",
+            "weekly mon 09:00 extra",
+            "every",
+            "every: 30",
+            "every: 0m",
+            "every: 30 m",
+            "every: 5w",
+            "every: 分m",
+            "cron:",
+            "cron: 0 9 * *",
+            "cron: 61 9 * * 1",
+        ] {
+            assert!(Schedule::parse(bad).is_err(), "{bad} should not parse");
+        }
+    }
+
+    #[test]
+    fn daily_slot_is_today_once_the_time_has_passed() {
+        let schedule = Schedule::Daily { at: hm(9, 0) };
+        assert_eq!(
+            schedule.last_slot(at((2026, 3, 4), (8, 59))),
+            Some(at((2026, 3, 3), (9, 0)))
+        );
+        assert_eq!(
+            schedule.last_slot(at((2026, 3, 4), (9, 0))),
+            Some(at((2026, 3, 4), (9, 0)))
+        );
+    }
+
+    #[test]
+    fn weekly_slot_goes_back_to_the_last_matching_day() {
+        // 2026-03-04 is a Wednesday
+        let schedule = Schedule::Weekly {
+            day: Weekday::Mon,
+            at: hm(9, 0),
+        };
+        assert_eq!(
+            schedule.last_slot(at((2026, 3, 4), (12, 0))),
+            Some(at((2026, 3, 2), (9, 0)))
+        );
+        // Monday before 09:00 → the previous Monday
+        assert_eq!(
+            schedule.last_slot(at((2026, 3, 2), (8, 0))),
+            Some(at((2026, 2, 23), (9, 0)))
+        );
+    }
+
+    #[test]
+    fn due_only_once_per_slot() {
+        let schedule = Schedule::Weekly {
+            day: Weekday::Mon,
+            at: hm(9, 0),
+        };
+        let now = at((2026, 3, 4), (12, 0));
+        assert!(schedule.is_due(None, now));
+        assert!(schedule.is_due(Some(at((2026, 3, 1), (12, 0))), now));
+        assert!(!schedule.is_due(Some(at((2026, 3, 2), (9, 30))), now));
+        // The next Monday's slot makes it due again
+        assert!(schedule.is_due(Some(at((2026, 3, 2), (9, 30))), at((2026, 3, 9), (9, 0))));
+    }
+
+    #[test]
+    fn every_is_due_once_the_interval_has_passed() {
+        let schedule = Schedule::Every { minutes: 30 };
+        let last = at((2026, 3, 4), (9, 0));
+        assert!(schedule.is_due(None, last));
+        assert!(!schedule.is_due(Some(last), at((2026, 3, 4), (9, 29))));
+        assert!(schedule.is_due(Some(last), at((2026, 3, 4), (9, 30))));
+        assert_eq!(schedule.last_slot(last), None);
+    }
+
+    #[test]
+    fn cron_slot_is_the_previous_match() {
+        // Mondays at 09:00; 2026-03-04 is a Wednesday
+        let schedule = Schedule::parse("cron: 0 9 * * MON").unwrap().unwrap();
+        assert_eq!(
+            schedule.last_slot(at((2026, 3, 4), (12, 0))),
+            Some(at((2026, 3, 2), (9, 0)))
+        );
+        let now = at((2026, 3, 4), (12, 0));
+        assert!(!schedule.is_due(Some(at((2026, 3, 2), (9, 1))), now));
+        assert!(schedule.is_due(Some(at((2026, 3, 2), (8, 0))), now));
+    }
+}
@@ -1,7 +1,9 @@
 pub mod registry;
 
 use crate::config::Config;
+use crate::util::edit_distance;
 use anyhow::Result;
+use std::fmt::Write;
 
 /// Integration status
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,6 +16,42 @@ pub enum IntegrationStatus {
     ComingSoon,
 }
 
+impl IntegrationStatus {
+    /// Name used by `--status` and in JSON output
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Available => "available",
+            Self::ComingSoon => "coming-soon",
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Active => "✅",
+            Self::Available => "⚪",
+            Self::ComingSoon => "🔜",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Active => "已激活",
+            Self::Available => "可用",
+            Self::ComingSoon => "即将推出",
+        }
+    }
+
+    fn parse(raw: &str) -> Result<Self> {
+        [Self::Active, Self::Available, Self::ComingSoon]
+            .into_iter()
+            .find(|status| normalize(status.key()) == normalize(raw))
+            .ok_or_else(|| {
+                anyhow::anyhow!("未知状态: {raw}（可选 active、available、coming-soon）")
+            })
+    }
+}
+
 /// Integration category
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum IntegrationCategory {
@@ -43,6 +81,35 @@ impl IntegrationCategory {
         }
     }
 
+    /// English name accepted by `--category` alongside the label
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Chat => "chat",
+            Self::AiModel => "ai-model",
+            Self::Productivity => "productivity",
+            Self::MusicAudio => "music-audio",
+            Self::SmartHome => "smart-home",
+            Self::ToolsAutomation => "tools-automation",
+            Self::MediaCreative => "media-creative",
+            Self::Social => "social",
+            Self::Platform => "platform",
+        }
+    }
+
+    /// The category named `raw` by key or label, ignoring case, spaces,
+    /// `-` and `_`.
+    fn parse(raw: &str) -> Result<Self> {
+        let wanted = normalize(raw);
+        Self::all()
+            .iter()
+            .copied()
+            .find(|cat| normalize(cat.key()) == wanted || normalize(cat.label()) == wanted)
+            .ok_or_else(|| {
+                let keys: Vec<&str> = Self::all().iter().map(|cat| cat.key()).collect();
+                anyhow::anyhow!("未知分类: {raw}（可选 {}）", keys.join("、"))
+            })
+    }
+
     pub fn all() -> &'static [Self] {
         &[
             Self::Chat,
@@ -66,108 +133,318 @@ pub struct IntegrationEntry {
     pub status_fn: fn(&Config) -> IntegrationStatus,
 }
 
+fn normalize(raw: &str) -> String {
+    raw.chars()
+        .filter(|c| !matches!(c, '-' | '_' | ' '))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
 /// Handle the `integrations` CLI command
 pub fn handle_command(command: crate::IntegrationCommands, config: &Config) -> Result<()> {
     match command {
+        crate::IntegrationCommands::List {
+            category,
+            status,
+            json,
+        } => list_integrations(config, category.as_deref(), status.as_deref(), json),
         crate::IntegrationCommands::Info { name } => show_integration_info(config, &name),
     }
 }
 
-fn show_integration_info(config: &Config, name: &str) -> Result<()> {
+/// Registry entries with their status for `config`, keeping those in
+/// `category` and with `status` when given.
+fn filter_integrations<'a>(
+    entries: &'a [IntegrationEntry],
+    config: &Config,
+    category: Option<&str>,
+    status: Option<&str>,
+) -> Result<Vec<(&'a IntegrationEntry, IntegrationStatus)>> {
+    let category = category.map(IntegrationCategory::parse).transpose()?;
+    let status = status.map(IntegrationStatus::parse).transpose()?;
+    Ok(entries
+        .iter()
+        .filter(|entry| category.is_none_or(|c| entry.category == c))
+        .map(|entry| (entry, (entry.status_fn)(config)))
+        .filter(|(_, s)| status.is_none_or(|wanted| *s == wanted))
+        .collect())
+}
+
+fn list_integrations(
+    config: &Config,
+    category: Option<&str>,
+    status: Option<&str>,
+    json: bool,
+) -> Result<()> {
     let entries = registry::all_integrations();
-    let name_lower = name.to_lowercase();
+    let matched = filter_integrations(&entries, config, category, status)?;
+
+    if json {
+        let items: Vec<serde_json::Value> = matched
+            .iter()
+            .map(|(entry, status)| {
+                serde_json::json!({
+                    "name": entry.name,
+                    "description": entry.description,
+                    "category": entry.category.key(),
+                    "status": status.key(),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&items)?);
+        return Ok(());
+    }
 
-    let Some(entry) = entries.iter().find(|e| e.name.to_lowercase() == name_lower) else {
-        anyhow::bail!(
-            "未知集成: {name}。请查看 README 了解支持的集成，或运行 `jarvis onboard --interactive` 配置通道/提供商。"
+    print!("{}", render_list(&matched));
+    Ok(())
+}
+
+/// The `list` output: entries grouped by category, with a legend.
+fn render_list(matched: &[(&IntegrationEntry, IntegrationStatus)]) -> String {
+    if matched.is_empty() {
+        return "没有符合条件的集成。\n".to_string();
+    }
+    let mut out = String::new();
+    for cat in IntegrationCategory::all() {
+        let in_category: Vec<_> = matched.iter().filter(|(e, _)| e.category == *cat).collect();
+        if in_category.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "  {} ({})",
+            console::style(cat.label()).white().bold(),
+            in_category.len()
         );
-    };
+        for (entry, status) in in_category {
+            let _ = writeln!(
+                out,
+                "    {} {:<18} {}",
+                status.icon(),
+                entry.name,
+                entry.description
+            );
+        }
+    }
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "  共 {} 个 · ✅ 已激活  ⚪ 可用  🔜 即将推出 · 详情: jarvis integrations info <name>",
+        matched.len()
+    );
+    let _ = writeln!(out);
+    out
+}
 
-    let status = (entry.status_fn)(config);
-    let (icon, label) = match status {
-        IntegrationStatus::Active => ("✅", "已激活"),
-        IntegrationStatus::Available => ("⚪", "可用"),
-        IntegrationStatus::ComingSoon => ("🔜", "即将推出"),
-    };
+fn show_integration_info(config: &Config, name: &str) -> Result<()> {
+    let entries = registry::all_integrations();
+    let entry = find_integration(&entries, name)?;
+    print!("{}", render_info(entry, (entry.status_fn)(config)));
+    Ok(())
+}
+
+/// The entry called `name`, ignoring case, spaces, `-` and `_`. Otherwise
+/// the error suggests the closest name, if one is close enough to be a typo.
+fn find_integration<'a>(
+    entries: &'a [IntegrationEntry],
+    name: &str,
+) -> Result<&'a IntegrationEntry> {
+    let wanted = normalize(name);
+    if let Some(entry) = entries.iter().find(|e| normalize(e.name) == wanted) {
+        return Ok(entry);
+    }
+    let typos = (wanted.chars().count() / 3).max(2);
+    let closest = entries
+        .iter()
+        .map(|e| (edit_distance(&normalize(e.name), &wanted), e.name))
+        .min_by_key(|(distance, _)| *distance)
+        .filter(|(distance, _)| *distance <= typos && *distance < wanted.chars().count());
+    match closest {
+        Some((_, suggestion)) => anyhow::bail!(
+            "未知集成: {name}。你是不是要找 {suggestion}？运行 `jarvis integrations list` 查看全部集成。"
+        ),
+        None => anyhow::bail!(
+            "未知集成: {name}。运行 `jarvis integrations list` 查看全部集成，或运行 `jarvis onboard --interactive` 配置通道/提供商。"
+        ),
+    }
+}
 
-    println!();
-    println!(
+/// Setup hints shown by `info`, by integration name.
+fn setup_steps(name: &str) -> &'static [&'static str] {
+    match name {
+        "Telegram" => &[
+            "  配置步骤:",
+            "    1. 在 Telegram 上联系 @BotFather",
+            "    2. 创建机器人并复制 token",
+            "    3. 运行: jarvis onboard",
+            "    4. 启动: jarvis channel start",
+        ],
+        "Discord" => &[
+            "  配置步骤:",
+            "    1. 前往 https://discord.com/developers/applications",
+            "    2. 创建应用 → Bot → 复制 token",
+            "    3. 启用 MESSAGE CONTENT intent",
+            "    4. 运行: jarvis onboard",
+        ],
+        "Slack" => &[
+            "  配置步骤:",
+            "    1. 前往 https://api.slack.com/apps",
+            "    2. 创建应用 → Bot Token Scopes → 安装",
+            "    3. 运行: jarvis onboard",
+        ],
+        "OpenRouter" => &[
+            "  配置步骤:",
+            "    1. 在 https://openrouter.ai/keys 获取 API key",
+            "    2. 运行: jarvis onboard",
+            "    一个 API key 即可访问 200+ 模型。",
+        ],
+        "Ollama" => &[
+            "  配置步骤:",
+            "    1. 安装: brew install ollama",
+            "    2. 拉取模型: ollama pull llama3",
+            "    3. 在 config.toml 中设置 provider 为 'ollama'",
+        ],
+        "iMessage" => &[
+            "  配置步骤 (仅限 macOS):",
+            "    通过 AppleScript 桥接收发 iMessage。",
+            "    需要在「系统设置 → 隐私」中授予「完全磁盘访问权限」。",
+        ],
+        "GitHub" => &[
+            "  配置步骤:",
+            "    1. 在 https://github.com/settings/tokens 创建个人访问令牌",
+            "    2. 添加到配置: [integrations.github] token = \"ghp_...\"",
+        ],
+        "Browser" => &[
+            "  内置功能:",
+            "    Jarvis 可控制 Chrome/Chromium 执行网页任务。",
+            "    使用无头浏览器自动化。",
+        ],
+        "Cron" => &[
+            "  内置功能:",
+            "    在 ~/.jarvis/workspace/cron/ 中调度任务。",
+            "    运行: jarvis cron list",
+        ],
+        "Webhooks" => &[
+            "  内置功能:",
+            "    用于外部触发的 HTTP 端点。",
+            "    运行: jarvis gateway",
+        ],
+        _ => &[],
+    }
+}
+
+/// The `info` output: status, category and how to set the integration up.
+fn render_info(entry: &IntegrationEntry, status: IntegrationStatus) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
         "  {} {} — {}",
-        icon,
+        status.icon(),
         console::style(entry.name).white().bold(),
         entry.description
     );
-    println!("  分类: {}", entry.category.label());
-    println!("  状态: {label}");
-    println!();
+    let _ = writeln!(out, "  分类: {}", entry.category.label());
+    let _ = writeln!(out, "  状态: {}", status.label());
+    let _ = writeln!(out);
 
     // 根据集成类型显示配置提示
-    match entry.name {
-        "Telegram" => {
-            println!("  配置步骤:");
-            println!("    1. 在 Telegram 上联系 @BotFather");
-            println!("    2. 创建机器人并复制 token");
-            println!("    3. 运行: jarvis onboard");
-            println!("    4. 启动: jarvis channel start");
-        }
-        "Discord" => {
-            println!("  配置步骤:");
-            println!("    1. 前往 https://discord.com/developers/applications");
-            println!("    2. 创建应用 → Bot → 复制 token");
-            println!("    3. 启用 MESSAGE CONTENT intent");
-            println!("    4. 运行: jarvis onboard");
-        }
-        "Slack" => {
-            println!("  配置步骤:");
-            println!("    1. 前往 https://api.slack.com/apps");
-            println!("    2. 创建应用 → Bot Token Scopes → 安装");
-            println!("    3. 运行: jarvis onboard");
-        }
-        "OpenRouter" => {
-            println!("  配置步骤:");
-            println!("    1. 在 https://openrouter.ai/keys 获取 API key");
-            println!("    2. 运行: jarvis onboard");
-            println!("    一个 API key 即可访问 200+ 模型。");
-        }
-        "Ollama" => {
-            println!("  配置步骤:");
-            println!("    1. 安装: brew install ollama");
-            println!("    2. 拉取模型: ollama pull llama3");
-            println!("    3. 在 config.toml 中设置 provider 为 'ollama'");
-        }
-        "iMessage" => {
-            println!("  配置步骤 (仅限 macOS):");
-            println!("    通过 AppleScript 桥接收发 iMessage。");
-            println!("    需要在「系统设置 → 隐私」中授予「完全磁盘访问权限」。");
-        }
-        "GitHub" => {
-            println!("  配置步骤:");
-            println!("    1. 在 https://github.com/settings/tokens 创建个人访问令牌");
-            println!("    2. 添加到配置: [integrations.github] token = \"ghp_...\"");
-        }
-        "Browser" => {
-            println!("  内置功能:");
-            println!("    Jarvis 可控制 Chrome/Chromium 执行网页任务。");
-            println!("    使用无头浏览器自动化。");
-        }
-        "Cron" => {
-            println!("  内置功能:");
-            println!("    在 ~/.jarvis/workspace/cron/ 中调度任务。");
-            println!("    运行: jarvis cron list");
-        }
-        "Webhooks" => {
-            println!("  内置功能:");
-            println!("    用于外部触发的 HTTP 端点。");
-            println!("    运行: jarvis gateway");
+    let steps = setup_steps(entry.name);
+    for line in steps {
+        let _ = writeln!(out, "{line}");
+    }
+    if steps.is_empty() && status == IntegrationStatus::ComingSoon {
+        let _ = writeln!(out, "  此集成正在规划中，敬请期待！");
+        let _ = writeln!(out, "  跟踪进度: https://github.com/Afee2019/jarvis");
+    }
+
+    let _ = writeln!(out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filtering_by_chat_returns_only_chat_entries() {
+        let entries = registry::all_integrations();
+        let config = Config::default();
+        for name in ["Chat", "chat", "聊天通道"] {
+            let chat = filter_integrations(&entries, &config, Some(name), None).unwrap();
+            assert!(!chat.is_empty());
+            assert!(chat
+                .iter()
+                .all(|(e, _)| e.category == IntegrationCategory::Chat));
+            assert_eq!(
+                chat.len(),
+                entries
+                    .iter()
+                    .filter(|e| e.category == IntegrationCategory::Chat)
+                    .count()
+            );
         }
-        _ => {
-            if status == IntegrationStatus::ComingSoon {
-                println!("  此集成正在规划中，敬请期待！");
-                println!("  跟踪进度: https://github.com/Afee2019/jarvis");
-            }
+    }
+
+    #[test]
+    fn filtering_by_status_and_unknown_names() {
+        let entries = registry::all_integrations();
+        let config = Config::default();
+        let soon = filter_integrations(&entries, &config, None, Some("coming_soon")).unwrap();
+        assert!(!soon.is_empty());
+        assert!(soon
+            .iter()
+            .all(|(_, s)| *s == IntegrationStatus::ComingSoon));
+        let all = filter_integrations(&entries, &config, None, None).unwrap();
+        assert_eq!(all.len(), entries.len());
+
+        let Err(err) = filter_integrations(&entries, &config, Some("fax"), None) else {
+            panic!("unknown category should be rejected");
+        };
+        assert!(err.to_string().contains("smart-home"), "{err}");
+        assert!(filter_integrations(&entries, &config, None, Some("done")).is_err());
+    }
+
+    #[test]
+    fn every_entry_renders_under_a_default_config() {
+        let entries = registry::all_integrations();
+        let config = Config::default();
+        let all = filter_integrations(&entries, &config, None, None).unwrap();
+        let list = render_list(&all);
+        for (entry, status) in &all {
+            assert!(
+                list.contains(entry.name),
+                "{} missing from the list",
+                entry.name
+            );
+            let info = render_info(entry, *status);
+            assert!(info.contains(entry.name) && info.contains(status.label()));
+            assert!(info.contains(entry.category.label()));
         }
+        assert!(list.contains(&format!("共 {} 个", entries.len())));
+        assert_eq!(render_list(&[]), "没有符合条件的集成。\n");
     }
 
-    println!();
-    Ok(())
+    #[test]
+    fn info_finds_names_loosely_and_suggests_close_ones() {
+        let entries = registry::all_integrations();
+        let error = |name: &str| find_integration(&entries, name).err().unwrap().to_string();
+        assert_eq!(
+            find_integration(&entries, "telegram").unwrap().name,
+            "Telegram"
+        );
+        assert_eq!(
+            find_integration(&entries, "open-router").unwrap().name,
+            "OpenRouter"
+        );
+
+        let err = error("telgram");
+        assert!(err.contains("你是不是要找 Telegram？"), "{err}");
+        let err = error("Slakc");
+        assert!(err.contains("你是不是要找 Slack？"), "{err}");
+        let err = error("fax machine");
+        assert!(!err.contains("你是不是要找"), "{err}");
+    }
 }
@@ -16,6 +16,7 @@ use serde::{Deserialize, Serialize};
 
 pub mod agent;
 pub mod channels;
+pub mod completions;
 pub mod config;
 pub mod cron;
 pub mod daemon;
@@ -26,16 +27,20 @@ pub mod heartbeat;
 pub mod integrations;
 pub mod memory;
 pub mod migration;
+pub mod notify;
 pub mod observability;
 pub mod onboard;
 pub mod providers;
 pub mod runtime;
 pub mod security;
 pub mod service;
+pub mod sessions;
 pub mod skills;
+pub mod tasks;
 pub mod tools;
 pub mod tui;
 pub mod tunnel;
+pub mod usage;
 pub mod util;
 
 pub use config::Config;
@@ -44,7 +49,11 @@ pub use config::Config;
 #[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ServiceCommands {
     /// 安装守护进程服务单元，支持自动启动和重启
-    Install,
+    Install {
+        /// 安装为系统级服务（开机启动，无需登录；需要 root）
+        #[arg(long)]
+        system: bool,
+    },
     /// 启动守护进程服务
     Start,
     /// 停止守护进程服务
@@ -64,6 +73,11 @@ pub enum ChannelCommands {
     Start,
     /// 运行已配置通道的健康检查（在 main.rs 中异步处理）
     Doctor,
+    /// 通过通道发送一条测试消息（在 main.rs 中异步处理）
+    Test {
+        /// 通道（telegram、discord、slack、matrix、whatsapp、imessage）
+        name: String,
+    },
     /// 添加新的通道配置
     Add {
         /// 通道类型（telegram、discord、slack、whatsapp、matrix、imessage、email）
@@ -76,6 +90,20 @@ pub enum ChannelCommands {
         /// 要移除的通道名称
         name: String,
     },
+    /// 将用户加入通道白名单
+    Allow {
+        /// 通道（telegram、discord、slack、matrix、whatsapp、irc）
+        channel: String,
+        /// 用户标识（Telegram 用户名或 ID、Discord ID、Slack 成员 ID、Matrix MXID、WhatsApp 号码、IRC 昵称）
+        user: String,
+    },
+    /// 将用户移出通道白名单
+    Deny {
+        /// 通道（telegram、discord、slack、matrix、whatsapp、irc）
+        channel: String,
+        /// 用户标识
+        user: String,
+    },
 }
 
 /// 技能管理子命令
@@ -85,8 +113,22 @@ pub enum SkillCommands {
     List,
     /// 从 URL 或本地路径安装新技能
     Install {
-        /// 来源 URL 或本地路径
+        /// 来源 URL（可加 @<tag 或 commit> 固定版本）或本地路径
         source: String,
+        /// 覆盖同名的已安装技能
+        #[arg(long)]
+        force: bool,
+    },
+    /// 更新通过 Git 安装的技能（显示改动并确认）
+    Update {
+        /// 技能目录名（默认全部）
+        name: Option<String>,
+        /// 跳过确认提示
+        #[arg(long)]
+        yes: bool,
+        /// 丢弃技能目录中的本地修改并更新
+        #[arg(long)]
+        force: bool,
     },
     /// 移除已安装的技能
     Remove {
@@ -98,7 +140,7 @@ pub enum SkillCommands {
 /// 迁移子命令
 #[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum MigrateCommands {
-    /// 从 `OpenClaw` 工作区导入记忆到当前 `Jarvis` 工作区
+    /// 从 `OpenClaw` 工作区导入记忆、定时任务和技能到当前 `Jarvis` 工作区
     Openclaw {
         /// `OpenClaw` 工作区路径（可选，默认 ~/.openclaw/workspace）
         #[arg(long)]
@@ -108,6 +150,35 @@ pub enum MigrateCommands {
         #[arg(long)]
         dry_run: bool,
     },
+    /// 从 `ChatGPT` 数据导出（conversations.json）导入对话作为记忆
+    Chatgpt {
+        /// conversations.json 路径，或解压后的导出目录
+        #[arg(long)]
+        source: std::path::PathBuf,
+
+        /// 仅预览将导入的对话数量，不写入任何数据
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// 将配置、记忆、定时任务、工作区文件和技能打包为迁移归档
+    Export {
+        /// 归档输出路径
+        #[arg(long, default_value = "jarvis-backup.tar.gz")]
+        output: std::path::PathBuf,
+
+        /// 在归档中保留密钥（默认脱敏）
+        #[arg(long)]
+        include_secrets: bool,
+    },
+    /// 从迁移归档恢复配置和工作区
+    Import {
+        /// `jarvis migrate export` 生成的归档路径
+        archive: std::path::PathBuf,
+
+        /// 覆盖非空工作区（原工作区会被移动为备份）
+        #[arg(long)]
+        force: bool,
+    },
 }
 
 /// 定时任务子命令
@@ -127,14 +198,248 @@ pub enum CronCommands {
         /// 任务 ID
         id: String,
     },
+    /// 校验 Cron 表达式并预览接下来的执行时间
+    Validate {
+        /// Cron 表达式
+        expression: String,
+        /// 预览的执行次数（最多 100）
+        #[arg(long, default_value = "5")]
+        count: usize,
+    },
+}
+
+/// 任务清单子命令
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TaskCommands {
+    /// 列出任务（默认仅未完成）
+    List {
+        /// 同时列出已完成的任务
+        #[arg(long)]
+        all: bool,
+    },
+    /// 添加任务
+    Add {
+        /// 任务标题
+        title: String,
+        /// 截止时间（YYYY-MM-DD、YYYY-MM-DD HH:MM 或 RFC 3339）
+        #[arg(long)]
+        due: Option<String>,
+        /// 备注
+        #[arg(long)]
+        notes: Option<String>,
+    },
+    /// 将任务标记为已完成
+    Done {
+        /// 任务 ID
+        id: i64,
+    },
+    /// 删除任务
+    Rm {
+        /// 任务 ID
+        id: i64,
+    },
+}
+
+/// 对话记录子命令
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SessionCommands {
+    /// 列出对话记录（最新在前）
+    List {
+        /// 仅显示该来源的记录（cli、tui、gateway、telegram、cron…）
+        #[arg(long)]
+        origin: Option<String>,
+        /// 最多显示的条数
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// 显示一次对话的完整记录
+    Show {
+        /// 对话 ID（见 sessions list）
+        id: String,
+    },
+}
+
+/// Profile 子命令
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ProfileCommands {
+    /// 列出所有 profile（* 标记当前使用的）
+    List,
+    /// 创建新的 profile（独立的配置、工作区、记忆和守护进程）
+    Create {
+        /// Profile 名称（字母、数字、- 和 _）
+        name: String,
+    },
+}
+
+/// 通知子命令
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum NotifyCommands {
+    /// 向已配置的通知渠道发送一条测试消息
+    Test {
+        /// 自定义测试消息内容
+        #[arg(long)]
+        message: Option<String>,
+    },
+}
+
+/// 记忆管理子命令
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MemoryCommands {
+    /// 列出记忆条目
+    List {
+        /// 按分类过滤（core、daily、conversation 或自定义分类）
+        #[arg(long)]
+        category: Option<String>,
+        /// 最多显示的条目数
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// 按关键词搜索记忆
+    Search {
+        /// 搜索关键词
+        query: String,
+        /// 最多返回的条目数
+        #[arg(long, default_value = "10")]
+        limit: usize,
+        /// 仅返回带有该标签的记忆（可重复，需同时匹配全部标签）
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// 显示指定记忆的完整内容
+    Show {
+        /// 记忆键名
+        key: String,
+    },
+    /// 删除指定记忆
+    Forget {
+        /// 记忆键名
+        key: String,
+        /// 跳过确认提示
+        #[arg(long)]
+        yes: bool,
+    },
+    /// 导出全部记忆（默认输出到标准输出）
+    Export {
+        /// 导出格式（json、markdown）
+        #[arg(long, default_value = "json")]
+        format: String,
+        /// 写入文件而不是标准输出
+        #[arg(long)]
+        out: Option<std::path::PathBuf>,
+    },
+    /// 从 JSON 导出文件导入记忆到当前后端（按 key + 内容去重）
+    Import {
+        /// `jarvis memory export` 生成的 JSON 文件
+        file: std::path::PathBuf,
+    },
+    /// 为缺少向量的记忆补生成 embedding（仅 sqlite 后端，可中断后继续）
+    Reindex {
+        /// 忽略已有向量，使用当前模型重新生成全部 embedding
+        #[arg(long)]
+        force: bool,
+        /// 每批发送给 embedding API 的条目数
+        #[arg(long, default_value = "32")]
+        batch_size: usize,
+        /// 每分钟最多调用 embedding API 的次数（0 表示不限）
+        #[arg(long, default_value = "60")]
+        requests_per_minute: u32,
+    },
+    /// 立即执行记忆清理（归档旧的 daily/conversation 记忆并清除过期归档）
+    Hygiene {
+        /// 仅预览将被归档/删除的内容，不做任何修改
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 /// 集成子命令
 #[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum IntegrationCommands {
+    /// 按分类列出集成及其状态
+    List {
+        /// 只显示该分类（如 chat、ai-model 或「聊天通道」）
+        #[arg(long)]
+        category: Option<String>,
+        /// 只显示该状态（active、available、coming-soon）
+        #[arg(long)]
+        status: Option<String>,
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
     /// 显示指定集成的详细信息
     Info {
         /// 集成名称
         name: String,
     },
 }
+
+/// 配置管理子命令
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ConfigCommands {
+    /// 读取配置项（点分路径，如 `heartbeat.interval_minutes`）
+    Get {
+        /// 配置项路径
+        key: String,
+        /// 显示密钥的明文
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// 设置配置项，值按该字段的类型解析
+    Set {
+        /// 配置项路径
+        key: String,
+        /// 新值
+        value: String,
+    },
+    /// 删除配置项，恢复默认值
+    Unset {
+        /// 配置项路径
+        key: String,
+    },
+    /// 严格校验 config.toml，列出所有问题（包括未知字段）
+    Validate,
+    /// 从 backups/ 中的备份恢复 config.toml（默认最近一份）
+    Restore {
+        /// 备份的文件名或 --list 中的序号
+        backup: Option<String>,
+        /// 列出备份，不恢复
+        #[arg(long)]
+        list: bool,
+    },
+}
+
+/// 安全相关子命令
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SecurityCommands {
+    /// 查看工具调用审计日志
+    Audit {
+        /// 时间范围（如 30m、24h、7d）
+        #[arg(long, default_value = "24h")]
+        since: String,
+        /// 仅显示指定工具
+        #[arg(long)]
+        tool: Option<String>,
+        /// 仅显示被拒绝的调用
+        #[arg(long)]
+        denied: bool,
+    },
+    /// 生成新的密钥文件，并用它重新加密 config.toml 中的所有密钥
+    RotateKey,
+}
+
+/// 审计日志子命令
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AuditCommands {
+    /// 显示最近的审计记录
+    Tail {
+        /// 显示条数
+        #[arg(short = 'n', long, default_value_t = 20)]
+        lines: usize,
+    },
+    /// 搜索工具名、来源、原因或参数中包含关键词的记录（不区分大小写）
+    Grep {
+        /// 关键词
+        term: String,
+    },
+}
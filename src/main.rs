@@ -9,13 +9,15 @@
 )]
 
 use anyhow::{bail, Context, Result};
-use clap::{Parser, Subcommand};
-use tracing::{info, Level};
+use clap::{CommandFactory, Parser, Subcommand};
+use tracing::info;
 use tracing_subscriber::fmt::time::FormatTime;
-use tracing_subscriber::FmtSubscriber;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::EnvFilter;
 
 mod agent;
 mod channels;
+mod completions;
 mod config;
 mod cron;
 mod daemon;
@@ -26,17 +28,21 @@ mod heartbeat;
 mod integrations;
 mod memory;
 mod migration;
+mod notify;
 mod observability;
 mod onboard;
 mod providers;
 mod runtime;
 mod security;
 mod service;
+mod sessions;
 mod skillforge;
 mod skills;
+mod tasks;
 mod tools;
 mod tui;
 mod tunnel;
+mod usage;
 mod util;
 
 use config::Config;
@@ -50,12 +56,24 @@ use config::Config;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// 日志级别或过滤规则（如 debug、`info,jarvis::channels=debug`），覆盖 [logging] level；设置了 `RUST_LOG` 时以其为准
+    #[arg(long, global = true)]
+    log_level: Option<String>,
+
+    /// 使用指定 profile（~/.jarvis/profiles/<name>），也可通过 `JARVIS_PROFILE` 设置
+    #[arg(long, global = true)]
+    profile: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
 enum ServiceCommands {
     /// 安装守护进程服务单元，支持自动启动和重启
-    Install,
+    Install {
+        /// 安装为系统级服务（开机启动，无需登录；需要 root）
+        #[arg(long)]
+        system: bool,
+    },
     /// 启动守护进程服务
     Start,
     /// 停止守护进程服务
@@ -89,6 +107,9 @@ enum Commands {
         /// 记忆后端（sqlite、markdown、none）- 快速模式下使用，默认：sqlite
         #[arg(long)]
         memory: Option<String>,
+
+        #[command(flatten)]
+        channels: Box<onboard::QuickChannels>,
     },
 
     /// 启动 AI agent 循环
@@ -127,10 +148,21 @@ enum Commands {
         /// 温度参数（0.0 - 2.0）
         #[arg(short, long, default_value = "0.7")]
         temperature: f64,
+
+        /// 恢复已保存的会话（省略名称则恢复最近的会话）
+        #[arg(long, value_name = "NAME", num_args = 0..=1, default_missing_value = "")]
+        resume: Option<String>,
+
+        /// 关闭回复的 Markdown 渲染（显示原始文本）
+        #[arg(long)]
+        no_markdown: bool,
     },
 
     /// 启动 Gateway 服务器（webhooks、websockets）
     Gateway {
+        #[command(subcommand)]
+        gateway_command: Option<GatewayCommands>,
+
         /// 监听端口（使用 0 表示随机可用端口）
         #[arg(short, long, default_value = "8299")]
         port: u16,
@@ -157,27 +189,111 @@ enum Commands {
         /// 停止正在运行的守护进程
         #[arg(long)]
         stop: bool,
+
+        /// 重启已熔断的组件（修复问题后使用）
+        #[arg(long)]
+        reset: bool,
+
+        /// 显示后台守护进程日志的末尾几行
+        #[arg(long)]
+        logs: bool,
+
+        /// 配合 --logs：每个日志文件显示的行数
+        #[arg(short = 'n', long, default_value = "50", requires = "logs")]
+        lines: usize,
+
+        /// 从指定目录加载配置（供 Windows 服务使用）
+        #[arg(long, hide = true)]
+        config_dir: Option<std::path::PathBuf>,
     },
 
-    /// 管理操作系统服务生命周期（launchd/systemd 用户服务）
+    /// 管理操作系统服务生命周期（launchd/systemd 用户服务、Windows 服务）
+    #[command(after_help = "示例:
+  jarvis service install           # 安装为用户服务
+  sudo jarvis service install --system
+  jarvis service start
+  jarvis service status")]
     Service {
         #[command(subcommand)]
         service_command: ServiceCommands,
     },
 
-    /// 运行诊断检查（守护进程/调度器/通道健康状态）
-    Doctor,
+    /// 运行诊断检查（守护进程/调度器/通道健康状态），发现问题时退出码非零
+    Doctor {
+        /// 不输出报告，仅通过退出码反映结果
+        #[arg(short, long, conflicts_with = "json")]
+        quiet: bool,
+        /// 以 JSON 输出诊断结果
+        #[arg(long)]
+        json: bool,
+        /// 额外在线检查 Provider API key 和各通道 token（需要网络）
+        #[arg(long)]
+        deep: bool,
+    },
 
     /// 显示系统状态（完整详情）
-    Status,
+    Status {
+        /// 以 JSON 输出（便于脚本和仪表盘读取）
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// 列出 Provider 可用的模型（含上下文长度和价格）
+    Models {
+        /// Provider 名称（默认为当前配置的 Provider）
+        #[arg(long)]
+        provider: Option<String>,
+    },
+
+    /// 按 Provider/模型汇总 Token 用量和估算费用
+    Usage {
+        /// 起始日期（YYYY-MM-DD，含当天）；默认汇总全部记录
+        #[arg(long)]
+        since: Option<String>,
+    },
 
     /// 配置和管理定时任务
+    #[command(after_help = "示例:
+  jarvis cron add '0 9 * * *' 'agent -m \"Good morning!\"'
+  jarvis cron add '*/30 * * * *' 'agent -m \"检查收件箱\"'
+  jarvis cron list
+  jarvis cron validate '0 9 * * 1-5' --count 3
+  jarvis cron remove <ID>          # ID 见 cron list")]
     Cron {
         #[command(subcommand)]
         cron_command: CronCommands,
     },
 
+    /// 管理任务清单（与 Agent 的 task_* 工具共用）
+    Tasks {
+        #[command(subcommand)]
+        task_command: TaskCommands,
+    },
+
+    /// 管理 profile（各自独立的配置、工作区和守护进程）
+    Profile {
+        #[command(subcommand)]
+        profile_command: ProfileCommands,
+    },
+
+    /// 查看 sessions/ 下记录的完整对话
+    Sessions {
+        #[command(subcommand)]
+        session_command: SessionCommands,
+    },
+
+    /// 测试心跳和定时任务的结果通知
+    Notify {
+        #[command(subcommand)]
+        notify_command: NotifyCommands,
+    },
+
     /// 管理通道（telegram、discord、slack）
+    #[command(after_help = "示例:
+  jarvis channel list
+  jarvis channel doctor
+  jarvis channel allow telegram alice
+  jarvis channel deny telegram alice")]
     Channel {
         #[command(subcommand)]
         channel_command: ChannelCommands,
@@ -190,21 +306,73 @@ enum Commands {
     },
 
     /// 管理技能（用户自定义能力）
+    #[command(after_help = "示例:
+  jarvis skills list
+  jarvis skills install https://github.com/user/weather-skill
+  jarvis skills remove weather-skill")]
     Skills {
         #[command(subcommand)]
         skill_command: SkillCommands,
     },
 
+    /// 查看和管理已存储的记忆（无需调用 Provider）
+    Memory {
+        #[command(subcommand)]
+        memory_command: MemoryCommands,
+    },
+
+    /// 读取、修改和校验 config.toml，无需打开编辑器
+    #[command(after_help = "示例:
+  jarvis config get default_model
+  jarvis config set default_temperature 0.3
+  jarvis config validate
+  jarvis config restore --list")]
+    Config {
+        #[command(subcommand)]
+        config_command: ConfigCommands,
+    },
+
+    /// 安全审计与密钥管理
+    Security {
+        #[command(subcommand)]
+        security_command: SecurityCommands,
+    },
+
+    /// 查看工具调用审计日志（tail、grep）
+    Audit {
+        #[command(subcommand)]
+        audit_command: AuditCommands,
+    },
+
     /// 从其他 Agent 运行时迁移数据
+    #[command(after_help = "示例:
+  jarvis migrate openclaw --dry-run
+  jarvis migrate export --output jarvis-backup.tar.gz
+  jarvis migrate import jarvis-backup.tar.gz")]
     Migrate {
         #[command(subcommand)]
         migrate_command: MigrateCommands,
     },
+
+    /// 生成 shell 补全脚本（bash、zsh、fish、powershell、elvish）
+    #[command(after_help = "示例:
+  jarvis completions bash > ~/.local/share/bash-completion/completions/jarvis
+  jarvis completions zsh > \"${fpath[1]}/_jarvis\"
+  jarvis completions fish > ~/.config/fish/completions/jarvis.fish
+  jarvis completions powershell >> $PROFILE")]
+    Completions {
+        /// 目标 shell
+        shell: clap_complete::Shell,
+    },
+
+    /// 补全脚本调用：列出当前的通道、技能或定时任务 ID
+    #[command(name = "__complete", hide = true)]
+    Complete { kind: completions::Kind },
 }
 
 #[derive(Subcommand, Debug)]
 enum MigrateCommands {
-    /// 从 `OpenClaw` 工作区导入记忆到当前 `Jarvis` 工作区
+    /// 从 `OpenClaw` 工作区导入记忆、定时任务和技能到当前 `Jarvis` 工作区
     Openclaw {
         /// `OpenClaw` 工作区路径（可选，默认 ~/.openclaw/workspace）
         #[arg(long)]
@@ -214,6 +382,35 @@ enum MigrateCommands {
         #[arg(long)]
         dry_run: bool,
     },
+    /// 从 `ChatGPT` 数据导出（conversations.json）导入对话作为记忆
+    Chatgpt {
+        /// conversations.json 路径，或解压后的导出目录
+        #[arg(long)]
+        source: std::path::PathBuf,
+
+        /// 仅预览将导入的对话数量，不写入任何数据
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// 将配置、记忆、定时任务、工作区文件和技能打包为迁移归档
+    Export {
+        /// 归档输出路径
+        #[arg(long, default_value = "jarvis-backup.tar.gz")]
+        output: std::path::PathBuf,
+
+        /// 在归档中保留密钥（默认脱敏）
+        #[arg(long)]
+        include_secrets: bool,
+    },
+    /// 从迁移归档恢复配置和工作区
+    Import {
+        /// `jarvis migrate export` 生成的归档路径
+        archive: std::path::PathBuf,
+
+        /// 覆盖非空工作区（原工作区会被移动为备份）
+        #[arg(long)]
+        force: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -232,6 +429,84 @@ enum CronCommands {
         /// 任务 ID
         id: String,
     },
+    /// 校验 Cron 表达式并预览接下来的执行时间
+    Validate {
+        /// Cron 表达式
+        expression: String,
+        /// 预览的执行次数（最多 100）
+        #[arg(long, default_value = "5")]
+        count: usize,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TaskCommands {
+    /// 列出任务（默认仅未完成）
+    List {
+        /// 同时列出已完成的任务
+        #[arg(long)]
+        all: bool,
+    },
+    /// 添加任务
+    Add {
+        /// 任务标题
+        title: String,
+        /// 截止时间（YYYY-MM-DD、YYYY-MM-DD HH:MM 或 RFC 3339）
+        #[arg(long)]
+        due: Option<String>,
+        /// 备注
+        #[arg(long)]
+        notes: Option<String>,
+    },
+    /// 将任务标记为已完成
+    Done {
+        /// 任务 ID
+        id: i64,
+    },
+    /// 删除任务
+    Rm {
+        /// 任务 ID
+        id: i64,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SessionCommands {
+    /// 列出对话记录（最新在前）
+    List {
+        /// 仅显示该来源的记录（cli、tui、gateway、telegram、cron…）
+        #[arg(long)]
+        origin: Option<String>,
+        /// 最多显示的条数
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// 显示一次对话的完整记录
+    Show {
+        /// 对话 ID（见 sessions list）
+        id: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ProfileCommands {
+    /// 列出所有 profile（* 标记当前使用的）
+    List,
+    /// 创建新的 profile（独立的配置、工作区、记忆和守护进程）
+    Create {
+        /// Profile 名称（字母、数字、- 和 _）
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum NotifyCommands {
+    /// 向已配置的通知渠道发送一条测试消息
+    Test {
+        /// 自定义测试消息内容
+        #[arg(long)]
+        message: Option<String>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -242,6 +517,11 @@ enum ChannelCommands {
     Start,
     /// 运行已配置通道的健康检查
     Doctor,
+    /// 通过通道发送一条测试消息
+    Test {
+        /// 通道（telegram、discord、slack、matrix、whatsapp、imessage）
+        name: String,
+    },
     /// 添加新通道
     Add {
         /// 通道类型
@@ -254,6 +534,20 @@ enum ChannelCommands {
         /// 通道名称
         name: String,
     },
+    /// 将用户加入通道白名单
+    Allow {
+        /// 通道（telegram、discord、slack、matrix、whatsapp、irc）
+        channel: String,
+        /// 用户标识（Telegram 用户名或 ID、Discord ID、Slack 成员 ID、Matrix MXID、WhatsApp 号码、IRC 昵称）
+        user: String,
+    },
+    /// 将用户移出通道白名单
+    Deny {
+        /// 通道（telegram、discord、slack、matrix、whatsapp、irc）
+        channel: String,
+        /// 用户标识
+        user: String,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -262,8 +556,22 @@ enum SkillCommands {
     List,
     /// 从 GitHub URL 或本地路径安装技能
     Install {
-        /// GitHub URL 或本地路径
+        /// GitHub URL（可加 @<tag 或 commit> 固定版本）或本地路径
         source: String,
+        /// 覆盖同名的已安装技能
+        #[arg(long)]
+        force: bool,
+    },
+    /// 更新通过 Git 安装的技能（显示改动并确认）
+    Update {
+        /// 技能目录名（默认全部）
+        name: Option<String>,
+        /// 跳过确认提示
+        #[arg(long)]
+        yes: bool,
+        /// 丢弃技能目录中的本地修改并更新
+        #[arg(long)]
+        force: bool,
     },
     /// 移除已安装的技能
     Remove {
@@ -272,8 +580,89 @@ enum SkillCommands {
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum MemoryCommands {
+    /// 列出记忆条目
+    List {
+        /// 按分类过滤（core、daily、conversation 或自定义分类）
+        #[arg(long)]
+        category: Option<String>,
+        /// 最多显示的条目数
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// 按关键词搜索记忆
+    Search {
+        /// 搜索关键词
+        query: String,
+        /// 最多返回的条目数
+        #[arg(long, default_value = "10")]
+        limit: usize,
+        /// 仅返回带有该标签的记忆（可重复，需同时匹配全部标签）
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// 显示指定记忆的完整内容
+    Show {
+        /// 记忆键名
+        key: String,
+    },
+    /// 删除指定记忆
+    Forget {
+        /// 记忆键名
+        key: String,
+        /// 跳过确认提示
+        #[arg(long)]
+        yes: bool,
+    },
+    /// 导出全部记忆（默认输出到标准输出）
+    Export {
+        /// 导出格式（json、markdown）
+        #[arg(long, default_value = "json")]
+        format: String,
+        /// 写入文件而不是标准输出
+        #[arg(long)]
+        out: Option<std::path::PathBuf>,
+    },
+    /// 从 JSON 导出文件导入记忆到当前后端（按 key + 内容去重）
+    Import {
+        /// `jarvis memory export` 生成的 JSON 文件
+        file: std::path::PathBuf,
+    },
+    /// 为缺少向量的记忆补生成 embedding（仅 sqlite 后端，可中断后继续）
+    Reindex {
+        /// 忽略已有向量，使用当前模型重新生成全部 embedding
+        #[arg(long)]
+        force: bool,
+        /// 每批发送给 embedding API 的条目数
+        #[arg(long, default_value = "32")]
+        batch_size: usize,
+        /// 每分钟最多调用 embedding API 的次数（0 表示不限）
+        #[arg(long, default_value = "60")]
+        requests_per_minute: u32,
+    },
+    /// 立即执行记忆清理（归档旧的 daily/conversation 记忆并清除过期归档）
+    Hygiene {
+        /// 仅预览将被归档/删除的内容，不做任何修改
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 enum IntegrationCommands {
+    /// 按分类列出集成及其状态
+    List {
+        /// 只显示该分类（如 chat、ai-model 或「聊天通道」）
+        #[arg(long)]
+        category: Option<String>,
+        /// 只显示该状态（active、available、coming-soon）
+        #[arg(long)]
+        status: Option<String>,
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
     /// 显示指定集成的详细信息
     Info {
         /// 集成名称
@@ -281,6 +670,96 @@ enum IntegrationCommands {
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum GatewayCommands {
+    /// 生成一次性配对码（10 分钟内有效），新设备通过 POST /pair 换取 token
+    Pair {
+        /// 改为显示 gateway 自身的 bearer token（首次运行时生成）
+        #[arg(long)]
+        token: bool,
+        /// 生成新的 gateway token，使旧 token 失效（隐含 --token）
+        #[arg(long)]
+        rotate: bool,
+        /// 配对链接使用的地址（默认：隧道公网地址或 http://<host>:<port>）
+        #[arg(long, conflicts_with_all = ["token", "rotate"])]
+        url: Option<String>,
+    },
+    /// 列出已配对的设备及最近使用时间
+    Devices,
+    /// 撤销已配对设备，使其 token 立即失效
+    Revoke {
+        /// 设备名称或 ID（见 jarvis gateway devices）
+        device: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommands {
+    /// 读取配置项（点分路径，如 `heartbeat.interval_minutes`）
+    Get {
+        /// 配置项路径
+        key: String,
+        /// 显示密钥的明文
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// 设置配置项，值按该字段的类型解析
+    Set {
+        /// 配置项路径
+        key: String,
+        /// 新值
+        value: String,
+    },
+    /// 删除配置项，恢复默认值
+    Unset {
+        /// 配置项路径
+        key: String,
+    },
+    /// 严格校验 config.toml，列出所有问题（包括未知字段）
+    Validate,
+    /// 从 backups/ 中的备份恢复 config.toml（默认最近一份）
+    Restore {
+        /// 备份的文件名或 --list 中的序号
+        backup: Option<String>,
+        /// 列出备份，不恢复
+        #[arg(long)]
+        list: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SecurityCommands {
+    /// 查看工具调用审计日志
+    Audit {
+        /// 时间范围（如 30m、24h、7d）
+        #[arg(long, default_value = "24h")]
+        since: String,
+        /// 仅显示指定工具
+        #[arg(long)]
+        tool: Option<String>,
+        /// 仅显示被拒绝的调用
+        #[arg(long)]
+        denied: bool,
+    },
+    /// 生成新的密钥文件，并用它重新加密 config.toml 中的所有密钥
+    RotateKey,
+}
+
+#[derive(Subcommand, Debug)]
+enum AuditCommands {
+    /// 显示最近的审计记录
+    Tail {
+        /// 显示条数
+        #[arg(short = 'n', long, default_value_t = 20)]
+        lines: usize,
+    },
+    /// 搜索工具名、来源、原因或参数中包含关键词的记录（不区分大小写）
+    Grep {
+        /// 关键词
+        term: String,
+    },
+}
+
 struct CompactTimer;
 
 impl FormatTime for CompactTimer {
@@ -290,18 +769,115 @@ impl FormatTime for CompactTimer {
     }
 }
 
+/// Where log lines are written
+enum LogSink {
+    Stdout,
+    /// The TUI draws on stdout, so its logs go to a file instead
+    File(std::path::PathBuf),
+}
+
+/// Build the subscriber from `[logging]`, `--log-level` and `RUST_LOG`;
+/// credentials are masked in every line.
+fn build_subscriber(
+    logging: &config::LoggingConfig,
+    cli_level: Option<&str>,
+    sink: &LogSink,
+) -> Result<Box<dyn tracing::Subscriber + Send + Sync>> {
+    let filter = log_filter(logging, cli_level)?;
+    let (writer, ansi) = match sink {
+        LogSink::Stdout => (
+            BoxMakeWriter::new(security::redact::RedactingMakeWriter(std::io::stdout)),
+            true,
+        ),
+        LogSink::File(path) => {
+            let file = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| {
+                    std::fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)
+                });
+            match file {
+                Ok(file) => (
+                    BoxMakeWriter::new(security::redact::RedactingMakeWriter(
+                        std::sync::Mutex::new(file),
+                    )),
+                    false,
+                ),
+                Err(_) => (BoxMakeWriter::new(std::io::sink), false),
+            }
+        }
+    };
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .with_ansi(ansi);
+    Ok(match logging.format {
+        config::LogFormat::Json => Box::new(builder.json().finish()),
+        config::LogFormat::Pretty => Box::new(builder.with_timer(CompactTimer).finish()),
+    })
+}
+
+/// `RUST_LOG` wins when set; a bad `--log-level` is an error, while a bad
+/// `RUST_LOG` or `[logging]` falls back so a daemon still boots.
+fn log_filter(logging: &config::LoggingConfig, cli_level: Option<&str>) -> Result<EnvFilter> {
+    if let Ok(env) = std::env::var(EnvFilter::DEFAULT_ENV)
+        && !env.trim().is_empty()
+    {
+        match EnvFilter::try_new(&env) {
+            Ok(filter) => return Ok(filter),
+            Err(e) => eprintln!("⚠️  RUST_LOG 无效（{e}），改用 [logging] 配置"),
+        }
+    }
+    let directives = logging.directives(cli_level);
+    match EnvFilter::try_new(&directives) {
+        Ok(filter) => Ok(filter),
+        Err(e) if cli_level.is_some() => bail!("--log-level 无效：{directives}（{e}）"),
+        Err(e) => {
+            eprintln!("⚠️  [logging] 配置无效（{e}），改用 info");
+            Ok(EnvFilter::new("info"))
+        }
+    }
+}
+
 #[tokio::main]
 #[allow(clippy::too_many_lines)]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    let log_level = cli.log_level.clone();
+    if let Some(name) = &cli.profile {
+        config::profile::select(name)?;
+    }
 
-    // Initialize logging
-    let subscriber = FmtSubscriber::builder()
-        .with_timer(CompactTimer)
-        .with_max_level(Level::INFO)
-        .finish();
+    // Completion scripts run these on every <Tab>: no logging, no config
+    // errors
+    match &cli.command {
+        Commands::Completions { shell } => {
+            return completions::write(&mut Cli::command(), *shell, &mut std::io::stdout());
+        }
+        Commands::Complete { kind } => {
+            completions::print_values(*kind);
+            return Ok(());
+        }
+        _ => {}
+    }
 
-    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+    // Onboard and config commands log with the defaults; everything else
+    // waits for `[logging]` from the loaded config
+    if matches!(
+        cli.command,
+        Commands::Onboard { .. } | Commands::Config { .. }
+    ) {
+        let subscriber = build_subscriber(
+            &config::LoggingConfig::default(),
+            log_level.as_deref(),
+            &LogSink::Stdout,
+        )?;
+        tracing::subscriber::set_global_default(subscriber)
+            .expect("setting default subscriber failed");
+    }
 
     // Onboard runs quick setup by default, or the interactive wizard with --interactive
     if let Commands::Onboard {
@@ -310,6 +886,7 @@ async fn main() -> Result<()> {
         api_key,
         provider,
         memory,
+        channels,
     } = &cli.command
     {
         if *interactive && *channels_only {
@@ -318,14 +895,26 @@ async fn main() -> Result<()> {
         if *channels_only && (api_key.is_some() || provider.is_some() || memory.is_some()) {
             bail!("--channels-only 不接受 --api-key、--provider 或 --memory 参数");
         }
+        if (*interactive || *channels_only) && !channels.is_empty() {
+            bail!("通道参数（--telegram-token、--from-file 等）仅用于快速设置，不能与 --interactive 或 --channels-only 同时使用");
+        }
 
-        let config = if *channels_only {
-            onboard::run_channels_repair_wizard()?
-        } else if *interactive {
-            onboard::run_wizard()?
-        } else {
-            onboard::run_quick_setup(api_key.as_deref(), provider.as_deref(), memory.as_deref())?
-        };
+        // Onboarding tests channel connections with a blocking HTTP client,
+        // which must not run directly on the async runtime
+        let config = tokio::task::block_in_place(|| {
+            if *channels_only {
+                onboard::run_channels_repair_wizard()
+            } else if *interactive {
+                onboard::run_wizard()
+            } else {
+                onboard::run_quick_setup(
+                    api_key.as_deref(),
+                    provider.as_deref(),
+                    memory.as_deref(),
+                    channels,
+                )
+            }
+        })?;
         // Auto-start channels if user said yes during wizard
         if std::env::var("JARVIS_AUTOSTART_CHANNELS").as_deref() == Ok("1") {
             channels::start_channels(config).await?;
@@ -333,11 +922,56 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    // All other commands need config loaded first
-    let config = Config::load_or_init()?;
+    // Config commands work on the file directly, so a broken config can
+    // still be inspected and repaired
+    if let Commands::Config { config_command } = cli.command {
+        return config::cli::handle_command(config_command, &Config::default_path()?);
+    }
+    if let Commands::Profile { profile_command } = cli.command {
+        return config::profile::handle_command(profile_command);
+    }
+
+    // All other commands need config loaded first. The Windows service runs
+    // as LocalSystem, so it is pointed at the installing user's directory
+    let config = {
+        let _loading = tracing::subscriber::set_default(build_subscriber(
+            &config::LoggingConfig::default(),
+            log_level.as_deref(),
+            &LogSink::Stdout,
+        )?);
+        match &cli.command {
+            Commands::Daemon {
+                config_dir: Some(dir),
+                ..
+            } => {
+                let mut config = Config::load_from(&dir.join("config.toml"))?;
+                config.apply_env_overrides();
+                config
+            }
+            _ => Config::load_or_init()?,
+        }
+    };
+    security::redact::register_config(&config);
+
+    let sink = match &cli.command {
+        Commands::Tui { .. } | Commands::Agent { tui: true, .. } => {
+            LogSink::File(daemon::logs::logs_dir(&config).join("tui.log"))
+        }
+        _ => LogSink::Stdout,
+    };
+    tracing::subscriber::set_global_default(build_subscriber(
+        &config.logging,
+        log_level.as_deref(),
+        &sink,
+    )?)
+    .expect("setting default subscriber failed");
 
     match cli.command {
-        Commands::Onboard { .. } => unreachable!(),
+        Commands::Onboard { .. }
+        | Commands::Config { .. }
+        | Commands::Profile { .. }
+        | Commands::Completions { .. }
+        | Commands::Complete { .. } => unreachable!(),
 
         Commands::Agent {
             message,
@@ -347,9 +981,11 @@ async fn main() -> Result<()> {
             tui: use_tui,
         } => {
             if use_tui {
-                tui::run(config, provider, model, temperature).await
+                tui::run(config, provider, model, temperature, None, true).await
             } else {
-                agent::run(config, message, provider, model, temperature).await
+                agent::run(config, message, provider, model, temperature, "cli")
+                    .await
+                    .map(|_| ())
             }
         }
 
@@ -357,9 +993,37 @@ async fn main() -> Result<()> {
             provider,
             model,
             temperature,
-        } => tui::run(config, provider, model, temperature).await,
+            resume,
+            no_markdown,
+        } => tui::run(config, provider, model, temperature, resume, !no_markdown).await,
+
+        Commands::Gateway {
+            gateway_command: Some(GatewayCommands::Pair { token, rotate, url }),
+            port,
+            host,
+        } => {
+            if token || rotate {
+                gateway::show_token(&config, rotate)
+            } else {
+                gateway::pair(&config, url.as_deref(), &host, port)
+            }
+        }
+
+        Commands::Gateway {
+            gateway_command: Some(GatewayCommands::Devices),
+            ..
+        } => gateway::list_devices(&config),
 
-        Commands::Gateway { port, host } => {
+        Commands::Gateway {
+            gateway_command: Some(GatewayCommands::Revoke { device }),
+            ..
+        } => gateway::revoke_device(&config, &device),
+
+        Commands::Gateway {
+            gateway_command: None,
+            port,
+            host,
+        } => {
             if port == 0 {
                 info!("🚀 正在启动 Jarvis Gateway，地址 {host}（随机端口）");
             } else {
@@ -373,12 +1037,30 @@ async fn main() -> Result<()> {
             host,
             foreground,
             stop,
+            reset,
+            logs,
+            lines,
+            config_dir: _,
         } => {
             if stop {
                 return daemon::stop_daemon(&config);
             }
+            if reset {
+                return daemon::reset_circuits(&config);
+            }
+            if logs {
+                daemon::logs::print_tail(&config, lines);
+                return Ok(());
+            }
 
             if foreground {
+                // Launched by the Windows Service Control Manager
+                #[cfg(windows)]
+                if tokio::task::block_in_place(|| {
+                    service::windows::run_if_service(&config, &host, port)
+                })? {
+                    return Ok(());
+                }
                 if port == 0 {
                     info!("🧠 正在启动 Jarvis 守护进程，地址 {host}（随机端口）");
                 } else {
@@ -392,16 +1074,15 @@ async fn main() -> Result<()> {
                     return Ok(());
                 }
 
-                // 创建日志目录
-                let logs_dir = config
-                    .config_path
-                    .parent()
-                    .map_or_else(|| std::path::PathBuf::from("."), std::path::PathBuf::from)
-                    .join("logs");
+                // 创建日志目录，并在重新打开前轮转过大的日志
+                let logs_dir = daemon::logs::logs_dir(&config);
                 std::fs::create_dir_all(&logs_dir)?;
+                if let Err(e) = daemon::logs::rotate_daemon_logs(&config, false) {
+                    eprintln!("⚠️  日志轮转失败：{e:#}");
+                }
 
-                let stdout_log = logs_dir.join("daemon.stdout.log");
-                let stderr_log = logs_dir.join("daemon.stderr.log");
+                let stdout_log = logs_dir.join(daemon::logs::STDOUT_LOG);
+                let stderr_log = logs_dir.join(daemon::logs::STDERR_LOG);
 
                 let exe = std::env::current_exe().context("无法获取当前可执行文件路径")?;
 
@@ -422,6 +1103,12 @@ async fn main() -> Result<()> {
                     .args(["--host", &host])
                     .stdout(stdout_file)
                     .stderr(stderr_file);
+                if let Some(level) = &log_level {
+                    cmd.args(["--log-level", level]);
+                }
+                if let Some(profile) = config::profile::active() {
+                    cmd.env(config::profile::PROFILE_ENV, profile);
+                }
 
                 // Unix: 使进程脱离当前会话
                 #[cfg(unix)]
@@ -429,6 +1116,9 @@ async fn main() -> Result<()> {
                     use std::os::unix::process::CommandExt;
                     cmd.process_group(0);
                 }
+                // Windows: 不附加控制台，也不随当前进程组收到 Ctrl+C
+                #[cfg(windows)]
+                daemon::windows::detach(&mut cmd);
 
                 let child = cmd.spawn().context("启动守护进程失败")?;
                 let child_pid = child.id();
@@ -438,7 +1128,10 @@ async fn main() -> Result<()> {
                 if daemon::is_daemon_running(&config).is_some() {
                     println!("🧠 Jarvis 守护进程已在后台启动（PID {child_pid}）");
                     println!("   Gateway：http://{host}:{port}");
-                    println!("   日志：{}", logs_dir.display());
+                    println!(
+                        "   日志：{}（jarvis daemon --logs 查看）",
+                        logs_dir.display()
+                    );
                     println!("   停止：jarvis daemon --stop");
                 } else {
                     println!("⚠️  守护进程可能启动失败，请查看日志：");
@@ -448,7 +1141,16 @@ async fn main() -> Result<()> {
             }
         }
 
-        Commands::Status => {
+        Commands::Models { provider } => providers::models::run(&config, provider.as_deref()).await,
+
+        Commands::Usage { since } => usage::ledger::run(&config, since.as_deref()),
+
+        Commands::Status { json } => {
+            if json {
+                println!("{}", serde_json::to_string_pretty(&status_json(&config))?);
+                return Ok(());
+            }
+
             println!("🤖 Jarvis 状态");
             println!();
             println!("版本：       {}", env!("CARGO_PKG_VERSION"));
@@ -484,13 +1186,30 @@ async fn main() -> Result<()> {
                 }
             );
 
+            if !config.env.is_empty() {
+                println!();
+                println!("来自环境变量：");
+                for (path, placeholder) in &config.env.placeholders {
+                    println!("  {path:28} ← {}", placeholder.template);
+                }
+                for (path, over) in &config.env.overrides {
+                    println!("  {path:28} ← {}（覆盖配置文件）", over.var);
+                }
+            }
+
             println!();
             println!("安全设置：");
             println!("  仅限工作区：     {}", config.autonomy.workspace_only);
-            println!(
-                "  允许的命令：     {}",
-                config.autonomy.allowed_commands.join(", ")
-            );
+            match config.autonomy.command_policy_mode {
+                security::CommandPolicyMode::Allow => println!(
+                    "  允许的命令：     {}",
+                    config.autonomy.allowed_commands.join(", ")
+                ),
+                security::CommandPolicyMode::Deny => println!(
+                    "  拒绝的命令：     {}（其余均允许）",
+                    config.autonomy.denied_commands.join(", ")
+                ),
+            }
             println!(
                 "  每小时最大操作数：{}",
                 config.autonomy.max_actions_per_hour
@@ -503,33 +1222,44 @@ async fn main() -> Result<()> {
             println!();
             if let Some(pid) = daemon::is_daemon_running(&config) {
                 println!("守护进程：    ✅ 运行中（PID {pid}）");
-                let state_path = daemon::state_file_path(&config);
-                if let Ok(data) = std::fs::read_to_string(&state_path) {
-                    if let Ok(state) = serde_json::from_str::<serde_json::Value>(&data) {
-                        if let Some(uptime) = state
-                            .get("uptime_seconds")
-                            .and_then(serde_json::Value::as_u64)
-                        {
-                            let hours = uptime / 3600;
-                            let mins = (uptime % 3600) / 60;
-                            if hours > 0 {
-                                println!("  运行时间：  {hours}小时{mins}分钟");
-                            } else {
-                                println!("  运行时间：  {mins}分钟");
-                            }
+                if let Some(state) = read_daemon_state(&config) {
+                    if let Some(uptime) = state
+                        .get("uptime_seconds")
+                        .and_then(serde_json::Value::as_u64)
+                    {
+                        let hours = uptime / 3600;
+                        let mins = (uptime % 3600) / 60;
+                        if hours > 0 {
+                            println!("  运行时间：  {hours}小时{mins}分钟");
+                        } else {
+                            println!("  运行时间：  {mins}分钟");
                         }
-                        if let Some(components) = state
-                            .get("components")
-                            .and_then(serde_json::Value::as_object)
-                        {
-                            println!("  组件：");
-                            for (name, info) in components {
-                                let status = info
-                                    .get("status")
-                                    .and_then(serde_json::Value::as_str)
-                                    .unwrap_or("未知");
-                                let icon = if status == "ok" { "✅" } else { "❌" };
-                                println!("    {name:12} {icon} {status}");
+                    }
+                    if let Some(components) = state
+                        .get("components")
+                        .and_then(serde_json::Value::as_object)
+                    {
+                        println!("  组件：");
+                        for (name, info) in components {
+                            let status = info
+                                .get("status")
+                                .and_then(serde_json::Value::as_str)
+                                .unwrap_or("未知");
+                            let circuit_open = info
+                                .get("circuit_open")
+                                .and_then(serde_json::Value::as_bool)
+                                .unwrap_or(false);
+                            let (icon, status) = match status {
+                                _ if circuit_open => ("🔌", "已熔断"),
+                                "ok" => ("✅", status),
+                                "degraded" => ("⚠️", status),
+                                _ => ("❌", status),
+                            };
+                            match info.get("detail").and_then(serde_json::Value::as_str) {
+                                Some(detail) => {
+                                    println!("    {name:12} {icon} {status}（{detail}）");
+                                }
+                                None => println!("    {name:12} {icon} {status}"),
                             }
                         }
                     }
@@ -562,14 +1292,35 @@ async fn main() -> Result<()> {
         }
 
         Commands::Cron { cron_command } => cron::handle_command(cron_command, &config),
+        Commands::Tasks { task_command } => tasks::handle_command(task_command, &config),
+        Commands::Sessions { session_command } => {
+            sessions::handle_command(session_command, &config)
+        }
+
+        Commands::Notify { notify_command } => {
+            notify::handle_command(notify_command, &config).await
+        }
 
         Commands::Service { service_command } => service::handle_command(&service_command, &config),
 
-        Commands::Doctor => doctor::run(&config),
+        Commands::Doctor { quiet, json, deep } => {
+            let output = if json {
+                doctor::Output::Json
+            } else if quiet {
+                doctor::Output::Quiet
+            } else {
+                doctor::Output::Human
+            };
+            if !doctor::run(&config, output, deep).await? {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
 
         Commands::Channel { channel_command } => match channel_command {
             ChannelCommands::Start => channels::start_channels(config).await,
             ChannelCommands::Doctor => channels::doctor_channels(config).await,
+            ChannelCommands::Test { name } => channels::test_send::run(&config, &name).await,
             other => channels::handle_command(other, &config),
         },
 
@@ -577,16 +1328,84 @@ async fn main() -> Result<()> {
             integration_command,
         } => integrations::handle_command(integration_command, &config),
 
-        Commands::Skills { skill_command } => {
-            skills::handle_command(skill_command, &config.workspace_dir)
+        Commands::Skills { skill_command } => skills::handle_command(skill_command, &config),
+
+        Commands::Memory { memory_command } => {
+            memory::handle_command(memory_command, &config).await
+        }
+
+        Commands::Security { security_command } => {
+            security::handle_command(security_command, &config)
         }
 
+        Commands::Audit { audit_command } => security::handle_audit_command(audit_command, &config),
+
         Commands::Migrate { migrate_command } => {
             migration::handle_command(migrate_command, &config).await
         }
     }
 }
 
+/// The daemon's last written state file (uptime, components), if readable.
+fn read_daemon_state(config: &Config) -> Option<serde_json::Value> {
+    let data = std::fs::read_to_string(daemon::state_file_path(config)).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+/// `status --json`: the same information as the pretty output.
+fn status_json(config: &Config) -> serde_json::Value {
+    let pid = daemon::is_daemon_running(config);
+    let state = pid.and_then(|_| read_daemon_state(config));
+    let state_field = |key: &str| {
+        state
+            .as_ref()
+            .and_then(|state| state.get(key))
+            .cloned()
+            .unwrap_or(serde_json::Value::Null)
+    };
+    let channels = &config.channels_config;
+
+    serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "workspace": config.workspace_dir,
+        "config_path": config.config_path,
+        "provider": config.default_provider.as_deref().unwrap_or("openrouter"),
+        "model": config.default_model,
+        "observability": config.observability.backend,
+        "runtime": config.runtime.kind,
+        "heartbeat": {
+            "enabled": config.heartbeat.enabled,
+            "interval_minutes": config.heartbeat.interval_minutes,
+        },
+        "memory": {
+            "backend": config.memory.backend,
+            "auto_save": config.memory.auto_save,
+        },
+        "autonomy": {
+            "level": config.autonomy.level,
+            "workspace_only": config.autonomy.workspace_only,
+            "command_policy_mode": config.autonomy.command_policy_mode,
+            "allowed_commands": config.autonomy.allowed_commands,
+            "denied_commands": config.autonomy.denied_commands,
+            "max_actions_per_hour": config.autonomy.max_actions_per_hour,
+            "max_cost_per_day_cents": config.autonomy.max_cost_per_day_cents,
+        },
+        "daemon": {
+            "running": pid.is_some(),
+            "pid": pid,
+            "uptime_seconds": state_field("uptime_seconds"),
+            "components": state_field("components"),
+        },
+        "channels": {
+            "cli": true,
+            "telegram": channels.telegram.is_some(),
+            "discord": channels.discord.is_some(),
+            "slack": channels.slack.is_some(),
+            "webhook": channels.webhook.is_some(),
+        },
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -596,4 +1415,43 @@ mod tests {
     fn cli_definition_has_no_flag_conflicts() {
         Cli::command().debug_assert();
     }
+
+    #[test]
+    fn status_json_has_expected_keys() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = Config {
+            workspace_dir: tmp.path().join("workspace"),
+            config_path: tmp.path().join("config.toml"),
+            ..Config::default()
+        };
+
+        let status = status_json(&config);
+        let mut keys: Vec<&str> = status
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            [
+                "autonomy",
+                "channels",
+                "config_path",
+                "daemon",
+                "heartbeat",
+                "memory",
+                "model",
+                "observability",
+                "provider",
+                "runtime",
+                "version",
+                "workspace",
+            ]
+        );
+        assert_eq!(status["daemon"]["running"], false);
+        assert_eq!(status["channels"]["cli"], true);
+        assert_eq!(status["autonomy"]["level"], "supervised");
+    }
 }
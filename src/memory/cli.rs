@@ -0,0 +1,446 @@
+use super::embeddings::{self, EmbeddingProvider};
+use super::portable;
+use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use super::SqliteMemory;
+use crate::config::Config;
+use crate::util::truncate_with_ellipsis;
+use anyhow::{Context, Result};
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Characters of content shown per entry in list/search output
+const PREVIEW_CHARS: usize = 80;
+
+/// Handle `jarvis memory ...` — talks to the configured backend directly, no provider involved.
+pub async fn handle_command(command: crate::MemoryCommands, config: &Config) -> Result<()> {
+    // Handled before `create_memory`, which would otherwise run a (non-dry) hygiene pass itself.
+    match command {
+        crate::MemoryCommands::Hygiene { dry_run } => return hygiene(config, dry_run),
+        crate::MemoryCommands::Reindex {
+            force,
+            batch_size,
+            requests_per_minute,
+        } => return reindex(config, force, batch_size, requests_per_minute).await,
+        _ => {}
+    }
+
+    let mem = super::create_memory(
+        &config.memory,
+        &config.workspace_dir,
+        config.api_key.as_deref(),
+    )?;
+
+    match command {
+        crate::MemoryCommands::List { category, limit } => {
+            let category = category.as_deref().map(parse_category);
+            let mut entries = mem.list(category.as_ref()).await?;
+            let total = entries.len();
+            if let Some(limit) = limit {
+                entries.truncate(limit);
+            }
+            if entries.is_empty() {
+                println!("暂无记忆（后端：{}）。", mem.name());
+                return Ok(());
+            }
+            println!(
+                "🧠 记忆（后端：{}，显示 {}/{total}）:",
+                mem.name(),
+                entries.len()
+            );
+            for entry in &entries {
+                print_entry_summary(entry);
+            }
+            Ok(())
+        }
+        crate::MemoryCommands::Search { query, limit, tags } => {
+            let entries = mem.recall(&query, limit, &tags).await?;
+            if entries.is_empty() {
+                println!("未找到与「{query}」相关的记忆。");
+                return Ok(());
+            }
+            println!("🔎 找到 {} 条记忆:", entries.len());
+            for entry in &entries {
+                print_entry_summary(entry);
+            }
+            Ok(())
+        }
+        crate::MemoryCommands::Show { key } => {
+            let Some(entry) = mem.get(&key).await? else {
+                anyhow::bail!("记忆「{key}」未找到");
+            };
+            println!("键名:   {}", entry.key);
+            println!("分类:   {}", entry.category);
+            if !entry.tags.is_empty() {
+                println!("标签:   {}", format_tags(&entry.tags));
+            }
+            println!("时间:   {}", entry.timestamp);
+            println!("ID:     {}", entry.id);
+            println!();
+            println!("{}", entry.content);
+            Ok(())
+        }
+        crate::MemoryCommands::Forget { key, yes } => forget(mem.as_ref(), &key, yes).await,
+        crate::MemoryCommands::Export { format, out } => {
+            export(mem.as_ref(), &format, out.as_deref()).await
+        }
+        crate::MemoryCommands::Import { file } => import(mem.as_ref(), &file).await,
+        crate::MemoryCommands::Hygiene { .. } | crate::MemoryCommands::Reindex { .. } => {
+            unreachable!("handled above")
+        }
+    }
+}
+
+async fn reindex(
+    config: &Config,
+    force: bool,
+    batch_size: usize,
+    requests_per_minute: u32,
+) -> Result<()> {
+    let memory = &config.memory;
+    if memory.backend != "sqlite" {
+        anyhow::bail!(
+            "`jarvis memory reindex` 仅支持 sqlite 后端（当前：{}）",
+            memory.backend
+        );
+    }
+
+    let embedder: Arc<dyn EmbeddingProvider> = Arc::from(embeddings::create_embedding_provider(
+        &memory.embedding_provider,
+        config.api_key.as_deref(),
+        &memory.embedding_model,
+        memory.embedding_dimensions,
+    ));
+    if embedder.dimensions() == 0 {
+        anyhow::bail!(
+            "未启用 embedding（memory.embedding_provider = \"{}\"），无法生成向量",
+            memory.embedding_provider
+        );
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    let mem = SqliteMemory::with_embedder(
+        &config.workspace_dir,
+        Arc::clone(&embedder),
+        memory.vector_weight as f32,
+        memory.keyword_weight as f32,
+        memory.embedding_cache_size,
+    )?;
+
+    if force {
+        let marked = mem.mark_all_embeddings_stale()?;
+        println!("🔁 --force：{marked} 条记忆将使用当前模型重新生成向量");
+    }
+
+    let pending = mem.pending_embeddings()?;
+    if pending.is_empty() {
+        println!(
+            "✅ 所有记忆均已使用 {}（{} 维）生成向量",
+            memory.embedding_model, memory.embedding_dimensions
+        );
+        return Ok(());
+    }
+
+    let total = pending.len();
+    let tokens: u64 = pending
+        .iter()
+        .map(|(_, content)| embeddings::estimate_tokens(content))
+        .sum();
+    println!(
+        "🧠 待生成向量：{total} 条（模型 {}，{} 维）",
+        memory.embedding_model, memory.embedding_dimensions
+    );
+    match embeddings::estimate_cost_usd(&memory.embedding_model, tokens) {
+        Some(cost) => {
+            println!("   预计 ≈ {tokens} tokens，费用 ≈ ${cost:.4}（命中缓存的条目不计费）");
+        }
+        None => println!("   预计 ≈ {tokens} tokens"),
+    }
+
+    let min_gap = (requests_per_minute > 0).then(|| Duration::from_mins(1) / requests_per_minute);
+    let mut last_call: Option<Instant> = None;
+    let mut done = 0_usize;
+    let mut api_rows = 0_usize;
+
+    for (batch_no, batch) in pending.chunks(batch_size.max(1)).enumerate() {
+        if let (Some(gap), Some(last)) = (min_gap, last_call) {
+            tokio::time::sleep_until((last + gap).into()).await;
+        }
+
+        let started = Instant::now();
+        let fetched = mem.backfill_batch(batch).await.with_context(|| {
+            format!(
+                "第 {} 批生成向量失败；已完成的条目已保存，重新运行即可继续",
+                batch_no + 1
+            )
+        })?;
+        if fetched > 0 {
+            last_call = Some(started);
+        }
+
+        done += batch.len();
+        api_rows += fetched;
+        println!(
+            "   [{done}/{total}] 本批 {} 条（{fetched} 条调用 API，{} 条来自缓存）",
+            batch.len(),
+            batch.len() - fetched
+        );
+    }
+
+    println!(
+        "✅ 完成：{total} 条记忆已生成向量（{api_rows} 条调用 API，{} 条来自缓存）",
+        total - api_rows
+    );
+    Ok(())
+}
+
+fn hygiene(config: &Config, dry_run: bool) -> Result<()> {
+    if !config.memory.hygiene_enabled {
+        println!("ℹ️  memory.hygiene_enabled = false，守护进程不会自动清理；本次为手动执行。");
+    }
+
+    let report = super::hygiene::run_now(&config.memory, &config.workspace_dir, dry_run)
+        .context("记忆清理失败")?;
+
+    if report.actions.is_empty() {
+        println!("✨ 没有需要归档或删除的记忆。");
+        return Ok(());
+    }
+
+    let heading = if dry_run {
+        "🔍 预览（dry-run，未做任何修改）:"
+    } else {
+        "🧹 记忆清理完成:"
+    };
+    println!("{heading}");
+    for action in &report.actions {
+        println!("  - {action}");
+    }
+    println!();
+    println!(
+        "归档 {} 项，删除 {} 项（保留策略：{} 天后归档，{} 天后删除，对话 {} 天）",
+        report.archived(),
+        report.purged(),
+        config.memory.archive_after_days,
+        config.memory.purge_after_days,
+        config.memory.conversation_retention_days
+    );
+    Ok(())
+}
+
+async fn export(mem: &dyn Memory, format: &str, out: Option<&Path>) -> Result<()> {
+    let export = portable::export_all(mem).await?;
+    let rendered = match format {
+        "json" => render_json(&export)?,
+        "markdown" | "md" => render_markdown(&export.entries),
+        other => anyhow::bail!("不支持的导出格式「{other}」（可选：json、markdown）"),
+    };
+    match out {
+        Some(path) => {
+            std::fs::write(path, rendered)
+                .with_context(|| format!("写入导出文件失败: {}", path.display()))?;
+            println!(
+                "✅ 已导出 {} 条记忆到 {}",
+                export.entries.len(),
+                path.display()
+            );
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
+
+async fn import(mem: &dyn Memory, file: &Path) -> Result<()> {
+    let raw = std::fs::read_to_string(file)
+        .with_context(|| format!("读取导入文件失败: {}", file.display()))?;
+    let entries = portable::parse_export(&raw)?;
+    let summary = portable::import_entries(mem, &entries).await?;
+    println!(
+        "✅ 已导入 {} 条记忆到 {} 后端（跳过 {} 条重复）",
+        summary.imported,
+        mem.name(),
+        summary.skipped
+    );
+    Ok(())
+}
+
+async fn forget(mem: &dyn Memory, key: &str, yes: bool) -> Result<()> {
+    let Some(entry) = mem.get(key).await? else {
+        anyhow::bail!("记忆「{key}」未找到");
+    };
+
+    if !yes {
+        print_entry_summary(&entry);
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!("  确定删除记忆「{key}」？"))
+            .default(false)
+            .interact()
+            .context("读取确认输入失败")?;
+        if !confirmed {
+            println!("已取消。");
+            return Ok(());
+        }
+    }
+
+    if mem.forget(key).await? {
+        println!("✅ 已删除记忆「{key}」");
+    } else {
+        println!(
+            "⚠️  {} 后端不支持删除（仅追加），记忆「{key}」未被移除",
+            mem.name()
+        );
+    }
+    Ok(())
+}
+
+/// Map a CLI category name onto a `MemoryCategory`.
+fn parse_category(raw: &str) -> MemoryCategory {
+    match raw.trim().to_lowercase().as_str() {
+        "core" => MemoryCategory::Core,
+        "daily" => MemoryCategory::Daily,
+        "conversation" => MemoryCategory::Conversation,
+        _ => MemoryCategory::Custom(raw.trim().to_string()),
+    }
+}
+
+/// Single-line content preview (newlines collapsed, truncated).
+fn preview(content: &str) -> String {
+    let flat = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    truncate_with_ellipsis(&flat, PREVIEW_CHARS)
+}
+
+fn print_entry_summary(entry: &MemoryEntry) {
+    let score = entry
+        .score
+        .map_or_else(String::new, |s| format!(" 相关度={s:.2}"));
+    let tags = if entry.tags.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", format_tags(&entry.tags))
+    };
+    println!(
+        "- {} [{}]{tags} {}{score}\n    {}",
+        entry.key,
+        entry.category,
+        entry.timestamp,
+        preview(&entry.content)
+    );
+}
+
+fn format_tags(tags: &[String]) -> String {
+    tags.iter()
+        .map(|t| format!("#{t}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn render_json(export: &portable::MemoryExport) -> Result<String> {
+    let mut out = serde_json::to_string_pretty(export).context("序列化记忆失败")?;
+    out.push('\n');
+    Ok(out)
+}
+
+fn render_markdown(entries: &[MemoryEntry]) -> String {
+    let mut out = String::from("# Jarvis Memory Export\n");
+    let mut current: Option<String> = None;
+
+    let mut sorted: Vec<&MemoryEntry> = entries.iter().collect();
+    sorted.sort_by_key(|e| e.category.to_string());
+
+    for entry in sorted {
+        let category = entry.category.to_string();
+        if current.as_deref() != Some(category.as_str()) {
+            let _ = write!(out, "\n## {category}\n\n");
+            current = Some(category);
+        }
+        let _ = writeln!(
+            out,
+            "- **{}** ({}): {}",
+            entry.key, entry.timestamp, entry.content
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(key: &str, content: &str, category: MemoryCategory) -> MemoryEntry {
+        MemoryEntry {
+            id: format!("id-{key}"),
+            key: key.into(),
+            content: content.into(),
+            category,
+            tags: Vec::new(),
+            timestamp: "2026-01-01T00:00:00+00:00".into(),
+            session_id: None,
+            score: None,
+        }
+    }
+
+    #[test]
+    fn parse_category_known_and_custom() {
+        assert_eq!(parse_category("core"), MemoryCategory::Core);
+        assert_eq!(parse_category("Daily"), MemoryCategory::Daily);
+        assert_eq!(parse_category("conversation"), MemoryCategory::Conversation);
+        assert_eq!(
+            parse_category("projects"),
+            MemoryCategory::Custom("projects".into())
+        );
+    }
+
+    #[test]
+    fn preview_collapses_newlines_and_truncates() {
+        assert_eq!(preview("line one\nline two"), "line one line two");
+        let long = "a".repeat(200);
+        assert!(preview(&long).ends_with("..."));
+    }
+
+    #[test]
+    fn render_json_roundtrips_entries() {
+        let export = portable::MemoryExport {
+            version: portable::EXPORT_VERSION,
+            exported_at: "2026-01-01T00:00:00+00:00".into(),
+            backend: "sqlite".into(),
+            entries: vec![entry("lang", "User likes Rust", MemoryCategory::Core)],
+        };
+        let json = render_json(&export).unwrap();
+        let parsed = portable::parse_export(&json).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].key, "lang");
+        assert_eq!(parsed[0].category, MemoryCategory::Core);
+    }
+
+    #[test]
+    fn render_markdown_groups_by_category() {
+        let entries = vec![
+            entry("a", "daily note", MemoryCategory::Daily),
+            entry("b", "core fact", MemoryCategory::Core),
+            entry("c", "another fact", MemoryCategory::Core),
+        ];
+        let md = render_markdown(&entries);
+        assert_eq!(md.matches("## core").count(), 1);
+        assert_eq!(md.matches("## daily").count(), 1);
+        assert!(md.contains("- **b** (2026-01-01T00:00:00+00:00): core fact"));
+        assert!(md.find("## core").unwrap() < md.find("## daily").unwrap());
+    }
+
+    #[tokio::test]
+    async fn forget_with_yes_removes_sqlite_entry() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mem = crate::memory::SqliteMemory::new(tmp.path()).unwrap();
+        mem.store("k", "v", MemoryCategory::Core).await.unwrap();
+
+        forget(&mem, "k", true).await.unwrap();
+        assert!(mem.get("k").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn forget_missing_key_errors() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mem = crate::memory::SqliteMemory::new(tmp.path()).unwrap();
+        assert!(forget(&mem, "missing", true).await.is_err());
+    }
+}
@@ -6,6 +6,9 @@ pub trait EmbeddingProvider: Send + Sync {
     /// Provider name
     fn name(&self) -> &str;
 
+    /// Model identifier (empty when the provider has no model)
+    fn model(&self) -> &str;
+
     /// Embedding dimensions
     fn dimensions(&self) -> usize;
 
@@ -31,6 +34,10 @@ impl EmbeddingProvider for NoopEmbedding {
         "none"
     }
 
+    fn model(&self) -> &str {
+        ""
+    }
+
     fn dimensions(&self) -> usize {
         0
     }
@@ -68,6 +75,10 @@ impl EmbeddingProvider for OpenAiEmbedding {
         "openai"
     }
 
+    fn model(&self) -> &str {
+        &self.model
+    }
+
     fn dimensions(&self) -> usize {
         self.dims
     }
@@ -123,8 +134,279 @@ impl EmbeddingProvider for OpenAiEmbedding {
     }
 }
 
+// ── Local (Ollama) embedding provider ────────────────────────
+
+/// Where `embedding_provider = "local"` finds Ollama
+pub const LOCAL_DEFAULT_URL: &str = "http://localhost:11434";
+
+/// Embeddings from a neural model served by a local Ollama, e.g.
+/// `ollama pull nomic-embed-text` — no API key, and no text leaves the machine.
+///
+/// `embedding_provider = "local:URL"` points at an Ollama elsewhere. `embedding_model`
+/// names the model as Ollama knows it; `embedding_dimensions` is sent with each request
+/// (models that support it truncate to it) and every vector returned is checked against
+/// it, so a mismatched setting fails loudly instead of mixing vector sizes.
+pub struct LocalEmbedding {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    dims: usize,
+}
+
+impl LocalEmbedding {
+    pub fn new(base_url: &str, model: &str, dims: usize) -> Self {
+        let base_url = base_url.trim().trim_end_matches('/');
+        Self {
+            client: reqwest::Client::new(),
+            base_url: if base_url.is_empty() {
+                LOCAL_DEFAULT_URL.to_string()
+            } else {
+                base_url.to_string()
+            },
+            model: model.to_string(),
+            dims,
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for LocalEmbedding {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dims
+    }
+
+    async fn embed(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let body = serde_json::json!({
+            "model": self.model,
+            "input": texts,
+            "dimensions": self.dims,
+        });
+        let resp = self
+            .client
+            .post(format!("{}/api/embed", self.base_url))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Cannot reach Ollama at {} for local embeddings (is `ollama serve` running?): {e}",
+                    self.base_url
+                )
+            })?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            if status == reqwest::StatusCode::NOT_FOUND {
+                anyhow::bail!(
+                    "Local embedding model '{}' is not available ({text}); run `ollama pull {}`",
+                    self.model,
+                    self.model
+                );
+            }
+            anyhow::bail!("Local embedding error {status}: {text}");
+        }
+
+        let json: serde_json::Value = resp.json().await?;
+        let data = json
+            .get("embeddings")
+            .and_then(|d| d.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Invalid embedding response: missing 'embeddings'"))?;
+        if data.len() != texts.len() {
+            anyhow::bail!(
+                "Local embedding model returned {} vectors for {} texts",
+                data.len(),
+                texts.len()
+            );
+        }
+
+        let mut embeddings = Vec::with_capacity(data.len());
+        for item in data {
+            let embedding = item
+                .as_array()
+                .ok_or_else(|| anyhow::anyhow!("Invalid embedding item"))?;
+            #[allow(clippy::cast_possible_truncation)]
+            let vec: Vec<f32> = embedding
+                .iter()
+                .filter_map(|v| v.as_f64().map(|f| f as f32))
+                .collect();
+            if vec.len() != self.dims {
+                anyhow::bail!(
+                    "Local embedding model '{}' returned {}-dimensional vectors; \
+                     set memory.embedding_dimensions = {}",
+                    self.model,
+                    vec.len(),
+                    vec.len()
+                );
+            }
+            embeddings.push(vec);
+        }
+
+        Ok(embeddings)
+    }
+}
+
+// ── Hashed (offline) embedding provider ──────────────────────
+
+/// The only model of the hashed provider
+pub const HASHED_MODEL: &str = "hashed-ngram-v1";
+/// Dimensions used when `embedding_dimensions` is 0
+const HASHED_DEFAULT_DIMS: usize = 384;
+/// Relative weight of sub-word features vs. whole words
+const HASHED_SUBWORD_WEIGHT: f32 = 0.5;
+
+/// Offline embeddings via signed feature hashing — no API key, no model download.
+///
+/// Each text is broken into whole words plus character n-grams (`<word>` trigrams for
+/// alphabetic scripts, unigrams + bigrams for CJK), hashed into `dims` buckets and
+/// L2-normalized. This matches spelling, not meaning: inflections ("deploying" ≈
+/// "deployment") and partial CJK words that FTS5 tokenization misses, but not synonyms
+/// or paraphrases. It is not a neural model.
+pub struct HashedEmbedding {
+    model: String,
+    dims: usize,
+}
+
+impl HashedEmbedding {
+    pub fn new(model: &str, dims: usize) -> Self {
+        if model != HASHED_MODEL {
+            tracing::warn!(
+                "The hashed embedding provider has no model '{model}'; using '{HASHED_MODEL}' \
+                 (set memory.embedding_model = \"{HASHED_MODEL}\")"
+            );
+        }
+        Self {
+            model: HASHED_MODEL.to_string(),
+            dims: if dims == 0 { HASHED_DEFAULT_DIMS } else { dims },
+        }
+    }
+
+    fn embed_text(&self, text: &str) -> Vec<f32> {
+        let mut v = vec![0.0_f32; self.dims];
+        let lower = text.to_lowercase();
+
+        for word in lower
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+        {
+            let chars: Vec<char> = word.chars().collect();
+            if chars.iter().any(|c| is_cjk(*c)) {
+                for c in &chars {
+                    self.add_feature(&mut v, &c.to_string(), HASHED_SUBWORD_WEIGHT);
+                }
+                for pair in chars.windows(2) {
+                    self.add_feature(&mut v, &pair.iter().collect::<String>(), 1.0);
+                }
+            } else {
+                self.add_feature(&mut v, word, 1.0);
+                let padded: Vec<char> = std::iter::once('<')
+                    .chain(chars.iter().copied())
+                    .chain(std::iter::once('>'))
+                    .collect();
+                for tri in padded.windows(3) {
+                    self.add_feature(
+                        &mut v,
+                        &tri.iter().collect::<String>(),
+                        HASHED_SUBWORD_WEIGHT,
+                    );
+                }
+            }
+        }
+
+        let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > f32::EPSILON {
+            for x in &mut v {
+                *x /= norm;
+            }
+        }
+        v
+    }
+
+    fn add_feature(&self, v: &mut [f32], feature: &str, weight: f32) {
+        let h = fnv1a(feature.as_bytes());
+        #[allow(clippy::cast_possible_truncation)]
+        let bucket = (h % self.dims as u64) as usize;
+        // Top bit picks the sign so unrelated collisions cancel out on average
+        let sign = if h >> 63 == 0 { 1.0 } else { -1.0 };
+        v[bucket] += sign * weight;
+    }
+}
+
+/// FNV-1a: stable across Rust versions, unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in bytes {
+        hash ^= u64::from(*b);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}'   // Hiragana / Katakana
+        | '\u{3400}'..='\u{4dbf}' // CJK Extension A
+        | '\u{4e00}'..='\u{9fff}' // CJK Unified Ideographs
+        | '\u{ac00}'..='\u{d7af}' // Hangul
+        | '\u{f900}'..='\u{faff}' // CJK Compatibility Ideographs
+    )
+}
+
+#[async_trait]
+impl EmbeddingProvider for HashedEmbedding {
+    fn name(&self) -> &str {
+        "hashed"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dims
+    }
+
+    async fn embed(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|t| self.embed_text(t)).collect())
+    }
+}
+
+// ── Cost estimation ──────────────────────────────────────────
+
+/// Rough token estimate (~4 chars per token) used for cost previews
+pub fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
+/// Estimated USD cost for embedding `tokens` with a known hosted model
+pub fn estimate_cost_usd(model: &str, tokens: u64) -> Option<f64> {
+    let per_million = match model {
+        "text-embedding-3-small" => 0.02,
+        "text-embedding-3-large" => 0.13,
+        "text-embedding-ada-002" => 0.10,
+        _ => return None,
+    };
+    #[allow(clippy::cast_precision_loss)]
+    Some(tokens as f64 / 1_000_000.0 * per_million)
+}
+
 // ── Factory ──────────────────────────────────────────────────
 
+/// `embedding_provider` values besides `local:URL` and `custom:URL`
+pub const EMBEDDING_PROVIDERS: &[&str] = &["none", "noop", "local", "openai", "hashed"];
+
 pub fn create_embedding_provider(
     provider: &str,
     api_key: Option<&str>,
@@ -141,12 +423,24 @@ pub fn create_embedding_provider(
                 dims,
             ))
         }
+        "local" => Box::new(LocalEmbedding::new(LOCAL_DEFAULT_URL, model, dims)),
+        name if name.starts_with("local:") => {
+            let base_url = name.strip_prefix("local:").unwrap_or("");
+            Box::new(LocalEmbedding::new(base_url, model, dims))
+        }
+        "hashed" => Box::new(HashedEmbedding::new(model, dims)),
         name if name.starts_with("custom:") => {
             let base_url = name.strip_prefix("custom:").unwrap_or("");
             let key = api_key.unwrap_or("");
             Box::new(OpenAiEmbedding::new(base_url, key, model, dims))
         }
-        _ => Box::new(NoopEmbedding),
+        "none" | "noop" => Box::new(NoopEmbedding),
+        name => {
+            tracing::warn!(
+                "Unknown embedding provider '{name}', vector search is off (keyword-only recall)"
+            );
+            Box::new(NoopEmbedding)
+        }
     }
 }
 
@@ -212,6 +506,155 @@ mod tests {
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn factory_hashed() {
+        let p = create_embedding_provider("hashed", None, HASHED_MODEL, 256);
+        assert_eq!(p.name(), "hashed");
+        assert_eq!(p.model(), HASHED_MODEL);
+        assert_eq!(p.dimensions(), 256);
+        // The provider has a single model; a hosted model name isn't taken for it
+        let p = create_embedding_provider("hashed", None, "text-embedding-3-small", 256);
+        assert_eq!(p.model(), HASHED_MODEL);
+    }
+
+    #[test]
+    fn factory_local_honors_model_dimensions_and_url() {
+        let p = create_embedding_provider("local", None, "nomic-embed-text", 768);
+        assert_eq!(p.name(), "local");
+        assert_eq!(p.model(), "nomic-embed-text");
+        assert_eq!(p.dimensions(), 768);
+        assert_eq!(
+            LocalEmbedding::new(LOCAL_DEFAULT_URL, "m", 8).base_url,
+            LOCAL_DEFAULT_URL
+        );
+        assert_eq!(
+            LocalEmbedding::new("http://gpu-box:11434/", "m", 8).base_url,
+            "http://gpu-box:11434"
+        );
+        assert_eq!(LocalEmbedding::new("", "m", 8).base_url, LOCAL_DEFAULT_URL);
+        let p = create_embedding_provider("local:http://gpu-box:11434", None, "bge-m3", 1024);
+        assert_eq!((p.name(), p.dimensions()), ("local", 1024));
+    }
+
+    /// A stand-in for Ollama's `/api/embed` returning `dims`-sized vectors.
+    async fn spawn_mock_ollama(
+        dims: usize,
+    ) -> (
+        String,
+        std::sync::Arc<std::sync::Mutex<Vec<serde_json::Value>>>,
+    ) {
+        use axum::{routing::post, Json, Router};
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        let app = Router::new().route(
+            "/api/embed",
+            post(move |Json(body): Json<serde_json::Value>| {
+                let seen = seen.clone();
+                async move {
+                    let count = body["input"].as_array().map_or(0, Vec::len);
+                    seen.lock().unwrap().push(body);
+                    Json(serde_json::json!({ "embeddings": vec![vec![0.5_f32; dims]; count] }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{addr}"), requests)
+    }
+
+    #[tokio::test]
+    async fn local_embeds_through_ollama_api() {
+        let (url, requests) = spawn_mock_ollama(4).await;
+        let p = LocalEmbedding::new(&url, "nomic-embed-text", 4);
+        let vectors = p.embed(&["first", "second"]).await.unwrap();
+        assert_eq!(vectors, [vec![0.5; 4], vec![0.5; 4]]);
+
+        let body = requests.lock().unwrap()[0].clone();
+        assert_eq!(body["model"], "nomic-embed-text");
+        assert_eq!(body["input"], serde_json::json!(["first", "second"]));
+        assert_eq!(body["dimensions"], 4);
+    }
+
+    #[tokio::test]
+    async fn local_rejects_vectors_of_the_wrong_size() {
+        let (url, _) = spawn_mock_ollama(768).await;
+        let err = LocalEmbedding::new(&url, "nomic-embed-text", 1536)
+            .embed_one("hello")
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("set memory.embedding_dimensions = 768"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn hashed_zero_dims_uses_default() {
+        assert_eq!(
+            HashedEmbedding::new(HASHED_MODEL, 0).dimensions(),
+            HASHED_DEFAULT_DIMS
+        );
+    }
+
+    #[tokio::test]
+    async fn hashed_embeddings_are_normalized_and_deterministic() {
+        let p = HashedEmbedding::new(HASHED_MODEL, 128);
+        let a = p.embed_one("Rust async runtime").await.unwrap();
+        let b = p.embed_one("Rust async runtime").await.unwrap();
+        assert_eq!(a.len(), 128);
+        assert_eq!(a, b);
+        let norm: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+    }
+
+    #[tokio::test]
+    async fn hashed_shared_word_forms_score_higher_than_unrelated() {
+        use super::super::vector::cosine_similarity;
+        let p = HashedEmbedding::new(HASHED_MODEL, 384);
+        let query = p.embed_one("deploying").await.unwrap();
+        let related = p.embed_one("deployment pipeline").await.unwrap();
+        let unrelated = p.embed_one("lunch with friends").await.unwrap();
+        assert!(cosine_similarity(&query, &related) > cosine_similarity(&query, &unrelated));
+
+        let query = p.embed_one("数据库").await.unwrap();
+        let related = p.embed_one("迁移数据库到新服务器").await.unwrap();
+        let unrelated = p.embed_one("今天天气很好").await.unwrap();
+        assert!(cosine_similarity(&query, &related) > cosine_similarity(&query, &unrelated));
+    }
+
+    #[tokio::test]
+    async fn hashed_empty_text_is_zero_vector() {
+        let p = HashedEmbedding::new(HASHED_MODEL, 16);
+        let v = p.embed_one("  ").await.unwrap();
+        assert!(v.iter().all(|x| *x == 0.0));
+    }
+
+    #[test]
+    fn provider_model_names() {
+        assert_eq!(NoopEmbedding.model(), "");
+        let p = create_embedding_provider("openai", Some("key"), "text-embedding-3-large", 3072);
+        assert_eq!(p.model(), "text-embedding-3-large");
+    }
+
+    #[test]
+    fn estimate_tokens_rounds_up() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abc"), 1);
+        assert_eq!(estimate_tokens("abcdefgh"), 2);
+        assert_eq!(estimate_tokens("abcdefghi"), 3);
+    }
+
+    #[test]
+    fn estimate_cost_known_and_unknown_models() {
+        let cost = estimate_cost_usd("text-embedding-3-small", 1_000_000).unwrap();
+        assert!((cost - 0.02).abs() < 1e-9);
+        assert!(estimate_cost_usd("my-local-model", 1_000_000).is_none());
+    }
+
     #[test]
     fn factory_empty_string_returns_noop() {
         let p = create_embedding_provider("", None, "model", 1536);
@@ -7,25 +7,47 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::{Duration as StdDuration, SystemTime};
 
-const HYGIENE_INTERVAL_HOURS: i64 = 12;
+const HYGIENE_INTERVAL_HOURS: i64 = 24;
 const STATE_FILE: &str = "memory_hygiene_state.json";
 
+/// Row categories that age out of the live table (core memories are never archived)
+const ARCHIVABLE_CATEGORIES: &str = "'daily', 'conversation'";
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-struct HygieneReport {
-    archived_memory_files: u64,
-    archived_session_files: u64,
-    purged_memory_archives: u64,
-    purged_session_archives: u64,
-    pruned_conversation_rows: u64,
+#[serde(default)]
+pub struct HygieneReport {
+    pub archived_memory_files: u64,
+    pub archived_session_files: u64,
+    pub archived_rows: u64,
+    pub purged_memory_archives: u64,
+    pub purged_session_archives: u64,
+    pub purged_rows: u64,
+    pub pruned_conversation_rows: u64,
+    pub pruned_tasks: u64,
+    pub pruned_transcripts: u64,
+    /// Human-readable list of every action taken (or, in dry-run, that would be taken)
+    #[serde(skip)]
+    pub actions: Vec<String>,
 }
 
 impl HygieneReport {
-    fn total_actions(&self) -> u64 {
-        self.archived_memory_files
-            + self.archived_session_files
-            + self.purged_memory_archives
+    /// Files and rows moved into an archive
+    pub fn archived(&self) -> u64 {
+        self.archived_memory_files + self.archived_session_files + self.archived_rows
+    }
+
+    /// Files and rows permanently deleted
+    pub fn purged(&self) -> u64 {
+        self.purged_memory_archives
             + self.purged_session_archives
+            + self.purged_rows
             + self.pruned_conversation_rows
+            + self.pruned_tasks
+            + self.pruned_transcripts
+    }
+
+    fn total_actions(&self) -> u64 {
+        self.archived() + self.purged()
     }
 }
 
@@ -37,44 +59,111 @@ struct HygieneState {
 
 /// Run memory/session hygiene if the cadence window has elapsed.
 ///
-/// This function is intentionally best-effort: callers should log and continue on failure.
-pub fn run_if_due(config: &MemoryConfig, workspace_dir: &Path) -> Result<()> {
+/// Returns the report when a pass actually ran. This function is intentionally
+/// best-effort: callers should log and continue on failure.
+pub fn run_if_due(config: &MemoryConfig, workspace_dir: &Path) -> Result<Option<HygieneReport>> {
     if !config.hygiene_enabled {
-        return Ok(());
+        return Ok(None);
     }
 
     if !should_run_now(workspace_dir)? {
-        return Ok(());
+        return Ok(None);
     }
 
-    let report = HygieneReport {
-        archived_memory_files: archive_daily_memory_files(
-            workspace_dir,
-            config.archive_after_days,
-        )?,
-        archived_session_files: archive_session_files(workspace_dir, config.archive_after_days)?,
-        purged_memory_archives: purge_memory_archives(workspace_dir, config.purge_after_days)?,
-        purged_session_archives: purge_session_archives(workspace_dir, config.purge_after_days)?,
-        pruned_conversation_rows: prune_conversation_rows(
-            workspace_dir,
-            config.conversation_retention_days,
-        )?,
-    };
+    run_now(config, workspace_dir, false).map(Some)
+}
+
+/// Run a hygiene pass immediately, ignoring the cadence gate and `hygiene_enabled`.
+///
+/// With `dry_run` nothing is moved or deleted and the state file is left untouched;
+/// the returned report describes what a real pass would do.
+pub fn run_now(
+    config: &MemoryConfig,
+    workspace_dir: &Path,
+    dry_run: bool,
+) -> Result<HygieneReport> {
+    let mut report = HygieneReport::default();
+    let actions = &mut report.actions;
+
+    report.archived_memory_files =
+        archive_daily_memory_files(workspace_dir, config.archive_after_days, dry_run, actions)?;
+    report.archived_session_files =
+        archive_session_files(workspace_dir, config.archive_after_days, dry_run, actions)?;
+    report.purged_memory_archives =
+        purge_memory_archives(workspace_dir, config.purge_after_days, dry_run, actions)?;
+    report.purged_session_archives =
+        purge_session_archives(workspace_dir, config.purge_after_days, dry_run, actions)?;
+
+    // Row order matters: retention prune → purge → archive, so rows about to be
+    // deleted are never copied into the archive table first.
+    report.pruned_conversation_rows = prune_conversation_rows(
+        workspace_dir,
+        config.conversation_retention_days,
+        dry_run,
+        actions,
+    )?;
+    report.purged_rows = purge_rows(workspace_dir, config.purge_after_days, dry_run, actions)?;
+    report.archived_rows =
+        archive_rows(workspace_dir, config.archive_after_days, dry_run, actions)?;
+    report.pruned_tasks = prune_tasks(workspace_dir, config.task_retention_days, dry_run, actions)?;
+    report.pruned_transcripts = prune_transcripts(
+        workspace_dir,
+        config.transcript_retention_days,
+        dry_run,
+        actions,
+    )?;
+
+    if dry_run {
+        return Ok(report);
+    }
 
     write_state(workspace_dir, &report)?;
 
     if report.total_actions() > 0 {
         tracing::info!(
-            "memory hygiene complete: archived_memory={} archived_sessions={} purged_memory={} purged_sessions={} pruned_conversation_rows={}",
+            "memory hygiene complete: archived_memory={} archived_sessions={} archived_rows={} purged_memory={} purged_sessions={} purged_rows={} pruned_conversation_rows={} pruned_tasks={} pruned_transcripts={}",
             report.archived_memory_files,
             report.archived_session_files,
+            report.archived_rows,
             report.purged_memory_archives,
             report.purged_session_archives,
+            report.purged_rows,
             report.pruned_conversation_rows,
+            report.pruned_tasks,
+            report.pruned_transcripts,
         );
     }
 
-    Ok(())
+    Ok(report)
+}
+
+/// One-line summary of the last completed pass, for the health snapshot.
+///
+/// e.g. `archived 120, purged 30, last run 2h ago`
+pub fn status_summary(workspace_dir: &Path) -> Option<String> {
+    let raw = fs::read_to_string(state_path(workspace_dir)).ok()?;
+    let state: HygieneState = serde_json::from_str(&raw).ok()?;
+    let last = DateTime::parse_from_rfc3339(state.last_run_at.as_deref()?).ok()?;
+    let age = Utc::now().signed_duration_since(last.with_timezone(&Utc));
+
+    Some(format!(
+        "archived {}, purged {}, last run {}",
+        state.last_report.archived(),
+        state.last_report.purged(),
+        format_age(age)
+    ))
+}
+
+fn format_age(age: Duration) -> String {
+    if age.num_minutes() < 1 {
+        "just now".into()
+    } else if age.num_hours() < 1 {
+        format!("{}m ago", age.num_minutes())
+    } else if age.num_days() < 1 {
+        format!("{}h ago", age.num_hours())
+    } else {
+        format!("{}d ago", age.num_days())
+    }
 }
 
 fn should_run_now(workspace_dir: &Path) -> Result<bool> {
@@ -120,7 +209,12 @@ fn state_path(workspace_dir: &Path) -> PathBuf {
     workspace_dir.join("state").join(STATE_FILE)
 }
 
-fn archive_daily_memory_files(workspace_dir: &Path, archive_after_days: u32) -> Result<u64> {
+fn archive_daily_memory_files(
+    workspace_dir: &Path,
+    archive_after_days: u32,
+    dry_run: bool,
+    actions: &mut Vec<String>,
+) -> Result<u64> {
     if archive_after_days == 0 {
         return Ok(0);
     }
@@ -131,7 +225,9 @@ fn archive_daily_memory_files(workspace_dir: &Path, archive_after_days: u32) ->
     }
 
     let archive_dir = memory_dir.join("archive");
-    fs::create_dir_all(&archive_dir)?;
+    if !dry_run {
+        fs::create_dir_all(&archive_dir)?;
+    }
 
     let cutoff = Local::now().date_naive() - Duration::days(i64::from(archive_after_days));
     let mut moved = 0_u64;
@@ -156,7 +252,10 @@ fn archive_daily_memory_files(workspace_dir: &Path, archive_after_days: u32) ->
         };
 
         if file_date < cutoff {
-            move_to_archive(&path, &archive_dir)?;
+            actions.push(format!("archive file memory/{filename}"));
+            if !dry_run {
+                move_to_archive(&path, &archive_dir)?;
+            }
             moved += 1;
         }
     }
@@ -164,7 +263,12 @@ fn archive_daily_memory_files(workspace_dir: &Path, archive_after_days: u32) ->
     Ok(moved)
 }
 
-fn archive_session_files(workspace_dir: &Path, archive_after_days: u32) -> Result<u64> {
+fn archive_session_files(
+    workspace_dir: &Path,
+    archive_after_days: u32,
+    dry_run: bool,
+    actions: &mut Vec<String>,
+) -> Result<u64> {
     if archive_after_days == 0 {
         return Ok(0);
     }
@@ -175,7 +279,9 @@ fn archive_session_files(workspace_dir: &Path, archive_after_days: u32) -> Resul
     }
 
     let archive_dir = sessions_dir.join("archive");
-    fs::create_dir_all(&archive_dir)?;
+    if !dry_run {
+        fs::create_dir_all(&archive_dir)?;
+    }
 
     let cutoff_date = Local::now().date_naive() - Duration::days(i64::from(archive_after_days));
     let cutoff_time = SystemTime::now()
@@ -204,7 +310,10 @@ fn archive_session_files(workspace_dir: &Path, archive_after_days: u32) -> Resul
         };
 
         if is_old {
-            move_to_archive(&path, &archive_dir)?;
+            actions.push(format!("archive file sessions/{filename}"));
+            if !dry_run {
+                move_to_archive(&path, &archive_dir)?;
+            }
             moved += 1;
         }
     }
@@ -212,7 +321,12 @@ fn archive_session_files(workspace_dir: &Path, archive_after_days: u32) -> Resul
     Ok(moved)
 }
 
-fn purge_memory_archives(workspace_dir: &Path, purge_after_days: u32) -> Result<u64> {
+fn purge_memory_archives(
+    workspace_dir: &Path,
+    purge_after_days: u32,
+    dry_run: bool,
+    actions: &mut Vec<String>,
+) -> Result<u64> {
     if purge_after_days == 0 {
         return Ok(0);
     }
@@ -242,7 +356,10 @@ fn purge_memory_archives(workspace_dir: &Path, purge_after_days: u32) -> Result<
         };
 
         if file_date < cutoff {
-            fs::remove_file(&path)?;
+            actions.push(format!("purge file memory/archive/{filename}"));
+            if !dry_run {
+                fs::remove_file(&path)?;
+            }
             removed += 1;
         }
     }
@@ -250,7 +367,12 @@ fn purge_memory_archives(workspace_dir: &Path, purge_after_days: u32) -> Result<
     Ok(removed)
 }
 
-fn purge_session_archives(workspace_dir: &Path, purge_after_days: u32) -> Result<u64> {
+fn purge_session_archives(
+    workspace_dir: &Path,
+    purge_after_days: u32,
+    dry_run: bool,
+    actions: &mut Vec<String>,
+) -> Result<u64> {
     if purge_after_days == 0 {
         return Ok(0);
     }
@@ -287,7 +409,10 @@ fn purge_session_archives(workspace_dir: &Path, purge_after_days: u32) -> Result
         };
 
         if is_old {
-            fs::remove_file(&path)?;
+            actions.push(format!("purge file sessions/archive/{filename}"));
+            if !dry_run {
+                fs::remove_file(&path)?;
+            }
             removed += 1;
         }
     }
@@ -295,25 +420,183 @@ fn purge_session_archives(workspace_dir: &Path, purge_after_days: u32) -> Result
     Ok(removed)
 }
 
-fn prune_conversation_rows(workspace_dir: &Path, retention_days: u32) -> Result<u64> {
+fn brain_db(workspace_dir: &Path) -> Result<Option<Connection>> {
+    let db_path = workspace_dir.join("memory").join("brain.db");
+    if !db_path.exists() {
+        return Ok(None);
+    }
+    let conn = Connection::open(db_path)?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS memories_archive (
+            id          TEXT PRIMARY KEY,
+            key         TEXT NOT NULL,
+            content     TEXT NOT NULL,
+            category    TEXT NOT NULL,
+            created_at  TEXT NOT NULL,
+            updated_at  TEXT NOT NULL,
+            archived_at TEXT NOT NULL
+        );",
+    )?;
+    Ok(Some(conn))
+}
+
+fn cutoff_rfc3339(days: u32) -> String {
+    (Local::now() - Duration::days(i64::from(days))).to_rfc3339()
+}
+
+/// Collect `category:key` labels for rows matched by `filter` (bound to `?1` = cutoff)
+fn matching_rows(
+    conn: &Connection,
+    table: &str,
+    filter: &str,
+    cutoff: &str,
+) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT category, key FROM {table} WHERE {filter} ORDER BY updated_at"
+    ))?;
+    let rows = stmt
+        .query_map(params![cutoff], |row| {
+            Ok(format!(
+                "{}:{}",
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows)
+}
+
+fn prune_conversation_rows(
+    workspace_dir: &Path,
+    retention_days: u32,
+    dry_run: bool,
+    actions: &mut Vec<String>,
+) -> Result<u64> {
     if retention_days == 0 {
         return Ok(0);
     }
 
-    let db_path = workspace_dir.join("memory").join("brain.db");
-    if !db_path.exists() {
+    let Some(conn) = brain_db(workspace_dir)? else {
         return Ok(0);
+    };
+    let cutoff = cutoff_rfc3339(retention_days);
+    let filter = "category = 'conversation' AND updated_at < ?1";
+
+    let matched = matching_rows(&conn, "memories", filter, &cutoff)?;
+    actions.extend(matched.iter().map(|row| format!("prune row {row}")));
+    if !dry_run {
+        conn.execute(
+            &format!("DELETE FROM memories WHERE {filter}"),
+            params![cutoff],
+        )?;
     }
 
-    let conn = Connection::open(db_path)?;
-    let cutoff = (Local::now() - Duration::days(i64::from(retention_days))).to_rfc3339();
+    Ok(matched.len() as u64)
+}
 
-    let affected = conn.execute(
-        "DELETE FROM memories WHERE category = 'conversation' AND updated_at < ?1",
-        params![cutoff],
-    )?;
+/// Delete tasks completed more than `retention_days` ago from the task list
+fn prune_tasks(
+    workspace_dir: &Path,
+    retention_days: u32,
+    dry_run: bool,
+    actions: &mut Vec<String>,
+) -> Result<u64> {
+    let pruned = crate::tasks::prune_completed(workspace_dir, retention_days, dry_run)?;
+    actions.extend(pruned.iter().map(|task| format!("prune task {task}")));
+    Ok(pruned.len() as u64)
+}
+
+fn prune_transcripts(
+    workspace_dir: &Path,
+    retention_days: u32,
+    dry_run: bool,
+    actions: &mut Vec<String>,
+) -> Result<u64> {
+    let pruned = crate::sessions::prune(workspace_dir, retention_days, dry_run)?;
+    actions.extend(pruned.iter().map(|file| format!("prune transcript {file}")));
+    Ok(pruned.len() as u64)
+}
+
+/// Hard-delete archived rows (and any still-live daily/conversation rows) past `purge_after_days`
+fn purge_rows(
+    workspace_dir: &Path,
+    purge_after_days: u32,
+    dry_run: bool,
+    actions: &mut Vec<String>,
+) -> Result<u64> {
+    if purge_after_days == 0 {
+        return Ok(0);
+    }
+
+    let Some(conn) = brain_db(workspace_dir)? else {
+        return Ok(0);
+    };
+    let cutoff = cutoff_rfc3339(purge_after_days);
+    let live_filter = format!("category IN ({ARCHIVABLE_CATEGORIES}) AND updated_at < ?1");
+    let archive_filter = "updated_at < ?1";
+
+    let live = matching_rows(&conn, "memories", &live_filter, &cutoff)?;
+    let archived = matching_rows(&conn, "memories_archive", archive_filter, &cutoff)?;
+    actions.extend(live.iter().map(|row| format!("purge row {row}")));
+    actions.extend(
+        archived
+            .iter()
+            .map(|row| format!("purge archived row {row}")),
+    );
+
+    if !dry_run {
+        conn.execute(
+            &format!("DELETE FROM memories WHERE {live_filter}"),
+            params![cutoff],
+        )?;
+        conn.execute(
+            &format!("DELETE FROM memories_archive WHERE {archive_filter}"),
+            params![cutoff],
+        )?;
+    }
 
-    Ok(u64::try_from(affected).unwrap_or(0))
+    Ok((live.len() + archived.len()) as u64)
+}
+
+/// Move daily/conversation rows past `archive_after_days` into `memories_archive`
+fn archive_rows(
+    workspace_dir: &Path,
+    archive_after_days: u32,
+    dry_run: bool,
+    actions: &mut Vec<String>,
+) -> Result<u64> {
+    if archive_after_days == 0 {
+        return Ok(0);
+    }
+
+    let Some(mut conn) = brain_db(workspace_dir)? else {
+        return Ok(0);
+    };
+    let cutoff = cutoff_rfc3339(archive_after_days);
+    let filter = format!("category IN ({ARCHIVABLE_CATEGORIES}) AND updated_at < ?1");
+
+    let matched = matching_rows(&conn, "memories", &filter, &cutoff)?;
+    actions.extend(matched.iter().map(|row| format!("archive row {row}")));
+
+    if !dry_run && !matched.is_empty() {
+        let tx = conn.transaction()?;
+        tx.execute(
+            &format!(
+                "INSERT OR REPLACE INTO memories_archive
+                    (id, key, content, category, created_at, updated_at, archived_at)
+                 SELECT id, key, content, category, created_at, updated_at, ?2
+                 FROM memories WHERE {filter}"
+            ),
+            params![cutoff, Local::now().to_rfc3339()],
+        )?;
+        tx.execute(
+            &format!("DELETE FROM memories WHERE {filter}"),
+            params![cutoff],
+        )?;
+        tx.commit()?;
+    }
+
+    Ok(matched.len() as u64)
 }
 
 fn memory_date_from_filename(filename: &str) -> Option<NaiveDate> {
@@ -539,4 +822,201 @@ mod tests {
             "core memory should remain"
         );
     }
+
+    fn backdate(workspace: &Path, key: &str, days: i64) {
+        let conn = Connection::open(workspace.join("memory").join("brain.db")).unwrap();
+        let ts = (Local::now() - Duration::days(days)).to_rfc3339();
+        conn.execute(
+            "UPDATE memories SET created_at = ?1, updated_at = ?1 WHERE key = ?2",
+            params![ts, key],
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn archives_old_daily_rows_and_keeps_core() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path();
+
+        let mem = SqliteMemory::new(workspace).unwrap();
+        mem.store("daily_old", "standup notes", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        mem.store("core_old", "user name", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("daily_new", "today", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        drop(mem);
+        backdate(workspace, "daily_old", 10);
+        backdate(workspace, "core_old", 10);
+
+        let report = run_now(&default_cfg(), workspace, false).unwrap();
+        assert_eq!(report.archived_rows, 1);
+        assert_eq!(report.purged_rows, 0);
+
+        let mem2 = SqliteMemory::new(workspace).unwrap();
+        assert!(mem2.get("daily_old").await.unwrap().is_none());
+        assert!(mem2.get("core_old").await.unwrap().is_some());
+        assert!(mem2.get("daily_new").await.unwrap().is_some());
+
+        let conn = Connection::open(workspace.join("memory").join("brain.db")).unwrap();
+        let archived: String = conn
+            .query_row(
+                "SELECT content FROM memories_archive WHERE key = 'daily_old'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(archived, "standup notes");
+    }
+
+    #[tokio::test]
+    async fn purges_rows_past_purge_window() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path();
+
+        let mem = SqliteMemory::new(workspace).unwrap();
+        mem.store("daily_ancient", "old", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        mem.store("daily_stale", "stale", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        drop(mem);
+        backdate(workspace, "daily_ancient", 60);
+        backdate(workspace, "daily_stale", 10);
+
+        // First pass: ancient row purged outright, stale row archived
+        let report = run_now(&default_cfg(), workspace, false).unwrap();
+        assert_eq!(report.purged_rows, 1);
+        assert_eq!(report.archived_rows, 1);
+
+        // Age the archived copy past the purge window
+        let conn = Connection::open(workspace.join("memory").join("brain.db")).unwrap();
+        let ts = (Local::now() - Duration::days(60)).to_rfc3339();
+        conn.execute("UPDATE memories_archive SET updated_at = ?1", params![ts])
+            .unwrap();
+
+        let report = run_now(&default_cfg(), workspace, false).unwrap();
+        assert_eq!(report.purged_rows, 1);
+        let left: i64 = conn
+            .query_row("SELECT COUNT(*) FROM memories_archive", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(left, 0);
+    }
+
+    #[tokio::test]
+    async fn dry_run_reports_without_changing_anything() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path();
+        fs::create_dir_all(workspace.join("memory")).unwrap();
+
+        let old = (Local::now().date_naive() - Duration::days(10))
+            .format("%Y-%m-%d")
+            .to_string();
+        let old_file = workspace.join("memory").join(format!("{old}.md"));
+        fs::write(&old_file, "old note").unwrap();
+
+        let mem = SqliteMemory::new(workspace).unwrap();
+        mem.store("conv_old", "chat", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        drop(mem);
+        backdate(workspace, "conv_old", 60);
+
+        let report = run_now(&default_cfg(), workspace, true).unwrap();
+        assert_eq!(report.archived_memory_files, 1);
+        assert_eq!(report.pruned_conversation_rows, 1);
+        assert!(report
+            .actions
+            .iter()
+            .any(|a| a == "prune row conversation:conv_old"));
+
+        assert!(old_file.exists(), "dry run must not move files");
+        assert!(!workspace.join("memory").join("archive").exists());
+        assert!(
+            !state_path(workspace).exists(),
+            "dry run must not write state"
+        );
+        let mem2 = SqliteMemory::new(workspace).unwrap();
+        assert!(mem2.get("conv_old").await.unwrap().is_some());
+    }
+
+    #[test]
+    fn prunes_old_completed_tasks() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path();
+        let old = crate::tasks::add_task(workspace, "Old chore", None, None, "cli").unwrap();
+        let open = crate::tasks::add_task(workspace, "Still open", None, None, "cli").unwrap();
+        crate::tasks::complete_task(workspace, old.id).unwrap();
+
+        let conn = Connection::open(workspace.join("tasks").join("tasks.db")).unwrap();
+        conn.execute(
+            "UPDATE tasks SET completed_at = ?1 WHERE id = ?2",
+            params![(Utc::now() - Duration::days(45)).to_rfc3339(), old.id],
+        )
+        .unwrap();
+        drop(conn);
+
+        let report = run_now(&default_cfg(), workspace, false).unwrap();
+        assert_eq!(report.pruned_tasks, 1);
+        assert_eq!(
+            report.actions,
+            [format!("prune task #{} Old chore", old.id)]
+        );
+
+        let left = crate::tasks::list_tasks(workspace, true).unwrap();
+        assert_eq!(left.iter().map(|t| t.id).collect::<Vec<_>>(), [open.id]);
+    }
+
+    #[test]
+    fn prunes_old_transcripts() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path();
+        let old = workspace.join("sessions").join("2020-01-01");
+        fs::create_dir_all(&old).unwrap();
+        fs::write(old.join("telegram-alice.jsonl"), "").unwrap();
+        crate::sessions::Transcript::start(workspace, "cli").user("hello");
+
+        let report = run_now(&default_cfg(), workspace, false).unwrap();
+        assert_eq!(report.pruned_transcripts, 1);
+        assert_eq!(
+            report.actions,
+            ["prune transcript sessions/2020-01-01/telegram-alice.jsonl"]
+        );
+        assert!(!old.exists());
+        assert_eq!(crate::sessions::list(workspace).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn status_summary_reads_last_report() {
+        let tmp = TempDir::new().unwrap();
+        assert!(status_summary(tmp.path()).is_none());
+
+        let report = HygieneReport {
+            archived_memory_files: 100,
+            archived_rows: 20,
+            purged_rows: 25,
+            pruned_conversation_rows: 5,
+            ..HygieneReport::default()
+        };
+        write_state(tmp.path(), &report).unwrap();
+
+        assert_eq!(
+            status_summary(tmp.path()).unwrap(),
+            "archived 120, purged 30, last run just now"
+        );
+    }
+
+    #[test]
+    fn format_age_units() {
+        assert_eq!(format_age(Duration::seconds(30)), "just now");
+        assert_eq!(format_age(Duration::minutes(45)), "45m ago");
+        assert_eq!(format_age(Duration::hours(2)), "2h ago");
+        assert_eq!(format_age(Duration::days(3)), "3d ago");
+    }
 }
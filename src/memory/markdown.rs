@@ -1,6 +1,6 @@
-use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use super::traits::{normalize_tags, Memory, MemoryCategory, MemoryEntry};
 use async_trait::async_trait;
-use chrono::Local;
+use chrono::{Local, NaiveDate};
 use std::path::{Path, PathBuf};
 use tokio::fs;
 
@@ -29,8 +29,51 @@ impl MarkdownMemory {
     }
 
     fn daily_path(&self) -> PathBuf {
-        let date = Local::now().format("%Y-%m-%d").to_string();
-        self.memory_dir().join(format!("{date}.md"))
+        self.daily_path_for(Local::now().date_naive())
+    }
+
+    fn daily_path_for(&self, date: NaiveDate) -> PathBuf {
+        self.memory_dir()
+            .join(format!("{}.md", date.format("%Y-%m-%d")))
+    }
+
+    /// File an entry belongs in: core → MEMORY.md, everything else → a daily log
+    fn path_for(&self, category: &MemoryCategory, date: Option<NaiveDate>) -> PathBuf {
+        match category {
+            MemoryCategory::Core => self.core_path(),
+            _ => date.map_or_else(|| self.daily_path(), |d| self.daily_path_for(d)),
+        }
+    }
+
+    /// `- **key**: content #tag`, tagged `[category]` when the file alone doesn't imply it
+    fn format_line(key: &str, content: &str, category: &MemoryCategory, tags: &[String]) -> String {
+        let mut line = match category {
+            MemoryCategory::Core | MemoryCategory::Daily => format!("- **{key}**: {content}"),
+            other => format!("- **{key}** [{other}]: {content}"),
+        };
+        let (_, inline) = split_tags(content);
+        for tag in normalize_tags(tags) {
+            if !inline.contains(&tag) {
+                line.push_str(" #");
+                line.push_str(&tag);
+            }
+        }
+        line
+    }
+
+    /// Split a `**key** [category]: content` line (bullet already stripped).
+    /// Returns `None` for free-form lines that weren't written by `store`.
+    fn parse_line(line: &str) -> Option<(&str, Option<MemoryCategory>, &str)> {
+        let rest = line.strip_prefix("**")?;
+        let (key, rest) = rest.split_once("**")?;
+        let (category, content) = match rest.strip_prefix(" [") {
+            Some(tagged) => {
+                let (cat, content) = tagged.split_once("]: ")?;
+                (Some(parse_category(cat)), content)
+            }
+            None => (None, rest.strip_prefix(": ")?),
+        };
+        Some((key, category, content))
     }
 
     async fn ensure_dirs(&self) -> anyhow::Result<()> {
@@ -51,7 +94,10 @@ impl MarkdownMemory {
             let header = if path == self.core_path() {
                 "# Long-Term Memory\n\n"
             } else {
-                let date = Local::now().format("%Y-%m-%d").to_string();
+                let date = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or_default();
                 &format!("# Daily Log — {date}\n\n")
             };
             format!("{header}{content}\n")
@@ -83,11 +129,21 @@ impl MarkdownMemory {
             .map(|(i, line)| {
                 let trimmed = line.trim();
                 let clean = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+                let (key, entry_category, content) = match Self::parse_line(clean) {
+                    Some((key, tagged, content)) => (
+                        key.to_string(),
+                        tagged.unwrap_or_else(|| category.clone()),
+                        content,
+                    ),
+                    None => (format!("{filename}:{i}"), category.clone(), clean),
+                };
+                let (content, tags) = split_tags(content);
                 MemoryEntry {
                     id: format!("{filename}:{i}"),
-                    key: format!("{filename}:{i}"),
-                    content: clean.to_string(),
-                    category: category.clone(),
+                    key,
+                    content: content.to_string(),
+                    category: entry_category,
+                    tags,
                     timestamp: filename.to_string(),
                     session_id: None,
                     score: None,
@@ -132,33 +188,97 @@ impl MarkdownMemory {
     }
 }
 
+/// A `#tag` word; `#1` and bare `#` are not tags
+fn is_hashtag(word: &str) -> bool {
+    word.strip_prefix('#')
+        .and_then(|tag| tag.chars().next())
+        .is_some_and(char::is_alphabetic)
+}
+
+/// Collect the `#tags` in a memory line. Trailing tags are stripped from the content;
+/// tags used inline stay part of the text.
+fn split_tags(content: &str) -> (&str, Vec<String>) {
+    let words: Vec<&str> = content
+        .split_whitespace()
+        .filter(|w| is_hashtag(w))
+        .map(|w| w.trim_end_matches(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_')))
+        .collect();
+    let tags = normalize_tags(&words);
+
+    let mut body = content.trim_end();
+    while let Some((rest, last)) = body.rsplit_once(char::is_whitespace) {
+        if !is_hashtag(last) {
+            break;
+        }
+        body = rest.trim_end();
+    }
+    (body, tags)
+}
+
+fn parse_category(raw: &str) -> MemoryCategory {
+    match raw {
+        "core" => MemoryCategory::Core,
+        "daily" => MemoryCategory::Daily,
+        "conversation" => MemoryCategory::Conversation,
+        other => MemoryCategory::Custom(other.to_string()),
+    }
+}
+
 #[async_trait]
 impl Memory for MarkdownMemory {
     fn name(&self) -> &str {
         "markdown"
     }
 
-    async fn store(
+    async fn store_with_tags(
         &self,
         key: &str,
         content: &str,
         category: MemoryCategory,
+        tags: &[String],
     ) -> anyhow::Result<()> {
-        let entry = format!("- **{key}**: {content}");
-        let path = match category {
-            MemoryCategory::Core => self.core_path(),
-            _ => self.daily_path(),
-        };
+        let entry = Self::format_line(key, content, &category, tags);
+        let path = self.path_for(&category, None);
         self.append_to_file(&path, &entry).await
     }
 
-    async fn recall(&self, query: &str, limit: usize) -> anyhow::Result<Vec<MemoryEntry>> {
-        let all = self.read_all_entries().await?;
+    async fn import(&self, entry: &MemoryEntry) -> anyhow::Result<()> {
+        // Keep dated entries in the daily log for the day they were written
+        let date = entry
+            .timestamp
+            .get(..10)
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+        let line = Self::format_line(&entry.key, &entry.content, &entry.category, &entry.tags);
+        let path = self.path_for(&entry.category, date);
+        self.append_to_file(&path, &line).await
+    }
+
+    async fn recall(
+        &self,
+        query: &str,
+        limit: usize,
+        tags: &[String],
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let tags = normalize_tags(tags);
+        let all = self
+            .read_all_entries()
+            .await?
+            .into_iter()
+            .filter(|entry| tags.iter().all(|t| entry.tags.contains(t)));
         let query_lower = query.to_lowercase();
         let keywords: Vec<&str> = query_lower.split_whitespace().collect();
 
+        if keywords.is_empty() && !tags.is_empty() {
+            return Ok(all
+                .map(|mut entry| {
+                    entry.score = Some(1.0);
+                    entry
+                })
+                .take(limit)
+                .collect());
+        }
+
         let mut scored: Vec<MemoryEntry> = all
-            .into_iter()
             .filter_map(|mut entry| {
                 let content_lower = entry.content.to_lowercase();
                 let matched = keywords
@@ -274,7 +394,7 @@ mod tests {
             .await
             .unwrap();
 
-        let results = mem.recall("Rust", 10).await.unwrap();
+        let results = mem.recall("Rust", 10, &[]).await.unwrap();
         assert!(results.len() >= 2);
         assert!(results
             .iter()
@@ -287,7 +407,7 @@ mod tests {
         mem.store("a", "Rust is great", MemoryCategory::Core)
             .await
             .unwrap();
-        let results = mem.recall("javascript", 10).await.unwrap();
+        let results = mem.recall("javascript", 10, &[]).await.unwrap();
         assert!(results.is_empty());
     }
 
@@ -332,7 +452,7 @@ mod tests {
     #[tokio::test]
     async fn markdown_empty_recall() {
         let (_tmp, mem) = temp_workspace();
-        let results = mem.recall("anything", 10).await.unwrap();
+        let results = mem.recall("anything", 10, &[]).await.unwrap();
         assert!(results.is_empty());
     }
 
@@ -341,4 +461,94 @@ mod tests {
         let (_tmp, mem) = temp_workspace();
         assert_eq!(mem.count().await.unwrap(), 0);
     }
+
+    #[tokio::test]
+    async fn markdown_list_parses_keys_and_tagged_categories() {
+        let (_tmp, mem) = temp_workspace();
+        mem.store("lang", "User likes Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("chat", "asked about tokio", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+
+        let all = mem.list(None).await.unwrap();
+        let lang = all.iter().find(|e| e.key == "lang").unwrap();
+        assert_eq!(lang.content, "User likes Rust");
+        assert_eq!(lang.category, MemoryCategory::Core);
+        let chat = all.iter().find(|e| e.key == "chat").unwrap();
+        assert_eq!(chat.content, "asked about tokio");
+        assert_eq!(chat.category, MemoryCategory::Conversation);
+    }
+
+    #[tokio::test]
+    async fn markdown_free_form_lines_keep_positional_keys() {
+        let (_tmp, mem) = temp_workspace();
+        sync_fs::write(mem.core_path(), "# Long-Term Memory\n\n- just a note\n").unwrap();
+        let all = mem.list(None).await.unwrap();
+        assert_eq!(all[0].key, "MEMORY:0");
+        assert_eq!(all[0].content, "just a note");
+    }
+
+    #[tokio::test]
+    async fn markdown_import_files_entry_under_its_date() {
+        let (_tmp, mem) = temp_workspace();
+        let entry = MemoryEntry {
+            id: "x".into(),
+            key: "old".into(),
+            content: "from last year".into(),
+            category: MemoryCategory::Daily,
+            tags: Vec::new(),
+            timestamp: "2025-03-04T10:00:00+00:00".into(),
+            session_id: None,
+            score: None,
+        };
+        mem.import(&entry).await.unwrap();
+
+        let path = mem.memory_dir().join("2025-03-04.md");
+        let content = sync_fs::read_to_string(path).unwrap();
+        assert!(content.starts_with("# Daily Log — 2025-03-04"));
+        assert!(content.contains("- **old**: from last year"));
+    }
+
+    #[tokio::test]
+    async fn markdown_tags_roundtrip_inline() {
+        let (_tmp, mem) = temp_workspace();
+        let tags = vec!["Project-X".to_string(), "#infra".to_string()];
+        mem.store_with_tags(
+            "deploy",
+            "Use blue/green deploys",
+            MemoryCategory::Core,
+            &tags,
+        )
+        .await
+        .unwrap();
+        mem.store("misc", "Use tabs in Makefiles", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let content = sync_fs::read_to_string(mem.core_path()).unwrap();
+        assert!(content.contains("- **deploy**: Use blue/green deploys #project-x #infra"));
+
+        let entry = mem.get("deploy").await.unwrap().unwrap();
+        assert_eq!(entry.content, "Use blue/green deploys");
+        assert_eq!(entry.tags, vec!["project-x", "infra"]);
+
+        let tagged = mem.recall("Use", 10, &["project-x".into()]).await.unwrap();
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].key, "deploy");
+        let listed = mem.recall("", 10, &["infra".into()]).await.unwrap();
+        assert_eq!(listed.len(), 1);
+    }
+
+    #[test]
+    fn split_tags_keeps_inline_tags_in_text() {
+        let (body, tags) = split_tags("Ask #alice about the #rust, migration #todo #q3");
+        assert_eq!(body, "Ask #alice about the #rust, migration");
+        assert_eq!(tags, vec!["alice", "rust", "todo", "q3"]);
+
+        let (body, tags) = split_tags("Fixed issue #42 in C#");
+        assert_eq!(body, "Fixed issue #42 in C#");
+        assert!(tags.is_empty());
+    }
 }
@@ -1,16 +1,21 @@
 pub mod chunker;
+pub mod cli;
 pub mod embeddings;
 pub mod hygiene;
 pub mod markdown;
+pub mod portable;
+#[cfg(feature = "postgres")]
+pub mod postgres;
 pub mod sqlite;
 pub mod traits;
 pub mod vector;
 
+pub use cli::handle_command;
 pub use markdown::MarkdownMemory;
 pub use sqlite::SqliteMemory;
 pub use traits::Memory;
 #[allow(unused_imports)]
-pub use traits::{MemoryCategory, MemoryEntry};
+pub use traits::{normalize_tags, MemoryCategory, MemoryEntry};
 
 use crate::config::MemoryConfig;
 use std::path::Path;
@@ -27,15 +32,18 @@ pub fn create_memory(
         tracing::warn!("memory hygiene skipped: {e}");
     }
 
+    let create_embedder = || -> Arc<dyn embeddings::EmbeddingProvider> {
+        Arc::from(embeddings::create_embedding_provider(
+            &config.embedding_provider,
+            api_key,
+            &config.embedding_model,
+            config.embedding_dimensions,
+        ))
+    };
+
     match config.backend.as_str() {
         "sqlite" => {
-            let embedder: Arc<dyn embeddings::EmbeddingProvider> =
-                Arc::from(embeddings::create_embedding_provider(
-                    &config.embedding_provider,
-                    api_key,
-                    &config.embedding_model,
-                    config.embedding_dimensions,
-                ));
+            let embedder = create_embedder();
 
             #[allow(clippy::cast_possible_truncation)]
             let mem = SqliteMemory::with_embedder(
@@ -47,6 +55,29 @@ pub fn create_memory(
             )?;
             Ok(Box::new(mem))
         }
+        #[cfg(feature = "postgres")]
+        "postgres" => {
+            let Some(url) = config
+                .postgres_url
+                .as_deref()
+                .filter(|u| !u.trim().is_empty())
+            else {
+                anyhow::bail!("memory.backend = \"postgres\" 需要设置 memory.postgres_url");
+            };
+
+            #[allow(clippy::cast_possible_truncation)]
+            let mem = postgres::PostgresMemory::new(
+                url,
+                create_embedder(),
+                config.vector_weight as f32,
+                config.keyword_weight as f32,
+            );
+            Ok(Box::new(mem))
+        }
+        #[cfg(not(feature = "postgres"))]
+        "postgres" => anyhow::bail!(
+            "memory.backend = \"postgres\" 需要使用 `cargo build --features postgres` 编译的 jarvis"
+        ),
         "markdown" | "none" => Ok(Box::new(MarkdownMemory::new(workspace_dir))),
         other => {
             tracing::warn!("Unknown memory backend '{other}', falling back to markdown");
@@ -93,6 +124,17 @@ mod tests {
         assert_eq!(mem.name(), "markdown");
     }
 
+    #[test]
+    fn factory_postgres_requires_url_or_feature() {
+        let tmp = TempDir::new().unwrap();
+        let cfg = MemoryConfig {
+            backend: "postgres".into(),
+            postgres_url: None,
+            ..MemoryConfig::default()
+        };
+        assert!(create_memory(&cfg, tmp.path(), None).is_err());
+    }
+
     #[test]
     fn factory_unknown_falls_back_to_markdown() {
         let tmp = TempDir::new().unwrap();
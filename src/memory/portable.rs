@@ -0,0 +1,210 @@
+use super::traits::{Memory, MemoryEntry};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Current export document version
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Portable, backend-independent memory export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryExport {
+    pub version: u32,
+    pub exported_at: String,
+    /// Backend the entries were read from (informational)
+    pub backend: String,
+    pub entries: Vec<MemoryEntry>,
+}
+
+/// Outcome of an import run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub skipped: usize,
+}
+
+/// Read every entry from `mem` into an export document
+pub async fn export_all(mem: &dyn Memory) -> Result<MemoryExport> {
+    let mut entries = mem.list(None).await?;
+    for entry in &mut entries {
+        entry.score = None;
+    }
+    Ok(MemoryExport {
+        version: EXPORT_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        backend: mem.name().to_string(),
+        entries,
+    })
+}
+
+/// Parse an export file — accepts the versioned document or a bare entry array
+/// (the output of `jarvis memory export` before the document format existed).
+pub fn parse_export(raw: &str) -> Result<Vec<MemoryEntry>> {
+    let value: serde_json::Value = serde_json::from_str(raw).context("导入文件不是有效的 JSON")?;
+    if value.is_array() {
+        return serde_json::from_value(value).context("解析记忆条目失败");
+    }
+
+    let doc: MemoryExport = serde_json::from_value(value).context("解析记忆导出文件失败")?;
+    if doc.version > EXPORT_VERSION {
+        anyhow::bail!(
+            "导出文件版本 {} 高于当前支持的版本 {EXPORT_VERSION}，请升级 jarvis",
+            doc.version
+        );
+    }
+    Ok(doc.entries)
+}
+
+/// Store `entries` into `mem`, skipping any (key, content) pair already present.
+///
+/// Re-importing the same file is therefore a no-op.
+pub async fn import_entries(mem: &dyn Memory, entries: &[MemoryEntry]) -> Result<ImportSummary> {
+    let mut seen: HashSet<(String, String)> = mem
+        .list(None)
+        .await?
+        .into_iter()
+        .map(|e| (e.key, e.content))
+        .collect();
+
+    let mut summary = ImportSummary::default();
+    for entry in entries {
+        if !seen.insert((entry.key.clone(), entry.content.clone())) {
+            summary.skipped += 1;
+            continue;
+        }
+        mem.import(entry)
+            .await
+            .with_context(|| format!("导入记忆「{}」失败", entry.key))?;
+        summary.imported += 1;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{MarkdownMemory, MemoryCategory, SqliteMemory};
+    use tempfile::TempDir;
+
+    async fn seed(mem: &dyn Memory) {
+        mem.store("lang", "User prefers Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("standup", "Shipped the gateway fix", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        mem.store("chat", "Asked about tokio", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        mem.store(
+            "proj",
+            "Jarvis release on Friday",
+            MemoryCategory::Custom("project".into()),
+        )
+        .await
+        .unwrap();
+    }
+
+    fn sorted(mut entries: Vec<MemoryEntry>) -> Vec<(String, String, MemoryCategory)> {
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        entries
+            .into_iter()
+            .map(|e| (e.key, e.content, e.category))
+            .collect()
+    }
+
+    async fn roundtrip(source: &dyn Memory, target: &dyn Memory) {
+        seed(source).await;
+        let json = serde_json::to_string(&export_all(source).await.unwrap()).unwrap();
+        let entries = parse_export(&json).unwrap();
+
+        let summary = import_entries(target, &entries).await.unwrap();
+        assert_eq!(
+            summary,
+            ImportSummary {
+                imported: 4,
+                skipped: 0
+            }
+        );
+        assert_eq!(
+            sorted(target.list(None).await.unwrap()),
+            sorted(source.list(None).await.unwrap())
+        );
+
+        // Idempotent re-import
+        let again = import_entries(target, &entries).await.unwrap();
+        assert_eq!(
+            again,
+            ImportSummary {
+                imported: 0,
+                skipped: 4
+            }
+        );
+        assert_eq!(target.count().await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn sqlite_to_markdown_roundtrip() {
+        let (a, b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let source = SqliteMemory::new(a.path()).unwrap();
+        let target = MarkdownMemory::new(b.path());
+        roundtrip(&source, &target).await;
+    }
+
+    #[tokio::test]
+    async fn markdown_to_sqlite_roundtrip() {
+        let (a, b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let source = MarkdownMemory::new(a.path());
+        let target = SqliteMemory::new(b.path()).unwrap();
+        roundtrip(&source, &target).await;
+    }
+
+    #[tokio::test]
+    async fn sqlite_to_sqlite_preserves_timestamps() {
+        let (a, b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let source = SqliteMemory::new(a.path()).unwrap();
+        let target = SqliteMemory::new(b.path()).unwrap();
+        source.store("k", "v", MemoryCategory::Core).await.unwrap();
+
+        let export = export_all(&source).await.unwrap();
+        import_entries(&target, &export.entries).await.unwrap();
+
+        let original = source.get("k").await.unwrap().unwrap();
+        let copied = target.get("k").await.unwrap().unwrap();
+        assert_eq!(copied.timestamp, original.timestamp);
+    }
+
+    #[tokio::test]
+    async fn import_updates_changed_content_for_same_key() {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        mem.store("k", "old", MemoryCategory::Core).await.unwrap();
+
+        let mut entry = mem.get("k").await.unwrap().unwrap();
+        entry.content = "new".into();
+        let summary = import_entries(&mem, &[entry]).await.unwrap();
+        assert_eq!(summary.imported, 1);
+        assert_eq!(mem.get("k").await.unwrap().unwrap().content, "new");
+    }
+
+    #[test]
+    fn parse_accepts_bare_array() {
+        let raw = r#"[{"id":"1","key":"k","content":"v","category":"core",
+            "timestamp":"2026-01-01T00:00:00+00:00","session_id":null,"score":null}]"#;
+        let entries = parse_export(raw).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "k");
+    }
+
+    #[test]
+    fn parse_rejects_newer_version() {
+        let raw = r#"{"version":99,"exported_at":"x","backend":"sqlite","entries":[]}"#;
+        assert!(parse_export(raw).is_err());
+    }
+
+    #[test]
+    fn parse_rejects_garbage() {
+        assert!(parse_export("not json").is_err());
+    }
+}
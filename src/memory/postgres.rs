@@ -0,0 +1,609 @@
+use super::embeddings::EmbeddingProvider;
+use super::traits::{normalize_tags, Memory, MemoryCategory, MemoryEntry};
+use super::vector;
+use async_trait::async_trait;
+use chrono::Local;
+use std::fmt::Write;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio_postgres::{Client, NoTls, Row};
+use uuid::Uuid;
+
+/// Table shared by every Jarvis instance pointed at the same database
+const TABLE: &str = "jarvis_memories";
+
+/// Postgres-backed shared memory — for teams running several Jarvis instances
+///
+/// Mirrors the sqlite backend's semantics:
+/// - **Keyword Search**: generated `tsvector` column + GIN index, ranked with `ts_rank_cd`
+/// - **Vector Search**: pgvector cosine distance (only when an embedder is configured)
+/// - **Hybrid Merge**: same `vector_weight` / `keyword_weight` fusion as sqlite
+/// - **Tags**: `TEXT[]` column with a GIN index, filtered with `@>`
+///
+/// The connection is opened lazily on first use and re-established if it drops.
+pub struct PostgresMemory {
+    url: String,
+    client: Mutex<Option<Arc<Client>>>,
+    embedder: Arc<dyn EmbeddingProvider>,
+    vector_weight: f32,
+    keyword_weight: f32,
+}
+
+impl PostgresMemory {
+    pub fn new(
+        url: &str,
+        embedder: Arc<dyn EmbeddingProvider>,
+        vector_weight: f32,
+        keyword_weight: f32,
+    ) -> Self {
+        Self {
+            url: url.to_string(),
+            client: Mutex::new(None),
+            embedder,
+            vector_weight,
+            keyword_weight,
+        }
+    }
+
+    fn vectors_enabled(&self) -> bool {
+        self.embedder.dimensions() > 0
+    }
+
+    /// Get a live client, connecting (and initializing the schema) if needed
+    async fn client(&self) -> anyhow::Result<Arc<Client>> {
+        let mut guard = self.client.lock().await;
+        if let Some(client) = guard.as_ref().filter(|c| !c.is_closed()) {
+            return Ok(Arc::clone(client));
+        }
+
+        let (client, connection) = tokio_postgres::connect(&self.url, NoTls)
+            .await
+            .map_err(|e| anyhow::anyhow!("连接 postgres 失败: {e}"))?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                tracing::warn!("postgres 记忆连接中断: {e}");
+            }
+        });
+
+        Self::init_schema(&client, self.embedder.dimensions()).await?;
+
+        let client = Arc::new(client);
+        *guard = Some(Arc::clone(&client));
+        Ok(client)
+    }
+
+    /// Create the memories table, full-text index and (optionally) the pgvector column
+    async fn init_schema(client: &Client, dims: usize) -> anyhow::Result<()> {
+        client
+            .batch_execute(&format!(
+                "CREATE TABLE IF NOT EXISTS {TABLE} (
+                    id          TEXT PRIMARY KEY,
+                    key         TEXT NOT NULL UNIQUE,
+                    content     TEXT NOT NULL,
+                    category    TEXT NOT NULL DEFAULT 'core',
+                    created_at  TEXT NOT NULL,
+                    updated_at  TEXT NOT NULL,
+                    search_tsv  TSVECTOR GENERATED ALWAYS AS
+                                (to_tsvector('simple', key || ' ' || content)) STORED
+                );
+                CREATE INDEX IF NOT EXISTS idx_{TABLE}_category ON {TABLE}(category);
+                CREATE INDEX IF NOT EXISTS idx_{TABLE}_tsv ON {TABLE} USING GIN(search_tsv);
+                ALTER TABLE {TABLE} ADD COLUMN IF NOT EXISTS tags TEXT[] NOT NULL DEFAULT '{{}}';
+                CREATE INDEX IF NOT EXISTS idx_{TABLE}_tags ON {TABLE} USING GIN(tags);"
+            ))
+            .await?;
+
+        if dims > 0 {
+            client
+                .batch_execute(&format!(
+                    "CREATE EXTENSION IF NOT EXISTS vector;
+                     ALTER TABLE {TABLE} ADD COLUMN IF NOT EXISTS embedding vector({dims});"
+                ))
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn category_to_str(cat: &MemoryCategory) -> String {
+        match cat {
+            MemoryCategory::Core => "core".into(),
+            MemoryCategory::Daily => "daily".into(),
+            MemoryCategory::Conversation => "conversation".into(),
+            MemoryCategory::Custom(name) => name.clone(),
+        }
+    }
+
+    fn str_to_category(s: &str) -> MemoryCategory {
+        match s {
+            "core" => MemoryCategory::Core,
+            "daily" => MemoryCategory::Daily,
+            "conversation" => MemoryCategory::Conversation,
+            other => MemoryCategory::Custom(other.to_string()),
+        }
+    }
+
+    fn row_to_entry(row: &Row, score: Option<f64>) -> MemoryEntry {
+        MemoryEntry {
+            id: row.get(0),
+            key: row.get(1),
+            content: row.get(2),
+            category: Self::str_to_category(&row.get::<_, String>(3)),
+            tags: row.get(5),
+            timestamp: row.get(4),
+            session_id: None,
+            score,
+        }
+    }
+
+    /// Build an OR-ed `to_tsquery` expression with every word quoted as a literal lexeme,
+    /// so user input can never inject tsquery operators.
+    fn build_tsquery(query: &str) -> String {
+        query
+            .split_whitespace()
+            .map(|w| format!("'{}'", w.replace('\\', "\\\\").replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    /// pgvector text literal: `[0.1,0.2,...]`
+    fn vector_literal(v: &[f32]) -> String {
+        let mut out = String::with_capacity(v.len() * 8 + 2);
+        out.push('[');
+        for (i, x) in v.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, "{x}");
+        }
+        out.push(']');
+        out
+    }
+
+    async fn embed(&self, text: &str) -> anyhow::Result<Option<Vec<f32>>> {
+        if !self.vectors_enabled() {
+            return Ok(None);
+        }
+        Ok(Some(self.embedder.embed_one(text).await?))
+    }
+
+    /// Full-text keyword search ranked by `ts_rank_cd`
+    async fn keyword_search(
+        client: &Client,
+        query: &str,
+        limit: usize,
+        tags: &[String],
+    ) -> anyhow::Result<Vec<(String, f32)>> {
+        let tsquery = Self::build_tsquery(query);
+        if tsquery.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        #[allow(clippy::cast_possible_wrap)]
+        let limit = limit as i64;
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT id, ts_rank_cd(search_tsv, q)::REAL AS score
+                     FROM {TABLE}, to_tsquery('simple', $1) q
+                     WHERE search_tsv @@ q AND tags @> $3
+                     ORDER BY score DESC
+                     LIMIT $2"
+                ),
+                &[&tsquery, &limit, &tags],
+            )
+            .await?;
+        Ok(rows.iter().map(|r| (r.get(0), r.get(1))).collect())
+    }
+
+    /// pgvector cosine similarity search
+    async fn vector_search(
+        client: &Client,
+        query_embedding: &[f32],
+        limit: usize,
+        tags: &[String],
+    ) -> anyhow::Result<Vec<(String, f32)>> {
+        let literal = Self::vector_literal(query_embedding);
+        #[allow(clippy::cast_possible_wrap)]
+        let limit = limit as i64;
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT id, (1 - (embedding <=> $1::TEXT::vector))::REAL AS sim
+                     FROM {TABLE}
+                     WHERE embedding IS NOT NULL AND tags @> $3
+                     ORDER BY embedding <=> $1::TEXT::vector
+                     LIMIT $2"
+                ),
+                &[&literal, &limit, &tags],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|r| (r.get::<_, String>(0), r.get::<_, f32>(1)))
+            .filter(|(_, sim)| *sim > 0.0)
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Memory for PostgresMemory {
+    fn name(&self) -> &str {
+        "postgres"
+    }
+
+    async fn store_with_tags(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        tags: &[String],
+    ) -> anyhow::Result<()> {
+        let tags = normalize_tags(tags);
+        let embedding = self.embed(content).await?;
+        let client = self.client().await?;
+        let now = Local::now().to_rfc3339();
+        let cat = Self::category_to_str(&category);
+        let id = Uuid::new_v4().to_string();
+
+        if self.vectors_enabled() {
+            let literal = embedding.as_deref().map(Self::vector_literal);
+            client
+                .execute(
+                    &format!(
+                        "INSERT INTO {TABLE} (id, key, content, category, embedding, tags, created_at, updated_at)
+                         VALUES ($1, $2, $3, $4, $5::TEXT::vector, $7, $6, $6)
+                         ON CONFLICT (key) DO UPDATE SET
+                            content = EXCLUDED.content,
+                            category = EXCLUDED.category,
+                            embedding = EXCLUDED.embedding,
+                            tags = EXCLUDED.tags,
+                            updated_at = EXCLUDED.updated_at"
+                    ),
+                    &[&id, &key, &content, &cat, &literal, &now, &tags],
+                )
+                .await?;
+        } else {
+            client
+                .execute(
+                    &format!(
+                        "INSERT INTO {TABLE} (id, key, content, category, tags, created_at, updated_at)
+                         VALUES ($1, $2, $3, $4, $6, $5, $5)
+                         ON CONFLICT (key) DO UPDATE SET
+                            content = EXCLUDED.content,
+                            category = EXCLUDED.category,
+                            tags = EXCLUDED.tags,
+                            updated_at = EXCLUDED.updated_at"
+                    ),
+                    &[&id, &key, &content, &cat, &now, &tags],
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn recall(
+        &self,
+        query: &str,
+        limit: usize,
+        tags: &[String],
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let tags = normalize_tags(tags);
+        #[allow(clippy::cast_possible_wrap)]
+        let limit_i64 = limit as i64;
+        if query.trim().is_empty() {
+            if tags.is_empty() {
+                return Ok(Vec::new());
+            }
+            let client = self.client().await?;
+            let rows = client
+                .query(
+                    &format!(
+                        "SELECT id, key, content, category, created_at, tags FROM {TABLE}
+                         WHERE tags @> $1
+                         ORDER BY updated_at DESC
+                         LIMIT $2"
+                    ),
+                    &[&tags, &limit_i64],
+                )
+                .await?;
+            return Ok(rows
+                .iter()
+                .map(|r| Self::row_to_entry(r, Some(1.0)))
+                .collect());
+        }
+
+        let query_embedding = self.embed(query).await?;
+        let client = self.client().await?;
+
+        let keyword_results = Self::keyword_search(&client, query, limit * 2, &tags)
+            .await
+            .unwrap_or_default();
+        let vector_results = match query_embedding {
+            Some(ref qe) => Self::vector_search(&client, qe, limit * 2, &tags)
+                .await
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+
+        let merged = if vector_results.is_empty() {
+            keyword_results
+                .iter()
+                .map(|(id, score)| vector::ScoredResult {
+                    id: id.clone(),
+                    vector_score: None,
+                    keyword_score: Some(*score),
+                    final_score: *score,
+                })
+                .collect::<Vec<_>>()
+        } else {
+            vector::hybrid_merge(
+                &vector_results,
+                &keyword_results,
+                self.vector_weight,
+                self.keyword_weight,
+                limit,
+            )
+        };
+
+        let mut results = Vec::new();
+        for scored in &merged {
+            let row = client
+                .query_opt(
+                    &format!(
+                        "SELECT id, key, content, category, created_at, tags FROM {TABLE} WHERE id = $1"
+                    ),
+                    &[&scored.id],
+                )
+                .await?;
+            if let Some(row) = row {
+                results.push(Self::row_to_entry(
+                    &row,
+                    Some(f64::from(scored.final_score)),
+                ));
+            }
+        }
+
+        // Full-text found nothing (e.g. partial words) — fall back to substring match
+        if results.is_empty() {
+            let patterns: Vec<String> =
+                query.split_whitespace().map(|w| format!("%{w}%")).collect();
+            let rows = client
+                .query(
+                    &format!(
+                        "SELECT id, key, content, category, created_at, tags FROM {TABLE}
+                         WHERE (content ILIKE ANY($1) OR key ILIKE ANY($1)) AND tags @> $3
+                         ORDER BY updated_at DESC
+                         LIMIT $2"
+                    ),
+                    &[&patterns, &limit_i64, &tags],
+                )
+                .await?;
+            results.extend(rows.iter().map(|r| Self::row_to_entry(r, Some(1.0))));
+        }
+
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>> {
+        let client = self.client().await?;
+        let row = client
+            .query_opt(
+                &format!(
+                    "SELECT id, key, content, category, created_at, tags FROM {TABLE} WHERE key = $1"
+                ),
+                &[&key],
+            )
+            .await?;
+        Ok(row.map(|r| Self::row_to_entry(&r, None)))
+    }
+
+    async fn list(&self, category: Option<&MemoryCategory>) -> anyhow::Result<Vec<MemoryEntry>> {
+        let client = self.client().await?;
+        let rows = if let Some(cat) = category {
+            let cat_str = Self::category_to_str(cat);
+            client
+                .query(
+                    &format!(
+                        "SELECT id, key, content, category, created_at, tags FROM {TABLE}
+                         WHERE category = $1 ORDER BY updated_at DESC"
+                    ),
+                    &[&cat_str],
+                )
+                .await?
+        } else {
+            client
+                .query(
+                    &format!(
+                        "SELECT id, key, content, category, created_at, tags FROM {TABLE}
+                         ORDER BY updated_at DESC"
+                    ),
+                    &[],
+                )
+                .await?
+        };
+        Ok(rows.iter().map(|r| Self::row_to_entry(r, None)).collect())
+    }
+
+    async fn forget(&self, key: &str) -> anyhow::Result<bool> {
+        let client = self.client().await?;
+        let affected = client
+            .execute(&format!("DELETE FROM {TABLE} WHERE key = $1"), &[&key])
+            .await?;
+        Ok(affected > 0)
+    }
+
+    async fn count(&self) -> anyhow::Result<usize> {
+        let client = self.client().await?;
+        let row = client
+            .query_one(&format!("SELECT COUNT(*) FROM {TABLE}"), &[])
+            .await?;
+        let count: i64 = row.get(0);
+        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+        Ok(count as usize)
+    }
+
+    async fn health_check(&self) -> bool {
+        match self.client().await {
+            Ok(client) => client.simple_query("SELECT 1").await.is_ok(),
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tsquery_quotes_every_word() {
+        assert_eq!(
+            PostgresMemory::build_tsquery("rust async"),
+            "'rust' | 'async'"
+        );
+        assert_eq!(PostgresMemory::build_tsquery("   "), "");
+    }
+
+    #[test]
+    fn tsquery_escapes_operators_and_quotes() {
+        assert_eq!(
+            PostgresMemory::build_tsquery("it's a&b !x"),
+            "'it''s' | 'a&b' | '!x'"
+        );
+        assert_eq!(
+            PostgresMemory::build_tsquery(r"back\slash"),
+            r"'back\\slash'"
+        );
+    }
+
+    #[test]
+    fn vector_literal_format() {
+        assert_eq!(
+            PostgresMemory::vector_literal(&[1.0, 0.5, -2.0]),
+            "[1,0.5,-2]"
+        );
+        assert_eq!(PostgresMemory::vector_literal(&[]), "[]");
+    }
+
+    #[test]
+    fn category_roundtrip() {
+        for cat in [
+            MemoryCategory::Core,
+            MemoryCategory::Daily,
+            MemoryCategory::Conversation,
+            MemoryCategory::Custom("project".into()),
+        ] {
+            let s = PostgresMemory::category_to_str(&cat);
+            assert_eq!(PostgresMemory::str_to_category(&s), cat);
+        }
+    }
+
+    // ── Integration tests (set JARVIS_TEST_POSTGRES_URL to run) ──
+
+    fn test_memory() -> Option<PostgresMemory> {
+        let url = std::env::var("JARVIS_TEST_POSTGRES_URL").ok()?;
+        Some(PostgresMemory::new(
+            &url,
+            Arc::new(super::super::embeddings::NoopEmbedding),
+            0.7,
+            0.3,
+        ))
+    }
+
+    fn unique_key(name: &str) -> String {
+        format!("test_{name}_{}", Uuid::new_v4().simple())
+    }
+
+    #[tokio::test]
+    async fn pg_store_get_forget_roundtrip() {
+        let Some(mem) = test_memory() else { return };
+        let key = unique_key("roundtrip");
+
+        mem.store(&key, "User prefers Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let entry = mem.get(&key).await.unwrap().unwrap();
+        assert_eq!(entry.content, "User prefers Rust");
+        assert_eq!(entry.category, MemoryCategory::Core);
+
+        assert!(mem.forget(&key).await.unwrap());
+        assert!(mem.get(&key).await.unwrap().is_none());
+        assert!(!mem.forget(&key).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn pg_store_upserts_on_same_key() {
+        let Some(mem) = test_memory() else { return };
+        let key = unique_key("upsert");
+
+        mem.store(&key, "first", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store(&key, "second", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        let entry = mem.get(&key).await.unwrap().unwrap();
+        assert_eq!(entry.content, "second");
+        assert_eq!(entry.category, MemoryCategory::Daily);
+
+        mem.forget(&key).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn pg_recall_filters_by_tags() {
+        let Some(mem) = test_memory() else { return };
+        let marker = Uuid::new_v4().simple().to_string();
+        let (tagged, plain) = (unique_key("tagged"), unique_key("plain"));
+        let tags = vec![format!("t-{marker}")];
+
+        mem.store_with_tags(
+            &tagged,
+            &format!("{marker} tagged"),
+            MemoryCategory::Core,
+            &tags,
+        )
+        .await
+        .unwrap();
+        mem.store(&plain, &format!("{marker} plain"), MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let hits = mem.recall(&marker, 5, &tags).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].key, tagged);
+        assert_eq!(hits[0].tags, tags);
+        assert_eq!(mem.recall("", 5, &tags).await.unwrap().len(), 1);
+
+        mem.forget(&tagged).await.unwrap();
+        mem.forget(&plain).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn pg_recall_keyword_and_category_list() {
+        let Some(mem) = test_memory() else { return };
+        let marker = Uuid::new_v4().simple().to_string();
+        let key = unique_key("recall");
+        let cat = MemoryCategory::Custom(format!("cat_{marker}"));
+
+        mem.store(&key, &format!("deploys use {marker} pipeline"), cat.clone())
+            .await
+            .unwrap();
+
+        let hits = mem.recall(&marker, 5, &[]).await.unwrap();
+        assert!(hits.iter().any(|e| e.key == key));
+        assert!(hits.iter().all(|e| e.score.is_some()));
+
+        let listed = mem.list(Some(&cat)).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].key, key);
+
+        mem.forget(&key).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn pg_health_check() {
+        let Some(mem) = test_memory() else { return };
+        assert!(mem.health_check().await);
+        assert!(mem.count().await.is_ok());
+    }
+}
@@ -1,9 +1,10 @@
 use super::embeddings::EmbeddingProvider;
-use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use super::traits::{normalize_tags, Memory, MemoryCategory, MemoryEntry};
 use super::vector;
 use async_trait::async_trait;
 use chrono::Local;
 use rusqlite::{params, Connection};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
@@ -15,6 +16,7 @@ use uuid::Uuid;
 /// - **Keyword Search**: FTS5 virtual table with BM25 scoring
 /// - **Hybrid Merge**: weighted fusion of vector + keyword results
 /// - **Embedding Cache**: LRU-evicted cache to avoid redundant API calls
+/// - **Tags**: `memory_tags` join table, filterable on recall
 /// - **Safe Reindex**: temp DB → seed → sync → atomic swap → rollback
 pub struct SqliteMemory {
     conn: Mutex<Connection>,
@@ -62,7 +64,7 @@ impl SqliteMemory {
         })
     }
 
-    /// Initialize all tables: memories, FTS5, `embedding_cache`
+    /// Initialize all tables: memories, FTS5, `embedding_cache`, `memory_tags`
     fn init_schema(conn: &Connection) -> anyhow::Result<()> {
         conn.execute_batch(
             "-- Core memories table
@@ -72,6 +74,7 @@ impl SqliteMemory {
                 content     TEXT NOT NULL,
                 category    TEXT NOT NULL DEFAULT 'core',
                 embedding   BLOB,
+                embedding_model TEXT,
                 created_at  TEXT NOT NULL,
                 updated_at  TEXT NOT NULL
             );
@@ -106,11 +109,43 @@ impl SqliteMemory {
                 created_at   TEXT NOT NULL,
                 accessed_at  TEXT NOT NULL
             );
-            CREATE INDEX IF NOT EXISTS idx_cache_accessed ON embedding_cache(accessed_at);",
+            CREATE INDEX IF NOT EXISTS idx_cache_accessed ON embedding_cache(accessed_at);
+
+            -- Tags (many per memory); removed with their memory
+            CREATE TABLE IF NOT EXISTS memory_tags (
+                memory_id TEXT NOT NULL,
+                tag       TEXT NOT NULL,
+                PRIMARY KEY (memory_id, tag)
+            );
+            CREATE INDEX IF NOT EXISTS idx_memory_tags_tag ON memory_tags(tag);
+            CREATE TRIGGER IF NOT EXISTS memories_tags_ad AFTER DELETE ON memories BEGIN
+                DELETE FROM memory_tags WHERE memory_id = old.id;
+            END;",
         )?;
+
+        // Databases created before embeddings were tagged with their model
+        if conn
+            .prepare("SELECT embedding_model FROM memories LIMIT 0")
+            .is_err()
+        {
+            conn.execute("ALTER TABLE memories ADD COLUMN embedding_model TEXT", [])?;
+        }
         Ok(())
     }
 
+    /// Identifies the embedding space (`provider:model:dims`); `None` for the noop embedder
+    fn embedding_signature(&self) -> Option<String> {
+        let dims = self.embedder.dimensions();
+        (dims > 0).then(|| format!("{}:{}:{dims}", self.embedder.name(), self.embedder.model()))
+    }
+
+    /// Cache key for `text` — scoped to the embedding space so a model change never
+    /// serves vectors from the previous model.
+    fn cache_key(&self, text: &str) -> String {
+        let signature = self.embedding_signature().unwrap_or_default();
+        Self::content_hash(&format!("{signature}\n{text}"))
+    }
+
     fn category_to_str(cat: &MemoryCategory) -> String {
         match cat {
             MemoryCategory::Core => "core".into(),
@@ -129,6 +164,113 @@ impl SqliteMemory {
         }
     }
 
+    /// Exported timestamps may be RFC 3339 (sqlite) or a bare date (markdown);
+    /// anything unparseable is stamped with the current time.
+    fn normalize_timestamp(raw: &str) -> String {
+        if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(raw) {
+            return ts.to_rfc3339();
+        }
+        if let Some(local) = chrono::NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .and_then(|dt| dt.and_local_timezone(Local).earliest())
+        {
+            return local.to_rfc3339();
+        }
+        Local::now().to_rfc3339()
+    }
+
+    /// Map a `SELECT id, key, content, category, created_at` row (tags are attached separately)
+    fn row_to_entry(row: &rusqlite::Row, score: Option<f64>) -> rusqlite::Result<MemoryEntry> {
+        Ok(MemoryEntry {
+            id: row.get(0)?,
+            key: row.get(1)?,
+            content: row.get(2)?,
+            category: Self::str_to_category(&row.get::<_, String>(3)?),
+            tags: Vec::new(),
+            timestamp: row.get(4)?,
+            session_id: None,
+            score,
+        })
+    }
+
+    /// Replace the tags of the memory stored under `key`
+    fn set_tags(conn: &Connection, key: &str, tags: &[String]) -> anyhow::Result<()> {
+        let id: String = conn.query_row(
+            "SELECT id FROM memories WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        )?;
+        conn.execute("DELETE FROM memory_tags WHERE memory_id = ?1", params![id])?;
+        for tag in normalize_tags(tags) {
+            conn.execute(
+                "INSERT OR IGNORE INTO memory_tags (memory_id, tag) VALUES (?1, ?2)",
+                params![id, tag],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Tags of one memory, in the order they were given
+    fn tags_for(conn: &Connection, id: &str) -> anyhow::Result<Vec<String>> {
+        let mut stmt =
+            conn.prepare_cached("SELECT tag FROM memory_tags WHERE memory_id = ?1 ORDER BY rowid")?;
+        let tags = stmt
+            .query_map(params![id], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(tags)
+    }
+
+    fn attach_tags(conn: &Connection, entries: &mut [MemoryEntry]) -> anyhow::Result<()> {
+        for entry in entries {
+            entry.tags = Self::tags_for(conn, &entry.id)?;
+        }
+        Ok(())
+    }
+
+    /// Ids of memories carrying every tag in `tags` (already normalized)
+    fn ids_with_tags(conn: &Connection, tags: &[String]) -> anyhow::Result<HashSet<String>> {
+        let placeholders = (1..=tags.len())
+            .map(|i| format!("?{i}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            "SELECT memory_id FROM memory_tags WHERE tag IN ({placeholders})
+             GROUP BY memory_id HAVING COUNT(DISTINCT tag) = {}",
+            tags.len()
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let ids = stmt
+            .query_map(rusqlite::params_from_iter(tags), |row| row.get(0))?
+            .collect::<rusqlite::Result<HashSet<String>>>()?;
+        Ok(ids)
+    }
+
+    /// Most recently updated memories carrying every tag — recall with an empty query
+    fn list_tagged(
+        conn: &Connection,
+        tags: &[String],
+        limit: usize,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let allowed = Self::ids_with_tags(conn, tags)?;
+        let mut stmt = conn.prepare(
+            "SELECT id, key, content, category, created_at FROM memories
+             ORDER BY updated_at DESC",
+        )?;
+        let mut results = Vec::new();
+        for row in stmt.query_map([], |row| Self::row_to_entry(row, Some(1.0)))? {
+            let entry = row?;
+            if allowed.contains(&entry.id) {
+                results.push(entry);
+                if results.len() >= limit {
+                    break;
+                }
+            }
+        }
+        Self::attach_tags(conn, &mut results)?;
+        Ok(results)
+    }
+
     /// Deterministic content hash for embedding cache.
     /// Uses SHA-256 (truncated) instead of `DefaultHasher`, which is
     /// explicitly documented as unstable across Rust versions.
@@ -152,61 +294,65 @@ impl SqliteMemory {
             return Ok(None); // Noop embedder
         }
 
-        let hash = Self::content_hash(text);
-        let now = Local::now().to_rfc3339();
-
-        // Check cache
-        {
-            let conn = self
-                .conn
-                .lock()
-                .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
-
-            let mut stmt =
-                conn.prepare("SELECT embedding FROM embedding_cache WHERE content_hash = ?1")?;
-            let cached: Option<Vec<u8>> = stmt.query_row(params![hash], |row| row.get(0)).ok();
-
-            if let Some(bytes) = cached {
-                // Update accessed_at for LRU
-                conn.execute(
-                    "UPDATE embedding_cache SET accessed_at = ?1 WHERE content_hash = ?2",
-                    params![now, hash],
-                )?;
-                return Ok(Some(vector::bytes_to_vec(&bytes)));
-            }
+        let hash = self.cache_key(text);
+        if let Some(cached) = self.cache_get(&hash)? {
+            return Ok(Some(cached));
         }
 
-        // Compute embedding
         let embedding = self.embedder.embed_one(text).await?;
-        let bytes = vector::vec_to_bytes(&embedding);
+        self.cache_put(&hash, &embedding)?;
+        Ok(Some(embedding))
+    }
 
-        // Store in cache + LRU eviction
-        {
-            let conn = self
-                .conn
-                .lock()
-                .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+    /// Cached embedding for `hash`, bumping its LRU timestamp
+    fn cache_get(&self, hash: &str) -> anyhow::Result<Option<Vec<f32>>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
 
-            conn.execute(
-                "INSERT OR REPLACE INTO embedding_cache (content_hash, embedding, created_at, accessed_at)
-                 VALUES (?1, ?2, ?3, ?4)",
-                params![hash, bytes, now, now],
-            )?;
+        let mut stmt =
+            conn.prepare("SELECT embedding FROM embedding_cache WHERE content_hash = ?1")?;
+        let cached: Option<Vec<u8>> = stmt.query_row(params![hash], |row| row.get(0)).ok();
 
-            // LRU eviction: keep only cache_max entries
-            #[allow(clippy::cast_possible_wrap)]
-            let max = self.cache_max as i64;
-            conn.execute(
-                "DELETE FROM embedding_cache WHERE content_hash IN (
-                    SELECT content_hash FROM embedding_cache
-                    ORDER BY accessed_at ASC
-                    LIMIT MAX(0, (SELECT COUNT(*) FROM embedding_cache) - ?1)
-                )",
-                params![max],
-            )?;
-        }
+        let Some(bytes) = cached else {
+            return Ok(None);
+        };
+        // Update accessed_at for LRU
+        conn.execute(
+            "UPDATE embedding_cache SET accessed_at = ?1 WHERE content_hash = ?2",
+            params![Local::now().to_rfc3339(), hash],
+        )?;
+        Ok(Some(vector::bytes_to_vec(&bytes)))
+    }
 
-        Ok(Some(embedding))
+    /// Store in cache + LRU eviction down to `cache_max` entries
+    fn cache_put(&self, hash: &str, embedding: &[f32]) -> anyhow::Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+        let now = Local::now().to_rfc3339();
+        let bytes = vector::vec_to_bytes(embedding);
+
+        conn.execute(
+            "INSERT OR REPLACE INTO embedding_cache (content_hash, embedding, created_at, accessed_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![hash, bytes, now, now],
+        )?;
+
+        // LRU eviction: keep only cache_max entries
+        #[allow(clippy::cast_possible_wrap)]
+        let max = self.cache_max as i64;
+        conn.execute(
+            "DELETE FROM embedding_cache WHERE content_hash IN (
+                SELECT content_hash FROM embedding_cache
+                ORDER BY accessed_at ASC
+                LIMIT MAX(0, (SELECT COUNT(*) FROM embedding_cache) - ?1)
+            )",
+            params![max],
+        )?;
+        Ok(())
     }
 
     /// FTS5 BM25 keyword search
@@ -214,6 +360,7 @@ impl SqliteMemory {
         conn: &Connection,
         query: &str,
         limit: usize,
+        allowed: Option<&HashSet<String>>,
     ) -> anyhow::Result<Vec<(String, f32)>> {
         // Escape FTS5 special chars and build query
         let fts_query: String = query
@@ -234,8 +381,9 @@ impl SqliteMemory {
                    LIMIT ?2";
 
         let mut stmt = conn.prepare(sql)?;
+        // With a tag filter, rank everything and filter afterwards (LIMIT -1 = no limit)
         #[allow(clippy::cast_possible_wrap)]
-        let limit_i64 = limit as i64;
+        let limit_i64 = if allowed.is_some() { -1 } else { limit as i64 };
 
         let rows = stmt.query_map(params![fts_query, limit_i64], |row| {
             let id: String = row.get(0)?;
@@ -247,8 +395,12 @@ impl SqliteMemory {
 
         let mut results = Vec::new();
         for row in rows {
-            results.push(row?);
+            let (id, score) = row?;
+            if allowed.is_none_or(|ids| ids.contains(&id)) {
+                results.push((id, score));
+            }
         }
+        results.truncate(limit);
         Ok(results)
     }
 
@@ -257,6 +409,7 @@ impl SqliteMemory {
         conn: &Connection,
         query_embedding: &[f32],
         limit: usize,
+        allowed: Option<&HashSet<String>>,
     ) -> anyhow::Result<Vec<(String, f32)>> {
         let mut stmt =
             conn.prepare("SELECT id, embedding FROM memories WHERE embedding IS NOT NULL")?;
@@ -270,6 +423,9 @@ impl SqliteMemory {
         let mut scored: Vec<(String, f32)> = Vec::new();
         for row in rows {
             let (id, blob) = row?;
+            if allowed.is_some_and(|ids| !ids.contains(&id)) {
+                continue;
+            }
             let emb = vector::bytes_to_vec(&blob);
             let sim = vector::cosine_similarity(query_embedding, &emb);
             if sim > 0.0 {
@@ -282,6 +438,55 @@ impl SqliteMemory {
         Ok(scored)
     }
 
+    /// Substring fallback when FTS5/vector search find nothing (e.g. partial words)
+    fn like_search(
+        conn: &Connection,
+        query: &str,
+        limit: usize,
+        allowed: Option<&HashSet<String>>,
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let keywords: Vec<String> = query.split_whitespace().map(|w| format!("%{w}%")).collect();
+        if keywords.is_empty() {
+            return Ok(Vec::new());
+        }
+        let conditions: Vec<String> = keywords
+            .iter()
+            .enumerate()
+            .map(|(i, _)| format!("(content LIKE ?{} OR key LIKE ?{})", i * 2 + 1, i * 2 + 2))
+            .collect();
+        let where_clause = conditions.join(" OR ");
+        let sql = format!(
+            "SELECT id, key, content, category, created_at FROM memories
+             WHERE {where_clause}
+             ORDER BY updated_at DESC
+             LIMIT ?{}",
+            keywords.len() * 2 + 1
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+        for kw in &keywords {
+            param_values.push(Box::new(kw.clone()));
+            param_values.push(Box::new(kw.clone()));
+        }
+        #[allow(clippy::cast_possible_wrap)]
+        param_values.push(Box::new(if allowed.is_some() { -1 } else { limit as i64 }));
+        let params_ref: Vec<&dyn rusqlite::types::ToSql> =
+            param_values.iter().map(AsRef::as_ref).collect();
+        let rows = stmt.query_map(params_ref.as_slice(), |row| {
+            Self::row_to_entry(row, Some(1.0))
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            let entry = row?;
+            if allowed.is_none_or(|ids| ids.contains(&entry.id)) {
+                results.push(entry);
+            }
+        }
+        results.truncate(limit);
+        Ok(results)
+    }
+
     /// Safe reindex: rebuild FTS5 + embeddings with rollback on failure
     #[allow(dead_code)]
     pub async fn reindex(&self) -> anyhow::Result<usize> {
@@ -323,8 +528,8 @@ impl SqliteMemory {
                     .lock()
                     .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
                 conn.execute(
-                    "UPDATE memories SET embedding = ?1 WHERE id = ?2",
-                    params![bytes, id],
+                    "UPDATE memories SET embedding = ?1, embedding_model = ?2 WHERE id = ?3",
+                    params![bytes, self.embedding_signature(), id],
                 )?;
                 count += 1;
             }
@@ -332,6 +537,91 @@ impl SqliteMemory {
 
         Ok(count)
     }
+
+    /// Rows still needing an embedding from the current model, as `(id, content)`.
+    ///
+    /// A row is done once its `embedding_model` matches the current signature, so an
+    /// interrupted backfill resumes where it stopped.
+    pub fn pending_embeddings(&self) -> anyhow::Result<Vec<(String, String)>> {
+        let Some(signature) = self.embedding_signature() else {
+            return Ok(Vec::new());
+        };
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, content FROM memories
+             WHERE embedding IS NULL OR embedding_model IS NOT ?1
+             ORDER BY created_at",
+        )?;
+        let rows = stmt
+            .query_map(params![signature], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    }
+
+    /// Mark every row as pending so the next backfill re-embeds everything.
+    /// Existing vectors stay searchable until each row is replaced.
+    pub fn mark_all_embeddings_stale(&self) -> anyhow::Result<usize> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+        Ok(conn.execute("UPDATE memories SET embedding_model = NULL", [])?)
+    }
+
+    /// Embed one batch of pending rows, using the embedding cache where possible.
+    ///
+    /// Each row is committed as soon as its vector is known. Returns how many rows
+    /// needed an API call (the rest came from cache).
+    pub async fn backfill_batch(&self, rows: &[(String, String)]) -> anyhow::Result<usize> {
+        let Some(signature) = self.embedding_signature() else {
+            anyhow::bail!("embedding provider is disabled (memory.embedding_provider = \"none\")");
+        };
+
+        let mut resolved: Vec<(&str, Vec<f32>)> = Vec::with_capacity(rows.len());
+        let mut misses: Vec<(&str, &str, String)> = Vec::new();
+        for (id, content) in rows {
+            let hash = self.cache_key(content);
+            match self.cache_get(&hash)? {
+                Some(cached) => resolved.push((id.as_str(), cached)),
+                None => misses.push((id.as_str(), content.as_str(), hash)),
+            }
+        }
+
+        if !misses.is_empty() {
+            let texts: Vec<&str> = misses.iter().map(|(_, content, _)| *content).collect();
+            let embeddings = self.embedder.embed(&texts).await?;
+            if embeddings.len() != misses.len() {
+                anyhow::bail!(
+                    "Embedding API returned {} vectors for {} inputs",
+                    embeddings.len(),
+                    misses.len()
+                );
+            }
+            for ((id, _, hash), embedding) in misses.iter().zip(embeddings) {
+                self.cache_put(hash, &embedding)?;
+                resolved.push((id, embedding));
+            }
+        }
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+        for (id, embedding) in &resolved {
+            conn.execute(
+                "UPDATE memories SET embedding = ?1, embedding_model = ?2 WHERE id = ?3",
+                params![vector::vec_to_bytes(embedding), signature, id],
+            )?;
+        }
+
+        Ok(misses.len())
+    }
 }
 
 #[async_trait]
@@ -340,11 +630,12 @@ impl Memory for SqliteMemory {
         "sqlite"
     }
 
-    async fn store(
+    async fn store_with_tags(
         &self,
         key: &str,
         content: &str,
         category: MemoryCategory,
+        tags: &[String],
     ) -> anyhow::Result<()> {
         // Compute embedding (async, before lock)
         let embedding_bytes = self
@@ -352,6 +643,10 @@ impl Memory for SqliteMemory {
             .await?
             .map(|emb| vector::vec_to_bytes(&emb));
 
+        let embedding_model = embedding_bytes
+            .as_ref()
+            .and_then(|_| self.embedding_signature());
+
         let conn = self
             .conn
             .lock()
@@ -361,22 +656,70 @@ impl Memory for SqliteMemory {
         let id = Uuid::new_v4().to_string();
 
         conn.execute(
-            "INSERT INTO memories (id, key, content, category, embedding, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "INSERT INTO memories (id, key, content, category, embedding, embedding_model, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
              ON CONFLICT(key) DO UPDATE SET
                 content = excluded.content,
                 category = excluded.category,
                 embedding = excluded.embedding,
+                embedding_model = excluded.embedding_model,
                 updated_at = excluded.updated_at",
-            params![id, key, content, cat, embedding_bytes, now, now],
+            params![id, key, content, cat, embedding_bytes, embedding_model, now, now],
         )?;
+        Self::set_tags(&conn, key, tags)?;
 
         Ok(())
     }
 
-    async fn recall(&self, query: &str, limit: usize) -> anyhow::Result<Vec<MemoryEntry>> {
+    async fn import(&self, entry: &MemoryEntry) -> anyhow::Result<()> {
+        let embedding_bytes = self
+            .get_or_compute_embedding(&entry.content)
+            .await?
+            .map(|emb| vector::vec_to_bytes(&emb));
+        let embedding_model = embedding_bytes
+            .as_ref()
+            .and_then(|_| self.embedding_signature());
+        let timestamp = Self::normalize_timestamp(&entry.timestamp);
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+        let cat = Self::category_to_str(&entry.category);
+        let id = Uuid::new_v4().to_string();
+
+        conn.execute(
+            "INSERT INTO memories (id, key, content, category, embedding, embedding_model, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+             ON CONFLICT(key) DO UPDATE SET
+                content = excluded.content,
+                category = excluded.category,
+                embedding = excluded.embedding,
+                embedding_model = excluded.embedding_model,
+                updated_at = excluded.updated_at",
+            params![id, entry.key, entry.content, cat, embedding_bytes, embedding_model, timestamp],
+        )?;
+        Self::set_tags(&conn, &entry.key, &entry.tags)?;
+
+        Ok(())
+    }
+
+    async fn recall(
+        &self,
+        query: &str,
+        limit: usize,
+        tags: &[String],
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let tags = normalize_tags(tags);
         if query.trim().is_empty() {
-            return Ok(Vec::new());
+            if tags.is_empty() {
+                return Ok(Vec::new());
+            }
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+            return Self::list_tagged(&conn, &tags, limit);
         }
 
         // Compute query embedding (async, before lock)
@@ -387,12 +730,23 @@ impl Memory for SqliteMemory {
             .lock()
             .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
 
+        // Restrict candidates to entries carrying every requested tag
+        let allowed = if tags.is_empty() {
+            None
+        } else {
+            Some(Self::ids_with_tags(&conn, &tags)?)
+        };
+        if allowed.as_ref().is_some_and(HashSet::is_empty) {
+            return Ok(Vec::new());
+        }
+
         // FTS5 BM25 keyword search
-        let keyword_results = Self::fts5_search(&conn, query, limit * 2).unwrap_or_default();
+        let keyword_results =
+            Self::fts5_search(&conn, query, limit * 2, allowed.as_ref()).unwrap_or_default();
 
         // Vector similarity search (if embeddings available)
         let vector_results = if let Some(ref qe) = query_embedding {
-            Self::vector_search(&conn, qe, limit * 2).unwrap_or_default()
+            Self::vector_search(&conn, qe, limit * 2, allowed.as_ref()).unwrap_or_default()
         } else {
             Vec::new()
         };
@@ -426,15 +780,7 @@ impl Memory for SqliteMemory {
                 "SELECT id, key, content, category, created_at FROM memories WHERE id = ?1",
             )?;
             if let Ok(entry) = stmt.query_row(params![scored.id], |row| {
-                Ok(MemoryEntry {
-                    id: row.get(0)?,
-                    key: row.get(1)?,
-                    content: row.get(2)?,
-                    category: Self::str_to_category(&row.get::<_, String>(3)?),
-                    timestamp: row.get(4)?,
-                    session_id: None,
-                    score: Some(f64::from(scored.final_score)),
-                })
+                Self::row_to_entry(row, Some(f64::from(scored.final_score)))
             }) {
                 results.push(entry);
             }
@@ -442,52 +788,11 @@ impl Memory for SqliteMemory {
 
         // If hybrid returned nothing, fall back to LIKE search
         if results.is_empty() {
-            let keywords: Vec<String> =
-                query.split_whitespace().map(|w| format!("%{w}%")).collect();
-            if !keywords.is_empty() {
-                let conditions: Vec<String> = keywords
-                    .iter()
-                    .enumerate()
-                    .map(|(i, _)| {
-                        format!("(content LIKE ?{} OR key LIKE ?{})", i * 2 + 1, i * 2 + 2)
-                    })
-                    .collect();
-                let where_clause = conditions.join(" OR ");
-                let sql = format!(
-                    "SELECT id, key, content, category, created_at FROM memories
-                     WHERE {where_clause}
-                     ORDER BY updated_at DESC
-                     LIMIT ?{}",
-                    keywords.len() * 2 + 1
-                );
-                let mut stmt = conn.prepare(&sql)?;
-                let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
-                for kw in &keywords {
-                    param_values.push(Box::new(kw.clone()));
-                    param_values.push(Box::new(kw.clone()));
-                }
-                #[allow(clippy::cast_possible_wrap)]
-                param_values.push(Box::new(limit as i64));
-                let params_ref: Vec<&dyn rusqlite::types::ToSql> =
-                    param_values.iter().map(AsRef::as_ref).collect();
-                let rows = stmt.query_map(params_ref.as_slice(), |row| {
-                    Ok(MemoryEntry {
-                        id: row.get(0)?,
-                        key: row.get(1)?,
-                        content: row.get(2)?,
-                        category: Self::str_to_category(&row.get::<_, String>(3)?),
-                        timestamp: row.get(4)?,
-                        session_id: None,
-                        score: Some(1.0),
-                    })
-                })?;
-                for row in rows {
-                    results.push(row?);
-                }
-            }
+            results = Self::like_search(&conn, query, limit, allowed.as_ref())?;
         }
 
         results.truncate(limit);
+        Self::attach_tags(&conn, &mut results)?;
         Ok(results)
     }
 
@@ -501,20 +806,13 @@ impl Memory for SqliteMemory {
             "SELECT id, key, content, category, created_at FROM memories WHERE key = ?1",
         )?;
 
-        let mut rows = stmt.query_map(params![key], |row| {
-            Ok(MemoryEntry {
-                id: row.get(0)?,
-                key: row.get(1)?,
-                content: row.get(2)?,
-                category: Self::str_to_category(&row.get::<_, String>(3)?),
-                timestamp: row.get(4)?,
-                session_id: None,
-                score: None,
-            })
-        })?;
+        let mut rows = stmt.query_map(params![key], |row| Self::row_to_entry(row, None))?;
 
         match rows.next() {
-            Some(Ok(entry)) => Ok(Some(entry)),
+            Some(Ok(mut entry)) => {
+                entry.tags = Self::tags_for(&conn, &entry.id)?;
+                Ok(Some(entry))
+            }
             _ => Ok(None),
         }
     }
@@ -527,17 +825,7 @@ impl Memory for SqliteMemory {
 
         let mut results = Vec::new();
 
-        let row_mapper = |row: &rusqlite::Row| -> rusqlite::Result<MemoryEntry> {
-            Ok(MemoryEntry {
-                id: row.get(0)?,
-                key: row.get(1)?,
-                content: row.get(2)?,
-                category: Self::str_to_category(&row.get::<_, String>(3)?),
-                timestamp: row.get(4)?,
-                session_id: None,
-                score: None,
-            })
-        };
+        let row_mapper = |row: &rusqlite::Row| Self::row_to_entry(row, None);
 
         if let Some(cat) = category {
             let cat_str = Self::category_to_str(cat);
@@ -560,6 +848,7 @@ impl Memory for SqliteMemory {
             }
         }
 
+        Self::attach_tags(&conn, &mut results)?;
         Ok(results)
     }
 
@@ -656,7 +945,7 @@ mod tests {
             .await
             .unwrap();
 
-        let results = mem.recall("Rust", 10).await.unwrap();
+        let results = mem.recall("Rust", 10, &[]).await.unwrap();
         assert_eq!(results.len(), 2);
         assert!(results
             .iter()
@@ -673,7 +962,7 @@ mod tests {
             .await
             .unwrap();
 
-        let results = mem.recall("fast safe", 10).await.unwrap();
+        let results = mem.recall("fast safe", 10, &[]).await.unwrap();
         assert!(!results.is_empty());
         // Entry with both keywords should score higher
         assert!(results[0].content.contains("safe") && results[0].content.contains("fast"));
@@ -685,7 +974,7 @@ mod tests {
         mem.store("a", "Rust rocks", MemoryCategory::Core)
             .await
             .unwrap();
-        let results = mem.recall("javascript", 10).await.unwrap();
+        let results = mem.recall("javascript", 10, &[]).await.unwrap();
         assert!(results.is_empty());
     }
 
@@ -813,7 +1102,7 @@ mod tests {
         .await
         .unwrap();
 
-        let results = mem.recall("Rust", 10).await.unwrap();
+        let results = mem.recall("Rust", 10, &[]).await.unwrap();
         assert!(results.len() >= 2);
         // All results should contain "Rust"
         for r in &results {
@@ -838,7 +1127,7 @@ mod tests {
             .await
             .unwrap();
 
-        let results = mem.recall("quick dog", 10).await.unwrap();
+        let results = mem.recall("quick dog", 10, &[]).await.unwrap();
         assert!(!results.is_empty());
         // "The quick dog runs fast" matches both terms
         assert!(results[0].content.contains("quick"));
@@ -848,7 +1137,7 @@ mod tests {
     async fn recall_empty_query_returns_empty() {
         let (_tmp, mem) = temp_sqlite();
         mem.store("a", "data", MemoryCategory::Core).await.unwrap();
-        let results = mem.recall("", 10).await.unwrap();
+        let results = mem.recall("", 10, &[]).await.unwrap();
         assert!(results.is_empty());
     }
 
@@ -856,7 +1145,7 @@ mod tests {
     async fn recall_whitespace_query_returns_empty() {
         let (_tmp, mem) = temp_sqlite();
         mem.store("a", "data", MemoryCategory::Core).await.unwrap();
-        let results = mem.recall("   ", 10).await.unwrap();
+        let results = mem.recall("   ", 10, &[]).await.unwrap();
         assert!(results.is_empty());
     }
 
@@ -1015,7 +1304,7 @@ mod tests {
         assert_eq!(count, 0);
 
         // FTS should still work after rebuild
-        let results = mem.recall("reindex", 10).await.unwrap();
+        let results = mem.recall("reindex", 10, &[]).await.unwrap();
         assert_eq!(results.len(), 2);
     }
 
@@ -1034,7 +1323,7 @@ mod tests {
             .unwrap();
         }
 
-        let results = mem.recall("common keyword", 5).await.unwrap();
+        let results = mem.recall("common keyword", 5, &[]).await.unwrap();
         assert!(results.len() <= 5);
     }
 
@@ -1047,7 +1336,7 @@ mod tests {
             .await
             .unwrap();
 
-        let results = mem.recall("scored", 10).await.unwrap();
+        let results = mem.recall("scored", 10, &[]).await.unwrap();
         assert!(!results.is_empty());
         for r in &results {
             assert!(r.score.is_some(), "Expected score on result: {:?}", r.key);
@@ -1063,7 +1352,7 @@ mod tests {
             .await
             .unwrap();
         // Quotes in query should not crash FTS5
-        let results = mem.recall("\"hello\"", 10).await.unwrap();
+        let results = mem.recall("\"hello\"", 10, &[]).await.unwrap();
         // May or may not match depending on FTS5 escaping, but must not error
         assert!(results.len() <= 10);
     }
@@ -1074,7 +1363,7 @@ mod tests {
         mem.store("a1", "wildcard test content", MemoryCategory::Core)
             .await
             .unwrap();
-        let results = mem.recall("wild*", 10).await.unwrap();
+        let results = mem.recall("wild*", 10, &[]).await.unwrap();
         assert!(results.len() <= 10);
     }
 
@@ -1084,7 +1373,7 @@ mod tests {
         mem.store("p1", "function call test", MemoryCategory::Core)
             .await
             .unwrap();
-        let results = mem.recall("function()", 10).await.unwrap();
+        let results = mem.recall("function()", 10, &[]).await.unwrap();
         assert!(results.len() <= 10);
     }
 
@@ -1095,7 +1384,10 @@ mod tests {
             .await
             .unwrap();
         // Should not crash or leak data
-        let results = mem.recall("'; DROP TABLE memories; --", 10).await.unwrap();
+        let results = mem
+            .recall("'; DROP TABLE memories; --", 10, &[])
+            .await
+            .unwrap();
         assert!(results.len() <= 10);
         // Table should still exist
         assert_eq!(mem.count().await.unwrap(), 1);
@@ -1162,7 +1454,7 @@ mod tests {
             .await
             .unwrap();
         // Single char may not match FTS5 but LIKE fallback should work
-        let results = mem.recall("x", 10).await.unwrap();
+        let results = mem.recall("x", 10, &[]).await.unwrap();
         // Should not crash; may or may not find results
         assert!(results.len() <= 10);
     }
@@ -1173,7 +1465,7 @@ mod tests {
         mem.store("a", "some content", MemoryCategory::Core)
             .await
             .unwrap();
-        let results = mem.recall("some", 0).await.unwrap();
+        let results = mem.recall("some", 0, &[]).await.unwrap();
         assert!(results.is_empty());
     }
 
@@ -1186,7 +1478,7 @@ mod tests {
         mem.store("b", "matching content beta", MemoryCategory::Core)
             .await
             .unwrap();
-        let results = mem.recall("matching content", 1).await.unwrap();
+        let results = mem.recall("matching content", 1, &[]).await.unwrap();
         assert_eq!(results.len(), 1);
     }
 
@@ -1201,7 +1493,7 @@ mod tests {
         .await
         .unwrap();
         // "rust" appears in key but not content — LIKE fallback checks key too
-        let results = mem.recall("rust", 10).await.unwrap();
+        let results = mem.recall("rust", 10, &[]).await.unwrap();
         assert!(!results.is_empty(), "Should match by key");
     }
 
@@ -1211,7 +1503,7 @@ mod tests {
         mem.store("jp", "日本語のテスト", MemoryCategory::Core)
             .await
             .unwrap();
-        let results = mem.recall("日本語", 10).await.unwrap();
+        let results = mem.recall("日本語", 10, &[]).await.unwrap();
         assert!(!results.is_empty());
     }
 
@@ -1252,7 +1544,7 @@ mod tests {
             .await
             .unwrap();
         mem.forget("ghost").await.unwrap();
-        let results = mem.recall("phantom memory", 10).await.unwrap();
+        let results = mem.recall("phantom memory", 10, &[]).await.unwrap();
         assert!(
             results.is_empty(),
             "Deleted memory should not appear in recall"
@@ -1293,7 +1585,7 @@ mod tests {
         let count = mem.reindex().await.unwrap();
         assert_eq!(count, 0); // Noop embedder → nothing to re-embed
                               // Data should still be intact
-        let results = mem.recall("reindex", 10).await.unwrap();
+        let results = mem.recall("reindex", 10, &[]).await.unwrap();
         assert_eq!(results.len(), 1);
     }
 
@@ -1370,4 +1662,432 @@ mod tests {
         let all = mem.list(None).await.unwrap();
         assert!(all.is_empty());
     }
+
+    // ── Embedding backfill ───────────────────────────────────────
+
+    struct FakeEmbedding {
+        model: &'static str,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl FakeEmbedding {
+        fn new(model: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                model,
+                calls: std::sync::atomic::AtomicUsize::new(0),
+            })
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl EmbeddingProvider for FakeEmbedding {
+        fn name(&self) -> &str {
+            "fake"
+        }
+
+        fn model(&self) -> &str {
+            self.model
+        }
+
+        fn dimensions(&self) -> usize {
+            3
+        }
+
+        async fn embed(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            #[allow(clippy::cast_precision_loss)]
+            Ok(texts
+                .iter()
+                .map(|t| vec![t.len() as f32, 1.0, 0.5])
+                .collect())
+        }
+    }
+
+    fn sqlite_with(tmp: &TempDir, embedder: Arc<FakeEmbedding>) -> SqliteMemory {
+        SqliteMemory::with_embedder(tmp.path(), embedder, 0.7, 0.3, 100).unwrap()
+    }
+
+    #[tokio::test]
+    async fn store_tags_embedding_model() {
+        let tmp = TempDir::new().unwrap();
+        let mem = sqlite_with(&tmp, FakeEmbedding::new("m1"));
+        mem.store("k", "hello", MemoryCategory::Core).await.unwrap();
+
+        assert!(mem.pending_embeddings().unwrap().is_empty());
+        let conn = mem.conn.lock().unwrap();
+        let tag: String = conn
+            .query_row(
+                "SELECT embedding_model FROM memories WHERE key = 'k'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(tag, "fake:m1:3");
+    }
+
+    #[tokio::test]
+    async fn backfill_embeds_rows_stored_without_embedder() {
+        let tmp = TempDir::new().unwrap();
+        {
+            let plain = SqliteMemory::new(tmp.path()).unwrap();
+            plain
+                .store("a", "alpha", MemoryCategory::Core)
+                .await
+                .unwrap();
+            plain
+                .store("b", "beta", MemoryCategory::Daily)
+                .await
+                .unwrap();
+        }
+
+        let fake = FakeEmbedding::new("m1");
+        let mem = sqlite_with(&tmp, Arc::clone(&fake));
+        let pending = mem.pending_embeddings().unwrap();
+        assert_eq!(pending.len(), 2);
+
+        let fetched = mem.backfill_batch(&pending).await.unwrap();
+        assert_eq!(fetched, 2);
+        assert_eq!(fake.calls(), 1, "one batch → one API call");
+        assert!(mem.pending_embeddings().unwrap().is_empty());
+
+        let conn = mem.conn.lock().unwrap();
+        let missing: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM memories WHERE embedding IS NULL",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(missing, 0);
+    }
+
+    #[tokio::test]
+    async fn model_change_makes_rows_pending_again() {
+        let tmp = TempDir::new().unwrap();
+        {
+            let old = sqlite_with(&tmp, FakeEmbedding::new("m1"));
+            old.store("a", "alpha", MemoryCategory::Core).await.unwrap();
+            assert!(old.pending_embeddings().unwrap().is_empty());
+        }
+
+        let mem = sqlite_with(&tmp, FakeEmbedding::new("m2"));
+        assert_eq!(mem.pending_embeddings().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn force_rebackfill_is_served_from_cache() {
+        let tmp = TempDir::new().unwrap();
+        let fake = FakeEmbedding::new("m1");
+        let mem = sqlite_with(&tmp, Arc::clone(&fake));
+        mem.store("a", "alpha", MemoryCategory::Core).await.unwrap();
+        mem.store("b", "beta", MemoryCategory::Core).await.unwrap();
+        let calls_after_store = fake.calls();
+
+        assert_eq!(mem.mark_all_embeddings_stale().unwrap(), 2);
+        let pending = mem.pending_embeddings().unwrap();
+        assert_eq!(pending.len(), 2);
+
+        let fetched = mem.backfill_batch(&pending).await.unwrap();
+        assert_eq!(fetched, 0);
+        assert_eq!(fake.calls(), calls_after_store);
+        assert!(mem.pending_embeddings().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn backfill_requires_real_embedder() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("a", "alpha", MemoryCategory::Core).await.unwrap();
+        assert!(mem.pending_embeddings().unwrap().is_empty());
+        assert!(mem
+            .backfill_batch(&[("x".into(), "y".into())])
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn migrates_databases_without_embedding_model_column() {
+        let tmp = TempDir::new().unwrap();
+        let db_dir = tmp.path().join("memory");
+        std::fs::create_dir_all(&db_dir).unwrap();
+        let conn = Connection::open(db_dir.join("brain.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE memories (
+                id TEXT PRIMARY KEY, key TEXT NOT NULL UNIQUE, content TEXT NOT NULL,
+                category TEXT NOT NULL DEFAULT 'core', embedding BLOB,
+                created_at TEXT NOT NULL, updated_at TEXT NOT NULL
+            );",
+        )
+        .unwrap();
+        drop(conn);
+
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        let conn = mem.conn.lock().unwrap();
+        assert!(conn
+            .prepare("SELECT embedding_model FROM memories LIMIT 0")
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn hashed_embeddings_match_inflections_keyword_search_misses() {
+        let tmp = TempDir::new().unwrap();
+        let embedder = Arc::new(super::super::embeddings::HashedEmbedding::new(
+            super::super::embeddings::HASHED_MODEL,
+            384,
+        ));
+        let mem = SqliteMemory::with_embedder(tmp.path(), embedder, 0.7, 0.3, 100).unwrap();
+        mem.store(
+            "deploy",
+            "The deployment pipeline freezes on Fridays",
+            MemoryCategory::Core,
+        )
+        .await
+        .unwrap();
+        mem.store(
+            "lunch",
+            "Had ramen for lunch with the team",
+            MemoryCategory::Daily,
+        )
+        .await
+        .unwrap();
+
+        {
+            let conn = mem.conn.lock().unwrap();
+            let keyword = SqliteMemory::fts5_search(&conn, "deploying", 10, None).unwrap();
+            assert!(
+                keyword.is_empty(),
+                "keyword search should miss the inflected form"
+            );
+        }
+
+        let results = mem.recall("deploying", 5, &[]).await.unwrap();
+        assert!(!results.is_empty());
+        assert_eq!(results[0].key, "deploy");
+    }
+
+    /// A stand-in for a semantic model served by Ollama: texts about the same
+    /// concept get the same direction whatever words they use.
+    async fn spawn_semantic_ollama() -> String {
+        use axum::{routing::post, Json, Router};
+        const CONCEPTS: &[&[&str]] = &[
+            &["car", "automobile", "vehicle", "tyres"],
+            &["lunch", "ramen", "meal"],
+            &["doctor", "physician", "clinic"],
+        ];
+        let app = Router::new().route(
+            "/api/embed",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                let embeddings: Vec<Vec<f32>> = body["input"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|text| {
+                        let text = text.as_str().unwrap_or("").to_lowercase();
+                        let words: Vec<&str> = text.split(|c: char| !c.is_alphanumeric()).collect();
+                        CONCEPTS
+                            .iter()
+                            .map(|concept| {
+                                if concept.iter().any(|w| words.contains(w)) {
+                                    1.0
+                                } else {
+                                    0.0
+                                }
+                            })
+                            .collect()
+                    })
+                    .collect();
+                Json(serde_json::json!({ "embeddings": embeddings }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn local_embeddings_recall_related_entries_keyword_search_misses() {
+        let tmp = TempDir::new().unwrap();
+        let url = spawn_semantic_ollama().await;
+        let embedder = Arc::new(super::super::embeddings::LocalEmbedding::new(
+            &url,
+            "nomic-embed-text",
+            3,
+        ));
+        let mem = SqliteMemory::with_embedder(tmp.path(), embedder, 0.7, 0.3, 100).unwrap();
+        mem.store(
+            "tyres",
+            "The automobile needs new tyres before winter",
+            MemoryCategory::Core,
+        )
+        .await
+        .unwrap();
+        mem.store(
+            "lunch",
+            "Had ramen for lunch with the team",
+            MemoryCategory::Daily,
+        )
+        .await
+        .unwrap();
+
+        {
+            let conn = mem.conn.lock().unwrap();
+            let keyword = SqliteMemory::fts5_search(&conn, "car", 10, None).unwrap();
+            assert!(keyword.is_empty(), "no entry contains the word");
+        }
+
+        let results = mem.recall("car", 5, &[]).await.unwrap();
+        assert!(!results.is_empty());
+        assert_eq!(results[0].key, "tyres");
+
+        // Stored vectors went through the embedding cache
+        let conn = mem.conn.lock().unwrap();
+        let cached: i64 = conn
+            .query_row("SELECT COUNT(*) FROM embedding_cache", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(cached, 3);
+    }
+
+    // ── Tags ─────────────────────────────────────────────────
+
+    fn tags(list: &[&str]) -> Vec<String> {
+        list.iter().map(ToString::to_string).collect()
+    }
+
+    #[tokio::test]
+    async fn tags_stored_and_returned() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store_with_tags(
+            "roadmap",
+            "Ship v2 in March",
+            MemoryCategory::Core,
+            &tags(&["Project-X", "#planning", "project-x"]),
+        )
+        .await
+        .unwrap();
+
+        let entry = mem.get("roadmap").await.unwrap().unwrap();
+        assert_eq!(entry.tags, tags(&["project-x", "planning"]));
+        let listed = mem.list(None).await.unwrap();
+        assert_eq!(listed[0].tags, entry.tags);
+    }
+
+    #[tokio::test]
+    async fn recall_requires_every_tag() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store_with_tags("a", "Rust api", MemoryCategory::Core, &tags(&["x", "api"]))
+            .await
+            .unwrap();
+        mem.store_with_tags("b", "Rust cli", MemoryCategory::Core, &tags(&["x"]))
+            .await
+            .unwrap();
+        mem.store("c", "Rust untagged", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        assert_eq!(mem.recall("Rust", 10, &[]).await.unwrap().len(), 3);
+        let x = mem.recall("Rust", 10, &tags(&["x"])).await.unwrap();
+        assert_eq!(x.len(), 2);
+        let both = mem.recall("Rust", 10, &tags(&["x", "API"])).await.unwrap();
+        assert_eq!(both.len(), 1);
+        assert_eq!(both[0].key, "a");
+        assert!(mem
+            .recall("Rust", 10, &tags(&["nope"]))
+            .await
+            .unwrap()
+            .is_empty());
+        // LIKE fallback honours the filter too
+        let partial = mem.recall("cl", 10, &tags(&["api"])).await.unwrap();
+        assert!(partial.is_empty());
+    }
+
+    #[tokio::test]
+    async fn recall_empty_query_lists_tagged() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store_with_tags("a", "first", MemoryCategory::Core, &tags(&["x"]))
+            .await
+            .unwrap();
+        mem.store("b", "second", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let listed = mem.recall("", 10, &tags(&["x"])).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].key, "a");
+        assert!(mem.recall("", 10, &[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn restore_replaces_tags_and_forget_clears_them() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store_with_tags("k", "v1", MemoryCategory::Core, &tags(&["old"]))
+            .await
+            .unwrap();
+        mem.store_with_tags("k", "v2", MemoryCategory::Core, &tags(&["new"]))
+            .await
+            .unwrap();
+        assert_eq!(mem.get("k").await.unwrap().unwrap().tags, tags(&["new"]));
+
+        mem.forget("k").await.unwrap();
+        let conn = mem.conn.lock().unwrap();
+        let orphans: i64 = conn
+            .query_row("SELECT COUNT(*) FROM memory_tags", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(orphans, 0);
+    }
+
+    #[tokio::test]
+    async fn import_keeps_tags() {
+        let (_tmp, mem) = temp_sqlite();
+        let entry = MemoryEntry {
+            id: "ignored".into(),
+            key: "k".into(),
+            content: "v".into(),
+            category: MemoryCategory::Core,
+            tags: tags(&["project-x"]),
+            timestamp: "2025-03-04T10:00:00+00:00".into(),
+            session_id: None,
+            score: None,
+        };
+        mem.import(&entry).await.unwrap();
+        assert_eq!(mem.get("k").await.unwrap().unwrap().tags, entry.tags);
+    }
+
+    #[tokio::test]
+    async fn import_preserves_timestamp() {
+        let (_tmp, mem) = temp_sqlite();
+        let entry = MemoryEntry {
+            id: "ignored".into(),
+            key: "k".into(),
+            content: "v".into(),
+            category: MemoryCategory::Daily,
+            tags: Vec::new(),
+            timestamp: "2025-03-04T10:00:00+00:00".into(),
+            session_id: None,
+            score: None,
+        };
+        mem.import(&entry).await.unwrap();
+
+        let got = mem.get("k").await.unwrap().unwrap();
+        assert_eq!(got.timestamp, "2025-03-04T10:00:00+00:00");
+        assert_eq!(got.category, MemoryCategory::Daily);
+    }
+
+    #[test]
+    fn normalize_timestamp_accepts_dates_and_rfc3339() {
+        assert_eq!(
+            SqliteMemory::normalize_timestamp("2025-03-04T10:00:00+00:00"),
+            "2025-03-04T10:00:00+00:00"
+        );
+        assert!(SqliteMemory::normalize_timestamp("2025-03-04").starts_with("2025-03-04T00:00:00"));
+        assert!(
+            chrono::DateTime::parse_from_rfc3339(&SqliteMemory::normalize_timestamp("MEMORY"))
+                .is_ok()
+        );
+    }
 }
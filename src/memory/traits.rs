@@ -8,6 +8,9 @@ pub struct MemoryEntry {
     pub key: String,
     pub content: String,
     pub category: MemoryCategory,
+    /// Free-form labels for grouping entries across categories (e.g. `project-x`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     pub timestamp: String,
     pub session_id: Option<String>,
     pub score: Option<f64>,
@@ -38,6 +41,20 @@ impl std::fmt::Display for MemoryCategory {
     }
 }
 
+/// Normalize user-supplied tags: trim, drop a leading `#`, lowercase, dedupe.
+/// Order of first appearance is kept.
+pub fn normalize_tags<S: AsRef<str>>(tags: &[S]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.as_ref().trim();
+        let tag = tag.strip_prefix('#').unwrap_or(tag).trim().to_lowercase();
+        if !tag.is_empty() && !tag.contains(char::is_whitespace) && !out.contains(&tag) {
+            out.push(tag);
+        }
+    }
+    out
+}
+
 /// Core memory trait — implement for any persistence backend
 #[async_trait]
 pub trait Memory: Send + Sync {
@@ -45,11 +62,46 @@ pub trait Memory: Send + Sync {
     fn name(&self) -> &str;
 
     /// Store a memory entry
-    async fn store(&self, key: &str, content: &str, category: MemoryCategory)
-        -> anyhow::Result<()>;
+    async fn store(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+    ) -> anyhow::Result<()> {
+        self.store_with_tags(key, content, category, &[]).await
+    }
+
+    /// Store a memory entry with tags. Re-storing a key replaces its tags.
+    async fn store_with_tags(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+        tags: &[String],
+    ) -> anyhow::Result<()>;
+
+    /// Store an entry from an export, keeping its original timestamp where the
+    /// backend can represent it. Defaults to `store_with_tags`, which stamps the current time.
+    async fn import(&self, entry: &MemoryEntry) -> anyhow::Result<()> {
+        self.store_with_tags(
+            &entry.key,
+            &entry.content,
+            entry.category.clone(),
+            &entry.tags,
+        )
+        .await
+    }
 
-    /// Recall memories matching a query (keyword search)
-    async fn recall(&self, query: &str, limit: usize) -> anyhow::Result<Vec<MemoryEntry>>;
+    /// Recall memories matching a query (keyword search).
+    ///
+    /// When `tags` is non-empty only entries carrying every tag are returned, and an
+    /// empty query lists the most recent entries with those tags.
+    async fn recall(
+        &self,
+        query: &str,
+        limit: usize,
+        tags: &[String],
+    ) -> anyhow::Result<Vec<MemoryEntry>>;
 
     /// Get a specific memory by key
     async fn get(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>>;
@@ -0,0 +1,663 @@
+//! Whole-workspace migration archives (`jarvis migrate export` / `import`).
+//!
+//! An archive is a gzipped tarball laid out as:
+//!
+//! ```text
+//! manifest.json      format/version + the source machine's layout
+//! config.toml        secrets redacted unless exported with --include-secrets
+//! .secret_key        only with --include-secrets
+//! workspace/...      MD files, skills, memory and cron databases
+//! ```
+
+use crate::config::{env, secrets, store, Config};
+use crate::security::SecretStore;
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+const ARCHIVE_FORMAT: &str = "jarvis-migrate";
+/// Current archive layout version
+pub const ARCHIVE_VERSION: u32 = 1;
+
+const MANIFEST_NAME: &str = "manifest.json";
+const CONFIG_NAME: &str = "config.toml";
+const SECRET_KEY_NAME: &str = ".secret_key";
+const WORKSPACE_PREFIX: &str = "workspace";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Manifest {
+    format: String,
+    version: u32,
+    jarvis_version: String,
+    created_at: String,
+    workspace_dir: PathBuf,
+    config_dir: PathBuf,
+    secrets_included: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct ExportSummary {
+    pub files: usize,
+    pub redacted_secrets: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub files: usize,
+    pub rewritten_paths: usize,
+    pub reencrypted_secrets: usize,
+    pub workspace_backup: Option<PathBuf>,
+    pub config_backup: Option<PathBuf>,
+}
+
+pub fn run_export(config: &Config, output: &Path, include_secrets: bool) -> Result<()> {
+    let summary = export_archive(config, output, include_secrets)?;
+    println!("📦 Exported Jarvis workspace to {}", output.display());
+    println!("  Workspace files: {}", summary.files);
+    if include_secrets {
+        println!("  Secrets: included (keep this archive private)");
+    } else {
+        println!(
+            "  Secrets: {} redacted — re-enter them after import, or export with --include-secrets",
+            summary.redacted_secrets
+        );
+    }
+    Ok(())
+}
+
+pub fn run_import(config: &Config, archive: &Path, force: bool) -> Result<()> {
+    let summary = import_archive(config, archive, force)?;
+    println!(
+        "✅ Imported {} into {}",
+        archive.display(),
+        config.workspace_dir.display()
+    );
+    println!("  Workspace files: {}", summary.files);
+    println!("  Rewritten paths: {}", summary.rewritten_paths);
+    if summary.reencrypted_secrets > 0 {
+        println!("  Re-encrypted secrets: {}", summary.reencrypted_secrets);
+    }
+    if let Some(path) = &summary.workspace_backup {
+        println!("  Previous workspace moved to: {}", path.display());
+    }
+    if let Some(path) = &summary.config_backup {
+        println!("  Previous config saved as: {}", path.display());
+    }
+    Ok(())
+}
+
+fn config_dir(config: &Config) -> PathBuf {
+    config
+        .config_path
+        .parent()
+        .map_or_else(|| PathBuf::from("."), Path::to_path_buf)
+}
+
+/// Package the config and workspace of `config` into a `.tar.gz` at `output`.
+pub fn export_archive(
+    config: &Config,
+    output: &Path,
+    include_secrets: bool,
+) -> Result<ExportSummary> {
+    if !config.workspace_dir.is_dir() {
+        bail!("Workspace not found: {}", config.workspace_dir.display());
+    }
+
+    let file = File::create(output)
+        .with_context(|| format!("Failed to create archive: {}", output.display()))?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    tar.follow_symlinks(false);
+
+    let manifest = Manifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        jarvis_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        workspace_dir: config.workspace_dir.clone(),
+        config_dir: config_dir(config),
+        secrets_included: include_secrets,
+    };
+    append_bytes(
+        &mut tar,
+        MANIFEST_NAME,
+        &serde_json::to_vec_pretty(&manifest)?,
+    )?;
+
+    // Prefer the file on disk so keys jarvis doesn't know about survive verbatim
+    let raw_config = if config.config_path.exists() {
+        fs::read_to_string(&config.config_path)
+            .with_context(|| format!("Failed to read {}", config.config_path.display()))?
+    } else {
+        toml::to_string_pretty(config)?
+    };
+    let mut config_value: toml::Value =
+        toml::from_str(&raw_config).context("Invalid config.toml")?;
+    let mut summary = ExportSummary::default();
+    if include_secrets {
+        let key_path = config_dir(config).join(SECRET_KEY_NAME);
+        if key_path.exists() {
+            tar.append_path_with_name(&key_path, SECRET_KEY_NAME)?;
+        }
+    } else {
+        summary.redacted_secrets = redact_config_secrets(&mut config_value);
+    }
+    append_bytes(
+        &mut tar,
+        CONFIG_NAME,
+        toml::to_string_pretty(&config_value)?.as_bytes(),
+    )?;
+
+    let skip = fs::canonicalize(output).ok();
+    let mut files = Vec::new();
+    collect_files(&config.workspace_dir, Path::new(""), &mut files)?;
+    for relative in files {
+        let source = config.workspace_dir.join(&relative);
+        if skip.is_some() && fs::canonicalize(&source).ok() == skip {
+            continue;
+        }
+        let name = Path::new(WORKSPACE_PREFIX).join(&relative);
+        if is_sqlite_db(&source) {
+            append_sqlite_snapshot(&mut tar, &source, &name)?;
+        } else {
+            tar.append_path_with_name(&source, &name)
+                .with_context(|| format!("Failed to archive {}", source.display()))?;
+        }
+        summary.files += 1;
+    }
+
+    tar.into_inner()?.finish()?;
+    Ok(summary)
+}
+
+/// Restore an archive produced by [`export_archive`] into the layout of `config`.
+pub fn import_archive(config: &Config, archive: &Path, force: bool) -> Result<ImportSummary> {
+    let workspace = &config.workspace_dir;
+    let parent = workspace
+        .parent()
+        .context("Workspace directory has no parent")?;
+    fs::create_dir_all(parent)?;
+
+    let staging = StagingDir(parent.join(format!(".jarvis-import-{}", uuid::Uuid::new_v4())));
+    let file =
+        File::open(archive).with_context(|| format!("Failed to open {}", archive.display()))?;
+    tar::Archive::new(GzDecoder::new(file))
+        .unpack(&staging.0)
+        .with_context(|| format!("Failed to unpack {}", archive.display()))?;
+
+    let manifest = read_manifest(&staging.0)?;
+    let mut summary = ImportSummary::default();
+
+    if dir_is_non_empty(workspace)? {
+        if !force {
+            bail!(
+                "Workspace {} is not empty; re-run with --force to replace it (the current workspace is kept as a backup)",
+                workspace.display()
+            );
+        }
+        let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        let backup = parent.join(format!(
+            "{}.bak-{timestamp}",
+            workspace
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("workspace")
+        ));
+        fs::rename(workspace, &backup)
+            .with_context(|| format!("Failed to move aside {}", workspace.display()))?;
+        summary.workspace_backup = Some(backup);
+    } else if workspace.exists() {
+        fs::remove_dir(workspace)?;
+    }
+
+    let staged_workspace = staging.0.join(WORKSPACE_PREFIX);
+    if staged_workspace.is_dir() {
+        let mut files = Vec::new();
+        collect_files(&staged_workspace, Path::new(""), &mut files)?;
+        summary.files = files.len();
+        fs::rename(&staged_workspace, workspace)
+            .with_context(|| format!("Failed to populate {}", workspace.display()))?;
+    } else {
+        fs::create_dir_all(workspace)?;
+    }
+
+    let raw_config = fs::read_to_string(staging.0.join(CONFIG_NAME))
+        .context("Archive is missing config.toml")?;
+    let mut config_value: toml::Value =
+        toml::from_str(&raw_config).context("Archive contains an invalid config.toml")?;
+
+    let target_config_dir = config_dir(config);
+    // Longest prefix first: the workspace usually lives inside the config dir
+    let mut rewrites = vec![
+        (manifest.workspace_dir.clone(), workspace.clone()),
+        (manifest.config_dir.clone(), target_config_dir.clone()),
+    ];
+    rewrites.sort_by_key(|(from, _)| std::cmp::Reverse(from.as_os_str().len()));
+    summary.rewritten_paths = rewrite_paths(&mut config_value, &rewrites);
+
+    let archived_key = staging.0.join(SECRET_KEY_NAME);
+    if manifest.secrets_included && archived_key.exists() {
+        let source = SecretStore::new(&staging.0, true);
+        let target = SecretStore::new(&target_config_dir, true);
+        summary.reencrypted_secrets = reencrypt_secrets(&mut config_value, &source, &target)?;
+        if summary.reencrypted_secrets > 0 {
+            crate::config::secrets::set_fingerprint(&mut config_value, &target)?;
+        }
+    }
+
+    fs::create_dir_all(&target_config_dir)?;
+    let lock = store::lock(&config.config_path)?;
+    summary.config_backup = store::backup(&config.config_path, &lock)?;
+    store::replace(
+        &config.config_path,
+        &toml::to_string_pretty(&config_value)?,
+        &lock,
+    )
+    .with_context(|| format!("Failed to write {}", config.config_path.display()))?;
+
+    Ok(summary)
+}
+
+/// Removes the staging directory however the import ends
+struct StagingDir(PathBuf);
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn read_manifest(staging: &Path) -> Result<Manifest> {
+    let raw = fs::read(staging.join(MANIFEST_NAME))
+        .context("Not a Jarvis migration archive (manifest.json missing)")?;
+    let manifest: Manifest =
+        serde_json::from_slice(&raw).context("Archive manifest is not valid")?;
+    if manifest.format != ARCHIVE_FORMAT {
+        bail!("Unsupported archive format: {}", manifest.format);
+    }
+    if manifest.version == 0 || manifest.version > ARCHIVE_VERSION {
+        bail!(
+            "Archive version {} is not supported (this jarvis reads up to version {ARCHIVE_VERSION}); upgrade jarvis first",
+            manifest.version
+        );
+    }
+    Ok(manifest)
+}
+
+fn dir_is_non_empty(path: &Path) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    Ok(fs::read_dir(path)?.next().is_some())
+}
+
+/// Collect regular files and symlinks under `root`, relative to it, in a stable order.
+fn collect_files(root: &Path, relative: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries: Vec<_> = fs::read_dir(root.join(relative))?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(fs::DirEntry::file_name);
+    for entry in entries {
+        let name = entry.file_name();
+        let path = relative.join(&name);
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(root, &path, out)?;
+            continue;
+        }
+        // Live sqlite side files are folded into the snapshot taken below
+        let name = name.to_string_lossy();
+        if name.ends_with("-wal") || name.ends_with("-shm") || name.ends_with("-journal") {
+            continue;
+        }
+        out.push(path);
+    }
+    Ok(())
+}
+
+fn is_sqlite_db(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some("db") && !path.is_symlink()
+}
+
+/// Archive a consistent copy of a (possibly open) sqlite database.
+fn append_sqlite_snapshot<W: std::io::Write>(
+    tar: &mut tar::Builder<W>,
+    source: &Path,
+    name: &Path,
+) -> Result<()> {
+    let snapshot =
+        std::env::temp_dir().join(format!("jarvis-snapshot-{}.db", uuid::Uuid::new_v4()));
+    let snapshot_guard = StagingFile(snapshot.clone());
+    let vacuumed = rusqlite::Connection::open(source)
+        .and_then(|conn| conn.execute("VACUUM INTO ?1", [snapshot.to_string_lossy()]))
+        .is_ok();
+    let from = if vacuumed { &snapshot_guard.0 } else { source };
+    tar.append_path_with_name(from, name)
+        .with_context(|| format!("Failed to archive {}", source.display()))?;
+    Ok(())
+}
+
+struct StagingFile(PathBuf);
+
+impl Drop for StagingFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn append_bytes<W: std::io::Write>(
+    tar: &mut tar::Builder<W>,
+    name: &str,
+    bytes: &[u8],
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o600);
+    header.set_mtime(u64::try_from(chrono::Utc::now().timestamp()).unwrap_or(0));
+    header.set_cksum();
+    tar.append_data(&mut header, name, bytes)?;
+    Ok(())
+}
+
+/// Blank every credential in the config (see [`secrets::secret_paths`]);
+/// returns how many were cleared.
+fn redact_config_secrets(config: &mut toml::Value) -> usize {
+    let mut count = 0;
+    for path in secrets::secret_paths(config) {
+        match env::value_at_mut(config, &path) {
+            Some(toml::Value::String(s)) if !s.is_empty() => {
+                s.clear();
+                count += 1;
+            }
+            Some(toml::Value::Array(items)) if !items.is_empty() => {
+                items.clear();
+                count += 1;
+            }
+            _ => {}
+        }
+    }
+    count
+}
+
+/// Rewrite string values that point inside one of the `from` directories.
+fn rewrite_paths(value: &mut toml::Value, rewrites: &[(PathBuf, PathBuf)]) -> usize {
+    match value {
+        toml::Value::String(s) => {
+            let path = Path::new(s.as_str());
+            for (from, to) in rewrites {
+                if from.as_os_str().is_empty() {
+                    continue;
+                }
+                if let Ok(rest) = path.strip_prefix(from) {
+                    let rewritten = if rest.as_os_str().is_empty() {
+                        to.clone()
+                    } else {
+                        to.join(rest)
+                    };
+                    *s = rewritten.to_string_lossy().into_owned();
+                    return 1;
+                }
+            }
+            0
+        }
+        toml::Value::Table(table) => table
+            .iter_mut()
+            .map(|(_, child)| rewrite_paths(child, rewrites))
+            .sum(),
+        toml::Value::Array(items) => items.iter_mut().map(|i| rewrite_paths(i, rewrites)).sum(),
+        _ => 0,
+    }
+}
+
+/// Move encrypted values from the archived key to the target machine's key.
+fn reencrypt_secrets(
+    value: &mut toml::Value,
+    source: &SecretStore,
+    target: &SecretStore,
+) -> Result<usize> {
+    match value {
+        toml::Value::String(s) if SecretStore::is_encrypted(s) => {
+            let plaintext = source
+                .decrypt(s)
+                .context("Failed to decrypt a secret with the archived key")?;
+            *s = target.encrypt(&plaintext)?;
+            Ok(1)
+        }
+        toml::Value::Table(table) => {
+            let mut count = 0;
+            for (_, child) in table.iter_mut() {
+                count += reencrypt_secrets(child, source, target)?;
+            }
+            Ok(count)
+        }
+        toml::Value::Array(items) => {
+            let mut count = 0;
+            for item in items {
+                count += reencrypt_secrets(item, source, target)?;
+            }
+            Ok(count)
+        }
+        _ => Ok(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MemoryConfig;
+    use crate::memory::{Memory, MemoryCategory, SqliteMemory};
+    use tempfile::TempDir;
+
+    fn test_config(root: &Path) -> Config {
+        Config {
+            workspace_dir: root.join("workspace"),
+            config_path: root.join("config.toml"),
+            memory: MemoryConfig {
+                backend: "sqlite".to_string(),
+                ..MemoryConfig::default()
+            },
+            ..Config::default()
+        }
+    }
+
+    async fn seeded_source(root: &Path) -> Config {
+        let mut config = test_config(root);
+        config.api_key = Some("sk-live-secret".into());
+        fs::create_dir_all(&config.workspace_dir).unwrap();
+        fs::write(config.workspace_dir.join("SOUL.md"), "# Soul\n").unwrap();
+        fs::create_dir_all(config.workspace_dir.join("skills/weather")).unwrap();
+        fs::write(
+            config.workspace_dir.join("skills/weather/SKILL.md"),
+            "# Weather\n",
+        )
+        .unwrap();
+
+        let mem = SqliteMemory::new(&config.workspace_dir).unwrap();
+        mem.store("lang", "User prefers Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("standup", "Shipped the gateway fix", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        crate::cron::add_job(&config, "*/5 * * * *", "echo hi").unwrap();
+        config.save().unwrap();
+        config
+    }
+
+    #[tokio::test]
+    async fn roundtrip_preserves_memories_and_cron_jobs() {
+        let (a, b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let source = seeded_source(a.path()).await;
+        let archive = a.path().join("backup.tar.gz");
+        export_archive(&source, &archive, false).unwrap();
+
+        let target = test_config(b.path());
+        let summary = import_archive(&target, &archive, false).unwrap();
+        assert!(summary.files >= 4);
+
+        let mem = SqliteMemory::new(&target.workspace_dir).unwrap();
+        assert_eq!(mem.count().await.unwrap(), 2);
+        assert_eq!(
+            mem.get("lang").await.unwrap().unwrap().content,
+            "User prefers Rust"
+        );
+
+        let jobs = crate::cron::list_jobs(&target).unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].command, "echo hi");
+
+        assert!(target.workspace_dir.join("SOUL.md").exists());
+        assert!(target
+            .workspace_dir
+            .join("skills/weather/SKILL.md")
+            .exists());
+    }
+
+    #[tokio::test]
+    async fn export_redacts_secrets_by_default() {
+        let a = TempDir::new().unwrap();
+        let b = TempDir::new().unwrap();
+        let source = seeded_source(a.path()).await;
+        let archive = a.path().join("backup.tar.gz");
+        let summary = export_archive(&source, &archive, false).unwrap();
+        assert_eq!(summary.redacted_secrets, 1);
+
+        let target = test_config(b.path());
+        import_archive(&target, &archive, false).unwrap();
+        let written = fs::read_to_string(&target.config_path).unwrap();
+        assert!(!written.contains("sk-live-secret"));
+    }
+
+    #[tokio::test]
+    async fn include_secrets_reencrypts_with_target_key() {
+        let (a, b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let mut source = seeded_source(a.path()).await;
+        let source_store = SecretStore::new(a.path(), true);
+        source.api_key = Some(source_store.encrypt("sk-live-secret").unwrap());
+        source.save().unwrap();
+
+        let archive = a.path().join("backup.tar.gz");
+        export_archive(&source, &archive, true).unwrap();
+
+        let target = test_config(b.path());
+        let summary = import_archive(&target, &archive, false).unwrap();
+        assert_eq!(summary.reencrypted_secrets, 1);
+
+        let written: toml::Value =
+            toml::from_str(&fs::read_to_string(&target.config_path).unwrap()).unwrap();
+        let api_key = written["api_key"].as_str().unwrap();
+        let target_store = SecretStore::new(b.path(), true);
+        assert_eq!(target_store.decrypt(api_key).unwrap(), "sk-live-secret");
+        assert_ne!(
+            fs::read(a.path().join(SECRET_KEY_NAME)).unwrap(),
+            fs::read(b.path().join(SECRET_KEY_NAME)).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn import_refuses_non_empty_workspace_without_force() {
+        let (a, b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let source = seeded_source(a.path()).await;
+        let archive = a.path().join("backup.tar.gz");
+        export_archive(&source, &archive, false).unwrap();
+
+        let target = test_config(b.path());
+        fs::create_dir_all(&target.workspace_dir).unwrap();
+        fs::write(target.workspace_dir.join("notes.md"), "keep me").unwrap();
+
+        let err = import_archive(&target, &archive, false).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        assert!(target.workspace_dir.join("notes.md").exists());
+
+        let summary = import_archive(&target, &archive, true).unwrap();
+        let backup = summary.workspace_backup.unwrap();
+        assert!(backup.join("notes.md").exists());
+        assert!(!target.workspace_dir.join("notes.md").exists());
+        assert!(target.workspace_dir.join("SOUL.md").exists());
+    }
+
+    #[tokio::test]
+    async fn import_rewrites_source_paths() {
+        let (a, b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let mut source = seeded_source(a.path()).await;
+        source.identity.aieos_path = Some(
+            source
+                .workspace_dir
+                .join("identity.json")
+                .to_string_lossy()
+                .into_owned(),
+        );
+        source.save().unwrap();
+        let archive = a.path().join("backup.tar.gz");
+        export_archive(&source, &archive, false).unwrap();
+
+        let target = test_config(b.path());
+        let summary = import_archive(&target, &archive, false).unwrap();
+        assert!(summary.rewritten_paths >= 1);
+
+        let written = fs::read_to_string(&target.config_path).unwrap();
+        assert!(!written.contains(&a.path().to_string_lossy().into_owned()));
+        let expected = target.workspace_dir.join("identity.json");
+        assert!(written.contains(&expected.to_string_lossy().into_owned()));
+    }
+
+    #[test]
+    fn import_rejects_unknown_version() {
+        let dir = TempDir::new().unwrap();
+        let archive = dir.path().join("future.tar.gz");
+        let file = File::create(&archive).unwrap();
+        let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        let manifest = serde_json::json!({
+            "format": ARCHIVE_FORMAT,
+            "version": ARCHIVE_VERSION + 1,
+            "jarvis_version": "99.0.0",
+            "created_at": "2030-01-01T00:00:00Z",
+            "workspace_dir": "/old/workspace",
+            "config_dir": "/old",
+            "secrets_included": false,
+        });
+        append_bytes(&mut tar, MANIFEST_NAME, manifest.to_string().as_bytes()).unwrap();
+        tar.into_inner().unwrap().finish().unwrap();
+
+        let target = test_config(dir.path());
+        let err = import_archive(&target, &archive, false).unwrap_err();
+        assert!(err.to_string().contains("not supported"));
+        assert!(!target.workspace_dir.exists());
+        assert!(!target.config_path.exists());
+    }
+
+    #[test]
+    fn redact_covers_nested_channel_tokens() {
+        let mut value: toml::Value = toml::from_str(
+            r#"
+            api_key = "sk"
+            [channels_config.telegram]
+            bot_token = "123:abc"
+            allowed_users = ["alice"]
+            [memory]
+            postgres_url = "postgres://jarvis:secret@db/jarvis"
+            [gateway]
+            paired_tokens = ["t1"]
+            [secrets.named]
+            ha_token = "eyJhbGci"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(redact_config_secrets(&mut value), 5);
+        assert_eq!(value["secrets"]["named"]["ha_token"].as_str(), Some(""));
+        assert_eq!(value["memory"]["postgres_url"].as_str(), Some(""));
+        assert!(value["gateway"]["paired_tokens"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            value["channels_config"]["telegram"]["bot_token"].as_str(),
+            Some("")
+        );
+        assert_eq!(
+            value["channels_config"]["telegram"]["allowed_users"][0].as_str(),
+            Some("alice")
+        );
+    }
+}
@@ -0,0 +1,521 @@
+//! Seed memory from a `ChatGPT` data export (`conversations.json`).
+//!
+//! Each conversation is a tree of message nodes (edits and regenerations
+//! branch off). Only the branch ending at `current_node` — what the user
+//! last saw — is imported, flattened into user/assistant turns and stored
+//! as one memory per conversation, tagged `chatgpt` plus its start date.
+
+use super::{
+    backup_target_memory, import_memories, paths_equal, plan_memories, MigrationStats, SourceEntry,
+};
+use crate::config::Config;
+use crate::memory::MemoryCategory;
+use crate::util::truncate_with_ellipsis;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Longest single turn kept in the stored memory
+const MAX_TURN_CHARS: usize = 600;
+/// Longest memory stored per conversation; later turns are summarized as a count
+const MAX_ENTRY_CHARS: usize = 6000;
+
+#[derive(Debug, Deserialize)]
+struct Conversation {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    create_time: Option<f64>,
+    #[serde(default)]
+    current_node: Option<String>,
+    #[serde(default)]
+    mapping: HashMap<String, Node>,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    conversation_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Node {
+    #[serde(default)]
+    message: Option<Message>,
+    #[serde(default)]
+    parent: Option<String>,
+    #[serde(default)]
+    children: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Message {
+    author: Author,
+    #[serde(default)]
+    content: Option<Content>,
+    #[serde(default)]
+    metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Author {
+    role: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Content {
+    #[serde(default)]
+    parts: Vec<serde_json::Value>,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+/// One user or assistant message on the active branch
+#[derive(Debug, PartialEq, Eq)]
+struct Turn {
+    role: &'static str,
+    text: String,
+}
+
+/// What was read from an export
+#[derive(Debug, Default)]
+struct ExportScan {
+    entries: Vec<SourceEntry>,
+    conversations: usize,
+    turns: usize,
+    /// Conversations that could not be read or had nothing to import
+    skipped: Vec<String>,
+    /// The file ended mid-array; `entries` holds what came before the cut
+    truncated: bool,
+}
+
+pub(super) async fn migrate_chatgpt(config: &Config, source: &Path, dry_run: bool) -> Result<()> {
+    let export = resolve_export(source)?;
+    if paths_equal(
+        export.parent().unwrap_or(Path::new(".")),
+        &config.workspace_dir,
+    ) {
+        bail!("ChatGPT export is inside the current Jarvis workspace; refusing to import it");
+    }
+    let raw = fs::read_to_string(&export)
+        .with_context(|| format!("Failed to read {}", export.display()))?;
+    let scan = scan_export(&raw)?;
+
+    if scan.entries.is_empty() {
+        println!("Nothing to import from {}", export.display());
+        print_skipped(&scan);
+        return Ok(());
+    }
+
+    if dry_run {
+        let plan = plan_memories(config, &scan.entries, "chatgpt").await?;
+        println!("🔎 Dry run: ChatGPT import preview");
+        println!("  Source: {}", export.display());
+        println!("  Target: {}", config.workspace_dir.display());
+        println!(
+            "  Conversations: {} ({} turns) — {}",
+            scan.entries.len(),
+            scan.turns,
+            plan.summary()
+        );
+        print_skipped(&scan);
+        super::print_conflicts(&[&plan.conflicts]);
+        println!();
+        println!("Run without --dry-run to import.");
+        return Ok(());
+    }
+
+    if let Some(backup_dir) = backup_target_memory(&config.workspace_dir, "chatgpt")? {
+        println!("🛟 Backup created: {}", backup_dir.display());
+    }
+    let mut stats = MigrationStats::default();
+    let conversations = scan.entries.len();
+    import_memories(config, scan.entries, &mut stats, "chatgpt").await?;
+
+    println!("✅ ChatGPT import complete");
+    println!("  Source: {}", export.display());
+    println!("  Target: {}", config.workspace_dir.display());
+    println!("  Conversations:    {conversations} ({} turns)", scan.turns);
+    println!("  Imported:         {}", stats.imported);
+    println!("  Skipped unchanged:{}", stats.skipped_unchanged);
+    println!("  Renamed conflicts:{}", stats.renamed_conflicts);
+    if scan.truncated || !scan.skipped.is_empty() {
+        println!(
+            "  Unreadable:       {} of {}",
+            scan.skipped.len(),
+            scan.conversations
+        );
+    }
+    Ok(())
+}
+
+fn print_skipped(scan: &ExportScan) {
+    if !scan.truncated && scan.skipped.is_empty() {
+        return;
+    }
+    println!("  Needs attention:");
+    if scan.truncated {
+        println!("    - export is truncated; only conversations before the cut were read");
+    }
+    for note in &scan.skipped {
+        println!("    - {note}");
+    }
+}
+
+/// Accept the export's `conversations.json` or the unzipped export directory.
+fn resolve_export(source: &Path) -> Result<PathBuf> {
+    let path = if source.is_dir() {
+        source.join("conversations.json")
+    } else {
+        source.to_path_buf()
+    };
+    if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+    {
+        bail!(
+            "{} is a zip archive; unzip the ChatGPT export and pass conversations.json",
+            path.display()
+        );
+    }
+    if !path.is_file() {
+        bail!(
+            "ChatGPT export not found at {}. Pass --source <conversations.json>",
+            path.display()
+        );
+    }
+    Ok(path)
+}
+
+/// Parse the export, keeping every conversation that can be read. A file cut
+/// off mid-array still yields the conversations before the cut.
+fn scan_export(raw: &str) -> Result<ExportScan> {
+    let mut scan = ExportScan::default();
+    let (values, truncated) = read_array(raw)?;
+    scan.truncated = truncated;
+    scan.conversations = values.len();
+
+    let mut seen_keys = HashSet::new();
+    for (idx, value) in values.into_iter().enumerate() {
+        let conversation: Conversation = match serde_json::from_value(value) {
+            Ok(conversation) => conversation,
+            Err(e) => {
+                scan.skipped
+                    .push(format!("conversation #{} is malformed: {e}", idx + 1));
+                continue;
+            }
+        };
+        let title = conversation
+            .title
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .unwrap_or("Untitled")
+            .to_string();
+        let turns = flatten(&conversation);
+        if !turns.iter().any(|t| t.role == "User") {
+            scan.skipped
+                .push(format!("conversation \"{title}\" has no user messages"));
+            continue;
+        }
+
+        let date = conversation
+            .create_time
+            .and_then(|secs| {
+                #[allow(clippy::cast_possible_truncation)]
+                chrono::DateTime::from_timestamp(secs as i64, 0)
+            })
+            .map(|t| t.format("%Y-%m-%d").to_string());
+        let id = conversation
+            .conversation_id
+            .as_deref()
+            .or(conversation.id.as_deref());
+        let mut key = conversation_key(date.as_deref(), &title, id, idx);
+        if !seen_keys.insert(key.clone()) {
+            key = format!("{key}_{}", idx + 1);
+            seen_keys.insert(key.clone());
+        }
+
+        let mut tags = vec!["chatgpt".to_string()];
+        tags.extend(date.clone());
+        scan.turns += turns.len();
+        scan.entries.push(SourceEntry {
+            key,
+            content: render(&title, date.as_deref(), &turns),
+            category: MemoryCategory::Custom("chatgpt".into()),
+            tags,
+        });
+    }
+    Ok(scan)
+}
+
+/// The top-level array's elements, and whether the array was cut short.
+fn read_array(raw: &str) -> Result<(Vec<serde_json::Value>, bool)> {
+    let Some(mut rest) = raw.trim_start().strip_prefix('[') else {
+        bail!("Not a ChatGPT conversations.json export (expected a JSON array)");
+    };
+    let mut values = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.starts_with(']') {
+            return Ok((values, false));
+        }
+        let mut stream = serde_json::Deserializer::from_str(rest).into_iter();
+        match stream.next() {
+            Some(Ok(value)) => values.push(value),
+            Some(Err(e)) if e.is_eof() => return Ok((values, true)),
+            None => return Ok((values, true)),
+            Some(Err(e)) => {
+                bail!(
+                    "Unreadable ChatGPT export after {} conversations: {e}",
+                    values.len()
+                )
+            }
+        }
+        rest = rest[stream.byte_offset()..].trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest);
+    }
+}
+
+/// User and assistant turns on the branch ending at `current_node`, oldest first.
+fn flatten(conversation: &Conversation) -> Vec<Turn> {
+    let mapping = &conversation.mapping;
+    let leaf = conversation
+        .current_node
+        .clone()
+        .filter(|id| mapping.contains_key(id))
+        .or_else(|| last_leaf(mapping));
+
+    let mut turns = Vec::new();
+    let mut visited = HashSet::new();
+    let mut cursor = leaf;
+    while let Some(id) = cursor.take() {
+        if !visited.insert(id.clone()) {
+            break;
+        }
+        let Some(node) = mapping.get(&id) else {
+            break;
+        };
+        if let Some(turn) = node.message.as_ref().and_then(turn) {
+            turns.push(turn);
+        }
+        cursor.clone_from(&node.parent);
+    }
+    turns.reverse();
+    turns
+}
+
+/// Without `current_node`, follow the newest child from the root.
+fn last_leaf(mapping: &HashMap<String, Node>) -> Option<String> {
+    let mut id = mapping
+        .iter()
+        .find(|(_, node)| {
+            node.parent
+                .as_ref()
+                .is_none_or(|p| !mapping.contains_key(p))
+        })
+        .map(|(id, _)| id.clone())?;
+    let mut visited = HashSet::new();
+    while visited.insert(id.clone()) {
+        match mapping
+            .get(&id)
+            .and_then(|node| node.children.last())
+            .filter(|child| mapping.contains_key(*child))
+        {
+            Some(child) => id.clone_from(child),
+            None => break,
+        }
+    }
+    Some(id)
+}
+
+fn turn(message: &Message) -> Option<Turn> {
+    let role = match message.author.role.as_str() {
+        "user" => "User",
+        "assistant" => "Assistant",
+        _ => return None,
+    };
+    let hidden = message
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("is_visually_hidden_from_conversation"))
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false);
+    if hidden {
+        return None;
+    }
+    let content = message.content.as_ref()?;
+    let mut text: Vec<&str> = content
+        .parts
+        .iter()
+        .filter_map(serde_json::Value::as_str)
+        .collect();
+    text.extend(content.text.as_deref());
+    let text = text.join("\n").trim().to_string();
+    (!text.is_empty()).then_some(Turn { role, text })
+}
+
+fn conversation_key(date: Option<&str>, title: &str, id: Option<&str>, idx: usize) -> String {
+    let slug: String = title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .take(6)
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+    let id: String = id
+        .unwrap_or_default()
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .take(8)
+        .collect();
+    let mut key = format!("chatgpt_{}", date.unwrap_or("undated"));
+    for part in [slug, id] {
+        if !part.is_empty() {
+            key.push('_');
+            key.push_str(&part);
+        }
+    }
+    if key.matches('_').count() == 1 {
+        key = format!("{key}_{}", idx + 1);
+    }
+    key
+}
+
+fn render(title: &str, date: Option<&str>, turns: &[Turn]) -> String {
+    let mut out = match date {
+        Some(date) => format!("ChatGPT conversation \"{title}\" ({date})"),
+        None => format!("ChatGPT conversation \"{title}\""),
+    };
+    for (idx, turn) in turns.iter().enumerate() {
+        let line = format!(
+            "\n{}: {}",
+            turn.role,
+            truncate_with_ellipsis(&turn.text, MAX_TURN_CHARS)
+        );
+        if out.chars().count() + line.chars().count() > MAX_ENTRY_CHARS {
+            let _ = write!(out, "\n… ({} more turns)", turns.len() - idx);
+            break;
+        }
+        out.push_str(&line);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MemoryConfig;
+    use crate::memory::{Memory, SqliteMemory};
+    use tempfile::TempDir;
+
+    fn fixture() -> String {
+        let path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/chatgpt/conversations.json");
+        fs::read_to_string(path).unwrap()
+    }
+
+    fn test_config(workspace: &Path) -> Config {
+        Config {
+            workspace_dir: workspace.to_path_buf(),
+            config_path: workspace.join("config.toml"),
+            memory: MemoryConfig {
+                backend: "sqlite".to_string(),
+                ..MemoryConfig::default()
+            },
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn flattens_the_active_branch_of_each_conversation() {
+        let scan = scan_export(&fixture()).unwrap();
+        assert!(!scan.truncated);
+        assert_eq!(scan.conversations, 4);
+        assert_eq!(scan.entries.len(), 2);
+        assert_eq!(scan.skipped.len(), 2, "{:?}", scan.skipped);
+
+        let rust = &scan.entries[0];
+        assert_eq!(rust.key, "chatgpt_2024-01-02_rust-lifetimes_c0ffee01");
+        assert_eq!(rust.tags, vec!["chatgpt", "2024-01-02"]);
+        assert_eq!(rust.category, MemoryCategory::Custom("chatgpt".into()));
+        assert_eq!(
+            rust.content,
+            "ChatGPT conversation \"Rust lifetimes\" (2024-01-02)\n\
+             User: I prefer explicit lifetimes in public APIs.\n\
+             Assistant: Noted — annotate them on public functions.\n\
+             User: Thanks!"
+        );
+
+        // Image parts are dropped, the text part is kept; no current_node
+        // falls back to the newest branch
+        let weekend = &scan.entries[1];
+        assert!(weekend.key.starts_with("chatgpt_2024-03-09_"));
+        assert!(weekend.content.contains("User: 周末去杭州，帮我排个行程"));
+        assert!(weekend.content.contains("Assistant: 第二版行程"));
+        assert!(!weekend.content.contains("第一版行程"));
+        assert_eq!(scan.turns, 5);
+    }
+
+    #[test]
+    fn truncated_exports_keep_complete_conversations() {
+        let raw = fixture();
+        let cut = raw.find("周末计划").unwrap();
+        let scan = scan_export(&raw[..cut]).unwrap();
+        assert!(scan.truncated);
+        assert_eq!(scan.entries.len(), 1);
+        assert_eq!(
+            scan.entries[0].key,
+            "chatgpt_2024-01-02_rust-lifetimes_c0ffee01"
+        );
+
+        assert!(scan_export("{\"not\": \"an export\"}").is_err());
+        assert!(scan_export("[{\"title\": \"x\"} oops]").is_err());
+        assert_eq!(scan_export("[]").unwrap().entries.len(), 0);
+    }
+
+    #[test]
+    fn long_conversations_are_capped() {
+        let turns: Vec<Turn> = (0..40)
+            .map(|i| Turn {
+                role: "User",
+                text: format!("{i} {}", "x".repeat(1000)),
+            })
+            .collect();
+        let content = render("Long", None, &turns);
+        assert!(content.chars().count() < MAX_ENTRY_CHARS + 40);
+        assert!(content.ends_with("more turns)"));
+    }
+
+    #[tokio::test]
+    async fn import_is_idempotent_and_dry_run_writes_nothing() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        fs::write(source.path().join("conversations.json"), fixture()).unwrap();
+        let config = test_config(target.path());
+
+        migrate_chatgpt(&config, source.path(), true).await.unwrap();
+        assert!(!target.path().join("memory").exists());
+
+        for _ in 0..2 {
+            migrate_chatgpt(&config, source.path(), false)
+                .await
+                .unwrap();
+        }
+        let mem = SqliteMemory::new(target.path()).unwrap();
+        assert_eq!(mem.count().await.unwrap(), 2);
+        let found = mem
+            .recall("", 10, &["2024-01-02".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].content.contains("explicit lifetimes"));
+
+        assert!(
+            migrate_chatgpt(&config, &source.path().join("missing.json"), false)
+                .await
+                .is_err()
+        );
+    }
+}
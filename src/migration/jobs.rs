@@ -0,0 +1,343 @@
+//! Translate `OpenClaw` scheduled jobs (`cron/jobs.json`) into Jarvis cron jobs.
+//!
+//! `OpenClaw` jobs carry a schedule (`cron` expression, fixed `every` interval or
+//! one-shot `at`) and a payload that is delivered to the agent. Jarvis jobs are a
+//! cron expression plus a shell command, so agent payloads become
+//! `jarvis agent -m '<message>'`.
+
+use crate::cron::CronJob;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JobsFile {
+    Versioned { jobs: Vec<SourceJob> },
+    Bare(Vec<SourceJob>),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SourceJob {
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+    #[serde(default)]
+    schedule: Option<serde_json::Value>,
+    #[serde(default)]
+    payload: Option<serde_json::Value>,
+    /// Older job files kept a plain shell command
+    #[serde(default)]
+    command: Option<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A job that translates cleanly into a Jarvis cron row
+#[derive(Debug, Clone)]
+pub(super) struct JobPlan {
+    pub name: String,
+    pub expression: String,
+    pub command: String,
+    /// Translated, but with a semantic difference worth reviewing
+    pub warnings: Vec<String>,
+}
+
+/// A job that has no Jarvis equivalent
+#[derive(Debug, Clone)]
+pub(super) struct Untranslatable {
+    pub name: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct JobScan {
+    pub source: Option<PathBuf>,
+    pub jobs: Vec<JobPlan>,
+    pub untranslatable: Vec<Untranslatable>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum JobAction {
+    Add,
+    Unchanged,
+    Conflict(String),
+}
+
+/// `cron/jobs.json` lives in the `OpenClaw` state dir (the workspace's parent), but a
+/// copied workspace may carry it alongside.
+fn jobs_file(source_workspace: &Path) -> Option<PathBuf> {
+    let mut candidates = vec![source_workspace.join("cron").join("jobs.json")];
+    if let Some(state_dir) = super::openclaw_state_dir(source_workspace) {
+        candidates.push(state_dir.join("cron").join("jobs.json"));
+    }
+    candidates.into_iter().find(|p| p.is_file())
+}
+
+pub(super) fn scan_openclaw_jobs(source_workspace: &Path) -> Result<JobScan> {
+    let Some(path) = jobs_file(source_workspace) else {
+        return Ok(JobScan::default());
+    };
+    let raw =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let jobs = match serde_json::from_str::<JobsFile>(&raw)
+        .with_context(|| format!("Failed to parse {}", path.display()))?
+    {
+        JobsFile::Versioned { jobs } | JobsFile::Bare(jobs) => jobs,
+    };
+
+    let mut scan = JobScan {
+        source: Some(path),
+        ..JobScan::default()
+    };
+    for (idx, job) in jobs.into_iter().enumerate() {
+        let name = job
+            .name
+            .clone()
+            .or_else(|| job.id.clone())
+            .unwrap_or_else(|| format!("job_{idx}"));
+        match translate_job(&job) {
+            Ok((expression, command, warnings)) => scan.jobs.push(JobPlan {
+                name,
+                expression,
+                command,
+                warnings,
+            }),
+            Err(reason) => scan.untranslatable.push(Untranslatable { name, reason }),
+        }
+    }
+    Ok(scan)
+}
+
+type Translation = (String, String, Vec<String>);
+
+fn translate_job(job: &SourceJob) -> std::result::Result<Translation, String> {
+    if !job.enabled {
+        return Err("disabled in OpenClaw".into());
+    }
+    let mut warnings = Vec::new();
+    let expression = translate_schedule(job.schedule.as_ref(), &mut warnings)?;
+    let command = translate_payload(job)?;
+    crate::cron::preview_runs(&expression, chrono::Utc::now(), 1)
+        .map_err(|e| format!("schedule `{expression}` is not valid for Jarvis: {e}"))?;
+    Ok((expression, command, warnings))
+}
+
+fn translate_schedule(
+    schedule: Option<&serde_json::Value>,
+    warnings: &mut Vec<String>,
+) -> std::result::Result<String, String> {
+    let schedule = schedule.ok_or("no schedule")?;
+    // Some exports store the bare expression
+    if let Some(expr) = schedule.as_str() {
+        return Ok(expr.trim().to_string());
+    }
+
+    match schedule.get("kind").and_then(|k| k.as_str()) {
+        Some("cron") => {
+            let expr = schedule
+                .get("expr")
+                .or_else(|| schedule.get("expression"))
+                .and_then(|e| e.as_str())
+                .ok_or("cron schedule without an expression")?;
+            let tz = schedule.get("tz").and_then(|t| t.as_str()).unwrap_or("");
+            if !matches!(tz, "" | "UTC" | "Etc/UTC") {
+                warnings.push(format!(
+                    "timezone {tz} dropped; Jarvis evaluates cron expressions in UTC"
+                ));
+            }
+            Ok(expr.trim().to_string())
+        }
+        Some("every") => {
+            let every_ms = schedule
+                .get("everyMs")
+                .and_then(serde_json::Value::as_u64)
+                .ok_or("interval schedule without everyMs")?;
+            every_to_cron(every_ms)
+        }
+        Some("at") => Err("one-shot `at` schedules have no Jarvis cron equivalent".into()),
+        Some(other) => Err(format!("unknown schedule kind `{other}`")),
+        None => Err("schedule without a kind".into()),
+    }
+}
+
+/// Fixed intervals map onto cron only when they divide evenly into the next unit
+fn every_to_cron(every_ms: u64) -> std::result::Result<String, String> {
+    const MINUTE_MS: u64 = 60_000;
+    if every_ms == 0 || !every_ms.is_multiple_of(MINUTE_MS) {
+        return Err(format!(
+            "interval of {every_ms}ms is not a whole number of minutes"
+        ));
+    }
+    let minutes = every_ms / MINUTE_MS;
+    match minutes {
+        1 => Ok("* * * * *".into()),
+        m if m < 60 && 60 % m == 0 => Ok(format!("*/{m} * * * *")),
+        60 => Ok("0 * * * *".into()),
+        m if m % 60 == 0 && m < 1440 && 24 % (m / 60) == 0 => Ok(format!("0 */{} * * *", m / 60)),
+        1440 => Ok("0 0 * * *".into()),
+        m => Err(format!(
+            "interval of {m} minutes does not map onto a cron expression"
+        )),
+    }
+}
+
+fn translate_payload(job: &SourceJob) -> std::result::Result<String, String> {
+    if let Some(command) = job.command.as_deref().filter(|c| !c.trim().is_empty()) {
+        return Ok(command.trim().to_string());
+    }
+    let payload = job.payload.as_ref().ok_or("no payload")?;
+    let text = |field: &str| {
+        payload
+            .get(field)
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+    };
+    match payload.get("kind").and_then(|k| k.as_str()) {
+        Some("agentTurn") => text("message")
+            .map(agent_command)
+            .ok_or_else(|| "agentTurn payload without a message".into()),
+        Some("systemEvent") => text("text")
+            .map(agent_command)
+            .ok_or_else(|| "systemEvent payload without text".into()),
+        Some("command" | "shell") => text("command")
+            .map(str::to_string)
+            .ok_or_else(|| "command payload without a command".into()),
+        Some(other) => Err(format!("unsupported payload kind `{other}`")),
+        None => Err("payload without a kind".into()),
+    }
+}
+
+fn agent_command(message: &str) -> String {
+    format!("jarvis agent -m {}", shell_quote(message))
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Compare a translated job with what the Jarvis scheduler already has
+pub(super) fn classify(plan: &JobPlan, existing: &[CronJob]) -> JobAction {
+    let same_command = existing.iter().filter(|job| job.command == plan.command);
+    let mut conflict = None;
+    for job in same_command {
+        if job.expression.trim() == plan.expression {
+            return JobAction::Unchanged;
+        }
+        conflict = Some(format!(
+            "job {} already runs this command on `{}`",
+            job.id, job.expression
+        ));
+    }
+    conflict.map_or(JobAction::Add, JobAction::Conflict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_jobs(dir: &Path, json: &str) {
+        fs::create_dir_all(dir.join("cron")).unwrap();
+        fs::write(dir.join("cron").join("jobs.json"), json).unwrap();
+    }
+
+    #[test]
+    fn scans_versioned_jobs_file() {
+        let tmp = TempDir::new().unwrap();
+        write_jobs(
+            tmp.path(),
+            r#"{"version":1,"jobs":[
+                {"id":"a","name":"morning","enabled":true,
+                 "schedule":{"kind":"cron","expr":"0 7 * * *","tz":"America/New_York"},
+                 "payload":{"kind":"agentTurn","message":"Plan Bob's day"}},
+                {"id":"b","name":"ping","schedule":{"kind":"every","everyMs":900000},
+                 "payload":{"kind":"systemEvent","text":"check inbox"}},
+                {"id":"c","name":"once","schedule":{"kind":"at","atMs":1767225600000},
+                 "payload":{"kind":"agentTurn","message":"remind"}},
+                {"id":"d","name":"off","enabled":false,
+                 "schedule":{"kind":"cron","expr":"0 * * * *"},
+                 "payload":{"kind":"agentTurn","message":"x"}}
+            ]}"#,
+        );
+
+        let scan = scan_openclaw_jobs(tmp.path()).unwrap();
+        assert_eq!(scan.jobs.len(), 2);
+        assert_eq!(scan.jobs[0].expression, "0 7 * * *");
+        assert_eq!(scan.jobs[0].command, r"jarvis agent -m 'Plan Bob'\''s day'");
+        assert_eq!(scan.jobs[0].warnings.len(), 1);
+        assert_eq!(scan.jobs[1].expression, "*/15 * * * *");
+
+        let reasons: Vec<_> = scan
+            .untranslatable
+            .iter()
+            .map(|u| u.name.as_str())
+            .collect();
+        assert_eq!(reasons, vec!["once", "off"]);
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        let tmp = TempDir::new().unwrap();
+        write_jobs(
+            tmp.path(),
+            r#"[{"name":"bad","schedule":{"kind":"cron","expr":"every day"},
+                 "payload":{"kind":"agentTurn","message":"x"}}]"#,
+        );
+        let scan = scan_openclaw_jobs(tmp.path()).unwrap();
+        assert!(scan.jobs.is_empty());
+        assert!(scan.untranslatable[0].reason.contains("not valid"));
+    }
+
+    #[test]
+    fn every_intervals_map_to_cron() {
+        assert_eq!(every_to_cron(60_000).unwrap(), "* * * * *");
+        assert_eq!(every_to_cron(5 * 60_000).unwrap(), "*/5 * * * *");
+        assert_eq!(every_to_cron(3_600_000).unwrap(), "0 * * * *");
+        assert_eq!(every_to_cron(6 * 3_600_000).unwrap(), "0 */6 * * *");
+        assert_eq!(every_to_cron(86_400_000).unwrap(), "0 0 * * *");
+        assert!(every_to_cron(7 * 60_000).is_err());
+        assert!(every_to_cron(90_000).is_err());
+        assert!(every_to_cron(2 * 86_400_000).is_err());
+    }
+
+    #[test]
+    fn classify_detects_unchanged_and_conflicts() {
+        let plan = JobPlan {
+            name: "n".into(),
+            expression: "0 7 * * *".into(),
+            command: "echo hi".into(),
+            warnings: Vec::new(),
+        };
+        let job = |expression: &str, command: &str| CronJob {
+            id: "id1".into(),
+            expression: expression.into(),
+            command: command.into(),
+            next_run: chrono::Utc::now(),
+            last_run: None,
+            last_status: None,
+        };
+
+        assert_eq!(classify(&plan, &[]), JobAction::Add);
+        assert_eq!(
+            classify(&plan, &[job("0 7 * * *", "echo hi")]),
+            JobAction::Unchanged
+        );
+        assert!(matches!(
+            classify(&plan, &[job("0 8 * * *", "echo hi")]),
+            JobAction::Conflict(_)
+        ));
+        assert_eq!(
+            classify(&plan, &[job("0 8 * * *", "echo other")]),
+            JobAction::Add
+        );
+    }
+}
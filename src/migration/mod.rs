@@ -0,0 +1,972 @@
+use crate::config::Config;
+use crate::memory::{MarkdownMemory, Memory, MemoryCategory, SqliteMemory};
+use anyhow::{bail, Context, Result};
+use directories::UserDirs;
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+mod archive;
+mod chatgpt;
+mod jobs;
+mod skills;
+
+#[derive(Debug, Clone)]
+struct SourceEntry {
+    key: String,
+    content: String,
+    category: MemoryCategory,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct MigrationStats {
+    from_sqlite: usize,
+    from_markdown: usize,
+    imported: usize,
+    skipped_unchanged: usize,
+    renamed_conflicts: usize,
+}
+
+/// New / unchanged / conflicting counts for one category of the dry-run report
+#[derive(Debug, Default)]
+struct CategoryPlan {
+    new: usize,
+    unchanged: usize,
+    conflicts: Vec<String>,
+}
+
+impl CategoryPlan {
+    fn summary(&self) -> String {
+        format!(
+            "new {}, unchanged {}, conflicts {}",
+            self.new,
+            self.unchanged,
+            self.conflicts.len()
+        )
+    }
+}
+
+/// Where an incoming memory lands in the target backend
+#[derive(Debug, PartialEq, Eq)]
+enum KeyResolution {
+    Store(String),
+    Renamed(String),
+    Unchanged,
+}
+
+pub async fn handle_command(command: crate::MigrateCommands, config: &Config) -> Result<()> {
+    match command {
+        crate::MigrateCommands::Openclaw { source, dry_run } => {
+            migrate_openclaw(config, source, dry_run).await
+        }
+        crate::MigrateCommands::Chatgpt { source, dry_run } => {
+            chatgpt::migrate_chatgpt(config, &source, dry_run).await
+        }
+        crate::MigrateCommands::Export {
+            output,
+            include_secrets,
+        } => archive::run_export(config, &output, include_secrets),
+        crate::MigrateCommands::Import { archive, force } => {
+            archive::run_import(config, &archive, force)
+        }
+    }
+}
+
+async fn migrate_openclaw(
+    config: &Config,
+    source_workspace: Option<PathBuf>,
+    dry_run: bool,
+) -> Result<()> {
+    let source_workspace = resolve_openclaw_workspace(source_workspace)?;
+    if !source_workspace.exists() {
+        bail!(
+            "OpenClaw workspace not found at {}. Pass --source <path> if needed.",
+            source_workspace.display()
+        );
+    }
+
+    if paths_equal(&source_workspace, &config.workspace_dir) {
+        bail!("Source workspace matches current Jarvis workspace; refusing self-migration");
+    }
+
+    let mut stats = MigrationStats::default();
+    let entries = collect_source_entries(&source_workspace, &mut stats)?;
+    let job_scan = jobs::scan_openclaw_jobs(&source_workspace)?;
+    let skill_plans = skills::scan_openclaw_skills(&source_workspace, &config.workspace_dir)?;
+
+    if entries.is_empty()
+        && job_scan.jobs.is_empty()
+        && job_scan.untranslatable.is_empty()
+        && skill_plans.is_empty()
+    {
+        println!("Nothing to import from {}", source_workspace.display());
+        println!(
+            "Checked for: memory/brain.db, MEMORY.md, memory/*.md, cron/jobs.json, skills/*/SKILL.md"
+        );
+        return Ok(());
+    }
+
+    let existing_jobs = existing_cron_jobs(config)?;
+    let job_actions: Vec<_> = job_scan
+        .jobs
+        .iter()
+        .map(|job| jobs::classify(job, &existing_jobs))
+        .collect();
+
+    if dry_run {
+        let memory_plan = plan_memories(config, &entries, "openclaw").await?;
+        println!("🔎 Dry run: OpenClaw migration preview");
+        println!("  Source: {}", source_workspace.display());
+        println!("  Target: {}", config.workspace_dir.display());
+        println!(
+            "  Memories:  {} (sqlite {}, markdown {}) — {}",
+            entries.len(),
+            stats.from_sqlite,
+            stats.from_markdown,
+            memory_plan.summary()
+        );
+        print_job_and_skill_plan(config, &job_scan, &job_actions, &skill_plans, &memory_plan);
+        println!();
+        println!("Run without --dry-run to import.");
+        return Ok(());
+    }
+
+    if !entries.is_empty() {
+        if let Some(backup_dir) = backup_target_memory(&config.workspace_dir, "openclaw")? {
+            println!("🛟 Backup created: {}", backup_dir.display());
+        }
+        import_memories(config, entries, &mut stats, "openclaw").await?;
+    }
+    let jobs_plan = import_jobs(config, &job_scan, &job_actions)?;
+    let skills_plan = import_skills(config, &skill_plans)?;
+
+    println!("✅ OpenClaw migration complete");
+    println!("  Source: {}", source_workspace.display());
+    println!("  Target: {}", config.workspace_dir.display());
+    println!("  Imported:         {}", stats.imported);
+    println!("  Skipped unchanged:{}", stats.skipped_unchanged);
+    println!("  Renamed conflicts:{}", stats.renamed_conflicts);
+    println!("  Source sqlite rows:{}", stats.from_sqlite);
+    println!("  Source markdown:   {}", stats.from_markdown);
+    println!(
+        "  Cron jobs: {} (untranslatable {})",
+        jobs_plan.summary(),
+        job_scan.untranslatable.len()
+    );
+    println!("  Skills:    {}", skills_plan.summary());
+    print_conflicts(&[&jobs_plan.conflicts, &skills_plan.conflicts]);
+
+    Ok(())
+}
+
+/// Store `entries` in the target backend; `source` names generated and
+/// renamed keys (`<source>_3`, `key__<source>_1`).
+async fn import_memories(
+    config: &Config,
+    entries: Vec<SourceEntry>,
+    stats: &mut MigrationStats,
+    source: &str,
+) -> Result<()> {
+    let memory = target_memory_backend(config)?;
+
+    for (idx, entry) in entries.into_iter().enumerate() {
+        let mut key = entry.key.trim().to_string();
+        if key.is_empty() {
+            key = format!("{source}_{idx}");
+        }
+
+        let key = match resolve_key(memory.as_ref(), &key, &entry.content, source).await? {
+            KeyResolution::Unchanged => {
+                stats.skipped_unchanged += 1;
+                continue;
+            }
+            KeyResolution::Renamed(renamed) => {
+                stats.renamed_conflicts += 1;
+                renamed
+            }
+            KeyResolution::Store(key) => key,
+        };
+
+        memory
+            .store_with_tags(&key, &entry.content, entry.category, &entry.tags)
+            .await?;
+        stats.imported += 1;
+    }
+    Ok(())
+}
+
+/// Count what an import would do without opening (and so creating) an absent target
+async fn plan_memories(
+    config: &Config,
+    entries: &[SourceEntry],
+    source: &str,
+) -> Result<CategoryPlan> {
+    let mut plan = CategoryPlan::default();
+    let Some(memory) = existing_target_memory(config)? else {
+        plan.new = entries.len();
+        return Ok(plan);
+    };
+    for entry in entries {
+        match resolve_key(memory.as_ref(), entry.key.trim(), &entry.content, source).await? {
+            KeyResolution::Store(_) => plan.new += 1,
+            KeyResolution::Unchanged => plan.unchanged += 1,
+            KeyResolution::Renamed(renamed) => plan.conflicts.push(format!(
+                "memory `{}` exists with different content (would import as `{renamed}`)",
+                entry.key.trim()
+            )),
+        }
+    }
+    Ok(plan)
+}
+
+fn existing_target_memory(config: &Config) -> Result<Option<Box<dyn Memory>>> {
+    let workspace = &config.workspace_dir;
+    let exists = match config.memory.backend.as_str() {
+        "sqlite" => workspace.join("memory").join("brain.db").exists(),
+        _ => workspace.join("MEMORY.md").exists() || workspace.join("memory").is_dir(),
+    };
+    if exists {
+        target_memory_backend(config).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+fn existing_cron_jobs(config: &Config) -> Result<Vec<crate::cron::CronJob>> {
+    if config.workspace_dir.join("cron").join("jobs.db").exists() {
+        crate::cron::list_jobs(config)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+fn import_jobs(
+    config: &Config,
+    scan: &jobs::JobScan,
+    actions: &[jobs::JobAction],
+) -> Result<CategoryPlan> {
+    let mut plan = CategoryPlan::default();
+    for (job, action) in scan.jobs.iter().zip(actions) {
+        match action {
+            jobs::JobAction::Add => {
+                crate::cron::add_job(config, &job.expression, &job.command)
+                    .with_context(|| format!("Failed to add cron job `{}`", job.name))?;
+                plan.new += 1;
+            }
+            jobs::JobAction::Unchanged => plan.unchanged += 1,
+            jobs::JobAction::Conflict(reason) => {
+                plan.conflicts
+                    .push(format!("job `{}`: {reason} (skipped)", job.name));
+            }
+        }
+    }
+    Ok(plan)
+}
+
+fn import_skills(config: &Config, plans: &[skills::SkillPlan]) -> Result<CategoryPlan> {
+    let mut plan = CategoryPlan::default();
+    for skill in plans {
+        match &skill.action {
+            skills::SkillAction::Copy => {
+                skills::copy_skill(skill, &config.workspace_dir)?;
+                plan.new += 1;
+            }
+            skills::SkillAction::Unchanged => plan.unchanged += 1,
+            skills::SkillAction::Conflict(reason) | skills::SkillAction::Invalid(reason) => {
+                plan.conflicts
+                    .push(format!("skill `{}`: {reason} (skipped)", skill.name));
+            }
+        }
+    }
+    Ok(plan)
+}
+
+fn print_job_and_skill_plan(
+    config: &Config,
+    job_scan: &jobs::JobScan,
+    job_actions: &[jobs::JobAction],
+    skill_plans: &[skills::SkillPlan],
+    memory_plan: &CategoryPlan,
+) {
+    let mut job_plan = CategoryPlan::default();
+    for (job, action) in job_scan.jobs.iter().zip(job_actions) {
+        match action {
+            jobs::JobAction::Add => job_plan.new += 1,
+            jobs::JobAction::Unchanged => job_plan.unchanged += 1,
+            jobs::JobAction::Conflict(reason) => {
+                job_plan
+                    .conflicts
+                    .push(format!("job `{}`: {reason}", job.name));
+            }
+        }
+    }
+    let mut skill_plan = CategoryPlan::default();
+    let mut invalid_skills = Vec::new();
+    for skill in skill_plans {
+        match &skill.action {
+            skills::SkillAction::Copy => skill_plan.new += 1,
+            skills::SkillAction::Unchanged => skill_plan.unchanged += 1,
+            skills::SkillAction::Conflict(reason) => {
+                skill_plan
+                    .conflicts
+                    .push(format!("skill `{}`: {reason}", skill.name));
+            }
+            skills::SkillAction::Invalid(reason) => {
+                invalid_skills.push(format!("skill `{}`: {reason}", skill.name));
+            }
+        }
+    }
+
+    println!(
+        "  Cron jobs: {} — {}, untranslatable {}",
+        job_scan.jobs.len() + job_scan.untranslatable.len(),
+        job_plan.summary(),
+        job_scan.untranslatable.len()
+    );
+    println!(
+        "  Skills:    {} — {}, invalid {}",
+        skill_plans.len(),
+        skill_plan.summary(),
+        invalid_skills.len()
+    );
+    print_conflicts(&[
+        &memory_plan.conflicts,
+        &job_plan.conflicts,
+        &skill_plan.conflicts,
+    ]);
+
+    let mut notes: Vec<String> = job_scan
+        .untranslatable
+        .iter()
+        .map(|job| format!("job `{}` not translatable: {}", job.name, job.reason))
+        .collect();
+    notes.extend(invalid_skills);
+    for job in &job_scan.jobs {
+        notes.extend(
+            job.warnings
+                .iter()
+                .map(|w| format!("job `{}`: {w}", job.name)),
+        );
+    }
+    let runs_agent = job_scan
+        .jobs
+        .iter()
+        .any(|j| j.command.starts_with("jarvis "));
+    if runs_agent
+        && crate::security::commands::CommandPolicy::from_config(&config.autonomy)
+            .map_or(true, |policy| policy.violation("jarvis agent").is_some())
+    {
+        notes.push(
+            "add \"jarvis\" to autonomy.allowed_commands so migrated agent jobs can run".into(),
+        );
+    }
+    if !notes.is_empty() {
+        println!("  Needs attention:");
+        for note in notes {
+            println!("    - {note}");
+        }
+    }
+}
+
+fn print_conflicts(groups: &[&[String]]) {
+    if groups.iter().all(|g| g.is_empty()) {
+        return;
+    }
+    println!("  Conflicts with existing Jarvis entries:");
+    for conflict in groups.iter().copied().flatten() {
+        println!("    - {conflict}");
+    }
+}
+
+fn target_memory_backend(config: &Config) -> Result<Box<dyn Memory>> {
+    match config.memory.backend.as_str() {
+        "sqlite" => Ok(Box::new(SqliteMemory::new(&config.workspace_dir)?)),
+        "markdown" | "none" => Ok(Box::new(MarkdownMemory::new(&config.workspace_dir))),
+        other => {
+            tracing::warn!(
+                "Unknown memory backend '{other}' during migration, defaulting to markdown"
+            );
+            Ok(Box::new(MarkdownMemory::new(&config.workspace_dir)))
+        }
+    }
+}
+
+fn collect_source_entries(
+    source_workspace: &Path,
+    stats: &mut MigrationStats,
+) -> Result<Vec<SourceEntry>> {
+    let mut entries = Vec::new();
+
+    let sqlite_path = source_workspace.join("memory").join("brain.db");
+    let sqlite_entries = read_openclaw_sqlite_entries(&sqlite_path)?;
+    stats.from_sqlite = sqlite_entries.len();
+    entries.extend(sqlite_entries);
+
+    let markdown_entries = read_openclaw_markdown_entries(source_workspace)?;
+    stats.from_markdown = markdown_entries.len();
+    entries.extend(markdown_entries);
+
+    // De-dup exact duplicates to make re-runs deterministic.
+    let mut seen = HashSet::new();
+    entries.retain(|entry| {
+        let sig = format!("{}\u{0}{}\u{0}{}", entry.key, entry.content, entry.category);
+        seen.insert(sig)
+    });
+
+    Ok(entries)
+}
+
+fn read_openclaw_sqlite_entries(db_path: &Path) -> Result<Vec<SourceEntry>> {
+    if !db_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open source db {}", db_path.display()))?;
+
+    let table_exists: Option<String> = conn
+        .query_row(
+            "SELECT name FROM sqlite_master WHERE type='table' AND name='memories' LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    if table_exists.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let columns = table_columns(&conn, "memories")?;
+    let key_expr = pick_column_expr(&columns, &["key", "id", "name"], "CAST(rowid AS TEXT)");
+    let Some(content_expr) =
+        pick_optional_column_expr(&columns, &["content", "value", "text", "memory"])
+    else {
+        bail!("OpenClaw memories table found but no content-like column was detected");
+    };
+    let category_expr = pick_column_expr(&columns, &["category", "kind", "type"], "'core'");
+
+    let sql = format!(
+        "SELECT {key_expr} AS key, {content_expr} AS content, {category_expr} AS category FROM memories"
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query([])?;
+
+    let mut entries = Vec::new();
+    let mut idx = 0_usize;
+
+    while let Some(row) = rows.next()? {
+        let key: String = row
+            .get(0)
+            .unwrap_or_else(|_| format!("openclaw_sqlite_{idx}"));
+        let content: String = row.get(1).unwrap_or_default();
+        let category_raw: String = row.get(2).unwrap_or_else(|_| "core".to_string());
+
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        entries.push(SourceEntry {
+            key: normalize_key(&key, idx),
+            content: content.trim().to_string(),
+            category: parse_category(&category_raw),
+            tags: Vec::new(),
+        });
+
+        idx += 1;
+    }
+
+    Ok(entries)
+}
+
+fn read_openclaw_markdown_entries(source_workspace: &Path) -> Result<Vec<SourceEntry>> {
+    let mut all = Vec::new();
+
+    let core_path = source_workspace.join("MEMORY.md");
+    if core_path.exists() {
+        let content = fs::read_to_string(&core_path)?;
+        all.extend(parse_markdown_file(
+            &core_path,
+            &content,
+            MemoryCategory::Core,
+            "openclaw_core",
+        ));
+    }
+
+    let daily_dir = source_workspace.join("memory");
+    if daily_dir.exists() {
+        for file in fs::read_dir(&daily_dir)? {
+            let file = file?;
+            let path = file.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                continue;
+            }
+            let content = fs::read_to_string(&path)?;
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("openclaw_daily");
+            all.extend(parse_markdown_file(
+                &path,
+                &content,
+                MemoryCategory::Daily,
+                stem,
+            ));
+        }
+    }
+
+    Ok(all)
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn parse_markdown_file(
+    _path: &Path,
+    content: &str,
+    default_category: MemoryCategory,
+    stem: &str,
+) -> Vec<SourceEntry> {
+    let mut entries = Vec::new();
+
+    for (idx, raw_line) in content.lines().enumerate() {
+        let trimmed = raw_line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let line = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+        let (key, text) = match parse_structured_memory_line(line) {
+            Some((k, v)) => (normalize_key(k, idx), v.trim().to_string()),
+            None => (
+                format!("openclaw_{stem}_{}", idx + 1),
+                line.trim().to_string(),
+            ),
+        };
+
+        if text.is_empty() {
+            continue;
+        }
+
+        entries.push(SourceEntry {
+            key,
+            content: text,
+            category: default_category.clone(),
+            tags: Vec::new(),
+        });
+    }
+
+    entries
+}
+
+fn parse_structured_memory_line(line: &str) -> Option<(&str, &str)> {
+    if !line.starts_with("**") {
+        return None;
+    }
+
+    let rest = line.strip_prefix("**")?;
+    let key_end = rest.find("**:")?;
+    let key = rest.get(..key_end)?.trim();
+    let value = rest.get(key_end + 3..)?.trim();
+
+    if key.is_empty() || value.is_empty() {
+        return None;
+    }
+
+    Some((key, value))
+}
+
+fn parse_category(raw: &str) -> MemoryCategory {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "core" | "" => MemoryCategory::Core,
+        "daily" => MemoryCategory::Daily,
+        "conversation" => MemoryCategory::Conversation,
+        other => MemoryCategory::Custom(other.to_string()),
+    }
+}
+
+fn normalize_key(key: &str, fallback_idx: usize) -> String {
+    let trimmed = key.trim();
+    if trimmed.is_empty() {
+        return format!("openclaw_{fallback_idx}");
+    }
+    trimmed.to_string()
+}
+
+/// Find where `content` should be stored under `key`, treating a copy that an earlier
+/// run already imported under a renamed key as unchanged (so re-runs are idempotent).
+async fn resolve_key(
+    memory: &dyn Memory,
+    key: &str,
+    content: &str,
+    source: &str,
+) -> Result<KeyResolution> {
+    let Some(existing) = memory.get(key).await? else {
+        return Ok(KeyResolution::Store(key.to_string()));
+    };
+    if existing.content.trim() == content.trim() {
+        return Ok(KeyResolution::Unchanged);
+    }
+
+    for i in 1..=10_000 {
+        let candidate = format!("{key}__{source}_{i}");
+        match memory.get(&candidate).await? {
+            None => return Ok(KeyResolution::Renamed(candidate)),
+            Some(entry) if entry.content.trim() == content.trim() => {
+                return Ok(KeyResolution::Unchanged);
+            }
+            Some(_) => {}
+        }
+    }
+
+    bail!("Unable to allocate non-conflicting key for '{key}'")
+}
+
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>> {
+    let pragma = format!("PRAGMA table_info({table})");
+    let mut stmt = conn.prepare(&pragma)?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(1))?;
+
+    let mut cols = Vec::new();
+    for col in rows {
+        cols.push(col?.to_ascii_lowercase());
+    }
+
+    Ok(cols)
+}
+
+fn pick_optional_column_expr(columns: &[String], candidates: &[&str]) -> Option<String> {
+    candidates
+        .iter()
+        .find(|candidate| columns.iter().any(|c| c == *candidate))
+        .map(std::string::ToString::to_string)
+}
+
+fn pick_column_expr(columns: &[String], candidates: &[&str], fallback: &str) -> String {
+    pick_optional_column_expr(columns, candidates).unwrap_or_else(|| fallback.to_string())
+}
+
+fn resolve_openclaw_workspace(source: Option<PathBuf>) -> Result<PathBuf> {
+    if let Some(src) = source {
+        return Ok(src);
+    }
+
+    let home = UserDirs::new()
+        .map(|u| u.home_dir().to_path_buf())
+        .context("Could not find home directory")?;
+
+    Ok(home.join(".openclaw").join("workspace"))
+}
+
+/// `OpenClaw` keeps jobs and managed skills beside the workspace (`~/.openclaw`);
+/// only look there when the source is laid out that way.
+fn openclaw_state_dir(source_workspace: &Path) -> Option<&Path> {
+    if source_workspace.file_name()? == "workspace" {
+        source_workspace.parent()
+    } else {
+        None
+    }
+}
+
+fn paths_equal(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn backup_target_memory(workspace_dir: &Path, source: &str) -> Result<Option<PathBuf>> {
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let backup_root = workspace_dir
+        .join("memory")
+        .join("migrations")
+        .join(format!("{source}-{timestamp}"));
+
+    let mut copied_any = false;
+    fs::create_dir_all(&backup_root)?;
+
+    let files_to_copy = [
+        workspace_dir.join("memory").join("brain.db"),
+        workspace_dir.join("MEMORY.md"),
+    ];
+
+    for source in files_to_copy {
+        if source.exists() {
+            let Some(name) = source.file_name() else {
+                continue;
+            };
+            fs::copy(&source, backup_root.join(name))?;
+            copied_any = true;
+        }
+    }
+
+    let daily_dir = workspace_dir.join("memory");
+    if daily_dir.exists() {
+        let daily_backup = backup_root.join("daily");
+        for file in fs::read_dir(&daily_dir)? {
+            let file = file?;
+            let path = file.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                continue;
+            }
+            fs::create_dir_all(&daily_backup)?;
+            let Some(name) = path.file_name() else {
+                continue;
+            };
+            fs::copy(&path, daily_backup.join(name))?;
+            copied_any = true;
+        }
+    }
+
+    if copied_any {
+        Ok(Some(backup_root))
+    } else {
+        let _ = fs::remove_dir_all(&backup_root);
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, MemoryConfig};
+    use rusqlite::params;
+    use tempfile::TempDir;
+
+    fn test_config(workspace: &Path) -> Config {
+        Config {
+            workspace_dir: workspace.to_path_buf(),
+            config_path: workspace.join("config.toml"),
+            memory: MemoryConfig {
+                backend: "sqlite".to_string(),
+                ..MemoryConfig::default()
+            },
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn parse_structured_markdown_line() {
+        let line = "**user_pref**: likes Rust";
+        let parsed = parse_structured_memory_line(line).unwrap();
+        assert_eq!(parsed.0, "user_pref");
+        assert_eq!(parsed.1, "likes Rust");
+    }
+
+    #[test]
+    fn parse_unstructured_markdown_generates_key() {
+        let entries = parse_markdown_file(
+            Path::new("/tmp/MEMORY.md"),
+            "- plain note",
+            MemoryCategory::Core,
+            "core",
+        );
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].key.starts_with("openclaw_core_"));
+        assert_eq!(entries[0].content, "plain note");
+    }
+
+    #[test]
+    fn sqlite_reader_supports_legacy_value_column() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("brain.db");
+        let conn = Connection::open(&db_path).unwrap();
+
+        conn.execute_batch("CREATE TABLE memories (key TEXT, value TEXT, type TEXT);")
+            .unwrap();
+        conn.execute(
+            "INSERT INTO memories (key, value, type) VALUES (?1, ?2, ?3)",
+            params!["legacy_key", "legacy_value", "daily"],
+        )
+        .unwrap();
+
+        let rows = read_openclaw_sqlite_entries(&db_path).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].key, "legacy_key");
+        assert_eq!(rows[0].content, "legacy_value");
+        assert_eq!(rows[0].category, MemoryCategory::Daily);
+    }
+
+    #[tokio::test]
+    async fn migration_renames_conflicting_key() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+
+        // Existing target memory
+        let target_mem = SqliteMemory::new(target.path()).unwrap();
+        target_mem
+            .store("k", "new value", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        // Source sqlite with conflicting key + different content
+        let source_db_dir = source.path().join("memory");
+        fs::create_dir_all(&source_db_dir).unwrap();
+        let source_db = source_db_dir.join("brain.db");
+        let conn = Connection::open(&source_db).unwrap();
+        conn.execute_batch("CREATE TABLE memories (key TEXT, content TEXT, category TEXT);")
+            .unwrap();
+        conn.execute(
+            "INSERT INTO memories (key, content, category) VALUES (?1, ?2, ?3)",
+            params!["k", "old value", "core"],
+        )
+        .unwrap();
+
+        let config = test_config(target.path());
+        migrate_openclaw(&config, Some(source.path().to_path_buf()), false)
+            .await
+            .unwrap();
+
+        let all = target_mem.list(None).await.unwrap();
+        assert!(all.iter().any(|e| e.key == "k" && e.content == "new value"));
+        assert!(all
+            .iter()
+            .any(|e| e.key.starts_with("k__openclaw_") && e.content == "old value"));
+    }
+
+    #[tokio::test]
+    async fn dry_run_does_not_write() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        let source_db_dir = source.path().join("memory");
+        fs::create_dir_all(&source_db_dir).unwrap();
+
+        let source_db = source_db_dir.join("brain.db");
+        let conn = Connection::open(&source_db).unwrap();
+        conn.execute_batch("CREATE TABLE memories (key TEXT, content TEXT, category TEXT);")
+            .unwrap();
+        conn.execute(
+            "INSERT INTO memories (key, content, category) VALUES (?1, ?2, ?3)",
+            params!["dry", "run", "core"],
+        )
+        .unwrap();
+
+        let config = test_config(target.path());
+        migrate_openclaw(&config, Some(source.path().to_path_buf()), true)
+            .await
+            .unwrap();
+
+        let target_mem = SqliteMemory::new(target.path()).unwrap();
+        assert_eq!(target_mem.count().await.unwrap(), 0);
+    }
+
+    fn seed_openclaw_source(source: &Path) {
+        let db_dir = source.join("memory");
+        fs::create_dir_all(&db_dir).unwrap();
+        let conn = Connection::open(db_dir.join("brain.db")).unwrap();
+        conn.execute_batch("CREATE TABLE memories (key TEXT, content TEXT, category TEXT);")
+            .unwrap();
+        conn.execute(
+            "INSERT INTO memories (key, content, category) VALUES (?1, ?2, ?3)",
+            params!["k", "old value", "core"],
+        )
+        .unwrap();
+
+        fs::create_dir_all(source.join("cron")).unwrap();
+        fs::write(
+            source.join("cron").join("jobs.json"),
+            r#"{"version":1,"jobs":[
+                {"name":"digest","schedule":{"kind":"cron","expr":"0 8 * * *"},
+                 "payload":{"kind":"agentTurn","message":"Send the digest"}},
+                {"name":"once","schedule":{"kind":"at","atMs":1767225600000},
+                 "payload":{"kind":"agentTurn","message":"x"}}
+            ]}"#,
+        )
+        .unwrap();
+
+        let skill = source.join("skills").join("weather");
+        fs::create_dir_all(&skill).unwrap();
+        fs::write(skill.join("SKILL.md"), "# Weather\nForecasts.\n").unwrap();
+    }
+
+    #[tokio::test]
+    async fn migration_imports_jobs_and_skills_idempotently() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        seed_openclaw_source(source.path());
+
+        // Existing conflicting memory forces a rename on the first run
+        let target_mem = SqliteMemory::new(target.path()).unwrap();
+        target_mem
+            .store("k", "new value", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let config = test_config(target.path());
+        for _ in 0..2 {
+            migrate_openclaw(&config, Some(source.path().to_path_buf()), false)
+                .await
+                .unwrap();
+        }
+
+        let jobs = crate::cron::list_jobs(&config).unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].expression, "0 8 * * *");
+        assert_eq!(jobs[0].command, "jarvis agent -m 'Send the digest'");
+
+        let skill = crate::skills::load_skill_dir(&target.path().join("skills/weather")).unwrap();
+        assert_eq!(skill.name, "weather");
+
+        let renamed: Vec<_> = target_mem
+            .list(None)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.key.starts_with("k__openclaw_"))
+            .collect();
+        assert_eq!(renamed.len(), 1);
+        assert_eq!(target_mem.count().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn dry_run_plans_jobs_and_skills_without_writing() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        seed_openclaw_source(source.path());
+
+        let config = test_config(target.path());
+        migrate_openclaw(&config, Some(source.path().to_path_buf()), true)
+            .await
+            .unwrap();
+
+        assert!(!target.path().join("cron").exists());
+        assert!(!target.path().join("skills").exists());
+        assert!(!target.path().join("memory").exists());
+    }
+
+    #[tokio::test]
+    async fn plan_memories_reports_conflicts() {
+        let target = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(target.path()).unwrap();
+        mem.store("same", "v", MemoryCategory::Core).await.unwrap();
+        mem.store("diff", "mine", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let entries = vec![
+            SourceEntry {
+                key: "same".into(),
+                content: "v".into(),
+                category: MemoryCategory::Core,
+                tags: Vec::new(),
+            },
+            SourceEntry {
+                key: "diff".into(),
+                content: "theirs".into(),
+                category: MemoryCategory::Core,
+                tags: Vec::new(),
+            },
+            SourceEntry {
+                key: "fresh".into(),
+                content: "x".into(),
+                category: MemoryCategory::Core,
+                tags: Vec::new(),
+            },
+        ];
+        let plan = plan_memories(&test_config(target.path()), &entries, "openclaw")
+            .await
+            .unwrap();
+        assert_eq!((plan.new, plan.unchanged, plan.conflicts.len()), (1, 1, 1));
+        assert!(plan.conflicts[0].contains("diff__openclaw_1"));
+    }
+}
@@ -0,0 +1,165 @@
+//! Copy `OpenClaw` skills into `workspace/skills`.
+//!
+//! `OpenClaw` keeps per-workspace skills in `<workspace>/skills/<name>/SKILL.md` and
+//! managed skills in `<state dir>/skills`. Each candidate must load through the
+//! Jarvis skills loader before it is copied.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum SkillAction {
+    Copy,
+    Unchanged,
+    Conflict(String),
+    Invalid(String),
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct SkillPlan {
+    pub name: String,
+    pub source: PathBuf,
+    pub action: SkillAction,
+}
+
+fn source_skill_dirs(source_workspace: &Path) -> Vec<PathBuf> {
+    let mut roots = vec![source_workspace.join("skills")];
+    if let Some(state_dir) = super::openclaw_state_dir(source_workspace) {
+        roots.push(state_dir.join("skills"));
+    }
+    roots
+}
+
+/// Plan the copy of every `OpenClaw` skill; workspace skills win over managed ones
+pub(super) fn scan_openclaw_skills(
+    source_workspace: &Path,
+    target_workspace: &Path,
+) -> Result<Vec<SkillPlan>> {
+    let target_root = crate::skills::skills_dir(target_workspace);
+    let mut plans: Vec<SkillPlan> = Vec::new();
+
+    for root in source_skill_dirs(source_workspace) {
+        if !root.is_dir() {
+            continue;
+        }
+        let mut entries: Vec<_> = fs::read_dir(&root)
+            .with_context(|| format!("Failed to read {}", root.display()))?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .collect();
+        entries.sort();
+
+        for dir in entries {
+            let Some(name) = dir.file_name().and_then(|n| n.to_str()).map(String::from) else {
+                continue;
+            };
+            if name.starts_with('.') || plans.iter().any(|p| p.name == name) {
+                continue;
+            }
+            let action = plan_skill(&dir, &target_root.join(&name));
+            plans.push(SkillPlan {
+                name,
+                source: dir,
+                action,
+            });
+        }
+    }
+    Ok(plans)
+}
+
+fn plan_skill(source: &Path, target: &Path) -> SkillAction {
+    if let Err(reason) = validate_skill(source) {
+        return SkillAction::Invalid(reason);
+    }
+    if !target.exists() {
+        return SkillAction::Copy;
+    }
+    if manifest_bytes(source) == manifest_bytes(target) {
+        SkillAction::Unchanged
+    } else {
+        SkillAction::Conflict(format!(
+            "{} already exists with a different manifest",
+            target.display()
+        ))
+    }
+}
+
+fn validate_skill(dir: &Path) -> std::result::Result<(), String> {
+    let skill = crate::skills::load_skill_dir(dir).map_err(|e| format!("{e:#}"))?;
+    if skill.prompts.iter().all(|p| p.trim().is_empty()) && skill.tools.is_empty() {
+        return Err("SKILL.md is empty".into());
+    }
+    Ok(())
+}
+
+/// SKILL.toml or SKILL.md contents — what identifies a skill's version
+fn manifest_bytes(dir: &Path) -> Option<Vec<u8>> {
+    fs::read(dir.join("SKILL.toml"))
+        .or_else(|_| fs::read(dir.join("SKILL.md")))
+        .ok()
+}
+
+pub(super) fn copy_skill(plan: &SkillPlan, target_workspace: &Path) -> Result<()> {
+    let target = crate::skills::skills_dir(target_workspace).join(&plan.name);
+    crate::skills::copy_dir_recursive(&plan.source, &target)
+        .with_context(|| format!("Failed to copy skill {}", plan.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_skill(root: &Path, name: &str, body: &str) {
+        let dir = root.join("skills").join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("SKILL.md"), body).unwrap();
+    }
+
+    #[test]
+    fn plans_copy_unchanged_conflict_and_invalid() {
+        let (source, target) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        write_skill(source.path(), "weather", "# Weather\nForecasts.\n");
+        write_skill(source.path(), "notes", "# Notes\nTake notes.\n");
+        write_skill(source.path(), "deploy", "# Deploy\nShip it.\n");
+        write_skill(source.path(), "empty", "   \n");
+        fs::create_dir_all(source.path().join("skills").join("no-manifest")).unwrap();
+
+        write_skill(target.path(), "notes", "# Notes\nTake notes.\n");
+        write_skill(target.path(), "deploy", "# Deploy\nLocal variant.\n");
+
+        let plans = scan_openclaw_skills(source.path(), target.path()).unwrap();
+        let action = |name: &str| {
+            plans
+                .iter()
+                .find(|p| p.name == name)
+                .map(|p| p.action.clone())
+                .unwrap()
+        };
+        assert_eq!(action("weather"), SkillAction::Copy);
+        assert_eq!(action("notes"), SkillAction::Unchanged);
+        assert!(matches!(action("deploy"), SkillAction::Conflict(_)));
+        assert!(matches!(action("empty"), SkillAction::Invalid(_)));
+        assert!(matches!(action("no-manifest"), SkillAction::Invalid(_)));
+    }
+
+    #[test]
+    fn copy_brings_supporting_files() {
+        let (source, target) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        write_skill(source.path(), "weather", "# Weather\nForecasts.\n");
+        let scripts = source.path().join("skills/weather/scripts");
+        fs::create_dir_all(&scripts).unwrap();
+        fs::write(scripts.join("fetch.sh"), "curl wttr.in").unwrap();
+
+        let plans = scan_openclaw_skills(source.path(), target.path()).unwrap();
+        copy_skill(&plans[0], target.path()).unwrap();
+
+        let copied = target.path().join("skills/weather");
+        assert!(copied.join("SKILL.md").exists());
+        assert!(copied.join("scripts/fetch.sh").exists());
+        let again = scan_openclaw_skills(source.path(), target.path()).unwrap();
+        assert_eq!(again[0].action, SkillAction::Unchanged);
+    }
+}
@@ -0,0 +1,331 @@
+//! Outbound notifications: heartbeat and cron results sent to the channel
+//! in `[notify]` (or `[heartbeat.notify_channel]` for heartbeat tasks),
+//! reusing the channel implementations to deliver them.
+//!
+//! The `threshold` decides which results are worth a message: everything,
+//! only results with something to act on (plus failures), or only failures.
+
+use crate::channels::{Channel, DiscordChannel, SlackChannel, TelegramChannel};
+use crate::config::{ChannelsConfig, Config, NotifyConfig, NotifyThreshold};
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use std::time::Duration;
+
+const WEBHOOK_TIMEOUT_SECS: u64 = 15;
+/// Longest body sent; chat channels cap message length.
+const MAX_BODY_CHARS: usize = 3500;
+
+/// One result to report.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    /// "heartbeat", "cron" or "test"
+    pub source: &'static str,
+    /// What ran: the heartbeat task or the cron command
+    pub title: String,
+    pub body: String,
+    pub failed: bool,
+    /// Whether a successful result has something for the user to act on
+    pub actionable: bool,
+}
+
+impl Notification {
+    fn icon(&self) -> &'static str {
+        match (self.failed, self.source) {
+            (true, _) => "⚠️",
+            (false, "heartbeat") => "💓",
+            (false, "cron") => "⏰",
+            _ => "🔔",
+        }
+    }
+}
+
+enum Destination {
+    Channel(Arc<dyn Channel>),
+    Webhook(reqwest::Client),
+}
+
+/// Sends notifications to one configured channel.
+pub struct Notifier {
+    destination: Destination,
+    target: String,
+    threshold: NotifyThreshold,
+}
+
+impl Notifier {
+    /// Build from a notify block, using the credentials of the matching
+    /// `[channels_config]` entry. `section` names the block in errors.
+    pub fn from_config(
+        notify: &NotifyConfig,
+        channels: &ChannelsConfig,
+        section: &str,
+    ) -> Result<Self> {
+        let target = notify.target.trim().to_string();
+        if target.is_empty() {
+            anyhow::bail!("{section}.target 不能为空");
+        }
+        let missing = |name: &str| {
+            anyhow::anyhow!("{section} 使用 {name}，但未配置 [channels_config.{name}]")
+        };
+        let destination = match notify.channel.as_str() {
+            "telegram" => {
+                let tg = channels
+                    .telegram
+                    .as_ref()
+                    .ok_or_else(|| missing("telegram"))?;
+                Destination::Channel(Arc::new(TelegramChannel::new(
+                    tg.bot_token.clone(),
+                    tg.allowed_users.clone(),
+                )))
+            }
+            "discord" => {
+                let dc = channels
+                    .discord
+                    .as_ref()
+                    .ok_or_else(|| missing("discord"))?;
+                Destination::Channel(Arc::new(DiscordChannel::from_config(dc)))
+            }
+            "slack" => {
+                let sl = channels.slack.as_ref().ok_or_else(|| missing("slack"))?;
+                Destination::Channel(Arc::new(SlackChannel::from_config(sl)))
+            }
+            "webhook" => {
+                if !target.starts_with("https://") && !target.starts_with("http://") {
+                    anyhow::bail!("{section}.target 必须是 http(s) URL");
+                }
+                Destination::Webhook(
+                    reqwest::Client::builder()
+                        .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+                        .build()?,
+                )
+            }
+            other => anyhow::bail!(
+                "不支持的 {section}.channel: {other}（可选 telegram、slack、discord、webhook）"
+            ),
+        };
+        Ok(Self {
+            destination,
+            target,
+            threshold: notify.threshold,
+        })
+    }
+
+    /// The notifier for `[notify]`, if configured.
+    pub fn from_notify(config: &Config) -> Result<Option<Self>> {
+        config
+            .notify
+            .as_ref()
+            .map(|notify| Self::from_config(notify, &config.channels_config, "notify"))
+            .transpose()
+    }
+
+    pub fn threshold(&self) -> NotifyThreshold {
+        self.threshold
+    }
+
+    /// Whether `notification` clears the threshold.
+    pub fn wants(&self, notification: &Notification) -> bool {
+        match self.threshold {
+            NotifyThreshold::All => true,
+            NotifyThreshold::Actionable => notification.failed || notification.actionable,
+            NotifyThreshold::Failures => notification.failed,
+        }
+    }
+
+    /// Send `notification` if it clears the threshold. Returns whether
+    /// anything was sent.
+    pub async fn deliver(&self, notification: &Notification) -> Result<bool> {
+        if !self.wants(notification) {
+            tracing::debug!(
+                "{} 结果未达到通知阈值：{}",
+                notification.source,
+                notification.title
+            );
+            return Ok(false);
+        }
+        self.send(notification).await?;
+        Ok(true)
+    }
+
+    /// Send `notification` regardless of the threshold.
+    pub async fn send(&self, notification: &Notification) -> Result<()> {
+        let body = crate::util::truncate_with_ellipsis(notification.body.trim(), MAX_BODY_CHARS);
+        match &self.destination {
+            Destination::Channel(channel) => {
+                let message = format!("{} {}\n\n{body}", notification.icon(), notification.title);
+                channel
+                    .send(&message, &self.target)
+                    .await
+                    .with_context(|| format!("通过 {} 发送通知失败", channel.name()))?;
+            }
+            Destination::Webhook(client) => {
+                let payload = serde_json::json!({
+                    "source": notification.source,
+                    "task": notification.title,
+                    "response": body,
+                    "success": !notification.failed,
+                });
+                let resp = client
+                    .post(&self.target)
+                    .json(&payload)
+                    .send()
+                    .await
+                    .context("发送通知 webhook 失败")?;
+                let status = resp.status();
+                if !status.is_success() {
+                    anyhow::bail!("通知 webhook 返回 {status}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Handle `jarvis notify ...`.
+pub async fn handle_command(command: crate::NotifyCommands, config: &Config) -> Result<()> {
+    match command {
+        crate::NotifyCommands::Test { message } => {
+            let mut destinations: Vec<(&str, NotifyConfig)> = Vec::new();
+            if let Some(notify) = &config.notify {
+                destinations.push(("notify", notify.clone()));
+            }
+            if let Some(heartbeat) = &config.heartbeat.notify_channel {
+                destinations.push(("heartbeat.notify_channel", heartbeat.into()));
+            }
+            if destinations.is_empty() {
+                anyhow::bail!(
+                    "未配置通知渠道：请在 config.toml 中添加 [notify]（channel、target）"
+                );
+            }
+
+            let notification = Notification {
+                source: "test",
+                title: "Jarvis 通知测试".into(),
+                body: message.unwrap_or_else(|| "收到这条消息说明通知渠道配置正确。".into()),
+                failed: false,
+                actionable: true,
+            };
+            let mut failures = 0;
+            for (section, notify) in &destinations {
+                let sent = match Notifier::from_config(notify, &config.channels_config, section) {
+                    Ok(notifier) => notifier.send(&notification).await,
+                    Err(e) => Err(e),
+                };
+                match sent {
+                    Ok(()) => println!(
+                        "✅ [{section}] 已发送测试通知到 {} {}",
+                        notify.channel,
+                        notify.target.trim()
+                    ),
+                    Err(e) => {
+                        failures += 1;
+                        println!("❌ [{section}] 发送失败：{e:#}");
+                    }
+                }
+            }
+            if failures > 0 {
+                anyhow::bail!("{failures} 个通知渠道发送失败");
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TelegramConfig;
+
+    fn notify(channel: &str, target: &str, threshold: NotifyThreshold) -> NotifyConfig {
+        NotifyConfig {
+            channel: channel.into(),
+            target: target.into(),
+            threshold,
+        }
+    }
+
+    fn result(failed: bool, actionable: bool) -> Notification {
+        Notification {
+            source: "cron",
+            title: "backup.sh".into(),
+            body: "done".into(),
+            failed,
+            actionable,
+        }
+    }
+
+    #[test]
+    fn from_config_requires_channel_credentials() {
+        let channels = ChannelsConfig::default();
+        let err = Notifier::from_config(
+            &notify("telegram", "123", NotifyThreshold::All),
+            &channels,
+            "notify",
+        )
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("channels_config.telegram"));
+
+        let channels = ChannelsConfig {
+            telegram: Some(TelegramConfig {
+                bot_token: "123:ABC".into(),
+                allowed_users: vec![],
+            }),
+            ..ChannelsConfig::default()
+        };
+        let build = |channel: &str, target: &str| {
+            Notifier::from_config(
+                &notify(channel, target, NotifyThreshold::All),
+                &channels,
+                "notify",
+            )
+        };
+        assert!(build("telegram", "123").is_ok());
+        assert!(build("telegram", " ").is_err());
+        assert!(build("sms", "123").is_err());
+        let err = build("webhook", "ftp://x").err().unwrap();
+        assert_eq!(err.to_string(), "notify.target 必须是 http(s) URL");
+    }
+
+    #[test]
+    fn threshold_filters_results() {
+        let channels = ChannelsConfig::default();
+        let notifier = |threshold| {
+            Notifier::from_config(
+                &notify("webhook", "https://example.com/hook", threshold),
+                &channels,
+                "notify",
+            )
+            .unwrap()
+        };
+        let routine = result(false, false);
+        let actionable = result(false, true);
+        let failed = result(true, false);
+
+        let all = notifier(NotifyThreshold::All);
+        assert!(all.wants(&routine) && all.wants(&actionable) && all.wants(&failed));
+        let default = notifier(NotifyThreshold::default());
+        assert!(!default.wants(&routine));
+        assert!(default.wants(&actionable) && default.wants(&failed));
+        let failures = notifier(NotifyThreshold::Failures);
+        assert!(!failures.wants(&routine) && !failures.wants(&actionable));
+        assert!(failures.wants(&failed));
+    }
+
+    #[tokio::test]
+    async fn results_below_the_threshold_are_not_sent() {
+        let channels = ChannelsConfig::default();
+        // Port 9 (discard) is never reached because nothing is sent
+        let notifier = Notifier::from_config(
+            &notify(
+                "webhook",
+                "http://127.0.0.1:9/hook",
+                NotifyThreshold::Failures,
+            ),
+            &channels,
+            "notify",
+        )
+        .unwrap();
+        assert!(!notifier.deliver(&result(false, true)).await.unwrap());
+        assert!(notifier.deliver(&result(true, false)).await.is_err());
+    }
+}
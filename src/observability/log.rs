@@ -19,17 +19,22 @@ impl Observer for LogObserver {
             ObserverEvent::AgentEnd {
                 duration,
                 tokens_used,
+                cost_usd,
             } => {
                 let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
-                info!(duration_ms = ms, tokens = ?tokens_used, "agent.end");
+                info!(duration_ms = ms, tokens = ?tokens_used, cost_usd = ?cost_usd, "agent.end");
+            }
+            ObserverEvent::ToolStart { tool, .. } => {
+                info!(tool = %tool, "tool.start");
             }
             ObserverEvent::ToolCall {
                 tool,
                 duration,
                 success,
+                error,
             } => {
                 let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
-                info!(tool = %tool, duration_ms = ms, success = success, "tool.call");
+                info!(tool = %tool, duration_ms = ms, success = success, error = ?error, "tool.call");
             }
             ObserverEvent::ChannelMessage { channel, direction } => {
                 info!(channel = %channel, direction = %direction, "channel.message");
@@ -37,6 +42,47 @@ impl Observer for LogObserver {
             ObserverEvent::HeartbeatTick => {
                 info!("heartbeat.tick");
             }
+            ObserverEvent::HeartbeatTask {
+                task,
+                duration,
+                success,
+                timed_out,
+            } => {
+                let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+                info!(task = %task, duration_ms = ms, success = success, timed_out = timed_out, "heartbeat.task");
+            }
+            ObserverEvent::ProviderCall {
+                provider,
+                model,
+                duration,
+                success,
+            } => {
+                let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+                info!(provider = %provider, model = %model, duration_ms = ms, success = success, "provider.call");
+            }
+            ObserverEvent::ProviderRetry {
+                provider,
+                attempt,
+                max_retries,
+                reason,
+                backoff,
+            } => {
+                let ms = u64::try_from(backoff.as_millis()).unwrap_or(u64::MAX);
+                info!(
+                    provider = %provider,
+                    "provider.retry: 重试 {attempt}/{max_retries}（{reason}，等待 {ms}ms）"
+                );
+            }
+            ObserverEvent::ProviderFallback {
+                primary,
+                served_by,
+                model,
+            } => {
+                info!(primary = %primary, served_by = %served_by, model = %model, "provider.fallback");
+            }
+            ObserverEvent::MemoryHygiene { archived, purged } => {
+                info!(archived = archived, purged = purged, "memory.hygiene");
+            }
             ObserverEvent::Error { component, message } => {
                 info!(component = %component, error = %message, "error");
             }
@@ -86,21 +132,50 @@ mod tests {
         obs.record_event(&ObserverEvent::AgentEnd {
             duration: Duration::from_millis(500),
             tokens_used: Some(100),
+            cost_usd: None,
         });
         obs.record_event(&ObserverEvent::AgentEnd {
             duration: Duration::ZERO,
             tokens_used: None,
+            cost_usd: None,
+        });
+        obs.record_event(&ObserverEvent::ToolStart {
+            tool: "shell".into(),
+            arguments: r#"{"command":"ls"}"#.into(),
         });
         obs.record_event(&ObserverEvent::ToolCall {
             tool: "shell".into(),
             duration: Duration::from_millis(10),
             success: false,
+            error: Some("exit status 1".into()),
         });
         obs.record_event(&ObserverEvent::ChannelMessage {
             channel: "telegram".into(),
             direction: "outbound".into(),
         });
         obs.record_event(&ObserverEvent::HeartbeatTick);
+        obs.record_event(&ObserverEvent::HeartbeatTask {
+            task: "Check my email".into(),
+            duration: Duration::from_secs(45),
+            success: false,
+            timed_out: true,
+        });
+        obs.record_event(&ObserverEvent::ProviderRetry {
+            provider: "openrouter".into(),
+            attempt: 2,
+            max_retries: 5,
+            reason: "429".into(),
+            backoff: Duration::from_millis(800),
+        });
+        obs.record_event(&ObserverEvent::ProviderFallback {
+            primary: "openrouter".into(),
+            served_by: "anthropic".into(),
+            model: "claude-sonnet-4-20250514".into(),
+        });
+        obs.record_event(&ObserverEvent::MemoryHygiene {
+            archived: 3,
+            purged: 1,
+        });
         obs.record_event(&ObserverEvent::Error {
             component: "provider".into(),
             message: "timeout".into(),
@@ -1,10 +1,14 @@
 pub mod log;
 pub mod multi;
 pub mod noop;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod prometheus;
 pub mod traits;
 
 pub use self::log::LogObserver;
 pub use noop::NoopObserver;
+pub use prometheus::PrometheusObserver;
 pub use traits::{Observer, ObserverEvent};
 
 use crate::config::ObservabilityConfig;
@@ -13,6 +17,16 @@ use crate::config::ObservabilityConfig;
 pub fn create_observer(config: &ObservabilityConfig) -> Box<dyn Observer> {
     match config.backend.as_str() {
         "log" => Box::new(LogObserver::new()),
+        "prometheus" => Box::new(PrometheusObserver::new()),
+        #[cfg(feature = "otel")]
+        "otel" => Box::new(otel::OtelObserver::new(config)),
+        #[cfg(not(feature = "otel"))]
+        "otel" => {
+            tracing::warn!(
+                "observability backend 'otel' needs jarvis built with `--features otel`, falling back to noop"
+            );
+            Box::new(NoopObserver)
+        }
         "none" | "noop" => Box::new(NoopObserver),
         _ => {
             tracing::warn!(
@@ -32,6 +46,7 @@ mod tests {
     fn factory_none_returns_noop() {
         let cfg = ObservabilityConfig {
             backend: "none".into(),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "noop");
     }
@@ -40,6 +55,7 @@ mod tests {
     fn factory_noop_returns_noop() {
         let cfg = ObservabilityConfig {
             backend: "noop".into(),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "noop");
     }
@@ -48,14 +64,39 @@ mod tests {
     fn factory_log_returns_log() {
         let cfg = ObservabilityConfig {
             backend: "log".into(),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "log");
     }
 
     #[test]
-    fn factory_unknown_falls_back_to_noop() {
+    fn factory_prometheus_returns_prometheus() {
         let cfg = ObservabilityConfig {
             backend: "prometheus".into(),
+            ..ObservabilityConfig::default()
+        };
+        assert_eq!(create_observer(&cfg).name(), "prometheus");
+    }
+
+    #[test]
+    fn factory_otel_needs_the_feature() {
+        let cfg = ObservabilityConfig {
+            backend: "otel".into(),
+            ..ObservabilityConfig::default()
+        };
+        let expected = if cfg!(feature = "otel") {
+            "otel"
+        } else {
+            "noop"
+        };
+        assert_eq!(create_observer(&cfg).name(), expected);
+    }
+
+    #[test]
+    fn factory_unknown_falls_back_to_noop() {
+        let cfg = ObservabilityConfig {
+            backend: "zipkin".into(),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "noop");
     }
@@ -64,6 +105,7 @@ mod tests {
     fn factory_empty_string_falls_back_to_noop() {
         let cfg = ObservabilityConfig {
             backend: String::new(),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "noop");
     }
@@ -72,6 +114,7 @@ mod tests {
     fn factory_garbage_falls_back_to_noop() {
         let cfg = ObservabilityConfig {
             backend: "xyzzy_garbage_123".into(),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "noop");
     }
@@ -29,6 +29,10 @@ mod tests {
     fn noop_record_event_does_not_panic() {
         let obs = NoopObserver;
         obs.record_event(&ObserverEvent::HeartbeatTick);
+        obs.record_event(&ObserverEvent::MemoryHygiene {
+            archived: 0,
+            purged: 0,
+        });
         obs.record_event(&ObserverEvent::AgentStart {
             provider: "test".into(),
             model: "test".into(),
@@ -36,15 +40,18 @@ mod tests {
         obs.record_event(&ObserverEvent::AgentEnd {
             duration: Duration::from_millis(100),
             tokens_used: Some(42),
+            cost_usd: None,
         });
         obs.record_event(&ObserverEvent::AgentEnd {
             duration: Duration::ZERO,
             tokens_used: None,
+            cost_usd: None,
         });
         obs.record_event(&ObserverEvent::ToolCall {
             tool: "shell".into(),
             duration: Duration::from_secs(1),
             success: true,
+            error: None,
         });
         obs.record_event(&ObserverEvent::ChannelMessage {
             channel: "cli".into(),
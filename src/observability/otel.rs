@@ -0,0 +1,641 @@
+//! `backend = "otel"`: OpenTelemetry traces, exported over OTLP/HTTP with
+//! the JSON encoding, so any collector (or Jaeger, Tempo, Honeycomb…)
+//! accepting OTLP on port 4318 can show them. Built with `--features otel`.
+//!
+//! An agent run, `AgentStart` to `AgentEnd`, is one span; the tool calls
+//! and provider requests made during it are its children. A run is sent
+//! when it ends. Tool calls outside a run (channels, the gateway) are
+//! spans of their own, sent as they finish.
+
+use super::traits::{Observer, ObserverEvent, ObserverMetric};
+use crate::config::ObservabilityConfig;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Where spans go when `otel_endpoint` isn't set: a local collector.
+pub const DEFAULT_ENDPOINT: &str = "http://localhost:4318";
+/// How long `flush` waits for the spans queued so far to be sent.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A span attribute value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    String(String),
+    Int(i64),
+    Double(f64),
+    Bool(bool),
+}
+
+/// A finished span.
+#[derive(Debug, Clone)]
+pub struct SpanData {
+    pub trace_id: [u8; 16],
+    pub span_id: [u8; 8],
+    pub parent_span_id: Option<[u8; 8]>,
+    pub name: String,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub attributes: Vec<(&'static str, AttributeValue)>,
+    /// Status message when the operation failed
+    pub error: Option<String>,
+}
+
+impl SpanData {
+    pub fn attribute(&self, key: &str) -> Option<&AttributeValue> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v)
+    }
+}
+
+/// Where finished spans are sent. Called from the thread recording the
+/// event, so implementations hand the spans off rather than block.
+pub trait SpanExporter: Send + Sync {
+    fn export(&self, spans: Vec<SpanData>);
+
+    /// Wait (briefly) until the spans exported so far have been sent.
+    fn flush(&self) {}
+}
+
+/// Keeps spans in memory, for tests.
+#[derive(Default, Clone)]
+pub struct InMemoryExporter {
+    spans: Arc<Mutex<Vec<SpanData>>>,
+}
+
+impl InMemoryExporter {
+    pub fn spans(&self) -> Vec<SpanData> {
+        self.spans
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl SpanExporter for InMemoryExporter {
+    fn export(&self, spans: Vec<SpanData>) {
+        self.spans
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend(spans);
+    }
+}
+
+enum Job {
+    Spans(Vec<SpanData>),
+    Flush(mpsc::SyncSender<()>),
+}
+
+/// Posts spans to `<endpoint>/v1/traces` from a background thread.
+pub struct OtlpExporter {
+    jobs: mpsc::Sender<Job>,
+}
+
+impl OtlpExporter {
+    pub fn new(endpoint: &str, headers: BTreeMap<String, String>) -> Self {
+        let endpoint = endpoint.trim().trim_end_matches('/');
+        let url = if endpoint.ends_with("/v1/traces") {
+            endpoint.to_string()
+        } else {
+            format!("{endpoint}/v1/traces")
+        };
+        let (tx, rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("otel-export".into())
+            .spawn(move || send_loop(&url, &headers, &rx))
+            .ok();
+        Self { jobs: tx }
+    }
+}
+
+fn send_loop(url: &str, headers: &BTreeMap<String, String>, jobs: &mpsc::Receiver<Job>) {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(EXPORT_TIMEOUT)
+        .build()
+        .unwrap_or_else(|_| reqwest::blocking::Client::new());
+    // Warn when exports start failing, not on every attempt
+    let mut failing = false;
+    for job in jobs {
+        let spans = match job {
+            Job::Spans(spans) => spans,
+            Job::Flush(done) => {
+                let _ = done.send(());
+                continue;
+            }
+        };
+        let mut request = client.post(url).json(&encode(&spans));
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        match request
+            .send()
+            .map(reqwest::blocking::Response::error_for_status)
+        {
+            Ok(Ok(_)) => {
+                if failing {
+                    tracing::info!("OpenTelemetry 导出已恢复: {url}");
+                }
+                failing = false;
+            }
+            Ok(Err(e)) | Err(e) => {
+                if !failing {
+                    tracing::warn!("导出 OpenTelemetry span 到 {url} 失败: {e}");
+                }
+                failing = true;
+            }
+        }
+    }
+}
+
+impl SpanExporter for OtlpExporter {
+    fn export(&self, spans: Vec<SpanData>) {
+        let _ = self.jobs.send(Job::Spans(spans));
+    }
+
+    fn flush(&self) {
+        let (done, wait) = mpsc::sync_channel(1);
+        if self.jobs.send(Job::Flush(done)).is_ok() {
+            let _ = wait.recv_timeout(FLUSH_TIMEOUT);
+        }
+    }
+}
+
+/// The OTLP/JSON `ExportTraceServiceRequest` body for `spans`.
+pub fn encode(spans: &[SpanData]) -> Value {
+    let spans: Vec<Value> = spans
+        .iter()
+        .map(|span| {
+            let mut encoded = json!({
+                "traceId": hex::encode(span.trace_id),
+                "spanId": hex::encode(span.span_id),
+                "name": span.name,
+                "kind": 1,
+                "startTimeUnixNano": unix_nanos(span.start),
+                "endTimeUnixNano": unix_nanos(span.end),
+                "attributes": span.attributes.iter().map(|(k, v)| attribute(k, v)).collect::<Vec<_>>(),
+                "status": match &span.error {
+                    Some(message) => json!({ "code": 2, "message": message }),
+                    None => json!({ "code": 1 }),
+                },
+            });
+            if let Some(parent) = span.parent_span_id {
+                encoded["parentSpanId"] = json!(hex::encode(parent));
+            }
+            encoded
+        })
+        .collect();
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [
+                    attribute("service.name", &AttributeValue::String("jarvis".into())),
+                    attribute(
+                        "service.version",
+                        &AttributeValue::String(env!("CARGO_PKG_VERSION").into()),
+                    ),
+                ],
+            },
+            "scopeSpans": [{
+                "scope": { "name": "jarvis", "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+fn attribute(key: &str, value: &AttributeValue) -> Value {
+    // int64 is a string in OTLP/JSON
+    let value = match value {
+        AttributeValue::String(s) => json!({ "stringValue": s }),
+        AttributeValue::Int(i) => json!({ "intValue": i.to_string() }),
+        AttributeValue::Double(d) => json!({ "doubleValue": d }),
+        AttributeValue::Bool(b) => json!({ "boolValue": b }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+/// A span that has started and not ended yet.
+struct OpenSpan {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    parent_span_id: Option<[u8; 8]>,
+    name: String,
+    start: SystemTime,
+    attributes: Vec<(&'static str, AttributeValue)>,
+}
+
+impl OpenSpan {
+    fn finish(self, end: SystemTime, error: Option<String>) -> SpanData {
+        SpanData {
+            trace_id: self.trace_id,
+            span_id: self.span_id,
+            parent_span_id: self.parent_span_id,
+            name: self.name,
+            start: self.start,
+            end,
+            attributes: self.attributes,
+            error,
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// The agent run in progress
+    run: Option<OpenSpan>,
+    /// Its children that have ended, sent with it
+    children: Vec<SpanData>,
+    /// Tool calls that have started, by tool name, in order
+    tools: Vec<(String, OpenSpan)>,
+}
+
+impl State {
+    /// A span starting at `start`: a child of the run, or a new trace.
+    fn open(
+        &self,
+        name: String,
+        start: SystemTime,
+        attributes: Vec<(&'static str, AttributeValue)>,
+    ) -> OpenSpan {
+        let (trace_id, parent_span_id) = match &self.run {
+            Some(run) => (run.trace_id, Some(run.span_id)),
+            None => (*uuid::Uuid::new_v4().as_bytes(), None),
+        };
+        OpenSpan {
+            trace_id,
+            span_id: span_id(),
+            parent_span_id,
+            name,
+            start,
+            attributes,
+        }
+    }
+
+    /// End the run, if any, and return it with its children to send. Tool
+    /// calls still open were cut short by the end of the run.
+    fn end_run(&mut self, now: SystemTime, end: SystemTime, error: Option<&str>) -> Vec<SpanData> {
+        let Some(run) = self.run.take() else {
+            self.tools.clear();
+            return Vec::new();
+        };
+        let mut send: Vec<SpanData> = std::mem::take(&mut self.tools)
+            .into_iter()
+            .map(|(_, span)| span.finish(now, Some("unfinished".into())))
+            .collect();
+        send.append(&mut self.children);
+        send.push(run.finish(end, error.map(str::to_string)));
+        send
+    }
+
+    /// Keep `span` for the run, or return it to send when there is none.
+    fn ended(&mut self, span: SpanData) -> Option<SpanData> {
+        if self.run.is_some() {
+            self.children.push(span);
+            None
+        } else {
+            Some(span)
+        }
+    }
+}
+
+fn span_id() -> [u8; 8] {
+    let mut id = [0; 8];
+    id.copy_from_slice(&uuid::Uuid::new_v4().as_bytes()[..8]);
+    id
+}
+
+/// Turns observer events into spans (see the module docs).
+pub struct OtelObserver {
+    exporter: Arc<dyn SpanExporter>,
+    state: Mutex<State>,
+}
+
+impl OtelObserver {
+    pub fn new(config: &ObservabilityConfig) -> Self {
+        let endpoint = config
+            .otel_endpoint
+            .as_deref()
+            .filter(|e| !e.trim().is_empty())
+            .unwrap_or(DEFAULT_ENDPOINT);
+        Self::with_exporter(Arc::new(OtlpExporter::new(
+            endpoint,
+            config.otel_headers.clone(),
+        )))
+    }
+
+    pub fn with_exporter(exporter: Arc<dyn SpanExporter>) -> Self {
+        Self {
+            exporter,
+            state: Mutex::default(),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn string(value: &str) -> AttributeValue {
+    AttributeValue::String(value.to_string())
+}
+
+impl Observer for OtelObserver {
+    fn record_event(&self, event: &ObserverEvent) {
+        let now = SystemTime::now();
+        let ago = |duration: &Duration| now.checked_sub(*duration).unwrap_or(now);
+        let mut state = self.state();
+        let send = match event {
+            ObserverEvent::AgentStart { provider, model } => {
+                // A run that never ended is closed first
+                let send = state.end_run(now, now, Some("agent run did not end"));
+                let run = state.open(
+                    "invoke_agent".into(),
+                    now,
+                    vec![
+                        ("gen_ai.system", string(provider)),
+                        ("gen_ai.request.model", string(model)),
+                    ],
+                );
+                state.run = Some(run);
+                send
+            }
+            ObserverEvent::AgentEnd {
+                duration,
+                tokens_used,
+                cost_usd,
+            } => {
+                if state.run.is_none() {
+                    state.run = Some(state.open("invoke_agent".into(), ago(duration), Vec::new()));
+                }
+                if let Some(run) = &mut state.run {
+                    if let Some(tokens) = tokens_used {
+                        run.attributes.push((
+                            "gen_ai.usage.total_tokens",
+                            AttributeValue::Int(i64::try_from(*tokens).unwrap_or(i64::MAX)),
+                        ));
+                    }
+                    if let Some(cost) = cost_usd {
+                        run.attributes
+                            .push(("jarvis.cost_usd", AttributeValue::Double(*cost)));
+                    }
+                }
+                state.end_run(now, now, None)
+            }
+            ObserverEvent::ToolStart { tool, .. } => {
+                let span = state.open(
+                    format!("execute_tool {tool}"),
+                    now,
+                    vec![("gen_ai.tool.name", string(tool))],
+                );
+                state.tools.push((tool.clone(), span));
+                Vec::new()
+            }
+            ObserverEvent::ToolCall {
+                tool,
+                duration,
+                success,
+                error,
+            } => {
+                let started = state.tools.iter().rposition(|(name, _)| name == tool);
+                let mut span = match started {
+                    Some(i) => state.tools.remove(i).1,
+                    None => state.open(
+                        format!("execute_tool {tool}"),
+                        ago(duration),
+                        vec![("gen_ai.tool.name", string(tool))],
+                    ),
+                };
+                span.attributes
+                    .push(("jarvis.tool.success", AttributeValue::Bool(*success)));
+                let error = (!success).then(|| error.clone().unwrap_or_else(|| "failed".into()));
+                state.ended(span.finish(now, error)).into_iter().collect()
+            }
+            ObserverEvent::ProviderCall {
+                provider,
+                model,
+                duration,
+                success,
+            } => {
+                let span = state.open(
+                    format!("chat {model}"),
+                    ago(duration),
+                    vec![
+                        ("gen_ai.system", string(provider)),
+                        ("gen_ai.request.model", string(model)),
+                    ],
+                );
+                let error = (!success).then(|| "request failed".to_string());
+                state.ended(span.finish(now, error)).into_iter().collect()
+            }
+            _ => Vec::new(),
+        };
+        drop(state);
+        if !send.is_empty() {
+            self.exporter.export(send);
+        }
+    }
+
+    fn record_metric(&self, _metric: &ObserverMetric) {}
+
+    fn flush(&self) {
+        self.exporter.flush();
+    }
+
+    fn name(&self) -> &str {
+        "otel"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_call(tool: &str, success: bool) -> ObserverEvent {
+        ObserverEvent::ToolCall {
+            tool: tool.into(),
+            duration: Duration::from_millis(5),
+            success,
+            error: (!success).then(|| "exit status 1".into()),
+        }
+    }
+
+    #[test]
+    fn an_agent_run_is_the_parent_of_its_tool_calls() {
+        let exporter = InMemoryExporter::default();
+        let obs = OtelObserver::with_exporter(Arc::new(exporter.clone()));
+
+        obs.record_event(&ObserverEvent::AgentStart {
+            provider: "openrouter".into(),
+            model: "gpt-4o".into(),
+        });
+        obs.record_event(&ObserverEvent::ProviderCall {
+            provider: "openrouter".into(),
+            model: "gpt-4o".into(),
+            duration: Duration::from_millis(300),
+            success: true,
+        });
+        obs.record_event(&ObserverEvent::ToolStart {
+            tool: "shell".into(),
+            arguments: "{}".into(),
+        });
+        obs.record_event(&tool_call("shell", false));
+        obs.record_event(&ObserverEvent::ToolStart {
+            tool: "file_read".into(),
+            arguments: "{}".into(),
+        });
+        obs.record_event(&tool_call("file_read", true));
+        assert!(exporter.spans().is_empty(), "sent before the run ended");
+        obs.record_event(&ObserverEvent::AgentEnd {
+            duration: Duration::from_secs(1),
+            tokens_used: Some(1234),
+            cost_usd: Some(0.0125),
+        });
+
+        let spans = exporter.spans();
+        let names: Vec<&str> = spans.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "chat gpt-4o",
+                "execute_tool shell",
+                "execute_tool file_read",
+                "invoke_agent"
+            ]
+        );
+        let root = &spans[3];
+        assert_eq!(root.parent_span_id, None);
+        assert_eq!(
+            root.attribute("gen_ai.usage.total_tokens"),
+            Some(&AttributeValue::Int(1234))
+        );
+        assert_eq!(
+            root.attribute("jarvis.cost_usd"),
+            Some(&AttributeValue::Double(0.0125))
+        );
+        for child in &spans[..3] {
+            assert_eq!(child.trace_id, root.trace_id);
+            assert_eq!(child.parent_span_id, Some(root.span_id));
+            assert!(child.start >= root.start - Duration::from_secs(1));
+        }
+        assert_eq!(spans[1].error.as_deref(), Some("exit status 1"));
+        assert_eq!(spans[2].error, None);
+
+        // Outside a run, a tool call is a trace of its own, sent at once
+        obs.record_event(&tool_call("shell", true));
+        let spans = exporter.spans();
+        assert_eq!(spans.len(), 5);
+        assert_eq!(spans[4].parent_span_id, None);
+        assert_ne!(spans[4].trace_id, root.trace_id);
+    }
+
+    #[test]
+    fn spans_are_encoded_as_otlp_json() {
+        let start = UNIX_EPOCH + Duration::from_secs(1);
+        let span = SpanData {
+            trace_id: [1; 16],
+            span_id: [2; 8],
+            parent_span_id: Some([3; 8]),
+            name: "execute_tool shell".into(),
+            start,
+            end: start + Duration::from_millis(5),
+            attributes: vec![
+                ("gen_ai.tool.name", string("shell")),
+                ("gen_ai.usage.total_tokens", AttributeValue::Int(7)),
+            ],
+            error: Some("exit status 1".into()),
+        };
+        let body = encode(&[span]);
+        let scope = &body["resourceSpans"][0]["scopeSpans"][0];
+        let encoded = &scope["spans"][0];
+        assert_eq!(encoded["traceId"], "01010101010101010101010101010101");
+        assert_eq!(encoded["parentSpanId"], "0303030303030303");
+        assert_eq!(encoded["startTimeUnixNano"], "1000000000");
+        assert_eq!(encoded["endTimeUnixNano"], "1005000000");
+        assert_eq!(
+            encoded["attributes"][1],
+            json!({ "key": "gen_ai.usage.total_tokens", "value": { "intValue": "7" } })
+        );
+        assert_eq!(
+            encoded["status"],
+            json!({ "code": 2, "message": "exit status 1" })
+        );
+        assert_eq!(
+            body["resourceSpans"][0]["resource"]["attributes"][0]["value"]["stringValue"],
+            "jarvis"
+        );
+    }
+
+    #[test]
+    fn otlp_exporter_posts_to_the_traces_path_and_flush_waits() {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                head.push_str(&line);
+            }
+            let length: usize = head
+                .lines()
+                .find_map(|l| {
+                    l.to_ascii_lowercase()
+                        .strip_prefix("content-length: ")
+                        .map(str::to_string)
+                })
+                .unwrap()
+                .parse()
+                .unwrap();
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .unwrap();
+            (head, String::from_utf8(body).unwrap())
+        });
+
+        let headers = BTreeMap::from([("x-api-key".to_string(), "secret".to_string())]);
+        let exporter = OtlpExporter::new(&format!("http://{addr}/"), headers);
+        let now = SystemTime::now();
+        exporter.export(vec![SpanData {
+            trace_id: [1; 16],
+            span_id: [2; 8],
+            parent_span_id: None,
+            name: "invoke_agent".into(),
+            start: now,
+            end: now,
+            attributes: Vec::new(),
+            error: None,
+        }]);
+        exporter.flush();
+
+        let (head, body) = server.join().unwrap();
+        assert!(head.starts_with("POST /v1/traces HTTP/1.1"), "{head}");
+        assert!(head.to_ascii_lowercase().contains("x-api-key: secret"));
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            body["resourceSpans"][0]["scopeSpans"][0]["spans"][0]["name"],
+            "invoke_agent"
+        );
+    }
+}
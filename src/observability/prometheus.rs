@@ -0,0 +1,461 @@
+use super::traits::{Observer, ObserverEvent, ObserverMetric};
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::Duration;
+
+/// `Content-Type` of the text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Upper bounds (seconds) of the latency histogram buckets
+const LATENCY_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+/// Metric families in exposition order: name, type, help
+const FAMILIES: &[(&str, &str, &str)] = &[
+    ("jarvis_agent_runs_total", "counter", "Agent runs started"),
+    (
+        "jarvis_agent_tokens_total",
+        "counter",
+        "Tokens reported by finished agent runs",
+    ),
+    (
+        "jarvis_tool_calls_total",
+        "counter",
+        "Tool calls by tool and outcome",
+    ),
+    (
+        "jarvis_tool_duration_seconds",
+        "histogram",
+        "Tool call latency",
+    ),
+    (
+        "jarvis_provider_requests_total",
+        "counter",
+        "Provider requests by provider and outcome",
+    ),
+    (
+        "jarvis_provider_errors_total",
+        "counter",
+        "Failed provider requests, including ones later retried",
+    ),
+    (
+        "jarvis_provider_request_duration_seconds",
+        "histogram",
+        "Provider request latency",
+    ),
+    (
+        "jarvis_provider_retries_total",
+        "counter",
+        "Provider retries by provider and reason",
+    ),
+    (
+        "jarvis_provider_fallbacks_total",
+        "counter",
+        "Requests served by a fallback provider",
+    ),
+    (
+        "jarvis_channel_messages_total",
+        "counter",
+        "Channel messages by channel and direction",
+    ),
+    (
+        "jarvis_heartbeat_tasks_total",
+        "counter",
+        "Heartbeat tasks by outcome",
+    ),
+    ("jarvis_errors_total", "counter", "Errors by component"),
+    (
+        "jarvis_request_latency_seconds",
+        "histogram",
+        "Request latency reported as a metric",
+    ),
+    ("jarvis_active_sessions", "gauge", "Active sessions"),
+    ("jarvis_queue_depth", "gauge", "Queued messages"),
+];
+
+type Series = (&'static str, Vec<(&'static str, String)>);
+
+struct Histogram {
+    buckets: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            buckets: vec![0; LATENCY_BUCKETS.len()],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn observe(&mut self, seconds: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+/// Counters, gauges and histograms kept in memory until scraped
+#[derive(Default)]
+pub struct Registry {
+    values: Mutex<BTreeMap<Series, u64>>,
+    histograms: Mutex<BTreeMap<Series, Histogram>>,
+}
+
+impl Registry {
+    fn add(&self, name: &'static str, labels: Vec<(&'static str, String)>, by: u64) {
+        let mut values = self.values.lock().unwrap_or_else(PoisonError::into_inner);
+        *values.entry((name, labels)).or_default() += by;
+    }
+
+    fn set(&self, name: &'static str, value: u64) {
+        let mut values = self.values.lock().unwrap_or_else(PoisonError::into_inner);
+        values.insert((name, Vec::new()), value);
+    }
+
+    fn observe(&self, name: &'static str, labels: Vec<(&'static str, String)>, d: Duration) {
+        let mut histograms = self
+            .histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        histograms
+            .entry((name, labels))
+            .or_insert_with(Histogram::new)
+            .observe(d.as_secs_f64());
+    }
+
+    /// Everything recorded so far in the Prometheus text format
+    pub fn render(&self) -> String {
+        let values = self.values.lock().unwrap_or_else(PoisonError::into_inner);
+        let histograms = self
+            .histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut out = String::new();
+        for (family, kind, help) in FAMILIES {
+            let _ = writeln!(out, "# HELP {family} {help}");
+            let _ = writeln!(out, "# TYPE {family} {kind}");
+            for ((name, labels), value) in values.iter() {
+                if name == family {
+                    let _ = writeln!(out, "{name}{} {value}", format_labels(labels, None));
+                }
+            }
+            for ((name, labels), hist) in histograms.iter() {
+                if name != family {
+                    continue;
+                }
+                for (bound, count) in LATENCY_BUCKETS.iter().zip(&hist.buckets) {
+                    let le = bound.to_string();
+                    let _ = writeln!(
+                        out,
+                        "{name}_bucket{} {count}",
+                        format_labels(labels, Some(&le))
+                    );
+                }
+                let _ = writeln!(
+                    out,
+                    "{name}_bucket{} {}",
+                    format_labels(labels, Some("+Inf")),
+                    hist.count
+                );
+                let labels = format_labels(labels, None);
+                let _ = writeln!(out, "{name}_sum{labels} {}", hist.sum);
+                let _ = writeln!(out, "{name}_count{labels} {}", hist.count);
+            }
+        }
+        out
+    }
+}
+
+fn format_labels(labels: &[(&'static str, String)], le: Option<&str>) -> String {
+    let mut parts: Vec<String> = labels
+        .iter()
+        .map(|(key, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{key}=\"{value}\"")
+        })
+        .collect();
+    if let Some(le) = le {
+        parts.push(format!("le=\"{le}\""));
+    }
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", parts.join(","))
+    }
+}
+
+/// The process-wide registry shared by every `PrometheusObserver::new()`,
+/// so agent, channel and heartbeat metrics all reach the same scrape.
+pub fn global() -> Arc<Registry> {
+    static REGISTRY: OnceLock<Arc<Registry>> = OnceLock::new();
+    Arc::clone(REGISTRY.get_or_init(Arc::default))
+}
+
+/// Prometheus observer — aggregates events into counters and histograms
+/// served at the gateway's `/metrics` (or `[observability] prometheus_listen`)
+pub struct PrometheusObserver {
+    registry: Arc<Registry>,
+}
+
+impl PrometheusObserver {
+    pub fn new() -> Self {
+        Self::with_registry(global())
+    }
+
+    pub fn with_registry(registry: Arc<Registry>) -> Self {
+        Self { registry }
+    }
+}
+
+fn outcome(success: bool) -> String {
+    if success { "true" } else { "false" }.to_string()
+}
+
+impl Observer for PrometheusObserver {
+    fn record_event(&self, event: &ObserverEvent) {
+        let registry = &self.registry;
+        match event {
+            ObserverEvent::AgentStart { .. } => registry.add("jarvis_agent_runs_total", vec![], 1),
+            ObserverEvent::AgentEnd { tokens_used, .. } => {
+                if let Some(tokens) = tokens_used {
+                    registry.add("jarvis_agent_tokens_total", vec![], *tokens);
+                }
+            }
+            ObserverEvent::ToolCall {
+                tool,
+                duration,
+                success,
+                ..
+            } => {
+                registry.add(
+                    "jarvis_tool_calls_total",
+                    vec![("tool", tool.clone()), ("success", outcome(*success))],
+                    1,
+                );
+                registry.observe(
+                    "jarvis_tool_duration_seconds",
+                    vec![("tool", tool.clone())],
+                    *duration,
+                );
+            }
+            ObserverEvent::ProviderCall {
+                provider,
+                duration,
+                success,
+                ..
+            } => {
+                registry.add(
+                    "jarvis_provider_requests_total",
+                    vec![
+                        ("provider", provider.clone()),
+                        ("success", outcome(*success)),
+                    ],
+                    1,
+                );
+                if !success {
+                    registry.add(
+                        "jarvis_provider_errors_total",
+                        vec![("provider", provider.clone())],
+                        1,
+                    );
+                }
+                registry.observe(
+                    "jarvis_provider_request_duration_seconds",
+                    vec![("provider", provider.clone())],
+                    *duration,
+                );
+            }
+            ObserverEvent::ProviderRetry {
+                provider, reason, ..
+            } => registry.add(
+                "jarvis_provider_retries_total",
+                vec![("provider", provider.clone()), ("reason", reason.clone())],
+                1,
+            ),
+            ObserverEvent::ProviderFallback {
+                primary, served_by, ..
+            } => registry.add(
+                "jarvis_provider_fallbacks_total",
+                vec![
+                    ("primary", primary.clone()),
+                    ("served_by", served_by.clone()),
+                ],
+                1,
+            ),
+            ObserverEvent::ChannelMessage { channel, direction } => registry.add(
+                "jarvis_channel_messages_total",
+                vec![
+                    ("channel", channel.clone()),
+                    ("direction", direction.clone()),
+                ],
+                1,
+            ),
+            ObserverEvent::HeartbeatTask { success, .. } => registry.add(
+                "jarvis_heartbeat_tasks_total",
+                vec![("success", outcome(*success))],
+                1,
+            ),
+            ObserverEvent::Error { component, .. } => registry.add(
+                "jarvis_errors_total",
+                vec![("component", component.clone())],
+                1,
+            ),
+            ObserverEvent::ToolStart { .. }
+            | ObserverEvent::HeartbeatTick
+            | ObserverEvent::MemoryHygiene { .. } => {}
+        }
+    }
+
+    fn record_metric(&self, metric: &ObserverMetric) {
+        match metric {
+            ObserverMetric::RequestLatency(d) => {
+                self.registry
+                    .observe("jarvis_request_latency_seconds", vec![], *d);
+            }
+            ObserverMetric::TokensUsed(t) => {
+                self.registry.add("jarvis_agent_tokens_total", vec![], *t);
+            }
+            ObserverMetric::ActiveSessions(s) => self.registry.set("jarvis_active_sessions", *s),
+            ObserverMetric::QueueDepth(d) => self.registry.set("jarvis_queue_depth", *d),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "prometheus"
+    }
+}
+
+/// Serve the global registry at `http://<listen>/metrics` from a listener of
+/// its own. Fails (without spawning anything) when `listen` can't be bound.
+pub async fn serve(listen: &str) -> Result<tokio::task::JoinHandle<()>> {
+    let listener = tokio::net::TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("无法监听 {listen}"))?;
+    let app = axum::Router::new().route(
+        "/metrics",
+        axum::routing::get(|| async {
+            (
+                [(axum::http::header::CONTENT_TYPE, CONTENT_TYPE)],
+                global().render(),
+            )
+        }),
+    );
+    Ok(tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            tracing::error!("Prometheus 指标端点已停止：{e}");
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observer() -> (PrometheusObserver, Arc<Registry>) {
+        let registry = Arc::new(Registry::default());
+        (
+            PrometheusObserver::with_registry(Arc::clone(&registry)),
+            registry,
+        )
+    }
+
+    #[test]
+    fn prometheus_observer_name() {
+        assert_eq!(PrometheusObserver::new().name(), "prometheus");
+    }
+
+    #[test]
+    fn counts_tool_calls_by_name_and_outcome() {
+        let (obs, registry) = observer();
+        for success in [true, true, false] {
+            obs.record_event(&ObserverEvent::ToolCall {
+                tool: "shell".into(),
+                duration: Duration::from_millis(200),
+                success,
+                error: None,
+            });
+        }
+        let text = registry.render();
+        assert!(text.contains("# TYPE jarvis_tool_calls_total counter"));
+        assert!(text.contains(r#"jarvis_tool_calls_total{tool="shell",success="true"} 2"#));
+        assert!(text.contains(r#"jarvis_tool_calls_total{tool="shell",success="false"} 1"#));
+        assert!(text.contains(r#"jarvis_tool_duration_seconds_bucket{tool="shell",le="0.1"} 0"#));
+        assert!(text.contains(r#"jarvis_tool_duration_seconds_bucket{tool="shell",le="0.25"} 3"#));
+        assert!(text.contains(r#"jarvis_tool_duration_seconds_bucket{tool="shell",le="+Inf"} 3"#));
+        assert!(text.contains(r#"jarvis_tool_duration_seconds_count{tool="shell"} 3"#));
+    }
+
+    #[test]
+    fn counts_agent_runs_provider_errors_and_channel_messages() {
+        let (obs, registry) = observer();
+        obs.record_event(&ObserverEvent::AgentStart {
+            provider: "openrouter".into(),
+            model: "m".into(),
+        });
+        obs.record_event(&ObserverEvent::AgentEnd {
+            duration: Duration::from_secs(1),
+            tokens_used: Some(42),
+            cost_usd: None,
+        });
+        for success in [false, true] {
+            obs.record_event(&ObserverEvent::ProviderCall {
+                provider: "openrouter".into(),
+                model: "m".into(),
+                duration: Duration::from_secs(2),
+                success,
+            });
+        }
+        for direction in ["inbound", "outbound"] {
+            obs.record_event(&ObserverEvent::ChannelMessage {
+                channel: "telegram".into(),
+                direction: direction.into(),
+            });
+        }
+
+        let text = registry.render();
+        assert!(text.contains("jarvis_agent_runs_total 1"));
+        assert!(text.contains("jarvis_agent_tokens_total 42"));
+        assert!(text.contains(r#"jarvis_provider_errors_total{provider="openrouter"} 1"#));
+        assert!(text
+            .contains(r#"jarvis_provider_requests_total{provider="openrouter",success="true"} 1"#));
+        assert!(text
+            .contains(r#"jarvis_provider_request_duration_seconds_sum{provider="openrouter"} 4"#));
+        assert!(text.contains(
+            r#"jarvis_channel_messages_total{channel="telegram",direction="inbound"} 1"#
+        ));
+        assert!(text.contains(
+            r#"jarvis_channel_messages_total{channel="telegram",direction="outbound"} 1"#
+        ));
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        let (obs, registry) = observer();
+        obs.record_event(&ObserverEvent::Error {
+            component: "a\"b\\c\nd".into(),
+            message: "boom".into(),
+        });
+        assert!(registry
+            .render()
+            .contains(r#"jarvis_errors_total{component="a\"b\\c\nd"} 1"#));
+    }
+
+    #[tokio::test]
+    async fn serve_fails_when_the_address_is_taken() {
+        let taken = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = taken.local_addr().unwrap().to_string();
+        assert!(serve(&addr).await.is_err());
+        assert!(serve("not an address").await.is_err());
+    }
+}
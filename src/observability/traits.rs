@@ -10,17 +10,60 @@ pub enum ObserverEvent {
     AgentEnd {
         duration: Duration,
         tokens_used: Option<u64>,
+        /// Estimated cost in USD, when every model used has a known price
+        cost_usd: Option<f64>,
+    },
+    /// A tool is about to run (`arguments` is the raw JSON from the model)
+    ToolStart {
+        tool: String,
+        arguments: String,
     },
     ToolCall {
         tool: String,
         duration: Duration,
         success: bool,
+        /// Error text when the call failed
+        error: Option<String>,
     },
     ChannelMessage {
         channel: String,
         direction: String,
     },
     HeartbeatTick,
+    /// A heartbeat task finished, failed, or was cancelled at its timeout
+    HeartbeatTask {
+        task: String,
+        duration: Duration,
+        success: bool,
+        timed_out: bool,
+    },
+    /// One provider request attempt, including ones that are retried
+    ProviderCall {
+        provider: String,
+        model: String,
+        duration: Duration,
+        success: bool,
+    },
+    /// A provider call failed and is retried after `backoff`
+    ProviderRetry {
+        provider: String,
+        /// 1-based number of this retry
+        attempt: u32,
+        max_retries: u32,
+        /// HTTP status (`429`, `503`, …), `timeout` or `error`
+        reason: String,
+        backoff: Duration,
+    },
+    /// A fallback provider served the request after the primary failed
+    ProviderFallback {
+        primary: String,
+        served_by: String,
+        model: String,
+    },
+    MemoryHygiene {
+        archived: u64,
+        purged: u64,
+    },
     Error {
         component: String,
         message: String,
@@ -1,3 +1,3 @@
 pub mod wizard;
 
-pub use wizard::{run_channels_repair_wizard, run_quick_setup, run_wizard};
+pub use wizard::{run_channels_repair_wizard, run_quick_setup, run_wizard, QuickChannels};
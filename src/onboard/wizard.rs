@@ -1,12 +1,18 @@
+use crate::channels::probe::{
+    check_discord, check_matrix, check_slack, check_telegram, check_whatsapp,
+};
 use crate::config::schema::{IrcConfig, WhatsAppConfig};
 use crate::config::{
     AutonomyConfig, BrowserConfig, ChannelsConfig, ComposioConfig, Config, DiscordConfig,
-    HeartbeatConfig, IMessageConfig, MatrixConfig, MemoryConfig, ObservabilityConfig,
-    RuntimeConfig, SecretsConfig, SlackConfig, TelegramConfig, WebhookConfig,
+    HeartbeatConfig, IMessageConfig, LoggingConfig, MatrixConfig, MemoryConfig,
+    ObservabilityConfig, RuntimeConfig, SecretsConfig, SlackConfig, TelegramConfig, WebhookConfig,
 };
+use crate::memory::embeddings::HASHED_MODEL;
+use crate::providers::models;
 use anyhow::{Context, Result};
 use console::style;
-use dialoguer::{Confirm, Input, Select};
+use dialoguer::{Confirm, FuzzySelect, Input, Select};
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -81,15 +87,16 @@ pub fn run_wizard() -> Result<Config> {
     let config = Config {
         workspace_dir: workspace_dir.clone(),
         config_path: config_path.clone(),
-        api_key: if api_key.is_empty() {
-            None
-        } else {
-            Some(api_key)
-        },
+        env: crate::config::EnvProvenance::default(),
+        unknown_keys: Vec::new(),
+        config_version: crate::config::CONFIG_VERSION,
+        api_key: (!api_key.is_empty()).then_some(api_key),
         default_provider: Some(provider),
         default_model: Some(model),
         default_temperature: 0.7,
+        strict_config: false,
         observability: ObservabilityConfig::default(),
+        logging: LoggingConfig::default(),
         autonomy: AutonomyConfig::default(),
         runtime: RuntimeConfig::default(),
         reliability: crate::config::ReliabilityConfig::default(),
@@ -103,6 +110,10 @@ pub fn run_wizard() -> Result<Config> {
         browser: BrowserConfig::default(),
         identity: crate::config::IdentityConfig::default(),
         brave_search: crate::config::BraveSearchConfig::default(),
+        git: crate::config::GitConfig::default(),
+        web_fetch: crate::config::WebFetchConfig::default(),
+        tools: crate::config::ToolsConfig::default(),
+        notify: None,
     };
 
     println!(
@@ -152,7 +163,7 @@ pub fn run_wizard() -> Result<Config> {
             println!();
             // Signal to main.rs to call start_channels after wizard returns
             // SAFETY: 单线程上下文，wizard 在 daemon 启动前执行
-unsafe { std::env::set_var("JARVIS_AUTOSTART_CHANNELS", "1") };
+            unsafe { std::env::set_var("JARVIS_AUTOSTART_CHANNELS", "1") };
         }
     }
 
@@ -206,7 +217,7 @@ pub fn run_channels_repair_wizard() -> Result<Config> {
             println!();
             // Signal to main.rs to call start_channels after wizard returns
             // SAFETY: 单线程上下文，wizard 在 daemon 启动前执行
-unsafe { std::env::set_var("JARVIS_AUTOSTART_CHANNELS", "1") };
+            unsafe { std::env::set_var("JARVIS_AUTOSTART_CHANNELS", "1") };
         }
     }
 
@@ -217,12 +228,15 @@ unsafe { std::env::set_var("JARVIS_AUTOSTART_CHANNELS", "1") };
 
 /// Non-interactive setup: generates a sensible default config instantly.
 /// Use `jarvis onboard` or `jarvis onboard --api-key sk-... --provider openrouter --memory sqlite`.
+/// Channels can be added with `--telegram-token`, `--discord-token`, `--slack-token`,
+/// `--webhook-port` or `--from-file channels.json`; each is connection-tested first.
 /// Use `jarvis onboard --interactive` for the full wizard.
 #[allow(clippy::too_many_lines)]
 pub fn run_quick_setup(
     api_key: Option<&str>,
     provider: Option<&str>,
     memory_backend: Option<&str>,
+    channels: &QuickChannels,
 ) -> Result<Config> {
     println!("{}", style(BANNER).cyan().bold());
     println!(
@@ -233,13 +247,14 @@ pub fn run_quick_setup(
     );
     println!();
 
-    let home = directories::UserDirs::new()
-        .map(|u| u.home_dir().to_path_buf())
-        .context("无法找到用户主目录")?;
-    let jarvis_dir = home.join(".jarvis");
+    let jarvis_dir = crate::config::profile::config_dir()?;
     let workspace_dir = jarvis_dir.join("workspace");
     let config_path = jarvis_dir.join("config.toml");
 
+    // Validate channels before anything is written, so a bad token leaves no half-done setup
+    let channels_config = channels.to_config()?;
+    verify_channels(&channels_config)?;
+
     fs::create_dir_all(&workspace_dir).context("创建工作区目录失败")?;
 
     let provider_name = provider.unwrap_or("openrouter").to_string();
@@ -262,6 +277,8 @@ pub fn run_quick_setup(
             0
         },
         conversation_retention_days: 30,
+        task_retention_days: 30,
+        transcript_retention_days: 30,
         embedding_provider: "none".to_string(),
         embedding_model: "text-embedding-3-small".to_string(),
         embedding_dimensions: 1536,
@@ -273,21 +290,27 @@ pub fn run_quick_setup(
             0
         },
         chunk_max_tokens: 512,
+        postgres_url: None,
     };
 
     let config = Config {
         workspace_dir: workspace_dir.clone(),
         config_path: config_path.clone(),
+        env: crate::config::EnvProvenance::default(),
+        unknown_keys: Vec::new(),
+        config_version: crate::config::CONFIG_VERSION,
         api_key: api_key.map(String::from),
         default_provider: Some(provider_name.clone()),
         default_model: Some(model.clone()),
         default_temperature: 0.7,
+        strict_config: false,
         observability: ObservabilityConfig::default(),
+        logging: LoggingConfig::default(),
         autonomy: AutonomyConfig::default(),
         runtime: RuntimeConfig::default(),
         reliability: crate::config::ReliabilityConfig::default(),
         heartbeat: HeartbeatConfig::default(),
-        channels_config: ChannelsConfig::default(),
+        channels_config,
         memory: memory_config,
         tunnel: crate::config::TunnelConfig::default(),
         gateway: crate::config::GatewayConfig::default(),
@@ -296,6 +319,10 @@ pub fn run_quick_setup(
         browser: BrowserConfig::default(),
         identity: crate::config::IdentityConfig::default(),
         brave_search: crate::config::BraveSearchConfig::default(),
+        git: crate::config::GitConfig::default(),
+        web_fetch: crate::config::WebFetchConfig::default(),
+        tools: crate::config::ToolsConfig::default(),
+        notify: None,
     };
 
     config.save()?;
@@ -350,6 +377,11 @@ pub fn run_quick_setup(
             "开"
         }
     );
+    println!(
+        "  {} 通道：      {}",
+        style("✓").green().bold(),
+        style(active_channels(&config.channels_config).join(", ")).green()
+    );
     println!(
         "  {} 密钥存储：  {}",
         style("✓").green().bold(),
@@ -380,7 +412,7 @@ pub fn run_quick_setup(
     println!("  {}", style("后续步骤：").white().bold());
     if api_key.is_none() {
         println!("    1. 设置 API 密钥：export OPENROUTER_API_KEY=\"sk-...\"");
-        println!("    2. 或编辑：       ~/.jarvis/config.toml");
+        println!("    2. 或编辑：       {}", config_path.display());
         println!("    3. 对话：         jarvis agent -m \"你好！\"");
         println!("    4. Gateway：      jarvis gateway");
     } else {
@@ -406,6 +438,287 @@ fn default_model_for_provider(provider: &str) -> String {
     }
 }
 
+// ── Quick setup channels ─────────────────────────────────────────
+
+/// Channel flags accepted by quick setup. Channels without dedicated flags
+/// can be supplied as a typed `ChannelsConfig` JSON file via `--from-file`;
+/// flags given on the command line override values from the file.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct QuickChannels {
+    /// 从 JSON 文件加载通道配置（ChannelsConfig 结构，命令行参数优先）
+    #[arg(long, value_name = "FILE")]
+    pub from_file: Option<PathBuf>,
+
+    /// Telegram 机器人 Token
+    #[arg(long)]
+    pub telegram_token: Option<String>,
+
+    /// 允许的 Telegram 用户名/用户 ID（逗号分隔，'*' 表示所有）
+    #[arg(long, value_delimiter = ',')]
+    pub telegram_allowed: Vec<String>,
+
+    /// Discord 机器人 Token
+    #[arg(long)]
+    pub discord_token: Option<String>,
+
+    /// Discord 服务器（Guild）ID
+    #[arg(long)]
+    pub discord_guild: Option<String>,
+
+    /// 允许的 Discord 用户 ID（逗号分隔，'*' 表示所有）
+    #[arg(long, value_delimiter = ',')]
+    pub discord_allowed: Vec<String>,
+
+    /// Slack Bot Token（xoxb-...）
+    #[arg(long)]
+    pub slack_token: Option<String>,
+
+    /// Slack App Token（xapp-...）
+    #[arg(long)]
+    pub slack_app_token: Option<String>,
+
+    /// Slack 默认频道 ID
+    #[arg(long)]
+    pub slack_channel: Option<String>,
+
+    /// 允许的 Slack 成员 ID（逗号分隔，'*' 表示所有）
+    #[arg(long, value_delimiter = ',')]
+    pub slack_allowed: Vec<String>,
+
+    /// Webhook 通道监听端口
+    #[arg(long)]
+    pub webhook_port: Option<u16>,
+
+    /// Webhook 共享密钥
+    #[arg(long)]
+    pub webhook_secret: Option<String>,
+}
+
+impl QuickChannels {
+    /// True when no channel flag was given.
+    pub fn is_empty(&self) -> bool {
+        self.from_file.is_none()
+            && self.telegram_token.is_none()
+            && self.telegram_allowed.is_empty()
+            && self.discord_token.is_none()
+            && self.discord_guild.is_none()
+            && self.discord_allowed.is_empty()
+            && self.slack_token.is_none()
+            && self.slack_app_token.is_none()
+            && self.slack_channel.is_none()
+            && self.slack_allowed.is_empty()
+            && self.webhook_port.is_none()
+            && self.webhook_secret.is_none()
+    }
+
+    /// Build the channels config from `--from-file` (if any) and the flags,
+    /// without touching the network.
+    pub fn to_config(&self) -> Result<ChannelsConfig> {
+        let mut config = match &self.from_file {
+            Some(path) => load_channels_file(path)?,
+            None => ChannelsConfig::default(),
+        };
+
+        if let Some(token) = &self.telegram_token {
+            config
+                .telegram
+                .get_or_insert_with(|| TelegramConfig {
+                    bot_token: String::new(),
+                    allowed_users: Vec::new(),
+                })
+                .bot_token = token.trim().to_string();
+        }
+        if !self.telegram_allowed.is_empty() {
+            config
+                .telegram
+                .as_mut()
+                .context(
+                    "--telegram-allowed 需要 --telegram-token（或在 --from-file 中配置 telegram）",
+                )?
+                .allowed_users = clean_list(&self.telegram_allowed);
+        }
+
+        if let Some(token) = &self.discord_token {
+            config
+                .discord
+                .get_or_insert_with(|| DiscordConfig {
+                    bot_token: String::new(),
+                    guild_id: None,
+                    allowed_users: Vec::new(),
+                    reply_in_thread: false,
+                    respond_to_mentions_only: false,
+                })
+                .bot_token = token.trim().to_string();
+        }
+        if self.discord_guild.is_some() || !self.discord_allowed.is_empty() {
+            let discord = config.discord.as_mut().context(
+                "--discord-guild/--discord-allowed 需要 --discord-token（或在 --from-file 中配置 discord）",
+            )?;
+            if let Some(guild) = &self.discord_guild {
+                discord.guild_id = Some(guild.trim().to_string());
+            }
+            if !self.discord_allowed.is_empty() {
+                discord.allowed_users = clean_list(&self.discord_allowed);
+            }
+        }
+
+        if let Some(token) = &self.slack_token {
+            config
+                .slack
+                .get_or_insert_with(|| SlackConfig {
+                    bot_token: String::new(),
+                    app_token: None,
+                    channel_id: None,
+                    allowed_users: Vec::new(),
+                })
+                .bot_token = token.trim().to_string();
+        }
+        if self.slack_app_token.is_some()
+            || self.slack_channel.is_some()
+            || !self.slack_allowed.is_empty()
+        {
+            let slack = config.slack.as_mut().context(
+                "--slack-app-token/--slack-channel/--slack-allowed 需要 --slack-token（或在 --from-file 中配置 slack）",
+            )?;
+            if let Some(app_token) = &self.slack_app_token {
+                slack.app_token = Some(app_token.trim().to_string());
+            }
+            if let Some(channel) = &self.slack_channel {
+                slack.channel_id = Some(channel.trim().to_string());
+            }
+            if !self.slack_allowed.is_empty() {
+                slack.allowed_users = clean_list(&self.slack_allowed);
+            }
+        }
+
+        if let Some(port) = self.webhook_port {
+            config
+                .webhook
+                .get_or_insert(WebhookConfig { port, secret: None })
+                .port = port;
+        }
+        if let Some(secret) = &self.webhook_secret {
+            config
+                .webhook
+                .as_mut()
+                .context("--webhook-secret 需要 --webhook-port（或在 --from-file 中配置 webhook）")?
+                .secret = Some(secret.clone());
+        }
+
+        Ok(config)
+    }
+}
+
+/// Read a `ChannelsConfig` JSON file. `cli` may be omitted and defaults to on.
+fn load_channels_file(path: &Path) -> Result<ChannelsConfig> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("读取通道配置文件失败：{}", path.display()))?;
+    let mut value: serde_json::Value = serde_json::from_str(&raw)
+        .with_context(|| format!("通道配置文件不是有效的 JSON：{}", path.display()))?;
+    let object = value
+        .as_object_mut()
+        .with_context(|| format!("通道配置文件必须是 JSON 对象：{}", path.display()))?;
+    object.entry("cli").or_insert(serde_json::Value::Bool(true));
+    serde_json::from_value(value)
+        .with_context(|| format!("通道配置文件格式不正确：{}", path.display()))
+}
+
+fn clean_list(items: &[String]) -> Vec<String> {
+    items
+        .iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Run the wizard's connection tests against every configured channel,
+/// failing on the first one that does not check out.
+fn verify_channels(config: &ChannelsConfig) -> Result<()> {
+    if let Some(telegram) = &config.telegram {
+        let bot_name = check_telegram(&telegram.bot_token)
+            .context("Telegram 连接测试失败 — 请检查 --telegram-token")?;
+        println!(
+            "  {} Telegram 已连接为 @{bot_name}",
+            style("✓").green().bold()
+        );
+        warn_empty_allowlist("Telegram", "--telegram-allowed", &telegram.allowed_users);
+    }
+    if let Some(discord) = &config.discord {
+        let bot_name = check_discord(&discord.bot_token)
+            .context("Discord 连接测试失败 — 请检查 --discord-token")?;
+        println!(
+            "  {} Discord 已连接为 {bot_name}",
+            style("✓").green().bold()
+        );
+        warn_empty_allowlist("Discord", "--discord-allowed", &discord.allowed_users);
+    }
+    if let Some(slack) = &config.slack {
+        let team =
+            check_slack(&slack.bot_token).context("Slack 连接测试失败 — 请检查 --slack-token")?;
+        println!(
+            "  {} Slack 已连接到工作区：{team}",
+            style("✓").green().bold()
+        );
+        warn_empty_allowlist("Slack", "--slack-allowed", &slack.allowed_users);
+    }
+    if let Some(matrix) = &config.matrix {
+        let user_id = check_matrix(&matrix.homeserver, &matrix.access_token)
+            .context("Matrix 连接测试失败 — 请检查 homeserver 和 access_token")?;
+        println!("  {} Matrix 已连接为 {user_id}", style("✓").green().bold());
+    }
+    if let Some(whatsapp) = &config.whatsapp {
+        check_whatsapp(&whatsapp.access_token, &whatsapp.phone_number_id)
+            .context("WhatsApp 连接测试失败 — 请检查 access_token 和 phone_number_id")?;
+        println!("  {} WhatsApp 已连接", style("✓").green().bold());
+    }
+    if config.imessage.is_some() && !cfg!(target_os = "macos") {
+        anyhow::bail!("iMessage 仅在 macOS 上可用");
+    }
+    if config.webhook.as_ref().is_some_and(|w| w.port == 0) {
+        anyhow::bail!("无效的 Webhook 端口：0");
+    }
+    Ok(())
+}
+
+fn warn_empty_allowlist(channel: &str, flag: &str, allowed: &[String]) {
+    if allowed.is_empty() {
+        println!(
+            "  {} 白名单为空 — {channel} 入站消息将被拒绝，直到你通过 {flag} 添加 ID 或 '*'。",
+            style("⚠").yellow().bold()
+        );
+    }
+}
+
+/// Names of the enabled channels, CLI first.
+fn active_channels(config: &ChannelsConfig) -> Vec<&'static str> {
+    let mut active = vec!["CLI"];
+    if config.telegram.is_some() {
+        active.push("Telegram");
+    }
+    if config.discord.is_some() {
+        active.push("Discord");
+    }
+    if config.slack.is_some() {
+        active.push("Slack");
+    }
+    if config.imessage.is_some() {
+        active.push("iMessage");
+    }
+    if config.matrix.is_some() {
+        active.push("Matrix");
+    }
+    if config.whatsapp.is_some() {
+        active.push("WhatsApp");
+    }
+    if config.irc.is_some() {
+        active.push("IRC");
+    }
+    if config.webhook.is_some() {
+        active.push("Webhook");
+    }
+    active
+}
+
 // ── Step helpers ─────────────────────────────────────────────────
 
 fn print_step(current: u8, total: u8, title: &str) {
@@ -425,10 +738,7 @@ fn print_bullet(text: &str) {
 // ── Step 1: Workspace ────────────────────────────────────────────
 
 fn setup_workspace() -> Result<(PathBuf, PathBuf)> {
-    let home = directories::UserDirs::new()
-        .map(|u| u.home_dir().to_path_buf())
-        .context("无法找到用户主目录")?;
-    let default_dir = home.join(".jarvis");
+    let default_dir = crate::config::profile::config_dir()?;
 
     print_bullet(&format!(
         "默认位置：{}",
@@ -680,138 +990,7 @@ fn setup_provider() -> Result<(String, String, String)> {
     };
 
     // ── Model selection ──
-    let models: Vec<(&str, &str)> = match provider_name {
-        "openrouter" => vec![
-            (
-                "anthropic/claude-sonnet-4-20250514",
-                "Claude Sonnet 4 (balanced, recommended)",
-            ),
-            (
-                "anthropic/claude-3.5-sonnet",
-                "Claude 3.5 Sonnet (fast, affordable)",
-            ),
-            ("openai/gpt-4o", "GPT-4o (OpenAI flagship)"),
-            ("openai/gpt-4o-mini", "GPT-4o Mini (fast, cheap)"),
-            (
-                "google/gemini-2.0-flash-001",
-                "Gemini 2.0 Flash (Google, fast)",
-            ),
-            (
-                "meta-llama/llama-3.3-70b-instruct",
-                "Llama 3.3 70B (open source)",
-            ),
-            ("deepseek/deepseek-chat", "DeepSeek Chat (affordable)"),
-        ],
-        "anthropic" => vec![
-            (
-                "claude-sonnet-4-20250514",
-                "Claude Sonnet 4 (balanced, recommended)",
-            ),
-            ("claude-3-5-sonnet-20241022", "Claude 3.5 Sonnet (fast)"),
-            (
-                "claude-3-5-haiku-20241022",
-                "Claude 3.5 Haiku (fastest, cheapest)",
-            ),
-        ],
-        "openai" => vec![
-            ("gpt-4o", "GPT-4o (flagship)"),
-            ("gpt-4o-mini", "GPT-4o Mini (fast, cheap)"),
-            ("o1-mini", "o1-mini (reasoning)"),
-        ],
-        "venice" => vec![
-            ("llama-3.3-70b", "Llama 3.3 70B (default, fast)"),
-            ("claude-opus-45", "Claude Opus 4.5 via Venice (strongest)"),
-            ("llama-3.1-405b", "Llama 3.1 405B (largest open source)"),
-        ],
-        "groq" => vec![
-            (
-                "llama-3.3-70b-versatile",
-                "Llama 3.3 70B (fast, recommended)",
-            ),
-            ("llama-3.1-8b-instant", "Llama 3.1 8B (instant)"),
-            ("mixtral-8x7b-32768", "Mixtral 8x7B (32K context)"),
-        ],
-        "mistral" => vec![
-            ("mistral-large-latest", "Mistral Large (flagship)"),
-            ("codestral-latest", "Codestral (code-focused)"),
-            ("mistral-small-latest", "Mistral Small (fast, cheap)"),
-        ],
-        "deepseek" => vec![
-            ("deepseek-chat", "DeepSeek Chat (V3, recommended)"),
-            ("deepseek-reasoner", "DeepSeek Reasoner (R1)"),
-        ],
-        "xai" => vec![
-            ("grok-3", "Grok 3 (flagship)"),
-            ("grok-3-mini", "Grok 3 Mini (fast)"),
-        ],
-        "perplexity" => vec![
-            ("sonar-pro", "Sonar Pro (search + reasoning)"),
-            ("sonar", "Sonar (search, fast)"),
-        ],
-        "fireworks" => vec![
-            (
-                "accounts/fireworks/models/llama-v3p3-70b-instruct",
-                "Llama 3.3 70B",
-            ),
-            (
-                "accounts/fireworks/models/mixtral-8x22b-instruct",
-                "Mixtral 8x22B",
-            ),
-        ],
-        "together" => vec![
-            (
-                "meta-llama/Meta-Llama-3.1-70B-Instruct-Turbo",
-                "Llama 3.1 70B Turbo",
-            ),
-            (
-                "meta-llama/Meta-Llama-3.1-8B-Instruct-Turbo",
-                "Llama 3.1 8B Turbo",
-            ),
-            ("mistralai/Mixtral-8x22B-Instruct-v0.1", "Mixtral 8x22B"),
-        ],
-        "cohere" => vec![
-            ("command-r-plus", "Command R+ (flagship)"),
-            ("command-r", "Command R (fast)"),
-        ],
-        "moonshot" => vec![
-            ("moonshot-v1-128k", "Moonshot V1 128K"),
-            ("moonshot-v1-32k", "Moonshot V1 32K"),
-        ],
-        "glm" => vec![
-            ("glm-4-plus", "GLM-4 Plus (flagship)"),
-            ("glm-4-flash", "GLM-4 Flash (fast)"),
-        ],
-        "minimax" => vec![
-            ("abab6.5s-chat", "ABAB 6.5s Chat"),
-            ("abab6.5-chat", "ABAB 6.5 Chat"),
-        ],
-        "ollama" => vec![
-            ("llama3.2", "Llama 3.2 (recommended local)"),
-            ("mistral", "Mistral 7B"),
-            ("codellama", "Code Llama"),
-            ("phi3", "Phi-3 (small, fast)"),
-        ],
-        "gemini" | "google" | "google-gemini" => vec![
-            ("gemini-2.0-flash", "Gemini 2.0 Flash (fast, recommended)"),
-            (
-                "gemini-2.0-flash-lite",
-                "Gemini 2.0 Flash Lite (fastest, cheapest)",
-            ),
-            ("gemini-1.5-pro", "Gemini 1.5 Pro (best quality)"),
-            ("gemini-1.5-flash", "Gemini 1.5 Flash (balanced)"),
-        ],
-        _ => vec![("default", "Default model")],
-    };
-
-    let model_labels: Vec<&str> = models.iter().map(|(_, label)| *label).collect();
-
-    let model_idx = Select::new()
-        .with_prompt("  选择默认模型")
-        .items(&model_labels)
-        .default(0)
-        .interact()?;
-
-    let model = models[model_idx].0.to_string();
+    let model = select_model(provider_name, &api_key)?;
 
     println!(
         "  {} Provider：{} | 模型：{}",
@@ -823,6 +1002,55 @@ fn setup_provider() -> Result<(String, String, String)> {
     Ok((provider_name.to_string(), api_key, model))
 }
 
+/// Pick the default model: the provider's live list when it can be fetched,
+/// otherwise the curated recommendations.
+fn select_model(provider_name: &str, api_key: &str) -> Result<String> {
+    let mut models: Vec<(String, String)> = models::curated(provider_name)
+        .iter()
+        .map(|(id, label)| ((*id).to_string(), (*label).to_string()))
+        .collect();
+
+    // Without a key only Ollama (local, keyless) can be listed
+    let can_fetch = !api_key.is_empty() || provider_name == "ollama";
+    let handle = tokio::runtime::Handle::try_current().ok();
+    if let (true, Some(handle)) = (can_fetch, handle) {
+        print_bullet("正在获取可用模型列表...");
+        let key = (!api_key.is_empty()).then_some(api_key);
+        let listing = handle.block_on(models::fetch(provider_name, key));
+        if listing.live {
+            let live: HashSet<&str> = listing.models.iter().map(|m| m.id.as_str()).collect();
+            // Recommendations the provider actually serves come first
+            models.retain(|(id, _)| live.contains(id.as_str()));
+            for info in &listing.models {
+                if !models.iter().any(|(id, _)| *id == info.id) {
+                    let label = match &info.description {
+                        Some(description) => format!("{} — {description}", info.id),
+                        None => info.id.clone(),
+                    };
+                    models.push((info.id.clone(), label));
+                }
+            }
+        } else if let Some(error) = listing.error {
+            print_bullet(&format!(
+                "无法获取在线模型列表（{error}），使用内置推荐列表。"
+            ));
+        }
+    }
+
+    if models.is_empty() {
+        models.push(("default".to_string(), "Default model".to_string()));
+    }
+    let model_labels: Vec<&str> = models.iter().map(|(_, label)| label.as_str()).collect();
+
+    let model_idx = FuzzySelect::new()
+        .with_prompt("  选择默认模型（可输入搜索）")
+        .items(&model_labels)
+        .default(0)
+        .interact()?;
+
+    Ok(models.swap_remove(model_idx).0)
+}
+
 /// Map provider name to its conventional env var
 fn provider_env_var(name: &str) -> &'static str {
     match name {
@@ -924,7 +1152,10 @@ fn setup_tool_mode() -> Result<(ComposioConfig, SecretsConfig)> {
         .default(true)
         .interact()?;
 
-    let secrets_config = SecretsConfig { encrypt };
+    let secrets_config = SecretsConfig {
+        encrypt,
+        ..SecretsConfig::default()
+    };
 
     if encrypt {
         println!(
@@ -1076,11 +1307,34 @@ fn setup_memory() -> Result<MemoryConfig> {
         save
     };
 
+    let (embedding_provider, embedding_model, embedding_dimensions) = if backend == "sqlite" {
+        let embedding_options = vec![
+            "无 — 仅关键词搜索",
+            "OpenAI — text-embedding-3-small，需要 OpenAI API Key",
+            "本地模型 — Ollama 的 nomic-embed-text，离线且无需 API Key（先运行 ollama pull nomic-embed-text）",
+            "哈希 n-gram — 离线计算，无需 API Key；只匹配词形（如 deploy/deploying），不理解语义",
+        ];
+        let embedding_choice = Select::new()
+            .with_prompt("  选择向量搜索的 embedding 来源")
+            .items(&embedding_options)
+            .default(0)
+            .interact()?;
+        match embedding_choice {
+            1 => ("openai", "text-embedding-3-small", 1536),
+            2 => ("local", "nomic-embed-text", 768),
+            3 => ("hashed", HASHED_MODEL, 384),
+            _ => ("none", "text-embedding-3-small", 1536),
+        }
+    } else {
+        ("none", "text-embedding-3-small", 1536)
+    };
+
     println!(
-        "  {} 记忆：{}（自动保存：{}）",
+        "  {} 记忆：{}（自动保存：{}，embedding：{}）",
         style("✓").green().bold(),
         style(backend).green(),
-        if auto_save { "开" } else { "关" }
+        if auto_save { "开" } else { "关" },
+        embedding_provider
     );
 
     Ok(MemoryConfig {
@@ -1090,13 +1344,16 @@ fn setup_memory() -> Result<MemoryConfig> {
         archive_after_days: if backend == "sqlite" { 7 } else { 0 },
         purge_after_days: if backend == "sqlite" { 30 } else { 0 },
         conversation_retention_days: 30,
-        embedding_provider: "none".to_string(),
-        embedding_model: "text-embedding-3-small".to_string(),
-        embedding_dimensions: 1536,
+        task_retention_days: 30,
+        transcript_retention_days: 30,
+        embedding_provider: embedding_provider.to_string(),
+        embedding_model: embedding_model.to_string(),
+        embedding_dimensions,
         vector_weight: 0.7,
         keyword_weight: 0.3,
         embedding_cache_size: if backend == "sqlite" { 10000 } else { 0 },
         chunk_max_tokens: 512,
+        postgres_url: None,
     })
 }
 
@@ -1118,6 +1375,7 @@ fn setup_channels() -> Result<ChannelsConfig> {
         matrix: None,
         whatsapp: None,
         irc: None,
+        ..ChannelsConfig::default()
     };
 
     loop {
@@ -1220,28 +1478,17 @@ fn setup_channels() -> Result<ChannelsConfig> {
 
                 // Test connection
                 print!("  {} 正在测试连接... ", style("⏳").dim());
-                let client = reqwest::blocking::Client::new();
-                let url = format!("https://api.telegram.org/bot{token}/getMe");
-                match client.get(&url).send() {
-                    Ok(resp) if resp.status().is_success() => {
-                        let data: serde_json::Value = resp.json().unwrap_or_default();
-                        let bot_name = data
-                            .get("result")
-                            .and_then(|r| r.get("username"))
-                            .and_then(serde_json::Value::as_str)
-                            .unwrap_or("unknown");
-                        println!(
-                            "\r  {} 已连接为 @{bot_name}        ",
-                            style("✅").green().bold()
-                        );
-                    }
-                    _ => {
-                        println!(
-                            "\r  {} 连接失败 — 请检查 Token 后重试",
-                            style("❌").red().bold()
-                        );
-                        continue;
-                    }
+                if let Ok(bot_name) = check_telegram(&token) {
+                    println!(
+                        "\r  {} 已连接为 @{bot_name}        ",
+                        style("✅").green().bold()
+                    );
+                } else {
+                    println!(
+                        "\r  {} 连接失败 — 请检查 Token 后重试",
+                        style("❌").red().bold()
+                    );
+                    continue;
                 }
 
                 print_bullet("建议先将你自己的 Telegram 身份加入白名单（安全且快速的设置方式）。");
@@ -1302,30 +1549,17 @@ fn setup_channels() -> Result<ChannelsConfig> {
 
                 // Test connection
                 print!("  {} 正在测试连接... ", style("⏳").dim());
-                let client = reqwest::blocking::Client::new();
-                match client
-                    .get("https://discord.com/api/v10/users/@me")
-                    .header("Authorization", format!("Bot {token}"))
-                    .send()
-                {
-                    Ok(resp) if resp.status().is_success() => {
-                        let data: serde_json::Value = resp.json().unwrap_or_default();
-                        let bot_name = data
-                            .get("username")
-                            .and_then(serde_json::Value::as_str)
-                            .unwrap_or("unknown");
-                        println!(
-                            "\r  {} 已连接为 {bot_name}        ",
-                            style("✅").green().bold()
-                        );
-                    }
-                    _ => {
-                        println!(
-                            "\r  {} 连接失败 — 请检查 Token 后重试",
-                            style("❌").red().bold()
-                        );
-                        continue;
-                    }
+                if let Ok(bot_name) = check_discord(&token) {
+                    println!(
+                        "\r  {} 已连接为 {bot_name}        ",
+                        style("✅").green().bold()
+                    );
+                } else {
+                    println!(
+                        "\r  {} 连接失败 — 请检查 Token 后重试",
+                        style("❌").red().bold()
+                    );
+                    continue;
                 }
 
                 let guild: String = Input::new()
@@ -1367,6 +1601,8 @@ fn setup_channels() -> Result<ChannelsConfig> {
                     bot_token: token,
                     guild_id: if guild.is_empty() { None } else { Some(guild) },
                     allowed_users,
+                    reply_in_thread: false,
+                    respond_to_mentions_only: false,
                 });
             }
             2 => {
@@ -1393,44 +1629,21 @@ fn setup_channels() -> Result<ChannelsConfig> {
 
                 // Test connection
                 print!("  {} 正在测试连接... ", style("⏳").dim());
-                let client = reqwest::blocking::Client::new();
-                match client
-                    .get("https://slack.com/api/auth.test")
-                    .bearer_auth(&token)
-                    .send()
-                {
-                    Ok(resp) if resp.status().is_success() => {
-                        let data: serde_json::Value = resp.json().unwrap_or_default();
-                        let ok = data
-                            .get("ok")
-                            .and_then(serde_json::Value::as_bool)
-                            .unwrap_or(false);
-                        let team = data
-                            .get("team")
-                            .and_then(serde_json::Value::as_str)
-                            .unwrap_or("unknown");
-                        if ok {
-                            println!(
-                                "\r  {} 已连接到工作区：{team}        ",
-                                style("✅").green().bold()
-                            );
-                        } else {
-                            let err = data
-                                .get("error")
-                                .and_then(serde_json::Value::as_str)
-                                .unwrap_or("unknown error");
-                            println!("\r  {} Slack 错误：{err}", style("❌").red().bold());
-                            continue;
-                        }
+                match check_slack(&token) {
+                    Ok(team) => {
+                        println!(
+                            "\r  {} 已连接到工作区：{team}        ",
+                            style("✅").green().bold()
+                        );
                     }
-                    _ => {
-                        println!("\r  {} 连接失败 — 请检查 Token", style("❌").red().bold());
+                    Err(e) => {
+                        println!("\r  {} 连接失败 — {e}", style("❌").red().bold());
                         continue;
                     }
                 }
 
                 let app_token: String = Input::new()
-                    .with_prompt("  App Token（xapp-...，可选，按 Enter 跳过）")
+                    .with_prompt("  App Token（xapp-...，启用 Socket Mode 实时接收消息；可选，按 Enter 跳过）")
                     .allow_empty(true)
                     .interact_text()?;
 
@@ -1556,32 +1769,18 @@ fn setup_channels() -> Result<ChannelsConfig> {
                 }
 
                 // Test connection
-                let hs = homeserver.trim_end_matches('/');
                 print!("  {} 正在测试连接... ", style("⏳").dim());
-                let client = reqwest::blocking::Client::new();
-                match client
-                    .get(format!("{hs}/_matrix/client/v3/account/whoami"))
-                    .header("Authorization", format!("Bearer {access_token}"))
-                    .send()
-                {
-                    Ok(resp) if resp.status().is_success() => {
-                        let data: serde_json::Value = resp.json().unwrap_or_default();
-                        let user_id = data
-                            .get("user_id")
-                            .and_then(serde_json::Value::as_str)
-                            .unwrap_or("unknown");
-                        println!(
-                            "\r  {} 已连接为 {user_id}        ",
-                            style("✅").green().bold()
-                        );
-                    }
-                    _ => {
-                        println!(
-                            "\r  {} 连接失败 — 请检查 Homeserver URL 和令牌",
-                            style("❌").red().bold()
-                        );
-                        continue;
-                    }
+                if let Ok(user_id) = check_matrix(&homeserver, &access_token) {
+                    println!(
+                        "\r  {} 已连接为 {user_id}        ",
+                        style("✅").green().bold()
+                    );
+                } else {
+                    println!(
+                        "\r  {} 连接失败 — 请检查 Homeserver URL 和令牌",
+                        style("❌").red().bold()
+                    );
+                    continue;
                 }
 
                 let room_id: String = Input::new()
@@ -1645,29 +1844,17 @@ fn setup_channels() -> Result<ChannelsConfig> {
 
                 // Test connection
                 print!("  {} 正在测试连接... ", style("⏳").dim());
-                let client = reqwest::blocking::Client::new();
-                let url = format!(
-                    "https://graph.facebook.com/v18.0/{}",
-                    phone_number_id.trim()
-                );
-                match client
-                    .get(&url)
-                    .header("Authorization", format!("Bearer {}", access_token.trim()))
-                    .send()
-                {
-                    Ok(resp) if resp.status().is_success() => {
-                        println!(
-                            "\r  {} 已连接到 WhatsApp API        ",
-                            style("✅").green().bold()
-                        );
-                    }
-                    _ => {
-                        println!(
-                            "\r  {} 连接失败 — 请检查访问令牌和手机号码 ID",
-                            style("❌").red().bold()
-                        );
-                        continue;
-                    }
+                if check_whatsapp(&access_token, &phone_number_id).is_ok() {
+                    println!(
+                        "\r  {} 已连接到 WhatsApp API        ",
+                        style("✅").green().bold()
+                    );
+                } else {
+                    println!(
+                        "\r  {} 连接失败 — 请检查访问令牌和手机号码 ID",
+                        style("❌").red().bold()
+                    );
+                    continue;
                 }
 
                 let users_str: String = Input::new()
@@ -1861,36 +2048,10 @@ fn setup_channels() -> Result<ChannelsConfig> {
     }
 
     // Summary line
-    let mut active: Vec<&str> = vec!["CLI"];
-    if config.telegram.is_some() {
-        active.push("Telegram");
-    }
-    if config.discord.is_some() {
-        active.push("Discord");
-    }
-    if config.slack.is_some() {
-        active.push("Slack");
-    }
-    if config.imessage.is_some() {
-        active.push("iMessage");
-    }
-    if config.matrix.is_some() {
-        active.push("Matrix");
-    }
-    if config.whatsapp.is_some() {
-        active.push("WhatsApp");
-    }
-    if config.irc.is_some() {
-        active.push("IRC");
-    }
-    if config.webhook.is_some() {
-        active.push("Webhook");
-    }
-
     println!(
         "  {} 通道：{}",
         style("✓").green().bold(),
-        style(active.join(", ")).green()
+        style(active_channels(&config).join(", ")).green()
     );
 
     Ok(config)
@@ -2101,6 +2262,7 @@ fn scaffold_workspace(workspace_dir: &Path, ctx: &ProjectContext) -> Result<()>
          - Memory is limited — if you want to remember something, WRITE IT TO A FILE\n\
          - \"Mental notes\" don't survive session restarts. Files do.\n\
          - When someone says \"remember this\" -> update daily file or MEMORY.md\n\
+         - When someone says \"remind me\" or a follow-up is left open -> `task_add` (not MEMORY.md)\n\
          - When you learn a lesson -> update AGENTS.md, TOOLS.md, or the relevant skill\n\n\
          ## Safety\n\n\
          - Don't exfiltrate private data. Ever.\n\
@@ -2133,10 +2295,14 @@ fn scaffold_workspace(workspace_dir: &Path, ctx: &ProjectContext) -> Result<()>
          # Keep this file empty (or with only comments) to skip heartbeat work.\n\
          # Add tasks below when you want {agent} to check something periodically.\n\
          #\n\
+         # Prefix a task with [daily HH:MM] or [weekly mon HH:MM] to run it\n\
+         # once per day/week; `- [x]` marks a task done so it is skipped.\n\
+         #\n\
          # Examples:\n\
          # - Check my email for important messages\n\
-         # - Review my calendar for upcoming events\n\
-         # - Run `git status` on my active projects\n"
+         # - [daily 09:00] Review my calendar for upcoming events\n\
+         # - [weekly mon] Run `git status` on my active projects\n\
+         # - [ ] Follow up on the pending invoice\n"
     );
 
     let soul = format!(
@@ -2211,9 +2377,12 @@ fn scaffold_workspace(workspace_dir: &Path, ctx: &ProjectContext) -> Result<()>
          - **file_read** — Read file contents\n\
            - Use when: inspecting project files, configs, or logs.\n\
            - Don't use when: you only need a quick string search (prefer targeted search first).\n\
-         - **file_write** — Write file contents\n\
-           - Use when: applying focused edits, scaffolding files, or updating docs/code.\n\
-           - Don't use when: unsure about side effects or when the file should remain user-owned.\n\
+         - **file_write** — Write a whole file\n\
+           - Use when: creating files or replacing most of a file's content.\n\
+           - Don't use when: changing part of an existing file (use file_edit), or unsure about side effects.\n\
+         - **file_edit** — Replace exact text in an existing file\n\
+           - Use when: changing part of a file; each old_text must match exactly once, and the original is backed up.\n\
+           - Don't use when: creating a new file.\n\
          - **memory_store** — Save to memory\n\
            - Use when: preserving durable preferences, decisions, or key context.\n\
            - Don't use when: info is transient, noisy, or sensitive without explicit need.\n\
@@ -2222,7 +2391,25 @@ fn scaffold_workspace(workspace_dir: &Path, ctx: &ProjectContext) -> Result<()>
            - Don't use when: the answer is already in current files/conversation.\n\
          - **memory_forget** — Delete a memory entry\n\
            - Use when: memory is incorrect, stale, or explicitly requested to be removed.\n\
-           - Don't use when: uncertain about impact; verify before deleting.\n\n\
+           - Don't use when: uncertain about impact; verify before deleting.\n\
+         - **task_add** — Add a task to the task list\n\
+           - Use when: the user asks to be reminded, or a follow-up needs tracking (give a due time if there is one).\n\
+           - Don't use when: it is a fact to remember rather than something to do (use memory_store).\n\
+         - **task_list** — List open tasks\n\
+           - Use when: checking what is pending or overdue.\n\
+           - Don't use when: you just listed them in this conversation.\n\
+         - **task_complete** — Mark a task done by id\n\
+           - Use when: a tracked task is finished.\n\
+           - Don't use when: the user wants to drop a task without doing it; ask first.\n\
+         - **clipboard** — Read or set the clipboard (only if `[tools.clipboard] enabled = true`)\n\
+           - Use when: the user says \"what I copied\" or asks you to put text on their clipboard.\n\
+           - Don't use when: they didn't mention the clipboard. It can hold passwords and other private text; never read it unprompted or repeat more than needed.\n\
+         - **reminders** — Add, list or complete to-dos in the `.ics` reminders file (only if `[tools.reminders] enabled = true`)\n\
+           - Use when: the user wants a reminder their calendar app can import, or asks what is due (list with `due_before`).\n\
+           - Don't use when: a task on the task list is enough; don't keep the same item in both.\n\
+         - **python** — Run a Python program in the workspace (only if `[tools.python] enabled = true`)\n\
+           - Use when: calculating or analysing data (CSV, JSON, logs) where a short script beats a shell one-liner; print the results you need.\n\
+           - Don't use when: a single shell command does it, or the code would change files outside the task.\n\n\
          ---\n\
          *Add whatever helps you do your job. This is your cheat sheet.*\n";
 
@@ -2263,7 +2450,7 @@ fn scaffold_workspace(workspace_dir: &Path, ctx: &ProjectContext) -> Result<()>
          ## Lessons Learned\n\
          (Document mistakes and insights here)\n\n\
          ## Open Loops\n\
-         (Track unfinished tasks and follow-ups here)\n";
+         (Unfinished tasks and follow-ups go in the task list: task_add / task_list / task_complete)\n";
 
     let files: Vec<(&str, String)> = vec![
         ("IDENTITY.md", identity),
@@ -2852,9 +3039,13 @@ mod tests {
             "shell",
             "file_read",
             "file_write",
+            "file_edit",
             "memory_store",
             "memory_recall",
             "memory_forget",
+            "task_add",
+            "task_list",
+            "task_complete",
         ] {
             assert!(
                 tools.contains(tool),
@@ -2966,4 +3157,101 @@ mod tests {
     fn provider_env_var_unknown_falls_back() {
         assert_eq!(provider_env_var("some-new-provider"), "API_KEY");
     }
+
+    // ── QuickChannels ───────────────────────────────────────────
+
+    #[test]
+    fn quick_channels_default_is_empty() {
+        let channels = QuickChannels::default();
+        assert!(channels.is_empty());
+        let config = channels.to_config().unwrap();
+        assert!(config.cli);
+        assert_eq!(active_channels(&config), vec!["CLI"]);
+    }
+
+    #[test]
+    fn quick_channels_flags_populate_configs() {
+        let channels = QuickChannels {
+            telegram_token: Some(" 123:abc ".into()),
+            telegram_allowed: vec!["alice".into(), " ".into(), "42".into()],
+            slack_token: Some("xoxb-1".into()),
+            slack_channel: Some("C123".into()),
+            webhook_port: Some(9000),
+            webhook_secret: Some("s3cret".into()),
+            ..QuickChannels::default()
+        };
+        assert!(!channels.is_empty());
+
+        let config = channels.to_config().unwrap();
+        let telegram = config.telegram.as_ref().unwrap();
+        assert_eq!(telegram.bot_token, "123:abc");
+        assert_eq!(telegram.allowed_users, vec!["alice", "42"]);
+        let slack = config.slack.as_ref().unwrap();
+        assert_eq!(slack.channel_id.as_deref(), Some("C123"));
+        assert!(slack.allowed_users.is_empty());
+        let webhook = config.webhook.as_ref().unwrap();
+        assert_eq!(webhook.port, 9000);
+        assert_eq!(webhook.secret.as_deref(), Some("s3cret"));
+        assert!(config.discord.is_none());
+        assert_eq!(
+            active_channels(&config),
+            vec!["CLI", "Telegram", "Slack", "Webhook"]
+        );
+    }
+
+    #[test]
+    fn quick_channels_dependent_flag_without_token_fails() {
+        let channels = QuickChannels {
+            discord_allowed: vec!["1".into()],
+            ..QuickChannels::default()
+        };
+        let err = channels.to_config().unwrap_err().to_string();
+        assert!(err.contains("--discord-token"), "{err}");
+    }
+
+    #[test]
+    fn quick_channels_flags_override_file() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("channels.json");
+        fs::write(
+            &path,
+            r##"{
+                "telegram": { "bot_token": "from-file", "allowed_users": ["bob"] },
+                "irc": {
+                    "server": "irc.libera.chat",
+                    "nickname": "jarvis",
+                    "channels": ["#jarvis"],
+                    "allowed_users": ["*"]
+                }
+            }"##,
+        )
+        .unwrap();
+
+        let channels = QuickChannels {
+            from_file: Some(path),
+            telegram_token: Some("from-flag".into()),
+            ..QuickChannels::default()
+        };
+        let config = channels.to_config().unwrap();
+        assert!(config.cli, "cli defaults to on when omitted from the file");
+        let telegram = config.telegram.as_ref().unwrap();
+        assert_eq!(telegram.bot_token, "from-flag");
+        assert_eq!(telegram.allowed_users, vec!["bob"]);
+        assert!(config.irc.is_some());
+        assert_eq!(active_channels(&config), vec!["CLI", "Telegram", "IRC"]);
+    }
+
+    #[test]
+    fn quick_channels_rejects_malformed_file() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("channels.json");
+        fs::write(&path, r#"{ "telegram": { "allowed_users": [] } }"#).unwrap();
+
+        let channels = QuickChannels {
+            from_file: Some(path),
+            ..QuickChannels::default()
+        };
+        let err = format!("{:#}", channels.to_config().unwrap_err());
+        assert!(err.contains("bot_token"), "{err}");
+    }
 }
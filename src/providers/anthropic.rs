@@ -1,3 +1,4 @@
+use crate::providers::structured::ResponseFormat;
 use crate::providers::traits::Provider;
 use async_trait::async_trait;
 use reqwest::Client;
@@ -35,6 +36,46 @@ struct ContentBlock {
     text: String,
 }
 
+/// Structured output via tool forcing: the model must call a single tool whose
+/// input schema is the requested JSON shape.
+#[derive(Debug, Serialize)]
+struct StructuredRequest {
+    #[serde(flatten)]
+    chat: ChatRequest,
+    tools: Vec<serde_json::Value>,
+    tool_choice: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct StructuredResponse {
+    content: Vec<StructuredBlock>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StructuredBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    input: Option<serde_json::Value>,
+}
+
+/// Tool inputs must be objects; other schemas are wrapped under this field.
+const WRAPPED_FIELD: &str = "value";
+
+fn forced_tool_schema(format: &ResponseFormat) -> (serde_json::Value, bool) {
+    let schema = format.schema();
+    if schema.get("type").and_then(serde_json::Value::as_str) == Some("object") {
+        (schema, false)
+    } else {
+        let wrapped = serde_json::json!({
+            "type": "object",
+            "properties": { WRAPPED_FIELD: schema },
+            "required": [WRAPPED_FIELD],
+        });
+        (wrapped, true)
+    }
+}
+
 impl AnthropicProvider {
     pub fn new(api_key: Option<&str>) -> Self {
         Self::with_base_url(api_key, None)
@@ -63,22 +104,14 @@ impl AnthropicProvider {
     }
 }
 
-#[async_trait]
-impl Provider for AnthropicProvider {
-    async fn chat_with_system(
-        &self,
+impl AnthropicProvider {
+    fn chat_request(
         system_prompt: Option<&str>,
         message: &str,
         model: &str,
         temperature: f64,
-    ) -> anyhow::Result<String> {
-        let credential = self.credential.as_ref().ok_or_else(|| {
-            anyhow::anyhow!(
-                "Anthropic credentials not set. Set ANTHROPIC_API_KEY or ANTHROPIC_OAUTH_TOKEN (setup-token)."
-            )
-        })?;
-
-        let request = ChatRequest {
+    ) -> ChatRequest {
+        ChatRequest {
             model: model.to_string(),
             max_tokens: 4096,
             system: system_prompt.map(ToString::to_string),
@@ -87,28 +120,65 @@ impl Provider for AnthropicProvider {
                 content: message.to_string(),
             }],
             temperature,
-        };
+        }
+    }
 
-        let mut request = self
+    /// Add the version header and whichever credential header applies.
+    fn authorize(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        let credential = self.credential.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Anthropic credentials not set. Set ANTHROPIC_API_KEY or ANTHROPIC_OAUTH_TOKEN (setup-token)."
+            )
+        })?;
+        let request = request.header("anthropic-version", "2023-06-01");
+        Ok(if Self::is_setup_token(credential) {
+            request.header("Authorization", format!("Bearer {credential}"))
+        } else {
+            request.header("x-api-key", credential)
+        })
+    }
+
+    /// POST a request body to the Messages API.
+    async fn send<T: Serialize + Sync>(&self, body: &T) -> anyhow::Result<reqwest::Response> {
+        let request = self
             .client
             .post(format!("{}/v1/messages", self.base_url))
-            .header("anthropic-version", "2023-06-01")
             .header("content-type", "application/json")
-            .json(&request);
+            .json(body);
 
-        if Self::is_setup_token(credential) {
-            request = request.header("Authorization", format!("Bearer {credential}"));
-        } else {
-            request = request.header("x-api-key", credential);
+        let response = self.authorize(request)?.send().await?;
+
+        if !response.status().is_success() {
+            return Err(super::api_error("Anthropic", response).await);
         }
 
-        let response = request.send().await?;
+        Ok(response)
+    }
+}
 
+#[async_trait]
+impl Provider for AnthropicProvider {
+    async fn ping(&self) -> anyhow::Result<()> {
+        let request = self.client.get(format!("{}/v1/models", self.base_url));
+        let response = self.authorize(request)?.send().await?;
         if !response.status().is_success() {
             return Err(super::api_error("Anthropic", response).await);
         }
+        Ok(())
+    }
 
-        let chat_response: ChatResponse = response.json().await?;
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let request = Self::chat_request(system_prompt, message, model, temperature);
+        let chat_response: ChatResponse = self.send(&request).await?.json().await?;
 
         chat_response
             .content
@@ -117,6 +187,41 @@ impl Provider for AnthropicProvider {
             .map(|c| c.text)
             .ok_or_else(|| anyhow::anyhow!("No response from Anthropic"))
     }
+
+    async fn chat_structured(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+        format: &ResponseFormat,
+    ) -> anyhow::Result<String> {
+        let (input_schema, wrapped) = forced_tool_schema(format);
+        let request = StructuredRequest {
+            chat: Self::chat_request(system_prompt, message, model, temperature),
+            tools: vec![serde_json::json!({
+                "name": format.name(),
+                "description": "Return the response as structured JSON.",
+                "input_schema": input_schema,
+            })],
+            tool_choice: serde_json::json!({ "type": "tool", "name": format.name() }),
+        };
+        let response: StructuredResponse = self.send(&request).await?.json().await?;
+
+        let mut input = response
+            .content
+            .into_iter()
+            .find(|block| block.kind == "tool_use")
+            .and_then(|block| block.input)
+            .ok_or_else(|| anyhow::anyhow!("No structured response from Anthropic"))?;
+        if wrapped {
+            input = input
+                .get_mut(WRAPPED_FIELD)
+                .map(serde_json::Value::take)
+                .unwrap_or_default();
+        }
+        Ok(input.to_string())
+    }
 }
 
 #[cfg(test)]
@@ -262,6 +367,41 @@ mod tests {
         assert_eq!(resp.content[1].text, "Second");
     }
 
+    #[test]
+    fn structured_request_forces_tool() {
+        let format = ResponseFormat::JsonObject;
+        let (input_schema, wrapped) = forced_tool_schema(&format);
+        assert!(!wrapped);
+        let req = StructuredRequest {
+            chat: AnthropicProvider::chat_request(None, "hello", "claude-3-opus", 0.0),
+            tools: vec![serde_json::json!({"name": format.name(), "input_schema": input_schema})],
+            tool_choice: serde_json::json!({"type": "tool", "name": format.name()}),
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["model"], "claude-3-opus");
+        assert_eq!(json["tool_choice"]["type"], "tool");
+        assert_eq!(json["tools"][0]["input_schema"]["type"], "object");
+    }
+
+    #[test]
+    fn non_object_schema_is_wrapped() {
+        let format = ResponseFormat::json_schema(
+            "labels",
+            serde_json::json!({"type": "array", "items": {"type": "string"}}),
+        );
+        let (input_schema, wrapped) = forced_tool_schema(&format);
+        assert!(wrapped);
+        assert_eq!(input_schema["properties"]["value"]["type"], "array");
+    }
+
+    #[test]
+    fn structured_response_finds_tool_input() {
+        let json = r#"{"content":[{"type":"text","text":"Sure"},{"type":"tool_use","id":"t1","name":"json_response","input":{"ok":true}}]}"#;
+        let resp: StructuredResponse = serde_json::from_str(json).unwrap();
+        let block = resp.content.iter().find(|b| b.kind == "tool_use").unwrap();
+        assert_eq!(block.input, Some(serde_json::json!({"ok": true})));
+    }
+
     #[test]
     fn temperature_range_serializes() {
         for temp in [0.0, 0.5, 1.0, 2.0] {
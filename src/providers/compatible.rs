@@ -2,8 +2,9 @@
 //! Most LLM APIs follow the same `/v1/chat/completions` format.
 //! This module provides a single implementation that works for all of them.
 
+use crate::providers::structured::ResponseFormat;
 use crate::providers::traits::{
-    ChatMessage, ChatResponse as ProviderChatResponse, FunctionCall, Provider, ToolCall,
+    ChatMessage, ChatResponse as ProviderChatResponse, FunctionCall, ModelInfo, Provider, ToolCall,
     ToolDefinition,
 };
 use async_trait::async_trait;
@@ -59,6 +60,14 @@ impl OpenAiCompatibleProvider {
         }
     }
 
+    /// The model list next to the chat completions endpoint, for `ping`.
+    fn models_url(&self) -> String {
+        match self.base_url.strip_suffix("chat/completions") {
+            Some(prefix) => format!("{prefix}models"),
+            None => format!("{}/models", self.base_url),
+        }
+    }
+
     /// Build the full URL for responses API, detecting if `base_url` already includes the path.
     fn responses_url(&self) -> String {
         // If base_url already contains "responses", use it as-is
@@ -112,6 +121,8 @@ struct ToolChatRequest {
     temperature: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<ToolDefinition>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
 }
 
 /// A message in the `OpenAI` wire format (untagged role variants).
@@ -328,8 +339,7 @@ impl OpenAiCompatibleProvider {
             .await?;
 
         if !response.status().is_success() {
-            let error = response.text().await?;
-            anyhow::bail!("{} Responses API error: {error}", self.name);
+            return Err(super::api_error(&format!("{} Responses", self.name), response).await);
         }
 
         let responses: ResponsesResponse = response.json().await?;
@@ -341,6 +351,30 @@ impl OpenAiCompatibleProvider {
 
 #[async_trait]
 impl Provider for OpenAiCompatibleProvider {
+    async fn ping(&self) -> anyhow::Result<()> {
+        let api_key = self.require_api_key()?;
+        let response = self
+            .apply_auth_header(self.client.get(self.models_url()), api_key)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(super::api_error(&self.name, response).await);
+        }
+        Ok(())
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let api_key = self.require_api_key()?;
+        let response = self
+            .apply_auth_header(self.client.get(self.models_url()), api_key)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(super::api_error(&self.name, response).await);
+        }
+        Ok(super::models::parse_openai_models(&response.json().await?))
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
@@ -377,23 +411,21 @@ impl Provider for OpenAiCompatibleProvider {
             .send()
             .await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
             let error = response.text().await?;
+            return self
+                .chat_via_responses(api_key, system_prompt, message, model)
+                .await
+                .map_err(|responses_err| {
+                    anyhow::anyhow!(
+                        "{} API error: {error} (chat completions unavailable; responses fallback failed: {responses_err})",
+                        self.name
+                    )
+                });
+        }
 
-            if status == reqwest::StatusCode::NOT_FOUND {
-                return self
-                    .chat_via_responses(api_key, system_prompt, message, model)
-                    .await
-                    .map_err(|responses_err| {
-                        anyhow::anyhow!(
-                            "{} API error: {error} (chat completions unavailable; responses fallback failed: {responses_err})",
-                            self.name
-                        )
-                    });
-            }
-
-            anyhow::bail!("{} API error: {error}", self.name);
+        if !response.status().is_success() {
+            return Err(super::api_error(&self.name, response).await);
         }
 
         let chat_response: WireChatResponse = response.json().await?;
@@ -412,10 +444,20 @@ impl Provider for OpenAiCompatibleProvider {
         tools: &[ToolDefinition],
         model: &str,
         temperature: f64,
+        response_format: Option<&ResponseFormat>,
     ) -> anyhow::Result<ProviderChatResponse> {
         let api_key = self.require_api_key()?;
 
-        let wire_messages: Vec<WireMessage> = messages.iter().map(WireMessage::from).collect();
+        // The hint also satisfies OpenAI's rule that JSON mode prompts mention JSON,
+        // and covers compatible servers that ignore `response_format`
+        let wire_messages: Vec<WireMessage> = match response_format {
+            Some(format) => format
+                .with_system_hint(messages)
+                .iter()
+                .map(WireMessage::from)
+                .collect(),
+            None => messages.iter().map(WireMessage::from).collect(),
+        };
 
         let tools_field = if tools.is_empty() {
             None
@@ -428,6 +470,7 @@ impl Provider for OpenAiCompatibleProvider {
             messages: wire_messages,
             temperature,
             tools: tools_field,
+            response_format: response_format.map(ResponseFormat::to_openai),
         };
 
         let url = self.chat_completions_url();
@@ -438,8 +481,7 @@ impl Provider for OpenAiCompatibleProvider {
             .await?;
 
         if !response.status().is_success() {
-            let error = response.text().await?;
-            anyhow::bail!("{} API error: {error}", self.name);
+            return Err(super::api_error(&self.name, response).await);
         }
 
         let chat_response: WireChatResponse = response.json().await?;
@@ -476,7 +518,10 @@ impl Provider for OpenAiCompatibleProvider {
         let text = msg
             .content
             .ok_or_else(|| anyhow::anyhow!("No content in response from {}", self.name))?;
-        Ok(ProviderChatResponse::Text(text))
+        match response_format {
+            Some(format) => Ok(ProviderChatResponse::Text(format.parse(&text)?.to_string())),
+            None => Ok(ProviderChatResponse::Text(text)),
+        }
     }
 }
 
@@ -701,6 +746,7 @@ mod tests {
                     parameters: serde_json::json!({"type": "object"}),
                 },
             }]),
+            response_format: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"tools\""));
@@ -714,11 +760,26 @@ mod tests {
             messages: vec![],
             temperature: 0.7,
             tools: None,
+            response_format: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(!json.contains("tools"));
     }
 
+    #[test]
+    fn tool_chat_request_serializes_response_format() {
+        let req = ToolChatRequest {
+            model: "test".into(),
+            messages: vec![],
+            temperature: 0.0,
+            tools: None,
+            response_format: Some(ResponseFormat::JsonObject.to_openai()),
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains(r#""response_format":{"type":"json_object"}"#));
+        assert!(!json.contains("tools"));
+    }
+
     #[tokio::test]
     async fn chat_with_tools_fails_without_key() {
         let p = make_provider("Test", "https://example.com", None);
@@ -730,6 +791,7 @@ mod tests {
                 &[],
                 "model",
                 0.7,
+                None,
             )
             .await;
         assert!(result.is_err());
@@ -888,6 +950,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn models_url_sits_next_to_chat_completions() {
+        let p = make_provider("test", "https://api.example.com/v1", None);
+        assert_eq!(p.models_url(), "https://api.example.com/v1/models");
+        let p = make_provider(
+            "volcengine",
+            "https://ark.cn-beijing.volces.com/api/coding/v3/chat/completions",
+            None,
+        );
+        assert_eq!(
+            p.models_url(),
+            "https://ark.cn-beijing.volces.com/api/coding/v3/models"
+        );
+    }
+
     #[test]
     fn chat_completions_url_base_with_v1() {
         let p = make_provider("test", "https://api.example.com/v1", None);
@@ -3,6 +3,7 @@
 //! - Gemini CLI OAuth tokens (reuse existing ~/.gemini/ authentication)
 //! - Google Cloud ADC (`GOOGLE_APPLICATION_CREDENTIALS`)
 
+use crate::providers::structured::ResponseFormat;
 use crate::providers::traits::Provider;
 use async_trait::async_trait;
 use directories::UserDirs;
@@ -46,6 +47,34 @@ struct GenerationConfig {
     temperature: f64,
     #[serde(rename = "maxOutputTokens")]
     max_output_tokens: u32,
+    #[serde(rename = "responseMimeType", skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<String>,
+    #[serde(rename = "responseSchema", skip_serializing_if = "Option::is_none")]
+    response_schema: Option<serde_json::Value>,
+}
+
+impl GenerationConfig {
+    fn new(temperature: f64) -> Self {
+        Self {
+            temperature,
+            max_output_tokens: 8192,
+            response_mime_type: None,
+            response_schema: None,
+        }
+    }
+}
+
+/// Gemini's `responseSchema` is an `OpenAPI` subset; drop JSON Schema keywords it rejects.
+fn gemini_schema(schema: &serde_json::Value) -> serde_json::Value {
+    match schema {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .filter(|(key, _)| !matches!(key.as_str(), "$schema" | "additionalProperties"))
+            .map(|(key, value)| (key.clone(), gemini_schema(value)))
+            .collect(),
+        serde_json::Value::Array(items) => items.iter().map(gemini_schema).collect(),
+        other => other.clone(),
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -179,16 +208,9 @@ impl GeminiProvider {
     }
 }
 
-#[async_trait]
-impl Provider for GeminiProvider {
-    async fn chat_with_system(
-        &self,
-        system_prompt: Option<&str>,
-        message: &str,
-        model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
-        let api_key = self.api_key.as_ref().ok_or_else(|| {
+impl GeminiProvider {
+    fn require_api_key(&self) -> anyhow::Result<&str> {
+        self.api_key.as_deref().ok_or_else(|| {
             anyhow::anyhow!(
                 "Gemini API key not found. Options:\n\
                  1. Set GEMINI_API_KEY env var\n\
@@ -196,7 +218,17 @@ impl Provider for GeminiProvider {
                  3. Get an API key from https://aistudio.google.com/app/apikey\n\
                  4. Run `jarvis onboard` to configure"
             )
-        })?;
+        })
+    }
+
+    async fn generate(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        generation_config: GenerationConfig,
+    ) -> anyhow::Result<String> {
+        let api_key = self.require_api_key()?;
 
         // Build request
         let system_instruction = system_prompt.map(|sys| Content {
@@ -214,10 +246,7 @@ impl Provider for GeminiProvider {
                 }],
             }],
             system_instruction,
-            generation_config: GenerationConfig {
-                temperature,
-                max_output_tokens: 8192,
-            },
+            generation_config,
         };
 
         // Gemini API endpoint
@@ -235,9 +264,7 @@ impl Provider for GeminiProvider {
         let response = self.client.post(&url).json(&request).send().await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Gemini API error ({status}): {error_text}");
+            return Err(super::api_error("Gemini", response).await);
         }
 
         let result: GenerateContentResponse = response.json().await?;
@@ -257,6 +284,64 @@ impl Provider for GeminiProvider {
     }
 }
 
+#[async_trait]
+impl Provider for GeminiProvider {
+    async fn ping(&self) -> anyhow::Result<()> {
+        let api_key = self.require_api_key()?;
+        let response = self
+            .client
+            .get(format!(
+                "https://generativelanguage.googleapis.com/v1beta/models?key={api_key}&pageSize=1"
+            ))
+            .send()
+            .await
+            // The URL embeds the key; keep it out of error messages
+            .map_err(reqwest::Error::without_url)?;
+        if !response.status().is_success() {
+            return Err(super::api_error("Gemini", response).await);
+        }
+        Ok(())
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.generate(
+            system_prompt,
+            message,
+            model,
+            GenerationConfig::new(temperature),
+        )
+        .await
+    }
+
+    async fn chat_structured(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+        format: &ResponseFormat,
+    ) -> anyhow::Result<String> {
+        let generation_config = GenerationConfig {
+            response_mime_type: Some("application/json".to_string()),
+            // Gemini rejects object schemas without properties, so plain JSON
+            // mode relies on the MIME type alone
+            response_schema: match format {
+                ResponseFormat::JsonObject => None,
+                ResponseFormat::JsonSchema { schema, .. } => Some(gemini_schema(schema)),
+            },
+            ..GenerationConfig::new(temperature)
+        };
+        self.generate(system_prompt, message, model, generation_config)
+            .await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -330,10 +415,7 @@ mod tests {
                     text: "You are helpful".to_string(),
                 }],
             }),
-            generation_config: GenerationConfig {
-                temperature: 0.7,
-                max_output_tokens: 8192,
-            },
+            generation_config: GenerationConfig::new(0.7),
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -343,6 +425,41 @@ mod tests {
         assert!(json.contains("\"maxOutputTokens\":8192"));
     }
 
+    #[test]
+    fn generation_config_serializes_json_mode() {
+        let plain = serde_json::to_string(&GenerationConfig::new(0.2)).unwrap();
+        assert!(!plain.contains("responseMimeType"));
+        assert!(!plain.contains("responseSchema"));
+
+        let config = GenerationConfig {
+            response_mime_type: Some("application/json".to_string()),
+            response_schema: Some(serde_json::json!({"type": "object"})),
+            ..GenerationConfig::new(0.2)
+        };
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["responseMimeType"], "application/json");
+        assert_eq!(json["responseSchema"]["type"], "object");
+    }
+
+    #[test]
+    fn gemini_schema_drops_unsupported_keywords() {
+        let schema = serde_json::json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {
+                "tags": {"type": "array", "items": {"type": "object", "additionalProperties": false}}
+            }
+        });
+        let cleaned = gemini_schema(&schema);
+        assert!(cleaned.get("$schema").is_none());
+        assert!(cleaned.get("additionalProperties").is_none());
+        assert!(cleaned["properties"]["tags"]["items"]
+            .get("additionalProperties")
+            .is_none());
+        assert_eq!(cleaned["type"], "object");
+    }
+
     #[test]
     fn response_deserialization() {
         let json = r#"{
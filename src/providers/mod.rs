@@ -1,21 +1,29 @@
 pub mod anthropic;
 pub mod compatible;
 pub mod gemini;
+pub mod models;
 pub mod ollama;
 pub mod openai;
 pub mod openrouter;
 pub mod reliable;
+pub mod structured;
 pub mod traits;
 
+#[allow(unused_imports)]
+pub use structured::ResponseFormat;
 pub use traits::Provider;
 #[allow(unused_imports)]
 pub use traits::{
-    tool_spec_to_definition, ChatMessage, ChatResponse, FunctionCall, FunctionDef, ToolCall,
-    ToolDefinition,
+    tool_spec_to_definition, ChatMessage, ChatResponse, FunctionCall, FunctionDef, ModelInfo,
+    ToolCall, ToolDefinition,
 };
 
+use crate::observability::Observer;
+use chrono::{DateTime, Utc};
 use compatible::{AuthStyle, OpenAiCompatibleProvider};
 use reliable::ReliableProvider;
+use std::sync::Arc;
+use std::time::Duration;
 
 const MAX_API_ERROR_CHARS: usize = 200;
 
@@ -84,15 +92,61 @@ pub fn sanitize_api_error(input: &str) -> String {
     format!("{}...", &scrubbed[..end])
 }
 
+/// A provider HTTP call that came back with a non-success status.
+///
+/// Kept as a typed error so the retry layer can classify the status code and
+/// honor the server's `Retry-After` hint.
+#[derive(Debug)]
+pub struct ProviderHttpError {
+    pub status: u16,
+    pub retry_after: Option<Duration>,
+    message: String,
+}
+
+impl std::fmt::Display for ProviderHttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ProviderHttpError {}
+
+/// Parse a `Retry-After` header value: delay-seconds or an HTTP-date.
+///
+/// Dates in the past yield a zero delay.
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        at.with_timezone(&Utc)
+            .signed_duration_since(now)
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
 /// Build a sanitized provider error from a failed HTTP response.
 pub async fn api_error(provider: &str, response: reqwest::Response) -> anyhow::Error {
     let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| parse_retry_after(v, Utc::now()));
     let body = response
         .text()
         .await
         .unwrap_or_else(|_| "<读取 Provider 错误响应体失败>".to_string());
     let sanitized = sanitize_api_error(&body);
-    anyhow::anyhow!("{provider} API 错误 ({status}): {sanitized}")
+    ProviderHttpError {
+        status: status.as_u16(),
+        retry_after,
+        message: format!("{provider} API 错误 ({status}): {sanitized}"),
+    }
+    .into()
 }
 
 /// Resolve API key for a provider from config and environment variables.
@@ -109,6 +163,24 @@ fn resolve_api_key(name: &str, api_key: Option<&str>) -> Option<String> {
         return Some(key.to_string());
     }
 
+    if let Some(key) = provider_env_key(name) {
+        return Some(key);
+    }
+
+    for env_var in ["JARVIS_API_KEY", "API_KEY"] {
+        if let Ok(value) = std::env::var(env_var) {
+            let value = value.trim();
+            if !value.is_empty() {
+                return Some(value.to_string());
+            }
+        }
+    }
+
+    None
+}
+
+/// Provider-specific API key from the environment (e.g. `ANTHROPIC_API_KEY`).
+pub(crate) fn provider_env_key(name: &str) -> Option<String> {
     let provider_env_candidates: Vec<&str> = match name {
         "anthropic" => vec!["ANTHROPIC_OAUTH_TOKEN", "ANTHROPIC_API_KEY"],
         "openrouter" => vec!["OPENROUTER_API_KEY"],
@@ -143,15 +215,6 @@ fn resolve_api_key(name: &str, api_key: Option<&str>) -> Option<String> {
         }
     }
 
-    for env_var in ["JARVIS_API_KEY", "API_KEY"] {
-        if let Ok(value) = std::env::var(env_var) {
-            let value = value.trim();
-            if !value.is_empty() {
-                return Some(value.to_string());
-            }
-        }
-    }
-
     None
 }
 
@@ -277,10 +340,14 @@ pub fn create_provider(name: &str, api_key: Option<&str>) -> anyhow::Result<Box<
 }
 
 /// Create provider chain with retry and fallback behavior.
+///
+/// Fallback providers use their own key from the environment (e.g.
+/// `ANTHROPIC_API_KEY`) when set, otherwise the primary's key.
 pub fn create_resilient_provider(
     primary_name: &str,
     api_key: Option<&str>,
     reliability: &crate::config::ReliabilityConfig,
+    observer: Arc<dyn Observer>,
 ) -> anyhow::Result<Box<dyn Provider>> {
     let mut providers: Vec<(String, Box<dyn Provider>)> = Vec::new();
 
@@ -294,7 +361,8 @@ pub fn create_resilient_provider(
             continue;
         }
 
-        if api_key.is_some() && fallback != "ollama" {
+        let own_key = provider_env_key(fallback);
+        if own_key.is_none() && api_key.is_some() && fallback != "ollama" {
             tracing::warn!(
                 fallback_provider = fallback,
                 primary_provider = primary_name,
@@ -303,7 +371,7 @@ pub fn create_resilient_provider(
             );
         }
 
-        match create_provider(fallback, api_key) {
+        match create_provider(fallback, own_key.as_deref().or(api_key)) {
             Ok(provider) => providers.push((fallback.clone(), provider)),
             Err(e) => {
                 tracing::warn!(fallback_provider = fallback, "忽略无效的备用 Provider: {e}");
@@ -311,11 +379,17 @@ pub fn create_resilient_provider(
         }
     }
 
-    Ok(Box::new(ReliableProvider::new(
-        providers,
-        reliability.provider_retries,
-        reliability.provider_backoff_ms,
-    )))
+    Ok(Box::new(
+        ReliableProvider::new(
+            providers,
+            reliability.provider_max_retries,
+            reliability.provider_initial_backoff_ms,
+        )
+        .with_max_backoff(Duration::from_millis(reliability.provider_max_backoff_ms))
+        .with_retry_on(reliability.provider_retry_on.clone())
+        .with_fallback_models(reliability.fallback_models.clone())
+        .with_observer(observer),
+    ))
 }
 
 #[cfg(test)]
@@ -554,28 +628,45 @@ mod tests {
     #[test]
     fn resilient_provider_ignores_duplicate_and_invalid_fallbacks() {
         let reliability = crate::config::ReliabilityConfig {
-            provider_retries: 1,
-            provider_backoff_ms: 100,
+            provider_max_retries: 1,
+            provider_initial_backoff_ms: 100,
+            provider_max_backoff_ms: 1_000,
+            provider_retry_on: vec![crate::config::RetryOn::RateLimited],
             fallback_providers: vec![
                 "openrouter".into(),
                 "nonexistent-provider".into(),
                 "openai".into(),
                 "openai".into(),
             ],
+            fallback_models: std::collections::HashMap::new(),
             channel_initial_backoff_secs: 2,
             channel_max_backoff_secs: 60,
+            circuit_breaker_failures: 5,
+            circuit_breaker_window_secs: 600,
+            log_max_bytes: 10 * 1024 * 1024,
+            log_backups: 5,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
         };
 
-        let provider = create_resilient_provider("openrouter", Some("sk-test"), &reliability);
+        let provider = create_resilient_provider(
+            "openrouter",
+            Some("sk-test"),
+            &reliability,
+            Arc::new(crate::observability::NoopObserver),
+        );
         assert!(provider.is_ok());
     }
 
     #[test]
     fn resilient_provider_errors_for_invalid_primary() {
         let reliability = crate::config::ReliabilityConfig::default();
-        let provider = create_resilient_provider("totally-invalid", Some("sk-test"), &reliability);
+        let provider = create_resilient_provider(
+            "totally-invalid",
+            Some("sk-test"),
+            &reliability,
+            Arc::new(crate::observability::NoopObserver),
+        );
         assert!(provider.is_err());
     }
 
@@ -618,6 +709,23 @@ mod tests {
 
     // ── API error sanitization ───────────────────────────────
 
+    #[test]
+    fn parse_retry_after_seconds_and_http_date() {
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_retry_after("120", now), Some(Duration::from_mins(2)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
     #[test]
     fn sanitize_scrubs_sk_prefix() {
         let input = "request failed: sk-1234567890abcdef";
@@ -0,0 +1,475 @@
+//! Model discovery: live lists from provider APIs, with a curated fallback.
+//!
+//! Used by `jarvis models` and the onboarding wizard's model picker.
+
+use super::traits::ModelInfo;
+use crate::config::Config;
+use anyhow::Result;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Upper bound for fetching a live list, so offline machines fail fast.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Recommended models per provider, with a short description.
+#[allow(clippy::too_many_lines)]
+pub fn curated(provider: &str) -> &'static [(&'static str, &'static str)] {
+    match provider {
+        "openrouter" => &[
+            (
+                "anthropic/claude-sonnet-4-20250514",
+                "Claude Sonnet 4 (balanced, recommended)",
+            ),
+            (
+                "anthropic/claude-3.5-sonnet",
+                "Claude 3.5 Sonnet (fast, affordable)",
+            ),
+            ("openai/gpt-4o", "GPT-4o (OpenAI flagship)"),
+            ("openai/gpt-4o-mini", "GPT-4o Mini (fast, cheap)"),
+            (
+                "google/gemini-2.0-flash-001",
+                "Gemini 2.0 Flash (Google, fast)",
+            ),
+            (
+                "meta-llama/llama-3.3-70b-instruct",
+                "Llama 3.3 70B (open source)",
+            ),
+            ("deepseek/deepseek-chat", "DeepSeek Chat (affordable)"),
+        ],
+        "anthropic" => &[
+            (
+                "claude-sonnet-4-20250514",
+                "Claude Sonnet 4 (balanced, recommended)",
+            ),
+            ("claude-3-5-sonnet-20241022", "Claude 3.5 Sonnet (fast)"),
+            (
+                "claude-3-5-haiku-20241022",
+                "Claude 3.5 Haiku (fastest, cheapest)",
+            ),
+        ],
+        "openai" => &[
+            ("gpt-4o", "GPT-4o (flagship)"),
+            ("gpt-4o-mini", "GPT-4o Mini (fast, cheap)"),
+            ("o1-mini", "o1-mini (reasoning)"),
+        ],
+        "venice" => &[
+            ("llama-3.3-70b", "Llama 3.3 70B (default, fast)"),
+            ("claude-opus-45", "Claude Opus 4.5 via Venice (strongest)"),
+            ("llama-3.1-405b", "Llama 3.1 405B (largest open source)"),
+        ],
+        "groq" => &[
+            (
+                "llama-3.3-70b-versatile",
+                "Llama 3.3 70B (fast, recommended)",
+            ),
+            ("llama-3.1-8b-instant", "Llama 3.1 8B (instant)"),
+            ("mixtral-8x7b-32768", "Mixtral 8x7B (32K context)"),
+        ],
+        "mistral" => &[
+            ("mistral-large-latest", "Mistral Large (flagship)"),
+            ("codestral-latest", "Codestral (code-focused)"),
+            ("mistral-small-latest", "Mistral Small (fast, cheap)"),
+        ],
+        "deepseek" => &[
+            ("deepseek-chat", "DeepSeek Chat (V3, recommended)"),
+            ("deepseek-reasoner", "DeepSeek Reasoner (R1)"),
+        ],
+        "xai" => &[
+            ("grok-3", "Grok 3 (flagship)"),
+            ("grok-3-mini", "Grok 3 Mini (fast)"),
+        ],
+        "perplexity" => &[
+            ("sonar-pro", "Sonar Pro (search + reasoning)"),
+            ("sonar", "Sonar (search, fast)"),
+        ],
+        "fireworks" => &[
+            (
+                "accounts/fireworks/models/llama-v3p3-70b-instruct",
+                "Llama 3.3 70B",
+            ),
+            (
+                "accounts/fireworks/models/mixtral-8x22b-instruct",
+                "Mixtral 8x22B",
+            ),
+        ],
+        "together" => &[
+            (
+                "meta-llama/Meta-Llama-3.1-70B-Instruct-Turbo",
+                "Llama 3.1 70B Turbo",
+            ),
+            (
+                "meta-llama/Meta-Llama-3.1-8B-Instruct-Turbo",
+                "Llama 3.1 8B Turbo",
+            ),
+            ("mistralai/Mixtral-8x22B-Instruct-v0.1", "Mixtral 8x22B"),
+        ],
+        "cohere" => &[
+            ("command-r-plus", "Command R+ (flagship)"),
+            ("command-r", "Command R (fast)"),
+        ],
+        "moonshot" => &[
+            ("moonshot-v1-128k", "Moonshot V1 128K"),
+            ("moonshot-v1-32k", "Moonshot V1 32K"),
+        ],
+        "glm" => &[
+            ("glm-4-plus", "GLM-4 Plus (flagship)"),
+            ("glm-4-flash", "GLM-4 Flash (fast)"),
+        ],
+        "minimax" => &[
+            ("abab6.5s-chat", "ABAB 6.5s Chat"),
+            ("abab6.5-chat", "ABAB 6.5 Chat"),
+        ],
+        "ollama" => &[
+            ("llama3.2", "Llama 3.2 (recommended local)"),
+            ("mistral", "Mistral 7B"),
+            ("codellama", "Code Llama"),
+            ("phi3", "Phi-3 (small, fast)"),
+        ],
+        "gemini" | "google" | "google-gemini" => &[
+            ("gemini-2.0-flash", "Gemini 2.0 Flash (fast, recommended)"),
+            (
+                "gemini-2.0-flash-lite",
+                "Gemini 2.0 Flash Lite (fastest, cheapest)",
+            ),
+            ("gemini-1.5-pro", "Gemini 1.5 Pro (best quality)"),
+            ("gemini-1.5-flash", "Gemini 1.5 Flash (balanced)"),
+        ],
+        _ => &[],
+    }
+}
+
+/// [`curated`] as [`ModelInfo`]s.
+pub fn curated_models(provider: &str) -> Vec<ModelInfo> {
+    curated(provider)
+        .iter()
+        .map(|(id, description)| ModelInfo {
+            id: (*id).to_string(),
+            description: Some((*description).to_string()),
+            ..ModelInfo::default()
+        })
+        .collect()
+}
+
+/// Models for one provider, and where they came from.
+#[derive(Debug)]
+pub struct ModelListing {
+    pub models: Vec<ModelInfo>,
+    /// Listed by the API (as opposed to the curated fallback).
+    pub live: bool,
+    /// Why the live list is missing, if fetching it failed.
+    pub error: Option<String>,
+}
+
+/// The provider's live model list, or the curated list when it has no
+/// listing endpoint or can't be reached.
+pub async fn fetch(provider_name: &str, api_key: Option<&str>) -> ModelListing {
+    let result = match super::create_provider(provider_name, api_key) {
+        Ok(provider) => tokio::time::timeout(FETCH_TIMEOUT, provider.list_models())
+            .await
+            .unwrap_or_else(|_| {
+                Err(anyhow::anyhow!(
+                    "请求超时（>{}秒）",
+                    FETCH_TIMEOUT.as_secs()
+                ))
+            }),
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(models) if !models.is_empty() => ModelListing {
+            models,
+            live: true,
+            error: None,
+        },
+        Ok(_) => ModelListing {
+            models: curated_models(provider_name),
+            live: false,
+            error: None,
+        },
+        Err(e) => ModelListing {
+            models: curated_models(provider_name),
+            live: false,
+            error: Some(super::sanitize_api_error(&e.to_string())),
+        },
+    }
+}
+
+/// Parse an OpenAI-style `{"data": [{"id": ...}]}` list. `OpenRouter` adds
+/// `name`, `context_length` and per-token `pricing`, which are kept.
+pub fn parse_openai_models(body: &Value) -> Vec<ModelInfo> {
+    let mut models: Vec<ModelInfo> = body
+        .get("data")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|model| {
+            let id = model.get("id")?.as_str()?;
+            let price = |kind: &str| per_million(model.get("pricing")?.get(kind)?);
+            Some(ModelInfo {
+                id: id.to_string(),
+                description: model
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .filter(|name| *name != id)
+                    .map(str::to_string),
+                context_length: ["context_length", "context_window"]
+                    .iter()
+                    .find_map(|key| model.get(*key)?.as_u64()),
+                prompt_price: price("prompt"),
+                completion_price: price("completion"),
+            })
+        })
+        .collect();
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    models
+}
+
+/// Per-token USD price (a string or number) to USD per million tokens.
+/// Negative prices mean "varies" (e.g. `openrouter/auto`).
+fn per_million(price: &Value) -> Option<f64> {
+    let per_token = match price {
+        Value::String(s) => s.parse::<f64>().ok()?,
+        other => other.as_f64()?,
+    };
+    (per_token >= 0.0).then_some(per_token * 1_000_000.0)
+}
+
+/// Parse Ollama's `{"models": [{"name": ..., "details": {...}}]}`.
+pub fn parse_ollama_models(body: &Value) -> Vec<ModelInfo> {
+    let mut models: Vec<ModelInfo> = body
+        .get("models")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|model| {
+            let details = model.get("details");
+            let detail = |key: &str| details?.get(key)?.as_str();
+            let description = [detail("parameter_size"), detail("quantization_level")]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ");
+            Some(ModelInfo {
+                id: model.get("name")?.as_str()?.to_string(),
+                description: (!description.is_empty()).then_some(description),
+                ..ModelInfo::default()
+            })
+        })
+        .collect();
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    models
+}
+
+/// `128000` → `128K`, `1000000` → `1M`.
+fn format_tokens(tokens: u64) -> String {
+    if tokens >= 1_000_000 && tokens.is_multiple_of(100_000) {
+        match (tokens / 100_000) % 10 {
+            0 => format!("{}M", tokens / 1_000_000),
+            tenths => format!("{}.{tenths}M", tokens / 1_000_000),
+        }
+    } else if tokens >= 1000 {
+        format!("{}K", tokens / 1000)
+    } else {
+        tokens.to_string()
+    }
+}
+
+/// `3.0` → `3`, `0.075` → `0.075`.
+fn format_price(price: f64) -> String {
+    let text = format!("{price:.3}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Context length, pricing and description, whichever are known.
+fn details(model: &ModelInfo) -> String {
+    let mut parts = Vec::new();
+    if let Some(tokens) = model.context_length {
+        parts.push(format!("{} 上下文", format_tokens(tokens)));
+    }
+    if let (Some(prompt), Some(completion)) = (model.prompt_price, model.completion_price) {
+        parts.push(format!(
+            "${} / ${} 每百万 token（输入/输出）",
+            format_price(prompt),
+            format_price(completion)
+        ));
+    }
+    if let Some(description) = &model.description {
+        parts.push(description.clone());
+    }
+    parts.join("  ")
+}
+
+/// `jarvis models [--provider X]`.
+pub async fn run(config: &Config, provider: Option<&str>) -> Result<()> {
+    let default_provider = config.default_provider.as_deref().unwrap_or("openrouter");
+    let name = provider.unwrap_or(default_provider);
+    // Another provider's key comes from its own env var, as for fallbacks
+    let api_key = if name == default_provider {
+        config.api_key.clone()
+    } else {
+        super::provider_env_key(name).or_else(|| config.api_key.clone())
+    };
+
+    let listing = fetch(name, api_key.as_deref()).await;
+    if let Some(error) = &listing.error {
+        println!("⚠️  无法获取 {name} 的在线模型列表：{error}");
+        println!("   以下为内置推荐列表，可能不是最新的。");
+        println!();
+    }
+    if listing.models.is_empty() {
+        println!("{name} 没有内置推荐列表，请查阅其文档获取模型名称。");
+        return Ok(());
+    }
+
+    let source = if listing.live {
+        "来自 API"
+    } else {
+        "内置推荐"
+    };
+    println!(
+        "📚 {name} 的模型（{source}，共 {} 个）：",
+        listing.models.len()
+    );
+    println!();
+    let current = if name == default_provider {
+        config.default_model.as_deref()
+    } else {
+        None
+    };
+    let width = listing
+        .models
+        .iter()
+        .map(|m| m.id.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(48);
+    for model in &listing.models {
+        let marker = if Some(model.id.as_str()) == current {
+            "*"
+        } else {
+            " "
+        };
+        println!("  {marker} {:width$}  {}", model.id, details(model));
+    }
+    if listing
+        .models
+        .iter()
+        .any(|m| Some(m.id.as_str()) == current)
+    {
+        println!();
+        println!("  * 当前默认模型");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_openrouter_models_with_context_and_pricing() {
+        let body = json!({
+            "data": [
+                {
+                    "id": "openai/gpt-4o-mini",
+                    "name": "OpenAI: GPT-4o-mini",
+                    "context_length": 128_000,
+                    "pricing": { "prompt": "0.00000015", "completion": "0.0000006" }
+                },
+                {
+                    "id": "anthropic/claude-sonnet-4",
+                    "name": "Anthropic: Claude Sonnet 4",
+                    "context_length": 200_000,
+                    "pricing": { "prompt": "0.000003", "completion": "0.000015" }
+                },
+                { "id": "openrouter/auto", "pricing": { "prompt": "-1", "completion": "-1" } },
+                { "name": "no id" }
+            ]
+        });
+        let models = parse_openai_models(&body);
+        let ids: Vec<&str> = models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "anthropic/claude-sonnet-4",
+                "openai/gpt-4o-mini",
+                "openrouter/auto"
+            ]
+        );
+        assert_eq!(models[0].context_length, Some(200_000));
+        assert_eq!(
+            details(&models[0]),
+            "200K 上下文  $3 / $15 每百万 token（输入/输出）  Anthropic: Claude Sonnet 4"
+        );
+        assert_eq!(
+            details(&models[1]),
+            "128K 上下文  $0.15 / $0.6 每百万 token（输入/输出）  OpenAI: GPT-4o-mini"
+        );
+        assert_eq!(models[2].prompt_price, None);
+        assert_eq!(details(&models[2]), "");
+    }
+
+    #[test]
+    fn parses_plain_openai_models() {
+        let body = json!({
+            "object": "list",
+            "data": [
+                { "id": "gpt-4o", "object": "model", "owned_by": "system" },
+                { "id": "gpt-4o-mini", "object": "model", "context_window": 128_000 }
+            ]
+        });
+        let models = parse_openai_models(&body);
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].description, None);
+        assert_eq!(models[1].context_length, Some(128_000));
+        assert!(parse_openai_models(&json!({ "error": "nope" })).is_empty());
+    }
+
+    #[test]
+    fn parses_ollama_tags() {
+        let body = json!({
+            "models": [
+                {
+                    "name": "llama3.2:latest",
+                    "details": { "parameter_size": "3.2B", "quantization_level": "Q4_K_M" }
+                },
+                { "name": "custom:dev" }
+            ]
+        });
+        let models = parse_ollama_models(&body);
+        assert_eq!(models[0].id, "custom:dev");
+        assert_eq!(models[0].description, None);
+        assert_eq!(models[1].id, "llama3.2:latest");
+        assert_eq!(models[1].description.as_deref(), Some("3.2B Q4_K_M"));
+    }
+
+    #[test]
+    fn formats_token_counts_and_prices() {
+        assert_eq!(format_tokens(512), "512");
+        assert_eq!(format_tokens(131_072), "131K");
+        assert_eq!(format_tokens(1_000_000), "1M");
+        assert_eq!(format_tokens(1_048_576), "1048K");
+        assert_eq!(format_tokens(2_500_000), "2.5M");
+        assert_eq!(format_price(3.0), "3");
+        assert_eq!(format_price(0.075), "0.075");
+        assert_eq!(format_price(0.0), "0");
+    }
+
+    #[tokio::test]
+    async fn providers_without_a_listing_use_the_curated_list() {
+        // Anthropic keeps the default (empty) list_models, so no request is made
+        let listing = fetch("anthropic", Some("sk-ant-test")).await;
+        assert!(!listing.live);
+        assert!(listing.error.is_none());
+        assert_eq!(listing.models, curated_models("anthropic"));
+        assert_eq!(listing.models[0].id, "claude-sonnet-4-20250514");
+    }
+
+    #[tokio::test]
+    async fn unknown_providers_report_the_error() {
+        let listing = fetch("no-such-provider", None).await;
+        assert!(!listing.live);
+        assert!(listing.models.is_empty());
+        assert!(listing.error.unwrap().contains("no-such-provider"));
+    }
+}
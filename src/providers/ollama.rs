@@ -1,4 +1,4 @@
-use crate::providers::traits::Provider;
+use crate::providers::traits::{ModelInfo, Provider};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -55,6 +55,31 @@ impl OllamaProvider {
 
 #[async_trait]
 impl Provider for OllamaProvider {
+    async fn ping(&self) -> anyhow::Result<()> {
+        let response = self
+            .client
+            .get(format!("{}/api/tags", self.base_url))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(super::api_error("Ollama", response).await);
+        }
+        Ok(())
+    }
+
+    /// Locally pulled models (`GET /api/tags`).
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let response = self
+            .client
+            .get(format!("{}/api/tags", self.base_url))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(super::api_error("Ollama", response).await);
+        }
+        Ok(super::models::parse_ollama_models(&response.json().await?))
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
@@ -1,10 +1,12 @@
-use crate::providers::traits::Provider;
+use crate::providers::structured::ResponseFormat;
+use crate::providers::traits::{ModelInfo, Provider};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 pub struct OpenAiProvider {
     api_key: Option<String>,
+    base_url: String,
     client: Client,
 }
 
@@ -13,6 +15,8 @@ struct ChatRequest {
     model: String,
     messages: Vec<Message>,
     temperature: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -38,8 +42,15 @@ struct ResponseMessage {
 
 impl OpenAiProvider {
     pub fn new(api_key: Option<&str>) -> Self {
+        Self::with_base_url(api_key, None)
+    }
+
+    pub fn with_base_url(api_key: Option<&str>, base_url: Option<&str>) -> Self {
         Self {
             api_key: api_key.map(ToString::to_string),
+            base_url: base_url
+                .map_or("https://api.openai.com/v1", |u| u.trim_end_matches('/'))
+                .to_string(),
             client: Client::builder()
                 .timeout(std::time::Duration::from_secs(120))
                 .connect_timeout(std::time::Duration::from_secs(10))
@@ -49,14 +60,14 @@ impl OpenAiProvider {
     }
 }
 
-#[async_trait]
-impl Provider for OpenAiProvider {
-    async fn chat_with_system(
+impl OpenAiProvider {
+    async fn send_chat(
         &self,
         system_prompt: Option<&str>,
         message: &str,
         model: &str,
         temperature: f64,
+        response_format: Option<serde_json::Value>,
     ) -> anyhow::Result<String> {
         let api_key = self.api_key.as_ref().ok_or_else(|| {
             anyhow::anyhow!("OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.")
@@ -80,11 +91,12 @@ impl Provider for OpenAiProvider {
             model: model.to_string(),
             messages,
             temperature,
+            response_format,
         };
 
         let response = self
             .client
-            .post("https://api.openai.com/v1/chat/completions")
+            .post(format!("{}/chat/completions", self.base_url))
             .header("Authorization", format!("Bearer {api_key}"))
             .json(&request)
             .send()
@@ -105,6 +117,72 @@ impl Provider for OpenAiProvider {
     }
 }
 
+#[async_trait]
+impl Provider for OpenAiProvider {
+    async fn ping(&self) -> anyhow::Result<()> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.")
+        })?;
+        let response = self
+            .client
+            .get(format!("{}/models", self.base_url))
+            .header("Authorization", format!("Bearer {api_key}"))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(super::api_error("OpenAI", response).await);
+        }
+        Ok(())
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.")
+        })?;
+        let response = self
+            .client
+            .get(format!("{}/models", self.base_url))
+            .header("Authorization", format!("Bearer {api_key}"))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(super::api_error("OpenAI", response).await);
+        }
+        Ok(super::models::parse_openai_models(&response.json().await?))
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.send_chat(system_prompt, message, model, temperature, None)
+            .await
+    }
+
+    async fn chat_structured(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+        format: &ResponseFormat,
+    ) -> anyhow::Result<String> {
+        // JSON mode requires the prompt itself to mention JSON
+        let system = format.system_hint(system_prompt);
+        self.send_chat(
+            Some(&system),
+            message,
+            model,
+            temperature,
+            Some(format.to_openai()),
+        )
+        .await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,6 +237,7 @@ mod tests {
                 },
             ],
             temperature: 0.7,
+            response_format: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"role\":\"system\""));
@@ -175,12 +254,109 @@ mod tests {
                 content: "hello".to_string(),
             }],
             temperature: 0.0,
+            response_format: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(!json.contains("system"));
         assert!(json.contains("\"temperature\":0.0"));
     }
 
+    #[test]
+    fn request_serializes_response_format() {
+        let req = ChatRequest {
+            model: "gpt-4o".to_string(),
+            messages: vec![],
+            temperature: 0.0,
+            response_format: Some(ResponseFormat::JsonObject.to_openai()),
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains(r#""response_format":{"type":"json_object"}"#));
+    }
+
+    /// Mock `/chat/completions` that records request bodies and replies with `content`.
+    async fn spawn_mock_openai(
+        content: &'static str,
+    ) -> (
+        String,
+        std::sync::Arc<std::sync::Mutex<Vec<serde_json::Value>>>,
+    ) {
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = std::sync::Arc::clone(&requests);
+        let app = axum::Router::new().route(
+            "/v1/chat/completions",
+            axum::routing::post(move |axum::Json(body): axum::Json<serde_json::Value>| {
+                seen.lock().unwrap().push(body);
+                async move {
+                    axum::Json(serde_json::json!({
+                        "choices": [{"message": {"content": content}}]
+                    }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{addr}/v1"), requests)
+    }
+
+    #[tokio::test]
+    async fn chat_json_returns_valid_json_from_mock() {
+        let (base_url, requests) =
+            spawn_mock_openai(r#"{"summary": "all good", "issues": 0}"#).await;
+        let provider = OpenAiProvider::with_base_url(Some("sk-test"), Some(&base_url));
+        let format = ResponseFormat::json_schema(
+            "status",
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "summary": { "type": "string" },
+                    "issues": { "type": "integer" }
+                },
+                "required": ["summary", "issues"]
+            }),
+        );
+
+        let value = provider
+            .chat_json(Some("You are Jarvis"), "status?", "gpt-4o", 0.0, &format)
+            .await
+            .unwrap();
+        assert_eq!(value["summary"], "all good");
+        assert_eq!(value["issues"], 0);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        let body = &requests[0];
+        assert_eq!(body["response_format"]["type"], "json_schema");
+        assert_eq!(body["response_format"]["json_schema"]["name"], "status");
+        let system = body["messages"][0]["content"].as_str().unwrap();
+        assert!(system.starts_with("You are Jarvis"));
+        assert!(system.contains("JSON"));
+    }
+
+    #[tokio::test]
+    async fn chat_json_repairs_fenced_reply_from_mock() {
+        let (base_url, _) = spawn_mock_openai("```json\n{\"ok\": true}\n```").await;
+        let provider = OpenAiProvider::with_base_url(Some("sk-test"), Some(&base_url));
+        let value = provider
+            .chat_json(None, "ok?", "gpt-4o", 0.0, &ResponseFormat::JsonObject)
+            .await
+            .unwrap();
+        assert_eq!(value, serde_json::json!({"ok": true}));
+    }
+
+    #[tokio::test]
+    async fn chat_json_rejects_prose_from_mock() {
+        let (base_url, _) = spawn_mock_openai("I cannot answer in JSON.").await;
+        let provider = OpenAiProvider::with_base_url(Some("sk-test"), Some(&base_url));
+        let err = provider
+            .chat_json(None, "ok?", "gpt-4o", 0.0, &ResponseFormat::JsonObject)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("JSON"));
+    }
+
     #[test]
     fn response_deserializes_single_choice() {
         let json = r#"{"choices":[{"message":{"content":"Hi!"}}]}"#;
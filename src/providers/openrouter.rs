@@ -1,4 +1,4 @@
-use crate::providers::traits::Provider;
+use crate::providers::traits::{ModelInfo, Provider};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -65,6 +65,37 @@ impl Provider for OpenRouterProvider {
         Ok(())
     }
 
+    async fn ping(&self) -> anyhow::Result<()> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "OpenRouter API key not set. Run `jarvis onboard` or set OPENROUTER_API_KEY env var."
+            )
+        })?;
+        let response = self
+            .client
+            .get("https://openrouter.ai/api/v1/auth/key")
+            .header("Authorization", format!("Bearer {api_key}"))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(super::api_error("OpenRouter", response).await);
+        }
+        Ok(())
+    }
+
+    /// `GET /models` is public, and reports context length and pricing.
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let mut request = self.client.get("https://openrouter.ai/api/v1/models");
+        if let Some(api_key) = self.api_key.as_ref() {
+            request = request.header("Authorization", format!("Bearer {api_key}"));
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(super::api_error("OpenRouter", response).await);
+        }
+        Ok(super::models::parse_openai_models(&response.json().await?))
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
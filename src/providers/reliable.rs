@@ -1,9 +1,20 @@
-use super::traits::{ChatMessage, ChatResponse, Provider, ToolDefinition};
+use super::structured::ResponseFormat;
+use super::traits::{ChatMessage, ChatResponse, ModelInfo, Provider, ToolDefinition};
+use super::ProviderHttpError;
+use crate::config::RetryOn;
+use crate::observability::{NoopObserver, Observer, ObserverEvent};
 use async_trait::async_trait;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Check if an error is non-retryable (client errors that won't resolve with retries).
 fn is_non_retryable(err: &anyhow::Error) -> bool {
+    // Typed HTTP errors from `super::api_error`: only rate limits, timeouts and
+    // transient gateway/server failures are worth another attempt
+    if let Some(http_err) = err.downcast_ref::<ProviderHttpError>() {
+        return !matches!(http_err.status, 408 | 429 | 500 | 502 | 503 | 504);
+    }
     // Check for reqwest status errors (returned by .error_for_status())
     if let Some(reqwest_err) = err.downcast_ref::<reqwest::Error>() {
         if let Some(status) = reqwest_err.status() {
@@ -26,11 +37,83 @@ fn is_non_retryable(err: &anyhow::Error) -> bool {
     false
 }
 
+/// Server-requested delay (`Retry-After`) attached to a provider error, if any.
+fn retry_after(err: &anyhow::Error) -> Option<Duration> {
+    err.downcast_ref::<ProviderHttpError>()
+        .and_then(|e| e.retry_after)
+}
+
+/// Classify a retryable error for `provider_retry_on`, with the reason shown
+/// in retry events: the HTTP status, `timeout`, or `error` for failures
+/// without a status (connection errors and the like count as timeouts).
+fn retry_reason(err: &anyhow::Error) -> (RetryOn, String) {
+    let status = err
+        .downcast_ref::<ProviderHttpError>()
+        .map(|e| e.status)
+        .or_else(|| {
+            err.downcast_ref::<reqwest::Error>()
+                .and_then(reqwest::Error::status)
+                .map(|s| s.as_u16())
+        })
+        .or_else(|| {
+            // Untyped errors: the first status-looking number in the message
+            err.to_string()
+                .split(|c: char| !c.is_ascii_digit())
+                .filter_map(|word| word.parse::<u16>().ok())
+                .find(|code| matches!(code, 408 | 429 | 500..=599))
+        });
+    match status {
+        Some(429) => (RetryOn::RateLimited, "429".into()),
+        Some(code @ 500..=599) => (RetryOn::ServerError, code.to_string()),
+        Some(code) => (RetryOn::Timeout, code.to_string()),
+        None if err
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(reqwest::Error::is_timeout) =>
+        {
+            (RetryOn::Timeout, "timeout".into())
+        }
+        None => (RetryOn::Timeout, "error".into()),
+    }
+}
+
+/// `backoff` with up to a quarter taken off, so clients that failed together
+/// don't retry in lockstep.
+fn jittered(backoff: Duration) -> Duration {
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    backoff.saturating_sub(backoff / 4 * (seed % 1000) / 1000)
+}
+
+/// Strip a `vendor/` prefix when it names the provider itself
+/// (`anthropic/claude-sonnet-4` → `claude-sonnet-4` for the `anthropic` provider).
+fn strip_vendor_prefix<'a>(provider: &str, model: &'a str) -> &'a str {
+    let Some((vendor, name)) = model.split_once('/') else {
+        return model;
+    };
+    let same_vendor = vendor == provider
+        || (vendor == "google" && matches!(provider, "gemini" | "google-gemini"));
+    if same_vendor {
+        name
+    } else {
+        model
+    }
+}
+
 /// Provider wrapper with retry + fallback behavior.
+///
+/// Retryable errors (429/408/5xx/network) are retried with jittered exponential
+/// backoff (or the server's `Retry-After`) when `retry_on` selects them, then
+/// the next provider in the chain is tried. Non-retryable errors (auth failures,
+/// malformed requests) are returned immediately — another provider won't fix them.
 pub struct ReliableProvider {
     providers: Vec<(String, Box<dyn Provider>)>,
     max_retries: u32,
     base_backoff_ms: u64,
+    max_backoff: Duration,
+    retry_on: Vec<RetryOn>,
+    fallback_models: HashMap<String, String>,
+    observer: Arc<dyn Observer>,
 }
 
 impl ReliableProvider {
@@ -43,7 +126,125 @@ impl ReliableProvider {
             providers,
             max_retries,
             base_backoff_ms: base_backoff_ms.max(50),
+            max_backoff: Duration::from_mins(1),
+            retry_on: vec![RetryOn::Timeout, RetryOn::RateLimited, RetryOn::ServerError],
+            fallback_models: HashMap::new(),
+            observer: Arc::new(NoopObserver),
+        }
+    }
+
+    /// Upper bound on any single retry delay, including `Retry-After` hints.
+    #[must_use]
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Failures retried on the same provider; others fail over right away.
+    #[must_use]
+    pub fn with_retry_on(mut self, retry_on: Vec<RetryOn>) -> Self {
+        self.retry_on = retry_on;
+        self
+    }
+
+    /// Delay before the next attempt: the server's `Retry-After` when given,
+    /// otherwise the jittered exponential backoff, capped at `max_backoff`.
+    fn retry_delay(&self, err: &anyhow::Error, backoff_ms: u64) -> Duration {
+        retry_after(err)
+            .unwrap_or_else(|| jittered(Duration::from_millis(backoff_ms)))
+            .min(self.max_backoff)
+    }
+
+    /// Wait before retry `attempt` (1-based) after `err` and double
+    /// `backoff_ms`. Returns `false` without waiting when `provider_retry_on`
+    /// excludes this kind of failure.
+    async fn pause_before_retry(
+        &self,
+        provider_name: &str,
+        attempt: u32,
+        err: &anyhow::Error,
+        backoff_ms: &mut u64,
+    ) -> bool {
+        let (kind, reason) = retry_reason(err);
+        if !self.retry_on.contains(&kind) {
+            tracing::warn!(
+                provider = provider_name,
+                reason,
+                "该类错误不在 provider_retry_on 中，不再重试"
+            );
+            return false;
+        }
+        let backoff = self.retry_delay(err, *backoff_ms);
+        tracing::warn!(
+            provider = provider_name,
+            attempt,
+            max_retries = self.max_retries,
+            reason,
+            backoff_ms = u64::try_from(backoff.as_millis()).unwrap_or(u64::MAX),
+            "Provider 调用失败，正在重试"
+        );
+        self.observer.record_event(&ObserverEvent::ProviderRetry {
+            provider: provider_name.to_string(),
+            attempt,
+            max_retries: self.max_retries,
+            reason,
+            backoff,
+        });
+        tokio::time::sleep(backoff).await;
+        let max_backoff_ms = u64::try_from(self.max_backoff.as_millis()).unwrap_or(u64::MAX);
+        *backoff_ms = backoff_ms.saturating_mul(2).min(max_backoff_ms);
+        true
+    }
+
+    /// Per-provider model overrides for fallbacks (keyed by provider name).
+    #[must_use]
+    pub fn with_fallback_models(mut self, fallback_models: HashMap<String, String>) -> Self {
+        self.fallback_models = fallback_models;
+        self
+    }
+
+    /// Observer notified of each provider attempt and when a fallback
+    /// provider serves a request.
+    #[must_use]
+    pub fn with_observer(mut self, observer: Arc<dyn Observer>) -> Self {
+        self.observer = observer;
+        self
+    }
+
+    /// Model to request from the provider at `index` in the chain.
+    fn model_for(&self, index: usize, provider_name: &str, model: &str) -> String {
+        if index == 0 {
+            return model.to_string();
         }
+        self.fallback_models
+            .get(provider_name)
+            .cloned()
+            .unwrap_or_else(|| strip_vendor_prefix(provider_name, model).to_string())
+    }
+
+    fn record_call(&self, provider_name: &str, model: &str, started: Instant, success: bool) {
+        self.observer.record_event(&ObserverEvent::ProviderCall {
+            provider: provider_name.to_string(),
+            model: model.to_string(),
+            duration: started.elapsed(),
+            success,
+        });
+    }
+
+    fn record_fallback(&self, provider_name: &str, model: &str) {
+        let primary = self.providers.first().map_or("", |(name, _)| name.as_str());
+        tracing::info!(
+            primary,
+            provider = provider_name,
+            model,
+            "请求由备用 Provider 完成"
+        );
+        self.observer
+            .record_event(&ObserverEvent::ProviderFallback {
+                primary: primary.to_string(),
+                served_by: provider_name.to_string(),
+                model: model.to_string(),
+            });
     }
 }
 
@@ -59,6 +260,22 @@ impl Provider for ReliableProvider {
         Ok(())
     }
 
+    /// Pings the primary provider; fallbacks only matter once it fails.
+    async fn ping(&self) -> anyhow::Result<()> {
+        match self.providers.first() {
+            Some((_, provider)) => provider.ping().await,
+            None => anyhow::bail!("未配置 Provider"),
+        }
+    }
+
+    /// Lists the primary provider's models.
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        match self.providers.first() {
+            Some((_, provider)) => provider.list_models().await,
+            None => Ok(Vec::new()),
+        }
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
@@ -68,16 +285,21 @@ impl Provider for ReliableProvider {
     ) -> anyhow::Result<String> {
         let mut failures = Vec::new();
 
-        for (provider_name, provider) in &self.providers {
+        for (index, (provider_name, provider)) in self.providers.iter().enumerate() {
             let mut backoff_ms = self.base_backoff_ms;
+            let model = self.model_for(index, provider_name, model);
 
             for attempt in 0..=self.max_retries {
-                match provider
-                    .chat_with_system(system_prompt, message, model, temperature)
-                    .await
-                {
+                let started = Instant::now();
+                let result = provider
+                    .chat_with_system(system_prompt, message, &model, temperature)
+                    .await;
+                self.record_call(provider_name, &model, started, result.is_ok());
+                match result {
                     Ok(resp) => {
-                        if attempt > 0 {
+                        if index > 0 {
+                            self.record_fallback(provider_name, &model);
+                        } else if attempt > 0 {
                             tracing::info!(
                                 provider = provider_name,
                                 attempt,
@@ -87,31 +309,27 @@ impl Provider for ReliableProvider {
                         return Ok(resp);
                     }
                     Err(e) => {
-                        let non_retryable = is_non_retryable(&e);
+                        if is_non_retryable(&e) {
+                            tracing::warn!(
+                                provider = provider_name,
+                                "不可重试的错误（认证或请求错误），不切换 Provider"
+                            );
+                            return Err(e);
+                        }
+
                         failures.push(format!(
                             "{provider_name} attempt {}/{}: {e}",
                             attempt + 1,
                             self.max_retries + 1
                         ));
 
-                        if non_retryable {
-                            tracing::warn!(
-                                provider = provider_name,
-                                "不可重试的错误，正在切换 Provider"
-                            );
+                        if attempt == self.max_retries
+                            || !self
+                                .pause_before_retry(provider_name, attempt + 1, &e, &mut backoff_ms)
+                                .await
+                        {
                             break;
                         }
-
-                        if attempt < self.max_retries {
-                            tracing::warn!(
-                                provider = provider_name,
-                                attempt = attempt + 1,
-                                max_retries = self.max_retries,
-                                "Provider 调用失败，正在重试"
-                            );
-                            tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
-                            backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
-                        }
                     }
                 }
             }
@@ -128,19 +346,25 @@ impl Provider for ReliableProvider {
         tools: &[ToolDefinition],
         model: &str,
         temperature: f64,
+        response_format: Option<&ResponseFormat>,
     ) -> anyhow::Result<ChatResponse> {
         let mut failures = Vec::new();
 
-        for (provider_name, provider) in &self.providers {
+        for (index, (provider_name, provider)) in self.providers.iter().enumerate() {
             let mut backoff_ms = self.base_backoff_ms;
+            let model = self.model_for(index, provider_name, model);
 
             for attempt in 0..=self.max_retries {
-                match provider
-                    .chat_with_tools(messages, tools, model, temperature)
-                    .await
-                {
+                let started = Instant::now();
+                let result = provider
+                    .chat_with_tools(messages, tools, &model, temperature, response_format)
+                    .await;
+                self.record_call(provider_name, &model, started, result.is_ok());
+                match result {
                     Ok(resp) => {
-                        if attempt > 0 {
+                        if index > 0 {
+                            self.record_fallback(provider_name, &model);
+                        } else if attempt > 0 {
                             tracing::info!(
                                 provider = provider_name,
                                 attempt,
@@ -150,31 +374,27 @@ impl Provider for ReliableProvider {
                         return Ok(resp);
                     }
                     Err(e) => {
-                        let non_retryable = is_non_retryable(&e);
+                        if is_non_retryable(&e) {
+                            tracing::warn!(
+                                provider = provider_name,
+                                "不可重试的错误（认证或请求错误），不切换 Provider (chat_with_tools)"
+                            );
+                            return Err(e);
+                        }
+
                         failures.push(format!(
                             "{provider_name} attempt {}/{}: {e}",
                             attempt + 1,
                             self.max_retries + 1
                         ));
 
-                        if non_retryable {
-                            tracing::warn!(
-                                provider = provider_name,
-                                "不可重试的错误，正在切换 Provider (chat_with_tools)"
-                            );
+                        if attempt == self.max_retries
+                            || !self
+                                .pause_before_retry(provider_name, attempt + 1, &e, &mut backoff_ms)
+                                .await
+                        {
                             break;
                         }
-
-                        if attempt < self.max_retries {
-                            tracing::warn!(
-                                provider = provider_name,
-                                attempt = attempt + 1,
-                                max_retries = self.max_retries,
-                                "Provider 调用失败，正在重试 (chat_with_tools)"
-                            );
-                            tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
-                            backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
-                        }
                     }
                 }
             }
@@ -196,7 +416,7 @@ impl Provider for ReliableProvider {
 mod tests {
     use super::*;
     use std::sync::atomic::{AtomicUsize, Ordering};
-    use std::sync::Arc;
+    use std::sync::Mutex;
 
     struct MockProvider {
         calls: Arc<AtomicUsize>,
@@ -364,7 +584,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn skips_retries_on_non_retryable_error() {
+    async fn does_not_fall_back_on_non_retryable_error() {
         let primary_calls = Arc::new(AtomicUsize::new(0));
         let fallback_calls = Arc::new(AtomicUsize::new(0));
 
@@ -393,10 +613,420 @@ mod tests {
             1,
         );
 
+        let err = provider
+            .chat("hello", "test", 0.0)
+            .await
+            .expect_err("auth errors must not fall back");
+        assert!(err.to_string().contains("401"));
+        // Primary should have been called only once (no retries), fallback never
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 0);
+    }
+
+    /// Records the model it was asked for; fails every call when `error` is set.
+    struct RecordingProvider {
+        models: Arc<Mutex<Vec<String>>>,
+        error: Option<&'static str>,
+    }
+
+    #[async_trait]
+    impl Provider for RecordingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.models.lock().unwrap().push(model.to_string());
+            match self.error {
+                Some(error) => anyhow::bail!(error),
+                None => Ok(format!("served {model}")),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct FallbackRecorder {
+        events: Mutex<Vec<(String, String, String)>>,
+        attempts: Mutex<Vec<(String, bool)>>,
+        retries: Mutex<Vec<(u32, u32, String)>>,
+    }
+
+    impl Observer for FallbackRecorder {
+        fn record_event(&self, event: &ObserverEvent) {
+            if let ObserverEvent::ProviderFallback {
+                primary,
+                served_by,
+                model,
+            } = event
+            {
+                self.events.lock().unwrap().push((
+                    primary.clone(),
+                    served_by.clone(),
+                    model.clone(),
+                ));
+            }
+            if let ObserverEvent::ProviderCall {
+                provider, success, ..
+            } = event
+            {
+                self.attempts
+                    .lock()
+                    .unwrap()
+                    .push((provider.clone(), *success));
+            }
+            if let ObserverEvent::ProviderRetry {
+                attempt,
+                max_retries,
+                reason,
+                ..
+            } = event
+            {
+                self.retries
+                    .lock()
+                    .unwrap()
+                    .push((*attempt, *max_retries, reason.clone()));
+            }
+        }
+
+        fn record_metric(&self, _metric: &crate::observability::traits::ObserverMetric) {}
+
+        fn name(&self) -> &str {
+            "fallback-recorder"
+        }
+    }
+
+    #[tokio::test]
+    async fn fallback_chain_maps_models_and_reports_serving_provider() {
+        let primary_models = Arc::new(Mutex::new(Vec::new()));
+        let openai_models = Arc::new(Mutex::new(Vec::new()));
+        let anthropic_models = Arc::new(Mutex::new(Vec::new()));
+        let observer = Arc::new(FallbackRecorder::default());
+
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "openrouter".into(),
+                    Box::new(RecordingProvider {
+                        models: Arc::clone(&primary_models),
+                        error: Some("429 Too Many Requests"),
+                    }),
+                ),
+                (
+                    "openai".into(),
+                    Box::new(RecordingProvider {
+                        models: Arc::clone(&openai_models),
+                        error: Some("503 Service Unavailable"),
+                    }),
+                ),
+                (
+                    "anthropic".into(),
+                    Box::new(RecordingProvider {
+                        models: Arc::clone(&anthropic_models),
+                        error: None,
+                    }),
+                ),
+            ],
+            1,
+            1,
+        )
+        .with_fallback_models(HashMap::from([("openai".into(), "gpt-4o".into())]))
+        .with_observer(observer.clone());
+
+        let result = provider
+            .chat("hello", "anthropic/claude-sonnet-4-20250514", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(result, "served claude-sonnet-4-20250514");
+
+        assert_eq!(
+            *primary_models.lock().unwrap(),
+            vec!["anthropic/claude-sonnet-4-20250514"; 2]
+        );
+        assert_eq!(*openai_models.lock().unwrap(), vec!["gpt-4o"; 2]);
+        assert_eq!(
+            *observer.events.lock().unwrap(),
+            vec![(
+                "openrouter".to_string(),
+                "anthropic".to_string(),
+                "claude-sonnet-4-20250514".to_string()
+            )]
+        );
+        assert_eq!(
+            *observer.attempts.lock().unwrap(),
+            vec![
+                ("openrouter".to_string(), false),
+                ("openrouter".to_string(), false),
+                ("openai".to_string(), false),
+                ("openai".to_string(), false),
+                ("anthropic".to_string(), true),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_each_retry_until_success() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let observer = Arc::new(FallbackRecorder::default());
+        let provider = ReliableProvider::new(
+            vec![(
+                "primary".into(),
+                Box::new(MockProvider {
+                    calls: Arc::clone(&calls),
+                    fail_until_attempt: 2,
+                    response: "recovered",
+                    error: "429 Too Many Requests",
+                }),
+            )],
+            5,
+            1,
+        )
+        .with_observer(observer.clone());
+
+        assert_eq!(
+            provider.chat("hello", "test", 0.0).await.unwrap(),
+            "recovered"
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            *observer.retries.lock().unwrap(),
+            vec![(1, 5, "429".to_string()), (2, 5, "429".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let observer = Arc::new(FallbackRecorder::default());
+        let provider = ReliableProvider::new(
+            vec![(
+                "primary".into(),
+                Box::new(MockProvider {
+                    calls: Arc::clone(&calls),
+                    fail_until_attempt: usize::MAX,
+                    response: "never",
+                    error: "503 Service Unavailable",
+                }),
+            )],
+            3,
+            1,
+        )
+        .with_observer(observer.clone());
+
+        let err = provider.chat("hello", "test", 0.0).await.unwrap_err();
+        assert!(err.to_string().contains("primary attempt 4/4"));
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        let retries = observer.retries.lock().unwrap();
+        assert_eq!(retries.iter().map(|r| r.0).collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(retries.iter().all(|r| r.2 == "503"));
+    }
+
+    #[tokio::test]
+    async fn retry_on_selects_failures_retried_before_failover() {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "primary".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&primary_calls),
+                        fail_until_attempt: usize::MAX,
+                        response: "never",
+                        error: "502 Bad Gateway",
+                    }),
+                ),
+                (
+                    "fallback".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&fallback_calls),
+                        fail_until_attempt: 1,
+                        response: "from fallback",
+                        error: "429 Too Many Requests",
+                    }),
+                ),
+            ],
+            2,
+            1,
+        )
+        .with_retry_on(vec![RetryOn::RateLimited]);
+
         let result = provider.chat("hello", "test", 0.0).await.unwrap();
         assert_eq!(result, "from fallback");
-        // Primary should have been called only once (no retries)
+        // 5xx is not selected: straight to the fallback, whose 429 is retried
         assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
-        assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn retry_reasons_follow_status_and_error_kind() {
+        let http = |status: u16| -> anyhow::Error {
+            ProviderHttpError {
+                status,
+                retry_after: None,
+                message: format!("Mock API 错误 ({status})"),
+            }
+            .into()
+        };
+        assert_eq!(
+            retry_reason(&http(429)),
+            (RetryOn::RateLimited, "429".into())
+        );
+        assert_eq!(
+            retry_reason(&http(503)),
+            (RetryOn::ServerError, "503".into())
+        );
+        assert_eq!(retry_reason(&http(408)), (RetryOn::Timeout, "408".into()));
+        assert_eq!(
+            retry_reason(&anyhow::anyhow!("500 Internal Server Error")),
+            (RetryOn::ServerError, "500".into())
+        );
+        assert_eq!(
+            retry_reason(&anyhow::anyhow!("connection reset")),
+            (RetryOn::Timeout, "error".into())
+        );
+    }
+
+    /// Mock OpenAI-compatible endpoint: the first request gets a 429 with the
+    /// given `Retry-After`, every later one a normal completion.
+    async fn spawn_rate_limited_server(retry_after: &'static str) -> (String, Arc<AtomicUsize>) {
+        use axum::http::{header, StatusCode};
+        use axum::response::IntoResponse;
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let app = axum::Router::new().route(
+            "/v1/chat/completions",
+            axum::routing::post(move || {
+                let counter = Arc::clone(&counter);
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                        (
+                            StatusCode::TOO_MANY_REQUESTS,
+                            [(header::RETRY_AFTER, retry_after)],
+                            "rate limited",
+                        )
+                            .into_response()
+                    } else {
+                        axum::Json(serde_json::json!({
+                            "choices": [{"message": {"content": "hi"}}]
+                        }))
+                        .into_response()
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{addr}/v1"), hits)
+    }
+
+    fn http_provider(base_url: &str) -> Box<dyn Provider> {
+        Box::new(super::super::compatible::OpenAiCompatibleProvider::new(
+            "Mock",
+            base_url,
+            Some("sk-test"),
+            super::super::compatible::AuthStyle::Bearer,
+        ))
+    }
+
+    #[tokio::test]
+    async fn waits_for_retry_after_on_429() {
+        let (base_url, hits) = spawn_rate_limited_server("1").await;
+        let provider = ReliableProvider::new(vec![("mock".into(), http_provider(&base_url))], 2, 1);
+
+        let started = std::time::Instant::now();
+        let result = provider.chat("hello", "test", 0.0).await.unwrap();
+        assert_eq!(result, "hi");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert!(
+            started.elapsed() >= Duration::from_secs(1),
+            "retried after {:?}, before Retry-After elapsed",
+            started.elapsed()
+        );
+    }
+
+    #[tokio::test]
+    async fn retry_after_is_capped_by_max_backoff() {
+        let (base_url, hits) = spawn_rate_limited_server("3600").await;
+        let provider = ReliableProvider::new(vec![("mock".into(), http_provider(&base_url))], 2, 1)
+            .with_max_backoff(Duration::from_millis(100));
+
+        let started = std::time::Instant::now();
+        assert_eq!(provider.chat("hello", "test", 0.0).await.unwrap(), "hi");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn typed_http_errors_classify_by_status() {
+        let http = |status: u16| -> anyhow::Error {
+            ProviderHttpError {
+                status,
+                retry_after: None,
+                message: format!("Mock API 错误 ({status})"),
+            }
+            .into()
+        };
+        for status in [400, 401, 403, 404, 422, 501] {
+            assert!(is_non_retryable(&http(status)), "{status} should stop");
+        }
+        for status in [408, 429, 500, 502, 503, 504] {
+            assert!(!is_non_retryable(&http(status)), "{status} should retry");
+        }
+    }
+
+    #[test]
+    fn retry_after_overrides_jittered_backoff_within_cap() {
+        let provider =
+            ReliableProvider::new(Vec::new(), 0, 100).with_max_backoff(Duration::from_secs(5));
+        let limited = |secs: u64| -> anyhow::Error {
+            ProviderHttpError {
+                status: 429,
+                retry_after: Some(Duration::from_secs(secs)),
+                message: "rate limited".into(),
+            }
+            .into()
+        };
+
+        assert_eq!(
+            provider.retry_delay(&limited(2), 100),
+            Duration::from_secs(2)
+        );
+        assert_eq!(provider.retry_delay(&limited(0), 800), Duration::ZERO);
+        assert_eq!(
+            provider.retry_delay(&limited(60), 100),
+            Duration::from_secs(5)
+        );
+        let backoff = provider.retry_delay(&anyhow::anyhow!("timeout"), 400);
+        assert!(
+            (Duration::from_millis(300)..=Duration::from_millis(400)).contains(&backoff),
+            "{backoff:?}"
+        );
+        assert_eq!(
+            provider.retry_delay(&anyhow::anyhow!("timeout"), 60_000),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn strip_vendor_prefix_only_for_matching_provider() {
+        assert_eq!(
+            strip_vendor_prefix("anthropic", "anthropic/claude-sonnet-4"),
+            "claude-sonnet-4"
+        );
+        assert_eq!(
+            strip_vendor_prefix("gemini", "google/gemini-2.0-flash"),
+            "gemini-2.0-flash"
+        );
+        assert_eq!(
+            strip_vendor_prefix("openai", "anthropic/claude-sonnet-4"),
+            "anthropic/claude-sonnet-4"
+        );
+        assert_eq!(strip_vendor_prefix("ollama", "llama3"), "llama3");
     }
 }
@@ -0,0 +1,313 @@
+//! Structured (JSON) output support shared by all providers.
+//!
+//! Providers with a native mode (`OpenAI` `response_format`, Anthropic forced
+//! tool use, Gemini `responseSchema`) use it; the rest get a system-prompt
+//! hint. Either way the reply goes through [`ResponseFormat::parse`], which
+//! strips common wrapping (code fences, surrounding prose) and checks the
+//! result against the requested shape.
+
+use super::traits::ChatMessage;
+use anyhow::Context;
+use serde_json::{json, Value};
+
+/// Requested shape of a model reply.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResponseFormat {
+    /// Any JSON object.
+    JsonObject,
+    /// JSON matching `schema`; `name` labels it for providers that require one.
+    JsonSchema { name: String, schema: Value },
+}
+
+impl ResponseFormat {
+    pub fn json_schema(name: &str, schema: Value) -> Self {
+        Self::JsonSchema {
+            name: name.to_string(),
+            schema,
+        }
+    }
+
+    /// Name used for `OpenAI` schemas and the Anthropic forced tool.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::JsonObject => "json_response",
+            Self::JsonSchema { name, .. } => name,
+        }
+    }
+
+    /// JSON schema describing the expected value.
+    pub fn schema(&self) -> Value {
+        match self {
+            Self::JsonObject => json!({ "type": "object" }),
+            Self::JsonSchema { schema, .. } => schema.clone(),
+        }
+    }
+
+    /// `OpenAI` chat completions `response_format` value.
+    pub fn to_openai(&self) -> Value {
+        match self {
+            Self::JsonObject => json!({ "type": "json_object" }),
+            Self::JsonSchema { name, schema } => json!({
+                "type": "json_schema",
+                "json_schema": { "name": name, "schema": schema, "strict": true },
+            }),
+        }
+    }
+
+    /// System prompt for providers without a native JSON mode.
+    pub fn system_hint(&self, system_prompt: Option<&str>) -> String {
+        let hint = match self {
+            Self::JsonObject => {
+                "Respond with valid JSON only: a single JSON object, no code fences, no prose."
+                    .to_string()
+            }
+            Self::JsonSchema { schema, .. } => format!(
+                "Respond with valid JSON only, no code fences, no prose. \
+                 The JSON must match this schema:\n{schema}"
+            ),
+        };
+        match system_prompt {
+            Some(sys) if !sys.trim().is_empty() => format!("{sys}\n\n{hint}"),
+            _ => hint,
+        }
+    }
+
+    /// Copy of `messages` with [`Self::system_hint`] merged into the system
+    /// prompt (added as one if there is none).
+    pub fn with_system_hint(&self, messages: &[ChatMessage]) -> Vec<ChatMessage> {
+        let mut hinted = messages.to_vec();
+        match hinted.iter_mut().find_map(|m| match m {
+            ChatMessage::System { content } => Some(content),
+            _ => None,
+        }) {
+            Some(content) => *content = self.system_hint(Some(content)),
+            None => hinted.insert(
+                0,
+                ChatMessage::System {
+                    content: self.system_hint(None),
+                },
+            ),
+        }
+        hinted
+    }
+
+    /// Extract the JSON value from a model reply and check it has the requested shape.
+    pub fn parse(&self, text: &str) -> anyhow::Result<Value> {
+        let value = extract_json(text).with_context(|| {
+            format!(
+                "模型未返回有效 JSON: {}",
+                crate::util::truncate_with_ellipsis(text.trim(), 200)
+            )
+        })?;
+        match self {
+            Self::JsonObject if !value.is_object() => {
+                anyhow::bail!("模型返回的 JSON 不是对象")
+            }
+            Self::JsonObject => {}
+            Self::JsonSchema { schema, .. } => {
+                check_schema(&value, schema, "$").context("模型返回的 JSON 不符合 schema")?;
+            }
+        }
+        Ok(value)
+    }
+}
+
+/// Parse `text` as JSON, falling back to a fenced block or the outermost
+/// `{...}` / `[...]` span when the model wrapped it in prose.
+fn extract_json(text: &str) -> Option<Value> {
+    let trimmed = text.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+
+    let fenced = trimmed.find("```").and_then(|start| {
+        let body = &trimmed[start + 3..];
+        // Skip the language tag on the opening fence
+        let body = body.split_once('\n').map_or(body, |(_, rest)| rest);
+        let end = body.find("```")?;
+        serde_json::from_str(body[..end].trim()).ok()
+    });
+    if fenced.is_some() {
+        return fenced;
+    }
+
+    [('{', '}'), ('[', ']')].iter().find_map(|&(open, close)| {
+        let start = trimmed.find(open)?;
+        let end = trimmed.rfind(close)?;
+        (start < end)
+            .then(|| serde_json::from_str(&trimmed[start..=end]).ok())
+            .flatten()
+    })
+}
+
+/// Check `value` against the commonly used subset of JSON Schema:
+/// `type`, `enum`, `properties`, `required` and `items`.
+fn check_schema(value: &Value, schema: &Value, path: &str) -> anyhow::Result<()> {
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| type_matches(value, t)) {
+            anyhow::bail!("{path}: expected {}, got {value}", allowed.join(" | "));
+        }
+    }
+
+    let outside_enum =
+        matches!(schema.get("enum"), Some(Value::Array(options)) if !options.contains(value));
+    if outside_enum {
+        anyhow::bail!("{path}: {value} is not one of the allowed values");
+    }
+
+    if let Value::Object(fields) = value {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !fields.contains_key(key) {
+                    anyhow::bail!("{path}: missing required field \"{key}\"");
+                }
+            }
+        }
+        if let Some(Value::Object(properties)) = schema.get("properties") {
+            for (key, sub_schema) in properties {
+                if let Some(field) = fields.get(key) {
+                    check_schema(field, sub_schema, &format!("{path}.{key}"))?;
+                }
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            check_schema(item, item_schema, &format!("{path}[{i}]"))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn type_matches(value: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary_schema() -> ResponseFormat {
+        ResponseFormat::json_schema(
+            "summary",
+            json!({
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string" },
+                    "priority": { "type": "string", "enum": ["low", "high"] },
+                    "tags": { "type": "array", "items": { "type": "string" } }
+                },
+                "required": ["title", "priority"]
+            }),
+        )
+    }
+
+    #[test]
+    fn parses_plain_json() {
+        let value = ResponseFormat::JsonObject
+            .parse(r#" {"ok": true} "#)
+            .unwrap();
+        assert_eq!(value, json!({"ok": true}));
+    }
+
+    #[test]
+    fn repairs_fenced_and_wrapped_json() {
+        let fenced = "Sure! Here it is:\n```json\n{\"ok\": 1}\n```\nAnything else?";
+        assert_eq!(
+            ResponseFormat::JsonObject.parse(fenced).unwrap(),
+            json!({"ok": 1})
+        );
+
+        let prose = "The answer is {\"ok\": 2} as requested.";
+        assert_eq!(
+            ResponseFormat::JsonObject.parse(prose).unwrap(),
+            json!({"ok": 2})
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_or_non_object_json() {
+        assert!(ResponseFormat::JsonObject.parse("no json here").is_err());
+        assert!(ResponseFormat::JsonObject.parse("{\"broken\": ").is_err());
+        assert!(ResponseFormat::JsonObject.parse("[1, 2]").is_err());
+    }
+
+    #[test]
+    fn validates_against_schema() {
+        let format = summary_schema();
+        assert!(format
+            .parse(r#"{"title": "t", "priority": "high", "tags": ["a"]}"#)
+            .is_ok());
+
+        let missing = format.parse(r#"{"title": "t"}"#).unwrap_err();
+        assert!(format!("{missing:#}").contains("priority"));
+
+        let wrong_enum = format.parse(r#"{"title": "t", "priority": "urgent"}"#);
+        assert!(wrong_enum.is_err());
+
+        let wrong_item = format
+            .parse(r#"{"title": "t", "priority": "low", "tags": [1]}"#)
+            .unwrap_err();
+        assert!(format!("{wrong_item:#}").contains("$.tags[0]"));
+    }
+
+    #[test]
+    fn hint_merges_into_existing_system_message() {
+        let messages = vec![
+            ChatMessage::System {
+                content: "Be brief.".into(),
+            },
+            ChatMessage::User {
+                content: "hi".into(),
+            },
+        ];
+        let hinted = ResponseFormat::JsonObject.with_system_hint(&messages);
+        assert_eq!(hinted.len(), 2);
+        assert!(
+            matches!(&hinted[0], ChatMessage::System { content } if content.starts_with("Be brief.") && content.contains("JSON"))
+        );
+
+        let hinted = ResponseFormat::JsonObject.with_system_hint(&messages[1..]);
+        assert_eq!(hinted.len(), 2);
+        assert!(matches!(&hinted[0], ChatMessage::System { .. }));
+    }
+
+    #[test]
+    fn openai_format_bodies() {
+        assert_eq!(
+            ResponseFormat::JsonObject.to_openai(),
+            json!({"type": "json_object"})
+        );
+        let body = summary_schema().to_openai();
+        assert_eq!(body["type"], "json_schema");
+        assert_eq!(body["json_schema"]["name"], "summary");
+        assert_eq!(body["json_schema"]["strict"], true);
+    }
+
+    #[test]
+    fn system_hint_appends_to_existing_prompt() {
+        let hint = summary_schema().system_hint(Some("You are Jarvis."));
+        assert!(hint.starts_with("You are Jarvis.\n\n"));
+        assert!(hint.contains("valid JSON only"));
+        assert!(hint.contains("\"priority\""));
+
+        let bare = ResponseFormat::JsonObject.system_hint(None);
+        assert!(bare.starts_with("Respond with valid JSON only"));
+    }
+}
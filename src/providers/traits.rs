@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+use super::structured::ResponseFormat;
 use crate::tools::ToolSpec;
 
 // ── Multi-turn chat message types (OpenAI function calling format) ───
@@ -70,6 +71,20 @@ pub enum ChatResponse {
     },
 }
 
+/// A model offered by a provider, as returned by [`Provider::list_models`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ModelInfo {
+    pub id: String,
+    /// Display name or short description, when known.
+    pub description: Option<String>,
+    /// Context window in tokens.
+    pub context_length: Option<u64>,
+    /// USD per million input tokens.
+    pub prompt_price: Option<f64>,
+    /// USD per million output tokens.
+    pub completion_price: Option<f64>,
+}
+
 /// Convert a `ToolSpec` (from the tool registry) into a `ToolDefinition` (for the API).
 pub fn tool_spec_to_definition(spec: &ToolSpec) -> ToolDefinition {
     ToolDefinition {
@@ -97,16 +112,65 @@ pub trait Provider: Send + Sync {
         temperature: f64,
     ) -> anyhow::Result<String>;
 
+    /// Single-turn chat constrained to `format`, returning the raw reply text.
+    ///
+    /// Default implementation adds a "respond with valid JSON only" hint to the
+    /// system prompt; providers with a native JSON mode override it. Callers
+    /// should go through [`Provider::chat_json`], which validates the reply.
+    async fn chat_structured(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+        format: &ResponseFormat,
+    ) -> anyhow::Result<String> {
+        let system = format.system_hint(system_prompt);
+        self.chat_with_system(Some(&system), message, model, temperature)
+            .await
+    }
+
+    /// Request strict JSON for a single message and return the parsed value.
+    async fn chat_json(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+        format: &ResponseFormat,
+    ) -> anyhow::Result<serde_json::Value> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(sys) = system_prompt {
+            messages.push(ChatMessage::System {
+                content: sys.to_string(),
+            });
+        }
+        messages.push(ChatMessage::User {
+            content: message.to_string(),
+        });
+        match self
+            .chat_with_tools(&messages, &[], model, temperature, Some(format))
+            .await?
+        {
+            ChatResponse::Text(text) => format.parse(&text),
+            ChatResponse::ToolUse { .. } => anyhow::bail!("请求 JSON 输出时模型返回了工具调用"),
+        }
+    }
+
     /// Multi-turn chat with tool definitions. Returns structured `ChatResponse`.
     ///
-    /// Default implementation ignores tools and falls back to `chat_with_system`,
-    /// extracting user message from the message list.
+    /// When `response_format` is set, a text reply is validated and normalized
+    /// to compact JSON.
+    ///
+    /// Default implementation ignores tools and falls back to `chat_with_system`
+    /// (or `chat_structured`), extracting user message from the message list.
     async fn chat_with_tools(
         &self,
         messages: &[ChatMessage],
         _tools: &[ToolDefinition],
         model: &str,
         temperature: f64,
+        response_format: Option<&ResponseFormat>,
     ) -> anyhow::Result<ChatResponse> {
         // Extract system prompt and last user message for fallback
         let system_prompt = messages.iter().find_map(|m| {
@@ -128,9 +192,18 @@ pub trait Provider: Send + Sync {
             })
             .unwrap_or("");
 
-        let text = self
-            .chat_with_system(system_prompt, user_message, model, temperature)
-            .await?;
+        let text = match response_format {
+            Some(format) => {
+                let text = self
+                    .chat_structured(system_prompt, user_message, model, temperature, format)
+                    .await?;
+                format.parse(&text)?.to_string()
+            }
+            None => {
+                self.chat_with_system(system_prompt, user_message, model, temperature)
+                    .await?
+            }
+        };
         Ok(ChatResponse::Text(text))
     }
 
@@ -139,6 +212,20 @@ pub trait Provider: Send + Sync {
     async fn warmup(&self) -> anyhow::Result<()> {
         Ok(())
     }
+
+    /// Check that the API is reachable and accepts the credentials, using the
+    /// cheapest authenticated request available (usually listing models).
+    /// Used by `jarvis doctor --deep`.
+    async fn ping(&self) -> anyhow::Result<()> {
+        anyhow::bail!("该 Provider 不支持连通性检查")
+    }
+
+    /// Models the API currently offers. The default returns an empty list,
+    /// meaning the provider has no listing endpoint; callers then use the
+    /// curated list (see [`crate::providers::models::fetch`]).
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        Ok(Vec::new())
+    }
 }
 
 #[cfg(test)]
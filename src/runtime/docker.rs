@@ -0,0 +1,593 @@
+//! Docker runtime: shell commands run with `docker exec` in a long-lived
+//! container that has the workspace bind-mounted read-write at the same
+//! path, so paths mean the same thing inside and out and the file tools keep
+//! working on the host.
+
+use super::traits::RuntimeAdapter;
+use crate::config::DockerRuntimeConfig;
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::time::Duration;
+
+/// How much longer than the caller's timeout the `timeout` wrapper inside
+/// the container waits. The caller kills `docker exec` first and reports
+/// the timeout as on the native runtime; the wrapper then ends the command,
+/// which killing the `docker exec` client leaves running.
+const EXEC_KILL_GRACE_SECS: u64 = 2;
+
+/// Docker runtime — shell commands run in a container
+pub struct DockerRuntime {
+    /// The docker CLI
+    docker: PathBuf,
+    container: String,
+    memory_budget: u64,
+}
+
+/// The container's state as `docker container inspect` reports it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContainerStatus {
+    Running {
+        image: String,
+    },
+    /// Created, exited, paused, …
+    Stopped {
+        state: String,
+    },
+    Missing,
+}
+
+impl DockerRuntime {
+    /// Check that docker is usable, then start the container (creating it
+    /// on first use).
+    pub fn start(config: &DockerRuntimeConfig, workspace_dir: &Path) -> Result<Self> {
+        Self::start_with(Path::new("docker"), config, workspace_dir)
+    }
+
+    fn start_with(
+        docker: &Path,
+        config: &DockerRuntimeConfig,
+        workspace_dir: &Path,
+    ) -> Result<Self> {
+        validate(config)?;
+        probe(docker)?;
+        let runtime = Self {
+            docker: docker.to_path_buf(),
+            container: container_name(config, workspace_dir),
+            memory_budget: config.memory.as_deref().and_then(parse_memory).unwrap_or(0),
+        };
+        runtime.ensure_container(config, workspace_dir)?;
+        Ok(runtime)
+    }
+
+    fn ensure_container(&self, config: &DockerRuntimeConfig, workspace_dir: &Path) -> Result<()> {
+        match inspect(&self.docker, &self.container)? {
+            ContainerStatus::Running { .. } => Ok(()),
+            ContainerStatus::Stopped { .. } => {
+                docker(&self.docker, &["start".to_string(), self.container.clone()])
+                    .with_context(|| format!("无法启动 Docker 容器 {}", self.container))?;
+                tracing::info!("已启动 Docker 容器 {}", self.container);
+                Ok(())
+            }
+            ContainerStatus::Missing => {
+                std::fs::create_dir_all(workspace_dir)
+                    .with_context(|| format!("无法创建工作区目录 {}", workspace_dir.display()))?;
+                tracing::info!(
+                    "正在创建 Docker 容器 {}（镜像 {}，首次使用需要拉取镜像）",
+                    self.container,
+                    config.image
+                );
+                docker(
+                    &self.docker,
+                    &run_args(config, workspace_dir, &self.container),
+                )
+                .with_context(|| format!("无法创建 Docker 容器 {}", self.container))?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl RuntimeAdapter for DockerRuntime {
+    fn name(&self) -> &str {
+        "docker"
+    }
+
+    fn has_shell_access(&self) -> bool {
+        true
+    }
+
+    fn has_filesystem_access(&self) -> bool {
+        true
+    }
+
+    fn storage_path(&self) -> PathBuf {
+        // Config and state stay on the host; only commands run in the container
+        crate::config::profile::config_dir().unwrap_or_else(|_| PathBuf::from(".jarvis"))
+    }
+
+    fn supports_long_running(&self) -> bool {
+        true
+    }
+
+    fn memory_budget(&self) -> u64 {
+        self.memory_budget
+    }
+
+    fn shell_command(
+        &self,
+        command: &str,
+        cwd: &Path,
+        env: &[(String, String)],
+        timeout: Duration,
+    ) -> tokio::process::Command {
+        // The docker CLI keeps the host environment (DOCKER_HOST and the
+        // like); the command only sees the container's own plus `env`
+        let mut cmd = tokio::process::Command::new(&self.docker);
+        cmd.kill_on_drop(true);
+        #[cfg(unix)]
+        cmd.process_group(0);
+
+        cmd.arg("exec").arg("--workdir").arg(cwd);
+        for (name, value) in env {
+            cmd.arg("--env").arg(format!("{name}={value}"));
+        }
+        cmd.arg(&self.container)
+            .args(["timeout", "-s", "KILL"])
+            .arg((timeout.as_secs() + EXEC_KILL_GRACE_SECS).to_string())
+            .args(["sh", "-c", command]);
+        cmd
+    }
+}
+
+/// Check the `[runtime.docker]` settings without talking to docker.
+pub fn validate(config: &DockerRuntimeConfig) -> Result<()> {
+    if config.image.trim().is_empty() {
+        anyhow::bail!("runtime.docker.image 不能为空");
+    }
+    if let Some(name) = &config.container_name {
+        let valid = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphanumeric())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+        if !valid {
+            anyhow::bail!(
+                "runtime.docker.container_name「{name}」无效：只能包含字母、数字、_ . -，且以字母或数字开头"
+            );
+        }
+    }
+    for (i, mount) in config.extra_mounts.iter().enumerate() {
+        let parts: Vec<&str> = mount.split(':').collect();
+        let valid = matches!(parts.len(), 2 | 3)
+            && parts[0].starts_with('/')
+            && parts[1].starts_with('/')
+            && parts.get(2).is_none_or(|mode| matches!(*mode, "ro" | "rw"));
+        if !valid {
+            anyhow::bail!(
+                "runtime.docker.extra_mounts[{i}]「{mount}」格式应为 /宿主机路径:/容器路径[:ro]"
+            );
+        }
+    }
+    if config.network.trim().is_empty() {
+        anyhow::bail!("runtime.docker.network 不能为空（不需要网络时设为 \"none\"）");
+    }
+    if let Some(memory) = &config.memory
+        && parse_memory(memory).is_none()
+    {
+        anyhow::bail!("runtime.docker.memory「{memory}」无效，示例：512m、2g");
+    }
+    if let Some(cpus) = config.cpus
+        && !(cpus.is_finite() && cpus > 0.0)
+    {
+        anyhow::bail!("runtime.docker.cpus 必须大于 0");
+    }
+    Ok(())
+}
+
+/// The container for this workspace: the configured name, or `jarvis-`
+/// and a hash of the workspace path.
+pub fn container_name(config: &DockerRuntimeConfig, workspace_dir: &Path) -> String {
+    if let Some(name) = &config.container_name {
+        return name.clone();
+    }
+    let digest = Sha256::digest(workspace_dir.to_string_lossy().as_bytes());
+    format!("jarvis-{}", &hex::encode(digest)[..12])
+}
+
+/// The state of this workspace's container, for `jarvis doctor`.
+pub fn container_status(
+    config: &DockerRuntimeConfig,
+    workspace_dir: &Path,
+) -> Result<ContainerStatus> {
+    let docker = Path::new("docker");
+    probe(docker)?;
+    inspect(docker, &container_name(config, workspace_dir))
+}
+
+/// Fail with a hint unless the docker CLI is installed and its daemon
+/// answers.
+fn probe(docker: &Path) -> Result<()> {
+    let output = match std::process::Command::new(docker)
+        .args(["version", "--format", "{{.Server.Version}}"])
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => anyhow::bail!(
+            "未找到 docker 命令。runtime.kind = \"docker\" 需要先安装 Docker，或改用 runtime.kind = \"native\""
+        ),
+        Err(e) => return Err(e).context("无法运行 docker 命令"),
+    };
+    if !output.status.success() {
+        anyhow::bail!(
+            "无法连接 Docker 守护进程：{}。请确认 Docker 已启动，且当前用户有权访问（如已加入 docker 组）",
+            stderr(&output)
+        );
+    }
+    Ok(())
+}
+
+fn inspect(docker: &Path, container: &str) -> Result<ContainerStatus> {
+    let output = std::process::Command::new(docker)
+        .args(["container", "inspect", "--format"])
+        .arg("{{.State.Status}} {{.Config.Image}}")
+        .arg(container)
+        .output()
+        .context("无法运行 docker 命令")?;
+    if !output.status.success() {
+        let error = stderr(&output);
+        if error.contains("No such container") || error.contains("no such container") {
+            return Ok(ContainerStatus::Missing);
+        }
+        anyhow::bail!("无法查询 Docker 容器 {container}：{error}");
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (state, image) = stdout.trim().split_once(' ').unwrap_or((stdout.trim(), ""));
+    Ok(if state == "running" {
+        ContainerStatus::Running {
+            image: image.to_string(),
+        }
+    } else {
+        ContainerStatus::Stopped {
+            state: state.to_string(),
+        }
+    })
+}
+
+/// Run a docker subcommand, failing with its stderr.
+fn docker(docker: &Path, args: &[String]) -> Result<()> {
+    let output = std::process::Command::new(docker)
+        .args(args)
+        .output()
+        .context("无法运行 docker 命令")?;
+    if !output.status.success() {
+        anyhow::bail!("{}", stderr(&output));
+    }
+    Ok(())
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).trim().to_string()
+}
+
+/// `docker run` arguments for a container that idles until commands are
+/// exec'd in it.
+fn run_args(config: &DockerRuntimeConfig, workspace_dir: &Path, container: &str) -> Vec<String> {
+    let workspace = workspace_dir.to_string_lossy().into_owned();
+    let mut args: Vec<String> = vec![
+        "run".into(),
+        "--detach".into(),
+        // Reaps the zombies that killed commands leave behind
+        "--init".into(),
+        "--name".into(),
+        container.into(),
+        "--label".into(),
+        format!("jarvis.workspace={workspace}"),
+        "--volume".into(),
+        format!("{workspace}:{workspace}:rw"),
+        "--workdir".into(),
+        workspace.clone(),
+        "--network".into(),
+        config.network.clone(),
+        // The host user usually has no home directory in the image
+        "--env".into(),
+        "HOME=/tmp".into(),
+    ];
+    // A symlinked workspace resolves to another path, which commands given
+    // an absolute `cwd` see
+    if let Ok(resolved) = workspace_dir.canonicalize()
+        && resolved != workspace_dir
+    {
+        let resolved = resolved.to_string_lossy();
+        args.extend(["--volume".into(), format!("{resolved}:{resolved}:rw")]);
+    }
+    // Files the commands create in the workspace belong to the host user
+    #[cfg(unix)]
+    {
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        args.extend(["--user".into(), format!("{uid}:{gid}")]);
+    }
+    if let Some(memory) = &config.memory {
+        args.extend(["--memory".into(), memory.clone()]);
+    }
+    if let Some(cpus) = config.cpus {
+        args.extend(["--cpus".into(), cpus.to_string()]);
+    }
+    for mount in &config.extra_mounts {
+        args.extend(["--volume".into(), mount.clone()]);
+    }
+    args.extend([config.image.clone(), "sleep".into(), "infinity".into()]);
+    args
+}
+
+/// A `--memory` value (`512m`, `2g`, `1048576`) in bytes.
+fn parse_memory(raw: &str) -> Option<u64> {
+    let raw = raw.trim().to_ascii_lowercase();
+    let split = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+    let (digits, unit) = raw.split_at(split);
+    let multiplier: u64 = match unit.trim_end_matches('b') {
+        "" => 1,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        _ => return None,
+    };
+    let bytes = digits.parse::<u64>().ok()?.checked_mul(multiplier)?;
+    (bytes > 0).then_some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::SecurityPolicy;
+    use crate::tools::traits::Tool;
+    use crate::tools::ShellTool;
+    use serde_json::json;
+    use std::os::unix::fs::PermissionsExt;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    /// A docker CLI stand-in: it logs its arguments, keeps the container's
+    /// state in a file and runs exec'd commands on the host.
+    const FAKE_DOCKER: &str = r#"#!/bin/sh
+dir=$(dirname "$0")
+echo "$*" >> "$dir/calls.log"
+case "$1" in
+version) echo 27.3.1 ;;
+container)
+  if [ -f "$dir/state" ]; then
+    echo "$(cat "$dir/state") buildpack-deps:bookworm-scm"
+  else
+    echo "Error response from daemon: No such container: $5" >&2
+    exit 1
+  fi ;;
+run|start) echo running > "$dir/state" ;;
+exec)
+  shift
+  while [ $# -gt 0 ]; do
+    case "$1" in
+      --workdir) cd "$2" || exit 126; shift 2 ;;
+      --env) export "$2"; shift 2 ;;
+      *) break ;;
+    esac
+  done
+  shift
+  exec "$@" ;;
+esac
+"#;
+
+    fn fake_docker(dir: &Path, script: &str) -> PathBuf {
+        let path = dir.join("docker");
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn calls(dir: &Path) -> Vec<String> {
+        std::fs::read_to_string(dir.join("calls.log"))
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn settings_are_validated() {
+        assert!(validate(&DockerRuntimeConfig::default()).is_ok());
+
+        let bad = [
+            DockerRuntimeConfig {
+                image: " ".into(),
+                ..DockerRuntimeConfig::default()
+            },
+            DockerRuntimeConfig {
+                container_name: Some("-jarvis".into()),
+                ..DockerRuntimeConfig::default()
+            },
+            DockerRuntimeConfig {
+                extra_mounts: vec!["data:/data".into()],
+                ..DockerRuntimeConfig::default()
+            },
+            DockerRuntimeConfig {
+                extra_mounts: vec!["/data:/data:rx".into()],
+                ..DockerRuntimeConfig::default()
+            },
+            DockerRuntimeConfig {
+                memory: Some("lots".into()),
+                ..DockerRuntimeConfig::default()
+            },
+            DockerRuntimeConfig {
+                cpus: Some(0.0),
+                ..DockerRuntimeConfig::default()
+            },
+        ];
+        let keys = [
+            "image",
+            "container_name",
+            "extra_mounts[0]",
+            "extra_mounts[0]",
+            "memory",
+            "cpus",
+        ];
+        for (config, key) in bad.iter().zip(keys) {
+            let err = validate(config).unwrap_err().to_string();
+            assert!(err.contains(&format!("runtime.docker.{key}")), "{err}");
+        }
+    }
+
+    #[test]
+    fn memory_limits_parse() {
+        assert_eq!(parse_memory("512m"), Some(512 << 20));
+        assert_eq!(parse_memory("2G"), Some(2 << 30));
+        assert_eq!(parse_memory("64kb"), Some(64 << 10));
+        assert_eq!(parse_memory("1048576"), Some(1 << 20));
+        assert_eq!(parse_memory("0"), None);
+        assert_eq!(parse_memory("2t"), None);
+        assert_eq!(parse_memory("m"), None);
+    }
+
+    #[test]
+    fn container_is_named_after_the_workspace() {
+        let config = DockerRuntimeConfig::default();
+        let a = container_name(&config, Path::new("/home/a/.jarvis/workspace"));
+        let b = container_name(&config, Path::new("/home/b/.jarvis/workspace"));
+        assert!(a.starts_with("jarvis-") && a.len() == 19, "{a}");
+        assert_ne!(a, b);
+
+        let named = DockerRuntimeConfig {
+            container_name: Some("sandbox".into()),
+            ..config
+        };
+        assert_eq!(container_name(&named, Path::new("/w")), "sandbox");
+    }
+
+    #[test]
+    fn container_mounts_the_workspace_and_applies_limits() {
+        let config = DockerRuntimeConfig {
+            extra_mounts: vec!["/data:/data:ro".into()],
+            network: "none".into(),
+            memory: Some("1g".into()),
+            cpus: Some(1.5),
+            ..DockerRuntimeConfig::default()
+        };
+        let args = run_args(&config, Path::new("/srv/ws"), "jarvis-test").join(" ");
+        assert!(
+            args.starts_with("run --detach --init --name jarvis-test "),
+            "{args}"
+        );
+        for expected in [
+            "--volume /srv/ws:/srv/ws:rw --workdir /srv/ws",
+            "--network none",
+            "--memory 1g --cpus 1.5 --volume /data:/data:ro",
+        ] {
+            assert!(args.contains(expected), "{args}");
+        }
+        assert!(args.ends_with(" buildpack-deps:bookworm-scm sleep infinity"));
+    }
+
+    #[test]
+    fn unusable_docker_is_explained() {
+        let tmp = TempDir::new().unwrap();
+        let config = DockerRuntimeConfig::default();
+
+        let missing = tmp.path().join("no-docker");
+        let err = DockerRuntime::start_with(&missing, &config, tmp.path())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("未找到 docker 命令"), "{err}");
+
+        let down = fake_docker(
+            tmp.path(),
+            "#!/bin/sh\necho 'Cannot connect to the Docker daemon' >&2\nexit 1\n",
+        );
+        let err = DockerRuntime::start_with(&down, &config, tmp.path())
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string().contains("无法连接 Docker 守护进程"),
+            "{err}"
+        );
+        assert!(err.to_string().contains("Cannot connect"), "{err}");
+    }
+
+    #[test]
+    fn container_is_created_once_then_reused_or_restarted() {
+        let tmp = TempDir::new().unwrap();
+        let docker = fake_docker(tmp.path(), FAKE_DOCKER);
+        let workspace = tmp.path().join("workspace");
+        let config = DockerRuntimeConfig {
+            container_name: Some("jarvis-test".into()),
+            memory: Some("2g".into()),
+            ..DockerRuntimeConfig::default()
+        };
+
+        let runtime = DockerRuntime::start_with(&docker, &config, &workspace).unwrap();
+        assert_eq!(runtime.name(), "docker");
+        assert_eq!(runtime.memory_budget(), 2 << 30);
+        assert!(workspace.is_dir());
+        assert_eq!(
+            inspect(&docker, "jarvis-test").unwrap(),
+            ContainerStatus::Running {
+                image: "buildpack-deps:bookworm-scm".into()
+            }
+        );
+
+        DockerRuntime::start_with(&docker, &config, &workspace).unwrap();
+        std::fs::write(tmp.path().join("state"), "exited").unwrap();
+        DockerRuntime::start_with(&docker, &config, &workspace).unwrap();
+
+        let subcommands: Vec<String> = calls(tmp.path())
+            .iter()
+            .map(|call| call.split(' ').next().unwrap_or_default().to_string())
+            .filter(|sub| sub != "version" && sub != "container")
+            .collect();
+        assert_eq!(subcommands, ["run", "start"]);
+    }
+
+    #[tokio::test]
+    async fn shell_commands_run_in_the_container() {
+        let tmp = TempDir::new().unwrap();
+        let docker = fake_docker(tmp.path(), FAKE_DOCKER);
+        let workspace = tmp.path().join("workspace");
+        let config = DockerRuntimeConfig {
+            container_name: Some("jarvis-test".into()),
+            ..DockerRuntimeConfig::default()
+        };
+        let runtime = DockerRuntime::start_with(&docker, &config, &workspace).unwrap();
+        let security = Arc::new(SecurityPolicy {
+            workspace_dir: workspace.clone(),
+            commands: crate::security::commands::CommandPolicy::allow_names(&[
+                "echo", "pwd", "sleep",
+            ]),
+            ..SecurityPolicy::default()
+        });
+        let tool = ShellTool::new(security).with_runtime(Arc::new(runtime));
+
+        let result = tool
+            .execute(json!({"command": "echo $GREETING; pwd", "env": {"GREETING": "hi"}}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, format!("hi\n{}\n", workspace.display()));
+        let exec = calls(tmp.path()).pop().unwrap();
+        assert_eq!(
+            exec,
+            format!(
+                "exec --workdir {} --env GREETING=hi jarvis-test timeout -s KILL 122 sh -c echo $GREETING; pwd",
+                workspace.display()
+            )
+        );
+
+        let result = tool
+            .execute(json!({"command": "echo started; sleep 30", "timeout_seconds": 1}))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.error.unwrap(),
+            "started\n[Command timed out after 1s and was killed]"
+        );
+    }
+}
@@ -1,25 +1,48 @@
+pub mod docker;
 pub mod native;
+pub mod sandbox;
 pub mod traits;
 
 pub use native::NativeRuntime;
 pub use traits::RuntimeAdapter;
 
 use crate::config::RuntimeConfig;
+use std::path::Path;
 
-/// Factory: create the right runtime from config
-pub fn create_runtime(config: &RuntimeConfig) -> anyhow::Result<Box<dyn RuntimeAdapter>> {
+/// Factory: create the right runtime from config. The docker runtime
+/// checks that docker is reachable and starts its container here; the
+/// sandbox runtime checks that its backend can build a sandbox.
+pub fn create_runtime(
+    config: &RuntimeConfig,
+    workspace_dir: &Path,
+) -> anyhow::Result<Box<dyn RuntimeAdapter>> {
+    validate(config)?;
     match config.kind.as_str() {
-        "native" => Ok(Box::new(NativeRuntime::new())),
-        "docker" => anyhow::bail!(
-            "runtime.kind='docker' 尚未实现。请使用 runtime.kind='native'，等待容器运行时支持。"
-        ),
+        "docker" => Ok(Box::new(docker::DockerRuntime::start(
+            &config.docker,
+            workspace_dir,
+        )?)),
+        "sandbox" => Ok(Box::new(sandbox::SandboxRuntime::start(
+            &config.sandbox,
+            workspace_dir,
+        )?)),
+        _ => Ok(Box::new(NativeRuntime::new())),
+    }
+}
+
+/// Check the `[runtime]` settings without starting anything.
+pub fn validate(config: &RuntimeConfig) -> anyhow::Result<()> {
+    match config.kind.as_str() {
+        "native" => Ok(()),
+        "docker" => docker::validate(&config.docker),
+        "sandbox" => sandbox::validate(&config.sandbox),
         "cloudflare" => {
             anyhow::bail!("runtime.kind='cloudflare' 尚未实现。请暂时使用 runtime.kind='native'。")
         }
         other if other.trim().is_empty() => {
-            anyhow::bail!("runtime.kind 不能为空。支持的值: native")
+            anyhow::bail!("runtime.kind 不能为空。支持的值: native、docker、sandbox")
         }
-        other => anyhow::bail!("未知的运行时类型「{other}」。支持的值: native"),
+        other => anyhow::bail!("未知的运行时类型「{other}」。支持的值: native、docker、sandbox"),
     }
 }
 
@@ -31,20 +54,38 @@ mod tests {
     fn factory_native() {
         let cfg = RuntimeConfig {
             kind: "native".into(),
+            ..RuntimeConfig::default()
         };
-        let rt = create_runtime(&cfg).unwrap();
+        let rt = create_runtime(&cfg, Path::new("/tmp")).unwrap();
         assert_eq!(rt.name(), "native");
         assert!(rt.has_shell_access());
     }
 
     #[test]
-    fn factory_docker_errors() {
-        let cfg = RuntimeConfig {
+    fn factory_docker_checks_settings_first() {
+        let mut cfg = RuntimeConfig {
             kind: "docker".into(),
+            ..RuntimeConfig::default()
         };
-        match create_runtime(&cfg) {
-            Err(err) => assert!(err.to_string().contains("尚未实现")),
-            Ok(_) => panic!("docker runtime should error"),
+        assert!(validate(&cfg).is_ok());
+        cfg.docker.image = String::new();
+        match create_runtime(&cfg, Path::new("/tmp")) {
+            Err(err) => assert!(err.to_string().contains("runtime.docker.image")),
+            Ok(_) => panic!("docker runtime without an image should error"),
+        }
+    }
+
+    #[test]
+    fn factory_sandbox_checks_settings_first() {
+        let mut cfg = RuntimeConfig {
+            kind: "sandbox".into(),
+            ..RuntimeConfig::default()
+        };
+        assert!(validate(&cfg).is_ok());
+        cfg.sandbox.backend = "chroot".into();
+        match create_runtime(&cfg, Path::new("/tmp")) {
+            Err(err) => assert!(err.to_string().contains("runtime.sandbox.backend")),
+            Ok(_) => panic!("sandbox runtime with an unknown backend should error"),
         }
     }
 
@@ -52,8 +93,9 @@ mod tests {
     fn factory_cloudflare_errors() {
         let cfg = RuntimeConfig {
             kind: "cloudflare".into(),
+            ..RuntimeConfig::default()
         };
-        match create_runtime(&cfg) {
+        match create_runtime(&cfg, Path::new("/tmp")) {
             Err(err) => assert!(err.to_string().contains("尚未实现")),
             Ok(_) => panic!("cloudflare runtime should error"),
         }
@@ -63,8 +105,9 @@ mod tests {
     fn factory_unknown_errors() {
         let cfg = RuntimeConfig {
             kind: "wasm-edge-unknown".into(),
+            ..RuntimeConfig::default()
         };
-        match create_runtime(&cfg) {
+        match create_runtime(&cfg, Path::new("/tmp")) {
             Err(err) => assert!(err.to_string().contains("未知的运行时类型")),
             Ok(_) => panic!("unknown runtime should error"),
         }
@@ -74,8 +117,9 @@ mod tests {
     fn factory_empty_errors() {
         let cfg = RuntimeConfig {
             kind: String::new(),
+            ..RuntimeConfig::default()
         };
-        match create_runtime(&cfg) {
+        match create_runtime(&cfg, Path::new("/tmp")) {
             Err(err) => assert!(err.to_string().contains("不能为空")),
             Ok(_) => panic!("empty runtime should error"),
         }
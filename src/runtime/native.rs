@@ -1,5 +1,7 @@
 use super::traits::RuntimeAdapter;
-use std::path::PathBuf;
+use crate::tools::shell::sandboxed_command;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Native runtime — full access, runs on Mac/Linux/Docker/Raspberry Pi
 pub struct NativeRuntime;
@@ -24,15 +26,24 @@ impl RuntimeAdapter for NativeRuntime {
     }
 
     fn storage_path(&self) -> PathBuf {
-        directories::UserDirs::new().map_or_else(
-            || PathBuf::from(".jarvis"),
-            |u| u.home_dir().join(".jarvis"),
-        )
+        crate::config::profile::config_dir().unwrap_or_else(|_| PathBuf::from(".jarvis"))
     }
 
     fn supports_long_running(&self) -> bool {
         true
     }
+
+    fn shell_command(
+        &self,
+        command: &str,
+        cwd: &Path,
+        env: &[(String, String)],
+        _timeout: Duration,
+    ) -> tokio::process::Command {
+        let mut cmd = sandboxed_command("sh", cwd);
+        cmd.arg("-c").arg(command).envs(env.iter().cloned());
+        cmd
+    }
 }
 
 #[cfg(test)]
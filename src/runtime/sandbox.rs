@@ -0,0 +1,446 @@
+//! Sandbox runtime (Linux): shell commands run under bubblewrap (`bwrap`) or
+//! firejail. The workspace and `writable_paths` are writable; the rest of
+//! the filesystem is read-only, the home and config directories (API keys,
+//! SSH keys) are hidden, and there is no network unless
+//! `[runtime.sandbox] network` is on. The file tools still work on the host
+//! workspace directly.
+
+use super::traits::RuntimeAdapter;
+use crate::config::SandboxRuntimeConfig;
+use crate::tools::shell::sandboxed_command;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The program a sandbox is built with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Bwrap,
+    Firejail,
+}
+
+impl Backend {
+    pub fn program(self) -> &'static str {
+        match self {
+            Self::Bwrap => "bwrap",
+            Self::Firejail => "firejail",
+        }
+    }
+}
+
+/// What the sandbox lets commands see and touch.
+#[derive(Debug, Clone)]
+struct Layout {
+    /// The workspace, and its resolved path when it is a symlink
+    workspace: Vec<PathBuf>,
+    writable: Vec<PathBuf>,
+    /// Directories hidden behind an empty tmpfs (the workspace stays
+    /// visible when it is inside one of them)
+    hidden: Vec<PathBuf>,
+    network: bool,
+}
+
+/// Sandbox runtime — shell commands run under bwrap or firejail
+pub struct SandboxRuntime {
+    backend: Backend,
+    /// The backend's executable
+    program: PathBuf,
+    layout: Layout,
+}
+
+impl SandboxRuntime {
+    /// Find the backend and check that it can build a sandbox here.
+    pub fn start(config: &SandboxRuntimeConfig, workspace_dir: &Path) -> Result<Self> {
+        if !cfg!(target_os = "linux") {
+            anyhow::bail!(
+                "runtime.kind = \"sandbox\" 仅支持 Linux，其他系统可改用 runtime.kind = \"docker\""
+            );
+        }
+        validate(config)?;
+        let (backend, program) = find_backend(config, find_program)?;
+        std::fs::create_dir_all(workspace_dir)
+            .with_context(|| format!("无法创建工作区目录 {}", workspace_dir.display()))?;
+
+        let mut workspace = vec![workspace_dir.to_path_buf()];
+        if let Ok(resolved) = workspace_dir.canonicalize()
+            && resolved != workspace_dir
+        {
+            workspace.push(resolved);
+        }
+        let mut hidden: Vec<PathBuf> = std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .into_iter()
+            .chain(crate::config::profile::config_dir().ok())
+            .filter(|dir| dir.is_absolute() && dir.parent().is_some() && dir.is_dir())
+            .collect();
+        hidden.dedup();
+
+        let runtime = Self {
+            backend,
+            program,
+            layout: Layout {
+                workspace,
+                writable: config.writable_paths.iter().map(PathBuf::from).collect(),
+                hidden,
+                network: config.network,
+            },
+        };
+        runtime.probe(workspace_dir)?;
+        tracing::info!(
+            "shell 命令将在 {} 沙箱中运行（网络{}）",
+            backend.program(),
+            if config.network { "开启" } else { "关闭" }
+        );
+        Ok(runtime)
+    }
+
+    /// The backend's arguments up to the command it runs.
+    fn wrapper_args(&self, cwd: &Path) -> Vec<String> {
+        match self.backend {
+            Backend::Bwrap => bwrap_args(&self.layout, cwd),
+            Backend::Firejail => firejail_args(&self.layout),
+        }
+    }
+
+    /// Run `true` in the sandbox, so a backend that can't build one here
+    /// (e.g. unprivileged user namespaces are disabled) fails at startup
+    /// rather than on every command.
+    fn probe(&self, workspace_dir: &Path) -> Result<()> {
+        let output = std::process::Command::new(&self.program)
+            .args(self.wrapper_args(workspace_dir))
+            .arg("true")
+            .current_dir(workspace_dir)
+            .output()
+            .with_context(|| format!("无法运行 {}", self.program.display()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let hint = match self.backend {
+                Backend::Bwrap => {
+                    "。请确认内核允许非特权用户命名空间（sysctl kernel.unprivileged_userns_clone、\
+                     kernel.apparmor_restrict_unprivileged_userns），或改用 backend = \"firejail\""
+                }
+                Backend::Firejail => "",
+            };
+            anyhow::bail!(
+                "{} 无法创建沙箱：{}{hint}",
+                self.backend.program(),
+                stderr.trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+impl RuntimeAdapter for SandboxRuntime {
+    fn name(&self) -> &str {
+        "sandbox"
+    }
+
+    fn has_shell_access(&self) -> bool {
+        true
+    }
+
+    fn has_filesystem_access(&self) -> bool {
+        true
+    }
+
+    fn storage_path(&self) -> PathBuf {
+        // Config and state stay outside the sandbox
+        crate::config::profile::config_dir().unwrap_or_else(|_| PathBuf::from(".jarvis"))
+    }
+
+    fn supports_long_running(&self) -> bool {
+        true
+    }
+
+    fn shell_command(
+        &self,
+        command: &str,
+        cwd: &Path,
+        env: &[(String, String)],
+        _timeout: Duration,
+    ) -> tokio::process::Command {
+        // Both backends pass the (already minimal) environment through
+        let mut cmd = sandboxed_command(&self.program.to_string_lossy(), cwd);
+        cmd.args(self.wrapper_args(cwd))
+            .args(["sh", "-c", command])
+            .envs(env.iter().cloned());
+        cmd
+    }
+}
+
+/// Check the `[runtime.sandbox]` settings without looking for the backend.
+pub fn validate(config: &SandboxRuntimeConfig) -> Result<()> {
+    if !matches!(config.backend.as_str(), "auto" | "bwrap" | "firejail") {
+        anyhow::bail!(
+            "runtime.sandbox.backend「{}」无效。支持的值: auto、bwrap、firejail",
+            config.backend
+        );
+    }
+    for (i, path) in config.writable_paths.iter().enumerate() {
+        if !Path::new(path).is_absolute() {
+            anyhow::bail!("runtime.sandbox.writable_paths[{i}]「{path}」必须是绝对路径");
+        }
+    }
+    Ok(())
+}
+
+/// The backend `config` asks for and where it is installed, for
+/// `jarvis doctor`.
+pub fn locate(config: &SandboxRuntimeConfig) -> Result<(Backend, PathBuf)> {
+    find_backend(config, find_program)
+}
+
+/// The backend `config` asks for and its executable, found with `find`.
+fn find_backend(
+    config: &SandboxRuntimeConfig,
+    find: impl Fn(&str) -> Option<PathBuf>,
+) -> Result<(Backend, PathBuf)> {
+    let candidates: &[Backend] = match config.backend.as_str() {
+        "bwrap" => &[Backend::Bwrap],
+        "firejail" => &[Backend::Firejail],
+        _ => &[Backend::Bwrap, Backend::Firejail],
+    };
+    for &backend in candidates {
+        if let Some(program) = find(backend.program()) {
+            return Ok((backend, program));
+        }
+    }
+    let names = candidates
+        .iter()
+        .map(|b| b.program())
+        .collect::<Vec<_>>()
+        .join(" 或 ");
+    anyhow::bail!(
+        "未找到 {names} 命令。runtime.kind = \"sandbox\" 需要先安装（如 apt install bubblewrap），\
+         或改用 runtime.kind = \"native\""
+    )
+}
+
+/// `name` on `PATH`.
+fn find_program(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+fn path_arg(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// bwrap: a read-only view of `/` with fresh `/dev`, `/proc` and `/tmp`,
+/// the hidden directories emptied, then the writable paths bound back in.
+fn bwrap_args(layout: &Layout, cwd: &Path) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "--ro-bind",
+        "/",
+        "/",
+        "--dev",
+        "/dev",
+        "--proc",
+        "/proc",
+        "--tmpfs",
+        "/tmp",
+    ]
+    .map(String::from)
+    .to_vec();
+    for dir in &layout.hidden {
+        args.extend(["--tmpfs".into(), path_arg(dir)]);
+    }
+    for path in layout.workspace.iter().chain(&layout.writable) {
+        args.extend(["--bind".into(), path_arg(path), path_arg(path)]);
+    }
+    args.push("--unshare-all".into());
+    if layout.network {
+        args.push("--share-net".into());
+    }
+    // No --new-session: the command stays in the caller's process group,
+    // so a timeout kills it along with bwrap
+    args.extend([
+        "--die-with-parent".into(),
+        "--chdir".into(),
+        path_arg(cwd),
+        "--".into(),
+    ]);
+    args
+}
+
+/// firejail: everything read-only except the writable paths; hidden
+/// directories holding the workspace are reduced to it with `--whitelist`.
+fn firejail_args(layout: &Layout) -> Vec<String> {
+    let mut args: Vec<String> = ["--quiet", "--noprofile", "--private-tmp", "--private-dev"]
+        .map(String::from)
+        .to_vec();
+    let workspace = layout.workspace.first();
+    for dir in &layout.hidden {
+        match workspace {
+            Some(ws) if ws.starts_with(dir) => args.push(format!("--whitelist={}", path_arg(ws))),
+            _ => args.push(format!("--blacklist={}", path_arg(dir))),
+        }
+    }
+    args.dedup();
+    args.push("--read-only=/".into());
+    for path in layout.workspace.iter().chain(&layout.writable) {
+        args.push(format!("--read-write={}", path_arg(path)));
+    }
+    if !layout.network {
+        args.push("--net=none".into());
+    }
+    args.push("--".into());
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn layout(network: bool) -> Layout {
+        Layout {
+            workspace: vec![PathBuf::from("/home/me/.jarvis/workspace")],
+            writable: vec![PathBuf::from("/data")],
+            hidden: vec![PathBuf::from("/home/me"), PathBuf::from("/home/me/.jarvis")],
+            network,
+        }
+    }
+
+    #[test]
+    fn settings_are_validated() {
+        assert!(validate(&SandboxRuntimeConfig::default()).is_ok());
+        let bad_backend = SandboxRuntimeConfig {
+            backend: "nsjail".into(),
+            ..SandboxRuntimeConfig::default()
+        };
+        assert!(validate(&bad_backend)
+            .unwrap_err()
+            .to_string()
+            .contains("runtime.sandbox.backend"));
+        let relative = SandboxRuntimeConfig {
+            writable_paths: vec!["data".into()],
+            ..SandboxRuntimeConfig::default()
+        };
+        assert!(validate(&relative)
+            .unwrap_err()
+            .to_string()
+            .contains("writable_paths[0]"));
+    }
+
+    #[test]
+    fn backend_is_found_or_explained() {
+        let only_firejail =
+            |name: &str| (name == "firejail").then(|| PathBuf::from("/usr/bin/firejail"));
+        let auto = SandboxRuntimeConfig::default();
+        assert_eq!(
+            find_backend(&auto, only_firejail).unwrap(),
+            (Backend::Firejail, PathBuf::from("/usr/bin/firejail"))
+        );
+
+        let bwrap = SandboxRuntimeConfig {
+            backend: "bwrap".into(),
+            ..auto.clone()
+        };
+        let err = find_backend(&bwrap, only_firejail).unwrap_err().to_string();
+        assert!(err.contains("未找到 bwrap 命令"), "{err}");
+        let err = find_backend(&auto, |_| None).unwrap_err().to_string();
+        assert!(err.contains("bwrap 或 firejail"), "{err}");
+    }
+
+    #[test]
+    fn bwrap_binds_the_workspace_over_a_read_only_root() {
+        let args = bwrap_args(&layout(false), Path::new("/home/me/.jarvis/workspace/src"));
+        let joined = args.join(" ");
+        assert!(joined.starts_with("--ro-bind / / --dev /dev --proc /proc --tmpfs /tmp"));
+        // Hidden directories are emptied before the workspace is bound back
+        let hide = joined.find("--tmpfs /home/me ").unwrap();
+        let bind = joined
+            .find("--bind /home/me/.jarvis/workspace /home/me/.jarvis/workspace")
+            .unwrap();
+        assert!(hide < bind);
+        assert!(joined.contains("--bind /data /data"));
+        assert!(joined.contains("--unshare-all"));
+        assert!(!joined.contains("--share-net"));
+        assert!(joined.ends_with("--chdir /home/me/.jarvis/workspace/src --"));
+
+        let args = bwrap_args(&layout(true), Path::new("/"));
+        assert!(args.contains(&"--share-net".to_string()));
+    }
+
+    #[test]
+    fn firejail_keeps_only_the_workspace_writable() {
+        let args = firejail_args(&layout(false));
+        assert_eq!(
+            args,
+            [
+                "--quiet",
+                "--noprofile",
+                "--private-tmp",
+                "--private-dev",
+                "--whitelist=/home/me/.jarvis/workspace",
+                "--read-only=/",
+                "--read-write=/home/me/.jarvis/workspace",
+                "--read-write=/data",
+                "--net=none",
+                "--",
+            ]
+        );
+        let outside = Layout {
+            workspace: vec![PathBuf::from("/srv/workspace")],
+            ..layout(true)
+        };
+        let args = firejail_args(&outside);
+        assert!(args.contains(&"--blacklist=/home/me".to_string()));
+        assert!(!args.contains(&"--net=none".to_string()));
+    }
+
+    /// Runs only where bwrap is installed and may create namespaces.
+    #[tokio::test]
+    async fn writes_outside_the_workspace_are_blocked() {
+        let config = SandboxRuntimeConfig {
+            backend: "bwrap".into(),
+            ..SandboxRuntimeConfig::default()
+        };
+        let workspace = TempDir::new().unwrap();
+        let runtime = match SandboxRuntime::start(&config, workspace.path()) {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                eprintln!("skipping: {e:#}");
+                return;
+            }
+        };
+        let outside = TempDir::new().unwrap();
+        let inside_file = workspace.path().join("inside.txt");
+        let outside_file = outside.path().join("outside.txt");
+
+        let output = runtime
+            .shell_command(
+                &format!("echo ok > {}", inside_file.display()),
+                workspace.path(),
+                &[],
+                Duration::from_secs(10),
+            )
+            .output()
+            .await
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        assert_eq!(std::fs::read_to_string(&inside_file).unwrap(), "ok\n");
+
+        for target in [
+            outside_file.clone(),
+            PathBuf::from("/etc/jarvis-sandbox-test"),
+        ] {
+            let output = runtime
+                .shell_command(
+                    &format!("echo no > {}", target.display()),
+                    workspace.path(),
+                    &[],
+                    Duration::from_secs(10),
+                )
+                .output()
+                .await
+                .unwrap();
+            assert!(!output.status.success(), "{target:?} was writable");
+            assert!(!target.exists());
+        }
+    }
+}
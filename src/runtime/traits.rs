@@ -1,4 +1,5 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Runtime adapter — abstracts platform differences so the same agent
 /// code runs on native, Docker, Cloudflare Workers, Raspberry Pi, etc.
@@ -22,4 +23,14 @@ pub trait RuntimeAdapter: Send + Sync {
     fn memory_budget(&self) -> u64 {
         0
     }
+
+    /// The command that runs `command` with `sh -c` in `cwd`, adding `env`
+    /// to a minimal environment. The caller kills it after `timeout`.
+    fn shell_command(
+        &self,
+        command: &str,
+        cwd: &Path,
+        env: &[(String, String)],
+        timeout: Duration,
+    ) -> tokio::process::Command;
 }
@@ -0,0 +1,526 @@
+//! Human-in-the-loop approval for shell commands outside the allowlist, and
+//! for destructive commands (`autonomy.destructive_commands`).
+//!
+//! In supervised mode the shell tool asks before refusing a command: the
+//! conversation that started the turn when it can answer (a gateway chat
+//! session, see [`route_requests`]), else the attached front-end (CLI or
+//! TUI). With no one to ask — one-shot `agent -m`, `POST /v1/agent` —
+//! commands off the allowlist are refused as before, and so are destructive
+//! ones. A gateway chat session can also opt in to destructive commands
+//! with [`APPROVE_DESTRUCTIVE_COMMAND`], and its "always" answers only
+//! extend its own allowlist (see [`SessionApproval`]).
+
+use anyhow::Result;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::config::Config;
+
+/// Default time to wait for an answer before giving up.
+pub const DEFAULT_APPROVAL_TIMEOUT_SECS: u64 = 120;
+
+/// The user's answer to an approval request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalDecision {
+    Approved,
+    Denied,
+    /// Approved, and the command names are added to the allowlist
+    Always,
+    TimedOut,
+}
+
+impl ApprovalDecision {
+    /// Parse a `[y/N/always]` reply; anything unrecognised counts as "no".
+    pub fn from_reply(reply: &str) -> Self {
+        match reply.trim().to_lowercase().as_str() {
+            "y" | "yes" | "是" | "允许" => Self::Approved,
+            "a" | "always" | "总是" | "始终允许" => Self::Always,
+            _ => Self::Denied,
+        }
+    }
+}
+
+/// A pending question for the user, answered through [`ApprovalRequest::respond`].
+#[derive(Debug)]
+pub struct ApprovalRequest {
+    pub command: String,
+    /// Command names not on the allowlist (what "always" would add)
+    pub unlisted: Vec<String>,
+    /// Why the command looks destructive, if it does
+    pub danger: Option<String>,
+    reply: oneshot::Sender<ApprovalDecision>,
+}
+
+impl ApprovalRequest {
+    pub fn prompt(&self) -> String {
+        match &self.danger {
+            Some(danger) if self.unlisted.is_empty() => format!(
+                "`{}` looks destructive ({danger}). Run it? [y/N]",
+                self.command
+            ),
+            Some(danger) => format!(
+                "`{}` looks destructive ({danger}). Allow it? [y/N/always]",
+                self.command
+            ),
+            None => format!("Allow `{}`? [y/N/always]", self.command),
+        }
+    }
+
+    pub fn respond(self, decision: ApprovalDecision) {
+        let _ = self.reply.send(decision);
+    }
+
+    /// The tool stopped waiting (timed out); answering is pointless.
+    pub fn is_cancelled(&self) -> bool {
+        self.reply.is_closed()
+    }
+}
+
+/// Routes approval requests from the shell tool to the attached front-end
+/// and remembers commands approved with "always" for the session.
+#[derive(Debug)]
+pub struct ApprovalGate {
+    handler: Mutex<Option<mpsc::UnboundedSender<ApprovalRequest>>>,
+    always_allowed: Mutex<Vec<String>>,
+    timeout: Duration,
+}
+
+impl Default for ApprovalGate {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(DEFAULT_APPROVAL_TIMEOUT_SECS))
+    }
+}
+
+impl Clone for ApprovalGate {
+    fn clone(&self) -> Self {
+        Self {
+            handler: Mutex::new(lock(&self.handler).clone()),
+            always_allowed: Mutex::new(lock(&self.always_allowed).clone()),
+            timeout: self.timeout,
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+impl ApprovalGate {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            handler: Mutex::new(None),
+            always_allowed: Mutex::new(Vec::new()),
+            timeout,
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Start receiving approval requests (replaces any earlier front-end).
+    pub fn attach(&self) -> mpsc::UnboundedReceiver<ApprovalRequest> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        *lock(&self.handler) = Some(tx);
+        rx
+    }
+
+    /// Whether `base_cmd` was approved with "always" by the front-end, or by
+    /// the chat session the current task runs for.
+    pub fn is_always_allowed(&self, base_cmd: &str) -> bool {
+        lock(&self.always_allowed).iter().any(|c| c == base_cmd)
+            || SessionApproval::current_allows(base_cmd)
+    }
+
+    /// Ask about `command` — the conversation of the current turn (see
+    /// [`route_requests`]) or the front-end. `None` when there is neither
+    /// (or it went away), i.e. the command should just be refused.
+    pub async fn request(
+        &self,
+        command: &str,
+        unlisted: Vec<String>,
+        danger: Option<String>,
+    ) -> Option<ApprovalDecision> {
+        let handler = TURN_APPROVALS
+            .try_with(Clone::clone)
+            .ok()
+            .or_else(|| lock(&self.handler).clone())?;
+        let (reply, answer) = oneshot::channel();
+        handler
+            .send(ApprovalRequest {
+                command: command.to_string(),
+                unlisted: unlisted.clone(),
+                danger,
+                reply,
+            })
+            .ok()?;
+
+        let decision = match tokio::time::timeout(self.timeout, answer).await {
+            Ok(Ok(decision)) => decision,
+            // Front-end dropped the request without answering
+            Ok(Err(_)) => ApprovalDecision::Denied,
+            Err(_) => ApprovalDecision::TimedOut,
+        };
+        if decision == ApprovalDecision::Always {
+            // A chat session's answer holds for that session only
+            let in_session = SESSION_APPROVAL
+                .try_with(|session| session.allow(&unlisted))
+                .is_ok();
+            if !in_session {
+                allow(&mut lock(&self.always_allowed), &unlisted);
+            }
+        }
+        Some(decision)
+    }
+}
+
+fn allow(always: &mut Vec<String>, commands: &[String]) {
+    for cmd in commands {
+        if !always.contains(cmd) {
+            always.push(cmd.clone());
+        }
+    }
+}
+
+tokio::task_local! {
+    static TURN_APPROVALS: mpsc::UnboundedSender<ApprovalRequest>;
+}
+
+/// Run `future` (one turn of a conversation) with its approval requests sent
+/// to `requests` rather than the attached front-end, so the user who started
+/// the turn is the one asked. The gate's timeout still applies.
+pub async fn route_requests<F: std::future::Future>(
+    requests: mpsc::UnboundedSender<ApprovalRequest>,
+    future: F,
+) -> F::Output {
+    TURN_APPROVALS.scope(requests, future).await
+}
+
+/// What a gateway chat user sends to let the session run destructive commands.
+pub const APPROVE_DESTRUCTIVE_COMMAND: &str = "/approve-destructive";
+/// What a gateway chat user sends to take that approval back.
+pub const REVOKE_DESTRUCTIVE_COMMAND: &str = "/revoke-destructive";
+
+tokio::task_local! {
+    static SESSION_APPROVAL: SessionApproval;
+}
+
+/// Approvals that belong to one chat session: destructive commands allowed
+/// up front, and command names answered with "always". The shell tool sees
+/// them while the session's turn runs inside [`SessionApproval::scope`].
+#[derive(Debug, Clone, Default)]
+pub struct SessionApproval(Arc<SessionApprovalState>);
+
+#[derive(Debug, Default)]
+struct SessionApprovalState {
+    destructive: AtomicBool,
+    always_allowed: Mutex<Vec<String>>,
+}
+
+impl SessionApproval {
+    pub fn grant(&self) {
+        self.0.destructive.store(true, Ordering::Relaxed);
+    }
+
+    pub fn revoke(&self) {
+        self.0.destructive.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_granted(&self) -> bool {
+        self.0.destructive.load(Ordering::Relaxed)
+    }
+
+    /// Add `commands` to the session's allowlist.
+    fn allow(&self, commands: &[String]) {
+        allow(&mut lock(&self.0.always_allowed), commands);
+    }
+
+    fn allows(&self, base_cmd: &str) -> bool {
+        lock(&self.0.always_allowed).iter().any(|c| c == base_cmd)
+    }
+
+    /// Run `future` (one turn of the session) with this approval in effect.
+    pub async fn scope<F: std::future::Future>(&self, future: F) -> F::Output {
+        SESSION_APPROVAL.scope(self.clone(), future).await
+    }
+
+    /// Whether the session the current task runs for approved destructive
+    /// commands. `false` outside any session.
+    pub fn current_granted() -> bool {
+        SESSION_APPROVAL
+            .try_with(SessionApproval::is_granted)
+            .unwrap_or(false)
+    }
+
+    /// Whether the session the current task runs for answered "always" for
+    /// `base_cmd`. `false` outside any session.
+    fn current_allows(base_cmd: &str) -> bool {
+        SESSION_APPROVAL
+            .try_with(|session| session.allows(base_cmd))
+            .unwrap_or(false)
+    }
+}
+
+/// Answer `request` with the user's `reply`, saving "always" answers to
+/// config.toml when `autonomy.persist_approvals` is set.
+pub fn answer(request: ApprovalRequest, reply: &str, config: &Config) -> ApprovalDecision {
+    let decision = ApprovalDecision::from_reply(reply);
+    if decision == ApprovalDecision::Always && config.autonomy.persist_approvals {
+        let config_path = config.config_path.clone();
+        let commands = request.unlisted.clone();
+        let persist = move || {
+            persist_allowed_commands(&config_path, &commands)
+                .unwrap_or_else(|e| tracing::warn!("保存命令白名单失败: {e:#}"));
+        };
+        // Saving may wait for the config lock; keep that off the runtime
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => drop(handle.spawn_blocking(persist)),
+            Err(_) => persist(),
+        }
+    }
+    request.respond(decision);
+    decision
+}
+
+/// Append `commands` to `autonomy.allowed_commands` in the config file.
+///
+/// Re-reads the file rather than saving the in-memory config, so environment
+/// overrides (API keys etc.) never end up on disk.
+fn persist_allowed_commands(config_path: &Path, commands: &[String]) -> Result<()> {
+    Config::update(config_path, |config| {
+        for cmd in commands {
+            if !config.autonomy.allowed_commands.contains(cmd) {
+                config.autonomy.allowed_commands.push(cmd.clone());
+            }
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_replies() {
+        assert_eq!(
+            ApprovalDecision::from_reply("y"),
+            ApprovalDecision::Approved
+        );
+        assert_eq!(
+            ApprovalDecision::from_reply(" YES "),
+            ApprovalDecision::Approved
+        );
+        assert_eq!(
+            ApprovalDecision::from_reply("always"),
+            ApprovalDecision::Always
+        );
+        assert_eq!(ApprovalDecision::from_reply(""), ApprovalDecision::Denied);
+        assert_eq!(ApprovalDecision::from_reply("n"), ApprovalDecision::Denied);
+        assert_eq!(
+            ApprovalDecision::from_reply("sure, why not"),
+            ApprovalDecision::Denied
+        );
+    }
+
+    #[tokio::test]
+    async fn no_front_end_means_no_decision() {
+        let gate = ApprovalGate::default();
+        assert_eq!(
+            gate.request("rm -rf target", vec!["rm".into()], None).await,
+            None
+        );
+
+        // A front-end that went away counts as none
+        drop(gate.attach());
+        assert_eq!(
+            gate.request("rm -rf target", vec!["rm".into()], None).await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn always_extends_the_session_allowlist() {
+        let gate = ApprovalGate::default();
+        let mut requests = gate.attach();
+        let front_end = tokio::spawn(async move {
+            let request = requests.recv().await.unwrap();
+            assert_eq!(request.prompt(), "Allow `rm -rf target`? [y/N/always]");
+            request.respond(ApprovalDecision::Always);
+        });
+
+        let decision = gate.request("rm -rf target", vec!["rm".into()], None).await;
+        front_end.await.unwrap();
+        assert_eq!(decision, Some(ApprovalDecision::Always));
+        assert!(gate.is_always_allowed("rm"));
+        assert!(!gate.is_always_allowed("curl"));
+    }
+
+    #[tokio::test]
+    async fn unanswered_request_times_out() {
+        let gate = ApprovalGate::new(Duration::from_millis(20));
+        let mut requests = gate.attach();
+        let decision = gate
+            .request("curl example.com", vec!["curl".into()], None)
+            .await;
+        assert_eq!(decision, Some(ApprovalDecision::TimedOut));
+
+        let request = requests.recv().await.unwrap();
+        assert!(request.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn routed_turns_ask_their_own_conversation() {
+        let gate = ApprovalGate::default();
+        let mut front_end = gate.attach();
+        let (tx, mut conversation) = mpsc::unbounded_channel::<ApprovalRequest>();
+        let asker = tokio::spawn(async move {
+            let request = conversation.recv().await.unwrap();
+            assert_eq!(request.command, "curl example.com");
+            request.respond(ApprovalDecision::from_reply("yes"));
+        });
+
+        let decision = route_requests(
+            tx,
+            gate.request("curl example.com", vec!["curl".into()], None),
+        )
+        .await;
+        asker.await.unwrap();
+        assert_eq!(decision, Some(ApprovalDecision::Approved));
+        assert!(front_end.try_recv().is_err());
+
+        // A conversation that went away has no one to ask, front-end or not
+        let (tx, conversation) = mpsc::unbounded_channel();
+        drop(conversation);
+        let decision = route_requests(
+            tx,
+            gate.request("curl example.com", vec!["curl".into()], None),
+        )
+        .await;
+        assert_eq!(decision, None);
+    }
+
+    #[tokio::test]
+    async fn destructive_prompt_and_session_scope() {
+        let gate = ApprovalGate::default();
+        let mut requests = gate.attach();
+        let front_end = tokio::spawn(async move {
+            let request = requests.recv().await.unwrap();
+            assert_eq!(
+                request.prompt(),
+                "`rm -rf build` looks destructive (matches `rm`). Run it? [y/N]"
+            );
+            request.respond(ApprovalDecision::Denied);
+        });
+        let decision = gate
+            .request("rm -rf build", vec![], Some("matches `rm`".into()))
+            .await;
+        front_end.await.unwrap();
+        assert_eq!(decision, Some(ApprovalDecision::Denied));
+
+        let session = SessionApproval::default();
+        assert!(
+            !session
+                .scope(async { SessionApproval::current_granted() })
+                .await
+        );
+        session.grant();
+        assert!(
+            session
+                .scope(async { SessionApproval::current_granted() })
+                .await
+        );
+        assert!(!SessionApproval::current_granted());
+        session.revoke();
+        assert!(
+            !session
+                .scope(async { SessionApproval::current_granted() })
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn session_always_answers_stay_in_the_session() {
+        let gate = ApprovalGate::default();
+        let session = SessionApproval::default();
+        let (tx, mut conversation) = mpsc::unbounded_channel::<ApprovalRequest>();
+        let asker = tokio::spawn(async move {
+            let request = conversation.recv().await.unwrap();
+            request.respond(ApprovalDecision::Always);
+        });
+
+        let decision = session
+            .scope(route_requests(
+                tx,
+                gate.request("curl example.com", vec!["curl".into()], None),
+            ))
+            .await;
+        asker.await.unwrap();
+        assert_eq!(decision, Some(ApprovalDecision::Always));
+        assert!(session.scope(async { gate.is_always_allowed("curl") }).await);
+        assert!(!gate.is_always_allowed("curl"), "not outside the session");
+        let other = SessionApproval::default();
+        assert!(!other.scope(async { gate.is_always_allowed("curl") }).await);
+    }
+
+    #[test]
+    fn persists_always_answers_to_config_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = Config {
+            config_path: tmp.path().join("config.toml"),
+            workspace_dir: tmp.path().join("workspace"),
+            ..Config::default()
+        };
+        config.save().unwrap();
+
+        persist_allowed_commands(&config.config_path, &["rm".into(), "git".into()]).unwrap();
+        let saved: Config =
+            toml::from_str(&std::fs::read_to_string(&config.config_path).unwrap()).unwrap();
+        let allowed = &saved.autonomy.allowed_commands;
+        assert_eq!(allowed.iter().filter(|c| *c == "rm").count(), 1);
+        assert_eq!(allowed.iter().filter(|c| *c == "git").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn always_answers_are_saved_off_the_runtime() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut config = Config {
+            config_path: tmp.path().join("config.toml"),
+            workspace_dir: tmp.path().join("workspace"),
+            ..Config::default()
+        };
+        config.autonomy.persist_approvals = true;
+        config.save().unwrap();
+
+        // Another writer holds the lock, so the save has to wait for it
+        let lock = crate::config::store::lock(&config.config_path).unwrap();
+        let (reply, answer_rx) = oneshot::channel();
+        let request = ApprovalRequest {
+            command: "rm x".into(),
+            unlisted: vec!["rm".into()],
+            danger: None,
+            reply,
+        };
+        assert_eq!(answer(request, "always", &config), ApprovalDecision::Always);
+        assert_eq!(answer_rx.await.unwrap(), ApprovalDecision::Always);
+        drop(lock);
+
+        let saved = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let loaded = Config::load_from(&config.config_path).unwrap();
+                if loaded.autonomy.allowed_commands.contains(&"rm".to_string()) {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await;
+        assert!(saved.is_ok(), "saved once the lock was released");
+    }
+}
@@ -0,0 +1,414 @@
+//! Append-only audit trail of tool actions: `workspace/state/audit.log`,
+//! one JSON object per line. The file is rotated to `audit.log.1…N` once it
+//! grows past [`MAX_LOG_BYTES`].
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Size at which `audit.log` is rotated.
+pub const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+/// Rotated copies kept next to `audit.log`.
+pub const LOG_BACKUPS: usize = 3;
+/// Longest redacted argument string stored per entry.
+const MAX_ARGS_CHARS: usize = 500;
+/// Argument keys whose values are never written, matched as substrings.
+const SECRET_ARG_KEYS: &[&str] = &[
+    "token",
+    "password",
+    "passwd",
+    "secret",
+    "api_key",
+    "apikey",
+    "authorization",
+    "credential",
+    "cookie",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditDecision {
+    Allowed,
+    Denied,
+}
+
+/// One line of the audit log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub tool: String,
+    /// Hash of the raw arguments, which may themselves contain secrets
+    pub args_hash: String,
+    pub decision: AuditDecision,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Where the action came from: cli, tui, cron, …
+    pub origin: String,
+    /// Arguments with secrets replaced by `[REDACTED]`, truncated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<String>,
+    /// Set once the tool has run; absent for calls refused up front
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+impl AuditEntry {
+    /// Case-insensitive substring match over tool, origin, reason and args.
+    pub fn matches(&self, term: &str) -> bool {
+        let term = term.to_lowercase();
+        [
+            Some(self.tool.as_str()),
+            Some(self.origin.as_str()),
+            self.reason.as_deref(),
+            self.args.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|field| field.to_lowercase().contains(&term))
+    }
+}
+
+/// Short SHA-256 fingerprint of tool arguments, enough to correlate repeats.
+pub fn args_hash(arguments: &str) -> String {
+    let digest = Sha256::digest(arguments.as_bytes());
+    hex::encode(&digest[..8])
+}
+
+/// Tool arguments as stored in the log: values under secret-looking keys are
+/// replaced, known token patterns are scrubbed, and the result is truncated.
+pub fn redact_arguments(arguments: &str) -> String {
+    let redacted = match serde_json::from_str::<serde_json::Value>(arguments) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        }
+        Err(_) => scrub(arguments),
+    };
+    crate::util::truncate_with_ellipsis(&redacted, MAX_ARGS_CHARS)
+}
+
+fn redact_value(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SECRET_ARG_KEYS.iter().any(|secret| key.contains(secret)) {
+                    *child = serde_json::Value::String("[REDACTED]".into());
+                } else {
+                    redact_value(child);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_value),
+        serde_json::Value::String(s) => *s = scrub(s),
+        _ => {}
+    }
+}
+
+/// Mask configured secrets and token-shaped credentials (e.g. in a curl
+/// command line).
+fn scrub(text: &str) -> String {
+    super::redact::redact(text)
+}
+
+/// Writer for the audit log. The default is disabled (tests, ad-hoc policies).
+#[derive(Debug, Default)]
+pub struct AuditLog {
+    path: Option<PathBuf>,
+    origin: String,
+    /// Serializes appends from concurrent tool calls
+    write_lock: Mutex<()>,
+}
+
+impl Clone for AuditLog {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            origin: self.origin.clone(),
+            write_lock: Mutex::new(()),
+        }
+    }
+}
+
+impl AuditLog {
+    /// Location of the audit log inside a workspace.
+    pub fn default_path(workspace_dir: &Path) -> PathBuf {
+        workspace_dir.join("state").join("audit.log")
+    }
+
+    pub fn new(path: PathBuf, origin: &str) -> Self {
+        Self {
+            path: Some(path),
+            origin: origin.to_string(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn set_origin(&mut self, origin: &str) {
+        self.origin = origin.to_string();
+    }
+
+    /// Where the logged actions come from (cli, tui, cron, …).
+    pub fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    /// Append an entry for a call that was decided without running the tool
+    /// (best-effort: a failed write is logged, never fatal).
+    pub fn record(
+        &self,
+        tool: &str,
+        arguments: &str,
+        decision: AuditDecision,
+        reason: Option<&str>,
+    ) {
+        self.write(&self.entry(tool, arguments, decision, reason));
+    }
+
+    /// Append an entry for a tool that ran, with its outcome.
+    pub fn record_execution(
+        &self,
+        tool: &str,
+        arguments: &str,
+        decision: AuditDecision,
+        reason: Option<&str>,
+        success: bool,
+        duration: Duration,
+    ) {
+        let mut entry = self.entry(tool, arguments, decision, reason);
+        entry.success = Some(success);
+        entry.duration_ms = Some(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX));
+        self.write(&entry);
+    }
+
+    fn entry(
+        &self,
+        tool: &str,
+        arguments: &str,
+        decision: AuditDecision,
+        reason: Option<&str>,
+    ) -> AuditEntry {
+        AuditEntry {
+            timestamp: Utc::now(),
+            tool: tool.to_string(),
+            args_hash: args_hash(arguments),
+            decision,
+            reason: reason.map(str::to_string),
+            origin: self.origin.clone(),
+            args: Some(redact_arguments(arguments)),
+            success: None,
+            duration_ms: None,
+        }
+    }
+
+    fn write(&self, entry: &AuditEntry) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let _guard = self
+            .write_lock
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Err(e) =
+            crate::daemon::logs::rotate_if_needed(path, MAX_LOG_BYTES, LOG_BACKUPS, false)
+        {
+            tracing::warn!("轮转审计日志失败 {}: {e:#}", path.display());
+        }
+        if let Err(e) = append(path, entry) {
+            tracing::warn!("写入审计日志失败 {}: {e:#}", path.display());
+        }
+    }
+}
+
+fn append(path: &Path, entry: &AuditEntry) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Read all entries oldest first, including rotated copies, skipping lines
+/// that don't parse (e.g. a torn last write).
+pub fn read_entries(path: &Path) -> Result<Vec<AuditEntry>> {
+    let mut entries = Vec::new();
+    for n in (1..=LOG_BACKUPS).rev() {
+        entries.extend(read_file(&crate::daemon::logs::backup_path(path, n))?);
+    }
+    entries.extend(read_file(path)?);
+    Ok(entries)
+}
+
+fn read_file(path: &Path) -> Result<Vec<AuditEntry>> {
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(e).with_context(|| format!("读取审计日志失败: {}", path.display()));
+        }
+    };
+    Ok(raw
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Parse a look-back window such as `90s`, `30m`, `24h` or `7d`.
+pub fn parse_since(value: &str) -> Result<chrono::Duration> {
+    let value = value.trim();
+    let (digits, unit) = value.split_at(value.len().saturating_sub(1));
+    let amount: i64 = digits
+        .parse()
+        .ok()
+        .filter(|n| *n > 0)
+        .with_context(|| format!("无效的时间范围「{value}」，示例: 30m、24h、7d"))?;
+    let duration = match unit {
+        "s" => chrono::Duration::seconds(amount),
+        "m" => chrono::Duration::minutes(amount),
+        "h" => chrono::Duration::hours(amount),
+        "d" => chrono::Duration::days(amount),
+        _ => anyhow::bail!("无效的时间单位「{value}」，支持 s、m、h、d"),
+    };
+    Ok(duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn records_append_as_json_lines() {
+        let tmp = TempDir::new().unwrap();
+        let path = AuditLog::default_path(tmp.path());
+        let log = AuditLog::new(path.clone(), "cli");
+
+        log.record("shell", r#"{"command":"ls"}"#, AuditDecision::Allowed, None);
+        log.record(
+            "file_write",
+            r#"{"path":"/etc/passwd"}"#,
+            AuditDecision::Denied,
+            Some("path not allowed"),
+        );
+
+        let raw = fs::read_to_string(&path).unwrap();
+        assert_eq!(raw.lines().count(), 2);
+
+        let entries = read_entries(&path).unwrap();
+        assert_eq!(entries[0].tool, "shell");
+        assert_eq!(entries[0].decision, AuditDecision::Allowed);
+        assert_eq!(entries[0].origin, "cli");
+        assert_eq!(entries[1].reason.as_deref(), Some("path not allowed"));
+        assert_eq!(entries[1].args_hash, args_hash(r#"{"path":"/etc/passwd"}"#));
+        assert_eq!(
+            entries[1].args.as_deref(),
+            Some(r#"{"path":"/etc/passwd"}"#)
+        );
+        assert_eq!(entries[1].success, None);
+    }
+
+    #[test]
+    fn executions_carry_outcome_and_duration() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("audit.log");
+        AuditLog::new(path.clone(), "gateway").record_execution(
+            "shell",
+            r#"{"command":"ls"}"#,
+            AuditDecision::Allowed,
+            None,
+            false,
+            Duration::from_millis(42),
+        );
+        let entry = &read_entries(&path).unwrap()[0];
+        assert_eq!(entry.success, Some(false));
+        assert_eq!(entry.duration_ms, Some(42));
+        assert!(entry.matches("GATEWAY"));
+        assert!(entry.matches("command"));
+        assert!(!entry.matches("file_read"));
+    }
+
+    #[test]
+    fn redacts_secret_arguments() {
+        let redacted = redact_arguments(
+            r#"{"url":"https://x","headers":{"Authorization":"Bearer abc"},"api_key":"k1","body":"key sk-live123"}"#,
+        );
+        assert!(!redacted.contains("abc"), "{redacted}");
+        assert!(!redacted.contains("k1"), "{redacted}");
+        assert!(!redacted.contains("sk-live123"), "{redacted}");
+        assert!(redacted.contains("https://x"));
+
+        let command =
+            redact_arguments(r#"{"command":"curl -H 'Authorization: Bearer tok123' https://api"}"#);
+        assert!(!command.contains("tok123"), "{command}");
+        assert!(command.contains("Bearer ****"));
+
+        assert_eq!(redact_arguments("not json sk-abc"), "not json sk-****");
+        assert!(redact_arguments(&"x".repeat(2000)).len() < 600);
+    }
+
+    #[test]
+    fn rotates_and_reads_across_backups() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("audit.log");
+        let log = AuditLog::new(path.clone(), "cli");
+        log.record("shell", "{}", AuditDecision::Allowed, None);
+        // Inflate the live file past the limit so the next write rotates it
+        let mut raw = fs::read_to_string(&path).unwrap();
+        raw.push_str(&" ".repeat(usize::try_from(MAX_LOG_BYTES).unwrap()));
+        raw.push('\n');
+        fs::write(&path, raw).unwrap();
+        log.record("file_read", "{}", AuditDecision::Denied, None);
+
+        assert!(crate::daemon::logs::backup_path(&path, 1).exists());
+        assert!(fs::metadata(&path).unwrap().len() < 1024);
+        let tools: Vec<String> = read_entries(&path)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.tool)
+            .collect();
+        assert_eq!(tools, ["shell", "file_read"]);
+    }
+
+    #[test]
+    fn disabled_log_writes_nothing() {
+        AuditLog::default().record("shell", "{}", AuditDecision::Allowed, None);
+    }
+
+    #[test]
+    fn reading_skips_garbage_and_missing_file() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("audit.log");
+        assert!(read_entries(&path).unwrap().is_empty());
+
+        AuditLog::new(path.clone(), "tui").record("shell", "{}", AuditDecision::Denied, None);
+        let mut raw = fs::read_to_string(&path).unwrap();
+        raw.push_str("{\"timestamp\": \"trunc");
+        fs::write(&path, raw).unwrap();
+        assert_eq!(read_entries(&path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn parses_since_windows() {
+        assert_eq!(parse_since("24h").unwrap(), chrono::Duration::hours(24));
+        assert_eq!(parse_since("30m").unwrap(), chrono::Duration::minutes(30));
+        assert_eq!(parse_since("7d").unwrap(), chrono::Duration::days(7));
+        for bad in ["", "h", "0h", "12", "3w", "-1h"] {
+            assert!(parse_since(bad).is_err(), "{bad:?} accepted");
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use super::audit::{self, AuditDecision, AuditEntry, AuditLog};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+
+/// Handle `jarvis security ...`.
+pub fn handle_command(command: crate::SecurityCommands, config: &Config) -> Result<()> {
+    match command {
+        crate::SecurityCommands::Audit {
+            since,
+            tool,
+            denied,
+        } => {
+            let cutoff = Utc::now() - audit::parse_since(&since)?;
+            let path = AuditLog::default_path(&config.workspace_dir);
+            let entries =
+                filter_entries(audit::read_entries(&path)?, cutoff, tool.as_deref(), denied);
+            if entries.is_empty() {
+                println!("最近 {since} 内没有匹配的审计记录（{}）。", path.display());
+                return Ok(());
+            }
+
+            println!("🛡️  审计日志（最近 {since}，{} 条）:", entries.len());
+            for entry in &entries {
+                print_entry(entry);
+            }
+            let denied_count = entries
+                .iter()
+                .filter(|e| e.decision == AuditDecision::Denied)
+                .count();
+            println!();
+            println!(
+                "合计: 允许 {}，拒绝 {denied_count}",
+                entries.len() - denied_count
+            );
+            Ok(())
+        }
+        crate::SecurityCommands::RotateKey => {
+            let count = crate::config::secrets::rotate_key(&config.config_path)?;
+            println!(
+                "🔑 已生成新密钥并重新加密 {count} 个密钥（{}）",
+                config.config_path.display()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Handle `jarvis audit ...`.
+pub fn handle_audit_command(command: crate::AuditCommands, config: &Config) -> Result<()> {
+    let path = AuditLog::default_path(&config.workspace_dir);
+    let entries = audit::read_entries(&path)?;
+    let (entries, empty) = match command {
+        crate::AuditCommands::Tail { lines } => (
+            last_entries(entries, lines),
+            format!("审计日志为空（{}）。", path.display()),
+        ),
+        crate::AuditCommands::Grep { term } => (
+            entries.into_iter().filter(|e| e.matches(&term)).collect(),
+            format!("没有包含「{term}」的审计记录（{}）。", path.display()),
+        ),
+    };
+    if entries.is_empty() {
+        println!("{empty}");
+        return Ok(());
+    }
+    for entry in &entries {
+        print_entry(entry);
+    }
+    Ok(())
+}
+
+fn last_entries(mut entries: Vec<AuditEntry>, count: usize) -> Vec<AuditEntry> {
+    let skip = entries.len().saturating_sub(count);
+    entries.split_off(skip)
+}
+
+fn filter_entries(
+    entries: Vec<AuditEntry>,
+    cutoff: DateTime<Utc>,
+    tool: Option<&str>,
+    denied_only: bool,
+) -> Vec<AuditEntry> {
+    entries
+        .into_iter()
+        .filter(|e| e.timestamp >= cutoff)
+        .filter(|e| tool.is_none_or(|t| e.tool == t))
+        .filter(|e| !denied_only || e.decision == AuditDecision::Denied)
+        .collect()
+}
+
+fn print_entry(entry: &AuditEntry) {
+    let (icon, decision) = match entry.decision {
+        AuditDecision::Allowed => ("✅", "允许"),
+        AuditDecision::Denied => ("⛔", "拒绝"),
+    };
+    let reason = entry
+        .reason
+        .as_deref()
+        .map(|r| format!("（{r}）"))
+        .unwrap_or_default();
+    let outcome = match (entry.success, entry.duration_ms) {
+        (Some(true), Some(ms)) => format!("  成功 {ms}ms"),
+        (Some(false), Some(ms)) => format!("  失败 {ms}ms"),
+        _ => String::new(),
+    };
+    println!(
+        "  {icon} {} [{}] {:<14} {decision}{reason}{outcome}  参数 {}",
+        entry
+            .timestamp
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S"),
+        entry.origin,
+        entry.tool,
+        entry.args.as_deref().unwrap_or(&entry.args_hash),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tool: &str, decision: AuditDecision, hours_ago: i64) -> AuditEntry {
+        AuditEntry {
+            timestamp: Utc::now() - chrono::Duration::hours(hours_ago),
+            tool: tool.into(),
+            args_hash: audit::args_hash("{}"),
+            decision,
+            reason: None,
+            origin: "cli".into(),
+            args: None,
+            success: None,
+            duration_ms: None,
+        }
+    }
+
+    #[test]
+    fn filters_by_time_tool_and_decision() {
+        let entries = vec![
+            entry("shell", AuditDecision::Allowed, 1),
+            entry("shell", AuditDecision::Denied, 2),
+            entry("file_read", AuditDecision::Denied, 3),
+            entry("shell", AuditDecision::Denied, 48),
+        ];
+        let cutoff = Utc::now() - chrono::Duration::hours(24);
+
+        assert_eq!(
+            filter_entries(entries.clone(), cutoff, None, false).len(),
+            3
+        );
+        assert_eq!(
+            filter_entries(entries.clone(), cutoff, Some("shell"), false).len(),
+            2
+        );
+        let denied = filter_entries(entries, cutoff, Some("shell"), true);
+        assert_eq!(denied.len(), 1);
+        assert_eq!(denied[0].decision, AuditDecision::Denied);
+    }
+
+    #[test]
+    fn tail_keeps_the_newest_entries() {
+        let entries: Vec<AuditEntry> = (0..5)
+            .rev()
+            .map(|h| entry(&format!("tool{h}"), AuditDecision::Allowed, h))
+            .collect();
+        let tail = last_entries(entries.clone(), 2);
+        assert_eq!(tail.len(), 2);
+        assert_eq!(tail[1].tool, "tool0");
+        assert_eq!(last_entries(entries, 10).len(), 5);
+    }
+}
@@ -0,0 +1,305 @@
+//! Shell command patterns for `autonomy.allowed_commands` and
+//! `autonomy.denied_commands`.
+//!
+//! Each entry is one of:
+//!
+//! - a bare command name (`git`) — matches that command with any arguments
+//! - a glob over the whole sub-command (`git *`, `cargo test*`), where `*`
+//!   is any text and `?` any one character
+//! - a regex when it starts with `^` or ends with `$` (`^ls( |$)`)
+//!
+//! Globs and regexes see the sub-command with leading `VAR=value`
+//! assignments dropped, the command's directory stripped (`/usr/bin/ls` →
+//! `ls`) and whitespace collapsed.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Default `autonomy.destructive_commands`: sub-commands that need the user's
+/// confirmation in supervised mode even when the command policy permits them.
+pub const DEFAULT_DESTRUCTIVE_COMMANDS: &[&str] = &[
+    "^rm( .*)? (-[a-zA-Z]*[rR][a-zA-Z]*|--recursive)( |$)",
+    "dd",
+    "shred",
+    "^mkfs",
+    "^git push( .*)? (-f|--force|--force-with-lease)( |$)",
+    "^git push( .*)? \\+",
+    "git reset --hard*",
+    "^git clean( .*)? -[a-zA-Z]*f",
+    "^find .* -delete( |$)",
+];
+
+/// Whether the command list says what may run or what may not.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandPolicyMode {
+    /// Only sub-commands matching `allowed_commands` run
+    #[default]
+    Allow,
+    /// Everything runs except sub-commands matching `denied_commands`
+    Deny,
+}
+
+/// One compiled entry of a command list.
+#[derive(Debug, Clone)]
+pub enum CommandPattern {
+    Name(String),
+    Pattern { source: String, regex: Regex },
+}
+
+impl CommandPattern {
+    pub fn parse(raw: &str) -> Result<Self> {
+        let source = raw.trim();
+        if source.is_empty() {
+            anyhow::bail!("命令模式不能为空");
+        }
+        let regex = if source.starts_with('^') || source.ends_with('$') {
+            Regex::new(source).with_context(|| format!("无效的正则「{source}」"))?
+        } else if source.contains(['*', '?']) || source.contains(char::is_whitespace) {
+            Regex::new(&glob_to_regex(source))
+                .with_context(|| format!("无效的通配符「{source}」"))?
+        } else {
+            return Ok(Self::Name(source.to_string()));
+        };
+        Ok(Self::Pattern {
+            source: source.to_string(),
+            regex,
+        })
+    }
+
+    /// Whether the pattern covers a sub-command: names compare against
+    /// `base_cmd`, globs and regexes against the `normalized` sub-command.
+    fn matches(&self, base_cmd: &str, normalized: &str) -> bool {
+        match self {
+            Self::Name(name) => name == base_cmd,
+            Self::Pattern { regex, .. } => regex.is_match(normalized),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Name(name) => name,
+            Self::Pattern { source, .. } => source,
+        }
+    }
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    for word in glob.split_whitespace() {
+        if regex.len() > 1 {
+            regex.push(' ');
+        }
+        for c in word.chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+/// The compiled command list and how to apply it.
+#[derive(Debug, Clone)]
+pub struct CommandPolicy {
+    mode: CommandPolicyMode,
+    patterns: Vec<CommandPattern>,
+}
+
+impl CommandPolicy {
+    /// Compile `patterns`, failing on the first malformed one.
+    pub fn new(mode: CommandPolicyMode, patterns: &[String]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|raw| CommandPattern::parse(raw))
+            .collect::<Result<_>>()?;
+        Ok(Self { mode, patterns })
+    }
+
+    /// An allowlist of plain command names.
+    pub fn allow_names(names: &[&str]) -> Self {
+        Self {
+            mode: CommandPolicyMode::Allow,
+            patterns: names
+                .iter()
+                .map(|name| CommandPattern::Name((*name).to_string()))
+                .collect(),
+        }
+    }
+
+    /// The compiled [`DEFAULT_DESTRUCTIVE_COMMANDS`], matched in deny mode.
+    pub fn default_destructive() -> Self {
+        let patterns: Vec<String> = DEFAULT_DESTRUCTIVE_COMMANDS
+            .iter()
+            .map(|p| (*p).to_string())
+            .collect();
+        Self::new(CommandPolicyMode::Deny, &patterns).expect("default destructive patterns compile")
+    }
+
+    /// Build from `[autonomy]`, using `allowed_commands` or `denied_commands`
+    /// depending on `command_policy_mode`.
+    pub fn from_config(autonomy: &crate::config::AutonomyConfig) -> Result<Self> {
+        let (field, patterns) = match autonomy.command_policy_mode {
+            CommandPolicyMode::Allow => ("allowed_commands", &autonomy.allowed_commands),
+            CommandPolicyMode::Deny => ("denied_commands", &autonomy.denied_commands),
+        };
+        Self::new(autonomy.command_policy_mode, patterns)
+            .with_context(|| format!("autonomy.{field} 中有无效的命令模式"))
+    }
+
+    pub fn mode(&self) -> CommandPolicyMode {
+        self.mode
+    }
+
+    pub fn patterns(&self) -> impl Iterator<Item = &str> {
+        self.patterns.iter().map(CommandPattern::as_str)
+    }
+
+    /// The first pattern covering a sub-command, regardless of mode.
+    pub fn matched(&self, segment: &str) -> Option<&str> {
+        let base_cmd = super::policy::base_command(segment);
+        let normalized = normalize(base_cmd, segment);
+        self.patterns
+            .iter()
+            .find(|pattern| pattern.matches(base_cmd, &normalized))
+            .map(CommandPattern::as_str)
+    }
+
+    /// Why a sub-command is refused, or `None` when it may run.
+    pub fn violation(&self, segment: &str) -> Option<String> {
+        let base_cmd = super::policy::base_command(segment);
+        let normalized = normalize(base_cmd, segment);
+        match (self.mode, self.matched(segment)) {
+            (CommandPolicyMode::Allow, Some(_)) | (CommandPolicyMode::Deny, None) => None,
+            (CommandPolicyMode::Allow, None) => Some(format!(
+                "`{normalized}` does not match autonomy.allowed_commands"
+            )),
+            (CommandPolicyMode::Deny, Some(pattern)) => Some(format!(
+                "`{normalized}` matches the denied pattern `{pattern}`"
+            )),
+        }
+    }
+}
+
+/// `FOO=1 /usr/bin/git   status` → `git status`
+fn normalize(base_cmd: &str, segment: &str) -> String {
+    std::iter::once(base_cmd)
+        .chain(
+            super::policy::skip_env_assignments(segment)
+                .split_whitespace()
+                .skip(1),
+        )
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(mode: CommandPolicyMode, patterns: &[&str]) -> CommandPolicy {
+        let patterns: Vec<String> = patterns.iter().map(|p| (*p).to_string()).collect();
+        CommandPolicy::new(mode, &patterns).unwrap()
+    }
+
+    fn allows(policy: &CommandPolicy, segment: &str) -> bool {
+        policy.violation(segment).is_none()
+    }
+
+    #[test]
+    fn names_globs_and_regexes_match() {
+        let p = policy(
+            CommandPolicyMode::Allow,
+            &["cat", "git *", "cargo test*", "^ls( |$)", "npm run ?uild"],
+        );
+        for ok in [
+            "cat README.md",
+            "/bin/cat a b",
+            "git status",
+            "git   log  --oneline",
+            "cargo test --lib",
+            "cargo test",
+            "ls",
+            "ls -la",
+            "FOO=1 /usr/bin/ls -la",
+            "npm run build",
+        ] {
+            assert!(allows(&p, ok), "{ok} should match");
+        }
+        for refused in [
+            "git",
+            "cargo build",
+            "lsof",
+            "catalog",
+            "npm run rebuild",
+            "rm -rf /",
+        ] {
+            assert!(!allows(&p, refused), "{refused} should not match");
+        }
+    }
+
+    #[test]
+    fn deny_mode_refuses_only_matches() {
+        let p = policy(
+            CommandPolicyMode::Deny,
+            &["rm", "git push*", "^curl .*example\\.com"],
+        );
+        assert!(allows(&p, "ls -la"));
+        assert!(allows(&p, "git status"));
+        assert!(!allows(&p, "rm -rf target"));
+        assert!(!allows(&p, "git push --force"));
+        assert!(!allows(&p, "curl -s https://example.com/x"));
+        assert!(allows(&p, "curl -s https://example.org"));
+
+        let reason = p.violation("git push origin").unwrap();
+        assert!(reason.contains("git push*"), "{reason}");
+    }
+
+    #[test]
+    fn default_destructive_patterns() {
+        let p = CommandPolicy::default_destructive();
+        for flagged in [
+            "rm -rf build",
+            "rm -fr /",
+            "rm --recursive target",
+            "/bin/rm -r -f x",
+            "dd if=/dev/zero of=/dev/sda",
+            "mkfs.ext4 /dev/sdb1",
+            "git push --force origin main",
+            "git push origin +main",
+            "git reset --hard HEAD~3",
+            "git clean -fdx",
+            "find . -name '*.o' -delete",
+        ] {
+            assert!(p.matched(flagged).is_some(), "{flagged} should be flagged");
+        }
+        for safe in [
+            "rm notes.txt",
+            "ls -rf",
+            "git push origin main",
+            "git reset HEAD file",
+            "git clean -n",
+            "find . -name '*.rs'",
+            "cargo build --release",
+        ] {
+            assert!(p.matched(safe).is_none(), "{safe} should not be flagged");
+        }
+    }
+
+    #[test]
+    fn malformed_patterns_are_rejected() {
+        for bad in ["^ls(", "", "   ", "(unclosed$"] {
+            assert!(
+                CommandPattern::parse(bad).is_err(),
+                "{bad:?} should be rejected"
+            );
+        }
+        let err =
+            CommandPolicy::new(CommandPolicyMode::Allow, &["git".into(), "^[".into()]).unwrap_err();
+        assert!(format!("{err:#}").contains("^["));
+    }
+}
@@ -0,0 +1,323 @@
+//! Paired gateway devices: `workspace/state/gateway_tokens.json`.
+//!
+//! `jarvis gateway pair` issues a one-time code here; a client redeems it
+//! through `POST /pair` for a long-lived bearer token, of which only the
+//! SHA-256 hash is kept, next to the device name and when it was last used.
+//! The CLI and a running gateway share the file, so every change happens
+//! under an exclusive file lock — a revoked device can't be written back by
+//! a concurrent last-seen update.
+
+use super::pairing::{constant_time_eq, generate_code, generate_token, hash_token};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+/// How long a pairing code from `jarvis gateway pair` stays valid.
+pub const PAIRING_CODE_TTL_SECS: i64 = 600;
+/// Skip last-seen writes for a device used again within this window.
+const LAST_SEEN_RESOLUTION_SECS: i64 = 60;
+
+/// A device holding a bearer token.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PairedDevice {
+    /// Short random id, for revoking devices that share a name
+    pub id: String,
+    pub name: String,
+    pub token_hash: String,
+    pub paired_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// An unredeemed pairing code.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingCode {
+    code_hash: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DeviceFile {
+    #[serde(default)]
+    devices: Vec<PairedDevice>,
+    #[serde(default)]
+    codes: Vec<PendingCode>,
+}
+
+impl DeviceFile {
+    fn prune_codes(&mut self, now: DateTime<Utc>) {
+        self.codes.retain(|code| code.expires_at > now);
+    }
+}
+
+/// A freshly issued pairing code.
+#[derive(Debug, Clone)]
+pub struct PairingCode {
+    pub code: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Handle on a workspace's paired devices file.
+#[derive(Debug, Clone)]
+pub struct DeviceStore {
+    path: PathBuf,
+}
+
+impl DeviceStore {
+    pub fn new(workspace_dir: &Path) -> Self {
+        Self {
+            path: Self::default_path(workspace_dir),
+        }
+    }
+
+    /// Location of the devices file inside a workspace.
+    pub fn default_path(workspace_dir: &Path) -> PathBuf {
+        workspace_dir.join("state").join("gateway_tokens.json")
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Issue a single-use pairing code valid for [`PAIRING_CODE_TTL_SECS`].
+    pub fn issue_code(&self) -> Result<PairingCode> {
+        let code = generate_code();
+        let expires_at = Utc::now() + Duration::seconds(PAIRING_CODE_TTL_SECS);
+        self.update(|file| {
+            file.prune_codes(Utc::now());
+            file.codes.push(PendingCode {
+                code_hash: hash_token(&code),
+                expires_at,
+            });
+        })?;
+        Ok(PairingCode { code, expires_at })
+    }
+
+    /// Redeem a pairing code for a new device, returning its bearer token,
+    /// or `None` when the code is unknown, expired or already used.
+    pub fn redeem(&self, code: &str, device_name: &str) -> Result<Option<String>> {
+        let code_hash = hash_token(code.trim());
+        self.update(|file| {
+            file.prune_codes(Utc::now());
+            let index = file
+                .codes
+                .iter()
+                .position(|pending| constant_time_eq(&pending.code_hash, &code_hash))?;
+            file.codes.remove(index);
+            let token = generate_token();
+            file.devices.push(new_device(device_name, &token));
+            Some(token)
+        })
+    }
+
+    /// Record a device paired some other way (the gateway's startup code).
+    pub fn register(&self, device_name: &str, token: &str) -> Result<PairedDevice> {
+        let device = new_device(device_name, token);
+        self.update(|file| file.devices.push(device.clone()))?;
+        Ok(device)
+    }
+
+    /// Whether `token` belongs to a paired device, noting that it was seen.
+    pub fn authenticate(&self, token: &str) -> Result<bool> {
+        let token_hash = hash_token(token);
+        let now = Utc::now();
+        let stale = |device: &PairedDevice| {
+            device
+                .last_seen
+                .is_none_or(|seen| now - seen >= Duration::seconds(LAST_SEEN_RESOLUTION_SECS))
+        };
+
+        let Some(device) = self
+            .read()?
+            .devices
+            .into_iter()
+            .find(|device| constant_time_eq(&device.token_hash, &token_hash))
+        else {
+            return Ok(false);
+        };
+        if stale(&device) {
+            // Looked up again under the write lock: it may have been revoked
+            return self.update(|file| {
+                let device = file
+                    .devices
+                    .iter_mut()
+                    .find(|device| constant_time_eq(&device.token_hash, &token_hash));
+                device.is_some_and(|device| {
+                    device.last_seen = Some(now);
+                    true
+                })
+            });
+        }
+        Ok(true)
+    }
+
+    /// All paired devices, oldest first.
+    pub fn devices(&self) -> Result<Vec<PairedDevice>> {
+        Ok(self.read()?.devices)
+    }
+
+    /// Revoke the device with this id or name.
+    pub fn revoke(&self, name_or_id: &str) -> Result<PairedDevice> {
+        self.update(|file| {
+            let matches: Vec<usize> = file
+                .devices
+                .iter()
+                .enumerate()
+                .filter(|(_, device)| device.id == name_or_id || device.name == name_or_id)
+                .map(|(index, _)| index)
+                .collect();
+            match matches.as_slice() {
+                [] => anyhow::bail!(
+                    "没有名为 {name_or_id} 的已配对设备（jarvis gateway devices 查看）"
+                ),
+                [index] => Ok(file.devices.remove(*index)),
+                _ => {
+                    let ids: Vec<&str> = matches
+                        .iter()
+                        .map(|&index| file.devices[index].id.as_str())
+                        .collect();
+                    anyhow::bail!(
+                        "有多个名为 {name_or_id} 的设备，请改用设备 ID：{}",
+                        ids.join(", ")
+                    )
+                }
+            }
+        })?
+    }
+
+    fn read(&self) -> Result<DeviceFile> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(DeviceFile::default()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("读取失败 {}", self.path.display()));
+            }
+        };
+        file.lock_shared()
+            .with_context(|| format!("锁定失败 {}", self.path.display()))?;
+        self.parse(&mut file)
+    }
+
+    /// Read-modify-write under an exclusive lock.
+    fn update<T>(&self, change: impl FnOnce(&mut DeviceFile) -> T) -> Result<T> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)
+            .with_context(|| format!("打开失败 {}", self.path.display()))?;
+        file.lock()
+            .with_context(|| format!("锁定失败 {}", self.path.display()))?;
+
+        let mut data = self.parse(&mut file)?;
+        let out = change(&mut data);
+        let json = serde_json::to_vec_pretty(&data)?;
+        file.set_len(0)?;
+        file.rewind()?;
+        file.write_all(&json)
+            .with_context(|| format!("写入失败 {}", self.path.display()))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&self.path, fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(out)
+    }
+
+    fn parse(&self, file: &mut File) -> Result<DeviceFile> {
+        let mut raw = String::new();
+        file.read_to_string(&mut raw)
+            .with_context(|| format!("读取失败 {}", self.path.display()))?;
+        if raw.trim().is_empty() {
+            return Ok(DeviceFile::default());
+        }
+        serde_json::from_str(&raw).with_context(|| format!("解析失败 {}", self.path.display()))
+    }
+}
+
+fn new_device(name: &str, token: &str) -> PairedDevice {
+    let id = uuid::Uuid::new_v4().as_simple().to_string()[..8].to_string();
+    let name = name.trim();
+    PairedDevice {
+        name: if name.is_empty() {
+            format!("device-{id}")
+        } else {
+            name.to_string()
+        },
+        id,
+        token_hash: hash_token(token),
+        paired_at: Utc::now(),
+        last_seen: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_is_single_use() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = DeviceStore::new(tmp.path());
+        let issued = store.issue_code().unwrap();
+        assert_eq!(issued.code.len(), 6);
+
+        let token = store.redeem(&issued.code, "phone").unwrap().unwrap();
+        assert!(token.starts_with("zc_"));
+        assert!(store.redeem(&issued.code, "laptop").unwrap().is_none());
+        assert!(store.authenticate(&token).unwrap());
+        assert!(!store.authenticate("zc_wrong").unwrap());
+
+        let devices = store.devices().unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].name, "phone");
+        assert!(devices[0].last_seen.is_some());
+
+        let raw = fs::read_to_string(store.path()).unwrap();
+        assert!(!raw.contains(&token), "tokens are stored hashed");
+        assert!(!raw.contains(&issued.code), "codes are stored hashed");
+    }
+
+    #[test]
+    fn expired_codes_are_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = DeviceStore::new(tmp.path());
+        let issued = store.issue_code().unwrap();
+        store
+            .update(|file| file.codes[0].expires_at = Utc::now() - Duration::seconds(1))
+            .unwrap();
+
+        assert!(store.redeem(&issued.code, "phone").unwrap().is_none());
+        assert!(store.devices().unwrap().is_empty());
+    }
+
+    #[test]
+    fn revoke_by_name_or_id() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = DeviceStore::new(tmp.path());
+        store.register("phone", "zc_phone").unwrap();
+        let second = store.register("phone", "zc_phone2").unwrap();
+        store.register("", "zc_unnamed").unwrap();
+
+        let err = store.revoke("phone").unwrap_err().to_string();
+        assert!(err.contains(&second.id));
+        assert!(store.revoke("tablet").is_err());
+
+        assert_eq!(store.revoke(&second.id).unwrap().name, "phone");
+        assert!(!store.authenticate("zc_phone2").unwrap());
+        store.revoke("phone").unwrap();
+        assert!(!store.authenticate("zc_phone").unwrap());
+
+        let remaining = store.devices().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert!(remaining[0].name.starts_with("device-"));
+    }
+}
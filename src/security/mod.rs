@@ -1,7 +1,15 @@
+pub mod approval;
+pub mod audit;
+pub mod cli;
+pub mod commands;
+pub mod devices;
 pub mod pairing;
 pub mod policy;
+pub mod redact;
 pub mod secrets;
 
+pub use cli::{handle_audit_command, handle_command};
+pub use commands::CommandPolicyMode;
 #[allow(unused_imports)]
 pub use pairing::PairingGuard;
 pub use policy::{AutonomyLevel, SecurityPolicy};
@@ -5,11 +5,19 @@
 // header on a `POST /pair` request. The server responds with a bearer token
 // that must be sent on all subsequent requests via `Authorization: Bearer <token>`.
 //
-// Already-paired tokens are persisted in config so restarts don't require
-// re-pairing.
-
+// Devices paired this way, or with a code from `jarvis gateway pair`, are
+// kept in the workspace device store (see `devices`) so restarts don't
+// require re-pairing; tokens listed in config are accepted too. The gateway
+// also accepts its own token, created on first run and kept in
+// `<workspace>/.gateway_token`; `jarvis gateway pair --token` prints or
+// rotates it.
+
+use super::devices::{DeviceStore, PAIRING_CODE_TTL_SECS};
+use anyhow::{Context, Result};
 use sha2::{Digest, Sha256};
 use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use std::time::Instant;
 
@@ -28,9 +36,11 @@ pub struct PairingGuard {
     /// Whether pairing is required at all.
     require_pairing: bool,
     /// One-time pairing code (generated on startup, consumed on first pair).
-    pairing_code: Mutex<Option<String>>,
-    /// Set of SHA-256 hashed bearer tokens (persisted across restarts).
+    pairing_code: Mutex<Option<(String, Instant)>>,
+    /// Set of SHA-256 hashed bearer tokens (from config, plus the gateway token).
     paired_tokens: Mutex<HashSet<String>>,
+    /// Devices paired through `POST /pair`, shared with the CLI.
+    devices: Option<DeviceStore>,
     /// Brute-force protection: failed attempt counter + lockout time.
     failed_attempts: Mutex<(u32, Option<Instant>)>,
 }
@@ -56,7 +66,7 @@ impl PairingGuard {
             })
             .collect();
         let code = if require_pairing && tokens.is_empty() {
-            Some(generate_code())
+            Some((generate_code(), Instant::now()))
         } else {
             None
         };
@@ -64,16 +74,34 @@ impl PairingGuard {
             require_pairing,
             pairing_code: Mutex::new(code),
             paired_tokens: Mutex::new(tokens),
+            devices: None,
             failed_attempts: Mutex::new((0, None)),
         }
     }
 
-    /// The one-time pairing code (only set when no tokens exist yet).
+    /// Persist pairings in (and accept tokens from) a device store. No
+    /// startup code is offered once the store has paired devices.
+    #[must_use]
+    pub fn with_devices(mut self, devices: DeviceStore) -> Self {
+        if devices.devices().is_ok_and(|paired| !paired.is_empty()) {
+            *self
+                .pairing_code
+                .get_mut()
+                .unwrap_or_else(std::sync::PoisonError::into_inner) = None;
+        }
+        self.devices = Some(devices);
+        self
+    }
+
+    /// The one-time startup pairing code (only set when nothing is paired
+    /// yet, and only for [`PAIRING_CODE_TTL_SECS`]).
     pub fn pairing_code(&self) -> Option<String> {
         self.pairing_code
             .lock()
             .unwrap_or_else(std::sync::PoisonError::into_inner)
-            .clone()
+            .as_ref()
+            .filter(|(_, issued)| issued.elapsed().as_secs() < PAIRING_CODE_TTL_SECS.unsigned_abs())
+            .map(|(code, _)| code.clone())
     }
 
     /// Whether pairing is required at all.
@@ -81,9 +109,10 @@ impl PairingGuard {
         self.require_pairing
     }
 
-    /// Attempt to pair with the given code. Returns a bearer token on success.
+    /// Attempt to pair `device_name` with the given code — the startup code
+    /// or one from the device store. Returns a bearer token on success.
     /// Returns `Err(lockout_seconds)` if locked out due to brute force.
-    pub fn try_pair(&self, code: &str) -> Result<Option<String>, u64> {
+    pub fn try_pair(&self, code: &str, device_name: &str) -> Result<Option<String>, u64> {
         // Check brute force lockout
         {
             let attempts = self
@@ -105,28 +134,52 @@ impl PairingGuard {
                 .pairing_code
                 .lock()
                 .unwrap_or_else(std::sync::PoisonError::into_inner);
-            if let Some(ref expected) = *pairing_code {
-                if constant_time_eq(code.trim(), expected.trim()) {
-                    // Reset failed attempts on success
-                    {
-                        let mut attempts = self
-                            .failed_attempts
-                            .lock()
-                            .unwrap_or_else(std::sync::PoisonError::into_inner);
-                        *attempts = (0, None);
-                    }
-                    let token = generate_token();
-                    let mut tokens = self
-                        .paired_tokens
+            let expected = pairing_code.as_ref().filter(|(_, issued)| {
+                issued.elapsed().as_secs() < PAIRING_CODE_TTL_SECS.unsigned_abs()
+            });
+            if let Some((expected, _)) = expected
+                && constant_time_eq(code.trim(), expected.trim())
+            {
+                // Reset failed attempts on success
+                {
+                    let mut attempts = self
+                        .failed_attempts
                         .lock()
                         .unwrap_or_else(std::sync::PoisonError::into_inner);
-                    tokens.insert(hash_token(&token));
+                    *attempts = (0, None);
+                }
+                let token = generate_token();
+                let stored = self.devices.as_ref().is_some_and(|devices| {
+                    devices
+                        .register(device_name, &token)
+                        .inspect_err(|e| tracing::warn!("保存已配对设备失败：{e:#}"))
+                        .is_ok()
+                });
+                if !stored {
+                    self.paired_tokens
+                        .lock()
+                        .unwrap_or_else(std::sync::PoisonError::into_inner)
+                        .insert(hash_token(&token));
+                }
+
+                // Consume the pairing code so it cannot be reused
+                *pairing_code = None;
 
-                    // Consume the pairing code so it cannot be reused
-                    *pairing_code = None;
+                return Ok(Some(token));
+            }
+        }
 
+        if let Some(devices) = &self.devices {
+            match devices.redeem(code, device_name) {
+                Ok(Some(token)) => {
+                    *self
+                        .failed_attempts
+                        .lock()
+                        .unwrap_or_else(std::sync::PoisonError::into_inner) = (0, None);
                     return Ok(Some(token));
                 }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("读取配对码失败：{e:#}"),
             }
         }
 
@@ -151,20 +204,41 @@ impl PairingGuard {
             return true;
         }
         let hashed = hash_token(token);
-        let tokens = self
+        let known = self
             .paired_tokens
             .lock()
-            .unwrap_or_else(std::sync::PoisonError::into_inner);
-        tokens.contains(&hashed)
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .contains(&hashed);
+        known
+            || self.devices.as_ref().is_some_and(|devices| {
+                devices
+                    .authenticate(token)
+                    .inspect_err(|e| tracing::warn!("读取已配对设备失败：{e:#}"))
+                    .unwrap_or(false)
+            })
+    }
+
+    /// Accept an additional bearer token (e.g. the workspace gateway token)
+    /// without affecting the one-time pairing code.
+    pub fn accept_token(&self, token: &str) {
+        self.paired_tokens
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(hash_token(token));
     }
 
     /// Returns true if the gateway is already paired (has at least one token).
     pub fn is_paired(&self) -> bool {
-        let tokens = self
+        let has_tokens = !self
             .paired_tokens
             .lock()
-            .unwrap_or_else(std::sync::PoisonError::into_inner);
-        !tokens.is_empty()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .is_empty();
+        has_tokens
+            || self
+                .devices
+                .as_ref()
+                .is_some_and(|devices| devices.devices().is_ok_and(|paired| !paired.is_empty()))
     }
 
     /// Get all paired token hashes (for persisting to config).
@@ -178,7 +252,7 @@ impl PairingGuard {
 }
 
 /// Generate a 6-digit numeric pairing code using cryptographically secure randomness.
-fn generate_code() -> String {
+pub(super) fn generate_code() -> String {
     // UUID v4 uses getrandom (backed by /dev/urandom on Linux, BCryptGenRandom
     // on Windows) — a CSPRNG. We extract 4 bytes from it for a uniform random
     // number in [0, 1_000_000).
@@ -202,12 +276,50 @@ fn generate_code() -> String {
 }
 
 /// Generate a cryptographically-adequate bearer token (hex-encoded).
-fn generate_token() -> String {
+pub(super) fn generate_token() -> String {
     format!("zc_{}", uuid::Uuid::new_v4().as_simple())
 }
 
+/// File under the workspace that holds the gateway token.
+const GATEWAY_TOKEN_FILE: &str = ".gateway_token";
+
+/// Where the gateway token for `workspace_dir` is stored.
+pub fn gateway_token_path(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join(GATEWAY_TOKEN_FILE)
+}
+
+/// Read the workspace gateway token, creating it on first use.
+pub fn load_or_create_gateway_token(workspace_dir: &Path) -> Result<String> {
+    let path = gateway_token_path(workspace_dir);
+    if path.exists() {
+        let token = fs::read_to_string(&path)
+            .with_context(|| format!("读取 gateway token 失败：{}", path.display()))?;
+        let token = token.trim();
+        if !token.is_empty() {
+            return Ok(token.to_string());
+        }
+    }
+    rotate_gateway_token(workspace_dir)
+}
+
+/// Replace the workspace gateway token with a fresh one and return it.
+pub fn rotate_gateway_token(workspace_dir: &Path) -> Result<String> {
+    let path = gateway_token_path(workspace_dir);
+    fs::create_dir_all(workspace_dir)?;
+    let token = generate_token();
+    fs::write(&path, format!("{token}\n")).context("写入 gateway token 失败")?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+            .context("设置 gateway token 文件权限失败")?;
+    }
+    Ok(token)
+}
+
 /// SHA-256 hash a bearer token for storage. Returns lowercase hex.
-fn hash_token(token: &str) -> String {
+pub(super) fn hash_token(token: &str) -> String {
     format!("{:x}", Sha256::digest(token.as_bytes()))
 }
 
@@ -278,7 +390,7 @@ mod tests {
     fn try_pair_correct_code() {
         let guard = PairingGuard::new(true, &[]);
         let code = guard.pairing_code().unwrap().to_string();
-        let token = guard.try_pair(&code).unwrap();
+        let token = guard.try_pair(&code, "test").unwrap();
         assert!(token.is_some());
         assert!(token.unwrap().starts_with("zc_"));
         assert!(guard.is_paired());
@@ -287,7 +399,7 @@ mod tests {
     #[test]
     fn try_pair_wrong_code() {
         let guard = PairingGuard::new(true, &[]);
-        let result = guard.try_pair("000000").unwrap();
+        let result = guard.try_pair("000000", "test").unwrap();
         // Might succeed if code happens to be 000000, but extremely unlikely
         // Just check it returns Ok(None) normally
         let _ = result;
@@ -296,7 +408,7 @@ mod tests {
     #[test]
     fn try_pair_empty_code() {
         let guard = PairingGuard::new(true, &[]);
-        assert!(guard.try_pair("").unwrap().is_none());
+        assert!(guard.try_pair("", "test").unwrap().is_none());
     }
 
     #[test]
@@ -344,7 +456,7 @@ mod tests {
     fn pair_then_authenticate() {
         let guard = PairingGuard::new(true, &[]);
         let code = guard.pairing_code().unwrap().to_string();
-        let token = guard.try_pair(&code).unwrap().unwrap();
+        let token = guard.try_pair(&code, "test").unwrap().unwrap();
         assert!(guard.is_authenticated(&token));
         assert!(!guard.is_authenticated("wrong"));
     }
@@ -448,11 +560,11 @@ mod tests {
         let guard = PairingGuard::new(true, &[]);
         // Exhaust all attempts with wrong codes
         for i in 0..MAX_PAIR_ATTEMPTS {
-            let result = guard.try_pair(&format!("wrong_{i}"));
+            let result = guard.try_pair(&format!("wrong_{i}"), "test");
             assert!(result.is_ok(), "Attempt {i} should not be locked out yet");
         }
         // Next attempt should be locked out
-        let result = guard.try_pair("another_wrong");
+        let result = guard.try_pair("another_wrong", "test");
         assert!(
             result.is_err(),
             "Should be locked out after {MAX_PAIR_ATTEMPTS} attempts"
@@ -471,24 +583,83 @@ mod tests {
         let code = guard.pairing_code().unwrap().to_string();
         // Fail a few times
         for _ in 0..3 {
-            let _ = guard.try_pair("wrong");
+            let _ = guard.try_pair("wrong", "test");
         }
         // Correct code should still work (under MAX_PAIR_ATTEMPTS)
-        let result = guard.try_pair(&code).unwrap();
+        let result = guard.try_pair(&code, "test").unwrap();
         assert!(result.is_some(), "Correct code should work before lockout");
     }
 
+    #[test]
+    fn accepted_token_authenticates_and_keeps_pairing_code() {
+        let guard = PairingGuard::new(true, &[]);
+        guard.accept_token("zc_workspace");
+        assert!(guard.is_authenticated("zc_workspace"));
+        assert!(!guard.is_authenticated("zc_other"));
+        assert!(guard.pairing_code().is_some());
+    }
+
+    #[test]
+    fn gateway_token_is_created_once_and_rotated() {
+        let tmp = tempfile::tempdir().unwrap();
+        let token = load_or_create_gateway_token(tmp.path()).unwrap();
+        assert!(token.starts_with("zc_"));
+        assert_eq!(load_or_create_gateway_token(tmp.path()).unwrap(), token);
+
+        let rotated = rotate_gateway_token(tmp.path()).unwrap();
+        assert_ne!(rotated, token);
+        assert_eq!(load_or_create_gateway_token(tmp.path()).unwrap(), rotated);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(gateway_token_path(tmp.path()))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
     #[test]
     fn lockout_returns_remaining_seconds() {
         let guard = PairingGuard::new(true, &[]);
         for _ in 0..MAX_PAIR_ATTEMPTS {
-            let _ = guard.try_pair("wrong");
+            let _ = guard.try_pair("wrong", "test");
         }
-        let err = guard.try_pair("wrong").unwrap_err();
+        let err = guard.try_pair("wrong", "test").unwrap_err();
         // Should be close to PAIR_LOCKOUT_SECS (within a second)
         assert!(
             err >= PAIR_LOCKOUT_SECS - 1,
             "Remaining lockout should be ~{PAIR_LOCKOUT_SECS}s, got {err}s"
         );
     }
+
+    #[test]
+    fn guard_with_devices_persists_and_redeems_issued_codes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = DeviceStore::new(tmp.path());
+        let guard = PairingGuard::new(true, &[]).with_devices(store.clone());
+
+        // Startup code pairings land in the store
+        let code = guard.pairing_code().unwrap();
+        let first = guard.try_pair(&code, "laptop").unwrap().unwrap();
+        assert_eq!(store.devices().unwrap()[0].name, "laptop");
+
+        // Codes issued later (by the CLI) are honoured once
+        let issued = store.issue_code().unwrap();
+        let second = guard.try_pair(&issued.code, "phone").unwrap().unwrap();
+        assert!(guard.try_pair(&issued.code, "phone").unwrap().is_none());
+        assert!(guard.is_authenticated(&first));
+        assert!(guard.is_authenticated(&second));
+
+        store.revoke("phone").unwrap();
+        assert!(!guard.is_authenticated(&second));
+
+        // A restarted gateway doesn't offer a startup code again
+        let restarted = PairingGuard::new(true, &[]).with_devices(store);
+        assert!(restarted.pairing_code().is_none());
+        assert!(restarted.is_paired());
+        assert!(restarted.is_authenticated(&first));
+    }
 }
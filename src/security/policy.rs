@@ -1,7 +1,11 @@
+use super::approval::{ApprovalDecision, ApprovalGate, SessionApproval};
+use super::audit::{AuditDecision, AuditLog};
+use super::commands::{CommandPolicy, CommandPolicyMode};
+use crate::config::RateLimitsConfig;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 /// How much autonomy the agent has
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -70,17 +74,87 @@ impl Clone for ActionTracker {
     }
 }
 
+/// Rate-limit bucket a tool's calls are counted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolCategory {
+    Shell,
+    File,
+    Memory,
+    Network,
+    /// Only counted against the shared `max_actions_per_hour` budget
+    Other,
+}
+
+impl ToolCategory {
+    pub fn of(tool: &str) -> Self {
+        match tool {
+            "shell" => Self::Shell,
+            "file_read" | "file_write" | "file_edit" => Self::File,
+            "browser" | "browser_open" | "web_search" | "http_request" | "composio" => {
+                Self::Network
+            }
+            t if t.starts_with("memory_") => Self::Memory,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// One sliding window per tool category with its own limit.
+#[derive(Debug, Clone)]
+pub struct CategoryTrackers {
+    shell: ActionTracker,
+    file: ActionTracker,
+    memory: ActionTracker,
+    network: ActionTracker,
+}
+
+impl CategoryTrackers {
+    pub fn new() -> Self {
+        Self {
+            shell: ActionTracker::new(),
+            file: ActionTracker::new(),
+            memory: ActionTracker::new(),
+            network: ActionTracker::new(),
+        }
+    }
+
+    fn get(&self, category: ToolCategory) -> Option<&ActionTracker> {
+        match category {
+            ToolCategory::Shell => Some(&self.shell),
+            ToolCategory::File => Some(&self.file),
+            ToolCategory::Memory => Some(&self.memory),
+            ToolCategory::Network => Some(&self.network),
+            ToolCategory::Other => None,
+        }
+    }
+}
+
 /// Security policy enforced on all tool executions
 #[derive(Debug, Clone)]
 pub struct SecurityPolicy {
     pub autonomy: AutonomyLevel,
     pub workspace_dir: PathBuf,
     pub workspace_only: bool,
-    pub allowed_commands: Vec<String>,
+    /// Compiled `allowed_commands` / `denied_commands`
+    pub commands: CommandPolicy,
+    /// Compiled `destructive_commands`, confirmed before running in
+    /// supervised mode
+    pub destructive: CommandPolicy,
     pub forbidden_paths: Vec<String>,
     pub max_actions_per_hour: u32,
     pub max_cost_per_day_cents: u32,
     pub tracker: ActionTracker,
+    /// Human approval for commands off the allowlist (supervised mode)
+    pub approvals: ApprovalGate,
+    /// Shell command timeout when the call sets none
+    pub shell_timeout: Duration,
+    /// Longest timeout a shell call may ask for
+    pub max_shell_timeout: Duration,
+    /// Per-category hourly limits (0 = use the shared budget)
+    pub rate_limits: RateLimitsConfig,
+    pub category_trackers: CategoryTrackers,
+    /// Record of every tool call decision (disabled unless built from config)
+    pub audit: AuditLog,
 }
 
 impl Default for SecurityPolicy {
@@ -89,21 +163,11 @@ impl Default for SecurityPolicy {
             autonomy: AutonomyLevel::Supervised,
             workspace_dir: PathBuf::from("."),
             workspace_only: true,
-            allowed_commands: vec![
-                "git".into(),
-                "npm".into(),
-                "cargo".into(),
-                "ls".into(),
-                "cat".into(),
-                "grep".into(),
-                "find".into(),
-                "echo".into(),
-                "pwd".into(),
-                "wc".into(),
-                "head".into(),
-                "tail".into(),
-                "date".into(),
-            ],
+            commands: CommandPolicy::allow_names(&[
+                "git", "npm", "cargo", "ls", "cat", "grep", "find", "echo", "pwd", "wc", "head",
+                "tail", "date",
+            ]),
+            destructive: CommandPolicy::default_destructive(),
             forbidden_paths: vec![
                 // System directories (blocked even when workspace_only=false)
                 "/etc".into(),
@@ -129,13 +193,19 @@ impl Default for SecurityPolicy {
             max_actions_per_hour: 20,
             max_cost_per_day_cents: 500,
             tracker: ActionTracker::new(),
+            approvals: ApprovalGate::default(),
+            shell_timeout: Duration::from_secs(crate::tools::shell::DEFAULT_TIMEOUT_SECS),
+            max_shell_timeout: Duration::from_secs(crate::tools::shell::DEFAULT_MAX_TIMEOUT_SECS),
+            rate_limits: RateLimitsConfig::default(),
+            category_trackers: CategoryTrackers::new(),
+            audit: AuditLog::default(),
         }
     }
 }
 
 /// Skip leading environment variable assignments (e.g. `FOO=bar cmd args`).
 /// Returns the remainder starting at the first non-assignment word.
-fn skip_env_assignments(s: &str) -> &str {
+pub(super) fn skip_env_assignments(s: &str) -> &str {
     let mut rest = s;
     loop {
         let Some(word) = rest.split_whitespace().next() else {
@@ -156,84 +226,202 @@ fn skip_env_assignments(s: &str) -> &str {
     }
 }
 
+/// Split a shell command on separators (`|`, `&&`, `||`, `;`, newlines)
+/// into trimmed, non-empty sub-commands.
+fn split_commands(command: &str) -> Vec<String> {
+    let mut normalized = command.to_string();
+    for sep in ["&&", "||"] {
+        normalized = normalized.replace(sep, "\x00");
+    }
+    for sep in ['\n', ';', '|'] {
+        normalized = normalized.replace(sep, "\x00");
+    }
+    normalized
+        .split('\x00')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Command name of a sub-command: env assignments and any path stripped.
+pub(super) fn base_command(segment: &str) -> &str {
+    skip_env_assignments(segment)
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .rsplit('/')
+        .next()
+        .unwrap_or("")
+}
+
+/// Canonicalize `path`; for a path that doesn't exist yet, canonicalize its
+/// deepest existing ancestor and re-append the missing components (which,
+/// not existing, can't be symlinks).
+pub fn canonicalize_lenient(path: &Path) -> std::io::Result<PathBuf> {
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        match existing.canonicalize() {
+            Ok(resolved) => {
+                return Ok(missing
+                    .iter()
+                    .rev()
+                    .fold(resolved, |acc: PathBuf, part| acc.join(part)));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+                    return Err(e);
+                };
+                missing.push(name.to_os_string());
+                existing = parent;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// First symlink along `relative` (walking down from `base`), as a path
+/// relative to `base`.
+fn first_symlink(base: &Path, relative: &Path) -> Option<PathBuf> {
+    let mut current = base.to_path_buf();
+    let mut shown = PathBuf::new();
+    for component in relative.components() {
+        current.push(component);
+        shown.push(component);
+        let meta = std::fs::symlink_metadata(&current).ok()?;
+        if meta.file_type().is_symlink() {
+            return Some(shown);
+        }
+    }
+    None
+}
+
 impl SecurityPolicy {
     /// Check if a shell command is allowed.
+    pub fn is_command_allowed(&self, command: &str) -> bool {
+        self.command_violation(command).is_none()
+    }
+
+    /// Why `command` is refused by [`Self::is_command_allowed`], in words the
+    /// model can act on. `None` when the command may run.
     ///
     /// Validates the **entire** command string, not just the first word:
     /// - Blocks subshell operators (`` ` ``, `$(`) that hide arbitrary execution
     /// - Splits on command separators (`|`, `&&`, `||`, `;`, newlines) and
-    ///   validates each sub-command against the allowlist
+    ///   checks each sub-command against the command policy
     /// - Blocks output redirections (`>`, `>>`) that could write outside workspace
-    pub fn is_command_allowed(&self, command: &str) -> bool {
+    pub fn command_violation(&self, command: &str) -> Option<String> {
         if self.autonomy == AutonomyLevel::ReadOnly {
-            return false;
+            return Some("commands cannot run in read-only autonomy mode".into());
         }
 
         // Block subshell/expansion operators — these allow hiding arbitrary
         // commands inside an allowed command (e.g. `echo $(rm -rf /)`)
         if command.contains('`') || command.contains("$(") || command.contains("${") {
-            return false;
+            return Some("subshells and `${}` expansions are not allowed".into());
         }
 
         // Block output redirections — they can write to arbitrary paths
         if command.contains('>') {
-            return false;
+            return Some("output redirection is not allowed".into());
         }
 
         // Split on command separators and validate each sub-command.
-        // We collect segments by scanning for separator characters.
-        let mut normalized = command.to_string();
-        for sep in ["&&", "||"] {
-            normalized = normalized.replace(sep, "\x00");
-        }
-        for sep in ['\n', ';', '|'] {
-            normalized = normalized.replace(sep, "\x00");
-        }
-
-        for segment in normalized.split('\x00') {
-            let segment = segment.trim();
-            if segment.is_empty() {
+        let segments = split_commands(command);
+        for segment in &segments {
+            let base_cmd = base_command(segment);
+            if base_cmd.is_empty() || self.approvals.is_always_allowed(base_cmd) {
                 continue;
             }
+            if let Some(violation) = self.commands.violation(segment) {
+                return Some(violation);
+            }
+        }
 
-            // Strip leading env var assignments (e.g. FOO=bar cmd)
-            let cmd_part = skip_env_assignments(segment);
-
-            let base_cmd = cmd_part
-                .split_whitespace()
-                .next()
-                .unwrap_or("")
-                .rsplit('/')
-                .next()
-                .unwrap_or("");
+        // At least one command must be present
+        if segments
+            .iter()
+            .any(|s| skip_env_assignments(s).split_whitespace().next().is_some())
+        {
+            None
+        } else {
+            Some("the command is empty".into())
+        }
+    }
 
-            if base_cmd.is_empty() {
-                continue;
-            }
+    /// Passes the command policy, or approved with "always" this session.
+    fn is_listed(&self, segment: &str) -> bool {
+        self.approvals.is_always_allowed(base_command(segment))
+            || self.commands.violation(segment).is_none()
+    }
 
-            if !self
-                .allowed_commands
-                .iter()
-                .any(|allowed| allowed == base_cmd)
+    /// Base command names in `command` that the command policy refuses.
+    pub fn unlisted_commands(&self, command: &str) -> Vec<String> {
+        let mut unlisted: Vec<String> = Vec::new();
+        for segment in split_commands(command) {
+            let base_cmd = base_command(&segment);
+            if !base_cmd.is_empty()
+                && !self.is_listed(&segment)
+                && !unlisted.iter().any(|c| c == base_cmd)
             {
-                return false;
+                unlisted.push(base_cmd.to_string());
             }
         }
+        unlisted
+    }
 
-        // At least one command must be present
-        let has_cmd = normalized.split('\x00').any(|s| {
-            let s = skip_env_assignments(s.trim());
-            s.split_whitespace().next().is_some_and(|w| !w.is_empty())
-        });
-
-        has_cmd
+    /// Why `command` needs the user's confirmation before it runs even
+    /// though the policy may permit it: a sub-command matches
+    /// `destructive_commands`, or it defines a shell function the way
+    /// `:(){ :|:& };:` does. Only supervised mode asks, and not within a chat
+    /// session that approved destructive commands.
+    pub fn destructive_reason(&self, command: &str) -> Option<String> {
+        if self.autonomy != AutonomyLevel::Supervised || SessionApproval::current_granted() {
+            return None;
+        }
+        if command
+            .split_whitespace()
+            .collect::<String>()
+            .contains("(){")
+        {
+            return Some("defines a shell function, as fork bombs do".into());
+        }
+        split_commands(command).iter().find_map(|segment| {
+            self.destructive
+                .matched(segment)
+                .map(|pattern| format!("matches `{pattern}`"))
+        })
+    }
+
+    /// Ask the user whether to run a command [`Self::is_command_allowed`]
+    /// rejected or [`Self::destructive_reason`] flagged. Only supervised mode
+    /// asks; `None` means there is no one to ask and the command stays
+    /// refused.
+    pub async fn request_approval(
+        &self,
+        command: &str,
+        danger: Option<String>,
+    ) -> Option<ApprovalDecision> {
+        if self.autonomy != AutonomyLevel::Supervised {
+            return None;
+        }
+        self.approvals
+            .request(command, self.unlisted_commands(command), danger)
+            .await
     }
 
     /// Check if a file path is allowed (no path traversal, within workspace)
     pub fn is_path_allowed(&self, path: &str) -> bool {
+        self.path_violation(path).is_none()
+    }
+
+    /// Why `path` is refused by [`Self::is_path_allowed`], in words the
+    /// model can act on. `None` when the path is allowed.
+    pub fn path_violation(&self, path: &str) -> Option<&'static str> {
         // Block null bytes (can truncate paths in C-backed syscalls)
         if path.contains('\0') {
-            return false;
+            return Some("path contains a null byte");
         }
 
         // Block path traversal: check for ".." as a path component
@@ -241,13 +429,13 @@ impl SecurityPolicy {
             .components()
             .any(|c| matches!(c, std::path::Component::ParentDir))
         {
-            return false;
+            return Some("`..` components are not allowed");
         }
 
         // Block URL-encoded traversal attempts (e.g. ..%2f)
         let lower = path.to_lowercase();
         if lower.contains("..%2f") || lower.contains("%2f..") {
-            return false;
+            return Some("`..` components are not allowed");
         }
 
         // Expand tilde for comparison
@@ -263,7 +451,7 @@ impl SecurityPolicy {
 
         // Block absolute paths when workspace_only is set
         if self.workspace_only && Path::new(&expanded).is_absolute() {
-            return false;
+            return Some("use a path relative to the workspace");
         }
 
         // Block forbidden paths using path-component-aware matching
@@ -280,11 +468,11 @@ impl SecurityPolicy {
             };
             let forbidden_path = Path::new(&forbidden_expanded);
             if expanded_path.starts_with(forbidden_path) {
-                return false;
+                return Some("the location is on the forbidden list");
             }
         }
 
-        true
+        None
     }
 
     /// Validate that a resolved path is still inside the workspace.
@@ -293,11 +481,27 @@ impl SecurityPolicy {
         // Must be under workspace_dir (prevents symlink escapes).
         // Prefer canonical workspace root so `/a/../b` style config paths don't
         // cause false positives or negatives.
-        let workspace_root = self
-            .workspace_dir
+        resolved.starts_with(self.workspace_root())
+    }
+
+    fn workspace_root(&self) -> PathBuf {
+        self.workspace_dir
             .canonicalize()
-            .unwrap_or_else(|_| self.workspace_dir.clone());
-        resolved.starts_with(workspace_root)
+            .unwrap_or_else(|_| self.workspace_dir.clone())
+    }
+
+    /// Error for a path that resolved outside the workspace, naming the
+    /// symlink it went through when there is one.
+    pub fn escape_error(&self, path: &str, resolved: &Path) -> String {
+        let via = first_symlink(&self.workspace_dir, Path::new(path))
+            .map(|link| format!(" through the symlink `{}`", link.display()))
+            .unwrap_or_default();
+        format!(
+            "Resolved path escapes workspace: `{path}` points to {}{via}. \
+             File tools only work inside {}",
+            resolved.display(),
+            self.workspace_root().display()
+        )
     }
 
     /// Check if autonomy level permits any action at all
@@ -312,6 +516,47 @@ impl SecurityPolicy {
         count <= self.max_actions_per_hour as usize
     }
 
+    /// Record a call to `tool` against its category's limit, or against the
+    /// shared budget when the category has none. Returns `false` if
+    /// rate-limited.
+    pub fn record_tool_action(&self, tool: &str) -> bool {
+        let category = ToolCategory::of(tool);
+        let limit = match category {
+            ToolCategory::Shell => self.rate_limits.shell,
+            ToolCategory::File => self.rate_limits.file,
+            ToolCategory::Memory => self.rate_limits.memory,
+            ToolCategory::Network => self.rate_limits.network,
+            ToolCategory::Other => 0,
+        };
+        match self.category_trackers.get(category) {
+            Some(tracker) if limit > 0 => tracker.record() <= limit as usize,
+            _ => self.record_action(),
+        }
+    }
+
+    /// Like [`Self::record_tool_action`], also writing rate-limited calls to
+    /// the audit log.
+    pub fn admit_tool_call(&self, tool: &str, arguments: &str) -> bool {
+        let allowed = self.record_tool_action(tool);
+        if !allowed {
+            self.audit_denial(tool, arguments, "rate limit exceeded");
+        }
+        allowed
+    }
+
+    /// Log an action refused by this policy.
+    pub fn audit_denial(&self, tool: &str, arguments: &str, reason: &str) {
+        self.audit
+            .record(tool, arguments, AuditDecision::Denied, Some(reason));
+    }
+
+    /// Tag audit entries with where the actions come from (cli, tui, cron, …).
+    #[must_use]
+    pub fn with_origin(mut self, origin: &str) -> Self {
+        self.audit.set_origin(origin);
+        self
+    }
+
     /// Check if the rate limit would be exceeded without recording.
     pub fn is_rate_limited(&self) -> bool {
         self.tracker.count() >= self.max_actions_per_hour as usize
@@ -326,11 +571,33 @@ impl SecurityPolicy {
             autonomy: autonomy_config.level,
             workspace_dir: workspace_dir.to_path_buf(),
             workspace_only: autonomy_config.workspace_only,
-            allowed_commands: autonomy_config.allowed_commands.clone(),
+            commands: CommandPolicy::from_config(autonomy_config).unwrap_or_else(|e| {
+                // Fail closed: a broken denylist must not let everything through
+                tracing::warn!("{e:#}；在修正配置前拒绝所有 shell 命令");
+                CommandPolicy::allow_names(&[])
+            }),
+            destructive: CommandPolicy::new(
+                CommandPolicyMode::Deny,
+                &autonomy_config.destructive_commands,
+            )
+            .unwrap_or_else(|e| {
+                tracing::warn!(
+                    "autonomy.destructive_commands 中有无效的命令模式: {e:#}；改用默认列表"
+                );
+                CommandPolicy::default_destructive()
+            }),
             forbidden_paths: autonomy_config.forbidden_paths.clone(),
             max_actions_per_hour: autonomy_config.max_actions_per_hour,
             max_cost_per_day_cents: autonomy_config.max_cost_per_day_cents,
             tracker: ActionTracker::new(),
+            approvals: ApprovalGate::new(Duration::from_secs(
+                autonomy_config.approval_timeout_secs,
+            )),
+            shell_timeout: Duration::from_secs(autonomy_config.shell_timeout_secs.max(1)),
+            max_shell_timeout: Duration::from_secs(autonomy_config.max_shell_timeout_secs.max(1)),
+            rate_limits: autonomy_config.rate_limits.clone(),
+            category_trackers: CategoryTrackers::new(),
+            audit: AuditLog::new(AuditLog::default_path(workspace_dir), "cli"),
         }
     }
 }
@@ -391,6 +658,45 @@ mod tests {
 
     // ── is_command_allowed ───────────────────────────────────
 
+    #[test]
+    fn unlisted_commands_names_each_blocked_command_once() {
+        let p = default_policy();
+        assert_eq!(
+            p.unlisted_commands("rm -rf target && ls | xargs rm; /usr/bin/curl x"),
+            ["rm", "xargs", "curl"]
+        );
+        assert!(p.unlisted_commands("git status && cargo test").is_empty());
+    }
+
+    #[tokio::test]
+    async fn approval_only_requested_in_supervised_mode() {
+        for autonomy in [AutonomyLevel::ReadOnly, AutonomyLevel::Full] {
+            let p = SecurityPolicy {
+                autonomy,
+                ..SecurityPolicy::default()
+            };
+            let _front_end = p.approvals.attach();
+            assert_eq!(p.request_approval("rm -rf target", None).await, None);
+            assert_eq!(p.destructive_reason("rm -rf target"), None);
+        }
+    }
+
+    #[tokio::test]
+    async fn destructive_commands_flagged_in_supervised_mode() {
+        let p = default_policy();
+        let reason = p.destructive_reason("git status && rm -rf target").unwrap();
+        assert!(reason.contains("rm"), "{reason}");
+        assert!(p.destructive_reason(":(){ :|:& };:").is_some());
+        assert_eq!(p.destructive_reason("git status && cargo test"), None);
+
+        let session = SessionApproval::default();
+        session.grant();
+        let in_session = session
+            .scope(async { p.destructive_reason("rm -rf target") })
+            .await;
+        assert_eq!(in_session, None);
+    }
+
     #[test]
     fn allowed_commands_basic() {
         let p = default_policy();
@@ -455,7 +761,7 @@ mod tests {
     #[test]
     fn custom_allowlist() {
         let p = SecurityPolicy {
-            allowed_commands: vec!["docker".into(), "kubectl".into()],
+            commands: CommandPolicy::allow_names(&["docker", "kubectl"]),
             ..SecurityPolicy::default()
         };
         assert!(p.is_command_allowed("docker ps"));
@@ -467,7 +773,7 @@ mod tests {
     #[test]
     fn empty_allowlist_blocks_everything() {
         let p = SecurityPolicy {
-            allowed_commands: vec![],
+            commands: CommandPolicy::allow_names(&[]),
             ..SecurityPolicy::default()
         };
         assert!(!p.is_command_allowed("ls"));
@@ -544,18 +850,27 @@ mod tests {
             level: AutonomyLevel::Full,
             workspace_only: false,
             allowed_commands: vec!["docker".into()],
+            command_policy_mode: crate::security::CommandPolicyMode::Allow,
+            denied_commands: vec![],
+            destructive_commands: vec![],
             forbidden_paths: vec!["/secret".into()],
             max_actions_per_hour: 100,
             max_cost_per_day_cents: 1000,
             max_tool_iterations: 25,
             max_history_turns: 20,
+            compact_history: false,
+            approval_timeout_secs: 120,
+            persist_approvals: false,
+            shell_timeout_secs: 120,
+            max_shell_timeout_secs: 600,
+            rate_limits: crate::config::RateLimitsConfig::default(),
         };
         let workspace = PathBuf::from("/tmp/test-workspace");
         let policy = SecurityPolicy::from_config(&autonomy_config, &workspace);
 
         assert_eq!(policy.autonomy, AutonomyLevel::Full);
         assert!(!policy.workspace_only);
-        assert_eq!(policy.allowed_commands, vec!["docker"]);
+        assert_eq!(policy.commands.patterns().collect::<Vec<_>>(), ["docker"]);
         assert_eq!(policy.forbidden_paths, vec!["/secret"]);
         assert_eq!(policy.max_actions_per_hour, 100);
         assert_eq!(policy.max_cost_per_day_cents, 1000);
@@ -569,7 +884,7 @@ mod tests {
         let p = SecurityPolicy::default();
         assert_eq!(p.autonomy, AutonomyLevel::Supervised);
         assert!(p.workspace_only);
-        assert!(!p.allowed_commands.is_empty());
+        assert!(p.commands.patterns().next().is_some());
         assert!(!p.forbidden_paths.is_empty());
         assert!(p.max_actions_per_hour > 0);
         assert!(p.max_cost_per_day_cents > 0);
@@ -615,6 +930,63 @@ mod tests {
         assert!(!p.record_action()); // 4 — over limit
     }
 
+    #[test]
+    fn tool_categories() {
+        assert_eq!(ToolCategory::of("shell"), ToolCategory::Shell);
+        assert_eq!(ToolCategory::of("file_write"), ToolCategory::File);
+        assert_eq!(ToolCategory::of("file_edit"), ToolCategory::File);
+        assert_eq!(ToolCategory::of("memory_recall"), ToolCategory::Memory);
+        assert_eq!(ToolCategory::of("web_search"), ToolCategory::Network);
+        assert_eq!(ToolCategory::of("schedule"), ToolCategory::Other);
+    }
+
+    #[test]
+    fn category_limits_are_separate_budgets() {
+        let p = SecurityPolicy {
+            max_actions_per_hour: 2,
+            rate_limits: RateLimitsConfig {
+                shell: 1,
+                file: 0,
+                memory: 3,
+                network: 0,
+            },
+            ..SecurityPolicy::default()
+        };
+        assert!(p.record_tool_action("shell"));
+        assert!(!p.record_tool_action("shell"));
+
+        // Memory calls don't touch the shared budget…
+        for _ in 0..3 {
+            assert!(p.record_tool_action("memory_store"));
+        }
+        assert!(!p.record_tool_action("memory_recall"));
+        assert_eq!(p.tracker.count(), 0);
+
+        // …while file (limit 0) falls back to it
+        assert!(p.record_tool_action("file_read"));
+        assert!(p.record_tool_action("file_write"));
+        assert!(!p.record_tool_action("file_read"));
+        assert!(!p.record_tool_action("web_search"));
+    }
+
+    #[test]
+    fn rate_limited_calls_are_audited() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("audit.log");
+        let p = SecurityPolicy {
+            max_actions_per_hour: 1,
+            audit: AuditLog::new(path.clone(), "cli"),
+            ..SecurityPolicy::default()
+        };
+        assert!(p.admit_tool_call("shell", r#"{"command":"ls"}"#));
+        assert!(!p.admit_tool_call("shell", r#"{"command":"ls"}"#));
+
+        let entries = super::super::audit::read_entries(&path).unwrap();
+        assert_eq!(entries.len(), 1, "only the refusal is logged here");
+        assert_eq!(entries[0].decision, AuditDecision::Denied);
+        assert_eq!(entries[0].reason.as_deref(), Some("rate limit exceeded"));
+    }
+
     #[test]
     fn is_rate_limited_reflects_count() {
         let p = SecurityPolicy {
@@ -815,7 +1187,7 @@ mod tests {
     fn readonly_blocks_even_safe_commands() {
         let p = SecurityPolicy {
             autonomy: AutonomyLevel::ReadOnly,
-            allowed_commands: vec!["ls".into(), "cat".into()],
+            commands: CommandPolicy::allow_names(&["ls", "cat"]),
             ..SecurityPolicy::default()
         };
         assert!(!p.is_command_allowed("ls"));
@@ -827,7 +1199,7 @@ mod tests {
     fn supervised_allows_listed_commands() {
         let p = SecurityPolicy {
             autonomy: AutonomyLevel::Supervised,
-            allowed_commands: vec!["git".into()],
+            commands: CommandPolicy::allow_names(&["git"]),
             ..SecurityPolicy::default()
         };
         assert!(p.is_command_allowed("git status"));
@@ -847,17 +1219,78 @@ mod tests {
 
     // ── Edge cases: from_config preserves tracker ────────────
 
+    #[test]
+    fn path_violation_explains_refusals() {
+        let p = default_policy();
+        assert_eq!(p.path_violation("src/main.rs"), None);
+        assert_eq!(
+            p.path_violation("a/../../b"),
+            Some("`..` components are not allowed")
+        );
+        assert_eq!(
+            p.path_violation("/srv/data"),
+            Some("use a path relative to the workspace")
+        );
+        let open = SecurityPolicy {
+            workspace_only: false,
+            ..default_policy()
+        };
+        assert_eq!(
+            open.path_violation("/etc/passwd"),
+            Some("the location is on the forbidden list")
+        );
+    }
+
+    #[test]
+    fn canonicalize_lenient_handles_missing_components() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        assert_eq!(canonicalize_lenient(tmp.path()).unwrap(), root);
+        assert_eq!(
+            canonicalize_lenient(&tmp.path().join("new/nested/file.txt")).unwrap(),
+            root.join("new/nested/file.txt")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn escape_error_names_the_symlink() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let workspace = tmp.path().join("workspace");
+        std::fs::create_dir_all(workspace.join("a")).unwrap();
+        std::os::unix::fs::symlink("/", workspace.join("a/root")).unwrap();
+        let p = SecurityPolicy {
+            workspace_dir: workspace.clone(),
+            ..default_policy()
+        };
+
+        let resolved = canonicalize_lenient(&workspace.join("a/root/etc/x")).unwrap();
+        assert!(!p.is_resolved_path_allowed(&resolved));
+        let error = p.escape_error("a/root/etc/x", &resolved);
+        assert!(error.contains("through the symlink `a/root`"), "{error}");
+        assert!(error.contains(&workspace.canonicalize().unwrap().display().to_string()));
+    }
+
     #[test]
     fn from_config_creates_fresh_tracker() {
         let autonomy_config = crate::config::AutonomyConfig {
             level: AutonomyLevel::Full,
             workspace_only: false,
             allowed_commands: vec![],
+            command_policy_mode: crate::security::CommandPolicyMode::Allow,
+            denied_commands: vec![],
+            destructive_commands: vec![],
             forbidden_paths: vec![],
             max_actions_per_hour: 10,
             max_cost_per_day_cents: 100,
             max_tool_iterations: 25,
             max_history_turns: 20,
+            compact_history: false,
+            approval_timeout_secs: 120,
+            persist_approvals: false,
+            shell_timeout_secs: 120,
+            max_shell_timeout_secs: 600,
+            rate_limits: crate::config::RateLimitsConfig::default(),
         };
         let workspace = PathBuf::from("/tmp/test");
         let policy = SecurityPolicy::from_config(&autonomy_config, &workspace);
@@ -0,0 +1,229 @@
+//! Masking of credentials before text leaves the process: log lines, audit
+//! entries and observer events. Two kinds of secret are caught — the values
+//! of the credentials in config.toml, registered at startup, and common
+//! token shapes (`sk-…`, `xoxb-…`, `Bearer …`) whatever their origin.
+//!
+//! Masks keep a token's prefix so the kind of credential stays visible:
+//! `sk-live-abc123` becomes `sk-****`.
+
+use regex::Regex;
+use std::io;
+use std::sync::{OnceLock, RwLock};
+use tracing_subscriber::fmt::MakeWriter;
+
+const MASK: &str = "****";
+
+/// Token prefixes recognised anywhere in text, kept in front of the mask.
+const TOKEN_PREFIXES: &[&str] = &["sk-", "xoxb-", "xoxp-", "xapp-", "ghp_", "github_pat_"];
+
+/// Configured values shorter than this are not masked: they would match
+/// ordinary words.
+const MIN_SECRET_LEN: usize = 8;
+
+/// Plaintext credential values from config, longest first so a secret that
+/// contains another is masked whole.
+static KNOWN_SECRETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+fn token_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        let prefixes = TOKEN_PREFIXES
+            .iter()
+            .map(|p| regex::escape(p))
+            .collect::<Vec<_>>()
+            .join("|");
+        Regex::new(&format!(
+            r"(?i)\b(?:(bearer\s+)[A-Za-z0-9._~+/=-]+|({prefixes})[A-Za-z0-9_-]+)"
+        ))
+        .expect("token pattern compiles")
+    })
+}
+
+/// Remember `values` so [`redact`] masks them from now on.
+pub fn register_secrets(values: impl IntoIterator<Item = String>) {
+    let mut known = KNOWN_SECRETS
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    known.extend(
+        values
+            .into_iter()
+            .map(|v| v.trim().to_string())
+            .filter(|v| v.chars().count() >= MIN_SECRET_LEN),
+    );
+    known.sort_by_key(|v| std::cmp::Reverse(v.len()));
+    known.dedup();
+}
+
+/// Register every credential set in `config`.
+pub fn register_config(config: &crate::config::Config) {
+    register_secrets(crate::config::secrets::secret_values(config));
+}
+
+/// `secret` as it appears once masked: its token prefix, if any, then `****`.
+pub fn mask(secret: &str) -> String {
+    let prefix = TOKEN_PREFIXES
+        .iter()
+        .find(|p| {
+            secret
+                .get(..p.len())
+                .is_some_and(|head| head.eq_ignore_ascii_case(p))
+        })
+        .map_or("", |p| &secret[..p.len()]);
+    format!("{prefix}{MASK}")
+}
+
+/// `text` with registered secrets and token-shaped credentials masked.
+pub fn redact(text: &str) -> String {
+    let mut out = text.to_string();
+    {
+        let known = KNOWN_SECRETS
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        for secret in known.iter() {
+            if out.contains(secret.as_str()) {
+                out = out.replace(secret.as_str(), &mask(secret));
+            }
+        }
+    }
+    token_pattern()
+        .replace_all(&out, |caps: &regex::Captures| {
+            let prefix = caps
+                .get(1)
+                .or_else(|| caps.get(2))
+                .map_or("", |m| m.as_str());
+            format!("{prefix}{MASK}")
+        })
+        .into_owned()
+}
+
+/// Wraps the log subscriber's writer so every formatted line is passed
+/// through [`redact`] before it is written.
+pub struct RedactingMakeWriter<M>(pub M);
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingMakeWriter<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter {
+            inner: self.0.make_writer(),
+            buf: Vec::new(),
+        }
+    }
+}
+
+/// Buffers one event's output and writes it redacted on flush or drop.
+pub struct RedactingWriter<W: io::Write> {
+    inner: W,
+    buf: Vec<u8>,
+}
+
+impl<W: io::Write> RedactingWriter<W> {
+    fn emit(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let text = redact(&String::from_utf8_lossy(&self.buf));
+        self.buf.clear();
+        self.inner.write_all(text.as_bytes())
+    }
+}
+
+impl<W: io::Write> io::Write for RedactingWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.emit()?;
+        self.inner.flush()
+    }
+}
+
+impl<W: io::Write> Drop for RedactingWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.emit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn masks_token_shapes_with_their_prefix() {
+        assert_eq!(redact("key sk-live-abc123 used"), "key sk-**** used");
+        assert_eq!(redact("xoxb-1-2-abc and XOXP-9"), "xoxb-**** and XOXP-****");
+        assert_eq!(
+            redact(r#"{"Authorization":"Bearer eyJhbGci.x-y"}"#),
+            r#"{"Authorization":"Bearer ****"}"#
+        );
+        assert_eq!(redact("bearer\tabc/def=="), "bearer\t****");
+        // Lookalikes inside words and bare prefixes are left alone
+        assert_eq!(
+            redact("a risk-free task-runner, sk-"),
+            "a risk-free task-runner, sk-"
+        );
+        assert_eq!(redact("sk-****"), "sk-****");
+    }
+
+    #[test]
+    fn masks_registered_config_secrets() {
+        let mut config = crate::config::Config {
+            api_key: Some("sk-or-v1-registered0123".into()),
+            ..crate::config::Config::default()
+        };
+        config.channels_config.telegram = Some(crate::config::TelegramConfig {
+            bot_token: "123456:telegram-bot-token".into(),
+            allowed_users: vec![],
+        });
+        config.brave_search.api_key = Some("short".into());
+        register_config(&config);
+
+        let text = redact(
+            "calling with key=sk-or-v1-registered0123 then \
+            https://api.telegram.org/bot123456:telegram-bot-token/getMe short",
+        );
+        assert_eq!(
+            text,
+            "calling with key=sk-**** then https://api.telegram.org/bot****/getMe short"
+        );
+    }
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(data);
+            Ok(data.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn log_lines_are_redacted() {
+        let captured = Captured::default();
+        let sink = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(false)
+            .without_time()
+            .with_writer(RedactingMakeWriter(move || sink.clone()))
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(header = "Bearer abc.def", "calling with sk-test-123456");
+        });
+
+        let line = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(line.contains("calling with sk-****"), "{line}");
+        assert!(line.contains("Bearer ****"), "{line}");
+        assert!(
+            !line.contains("123456") && !line.contains("abc.def"),
+            "{line}"
+        );
+    }
+}
@@ -32,6 +32,9 @@ const KEY_LEN: usize = 32;
 /// ChaCha20-Poly1305 nonce length in bytes.
 const NONCE_LEN: usize = 12;
 
+/// Poly1305 authentication tag length in bytes.
+const TAG_LEN: usize = 16;
+
 /// Manages encrypted storage of secrets (API keys, tokens, etc.)
 #[derive(Debug, Clone)]
 pub struct SecretStore {
@@ -161,6 +164,41 @@ impl SecretStore {
         value.starts_with("enc2:")
     }
 
+    /// Path of the key file.
+    pub fn key_path(&self) -> &Path {
+        &self.key_path
+    }
+
+    /// Whether the key file exists yet (it is created on first encryption).
+    pub fn key_exists(&self) -> bool {
+        self.key_path.exists()
+    }
+
+    /// Short fingerprint of the key (first 8 bytes of its SHA-256, hex),
+    /// recorded next to encrypted values so a wrong key can be told apart
+    /// from damaged ciphertext. Creates the key if it doesn't exist.
+    pub fn fingerprint(&self) -> Result<String> {
+        use sha2::{Digest, Sha256};
+        let key = self.load_or_create_key()?;
+        Ok(hex_encode(&Sha256::digest(&key)[..8]))
+    }
+
+    /// Check that an encrypted value is well-formed (valid hex, long enough
+    /// for nonce and tag) without decrypting it. Plaintext passes.
+    pub fn check_ciphertext(value: &str) -> Result<()> {
+        if let Some(hex_str) = value.strip_prefix("enc2:") {
+            let blob = hex_decode(hex_str).context("十六进制数据损坏")?;
+            anyhow::ensure!(
+                blob.len() > NONCE_LEN + TAG_LEN,
+                "加密值过短（{} 字节）",
+                blob.len()
+            );
+        } else if let Some(hex_str) = value.strip_prefix("enc:") {
+            hex_decode(hex_str).context("十六进制数据损坏")?;
+        }
+        Ok(())
+    }
+
     /// Load the encryption key from disk, or create one if it doesn't exist.
     fn load_or_create_key(&self) -> Result<Vec<u8>> {
         if self.key_path.exists() {
@@ -441,6 +479,30 @@ mod tests {
         assert!(result.is_err(), "Too-short ciphertext must be rejected");
     }
 
+    #[test]
+    fn fingerprint_is_stable_per_key() {
+        let (a, b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let store = SecretStore::new(a.path(), true);
+        assert!(!store.key_exists());
+        let fp = store.fingerprint().unwrap();
+        assert!(store.key_exists());
+        assert_eq!(fp.len(), 16);
+        assert_eq!(SecretStore::new(a.path(), true).fingerprint().unwrap(), fp);
+        assert_ne!(SecretStore::new(b.path(), true).fingerprint().unwrap(), fp);
+    }
+
+    #[test]
+    fn check_ciphertext_validates_shape_only() {
+        let tmp = TempDir::new().unwrap();
+        let store = SecretStore::new(tmp.path(), true);
+        let encrypted = store.encrypt("sk-secret").unwrap();
+        assert!(SecretStore::check_ciphertext(&encrypted).is_ok());
+        assert!(SecretStore::check_ciphertext("plaintext").is_ok());
+        assert!(SecretStore::check_ciphertext("enc2:zz").is_err());
+        assert!(SecretStore::check_ciphertext("enc2:00112233").is_err());
+        assert!(SecretStore::check_ciphertext("enc:abc").is_err());
+    }
+
     // ── Legacy XOR backward compatibility ───────────────────────
 
     #[test]
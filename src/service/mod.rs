@@ -1,14 +1,64 @@
 use crate::config::Config;
 use anyhow::{Context, Result};
+use std::fmt::Write as _;
 use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+#[cfg(windows)]
+pub mod windows;
+
 const SERVICE_LABEL: &str = "com.jarvis.daemon";
+#[cfg(not(windows))]
+const UNSUPPORTED: &str = "服务管理仅支持 macOS、Linux 和 Windows";
+/// System-wide launchd daemons (start at boot, no login needed)
+const MACOS_SYSTEM_DIR: &str = "/Library/LaunchDaemons";
+/// System-wide systemd units
+const LINUX_SYSTEM_DIR: &str = "/etc/systemd/system";
+
+/// Per-user units (the default) start once the user logs in; system-wide
+/// ones start at boot and need root to install.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    User,
+    System,
+}
+
+impl Scope {
+    /// The scope of the installed unit (system-wide if both exist).
+    fn installed(config: &Config) -> Self {
+        if service_file(Self::System, config).is_ok_and(|file| file.exists()) {
+            Self::System
+        } else {
+            Self::User
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::User => "用户级（登录后启动）",
+            Self::System => "系统级（开机启动）",
+        }
+    }
+}
+
+/// Account and config directory a system-wide unit runs the daemon with.
+struct RunAs {
+    /// The user who invoked sudo; `None` runs as root
+    user: Option<String>,
+    config_dir: PathBuf,
+}
 
 pub fn handle_command(command: &crate::ServiceCommands, config: &Config) -> Result<()> {
+    // Unit names are fixed, so a profile's unit would replace the default one
+    if let Some(profile) = crate::config::profile::active() {
+        anyhow::bail!(
+            "服务管理仅支持默认 profile（当前：{profile}）。请用 `jarvis --profile {profile} daemon` 在后台运行该 profile"
+        );
+    }
     match command {
-        crate::ServiceCommands::Install => install(config),
+        crate::ServiceCommands::Install { system } => install(config, *system),
         crate::ServiceCommands::Start => start(config),
         crate::ServiceCommands::Stop => stop(config),
         crate::ServiceCommands::Status => status(config),
@@ -16,37 +66,70 @@ pub fn handle_command(command: &crate::ServiceCommands, config: &Config) -> Resu
     }
 }
 
-fn install(config: &Config) -> Result<()> {
+fn install(config: &Config, system: bool) -> Result<()> {
+    let scope = if system { Scope::System } else { Scope::User };
+    if cfg!(target_os = "macos") || cfg!(target_os = "linux") {
+        let other = match scope {
+            Scope::User => Scope::System,
+            Scope::System => Scope::User,
+        };
+        if let Ok(file) = service_file(other, config)
+            && file.exists()
+        {
+            println!(
+                "⚠️  已安装{}服务（{}）；两个守护进程同时运行会冲突，建议先卸载",
+                other.label(),
+                file.display()
+            );
+        }
+    }
+
     if cfg!(target_os = "macos") {
-        install_macos(config)
+        install_macos(config, scope)
     } else if cfg!(target_os = "linux") {
-        install_linux(config)
+        install_linux(config, scope)
     } else {
-        anyhow::bail!("服务管理仅支持 macOS 和 Linux");
+        // Windows services are always system-wide
+        #[cfg(windows)]
+        {
+            windows::install(config)
+        }
+        #[cfg(not(windows))]
+        {
+            anyhow::bail!("{UNSUPPORTED}")
+        }
     }
 }
 
 fn start(config: &Config) -> Result<()> {
+    let scope = Scope::installed(config);
     if cfg!(target_os = "macos") {
-        let plist = macos_service_file()?;
+        let plist = service_file(scope, config)?;
         run_checked(Command::new("launchctl").arg("load").arg("-w").arg(&plist))?;
         run_checked(Command::new("launchctl").arg("start").arg(SERVICE_LABEL))?;
         println!("✅ 服务已启动");
         Ok(())
     } else if cfg!(target_os = "linux") {
-        run_checked(Command::new("systemctl").args(["--user", "daemon-reload"]))?;
-        run_checked(Command::new("systemctl").args(["--user", "start", "jarvis.service"]))?;
+        run_checked(systemctl(scope).arg("daemon-reload"))?;
+        run_checked(systemctl(scope).args(["start", "jarvis.service"]))?;
         println!("✅ 服务已启动");
         Ok(())
     } else {
-        let _ = config;
-        anyhow::bail!("服务管理仅支持 macOS 和 Linux")
+        #[cfg(windows)]
+        {
+            windows::start()
+        }
+        #[cfg(not(windows))]
+        {
+            anyhow::bail!("{UNSUPPORTED}")
+        }
     }
 }
 
 fn stop(config: &Config) -> Result<()> {
+    let scope = Scope::installed(config);
     if cfg!(target_os = "macos") {
-        let plist = macos_service_file()?;
+        let plist = service_file(scope, config)?;
         let _ = run_checked(Command::new("launchctl").arg("stop").arg(SERVICE_LABEL));
         let _ = run_checked(
             Command::new("launchctl")
@@ -57,19 +140,35 @@ fn stop(config: &Config) -> Result<()> {
         println!("✅ 服务已停止");
         Ok(())
     } else if cfg!(target_os = "linux") {
-        let _ = run_checked(Command::new("systemctl").args(["--user", "stop", "jarvis.service"]));
+        let _ = run_checked(systemctl(scope).args(["stop", "jarvis.service"]));
         println!("✅ 服务已停止");
         Ok(())
     } else {
-        let _ = config;
-        anyhow::bail!("服务管理仅支持 macOS 和 Linux")
+        #[cfg(windows)]
+        {
+            windows::stop()
+        }
+        #[cfg(not(windows))]
+        {
+            anyhow::bail!("{UNSUPPORTED}")
+        }
     }
 }
 
 fn status(config: &Config) -> Result<()> {
+    let scope = Scope::installed(config);
     if cfg!(target_os = "macos") {
-        let out = run_capture(Command::new("launchctl").arg("list"))?;
-        let running = out.lines().any(|line| line.contains(SERVICE_LABEL));
+        let running = match scope {
+            Scope::User => run_capture(Command::new("launchctl").arg("list"))?
+                .lines()
+                .any(|line| line.contains(SERVICE_LABEL)),
+            Scope::System => run_checked(
+                Command::new("launchctl")
+                    .arg("print")
+                    .arg(format!("system/{SERVICE_LABEL}")),
+            )
+            .is_ok(),
+        };
         println!(
             "服务: {}",
             if running {
@@ -78,78 +177,115 @@ fn status(config: &Config) -> Result<()> {
                 "❌ 未加载"
             }
         );
-        println!("单元文件: {}", macos_service_file()?.display());
+        println!("范围: {}", scope.label());
+        println!("单元文件: {}", service_file(scope, config)?.display());
         return Ok(());
     }
 
     if cfg!(target_os = "linux") {
-        let out =
-            run_capture(Command::new("systemctl").args(["--user", "is-active", "jarvis.service"]))
-                .unwrap_or_else(|_| "unknown".into());
+        let out = run_capture(systemctl(scope).args(["is-active", "jarvis.service"]))
+            .unwrap_or_else(|_| "unknown".into());
         println!("服务状态: {}", out.trim());
-        println!("单元文件: {}", linux_service_file(config)?.display());
+        println!("范围: {}", scope.label());
+        println!("单元文件: {}", service_file(scope, config)?.display());
         return Ok(());
     }
 
-    anyhow::bail!("服务管理仅支持 macOS 和 Linux")
+    #[cfg(windows)]
+    {
+        windows::status()
+    }
+    #[cfg(not(windows))]
+    {
+        anyhow::bail!("{UNSUPPORTED}")
+    }
 }
 
 fn uninstall(config: &Config) -> Result<()> {
+    let scope = Scope::installed(config);
     stop(config)?;
 
-    if cfg!(target_os = "macos") {
-        let file = macos_service_file()?;
+    if cfg!(target_os = "macos") || cfg!(target_os = "linux") {
+        let file = service_file(scope, config)?;
         if file.exists() {
-            fs::remove_file(&file).with_context(|| format!("删除失败 {}", file.display()))?;
+            fs::remove_file(&file).map_err(|e| unit_file_error(e, &file, scope, "uninstall"))?;
         }
-        println!("✅ 服务已卸载 ({})", file.display());
-        return Ok(());
-    }
-
-    if cfg!(target_os = "linux") {
-        let file = linux_service_file(config)?;
-        if file.exists() {
-            fs::remove_file(&file).with_context(|| format!("删除失败 {}", file.display()))?;
+        if cfg!(target_os = "linux") {
+            let _ = run_checked(systemctl(scope).arg("daemon-reload"));
         }
-        let _ = run_checked(Command::new("systemctl").args(["--user", "daemon-reload"]));
         println!("✅ 服务已卸载 ({})", file.display());
         return Ok(());
     }
 
-    anyhow::bail!("服务管理仅支持 macOS 和 Linux")
+    #[cfg(windows)]
+    {
+        windows::uninstall()
+    }
+    #[cfg(not(windows))]
+    {
+        anyhow::bail!("{UNSUPPORTED}")
+    }
 }
 
-fn install_macos(config: &Config) -> Result<()> {
-    let file = macos_service_file()?;
-    if let Some(parent) = file.parent() {
-        fs::create_dir_all(parent)?;
-    }
+fn install_macos(config: &Config, scope: Scope) -> Result<()> {
+    let file = service_file(scope, config)?;
+    let run_as = match scope {
+        Scope::User => None,
+        Scope::System => Some(system_run_as(config)?),
+    };
 
     let exe = std::env::current_exe().context("解析当前可执行文件路径失败")?;
-    let logs_dir = config
-        .config_path
-        .parent()
-        .map_or_else(|| PathBuf::from("."), PathBuf::from)
-        .join("logs");
+    let logs_dir = crate::daemon::logs::logs_dir(config);
+    let stdout = logs_dir.join(crate::daemon::logs::STDOUT_LOG);
+    let stderr = logs_dir.join(crate::daemon::logs::STDERR_LOG);
+
+    write_unit(
+        &file,
+        &macos_plist(&exe, &stdout, &stderr, run_as.as_ref()),
+        scope,
+    )?;
     fs::create_dir_all(&logs_dir)?;
+    match scope {
+        Scope::User => println!("✅ 已安装 launchd 服务: {}", file.display()),
+        Scope::System => println!("✅ 已安装 launchd 系统服务: {}", file.display()),
+    }
+    println!("   启动命令: {}jarvis service start", sudo_prefix(scope));
+    Ok(())
+}
 
-    let stdout = logs_dir.join("daemon.stdout.log");
-    let stderr = logs_dir.join("daemon.stderr.log");
+fn macos_plist(exe: &Path, stdout: &Path, stderr: &Path, run_as: Option<&RunAs>) -> String {
+    let mut arguments = vec![
+        exe.display().to_string(),
+        "daemon".into(),
+        "--foreground".into(),
+    ];
+    let mut user_name = String::new();
+    if let Some(run_as) = run_as {
+        arguments.push("--config-dir".into());
+        arguments.push(run_as.config_dir.display().to_string());
+        if let Some(user) = &run_as.user {
+            user_name = format!(
+                "  <key>UserName</key>\n  <string>{}</string>\n",
+                xml_escape(user)
+            );
+        }
+    }
+    let arguments = arguments.iter().fold(String::new(), |mut out, arg| {
+        let _ = writeln!(out, "    <string>{}</string>", xml_escape(arg));
+        out
+    });
 
-    let plist = format!(
-        r#"<?xml version=\"1.0\" encoding=\"UTF-8\"?>
-<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">
-<plist version=\"1.0\">
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
 <dict>
   <key>Label</key>
   <string>{label}</string>
   <key>ProgramArguments</key>
   <array>
-    <string>{exe}</string>
-    <string>daemon</string>
-    <string>--foreground</string>
-  </array>
-  <key>RunAtLoad</key>
+{arguments}  </array>
+{user_name}  <key>RunAtLoad</key>
   <true/>
   <key>KeepAlive</key>
   <true/>
@@ -161,35 +297,135 @@ fn install_macos(config: &Config) -> Result<()> {
 </plist>
 "#,
         label = SERVICE_LABEL,
-        exe = xml_escape(&exe.display().to_string()),
         stdout = xml_escape(&stdout.display().to_string()),
         stderr = xml_escape(&stderr.display().to_string())
-    );
+    )
+}
+
+fn install_linux(config: &Config, scope: Scope) -> Result<()> {
+    let file = service_file(scope, config)?;
+    let run_as = match scope {
+        Scope::User => None,
+        Scope::System => Some(system_run_as(config)?),
+    };
 
-    fs::write(&file, plist)?;
-    println!("✅ 已安装 launchd 服务: {}", file.display());
-    println!("   启动命令: jarvis service start");
+    let exe = std::env::current_exe().context("解析当前可执行文件路径失败")?;
+    write_unit(&file, &linux_unit(&exe, run_as.as_ref()), scope)?;
+    let _ = run_checked(systemctl(scope).arg("daemon-reload"));
+    let _ = run_checked(systemctl(scope).args(["enable", "jarvis.service"]));
+    match scope {
+        Scope::User => println!("✅ 已安装 systemd 用户服务: {}", file.display()),
+        Scope::System => println!("✅ 已安装 systemd 系统服务: {}", file.display()),
+    }
+    println!("   启动命令: {}jarvis service start", sudo_prefix(scope));
     Ok(())
 }
 
-fn install_linux(config: &Config) -> Result<()> {
-    let file = linux_service_file(config)?;
-    if let Some(parent) = file.parent() {
-        fs::create_dir_all(parent)?;
+fn linux_unit(exe: &Path, run_as: Option<&RunAs>) -> String {
+    let Some(run_as) = run_as else {
+        return format!(
+            "[Unit]\nDescription=Jarvis daemon\nAfter=network.target\n\n[Service]\nType=simple\nExecStart={} daemon --foreground\nRestart=always\nRestartSec=3\n\n[Install]\nWantedBy=default.target\n",
+            exe.display()
+        );
+    };
+    let user = run_as
+        .user
+        .as_ref()
+        .map(|user| format!("User={user}\n"))
+        .unwrap_or_default();
+    format!(
+        "[Unit]\nDescription=Jarvis daemon\nAfter=network-online.target\nWants=network-online.target\n\n[Service]\nType=simple\n{user}ExecStart={} daemon --foreground --config-dir {}\nRestart=always\nRestartSec=3\n\n[Install]\nWantedBy=multi-user.target\n",
+        exe.display(),
+        run_as.config_dir.display()
+    )
+}
+
+/// `systemctl`, with `--user` for per-user units.
+fn systemctl(scope: Scope) -> Command {
+    let mut command = Command::new("systemctl");
+    if scope == Scope::User {
+        command.arg("--user");
     }
+    command
+}
 
-    let exe = std::env::current_exe().context("解析当前可执行文件路径失败")?;
-    let unit = format!(
-        "[Unit]\nDescription=Jarvis daemon\nAfter=network.target\n\n[Service]\nType=simple\nExecStart={} daemon --foreground\nRestart=always\nRestartSec=3\n\n[Install]\nWantedBy=default.target\n",
-        exe.display()
-    );
-
-    fs::write(&file, unit)?;
-    let _ = run_checked(Command::new("systemctl").args(["--user", "daemon-reload"]));
-    let _ = run_checked(Command::new("systemctl").args(["--user", "enable", "jarvis.service"]));
-    println!("✅ 已安装 systemd 用户服务: {}", file.display());
-    println!("   启动命令: jarvis service start");
-    Ok(())
+fn sudo_prefix(scope: Scope) -> &'static str {
+    match scope {
+        Scope::User => "",
+        Scope::System => "sudo ",
+    }
+}
+
+fn service_file(scope: Scope, config: &Config) -> Result<PathBuf> {
+    match scope {
+        Scope::User if cfg!(target_os = "macos") => macos_service_file(),
+        Scope::User => linux_service_file(config),
+        Scope::System if cfg!(target_os = "macos") => {
+            Ok(Path::new(MACOS_SYSTEM_DIR).join(format!("{SERVICE_LABEL}.plist")))
+        }
+        Scope::System => Ok(Path::new(LINUX_SYSTEM_DIR).join("jarvis.service")),
+    }
+}
+
+/// A system-wide unit runs the daemon as the user who invoked sudo, with
+/// the config this command loaded. Under sudo that is only their config if
+/// `HOME` was preserved, so refuse a config directory they don't own.
+fn system_run_as(config: &Config) -> Result<RunAs> {
+    let config_dir = config
+        .config_path
+        .parent()
+        .context("无法确定配置目录")?
+        .to_path_buf();
+    let user = std::env::var("SUDO_USER")
+        .ok()
+        .filter(|user| !user.is_empty() && user != "root");
+
+    #[cfg(unix)]
+    if let (Some(user), Some(uid)) = (
+        &user,
+        std::env::var("SUDO_UID")
+            .ok()
+            .and_then(|uid| uid.parse::<u32>().ok()),
+    ) {
+        use std::os::unix::fs::MetadataExt;
+
+        let owner = fs::metadata(&config_dir)
+            .with_context(|| format!("读取配置目录失败 {}", config_dir.display()))?
+            .uid();
+        if owner != uid {
+            anyhow::bail!(
+                "配置目录 {} 不属于 {user}（sudo 重置了 HOME）。\n请改用：sudo --preserve-env=HOME jarvis service install --system",
+                config_dir.display()
+            );
+        }
+    }
+
+    Ok(RunAs { user, config_dir })
+}
+
+/// Write a unit file, with a sudo hint when a system-wide directory isn't
+/// writable.
+fn write_unit(file: &Path, contents: &str, scope: Scope) -> Result<()> {
+    file.parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(file, contents))
+        .map_err(|e| unit_file_error(e, file, scope, "install --system"))
+}
+
+fn unit_file_error(err: io::Error, file: &Path, scope: Scope, command: &str) -> anyhow::Error {
+    if scope == Scope::System && err.kind() == io::ErrorKind::PermissionDenied {
+        let hint = if command == "uninstall" {
+            "sudo jarvis service uninstall".to_string()
+        } else {
+            format!("sudo --preserve-env=HOME jarvis service {command}")
+        };
+        anyhow::anyhow!(
+            "没有写入 {} 的权限 — 系统级服务需要 root 权限。\n请改用：{hint}",
+            file.display()
+        )
+    } else {
+        anyhow::Error::new(err).context(format!("写入失败 {}", file.display()))
+    }
 }
 
 fn macos_service_file() -> Result<PathBuf> {
@@ -277,4 +513,64 @@ mod tests {
         let path = file.to_string_lossy();
         assert!(path.ends_with(".config/systemd/user/jarvis.service"));
     }
+
+    #[test]
+    fn system_service_file_uses_system_dir() {
+        let file = service_file(Scope::System, &Config::default()).unwrap();
+        if cfg!(target_os = "macos") {
+            assert!(file.starts_with(MACOS_SYSTEM_DIR));
+        } else {
+            assert_eq!(file, Path::new(LINUX_SYSTEM_DIR).join("jarvis.service"));
+        }
+    }
+
+    #[test]
+    fn linux_unit_user_scope_targets_default() {
+        let unit = linux_unit(Path::new("/usr/bin/jarvis"), None);
+        assert!(unit.contains("ExecStart=/usr/bin/jarvis daemon --foreground\n"));
+        assert!(unit.contains("WantedBy=default.target"));
+        assert!(!unit.contains("User="));
+    }
+
+    #[test]
+    fn linux_unit_system_scope_runs_as_user_at_boot() {
+        let run_as = RunAs {
+            user: Some("alice".into()),
+            config_dir: PathBuf::from("/home/alice/.jarvis"),
+        };
+        let unit = linux_unit(Path::new("/usr/bin/jarvis"), Some(&run_as));
+        assert!(unit.contains("User=alice\n"));
+        assert!(unit.contains(
+            "ExecStart=/usr/bin/jarvis daemon --foreground --config-dir /home/alice/.jarvis\n"
+        ));
+        assert!(unit.contains("Wants=network-online.target"));
+        assert!(unit.contains("WantedBy=multi-user.target"));
+    }
+
+    #[test]
+    fn macos_plist_system_scope_sets_user_and_config_dir() {
+        let run_as = RunAs {
+            user: Some("alice".into()),
+            config_dir: PathBuf::from("/Users/alice/.jarvis"),
+        };
+        let plist = macos_plist(
+            Path::new("/usr/local/bin/jarvis"),
+            Path::new("/tmp/out.log"),
+            Path::new("/tmp/err.log"),
+            Some(&run_as),
+        );
+        assert!(plist.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+        assert!(plist.contains("<key>UserName</key>\n  <string>alice</string>"));
+        assert!(plist.contains("<string>--config-dir</string>"));
+        assert!(plist.contains("<string>/Users/alice/.jarvis</string>"));
+
+        let user_plist = macos_plist(
+            Path::new("/usr/local/bin/jarvis"),
+            Path::new("/tmp/out.log"),
+            Path::new("/tmp/err.log"),
+            None,
+        );
+        assert!(!user_plist.contains("UserName"));
+        assert!(!user_plist.contains("--config-dir"));
+    }
 }
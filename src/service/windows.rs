@@ -0,0 +1,199 @@
+//! Windows backend for `jarvis service`: registers a `jarvis` service with
+//! the Service Control Manager that runs `jarvis daemon --foreground`.
+//!
+//! The service runs as `LocalSystem` and is handed the installing user's
+//! config directory (`--config-dir`), so it uses the same `~/.jarvis` as the
+//! CLI. Installing, starting and stopping services needs an administrator
+//! prompt.
+
+use crate::config::Config;
+use anyhow::{Context, Result};
+use std::ffi::OsString;
+use std::sync::OnceLock;
+use std::time::Duration;
+use windows_service::service::{
+    Service, ServiceAccess, ServiceControl, ServiceControlAccept, ServiceErrorControl,
+    ServiceExitCode, ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+};
+use windows_service::service_control_handler::{self, ServiceControlHandlerResult};
+use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+use windows_service::{define_windows_service, service_dispatcher};
+
+const SERVICE_NAME: &str = "jarvis";
+const DISPLAY_NAME: &str = "Jarvis daemon";
+const DESCRIPTION: &str = "Jarvis autonomous runtime (gateway, channels, heartbeat, scheduler)";
+/// `service_dispatcher::start` fails with this when not launched by the SCM
+const ERROR_FAILED_SERVICE_CONTROLLER_CONNECT: i32 = 1063;
+
+fn manager(access: ServiceManagerAccess) -> Result<ServiceManager> {
+    ServiceManager::local_computer(None::<&str>, access)
+        .context("连接服务控制管理器失败（需要管理员权限）")
+}
+
+fn open(access: ServiceAccess) -> Result<Service> {
+    manager(ServiceManagerAccess::CONNECT)?
+        .open_service(SERVICE_NAME, access)
+        .context("打开 jarvis 服务失败（是否已运行 jarvis service install？需要管理员权限）")
+}
+
+pub(super) fn install(config: &Config) -> Result<()> {
+    let exe = std::env::current_exe().context("解析当前可执行文件路径失败")?;
+    let config_dir = config
+        .config_path
+        .parent()
+        .context("无法确定配置目录")?
+        .to_path_buf();
+
+    let info = ServiceInfo {
+        name: SERVICE_NAME.into(),
+        display_name: DISPLAY_NAME.into(),
+        service_type: ServiceType::OWN_PROCESS,
+        start_type: ServiceStartType::AutoStart,
+        error_control: ServiceErrorControl::Normal,
+        executable_path: exe,
+        launch_arguments: vec![
+            "daemon".into(),
+            "--foreground".into(),
+            "--config-dir".into(),
+            config_dir.into_os_string(),
+        ],
+        dependencies: Vec::new(),
+        account_name: None, // LocalSystem
+        account_password: None,
+    };
+    let service = manager(ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE)?
+        .create_service(&info, ServiceAccess::CHANGE_CONFIG)
+        .context(
+            "创建 Windows 服务失败（需要管理员权限；已安装时请先 jarvis service uninstall）",
+        )?;
+    service.set_description(DESCRIPTION)?;
+
+    println!("✅ 已安装 Windows 服务: {SERVICE_NAME}");
+    println!("   启动命令: jarvis service start");
+    Ok(())
+}
+
+pub(super) fn start() -> Result<()> {
+    let service = open(ServiceAccess::START | ServiceAccess::QUERY_STATUS)?;
+    if service.query_status()?.current_state != ServiceState::Running {
+        service
+            .start::<&str>(&[])
+            .context("启动 Windows 服务失败")?;
+    }
+    println!("✅ 服务已启动");
+    Ok(())
+}
+
+pub(super) fn stop() -> Result<()> {
+    let service = open(ServiceAccess::STOP | ServiceAccess::QUERY_STATUS)?;
+    if service.query_status()?.current_state != ServiceState::Stopped {
+        service.stop().context("停止 Windows 服务失败")?;
+    }
+    println!("✅ 服务已停止");
+    Ok(())
+}
+
+pub(super) fn status() -> Result<()> {
+    let state = open(ServiceAccess::QUERY_STATUS)?
+        .query_status()?
+        .current_state;
+    println!("服务状态: {}", state_label(state));
+    println!("服务名称: {SERVICE_NAME}（sc query {SERVICE_NAME}）");
+    Ok(())
+}
+
+pub(super) fn uninstall() -> Result<()> {
+    open(ServiceAccess::DELETE)?
+        .delete()
+        .context("删除 Windows 服务失败")?;
+    println!("✅ 服务已卸载 ({SERVICE_NAME})");
+    Ok(())
+}
+
+fn state_label(state: ServiceState) -> &'static str {
+    match state {
+        ServiceState::Running => "运行中",
+        ServiceState::Stopped => "已停止",
+        ServiceState::StartPending => "启动中",
+        ServiceState::StopPending => "停止中",
+        ServiceState::Paused => "已暂停",
+        ServiceState::PausePending | ServiceState::ContinuePending => "切换中",
+    }
+}
+
+/// What the service thread runs: config, host and port
+static LAUNCH: OnceLock<(Config, String, u16)> = OnceLock::new();
+
+define_windows_service!(ffi_service_main, service_main);
+
+/// Hand this thread to the service dispatcher when the SCM launched us, and
+/// return `true` once the service has stopped. From a console this returns
+/// `false` straight away and the caller runs the daemon itself.
+pub fn run_if_service(config: &Config, host: &str, port: u16) -> Result<bool> {
+    let _ = LAUNCH.set((config.clone(), host.to_string(), port));
+    match service_dispatcher::start(SERVICE_NAME, ffi_service_main) {
+        Ok(()) => Ok(true),
+        Err(windows_service::Error::Winapi(e))
+            if e.raw_os_error() == Some(ERROR_FAILED_SERVICE_CONTROLLER_CONNECT) =>
+        {
+            Ok(false)
+        }
+        Err(e) => Err(e).context("启动 Windows 服务调度器失败"),
+    }
+}
+
+fn service_main(_arguments: Vec<OsString>) {
+    if let Err(e) = run_service() {
+        tracing::error!("Windows 服务异常退出：{e:#}");
+    }
+}
+
+fn service_status(state: ServiceState, exit_code: ServiceExitCode) -> ServiceStatus {
+    ServiceStatus {
+        service_type: ServiceType::OWN_PROCESS,
+        current_state: state,
+        controls_accepted: if state == ServiceState::Running {
+            ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN
+        } else {
+            ServiceControlAccept::empty()
+        },
+        exit_code,
+        checkpoint: 0,
+        wait_hint: Duration::ZERO,
+        process_id: None,
+    }
+}
+
+fn run_service() -> Result<()> {
+    let (config, host, port) = LAUNCH.get().cloned().context("缺少服务启动参数")?;
+
+    let status_handle = service_control_handler::register(SERVICE_NAME, |control| match control {
+        // Same path as `jarvis daemon --stop`
+        ServiceControl::Stop | ServiceControl::Shutdown => {
+            if let Err(e) = crate::daemon::windows::request_stop(std::process::id()) {
+                tracing::warn!("停止守护进程失败：{e:#}");
+            }
+            ServiceControlHandlerResult::NoError
+        }
+        ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+        _ => ServiceControlHandlerResult::NotImplemented,
+    })?;
+    status_handle.set_service_status(service_status(
+        ServiceState::Running,
+        ServiceExitCode::NO_ERROR,
+    ))?;
+
+    let result = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .context("创建 tokio 运行时失败")?
+        .block_on(crate::daemon::run(config, host, port));
+
+    let exit_code = if result.is_ok() {
+        ServiceExitCode::NO_ERROR
+    } else {
+        ServiceExitCode::ServiceSpecific(1)
+    };
+    status_handle.set_service_status(service_status(ServiceState::Stopped, exit_code))?;
+    result
+}
@@ -0,0 +1,530 @@
+//! Conversation transcripts: every agent run appends what was said and which
+//! tools ran to `workspace/sessions/YYYY-MM-DD/<session-id>.jsonl`, one JSON
+//! object per line. Text is passed through [`redact`] before it is written
+//! and long messages and tool output are truncated. Read back by
+//! `jarvis sessions list|show`; memory hygiene deletes days older than
+//! `memory.transcript_retention_days`.
+
+use crate::config::Config;
+use crate::providers::ToolCall;
+use crate::security::audit::redact_arguments;
+use crate::security::redact::redact;
+use crate::util::truncate_with_ellipsis;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Longest user or assistant message stored per entry.
+const MAX_MESSAGE_CHARS: usize = 20_000;
+/// Longest tool output stored per entry.
+const MAX_TOOL_OUTPUT_CHARS: usize = 2_000;
+/// Characters of the first user message shown by `sessions list`.
+const PREVIEW_CHARS: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    User,
+    Assistant,
+    ToolCall,
+    ToolResult,
+}
+
+/// One line of a transcript.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub timestamp: DateTime<Utc>,
+    pub session: String,
+    /// Where the conversation happened: cli, tui, telegram, heartbeat, …
+    pub origin: String,
+    pub kind: EntryKind,
+    /// Message text, tool arguments or tool output, redacted and truncated
+    pub content: String,
+    /// Model that wrote an assistant message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// Set on tool results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+}
+
+/// Writer for one session's transcript. The default records nothing (tests).
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    path: Option<PathBuf>,
+    id: String,
+    origin: String,
+}
+
+impl Transcript {
+    /// A new session with a fresh id, e.g. `20261017-153012-cli-1a2b3c4d`.
+    pub fn start(workspace_dir: &Path, origin: &str) -> Self {
+        let now = Local::now();
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        let id = format!(
+            "{}-{}-{}",
+            now.format("%Y%m%d-%H%M%S"),
+            slug(origin),
+            &suffix[..8]
+        );
+        Self::at(workspace_dir, origin, id, now.date_naive())
+    }
+
+    /// Today's file for a conversation identified by `key` (e.g. a channel
+    /// and sender), so every message from it lands in one session per day.
+    pub fn resume(workspace_dir: &Path, origin: &str, key: &str) -> Self {
+        Self::at(workspace_dir, origin, slug(key), Local::now().date_naive())
+    }
+
+    fn at(workspace_dir: &Path, origin: &str, id: String, date: NaiveDate) -> Self {
+        let path = sessions_dir(workspace_dir)
+            .join(date.format("%Y-%m-%d").to_string())
+            .join(format!("{id}.jsonl"));
+        Self {
+            path: Some(path),
+            id,
+            origin: origin.to_string(),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    pub fn user(&self, content: &str) {
+        self.write(
+            EntryKind::User,
+            truncate_with_ellipsis(&redact(content), MAX_MESSAGE_CHARS),
+            |_| {},
+        );
+    }
+
+    pub fn assistant(&self, content: &str, model: &str) {
+        self.write(
+            EntryKind::Assistant,
+            truncate_with_ellipsis(&redact(content), MAX_MESSAGE_CHARS),
+            |entry| entry.model = Some(model.to_string()),
+        );
+    }
+
+    pub fn tool_call(&self, call: &ToolCall) {
+        self.write(
+            EntryKind::ToolCall,
+            redact_arguments(&call.function.arguments),
+            |entry| {
+                entry.tool = Some(call.function.name.clone());
+                entry.tool_call_id = Some(call.id.clone());
+            },
+        );
+    }
+
+    pub fn tool_result(&self, call: &ToolCall, output: &str) {
+        self.write(
+            EntryKind::ToolResult,
+            truncate_with_ellipsis(&redact(output), MAX_TOOL_OUTPUT_CHARS),
+            |entry| {
+                entry.tool = Some(call.function.name.clone());
+                entry.tool_call_id = Some(call.id.clone());
+                entry.success = Some(!output.starts_with("Error:"));
+            },
+        );
+    }
+
+    /// Append an entry (best-effort: a failed write is logged, never fatal).
+    fn write(&self, kind: EntryKind, content: String, fill: impl FnOnce(&mut TranscriptEntry)) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let mut entry = TranscriptEntry {
+            timestamp: Utc::now(),
+            session: self.id.clone(),
+            origin: self.origin.clone(),
+            kind,
+            content,
+            model: None,
+            tool: None,
+            tool_call_id: None,
+            success: None,
+        };
+        fill(&mut entry);
+        if let Err(e) = append(path, &entry) {
+            tracing::warn!("写入对话记录失败 {}: {e:#}", path.display());
+        }
+    }
+}
+
+fn append(path: &Path, entry: &TranscriptEntry) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    // One write per line, so concurrent writers never interleave within a line
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())?;
+    Ok(())
+}
+
+fn sessions_dir(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join("sessions")
+}
+
+/// `raw` reduced to characters that are safe in a file name.
+fn slug(raw: &str) -> String {
+    let slug: String = raw
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(80)
+        .collect();
+    let slug = slug.trim_start_matches('.');
+    if slug.is_empty() {
+        "session".into()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Day directories under `sessions/`, oldest first.
+fn day_dirs(workspace_dir: &Path) -> Result<Vec<(NaiveDate, PathBuf)>> {
+    let dir = sessions_dir(workspace_dir);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("读取 {} 失败", dir.display())),
+    };
+    let mut days: Vec<_> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_name()?.to_str()?;
+            let date = NaiveDate::parse_from_str(name, "%Y-%m-%d").ok()?;
+            path.is_dir().then_some((date, path))
+        })
+        .collect();
+    days.sort();
+    Ok(days)
+}
+
+/// Transcript files in a day directory, with their session ids.
+fn session_files(day: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut files: Vec<_> = fs::read_dir(day)
+        .with_context(|| format!("读取 {} 失败", day.display()))?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let id = path
+                .file_name()?
+                .to_str()?
+                .strip_suffix(".jsonl")?
+                .to_string();
+            Some((id, path))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Entries of one transcript file, skipping lines that don't parse (e.g. a
+/// torn last write).
+fn read_file(path: &Path) -> Result<Vec<TranscriptEntry>> {
+    let raw = fs::read_to_string(path).with_context(|| format!("读取 {} 失败", path.display()))?;
+    Ok(raw
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// One row of `jarvis sessions list`.
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+    pub id: String,
+    pub date: NaiveDate,
+    pub origin: String,
+    pub entries: usize,
+    pub updated_at: DateTime<Utc>,
+    /// Start of the first user message
+    pub preview: String,
+}
+
+/// Every transcript file, most recently active first. A conversation kept
+/// under the same id on several days is listed once per day.
+pub fn list(workspace_dir: &Path) -> Result<Vec<SessionSummary>> {
+    let mut summaries = Vec::new();
+    for (date, day) in day_dirs(workspace_dir)? {
+        for (id, path) in session_files(&day)? {
+            let entries = read_file(&path)?;
+            let Some(last) = entries.last() else {
+                continue;
+            };
+            let preview = entries
+                .iter()
+                .find(|entry| entry.kind == EntryKind::User)
+                .map(|entry| {
+                    truncate_with_ellipsis(
+                        &entry
+                            .content
+                            .split_whitespace()
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                        PREVIEW_CHARS,
+                    )
+                })
+                .unwrap_or_default();
+            summaries.push(SessionSummary {
+                id,
+                date,
+                origin: last.origin.clone(),
+                entries: entries.len(),
+                updated_at: last.timestamp,
+                preview,
+            });
+        }
+    }
+    summaries.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+    Ok(summaries)
+}
+
+/// All entries of session `id`, oldest first, across every day it was
+/// active.
+pub fn read(workspace_dir: &Path, id: &str) -> Result<Vec<TranscriptEntry>> {
+    anyhow::ensure!(!id.is_empty() && slug(id) == id, "无效的会话 ID「{id}」");
+    let mut entries = Vec::new();
+    let mut found = false;
+    for (_, day) in day_dirs(workspace_dir)? {
+        let path = day.join(format!("{id}.jsonl"));
+        if path.is_file() {
+            found = true;
+            entries.extend(read_file(&path)?);
+        }
+    }
+    anyhow::ensure!(
+        found,
+        "没有找到会话「{id}」，运行 `jarvis sessions list` 查看"
+    );
+    Ok(entries)
+}
+
+/// Delete the days of transcripts older than `days`, returning the files
+/// removed (relative to the workspace). With `dry_run` nothing is deleted;
+/// `days == 0` keeps everything.
+pub fn prune(workspace_dir: &Path, days: u32, dry_run: bool) -> Result<Vec<String>> {
+    if days == 0 {
+        return Ok(Vec::new());
+    }
+    let cutoff = Local::now().date_naive() - chrono::Duration::days(i64::from(days));
+    let mut removed = Vec::new();
+    for (date, day) in day_dirs(workspace_dir)? {
+        if date >= cutoff {
+            continue;
+        }
+        let name = date.format("%Y-%m-%d");
+        removed.extend(
+            session_files(&day)?
+                .into_iter()
+                .map(|(id, _)| format!("sessions/{name}/{id}.jsonl")),
+        );
+        if !dry_run {
+            fs::remove_dir_all(&day).with_context(|| format!("删除 {} 失败", day.display()))?;
+        }
+    }
+    Ok(removed)
+}
+
+/// Handle `jarvis sessions ...`.
+pub fn handle_command(command: crate::SessionCommands, config: &Config) -> Result<()> {
+    let workspace_dir = &config.workspace_dir;
+    match command {
+        crate::SessionCommands::List { origin, limit } => {
+            let sessions: Vec<_> = list(workspace_dir)?
+                .into_iter()
+                .filter(|s| origin.as_ref().is_none_or(|o| s.origin == *o))
+                .collect();
+            if sessions.is_empty() {
+                println!("暂无对话记录。");
+                return Ok(());
+            }
+            println!("🗂️  对话记录 ({}):", sessions.len());
+            for s in sessions.iter().take(limit) {
+                println!(
+                    "  {}  {:<10} {}  {:>4} 条  {}",
+                    s.id,
+                    s.origin,
+                    s.updated_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                    s.entries,
+                    s.preview
+                );
+            }
+            if sessions.len() > limit {
+                println!("  …… 另有 {} 个，使用 -n 显示更多", sessions.len() - limit);
+            }
+            println!("\n查看: jarvis sessions show <id>");
+            Ok(())
+        }
+        crate::SessionCommands::Show { id } => {
+            let entries = read(workspace_dir, &id)?;
+            let origin = entries.first().map_or("", |e| e.origin.as_str());
+            println!("🗂️  会话 {id}（来源 {origin}，{} 条）", entries.len());
+            for entry in &entries {
+                let time = entry
+                    .timestamp
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S");
+                let tool = entry.tool.as_deref().unwrap_or("?");
+                let label = match entry.kind {
+                    EntryKind::User => "👤 用户".to_string(),
+                    EntryKind::Assistant => match &entry.model {
+                        Some(model) => format!("🤖 助手（{model}）"),
+                        None => "🤖 助手".to_string(),
+                    },
+                    EntryKind::ToolCall => format!("🔧 调用 {tool}"),
+                    EntryKind::ToolResult if entry.success == Some(false) => {
+                        format!("❌ {tool} 结果")
+                    }
+                    EntryKind::ToolResult => format!("📎 {tool} 结果"),
+                };
+                println!("\n[{time}] {label}:");
+                for line in entry.content.lines() {
+                    println!("  {line}");
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::traits::FunctionCall;
+    use tempfile::TempDir;
+
+    fn call(name: &str, arguments: &str) -> ToolCall {
+        ToolCall {
+            id: "call_1".into(),
+            function: FunctionCall {
+                name: name.into(),
+                arguments: arguments.into(),
+            },
+        }
+    }
+
+    #[test]
+    fn records_a_conversation_and_reads_it_back() {
+        let tmp = TempDir::new().unwrap();
+        let transcript = Transcript::start(tmp.path(), "cli");
+        assert!(transcript.id().contains("-cli-"));
+        transcript.user("list the files");
+        let shell = call("shell", r#"{"command":"ls"}"#);
+        transcript.tool_call(&shell);
+        transcript.tool_result(&shell, &"x".repeat(5000));
+        transcript.tool_result(&shell, "Error: boom");
+        transcript.assistant("Two files.", "test-model");
+
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let path = tmp
+            .path()
+            .join("sessions")
+            .join(today)
+            .join(format!("{}.jsonl", transcript.id()));
+        assert!(path.is_file());
+
+        let entries = read(tmp.path(), transcript.id()).unwrap();
+        let kinds: Vec<_> = entries.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                EntryKind::User,
+                EntryKind::ToolCall,
+                EntryKind::ToolResult,
+                EntryKind::ToolResult,
+                EntryKind::Assistant
+            ]
+        );
+        assert_eq!(entries[1].tool.as_deref(), Some("shell"));
+        assert_eq!(
+            entries[2].content.chars().count(),
+            MAX_TOOL_OUTPUT_CHARS + 3
+        );
+        assert_eq!(entries[2].success, Some(true));
+        assert_eq!(entries[3].success, Some(false));
+        assert_eq!(entries[4].model.as_deref(), Some("test-model"));
+        assert!(entries.iter().all(|e| e.origin == "cli"));
+
+        let listed = list(tmp.path()).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].entries, 5);
+        assert_eq!(listed[0].preview, "list the files");
+    }
+
+    #[test]
+    fn redacts_configured_secrets() {
+        let tmp = TempDir::new().unwrap();
+        crate::security::redact::register_secrets(["transcript-secret-4f9a".to_string()]);
+        let transcript = Transcript::resume(tmp.path(), "telegram", "telegram-alice");
+        transcript.user("my key is transcript-secret-4f9a");
+        transcript.tool_call(&call(
+            "http_request",
+            r#"{"url":"https://x","headers":{"Authorization":"Bearer abc"},"body":"transcript-secret-4f9a"}"#,
+        ));
+
+        let raw = fs::read_to_string(
+            sessions_dir(tmp.path())
+                .join(Local::now().format("%Y-%m-%d").to_string())
+                .join("telegram-alice.jsonl"),
+        )
+        .unwrap();
+        assert!(!raw.contains("transcript-secret-4f9a"), "{raw}");
+        assert!(!raw.contains("Bearer abc"), "{raw}");
+    }
+
+    #[test]
+    fn resume_keys_are_file_safe_and_ids_are_validated() {
+        let tmp = TempDir::new().unwrap();
+        let transcript = Transcript::resume(tmp.path(), "imessage", "imessage-user@icloud.com");
+        assert_eq!(transcript.id(), "imessage-user_icloud.com");
+        assert_eq!(slug("../../etc"), "_.._etc");
+        assert!(read(tmp.path(), "../x").is_err());
+        assert!(read(tmp.path(), "missing").is_err());
+        Transcript::default().user("not written anywhere");
+    }
+
+    #[test]
+    fn prune_removes_only_old_days() {
+        let tmp = TempDir::new().unwrap();
+        let old = Local::now().date_naive() - chrono::Duration::days(40);
+        Transcript::at(tmp.path(), "cli", "old".into(), old).user("old");
+        Transcript::start(tmp.path(), "cli").user("new");
+        fs::create_dir_all(tmp.path().join("sessions").join("archive")).unwrap();
+
+        let removed = prune(tmp.path(), 30, true).unwrap();
+        assert_eq!(
+            removed,
+            [format!("sessions/{}/old.jsonl", old.format("%Y-%m-%d"))]
+        );
+        assert_eq!(list(tmp.path()).unwrap().len(), 2);
+
+        assert_eq!(prune(tmp.path(), 30, false).unwrap(), removed);
+        assert_eq!(list(tmp.path()).unwrap().len(), 1);
+        assert!(tmp.path().join("sessions").join("archive").is_dir());
+        assert!(prune(tmp.path(), 0, false).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,357 @@
+//! `skills/skills.lock.json` — where each installed skill came from, so a
+//! setup can be reproduced and `jarvis skills update` knows what to fetch —
+//! and the git plumbing behind pinned installs and update checks.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const LOCK_FILE: &str = "skills.lock.json";
+
+/// How one skill directory was installed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedSkill {
+    /// Git URL, or the local path the skill was linked or copied from
+    pub source: String,
+    /// Tag, branch or commit the install is pinned to
+    #[serde(default, rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    /// Commit checked out (git installs only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    pub installed_at: DateTime<Utc>,
+    /// Manifest version at install or last update
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Installed from a local path; never checked for updates
+    #[serde(default)]
+    pub local: bool,
+}
+
+impl LockedSkill {
+    pub fn git(url: &str, git_ref: Option<&str>, commit: String, version: &str) -> Self {
+        Self {
+            source: url.to_string(),
+            git_ref: git_ref.map(str::to_string),
+            commit: Some(commit),
+            installed_at: Utc::now(),
+            version: Some(version.to_string()),
+            local: false,
+        }
+    }
+
+    pub fn local(path: &Path, version: &str) -> Self {
+        Self {
+            source: path.display().to_string(),
+            git_ref: None,
+            commit: None,
+            installed_at: Utc::now(),
+            version: Some(version.to_string()),
+            local: true,
+        }
+    }
+}
+
+/// Lockfile contents, keyed by skill directory name.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SkillsLock {
+    #[serde(default)]
+    pub skills: BTreeMap<String, LockedSkill>,
+}
+
+impl SkillsLock {
+    pub fn path(skills_path: &Path) -> PathBuf {
+        skills_path.join(LOCK_FILE)
+    }
+
+    /// The lockfile, empty when missing. A corrupt file is an error rather
+    /// than being silently replaced.
+    pub fn load(skills_path: &Path) -> Result<Self> {
+        let path = Self::path(skills_path);
+        match std::fs::read_to_string(&path) {
+            Ok(raw) => {
+                serde_json::from_str(&raw).with_context(|| format!("解析 {} 失败", path.display()))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("读取 {} 失败", path.display())),
+        }
+    }
+
+    /// Write the lockfile, dropping entries whose directory is gone.
+    pub fn save(&mut self, skills_path: &Path) -> Result<()> {
+        self.skills
+            .retain(|name, _| skills_path.join(name).symlink_metadata().is_ok());
+        let path = Self::path(skills_path);
+        std::fs::write(&path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("写入失败 {}", path.display()))
+    }
+}
+
+/// Split `<url>@<ref>`. Only an `@` in the URL path counts, so
+/// `https://user@host/repo` keeps its user.
+pub fn split_ref(source: &str) -> (&str, Option<&str>) {
+    let host_start = source.find("://").map_or(0, |i| i + 3);
+    let Some(path_start) = source[host_start..].find('/').map(|i| host_start + i) else {
+        return (source, None);
+    };
+    match source[path_start..].rfind('@').map(|i| path_start + i) {
+        Some(at) if at + 1 < source.len() => (&source[..at], Some(&source[at + 1..])),
+        _ => (source, None),
+    }
+}
+
+fn is_commit_sha(git_ref: &str) -> bool {
+    git_ref.len() == 40 && git_ref.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn git(dir: Option<&Path>, args: &[&str]) -> Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.args(args)
+        // Never wait on a credential prompt nobody can answer
+        .env("GIT_TERMINAL_PROMPT", "0");
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+    let output = cmd.output().context("运行 git 失败")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} 失败: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Clone `url` into `dest`, at `git_ref` when pinned, and return the
+/// commit checked out.
+pub fn clone(url: &str, git_ref: Option<&str>, dest: &Path) -> Result<String> {
+    let dest_str = dest.to_string_lossy();
+    match git_ref {
+        None => {
+            git(None, &["clone", "--depth", "1", url, &dest_str])?;
+        }
+        Some(git_ref) => {
+            // `clone --branch` can't take a commit SHA; fetching one works
+            std::fs::create_dir_all(dest)?;
+            git(Some(dest), &["init", "-q"])?;
+            git(Some(dest), &["remote", "add", "origin", url])?;
+            fetch(dest, Some(git_ref))?;
+            git(Some(dest), &["checkout", "-q", "--detach", "FETCH_HEAD"])?;
+        }
+    }
+    head_commit(dest)
+}
+
+pub fn head_commit(dir: &Path) -> Result<String> {
+    git(Some(dir), &["rev-parse", "HEAD"])
+}
+
+/// Fetch `git_ref` (or the remote default branch) into the clone at `dir`
+/// and return the fetched commit.
+pub fn fetch(dir: &Path, git_ref: Option<&str>) -> Result<String> {
+    git(
+        Some(dir),
+        &[
+            "fetch",
+            "-q",
+            "--depth",
+            "1",
+            "origin",
+            git_ref.unwrap_or("HEAD"),
+        ],
+    )?;
+    git(Some(dir), &["rev-parse", "FETCH_HEAD^{commit}"])
+}
+
+/// `git diff --stat` between two fetched commits.
+pub fn diff_stat(dir: &Path, from: &str, to: &str) -> Result<String> {
+    git(Some(dir), &["diff", "--stat", from, to])
+}
+
+pub fn checkout(dir: &Path, commit: &str) -> Result<()> {
+    git(Some(dir), &["checkout", "-q", "--detach", commit]).map(|_| ())
+}
+
+/// Files in the clone at `dir` edited, added or deleted since checkout.
+pub fn local_changes(dir: &Path) -> Result<Vec<String>> {
+    let status = git(
+        Some(dir),
+        &["status", "--porcelain", "--untracked-files=all"],
+    )?;
+    // `XY path`; the output is trimmed, so the first line may have lost
+    // the space of an unstaged ` M`
+    Ok(status
+        .lines()
+        .filter_map(|line| line.get(2..))
+        .map(|path| path.trim_start().to_string())
+        .collect())
+}
+
+/// Throw away local edits and untracked files in the clone at `dir`.
+pub fn discard_changes(dir: &Path) -> Result<()> {
+    git(Some(dir), &["reset", "-q", "--hard"])?;
+    git(Some(dir), &["clean", "-q", "-f", "-d"]).map(|_| ())
+}
+
+/// The commit `git_ref` (or the default branch) points at on the remote;
+/// `None` when the install is pinned to a commit, which cannot move.
+pub fn remote_commit(url: &str, git_ref: Option<&str>) -> Result<Option<String>> {
+    if git_ref.is_some_and(is_commit_sha) {
+        return Ok(None);
+    }
+    let git_ref = git_ref.unwrap_or("HEAD");
+    let listing = git(None, &["ls-remote", url, git_ref])?;
+    pick_ref(&listing, git_ref)
+        .map(Some)
+        .with_context(|| format!("远程没有 {git_ref}"))
+}
+
+/// The commit for `git_ref` in `git ls-remote` output, preferring the peeled
+/// commit of an annotated tag, then tags, then branches.
+fn pick_ref(listing: &str, git_ref: &str) -> Option<String> {
+    let refs: Vec<(&str, &str)> = listing
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .collect();
+    [
+        format!("refs/tags/{git_ref}^{{}}"),
+        format!("refs/tags/{git_ref}"),
+        format!("refs/heads/{git_ref}"),
+        git_ref.to_string(),
+    ]
+    .iter()
+    .find_map(|wanted| {
+        refs.iter()
+            .find(|(_, name)| name == wanted)
+            .map(|(sha, _)| (*sha).to_string())
+    })
+}
+
+/// The first seven characters of a commit, for display.
+pub fn short(commit: &str) -> &str {
+    commit.get(..7).unwrap_or(commit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?}");
+    }
+
+    fn commit(dir: &Path, file: &str, content: &str) {
+        std::fs::write(dir.join(file), content).unwrap();
+        run(dir, &["add", "."]);
+        run(dir, &["commit", "-q", "-m", file]);
+    }
+
+    #[test]
+    fn splits_pinned_refs() {
+        assert_eq!(
+            split_ref("https://github.com/acme/skill@v1.2.0"),
+            ("https://github.com/acme/skill", Some("v1.2.0"))
+        );
+        assert_eq!(
+            split_ref("https://github.com/acme/skill.git@release/1.x"),
+            ("https://github.com/acme/skill.git", Some("release/1.x"))
+        );
+        assert_eq!(
+            split_ref("https://token@github.com/acme/skill"),
+            ("https://token@github.com/acme/skill", None)
+        );
+        assert_eq!(
+            split_ref("https://github.com/acme/skill@"),
+            ("https://github.com/acme/skill@", None)
+        );
+    }
+
+    #[test]
+    fn picks_peeled_tags_then_branches() {
+        let listing = "aaa\trefs/heads/v1\nbbb\trefs/tags/v1\nccc\trefs/tags/v1^{}\n";
+        assert_eq!(pick_ref(listing, "v1").as_deref(), Some("ccc"));
+        assert_eq!(pick_ref("ddd\tHEAD\n", "HEAD").as_deref(), Some("ddd"));
+        assert_eq!(pick_ref("eee\trefs/heads/x/main\n", "main"), None);
+    }
+
+    #[test]
+    fn lock_round_trips_and_prunes_missing_dirs() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmp.path().join("weather")).unwrap();
+        let mut lock = SkillsLock::load(tmp.path()).unwrap();
+        assert!(lock.skills.is_empty());
+        lock.skills.insert(
+            "weather".into(),
+            LockedSkill::git(
+                "https://example.com/weather",
+                Some("v1"),
+                "abc".into(),
+                "1.0.0",
+            ),
+        );
+        lock.skills.insert(
+            "gone".into(),
+            LockedSkill::local(Path::new("/src/gone"), "0.1.0"),
+        );
+        lock.save(tmp.path()).unwrap();
+
+        let raw = std::fs::read_to_string(SkillsLock::path(tmp.path())).unwrap();
+        assert!(raw.contains("\"ref\": \"v1\""), "{raw}");
+        let loaded = SkillsLock::load(tmp.path()).unwrap();
+        assert_eq!(loaded.skills.len(), 1);
+        assert_eq!(loaded.skills["weather"].commit.as_deref(), Some("abc"));
+        assert_eq!(loaded.skills["weather"].version.as_deref(), Some("1.0.0"));
+    }
+
+    #[test]
+    fn pinned_clone_fetches_and_detects_moves() {
+        let tmp = tempfile::tempdir().unwrap();
+        let remote = tmp.path().join("remote");
+        std::fs::create_dir(&remote).unwrap();
+        for args in [
+            &["init", "-q", "-b", "main"][..],
+            &["config", "user.name", "Tester"],
+            &["config", "user.email", "tester@example.com"],
+            &["config", "commit.gpgsign", "false"],
+        ] {
+            run(&remote, args);
+        }
+        commit(&remote, "SKILL.md", "# v1\n");
+        run(&remote, &["tag", "v1"]);
+        let url = remote.to_string_lossy().to_string();
+
+        let pinned = tmp.path().join("pinned");
+        let v1 = clone(&url, Some("v1"), &pinned).unwrap();
+        assert_eq!(remote_commit(&url, Some("v1")).unwrap(), Some(v1.clone()));
+        assert_eq!(remote_commit(&url, Some(&v1)).unwrap(), None);
+
+        let tracking = tmp.path().join("tracking");
+        assert_eq!(clone(&url, None, &tracking).unwrap(), v1);
+        commit(&remote, "SKILL.md", "# v2\n");
+        let v2 = remote_commit(&url, None).unwrap().unwrap();
+        assert_ne!(v2, v1);
+
+        assert_eq!(fetch(&tracking, None).unwrap(), v2);
+        assert!(diff_stat(&tracking, &v1, &v2).unwrap().contains("SKILL.md"));
+        checkout(&tracking, &v2).unwrap();
+        assert_eq!(head_commit(&tracking).unwrap(), v2);
+        assert_eq!(fetch(&pinned, Some("v1")).unwrap(), v1);
+
+        assert!(local_changes(&tracking).unwrap().is_empty());
+        std::fs::write(tracking.join("SKILL.md"), "# edited\n").unwrap();
+        std::fs::write(tracking.join("notes.txt"), "mine\n").unwrap();
+        assert_eq!(local_changes(&tracking).unwrap(), ["SKILL.md", "notes.txt"]);
+        discard_changes(&tracking).unwrap();
+        assert!(local_changes(&tracking).unwrap().is_empty());
+        assert!(!tracking.join("notes.txt").exists());
+    }
+}
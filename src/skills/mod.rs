@@ -1,4 +1,5 @@
-use anyhow::Result;
+use crate::config::Config;
+use anyhow::{Context, Result};
 use directories::UserDirs;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -6,6 +7,10 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::time::{Duration, SystemTime};
 
+mod lock;
+
+use lock::{LockedSkill, SkillsLock};
+
 const OPEN_SKILLS_REPO_URL: &str = "https://github.com/besoeasy/open-skills";
 const OPEN_SKILLS_SYNC_MARKER: &str = ".jarvis-open-skills-sync";
 const OPEN_SKILLS_SYNC_INTERVAL_SECS: u64 = 60 * 60 * 24 * 7;
@@ -22,10 +27,16 @@ pub struct Skill {
     pub author: Option<String>,
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Built-in tools (`shell`, `git`, ...) the skill's instructions rely on
+    #[serde(default)]
+    pub required_tools: Vec<String>,
     #[serde(default)]
     pub tools: Vec<SkillTool>,
     #[serde(default)]
     pub prompts: Vec<String>,
+    /// Script the model can run as the `skill_<name>` tool
+    #[serde(default)]
+    pub entrypoint: Option<SkillEntrypoint>,
     #[serde(skip)]
     pub location: Option<PathBuf>,
 }
@@ -43,6 +54,43 @@ pub struct SkillTool {
     pub args: HashMap<String, String>,
 }
 
+/// The `[entrypoint]` of a SKILL.toml: a script run with typed arguments.
+///
+/// ```toml
+/// [entrypoint]
+/// script = "stats.sh"
+/// interpreter = "sh"
+///
+/// [[entrypoint.args]]
+/// name = "text"
+/// type = "string"
+/// required = true
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillEntrypoint {
+    /// Script to run, relative to the skill directory
+    pub script: String,
+    /// Program the script is passed to; it must be permitted by the
+    /// command policy like any shell command
+    pub interpreter: String,
+    /// Arguments, given to the script as `SKILL_ARG_<NAME>` variables
+    #[serde(default)]
+    pub args: Vec<SkillArg>,
+}
+
+/// One typed argument of a [`SkillEntrypoint`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillArg {
+    pub name: String,
+    /// "string", "integer", "number" or "boolean"
+    #[serde(rename = "type", default = "default_arg_type")]
+    pub kind: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub required: bool,
+}
+
 /// Skill manifest parsed from SKILL.toml
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct SkillManifest {
@@ -51,6 +99,8 @@ struct SkillManifest {
     tools: Vec<SkillTool>,
     #[serde(default)]
     prompts: Vec<String>,
+    #[serde(default)]
+    entrypoint: Option<SkillEntrypoint>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,12 +113,214 @@ struct SkillMeta {
     author: Option<String>,
     #[serde(default)]
     tags: Vec<String>,
+    #[serde(default)]
+    required_tools: Vec<String>,
+    /// Markdown file in the skill directory with the entry instructions
+    #[serde(default)]
+    entry: Option<String>,
+}
+
+/// Front-matter at the top of a SKILL.md, between `---` lines:
+///
+/// ```text
+/// ---
+/// name: my-skill
+/// version: 1.2.0
+/// description: What this skill does
+/// required_tools: [shell, git]
+/// ---
+/// ```
+///
+/// Lists may also be written one `- item` per line. Unknown keys are ignored.
+#[derive(Debug, Default)]
+struct FrontMatter {
+    name: Option<String>,
+    version: Option<String>,
+    description: Option<String>,
+    author: Option<String>,
+    tags: Vec<String>,
+    required_tools: Vec<String>,
+}
+
+const SKILL_TOOL_KINDS: &[&str] = &["shell", "http", "script"];
+const SKILL_ARG_TYPES: &[&str] = &["string", "integer", "number", "boolean"];
+
+impl Skill {
+    /// Check the metadata a skill needs before it is installed or prompted.
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty()
+            || self.name.contains(['/', '\\'])
+            || self.name.contains("..")
+            || self.name.chars().any(char::is_control)
+        {
+            anyhow::bail!("无效的技能名称「{}」", self.name);
+        }
+        if self.version.trim().is_empty() {
+            anyhow::bail!("技能「{}」缺少 version", self.name);
+        }
+        if version_key(&self.version).is_none() {
+            anyhow::bail!(
+                "技能「{}」的 version「{}」无效（应为 1.2.0 这样的版本号）",
+                self.name,
+                self.version
+            );
+        }
+        if self.description.trim().is_empty() {
+            anyhow::bail!("技能「{}」缺少 description", self.name);
+        }
+        for (i, tool) in self.tools.iter().enumerate() {
+            if tool.name.trim().is_empty() || tool.command.trim().is_empty() {
+                anyhow::bail!("技能「{}」的工具缺少 name 或 command", self.name);
+            }
+            if self.tools[..i].iter().any(|t| t.name == tool.name) {
+                anyhow::bail!("技能「{}」的工具 {} 重复定义", self.name, tool.name);
+            }
+            if !SKILL_TOOL_KINDS.contains(&tool.kind.as_str()) {
+                anyhow::bail!(
+                    "技能「{}」的工具 {} 使用未知类型「{}」（可选 {}）",
+                    self.name,
+                    tool.name,
+                    tool.kind,
+                    SKILL_TOOL_KINDS.join("、")
+                );
+            }
+        }
+        if self.required_tools.iter().any(|t| t.trim().is_empty()) {
+            anyhow::bail!("技能「{}」的 required_tools 含有空项", self.name);
+        }
+        if let Some(entrypoint) = &self.entrypoint {
+            self.validate_entrypoint(entrypoint)?;
+        }
+        Ok(())
+    }
+
+    fn validate_entrypoint(&self, entrypoint: &SkillEntrypoint) -> Result<()> {
+        let script = Path::new(&entrypoint.script);
+        if entrypoint.script.trim().is_empty()
+            || script.is_absolute()
+            || script
+                .components()
+                .any(|c| matches!(c, std::path::Component::ParentDir))
+        {
+            anyhow::bail!(
+                "技能「{}」的 entrypoint.script 必须是技能目录内的相对路径",
+                self.name
+            );
+        }
+        if let Some(dir) = self.dir()
+            && !dir.join(script).is_file()
+        {
+            anyhow::bail!(
+                "技能「{}」的入口脚本 {} 不存在",
+                self.name,
+                entrypoint.script
+            );
+        }
+        let interpreter = &entrypoint.interpreter;
+        if interpreter.is_empty()
+            || !interpreter
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | '+'))
+        {
+            anyhow::bail!(
+                "技能「{}」的 entrypoint.interpreter「{interpreter}」无效，应为程序名（如 python3）",
+                self.name
+            );
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for arg in &entrypoint.args {
+            if !is_arg_name(&arg.name) {
+                anyhow::bail!(
+                    "技能「{}」的参数名「{}」无效（只能包含字母、数字和下划线）",
+                    self.name,
+                    arg.name
+                );
+            }
+            if !seen.insert(arg.name.to_ascii_uppercase()) {
+                anyhow::bail!("技能「{}」的参数「{}」重复", self.name, arg.name);
+            }
+            if !SKILL_ARG_TYPES.contains(&arg.kind.as_str()) {
+                anyhow::bail!(
+                    "技能「{}」的参数 {} 使用未知类型「{}」（可选 {}）",
+                    self.name,
+                    arg.name,
+                    arg.kind,
+                    SKILL_ARG_TYPES.join("、")
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Directory holding the skill's files, when loaded from disk.
+    pub fn dir(&self) -> Option<&Path> {
+        self.location.as_deref().and_then(Path::parent)
+    }
+
+    /// Name of the tool that runs the skill's entry point.
+    pub fn tool_name(&self) -> String {
+        let name: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("skill_{name}")
+    }
+
+    /// Required tools that are neither in `available` nor defined by the
+    /// skill itself.
+    pub fn missing_tools(&self, available: &[&str]) -> Vec<&str> {
+        self.required_tools
+            .iter()
+            .map(String::as_str)
+            .filter(|name| !available.contains(name))
+            .filter(|name| !self.tools.iter().any(|t| t.name == *name))
+            .collect()
+    }
 }
 
 fn default_version() -> String {
     "0.1.0".to_string()
 }
 
+fn default_arg_type() -> String {
+    "string".to_string()
+}
+
+/// The numeric parts of a `1.2.0`-style version, for comparison. Up to
+/// three parts; a `-beta.1` or `+build` suffix is allowed and ignored.
+fn version_key(version: &str) -> Option<Vec<u64>> {
+    let core = version.trim().split(['-', '+']).next().unwrap_or_default();
+    let parts: Vec<u64> = core
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    (1..=3).contains(&parts.len()).then_some(parts)
+}
+
+/// Compare two valid skill versions, treating missing parts as zero.
+fn compare_versions(a: &str, b: &str) -> Option<std::cmp::Ordering> {
+    let (mut a, mut b) = (version_key(a)?, version_key(b)?);
+    a.resize(3, 0);
+    b.resize(3, 0);
+    Some(a.cmp(&b))
+}
+
+/// Whether `name` can become a `SKILL_ARG_<NAME>` variable.
+fn is_arg_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 /// Load all skills from the workspace skills directory
 pub fn load_skills(workspace_dir: &Path) -> Vec<Skill> {
     let mut skills = Vec::new();
@@ -99,28 +351,37 @@ fn load_skills_from_directory(skills_dir: &Path) -> Vec<Skill> {
 
     for entry in entries.flatten() {
         let path = entry.path();
-        if !path.is_dir() {
+        // Hidden directories hold in-progress installs
+        if !path.is_dir() || entry.file_name().to_string_lossy().starts_with('.') {
             continue;
         }
 
-        // Try SKILL.toml first, then SKILL.md
-        let manifest_path = path.join("SKILL.toml");
-        let md_path = path.join("SKILL.md");
-
-        if manifest_path.exists() {
-            if let Ok(skill) = load_skill_toml(&manifest_path) {
-                skills.push(skill);
-            }
-        } else if md_path.exists() {
-            if let Ok(skill) = load_skill_md(&md_path, &path) {
-                skills.push(skill);
-            }
+        match load_skill_dir(&path) {
+            Ok(skill) => skills.push(skill),
+            Err(e) => tracing::warn!("跳过无效技能 {}: {e:#}", path.display()),
         }
     }
 
     skills
 }
 
+/// Load and validate one skill directory — SKILL.toml first, then SKILL.md
+pub fn load_skill_dir(dir: &Path) -> Result<Skill> {
+    let manifest_path = dir.join("SKILL.toml");
+    let md_path = dir.join("SKILL.md");
+
+    let skill = if manifest_path.exists() {
+        load_skill_toml(&manifest_path, dir)
+            .with_context(|| format!("读取 {} 失败", manifest_path.display()))?
+    } else if md_path.exists() {
+        load_skill_md(&md_path, dir).with_context(|| format!("读取 {} 失败", md_path.display()))?
+    } else {
+        anyhow::bail!("{} 中没有 SKILL.toml 或 SKILL.md", dir.display())
+    };
+    skill.validate()?;
+    Ok(skill)
+}
+
 fn load_open_skills(repo_dir: &Path) -> Vec<Skill> {
     let mut skills = Vec::new();
 
@@ -282,43 +543,137 @@ fn mark_open_skills_synced(repo_dir: &Path) -> Result<()> {
 }
 
 /// Load a skill from a SKILL.toml manifest
-fn load_skill_toml(path: &Path) -> Result<Skill> {
+fn load_skill_toml(path: &Path, dir: &Path) -> Result<Skill> {
     let content = std::fs::read_to_string(path)?;
     let manifest: SkillManifest = toml::from_str(&content)?;
 
+    let mut prompts = manifest.prompts;
+    if let Some(entry) = &manifest.skill.entry {
+        let entry_path = Path::new(entry);
+        if entry_path.is_absolute() || entry.contains("..") {
+            anyhow::bail!("entry 必须是技能目录内的相对路径: {entry}");
+        }
+        let instructions = std::fs::read_to_string(dir.join(entry_path))
+            .with_context(|| format!("读取 entry 文件 {entry} 失败"))?;
+        prompts.push(instructions);
+    }
+
     Ok(Skill {
         name: manifest.skill.name,
         description: manifest.skill.description,
         version: manifest.skill.version,
         author: manifest.skill.author,
         tags: manifest.skill.tags,
+        required_tools: manifest.skill.required_tools,
         tools: manifest.tools,
-        prompts: manifest.prompts,
+        prompts,
+        entrypoint: manifest.entrypoint,
         location: Some(path.to_path_buf()),
     })
 }
 
-/// Load a skill from a SKILL.md file (simpler format)
+/// Load a skill from a SKILL.md file (simpler format), with optional
+/// front-matter
 fn load_skill_md(path: &Path, dir: &Path) -> Result<Skill> {
     let content = std::fs::read_to_string(path)?;
-    let name = dir
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown")
-        .to_string();
+    let (meta, body) = split_front_matter(&content)?;
+    let name = meta.name.unwrap_or_else(|| {
+        dir.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string()
+    });
 
     Ok(Skill {
         name,
-        description: extract_description(&content),
-        version: "0.1.0".to_string(),
-        author: None,
-        tags: Vec::new(),
+        description: meta
+            .description
+            .unwrap_or_else(|| extract_description(body)),
+        version: meta.version.unwrap_or_else(default_version),
+        author: meta.author,
+        tags: meta.tags,
+        required_tools: meta.required_tools,
         tools: Vec::new(),
-        prompts: vec![content],
+        prompts: vec![body.to_string()],
+        entrypoint: None,
         location: Some(path.to_path_buf()),
     })
 }
 
+/// Split a SKILL.md into its front-matter (empty when absent) and body.
+fn split_front_matter(content: &str) -> Result<(FrontMatter, &str)> {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return Ok((FrontMatter::default(), content));
+    };
+
+    let mut meta = FrontMatter::default();
+    let mut list_key: Option<&str> = None;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let line = line.trim();
+        if line == "---" {
+            return Ok((meta, rest[offset..].trim_start_matches(['\r', '\n'])));
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(item) = line.strip_prefix("- ") {
+            let Some(key) = list_key else {
+                anyhow::bail!("front-matter 列表项缺少键: {line}");
+            };
+            meta.push_list(key, vec![unquote(item)]);
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            anyhow::bail!("无法解析 front-matter 行: {line}");
+        };
+        let (key, value) = (key.trim(), value.trim());
+        list_key = value.is_empty().then_some(key);
+        if !value.is_empty() {
+            meta.set(key, value);
+        }
+    }
+    anyhow::bail!("front-matter 缺少结束的 ---")
+}
+
+impl FrontMatter {
+    fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "name" => self.name = Some(unquote(value)),
+            "version" => self.version = Some(unquote(value)),
+            "description" => self.description = Some(unquote(value)),
+            "author" => self.author = Some(unquote(value)),
+            "tags" | "required_tools" => {
+                let items = value
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .split(',')
+                    .map(unquote)
+                    .filter(|item| !item.is_empty())
+                    .collect();
+                self.push_list(key, items);
+            }
+            _ => {}
+        }
+    }
+
+    fn push_list(&mut self, key: &str, items: Vec<String>) {
+        match key {
+            "tags" => self.tags.extend(items),
+            "required_tools" => self.required_tools.extend(items),
+            _ => {}
+        }
+    }
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches(['"', '\'']).trim().to_string()
+}
+
 fn load_open_skill_md(path: &Path) -> Result<Skill> {
     let content = std::fs::read_to_string(path)?;
     let name = path
@@ -333,8 +688,10 @@ fn load_open_skill_md(path: &Path) -> Result<Skill> {
         version: "open-skills".to_string(),
         author: Some("besoeasy/open-skills".to_string()),
         tags: vec!["open-skills".to_string()],
+        required_tools: Vec::new(),
         tools: Vec::new(),
         prompts: vec![content],
+        entrypoint: None,
         location: Some(path.to_path_buf()),
     })
 }
@@ -372,6 +729,9 @@ pub fn skills_to_prompt(skills: &[Skill]) -> String {
                 );
             }
         }
+        if skill.entrypoint.is_some() {
+            let _ = writeln!(prompt, "Run it with the `{}` tool.", skill.tool_name());
+        }
 
         for p in &skill.prompts {
             prompt.push_str(p);
@@ -407,30 +767,59 @@ pub fn init_skills_dir(workspace_dir: &Path) -> Result<()> {
              description = \"What this skill does\"\n\
              version = \"0.1.0\"\n\
              author = \"your-name\"\n\
-             tags = [\"productivity\", \"automation\"]\n\n\
+             tags = [\"productivity\", \"automation\"]\n\
+             required_tools = [\"shell\"]\n\
+             entry = \"INSTRUCTIONS.md\"\n\n\
              [[tools]]\n\
              name = \"my_tool\"\n\
              description = \"What this tool does\"\n\
              kind = \"shell\"\n\
-             command = \"echo hello\"\n\
+             command = \"echo hello\"\n\n\
+             # Optional: a script the agent can call as the skill_my_skill tool\n\
+             [entrypoint]\n\
+             script = \"run.py\"\n\
+             interpreter = \"python3\"   # must be allowed by [autonomy] allowed_commands\n\n\
+             [[entrypoint.args]]\n\
+             name = \"query\"\n\
+             type = \"string\"         # string, integer, number or boolean\n\
+             description = \"What to look up\"\n\
+             required = true\n\
              ```\n\n\
+             The script runs in the workspace with a clean environment. It gets each\n\
+             argument as `SKILL_ARG_<NAME>`, all of them as JSON in `SKILL_ARGS`, and its\n\
+             own directory as `SKILL_DIR`; its output is returned to the agent.\n\n\
              ## SKILL.md format (simpler)\n\n\
              Just write a markdown file with instructions for the agent.\n\
-             The agent will read it and follow the instructions.\n\n\
+             The agent will read it and follow the instructions.\n\
+             Optional front-matter declares the metadata:\n\n\
+             ```markdown\n\
+             ---\n\
+             name: my-skill\n\
+             version: 0.1.0\n\
+             description: What this skill does\n\
+             required_tools: [shell, git]\n\
+             ---\n\
+             ```\n\n\
              ## Installing community skills\n\n\
              ```bash\n\
              jarvis skills install <github-url>\n\
+             jarvis skills install <github-url>@v1.0.0   # pin a tag, branch or commit\n\
+             jarvis skills install <path> --force   # replace a skill with the same name\n\
              jarvis skills list\n\
-             ```\n",
+             jarvis skills update [name]   # fetch, review and apply upstream changes\n\
+             jarvis skills update <name> --force   # ...discarding local edits to the skill\n\
+             ```\n\n\
+             Manifests are validated before install: `version` must look like `1.2.0`, and\n\
+             tool and argument names must be unique. Installs are recorded in\n\
+             `skills.lock.json` (source, ref, commit, version, install time).\n",
         )?;
     }
 
     Ok(())
 }
 
-/// Recursively copy a directory (used as fallback when symlinks aren't available)
-#[cfg(any(windows, not(unix)))]
-fn copy_dir_recursive(src: &Path, dest: &Path) -> Result<()> {
+/// Recursively copy a directory (symlink fallback, and skill imports)
+pub(crate) fn copy_dir_recursive(src: &Path, dest: &Path) -> Result<()> {
     std::fs::create_dir_all(dest)?;
     for entry in std::fs::read_dir(src)? {
         let entry = entry?;
@@ -445,9 +834,251 @@ fn copy_dir_recursive(src: &Path, dest: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Directory name for a skill cloned from `url` — the repository name.
+fn repo_dir_name(url: &str) -> Result<String> {
+    let path = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let name = path
+        .trim_end_matches('/')
+        .split_once('/')
+        .and_then(|(_, repo)| repo.rsplit('/').next())
+        .unwrap_or_default()
+        .trim_end_matches(".git");
+    if name.is_empty() || name.starts_with('.') {
+        anyhow::bail!("无法从 URL 推断技能目录名: {url}");
+    }
+    Ok(name.to_string())
+}
+
+/// Validate the skill in `src` before it is installed as `dest`.
+///
+/// An installed skill with the same name, or anything already at `dest`, is
+/// a conflict: it is removed when `force` is set and refused otherwise.
+/// Required tools missing from `available` only produce a warning.
+fn prepare_install(
+    src: &Path,
+    dest: &Path,
+    skills_path: &Path,
+    available: &[&str],
+    force: bool,
+) -> Result<Skill> {
+    let skill = load_skill_dir(src).context("技能校验失败")?;
+
+    let mut conflicts: Vec<PathBuf> = load_skills_from_directory(skills_path)
+        .iter()
+        .filter(|installed| installed.name == skill.name)
+        .filter_map(|installed| {
+            installed
+                .location
+                .as_deref()?
+                .parent()
+                .map(Path::to_path_buf)
+        })
+        .collect();
+    if dest.symlink_metadata().is_ok() && !conflicts.iter().any(|c| c == dest) {
+        conflicts.push(dest.to_path_buf());
+    }
+    if let Some(existing) = conflicts.first() {
+        if !force {
+            anyhow::bail!(
+                "技能「{}」与已安装的 {} 冲突；使用 --force 覆盖",
+                skill.name,
+                existing.display()
+            );
+        }
+        for existing in &conflicts {
+            if existing.symlink_metadata()?.is_dir() {
+                std::fs::remove_dir_all(existing)?;
+            } else {
+                std::fs::remove_file(existing)?;
+            }
+            println!("  已移除旧版本: {}", existing.display());
+        }
+    }
+
+    println!(
+        "  {} 技能「{}」v{} 校验通过",
+        console::style("✓").green().bold(),
+        skill.name,
+        skill.version
+    );
+    let missing = skill.missing_tools(available);
+    if !missing.is_empty() {
+        println!(
+            "  {} 需要当前未启用的工具: {}",
+            console::style("⚠").yellow().bold(),
+            missing.join(", ")
+        );
+    }
+    Ok(skill)
+}
+
+/// Add `dir_name` to the lockfile.
+fn record_install(skills_path: &Path, dir_name: &str, locked: LockedSkill) -> Result<()> {
+    let mut lock = SkillsLock::load(skills_path)?;
+    lock.skills.insert(dir_name.to_string(), locked);
+    lock.save(skills_path)
+}
+
+/// The directory name of a skill installed under `skills_path`, which is
+/// its lockfile key.
+fn installed_dir(skill: &Skill, skills_path: &Path) -> Option<String> {
+    let dir = skill.location.as_deref()?.parent()?;
+    (dir.parent()? == skills_path).then(|| dir.file_name()?.to_str().map(str::to_string))?
+}
+
+/// Where a skill came from, checking git installs for a moved remote ref.
+fn describe_source(locked: &LockedSkill) -> String {
+    if locked.local {
+        return format!("本地 {}", locked.source);
+    }
+    let pin = locked
+        .git_ref
+        .as_deref()
+        .map(|r| format!(" @ {r}"))
+        .unwrap_or_default();
+    let commit = locked.commit.as_deref().unwrap_or_default();
+    let update = match lock::remote_commit(&locked.source, locked.git_ref.as_deref()) {
+        Ok(Some(remote)) if remote != commit => console::style(format!(
+            " — 有可用更新（{}），运行 `jarvis skills update`",
+            lock::short(&remote)
+        ))
+        .yellow()
+        .to_string(),
+        Ok(_) => String::new(),
+        Err(e) => {
+            tracing::debug!("检查技能更新失败 {}: {e:#}", locked.source);
+            String::new()
+        }
+    };
+    format!("{}{pin} ({}){update}", locked.source, lock::short(commit))
+}
+
+/// `jarvis skills update [name]`: fetch each git-installed skill's pinned
+/// ref, show what changed and check it out once confirmed, then compare the
+/// manifest versions. A clone with local edits is left alone unless `force`
+/// is set, which discards them.
+fn update_skills(skills_path: &Path, name: Option<&str>, yes: bool, force: bool) -> Result<()> {
+    let mut lock = SkillsLock::load(skills_path)?;
+    let names: Vec<String> = match name {
+        Some(name) if !lock.skills.contains_key(name) => {
+            anyhow::bail!("skills.lock.json 中没有技能「{name}」（只记录通过 install 安装的技能）")
+        }
+        Some(name) => vec![name.to_string()],
+        None => lock.skills.keys().cloned().collect(),
+    };
+    if names.is_empty() {
+        println!("没有通过 `jarvis skills install` 安装的技能。");
+        return Ok(());
+    }
+
+    for name in names {
+        let Some(locked) = lock.skills.get(&name).cloned() else {
+            continue;
+        };
+        if locked.local {
+            println!("  {name}: 本地安装，跳过");
+            continue;
+        }
+        let dir = skills_path.join(&name);
+        let current = lock::head_commit(&dir)?;
+        let latest = lock::fetch(&dir, locked.git_ref.as_deref())
+            .with_context(|| format!("获取技能「{name}」的更新失败"))?;
+        if latest == current {
+            println!(
+                "  {} {name} 已是最新（{}）",
+                console::style("✓").green().bold(),
+                lock::short(&current)
+            );
+            continue;
+        }
+
+        let changes = lock::local_changes(&dir)?;
+        if !changes.is_empty() && !force {
+            println!(
+                "  {} {name} 有本地修改（{}），未更新；使用 --force 丢弃修改并更新",
+                console::style("⚠").yellow().bold(),
+                changes.join(", ")
+            );
+            continue;
+        }
+
+        println!(
+            "  {name}: {} → {}",
+            lock::short(&current),
+            lock::short(&latest)
+        );
+        println!("{}", lock::diff_stat(&dir, &current, &latest)?);
+        if !yes {
+            let confirmed = dialoguer::Confirm::new()
+                .with_prompt(format!("  更新技能「{name}」？"))
+                .default(false)
+                .interact()
+                .context("读取确认输入失败")?;
+            if !confirmed {
+                println!("  已跳过。");
+                continue;
+            }
+        }
+
+        let installed_version = load_skill_dir(&dir)
+            .map(|skill| skill.version)
+            .ok()
+            .or_else(|| locked.version.clone());
+        if !changes.is_empty() {
+            lock::discard_changes(&dir)?;
+            println!("  已丢弃本地修改: {}", changes.join(", "));
+        }
+        lock::checkout(&dir, &latest)?;
+        let updated = match load_skill_dir(&dir) {
+            Ok(skill) => skill,
+            Err(e) => {
+                lock::checkout(&dir, &current)?;
+                println!(
+                    "  {} {name} 的新版本校验失败，已回滚: {e:#}",
+                    console::style("⚠").yellow().bold()
+                );
+                continue;
+            }
+        };
+        if let Some(installed) = &installed_version {
+            print_version_change(installed, &updated.version);
+        }
+        lock.skills.insert(
+            name.clone(),
+            LockedSkill {
+                commit: Some(latest.clone()),
+                installed_at: chrono::Utc::now(),
+                version: Some(updated.version.clone()),
+                ..locked
+            },
+        );
+        lock.save(skills_path)?;
+        println!(
+            "  {} {name} 已更新到 v{}（{}）",
+            console::style("✓").green().bold(),
+            updated.version,
+            lock::short(&latest)
+        );
+    }
+    Ok(())
+}
+
+fn print_version_change(installed: &str, updated: &str) {
+    match compare_versions(installed, updated) {
+        Some(std::cmp::Ordering::Less) => println!("  版本: v{installed} → v{updated}"),
+        Some(std::cmp::Ordering::Equal) => println!("  版本号未变（v{installed}）"),
+        _ => println!(
+            "  {} 版本号从 v{installed} 降到了 v{updated}",
+            console::style("⚠").yellow().bold()
+        ),
+    }
+}
+
 /// Handle the `skills` CLI command
 #[allow(clippy::too_many_lines)]
-pub fn handle_command(command: crate::SkillCommands, workspace_dir: &Path) -> Result<()> {
+pub fn handle_command(command: crate::SkillCommands, config: &Config) -> Result<()> {
+    let workspace_dir = config.workspace_dir.as_path();
+    let available = crate::tools::configured_tool_names(config);
     match command {
         crate::SkillCommands::List => {
             let skills = load_skills(workspace_dir);
@@ -461,6 +1092,11 @@ pub fn handle_command(command: crate::SkillCommands, workspace_dir: &Path) -> Re
                 println!();
                 println!("  或安装:   jarvis skills install <github-url>");
             } else {
+                let skills_path = skills_dir(workspace_dir);
+                let lock = SkillsLock::load(&skills_path).unwrap_or_else(|e| {
+                    tracing::warn!("{e:#}");
+                    SkillsLock::default()
+                });
                 println!("已安装的技能 ({}):", skills.len());
                 println!();
                 for skill in &skills {
@@ -481,34 +1117,81 @@ pub fn handle_command(command: crate::SkillCommands, workspace_dir: &Path) -> Re
                                 .join(", ")
                         );
                     }
+                    if !skill.required_tools.is_empty() {
+                        let missing = skill.missing_tools(&available);
+                        let requires = skill
+                            .required_tools
+                            .iter()
+                            .map(|t| {
+                                if missing.contains(&t.as_str()) {
+                                    console::style(format!("{t}（未启用）"))
+                                        .yellow()
+                                        .to_string()
+                                } else {
+                                    t.clone()
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        println!("    Requires: {requires}");
+                    }
                     if !skill.tags.is_empty() {
                         println!("    Tags:  {}", skill.tags.join(", "));
                     }
+                    if let Some(locked) =
+                        installed_dir(skill, &skills_path).and_then(|dir| lock.skills.get(&dir))
+                    {
+                        println!("    Source: {}", describe_source(locked));
+                    }
                 }
             }
             println!();
             Ok(())
         }
-        crate::SkillCommands::Install { source } => {
+        crate::SkillCommands::Install { source, force } => {
             println!("正在从以下位置安装技能: {source}");
 
             let skills_path = skills_dir(workspace_dir);
             std::fs::create_dir_all(&skills_path)?;
 
             if source.starts_with("https://") || source.starts_with("http://") {
-                // Git clone
-                let output = std::process::Command::new("git")
-                    .args(["clone", "--depth", "1", &source])
-                    .current_dir(&skills_path)
-                    .output()?;
-
-                if output.status.success() {
-                    println!("  {} 技能安装成功！", console::style("✓").green().bold());
-                    println!("  重启 `jarvis channel start` 以激活。");
-                } else {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    anyhow::bail!("Git clone 失败: {stderr}");
+                // Git clone into a hidden staging directory, then validate
+                let (url, git_ref) = lock::split_ref(&source);
+                let dir_name = repo_dir_name(url)?;
+                let dest = skills_path.join(&dir_name);
+                let staging = skills_path.join(format!(".installing-{dir_name}"));
+                if staging.exists() {
+                    std::fs::remove_dir_all(&staging)?;
                 }
+                let commit = match lock::clone(url, git_ref, &staging) {
+                    Ok(commit) => commit,
+                    Err(e) => {
+                        let _ = std::fs::remove_dir_all(&staging);
+                        return Err(e.context("Git clone 失败"));
+                    }
+                };
+
+                let skill = match prepare_install(&staging, &dest, &skills_path, &available, force)
+                {
+                    Ok(skill) => skill,
+                    Err(e) => {
+                        let _ = std::fs::remove_dir_all(&staging);
+                        return Err(e);
+                    }
+                };
+                std::fs::rename(&staging, &dest)?;
+                record_install(
+                    &skills_path,
+                    &dir_name,
+                    LockedSkill::git(url, git_ref, commit.clone(), &skill.version),
+                )?;
+                println!(
+                    "  {} 技能安装成功！（{}{}）",
+                    console::style("✓").green().bold(),
+                    git_ref.map(|r| format!("{r} @ ")).unwrap_or_default(),
+                    lock::short(&commit)
+                );
+                println!("  重启 `jarvis channel start` 以激活。");
             } else {
                 // Local path — symlink or copy
                 let src = PathBuf::from(&source);
@@ -517,6 +1200,11 @@ pub fn handle_command(command: crate::SkillCommands, workspace_dir: &Path) -> Re
                 }
                 let name = src.file_name().unwrap_or_default();
                 let dest = skills_path.join(name);
+                let skill = prepare_install(&src, &dest, &skills_path, &available, force)?;
+                let locked = LockedSkill::local(
+                    &src.canonicalize().unwrap_or_else(|_| src.clone()),
+                    &skill.version,
+                );
 
                 #[cfg(unix)]
                 {
@@ -573,10 +1261,14 @@ pub fn handle_command(command: crate::SkillCommands, workspace_dir: &Path) -> Re
                         dest.display()
                     );
                 }
+                record_install(&skills_path, &name.to_string_lossy(), locked)?;
             }
 
             Ok(())
         }
+        crate::SkillCommands::Update { name, yes, force } => {
+            update_skills(&skills_dir(workspace_dir), name.as_deref(), yes, force)
+        }
         crate::SkillCommands::Remove { name } => {
             // 拒绝路径遍历攻击
             if name.contains("..") || name.contains('/') || name.contains('\\') {
@@ -600,6 +1292,7 @@ pub fn handle_command(command: crate::SkillCommands, workspace_dir: &Path) -> Re
             }
 
             std::fs::remove_dir_all(&skill_path)?;
+            SkillsLock::load(&skills_dir(workspace_dir))?.save(&skills_dir(workspace_dir))?;
             println!(
                 "  {} 技能「{}」已移除。",
                 console::style("✓").green().bold(),
@@ -688,8 +1381,10 @@ command = "echo hello"
             version: "1.0.0".to_string(),
             author: None,
             tags: vec![],
+            required_tools: vec![],
             tools: vec![],
             prompts: vec!["Do the thing.".to_string()],
+            entrypoint: None,
             location: None,
         }];
         let prompt = skills_to_prompt(&skills);
@@ -872,6 +1567,7 @@ description = "Bare minimum"
             version: "1.0.0".to_string(),
             author: None,
             tags: vec![],
+            required_tools: vec![],
             tools: vec![SkillTool {
                 name: "get_weather".to_string(),
                 description: "Fetch forecast".to_string(),
@@ -880,6 +1576,7 @@ description = "Bare minimum"
                 args: HashMap::new(),
             }],
             prompts: vec![],
+            entrypoint: None,
             location: None,
         }];
         let prompt = skills_to_prompt(&skills);
@@ -914,6 +1611,378 @@ description = "Bare minimum"
         assert_eq!(skills.len(), 1);
         assert_eq!(skills[0].name, "from-toml"); // TOML takes priority
     }
+
+    #[test]
+    fn md_front_matter_sets_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let skill_dir = dir.path().join("skills").join("dir-name");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            "---\nname: reviewer\nversion: \"1.2.0\"\ndescription: Reviews diffs\n\
+             required_tools: [git, shell]\ntags:\n  - code\n  - review\n---\n\
+             # Reviewer\nRead the diff first.\n",
+        )
+        .unwrap();
+
+        let skills = load_skills(dir.path());
+        assert_eq!(skills.len(), 1);
+        let s = &skills[0];
+        assert_eq!(s.name, "reviewer");
+        assert_eq!(s.version, "1.2.0");
+        assert_eq!(s.description, "Reviews diffs");
+        assert_eq!(s.required_tools, vec!["git", "shell"]);
+        assert_eq!(s.tags, vec!["code", "review"]);
+        assert!(s.prompts[0].starts_with("# Reviewer"));
+        assert!(!s.prompts[0].contains("required_tools"));
+    }
+
+    #[test]
+    fn invalid_skills_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let skills_dir = dir.path().join("skills");
+        let write = |name: &str, file: &str, content: &str| {
+            let skill_dir = skills_dir.join(name);
+            fs::create_dir_all(&skill_dir).unwrap();
+            fs::write(skill_dir.join(file), content).unwrap();
+        };
+        write("good", "SKILL.md", "# Good\nDoes things.\n");
+        write("no-version", "SKILL.md", "---\nversion: \"\"\n---\nBody\n");
+        write("unterminated", "SKILL.md", "---\nname: x\n# Body\n");
+        write(
+            "bad-kind",
+            "SKILL.toml",
+            "[skill]\nname = \"bad-kind\"\ndescription = \"x\"\n\n\
+             [[tools]]\nname = \"t\"\ndescription = \"t\"\nkind = \"ftp\"\ncommand = \"x\"\n",
+        );
+        write(
+            "escape",
+            "SKILL.toml",
+            "[skill]\nname = \"escape\"\ndescription = \"x\"\nentry = \"../secret.md\"\n",
+        );
+
+        let skills = load_skills(dir.path());
+        let names: Vec<&str> = skills.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["good"]);
+
+        let err = load_skill_dir(&skills_dir.join("bad-kind")).unwrap_err();
+        assert!(format!("{err:#}").contains("ftp"), "{err:#}");
+    }
+
+    #[test]
+    fn toml_entry_file_becomes_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        let skill_dir = dir.path().join("skills").join("entry");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.toml"),
+            "[skill]\nname = \"entry\"\ndescription = \"x\"\n\
+             required_tools = [\"shell\"]\nentry = \"INSTRUCTIONS.md\"\n",
+        )
+        .unwrap();
+        fs::write(skill_dir.join("INSTRUCTIONS.md"), "Always run tests.").unwrap();
+
+        let skill = load_skill_dir(&skill_dir).unwrap();
+        assert_eq!(skill.prompts, vec!["Always run tests."]);
+        assert_eq!(skill.required_tools, vec!["shell"]);
+    }
+
+    #[test]
+    fn entrypoint_manifests_are_validated_at_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let load = |name: &str, entrypoint: &str| {
+            let skill_dir = dir.path().join(name);
+            fs::create_dir_all(&skill_dir).unwrap();
+            fs::write(skill_dir.join("run.py"), "print('hi')\n").unwrap();
+            fs::write(
+                skill_dir.join("SKILL.toml"),
+                format!("[skill]\nname = \"{name}\"\ndescription = \"x\"\n\n{entrypoint}"),
+            )
+            .unwrap();
+            load_skill_dir(&skill_dir).map_err(|e| format!("{e:#}"))
+        };
+
+        let skill = load(
+            "Weather Now",
+            "[entrypoint]\nscript = \"run.py\"\ninterpreter = \"python3\"\n\n\
+             [[entrypoint.args]]\nname = \"city\"\nrequired = true\n",
+        )
+        .unwrap();
+        let entrypoint = skill.entrypoint.as_ref().unwrap();
+        assert_eq!(entrypoint.args[0].kind, "string");
+        assert_eq!(skill.tool_name(), "skill_weather_now");
+        assert!(skills_to_prompt(&[skill]).contains("`skill_weather_now` tool"));
+
+        for (name, entrypoint, error) in [
+            (
+                "missing",
+                "[entrypoint]\nscript = \"gone.py\"\ninterpreter = \"python3\"\n",
+                "gone.py 不存在",
+            ),
+            (
+                "escape",
+                "[entrypoint]\nscript = \"../run.py\"\ninterpreter = \"python3\"\n",
+                "相对路径",
+            ),
+            (
+                "shell-line",
+                "[entrypoint]\nscript = \"run.py\"\ninterpreter = \"python3; rm -rf /\"\n",
+                "interpreter",
+            ),
+            (
+                "bad-type",
+                "[entrypoint]\nscript = \"run.py\"\ninterpreter = \"python3\"\n\n\
+                 [[entrypoint.args]]\nname = \"n\"\ntype = \"list\"\n",
+                "list",
+            ),
+            (
+                "bad-name",
+                "[entrypoint]\nscript = \"run.py\"\ninterpreter = \"python3\"\n\n\
+                 [[entrypoint.args]]\nname = \"a-b\"\n",
+                "a-b",
+            ),
+            (
+                "duplicate",
+                "[entrypoint]\nscript = \"run.py\"\ninterpreter = \"python3\"\n\n\
+                 [[entrypoint.args]]\nname = \"city\"\n\n[[entrypoint.args]]\nname = \"CITY\"\n",
+                "重复",
+            ),
+        ] {
+            let err = load(name, entrypoint).unwrap_err();
+            assert!(err.contains(error), "{name}: {err}");
+        }
+    }
+
+    #[test]
+    fn missing_tools_ignores_available_and_own_tools() {
+        let skill = Skill {
+            name: "s".into(),
+            description: "d".into(),
+            version: "1.0.0".into(),
+            author: None,
+            tags: vec![],
+            required_tools: vec!["shell".into(), "git".into(), "deploy".into()],
+            tools: vec![SkillTool {
+                name: "deploy".into(),
+                description: "Deploy".into(),
+                kind: "shell".into(),
+                command: "make deploy".into(),
+                args: HashMap::new(),
+            }],
+            prompts: vec![],
+            entrypoint: None,
+            location: None,
+        };
+        assert_eq!(skill.missing_tools(&["shell", "file_read"]), vec!["git"]);
+    }
+
+    #[test]
+    fn install_rejects_duplicate_names_unless_forced() {
+        let dir = tempfile::tempdir().unwrap();
+        let skills_path = dir.path().join("skills");
+        let installed = skills_path.join("old-dir");
+        fs::create_dir_all(&installed).unwrap();
+        fs::write(
+            installed.join("SKILL.md"),
+            "---\nname: weather\n---\nOld forecast.\n",
+        )
+        .unwrap();
+
+        let src = dir.path().join("new-weather");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(
+            src.join("SKILL.md"),
+            "---\nname: weather\nversion: 2.0.0\n---\nNew forecast.\n",
+        )
+        .unwrap();
+        let dest = skills_path.join("new-weather");
+
+        let err = prepare_install(&src, &dest, &skills_path, &["shell"], false).unwrap_err();
+        assert!(err.to_string().contains("--force"), "{err}");
+        assert!(installed.exists());
+
+        let skill = prepare_install(&src, &dest, &skills_path, &["shell"], true).unwrap();
+        assert_eq!(skill.version, "2.0.0");
+        assert!(!installed.exists());
+
+        let broken = dir.path().join("broken");
+        fs::create_dir_all(&broken).unwrap();
+        assert!(prepare_install(&broken, &dest, &skills_path, &[], true).is_err());
+    }
+
+    #[test]
+    fn install_validates_and_records_the_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            workspace_dir: dir.path().join("workspace"),
+            ..Config::default()
+        };
+        let src = dir.path().join("weather");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(
+            src.join("SKILL.md"),
+            "---\nname: weather\nversion: 1.4.0\ndescription: Forecasts\n---\nBody\n",
+        )
+        .unwrap();
+
+        let install = |force| crate::SkillCommands::Install {
+            source: src.to_string_lossy().into_owned(),
+            force,
+        };
+        handle_command(install(false), &config).unwrap();
+        let skills_path = skills_dir(&config.workspace_dir);
+        let lock = SkillsLock::load(&skills_path).unwrap();
+        assert_eq!(lock.skills["weather"].version.as_deref(), Some("1.4.0"));
+        assert!(lock.skills["weather"].local);
+        assert_eq!(load_skills_from_directory(&skills_path).len(), 1);
+
+        let err = handle_command(install(false), &config).unwrap_err();
+        assert!(err.to_string().contains("--force"), "{err}");
+    }
+
+    #[test]
+    fn install_rejects_malformed_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        let skills_path = dir.path().join("skills");
+        let src = dir.path().join("bad");
+        fs::create_dir_all(&src).unwrap();
+        let dest = skills_path.join("bad");
+        let manifest = |version: &str, second_tool: &str| {
+            format!(
+                "[skill]\nname = \"bad\"\ndescription = \"x\"\nversion = \"{version}\"\n\n\
+                 [[tools]]\nname = \"fetch\"\ndescription = \"t\"\nkind = \"shell\"\ncommand = \"x\"\n\n\
+                 [[tools]]\nname = \"{second_tool}\"\ndescription = \"t\"\nkind = \"shell\"\ncommand = \"y\"\n"
+            )
+        };
+
+        for (content, message) in [
+            (manifest("one", "other"), "version「one」无效"),
+            (manifest("1.2.3.4", "other"), "version「1.2.3.4」无效"),
+            (manifest("1.0.0", "fetch"), "工具 fetch 重复定义"),
+            ("[skill]\nname = \"bad\"\n".to_string(), "missing field"),
+        ] {
+            fs::write(src.join("SKILL.toml"), content).unwrap();
+            let err = prepare_install(&src, &dest, &skills_path, &[], false).unwrap_err();
+            assert!(format!("{err:#}").contains(message), "{err:#}");
+            assert!(format!("{err:#}").starts_with("技能校验失败"), "{err:#}");
+        }
+        assert!(!dest.exists());
+
+        fs::write(src.join("SKILL.toml"), manifest("2.0.0-beta.1", "other")).unwrap();
+        assert!(prepare_install(&src, &dest, &skills_path, &[], false).is_ok());
+    }
+
+    #[test]
+    fn versions_compare_numerically() {
+        use std::cmp::Ordering;
+        assert_eq!(compare_versions("1.10.0", "1.9.3"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("1.2", "1.2.0"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("1.2.0-rc.1", "1.3"), Some(Ordering::Less));
+        assert_eq!(compare_versions("latest", "1.0.0"), None);
+        assert_eq!(version_key("v1.0"), None);
+    }
+
+    #[test]
+    fn update_compares_versions_and_keeps_local_edits_unless_forced() {
+        let git = |dir: &Path, args: &[&str]| {
+            let status = Command::new("git")
+                .args(args)
+                .current_dir(dir)
+                .status()
+                .unwrap();
+            assert!(status.success(), "git {args:?}");
+        };
+        let publish = |dir: &Path, version: &str| {
+            fs::write(
+                dir.join("SKILL.md"),
+                format!("---\nname: weather\nversion: {version}\ndescription: Forecasts\n---\nv{version}\n"),
+            )
+            .unwrap();
+            git(dir, &["add", "."]);
+            git(dir, &["commit", "-q", "-m", version]);
+        };
+
+        let tmp = tempfile::tempdir().unwrap();
+        let remote = tmp.path().join("remote");
+        fs::create_dir(&remote).unwrap();
+        for args in [
+            &["init", "-q", "-b", "main"][..],
+            &["config", "user.name", "Tester"],
+            &["config", "user.email", "tester@example.com"],
+            &["config", "commit.gpgsign", "false"],
+        ] {
+            git(&remote, args);
+        }
+        publish(&remote, "1.0.0");
+        let url = remote.to_string_lossy().into_owned();
+
+        let skills_path = tmp.path().join("skills");
+        let installed = skills_path.join("weather");
+        let commit = lock::clone(&url, None, &installed).unwrap();
+        record_install(
+            &skills_path,
+            "weather",
+            LockedSkill::git(&url, None, commit.clone(), "1.0.0"),
+        )
+        .unwrap();
+        let locked = |field: fn(&LockedSkill) -> Option<String>| {
+            field(&SkillsLock::load(&skills_path).unwrap().skills["weather"])
+        };
+
+        publish(&remote, "1.1.0");
+        fs::write(
+            installed.join("SKILL.md"),
+            "---\nname: weather\n---\nMine\n",
+        )
+        .unwrap();
+        update_skills(&skills_path, Some("weather"), true, false).unwrap();
+        assert!(fs::read_to_string(installed.join("SKILL.md"))
+            .unwrap()
+            .contains("Mine"));
+        assert_eq!(locked(|l| l.commit.clone()), Some(commit.clone()));
+
+        update_skills(&skills_path, Some("weather"), true, true).unwrap();
+        assert_eq!(load_skill_dir(&installed).unwrap().version, "1.1.0");
+        assert_eq!(locked(|l| l.version.clone()).as_deref(), Some("1.1.0"));
+        assert_ne!(locked(|l| l.commit.clone()), Some(commit));
+        assert!(lock::local_changes(&installed).unwrap().is_empty());
+
+        assert!(update_skills(&skills_path, Some("missing"), true, false).is_err());
+    }
+
+    #[test]
+    fn installed_dir_is_the_lockfile_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let skills_path = dir.path().join("skills");
+        let skill_dir = skills_path.join("weather-skill");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            "---\nname: weather\n---\nBody\n",
+        )
+        .unwrap();
+
+        let skill = load_skill_dir(&skill_dir).unwrap();
+        assert_eq!(
+            installed_dir(&skill, &skills_path).as_deref(),
+            Some("weather-skill")
+        );
+        assert_eq!(installed_dir(&skill, dir.path()), None);
+    }
+
+    #[test]
+    fn repo_dir_name_from_url() {
+        assert_eq!(
+            repo_dir_name("https://github.com/acme/weather-skill.git").unwrap(),
+            "weather-skill"
+        );
+        assert_eq!(
+            repo_dir_name("https://github.com/acme/weather/").unwrap(),
+            "weather"
+        );
+        assert!(repo_dir_name("https://").is_err());
+        assert!(repo_dir_name("https://github.com").is_err());
+    }
 }
 
 #[cfg(test)]
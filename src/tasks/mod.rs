@@ -0,0 +1,582 @@
+use crate::config::Config;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+/// How long a writer waits for another process (daemon or CLI) holding the
+/// database lock before giving up
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+    Open,
+    Done,
+}
+
+impl TaskStatus {
+    fn parse(raw: &str) -> Result<Self> {
+        match raw {
+            "open" => Ok(Self::Open),
+            "done" => Ok(Self::Done),
+            other => anyhow::bail!("任务数据库中的状态无效: {other}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Task {
+    pub id: i64,
+    pub title: String,
+    pub status: TaskStatus,
+    pub due: Option<DateTime<Utc>>,
+    /// Where the task was added from: the audit origin of the agent's
+    /// policy (`cli`, `tui`, `gateway`, `cron`) or `cli` for `jarvis tasks`
+    pub created_from: String,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl Task {
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        self.status == TaskStatus::Open && self.due.is_some_and(|due| due < now)
+    }
+
+    /// `#3 Renew passport (due 2026-10-01 09:00)` in local time.
+    pub fn summary(&self) -> String {
+        let mut line = format!("#{} {}", self.id, self.title);
+        if let Some(due) = self.due {
+            let _ = write!(line, " (due {})", format_local(due));
+        }
+        line
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn handle_command(command: crate::TaskCommands, config: &Config) -> Result<()> {
+    let workspace_dir = &config.workspace_dir;
+    match command {
+        crate::TaskCommands::List { all } => {
+            let tasks = list_tasks(workspace_dir, all)?;
+            if tasks.is_empty() {
+                println!(
+                    "{}",
+                    if all {
+                        "暂无任务。"
+                    } else {
+                        "暂无未完成的任务。"
+                    }
+                );
+                println!("\n用法:");
+                println!("  jarvis tasks add '续签护照' --due 2026-11-01");
+                return Ok(());
+            }
+
+            let now = Utc::now();
+            println!("📋 任务 ({}):", tasks.len());
+            for task in tasks {
+                let mark = match task.status {
+                    TaskStatus::Done => "✅",
+                    TaskStatus::Open if task.is_overdue(now) => "⏰",
+                    TaskStatus::Open => "⬜",
+                };
+                println!("{mark} {} | 来源={}", task.summary(), task.created_from);
+                if let Some(notes) = &task.notes {
+                    println!("    备注: {notes}");
+                }
+            }
+            Ok(())
+        }
+        crate::TaskCommands::Add { title, due, notes } => {
+            let due = due.as_deref().map(parse_due).transpose()?;
+            let task = add_task(workspace_dir, &title, due, notes.as_deref(), "cli")?;
+            println!("✅ 已添加任务 {}", task.summary());
+            Ok(())
+        }
+        crate::TaskCommands::Done { id } => {
+            let task = complete_task(workspace_dir, id)?;
+            println!("✅ 已完成任务 {}", task.summary());
+            Ok(())
+        }
+        crate::TaskCommands::Rm { id } => {
+            remove_task(workspace_dir, id)?;
+            println!("✅ 已删除任务 #{id}");
+            Ok(())
+        }
+    }
+}
+
+/// Parse a due time: RFC 3339, `YYYY-MM-DD HH:MM` in local time, or a bare
+/// `YYYY-MM-DD`, which is due by the end of that local day.
+pub fn parse_due(raw: &str) -> Result<DateTime<Utc>> {
+    let raw = raw.trim();
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(parsed.with_timezone(&Utc));
+    }
+    let local = if let Ok(datetime) = NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M") {
+        datetime
+    } else if let Ok(date) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        date.and_time(NaiveTime::from_hms_opt(23, 59, 59).unwrap_or_default())
+    } else {
+        anyhow::bail!("无效的截止时间「{raw}」（支持 YYYY-MM-DD、YYYY-MM-DD HH:MM 或 RFC 3339）");
+    };
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|due| due.with_timezone(&Utc))
+        .with_context(|| format!("本地时区中不存在该时间: {raw}"))
+}
+
+pub fn add_task(
+    workspace_dir: &Path,
+    title: &str,
+    due: Option<DateTime<Utc>>,
+    notes: Option<&str>,
+    created_from: &str,
+) -> Result<Task> {
+    let title = title.trim();
+    if title.is_empty() {
+        anyhow::bail!("任务标题不能为空");
+    }
+    let notes = notes.map(str::trim).filter(|n| !n.is_empty());
+    let now = Utc::now();
+
+    let id = with_connection(workspace_dir, |conn| {
+        conn.execute(
+            "INSERT INTO tasks (title, status, due, created_from, notes, created_at)
+             VALUES (?1, 'open', ?2, ?3, ?4, ?5)",
+            params![
+                title,
+                due.map(|d| d.to_rfc3339()),
+                created_from,
+                notes,
+                now.to_rfc3339()
+            ],
+        )
+        .context("插入任务失败")?;
+        Ok(conn.last_insert_rowid())
+    })?;
+
+    Ok(Task {
+        id,
+        title: title.to_string(),
+        status: TaskStatus::Open,
+        due,
+        created_from: created_from.to_string(),
+        notes: notes.map(str::to_string),
+        created_at: now,
+        completed_at: None,
+    })
+}
+
+/// Open tasks by due time (undated last), or every task with `include_done`.
+pub fn list_tasks(workspace_dir: &Path, include_done: bool) -> Result<Vec<Task>> {
+    let filter = if include_done {
+        ""
+    } else {
+        "WHERE status = 'open'"
+    };
+    query_tasks(
+        workspace_dir,
+        &format!(
+            "SELECT {COLUMNS} FROM tasks {filter}
+             ORDER BY status = 'done', due IS NULL, due ASC, id ASC"
+        ),
+        &[],
+    )
+}
+
+/// Open tasks whose due time has passed.
+pub fn overdue_tasks(workspace_dir: &Path, now: DateTime<Utc>) -> Result<Vec<Task>> {
+    query_tasks(
+        workspace_dir,
+        &format!(
+            "SELECT {COLUMNS} FROM tasks
+             WHERE status = 'open' AND due IS NOT NULL AND due < ?1
+             ORDER BY due ASC, id ASC"
+        ),
+        &[&now.to_rfc3339()],
+    )
+}
+
+/// The heartbeat prompt reminding the agent of overdue tasks, if any.
+pub fn overdue_prompt(tasks: &[Task]) -> Option<String> {
+    if tasks.is_empty() {
+        return None;
+    }
+    let noun = if tasks.len() == 1 { "task" } else { "tasks" };
+    let mut prompt = format!("You have {} overdue {noun}:\n", tasks.len());
+    for task in tasks {
+        let _ = writeln!(prompt, "- {}", task.summary());
+    }
+    prompt.push_str(
+        "Remind the user about them, or mark any that are already finished with task_complete.",
+    );
+    Some(prompt)
+}
+
+/// Mark a task done. Completing an already-done task keeps its original
+/// completion time.
+pub fn complete_task(workspace_dir: &Path, id: i64) -> Result<Task> {
+    let now = Utc::now();
+    with_connection(workspace_dir, |conn| {
+        conn.execute(
+            "UPDATE tasks SET status = 'done', completed_at = ?1
+             WHERE id = ?2 AND status = 'open'",
+            params![now.to_rfc3339(), id],
+        )
+        .context("更新任务状态失败")?;
+        conn.query_row(
+            &format!("SELECT {COLUMNS} FROM tasks WHERE id = ?1"),
+            params![id],
+            read_row,
+        )
+        .optional()?
+        .with_context(|| format!("任务 #{id} 未找到"))?
+    })
+}
+
+pub fn remove_task(workspace_dir: &Path, id: i64) -> Result<()> {
+    let changed = with_connection(workspace_dir, |conn| {
+        conn.execute("DELETE FROM tasks WHERE id = ?1", params![id])
+            .context("删除任务失败")
+    })?;
+    if changed == 0 {
+        anyhow::bail!("任务 #{id} 未找到");
+    }
+    Ok(())
+}
+
+/// Delete tasks completed more than `days` ago, returning their summaries.
+/// With `dry_run` nothing is deleted. Does not create the database.
+pub fn prune_completed(workspace_dir: &Path, days: u32, dry_run: bool) -> Result<Vec<String>> {
+    if days == 0 || !db_path(workspace_dir).exists() {
+        return Ok(Vec::new());
+    }
+    let cutoff = (Utc::now() - chrono::Duration::days(i64::from(days))).to_rfc3339();
+    let filter = "status = 'done' AND completed_at < ?1";
+
+    let pruned: Vec<String> = query_tasks(
+        workspace_dir,
+        &format!("SELECT {COLUMNS} FROM tasks WHERE {filter} ORDER BY id ASC"),
+        &[&cutoff],
+    )?
+    .iter()
+    .map(Task::summary)
+    .collect();
+    if !dry_run && !pruned.is_empty() {
+        with_connection(workspace_dir, |conn| {
+            conn.execute(
+                &format!("DELETE FROM tasks WHERE {filter}"),
+                params![cutoff],
+            )
+            .context("清理已完成任务失败")
+        })?;
+    }
+    Ok(pruned)
+}
+
+const COLUMNS: &str = "id, title, status, due, created_from, notes, created_at, completed_at";
+
+fn query_tasks(
+    workspace_dir: &Path,
+    sql: &str,
+    params: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<Task>> {
+    with_connection(workspace_dir, |conn| {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params, read_row)?;
+        rows.map(|row| row?).collect()
+    })
+}
+
+/// One row in [`COLUMNS`] order. Timestamp errors are kept for the caller,
+/// as the closure can only return rusqlite errors.
+fn read_row(row: &Row<'_>) -> rusqlite::Result<Result<Task>> {
+    let status: String = row.get(2)?;
+    let due: Option<String> = row.get(3)?;
+    let created_at: String = row.get(6)?;
+    let completed_at: Option<String> = row.get(7)?;
+    let (id, title, created_from, notes) = (row.get(0)?, row.get(1)?, row.get(4)?, row.get(5)?);
+    Ok((|| {
+        Ok(Task {
+            id,
+            title,
+            status: TaskStatus::parse(&status)?,
+            due: due.as_deref().map(parse_rfc3339).transpose()?,
+            created_from,
+            notes,
+            created_at: parse_rfc3339(&created_at)?,
+            completed_at: completed_at.as_deref().map(parse_rfc3339).transpose()?,
+        })
+    })())
+}
+
+fn parse_rfc3339(raw: &str) -> Result<DateTime<Utc>> {
+    let parsed = DateTime::parse_from_rfc3339(raw)
+        .with_context(|| format!("任务数据库中的 RFC3339 时间戳无效: {raw}"))?;
+    Ok(parsed.with_timezone(&Utc))
+}
+
+fn format_local(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+fn db_path(workspace_dir: &Path) -> std::path::PathBuf {
+    workspace_dir.join("tasks").join("tasks.db")
+}
+
+fn with_connection<T>(workspace_dir: &Path, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+    let db_path = db_path(workspace_dir);
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("创建任务目录失败: {}", parent.display()))?;
+    }
+
+    let conn = Connection::open(&db_path)
+        .with_context(|| format!("打开任务数据库失败: {}", db_path.display()))?;
+    // The daemon's tools and `jarvis tasks` may write at the same time
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .context("设置任务数据库锁等待时间失败")?;
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tasks (
+            id           INTEGER PRIMARY KEY AUTOINCREMENT,
+            title        TEXT NOT NULL,
+            status       TEXT NOT NULL DEFAULT 'open',
+            due          TEXT,
+            created_from TEXT NOT NULL,
+            notes        TEXT,
+            created_at   TEXT NOT NULL,
+            completed_at TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_tasks_status_due ON tasks(status, due);",
+    )
+    .context("初始化任务表结构失败")?;
+
+    f(&conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+    use tempfile::TempDir;
+
+    fn set_completed_at(workspace: &Path, id: i64, at: DateTime<Utc>) {
+        with_connection(workspace, |conn| {
+            conn.execute(
+                "UPDATE tasks SET completed_at = ?1 WHERE id = ?2",
+                params![at.to_rfc3339(), id],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn add_list_complete_and_remove() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path();
+        let due = parse_due("2030-01-02 09:30").unwrap();
+
+        let first = add_task(
+            workspace,
+            " Renew passport ",
+            Some(due),
+            Some(" form DS-82 "),
+            "cli",
+        )
+        .unwrap();
+        let second = add_task(workspace, "Call mom", None, Some("  "), "gateway").unwrap();
+        assert_eq!(first.title, "Renew passport");
+        assert_eq!(first.notes.as_deref(), Some("form DS-82"));
+        assert_eq!(second.notes, None);
+
+        let open = list_tasks(workspace, false).unwrap();
+        assert_eq!(
+            open.iter().map(|t| t.id).collect::<Vec<_>>(),
+            [first.id, second.id]
+        );
+        assert_eq!(open[0].due, Some(due));
+        assert_eq!(open[1].created_from, "gateway");
+
+        let done = complete_task(workspace, first.id).unwrap();
+        assert_eq!(done.status, TaskStatus::Done);
+        assert!(done.completed_at.is_some());
+        assert_eq!(list_tasks(workspace, false).unwrap().len(), 1);
+        assert_eq!(list_tasks(workspace, true).unwrap().len(), 2);
+
+        remove_task(workspace, second.id).unwrap();
+        assert!(remove_task(workspace, second.id)
+            .unwrap_err()
+            .to_string()
+            .contains("未找到"));
+        assert!(complete_task(workspace, 99).is_err());
+        assert!(add_task(workspace, "  ", None, None, "cli").is_err());
+    }
+
+    #[test]
+    fn completing_twice_keeps_the_first_completion_time() {
+        let tmp = TempDir::new().unwrap();
+        let task = add_task(tmp.path(), "Water plants", None, None, "cli").unwrap();
+        let first = complete_task(tmp.path(), task.id).unwrap().completed_at;
+        let again = complete_task(tmp.path(), task.id).unwrap().completed_at;
+        assert_eq!(first, again);
+    }
+
+    #[test]
+    fn parse_due_formats() {
+        let rfc = parse_due("2030-01-02T03:04:05Z").unwrap();
+        assert_eq!(rfc.to_rfc3339(), "2030-01-02T03:04:05+00:00");
+
+        let end_of_day = parse_due("2030-01-02").unwrap().with_timezone(&Local);
+        assert_eq!(
+            end_of_day.format("%Y-%m-%d %H:%M:%S").to_string(),
+            "2030-01-02 23:59:59"
+        );
+
+        let err = parse_due("next tuesday").unwrap_err();
+        assert!(err.to_string().contains("无效的截止时间"));
+    }
+
+    #[test]
+    fn overdue_tasks_and_prompt() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path();
+        let now = Utc::now();
+        let late = add_task(
+            workspace,
+            "File taxes",
+            Some(now - ChronoDuration::days(2)),
+            None,
+            "cli",
+        )
+        .unwrap();
+        let later = add_task(
+            workspace,
+            "Pay rent",
+            Some(now - ChronoDuration::hours(1)),
+            None,
+            "cli",
+        )
+        .unwrap();
+        add_task(
+            workspace,
+            "Book flights",
+            Some(now + ChronoDuration::days(1)),
+            None,
+            "cli",
+        )
+        .unwrap();
+        add_task(workspace, "Someday", None, None, "cli").unwrap();
+        let finished = add_task(
+            workspace,
+            "Old chore",
+            Some(now - ChronoDuration::days(5)),
+            None,
+            "cli",
+        )
+        .unwrap();
+        complete_task(workspace, finished.id).unwrap();
+
+        let overdue = overdue_tasks(workspace, now).unwrap();
+        assert_eq!(
+            overdue.iter().map(|t| t.id).collect::<Vec<_>>(),
+            [late.id, later.id]
+        );
+        let prompt = overdue_prompt(&overdue).unwrap();
+        assert!(
+            prompt.starts_with("You have 2 overdue tasks:\n"),
+            "{prompt}"
+        );
+        assert!(prompt.contains(&format!("- #{} File taxes (due ", late.id)));
+        assert!(prompt.contains("task_complete"));
+        assert!(overdue_prompt(&[]).is_none());
+    }
+
+    #[test]
+    fn prune_completed_removes_only_old_done_tasks() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path();
+        assert!(prune_completed(workspace, 30, false).unwrap().is_empty());
+        assert!(
+            !db_path(workspace).exists(),
+            "pruning must not create the store"
+        );
+
+        let old = add_task(workspace, "Old", None, None, "cli").unwrap();
+        let recent = add_task(workspace, "Recent", None, None, "cli").unwrap();
+        let open = add_task(workspace, "Open", None, None, "cli").unwrap();
+        complete_task(workspace, old.id).unwrap();
+        complete_task(workspace, recent.id).unwrap();
+        set_completed_at(workspace, old.id, Utc::now() - ChronoDuration::days(40));
+
+        assert_eq!(
+            prune_completed(workspace, 30, true).unwrap(),
+            [format!("#{} Old", old.id)]
+        );
+        assert_eq!(list_tasks(workspace, true).unwrap().len(), 3);
+
+        assert_eq!(prune_completed(workspace, 30, false).unwrap().len(), 1);
+        let left: Vec<i64> = list_tasks(workspace, true)
+            .unwrap()
+            .iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(left, [open.id, recent.id]);
+        assert!(prune_completed(workspace, 0, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn daemon_and_cli_can_write_concurrently() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path().to_path_buf();
+        add_task(&workspace, "Seed", None, None, "cli").unwrap();
+
+        // Separate connections, as the daemon and a `jarvis tasks` process have
+        let writers: Vec<_> = ["daemon", "cli"]
+            .into_iter()
+            .map(|origin| {
+                let workspace = workspace.clone();
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        let task =
+                            add_task(&workspace, &format!("{origin} {i}"), None, None, origin)
+                                .unwrap();
+                        if i % 5 == 0 {
+                            complete_task(&workspace, task.id).unwrap();
+                        }
+                        list_tasks(&workspace, true).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let tasks = list_tasks(&workspace, true).unwrap();
+        assert_eq!(tasks.len(), 51);
+        let mut ids: Vec<i64> = tasks.iter().map(|t| t.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 51);
+        assert_eq!(
+            tasks
+                .iter()
+                .filter(|t| t.status == TaskStatus::Done)
+                .count(),
+            10
+        );
+        assert_eq!(
+            tasks.iter().filter(|t| t.created_from == "daemon").count(),
+            25
+        );
+    }
+}
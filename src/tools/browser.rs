@@ -708,32 +708,7 @@ fn is_private_host(host: &str) -> bool {
 
     // Parse as IP address to catch all representations (decimal, hex, octal, mapped)
     if let Ok(ip) = bare.parse::<std::net::IpAddr>() {
-        return match ip {
-            std::net::IpAddr::V4(v4) => {
-                v4.is_loopback()
-                    || v4.is_private()
-                    || v4.is_link_local()
-                    || v4.is_unspecified()
-                    || v4.is_broadcast()
-            }
-            std::net::IpAddr::V6(v6) => {
-                let segs = v6.segments();
-                v6.is_loopback()
-                    || v6.is_unspecified()
-                    // Unique-local (fc00::/7) — IPv6 equivalent of RFC 1918
-                    || (segs[0] & 0xfe00) == 0xfc00
-                    // Link-local (fe80::/10)
-                    || (segs[0] & 0xffc0) == 0xfe80
-                    // IPv4-mapped addresses (::ffff:127.0.0.1)
-                    || v6.to_ipv4_mapped().is_some_and(|v4| {
-                        v4.is_loopback()
-                            || v4.is_private()
-                            || v4.is_link_local()
-                            || v4.is_unspecified()
-                            || v4.is_broadcast()
-                    })
-            }
-        };
+        return is_private_ip(ip);
     }
 
     // Fallback string patterns for hostnames that look like IPs but don't parse
@@ -747,6 +722,32 @@ fn is_private_host(host: &str) -> bool {
     string_patterns.iter().any(|p| bare.starts_with(p))
 }
 
+/// Loopback, private-network, link-local and unspecified addresses,
+/// including IPv4-mapped IPv6 forms.
+pub(crate) fn is_private_ip(ip: std::net::IpAddr) -> bool {
+    let private_v4 = |v4: std::net::Ipv4Addr| {
+        v4.is_loopback()
+            || v4.is_private()
+            || v4.is_link_local()
+            || v4.is_unspecified()
+            || v4.is_broadcast()
+    };
+    match ip {
+        std::net::IpAddr::V4(v4) => private_v4(v4),
+        std::net::IpAddr::V6(v6) => {
+            let segs = v6.segments();
+            v6.is_loopback()
+                || v6.is_unspecified()
+                // Unique-local (fc00::/7) — IPv6 equivalent of RFC 1918
+                || (segs[0] & 0xfe00) == 0xfc00
+                // Link-local (fe80::/10)
+                || (segs[0] & 0xffc0) == 0xfe80
+                // IPv4-mapped addresses (::ffff:127.0.0.1)
+                || v6.to_ipv4_mapped().is_some_and(private_v4)
+        }
+    }
+}
+
 fn host_matches_allowlist(host: &str, allowed: &[String]) -> bool {
     allowed.iter().any(|pattern| {
         if pattern == "*" {
@@ -0,0 +1,257 @@
+use super::traits::{Tool, ToolResult};
+use crate::config::ClipboardConfig;
+use crate::security::SecurityPolicy;
+use crate::util::truncate_with_ellipsis;
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Text access to a clipboard. A trait so tests never touch the real one.
+trait Backend: Send {
+    fn get(&mut self) -> Result<String, arboard::Error>;
+    fn set(&mut self, text: &str) -> Result<(), arboard::Error>;
+}
+
+impl Backend for arboard::Clipboard {
+    fn get(&mut self) -> Result<String, arboard::Error> {
+        self.get_text()
+    }
+
+    fn set(&mut self, text: &str) -> Result<(), arboard::Error> {
+        self.set_text(text)
+    }
+}
+
+type Opener = Box<dyn Fn() -> Result<Box<dyn Backend>, arboard::Error> + Send + Sync>;
+
+/// Read or set the user's clipboard text (`[tools.clipboard]`).
+///
+/// The system clipboard is opened on first use and kept: on X11/Wayland the
+/// process that set the text serves it, so dropping the handle right after
+/// `set` could lose it.
+pub struct ClipboardTool {
+    security: Arc<SecurityPolicy>,
+    max_chars: usize,
+    open: Opener,
+    backend: Mutex<Option<Box<dyn Backend>>>,
+}
+
+impl ClipboardTool {
+    pub fn new(security: Arc<SecurityPolicy>, config: &ClipboardConfig) -> Self {
+        Self {
+            security,
+            max_chars: config.max_chars,
+            open: Box::new(|| Ok(Box::new(arboard::Clipboard::new()?))),
+            backend: Mutex::new(None),
+        }
+    }
+
+    /// Run `f` on the clipboard, opening it if needed.
+    fn with_backend<T>(
+        &self,
+        f: impl FnOnce(&mut dyn Backend) -> Result<T, arboard::Error>,
+    ) -> Result<T, String> {
+        let mut backend = self.backend.lock().unwrap_or_else(PoisonError::into_inner);
+        if backend.is_none() {
+            *backend = Some((self.open)().map_err(|e| {
+                format!(
+                    "No clipboard available ({e}). Headless sessions and SSH without \
+                     display forwarding have no clipboard; ask the user to paste the text instead."
+                )
+            })?);
+        }
+        f(backend.as_deref_mut().expect("opened above")).map_err(|e| match e {
+            arboard::Error::ContentNotAvailable => "The clipboard holds no text".to_string(),
+            e => format!("Clipboard access failed: {e}"),
+        })
+    }
+}
+
+#[async_trait]
+impl Tool for ClipboardTool {
+    fn name(&self) -> &str {
+        "clipboard"
+    }
+
+    fn description(&self) -> &str {
+        "Read the text the user last copied (action \"get\"), or put text on their clipboard \
+         for them to paste elsewhere (action \"set\")."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["get", "set"],
+                    "description": "get: read the clipboard; set: replace it with `text`"
+                },
+                "text": {
+                    "type": "string",
+                    "description": "Text to place on the clipboard (required for set)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let action = args
+            .get("action")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Missing 'action' parameter"))?;
+
+        let failure = |error: String| ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(error),
+        };
+
+        let result = match action {
+            "get" => self.with_backend(|clipboard| clipboard.get()).map(|text| {
+                if text.is_empty() {
+                    "The clipboard is empty".to_string()
+                } else {
+                    truncate_with_ellipsis(&text, self.max_chars)
+                }
+            }),
+            "set" => {
+                let text = args
+                    .get("text")
+                    .and_then(serde_json::Value::as_str)
+                    .ok_or_else(|| anyhow::anyhow!("Missing 'text' parameter for set"))?;
+                if !self.security.can_act() {
+                    return Ok(failure("Action blocked: autonomy is read-only".into()));
+                }
+                if !self.security.record_action() {
+                    return Ok(failure("Action blocked: rate limit exceeded".into()));
+                }
+                self.with_backend(|clipboard| clipboard.set(text))
+                    .map(|()| {
+                        format!(
+                            "Copied {} characters to the clipboard",
+                            text.chars().count()
+                        )
+                    })
+            }
+            other => Err(format!("Unknown action '{other}' (expected get or set)")),
+        };
+
+        Ok(match result {
+            Ok(output) => ToolResult {
+                success: true,
+                output,
+                error: None,
+            },
+            Err(error) => failure(error),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+
+    /// In-memory clipboard.
+    struct Fake(Arc<Mutex<Option<String>>>);
+
+    impl Backend for Fake {
+        fn get(&mut self) -> Result<String, arboard::Error> {
+            self.0
+                .lock()
+                .unwrap()
+                .clone()
+                .ok_or(arboard::Error::ContentNotAvailable)
+        }
+
+        fn set(&mut self, text: &str) -> Result<(), arboard::Error> {
+            *self.0.lock().unwrap() = Some(text.to_string());
+            Ok(())
+        }
+    }
+
+    fn tool(autonomy: AutonomyLevel, open: Opener) -> ClipboardTool {
+        ClipboardTool {
+            open,
+            ..ClipboardTool::new(
+                Arc::new(SecurityPolicy {
+                    autonomy,
+                    ..SecurityPolicy::default()
+                }),
+                &ClipboardConfig {
+                    enabled: true,
+                    max_chars: 10,
+                },
+            )
+        }
+    }
+
+    fn fake(content: Option<&str>) -> Opener {
+        let content = Arc::new(Mutex::new(content.map(String::from)));
+        Box::new(move || Ok(Box::new(Fake(Arc::clone(&content)))))
+    }
+
+    fn headless() -> Opener {
+        Box::new(|| Err(arboard::Error::ClipboardNotSupported))
+    }
+
+    #[tokio::test]
+    async fn sets_then_gets_text() {
+        let tool = tool(AutonomyLevel::Supervised, fake(None));
+        assert_eq!(tool.name(), "clipboard");
+
+        let result = tool.execute(json!({"action": "get"})).await.unwrap();
+        assert_eq!(result.error.as_deref(), Some("The clipboard holds no text"));
+
+        let result = tool
+            .execute(json!({"action": "set", "text": "hello"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "Copied 5 characters to the clipboard");
+
+        let result = tool.execute(json!({"action": "get"})).await.unwrap();
+        assert_eq!(result.output, "hello");
+
+        tool.execute(json!({"action": "set", "text": "a long clipboard entry"}))
+            .await
+            .unwrap();
+        let result = tool.execute(json!({"action": "get"})).await.unwrap();
+        assert_eq!(result.output, "a long cli...");
+    }
+
+    #[tokio::test]
+    async fn read_only_autonomy_can_read_but_not_set() {
+        let tool = tool(AutonomyLevel::ReadOnly, fake(Some("copied")));
+
+        let result = tool
+            .execute(json!({"action": "set", "text": "x"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("read-only"));
+        assert_eq!(
+            tool.execute(json!({"action": "get"})).await.unwrap().output,
+            "copied"
+        );
+    }
+
+    #[tokio::test]
+    async fn headless_sessions_get_a_clear_error() {
+        let tool = tool(AutonomyLevel::Full, headless());
+        let result = tool.execute(json!({"action": "get"})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().starts_with("No clipboard available"));
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_arguments() {
+        let tool = tool(AutonomyLevel::Full, headless());
+        assert!(tool.execute(json!({})).await.is_err());
+        assert!(tool.execute(json!({"action": "set"})).await.is_err());
+        let result = tool.execute(json!({"action": "clear"})).await.unwrap();
+        assert!(result.error.unwrap().contains("Unknown action"));
+    }
+}
@@ -0,0 +1,576 @@
+use super::shell::failed;
+use super::traits::{Tool, ToolResult};
+use crate::config::FileEditConfig;
+use crate::security::{AutonomyLevel, SecurityPolicy};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Largest file that is edited in place.
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+/// Length of the UTC timestamp in backup names, e.g. `20260301T091500123456Z`.
+const BACKUP_STAMP_LEN: usize = 22;
+
+/// Targeted search/replace edits to an existing workspace file, with a
+/// backup of the original under `state/backups/`
+pub struct FileEditTool {
+    security: Arc<SecurityPolicy>,
+    keep_backups: usize,
+}
+
+impl FileEditTool {
+    pub fn new(security: Arc<SecurityPolicy>, config: &FileEditConfig) -> Self {
+        Self {
+            security,
+            keep_backups: config.keep_backups,
+        }
+    }
+}
+
+/// One replacement: `old` must occur exactly once in the file.
+#[derive(Debug, Default, PartialEq)]
+struct Edit {
+    old: String,
+    new: String,
+}
+
+#[async_trait]
+impl Tool for FileEditTool {
+    fn name(&self) -> &str {
+        "file_edit"
+    }
+
+    fn description(&self) -> &str {
+        "Edit part of an existing file in the workspace: each old_text is replaced by its new_text, and must match exactly once. Prefer this over file_write for changes to existing files"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Relative path to the file within the workspace"
+                },
+                "edits": {
+                    "type": "array",
+                    "description": "Replacements, applied in order",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "old_text": {
+                                "type": "string",
+                                "description": "Exact text to replace, with enough surrounding lines to match once"
+                            },
+                            "new_text": {
+                                "type": "string",
+                                "description": "Replacement text"
+                            }
+                        },
+                        "required": ["old_text", "new_text"]
+                    }
+                },
+                "diff": {
+                    "type": "string",
+                    "description": "Unified diff hunks (@@ ... @@) instead of edits; each hunk's context and removed lines must match once"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let path = args
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'path' parameter"))?;
+        let (edits, label) = match (args.get("edits"), args.get("diff")) {
+            (Some(edits), None) => (parse_edits(edits)?, "Edit"),
+            (None, Some(diff)) => {
+                let diff = diff
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("'diff' must be a string"))?;
+                match parse_diff(diff) {
+                    Ok(hunks) => (hunks, "Hunk"),
+                    Err(error) => return Ok(failed(error)),
+                }
+            }
+            _ => anyhow::bail!("Provide exactly one of 'edits' or 'diff'"),
+        };
+        if edits.is_empty() {
+            return Ok(failed("No edits given".into()));
+        }
+
+        if self.security.autonomy == AutonomyLevel::ReadOnly {
+            return Ok(failed(
+                "file_edit is not allowed in read-only autonomy mode".into(),
+            ));
+        }
+        if let Some(reason) = self.security.path_violation(path) {
+            return Ok(failed(format!(
+                "Path not allowed by security policy: {path} ({reason})"
+            )));
+        }
+
+        let full_path = self.security.workspace_dir.join(path);
+        let resolved = match tokio::fs::canonicalize(&full_path).await {
+            Ok(p) => p,
+            Err(e) => return Ok(failed(format!("Failed to resolve file path: {e}"))),
+        };
+        if !self.security.is_resolved_path_allowed(&resolved) {
+            return Ok(failed(self.security.escape_error(path, &resolved)));
+        }
+        match tokio::fs::metadata(&resolved).await {
+            Ok(meta) if !meta.is_file() => {
+                return Ok(failed(format!("Not a file: {path}")));
+            }
+            Ok(meta) if meta.len() > MAX_FILE_SIZE => {
+                return Ok(failed(format!(
+                    "File too large: {} bytes (limit: {MAX_FILE_SIZE} bytes)",
+                    meta.len()
+                )));
+            }
+            Ok(_) => {}
+            Err(e) => return Ok(failed(format!("Failed to read file metadata: {e}"))),
+        }
+
+        let original = match tokio::fs::read_to_string(&resolved).await {
+            Ok(content) => content,
+            Err(e) => return Ok(failed(format!("Failed to read file: {e}"))),
+        };
+        let (edited, changes) = match apply(&original, &edits, label) {
+            Ok(result) => result,
+            Err(error) => return Ok(failed(format!("{error}. The file was not changed."))),
+        };
+
+        let backup = match self.backup(&resolved, &original).await {
+            Ok(backup) => backup,
+            Err(e) => {
+                return Ok(failed(format!(
+                    "Failed to back up {path}, so it was not changed: {e}"
+                )));
+            }
+        };
+        if let Err(e) = tokio::fs::write(&resolved, &edited).await {
+            return Ok(failed(format!("Failed to write file: {e}")));
+        }
+
+        let mut output = format!(
+            "Edited {path}: {} change{}",
+            changes.len(),
+            if changes.len() == 1 { "" } else { "s" }
+        );
+        if let Some(backup) = backup {
+            let _ = write!(output, " (original saved to {})", backup.display());
+        }
+        for change in changes {
+            output.push_str("\n- ");
+            output.push_str(&change);
+        }
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+        })
+    }
+}
+
+impl FileEditTool {
+    /// Copy `original` to `state/backups/<path>.<timestamp>.bak`, keeping
+    /// the newest `keep_backups` copies of the file. Returns the backup's
+    /// path relative to the workspace.
+    async fn backup(&self, resolved: &Path, original: &str) -> std::io::Result<Option<PathBuf>> {
+        if self.keep_backups == 0 {
+            return Ok(None);
+        }
+        let root = tokio::fs::canonicalize(&self.security.workspace_dir).await?;
+        let relative = resolved
+            .strip_prefix(&root)
+            .map_err(|_| std::io::Error::other("the file is outside the workspace"))?;
+        let file_name = relative
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let backups = Path::new("state").join("backups");
+        let dir = match relative.parent() {
+            Some(parent) => backups.join(parent),
+            None => backups,
+        };
+        tokio::fs::create_dir_all(root.join(&dir)).await?;
+
+        let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%6fZ");
+        let backup = dir.join(format!("{file_name}.{stamp}.bak"));
+        tokio::fs::write(root.join(&backup), original).await?;
+
+        let mut existing = Vec::new();
+        let mut entries = tokio::fs::read_dir(root.join(&dir)).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if is_backup_of(&name, &file_name) {
+                existing.push(entry.path());
+            }
+        }
+        existing.sort();
+        let excess = existing.len().saturating_sub(self.keep_backups);
+        for old in &existing[..excess] {
+            tokio::fs::remove_file(old).await?;
+        }
+        Ok(Some(backup))
+    }
+}
+
+/// Whether `name` is `<file_name>.<timestamp>.bak`.
+fn is_backup_of(name: &str, file_name: &str) -> bool {
+    name.strip_prefix(file_name)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .and_then(|rest| rest.strip_suffix(".bak"))
+        .is_some_and(|stamp| {
+            stamp.len() == BACKUP_STAMP_LEN && stamp.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+fn parse_edits(edits: &serde_json::Value) -> anyhow::Result<Vec<Edit>> {
+    let edits = edits
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("'edits' must be an array"))?;
+    edits
+        .iter()
+        .enumerate()
+        .map(|(i, edit)| {
+            let field = |name: &str| {
+                edit.get(name)
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow::anyhow!("Edit {} is missing '{name}'", i + 1))
+            };
+            Ok(Edit {
+                old: field("old_text")?,
+                new: field("new_text")?,
+            })
+        })
+        .collect()
+}
+
+/// Turn unified-diff hunks into edits: context and `-` lines make up the
+/// old text, context and `+` lines the new text. Headers and the line
+/// numbers in `@@` markers are ignored.
+fn parse_diff(diff: &str) -> Result<Vec<Edit>, String> {
+    #[derive(Clone, Copy)]
+    enum Side {
+        Both,
+        Old,
+        New,
+    }
+
+    let mut hunks: Vec<Edit> = Vec::new();
+    let mut last = Side::Both;
+    for line in diff.lines() {
+        if line.starts_with("@@") {
+            hunks.push(Edit::default());
+            continue;
+        }
+        // Headers (`---`, `+++`, `diff --git`) come before the first hunk
+        let Some(hunk) = hunks.last_mut() else {
+            continue;
+        };
+        let (side, text) = match line.chars().next() {
+            Some(' ') => (Side::Both, &line[1..]),
+            None => (Side::Both, ""),
+            Some('-') => (Side::Old, &line[1..]),
+            Some('+') => (Side::New, &line[1..]),
+            Some('\\') => {
+                // "\ No newline at end of file" applies to the line before
+                if matches!(last, Side::Both | Side::Old) {
+                    hunk.old.pop();
+                }
+                if matches!(last, Side::Both | Side::New) {
+                    hunk.new.pop();
+                }
+                continue;
+            }
+            Some(_) => return Err(format!("Unexpected line in diff hunk: {line}")),
+        };
+        if matches!(side, Side::Both | Side::Old) {
+            hunk.old.push_str(text);
+            hunk.old.push('\n');
+        }
+        if matches!(side, Side::Both | Side::New) {
+            hunk.new.push_str(text);
+            hunk.new.push('\n');
+        }
+        last = side;
+    }
+    if hunks.is_empty() {
+        return Err("The diff has no @@ hunks".into());
+    }
+    Ok(hunks)
+}
+
+/// Apply `edits` in order, each to the result of the previous one. Returns
+/// the new content and a line-numbered summary of each change, or the
+/// first edit that does not match exactly once.
+fn apply(content: &str, edits: &[Edit], label: &str) -> Result<(String, Vec<String>), String> {
+    let mut text = content.to_string();
+    let mut changes = Vec::new();
+    for (i, edit) in edits.iter().enumerate() {
+        let n = i + 1;
+        if edit.old.is_empty() {
+            return Err(format!(
+                "{label} {n}: old_text is empty; use file_write to create a file"
+            ));
+        }
+        let mut matches = text.match_indices(&edit.old).map(|(pos, _)| pos);
+        let Some(pos) = matches.next() else {
+            return Err(format!(
+                "{label} {n}: old_text was not found; read the file again and copy the text exactly"
+            ));
+        };
+        let extra = matches.count();
+        if extra > 0 {
+            return Err(format!(
+                "{label} {n}: old_text matches {} times; include more surrounding lines so it matches exactly once",
+                extra + 1
+            ));
+        }
+
+        let start = text[..pos].matches('\n').count() + 1;
+        let old = lines(start, line_count(&edit.old));
+        changes.push(match line_count(&edit.new) {
+            0 => format!("{old} removed"),
+            count => format!("{old} → {}", lines(start, count)),
+        });
+        text.replace_range(pos..pos + edit.old.len(), &edit.new);
+    }
+    Ok((text, changes))
+}
+
+fn line_count(text: &str) -> usize {
+    text.lines().count()
+}
+
+fn lines(start: usize, count: usize) -> String {
+    match count {
+        0 | 1 => format!("line {start}"),
+        _ => format!("lines {start}-{}", start + count - 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tool(workspace: &Path, keep_backups: usize) -> FileEditTool {
+        FileEditTool::new(
+            Arc::new(SecurityPolicy {
+                workspace_dir: workspace.to_path_buf(),
+                ..SecurityPolicy::default()
+            }),
+            &FileEditConfig { keep_backups },
+        )
+    }
+
+    fn backups(workspace: &Path, dir: &str) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(workspace.join("state/backups").join(dir))
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|e| e.file_name().to_string_lossy().into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn apply_replaces_unique_matches_and_reports_lines() {
+        let content = "fn a() {\n    1\n}\n\nfn b() {\n    2\n}\n";
+        let edits = [
+            Edit {
+                old: "fn b() {\n    2\n}\n".into(),
+                new: "fn b() {\n    let x = 2;\n    x\n}\n".into(),
+            },
+            Edit {
+                old: "    1\n".into(),
+                new: String::new(),
+            },
+        ];
+        let (text, changes) = apply(content, &edits, "Edit").unwrap();
+        assert_eq!(text, "fn a() {\n}\n\nfn b() {\n    let x = 2;\n    x\n}\n");
+        assert_eq!(changes, vec!["lines 5-7 → lines 5-8", "line 2 removed"]);
+    }
+
+    #[test]
+    fn apply_requires_exactly_one_match() {
+        let content = "x = 1\ny = 1\n";
+        let edit = |old: &str| Edit {
+            old: old.into(),
+            new: "z".into(),
+        };
+        let err = apply(content, &[edit("= 1")], "Edit").unwrap_err();
+        assert!(err.starts_with("Edit 1: old_text matches 2 times"), "{err}");
+        let err = apply(content, &[edit("x = 1"), edit("w")], "Hunk").unwrap_err();
+        assert!(err.starts_with("Hunk 2: old_text was not found"), "{err}");
+        assert!(apply(content, &[edit("")], "Edit").is_err());
+    }
+
+    #[test]
+    fn diff_hunks_become_edits() {
+        let diff = "--- a/lib.rs\n+++ b/lib.rs\n@@ -1,3 +1,3 @@\n fn a() {\n-    1\n+    2\n }\n\
+                    @@ -9,2 +9,2 @@\n-last\n\\ No newline at end of file\n+final\n";
+        let hunks = parse_diff(diff).unwrap();
+        assert_eq!(
+            hunks,
+            vec![
+                Edit {
+                    old: "fn a() {\n    1\n}\n".into(),
+                    new: "fn a() {\n    2\n}\n".into(),
+                },
+                Edit {
+                    old: "last".into(),
+                    new: "final\n".into(),
+                },
+            ]
+        );
+        assert!(parse_diff("just text").is_err());
+        assert!(parse_diff("@@ -1 +1 @@\n*odd\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn edits_file_and_keeps_a_backup() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir(tmp.path().join("src")).unwrap();
+        std::fs::write(tmp.path().join("src/lib.rs"), "one\ntwo\nthree\n").unwrap();
+        let tool = tool(tmp.path(), 10);
+
+        let result = tool
+            .execute(json!({
+                "path": "src/lib.rs",
+                "edits": [{"old_text": "two\n", "new_text": "2\n"}]
+            }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.starts_with(
+            "Edited src/lib.rs: 1 change (original saved to state/backups/src/lib.rs."
+        ));
+        assert!(result.output.ends_with("\n- line 2 → line 2"));
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("src/lib.rs")).unwrap(),
+            "one\n2\nthree\n"
+        );
+        let saved = backups(tmp.path(), "src");
+        assert_eq!(saved.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("state/backups/src").join(&saved[0])).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+
+        let result = tool
+            .execute(json!({
+                "path": "src/lib.rs",
+                "diff": "@@ -1,2 +1,2 @@\n-one\n+1\n 2\n"
+            }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("src/lib.rs")).unwrap(),
+            "1\n2\nthree\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_edits_leave_the_file_alone() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("notes.txt"), "a\na\n").unwrap();
+        let tool = tool(tmp.path(), 10);
+
+        let result = tool
+            .execute(json!({
+                "path": "notes.txt",
+                "edits": [{"old_text": "a", "new_text": "b"}]
+            }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            result.error.unwrap(),
+            "Edit 1: old_text matches 2 times; include more surrounding lines so it matches exactly once. The file was not changed."
+        );
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("notes.txt")).unwrap(),
+            "a\na\n"
+        );
+        assert!(backups(tmp.path(), "").is_empty());
+    }
+
+    #[tokio::test]
+    async fn old_backups_are_pruned() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("n.txt"), "0").unwrap();
+        // Another file whose name shares the prefix is left alone
+        std::fs::create_dir_all(tmp.path().join("state/backups")).unwrap();
+        let other = "n.txt.old.20200101T000000000000Z.bak";
+        std::fs::write(tmp.path().join("state/backups").join(other), "x").unwrap();
+        let tool = tool(tmp.path(), 2);
+
+        for i in 0..4 {
+            let result = tool
+                .execute(json!({
+                    "path": "n.txt",
+                    "edits": [{"old_text": i.to_string(), "new_text": (i + 1).to_string()}]
+                }))
+                .await
+                .unwrap();
+            assert!(result.success, "{:?}", result.error);
+        }
+        let saved = backups(tmp.path(), "");
+        assert_eq!(saved.len(), 3, "{saved:?}");
+        assert!(saved.contains(&other.to_string()));
+        let newest = saved.iter().rfind(|n| is_backup_of(n, "n.txt")).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("state/backups").join(newest)).unwrap(),
+            "3"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn honors_workspace_path_restrictions() {
+        let tmp = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), "s").unwrap();
+        std::os::unix::fs::symlink(outside.path(), tmp.path().join("link")).unwrap();
+        let tool = tool(tmp.path(), 10);
+
+        for path in ["../secret.txt", "/etc/hosts", "link/secret.txt"] {
+            let result = tool
+                .execute(json!({
+                    "path": path,
+                    "edits": [{"old_text": "s", "new_text": "t"}]
+                }))
+                .await
+                .unwrap();
+            assert!(!result.success, "{path} was edited");
+        }
+        assert_eq!(
+            std::fs::read_to_string(outside.path().join("secret.txt")).unwrap(),
+            "s"
+        );
+    }
+
+    #[tokio::test]
+    async fn needs_exactly_one_kind_of_edit() {
+        let tmp = TempDir::new().unwrap();
+        let tool = tool(tmp.path(), 10);
+        assert!(tool.execute(json!({"path": "a.txt"})).await.is_err());
+        assert!(tool
+            .execute(json!({"path": "a.txt", "edits": [], "diff": "@@\n"}))
+            .await
+            .is_err());
+    }
+}
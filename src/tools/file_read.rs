@@ -46,11 +46,13 @@ impl Tool for FileReadTool {
             .ok_or_else(|| anyhow::anyhow!("Missing 'path' parameter"))?;
 
         // Security check: validate path is within workspace
-        if !self.security.is_path_allowed(path) {
+        if let Some(reason) = self.security.path_violation(path) {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
-                error: Some(format!("Path not allowed by security policy: {path}")),
+                error: Some(format!(
+                    "Path not allowed by security policy: {path} ({reason})"
+                )),
             });
         }
 
@@ -72,10 +74,7 @@ impl Tool for FileReadTool {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
-                error: Some(format!(
-                    "Resolved path escapes workspace: {}",
-                    resolved_path.display()
-                )),
+                error: Some(self.security.escape_error(path, &resolved_path)),
             });
         }
 
@@ -280,6 +279,40 @@ mod tests {
         let _ = tokio::fs::remove_dir_all(&root).await;
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn file_read_blocks_symlinked_directory_escape() {
+        use std::os::unix::fs::symlink;
+
+        let root = std::env::temp_dir().join("jarvis_test_file_read_symlink_dir");
+        let workspace = root.join("workspace");
+        let outside = root.join("outside");
+
+        let _ = tokio::fs::remove_dir_all(&root).await;
+        tokio::fs::create_dir_all(workspace.join("docs"))
+            .await
+            .unwrap();
+        tokio::fs::create_dir_all(&outside).await.unwrap();
+        tokio::fs::write(outside.join("passwd"), "root:x:0:0")
+            .await
+            .unwrap();
+
+        symlink(&outside, workspace.join("docs").join("etc")).unwrap();
+
+        let tool = FileReadTool::new(test_security(workspace.clone()));
+        let result = tool
+            .execute(json!({"path": "docs/etc/passwd"}))
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        let error = result.error.unwrap_or_default();
+        assert!(error.contains("escapes workspace"), "{error}");
+        assert!(error.contains("through the symlink `docs/etc`"), "{error}");
+
+        let _ = tokio::fs::remove_dir_all(&root).await;
+    }
+
     #[tokio::test]
     async fn file_read_rejects_oversized_file() {
         let dir = std::env::temp_dir().join("jarvis_test_file_read_large");
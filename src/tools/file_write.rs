@@ -1,4 +1,5 @@
 use super::traits::{Tool, ToolResult};
+use crate::security::policy::canonicalize_lenient;
 use crate::security::SecurityPolicy;
 use async_trait::async_trait;
 use serde_json::json;
@@ -54,11 +55,13 @@ impl Tool for FileWriteTool {
             .ok_or_else(|| anyhow::anyhow!("Missing 'content' parameter"))?;
 
         // Security check: validate path is within workspace
-        if !self.security.is_path_allowed(path) {
+        if let Some(reason) = self.security.path_violation(path) {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
-                error: Some(format!("Path not allowed by security policy: {path}")),
+                error: Some(format!(
+                    "Path not allowed by security policy: {path} ({reason})"
+                )),
             });
         }
 
@@ -72,10 +75,30 @@ impl Tool for FileWriteTool {
             });
         };
 
+        // Resolve BEFORE creating anything: directories must not be created
+        // through a symlink that leads out of the workspace.
+        let planned_parent = match canonicalize_lenient(parent) {
+            Ok(p) => p,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("Failed to resolve file path: {e}")),
+                });
+            }
+        };
+        if !self.security.is_resolved_path_allowed(&planned_parent) {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(self.security.escape_error(path, &planned_parent)),
+            });
+        }
+
         // Ensure parent directory exists
         tokio::fs::create_dir_all(parent).await?;
 
-        // Resolve parent AFTER creation to block symlink escapes.
+        // Resolve parent again AFTER creation in case it changed meanwhile.
         let resolved_parent = match tokio::fs::canonicalize(parent).await {
             Ok(p) => p,
             Err(e) => {
@@ -91,10 +114,7 @@ impl Tool for FileWriteTool {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
-                error: Some(format!(
-                    "Resolved path escapes workspace: {}",
-                    resolved_parent.display()
-                )),
+                error: Some(self.security.escape_error(path, &resolved_parent)),
             });
         }
 
@@ -324,4 +344,55 @@ mod tests {
 
         let _ = tokio::fs::remove_dir_all(&root).await;
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn file_write_does_not_create_dirs_through_symlinked_dir() {
+        use std::os::unix::fs::symlink;
+
+        let root = std::env::temp_dir().join("jarvis_test_file_write_symlink_nested");
+        let workspace = root.join("workspace");
+        let outside = root.join("outside");
+
+        let _ = tokio::fs::remove_dir_all(&root).await;
+        tokio::fs::create_dir_all(&workspace).await.unwrap();
+        tokio::fs::create_dir_all(&outside).await.unwrap();
+
+        symlink(&outside, workspace.join("etc_link")).unwrap();
+
+        let tool = FileWriteTool::new(test_security(workspace.clone()));
+        let result = tool
+            .execute(json!({"path": "etc_link/cron.d/nested/job", "content": "bad"}))
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        let error = result.error.unwrap_or_default();
+        assert!(error.contains("escapes workspace"), "{error}");
+        assert!(error.contains("through the symlink `etc_link`"), "{error}");
+        assert!(!outside.join("cron.d").exists());
+
+        let _ = tokio::fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn file_write_blocks_traversal_out_of_home_workspace() {
+        let dir = std::env::temp_dir().join("jarvis_test_file_write_home_traversal");
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+
+        let tool = FileWriteTool::new(test_security(dir.clone()));
+        let result = tool
+            .execute(json!({
+                "path": "~/.jarvis/workspace/../../.ssh/authorized_keys",
+                "content": "ssh-ed25519 AAAA"
+            }))
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert!(result.error.unwrap_or_default().contains("`..`"));
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
 }
@@ -0,0 +1,646 @@
+use super::shell::SAFE_ENV_VARS;
+use super::traits::{Tool, ToolResult};
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Maximum git execution time before kill.
+const GIT_TIMEOUT_SECS: u64 = 60;
+/// Maximum diff size returned to the model (256KB).
+const MAX_OUTPUT_BYTES: usize = 262_144;
+/// Default and maximum number of commits returned by `log`.
+const DEFAULT_LOG_LIMIT: u64 = 10;
+const MAX_LOG_LIMIT: u64 = 100;
+
+/// Structured git operations on the workspace repository
+pub struct GitTool {
+    security: Arc<SecurityPolicy>,
+    allow_write: bool,
+}
+
+impl GitTool {
+    pub fn new(security: Arc<SecurityPolicy>, allow_write: bool) -> Self {
+        Self {
+            security,
+            allow_write,
+        }
+    }
+
+    /// The repository to run in: the workspace, or `path` if the security
+    /// policy allows it.
+    fn repo_dir(&self, path: Option<&str>) -> Result<PathBuf, String> {
+        let Some(path) = path else {
+            return Ok(self.security.workspace_dir.clone());
+        };
+        if let Some(reason) = self.security.path_violation(path) {
+            return Err(format!(
+                "Path not allowed by security policy: {path} ({reason})"
+            ));
+        }
+        let resolved = std::fs::canonicalize(self.security.workspace_dir.join(path))
+            .map_err(|e| format!("Failed to resolve repository path: {e}"))?;
+        if self.security.workspace_only {
+            if !self.security.is_resolved_path_allowed(&resolved) {
+                return Err(self.security.escape_error(path, &resolved));
+            }
+        } else if let Some(reason) = self.security.path_violation(&resolved.to_string_lossy()) {
+            return Err(format!(
+                "Path not allowed by security policy: {path} ({reason})"
+            ));
+        }
+        Ok(resolved)
+    }
+
+    /// Run git in `repo`, returning stdout or the error git reported.
+    async fn git(&self, repo: &Path, args: &[&str]) -> Result<String, String> {
+        let mut cmd = tokio::process::Command::new("git");
+        cmd.args(["--no-pager", "-c", "color.ui=false"])
+            .args(args)
+            .current_dir(repo)
+            .env_clear()
+            // Never wait on a credential prompt nobody can answer
+            .env("GIT_TERMINAL_PROMPT", "0");
+        for var in SAFE_ENV_VARS {
+            if let Ok(val) = std::env::var(var) {
+                cmd.env(var, val);
+            }
+        }
+
+        let output = tokio::time::timeout(Duration::from_secs(GIT_TIMEOUT_SECS), cmd.output())
+            .await
+            .map_err(|_| format!("git timed out after {GIT_TIMEOUT_SECS}s and was killed"))?
+            .map_err(|e| format!("Failed to run git: {e}"))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        if output.status.success() {
+            return Ok(stdout);
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        };
+        Err(format!("git {} failed: {message}", args[0]))
+    }
+
+    async fn run(&self, operation: &str, args: &serde_json::Value) -> Result<String, String> {
+        let repo = self.repo_dir(args.get("path").and_then(|v| v.as_str()))?;
+
+        let mutating = matches!(operation, "add" | "commit" | "push" | "reset")
+            || (operation == "branch" && args.get("name").is_some());
+        if mutating && !self.security.can_act() {
+            return Err(format!(
+                "git {operation} is not allowed in read-only autonomy mode"
+            ));
+        }
+        if matches!(operation, "push" | "reset") && !self.allow_write {
+            return Err(format!(
+                "git {operation} is disabled: set allow_write = true under [git] in config.toml to enable it"
+            ));
+        }
+
+        match operation {
+            "status" => {
+                let raw = self
+                    .git(&repo, &["status", "--porcelain=v1", "--branch"])
+                    .await?;
+                Ok(format_status(&raw))
+            }
+            "diff" => self.diff(&repo, args).await,
+            "log" => self.log(&repo, args).await,
+            "add" => {
+                let paths = string_list(args, "paths")?;
+                if paths.is_empty() {
+                    return Err("git add needs at least one entry in 'paths'".into());
+                }
+                self.check_paths(&paths)?;
+                let mut git_args = vec!["add", "--"];
+                git_args.extend(paths.iter().map(String::as_str));
+                self.git(&repo, &git_args).await?;
+                Ok(format!("Staged: {}", paths.join(", ")))
+            }
+            "commit" => {
+                let message = args
+                    .get("message")
+                    .and_then(|v| v.as_str())
+                    .map(str::trim)
+                    .filter(|m| !m.is_empty())
+                    .ok_or("git commit needs a non-empty 'message'")?;
+                self.git(&repo, &["commit", "-m", message]).await?;
+                Ok(format!("Committed {}", self.head(&repo).await?))
+            }
+            "branch" => self.branch(&repo, args).await,
+            "push" => {
+                let mut git_args = vec!["push"];
+                for key in ["remote", "branch"] {
+                    if let Some(value) = args.get(key).and_then(|v| v.as_str()) {
+                        git_args.push(ref_arg(value, key)?);
+                    }
+                }
+                self.git(&repo, &git_args).await?;
+                Ok("Pushed".to_string())
+            }
+            "reset" => self.reset(&repo, args).await,
+            other => Err(format!(
+                "Unknown git operation: {other} (expected status, diff, log, add, commit, branch, push or reset)"
+            )),
+        }
+    }
+
+    async fn diff(&self, repo: &Path, args: &serde_json::Value) -> Result<String, String> {
+        let paths = string_list(args, "paths")?;
+        self.check_paths(&paths)?;
+        let mut git_args = vec!["diff", "--no-ext-diff"];
+        if args.get("staged").and_then(serde_json::Value::as_bool) == Some(true) {
+            git_args.push("--cached");
+        }
+        git_args.push("--");
+        git_args.extend(paths.iter().map(String::as_str));
+        let diff = self.git(repo, &git_args).await?;
+        Ok(if diff.trim().is_empty() {
+            "No changes".to_string()
+        } else {
+            truncate(diff)
+        })
+    }
+
+    async fn log(&self, repo: &Path, args: &serde_json::Value) -> Result<String, String> {
+        let limit = args
+            .get("limit")
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or(DEFAULT_LOG_LIMIT)
+            .clamp(1, MAX_LOG_LIMIT)
+            .to_string();
+        let raw = self
+            .git(
+                repo,
+                &[
+                    "log",
+                    "-n",
+                    &limit,
+                    "--date=short",
+                    "--pretty=format:%h%x1f%ad%x1f%an%x1f%s",
+                ],
+            )
+            .await?;
+        Ok(format_log(&raw))
+    }
+
+    /// List branches, or create `name` without switching to it.
+    async fn branch(&self, repo: &Path, args: &serde_json::Value) -> Result<String, String> {
+        if let Some(name) = args.get("name").and_then(|v| v.as_str()) {
+            let name = ref_arg(name, "name")?;
+            self.git(repo, &["branch", name]).await?;
+            return Ok(format!("Created branch {name}"));
+        }
+        let raw = self
+            .git(
+                repo,
+                &["branch", "--list", "--format=%(HEAD) %(refname:short)"],
+            )
+            .await?;
+        Ok(if raw.trim().is_empty() {
+            "No branches yet".to_string()
+        } else {
+            raw.trim_end().to_string()
+        })
+    }
+
+    async fn reset(&self, repo: &Path, args: &serde_json::Value) -> Result<String, String> {
+        let mode = match args.get("mode").and_then(|v| v.as_str()).unwrap_or("mixed") {
+            "soft" => "--soft",
+            "mixed" => "--mixed",
+            "hard" => "--hard",
+            other => return Err(format!("Unknown reset mode: {other}")),
+        };
+        let target = ref_arg(
+            args.get("target")
+                .and_then(|v| v.as_str())
+                .unwrap_or("HEAD"),
+            "target",
+        )?;
+        self.git(repo, &["reset", mode, target, "--"]).await?;
+        Ok(format!(
+            "Reset ({}) to {}",
+            &mode[2..],
+            self.head(repo).await?
+        ))
+    }
+
+    /// `abc1234 subject` of the current commit.
+    async fn head(&self, repo: &Path) -> Result<String, String> {
+        self.git(repo, &["log", "-1", "--pretty=format:%h %s"])
+            .await
+    }
+
+    fn check_paths(&self, paths: &[String]) -> Result<(), String> {
+        for path in paths {
+            if let Some(reason) = self.security.path_violation(path) {
+                return Err(format!(
+                    "Path not allowed by security policy: {path} ({reason})"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Tool for GitTool {
+    fn name(&self) -> &str {
+        "git"
+    }
+
+    fn description(&self) -> &str {
+        "Inspect and commit changes in the workspace git repository: status, diff, log, add, commit, branch (push and reset only when enabled in config)"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "enum": ["status", "diff", "log", "add", "commit", "branch", "push", "reset"],
+                    "description": "The git operation to run"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Repository directory relative to the workspace (default: the workspace)"
+                },
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Files for add (required) or diff (optional)"
+                },
+                "staged": {
+                    "type": "boolean",
+                    "description": "diff: show staged changes instead of unstaged ones"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "log: number of commits (default 10, max 100)"
+                },
+                "message": {
+                    "type": "string",
+                    "description": "commit: the commit message"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "branch: create a branch with this name (omit to list branches)"
+                },
+                "remote": {
+                    "type": "string",
+                    "description": "push: remote name"
+                },
+                "branch": {
+                    "type": "string",
+                    "description": "push: branch to push"
+                },
+                "target": {
+                    "type": "string",
+                    "description": "reset: commit to reset to (default HEAD)"
+                },
+                "mode": {
+                    "type": "string",
+                    "enum": ["soft", "mixed", "hard"],
+                    "description": "reset: reset mode (default mixed)"
+                }
+            },
+            "required": ["operation"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let operation = args
+            .get("operation")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'operation' parameter"))?;
+
+        Ok(match self.run(operation, &args).await {
+            Ok(output) => ToolResult {
+                success: true,
+                output,
+                error: None,
+            },
+            Err(error) => ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(error),
+            },
+        })
+    }
+}
+
+fn string_list(args: &serde_json::Value, key: &str) -> Result<Vec<String>, String> {
+    match args.get(key) {
+        None | Some(serde_json::Value::Null) => Ok(Vec::new()),
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .map(ToString::to_string)
+                    .ok_or_else(|| format!("'{key}' must be a list of strings"))
+            })
+            .collect(),
+        Some(_) => Err(format!("'{key}' must be a list of strings")),
+    }
+}
+
+/// A ref-like argument, refused when git would read it as an option.
+fn ref_arg<'a>(value: &'a str, key: &str) -> Result<&'a str, String> {
+    let value = value.trim();
+    if value.is_empty() || value.starts_with('-') {
+        return Err(format!("Invalid '{key}': {value:?}"));
+    }
+    Ok(value)
+}
+
+fn truncate(mut text: String) -> String {
+    if text.len() > MAX_OUTPUT_BYTES {
+        let mut end = MAX_OUTPUT_BYTES;
+        while end > 0 && !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push_str("\n... [diff truncated at 256KB; pass 'paths' to narrow it]");
+    }
+    text
+}
+
+/// Turn `git status --porcelain=v1 --branch` into a short summary.
+fn format_status(raw: &str) -> String {
+    let mut branch = String::new();
+    let (mut staged, mut unstaged, mut untracked, mut conflicts) =
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+
+    for line in raw.lines() {
+        if let Some(header) = line.strip_prefix("## ") {
+            branch = format_branch(header);
+            continue;
+        }
+        if line.len() < 4 {
+            continue;
+        }
+        let (code, path) = line.split_at(3);
+        let mut code = code.chars();
+        let (x, y) = (code.next().unwrap_or(' '), code.next().unwrap_or(' '));
+        match (x, y) {
+            ('?', '?') => untracked.push(path.to_string()),
+            ('!', '!') => {}
+            ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => conflicts.push(path.to_string()),
+            _ => {
+                if x != ' ' {
+                    staged.push(format!("{}: {path}", change_label(x)));
+                }
+                if y != ' ' {
+                    unstaged.push(format!("{}: {path}", change_label(y)));
+                }
+            }
+        }
+    }
+
+    let mut out = branch;
+    if staged.is_empty() && unstaged.is_empty() && untracked.is_empty() && conflicts.is_empty() {
+        out.push_str("\nWorking tree clean");
+        return out;
+    }
+    for (title, entries) in [
+        ("Conflicts", &conflicts),
+        ("Staged", &staged),
+        ("Unstaged", &unstaged),
+        ("Untracked", &untracked),
+    ] {
+        if !entries.is_empty() {
+            let _ = write!(out, "\n{title}:");
+            for entry in entries {
+                let _ = write!(out, "\n  {entry}");
+            }
+        }
+    }
+    out
+}
+
+/// `main...origin/main [ahead 1, behind 2]` → `On branch main (origin/main, ahead 1, behind 2)`
+fn format_branch(header: &str) -> String {
+    if let Some(branch) = header.strip_prefix("No commits yet on ") {
+        return format!("On branch {branch} (no commits yet)");
+    }
+    if header.starts_with("HEAD (no branch)") {
+        return "HEAD detached".to_string();
+    }
+    let (names, tracking) = match header.split_once(" [") {
+        Some((names, rest)) => (names, Some(rest.trim_end_matches(']'))),
+        None => (header, None),
+    };
+    let (local, upstream) = match names.split_once("...") {
+        Some((local, upstream)) => (local, Some(upstream)),
+        None => (names, None),
+    };
+    let details: Vec<&str> = upstream.into_iter().chain(tracking).collect();
+    if details.is_empty() {
+        format!("On branch {local}")
+    } else {
+        format!("On branch {local} ({})", details.join(", "))
+    }
+}
+
+fn change_label(code: char) -> &'static str {
+    match code {
+        'M' => "modified",
+        'A' => "added",
+        'D' => "deleted",
+        'R' => "renamed",
+        'C' => "copied",
+        'T' => "type changed",
+        _ => "changed",
+    }
+}
+
+/// One commit per line: `abc1234 2026-03-04 Alice: subject`.
+fn format_log(raw: &str) -> String {
+    raw.lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\u{1f}');
+            let (hash, date, author, subject) = (
+                fields.next()?,
+                fields.next()?,
+                fields.next()?,
+                fields.next()?,
+            );
+            Some(format!("{hash} {date} {author}: {subject}"))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn init_repo(dir: &Path) {
+        for args in [
+            &["init", "-q", "-b", "main"][..],
+            &["config", "user.name", "Tester"],
+            &["config", "user.email", "tester@example.com"],
+            &["config", "commit.gpgsign", "false"],
+        ] {
+            let status = Command::new("git")
+                .args(args)
+                .current_dir(dir)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+    }
+
+    fn tool(dir: &Path, autonomy: AutonomyLevel, allow_write: bool) -> GitTool {
+        GitTool::new(
+            Arc::new(SecurityPolicy {
+                autonomy,
+                workspace_dir: dir.to_path_buf(),
+                ..SecurityPolicy::default()
+            }),
+            allow_write,
+        )
+    }
+
+    #[test]
+    fn status_is_summarised() {
+        let raw = "## main...origin/main [ahead 1]\nM  src/lib.rs\n M README.md\nMM both.rs\nUU conflict.rs\n?? new.txt\n";
+        assert_eq!(
+            format_status(raw),
+            "On branch main (origin/main, ahead 1)\n\
+             Conflicts:\n  conflict.rs\n\
+             Staged:\n  modified: src/lib.rs\n  modified: both.rs\n\
+             Unstaged:\n  modified: README.md\n  modified: both.rs\n\
+             Untracked:\n  new.txt"
+        );
+        assert_eq!(
+            format_status("## No commits yet on main\n"),
+            "On branch main (no commits yet)\nWorking tree clean"
+        );
+    }
+
+    #[test]
+    fn log_lines_are_readable() {
+        let raw = "abc1234\u{1f}2026-03-04\u{1f}Alice\u{1f}Fix parser\ndef5678\u{1f}2026-03-03\u{1f}Bob\u{1f}Init";
+        assert_eq!(
+            format_log(raw),
+            "abc1234 2026-03-04 Alice: Fix parser\ndef5678 2026-03-03 Bob: Init"
+        );
+    }
+
+    #[tokio::test]
+    async fn add_commit_and_log() {
+        let tmp = TempDir::new().unwrap();
+        init_repo(tmp.path());
+        std::fs::write(tmp.path().join("notes.md"), "hello\n").unwrap();
+        let git = tool(tmp.path(), AutonomyLevel::Supervised, false);
+
+        let status = git.execute(json!({"operation": "status"})).await.unwrap();
+        assert!(status.success);
+        assert!(status.output.contains("Untracked:\n  notes.md"));
+
+        let added = git
+            .execute(json!({"operation": "add", "paths": ["notes.md"]}))
+            .await
+            .unwrap();
+        assert_eq!(added.output, "Staged: notes.md");
+
+        let diff = git
+            .execute(json!({"operation": "diff", "staged": true}))
+            .await
+            .unwrap();
+        assert!(diff.output.contains("+hello"));
+
+        let commit = git
+            .execute(json!({"operation": "commit", "message": "Add notes"}))
+            .await
+            .unwrap();
+        assert!(commit.success, "{:?}", commit.error);
+        assert!(commit.output.ends_with(" Add notes"));
+
+        let log = git.execute(json!({"operation": "log"})).await.unwrap();
+        assert!(log.output.ends_with("Tester: Add notes"));
+
+        let status = git.execute(json!({"operation": "status"})).await.unwrap();
+        assert_eq!(status.output, "On branch main\nWorking tree clean");
+
+        git.execute(json!({"operation": "branch", "name": "feature"}))
+            .await
+            .unwrap();
+        let branches = git.execute(json!({"operation": "branch"})).await.unwrap();
+        assert_eq!(branches.output, "  feature\n* main");
+    }
+
+    #[tokio::test]
+    async fn push_and_reset_need_allow_write() {
+        let tmp = TempDir::new().unwrap();
+        init_repo(tmp.path());
+        let git = tool(tmp.path(), AutonomyLevel::Full, false);
+        for operation in ["push", "reset"] {
+            let result = git
+                .execute(json!({"operation": operation, "mode": "hard"}))
+                .await
+                .unwrap();
+            assert!(!result.success);
+            assert!(result.error.unwrap().contains("allow_write"));
+        }
+    }
+
+    #[tokio::test]
+    async fn readonly_mode_only_inspects() {
+        let tmp = TempDir::new().unwrap();
+        init_repo(tmp.path());
+        std::fs::write(tmp.path().join("a.txt"), "a").unwrap();
+        let git = tool(tmp.path(), AutonomyLevel::ReadOnly, true);
+
+        assert!(
+            git.execute(json!({"operation": "status"}))
+                .await
+                .unwrap()
+                .success
+        );
+        let result = git
+            .execute(json!({"operation": "add", "paths": ["a.txt"]}))
+            .await
+            .unwrap();
+        assert!(result.error.unwrap().contains("read-only"));
+    }
+
+    #[tokio::test]
+    async fn rejects_paths_and_refs_outside_policy() {
+        let tmp = TempDir::new().unwrap();
+        init_repo(tmp.path());
+        let git = tool(tmp.path(), AutonomyLevel::Supervised, true);
+
+        let result = git
+            .execute(json!({"operation": "status", "path": "../elsewhere"}))
+            .await
+            .unwrap();
+        assert!(result.error.unwrap().contains("not allowed"));
+
+        let result = git
+            .execute(json!({"operation": "add", "paths": ["/etc/passwd"]}))
+            .await
+            .unwrap();
+        assert!(result.error.unwrap().contains("not allowed"));
+
+        let result = git
+            .execute(json!({"operation": "branch", "name": "--delete"}))
+            .await
+            .unwrap();
+        assert!(result.error.unwrap().contains("Invalid 'name'"));
+
+        assert!(git.execute(json!({})).await.is_err());
+    }
+}
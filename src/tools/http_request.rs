@@ -0,0 +1,536 @@
+use super::traits::{Tool, ToolResult};
+use crate::config::HttpRequestConfig;
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, Url};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+const USER_AGENT: &str = concat!("jarvis/", env!("CARGO_PKG_VERSION"), " (http_request tool)");
+
+/// Prefix that makes an argument a reference into `[secrets.named]`.
+const SECRET_REF: &str = "secret:";
+
+/// Call a JSON API on one of the hosts in `[tools.http] allowed_hosts`.
+pub struct HttpRequestTool {
+    security: Arc<SecurityPolicy>,
+    allowed_hosts: Vec<String>,
+    allowed_methods: Vec<String>,
+    max_response_bytes: usize,
+    timeout: Duration,
+    secrets: BTreeMap<String, String>,
+}
+
+impl HttpRequestTool {
+    pub fn new(
+        security: Arc<SecurityPolicy>,
+        config: &HttpRequestConfig,
+        secrets: &BTreeMap<String, String>,
+    ) -> Self {
+        Self {
+            security,
+            allowed_hosts: config
+                .allowed_hosts
+                .iter()
+                .map(|h| h.trim().to_ascii_lowercase())
+                .filter(|h| !h.is_empty())
+                .collect(),
+            allowed_methods: config
+                .allowed_methods
+                .iter()
+                .map(|m| m.trim().to_ascii_uppercase())
+                .collect(),
+            max_response_bytes: config.max_response_bytes.max(1),
+            timeout: Duration::from_secs(config.timeout_secs.max(1)),
+            secrets: secrets.clone(),
+        }
+    }
+
+    /// Whether `host` is listed exactly or matches a `*.domain` entry.
+    fn host_allowed(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        self.allowed_hosts.iter().any(|allowed| {
+            allowed
+                .strip_prefix("*.")
+                .map_or(*allowed == host, |domain| {
+                    host.strip_suffix(domain)
+                        .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.'))
+                })
+        })
+    }
+
+    fn check_url(&self, url: &Url) -> Result<(), String> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!(
+                "Only http:// and https:// URLs can be called (got {}:)",
+                url.scheme()
+            ));
+        }
+        let Some(host) = url.host_str() else {
+            return Err(format!("URL has no host: {url}"));
+        };
+        if self.host_allowed(host) {
+            return Ok(());
+        }
+        let allowed = if self.allowed_hosts.is_empty() {
+            "none".to_string()
+        } else {
+            self.allowed_hosts.join(", ")
+        };
+        Err(format!(
+            "Host `{host}` is not allowed by security policy (allowed hosts: {allowed}). \
+            The user can add it to [tools.http] allowed_hosts in config.toml."
+        ))
+    }
+
+    /// `value` with a `secret:<name>` reference replaced by the named secret.
+    /// Returns the resolved value and, for references, the secret itself.
+    fn resolve<'a>(&'a self, value: &'a str) -> Result<(&'a str, Option<&'a str>), String> {
+        let Some(name) = value.strip_prefix(SECRET_REF) else {
+            return Ok((value, None));
+        };
+        match self.secrets.get(name.trim()) {
+            Some(secret) => Ok((secret, Some(secret))),
+            None if self.secrets.is_empty() => Err(format!(
+                "Unknown secret `{name}`: no secrets are configured under [secrets.named]"
+            )),
+            None => Err(format!(
+                "Unknown secret `{name}` (configured: {})",
+                self.secrets.keys().cloned().collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+
+    /// Request headers from the `headers` and `auth` arguments, plus the
+    /// secrets they pulled in (so they can be masked in the response).
+    fn headers<'a>(&'a self, args: &'a Value) -> Result<(HeaderMap, Vec<&'a str>), String> {
+        let mut headers = HeaderMap::new();
+        let mut used = Vec::new();
+        if let Some(given) = args.get("headers").filter(|v| !v.is_null()) {
+            let Some(given) = given.as_object() else {
+                return Err("'headers' must be an object of header names to strings".into());
+            };
+            for (name, value) in given {
+                let Some(value) = value.as_str() else {
+                    return Err(format!("Header `{name}` must be a string"));
+                };
+                let (value, secret) = self.resolve(value)?;
+                used.extend(secret);
+                let name = HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|_| format!("Invalid header name `{name}`"))?;
+                let value = HeaderValue::from_str(value)
+                    .map_err(|_| format!("Invalid value for header `{name}`"))?;
+                headers.insert(name, value);
+            }
+        }
+        if let Some(auth) = args.get("auth").and_then(Value::as_str) {
+            if !auth.starts_with(SECRET_REF) {
+                return Err(
+                    "'auth' must name a configured secret as secret:<name>; credentials are never passed inline"
+                        .into(),
+                );
+            }
+            let (token, secret) = self.resolve(auth)?;
+            used.extend(secret);
+            let value = HeaderValue::from_str(&format!("Bearer {token}")).map_err(|_| {
+                "The secret named in 'auth' is not a valid header value".to_string()
+            })?;
+            headers.insert(reqwest::header::AUTHORIZATION, value);
+        }
+        Ok((headers, used))
+    }
+
+    /// Read at most `max_response_bytes` of the body; the flag says whether
+    /// more was left unread.
+    async fn read_body(&self, mut response: reqwest::Response) -> Result<(Vec<u8>, bool), String> {
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Failed to read the response: {e}"))?
+        {
+            let room = self.max_response_bytes - body.len();
+            if chunk.len() > room {
+                body.extend_from_slice(&chunk[..room]);
+                return Ok((body, true));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok((body, false))
+    }
+}
+
+/// The body pretty-printed if it is JSON, otherwise as text, with the
+/// `secrets` masked and cut to `max_chars`.
+fn render_body(body: &[u8], cut_short: bool, secrets: &[&str], max_chars: usize) -> String {
+    let mut text = serde_json::from_slice::<Value>(body)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+        .unwrap_or_else(|| String::from_utf8_lossy(body).trim().to_string());
+    for secret in secrets.iter().filter(|s| !s.is_empty()) {
+        text = text.replace(secret, &crate::security::redact::mask(secret));
+    }
+    let total = text.chars().count();
+    if total > max_chars {
+        text = text.chars().take(max_chars).collect();
+        let _ = write!(
+            text,
+            "\n\n[Truncated: showing {max_chars} of {total} characters.]"
+        );
+    } else if cut_short {
+        text.push_str("\n\n[Truncated: the response was larger than the configured limit.]");
+    }
+    text
+}
+
+#[async_trait]
+impl Tool for HttpRequestTool {
+    fn name(&self) -> &str {
+        "http_request"
+    }
+
+    fn description(&self) -> &str {
+        "Call an HTTP JSON API on an allowed host (e.g. a Home Assistant REST endpoint) and return the response. \
+        Put credentials in 'auth' or header values as secret:<name>; they are filled in from the user's \
+        configured secrets and never shown to you."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "method": {
+                    "type": "string",
+                    "description": "HTTP method (default GET)",
+                    "enum": self.allowed_methods
+                },
+                "url": {
+                    "type": "string",
+                    "description": "The http:// or https:// URL; its host must be in the allowlist"
+                },
+                "headers": {
+                    "type": "object",
+                    "description": "Extra request headers; a value of secret:<name> is replaced by that secret",
+                    "additionalProperties": { "type": "string" }
+                },
+                "body": {
+                    "description": "JSON request body"
+                },
+                "auth": {
+                    "type": "string",
+                    "description": "secret:<name> to send as an `Authorization: Bearer` header"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        let raw_url = args
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'url' parameter"))?;
+        let method = args
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or("GET")
+            .trim()
+            .to_ascii_uppercase();
+
+        let failure = |error: String| ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(error),
+        };
+
+        if !self.security.record_action() {
+            return Ok(failure("Action blocked: rate limit exceeded".into()));
+        }
+
+        if !self.allowed_methods.contains(&method) {
+            return Ok(failure(format!(
+                "Method {method} is not allowed by security policy (allowed: {}). \
+                The user can change [tools.http] allowed_methods in config.toml.",
+                self.allowed_methods.join(", ")
+            )));
+        }
+        let Ok(method) = Method::from_bytes(method.as_bytes()) else {
+            return Ok(failure(format!("Invalid HTTP method `{method}`")));
+        };
+        let url = match Url::parse(raw_url.trim()) {
+            Ok(url) => url,
+            Err(e) => return Ok(failure(format!("Invalid URL `{raw_url}`: {e}"))),
+        };
+        if let Err(e) = self.check_url(&url) {
+            return Ok(failure(e));
+        }
+        let (headers, secrets) = match self.headers(&args) {
+            Ok(resolved) => resolved,
+            Err(e) => return Ok(failure(e)),
+        };
+
+        // Redirects are not followed: the target may not be on the allowlist
+        let client = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(self.timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+        let mut request = client
+            .request(method.clone(), url.clone())
+            .header(reqwest::header::ACCEPT, "application/json")
+            .headers(headers);
+        if let Some(body) = args.get("body").filter(|v| !v.is_null()) {
+            request = request.json(body);
+        }
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => return Ok(failure(format!("{method} {url} failed: {e}"))),
+        };
+
+        let status = response.status();
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let (body, cut_short) = match self.read_body(response).await {
+            Ok(read) => read,
+            Err(e) => return Ok(failure(e)),
+        };
+        let body = render_body(&body, cut_short, &secrets, self.max_response_bytes);
+
+        if status.is_success() {
+            return Ok(ToolResult {
+                success: true,
+                output: format!("HTTP {status}\n\n{body}"),
+                error: None,
+            });
+        }
+        let mut error = format!("{method} {url} returned HTTP {status}");
+        if let Some(location) = location {
+            let _ = write!(error, " (redirect to {location}, not followed)");
+        }
+        if !body.is_empty() {
+            let _ = write!(error, "\n\n{body}");
+        }
+        Ok(failure(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::oneshot;
+
+    fn tool(config: &HttpRequestConfig) -> HttpRequestTool {
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Full,
+            ..SecurityPolicy::default()
+        });
+        let secrets = BTreeMap::from([("ha_token".to_string(), "ha-long-lived-token".to_string())]);
+        HttpRequestTool::new(security, config, &secrets)
+    }
+
+    fn local_config() -> HttpRequestConfig {
+        HttpRequestConfig {
+            enabled: true,
+            allowed_hosts: vec!["127.0.0.1".into()],
+            ..HttpRequestConfig::default()
+        }
+    }
+
+    /// Answer one request on a loopback port with `response`, handing back
+    /// the raw request that was received.
+    async fn serve(response: String) -> (String, oneshot::Receiver<String>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .find_map(|l| {
+                            l.to_ascii_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|v| v.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if body.len() >= length {
+                        let _ = tx.send(text);
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+            let _ = stream.write_all(response.as_bytes()).await;
+            let _ = stream.shutdown().await;
+        });
+        (format!("http://{addr}"), rx)
+    }
+
+    fn reply(status: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    #[test]
+    fn allowlist_matches_exact_hosts_and_subdomain_wildcards() {
+        let tool = tool(&HttpRequestConfig {
+            allowed_hosts: vec!["HomeAssistant.local".into(), "*.example.com".into()],
+            ..HttpRequestConfig::default()
+        });
+        assert!(tool.host_allowed("homeassistant.local"));
+        assert!(tool.host_allowed("api.example.com"));
+        assert!(tool.host_allowed("a.b.example.com"));
+        assert!(!tool.host_allowed("example.com"));
+        assert!(!tool.host_allowed("evilexample.com"));
+        assert!(!tool.host_allowed("homeassistant.local.evil.net"));
+    }
+
+    #[tokio::test]
+    async fn refuses_hosts_and_methods_off_the_allowlist() {
+        let tool = tool(&local_config());
+        let result = tool
+            .execute(json!({"url": "https://api.github.com/user"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(
+            error.contains("`api.github.com` is not allowed by security policy"),
+            "{error}"
+        );
+        assert!(error.contains("allowed hosts: 127.0.0.1"), "{error}");
+
+        let result = tool
+            .execute(json!({"method": "delete", "url": "http://127.0.0.1/x"}))
+            .await
+            .unwrap();
+        assert!(result
+            .error
+            .unwrap()
+            .starts_with("Method DELETE is not allowed by security policy"));
+    }
+
+    #[tokio::test]
+    async fn injects_named_secrets_and_pretty_prints_json() {
+        let (base, received) = serve(reply(
+            "200 OK",
+            r#"{"entity_id":"light.kitchen","state":"on","echo":"ha-long-lived-token"}"#,
+        ))
+        .await;
+        let tool = tool(&local_config());
+        let result = tool
+            .execute(json!({
+                "method": "POST",
+                "url": format!("{base}/api/services/light/turn_on"),
+                "auth": "secret:ha_token",
+                "headers": {"X-Trace": "abc"},
+                "body": {"entity_id": "light.kitchen"}
+            }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(
+            result
+                .output
+                .starts_with("HTTP 200 OK\n\n{\n  \"echo\": \"****\",\n  \"entity_id\""),
+            "{}",
+            result.output
+        );
+        // Echoed secrets are masked before the model sees them
+        assert!(
+            !result.output.contains("ha-long-lived-token"),
+            "{}",
+            result.output
+        );
+
+        let request = received.await.unwrap();
+        assert!(
+            request.starts_with("POST /api/services/light/turn_on"),
+            "{request}"
+        );
+        let lower = request.to_ascii_lowercase();
+        assert!(
+            lower.contains("authorization: bearer ha-long-lived-token"),
+            "{request}"
+        );
+        assert!(lower.contains("x-trace: abc"), "{request}");
+        assert!(
+            request.ends_with(r#"{"entity_id":"light.kitchen"}"#),
+            "{request}"
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_error_statuses_with_the_body() {
+        let (base, _received) =
+            serve(reply("404 Not Found", r#"{"message":"Entity not found."}"#)).await;
+        let result = tool(&local_config())
+            .execute(json!({"url": format!("{base}/api/states/light.nope")}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(error.contains("returned HTTP 404 Not Found"), "{error}");
+        assert!(
+            error.contains("\"message\": \"Entity not found.\""),
+            "{error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_and_inline_credentials_are_refused() {
+        let tool = tool(&local_config());
+        let result = tool
+            .execute(json!({"url": "http://127.0.0.1/", "auth": "secret:nope"}))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.error.unwrap(),
+            "Unknown secret `nope` (configured: ha_token)"
+        );
+        let result = tool
+            .execute(json!({"url": "http://127.0.0.1/", "auth": "plain-token"}))
+            .await
+            .unwrap();
+        assert!(result
+            .error
+            .unwrap()
+            .starts_with("'auth' must name a configured secret"));
+    }
+
+    #[test]
+    fn long_responses_are_truncated() {
+        let body = serde_json::to_vec(&json!({"items": vec!["x"; 100]})).unwrap();
+        let text = render_body(&body, false, &[], 50);
+        assert!(text.starts_with("{\n  \"items\": ["), "{text}");
+        assert!(
+            text.ends_with("[Truncated: showing 50 of 919 characters.]"),
+            "{text}"
+        );
+
+        let text = render_body(b"{\"partial\": ", true, &[], 1000);
+        assert_eq!(
+            text,
+            "{\"partial\":\n\n[Truncated: the response was larger than the configured limit.]"
+        );
+    }
+}
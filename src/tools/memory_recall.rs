@@ -23,7 +23,7 @@ impl Tool for MemoryRecallTool {
     }
 
     fn description(&self) -> &str {
-        "Search long-term memory for relevant facts, preferences, or context. Returns scored results ranked by relevance."
+        "Search long-term memory for relevant facts, preferences, or context. Returns scored results ranked by relevance. Pass tags to only search memories carrying all of them."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -37,6 +37,11 @@ impl Tool for MemoryRecallTool {
                 "limit": {
                     "type": "integer",
                     "description": "Max results to return (default: 5)"
+                },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Only return memories with all of these tags; with an empty query, lists the latest tagged memories"
                 }
             },
             "required": ["query"]
@@ -44,10 +49,12 @@ impl Tool for MemoryRecallTool {
     }
 
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
-        let query = args
-            .get("query")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("Missing 'query' parameter"))?;
+        let tags = super::memory_store::string_array(args.get("tags"));
+        let query = match args.get("query").and_then(|v| v.as_str()) {
+            Some(query) => query,
+            None if !tags.is_empty() => "",
+            None => anyhow::bail!("Missing 'query' parameter"),
+        };
 
         #[allow(clippy::cast_possible_truncation)]
         let limit = args
@@ -55,7 +62,7 @@ impl Tool for MemoryRecallTool {
             .and_then(serde_json::Value::as_u64)
             .map_or(5, |v| v as usize);
 
-        match self.memory.recall(query, limit).await {
+        match self.memory.recall(query, limit, &tags).await {
             Ok(entries) if entries.is_empty() => Ok(ToolResult {
                 success: true,
                 output: "No memories found matching that query.".into(),
@@ -67,9 +74,13 @@ impl Tool for MemoryRecallTool {
                     let score = entry
                         .score
                         .map_or_else(String::new, |s| format!(" [{s:.0}%]"));
+                    let mut tags = String::new();
+                    for tag in &entry.tags {
+                        let _ = write!(tags, " #{tag}");
+                    }
                     let _ = writeln!(
                         output,
-                        "- [{}] {}: {}{score}",
+                        "- [{}] {}: {}{tags}{score}",
                         entry.category, entry.key, entry.content
                     );
                 }
@@ -148,6 +159,37 @@ mod tests {
         assert!(result.output.contains("Found 3"));
     }
 
+    #[tokio::test]
+    async fn recall_filters_by_tags() {
+        let (_tmp, mem) = seeded_mem();
+        let tags = vec!["project-x".to_string()];
+        mem.store_with_tags(
+            "x1",
+            "Rust service for project x",
+            MemoryCategory::Core,
+            &tags,
+        )
+        .await
+        .unwrap();
+        mem.store("other", "Rust hobby crate", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let tool = MemoryRecallTool::new(mem);
+        let result = tool
+            .execute(json!({"query": "Rust", "tags": ["project-x"]}))
+            .await
+            .unwrap();
+        assert!(result.output.contains("Found 1"));
+        assert!(result.output.contains("x1"));
+        assert!(result.output.contains("#project-x"));
+
+        // Tags alone list the tagged memories
+        let result = tool.execute(json!({"tags": ["#Project-X"]})).await.unwrap();
+        assert!(result.output.contains("Found 1"));
+        assert!(!result.output.contains("hobby"));
+    }
+
     #[tokio::test]
     async fn recall_missing_query() {
         let (_tmp, mem) = seeded_mem();
@@ -1,5 +1,5 @@
 use super::traits::{Tool, ToolResult};
-use crate::memory::{Memory, MemoryCategory};
+use crate::memory::{normalize_tags, Memory, MemoryCategory};
 use async_trait::async_trait;
 use serde_json::json;
 use std::sync::Arc;
@@ -41,6 +41,11 @@ impl Tool for MemoryStoreTool {
                     "type": "string",
                     "enum": ["core", "daily", "conversation"],
                     "description": "Memory category: core (permanent), daily (session), conversation (chat)"
+                },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional labels for grouping related memories (e.g. ['project-x'])"
                 }
             },
             "required": ["key", "content"]
@@ -64,10 +69,20 @@ impl Tool for MemoryStoreTool {
             _ => MemoryCategory::Core,
         };
 
-        match self.memory.store(key, content, category).await {
+        let tags = normalize_tags(&string_array(args.get("tags")));
+
+        match self
+            .memory
+            .store_with_tags(key, content, category, &tags)
+            .await
+        {
             Ok(()) => Ok(ToolResult {
                 success: true,
-                output: format!("Stored memory: {key}"),
+                output: if tags.is_empty() {
+                    format!("Stored memory: {key}")
+                } else {
+                    format!("Stored memory: {key} (tags: {})", tags.join(", "))
+                },
                 error: None,
             }),
             Err(e) => Ok(ToolResult {
@@ -79,6 +94,19 @@ impl Tool for MemoryStoreTool {
     }
 }
 
+/// Strings from an optional JSON array argument; non-string items are ignored
+pub(super) fn string_array(value: Option<&serde_json::Value>) -> Vec<String> {
+    value
+        .and_then(serde_json::Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,4 +171,23 @@ mod tests {
         let result = tool.execute(json!({"key": "no_content"})).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn store_with_tags_normalizes() {
+        let (_tmp, mem) = test_mem();
+        let tool = MemoryStoreTool::new(mem.clone());
+        let result = tool
+            .execute(json!({
+                "key": "roadmap",
+                "content": "Ship v2 in March",
+                "tags": ["#Project-X", "planning", "project-x", 7]
+            }))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("project-x, planning"));
+
+        let entry = mem.get("roadmap").await.unwrap().unwrap();
+        assert_eq!(entry.tags, vec!["project-x", "planning"]);
+    }
 }
@@ -1,27 +1,49 @@
 pub mod browser;
 pub mod browser_open;
+pub mod clipboard;
 pub mod composio;
+pub mod file_edit;
 pub mod file_read;
 pub mod file_write;
+pub mod git;
+pub mod http_request;
 pub mod memory_forget;
 pub mod memory_recall;
 pub mod memory_store;
+pub mod python;
+pub mod reminders;
 pub mod shell;
+pub mod skill_script;
+pub mod task_add;
+pub mod task_complete;
+pub mod task_list;
 pub mod traits;
+pub mod web_fetch;
 pub mod web_search;
 
 pub use browser::BrowserTool;
 pub use browser_open::BrowserOpenTool;
+pub use clipboard::ClipboardTool;
 pub use composio::ComposioTool;
+pub use file_edit::FileEditTool;
 pub use file_read::FileReadTool;
 pub use file_write::FileWriteTool;
+pub use git::GitTool;
+pub use http_request::HttpRequestTool;
 pub use memory_forget::MemoryForgetTool;
 pub use memory_recall::MemoryRecallTool;
 pub use memory_store::MemoryStoreTool;
+pub use python::PythonTool;
+pub use reminders::RemindersTool;
 pub use shell::ShellTool;
+pub use skill_script::SkillScriptTool;
+pub use task_add::TaskAddTool;
+pub use task_complete::TaskCompleteTool;
+pub use task_list::TaskListTool;
 pub use traits::Tool;
 #[allow(unused_imports)]
 pub use traits::{ToolResult, ToolSpec};
+pub use web_fetch::WebFetchTool;
 pub use web_search::WebSearchTool;
 
 use crate::memory::Memory;
@@ -38,20 +60,31 @@ pub fn default_tools(security: Arc<SecurityPolicy>) -> Vec<Box<dyn Tool>> {
 }
 
 /// Create full tool registry including memory tools and optional integrations
+#[allow(clippy::too_many_arguments, clippy::needless_pass_by_value)]
 pub fn all_tools(
     security: &Arc<SecurityPolicy>,
     memory: Arc<dyn Memory>,
     composio_key: Option<&str>,
     browser_config: &crate::config::BrowserConfig,
     brave_search_config: &crate::config::BraveSearchConfig,
+    git_config: &crate::config::GitConfig,
+    web_fetch_config: &crate::config::WebFetchConfig,
+    tools_config: &crate::config::ToolsConfig,
+    named_secrets: &std::collections::BTreeMap<String, String>,
+    skills: &[crate::skills::Skill],
+    runtime: Arc<dyn crate::runtime::RuntimeAdapter>,
 ) -> Vec<Box<dyn Tool>> {
     let mut tools: Vec<Box<dyn Tool>> = vec![
-        Box::new(ShellTool::new(security.clone())),
+        Box::new(ShellTool::new(security.clone()).with_runtime(runtime.clone())),
         Box::new(FileReadTool::new(security.clone())),
         Box::new(FileWriteTool::new(security.clone())),
+        Box::new(FileEditTool::new(security.clone(), &tools_config.file_edit)),
         Box::new(MemoryStoreTool::new(memory.clone())),
         Box::new(MemoryRecallTool::new(memory.clone())),
         Box::new(MemoryForgetTool::new(memory)),
+        Box::new(TaskAddTool::new(security.clone())),
+        Box::new(TaskListTool::new(security.clone())),
+        Box::new(TaskCompleteTool::new(security.clone())),
     ];
 
     if browser_config.enabled {
@@ -68,26 +101,126 @@ pub fn all_tools(
         )));
     }
 
+    if git_config.enabled {
+        tools.push(Box::new(GitTool::new(
+            security.clone(),
+            git_config.allow_write,
+        )));
+    }
+
+    if web_fetch_config.enabled {
+        tools.push(Box::new(WebFetchTool::new(
+            security.clone(),
+            web_fetch_config,
+        )));
+    }
+
+    if tools_config.http.enabled {
+        tools.push(Box::new(HttpRequestTool::new(
+            security.clone(),
+            &tools_config.http,
+            named_secrets,
+        )));
+    }
+
+    if tools_config.clipboard.enabled {
+        tools.push(Box::new(ClipboardTool::new(
+            security.clone(),
+            &tools_config.clipboard,
+        )));
+    }
+
+    if tools_config.reminders.enabled {
+        tools.push(Box::new(RemindersTool::new(
+            security.clone(),
+            &tools_config.reminders,
+        )));
+    }
+
+    if tools_config.python.enabled {
+        tools.push(Box::new(
+            PythonTool::new(security.clone(), &tools_config.python)
+                .with_runtime(runtime.clone()),
+        ));
+    }
+
     if let Some(key) = composio_key {
         if !key.is_empty() {
             tools.push(Box::new(ComposioTool::new(key)));
         }
     }
 
-    if brave_search_config.enabled {
-        if let Some(ref key) = brave_search_config.api_key {
-            if !key.is_empty() {
-                tools.push(Box::new(WebSearchTool::new(
-                    key,
-                    brave_search_config.default_count,
-                )));
-            }
+    if let Some(provider) =
+        web_search::provider_from_config(&tools_config.web_search, brave_search_config)
+    {
+        tools.push(Box::new(WebSearchTool::new(provider)));
+    }
+
+    for skill in skills {
+        let Some(tool) = SkillScriptTool::new(security.clone(), skill) else {
+            continue;
+        };
+        let tool = tool.with_runtime(runtime.clone());
+        if tools.iter().any(|t| t.name() == tool.name()) {
+            tracing::warn!(
+                "技能「{}」的工具名 {} 已被占用，已跳过",
+                skill.name,
+                tool.name()
+            );
+            continue;
         }
+        tools.push(Box::new(tool));
     }
 
     tools
 }
 
+/// Names of the tools [`all_tools`] registers for `config`, without
+/// building them
+pub fn configured_tool_names(config: &crate::config::Config) -> Vec<&'static str> {
+    let has_key = |key: Option<&String>| key.is_some_and(|k| !k.is_empty());
+    let mut names = vec![
+        "shell",
+        "file_read",
+        "file_write",
+        "file_edit",
+        "memory_store",
+        "memory_recall",
+        "memory_forget",
+        "task_add",
+        "task_list",
+        "task_complete",
+    ];
+    if config.browser.enabled {
+        names.extend(["browser_open", "browser"]);
+    }
+    if config.git.enabled {
+        names.push("git");
+    }
+    if config.web_fetch.enabled {
+        names.push("web_fetch");
+    }
+    if config.tools.http.enabled {
+        names.push("http_request");
+    }
+    if config.tools.clipboard.enabled {
+        names.push("clipboard");
+    }
+    if config.tools.reminders.enabled {
+        names.push("reminders");
+    }
+    if config.tools.python.enabled {
+        names.push("python");
+    }
+    if config.composio.enabled && has_key(config.composio.api_key.as_ref()) {
+        names.push("composio");
+    }
+    if web_search::provider_from_config(&config.tools.web_search, &config.brave_search).is_some() {
+        names.push("web_search");
+    }
+    names
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,7 +252,20 @@ mod tests {
         };
 
         let brave = crate::config::BraveSearchConfig::default();
-        let tools = all_tools(&security, mem, None, &browser, &brave);
+        let git = crate::config::GitConfig::default();
+        let tools = all_tools(
+            &security,
+            mem,
+            None,
+            &browser,
+            &brave,
+            &git,
+            &crate::config::WebFetchConfig::default(),
+            &crate::config::ToolsConfig::default(),
+            &std::collections::BTreeMap::new(),
+            &[],
+            Arc::new(crate::runtime::NativeRuntime::new()),
+        );
         let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
         assert!(!names.contains(&"browser_open"));
     }
@@ -142,11 +288,153 @@ mod tests {
         };
 
         let brave = crate::config::BraveSearchConfig::default();
-        let tools = all_tools(&security, mem, None, &browser, &brave);
+        let git = crate::config::GitConfig::default();
+        let tools = all_tools(
+            &security,
+            mem,
+            None,
+            &browser,
+            &brave,
+            &git,
+            &crate::config::WebFetchConfig::default(),
+            &crate::config::ToolsConfig::default(),
+            &std::collections::BTreeMap::new(),
+            &[],
+            Arc::new(crate::runtime::NativeRuntime::new()),
+        );
         let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
         assert!(names.contains(&"browser_open"));
     }
 
+    #[test]
+    fn all_tools_includes_git_only_when_enabled() {
+        let tmp = TempDir::new().unwrap();
+        let security = Arc::new(SecurityPolicy::default());
+        let mem_cfg = MemoryConfig {
+            backend: "markdown".into(),
+            ..MemoryConfig::default()
+        };
+        let mem: Arc<dyn Memory> =
+            Arc::from(crate::memory::create_memory(&mem_cfg, tmp.path(), None).unwrap());
+        let browser = BrowserConfig::default();
+        let brave = crate::config::BraveSearchConfig::default();
+
+        let tools = all_tools(
+            &security,
+            mem.clone(),
+            None,
+            &browser,
+            &brave,
+            &crate::config::GitConfig::default(),
+            &crate::config::WebFetchConfig::default(),
+            &crate::config::ToolsConfig::default(),
+            &std::collections::BTreeMap::new(),
+            &[],
+            Arc::new(crate::runtime::NativeRuntime::new()),
+        );
+        assert!(!tools.iter().any(|t| t.name() == "git"));
+
+        let git = crate::config::GitConfig {
+            enabled: true,
+            allow_write: false,
+        };
+        let tools = all_tools(
+            &security,
+            mem,
+            None,
+            &browser,
+            &brave,
+            &git,
+            &crate::config::WebFetchConfig::default(),
+            &crate::config::ToolsConfig::default(),
+            &std::collections::BTreeMap::new(),
+            &[],
+            Arc::new(crate::runtime::NativeRuntime::new()),
+        );
+        assert!(tools.iter().any(|t| t.name() == "git"));
+    }
+
+    #[test]
+    fn all_tools_registers_skill_entrypoints() {
+        let tmp = TempDir::new().unwrap();
+        let security = Arc::new(SecurityPolicy::default());
+        let mem_cfg = MemoryConfig {
+            backend: "markdown".into(),
+            ..MemoryConfig::default()
+        };
+        let mem: Arc<dyn Memory> =
+            Arc::from(crate::memory::create_memory(&mem_cfg, tmp.path(), None).unwrap());
+        let sample = crate::skills::load_skill_dir(
+            &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/skills/text-stats"),
+        )
+        .unwrap();
+        let mut prompt_only = sample.clone();
+        prompt_only.name = "notes".into();
+        prompt_only.entrypoint = None;
+
+        let tools = all_tools(
+            &security,
+            mem,
+            None,
+            &BrowserConfig::default(),
+            &crate::config::BraveSearchConfig::default(),
+            &crate::config::GitConfig::default(),
+            &crate::config::WebFetchConfig::default(),
+            &crate::config::ToolsConfig::default(),
+            &std::collections::BTreeMap::new(),
+            &[sample.clone(), prompt_only, sample],
+            Arc::new(crate::runtime::NativeRuntime::new()),
+        );
+        let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
+        assert_eq!(
+            names.iter().filter(|n| n.starts_with("skill_")).count(),
+            1,
+            "{names:?}"
+        );
+        assert!(names.contains(&"skill_text_stats"));
+    }
+
+    #[test]
+    fn configured_tool_names_match_all_tools() {
+        let tmp = TempDir::new().unwrap();
+        let security = Arc::new(SecurityPolicy::default());
+        let mem_cfg = MemoryConfig {
+            backend: "markdown".into(),
+            ..MemoryConfig::default()
+        };
+        let mem: Arc<dyn Memory> =
+            Arc::from(crate::memory::create_memory(&mem_cfg, tmp.path(), None).unwrap());
+        let mut config = crate::config::Config::default();
+        config.browser.enabled = true;
+        config.git.enabled = true;
+        config.web_fetch.enabled = true;
+        config.tools.http.enabled = true;
+        config.tools.clipboard.enabled = true;
+        config.tools.reminders.enabled = true;
+        config.tools.python.enabled = true;
+        config.tools.web_search.provider = "searxng".into();
+        config.tools.web_search.searxng.url = Some("http://127.0.0.1:8888".into());
+
+        let tools = all_tools(
+            &security,
+            mem,
+            None,
+            &config.browser,
+            &config.brave_search,
+            &config.git,
+            &config.web_fetch,
+            &config.tools,
+            &config.secrets.named,
+            &[],
+            Arc::new(crate::runtime::NativeRuntime::new()),
+        );
+        let mut built: Vec<&str> = tools.iter().map(|t| t.name()).collect();
+        let mut named = configured_tool_names(&config);
+        built.sort_unstable();
+        named.sort_unstable();
+        assert_eq!(built, named);
+    }
+
     #[test]
     fn default_tools_names() {
         let security = Arc::new(SecurityPolicy::default());
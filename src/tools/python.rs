@@ -0,0 +1,370 @@
+use super::shell::{failed, finish, run};
+use super::traits::{Tool, ToolResult};
+use crate::config::PythonConfig;
+use crate::runtime::{NativeRuntime, RuntimeAdapter};
+use crate::security::approval::ApprovalDecision;
+use crate::security::{AutonomyLevel, SecurityPolicy};
+use async_trait::async_trait;
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Run a Python snippet (`[tools.python]`).
+///
+/// The code is written to a hidden file in the workspace and run there by
+/// the pinned interpreter, through the runtime the shell tool uses, so the
+/// docker and sandbox runtimes contain it too. The interpreter comes from
+/// config, never from the model, so the command allowlist doesn't apply;
+/// instead, in supervised mode every run needs the user's approval.
+pub struct PythonTool {
+    security: Arc<SecurityPolicy>,
+    runtime: Arc<dyn RuntimeAdapter>,
+    interpreter: String,
+    venv: Option<String>,
+    timeout: Duration,
+    max_output_bytes: usize,
+}
+
+impl PythonTool {
+    pub fn new(security: Arc<SecurityPolicy>, config: &PythonConfig) -> Self {
+        let venv = config
+            .venv
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty());
+        let venv = venv.map(|venv| {
+            security
+                .workspace_dir
+                .join(venv)
+                .to_string_lossy()
+                .into_owned()
+        });
+        let interpreter = match &venv {
+            Some(venv) => Path::new(venv)
+                .join("bin/python")
+                .to_string_lossy()
+                .into_owned(),
+            None => config.interpreter.trim().to_string(),
+        };
+        Self {
+            security,
+            runtime: Arc::new(NativeRuntime::new()),
+            interpreter,
+            venv,
+            timeout: Duration::from_secs(config.timeout_secs.max(1)),
+            max_output_bytes: config.max_output_bytes,
+        }
+    }
+
+    /// Run the code through `runtime` instead of directly on this machine.
+    pub fn with_runtime(mut self, runtime: Arc<dyn RuntimeAdapter>) -> Self {
+        self.runtime = runtime;
+        self
+    }
+
+    /// The call's `timeout_seconds`, capped at the shell maximum.
+    fn timeout(&self, requested: Option<&serde_json::Value>) -> anyhow::Result<Duration> {
+        let Some(requested) = requested.filter(|v| !v.is_null()) else {
+            return Ok(self.timeout.min(self.security.max_shell_timeout));
+        };
+        let secs = requested
+            .as_u64()
+            .filter(|secs| *secs > 0)
+            .ok_or_else(|| anyhow::anyhow!("'timeout_seconds' must be a positive integer"))?;
+        Ok(Duration::from_secs(secs).min(self.security.max_shell_timeout))
+    }
+
+    /// The `sh -c` line running `script`, a file name in the workspace.
+    fn command_line(&self, script: &str) -> Result<String, String> {
+        let quote = |word: &str| {
+            shlex::try_quote(word)
+                .map(std::borrow::Cow::into_owned)
+                .map_err(|e| format!("Cannot run interpreter `{}`: {e}", self.interpreter))
+        };
+        Ok(format!("{} {}", quote(&self.interpreter)?, quote(script)?))
+    }
+
+    /// Ask the user before running `code` in supervised mode; the reason
+    /// for refusing when they don't approve. Full autonomy runs it as is.
+    async fn approval_refusal(&self, code: &str) -> Option<String> {
+        if self.security.autonomy == AutonomyLevel::Full {
+            return None;
+        }
+        let shown = shlex::try_quote(code).map_or_else(|_| code.into(), std::borrow::Cow::into_owned);
+        let command = format!("{} -c {shown}", self.interpreter);
+        match self.security.approvals.request(&command, vec![], None).await {
+            Some(ApprovalDecision::Approved | ApprovalDecision::Always) => None,
+            Some(ApprovalDecision::Denied) => Some("Code denied by the user".into()),
+            Some(ApprovalDecision::TimedOut) => Some(format!(
+                "Code not run: no approval received within {}s",
+                self.security.approvals.timeout().as_secs()
+            )),
+            None => Some(
+                "Code not run: supervised mode needs the user's approval and no one is \
+                 available to ask"
+                    .into(),
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for PythonTool {
+    fn name(&self) -> &str {
+        "python"
+    }
+
+    fn description(&self) -> &str {
+        "Run a Python 3 program in the workspace directory and return what it printed \
+         (stdout and stderr together, in order). Files it writes stay in the workspace. \
+         Print the results you need; the value of the last expression is not shown."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "code": {
+                    "type": "string",
+                    "description": "The Python source to run"
+                },
+                "timeout_seconds": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": format!(
+                        "Kill the program after this many seconds (default {}, at most {})",
+                        self.timeout.min(self.security.max_shell_timeout).as_secs(),
+                        self.security.max_shell_timeout.as_secs()
+                    )
+                }
+            },
+            "required": ["code"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let code = args
+            .get("code")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'code' parameter"))?;
+        let timeout = self.timeout(args.get("timeout_seconds"))?;
+
+        if !self.security.can_act() {
+            return Ok(failed(
+                "Action blocked: code cannot run in read-only autonomy mode".into(),
+            ));
+        }
+        if let Some(refusal) = self.approval_refusal(code).await {
+            return Ok(failed(refusal));
+        }
+        if !self.security.record_action() {
+            return Ok(failed("Action blocked: rate limit exceeded".into()));
+        }
+
+        let workspace = &self.security.workspace_dir;
+        let script = format!(".python-{}.py", uuid::Uuid::new_v4().simple());
+        let command = match self.command_line(&script) {
+            Ok(command) => command,
+            Err(error) => return Ok(failed(error)),
+        };
+        let path = workspace.join(&script);
+        if let Err(e) = tokio::fs::write(&path, code).await {
+            return Ok(failed(format!(
+                "Failed to write the program to the workspace: {e}"
+            )));
+        }
+
+        let mut env = vec![
+            ("PYTHONUNBUFFERED".to_string(), "1".to_string()),
+            ("PYTHONDONTWRITEBYTECODE".to_string(), "1".to_string()),
+        ];
+        if let Some(venv) = &self.venv {
+            env.push(("VIRTUAL_ENV".to_string(), venv.clone()));
+        }
+        let cmd = self
+            .runtime
+            .shell_command(&command, workspace, &env, timeout);
+        let ran = run(cmd, timeout).await;
+        let _ = tokio::fs::remove_file(&path).await;
+
+        match ran {
+            Ok((output, status)) => Ok(finish(cap(output, self.max_output_bytes), status, timeout)),
+            Err(e) => Ok(failed(format!("Failed to run `{}`: {e}", self.interpreter))),
+        }
+    }
+}
+
+/// `output` cut to at most `max` bytes, on a character boundary, with a
+/// note saying so.
+fn cap(output: String, max: usize) -> String {
+    if output.len() <= max {
+        return output;
+    }
+    let mut end = max;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}\n... [output truncated at {max} bytes]", &output[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(workspace: &Path, config: &PythonConfig) -> PythonTool {
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Full,
+            workspace_dir: workspace.to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        PythonTool::new(security, config)
+    }
+
+    fn has_python() -> bool {
+        std::process::Command::new("python3")
+            .arg("--version")
+            .output()
+            .is_ok_and(|o| o.status.success())
+    }
+
+    #[tokio::test]
+    async fn runs_code_in_the_workspace_and_cleans_up() {
+        if !has_python() {
+            eprintln!("python3 not installed, skipping");
+            return;
+        }
+        let workspace = tempfile::TempDir::new().unwrap();
+        let tool = tool(workspace.path(), &PythonConfig::default());
+
+        let result = tool
+            .execute(json!({"code": "import os, sys\nprint(sum(range(10)))\nprint('oops', file=sys.stderr)\nopen('out.txt', 'w').write('x')"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "45\noops\n");
+        let left: Vec<_> = std::fs::read_dir(workspace.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(left, ["out.txt"]);
+
+        let result = tool
+            .execute(json!({"code": "raise SystemExit(3)"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("exit status: 3"));
+    }
+
+    #[tokio::test]
+    async fn timeouts_and_output_size_are_bounded() {
+        if !has_python() {
+            eprintln!("python3 not installed, skipping");
+            return;
+        }
+        let workspace = tempfile::TempDir::new().unwrap();
+        let config = PythonConfig {
+            max_output_bytes: 10,
+            ..PythonConfig::default()
+        };
+        let tool = tool(workspace.path(), &config);
+
+        let result = tool
+            .execute(json!({"code": "print('a' * 100)"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            result.output,
+            "aaaaaaaaaa\n... [output truncated at 10 bytes]"
+        );
+
+        let result = tool
+            .execute(json!({"code": "import time\nprint('started')\ntime.sleep(30)", "timeout_seconds": 1}))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.error.unwrap(),
+            "started\n[Command timed out after 1s and was killed]"
+        );
+    }
+
+    #[test]
+    fn venv_pins_the_interpreter() {
+        let workspace = Path::new("/home/user/workspace");
+        let config = PythonConfig {
+            interpreter: "/usr/bin/python3.12".into(),
+            venv: Some(".venv".into()),
+            ..PythonConfig::default()
+        };
+        let pinned = tool(workspace, &config);
+        assert_eq!(pinned.interpreter, "/home/user/workspace/.venv/bin/python");
+        assert_eq!(
+            pinned.command_line(".python-1.py").unwrap(),
+            "/home/user/workspace/.venv/bin/python .python-1.py"
+        );
+
+        let plain = tool(workspace, &PythonConfig::default());
+        assert_eq!(plain.command_line("a.py").unwrap(), "python3 a.py");
+        assert_eq!(
+            cap("héllo".into(), 2),
+            "h\n... [output truncated at 2 bytes]"
+        );
+    }
+
+    #[tokio::test]
+    async fn read_only_autonomy_refuses_to_run() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::ReadOnly,
+            workspace_dir: workspace.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        let tool = PythonTool::new(security, &PythonConfig::default());
+        let result = tool.execute(json!({"code": "print(1)"})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("read-only"));
+        assert!(tool.execute(json!({})).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn supervised_runs_need_approval() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let supervised = || {
+            Arc::new(SecurityPolicy {
+                workspace_dir: workspace.path().to_path_buf(),
+                ..SecurityPolicy::default()
+            })
+        };
+
+        // No one to ask: refused without running
+        let tool = PythonTool::new(supervised(), &PythonConfig::default());
+        let result = tool
+            .execute(json!({"code": "open('ran', 'w')"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("approval"));
+
+        let security = supervised();
+        let mut requests = security.approvals.attach();
+        let front_end = tokio::spawn(async move {
+            let request = requests.recv().await.unwrap();
+            assert_eq!(
+                request.prompt(),
+                "Allow `python3 -c \"open('ran', 'w')\"`? [y/N/always]"
+            );
+            request.respond(ApprovalDecision::Denied);
+        });
+        let tool = PythonTool::new(security, &PythonConfig::default());
+        let result = tool
+            .execute(json!({"code": "open('ran', 'w')"}))
+            .await
+            .unwrap();
+        front_end.await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("denied by the user"));
+        assert!(!workspace.path().join("ran").exists());
+    }
+}
@@ -0,0 +1,853 @@
+use super::traits::{Tool, ToolResult};
+use crate::config::RemindersConfig;
+use crate::security::policy::canonicalize_lenient;
+use crate::security::SecurityPolicy;
+use crate::tasks;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde_json::json;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Longest content line written before folding, in octets (RFC 5545 §3.1)
+const FOLD_AT: usize = 75;
+
+const PRODID: &str = "-//Jarvis//Reminders//EN";
+
+/// Serializes read-modify-write cycles on reminder files, so two agents in
+/// one process (say the gateway and a channel) can't drop each other's edits
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Keep to-dos as VTODO entries in an iCalendar file in the workspace
+/// (`[tools.reminders]`), so they survive outside Jarvis: any calendar app
+/// can import the file.
+///
+/// Entries written by other apps are kept as they are, including events,
+/// alarms and properties this tool doesn't understand.
+pub struct RemindersTool {
+    security: Arc<SecurityPolicy>,
+    path: String,
+}
+
+impl RemindersTool {
+    pub fn new(security: Arc<SecurityPolicy>, config: &RemindersConfig) -> Self {
+        Self {
+            security,
+            path: config.path.clone(),
+        }
+    }
+
+    /// The reminders file, resolved and checked against the workspace.
+    fn resolve(&self) -> Result<PathBuf, String> {
+        if let Some(reason) = self.security.path_violation(&self.path) {
+            return Err(format!(
+                "Reminders file not allowed by security policy: {} ({reason})",
+                self.path
+            ));
+        }
+        let full = self.security.workspace_dir.join(&self.path);
+        let resolved = canonicalize_lenient(&full)
+            .map_err(|e| format!("Failed to resolve the reminders file: {e}"))?;
+        if !self.security.is_resolved_path_allowed(&resolved) {
+            return Err(self.security.escape_error(&self.path, &resolved));
+        }
+        Ok(resolved)
+    }
+
+    fn add(&self, path: &Path, args: &serde_json::Value) -> Result<String, String> {
+        let summary = args
+            .get("summary")
+            .and_then(serde_json::Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .ok_or("Missing 'summary' for add")?;
+        let due = args
+            .get("due")
+            .and_then(serde_json::Value::as_str)
+            .map(|raw| {
+                tasks::parse_due(raw).map_err(|_| {
+                    format!(
+                        "Invalid due time '{raw}': use YYYY-MM-DD, YYYY-MM-DD HH:MM or RFC 3339"
+                    )
+                })
+            })
+            .transpose()?;
+        let notes = args
+            .get("notes")
+            .and_then(serde_json::Value::as_str)
+            .filter(|s| !s.trim().is_empty());
+
+        let now = Utc::now();
+        let mut todo = Component::new("VTODO");
+        todo.set("UID", &format!("{}@jarvis", uuid::Uuid::new_v4().simple()));
+        todo.set("DTSTAMP", &format_utc(now));
+        todo.set("CREATED", &format_utc(now));
+        todo.set("SUMMARY", &escape_text(summary));
+        if let Some(notes) = notes {
+            todo.set("DESCRIPTION", &escape_text(notes));
+        }
+        if let Some(due) = due {
+            todo.set("DUE", &format_utc(due));
+        }
+        todo.set("STATUS", "NEEDS-ACTION");
+        let reminder = Reminder::from_todo(&todo);
+
+        self.update(path, |calendar| {
+            calendar.children.push(todo);
+            Ok(())
+        })?;
+        Ok(format!("Added reminder {}", reminder.line(now)))
+    }
+
+    fn complete(&self, path: &Path, args: &serde_json::Value) -> Result<String, String> {
+        let id = args
+            .get("id")
+            .and_then(serde_json::Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .ok_or("Missing 'id' for complete")?;
+        let now = Utc::now();
+        let mut done = None;
+        self.update(path, |calendar| {
+            let todo = find_todo(calendar, id)?;
+            todo.set("STATUS", "COMPLETED");
+            todo.set("COMPLETED", &format_utc(now));
+            todo.set("PERCENT-COMPLETE", "100");
+            todo.set("DTSTAMP", &format_utc(now));
+            todo.set("LAST-MODIFIED", &format_utc(now));
+            done = Some(Reminder::from_todo(todo));
+            Ok(())
+        })?;
+        let done = done.expect("set by a successful update");
+        Ok(format!("Completed reminder {}", done.line(now)))
+    }
+
+    /// Load the file, apply `edit` and write it back, all under the lock.
+    /// A file that doesn't parse is never overwritten.
+    fn update(
+        &self,
+        path: &Path,
+        edit: impl FnOnce(&mut Component) -> Result<(), String>,
+    ) -> Result<(), String> {
+        if !self.security.can_act() {
+            return Err("Action blocked: autonomy is read-only".into());
+        }
+        if !self.security.record_action() {
+            return Err("Action blocked: rate limit exceeded".into());
+        }
+        let _guard = FILE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut calendar = load(path).map_err(|e| self.unreadable(&e))?;
+        edit(&mut calendar)?;
+        save(path, &calendar).map_err(|e| format!("Failed to save reminders: {e:#}"))
+    }
+
+    fn list(&self, path: &Path, args: &serde_json::Value) -> Result<String, String> {
+        let include_completed = args
+            .get("include_completed")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        let due_before = args
+            .get("due_before")
+            .and_then(serde_json::Value::as_str)
+            .map(|raw| {
+                tasks::parse_due(raw).map_err(|_| format!("Invalid due_before time '{raw}'"))
+            })
+            .transpose()?;
+
+        let calendar = {
+            let _guard = FILE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+            load(path).map_err(|e| self.unreadable(&e))?
+        };
+        let mut reminders: Vec<Reminder> = calendar
+            .children
+            .iter()
+            .filter(|c| c.name == "VTODO")
+            .map(Reminder::from_todo)
+            .filter(|r| include_completed || !r.done)
+            .filter(|r| due_before.is_none_or(|limit| r.due.is_some_and(|due| due <= limit)))
+            .collect();
+        if reminders.is_empty() {
+            return Ok("No matching reminders".into());
+        }
+        reminders.sort_by(|a, b| {
+            (a.done, a.due.is_none(), a.due, &a.summary).cmp(&(
+                b.done,
+                b.due.is_none(),
+                b.due,
+                &b.summary,
+            ))
+        });
+
+        let now = Utc::now();
+        let mut output = format!("{} reminder(s):", reminders.len());
+        for reminder in &reminders {
+            let _ = write!(output, "\n{}", reminder.line(now));
+            if let Some(notes) = &reminder.notes {
+                let _ = write!(output, "\n    {}", notes.replace('\n', "\n    "));
+            }
+        }
+        Ok(output)
+    }
+
+    fn unreadable(&self, error: &anyhow::Error) -> String {
+        format!(
+            "The reminders file {} could not be read: {error:#}. It was left unchanged; \
+             ask the user to fix or move it.",
+            self.path
+        )
+    }
+}
+
+#[async_trait]
+impl Tool for RemindersTool {
+    fn name(&self) -> &str {
+        "reminders"
+    }
+
+    fn description(&self) -> &str {
+        "Keep reminders in the user's iCalendar (.ics) to-do file: add one, list open (or due) \
+         ones, or mark one complete by the id shown in the list."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["add", "list", "complete"],
+                    "description": "add: create a reminder; list: show reminders; complete: mark one done"
+                },
+                "summary": {
+                    "type": "string",
+                    "description": "What to remember (required for add)"
+                },
+                "due": {
+                    "type": "string",
+                    "description": "Optional due time for add: 'YYYY-MM-DD' (end of that day), 'YYYY-MM-DD HH:MM' in local time, or RFC 3339"
+                },
+                "notes": {
+                    "type": "string",
+                    "description": "Optional details for add"
+                },
+                "id": {
+                    "type": "string",
+                    "description": "Reminder id from list (or any unique prefix of it), required for complete"
+                },
+                "due_before": {
+                    "type": "string",
+                    "description": "For list: only reminders due at or before this time, e.g. now for what is due"
+                },
+                "include_completed": {
+                    "type": "boolean",
+                    "description": "For list: also show completed reminders (default false)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let action = args
+            .get("action")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Missing 'action' parameter"))?;
+
+        let result = self.resolve().and_then(|path| match action {
+            "add" => self.add(&path, &args),
+            "list" => self.list(&path, &args),
+            "complete" => self.complete(&path, &args),
+            other => Err(format!(
+                "Unknown action '{other}' (expected add, list or complete)"
+            )),
+        });
+
+        Ok(match result {
+            Ok(output) => ToolResult {
+                success: true,
+                output,
+                error: None,
+            },
+            Err(error) => ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(error),
+            },
+        })
+    }
+}
+
+/// The fields of a VTODO the tool shows.
+#[derive(Debug)]
+struct Reminder {
+    uid: String,
+    summary: String,
+    notes: Option<String>,
+    due: Option<DateTime<Utc>>,
+    done: bool,
+}
+
+impl Reminder {
+    fn from_todo(todo: &Component) -> Self {
+        let status = todo.get("STATUS").unwrap_or_default().to_ascii_uppercase();
+        Self {
+            uid: todo.get("UID").unwrap_or_default().to_string(),
+            summary: todo
+                .get("SUMMARY")
+                .map_or_else(|| "(untitled)".to_string(), unescape_text),
+            notes: todo.get("DESCRIPTION").map(unescape_text),
+            due: todo.get("DUE").and_then(parse_datetime),
+            done: status == "COMPLETED" || status == "CANCELLED" || todo.get("COMPLETED").is_some(),
+        }
+    }
+
+    /// `[3f2a9c1e] Renew passport (due 2026-10-01 09:00)`, local time.
+    fn line(&self, now: DateTime<Utc>) -> String {
+        let mut line = format!("[{}] {}", short_id(&self.uid), self.summary);
+        if let Some(due) = self.due {
+            let local = due.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+            let _ = write!(line, " (due {local})");
+            if !self.done && due < now {
+                line.push_str(" OVERDUE");
+            }
+        }
+        if self.done {
+            line.push_str(" ✓ done");
+        }
+        line
+    }
+}
+
+/// The id shown for a UID: its first 8 characters.
+fn short_id(uid: &str) -> &str {
+    uid.char_indices()
+        .nth(8)
+        .map_or(uid, |(end, _)| &uid[..end])
+}
+
+/// The one VTODO whose UID is `id` or starts with it.
+fn find_todo<'a>(calendar: &'a mut Component, id: &str) -> Result<&'a mut Component, String> {
+    let matches: Vec<usize> = calendar
+        .children
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.name == "VTODO")
+        .filter(|(_, c)| c.get("UID").is_some_and(|uid| uid.starts_with(id)))
+        .map(|(i, _)| i)
+        .collect();
+    match matches.as_slice() {
+        [index] => Ok(&mut calendar.children[*index]),
+        [] => Err(format!("No reminder with id '{id}' (see list)")),
+        _ => Err(format!(
+            "Id '{id}' matches {} reminders; use more of the id",
+            matches.len()
+        )),
+    }
+}
+
+/// One content line: `NAME;PARAMS:value`, with `params` kept verbatim
+/// (including its leading `;`) so foreign properties round-trip.
+#[derive(Debug, Clone, PartialEq)]
+struct Property {
+    name: String,
+    params: String,
+    value: String,
+}
+
+/// A `BEGIN:X` … `END:X` block with its properties and nested blocks.
+#[derive(Debug, Clone, PartialEq)]
+struct Component {
+    name: String,
+    properties: Vec<Property>,
+    children: Vec<Component>,
+}
+
+impl Component {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            properties: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.value.as_str())
+    }
+
+    /// Replace every `name` property with one carrying `value` (no params).
+    fn set(&mut self, name: &str, value: &str) {
+        self.properties.retain(|p| p.name != name);
+        self.properties.push(Property {
+            name: name.to_string(),
+            params: String::new(),
+            value: value.to_string(),
+        });
+    }
+
+    fn write(&self, out: &mut String) {
+        write_line(out, &format!("BEGIN:{}", self.name));
+        for property in &self.properties {
+            write_line(
+                out,
+                &format!("{}{}:{}", property.name, property.params, property.value),
+            );
+        }
+        for child in &self.children {
+            child.write(out);
+        }
+        write_line(out, &format!("END:{}", self.name));
+    }
+}
+
+fn empty_calendar() -> Component {
+    let mut calendar = Component::new("VCALENDAR");
+    calendar.set("VERSION", "2.0");
+    calendar.set("PRODID", PRODID);
+    calendar
+}
+
+/// Read the calendar at `path`; a missing file is an empty calendar.
+fn load(path: &Path) -> Result<Component> {
+    match std::fs::read_to_string(path) {
+        Ok(raw) => parse_calendar(&raw).context("not a valid iCalendar file"),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(empty_calendar()),
+        Err(e) => Err(e).context("read failed"),
+    }
+}
+
+/// Write through a temporary file, so a crash never leaves half a calendar.
+fn save(path: &Path, calendar: &Component) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    let mut out = String::new();
+    calendar.write(&mut out);
+    let tmp = path.with_extension("ics.tmp");
+    std::fs::write(&tmp, out).with_context(|| format!("write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("replace {}", path.display()))
+}
+
+/// Parse a single VCALENDAR. Structure is checked strictly (every BEGIN has
+/// its END, every line has a name and a colon); property values are not.
+fn parse_calendar(raw: &str) -> Result<Component> {
+    let mut stack: Vec<Component> = Vec::new();
+    let mut calendar = None;
+    for (number, line) in unfold(raw) {
+        if line.is_empty() {
+            continue;
+        }
+        if calendar.is_some() {
+            anyhow::bail!("line {number}: content after END:VCALENDAR");
+        }
+        let property = parse_line(&line).with_context(|| format!("line {number}"))?;
+        match property.name.as_str() {
+            "BEGIN" => {
+                let name = property.value.to_ascii_uppercase();
+                if stack.is_empty() && name != "VCALENDAR" {
+                    anyhow::bail!("line {number}: expected BEGIN:VCALENDAR, found BEGIN:{name}");
+                }
+                stack.push(Component::new(&name));
+            }
+            "END" => {
+                let name = property.value.to_ascii_uppercase();
+                let Some(component) = stack.pop().filter(|c| c.name == name) else {
+                    anyhow::bail!("line {number}: END:{name} without a matching BEGIN");
+                };
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(component),
+                    None => calendar = Some(component),
+                }
+            }
+            _ => match stack.last_mut() {
+                Some(component) => component.properties.push(property),
+                None => anyhow::bail!("line {number}: expected BEGIN:VCALENDAR"),
+            },
+        }
+    }
+    if let Some(open) = stack.last() {
+        anyhow::bail!("BEGIN:{} is never closed", open.name);
+    }
+    calendar.context("no BEGIN:VCALENDAR found")
+}
+
+/// Join folded lines (a CRLF followed by a space or tab continues the
+/// previous line), numbering each logical line by where it starts.
+fn unfold(raw: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (index, line) in raw.lines().enumerate() {
+        if let Some(rest) = line.strip_prefix([' ', '\t'])
+            && let Some((_, last)) = lines.last_mut()
+        {
+            last.push_str(rest);
+        } else {
+            lines.push((index + 1, line.to_string()));
+        }
+    }
+    lines
+}
+
+/// Split `NAME;PARAM=x:value`. Colons inside quoted parameter values don't
+/// end the parameters.
+fn parse_line(line: &str) -> Result<Property> {
+    let mut quoted = false;
+    let colon = line
+        .char_indices()
+        .find(|&(_, c)| {
+            if c == '"' {
+                quoted = !quoted;
+            }
+            c == ':' && !quoted
+        })
+        .map(|(i, _)| i)
+        .with_context(|| format!("no ':' in \"{line}\""))?;
+    let head = &line[..colon];
+    let name_end = head.find(';').unwrap_or(head.len());
+    let name = &head[..name_end];
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        anyhow::bail!("invalid property name in \"{line}\"");
+    }
+    Ok(Property {
+        name: name.to_ascii_uppercase(),
+        params: head[name_end..].to_string(),
+        value: line[colon + 1..].to_string(),
+    })
+}
+
+/// Append `line` with CRLF, folded so no physical line exceeds 75 octets.
+fn write_line(out: &mut String, line: &str) {
+    let mut limit = FOLD_AT;
+    let mut start = 0;
+    while line.len() - start > limit {
+        let mut end = start + limit;
+        while !line.is_char_boundary(end) {
+            end -= 1;
+        }
+        out.push_str(&line[start..end]);
+        out.push_str("\r\n ");
+        start = end;
+        // The leading space of a continuation counts towards its length
+        limit = FOLD_AT - 1;
+    }
+    out.push_str(&line[start..]);
+    out.push_str("\r\n");
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn format_utc(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// A DATE-TIME in UTC (`…Z`) or floating/TZID time (taken as local), or a
+/// DATE, due by the end of that local day like task due dates.
+fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|naive| naive.and_utc());
+    }
+    let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y%m%d")
+                .ok()
+                .map(|date| date.and_time(NaiveTime::from_hms_opt(23, 59, 59).unwrap_or_default()))
+        })?;
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use tempfile::TempDir;
+
+    fn tool(workspace: &Path) -> RemindersTool {
+        RemindersTool::new(
+            Arc::new(SecurityPolicy {
+                workspace_dir: workspace.to_path_buf(),
+                ..SecurityPolicy::default()
+            }),
+            &RemindersConfig {
+                enabled: true,
+                ..RemindersConfig::default()
+            },
+        )
+    }
+
+    async fn run(tool: &RemindersTool, args: serde_json::Value) -> ToolResult {
+        tool.execute(args).await.unwrap()
+    }
+
+    /// The id in an `Added reminder [id] …` line.
+    fn id_of(output: &str) -> String {
+        let start = output.find('[').unwrap() + 1;
+        output[start..start + 8].to_string()
+    }
+
+    const FOREIGN: &str = "BEGIN:VCALENDAR\r\n\
+        VERSION:2.0\r\n\
+        PRODID:-//Other App//EN\r\n\
+        X-WR-CALNAME:Home\r\n\
+        BEGIN:VEVENT\r\n\
+        UID:event-1\r\n\
+        SUMMARY:Dentist\r\n\
+        DTSTART:20300105T090000Z\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VTODO\r\n\
+        UID:abc-from-phone\r\n\
+        SUMMARY:Buy milk\\, eggs and a very long list of other things that needs to\r\n  be folded\r\n\
+        DUE;VALUE=DATE:20000101\r\n\
+        X-APPLE-SORT-ORDER:7\r\n\
+        BEGIN:VALARM\r\n\
+        ACTION:DISPLAY\r\n\
+        TRIGGER:-PT15M\r\n\
+        END:VALARM\r\n\
+        END:VTODO\r\n\
+        END:VCALENDAR\r\n";
+
+    #[tokio::test]
+    async fn add_list_complete_roundtrip() {
+        let tmp = TempDir::new().unwrap();
+        let tool = tool(tmp.path());
+        assert_eq!(tool.name(), "reminders");
+
+        let empty = run(&tool, json!({"action": "list"})).await;
+        assert!(empty.success, "{:?}", empty.error);
+        assert_eq!(empty.output, "No matching reminders");
+
+        let later = run(
+            &tool,
+            json!({"action": "add", "summary": "Call mom", "notes": "About the trip; bring dates"}),
+        )
+        .await;
+        assert!(later.success, "{:?}", later.error);
+        let soon = run(
+            &tool,
+            json!({"action": "add", "summary": "Renew passport", "due": "2030-01-02 09:30"}),
+        )
+        .await;
+        assert!(soon.output.starts_with("Added reminder ["));
+        assert!(soon
+            .output
+            .ends_with("Renew passport (due 2030-01-02 09:30)"));
+
+        let listed = run(&tool, json!({"action": "list"})).await;
+        let lines: Vec<&str> = listed.output.lines().collect();
+        assert_eq!(lines[0], "2 reminder(s):");
+        assert!(lines[1].contains("Renew passport"), "{lines:?}");
+        assert!(lines[2].contains("Call mom"));
+        assert_eq!(lines[3], "    About the trip; bring dates");
+
+        let due = run(&tool, json!({"action": "list", "due_before": "2030-01-03"})).await;
+        assert!(due.output.starts_with("1 reminder(s):"));
+
+        let done = run(
+            &tool,
+            json!({"action": "complete", "id": id_of(&soon.output)}),
+        )
+        .await;
+        assert!(done.success, "{:?}", done.error);
+        assert!(done.output.starts_with("Completed reminder"));
+        assert!(done.output.ends_with("✓ done"));
+
+        let open = run(&tool, json!({"action": "list"})).await;
+        assert!(open.output.starts_with("1 reminder(s):"));
+        assert!(!open.output.contains("Renew passport"));
+        let all = run(&tool, json!({"action": "list", "include_completed": true})).await;
+        assert!(all.output.starts_with("2 reminder(s):"));
+
+        let raw = std::fs::read_to_string(tmp.path().join("reminders.ics")).unwrap();
+        assert!(raw.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(raw.contains("SUMMARY:Call mom\r\nDESCRIPTION:About the trip\\; bring dates"));
+        let calendar = parse_calendar(&raw).unwrap();
+        let statuses: Vec<_> = calendar
+            .children
+            .iter()
+            .map(|todo| todo.get("STATUS").unwrap())
+            .collect();
+        assert_eq!(statuses, ["NEEDS-ACTION", "COMPLETED"]);
+    }
+
+    #[tokio::test]
+    async fn keeps_entries_from_other_apps() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("reminders.ics"), FOREIGN).unwrap();
+        let tool = tool(tmp.path());
+
+        let listed = run(&tool, json!({"action": "list"})).await;
+        assert!(listed.success, "{:?}", listed.error);
+        assert!(listed.output.contains(
+            "[abc-from] Buy milk, eggs and a very long list of other things that needs to be folded"
+        ));
+        assert!(listed.output.contains("(due 2000-01-01 23:59) OVERDUE"));
+
+        let done = run(&tool, json!({"action": "complete", "id": "abc"})).await;
+        assert!(done.success, "{:?}", done.error);
+
+        let raw = std::fs::read_to_string(tmp.path().join("reminders.ics")).unwrap();
+        for kept in [
+            "PRODID:-//Other App//EN",
+            "X-WR-CALNAME:Home",
+            "BEGIN:VEVENT\r\nUID:event-1",
+            "DUE;VALUE=DATE:20000101",
+            "X-APPLE-SORT-ORDER:7",
+            "BEGIN:VALARM\r\nACTION:DISPLAY\r\nTRIGGER:-PT15M\r\nEND:VALARM",
+        ] {
+            assert!(raw.contains(kept), "lost {kept:?} in\n{raw}");
+        }
+        assert!(raw.lines().all(|line| line.len() <= FOLD_AT));
+        let todo = &parse_calendar(&raw).unwrap().children[1];
+        assert_eq!(todo.get("STATUS"), Some("COMPLETED"));
+    }
+
+    #[tokio::test]
+    async fn malformed_file_is_reported_and_left_alone() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("reminders.ics");
+        let tool = tool(tmp.path());
+        for (content, expected) in [
+            ("hello\r\n", "no ':'"),
+            ("BEGIN:VTODO\r\nEND:VTODO\r\n", "expected BEGIN:VCALENDAR"),
+            (
+                "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nEND:VCALENDAR\r\n",
+                "END:VCALENDAR without",
+            ),
+            (
+                "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\n",
+                "BEGIN:VTODO is never closed",
+            ),
+            (
+                "BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\nX:1\r\n",
+                "after END:VCALENDAR",
+            ),
+            (
+                "BEGIN:VCALENDAR\r\nBAD NAME:x\r\nEND:VCALENDAR\r\n",
+                "invalid property name",
+            ),
+            ("", "no BEGIN:VCALENDAR"),
+        ] {
+            std::fs::write(&path, content).unwrap();
+            for args in [
+                json!({"action": "list"}),
+                json!({"action": "add", "summary": "x"}),
+                json!({"action": "complete", "id": "x"}),
+            ] {
+                let result = run(&tool, args).await;
+                assert!(!result.success);
+                let error = result.error.unwrap();
+                assert!(error.contains(expected), "{content:?}: {error}");
+                assert!(error.contains("left unchanged"));
+            }
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_input_and_paths() {
+        let tmp = TempDir::new().unwrap();
+        let tool = tool(tmp.path());
+        assert!(tool.execute(json!({})).await.is_err());
+        for (args, expected) in [
+            (json!({"action": "snooze"}), "Unknown action 'snooze'"),
+            (json!({"action": "add"}), "Missing 'summary'"),
+            (
+                json!({"action": "add", "summary": "x", "due": "soon"}),
+                "Invalid due time 'soon'",
+            ),
+            (json!({"action": "complete"}), "Missing 'id'"),
+            (
+                json!({"action": "complete", "id": "nope"}),
+                "No reminder with id 'nope'",
+            ),
+        ] {
+            let error = run(&tool, args).await.error.unwrap();
+            assert!(error.contains(expected), "{error}");
+        }
+        assert!(!tmp.path().join("reminders.ics").exists());
+
+        let escaping = RemindersTool {
+            path: "../reminders.ics".into(),
+            ..tool
+        };
+        let error = run(&escaping, json!({"action": "list"}))
+            .await
+            .error
+            .unwrap();
+        assert!(error.contains("not allowed by security policy"), "{error}");
+
+        let read_only = RemindersTool::new(
+            Arc::new(SecurityPolicy {
+                workspace_dir: tmp.path().to_path_buf(),
+                autonomy: AutonomyLevel::ReadOnly,
+                ..SecurityPolicy::default()
+            }),
+            &RemindersConfig::default(),
+        );
+        let error = run(&read_only, json!({"action": "add", "summary": "x"}))
+            .await
+            .error
+            .unwrap();
+        assert!(error.contains("read-only"));
+        assert!(run(&read_only, json!({"action": "list"})).await.success);
+    }
+
+    #[test]
+    fn folding_and_escaping_roundtrip() {
+        let long = format!("SUMMARY:{}", "日本語のリマインダー".repeat(10));
+        let mut out = String::new();
+        write_line(&mut out, &long);
+        assert!(out.split("\r\n").all(|line| line.len() <= FOLD_AT));
+        assert_eq!(unfold(&out), vec![(1, long)]);
+
+        let text = "a,b;c\\d\nnext";
+        assert_eq!(escape_text(text), "a\\,b\\;c\\\\d\\nnext");
+        assert_eq!(unescape_text(&escape_text(text)), text);
+
+        let quoted = parse_line("ATTENDEE;CN=\"Doe: John\":mailto:j@example.com").unwrap();
+        assert_eq!(quoted.params, ";CN=\"Doe: John\"");
+        assert_eq!(quoted.value, "mailto:j@example.com");
+
+        assert_eq!(
+            parse_datetime("20300102T093000Z")
+                .map(format_utc)
+                .as_deref(),
+            Some("20300102T093000Z")
+        );
+        assert!(parse_datetime("20300102").is_some());
+        assert!(parse_datetime("next week").is_none());
+    }
+}
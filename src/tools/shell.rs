@@ -1,28 +1,76 @@
 use super::traits::{Tool, ToolResult};
+use crate::runtime::{NativeRuntime, RuntimeAdapter};
+use crate::security::approval::{ApprovalDecision, APPROVE_DESTRUCTIVE_COMMAND};
 use crate::security::SecurityPolicy;
 use async_trait::async_trait;
 use serde_json::json;
-use std::sync::Arc;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-/// Maximum shell command execution time before kill.
-const SHELL_TIMEOUT_SECS: u64 = 60;
+/// Shell command timeout when `autonomy.shell_timeout_secs` is not set.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 120;
+/// Cap on `timeout_seconds` when `autonomy.max_shell_timeout_secs` is not set.
+pub const DEFAULT_MAX_TIMEOUT_SECS: u64 = 600;
+/// How long output is still collected once the command has exited or been
+/// killed, in case a detached child keeps the pipe open.
+const DRAIN_GRACE: Duration = Duration::from_secs(2);
 /// Maximum output size in bytes (1MB).
 const MAX_OUTPUT_BYTES: usize = 1_048_576;
 /// Environment variables safe to pass to shell commands.
 /// Only functional variables are included — never API keys or secrets.
-const SAFE_ENV_VARS: &[&str] = &[
+pub(super) const SAFE_ENV_VARS: &[&str] = &[
     "PATH", "HOME", "TERM", "LANG", "LC_ALL", "LC_CTYPE", "USER", "SHELL", "TMPDIR",
 ];
+/// Variables the `env` parameter may not set: they change which programs
+/// run or what they load, getting around the command policy.
+const DENIED_ENV_VARS: &[&str] = &[
+    "PATH",
+    "LD_PRELOAD",
+    "LD_LIBRARY_PATH",
+    "LD_AUDIT",
+    "DYLD_INSERT_LIBRARIES",
+    "DYLD_LIBRARY_PATH",
+    "BASH_ENV",
+    "ENV",
+    "IFS",
+    "PS4",
+    "SHELLOPTS",
+    "PROMPT_COMMAND",
+];
+/// Name fragments of credential variables, which the `env` parameter may
+/// not set either.
+const SENSITIVE_ENV_MARKERS: &[&str] = &[
+    "KEY",
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "CREDENTIAL",
+    "AUTH",
+];
 
 /// Shell command execution tool with sandboxing
 pub struct ShellTool {
     security: Arc<SecurityPolicy>,
+    runtime: Arc<dyn RuntimeAdapter>,
 }
 
 impl ShellTool {
     pub fn new(security: Arc<SecurityPolicy>) -> Self {
-        Self { security }
+        Self {
+            security,
+            runtime: Arc::new(NativeRuntime::new()),
+        }
+    }
+
+    /// Run commands through `runtime` (e.g. in a container) instead of
+    /// directly on this machine.
+    pub fn with_runtime(mut self, runtime: Arc<dyn RuntimeAdapter>) -> Self {
+        self.runtime = runtime;
+        self
     }
 }
 
@@ -33,7 +81,7 @@ impl Tool for ShellTool {
     }
 
     fn description(&self) -> &str {
-        "Execute a shell command in the workspace directory"
+        "Execute a shell command in the workspace directory (or `cwd` inside it). stdout and stderr are returned together, in the order they were written"
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -43,6 +91,24 @@ impl Tool for ShellTool {
                 "command": {
                     "type": "string",
                     "description": "The shell command to execute"
+                },
+                "cwd": {
+                    "type": "string",
+                    "description": "Directory to run in, relative to the workspace (default: the workspace)"
+                },
+                "timeout_seconds": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": format!(
+                        "Kill the command after this many seconds (default {}, at most {})",
+                        self.security.shell_timeout.as_secs(),
+                        self.security.max_shell_timeout.as_secs()
+                    )
+                },
+                "env": {
+                    "type": "object",
+                    "additionalProperties": {"type": "string"},
+                    "description": "Extra environment variables; credentials and loader variables such as PATH or LD_PRELOAD are refused"
                 }
             },
             "required": ["command"]
@@ -54,79 +120,292 @@ impl Tool for ShellTool {
             .get("command")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing 'command' parameter"))?;
+        let timeout = self.timeout(args.get("timeout_seconds"))?;
+        let env = match extra_env(args.get("env")) {
+            Ok(env) => env,
+            Err(error) => return Ok(failed(error)),
+        };
+        let cwd = match self.working_dir(args.get("cwd")).await {
+            Ok(cwd) => cwd,
+            Err(error) => return Ok(failed(error)),
+        };
+
+        // Security check: validate command against allowlist and flag
+        // destructive commands, asking the user (supervised mode) before
+        // refusing
+        let violation = self.security.command_violation(command);
+        let danger = self.security.destructive_reason(command);
+        let mut approved = false;
+        if violation.is_some() || danger.is_some() {
+            let refusal = match self
+                .security
+                .request_approval(command, danger.clone())
+                .await
+            {
+                Some(ApprovalDecision::Approved | ApprovalDecision::Always) => None,
+                Some(ApprovalDecision::Denied) => {
+                    Some(format!("Command denied by the user: {command}"))
+                }
+                Some(ApprovalDecision::TimedOut) => Some(format!(
+                    "Command not run: no approval received within {}s: {command}",
+                    self.security.approvals.timeout().as_secs()
+                )),
+                None => Some(if let Some(violation) = violation {
+                    format!("Command not permitted by policy: {command} ({violation})")
+                } else {
+                    format!(
+                        "Confirmation required: `{command}` looks destructive ({}) and was not run. Ask the user to confirm; in a chat session they can send {APPROVE_DESTRUCTIVE_COMMAND}.",
+                        danger.unwrap_or_default()
+                    )
+                }),
+            };
+            if let Some(error) = refusal {
+                return Ok(failed(error));
+            }
+            approved = true;
+        }
 
-        // Security check: validate command against allowlist
-        if !self.security.is_command_allowed(command) {
-            return Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!("Command not allowed by security policy: {command}")),
-            });
+        let cmd = self.runtime.shell_command(command, &cwd, &env, timeout);
+        let (mut output, status) = match run(cmd, timeout).await {
+            Ok(run) => run,
+            Err(e) => return Ok(failed(format!("Failed to execute command: {e}"))),
+        };
+        if approved {
+            output.insert_str(0, "[Command approved by the user]\n");
         }
+        Ok(finish(output, status, timeout))
+    }
+}
 
-        // Execute with timeout to prevent hanging commands.
-        // Clear the environment to prevent leaking API keys and other secrets
-        // (CWE-200), then re-add only safe, functional variables.
-        let mut cmd = tokio::process::Command::new("sh");
-        cmd.arg("-c")
-            .arg(command)
-            .current_dir(&self.security.workspace_dir)
-            .env_clear();
-
-        for var in SAFE_ENV_VARS {
-            if let Ok(val) = std::env::var(var) {
-                cmd.env(var, val);
-            }
+impl ShellTool {
+    /// The call's `timeout_seconds`, capped at the configured maximum.
+    fn timeout(&self, requested: Option<&serde_json::Value>) -> anyhow::Result<Duration> {
+        let Some(requested) = requested.filter(|v| !v.is_null()) else {
+            return Ok(self.security.shell_timeout);
+        };
+        let secs = requested
+            .as_u64()
+            .filter(|secs| *secs > 0)
+            .ok_or_else(|| anyhow::anyhow!("'timeout_seconds' must be a positive integer"))?;
+        Ok(Duration::from_secs(secs).min(self.security.max_shell_timeout))
+    }
+
+    /// The directory to run in: the workspace, or `cwd` once it passes the
+    /// path policy (and, with `workspace_only`, resolves inside the
+    /// workspace).
+    async fn working_dir(&self, cwd: Option<&serde_json::Value>) -> Result<PathBuf, String> {
+        let Some(cwd) = cwd.filter(|v| !v.is_null()) else {
+            return Ok(self.security.workspace_dir.clone());
+        };
+        let cwd = cwd
+            .as_str()
+            .ok_or_else(|| "'cwd' must be a string".to_string())?;
+        if let Some(reason) = self.security.path_violation(cwd) {
+            return Err(format!(
+                "Working directory not allowed by security policy: {cwd} ({reason})"
+            ));
         }
 
-        let result =
-            tokio::time::timeout(Duration::from_secs(SHELL_TIMEOUT_SECS), cmd.output()).await;
-
-        match result {
-            Ok(Ok(output)) => {
-                let mut stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let mut stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-                // Truncate output to prevent OOM
-                if stdout.len() > MAX_OUTPUT_BYTES {
-                    let mut end = MAX_OUTPUT_BYTES;
-                    while end > 0 && !stdout.is_char_boundary(end) {
-                        end -= 1;
-                    }
-                    stdout.truncate(end);
-                    stdout.push_str("\n... [output truncated at 1MB]");
-                }
-                if stderr.len() > MAX_OUTPUT_BYTES {
-                    let mut end = MAX_OUTPUT_BYTES;
-                    while end > 0 && !stderr.is_char_boundary(end) {
-                        end -= 1;
-                    }
-                    stderr.truncate(end);
-                    stderr.push_str("\n... [stderr truncated at 1MB]");
-                }
+        let resolved = tokio::fs::canonicalize(self.security.workspace_dir.join(cwd))
+            .await
+            .map_err(|e| format!("Failed to resolve working directory {cwd}: {e}"))?;
+        // Outside the workspace only without `workspace_only`, and never on
+        // the forbidden list
+        let allowed = self.security.is_resolved_path_allowed(&resolved)
+            || (!self.security.workspace_only
+                && self.security.is_path_allowed(&resolved.to_string_lossy()));
+        if !allowed {
+            return Err(format!(
+                "Working directory not allowed by security policy: {cwd} resolves to {}",
+                resolved.display()
+            ));
+        }
+        if !resolved.is_dir() {
+            return Err(format!("Working directory is not a directory: {cwd}"));
+        }
+        Ok(resolved)
+    }
+}
 
-                Ok(ToolResult {
-                    success: output.status.success(),
-                    output: stdout,
-                    error: if stderr.is_empty() {
-                        None
-                    } else {
-                        Some(stderr)
-                    },
-                })
+/// The `env` parameter as name/value pairs, refusing denied and credential
+/// names.
+fn extra_env(env: Option<&serde_json::Value>) -> Result<Vec<(String, String)>, String> {
+    let Some(env) = env.filter(|v| !v.is_null()) else {
+        return Ok(Vec::new());
+    };
+    let env = env
+        .as_object()
+        .ok_or_else(|| "'env' must be an object of strings".to_string())?;
+    env.iter()
+        .map(|(name, value)| {
+            let value = value
+                .as_str()
+                .ok_or_else(|| format!("'env.{name}' must be a string"))?;
+            let valid = name
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid {
+                return Err(format!("Invalid environment variable name: {name}"));
             }
-            Ok(Err(e)) => Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!("Failed to execute command: {e}")),
-            }),
-            Err(_) => Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!(
-                    "Command timed out after {SHELL_TIMEOUT_SECS}s and was killed"
-                )),
-            }),
+            let upper = name.to_ascii_uppercase();
+            if DENIED_ENV_VARS.contains(&upper.as_str())
+                || SENSITIVE_ENV_MARKERS.iter().any(|m| upper.contains(m))
+            {
+                return Err(format!(
+                    "Environment variable not allowed by security policy: {name}"
+                ));
+            }
+            Ok((name.clone(), value.to_string()))
+        })
+        .collect()
+}
+
+pub(super) fn failed(error: String) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(error),
+    }
+}
+
+/// The result of a [`run`]: the output on success, otherwise the output
+/// followed by why the command failed.
+pub(super) fn finish(output: String, status: Option<ExitStatus>, timeout: Duration) -> ToolResult {
+    let marker = match status {
+        Some(status) if status.success() => {
+            return ToolResult {
+                success: true,
+                output,
+                error: None,
+            };
+        }
+        Some(status) => format!("[Command failed: {status}]"),
+        None => format!(
+            "[Command timed out after {}s and was killed]",
+            timeout.as_secs()
+        ),
+    };
+    // A failed result is shown to the model through `error` alone
+    let error = if output.trim().is_empty() {
+        marker
+    } else {
+        format!("{}\n{marker}", output.trim_end())
+    };
+    ToolResult {
+        success: false,
+        output,
+        error: Some(error),
+    }
+}
+
+/// `program` set up to run in `cwd` with only [`SAFE_ENV_VARS`] in its
+/// environment.
+pub(crate) fn sandboxed_command(program: &str, cwd: &Path) -> tokio::process::Command {
+    // Clear the environment to prevent leaking API keys and other secrets
+    // (CWE-200), then re-add only safe, functional variables.
+    let mut cmd = tokio::process::Command::new(program);
+    cmd.current_dir(cwd)
+        .env_clear()
+        // A cancelled agent run (e.g. a timed-out heartbeat task) must
+        // not leave the command running
+        .kill_on_drop(true);
+    // Its own process group, so a timeout also kills what it started
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    for var in SAFE_ENV_VARS {
+        if let Ok(val) = std::env::var(var) {
+            cmd.env(var, val);
+        }
+    }
+    cmd
+}
+
+/// Run `cmd` with stdout and stderr on one pipe, so the output keeps the
+/// order it was written in. Returns the output and the exit status, or
+/// `None` when the command timed out and its process group was killed.
+pub(super) async fn run(
+    mut cmd: tokio::process::Command,
+    timeout: Duration,
+) -> std::io::Result<(String, Option<ExitStatus>)> {
+    let (reader, writer) = std::io::pipe()?;
+    cmd.stdin(Stdio::null())
+        .stdout(writer.try_clone()?)
+        .stderr(writer);
+
+    let mut child = cmd.spawn()?;
+    // Drop our copies of the write end so the pipe closes when the
+    // command's processes exit
+    drop(cmd);
+    let mut group = ProcessGroup(child.id());
+
+    let captured = Arc::new(Mutex::new(Captured::default()));
+    let drain = tokio::task::spawn_blocking({
+        let captured = captured.clone();
+        move || drain(reader, &captured)
+    });
+
+    let status = if let Ok(status) = tokio::time::timeout(timeout, child.wait()).await {
+        // Background jobs the command started are left running
+        group.0 = None;
+        Some(status?)
+    } else {
+        drop(group);
+        let _ = child.start_kill();
+        let _ = child.wait().await;
+        None
+    };
+    let _ = tokio::time::timeout(DRAIN_GRACE, drain).await;
+
+    let captured = captured
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let mut output = String::from_utf8_lossy(&captured.bytes).into_owned();
+    if captured.truncated {
+        output.push_str("\n... [output truncated at 1MB]");
+    }
+    Ok((output, status))
+}
+
+/// Command output collected so far, capped at [`MAX_OUTPUT_BYTES`].
+#[derive(Default)]
+struct Captured {
+    bytes: Vec<u8>,
+    truncated: bool,
+}
+
+/// Read the pipe to EOF, keeping what fits. Reading continues past the cap
+/// so the command never blocks on a full pipe.
+fn drain(mut reader: std::io::PipeReader, captured: &Mutex<Captured>) {
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => return,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => return,
+        };
+        let mut captured = captured
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let room = MAX_OUTPUT_BYTES.saturating_sub(captured.bytes.len());
+        captured.bytes.extend_from_slice(&buf[..n.min(room)]);
+        captured.truncated |= n > room;
+    }
+}
+
+/// Kills the command's process group when dropped, unless its id has been
+/// cleared.
+struct ProcessGroup(Option<u32>);
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pid) = self.0 {
+            #[allow(clippy::cast_possible_wrap)]
+            let _ = unsafe { libc::killpg(pid as libc::pid_t, libc::SIGKILL) };
         }
     }
 }
@@ -184,7 +463,147 @@ mod tests {
         let tool = ShellTool::new(test_security(AutonomyLevel::Supervised));
         let result = tool.execute(json!({"command": "rm -rf /"})).await.unwrap();
         assert!(!result.success);
-        assert!(result.error.as_ref().unwrap().contains("not allowed"));
+        assert!(result
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("not permitted by policy"));
+    }
+
+    #[tokio::test]
+    async fn shell_denylist_mode_refuses_matching_commands() {
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Full,
+            workspace_dir: std::env::temp_dir(),
+            commands: crate::security::commands::CommandPolicy::new(
+                crate::security::CommandPolicyMode::Deny,
+                &["rm *".into(), "^curl ".into()],
+            )
+            .unwrap(),
+            ..SecurityPolicy::default()
+        });
+        let tool = ShellTool::new(security);
+
+        let result = tool
+            .execute(json!({"command": "printf ok && rm -rf target"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(error.contains("not permitted by policy"), "{error}");
+        assert!(error.contains("denied pattern `rm *`"), "{error}");
+
+        let result = tool.execute(json!({"command": "printf ok"})).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "ok");
+    }
+
+    /// Answer every approval request with `reply`.
+    fn answer_approvals(security: &SecurityPolicy, reply: ApprovalDecision) {
+        let mut requests = security.approvals.attach();
+        tokio::spawn(async move {
+            while let Some(request) = requests.recv().await {
+                request.respond(reply);
+            }
+        });
+    }
+
+    #[tokio::test]
+    async fn shell_runs_command_approved_by_user() {
+        let security = test_security(AutonomyLevel::Supervised);
+        answer_approvals(&security, ApprovalDecision::Approved);
+        let tool = ShellTool::new(security.clone());
+
+        let result = tool
+            .execute(json!({"command": "printf approved"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "[Command approved by the user]\napproved");
+        assert!(
+            !security.is_command_allowed("printf again"),
+            "one-off approval"
+        );
+    }
+
+    #[tokio::test]
+    async fn shell_always_approval_extends_allowlist() {
+        let security = test_security(AutonomyLevel::Supervised);
+        answer_approvals(&security, ApprovalDecision::Always);
+        let tool = ShellTool::new(security.clone());
+
+        let result = tool.execute(json!({"command": "printf x"})).await.unwrap();
+        assert!(result.success);
+        assert!(security.is_command_allowed("printf y | wc -c"));
+    }
+
+    #[tokio::test]
+    async fn shell_reports_denied_and_timed_out_approvals() {
+        let security = test_security(AutonomyLevel::Supervised);
+        answer_approvals(&security, ApprovalDecision::Denied);
+        let tool = ShellTool::new(security);
+        let result = tool.execute(json!({"command": "rm -rf /"})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("denied by the user"));
+
+        let security = Arc::new(SecurityPolicy {
+            workspace_dir: std::env::temp_dir(),
+            approvals: crate::security::approval::ApprovalGate::new(Duration::from_millis(20)),
+            ..SecurityPolicy::default()
+        });
+        let _unanswered = security.approvals.attach();
+        let tool = ShellTool::new(security);
+        let result = tool.execute(json!({"command": "rm -rf /"})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("no approval received"));
+    }
+
+    #[tokio::test]
+    async fn shell_asks_before_destructive_commands() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let security = Arc::new(SecurityPolicy {
+            workspace_dir: workspace.path().to_path_buf(),
+            commands: crate::security::commands::CommandPolicy::allow_names(&["rm", "printf"]),
+            ..SecurityPolicy::default()
+        });
+        std::fs::create_dir(workspace.path().join("build")).unwrap();
+        let tool = ShellTool::new(security.clone());
+
+        // Unflagged: runs without asking
+        let result = tool.execute(json!({"command": "printf ok"})).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "ok");
+
+        // Flagged, nobody to ask: refused and left alone
+        let result = tool
+            .execute(json!({"command": "rm -rf build"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(error.contains("Confirmation required"), "{error}");
+        assert!(error.contains(APPROVE_DESTRUCTIVE_COMMAND), "{error}");
+        assert!(workspace.path().join("build").exists());
+
+        // A chat session that approved destructive commands runs it
+        let session = crate::security::approval::SessionApproval::default();
+        session.grant();
+        let result = session
+            .scope(tool.execute(json!({"command": "rm -rf build"})))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(!workspace.path().join("build").exists());
+
+        // Confirmed at the prompt
+        std::fs::create_dir(workspace.path().join("build")).unwrap();
+        answer_approvals(&security, ApprovalDecision::Approved);
+        let result = tool
+            .execute(json!({"command": "rm -rf build"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(result.output.starts_with("[Command approved by the user]"));
     }
 
     #[tokio::test]
@@ -192,7 +611,7 @@ mod tests {
         let tool = ShellTool::new(test_security(AutonomyLevel::ReadOnly));
         let result = tool.execute(json!({"command": "ls"})).await.unwrap();
         assert!(!result.success);
-        assert!(result.error.as_ref().unwrap().contains("not allowed"));
+        assert!(result.error.as_ref().unwrap().contains("read-only"));
     }
 
     #[tokio::test]
@@ -224,7 +643,7 @@ mod tests {
         Arc::new(SecurityPolicy {
             autonomy: AutonomyLevel::Supervised,
             workspace_dir: std::env::temp_dir(),
-            allowed_commands: vec!["env".into(), "echo".into()],
+            commands: crate::security::commands::CommandPolicy::allow_names(&["env", "echo"]),
             ..SecurityPolicy::default()
         })
     }
@@ -239,7 +658,7 @@ mod tests {
     impl EnvGuard {
         fn set(key: &'static str, value: &str) -> Self {
             let original = std::env::var(key).ok();
-            std::env::set_var(key, value);
+            unsafe { std::env::set_var(key, value) };
             Self { key, original }
         }
     }
@@ -247,8 +666,8 @@ mod tests {
     impl Drop for EnvGuard {
         fn drop(&mut self) {
             match &self.original {
-                Some(val) => std::env::set_var(self.key, val),
-                None => std::env::remove_var(self.key),
+                Some(val) => unsafe { std::env::set_var(self.key, val) },
+                None => unsafe { std::env::remove_var(self.key) },
             }
         }
     }
@@ -295,4 +714,129 @@ mod tests {
             "PATH should be available in shell"
         );
     }
+
+    fn test_security_in(workspace: &Path, commands: &[&str]) -> Arc<SecurityPolicy> {
+        Arc::new(SecurityPolicy {
+            workspace_dir: workspace.to_path_buf(),
+            commands: crate::security::commands::CommandPolicy::allow_names(commands),
+            ..SecurityPolicy::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn shell_timeout_kills_the_command() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let tool = ShellTool::new(test_security_in(workspace.path(), &["echo", "sleep"]));
+
+        let started = std::time::Instant::now();
+        let result = tool
+            .execute(json!({"command": "echo started; sleep 30", "timeout_seconds": 1}))
+            .await
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(!result.success);
+        assert_eq!(result.output, "started\n");
+        assert_eq!(
+            result.error.unwrap(),
+            "started\n[Command timed out after 1s and was killed]"
+        );
+    }
+
+    #[test]
+    fn shell_timeout_is_capped_and_validated() {
+        let security = Arc::new(SecurityPolicy {
+            max_shell_timeout: Duration::from_mins(5),
+            ..SecurityPolicy::default()
+        });
+        let tool = ShellTool::new(security);
+        assert_eq!(tool.timeout(None).unwrap(), Duration::from_mins(2));
+        assert_eq!(
+            tool.timeout(Some(&json!(5))).unwrap(),
+            Duration::from_secs(5)
+        );
+        assert_eq!(
+            tool.timeout(Some(&json!(3600))).unwrap(),
+            Duration::from_mins(5)
+        );
+        assert!(tool.timeout(Some(&json!(0))).is_err());
+        assert!(tool.timeout(Some(&json!("10"))).is_err());
+    }
+
+    #[tokio::test]
+    async fn shell_runs_in_cwd_inside_the_workspace() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(workspace.path().join("app")).unwrap();
+        let tool = ShellTool::new(test_security_in(workspace.path(), &["pwd"]));
+
+        let result = tool
+            .execute(json!({"command": "pwd", "cwd": "app"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(
+            result.output.trim_end().ends_with("/app"),
+            "{}",
+            result.output
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn shell_rejects_cwd_outside_the_workspace() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let outside = tempfile::TempDir::new().unwrap();
+        std::os::unix::fs::symlink(outside.path(), workspace.path().join("link")).unwrap();
+        let tool = ShellTool::new(test_security_in(workspace.path(), &["pwd"]));
+
+        for cwd in ["/", "..", "link"] {
+            let result = tool
+                .execute(json!({"command": "pwd", "cwd": cwd}))
+                .await
+                .unwrap();
+            assert!(!result.success, "{cwd} was accepted");
+            let error = result.error.unwrap();
+            assert!(error.contains("not allowed by security policy"), "{error}");
+        }
+    }
+
+    #[tokio::test]
+    async fn shell_interleaves_stdout_and_stderr() {
+        let tool = ShellTool::new(test_security(AutonomyLevel::Supervised));
+        let result = tool
+            .execute(json!({"command": "echo first; ls /nonexistent_dir_xyz; echo last"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        let lines: Vec<&str> = result.output.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", result.output);
+        assert_eq!(lines[0], "first");
+        assert!(lines[1].contains("nonexistent_dir_xyz"), "{}", lines[1]);
+        assert_eq!(lines[2], "last");
+    }
+
+    #[tokio::test]
+    async fn shell_env_adds_variables_but_refuses_sensitive_ones() {
+        let tool = ShellTool::new(test_security_with_env_cmd());
+        let result = tool
+            .execute(json!({"command": "echo $NODE_ENV", "env": {"NODE_ENV": "test"}}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "test\n");
+
+        for name in [
+            "PATH",
+            "LD_PRELOAD",
+            "OPENAI_API_KEY",
+            "github_token",
+            "1BAD",
+        ] {
+            let result = tool
+                .execute(json!({"command": "echo hi", "env": {name: "x"}}))
+                .await
+                .unwrap();
+            assert!(!result.success, "{name} was accepted");
+            assert!(result.error.unwrap().contains(name));
+        }
+    }
 }
@@ -0,0 +1,323 @@
+use super::shell::{failed, finish, run};
+use super::traits::{Tool, ToolResult};
+use crate::runtime::{NativeRuntime, RuntimeAdapter};
+use crate::security::SecurityPolicy;
+use crate::skills::{Skill, SkillEntrypoint};
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Runs a skill's `[entrypoint]` script with the model's arguments, in the
+/// same sandbox as the shell tool: the interpreter must pass the command
+/// policy, the script runs through the configured runtime with a minimal
+/// environment, and the run is bounded by `autonomy.shell_timeout_secs`.
+pub struct SkillScriptTool {
+    security: Arc<SecurityPolicy>,
+    runtime: Arc<dyn RuntimeAdapter>,
+    name: String,
+    skill: String,
+    description: String,
+    dir: PathBuf,
+    entrypoint: SkillEntrypoint,
+}
+
+impl SkillScriptTool {
+    /// The tool for `skill`'s entry point, if it has one.
+    pub fn new(security: Arc<SecurityPolicy>, skill: &Skill) -> Option<Self> {
+        Some(Self {
+            security,
+            runtime: Arc::new(NativeRuntime::new()),
+            name: skill.tool_name(),
+            skill: skill.name.clone(),
+            description: skill.description.clone(),
+            dir: skill.dir()?.to_path_buf(),
+            entrypoint: skill.entrypoint.clone()?,
+        })
+    }
+
+    /// Run the script through `runtime` instead of directly on this machine.
+    pub fn with_runtime(mut self, runtime: Arc<dyn RuntimeAdapter>) -> Self {
+        self.runtime = runtime;
+        self
+    }
+
+    /// The `sh -c` line running the entry point script.
+    fn command_line(&self) -> Result<String, String> {
+        let interpreter = &self.entrypoint.interpreter;
+        let script = self.dir.join(&self.entrypoint.script);
+        let quote = |word: &str| {
+            shlex::try_quote(word)
+                .map(std::borrow::Cow::into_owned)
+                .map_err(|e| format!("Cannot run skill {} with `{interpreter}`: {e}", self.skill))
+        };
+        Ok(format!(
+            "{} {}",
+            quote(interpreter)?,
+            quote(&script.to_string_lossy())?
+        ))
+    }
+
+    /// The arguments as `SKILL_ARG_<NAME>` variables, checked against the
+    /// declared names and types.
+    fn variables(&self, args: &Map<String, Value>) -> Result<Vec<(String, String)>, String> {
+        if let Some(unknown) = args
+            .keys()
+            .find(|name| !self.entrypoint.args.iter().any(|a| &a.name == *name))
+        {
+            return Err(format!("Unknown argument '{unknown}'"));
+        }
+
+        let mut variables = Vec::new();
+        for arg in &self.entrypoint.args {
+            let value = match args.get(&arg.name) {
+                None | Some(Value::Null) if arg.required => {
+                    return Err(format!("Missing '{}' argument", arg.name));
+                }
+                None | Some(Value::Null) => continue,
+                Some(value) => value,
+            };
+            let text = match (arg.kind.as_str(), value) {
+                ("string", Value::String(s)) => s.clone(),
+                ("integer", Value::Number(n)) if n.is_i64() || n.is_u64() => n.to_string(),
+                ("number", Value::Number(n)) => n.to_string(),
+                ("boolean", Value::Bool(b)) => b.to_string(),
+                (kind, _) => return Err(format!("'{}' must be of type {kind}", arg.name)),
+            };
+            variables.push((format!("SKILL_ARG_{}", arg.name.to_ascii_uppercase()), text));
+        }
+        Ok(variables)
+    }
+}
+
+#[async_trait]
+impl Tool for SkillScriptTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> Value {
+        let properties: Map<String, Value> = self
+            .entrypoint
+            .args
+            .iter()
+            .map(|arg| {
+                let mut property = json!({ "type": arg.kind });
+                if !arg.description.is_empty() {
+                    property["description"] = json!(arg.description);
+                }
+                (arg.name.clone(), property)
+            })
+            .collect();
+        let required: Vec<&str> = self
+            .entrypoint
+            .args
+            .iter()
+            .filter(|arg| arg.required)
+            .map(|arg| arg.name.as_str())
+            .collect();
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false
+        })
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        let empty = Map::new();
+        let args = match &args {
+            Value::Object(args) => args,
+            Value::Null => &empty,
+            _ => anyhow::bail!("Arguments must be an object"),
+        };
+        let variables = match self.variables(args) {
+            Ok(variables) => variables,
+            Err(error) => return Ok(failed(error)),
+        };
+
+        let interpreter = &self.entrypoint.interpreter;
+        if let Some(violation) = self.security.command_violation(interpreter) {
+            return Ok(failed(format!(
+                "Skill {} not allowed by security policy: its interpreter `{interpreter}` is refused ({violation})",
+                self.skill
+            )));
+        }
+
+        let command = match self.command_line() {
+            Ok(command) => command,
+            Err(error) => return Ok(failed(error)),
+        };
+        let mut env = vec![
+            (
+                "SKILL_DIR".to_string(),
+                self.dir.to_string_lossy().into_owned(),
+            ),
+            (
+                "SKILL_ARGS".to_string(),
+                Value::Object(args.clone()).to_string(),
+            ),
+        ];
+        env.extend(variables);
+        let timeout = self.security.shell_timeout;
+        let cmd = self.runtime.shell_command(
+            &command,
+            &self.security.workspace_dir,
+            &env,
+            timeout,
+        );
+        match run(cmd, timeout).await {
+            Ok((output, status)) => Ok(finish(output, status, timeout)),
+            Err(e) => Ok(failed(format!(
+                "Failed to run skill {} with `{interpreter}`: {e}",
+                self.skill
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::commands::CommandPolicy;
+    use std::path::Path;
+
+    /// The sample skill shipped in `examples/skills`.
+    fn sample_skill() -> Skill {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/skills/text-stats");
+        crate::skills::load_skill_dir(&dir).unwrap()
+    }
+
+    fn security(workspace: &Path, commands: &[&str]) -> Arc<SecurityPolicy> {
+        Arc::new(SecurityPolicy {
+            workspace_dir: workspace.to_path_buf(),
+            commands: CommandPolicy::allow_names(commands),
+            ..SecurityPolicy::default()
+        })
+    }
+
+    #[test]
+    fn schema_follows_the_manifest() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let tool =
+            SkillScriptTool::new(security(workspace.path(), &["sh"]), &sample_skill()).unwrap();
+        assert_eq!(tool.name(), "skill_text_stats");
+        let schema = tool.parameters_schema();
+        assert_eq!(schema["properties"]["text"]["type"], "string");
+        assert_eq!(schema["properties"]["top"]["type"], "integer");
+        assert_eq!(schema["required"], json!(["text"]));
+    }
+
+    #[tokio::test]
+    async fn runs_the_sample_skill() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let tool =
+            SkillScriptTool::new(security(workspace.path(), &["sh"]), &sample_skill()).unwrap();
+
+        let result = tool
+            .execute(json!({"text": "the cat saw the other cat", "top": 2}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            result.output,
+            "lines: 1\nwords: 6\ntop words:\n  cat (2)\n  the (2)\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn checks_arguments_before_running() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let tool =
+            SkillScriptTool::new(security(workspace.path(), &["sh"]), &sample_skill()).unwrap();
+
+        for (args, error) in [
+            (json!({}), "Missing 'text' argument"),
+            (json!({"text": 3}), "'text' must be of type string"),
+            (
+                json!({"text": "a", "top": 1.5}),
+                "'top' must be of type integer",
+            ),
+            (
+                json!({"text": "a", "lang": "en"}),
+                "Unknown argument 'lang'",
+            ),
+        ] {
+            let result = tool.execute(args).await.unwrap();
+            assert!(!result.success);
+            assert_eq!(result.error.unwrap(), error);
+        }
+        assert!(tool.execute(json!("text")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn interpreter_must_pass_the_command_policy() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let tool =
+            SkillScriptTool::new(security(workspace.path(), &["echo"]), &sample_skill()).unwrap();
+        let result = tool.execute(json!({"text": "hi"})).await.unwrap();
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(error.contains("not allowed by security policy"), "{error}");
+    }
+
+    /// Runs natively, remembering every command line it was given.
+    #[derive(Default)]
+    struct RecordingRuntime(std::sync::Mutex<Vec<String>>);
+
+    impl RuntimeAdapter for RecordingRuntime {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn has_shell_access(&self) -> bool {
+            true
+        }
+
+        fn has_filesystem_access(&self) -> bool {
+            true
+        }
+
+        fn storage_path(&self) -> PathBuf {
+            PathBuf::from(".")
+        }
+
+        fn supports_long_running(&self) -> bool {
+            false
+        }
+
+        fn shell_command(
+            &self,
+            command: &str,
+            cwd: &Path,
+            env: &[(String, String)],
+            timeout: std::time::Duration,
+        ) -> tokio::process::Command {
+            self.0.lock().unwrap().push(command.to_string());
+            NativeRuntime::new().shell_command(command, cwd, env, timeout)
+        }
+    }
+
+    #[tokio::test]
+    async fn runs_through_the_configured_runtime() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let skill = sample_skill();
+        let runtime = Arc::new(RecordingRuntime::default());
+        let tool = SkillScriptTool::new(security(workspace.path(), &["sh"]), &skill)
+            .unwrap()
+            .with_runtime(runtime.clone());
+
+        let result = tool.execute(json!({"text": "a b a"})).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.starts_with("lines: 1\nwords: 3\n"));
+        let script = skill.dir().unwrap().join(&skill.entrypoint.as_ref().unwrap().script);
+        assert_eq!(
+            *runtime.0.lock().unwrap(),
+            [format!("sh {}", shlex::try_quote(&script.to_string_lossy()).unwrap())]
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use super::traits::{Tool, ToolResult};
+use crate::security::SecurityPolicy;
+use crate::tasks;
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Let the agent add a task to the workspace task list. Tasks are recorded
+/// as coming from the policy's audit origin.
+pub struct TaskAddTool {
+    security: Arc<SecurityPolicy>,
+}
+
+impl TaskAddTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+}
+
+#[async_trait]
+impl Tool for TaskAddTool {
+    fn name(&self) -> &str {
+        "task_add"
+    }
+
+    fn description(&self) -> &str {
+        "Add a task to the user's task list, optionally with a due time. Use this instead of writing open loops into memory files."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "title": {
+                    "type": "string",
+                    "description": "Short description of what needs doing"
+                },
+                "due": {
+                    "type": "string",
+                    "description": "Optional due time: 'YYYY-MM-DD' (end of that day), 'YYYY-MM-DD HH:MM' in local time, or RFC 3339"
+                },
+                "notes": {
+                    "type": "string",
+                    "description": "Optional details or context"
+                }
+            },
+            "required": ["title"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let title = args
+            .get("title")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'title' parameter"))?;
+        let notes = args.get("notes").and_then(|v| v.as_str());
+
+        let due = match args.get("due").and_then(|v| v.as_str()) {
+            Some(raw) => match tasks::parse_due(raw) {
+                Ok(due) => Some(due),
+                Err(_) => {
+                    return Ok(ToolResult {
+                        success: false,
+                        output: String::new(),
+                        error: Some(format!(
+                            "Invalid due time '{raw}': use YYYY-MM-DD, YYYY-MM-DD HH:MM or RFC 3339"
+                        )),
+                    });
+                }
+            },
+            None => None,
+        };
+
+        let origin = match self.security.audit.origin() {
+            "" => "agent",
+            origin => origin,
+        };
+        match tasks::add_task(&self.security.workspace_dir, title, due, notes, origin) {
+            Ok(task) => Ok(ToolResult {
+                success: true,
+                output: format!("Added task {}", task.summary()),
+                error: None,
+            }),
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Failed to add task: {e}")),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn security(workspace: &Path) -> Arc<SecurityPolicy> {
+        Arc::new(SecurityPolicy {
+            workspace_dir: workspace.to_path_buf(),
+            ..SecurityPolicy::default()
+        })
+    }
+
+    #[test]
+    fn name_and_schema() {
+        let tool = TaskAddTool::new(security(Path::new("/tmp")));
+        assert_eq!(tool.name(), "task_add");
+        let schema = tool.parameters_schema();
+        assert!(schema["properties"]["due"].is_object());
+        assert_eq!(schema["required"], json!(["title"]));
+    }
+
+    #[tokio::test]
+    async fn adds_with_the_policy_origin() {
+        let tmp = TempDir::new().unwrap();
+        let policy = SecurityPolicy {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        };
+        let tool = TaskAddTool::new(Arc::new(policy.with_origin("gateway")));
+        let result = tool
+            .execute(json!({"title": "Renew passport", "due": "2030-01-02"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result
+            .output
+            .starts_with("Added task #1 Renew passport (due 2030-01-02"));
+
+        let task = &tasks::list_tasks(tmp.path(), false).unwrap()[0];
+        assert_eq!(task.created_from, "gateway");
+        assert!(task.due.is_some());
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_input() {
+        let tmp = TempDir::new().unwrap();
+        let tool = TaskAddTool::new(security(tmp.path()));
+        assert!(tool.execute(json!({})).await.is_err());
+
+        let result = tool
+            .execute(json!({"title": "x", "due": "soon"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Invalid due time 'soon'"));
+        assert!(tasks::list_tasks(tmp.path(), true).unwrap().is_empty());
+    }
+}
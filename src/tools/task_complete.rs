@@ -0,0 +1,103 @@
+use super::traits::{Tool, ToolResult};
+use crate::security::SecurityPolicy;
+use crate::tasks;
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Let the agent mark a task on the workspace task list as done
+pub struct TaskCompleteTool {
+    security: Arc<SecurityPolicy>,
+}
+
+impl TaskCompleteTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+}
+
+#[async_trait]
+impl Tool for TaskCompleteTool {
+    fn name(&self) -> &str {
+        "task_complete"
+    }
+
+    fn description(&self) -> &str {
+        "Mark a task as done by its id (see task_list)."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "integer",
+                    "description": "The task id, e.g. 3 for #3"
+                }
+            },
+            "required": ["id"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let id = args
+            .get("id")
+            .and_then(serde_json::Value::as_i64)
+            .ok_or_else(|| anyhow::anyhow!("Missing 'id' parameter"))?;
+
+        match tasks::complete_task(&self.security.workspace_dir, id) {
+            Ok(task) => Ok(ToolResult {
+                success: true,
+                output: format!("Completed task {}", task.summary()),
+                error: None,
+            }),
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Failed to complete task #{id}: {e}")),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::TaskStatus;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn security(workspace: &Path) -> Arc<SecurityPolicy> {
+        Arc::new(SecurityPolicy {
+            workspace_dir: workspace.to_path_buf(),
+            ..SecurityPolicy::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn completes_by_id() {
+        let tmp = TempDir::new().unwrap();
+        let task = tasks::add_task(tmp.path(), "Water plants", None, None, "cli").unwrap();
+        let tool = TaskCompleteTool::new(security(tmp.path()));
+        assert_eq!(tool.name(), "task_complete");
+
+        let result = tool.execute(json!({"id": task.id})).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "Completed task #1 Water plants");
+        let stored = &tasks::list_tasks(tmp.path(), true).unwrap()[0];
+        assert_eq!(stored.status, TaskStatus::Done);
+    }
+
+    #[tokio::test]
+    async fn unknown_or_missing_id() {
+        let tmp = TempDir::new().unwrap();
+        let tool = TaskCompleteTool::new(security(tmp.path()));
+        let result = tool.execute(json!({"id": 7})).await.unwrap();
+        assert!(!result.success);
+        assert!(result
+            .error
+            .unwrap()
+            .starts_with("Failed to complete task #7"));
+        assert!(tool.execute(json!({"id": "7"})).await.is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use super::traits::{Tool, ToolResult};
+use crate::security::SecurityPolicy;
+use crate::tasks::{self, TaskStatus};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::json;
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+/// Let the agent read the workspace task list
+pub struct TaskListTool {
+    security: Arc<SecurityPolicy>,
+}
+
+impl TaskListTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+}
+
+#[async_trait]
+impl Tool for TaskListTool {
+    fn name(&self) -> &str {
+        "task_list"
+    }
+
+    fn description(&self) -> &str {
+        "List the user's open tasks with their ids, due times and notes. Overdue tasks are marked."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "include_done": {
+                    "type": "boolean",
+                    "description": "Also list completed tasks (default: false)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let include_done = args
+            .get("include_done")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+
+        let tasks = match tasks::list_tasks(&self.security.workspace_dir, include_done) {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("Failed to list tasks: {e}")),
+                });
+            }
+        };
+        if tasks.is_empty() {
+            return Ok(ToolResult {
+                success: true,
+                output: "No tasks.".into(),
+                error: None,
+            });
+        }
+
+        let now = Utc::now();
+        let mut output = format!("{} task(s):\n", tasks.len());
+        for task in &tasks {
+            let state = match task.status {
+                TaskStatus::Done => "[done] ",
+                TaskStatus::Open if task.is_overdue(now) => "[overdue] ",
+                TaskStatus::Open => "",
+            };
+            let _ = write!(output, "- {state}{}", task.summary());
+            if let Some(notes) = &task.notes {
+                let _ = write!(output, " — {notes}");
+            }
+            output.push('\n');
+        }
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn security(workspace: &Path) -> Arc<SecurityPolicy> {
+        Arc::new(SecurityPolicy {
+            workspace_dir: workspace.to_path_buf(),
+            ..SecurityPolicy::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn empty_list() {
+        let tmp = TempDir::new().unwrap();
+        let tool = TaskListTool::new(security(tmp.path()));
+        assert_eq!(tool.name(), "task_list");
+        let result = tool.execute(json!({})).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "No tasks.");
+    }
+
+    #[tokio::test]
+    async fn marks_overdue_and_done_tasks() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path();
+        let late = Utc::now() - Duration::days(1);
+        tasks::add_task(workspace, "Pay rent", Some(late), Some("landlord"), "cli").unwrap();
+        let done = tasks::add_task(workspace, "Buy milk", None, None, "cli").unwrap();
+        tasks::complete_task(workspace, done.id).unwrap();
+
+        let tool = TaskListTool::new(security(workspace));
+        let open = tool.execute(json!({})).await.unwrap().output;
+        assert!(
+            open.starts_with("1 task(s):\n- [overdue] #1 Pay rent (due "),
+            "{open}"
+        );
+        assert!(open.contains(" — landlord"));
+
+        let all = tool
+            .execute(json!({"include_done": true}))
+            .await
+            .unwrap()
+            .output;
+        assert!(all.contains("- [done] #2 Buy milk\n"), "{all}");
+    }
+}
@@ -0,0 +1,787 @@
+use super::traits::{Tool, ToolResult};
+use crate::config::WebFetchConfig;
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use reqwest::Url;
+use serde_json::{json, Value};
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+const USER_AGENT: &str = concat!(
+    "jarvis/",
+    env!("CARGO_PKG_VERSION"),
+    " (web_fetch tool; +https://github.com/Afee2019/jarvis)"
+);
+
+/// Redirects are followed by hand so every hop gets the host checks.
+const MAX_REDIRECTS: usize = 5;
+
+/// Same rough ratio the cost estimates use.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Fetch a URL and return its readable text.
+pub struct WebFetchTool {
+    security: Arc<SecurityPolicy>,
+    max_bytes: usize,
+    timeout: Duration,
+    max_tokens: usize,
+    allow_private_hosts: bool,
+}
+
+impl WebFetchTool {
+    pub fn new(security: Arc<SecurityPolicy>, config: &WebFetchConfig) -> Self {
+        if config.allow_private_hosts && security.workspace_only {
+            tracing::warn!(
+                "web_fetch.allow_private_hosts 仅在 autonomy.workspace_only = false 时生效，已忽略"
+            );
+        }
+        let allow_private_hosts = config.allow_private_hosts && !security.workspace_only;
+        Self {
+            security,
+            max_bytes: config.max_bytes.max(1),
+            timeout: Duration::from_secs(config.timeout_secs.max(1)),
+            max_tokens: config.max_tokens.max(1),
+            allow_private_hosts,
+        }
+    }
+
+    /// Refuse anything but HTTP(S), and — unless allowed — hosts that are or
+    /// resolve to loopback or private-network addresses.
+    async fn check_url(&self, url: &Url) -> Result<(), String> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!(
+                "Only http:// and https:// URLs can be fetched (got {}:)",
+                url.scheme()
+            ));
+        }
+        let Some(host) = url.host_str() else {
+            return Err(format!("URL has no host: {url}"));
+        };
+        if self.allow_private_hosts {
+            return Ok(());
+        }
+
+        let bare = host.trim_start_matches('[').trim_end_matches(']');
+        let addrs: Vec<std::net::IpAddr> = if let Ok(ip) = bare.parse() {
+            vec![ip]
+        } else if bare.eq_ignore_ascii_case("localhost") {
+            return Err(private_host_error(host));
+        } else {
+            let port = url.port_or_known_default().unwrap_or(80);
+            match tokio::net::lookup_host((bare, port)).await {
+                Ok(addrs) => addrs.map(|addr| addr.ip()).collect(),
+                Err(e) => return Err(format!("Could not resolve {host}: {e}")),
+            }
+        };
+        match addrs
+            .into_iter()
+            .find(|ip| super::browser::is_private_ip(*ip))
+        {
+            Some(ip) if ip.to_string() == bare => Err(private_host_error(host)),
+            Some(ip) => Err(private_host_error(&format!("{host} ({ip})"))),
+            None => Ok(()),
+        }
+    }
+
+    /// GET `url`, following redirects through [`Self::check_url`]. Returns
+    /// the final URL, content type, body (at most `max_bytes`) and whether
+    /// the body was cut short.
+    async fn fetch(&self, mut url: Url) -> Result<(Url, String, Vec<u8>, bool), String> {
+        let client = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(self.timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {e}"))?;
+
+        for _ in 0..=MAX_REDIRECTS {
+            self.check_url(&url).await?;
+            let mut response = client
+                .get(url.clone())
+                .header("Accept", "text/html,text/plain;q=0.9,*/*;q=0.5")
+                .send()
+                .await
+                .map_err(|e| format!("Request to {url} failed: {e}"))?;
+
+            let status = response.status();
+            if status.is_redirection() {
+                let location = response
+                    .headers()
+                    .get(reqwest::header::LOCATION)
+                    .and_then(|v| v.to_str().ok())
+                    .ok_or_else(|| format!("{url} redirected ({status}) without a Location"))?;
+                url = url
+                    .join(location)
+                    .map_err(|e| format!("Invalid redirect target `{location}`: {e}"))?;
+                continue;
+            }
+            if !status.is_success() {
+                return Err(format!("{url} returned {status}"));
+            }
+
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            let mut body = Vec::new();
+            let mut truncated = false;
+            while let Some(chunk) = response
+                .chunk()
+                .await
+                .map_err(|e| format!("Failed to read {url}: {e}"))?
+            {
+                let room = self.max_bytes - body.len();
+                if chunk.len() > room {
+                    body.extend_from_slice(&chunk[..room]);
+                    truncated = true;
+                    break;
+                }
+                body.extend_from_slice(&chunk);
+            }
+            return Ok((url, content_type, body, truncated));
+        }
+        Err(format!("Too many redirects (more than {MAX_REDIRECTS})"))
+    }
+}
+
+fn private_host_error(host: &str) -> String {
+    format!(
+        "Blocked local/private host {host}. Set [web_fetch] allow_private_hosts = true \
+        (with autonomy.workspace_only = false) to allow it."
+    )
+}
+
+#[async_trait]
+impl Tool for WebFetchTool {
+    fn name(&self) -> &str {
+        "web_fetch"
+    }
+
+    fn description(&self) -> &str {
+        "Fetch a web page over HTTP(S) and return its readable text (title, headings, paragraphs, optionally links). \
+        Use when you have a specific URL to read, e.g. from web_search results or the user."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "The http:// or https:// URL to fetch"
+                },
+                "include_links": {
+                    "type": "boolean",
+                    "description": "Keep link targets as [text](url) in the output (default false)"
+                },
+                "max_tokens": {
+                    "type": "integer",
+                    "description": "Lower the token budget for the returned text (cannot exceed the configured limit)",
+                    "minimum": 1
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        let raw_url = args
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'url' parameter"))?;
+        let include_links = args
+            .get("include_links")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let max_tokens = args
+            .get("max_tokens")
+            .and_then(Value::as_u64)
+            .and_then(|n| usize::try_from(n).ok())
+            .map_or(self.max_tokens, |n| n.clamp(1, self.max_tokens));
+
+        let failure = |error: String| ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(error),
+        };
+
+        if !self.security.record_action() {
+            return Ok(failure("Action blocked: rate limit exceeded".into()));
+        }
+
+        let url = match Url::parse(raw_url.trim()) {
+            Ok(url) => url,
+            Err(e) => return Ok(failure(format!("Invalid URL `{raw_url}`: {e}"))),
+        };
+        let (url, content_type, body, cut_short) = match self.fetch(url).await {
+            Ok(fetched) => fetched,
+            Err(e) => return Ok(failure(e)),
+        };
+
+        let body = String::from_utf8_lossy(&body);
+        let is_html = content_type.contains("html")
+            || (content_type.is_empty() && body.trim_start().starts_with('<'));
+        let (title, text) = if is_html {
+            let page = extract_text(&body, &url, include_links);
+            (page.title, page.text)
+        } else if content_type.is_empty()
+            || content_type.starts_with("text/")
+            || content_type.contains("json")
+            || content_type.contains("xml")
+        {
+            (None, body.trim().to_string())
+        } else {
+            return Ok(failure(format!(
+                "{url} is `{content_type}`, not text; web_fetch only returns text content"
+            )));
+        };
+
+        let mut output = format!("URL: {url}\n");
+        if let Some(title) = title {
+            let _ = writeln!(output, "Title: {title}");
+        }
+        output.push('\n');
+        output.push_str(&truncate_to_tokens(&text, max_tokens));
+        if cut_short {
+            let _ = write!(
+                output,
+                "\n\n[Download stopped at {} bytes; the rest of the page was not read.]",
+                self.max_bytes
+            );
+        }
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+        })
+    }
+}
+
+/// `text` cut to about `max_tokens`, preferring a line break, with a note
+/// saying how much was dropped.
+fn truncate_to_tokens(text: &str, max_tokens: usize) -> String {
+    let budget = max_tokens.saturating_mul(CHARS_PER_TOKEN);
+    let total = text.chars().count();
+    if total <= budget {
+        return text.to_string();
+    }
+    let cut = text
+        .char_indices()
+        .nth(budget)
+        .map_or(text.len(), |(i, _)| i);
+    let kept = match text[..cut].rfind('\n') {
+        Some(line_end) if line_end > cut / 2 => &text[..line_end],
+        _ => &text[..cut],
+    };
+    format!(
+        "{}\n\n[Truncated: showing about {max_tokens} of ~{} tokens. Ask with a larger max_tokens \
+        (up to the configured limit) if you need more.]",
+        kept.trim_end(),
+        total.div_ceil(CHARS_PER_TOKEN)
+    )
+}
+
+/// Readable parts of an HTML page.
+#[derive(Debug, Default)]
+struct Page {
+    title: Option<String>,
+    text: String,
+}
+
+/// Elements whose content is never shown.
+const SKIPPED: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "iframe", "object", "canvas",
+];
+
+/// Elements that start a new line.
+const BLOCKS: &[&str] = &[
+    "p",
+    "div",
+    "br",
+    "hr",
+    "section",
+    "article",
+    "main",
+    "header",
+    "footer",
+    "nav",
+    "aside",
+    "blockquote",
+    "pre",
+    "ul",
+    "ol",
+    "dl",
+    "dt",
+    "dd",
+    "table",
+    "tr",
+    "form",
+    "figure",
+    "figcaption",
+];
+
+/// Strip an HTML document down to its title and text: headings become
+/// `#` lines, list items `- ` lines, and with `include_links` anchors become
+/// `[text](url)` resolved against `base`.
+fn extract_text(html: &str, base: &Url, include_links: bool) -> Page {
+    let mut page = Page::default();
+    let mut out = String::new();
+    let mut rest = html;
+    let mut pre_depth = 0usize;
+    let mut open_link: Option<String> = None;
+
+    while let Some(lt) = rest.find('<') {
+        push_text(&mut out, &rest[..lt], pre_depth > 0);
+        rest = &rest[lt..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(tag) = Tag::parse(rest) else {
+            // A stray `<` in text
+            push_text(&mut out, "<", pre_depth > 0);
+            rest = &rest[1..];
+            continue;
+        };
+        rest = &rest[tag.len..];
+
+        if !tag.closing && (SKIPPED.contains(&tag.name.as_str()) || tag.name == "title") {
+            let (inner, after) = split_at_close(rest, &tag.name);
+            if tag.name == "title" && page.title.is_none() {
+                let title = collapse_whitespace(&decode_entities(inner));
+                page.title = Some(title).filter(|t| !t.is_empty());
+            }
+            rest = after;
+            continue;
+        }
+
+        let name = tag.name.as_str();
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                out.push_str("\n\n");
+                if !tag.closing {
+                    let level = usize::from(name.as_bytes()[1] - b'0');
+                    out.push_str(&"#".repeat(level));
+                    out.push(' ');
+                }
+            }
+            "li" if !tag.closing => out.push_str("\n- "),
+            "pre" => {
+                pre_depth = if tag.closing {
+                    pre_depth.saturating_sub(1)
+                } else {
+                    pre_depth + 1
+                };
+                out.push('\n');
+            }
+            "a" if include_links => {
+                if tag.closing {
+                    if let Some(href) = open_link.take() {
+                        let _ = write!(out, "]({href})");
+                    }
+                } else if open_link.is_none() {
+                    open_link = tag
+                        .attr("href")
+                        .filter(|href| !href.starts_with('#') && !href.starts_with("javascript:"))
+                        .and_then(|href| base.join(&href).ok())
+                        .map(String::from);
+                    if open_link.is_some() {
+                        out.push('[');
+                    }
+                }
+            }
+            "p" | "blockquote" | "table" | "ul" | "ol" => out.push_str("\n\n"),
+            "td" | "th" if !tag.closing && !out.ends_with(char::is_whitespace) => out.push(' '),
+            _ if BLOCKS.contains(&name) => out.push('\n'),
+            _ => {}
+        }
+    }
+    push_text(&mut out, rest, pre_depth > 0);
+
+    page.text = tidy_lines(&out);
+    page
+}
+
+/// One start or end tag.
+struct Tag {
+    name: String,
+    closing: bool,
+    /// Raw text between the name and `>`
+    attrs: String,
+    /// Bytes up to and including `>`
+    len: usize,
+}
+
+impl Tag {
+    /// Parse the tag at the start of `s` (which begins with `<`); `None` when
+    /// it isn't one (`a < b`, `<!DOCTYPE` is treated as a tag and ignored).
+    fn parse(s: &str) -> Option<Self> {
+        let body = &s[1..];
+        let (closing, body) = match body.strip_prefix('/') {
+            Some(b) => (true, b),
+            None => (false, body),
+        };
+        let first = body.chars().next()?;
+        if !first.is_ascii_alphabetic() && first != '!' && first != '?' {
+            return None;
+        }
+        let name_len = body
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '!' || c == '?' || c == '-'))
+            .unwrap_or(body.len());
+
+        // Find the closing `>`, skipping any inside quoted attribute values
+        let mut quote = None;
+        let end = body.char_indices().find_map(|(i, c)| match (quote, c) {
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                None
+            }
+            (Some(q), c) if c == q => {
+                quote = None;
+                None
+            }
+            (None, '>') => Some(i),
+            _ => None,
+        })?;
+        Some(Self {
+            name: body[..name_len].to_ascii_lowercase(),
+            closing,
+            attrs: body[name_len..end].to_string(),
+            len: s.len() - body.len() + end + 1,
+        })
+    }
+
+    /// The decoded value of attribute `name`.
+    fn attr(&self, name: &str) -> Option<String> {
+        let mut rest = self.attrs.as_str();
+        while let Some(eq) = rest.find('=') {
+            let key = rest[..eq]
+                .rsplit(|c: char| c.is_whitespace())
+                .next()
+                .unwrap_or_default();
+            let after = rest[eq + 1..].trim_start();
+            let (value, next) = if let Some(q @ ('"' | '\'')) = after.chars().next() {
+                let inner = &after[1..];
+                let end = inner.find(q).unwrap_or(inner.len());
+                (&inner[..end], inner.get(end + 1..).unwrap_or_default())
+            } else {
+                let end = after
+                    .find(|c: char| c.is_whitespace() || c == '/')
+                    .unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            };
+            if key.eq_ignore_ascii_case(name) {
+                return Some(decode_entities(value.trim()));
+            }
+            rest = next;
+        }
+        None
+    }
+}
+
+/// Split at the matching `</name>`: the element's raw content and what
+/// follows it (everything when it never closes).
+fn split_at_close<'a>(s: &'a str, name: &str) -> (&'a str, &'a str) {
+    let needle = format!("</{name}");
+    let lower = s.to_ascii_lowercase();
+    match lower.find(&needle) {
+        Some(start) => {
+            let after = s[start..].find('>').map_or("", |gt| &s[start + gt + 1..]);
+            (&s[..start], after)
+        }
+        None => (s, ""),
+    }
+}
+
+fn push_text(out: &mut String, raw: &str, preformatted: bool) {
+    if raw.is_empty() {
+        return;
+    }
+    let text = decode_entities(raw);
+    if preformatted {
+        out.push_str(&text);
+        return;
+    }
+    for (i, word) in text.split_whitespace().enumerate() {
+        let needs_space = (i > 0 || text.starts_with(char::is_whitespace))
+            && !out.is_empty()
+            && !out.ends_with(char::is_whitespace)
+            && !out.ends_with('[');
+        if needs_space {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    if text.ends_with(char::is_whitespace) && !out.ends_with(char::is_whitespace) {
+        out.push(' ');
+    }
+}
+
+fn collapse_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Drop trailing spaces and keep at most one blank line in a row.
+fn tidy_lines(s: &str) -> String {
+    let mut out = String::new();
+    let mut blank = false;
+    for line in s.lines().map(str::trim_end) {
+        if line.trim().is_empty() || line == "-" {
+            blank = !out.is_empty();
+            continue;
+        }
+        if blank {
+            out.push_str("\n\n");
+        } else if !out.is_empty() {
+            out.push('\n');
+        }
+        blank = false;
+        out.push_str(line);
+    }
+    out
+}
+
+/// Decode the common named entities and numeric character references.
+pub(crate) fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..]
+            .bytes()
+            .take(11)
+            .position(|b| b == b';')
+            .and_then(|semi| decode_entity(&rest[1..=semi]).map(|c| (c, semi + 2)));
+        if let Some((c, len)) = decoded {
+            out.push(c);
+            rest = &rest[len..];
+        } else {
+            out.push('&');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(num) = entity.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "mdash" => '—',
+        "ndash" => '–',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "copy" => '©',
+        "reg" => '®',
+        "middot" => '·',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn tool(config: &WebFetchConfig, workspace_only: bool) -> WebFetchTool {
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Supervised,
+            workspace_only,
+            ..SecurityPolicy::default()
+        });
+        WebFetchTool::new(security, config)
+    }
+
+    fn local_config() -> WebFetchConfig {
+        WebFetchConfig {
+            enabled: true,
+            allow_private_hosts: true,
+            ..WebFetchConfig::default()
+        }
+    }
+
+    /// Serve `responses` in order, one connection each, on a loopback port.
+    async fn serve(responses: Vec<String>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf).await;
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    fn ok(content_type: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    #[test]
+    fn extracts_title_headings_and_paragraphs() {
+        let html = r#"<!DOCTYPE html><html><head><title> Release &amp; Notes </title>
+            <style>body { color: red }</style><script>var x = "<p>no</p>";</script></head>
+            <body><nav><a href="/">Home</a></nav>
+            <h1>Version 2.0</h1><p>Faster   builds and
+            <b>fewer</b> bugs &mdash; see <a href="/changes#top">the list</a>.</p>
+            <!-- hidden --><ul><li>One</li><li>Two &lt;3</li></ul>
+            <pre>  keep
+  spacing</pre></body></html>"#;
+        let base = Url::parse("https://example.com/blog/").unwrap();
+
+        let page = extract_text(html, &base, false);
+        assert_eq!(page.title.as_deref(), Some("Release & Notes"));
+        assert!(page.text.contains("# Version 2.0"), "{}", page.text);
+        assert!(
+            page.text
+                .contains("Faster builds and fewer bugs — see the list."),
+            "{}",
+            page.text
+        );
+        assert!(page.text.contains("- One\n- Two <3"), "{}", page.text);
+        assert!(page.text.contains("  keep\n  spacing"), "{}", page.text);
+        assert!(!page.text.contains("color"));
+        assert!(!page.text.contains("var x"));
+        assert!(!page.text.contains("hidden"));
+
+        let linked = extract_text(html, &base, true);
+        assert!(
+            linked
+                .text
+                .contains("[the list](https://example.com/changes#top)"),
+            "{}",
+            linked.text
+        );
+        assert!(linked.text.contains("[Home](https://example.com/)"));
+    }
+
+    #[test]
+    fn decodes_entities_and_keeps_stray_ampersands() {
+        assert_eq!(
+            decode_entities("a &amp; b &#39;c&#x27; &copy;"),
+            "a & b 'c' ©"
+        );
+        assert_eq!(decode_entities("AT&T &bogus; 5 & 6"), "AT&T &bogus; 5 & 6");
+        assert_eq!(decode_entities("&lt;中文&gt;"), "<中文>");
+        assert_eq!(
+            extract_text("1 < 2 and <br>3", &Url::parse("http://x/").unwrap(), false).text,
+            "1 < 2 and\n3"
+        );
+    }
+
+    #[test]
+    fn truncation_notes_what_was_dropped() {
+        let text = "line one\n".repeat(100);
+        assert_eq!(truncate_to_tokens("short", 10), "short");
+        let cut = truncate_to_tokens(&text, 10);
+        assert!(cut.starts_with("line one\nline one\nline one\nline one"));
+        assert!(
+            cut.contains("[Truncated: showing about 10 of ~225 tokens."),
+            "{cut}"
+        );
+        assert!(cut.len() < 200);
+    }
+
+    #[tokio::test]
+    async fn refuses_other_schemes_and_private_hosts() {
+        let t = tool(&WebFetchConfig::default(), true);
+        for (url, expected) in [
+            ("file:///etc/passwd", "Only http:// and https://"),
+            ("ftp://example.com/x", "Only http:// and https://"),
+            ("http://127.0.0.1:8080/", "Blocked local/private host"),
+            ("http://localhost/", "Blocked local/private host"),
+            ("http://[::1]/", "Blocked local/private host"),
+            ("http://192.168.1.1/admin", "Blocked local/private host"),
+            ("http://[::ffff:10.0.0.1]/", "Blocked local/private host"),
+            ("not a url", "Invalid URL"),
+        ] {
+            let result = t.execute(json!({ "url": url })).await.unwrap();
+            assert!(!result.success, "{url} should be refused");
+            let error = result.error.unwrap();
+            assert!(error.contains(expected), "{url}: {error}");
+        }
+    }
+
+    #[tokio::test]
+    async fn private_hosts_need_the_flag_and_workspace_only_off() {
+        let base = serve(vec![ok("text/plain", "hello")]).await;
+        let flagged_but_confined = tool(&local_config(), true);
+        let result = flagged_but_confined
+            .execute(json!({ "url": base }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+
+        let result = tool(&local_config(), false)
+            .execute(json!({ "url": base }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.ends_with("\n\nhello"), "{}", result.output);
+    }
+
+    #[tokio::test]
+    async fn fetches_html_follows_redirects_and_caps_the_body() {
+        let page = format!(
+            "<html><head><title>Docs</title></head><body><h2>Intro</h2><p>{}</p></body></html>",
+            "word ".repeat(400)
+        );
+        let base = serve(vec![
+            "HTTP/1.1 302 Found\r\nLocation: /docs\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                .into(),
+            ok("text/html; charset=utf-8", &page),
+            ok("application/octet-stream", "\u{0}\u{1}"),
+        ])
+        .await;
+        let config = WebFetchConfig {
+            max_bytes: 1000,
+            max_tokens: 50,
+            ..local_config()
+        };
+        let t = tool(&config, false);
+
+        let result = t.execute(json!({ "url": base })).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        let out = result.output;
+        assert!(
+            out.starts_with(&format!("URL: {base}/docs\nTitle: Docs\n")),
+            "{out}"
+        );
+        assert!(out.contains("## Intro"), "{out}");
+        assert!(out.contains("[Truncated: showing about 50 of"), "{out}");
+        assert!(out.contains("[Download stopped at 1000 bytes"), "{out}");
+
+        let result = t.execute(json!({ "url": base })).await.unwrap();
+        assert!(result.error.unwrap().contains("not text"));
+    }
+}
@@ -0,0 +1,282 @@
+use super::{preview, quota_exhausted, Freshness, SearchProvider, SearchQuery, SearchResult};
+use crate::tools::web_fetch::decode_entities;
+use anyhow::Context;
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+const ENDPOINT: &str = "https://api.search.brave.com/res/v1/web/search";
+
+/// The Brave Search API (`[brave_search]`).
+pub struct BraveSearch {
+    api_key: String,
+    count: u8,
+    endpoint: String,
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Deserialize)]
+struct BraveSearchResponse {
+    web: Option<BraveWebResults>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BraveWebResults {
+    results: Vec<BraveSearchResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BraveSearchResult {
+    title: String,
+    url: String,
+    description: Option<String>,
+    age: Option<String>,
+}
+
+impl BraveSearch {
+    pub fn new(api_key: &str, count: u8) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            count: count.clamp(1, 20),
+            endpoint: ENDPOINT.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Send searches to `endpoint` instead of the Brave API.
+    #[cfg(test)]
+    pub(crate) fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl SearchProvider for BraveSearch {
+    fn name(&self) -> &str {
+        "Brave Search"
+    }
+
+    fn default_count(&self) -> u8 {
+        self.count
+    }
+
+    async fn search(&self, query: &SearchQuery) -> anyhow::Result<Vec<SearchResult>> {
+        let mut params: Vec<(&str, String)> =
+            vec![("q", query.terms()), ("count", query.count.to_string())];
+        if let Some(freshness) = query.freshness {
+            let code = match freshness {
+                Freshness::Day => "pd",
+                Freshness::Week => "pw",
+                Freshness::Month => "pm",
+                Freshness::Year => "py",
+            };
+            params.push(("freshness", code.to_string()));
+        }
+        if let Some(country) = &query.country {
+            params.push(("country", country.clone()));
+        }
+
+        let response = self
+            .client
+            .get(&self.endpoint)
+            .query(&params)
+            .header("Accept", "application/json")
+            .header("X-Subscription-Token", &self.api_key)
+            .send()
+            .await
+            .context("Brave Search request failed")?;
+
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(quota_exhausted(&response));
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Brave Search API error ({status}): {body}");
+        }
+        let text = response
+            .text()
+            .await
+            .context("Failed to read Brave Search response")?;
+        parse_response(&text)
+    }
+}
+
+fn parse_response(text: &str) -> anyhow::Result<Vec<SearchResult>> {
+    let body: BraveSearchResponse = serde_json::from_str(text).with_context(|| {
+        format!(
+            "Failed to parse Brave Search response\nBody preview: {}",
+            preview(text)
+        )
+    })?;
+    Ok(body
+        .web
+        .map(|w| w.results)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|r| SearchResult {
+            title: r.title,
+            url: r.url,
+            snippet: r
+                .description
+                .map(|d| plain_text(&d))
+                .filter(|d| !d.is_empty()),
+            age: r.age,
+        })
+        .collect())
+}
+
+/// Brave marks the matched words in descriptions with `<strong>`; keep the
+/// text only.
+fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    decode_entities(text.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::traits::Tool;
+    use crate::tools::web_search::WebSearchTool;
+    use axum::extract::{Query, State};
+    use axum::http::{HeaderMap, StatusCode as HttpStatus};
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// A Brave API stand-in counting the searches it serves. It echoes the
+    /// parameters it got in the result description; the query "quota"
+    /// answers 429.
+    async fn mock_api(hits: Arc<AtomicUsize>) -> String {
+        async fn search(
+            State(hits): State<Arc<AtomicUsize>>,
+            Query(params): Query<HashMap<String, String>>,
+        ) -> (HttpStatus, HeaderMap, Json<Value>) {
+            hits.fetch_add(1, Ordering::SeqCst);
+            if params["q"] == "quota" {
+                let mut headers = HeaderMap::new();
+                headers.insert("retry-after", "30".parse().unwrap());
+                return (HttpStatus::TOO_MANY_REQUESTS, headers, Json(json!({})));
+            }
+            let echo = format!(
+                "<strong>q</strong>={} count={} freshness={} country={} &amp; more",
+                params["q"],
+                params["count"],
+                params.get("freshness").map_or("-", String::as_str),
+                params.get("country").map_or("-", String::as_str)
+            );
+            let body = json!({ "web": { "results": [
+                { "title": "Tokio", "url": "https://docs.rs/tokio", "description": echo,
+                  "age": "3 days ago" }
+            ] } });
+            (HttpStatus::OK, HeaderMap::new(), Json(body))
+        }
+
+        let app = Router::new()
+            .route("/res/v1/web/search", get(search))
+            .with_state(hits);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{addr}/res/v1/web/search")
+    }
+
+    #[tokio::test]
+    async fn filters_cache_and_quota() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let endpoint = mock_api(hits.clone()).await;
+        let tool = WebSearchTool::new(Box::new(
+            BraveSearch::new("key", 5).with_endpoint(&endpoint),
+        ));
+
+        let args = json!({
+            "query": "tokio runtime", "count": 3, "freshness": "month",
+            "country": "de", "site": "docs.rs"
+        });
+        let result = tool.execute(args.clone()).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            result.output,
+            "[1] Tokio (3 days ago)\n    https://docs.rs/tokio\n    \
+             q=tokio runtime site:docs.rs count=3 freshness=pm country=DE & more\n"
+        );
+
+        // A retry of the same search is answered from the cache
+        let mut retry = args;
+        retry["query"] = json!("Tokio  Runtime");
+        assert_eq!(tool.execute(retry).await.unwrap().output, result.output);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        let result = tool.execute(json!({"query": "quota"})).await.unwrap();
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(
+            error.starts_with("Search temporarily unavailable"),
+            "{error}"
+        );
+
+        // Paused: new searches are not sent, cached ones still answer
+        let result = tool.execute(json!({"query": "axum"})).await.unwrap();
+        assert!(result
+            .error
+            .unwrap()
+            .starts_with("Search temporarily unavailable"));
+        let cached = json!({
+            "query": "tokio runtime", "count": 3, "freshness": "pm",
+            "country": "DE", "site": "docs.rs"
+        });
+        assert!(tool.execute(cached).await.unwrap().success);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn count_clamped() {
+        assert_eq!(BraveSearch::new("key", 50).default_count(), 20);
+        assert_eq!(BraveSearch::new("key", 0).default_count(), 1);
+    }
+
+    #[test]
+    fn results_are_normalized() {
+        let body = r#"{
+            "web": { "results": [
+                { "title": "Rust", "url": "https://www.rust-lang.org/",
+                  "description": "A language empowering everyone", "age": "2 days ago" },
+                { "title": "Docs", "url": "https://doc.rust-lang.org/" }
+            ] }
+        }"#;
+        let results = parse_response(body).unwrap();
+        assert_eq!(
+            results[0],
+            SearchResult {
+                title: "Rust".into(),
+                url: "https://www.rust-lang.org/".into(),
+                snippet: Some("A language empowering everyone".into()),
+                age: Some("2 days ago".into()),
+            }
+        );
+        assert_eq!(results[1].snippet, None);
+        assert_eq!(
+            plain_text("Tokio is <strong>fast</strong> &amp; <em>safe</em>"),
+            "Tokio is fast & safe"
+        );
+
+        assert!(parse_response(r#"{"query": {}}"#).unwrap().is_empty());
+        let err = parse_response("<html>").unwrap_err();
+        assert!(format!("{err:#}").contains("Body preview: <html>"));
+    }
+}
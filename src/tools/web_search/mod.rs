@@ -0,0 +1,585 @@
+//! The `web_search` tool and the search engines behind it.
+//!
+//! Engines implement [`SearchProvider`] and return normalized
+//! [`SearchResult`]s; [`provider_from_config`] picks the configured one, so
+//! the agent always sees a single `web_search` tool.
+//!
+//! Identical searches are answered from a short-lived cache, so a model
+//! retrying the same query does not spend the engine's quota again. When the
+//! engine reports its quota exhausted (HTTP 429), searching pauses for the
+//! `Retry-After` delay and the model is told search is unavailable.
+
+pub mod brave;
+pub mod searxng;
+
+use super::traits::{Tool, ToolResult};
+use crate::config::{BraveSearchConfig, WebSearchConfig};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+pub use brave::BraveSearch;
+pub use searxng::Searxng;
+
+/// How long a search is answered from the cache.
+const CACHE_TTL: Duration = Duration::from_mins(5);
+
+/// Most searches kept in the cache.
+const CACHE_CAPACITY: usize = 64;
+
+/// Pause after a quota error that carries no `Retry-After`.
+const QUOTA_PAUSE: Duration = Duration::from_mins(1);
+
+/// Longest pause a `Retry-After` can ask for.
+const MAX_QUOTA_PAUSE: Duration = Duration::from_hours(1);
+
+/// How recent results must be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Freshness {
+    /// Parse the tool's `day`/`week`/`month`/`year` argument; Brave's
+    /// `pd`/`pw`/`pm`/`py` codes are accepted too.
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "day" | "pd" => Some(Self::Day),
+            "week" | "pw" => Some(Self::Week),
+            "month" | "pm" => Some(Self::Month),
+            "year" | "py" => Some(Self::Year),
+            _ => None,
+        }
+    }
+}
+
+/// One search as the agent asked for it.
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    pub query: String,
+    /// Results wanted, 1-20
+    pub count: u8,
+    pub freshness: Option<Freshness>,
+    /// Two-letter country code, upper case; engines without a country
+    /// filter ignore it
+    pub country: Option<String>,
+    /// Only results from this domain
+    pub site: Option<String>,
+}
+
+impl SearchQuery {
+    /// The query text to send, with the `site:` restriction applied.
+    pub fn terms(&self) -> String {
+        match &self.site {
+            Some(site) => format!("{} site:{site}", self.query),
+            None => self.query.clone(),
+        }
+    }
+
+    /// Searches that differ only in case or spacing share a cache entry.
+    fn cache_key(&self) -> String {
+        let query = self.query.to_lowercase();
+        let query = query.split_whitespace().collect::<Vec<_>>().join(" ");
+        format!(
+            "{query}|{}|{:?}|{}|{}",
+            self.count,
+            self.freshness,
+            self.country.as_deref().unwrap_or_default(),
+            self.site.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+/// The engine refused a search because its quota or rate limit is used up
+/// (HTTP 429). Kept as a typed error so `web_search` can pause searching
+/// instead of passing the raw API error on.
+#[derive(Debug)]
+pub struct QuotaExhausted {
+    pub retry_after: Option<Duration>,
+}
+
+impl std::fmt::Display for QuotaExhausted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("search quota exhausted")
+    }
+}
+
+impl std::error::Error for QuotaExhausted {}
+
+/// A [`QuotaExhausted`] from a 429 response, honoring its `Retry-After`.
+fn quota_exhausted(response: &reqwest::Response) -> anyhow::Error {
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| crate::providers::parse_retry_after(v, chrono::Utc::now()));
+    QuotaExhausted { retry_after }.into()
+}
+
+/// A result in the same shape whichever engine found it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: Option<String>,
+    /// Publication date or age, as the engine reports it
+    pub age: Option<String>,
+}
+
+/// A search engine `web_search` can query.
+#[async_trait]
+pub trait SearchProvider: Send + Sync {
+    /// Engine name shown to the model, e.g. "Brave Search"
+    fn name(&self) -> &str;
+
+    /// Results per search when the call sets no `count`
+    fn default_count(&self) -> u8;
+
+    /// At most `query.count` results, best first. Errors carry a message
+    /// the model can act on.
+    async fn search(&self, query: &SearchQuery) -> anyhow::Result<Vec<SearchResult>>;
+}
+
+/// The engine `[tools.web_search]` selects, if it is fully configured:
+/// Brave needs `[brave_search]` enabled with an API key, searxng needs an
+/// instance URL.
+pub fn provider_from_config(
+    web_search: &WebSearchConfig,
+    brave: &BraveSearchConfig,
+) -> Option<Box<dyn SearchProvider>> {
+    match web_search.provider.as_str() {
+        "brave" => {
+            let key = brave.api_key.as_deref().filter(|k| !k.is_empty())?;
+            brave
+                .enabled
+                .then(|| Box::new(BraveSearch::new(key, brave.default_count)) as _)
+        }
+        "searxng" => {
+            let url = web_search.searxng.url.as_deref().map(str::trim);
+            let url = url.filter(|u| !u.is_empty())?;
+            Some(Box::new(Searxng::new(
+                url,
+                web_search.searxng.default_count,
+            )))
+        }
+        other => {
+            tracing::warn!("未知的搜索后端「{other}」（支持 brave、searxng），web_search 未启用");
+            None
+        }
+    }
+}
+
+/// Web search through the configured [`SearchProvider`].
+pub struct WebSearchTool {
+    provider: Box<dyn SearchProvider>,
+    description: String,
+    /// Recent results by [`SearchQuery::cache_key`], with when they were fetched
+    cache: Mutex<HashMap<String, (Instant, Vec<SearchResult>)>>,
+    /// Set after a quota error; no searches are sent before then
+    paused_until: Mutex<Option<Instant>>,
+}
+
+impl WebSearchTool {
+    pub fn new(provider: Box<dyn SearchProvider>) -> Self {
+        let description = format!(
+            "Search the web using {}. Returns a numbered list of the top results with title, age, URL, \
+             and a snippet, so you can often answer without fetching the page; cite results by number. \
+             Use when you need current information, facts, documentation, or any knowledge beyond your training data.",
+            provider.name()
+        );
+        Self {
+            provider,
+            description,
+            cache: Mutex::new(HashMap::new()),
+            paused_until: Mutex::new(None),
+        }
+    }
+
+    /// The search the tool arguments ask for, or why they are invalid.
+    fn parse_query(&self, args: &Value) -> anyhow::Result<Result<SearchQuery, String>> {
+        let query = args
+            .get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'query' parameter"))?;
+        if query.trim().is_empty() {
+            return Ok(Err("Search query cannot be empty".into()));
+        }
+
+        let count = args
+            .get("count")
+            .and_then(serde_json::Value::as_u64)
+            .map_or(self.provider.default_count(), |c| {
+                u8::try_from(c).unwrap_or(20).clamp(1, 20)
+            });
+        let freshness = args
+            .get("freshness")
+            .and_then(|v| v.as_str())
+            .and_then(Freshness::parse);
+        let country = match args.get("country").and_then(|v| v.as_str()).map(str::trim) {
+            None | Some("") => None,
+            Some(c) if c.len() == 2 && c.bytes().all(|b| b.is_ascii_alphabetic()) => {
+                Some(c.to_ascii_uppercase())
+            }
+            Some(c) => {
+                return Ok(Err(format!(
+                    "Invalid country '{c}': use a two-letter country code such as US or DE"
+                )));
+            }
+        };
+        let site = match args.get("site").and_then(|v| v.as_str()) {
+            None => None,
+            Some(raw) => match normalize_site(raw) {
+                Some(site) => Some(site),
+                None if raw.trim().is_empty() => None,
+                None => {
+                    return Ok(Err(format!(
+                        "Invalid site '{raw}': use a domain such as docs.rs"
+                    )));
+                }
+            },
+        };
+
+        Ok(Ok(SearchQuery {
+            query: query.trim().to_string(),
+            count,
+            freshness,
+            country,
+            site,
+        }))
+    }
+
+    fn cached(&self, key: &str) -> Option<Vec<SearchResult>> {
+        let cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        cache
+            .get(key)
+            .filter(|(at, _)| at.elapsed() < CACHE_TTL)
+            .map(|(_, results)| results.clone())
+    }
+
+    fn remember(&self, key: String, results: &[SearchResult]) {
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        cache.retain(|_, (at, _)| at.elapsed() < CACHE_TTL);
+        if cache.len() >= CACHE_CAPACITY
+            && let Some(oldest) = cache
+                .iter()
+                .min_by_key(|(_, (at, _))| *at)
+                .map(|(k, _)| k.clone())
+        {
+            cache.remove(&oldest);
+        }
+        cache.insert(key, (Instant::now(), results.to_vec()));
+    }
+
+    /// Whether searching is paused after a quota error.
+    fn paused(&self) -> bool {
+        let paused_until = self
+            .paused_until
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        paused_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    fn pause(&self, retry_after: Option<Duration>) {
+        let pause = retry_after.unwrap_or(QUOTA_PAUSE).min(MAX_QUOTA_PAUSE);
+        tracing::warn!(
+            "{} 配额已用尽或被限流（HTTP 429），{} 秒内暂停网页搜索",
+            self.provider.name(),
+            pause.as_secs()
+        );
+        *self
+            .paused_until
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(Instant::now() + pause);
+    }
+
+    fn unavailable(&self) -> ToolResult {
+        ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(format!(
+                "Search temporarily unavailable: the {} quota is used up. Do not retry now; \
+                 answer from what you already know and say that it could not be checked online.",
+                self.provider.name()
+            )),
+        }
+    }
+}
+
+/// `site` as a bare domain: scheme, `site:` prefix, path and a leading
+/// `www.` dropped.
+fn normalize_site(raw: &str) -> Option<String> {
+    let site = raw.trim();
+    let site = site.strip_prefix("site:").unwrap_or(site);
+    let site = site.split_once("://").map_or(site, |(_, rest)| rest);
+    let site = site.split(['/', '?', '#']).next().unwrap_or_default();
+    let site = site
+        .strip_prefix("www.")
+        .unwrap_or(site)
+        .to_ascii_lowercase();
+    let valid = site.contains('.')
+        && !site.starts_with('.')
+        && !site.ends_with('.')
+        && site
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'.' || b == b'-');
+    valid.then_some(site)
+}
+
+#[async_trait]
+impl Tool for WebSearchTool {
+    fn name(&self) -> &str {
+        "web_search"
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "The search query"
+                },
+                "count": {
+                    "type": "integer",
+                    "description": "Number of results to return (1-20, default from config)",
+                    "minimum": 1,
+                    "maximum": 20
+                },
+                "freshness": {
+                    "type": "string",
+                    "enum": ["day", "week", "month", "year"],
+                    "description": "Only results from the past day, week, month, or year"
+                },
+                "country": {
+                    "type": "string",
+                    "description": "Two-letter country code to localize results, e.g. US or DE"
+                },
+                "site": {
+                    "type": "string",
+                    "description": "Only return results from this domain, e.g. docs.rs"
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        let search = match self.parse_query(&args)? {
+            Ok(search) => search,
+            Err(error) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(error),
+                });
+            }
+        };
+
+        let key = search.cache_key();
+        let results = if let Some(results) = self.cached(&key) {
+            results
+        } else {
+            if self.paused() {
+                return Ok(self.unavailable());
+            }
+            match self.provider.search(&search).await {
+                Ok(results) => {
+                    self.remember(key, &results);
+                    results
+                }
+                Err(e) => {
+                    if let Some(quota) = e.downcast_ref::<QuotaExhausted>() {
+                        self.pause(quota.retry_after);
+                        return Ok(self.unavailable());
+                    }
+                    return Ok(ToolResult {
+                        success: false,
+                        output: String::new(),
+                        error: Some(format!("{e:#}")),
+                    });
+                }
+            }
+        };
+
+        if results.is_empty() {
+            return Ok(ToolResult {
+                success: true,
+                output: format!("No results found for: {}", search.terms()),
+                error: None,
+            });
+        }
+
+        Ok(ToolResult {
+            success: true,
+            output: format_results(&results),
+            error: None,
+        })
+    }
+}
+
+/// Results as a compact numbered list: title and age, URL, snippet.
+fn format_results(results: &[SearchResult]) -> String {
+    let mut output = String::new();
+    for (i, r) in results.iter().enumerate() {
+        let _ = write!(output, "[{}] {}", i + 1, r.title);
+        if let Some(ref age) = r.age {
+            let _ = write!(output, " ({age})");
+        }
+        let _ = writeln!(output, "\n    {}", r.url);
+        if let Some(ref snippet) = r.snippet {
+            let _ = writeln!(output, "    {snippet}");
+        }
+    }
+    output
+}
+
+/// The start of a response body for error messages.
+fn preview(body: &str) -> &str {
+    match body.char_indices().nth(200) {
+        Some((end, _)) => &body[..end],
+        None => body,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool() -> WebSearchTool {
+        WebSearchTool::new(Box::new(BraveSearch::new("test-key", 5)))
+    }
+
+    #[test]
+    fn tool_name_and_description() {
+        let tool = tool();
+        assert_eq!(tool.name(), "web_search");
+        assert!(tool
+            .description()
+            .starts_with("Search the web using Brave Search."));
+    }
+
+    #[test]
+    fn parameters_schema_has_query() {
+        let schema = tool().parameters_schema();
+        assert!(schema["properties"]["query"].is_object());
+        assert_eq!(schema["required"][0], "query");
+    }
+
+    #[test]
+    fn arguments_become_a_query() {
+        let tool = tool();
+        let search = tool
+            .parse_query(&json!({
+                "query": " Tokio   Runtime ",
+                "count": 50,
+                "freshness": "week",
+                "country": "de",
+                "site": "https://www.docs.rs/tokio/latest/"
+            }))
+            .unwrap()
+            .unwrap();
+        assert_eq!(search.count, 20);
+        assert_eq!(search.freshness, Some(Freshness::Week));
+        assert_eq!(search.country.as_deref(), Some("DE"));
+        assert_eq!(search.terms(), "Tokio   Runtime site:docs.rs");
+
+        let same = tool
+            .parse_query(&json!({
+                "query": "tokio runtime", "count": 20, "freshness": "pw",
+                "country": "DE", "site": "site:docs.rs"
+            }))
+            .unwrap()
+            .unwrap();
+        assert_eq!(same.cache_key(), search.cache_key());
+
+        let plain = tool
+            .parse_query(&json!({"query": "rust"}))
+            .unwrap()
+            .unwrap();
+        assert_eq!((plain.count, plain.terms()), (5, "rust".to_string()));
+
+        for bad in [
+            json!({"country": "Germany"}),
+            json!({"site": "not a domain"}),
+        ] {
+            let mut args = bad;
+            args["query"] = json!("rust");
+            assert!(tool.parse_query(&args).unwrap().is_err(), "{args}");
+        }
+    }
+
+    #[tokio::test]
+    async fn empty_query_returns_error() {
+        let result = tool().execute(json!({"query": "  "})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("empty"));
+    }
+
+    #[tokio::test]
+    async fn missing_query_returns_error() {
+        let result = tool().execute(json!({})).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn backend_follows_config() {
+        let brave = BraveSearchConfig {
+            enabled: true,
+            api_key: Some("key".into()),
+            default_count: 7,
+        };
+        let mut web_search = WebSearchConfig::default();
+        let picked = provider_from_config(&web_search, &brave).unwrap();
+        assert_eq!((picked.name(), picked.default_count()), ("Brave Search", 7));
+
+        let disabled = BraveSearchConfig {
+            enabled: false,
+            ..brave.clone()
+        };
+        assert!(provider_from_config(&web_search, &disabled).is_none());
+
+        web_search.provider = "searxng".into();
+        assert!(provider_from_config(&web_search, &brave).is_none());
+        web_search.searxng.url = Some("https://searx.example.org/".into());
+        web_search.searxng.default_count = 3;
+        let picked = provider_from_config(&web_search, &disabled).unwrap();
+        assert_eq!((picked.name(), picked.default_count()), ("SearXNG", 3));
+
+        web_search.provider = "bing".into();
+        assert!(provider_from_config(&web_search, &brave).is_none());
+    }
+
+    #[test]
+    fn results_are_formatted_alike() {
+        let results = [
+            SearchResult {
+                title: "Rust".into(),
+                url: "https://www.rust-lang.org/".into(),
+                snippet: Some("A language empowering everyone".into()),
+                age: Some("2 days ago".into()),
+            },
+            SearchResult {
+                title: "Docs".into(),
+                url: "https://doc.rust-lang.org/".into(),
+                snippet: None,
+                age: None,
+            },
+        ];
+        assert_eq!(
+            format_results(&results),
+            "[1] Rust (2 days ago)\n    https://www.rust-lang.org/\n    A language empowering everyone\n\
+             [2] Docs\n    https://doc.rust-lang.org/\n"
+        );
+        assert_eq!(preview(&"é".repeat(300)).chars().count(), 200);
+    }
+}
@@ -0,0 +1,252 @@
+use super::{preview, quota_exhausted, Freshness, SearchProvider, SearchQuery, SearchResult};
+use anyhow::Context;
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+/// A self-hosted or public searxng instance, queried through its JSON API.
+/// No API key is needed, but the instance must list `json` under
+/// `search.formats` in its `settings.yml`.
+pub struct Searxng {
+    base_url: String,
+    count: u8,
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearxngResponse {
+    #[serde(default)]
+    results: Vec<SearxngResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearxngResult {
+    #[serde(default)]
+    title: String,
+    url: String,
+    content: Option<String>,
+    #[serde(rename = "publishedDate")]
+    published_date: Option<String>,
+}
+
+impl Searxng {
+    pub fn new(base_url: &str, count: u8) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            count: count.clamp(1, 20),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl SearchProvider for Searxng {
+    fn name(&self) -> &str {
+        "SearXNG"
+    }
+
+    fn default_count(&self) -> u8 {
+        self.count
+    }
+
+    async fn search(&self, query: &SearchQuery) -> anyhow::Result<Vec<SearchResult>> {
+        let terms = query.terms();
+        let mut params = vec![("q", terms.as_str()), ("format", "json")];
+        if let Some(freshness) = query.freshness {
+            let range = match freshness {
+                Freshness::Day => "day",
+                Freshness::Week => "week",
+                Freshness::Month => "month",
+                Freshness::Year => "year",
+            };
+            params.push(("time_range", range));
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/search", self.base_url))
+            .query(&params)
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .context("SearXNG request failed")?;
+
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(quota_exhausted(&response));
+        }
+        if status == StatusCode::FORBIDDEN {
+            anyhow::bail!(
+                "SearXNG refused the request (403). The instance must enable the json format \
+                 (search.formats in settings.yml)"
+            );
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("SearXNG error ({status}): {}", preview(&body));
+        }
+        let text = response
+            .text()
+            .await
+            .context("Failed to read SearXNG response")?;
+        let mut results = parse_response(&text)?;
+        results.truncate(usize::from(query.count));
+        Ok(results)
+    }
+}
+
+fn parse_response(text: &str) -> anyhow::Result<Vec<SearchResult>> {
+    let body: SearxngResponse = serde_json::from_str(text).with_context(|| {
+        format!(
+            "Failed to parse SearXNG response\nBody preview: {}",
+            preview(text)
+        )
+    })?;
+    Ok(body
+        .results
+        .into_iter()
+        .map(|r| SearchResult {
+            title: if r.title.trim().is_empty() {
+                r.url.clone()
+            } else {
+                r.title
+            },
+            url: r.url,
+            snippet: r.content.filter(|c| !c.trim().is_empty()),
+            age: r.published_date.filter(|d| !d.is_empty()),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::traits::Tool;
+    use crate::tools::web_search::WebSearchTool;
+    use axum::extract::Query;
+    use axum::http::StatusCode as HttpStatus;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+
+    const FIXTURE: &str = r#"{
+        "query": "rust",
+        "number_of_results": 0,
+        "results": [
+            { "url": "https://www.rust-lang.org/", "title": "Rust Programming Language",
+              "content": "A language empowering everyone to build reliable and efficient software.",
+              "engine": "duckduckgo", "score": 4.0, "category": "general" },
+            { "url": "https://blog.rust-lang.org/2026/10/01/Rust-1.90.0.html",
+              "title": "Announcing Rust 1.90.0", "content": "",
+              "publishedDate": "2026-10-01T00:00:00", "engine": "bing" },
+            { "url": "https://doc.rust-lang.org/book/", "title": "",
+              "engine": "google" }
+        ],
+        "answers": [], "suggestions": ["rust book"], "unresponsive_engines": []
+    }"#;
+
+    /// A searxng stand-in that echoes the query parameters it got back in
+    /// the first result's snippet.
+    async fn mock_instance() -> String {
+        async fn search(
+            Query(params): Query<HashMap<String, String>>,
+        ) -> (HttpStatus, Json<Value>) {
+            if params.get("format").map(String::as_str) != Some("json") {
+                return (HttpStatus::FORBIDDEN, Json(json!({})));
+            }
+            if params.get("q").map(String::as_str) == Some("forbidden") {
+                return (HttpStatus::FORBIDDEN, Json(json!({})));
+            }
+            let mut body: Value = serde_json::from_str(FIXTURE).unwrap();
+            let echo = format!(
+                "q={} time_range={}",
+                params["q"],
+                params.get("time_range").map_or("-", String::as_str)
+            );
+            body["results"][0]["content"] = Value::String(echo);
+            (HttpStatus::OK, Json(body))
+        }
+
+        let app = Router::new().route("/search", get(search));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{addr}/")
+    }
+
+    #[test]
+    fn results_are_normalized() {
+        let results = parse_response(FIXTURE).unwrap();
+        assert_eq!(
+            results,
+            [
+                SearchResult {
+                    title: "Rust Programming Language".into(),
+                    url: "https://www.rust-lang.org/".into(),
+                    snippet: Some(
+                        "A language empowering everyone to build reliable and efficient software."
+                            .into()
+                    ),
+                    age: None,
+                },
+                SearchResult {
+                    title: "Announcing Rust 1.90.0".into(),
+                    url: "https://blog.rust-lang.org/2026/10/01/Rust-1.90.0.html".into(),
+                    snippet: None,
+                    age: Some("2026-10-01T00:00:00".into()),
+                },
+                SearchResult {
+                    title: "https://doc.rust-lang.org/book/".into(),
+                    url: "https://doc.rust-lang.org/book/".into(),
+                    snippet: None,
+                    age: None,
+                },
+            ]
+        );
+        assert!(parse_response(r#"{"query": "x"}"#).unwrap().is_empty());
+        assert!(parse_response("<html>").is_err());
+    }
+
+    #[tokio::test]
+    async fn searches_the_instance() {
+        let tool = WebSearchTool::new(Box::new(Searxng::new(&mock_instance().await, 5)));
+        assert!(tool
+            .description()
+            .starts_with("Search the web using SearXNG."));
+
+        let result = tool
+            .execute(json!({"query": "rust lang", "count": 2, "freshness": "pw"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            result.output,
+            "[1] Rust Programming Language\n    https://www.rust-lang.org/\n    \
+             q=rust lang time_range=week\n\
+             [2] Announcing Rust 1.90.0 (2026-10-01T00:00:00)\n    \
+             https://blog.rust-lang.org/2026/10/01/Rust-1.90.0.html\n"
+        );
+
+        let result = tool
+            .execute(json!({"query": "rust", "site": "rust-lang.org"}))
+            .await
+            .unwrap();
+        assert!(result
+            .output
+            .contains("q=rust site:rust-lang.org time_range=-"));
+        assert!(result
+            .output
+            .contains("[3] https://doc.rust-lang.org/book/"));
+    }
+
+    #[tokio::test]
+    async fn disabled_json_format_is_explained() {
+        let tool = WebSearchTool::new(Box::new(Searxng::new(&mock_instance().await, 5)));
+        let result = tool.execute(json!({"query": "forbidden"})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("settings.yml"));
+    }
+}
@@ -1,4 +1,25 @@
 use chrono::Local;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use super::clipboard::{Clipboard, Copied};
+use super::history::InputHistory;
+use super::markdown;
+use crate::providers::ChatMessage as HistoryMessage;
+use crate::security::approval::{ApprovalDecision, ApprovalRequest};
+use crate::usage::UsageMeter;
+use crate::util::truncate_with_ellipsis;
+
+/// Header of the memory recall block prepended to user messages sent to the model.
+pub const MEMORY_CONTEXT_HEADER: &str = "[Memory context]";
+
+/// The text the user typed, without the memory block added before sending.
+fn strip_memory_context(content: &str) -> &str {
+    content
+        .strip_prefix(MEMORY_CONTEXT_HEADER)
+        .and_then(|rest| rest.split_once("\n\n"))
+        .map_or(content, |(_, typed)| typed)
+}
 
 /// A single chat message.
 #[derive(Clone, Debug)]
@@ -13,6 +34,41 @@ pub enum MessageRole {
     User,
     Assistant,
     System,
+    /// Tool activity line shown while the agent works
+    Tool(ToolStatus),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToolStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// Max chars of tool arguments shown on an activity line.
+const TOOL_ARGS_PREVIEW_CHARS: usize = 60;
+
+/// One-line preview of a tool call's JSON arguments.
+///
+/// Prefers the field that says what the call does (`command`, `path`, …),
+/// falling back to the first string field, then the compact JSON.
+pub fn summarize_tool_args(arguments: &str) -> String {
+    const PREFERRED: &[&str] = &["command", "path", "query", "url", "key", "action"];
+
+    let summary = match serde_json::from_str::<serde_json::Value>(arguments) {
+        Ok(serde_json::Value::Object(map)) => PREFERRED
+            .iter()
+            .find_map(|k| map.get(*k).and_then(|v| v.as_str()))
+            .or_else(|| map.values().find_map(|v| v.as_str()))
+            .map_or_else(
+                || serde_json::Value::Object(map.clone()).to_string(),
+                str::to_string,
+            ),
+        Ok(other) => other.to_string(),
+        Err(_) => arguments.to_string(),
+    };
+    let single_line = summary.split_whitespace().collect::<Vec<_>>().join(" ");
+    truncate_with_ellipsis(&single_line, TOOL_ARGS_PREVIEW_CHARS)
 }
 
 /// App status.
@@ -22,14 +78,49 @@ pub enum AppStatus {
     Waiting,
 }
 
+/// What Ctrl+Y / Alt+Y (and `/copy`) put on the clipboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CopyTarget {
+    /// The most recent assistant message
+    Message,
+    /// The last fenced code block in the assistant's replies
+    CodeBlock,
+}
+
+/// How long a status-line notice such as "Copied" stays visible.
+const NOTICE_DURATION: Duration = Duration::from_secs(2);
+
 /// Slash-command result.
 pub enum SlashResult {
     Quit,
     Clear,
     Help,
+    /// Switch the model for the rest of the session
+    Model(String),
+    /// Switch to another provider (rebuilt through the resilient factory)
+    Provider(String),
+    /// Set the sampling temperature (validated to 0.0–2.0)
+    Temperature(f64),
+    /// Save the conversation, optionally under a new name
+    Save(Option<String>),
+    /// Replace the conversation with a saved session
+    Load(String),
+    /// Summarize older turns into a context note
+    Compact,
+    /// Show this session's token usage and estimated cost
+    Cost,
+    /// Copy the last reply or code block
+    Copy(CopyTarget),
+    /// Toggle Markdown rendering of assistant replies
+    ToggleMarkdown,
+    /// Malformed command; the message explains the expected usage
+    Invalid(String),
     None,
 }
 
+/// Accepted `/temp` range.
+const TEMPERATURE_RANGE: std::ops::RangeInclusive<f64> = 0.0..=2.0;
+
 /// Core TUI application state.
 pub struct App {
     pub messages: Vec<ChatMessage>,
@@ -41,7 +132,21 @@ pub struct App {
     pub provider_display: String,
     pub model_display: String,
     pub memory_display: String,
+    pub temperature: f64,
+    /// Render assistant replies as Markdown (off: raw text)
+    pub markdown: bool,
     pub spinner_tick: usize,
+    /// Submitted inputs, recalled with Up/Down
+    pub history: InputHistory,
+    pub clipboard: Clipboard,
+    /// Transient status-line message and when it was set
+    notice: Option<(String, Instant)>,
+    /// Shell command approval the next submitted input answers
+    pub pending_approval: Option<ApprovalRequest>,
+    /// Estimated tokens and cost of this conversation
+    pub usage: UsageMeter,
+    /// Cancels the agent turn in progress
+    pub turn: Option<CancellationToken>,
 }
 
 const SPINNER_FRAMES: &[char] = &['|', '/', '-', '\\'];
@@ -58,7 +163,15 @@ impl App {
             provider_display: provider.to_string(),
             model_display: model.to_string(),
             memory_display: memory.to_string(),
+            temperature: 0.7,
+            markdown: true,
             spinner_tick: 0,
+            history: InputHistory::default(),
+            clipboard: Clipboard::default(),
+            notice: None,
+            pending_approval: None,
+            usage: UsageMeter::default(),
+            turn: None,
         }
     }
 
@@ -72,11 +185,158 @@ impl App {
         self.scroll_offset = 0;
     }
 
+    /// Show a running tool call in the chat.
+    pub fn tool_started(&mut self, tool: &str, arguments: &str) {
+        let preview = summarize_tool_args(arguments);
+        let content = if preview.is_empty() {
+            tool.to_string()
+        } else {
+            format!("{tool}: {preview}")
+        };
+        self.push_message(MessageRole::Tool(ToolStatus::Running), &content);
+    }
+
+    /// Mark the latest running call of `tool` finished, appending its duration,
+    /// outcome and (on failure) the error on the next line.
+    pub fn tool_finished(
+        &mut self,
+        tool: &str,
+        duration: Duration,
+        success: bool,
+        error: Option<&str>,
+    ) {
+        let prefix = format!("{tool}:");
+        let running = self.messages.iter_mut().rev().find(|m| {
+            m.role == MessageRole::Tool(ToolStatus::Running)
+                && (m.content == tool || m.content.starts_with(&prefix))
+        });
+        let msg = if let Some(msg) = running {
+            msg
+        } else {
+            self.push_message(MessageRole::Tool(ToolStatus::Running), tool);
+            self.messages.last_mut().expect("just pushed")
+        };
+
+        let (status, mark) = if success {
+            (ToolStatus::Succeeded, '✓')
+        } else {
+            (ToolStatus::Failed, '✗')
+        };
+        msg.role = MessageRole::Tool(status);
+        msg.content = format!("{} ({:.1}s {mark})", msg.content, duration.as_secs_f64());
+        if let Some(error) = error.filter(|e| !e.is_empty()) {
+            msg.content.push('\n');
+            msg.content.push_str(error);
+        }
+        self.scroll_offset = 0;
+    }
+
+    /// Replace the chat with a restored conversation. Tool calls become
+    /// finished activity lines; tool output and the system prompt are not shown.
+    pub fn restore_history(&mut self, history: &[HistoryMessage], timestamp: &str) {
+        self.messages.clear();
+        let push = |messages: &mut Vec<ChatMessage>, role: MessageRole, content: String| {
+            messages.push(ChatMessage {
+                role,
+                content,
+                timestamp: timestamp.to_string(),
+            });
+        };
+        // Activity line index for each tool call id, to mark failures
+        let mut tool_lines: Vec<(String, usize)> = Vec::new();
+
+        for msg in history {
+            match msg {
+                HistoryMessage::System { .. } => {}
+                HistoryMessage::User { content } => push(
+                    &mut self.messages,
+                    MessageRole::User,
+                    strip_memory_context(content).to_string(),
+                ),
+                HistoryMessage::Assistant {
+                    content,
+                    tool_calls,
+                } => {
+                    if let Some(text) = content.as_deref().filter(|t| !t.trim().is_empty()) {
+                        push(&mut self.messages, MessageRole::Assistant, text.to_string());
+                    }
+                    for call in tool_calls.iter().flatten() {
+                        let preview = summarize_tool_args(&call.function.arguments);
+                        let line = if preview.is_empty() {
+                            call.function.name.clone()
+                        } else {
+                            format!("{}: {preview}", call.function.name)
+                        };
+                        tool_lines.push((call.id.clone(), self.messages.len()));
+                        push(
+                            &mut self.messages,
+                            MessageRole::Tool(ToolStatus::Succeeded),
+                            line,
+                        );
+                    }
+                }
+                HistoryMessage::Tool {
+                    tool_call_id,
+                    content,
+                } => {
+                    let failed_line = tool_lines
+                        .iter()
+                        .find(|(id, _)| id == tool_call_id)
+                        .map(|&(_, index)| index)
+                        .filter(|_| content.starts_with("Error"));
+                    if let Some(index) = failed_line {
+                        self.messages[index].role = MessageRole::Tool(ToolStatus::Failed);
+                    }
+                }
+            }
+        }
+        self.scroll_offset = 0;
+    }
+
     pub fn insert_char(&mut self, c: char) {
         self.input.insert(self.cursor_pos, c);
         self.cursor_pos += c.len_utf8();
     }
 
+    pub fn insert_newline(&mut self) {
+        self.insert_char('\n');
+    }
+
+    /// Insert pasted text at the cursor, normalizing CRLF/CR line endings.
+    pub fn insert_str(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        self.input.insert_str(self.cursor_pos, &text);
+        self.cursor_pos += text.len();
+    }
+
+    /// Number of logical lines in the input (at least 1).
+    pub fn input_line_count(&self) -> usize {
+        self.input.split('\n').count()
+    }
+
+    /// Byte offset where the input line containing the cursor starts.
+    fn line_start(&self) -> usize {
+        self.input[..self.cursor_pos]
+            .rfind('\n')
+            .map_or(0, |i| i + 1)
+    }
+
+    /// Byte offset where the input line containing the cursor ends (before `\n`).
+    fn line_end(&self) -> usize {
+        self.input[self.cursor_pos..]
+            .find('\n')
+            .map_or(self.input.len(), |i| self.cursor_pos + i)
+    }
+
+    /// Byte offset of the `column`-th char of the line starting at `start`,
+    /// clamped to the end of that line.
+    fn offset_in_line(&self, start: usize, column: usize) -> usize {
+        let line = self.input[start..].split('\n').next().unwrap_or("");
+        line.char_indices()
+            .nth(column)
+            .map_or(start + line.len(), |(i, _)| start + i)
+    }
+
     pub fn delete_char_before(&mut self) {
         if self.cursor_pos > 0 {
             let prev = self.input[..self.cursor_pos]
@@ -116,32 +376,241 @@ impl App {
         }
     }
 
+    /// Move the cursor to the previous input line, keeping its column.
+    ///
+    /// Returns `false` when the cursor is already on the first line, so the
+    /// caller can fall back to scrolling the chat.
+    pub fn move_cursor_up(&mut self) -> bool {
+        let start = self.line_start();
+        if start == 0 {
+            return false;
+        }
+        let column = self.input[start..self.cursor_pos].chars().count();
+        let prev_start = self.input[..start - 1].rfind('\n').map_or(0, |i| i + 1);
+        self.cursor_pos = self.offset_in_line(prev_start, column);
+        true
+    }
+
+    /// Move the cursor to the next input line, keeping its column.
+    ///
+    /// Returns `false` when the cursor is already on the last line.
+    pub fn move_cursor_down(&mut self) -> bool {
+        let end = self.line_end();
+        if end == self.input.len() {
+            return false;
+        }
+        let column = self.input[self.line_start()..self.cursor_pos]
+            .chars()
+            .count();
+        self.cursor_pos = self.offset_in_line(end + 1, column);
+        true
+    }
+
+    /// Replace the input with `text`, cursor at the end.
+    fn set_input(&mut self, text: String) {
+        self.input = text;
+        self.cursor_pos = self.input.len();
+    }
+
+    /// Recall the previous history entry. Returns `false` if there is none.
+    pub fn history_prev(&mut self) -> bool {
+        match self.history.older(&self.input) {
+            Some(entry) => {
+                self.set_input(entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Recall the next history entry (or the draft). Returns `false` if not browsing.
+    pub fn history_next(&mut self) -> bool {
+        match self.history.newer() {
+            Some(entry) => {
+                self.set_input(entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Up arrow: move within a multi-line input, recall history from the first
+    /// line of a non-empty input, otherwise scroll the chat.
+    pub fn cursor_up_or_scroll(&mut self) {
+        if self.move_cursor_up() {
+            return;
+        }
+        if self.input.is_empty() || !self.history_prev() {
+            self.scroll_up(1);
+        }
+    }
+
+    /// Down arrow: move within a multi-line input, step forward through history
+    /// from the last line, otherwise scroll the chat.
+    pub fn cursor_down_or_scroll(&mut self) {
+        if self.move_cursor_down() {
+            return;
+        }
+        if !self.history_next() {
+            self.scroll_down(1);
+        }
+    }
+
     pub fn move_cursor_home(&mut self) {
-        self.cursor_pos = 0;
+        self.cursor_pos = self.line_start();
     }
 
     pub fn move_cursor_end(&mut self) {
-        self.cursor_pos = self.input.len();
+        self.cursor_pos = self.line_end();
     }
 
     /// Submit the current input. Returns the submitted text (or empty if blank).
     pub fn submit_input(&mut self) -> String {
+        let text = self.take_input();
+        self.history.push(&text);
+        text
+    }
+
+    /// Clear the input box and return its trimmed contents.
+    pub fn take_input(&mut self) -> String {
         let text = self.input.trim().to_string();
         self.input.clear();
         self.cursor_pos = 0;
         text
     }
 
+    /// Show a shell command approval prompt; the next submitted input answers it.
+    pub fn ask_approval(&mut self, request: ApprovalRequest) {
+        self.push_message(MessageRole::System, &format!("🔐 {}", request.prompt()));
+        self.pending_approval = Some(request);
+    }
+
+    /// Report how an approval request was answered.
+    pub fn approval_answered(&mut self, decision: ApprovalDecision, unlisted: &[String]) {
+        let text = match decision {
+            ApprovalDecision::Approved => "Allowed.".to_string(),
+            ApprovalDecision::Always if unlisted.is_empty() => "Allowed.".to_string(),
+            ApprovalDecision::Always => format!(
+                "Allowed; won't ask again this session for: {}",
+                unlisted.join(", ")
+            ),
+            ApprovalDecision::Denied => "Denied.".to_string(),
+            ApprovalDecision::TimedOut => "No answer in time; the command was not run.".to_string(),
+        };
+        self.push_message(MessageRole::System, &text);
+    }
+
+    /// Drop an approval prompt the tool stopped waiting for.
+    pub fn expire_approval(&mut self) {
+        if self
+            .pending_approval
+            .as_ref()
+            .is_some_and(ApprovalRequest::is_cancelled)
+        {
+            let request = self.pending_approval.take();
+            let unlisted = request.map(|r| r.unlisted).unwrap_or_default();
+            self.approval_answered(ApprovalDecision::TimedOut, &unlisted);
+        }
+    }
+
     /// Handle slash commands. Returns the action to take.
     pub fn handle_slash_command(input: &str) -> SlashResult {
-        match input {
-            "/quit" | "/exit" | "/q" => SlashResult::Quit,
-            "/clear" | "/cls" => SlashResult::Clear,
-            "/help" | "/h" | "/?" => SlashResult::Help,
+        let (command, arg) = input
+            .split_once(char::is_whitespace)
+            .map_or((input, ""), |(c, a)| (c, a.trim()));
+        match (command, arg) {
+            ("/quit" | "/exit" | "/q", "") => SlashResult::Quit,
+            ("/clear" | "/cls", "") => SlashResult::Clear,
+            ("/help" | "/h" | "/?", "") => SlashResult::Help,
+            ("/model", "") => SlashResult::Invalid("Usage: /model <name>".into()),
+            ("/model", name) => SlashResult::Model(name.to_string()),
+            ("/provider", "") => SlashResult::Invalid("Usage: /provider <name>".into()),
+            ("/provider", name) => SlashResult::Provider(name.to_string()),
+            ("/save", "") => SlashResult::Save(None),
+            ("/save", name) => SlashResult::Save(Some(name.to_string())),
+            ("/load", "") => SlashResult::Invalid("Usage: /load <name>".into()),
+            ("/load", name) => SlashResult::Load(name.to_string()),
+            ("/markdown" | "/md", "") => SlashResult::ToggleMarkdown,
+            ("/compact", "") => SlashResult::Compact,
+            ("/cost" | "/usage", "") => SlashResult::Cost,
+            ("/copy", "") => SlashResult::Copy(CopyTarget::Message),
+            ("/copy", "code") => SlashResult::Copy(CopyTarget::CodeBlock),
+            ("/copy", _) => SlashResult::Invalid("Usage: /copy [code]".into()),
+            ("/temp" | "/temperature", value) => match value.parse::<f64>() {
+                Ok(t) if TEMPERATURE_RANGE.contains(&t) => SlashResult::Temperature(t),
+                _ => SlashResult::Invalid(
+                    "Usage: /temp <value> — temperature must be between 0.0 and 2.0".into(),
+                ),
+            },
             _ => SlashResult::None,
         }
     }
 
+    /// Text that `target` refers to, if any.
+    pub fn copy_text(&self, target: CopyTarget) -> Option<String> {
+        let mut replies = self
+            .messages
+            .iter()
+            .rev()
+            .filter(|m| m.role == MessageRole::Assistant);
+        match target {
+            CopyTarget::Message => replies.next().map(|m| m.content.clone()),
+            CopyTarget::CodeBlock => replies.find_map(|m| markdown::code_blocks(&m.content).pop()),
+        }
+    }
+
+    /// Copy `target` to the clipboard and report the outcome.
+    pub fn copy(&mut self, target: CopyTarget) {
+        let what = match target {
+            CopyTarget::Message => "reply",
+            CopyTarget::CodeBlock => "code block",
+        };
+        let Some(text) = self.copy_text(target) else {
+            self.set_notice(&format!("No {what} to copy"));
+            return;
+        };
+        match self.clipboard.copy(&text) {
+            Ok(Copied::Clipboard) => self.set_notice(&format!("Copied {what}")),
+            Ok(Copied::File(path)) => {
+                self.set_notice(&format!("Copied {what} to file"));
+                self.push_message(
+                    MessageRole::System,
+                    &format!(
+                        "No system clipboard available; saved the {what} to {}",
+                        path.display()
+                    ),
+                );
+            }
+            Err(e) => self.push_message(MessageRole::System, &format!("Copy failed: {e:#}")),
+        }
+    }
+
+    /// Cancel the agent turn in progress, denying a pending approval.
+    /// Returns `false` when no turn is running.
+    pub fn cancel_turn(&mut self) -> bool {
+        let Some(turn) = self.turn.take() else {
+            return false;
+        };
+        turn.cancel();
+        if let Some(request) = self.pending_approval.take() {
+            request.respond(ApprovalDecision::Denied);
+        }
+        self.set_notice("Cancelling…");
+        true
+    }
+
+    pub fn set_notice(&mut self, text: &str) {
+        self.notice = Some((text.to_string(), Instant::now()));
+    }
+
+    /// The status-line notice, while it is still fresh.
+    pub fn notice(&self) -> Option<&str> {
+        self.notice
+            .as_ref()
+            .filter(|(_, at)| at.elapsed() < NOTICE_DURATION)
+            .map(|(text, _)| text.as_str())
+    }
+
     pub fn scroll_up(&mut self, amount: u16) {
         self.scroll_offset = self.scroll_offset.saturating_add(amount);
     }
@@ -215,6 +684,54 @@ mod tests {
         assert_eq!(app.cursor_pos, 5); // already at end
     }
 
+    #[test]
+    fn test_summarize_tool_args() {
+        assert_eq!(
+            summarize_tool_args(r#"{"command":"cargo test\n  --all"}"#),
+            "cargo test --all"
+        );
+        assert_eq!(
+            summarize_tool_args(r#"{"content":"lots of text","path":"src/main.rs"}"#),
+            "src/main.rs"
+        );
+        assert_eq!(summarize_tool_args(r#"{"limit":5}"#), r#"{"limit":5}"#);
+        assert_eq!(summarize_tool_args("not json"), "not json");
+        let long = format!(r#"{{"command":"{}"}}"#, "x".repeat(100));
+        assert!(summarize_tool_args(&long).chars().count() <= TOOL_ARGS_PREVIEW_CHARS + 3);
+    }
+
+    #[test]
+    fn test_tool_activity_lines() {
+        let mut app = App::new("test", "test", "none");
+        app.push_message(MessageRole::User, "run the tests");
+        app.tool_started("shell", r#"{"command":"cargo test"}"#);
+        app.tool_started("file_read", r#"{"path":"Cargo.toml"}"#);
+        assert_eq!(app.messages[1].role, MessageRole::Tool(ToolStatus::Running));
+
+        app.tool_finished("file_read", Duration::from_millis(40), true, None);
+        app.tool_finished(
+            "shell",
+            Duration::from_millis(2300),
+            false,
+            Some("exit status 101"),
+        );
+
+        assert_eq!(app.messages[1].role, MessageRole::Tool(ToolStatus::Failed));
+        assert_eq!(
+            app.messages[1].content,
+            "shell: cargo test (2.3s ✗)\nexit status 101"
+        );
+        assert_eq!(
+            app.messages[2].role,
+            MessageRole::Tool(ToolStatus::Succeeded)
+        );
+        assert_eq!(app.messages[2].content, "file_read: Cargo.toml (0.0s ✓)");
+
+        // A finish without a matching start still shows up
+        app.tool_finished("memory_store", Duration::from_secs(1), true, None);
+        assert_eq!(app.messages[3].content, "memory_store (1.0s ✓)");
+    }
+
     #[test]
     fn test_submit_input() {
         let mut app = App::new("test", "test", "none");
@@ -246,6 +763,131 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_save_load_slash_commands() {
+        assert!(matches!(
+            App::handle_slash_command("/save"),
+            SlashResult::Save(None)
+        ));
+        assert!(matches!(
+            App::handle_slash_command("/save refactor-notes"),
+            SlashResult::Save(Some(n)) if n == "refactor-notes"
+        ));
+        assert!(matches!(
+            App::handle_slash_command("/load refactor-notes"),
+            SlashResult::Load(n) if n == "refactor-notes"
+        ));
+        assert!(matches!(
+            App::handle_slash_command("/load"),
+            SlashResult::Invalid(_)
+        ));
+    }
+
+    #[test]
+    fn restore_history_renders_roles() {
+        use crate::providers::{FunctionCall, ToolCall};
+
+        let history = vec![
+            HistoryMessage::System {
+                content: "prompt".into(),
+            },
+            HistoryMessage::User {
+                content: format!("{MEMORY_CONTEXT_HEADER}\n- lang: rust\n\nrun the tests"),
+            },
+            HistoryMessage::Assistant {
+                content: None,
+                tool_calls: Some(vec![ToolCall {
+                    id: "call_1".into(),
+                    function: FunctionCall {
+                        name: "shell".into(),
+                        arguments: r#"{"command":"cargo test"}"#.into(),
+                    },
+                }]),
+            },
+            HistoryMessage::Tool {
+                tool_call_id: "call_1".into(),
+                content: "Error: exit status 101".into(),
+            },
+            HistoryMessage::Assistant {
+                content: Some("Tests fail.".into()),
+                tool_calls: None,
+            },
+        ];
+
+        let mut app = App::new("openrouter", "model", "sqlite");
+        app.push_message(MessageRole::System, "Welcome");
+        app.restore_history(&history, "12:00:00");
+
+        let shown: Vec<_> = app
+            .messages
+            .iter()
+            .map(|m| (m.role.clone(), m.content.as_str()))
+            .collect();
+        assert_eq!(
+            shown,
+            vec![
+                (MessageRole::User, "run the tests"),
+                (MessageRole::Tool(ToolStatus::Failed), "shell: cargo test"),
+                (MessageRole::Assistant, "Tests fail."),
+            ]
+        );
+        assert!(app.messages.iter().all(|m| m.timestamp == "12:00:00"));
+    }
+
+    #[test]
+    fn test_session_slash_commands() {
+        assert!(matches!(
+            App::handle_slash_command("/model  gpt-4o "),
+            SlashResult::Model(m) if m == "gpt-4o"
+        ));
+        assert!(matches!(
+            App::handle_slash_command("/provider anthropic"),
+            SlashResult::Provider(p) if p == "anthropic"
+        ));
+        assert!(matches!(
+            App::handle_slash_command("/temp 1.2"),
+            SlashResult::Temperature(t) if (t - 1.2).abs() < f64::EPSILON
+        ));
+        assert!(matches!(
+            App::handle_slash_command("/compact"),
+            SlashResult::Compact
+        ));
+        assert!(matches!(
+            App::handle_slash_command("/cost"),
+            SlashResult::Cost
+        ));
+        assert!(matches!(
+            App::handle_slash_command("/usage"),
+            SlashResult::Cost
+        ));
+        assert!(matches!(
+            App::handle_slash_command("/md"),
+            SlashResult::ToggleMarkdown
+        ));
+        assert!(matches!(
+            App::handle_slash_command("/temp 0"),
+            SlashResult::Temperature(t) if t == 0.0
+        ));
+        for bad in [
+            "/temp 2.5",
+            "/temp -1",
+            "/temp hot",
+            "/temp",
+            "/model",
+            "/provider",
+        ] {
+            assert!(
+                matches!(App::handle_slash_command(bad), SlashResult::Invalid(_)),
+                "{bad} should be rejected"
+            );
+        }
+        // Arguments don't turn other commands into something else
+        assert!(matches!(
+            App::handle_slash_command("/quit now"),
+            SlashResult::None
+        ));
+    }
+
     #[test]
     fn test_scroll() {
         let mut app = App::new("test", "test", "none");
@@ -281,4 +923,133 @@ mod tests {
         assert_eq!(app.input, "好");
         assert_eq!(app.cursor_pos, 0);
     }
+
+    #[test]
+    fn test_newline_and_paste() {
+        let mut app = App::new("test", "test", "none");
+        app.insert_str("fn main() {");
+        app.insert_newline();
+        app.insert_str("    run();\r\n}");
+        assert_eq!(app.input, "fn main() {\n    run();\n}");
+        assert_eq!(app.cursor_pos, app.input.len());
+        assert_eq!(app.input_line_count(), 3);
+
+        let text = app.submit_input();
+        assert_eq!(text, "fn main() {\n    run();\n}");
+    }
+
+    #[test]
+    fn test_vertical_cursor_movement() {
+        let mut app = App::new("test", "test", "none");
+        app.insert_str("first line\nab\n你好世界");
+        // Cursor at end of "你好世界" (column 4)
+        assert!(app.move_cursor_up());
+        assert_eq!(app.cursor_pos, "first line\nab".len()); // clamped to "ab"
+        assert!(app.move_cursor_up());
+        assert_eq!(app.cursor_pos, 2); // column 2 of "first line"
+        assert!(!app.move_cursor_up());
+
+        assert!(app.move_cursor_down());
+        assert_eq!(app.cursor_pos, "first line\nab".len());
+        assert!(app.move_cursor_down());
+        assert_eq!(app.cursor_pos, "first line\nab\n你好".len());
+        assert!(!app.move_cursor_down());
+
+        // Past the last line with no history, Down falls back to chat scrolling
+        app.scroll_up(3);
+        app.cursor_down_or_scroll();
+        assert_eq!(app.scroll_offset, 2);
+    }
+
+    #[test]
+    fn test_up_down_recall_history() {
+        let mut app = App::new("test", "test", "none");
+        // Empty input and empty history: Up scrolls the chat
+        app.cursor_up_or_scroll();
+        assert_eq!(app.scroll_offset, 1);
+
+        app.input = "first".into();
+        app.submit_input();
+        app.input = "/help".into();
+        app.submit_input();
+
+        // Empty input still scrolls; Ctrl+Up recalls regardless
+        app.cursor_up_or_scroll();
+        assert_eq!(app.scroll_offset, 2);
+        assert!(app.history_prev());
+        assert_eq!(app.input, "/help");
+        assert_eq!(app.cursor_pos, app.input.len());
+
+        // Non-empty input on its first line: Up keeps walking back
+        app.cursor_up_or_scroll();
+        assert_eq!(app.input, "first");
+        app.cursor_up_or_scroll();
+        assert_eq!(app.input, "first");
+        assert_eq!(app.scroll_offset, 3, "past the oldest entry Up scrolls");
+
+        app.cursor_down_or_scroll();
+        assert_eq!(app.input, "/help");
+        app.cursor_down_or_scroll();
+        assert_eq!(app.input, "", "draft restored");
+    }
+
+    #[test]
+    fn test_clear_keeps_input_history() {
+        let mut app = App::new("test", "test", "none");
+        app.input = "/clear".into();
+        let text = app.submit_input();
+        assert!(matches!(
+            App::handle_slash_command(&text),
+            SlashResult::Clear
+        ));
+        app.messages.clear();
+        assert_eq!(app.history.entries(), ["/clear"]);
+    }
+
+    #[test]
+    fn test_home_end_stay_on_current_line() {
+        let mut app = App::new("test", "test", "none");
+        app.insert_str("one\ntwo\nthree");
+        app.move_cursor_up();
+        app.move_cursor_home();
+        assert_eq!(app.cursor_pos, "one\n".len());
+        app.move_cursor_end();
+        assert_eq!(app.cursor_pos, "one\ntwo".len());
+    }
+
+    #[test]
+    fn test_copy_targets() {
+        let mut app = App::new("test", "test", "none");
+        assert_eq!(app.copy_text(CopyTarget::Message), None);
+        app.copy(CopyTarget::Message);
+        assert_eq!(app.notice(), Some("No reply to copy"));
+
+        app.push_message(MessageRole::Assistant, "Run:\n```sh\ncargo test\n```");
+        app.push_message(MessageRole::User, "and then?");
+        app.push_message(MessageRole::Assistant, "That's it.");
+        app.push_message(MessageRole::System, "Session saved");
+
+        assert_eq!(
+            app.copy_text(CopyTarget::Message).as_deref(),
+            Some("That's it.")
+        );
+        // The latest reply has no code, so the previous block is used
+        assert_eq!(
+            app.copy_text(CopyTarget::CodeBlock).as_deref(),
+            Some("cargo test")
+        );
+
+        assert!(matches!(
+            App::handle_slash_command("/copy code"),
+            SlashResult::Copy(CopyTarget::CodeBlock)
+        ));
+        assert!(matches!(
+            App::handle_slash_command("/copy"),
+            SlashResult::Copy(CopyTarget::Message)
+        ));
+        assert!(matches!(
+            App::handle_slash_command("/copy everything"),
+            SlashResult::Invalid(_)
+        ));
+    }
 }
@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a copy ended up.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Copied {
+    Clipboard,
+    /// No system clipboard (headless, SSH without forwarding, …): text was
+    /// written to this file instead
+    File(PathBuf),
+}
+
+/// System clipboard with a file fallback.
+///
+/// The system clipboard is opened on first use and kept for the rest of the
+/// session: on X11/Wayland the copying process serves the contents, so
+/// dropping the handle right after a copy could lose them.
+#[derive(Default)]
+pub struct Clipboard {
+    system: Option<arboard::Clipboard>,
+    /// Opening the system clipboard was already attempted
+    probed: bool,
+    fallback: Option<PathBuf>,
+}
+
+impl Clipboard {
+    /// Location of the fallback copy file inside a workspace.
+    pub fn fallback_path(workspace_dir: &Path) -> PathBuf {
+        workspace_dir.join("state").join("last_copy.txt")
+    }
+
+    pub fn with_fallback(path: PathBuf) -> Self {
+        Self {
+            fallback: Some(path),
+            ..Self::default()
+        }
+    }
+
+    pub fn copy(&mut self, text: &str) -> Result<Copied> {
+        if !self.probed {
+            self.probed = true;
+            self.system = arboard::Clipboard::new()
+                .inspect_err(|e| tracing::debug!("系统剪贴板不可用: {e}"))
+                .ok();
+        }
+        if let Some(system) = &mut self.system {
+            match system.set_text(text) {
+                Ok(()) => return Ok(Copied::Clipboard),
+                Err(e) => tracing::debug!("写入系统剪贴板失败: {e}"),
+            }
+        }
+
+        let path = self.fallback.clone().context("No clipboard available")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(Copied::File(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A clipboard that never touches the system one.
+    fn headless(fallback: Option<PathBuf>) -> Clipboard {
+        Clipboard {
+            system: None,
+            probed: true,
+            fallback,
+        }
+    }
+
+    #[test]
+    fn falls_back_to_file_without_system_clipboard() {
+        let tmp = TempDir::new().unwrap();
+        let path = Clipboard::fallback_path(tmp.path());
+        let mut clipboard = headless(Some(path.clone()));
+
+        assert_eq!(clipboard.copy("first").unwrap(), Copied::File(path.clone()));
+        assert_eq!(
+            clipboard.copy("second").unwrap(),
+            Copied::File(path.clone())
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+    }
+
+    #[test]
+    fn errors_without_clipboard_or_fallback() {
+        let err = headless(None).copy("text").unwrap_err();
+        assert!(err.to_string().contains("No clipboard"));
+    }
+}
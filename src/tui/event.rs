@@ -1,7 +1,12 @@
+use crate::observability::traits::ObserverMetric;
+use crate::observability::{Observer, ObserverEvent};
 use crossterm::event::{self, Event, KeyEvent};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
 
+use crate::usage::TurnUsage;
+
 /// Events flowing through the TUI.
 #[derive(Debug)]
 pub enum AppEvent {
@@ -9,12 +14,79 @@ pub enum AppEvent {
     Key(KeyEvent),
     /// Periodic tick for spinner animation.
     Tick,
+    /// Text pasted into the terminal (bracketed paste), possibly multi-line.
+    Paste(String),
     /// Terminal was resized.
     Resize(u16, u16),
-    /// Agent returned a response.
-    AgentResponse(String),
+    /// Agent returned a response, with the turn's estimated token usage.
+    AgentResponse(String, TurnUsage),
     /// Agent encountered an error.
     AgentError(String),
+    /// The user cancelled the turn (Ctrl+C or Esc while waiting).
+    AgentCancelled,
+    /// `/compact` finished: number of messages summarized, or the error.
+    Compacted(Result<usize, String>),
+    /// The agent started running a tool.
+    ToolStarted { tool: String, arguments: String },
+    /// A tool call finished.
+    ToolFinished {
+        tool: String,
+        duration: Duration,
+        success: bool,
+        error: Option<String>,
+    },
+}
+
+/// Observer that forwards tool activity to the TUI event loop.
+///
+/// Sends on the same channel as the agent's final response, so tool lines
+/// always appear before the answer they led to.
+pub struct TuiObserver {
+    inner: Arc<dyn Observer>,
+    tx: mpsc::UnboundedSender<AppEvent>,
+}
+
+impl TuiObserver {
+    pub fn new(inner: Arc<dyn Observer>, tx: mpsc::UnboundedSender<AppEvent>) -> Self {
+        Self { inner, tx }
+    }
+}
+
+impl Observer for TuiObserver {
+    fn record_event(&self, event: &ObserverEvent) {
+        self.inner.record_event(event);
+        let forwarded = match event {
+            ObserverEvent::ToolStart { tool, arguments } => AppEvent::ToolStarted {
+                tool: tool.clone(),
+                arguments: arguments.clone(),
+            },
+            ObserverEvent::ToolCall {
+                tool,
+                duration,
+                success,
+                error,
+            } => AppEvent::ToolFinished {
+                tool: tool.clone(),
+                duration: *duration,
+                success: *success,
+                error: error.clone(),
+            },
+            _ => return,
+        };
+        let _ = self.tx.send(forwarded);
+    }
+
+    fn record_metric(&self, metric: &ObserverMetric) {
+        self.inner.record_metric(metric);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+
+    fn name(&self) -> &str {
+        "tui"
+    }
 }
 
 /// Bridges crossterm blocking event reads into a tokio mpsc channel.
@@ -33,6 +105,7 @@ pub fn spawn_event_reader(tx: mpsc::UnboundedSender<AppEvent>) {
                     if let Ok(ev) = event::read() {
                         let app_ev = match ev {
                             Event::Key(k) => AppEvent::Key(k),
+                            Event::Paste(text) => AppEvent::Paste(text),
                             Event::Resize(w, h) => AppEvent::Resize(w, h),
                             _ => continue,
                         };
@@ -66,8 +139,42 @@ mod tests {
 
     #[test]
     fn test_agent_response_event() {
-        let ev = AppEvent::AgentResponse("hello".to_string());
-        assert!(matches!(ev, AppEvent::AgentResponse(s) if s == "hello"));
+        let ev = AppEvent::AgentResponse("hello".to_string(), TurnUsage::default());
+        assert!(matches!(ev, AppEvent::AgentResponse(s, _) if s == "hello"));
+    }
+
+    #[test]
+    fn test_paste_event() {
+        let ev = AppEvent::Paste("a\nb".to_string());
+        assert!(matches!(ev, AppEvent::Paste(s) if s.lines().count() == 2));
+    }
+
+    #[test]
+    fn tui_observer_forwards_tool_events_only() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let obs = TuiObserver::new(Arc::new(crate::observability::NoopObserver), tx);
+
+        obs.record_event(&ObserverEvent::HeartbeatTick);
+        obs.record_event(&ObserverEvent::ToolStart {
+            tool: "shell".into(),
+            arguments: r#"{"command":"cargo test"}"#.into(),
+        });
+        obs.record_event(&ObserverEvent::ToolCall {
+            tool: "shell".into(),
+            duration: Duration::from_millis(2300),
+            success: false,
+            error: Some("exit status 101".into()),
+        });
+
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            AppEvent::ToolStarted { tool, .. } if tool == "shell"
+        ));
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            AppEvent::ToolFinished { success: false, error: Some(e), .. } if e == "exit status 101"
+        ));
+        assert!(rx.try_recv().is_err());
     }
 
     #[test]
@@ -0,0 +1,69 @@
+//! Syntax highlighting for fenced code blocks, via syntect (the default
+//! `syntax-highlighting` feature).
+//!
+//! One [`CodeHighlighter`] per fence, fed the block line by line, so a block
+//! comment or multi-line string keeps its colour past its first line.
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Span;
+use std::sync::LazyLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+static THEME: LazyLock<Theme> = LazyLock::new(|| {
+    ThemeSet::load_defaults()
+        .themes
+        .remove("base16-eighties.dark")
+        .unwrap_or_default()
+});
+
+/// Highlighter for the lines of one fenced code block.
+pub struct CodeHighlighter(HighlightLines<'static>);
+
+impl CodeHighlighter {
+    /// A highlighter for the fence language `lang` (`rust`, `py`, `sh`...),
+    /// `None` when syntect doesn't know it.
+    pub fn new(lang: &str) -> Option<Self> {
+        if lang.is_empty() {
+            return None;
+        }
+        let syntax = SYNTAXES.find_syntax_by_token(lang)?;
+        Some(Self(HighlightLines::new(syntax, &THEME)))
+    }
+
+    /// Highlight the next line of the block on top of `base`.
+    pub fn line(&mut self, line: &str, base: Style) -> Vec<Span<'static>> {
+        // The newline-terminated syntaxes need the line ending to close
+        // line comments and the like
+        let line_with_ending = format!("{line}\n");
+        let Ok(ranges) = self.0.highlight_line(&line_with_ending, &SYNTAXES) else {
+            return vec![Span::styled(line.to_string(), base)];
+        };
+        ranges
+            .into_iter()
+            .filter_map(|(style, text)| {
+                let text = text.strip_suffix('\n').unwrap_or(text);
+                (!text.is_empty()).then(|| Span::styled(text.to_string(), span_style(style, base)))
+            })
+            .collect()
+    }
+}
+
+/// `base` with the foreground and font style syntect chose.
+fn span_style(style: syntect::highlighting::Style, base: Style) -> Style {
+    let fg = style.foreground;
+    let mut span = base.fg(Color::Rgb(fg.r, fg.g, fg.b));
+    for (font, modifier) in [
+        (FontStyle::BOLD, Modifier::BOLD),
+        (FontStyle::ITALIC, Modifier::ITALIC),
+        (FontStyle::UNDERLINE, Modifier::UNDERLINED),
+    ] {
+        if style.font_style.contains(font) {
+            span = span.add_modifier(modifier);
+        }
+    }
+    span
+}
@@ -0,0 +1,240 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of submitted inputs kept (oldest dropped first).
+const MAX_ENTRIES: usize = 200;
+
+/// Submitted-input history with shell-style Up/Down recall.
+///
+/// Entries are ordered oldest → newest. Re-submitting an existing entry moves it
+/// to the newest slot instead of storing a duplicate. When created with
+/// [`InputHistory::load`], every push is written back to that file.
+#[derive(Debug, Default)]
+pub struct InputHistory {
+    entries: Vec<String>,
+    /// Index of the entry currently shown while browsing
+    position: Option<usize>,
+    /// Input that was being typed before browsing started
+    draft: String,
+    path: Option<PathBuf>,
+}
+
+impl InputHistory {
+    /// Location of the persisted history inside a workspace.
+    pub fn default_path(workspace_dir: &Path) -> PathBuf {
+        workspace_dir.join("state").join("tui_history.json")
+    }
+
+    /// Load history from `path`; a missing or unreadable file starts empty.
+    pub fn load(path: &Path) -> Self {
+        let mut entries: Vec<String> = fs::read_to_string(path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        if entries.len() > MAX_ENTRIES {
+            entries.drain(..entries.len() - MAX_ENTRIES);
+        }
+        Self {
+            entries,
+            path: Some(path.to_path_buf()),
+            ..Self::default()
+        }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Record a submitted input and stop browsing.
+    pub fn push(&mut self, entry: &str) {
+        self.reset();
+        if entry.trim().is_empty() {
+            return;
+        }
+        self.entries.retain(|e| e != entry);
+        self.entries.push(entry.to_string());
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.save();
+    }
+
+    /// Step to the previous (older) entry.
+    ///
+    /// `current` is the input box contents; if it no longer matches the entry
+    /// being browsed (it was edited), browsing restarts from the newest entry
+    /// with the edited text as the new draft. Returns `None` when there is
+    /// nothing older to show.
+    pub fn older(&mut self, current: &str) -> Option<String> {
+        let pos = match self.position {
+            Some(pos) if self.entries.get(pos).is_some_and(|e| e == current) => {
+                if pos == 0 {
+                    return None;
+                }
+                pos - 1
+            }
+            _ => {
+                if self.entries.is_empty() {
+                    return None;
+                }
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+        };
+        self.position = Some(pos);
+        Some(self.entries[pos].clone())
+    }
+
+    /// Step to the next (newer) entry; past the newest entry the draft is
+    /// restored. Returns `None` when not browsing.
+    pub fn newer(&mut self) -> Option<String> {
+        let pos = self.position?;
+        if pos + 1 < self.entries.len() {
+            self.position = Some(pos + 1);
+            Some(self.entries[pos + 1].clone())
+        } else {
+            self.position = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+
+    /// Index of the newest entry before `before` that contains `query`
+    /// (reverse incremental search). Pass `entries().len()` to start from the newest.
+    pub fn search(&self, query: &str, before: usize) -> Option<usize> {
+        self.entries[..before.min(self.entries.len())]
+            .iter()
+            .rposition(|e| e.contains(query))
+    }
+
+    pub fn is_browsing(&self) -> bool {
+        self.position.is_some()
+    }
+
+    /// Stop browsing and forget the saved draft.
+    pub fn reset(&mut self) {
+        self.position = None;
+        self.draft.clear();
+    }
+
+    /// Persist to the backing file (best-effort; the TUI keeps working without it).
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        match serde_json::to_vec_pretty(&self.entries) {
+            Ok(json) => {
+                if let Err(e) = fs::write(path, json) {
+                    tracing::warn!("保存 TUI 输入历史失败 {}: {e}", path.display());
+                }
+            }
+            Err(e) => tracing::warn!("序列化 TUI 输入历史失败: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn history(entries: &[&str]) -> InputHistory {
+        let mut h = InputHistory::default();
+        for e in entries {
+            h.push(e);
+        }
+        h
+    }
+
+    #[test]
+    fn empty_history_recalls_nothing() {
+        let mut h = InputHistory::default();
+        assert_eq!(h.older("typing"), None);
+        assert_eq!(h.newer(), None);
+        assert!(!h.is_browsing());
+    }
+
+    #[test]
+    fn navigation_stops_at_both_ends_and_restores_draft() {
+        let mut h = history(&["one", "two", "three"]);
+        assert_eq!(h.older("draft").as_deref(), Some("three"));
+        assert_eq!(h.older("three").as_deref(), Some("two"));
+        assert_eq!(h.older("two").as_deref(), Some("one"));
+        assert_eq!(h.older("one"), None, "already at the oldest entry");
+
+        assert_eq!(h.newer().as_deref(), Some("two"));
+        assert_eq!(h.newer().as_deref(), Some("three"));
+        assert_eq!(h.newer().as_deref(), Some("draft"));
+        assert_eq!(h.newer(), None, "past the newest entry");
+        assert!(!h.is_browsing());
+    }
+
+    #[test]
+    fn duplicates_collapse_to_newest() {
+        let h = history(&["a", "b", "a", "  ", ""]);
+        assert_eq!(h.entries(), ["b", "a"]);
+    }
+
+    #[test]
+    fn editing_recalled_entry_adds_new_item() {
+        let mut h = history(&["fix the bug", "/help"]);
+        assert_eq!(h.older("").as_deref(), Some("/help"));
+        assert_eq!(h.older("/help").as_deref(), Some("fix the bug"));
+
+        // Edited text no longer matches: browsing restarts from the newest entry
+        assert_eq!(h.older("fix the bug please").as_deref(), Some("/help"));
+        assert_eq!(h.newer().as_deref(), Some("fix the bug please"));
+
+        h.push("fix the bug please");
+        assert_eq!(h.entries(), ["fix the bug", "/help", "fix the bug please"]);
+    }
+
+    #[test]
+    fn search_walks_back_through_matches() {
+        let h = history(&["cargo build", "ls", "cargo test", "git status"]);
+        let newest = h.search("cargo", h.entries().len());
+        assert_eq!(newest, Some(2));
+        assert_eq!(h.search("cargo", 2), Some(0));
+        assert_eq!(h.search("cargo", 0), None);
+        assert_eq!(h.search("docker", 4), None);
+        assert_eq!(
+            h.search("", 4),
+            Some(3),
+            "empty query matches the newest entry"
+        );
+    }
+
+    #[test]
+    fn caps_entry_count() {
+        let mut h = InputHistory::default();
+        for i in 0..MAX_ENTRIES + 5 {
+            h.push(&format!("msg {i}"));
+        }
+        assert_eq!(h.entries().len(), MAX_ENTRIES);
+        assert_eq!(h.entries()[0], "msg 5");
+    }
+
+    #[test]
+    fn persists_across_loads() {
+        let tmp = TempDir::new().unwrap();
+        let path = InputHistory::default_path(tmp.path());
+
+        let mut h = InputHistory::load(&path);
+        assert!(h.entries().is_empty());
+        h.push("first");
+        h.push("second\nline");
+
+        let reloaded = InputHistory::load(&path);
+        assert_eq!(reloaded.entries(), ["first", "second\nline"]);
+    }
+
+    #[test]
+    fn corrupt_file_starts_empty() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("tui_history.json");
+        fs::write(&path, "not json").unwrap();
+        assert!(InputHistory::load(&path).entries().is_empty());
+    }
+}
@@ -0,0 +1,417 @@
+//! Markdown rendering for assistant messages in the chat pane.
+//!
+//! A small line-oriented renderer instead of a full Markdown parser:
+//! headings, emphasis, inline code, links, lists, quotes, rules and fenced
+//! code blocks, highlighted by language with syntect when built with the
+//! `syntax-highlighting` feature (see [`super::highlight`]). Anything it
+//! doesn't recognise — including an unterminated fence or `**` mid-stream —
+//! is shown as-is.
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Span;
+
+/// Background for fenced code blocks.
+pub const CODE_BG: Color = Color::Indexed(236);
+
+/// One source line rendered as styled spans (not yet wrapped).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedLine {
+    pub spans: Vec<Span<'static>>,
+    /// Part of a fenced code block: padded to full width with the code background
+    pub code_block: bool,
+}
+
+impl RenderedLine {
+    pub fn text(spans: Vec<Span<'static>>) -> Self {
+        Self {
+            spans,
+            code_block: false,
+        }
+    }
+
+    fn code(spans: Vec<Span<'static>>) -> Self {
+        Self {
+            spans,
+            code_block: true,
+        }
+    }
+}
+
+/// Render Markdown `text` into one styled line per source line.
+pub fn render(text: &str, base: Style) -> Vec<RenderedLine> {
+    let mut lines = Vec::new();
+    // The open fence, if inside a code block
+    let mut fence: Option<Fence> = None;
+
+    for raw in text.lines() {
+        let trimmed = raw.trim_start();
+        if let Some(info) = trimmed.strip_prefix("```") {
+            if fence.take().is_none() {
+                let lang = info.trim().to_ascii_lowercase();
+                let label = if lang.is_empty() { "code" } else { &lang };
+                lines.push(RenderedLine::code(vec![Span::styled(
+                    format!(" {label} "),
+                    code_style().fg(Color::DarkGray),
+                )]));
+                fence = Some(Fence::open(&lang));
+            }
+            continue;
+        }
+        match &mut fence {
+            Some(fence) => lines.push(RenderedLine::code(fence.line(raw))),
+            None => lines.push(render_block_line(raw, base)),
+        }
+    }
+    lines
+}
+
+/// Contents of the fenced code blocks in `text`, in order. An unterminated
+/// fence runs to the end of the text.
+pub fn code_blocks(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Option<Vec<&str>> = None;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            match current.take() {
+                Some(lines) => blocks.push(lines.join("\n")),
+                None => current = Some(Vec::new()),
+            }
+        } else if let Some(lines) = &mut current {
+            lines.push(line);
+        }
+    }
+    blocks.extend(current.map(|lines| lines.join("\n")));
+    blocks
+}
+
+fn code_style() -> Style {
+    Style::default().bg(CODE_BG)
+}
+
+/// An open fenced code block. Highlighting state carries from one line to
+/// the next, so multi-line comments and strings stay coloured.
+struct Fence {
+    #[cfg(feature = "syntax-highlighting")]
+    highlighter: Option<super::highlight::CodeHighlighter>,
+}
+
+impl Fence {
+    #[cfg_attr(not(feature = "syntax-highlighting"), allow(unused_variables))]
+    fn open(lang: &str) -> Self {
+        Self {
+            #[cfg(feature = "syntax-highlighting")]
+            highlighter: super::highlight::CodeHighlighter::new(lang),
+        }
+    }
+
+    /// Render the next line of the block.
+    #[cfg_attr(not(feature = "syntax-highlighting"), allow(clippy::unused_self))]
+    fn line(&mut self, line: &str) -> Vec<Span<'static>> {
+        #[cfg(feature = "syntax-highlighting")]
+        if let Some(highlighter) = &mut self.highlighter {
+            return highlighter.line(line, code_style());
+        }
+        vec![Span::styled(line.to_string(), code_style())]
+    }
+}
+
+/// Render a line outside code blocks: block markers first, then inline markup.
+fn render_block_line(line: &str, base: Style) -> RenderedLine {
+    let indent_len = line.len() - line.trim_start().len();
+    let (indent, body) = line.split_at(indent_len);
+
+    // Heading
+    let level = body.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&level) && body[level..].starts_with(' ') {
+        let mut style = base.fg(Color::Cyan).add_modifier(Modifier::BOLD);
+        if level == 1 {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        return RenderedLine::text(parse_inline(body[level..].trim(), style));
+    }
+
+    // Horizontal rule
+    let compact: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.len() >= 3
+        && ["-", "*", "_"]
+            .iter()
+            .any(|m| compact.chars().all(|c| c.to_string() == *m))
+    {
+        return RenderedLine::text(vec![Span::styled(
+            "─".repeat(24),
+            Style::default().fg(Color::DarkGray),
+        )]);
+    }
+
+    // Block quote
+    if let Some(quoted) = body.strip_prefix('>') {
+        let style = base.fg(Color::Gray).add_modifier(Modifier::ITALIC);
+        let mut spans = vec![Span::styled(
+            format!("{indent}│ "),
+            Style::default().fg(Color::DarkGray),
+        )];
+        spans.extend(parse_inline(quoted.trim_start(), style));
+        return RenderedLine::text(spans);
+    }
+
+    // Bullet list
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(item) = body.strip_prefix(marker) {
+            let mut spans = vec![Span::styled(format!("{indent}• "), base.fg(Color::Cyan))];
+            spans.extend(parse_inline(item, base));
+            return RenderedLine::text(spans);
+        }
+    }
+
+    // Ordered list: keep the number, style it like a bullet
+    let digits = body.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 && body[digits..].starts_with(". ") {
+        let mut spans = vec![Span::styled(
+            format!("{indent}{}", &body[..digits + 2]),
+            base.fg(Color::Cyan),
+        )];
+        spans.extend(parse_inline(&body[digits + 2..], base));
+        return RenderedLine::text(spans);
+    }
+
+    let mut spans = Vec::new();
+    if !indent.is_empty() {
+        spans.push(Span::styled(indent.to_string(), base));
+    }
+    spans.extend(parse_inline(body, base));
+    RenderedLine::text(spans)
+}
+
+/// Parse inline markup. Unclosed markers are kept as literal text.
+fn parse_inline(text: &str, base: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some((styled, consumed)) = inline_element(rest, base, plain.chars().last()) {
+            if !plain.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut plain), base));
+            }
+            spans.extend(styled);
+            rest = &rest[consumed..];
+        } else {
+            plain.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    if !plain.is_empty() {
+        spans.push(Span::styled(plain, base));
+    }
+    spans
+}
+
+/// Try to parse one inline element at the start of `text`, returning its
+/// spans and the number of bytes consumed.
+fn inline_element(
+    text: &str,
+    base: Style,
+    prev: Option<char>,
+) -> Option<(Vec<Span<'static>>, usize)> {
+    if let Some(inner) = text.strip_prefix('`') {
+        let end = inner.find('`')?;
+        let code = Span::styled(inner[..end].to_string(), base.fg(Color::Yellow).bg(CODE_BG));
+        return Some((vec![code], end + 2));
+    }
+
+    for (marker, modifier) in [
+        ("**", Modifier::BOLD),
+        ("__", Modifier::BOLD),
+        ("~~", Modifier::CROSSED_OUT),
+    ] {
+        if let Some(inner) = text.strip_prefix(marker) {
+            let end = inner.find(marker).filter(|&end| end > 0)?;
+            let spans = parse_inline(&inner[..end], base.add_modifier(modifier));
+            return Some((spans, end + 2 * marker.len()));
+        }
+    }
+
+    for marker in ['*', '_'] {
+        if let Some(inner) = text.strip_prefix(marker) {
+            // `_` inside words (snake_case) is not emphasis
+            if marker == '_' && prev.is_some_and(char::is_alphanumeric) {
+                return None;
+            }
+            if inner.starts_with(char::is_whitespace) {
+                return None;
+            }
+            let end = inner.find(marker).filter(|&end| end > 0)?;
+            let after = inner[end + 1..].chars().next();
+            if marker == '_' && after.is_some_and(char::is_alphanumeric) {
+                return None;
+            }
+            let spans = parse_inline(&inner[..end], base.add_modifier(Modifier::ITALIC));
+            return Some((spans, end + 2));
+        }
+    }
+
+    if let Some(inner) = text.strip_prefix('[') {
+        let label_end = inner.find("](")?;
+        let url_start = label_end + 2;
+        let url_end = url_start + inner[url_start..].find(')')?;
+        let mut spans = parse_inline(
+            &inner[..label_end],
+            base.fg(Color::Blue).add_modifier(Modifier::UNDERLINED),
+        );
+        spans.push(Span::styled(
+            format!(" ({})", &inner[url_start..url_end]),
+            Style::default().fg(Color::DarkGray),
+        ));
+        return Some((spans, url_end + 2));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_of(line: &RenderedLine) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    fn span<'a>(line: &'a RenderedLine, content: &str) -> &'a Span<'static> {
+        line.spans
+            .iter()
+            .find(|s| s.content == content)
+            .unwrap_or_else(|| panic!("no span {content:?} in {:?}", line.spans))
+    }
+
+    #[test]
+    fn inline_emphasis_and_code() {
+        let lines = render(
+            "Use **bold**, *italic*, `cargo test` and ~~old~~.",
+            Style::default(),
+        );
+        let line = &lines[0];
+        assert_eq!(text_of(line), "Use bold, italic, cargo test and old.");
+        assert!(span(line, "bold")
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+        assert!(span(line, "italic")
+            .style
+            .add_modifier
+            .contains(Modifier::ITALIC));
+        assert_eq!(span(line, "cargo test").style.bg, Some(CODE_BG));
+        assert!(span(line, "old")
+            .style
+            .add_modifier
+            .contains(Modifier::CROSSED_OUT));
+    }
+
+    #[test]
+    fn unclosed_markers_stay_literal() {
+        let lines = render("a **half and `open and snake_case_name", Style::default());
+        assert_eq!(text_of(&lines[0]), "a **half and `open and snake_case_name");
+    }
+
+    #[test]
+    fn headings_lists_quotes_and_links() {
+        let md = "# Title\n- item **one**\n  2. second\n> quoted\n[docs](https://example.com)\n---";
+        let lines = render(md, Style::default());
+        assert_eq!(text_of(&lines[0]), "Title");
+        assert!(span(&lines[0], "Title")
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+        assert_eq!(text_of(&lines[1]), "• item one");
+        assert_eq!(text_of(&lines[2]), "  2. second");
+        assert_eq!(text_of(&lines[3]), "│ quoted");
+        assert_eq!(text_of(&lines[4]), "docs (https://example.com)");
+        assert!(text_of(&lines[5]).starts_with('─'));
+    }
+
+    #[test]
+    fn fenced_code_is_highlighted() {
+        let md = "Run:\n```rust\nlet x = \"hi\"; // note\n```\nDone";
+        let lines = render(md, Style::default());
+        assert_eq!(lines.len(), 4, "fence lines collapse into a label line");
+        assert!(!lines[0].code_block);
+        assert!(lines[1].code_block);
+        assert_eq!(text_of(&lines[1]).trim(), "rust");
+
+        let code = &lines[2];
+        assert!(code.code_block);
+        assert_eq!(text_of(code), "let x = \"hi\"; // note");
+        assert!(code.spans.iter().all(|s| s.style.bg == Some(CODE_BG)));
+        assert!(!lines[3].code_block);
+
+        if cfg!(feature = "syntax-highlighting") {
+            let plain = span(code, ";").style.fg;
+            let keyword = span(code, "let").style.fg;
+            let string = span(code, "hi").style.fg;
+            let comment = span(code, "//").style.fg;
+            for colour in [keyword, string, comment] {
+                assert!(colour.is_some() && colour != plain, "{:?}", code.spans);
+            }
+            assert!(keyword != string && string != comment && keyword != comment);
+        } else {
+            assert_eq!(code.spans, [Span::styled(text_of(code), code_style())]);
+        }
+    }
+
+    #[cfg(feature = "syntax-highlighting")]
+    #[test]
+    fn comments_and_strings_carry_across_lines() {
+        let md = "```rust\n/* start\nlet y = 1;\n*/\nlet z = 2;\n```";
+        let lines = render(md, Style::default());
+        let comment = span(&lines[1], "/*").style.fg;
+        for line in &lines[2..=3] {
+            assert!(
+                line.spans.iter().all(|s| s.style.fg == comment),
+                "{:?}",
+                line.spans
+            );
+        }
+        assert_ne!(span(&lines[4], "let").style.fg, comment);
+
+        let md = "```python\n\"\"\"doc\ndef g\n\"\"\"\ndef h(): pass\n```";
+        let lines = render(md, Style::default());
+        let docstring = span(&lines[1], "doc").style.fg;
+        assert_eq!(span(&lines[2], "def g").style.fg, docstring);
+        assert_ne!(span(&lines[4], "def").style.fg, docstring);
+    }
+
+    #[test]
+    fn unterminated_fence_renders_rest_as_code() {
+        let lines = render("text\n```python\ndef f():\n    return 'x", Style::default());
+        assert_eq!(lines.len(), 4);
+        assert!(lines[2].code_block && lines[3].code_block);
+        assert_eq!(text_of(&lines[3]), "    return 'x");
+        if cfg!(feature = "syntax-highlighting") {
+            assert_ne!(
+                span(&lines[2], "def").style.fg,
+                span(&lines[2], ":").style.fg
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_language_is_plain_code() {
+        let lines = render("```brainfuck\n+[->+<]\n```", Style::default());
+        assert_eq!(lines[1].spans.len(), 1);
+        assert_eq!(lines[1].spans[0].style, code_style());
+    }
+
+    #[test]
+    fn extracts_code_blocks() {
+        let md = "a\n```sh\ncargo build\ncargo test\n```\nb\n```\nlast";
+        assert_eq!(
+            code_blocks(md),
+            ["cargo build\ncargo test".to_string(), "last".to_string()]
+        );
+        assert!(code_blocks("no code").is_empty());
+    }
+
+    #[test]
+    fn empty_text_renders_nothing() {
+        assert!(render("", Style::default()).is_empty());
+    }
+}
@@ -1,47 +1,175 @@
 pub mod app;
+pub mod clipboard;
 pub mod event;
+#[cfg(feature = "syntax-highlighting")]
+pub mod highlight;
+pub mod history;
+pub mod markdown;
+pub mod sessions;
 pub mod ui;
 
 use anyhow::Result;
-use crossterm::event::{KeyCode, KeyModifiers};
+use chrono::{DateTime, Local, Utc};
+use crossterm::event::{
+    DisableBracketedPaste, EnableBracketedPaste, KeyCode, KeyModifiers, KeyboardEnhancementFlags,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
 use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+    disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+    LeaveAlternateScreen,
 };
 use crossterm::ExecutableCommand;
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
 use std::io::stdout;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
-use crate::agent::loop_::{run_tool_loop, trim_history};
+use crate::agent::loop_::{
+    agent_end, compact_history, limit_history, run_tool_loop, trim_history, Cancelled,
+    MANUAL_COMPACT_KEEP_TURNS,
+};
 use crate::config::Config;
 use crate::memory::{self, Memory, MemoryCategory};
 use crate::observability::{self, Observer, ObserverEvent};
 use crate::providers::traits::{tool_spec_to_definition, ChatMessage, ToolDefinition};
 use crate::providers::{self, Provider};
 use crate::runtime;
+use crate::security::approval;
 use crate::security::SecurityPolicy;
+use crate::sessions::Transcript;
 use crate::tools::{self, Tool};
 use crate::util::truncate_with_ellipsis;
 
-use app::{App, AppStatus, MessageRole, SlashResult};
-use event::{spawn_event_reader, AppEvent};
+use crate::usage::ledger::UsageLog;
+use crate::usage::{HistorySize, TurnUsage, UsageMeter};
+use app::{App, AppStatus, CopyTarget, MessageRole, SlashResult, MEMORY_CONTEXT_HEADER};
+use clipboard::Clipboard;
+use event::{spawn_event_reader, AppEvent, TuiObserver};
+use history::InputHistory;
 
 const HELP_TEXT: &str = "\
 Commands:
   /quit, /exit, /q  — Exit TUI
-  /clear, /cls      — Clear chat history
+  /clear, /cls      — Clear chat history and usage counters
   /help, /h, /?     — Show this help
+  /model <name>     — Switch model for the rest of the session
+  /provider <name>  — Switch provider (e.g. openrouter, anthropic, ollama)
+  /temp <value>     — Set temperature (0.0–2.0)
+  /save [name]      — Save the conversation to workspace/sessions/
+  /load <name>      — Resume a saved conversation
+  /compact          — Summarize older turns to free up context
+  /cost, /usage     — Show this session's tokens and estimated cost
+  /copy [code]      — Copy the last reply (or its last code block)
+  /markdown, /md    — Toggle Markdown rendering of replies
 
 Keys:
   Enter       — Send message
-  Ctrl+C, Esc — Quit
+  Alt+Enter   — New line (Shift+Enter where the terminal reports it)
+  Ctrl+C, Esc — Cancel the reply in progress, otherwise quit
   Backspace   — Delete character
   Left/Right  — Move cursor
-  Up/Down     — Scroll chat
+  Home/End    — Start/end of the current input line
+  Up/Down     — Move between input lines, recall history, or scroll chat
+  Ctrl+Up/Dn  — Recall previous/next input
   PageUp/Down — Scroll chat (page)
-  Ctrl+L      — Clear screen";
+  Ctrl+L      — Clear screen
+  Ctrl+Y      — Copy the last reply
+  Alt+Y       — Copy the last code block";
+
+/// Provider settings that slash commands can change mid-session.
+struct Session {
+    /// File name the conversation is saved under on `/save` and on exit
+    name: String,
+    created_at: DateTime<Utc>,
+    provider_name: String,
+    provider: Arc<dyn Provider>,
+    model: Arc<String>,
+    temperature: f64,
+    system_prompt: Arc<String>,
+    tool_descs: Vec<(&'static str, &'static str)>,
+    skills: Vec<crate::skills::Skill>,
+    /// Conversation log under `sessions/`, one per TUI run
+    transcript: Transcript,
+}
+
+impl Session {
+    /// The system prompt names the model, so it is rebuilt on every model switch.
+    fn rebuild_system_prompt(&mut self, config: &Config) {
+        self.system_prompt = Arc::new(crate::channels::build_system_prompt(
+            &config.workspace_dir,
+            &self.model,
+            &self.tool_descs,
+            &self.skills,
+        ));
+    }
+
+    /// Forget the saved-session identity after the conversation is cleared.
+    fn start_fresh(&mut self) {
+        self.name = sessions::default_name();
+        self.created_at = Utc::now();
+    }
+
+    fn save(&self, config: &Config, history: &[ChatMessage]) -> Result<PathBuf> {
+        let saved = sessions::SavedSession {
+            version: sessions::SESSION_VERSION,
+            name: self.name.clone(),
+            provider: self.provider_name.clone(),
+            model: (*self.model).clone(),
+            created_at: self.created_at,
+            updated_at: Utc::now(),
+            history: history.to_vec(),
+        };
+        sessions::save(&config.workspace_dir, &saved)
+    }
+
+    /// Replace the conversation with the saved session `name`. The current
+    /// system prompt is kept and the usual history trimming applies.
+    async fn load(
+        &mut self,
+        app: &mut App,
+        name: &str,
+        config: &Config,
+        history: &tokio::sync::Mutex<Vec<ChatMessage>>,
+    ) -> Result<()> {
+        let saved = sessions::load(&config.workspace_dir, name)?;
+
+        let mut restored = saved.history;
+        if matches!(restored.first(), Some(ChatMessage::System { .. })) {
+            restored.remove(0);
+        }
+        restored.insert(
+            0,
+            ChatMessage::System {
+                content: (*self.system_prompt).clone(),
+            },
+        );
+        trim_history(&mut restored, config.autonomy.max_history_turns);
+
+        let saved_at = saved.updated_at.with_timezone(&Local);
+        app.restore_history(&restored, &saved_at.format("%H:%M:%S").to_string());
+        app.usage = UsageMeter {
+            history: HistorySize::of(&restored),
+            ..UsageMeter::default()
+        };
+        app.push_message(
+            MessageRole::System,
+            &format!(
+                "Resumed session {} (saved {} with {}/{})",
+                saved.name,
+                saved_at.format("%Y-%m-%d %H:%M"),
+                saved.provider,
+                saved.model
+            ),
+        );
+        *history.lock().await = restored;
+        self.name = saved.name;
+        self.created_at = saved.created_at;
+        Ok(())
+    }
+}
 
 /// Run the TUI agent loop.
 #[allow(clippy::too_many_lines)]
@@ -50,15 +178,19 @@ pub async fn run(
     provider_override: Option<String>,
     model_override: Option<String>,
     temperature: f64,
+    resume: Option<String>,
+    markdown: bool,
 ) -> Result<()> {
     // ── Wire up subsystems (same as agent::run) ──────────────
     let observer: Arc<dyn Observer> =
         Arc::from(observability::create_observer(&config.observability));
-    let _runtime = runtime::create_runtime(&config.runtime)?;
-    let security = Arc::new(SecurityPolicy::from_config(
-        &config.autonomy,
+    let runtime: Arc<dyn runtime::RuntimeAdapter> = Arc::from(runtime::create_runtime(
+        &config.runtime,
         &config.workspace_dir,
-    ));
+    )?);
+    let security = Arc::new(
+        SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir).with_origin("tui"),
+    );
 
     let mem: Arc<dyn Memory> = Arc::from(memory::create_memory(
         &config.memory,
@@ -71,12 +203,19 @@ pub async fn run(
     } else {
         None
     };
+    let skills = crate::skills::load_skills(&config.workspace_dir);
     let tools: Arc<Vec<Box<dyn Tool>>> = Arc::new(tools::all_tools(
         &security,
         mem.clone(),
         composio_key,
         &config.browser,
         &config.brave_search,
+        &config.git,
+        &config.web_fetch,
+        &config.tools,
+        &config.secrets.named,
+        &skills,
+        runtime,
     ));
 
     // Build tool definitions for function calling API
@@ -102,6 +241,7 @@ pub async fn run(
         provider_name,
         config.api_key.as_deref(),
         &config.reliability,
+        observer.clone(),
     )?);
 
     observer.record_event(&ObserverEvent::AgentStart {
@@ -109,42 +249,87 @@ pub async fn run(
         model: model_name.to_string(),
     });
 
-    let skills = crate::skills::load_skills(&config.workspace_dir);
     let mut tool_descs: Vec<(&str, &str)> = vec![
         ("shell", "Execute terminal commands."),
         ("file_read", "Read file contents."),
         ("file_write", "Write file contents."),
+        (
+            "file_edit",
+            "Replace exact text in an existing file; prefer it over file_write for edits.",
+        ),
         ("memory_store", "Save to memory."),
         ("memory_recall", "Search memory."),
         ("memory_forget", "Delete a memory entry."),
+        ("task_add", "Add a task to the task list."),
+        ("task_list", "List open tasks."),
+        ("task_complete", "Mark a task done."),
     ];
-    if config.brave_search.enabled {
-        tool_descs.push(("web_search", "Search the web using Brave Search."));
+    if crate::tools::web_search::provider_from_config(
+        &config.tools.web_search,
+        &config.brave_search,
+    )
+    .is_some()
+    {
+        tool_descs.push(("web_search", "Search the web."));
     }
-    let system_prompt = Arc::new(crate::channels::build_system_prompt(
-        &config.workspace_dir,
-        model_name,
-        &tool_descs,
-        &skills,
-    ));
-    let model_owned = Arc::new(model_name.to_string());
+    if config.web_fetch.enabled {
+        tool_descs.push(("web_fetch", "Fetch a URL and return its readable text."));
+    }
+    if config.tools.http.enabled {
+        tool_descs.push(("http_request", "Call a JSON API on an allowed host."));
+    }
+    if config.tools.clipboard.enabled {
+        tool_descs.push(("clipboard", "Read or set the user's clipboard text."));
+    }
+    if config.tools.reminders.enabled {
+        tool_descs.push((
+            "reminders",
+            "Add, list or complete reminders in the .ics file.",
+        ));
+    }
+    if config.tools.python.enabled {
+        tool_descs.push(("python", "Run a Python program in the workspace."));
+    }
+    if config.git.enabled {
+        tool_descs.push(("git", "Inspect and commit repository changes."));
+    }
+    let mut session = Session {
+        name: sessions::default_name(),
+        created_at: Utc::now(),
+        provider_name: provider_name.to_string(),
+        provider,
+        model: Arc::new(model_name.to_string()),
+        temperature,
+        system_prompt: Arc::new(String::new()),
+        tool_descs,
+        skills,
+        transcript: Transcript::start(&config.workspace_dir, "tui"),
+    };
+    session.rebuild_system_prompt(&config);
     let max_history_turns = config.autonomy.max_history_turns;
 
     // ── Shared conversation history ─────────────────────────
     let history: Arc<tokio::sync::Mutex<Vec<ChatMessage>>> =
         Arc::new(tokio::sync::Mutex::new(vec![ChatMessage::System {
-            content: (*system_prompt).clone(),
+            content: (*session.system_prompt).clone(),
         }]));
 
     // ── Initialize terminal ──────────────────────────────────
     enable_raw_mode()?;
     stdout().execute(EnterAlternateScreen)?;
+    stdout().execute(EnableBracketedPaste)?;
+    // Only terminals speaking the kitty keyboard protocol report Shift+Enter
+    let keyboard_enhanced = supports_keyboard_enhancement().unwrap_or(false);
+    if keyboard_enhanced {
+        stdout().execute(PushKeyboardEnhancementFlags(
+            KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES,
+        ))?;
+    }
 
     // Panic hook: restore terminal on panic
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |info| {
-        let _ = disable_raw_mode();
-        let _ = stdout().execute(LeaveAlternateScreen);
+        restore_terminal(keyboard_enhanced);
         original_hook(info);
     }));
 
@@ -154,17 +339,26 @@ pub async fn run(
 
     let memory_backend = config.memory.backend.clone();
     let mut app = App::new(provider_name, model_name, &memory_backend);
+    app.temperature = temperature;
+    app.markdown = markdown;
+    app.history = InputHistory::load(&InputHistory::default_path(&config.workspace_dir));
+    app.clipboard = Clipboard::with_fallback(Clipboard::fallback_path(&config.workspace_dir));
 
     app.push_message(
         MessageRole::System,
         "Welcome to Jarvis TUI! Type /help for commands.",
     );
+    if let Some(name) = resume {
+        resume_session(&mut app, &mut session, name, &config, &history).await;
+    }
 
     // ── Event channels ───────────────────────────────────────
     let (event_tx, mut event_rx) = mpsc::unbounded_channel::<AppEvent>();
     let (agent_tx, mut agent_rx) = mpsc::unbounded_channel::<AppEvent>();
 
     spawn_event_reader(event_tx);
+    // Shell commands off the allowlist are confirmed in the chat
+    let mut approvals = security.approvals.attach();
 
     // ── Main loop ────────────────────────────────────────────
     let start = std::time::Instant::now();
@@ -177,27 +371,31 @@ pub async fn run(
                 match ev {
                     AppEvent::Key(key) => {
                         if handle_key_event(
-                            &mut app, key, &mem, &provider, &model_owned,
-                            temperature, &system_prompt, &config, &agent_tx,
+                            &mut app, key, &mem, &mut session, &config, &agent_tx,
                             &tools, &tool_definitions, &security, &observer,
                             &history, max_history_turns,
                         ).await {
                             break;
                         }
                     }
+                    AppEvent::Paste(text) => app.insert_str(&text),
                     AppEvent::Tick => {
                         if app.status == AppStatus::Waiting {
                             app.tick_spinner();
                         }
+                        app.expire_approval();
                     }
                     _ => {}
                 }
             }
+            Some(request) = approvals.recv() => app.ask_approval(request),
             Some(agent_ev) = agent_rx.recv() => {
                 match agent_ev {
-                    AppEvent::AgentResponse(response) => {
+                    AppEvent::AgentResponse(response, usage) => {
                         app.status = AppStatus::Idle;
+                        app.turn = None;
                         app.push_message(MessageRole::Assistant, &response);
+                        app.usage.add(&usage);
 
                         if config.memory.auto_save {
                             let summary = truncate_with_ellipsis(&response, 100);
@@ -206,8 +404,29 @@ pub async fn run(
                     }
                     AppEvent::AgentError(err) => {
                         app.status = AppStatus::Idle;
+                        app.turn = None;
                         app.push_message(MessageRole::System, &format!("Error: {err}"));
                     }
+                    AppEvent::AgentCancelled => {
+                        app.status = AppStatus::Idle;
+                        app.turn = None;
+                        app.push_message(MessageRole::System, "Turn cancelled.");
+                    }
+                    AppEvent::Compacted(result) => {
+                        app.status = AppStatus::Idle;
+                        let notice = match result {
+                            Ok(0) => "Nothing to compact yet.".to_string(),
+                            Ok(n) => format!("Compacted {n} older messages into a summary."),
+                            Err(e) => format!("Compaction failed: {e}"),
+                        };
+                        app.push_message(MessageRole::System, &notice);
+                    }
+                    AppEvent::ToolStarted { tool, arguments } => {
+                        app.tool_started(&tool, &arguments);
+                    }
+                    AppEvent::ToolFinished { tool, duration, success, error } => {
+                        app.tool_finished(&tool, duration, success, error.as_deref());
+                    }
                     _ => {}
                 }
             }
@@ -219,27 +438,82 @@ pub async fn run(
     }
 
     // ── Restore terminal ─────────────────────────────────────
-    disable_raw_mode()?;
-    stdout().execute(LeaveAlternateScreen)?;
+    restore_terminal(keyboard_enhanced);
+    save_on_exit(&session, &config, &history);
 
-    observer.record_event(&ObserverEvent::AgentEnd {
-        duration: start.elapsed(),
-        tokens_used: None,
-    });
+    observer.record_event(&agent_end(start.elapsed(), &app.usage));
+    observer.flush();
 
     Ok(())
 }
 
+/// Resume a session requested with `--resume` (empty name = most recent).
+/// Failures are reported in the chat and the TUI starts fresh.
+async fn resume_session(
+    app: &mut App,
+    session: &mut Session,
+    name: String,
+    config: &Config,
+    history: &tokio::sync::Mutex<Vec<ChatMessage>>,
+) {
+    let name = if name.is_empty() {
+        sessions::latest(&config.workspace_dir)
+    } else {
+        Some(name)
+    };
+    let Some(name) = name else {
+        app.push_message(MessageRole::System, "No saved sessions to resume.");
+        return;
+    };
+    if let Err(e) = session.load(app, &name, config, history).await {
+        app.push_message(
+            MessageRole::System,
+            &format!("Could not resume session {name}: {e:#}\nStarting a fresh session."),
+        );
+    }
+}
+
+/// Save a conversation that has at least one user turn; the agent may still
+/// hold the history if the user quit mid-response, in which case it is skipped.
+fn save_on_exit(
+    session: &Session,
+    config: &Config,
+    history: &tokio::sync::Mutex<Vec<ChatMessage>>,
+) {
+    let Ok(hist) = history.try_lock() else {
+        tracing::warn!("Agent 仍在运行，跳过会话保存");
+        return;
+    };
+    if !hist.iter().any(|m| matches!(m, ChatMessage::User { .. })) {
+        return;
+    }
+    match session.save(config, &hist) {
+        Ok(path) => println!(
+            "💾 会话已保存到 {}（使用 `jarvis tui --resume {}` 恢复）",
+            path.display(),
+            session.name
+        ),
+        Err(e) => eprintln!("⚠️  保存会话失败: {e:#}"),
+    }
+}
+
+/// Undo the terminal modes enabled on startup (best-effort).
+fn restore_terminal(keyboard_enhanced: bool) {
+    if keyboard_enhanced {
+        let _ = stdout().execute(PopKeyboardEnhancementFlags);
+    }
+    let _ = stdout().execute(DisableBracketedPaste);
+    let _ = disable_raw_mode();
+    let _ = stdout().execute(LeaveAlternateScreen);
+}
+
 /// Handle a key event. Returns `true` if the app should quit.
 #[allow(clippy::too_many_arguments, clippy::too_many_lines)]
 async fn handle_key_event(
     app: &mut App,
     key: crossterm::event::KeyEvent,
     mem: &Arc<dyn Memory>,
-    provider: &Arc<dyn Provider>,
-    model_name: &Arc<String>,
-    temperature: f64,
-    system_prompt: &Arc<String>,
+    session: &mut Session,
     config: &Config,
     agent_tx: &mpsc::UnboundedSender<AppEvent>,
     tools: &Arc<Vec<Box<dyn Tool>>>,
@@ -250,7 +524,8 @@ async fn handle_key_event(
     max_history_turns: usize,
 ) -> bool {
     match (key.modifiers, key.code) {
-        // Quit
+        // Cancel the running turn, or quit
+        (KeyModifiers::CONTROL, KeyCode::Char('c')) | (_, KeyCode::Esc) if app.cancel_turn() => {}
         (KeyModifiers::CONTROL, KeyCode::Char('c')) | (_, KeyCode::Esc) => {
             app.should_quit = true;
             return true;
@@ -260,15 +535,32 @@ async fn handle_key_event(
         (KeyModifiers::CONTROL, KeyCode::Char('l')) => {
             app.messages.clear();
             app.scroll_offset = 0;
+            session.start_fresh();
             let mut hist = history.lock().await;
             hist.clear();
             hist.push(ChatMessage::System {
-                content: (**system_prompt).clone(),
+                content: (*session.system_prompt).clone(),
             });
         }
 
+        // Copy to clipboard
+        (KeyModifiers::CONTROL, KeyCode::Char('y')) => app.copy(CopyTarget::Message),
+        (KeyModifiers::ALT, KeyCode::Char('y')) => app.copy(CopyTarget::CodeBlock),
+
+        // New line
+        (m, KeyCode::Enter) if m.intersects(KeyModifiers::ALT | KeyModifiers::SHIFT) => {
+            app.insert_newline();
+        }
+
         // Submit
         (_, KeyCode::Enter) => {
+            if let Some(request) = app.pending_approval.take() {
+                let reply = app.take_input();
+                let unlisted = request.unlisted.clone();
+                let decision = approval::answer(request, &reply, config);
+                app.approval_answered(decision, &unlisted);
+                return false;
+            }
             if app.status == AppStatus::Waiting {
                 return false;
             }
@@ -287,10 +579,12 @@ async fn handle_key_event(
                 SlashResult::Clear => {
                     app.messages.clear();
                     app.scroll_offset = 0;
+                    app.usage = UsageMeter::default();
+                    session.start_fresh();
                     let mut hist = history.lock().await;
                     hist.clear();
                     hist.push(ChatMessage::System {
-                        content: (**system_prompt).clone(),
+                        content: (*session.system_prompt).clone(),
                     });
                     return false;
                 }
@@ -298,12 +592,76 @@ async fn handle_key_event(
                     app.push_message(MessageRole::System, HELP_TEXT);
                     return false;
                 }
+                SlashResult::Compact => {
+                    app.status = AppStatus::Waiting;
+                    let prov = Arc::clone(&session.provider);
+                    let model = Arc::clone(&session.model);
+                    let history_clone = Arc::clone(history);
+                    let tx = agent_tx.clone();
+                    tokio::spawn(async move {
+                        let mut hist = history_clone.lock().await;
+                        let result = compact_history(
+                            prov.as_ref(),
+                            &mut hist,
+                            MANUAL_COMPACT_KEEP_TURNS,
+                            &model,
+                        )
+                        .await;
+                        drop(hist);
+                        let _ = tx.send(AppEvent::Compacted(result.map_err(|e| format!("{e:#}"))));
+                    });
+                    return false;
+                }
+                SlashResult::Cost => {
+                    app.push_message(MessageRole::System, &app.usage.cost_report());
+                    return false;
+                }
+                SlashResult::Copy(target) => {
+                    app.copy(target);
+                    return false;
+                }
+                SlashResult::ToggleMarkdown => {
+                    app.markdown = !app.markdown;
+                    let state = if app.markdown { "on" } else { "off" };
+                    app.push_message(MessageRole::System, &format!("Markdown rendering {state}"));
+                    return false;
+                }
+                SlashResult::Save(name) => {
+                    if let Some(name) = name {
+                        session.name = name;
+                    }
+                    let saved = session.save(config, &history.lock().await);
+                    let reply = match saved {
+                        Ok(path) => format!("Session saved to {}", path.display()),
+                        Err(e) => format!("Failed to save session: {e:#}"),
+                    };
+                    app.push_message(MessageRole::System, &reply);
+                    return false;
+                }
+                SlashResult::Load(name) => {
+                    if let Err(e) = session.load(app, &name, config, history).await {
+                        app.push_message(
+                            MessageRole::System,
+                            &format!("Failed to load session {name}: {e:#}"),
+                        );
+                    }
+                    return false;
+                }
+                SlashResult::Invalid(usage) => {
+                    app.push_message(MessageRole::System, &usage);
+                    return false;
+                }
                 SlashResult::None => {}
+                switch => {
+                    apply_session_switch(app, session, switch, config, observer, history).await;
+                    return false;
+                }
             }
 
             // Regular message
             app.push_message(MessageRole::User, &text);
             app.status = AppStatus::Waiting;
+            session.transcript.user(&text);
 
             // Auto-save
             if config.memory.auto_save {
@@ -321,19 +679,28 @@ async fn handle_key_event(
             };
 
             // Clone Arc references for the spawned task
-            let prov = Arc::clone(provider);
-            let model = Arc::clone(model_name);
+            let prov = Arc::clone(&session.provider);
+            let model = Arc::clone(&session.model);
+            let temperature = session.temperature;
             let tx = agent_tx.clone();
             let tools_clone = Arc::clone(tools);
             let tool_defs_clone = Arc::clone(tool_definitions);
             let sec = Arc::clone(security);
-            let obs = Arc::clone(observer);
+            // Forward tool activity to the chat as it happens
+            let obs = TuiObserver::new(Arc::clone(observer), agent_tx.clone());
             let max_iter = config.autonomy.max_tool_iterations;
             let history_clone = Arc::clone(history);
+            let compact = config.autonomy.compact_history;
+            let transcript = session.transcript.clone();
+            let usage_log =
+                UsageLog::new(&config.workspace_dir, &session.provider_name, &transcript);
+            let cancel = CancellationToken::new();
+            app.turn = Some(cancel.clone());
 
             tokio::spawn(async move {
                 let mut hist = history_clone.lock().await;
-                trim_history(&mut hist, max_history_turns);
+                limit_history(prov.as_ref(), &mut hist, max_history_turns, &model, compact).await;
+                let turn_start = hist.len();
                 hist.push(ChatMessage::User { content: enriched });
                 let result = run_tool_loop(
                     prov.as_ref(),
@@ -344,14 +711,21 @@ async fn handle_key_event(
                     temperature,
                     max_iter,
                     &sec,
-                    obs.as_ref(),
+                    &obs,
+                    &transcript,
                     true, // quiet: suppress stdout/stderr in TUI mode
+                    &cancel,
                 )
                 .await;
+                let usage = TurnUsage::of_turn(&hist, turn_start, &tool_defs_clone, &model);
                 drop(hist); // explicitly release lock before sending
+                usage_log.record(&model, &usage);
                 match result {
                     Ok(response) => {
-                        let _ = tx.send(AppEvent::AgentResponse(response));
+                        let _ = tx.send(AppEvent::AgentResponse(response, usage));
+                    }
+                    Err(e) if e.is::<Cancelled>() => {
+                        let _ = tx.send(AppEvent::AgentCancelled);
                     }
                     Err(e) => {
                         let _ = tx.send(AppEvent::AgentError(e.to_string()));
@@ -368,9 +742,19 @@ async fn handle_key_event(
         (_, KeyCode::Home) => app.move_cursor_home(),
         (_, KeyCode::End) => app.move_cursor_end(),
 
+        // Input history
+        (KeyModifiers::CONTROL, KeyCode::Up) => {
+            app.history_prev();
+        }
+        (KeyModifiers::CONTROL, KeyCode::Down) => {
+            app.history_next();
+        }
+
+        // Move between input lines, then through history, then scroll the chat
+        (_, KeyCode::Up) => app.cursor_up_or_scroll(),
+        (_, KeyCode::Down) => app.cursor_down_or_scroll(),
+
         // Scrolling
-        (_, KeyCode::Up) => app.scroll_up(1),
-        (_, KeyCode::Down) => app.scroll_down(1),
         (_, KeyCode::PageUp) => app.scroll_up(10),
         (_, KeyCode::PageDown) => app.scroll_down(10),
 
@@ -384,18 +768,73 @@ async fn handle_key_event(
     false
 }
 
+/// Apply a `/model`, `/provider` or `/temp` command to the session and report
+/// the result in the chat. A provider that fails to build leaves the session
+/// unchanged.
+async fn apply_session_switch(
+    app: &mut App,
+    session: &mut Session,
+    switch: SlashResult,
+    config: &Config,
+    observer: &Arc<dyn Observer>,
+    history: &Arc<tokio::sync::Mutex<Vec<ChatMessage>>>,
+) {
+    match switch {
+        SlashResult::Model(model) => {
+            session.model = Arc::new(model.clone());
+            session.rebuild_system_prompt(config);
+            // Keep the conversation, but let the model see its new identity
+            let mut hist = history.lock().await;
+            if let Some(ChatMessage::System { content }) = hist.first_mut() {
+                content.clone_from(&session.system_prompt);
+            }
+            app.push_message(MessageRole::System, &format!("Model switched to {model}"));
+            app.model_display = model;
+        }
+        SlashResult::Provider(name) => match providers::create_resilient_provider(
+            &name,
+            config.api_key.as_deref(),
+            &config.reliability,
+            observer.clone(),
+        ) {
+            Ok(provider) => {
+                session.provider = Arc::from(provider);
+                session.provider_name.clone_from(&name);
+                app.push_message(MessageRole::System, &format!("Provider switched to {name}"));
+                app.provider_display = name;
+            }
+            Err(e) => {
+                app.push_message(
+                    MessageRole::System,
+                    &format!("Failed to switch provider to {name}: {e}"),
+                );
+            }
+        },
+        SlashResult::Temperature(temperature) => {
+            session.temperature = temperature;
+            app.temperature = temperature;
+            app.push_message(
+                MessageRole::System,
+                &format!("Temperature set to {temperature:.1}"),
+            );
+        }
+        _ => {}
+    }
+}
+
 /// Build context preamble by searching memory for relevant entries.
 async fn build_context(mem: &dyn Memory, user_msg: &str) -> String {
     use std::fmt::Write;
     let mut context = String::new();
-    if let Ok(entries) = mem.recall(user_msg, 5).await {
-        if !entries.is_empty() {
-            context.push_str("[Memory context]\n");
-            for entry in &entries {
-                let _ = writeln!(context, "- {}: {}", entry.key, entry.content);
-            }
-            context.push('\n');
+    if let Ok(entries) = mem.recall(user_msg, 5, &[]).await
+        && !entries.is_empty()
+    {
+        context.push_str(MEMORY_CONTEXT_HEADER);
+        context.push('\n');
+        for entry in &entries {
+            let _ = writeln!(context, "- {}: {}", entry.key, entry.content);
         }
+        context.push('\n');
     }
     context
 }
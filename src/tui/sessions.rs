@@ -0,0 +1,198 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::providers::ChatMessage;
+
+/// Format version written to session files; other versions are refused.
+pub const SESSION_VERSION: u32 = 1;
+
+/// A TUI conversation persisted under `workspace/sessions/<name>.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSession {
+    pub version: u32,
+    pub name: String,
+    pub provider: String,
+    pub model: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Full conversation, system prompt first.
+    pub history: Vec<ChatMessage>,
+}
+
+pub fn sessions_dir(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join("sessions")
+}
+
+/// Name for a session that was never saved explicitly. The date prefix lets
+/// memory hygiene archive old sessions by name.
+pub fn default_name() -> String {
+    Local::now().format("%Y-%m-%d-%H%M%S").to_string()
+}
+
+/// Reject names that could escape the sessions directory.
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    anyhow::ensure!(
+        valid,
+        "Invalid session name \"{name}\": use letters, digits, '-', '_' or '.'"
+    );
+    Ok(())
+}
+
+pub fn session_path(workspace_dir: &Path, name: &str) -> Result<PathBuf> {
+    validate_name(name)?;
+    Ok(sessions_dir(workspace_dir).join(format!("{name}.json")))
+}
+
+/// Write `session` to disk (via a temp file, so a crash never leaves half a file).
+pub fn save(workspace_dir: &Path, session: &SavedSession) -> Result<PathBuf> {
+    let path = session_path(workspace_dir, &session.name)?;
+    let dir = sessions_dir(workspace_dir);
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let json = serde_json::to_vec_pretty(session).context("Failed to serialize session")?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).with_context(|| format!("Failed to write {}", tmp.display()))?;
+    fs::rename(&tmp, &path).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Read a saved session, with a readable error for missing, corrupt or
+/// incompatible files.
+pub fn load(workspace_dir: &Path, name: &str) -> Result<SavedSession> {
+    let path = session_path(workspace_dir, name)?;
+    let raw = fs::read_to_string(&path)
+        .with_context(|| format!("Session \"{name}\" not found at {}", path.display()))?;
+
+    let value: serde_json::Value = serde_json::from_str(&raw)
+        .with_context(|| format!("Session file {} is not valid JSON", path.display()))?;
+    let version = value.get("version").and_then(serde_json::Value::as_u64);
+    anyhow::ensure!(
+        version == Some(u64::from(SESSION_VERSION)),
+        "Session file {} has unsupported version {} (expected {SESSION_VERSION})",
+        path.display(),
+        version.map_or_else(|| "none".to_string(), |v| v.to_string())
+    );
+
+    serde_json::from_value(value)
+        .with_context(|| format!("Session file {} is corrupt", path.display()))
+}
+
+/// Name of the most recently written session, if any.
+pub fn latest(workspace_dir: &Path) -> Option<String> {
+    fs::read_dir(sessions_dir(workspace_dir))
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+            let name = entry.path().file_stem()?.to_str()?.to_string();
+            Some((modified, name))
+        })
+        .max()
+        .map(|(_, name)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample(name: &str) -> SavedSession {
+        SavedSession {
+            version: SESSION_VERSION,
+            name: name.to_string(),
+            provider: "openrouter".into(),
+            model: "anthropic/claude-sonnet-4".into(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            history: vec![
+                ChatMessage::System {
+                    content: "You are Jarvis.".into(),
+                },
+                ChatMessage::User {
+                    content: "hello".into(),
+                },
+                ChatMessage::Assistant {
+                    content: Some("hi!".into()),
+                    tool_calls: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn save_and_load_roundtrip() {
+        let tmp = TempDir::new().unwrap();
+        let path = save(tmp.path(), &sample("work")).unwrap();
+        assert_eq!(path, tmp.path().join("sessions").join("work.json"));
+
+        let loaded = load(tmp.path(), "work").unwrap();
+        assert_eq!(loaded.name, "work");
+        assert_eq!(loaded.provider, "openrouter");
+        assert_eq!(loaded.history.len(), 3);
+        assert!(matches!(&loaded.history[1], ChatMessage::User { content } if content == "hello"));
+        assert!(!tmp.path().join("sessions").join("work.json.tmp").exists());
+    }
+
+    #[test]
+    fn rejects_path_like_names() {
+        let tmp = TempDir::new().unwrap();
+        for bad in ["", "../escape", "a/b", ".hidden", "with space"] {
+            assert!(session_path(tmp.path(), bad).is_err(), "{bad:?} accepted");
+        }
+        assert!(session_path(tmp.path(), "2026-01-02-030405").is_ok());
+    }
+
+    #[test]
+    fn missing_corrupt_and_mismatched_files_error() {
+        let tmp = TempDir::new().unwrap();
+        let err = load(tmp.path(), "nope").unwrap_err();
+        assert!(err.to_string().contains("not found"));
+
+        let dir = sessions_dir(tmp.path());
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("broken.json"), "{ not json").unwrap();
+        let err = load(tmp.path(), "broken").unwrap_err();
+        assert!(err.to_string().contains("not valid JSON"));
+
+        let mut old = serde_json::to_value(sample("old")).unwrap();
+        old["version"] = serde_json::json!(99);
+        fs::write(dir.join("old.json"), old.to_string()).unwrap();
+        let err = load(tmp.path(), "old").unwrap_err();
+        assert!(err.to_string().contains("unsupported version 99"));
+
+        fs::write(
+            dir.join("partial.json"),
+            r#"{"version": 1, "name": "partial"}"#,
+        )
+        .unwrap();
+        let err = load(tmp.path(), "partial").unwrap_err();
+        assert!(err.to_string().contains("corrupt"));
+    }
+
+    #[test]
+    fn latest_picks_most_recent_session() {
+        let tmp = TempDir::new().unwrap();
+        assert_eq!(latest(tmp.path()), None);
+
+        save(tmp.path(), &sample("first")).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        save(tmp.path(), &sample("second")).unwrap();
+        assert_eq!(latest(tmp.path()).as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn default_name_has_date_prefix() {
+        let name = default_name();
+        assert!(validate_name(&name).is_ok());
+        assert!(chrono::NaiveDate::parse_from_str(&name[..10], "%Y-%m-%d").is_ok());
+    }
+}
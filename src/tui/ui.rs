@@ -1,29 +1,42 @@
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Frame;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use super::app::{App, AppStatus, MessageRole};
+use super::app::{App, AppStatus, MessageRole, ToolStatus};
+use super::markdown::{self, RenderedLine};
+
+/// Maximum visible rows in the input box before it scrolls internally.
+const MAX_INPUT_ROWS: usize = 6;
 
 /// Render the entire TUI.
 pub fn draw(f: &mut Frame, app: &App) {
     let area = f.area();
 
-    // Four-part vertical layout: title(1) + chat(fill) + status(1) + input(3)
+    // Input grows with its content: borders(2) + 1..=MAX_INPUT_ROWS rows
+    let input = input_rows(
+        &app.input,
+        app.cursor_pos,
+        area.width.saturating_sub(2) as usize,
+    );
+    #[allow(clippy::cast_possible_truncation)]
+    let input_height = input.rows.len().min(MAX_INPUT_ROWS) as u16 + 2;
+
+    // Four-part vertical layout: title(1) + chat(fill) + status(1) + input
     let chunks = Layout::vertical([
         Constraint::Length(1),
         Constraint::Min(4),
         Constraint::Length(1),
-        Constraint::Length(3),
+        Constraint::Length(input_height),
     ])
     .split(area);
 
     draw_title_bar(f, chunks[0], app);
     draw_chat_area(f, chunks[1], app);
     draw_status_bar(f, chunks[2], app);
-    draw_input_area(f, chunks[3], app);
+    draw_input_area(f, chunks[3], &input);
 }
 
 /// Title bar: `Jarvis` TUI on the left, model info on the right.
@@ -57,7 +70,6 @@ fn draw_title_bar(f: &mut Frame, area: Rect, app: &App) {
 /// This avoids relying on `Paragraph::Wrap` (whose word-wrapping produces a
 /// different line count from any external estimate, especially with CJK text),
 /// so skip/take scrolling is pixel-perfect.
-#[allow(clippy::too_many_lines)]
 fn draw_chat_area(f: &mut Frame, area: Rect, app: &App) {
     let block = Block::default()
         .borders(Borders::LEFT | Borders::RIGHT)
@@ -92,47 +104,52 @@ fn draw_chat_area(f: &mut Frame, area: Rect, app: &App) {
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD),
             ),
+            MessageRole::Tool(ToolStatus::Failed) => ("⚙ ", Style::default().fg(Color::Red)),
+            MessageRole::Tool(_) => ("⚙ ", Style::default().fg(Color::DarkGray)),
         };
 
         let content_style = match msg.role {
             MessageRole::System => Style::default().fg(Color::Yellow),
+            MessageRole::Tool(ToolStatus::Failed) => Style::default().fg(Color::Red),
+            MessageRole::Tool(_) => Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::DIM),
             MessageRole::User | MessageRole::Assistant => Style::default(),
         };
 
         let label_display_width = UnicodeWidthStr::width(label);
         let prefix_width = 2 + label_display_width; // "  " + label
         let indent = " ".repeat(prefix_width);
-        let content_lines: Vec<&str> = msg.content.lines().collect();
-
-        // First content line: label takes up prefix_width columns
-        if let Some(first) = content_lines.first() {
-            let first_avail = inner_width.saturating_sub(prefix_width);
-            let wrapped = wrap_text(first, first_avail);
-
-            if let Some((first_seg, rest)) = wrapped.split_first() {
-                lines.push(Line::from(vec![
-                    Span::raw("  "),
-                    Span::styled(label, label_style),
-                    Span::styled(first_seg.clone(), content_style),
-                ]));
-                for seg in rest {
-                    lines.push(Line::from(vec![
-                        Span::raw(indent.clone()),
-                        Span::styled(seg.clone(), content_style),
-                    ]));
-                }
-            }
-        }
+        let avail = inner_width.saturating_sub(prefix_width);
+
+        let source_lines = if app.markdown && msg.role == MessageRole::Assistant {
+            markdown::render(&msg.content, content_style)
+        } else {
+            msg.content
+                .lines()
+                .map(|l| RenderedLine::text(vec![Span::styled(l.to_string(), content_style)]))
+                .collect()
+        };
 
-        // Remaining content lines: all indented
-        let rest_avail = inner_width.saturating_sub(prefix_width);
-        for content_line in content_lines.iter().skip(1) {
-            let wrapped = wrap_text(content_line, rest_avail);
-            for seg in &wrapped {
-                lines.push(Line::from(vec![
-                    Span::raw(indent.clone()),
-                    Span::styled(seg.clone(), content_style),
-                ]));
+        // The label takes up prefix_width columns on the first row; every
+        // other row is indented by the same amount
+        let mut first_row = true;
+        for source in source_lines {
+            for mut row in wrap_spans(&source.spans, avail) {
+                if source.code_block {
+                    let used: usize = row.iter().map(|s| s.content.width()).sum();
+                    row.push(Span::styled(
+                        " ".repeat(avail.saturating_sub(used)),
+                        Style::default().bg(markdown::CODE_BG),
+                    ));
+                }
+                let lead = if first_row {
+                    first_row = false;
+                    vec![Span::raw("  "), Span::styled(label, label_style)]
+                } else {
+                    vec![Span::raw(indent.clone())]
+                };
+                lines.push(Line::from([lead, row].concat()));
             }
         }
     }
@@ -143,7 +160,11 @@ fn draw_chat_area(f: &mut Frame, area: Rect, app: &App) {
         lines.push(Line::from(vec![
             Span::raw("  "),
             Span::styled(
-                format!("Thinking... {}", app.spinner_char()),
+                if app.pending_approval.is_some() {
+                    "Waiting for your approval: y / N / always".to_string()
+                } else {
+                    format!("Thinking... {}", app.spinner_char())
+                },
                 Style::default()
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD),
@@ -167,86 +188,167 @@ fn draw_chat_area(f: &mut Frame, area: Rect, app: &App) {
     f.render_widget(para, area);
 }
 
-/// Status bar: memory backend + current status.
+/// Status bar: memory backend, temperature, token/cost counters and status.
 fn draw_status_bar(f: &mut Frame, area: Rect, app: &App) {
     let status_text = match app.status {
         AppStatus::Idle => "Idle",
         AppStatus::Waiting => "Waiting...",
     };
 
-    let line = Line::from(vec![
+    let mut line = Line::from(vec![
         Span::styled(
             format!(" Memory: {} (auto)", app.memory_display),
             Style::default().fg(Color::White),
         ),
         Span::styled(" | ", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            format!("temp {:.1}", app.temperature),
+            Style::default().fg(Color::White),
+        ),
+        Span::styled(" | ", Style::default().fg(Color::DarkGray)),
+        Span::styled(app.usage.status_text(), Style::default().fg(Color::White)),
+        Span::styled(" | ", Style::default().fg(Color::DarkGray)),
         Span::styled(status_text, Style::default().fg(Color::White)),
     ]);
+    if let Some(notice) = app.notice() {
+        line.spans
+            .push(Span::styled(" | ", Style::default().fg(Color::DarkGray)));
+        line.spans.push(Span::styled(
+            format!("✓ {notice}"),
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
 
     let para = Paragraph::new(line).style(Style::default().bg(Color::DarkGray).fg(Color::White));
     f.render_widget(para, area);
 }
 
-/// Input area: bordered text input with cursor.
-fn draw_input_area(f: &mut Frame, area: Rect, app: &App) {
+/// Input text laid out into visual rows, plus where the cursor lands.
+struct InputLayout {
+    rows: Vec<String>,
+    cursor_row: usize,
+    cursor_col: usize,
+}
+
+/// Split the input at newlines and wrap each line to `max_width` display
+/// columns, tracking the visual position of the byte offset `cursor`.
+fn input_rows(input: &str, cursor: usize, max_width: usize) -> InputLayout {
+    let mut layout = InputLayout {
+        rows: vec![String::new()],
+        cursor_row: 0,
+        cursor_col: 0,
+    };
+    let mut width: usize = 0;
+
+    for (i, ch) in input.char_indices() {
+        if ch == '\n' {
+            if i == cursor {
+                layout.cursor_row = layout.rows.len() - 1;
+                layout.cursor_col = width;
+            }
+            layout.rows.push(String::new());
+            width = 0;
+            continue;
+        }
+
+        let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if max_width > 0 && width + ch_width > max_width && width > 0 {
+            layout.rows.push(String::new());
+            width = 0;
+        }
+        if i == cursor {
+            layout.cursor_row = layout.rows.len() - 1;
+            layout.cursor_col = width;
+        }
+        if let Some(row) = layout.rows.last_mut() {
+            row.push(ch);
+        }
+        width += ch_width;
+    }
+
+    if cursor >= input.len() {
+        layout.cursor_row = layout.rows.len() - 1;
+        layout.cursor_col = width;
+    }
+    layout
+}
+
+/// Input area: bordered text input with cursor, scrolled to keep it visible.
+fn draw_input_area(f: &mut Frame, area: Rect, input: &InputLayout) {
     let block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan))
         .title(" Input ");
 
-    // Calculate cursor display width (Chinese/fullwidth chars = 2 columns each)
-    let display_cursor = unicode_width::UnicodeWidthStr::width(&app.input[..app.cursor_pos]);
+    let visible_rows = block.inner(area).height as usize;
+    let skip = (input.cursor_row + 1).saturating_sub(visible_rows);
+    let lines: Vec<Line<'_>> = input
+        .rows
+        .iter()
+        .skip(skip)
+        .take(visible_rows)
+        .map(|row| Line::from(row.as_str()))
+        .collect();
 
-    let para = Paragraph::new(app.input.as_str())
-        .block(block)
-        .wrap(Wrap { trim: false });
-
-    f.render_widget(para, area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
 
     // Place the cursor inside the input block (accounting for border)
     #[allow(clippy::cast_possible_truncation)]
-    let cursor_x = area.x + 1 + display_cursor as u16;
-    let cursor_y = area.y + 1;
+    let cursor_x = area.x + 1 + input.cursor_col as u16;
+    #[allow(clippy::cast_possible_truncation)]
+    let cursor_y = area.y + 1 + (input.cursor_row - skip) as u16;
     if cursor_x < area.x + area.width - 1 {
         f.set_cursor_position((cursor_x, cursor_y));
     }
 }
 
-/// Wrap text into segments that each fit within `max_width` display columns.
+/// Wrap styled text into rows that each fit within `max_width` display
+/// columns, keeping each character's style.
 ///
 /// Handles CJK characters (2 columns each) correctly.
-/// Returns at least one entry (empty string for empty input).
-fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
-    if max_width == 0 {
-        return vec![text.to_string()];
-    }
-
-    let mut result = Vec::new();
-    let mut current = String::new();
-    let mut current_width: usize = 0;
-
-    for ch in text.chars() {
-        let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
-
-        // If adding this char would overflow, start a new line
-        if current_width + ch_width > max_width && !current.is_empty() {
-            result.push(std::mem::take(&mut current));
-            current_width = 0;
+/// Returns at least one row (empty for empty input).
+fn wrap_spans(spans: &[Span<'static>], max_width: usize) -> Vec<Vec<Span<'static>>> {
+    let mut rows = vec![Vec::new()];
+    let mut row_width: usize = 0;
+
+    for span in spans {
+        let mut current = String::new();
+        for ch in span.content.chars() {
+            let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+            if max_width > 0 && row_width + ch_width > max_width && row_width > 0 {
+                if !current.is_empty() {
+                    rows.last_mut()
+                        .expect("rows is never empty")
+                        .push(Span::styled(std::mem::take(&mut current), span.style));
+                }
+                rows.push(Vec::new());
+                row_width = 0;
+            }
+            current.push(ch);
+            row_width += ch_width;
+        }
+        if !current.is_empty() {
+            rows.last_mut()
+                .expect("rows is never empty")
+                .push(Span::styled(current, span.style));
         }
-
-        current.push(ch);
-        current_width += ch_width;
     }
-
-    // Always push the last segment (even if empty — represents an empty content line)
-    result.push(current);
-    result
+    rows
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
+        wrap_spans(&[Span::raw(text.to_string())], max_width)
+            .iter()
+            .map(|row| row.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
     #[test]
     fn wrap_text_ascii() {
         // 10 chars in width 5 → 2 segments
@@ -320,6 +422,96 @@ mod tests {
         terminal.draw(|f| draw(f, &app)).unwrap();
     }
 
+    #[test]
+    fn test_draw_tool_activity() {
+        let mut app = App::new("openrouter", "test-model", "sqlite");
+        app.push_message(MessageRole::User, "run tests");
+        app.tool_started("shell", r#"{"command":"cargo test"}"#);
+        app.tool_finished(
+            "shell",
+            std::time::Duration::from_millis(2300),
+            false,
+            Some("exit status 101"),
+        );
+
+        let backend = ratatui::backend::TestBackend::new(60, 24);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|f| draw(f, &app)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let screen: String = (0..24)
+            .flat_map(|y| (0..60).map(move |x| (x, y)))
+            .map(|(x, y)| buffer[(x, y)].symbol().to_string())
+            .collect();
+        assert!(screen.contains("shell: cargo test (2.3s ✗)"));
+        assert!(screen.contains("exit status 101"));
+    }
+
+    #[test]
+    fn test_draw_markdown_toggle() {
+        let render = |markdown: bool| {
+            let mut app = App::new("openrouter", "test-model", "sqlite");
+            app.markdown = markdown;
+            app.push_message(
+                MessageRole::Assistant,
+                "Use **cargo**:\n```sh\ncargo test\n```",
+            );
+            let backend = ratatui::backend::TestBackend::new(60, 24);
+            let mut terminal = ratatui::Terminal::new(backend).unwrap();
+            terminal.draw(|f| draw(f, &app)).unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            let screen: String = (0..24)
+                .flat_map(|y| (0..60).map(move |x| (x, y)))
+                .map(|(x, y)| buffer[(x, y)].symbol().to_string())
+                .collect();
+            (screen, buffer)
+        };
+
+        let (screen, buffer) = render(true);
+        assert!(screen.contains("Jarvis: Use cargo:"));
+        assert!(!screen.contains("```"));
+        // Code rows are padded with the code background up to the border
+        let code_row = (0..24)
+            .find(|&y| {
+                (0..60)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+                    .contains("cargo test")
+            })
+            .unwrap();
+        assert_eq!(buffer[(58, code_row)].bg, markdown::CODE_BG);
+
+        let (raw, _) = render(false);
+        assert!(raw.contains("Jarvis: Use **cargo**:"));
+        assert!(raw.contains("```sh"));
+    }
+
+    #[test]
+    fn test_draw_usage_counters() {
+        let mut app = App::new("openrouter", "gpt-4o", "sqlite");
+        app.usage.add(&crate::usage::TurnUsage {
+            tokens: crate::usage::TokenUsage {
+                prompt_tokens: 2_500,
+                completion_tokens: 300,
+            },
+            cost_usd: Some(0.01),
+            history: crate::usage::HistorySize {
+                turns: 2,
+                tokens: 1_200,
+            },
+        });
+
+        let backend = ratatui::backend::TestBackend::new(100, 24);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|f| draw(f, &app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let screen: String = (0..24)
+            .flat_map(|y| (0..100).map(move |x| (x, y)))
+            .map(|(x, y)| buffer[(x, y)].symbol().to_string())
+            .collect();
+        assert!(screen.contains("↑2.5k ↓300 ~$0.0100 | 2 turns ~1.2k ctx"));
+    }
+
     #[test]
     fn test_draw_small_terminal() {
         let app = App::new("p", "m", "none");
@@ -338,4 +530,51 @@ mod tests {
         let mut terminal = ratatui::Terminal::new(backend).unwrap();
         terminal.draw(|f| draw(f, &app)).unwrap();
     }
+
+    #[test]
+    fn input_rows_split_newlines_and_wrap() {
+        let layout = input_rows("abcdef\nxy", 9, 4);
+        assert_eq!(layout.rows, vec!["abcd", "ef", "xy"]);
+        assert_eq!((layout.cursor_row, layout.cursor_col), (2, 2));
+
+        let layout = input_rows("abcdef\nxy", 5, 4);
+        assert_eq!((layout.cursor_row, layout.cursor_col), (1, 1));
+
+        let layout = input_rows("a\n", 2, 10);
+        assert_eq!(layout.rows, vec!["a", ""]);
+        assert_eq!((layout.cursor_row, layout.cursor_col), (1, 0));
+    }
+
+    #[test]
+    fn input_rows_cjk_cursor_column() {
+        let layout = input_rows("你好\n世界", "你好\n世".len(), 10);
+        assert_eq!((layout.cursor_row, layout.cursor_col), (1, 2));
+    }
+
+    #[test]
+    fn test_draw_multiline_input_grows_and_scrolls() {
+        let mut app = App::new("openrouter", "test-model", "sqlite");
+        app.insert_str(
+            &(1..=10)
+                .map(|i| format!("line {i}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+
+        let backend = ratatui::backend::TestBackend::new(40, 24);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|f| draw(f, &app)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let row_text = |y: u16| -> String {
+            (0..40)
+                .map(|x| buffer[(x, y)].symbol().to_string())
+                .collect()
+        };
+        // Input box: top border at 24 - (6 + 2), last visible row is line 10
+        assert!(row_text(16).contains("Input"));
+        assert!(row_text(22).contains("line 10"));
+        assert!(row_text(17).contains("line 5"));
+        assert!(!(16..24).any(|y| row_text(y).contains("line 4")));
+    }
 }
@@ -1,8 +1,22 @@
-use super::{kill_shared, new_shared_process, SharedProcess, Tunnel, TunnelProcess};
-use anyhow::{bail, Result};
-use tokio::io::AsyncBufReadExt;
+use super::{
+    capture_url, exit_error, kill_shared, new_shared_process, shared_is_running, spawn_error,
+    SharedProcess, Tunnel, TunnelProcess,
+};
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use std::time::Duration;
 use tokio::process::Command;
 
+/// How long to wait for a configured `url_pattern` to match.
+const PATTERN_WAIT_SECS: u64 = 15;
+
+/// How long to look for a URL when no `url_pattern` is configured before
+/// falling back to the local address.
+const DEFAULT_WAIT_SECS: u64 = 5;
+
+/// Any http(s) URL, used when no `url_pattern` is configured.
+const DEFAULT_URL_PATTERN: &str = r#"https?://[^\s"'<>]+"#;
+
 /// Custom Tunnel — bring your own tunnel binary.
 ///
 /// Provide a `start_command` with `{port}` and `{host}` placeholders.
@@ -35,6 +49,35 @@ impl CustomTunnel {
     }
 }
 
+/// Substitute `{host}`/`{port}` and split `start_command` shell-style.
+pub(crate) fn build_command(template: &str, host: &str, port: u16) -> Result<Vec<String>> {
+    let cmd = template
+        .replace("{port}", &port.to_string())
+        .replace("{host}", host);
+    let Some(parts) = shlex::split(&cmd) else {
+        bail!("custom 隧道的 start_command 引号不匹配：{cmd}");
+    };
+    if parts.is_empty() {
+        bail!("custom 隧道的 start_command 为空");
+    }
+    Ok(parts)
+}
+
+/// Compile the URL pattern (the configured one, or any http(s) URL).
+pub(crate) fn url_regex(pattern: Option<&str>) -> Result<Regex> {
+    let pattern = pattern.unwrap_or(DEFAULT_URL_PATTERN);
+    Regex::new(pattern).with_context(|| format!("url_pattern 不是有效的正则表达式：{pattern}"))
+}
+
+/// Extract the URL from a line: the first capture group if the pattern has
+/// one, otherwise the whole match.
+pub(crate) fn match_url(re: &Regex, line: &str) -> Option<String> {
+    let caps = re.captures(line)?;
+    caps.get(1)
+        .or_else(|| caps.get(0))
+        .map(|m| m.as_str().to_string())
+}
+
 #[async_trait::async_trait]
 impl Tunnel for CustomTunnel {
     fn name(&self) -> &str {
@@ -42,70 +85,45 @@ impl Tunnel for CustomTunnel {
     }
 
     async fn start(&self, local_host: &str, local_port: u16) -> Result<String> {
-        let cmd = self
-            .start_command
-            .replace("{port}", &local_port.to_string())
-            .replace("{host}", local_host);
-
-        let parts: Vec<&str> = cmd.split_whitespace().collect();
-        if parts.is_empty() {
-            bail!("Custom tunnel start_command is empty");
-        }
+        let parts = build_command(&self.start_command, local_host, local_port)?;
+        let re = url_regex(self.url_pattern.as_deref())?;
 
-        let mut child = Command::new(parts[0])
+        let mut child = Command::new(&parts[0])
             .args(&parts[1..])
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
             .kill_on_drop(true)
-            .spawn()?;
-
-        let mut public_url = format!("http://{local_host}:{local_port}");
-
-        // If a URL pattern is provided, try to extract the public URL from stdout
-        if let Some(ref pattern) = self.url_pattern {
-            if let Some(stdout) = child.stdout.take() {
-                let mut reader = tokio::io::BufReader::new(stdout).lines();
-                let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_secs(15);
-
-                while tokio::time::Instant::now() < deadline {
-                    let line = tokio::time::timeout(
-                        tokio::time::Duration::from_secs(3),
-                        reader.next_line(),
-                    )
-                    .await;
-
-                    match line {
-                        Ok(Ok(Some(l))) => {
-                            tracing::debug!("custom-tunnel: {l}");
-                            // Simple substring match on the pattern
-                            if l.contains(pattern)
-                                || l.contains("https://")
-                                || l.contains("http://")
-                            {
-                                // Extract URL from the line
-                                if let Some(idx) = l.find("https://") {
-                                    let url_part = &l[idx..];
-                                    let end = url_part
-                                        .find(|c: char| c.is_whitespace())
-                                        .unwrap_or(url_part.len());
-                                    public_url = url_part[..end].to_string();
-                                    break;
-                                } else if let Some(idx) = l.find("http://") {
-                                    let url_part = &l[idx..];
-                                    let end = url_part
-                                        .find(|c: char| c.is_whitespace())
-                                        .unwrap_or(url_part.len());
-                                    public_url = url_part[..end].to_string();
-                                    break;
-                                }
-                            }
-                        }
-                        Ok(Ok(None) | Err(_)) => break,
-                        Err(_) => {}
-                    }
-                }
+            .spawn()
+            .map_err(|e| spawn_error(e, &parts[0]))?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("无法读取 custom 隧道的输出"))?;
+        let wait_secs = if self.url_pattern.is_some() {
+            PATTERN_WAIT_SECS
+        } else {
+            DEFAULT_WAIT_SECS
+        };
+
+        let Ok(captured) = capture_url(
+            stdout,
+            "custom-tunnel",
+            Duration::from_secs(wait_secs),
+            |line| match_url(&re, line),
+        )
+        .await
+        else {
+            return Err(exit_error(child, &parts[0]).await);
+        };
+        let public_url = match (captured, &self.url_pattern) {
+            (Some(url), _) => url,
+            (None, Some(pattern)) => {
+                child.kill().await.ok();
+                bail!("custom 隧道在 {wait_secs} 秒内没有输出匹配 url_pattern（{pattern}）的地址");
             }
-        }
+            (None, None) => format!("http://{local_host}:{local_port}"),
+        };
 
         let mut guard = self.proc.lock().await;
         *guard = Some(TunnelProcess {
@@ -121,7 +139,11 @@ impl Tunnel for CustomTunnel {
     }
 
     async fn health_check(&self) -> bool {
-        // If a health URL is configured, try to reach it
+        if !shared_is_running(&self.proc).await {
+            return false;
+        }
+
+        // If a health URL is configured, it must be reachable too
         if let Some(ref url) = self.health_url {
             return reqwest::Client::new()
                 .get(url)
@@ -130,10 +152,7 @@ impl Tunnel for CustomTunnel {
                 .await
                 .is_ok();
         }
-
-        // Otherwise check if the process is still alive
-        let guard = self.proc.lock().await;
-        guard.as_ref().is_some_and(|tp| tp.child.id().is_some())
+        true
     }
 
     fn public_url(&self) -> Option<String> {
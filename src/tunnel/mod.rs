@@ -9,13 +9,20 @@ pub use custom::CustomTunnel;
 pub use ngrok::NgrokTunnel;
 #[allow(unused_imports)]
 pub use none::NoneTunnel;
-pub use tailscale::TailscaleTunnel;
+pub use tailscale::{check_ready as check_tailscale_ready, TailscaleTunnel};
 
 use crate::config::schema::{TailscaleTunnelConfig, TunnelConfig};
 use anyhow::{bail, Result};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 
+/// Health component name for the daemon-supervised tunnel
+pub const TUNNEL_COMPONENT: &str = "tunnel";
+
+/// How often the supervised tunnel checks that its process is still alive.
+const TUNNEL_HEALTH_SECS: u64 = 10;
+
 // ── Tunnel trait ─────────────────────────────────────────────────
 
 /// Agnostic tunnel abstraction — bring your own tunnel provider.
@@ -67,6 +74,77 @@ pub(crate) async fn kill_shared(proc: &SharedProcess) -> Result<()> {
     Ok(())
 }
 
+/// Whether a shared tunnel process is still running.
+pub(crate) async fn shared_is_running(proc: &SharedProcess) -> bool {
+    let mut guard = proc.lock().await;
+    guard
+        .as_mut()
+        .is_some_and(|tp| matches!(tp.child.try_wait(), Ok(None)))
+}
+
+/// Watch a tunnel child's output for its public URL.
+///
+/// Returns `Ok(None)` if nothing matched within `wait`, and an error if the
+/// output ended first (the process exited). The rest of the output is drained
+/// in the background so the child never blocks on a full pipe.
+pub(crate) async fn capture_url<R>(
+    output: R,
+    name: &'static str,
+    wait: Duration,
+    find_url: impl Fn(&str) -> Option<String>,
+) -> Result<Option<String>>
+where
+    R: tokio::io::AsyncRead + Unpin + Send + 'static,
+{
+    use tokio::io::AsyncBufReadExt;
+
+    let mut reader = tokio::io::BufReader::new(output).lines();
+    let deadline = tokio::time::Instant::now() + wait;
+    let mut url = None;
+    loop {
+        match tokio::time::timeout_at(deadline, reader.next_line()).await {
+            Ok(Ok(Some(line))) => {
+                tracing::debug!("{name}: {line}");
+                if let Some(found) = find_url(&line) {
+                    url = Some(found);
+                    break;
+                }
+            }
+            Ok(Ok(None)) => bail!("{name} 在输出公网地址之前已退出"),
+            Ok(Err(e)) => bail!("读取 {name} 输出失败：{e}"),
+            Err(_) => break,
+        }
+    }
+
+    tokio::spawn(async move {
+        while let Ok(Some(line)) = reader.next_line().await {
+            tracing::debug!("{name}: {line}");
+        }
+    });
+    Ok(url)
+}
+
+/// Describe why a tunnel child exited, with its exit status and stderr.
+pub(crate) async fn exit_error(child: tokio::process::Child, name: &str) -> anyhow::Error {
+    match child.wait_with_output().await {
+        Ok(output) => anyhow::anyhow!(
+            "{name} 已退出（{}）：{}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => anyhow::anyhow!("{name} 已退出：{e}"),
+    }
+}
+
+/// Turn a spawn failure into a hint to install the binary if it is missing.
+pub(crate) fn spawn_error(e: std::io::Error, binary: &str) -> anyhow::Error {
+    if e.kind() == std::io::ErrorKind::NotFound {
+        anyhow::anyhow!("未找到 {binary} 命令，请先安装并确认它在 PATH 中")
+    } else {
+        anyhow::Error::new(e).context(format!("启动 {binary} 失败"))
+    }
+}
+
 // ── Factory ──────────────────────────────────────────────────────
 
 /// Create a tunnel from config. Returns `None` for provider "none".
@@ -120,6 +198,38 @@ pub fn create_tunnel(config: &TunnelConfig) -> Result<Option<Box<dyn Tunnel>>> {
     }
 }
 
+// ── Supervised entry point ───────────────────────────────────────
+
+/// Start `tunnel` in front of `host:port` and keep it up.
+///
+/// Publishes the public URL as the detail of the [`TUNNEL_COMPONENT`] health
+/// entry (written to the daemon state file and shown by `jarvis status`),
+/// then returns an error once the tunnel process dies so the daemon
+/// supervisor restarts it. The daemon calls [`Tunnel::stop`] on shutdown.
+pub async fn run_tunnel(tunnel: &dyn Tunnel, host: &str, port: u16) -> Result<()> {
+    crate::health::set_component_detail(TUNNEL_COMPONENT, format!("{} 启动中", tunnel.name()));
+    let url = match tunnel.start(host, port).await {
+        Ok(url) => url,
+        Err(e) => {
+            tunnel.stop().await.ok();
+            return Err(e);
+        }
+    };
+    tracing::info!("{} 隧道已激活：{url}", tunnel.name());
+    crate::health::set_component_detail(TUNNEL_COMPONENT, &url);
+    crate::health::mark_component_ok(TUNNEL_COMPONENT);
+
+    let mut interval = tokio::time::interval(Duration::from_secs(TUNNEL_HEALTH_SECS));
+    loop {
+        interval.tick().await;
+        if !tunnel.health_check().await {
+            tunnel.stop().await.ok();
+            bail!("{} 隧道进程已退出（{url}）", tunnel.name());
+        }
+        crate::health::mark_component_ok(TUNNEL_COMPONENT);
+    }
+}
+
 // ── Tests ────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -294,6 +404,124 @@ mod tests {
         assert_eq!(t.name(), "tailscale");
     }
 
+    #[test]
+    fn tailscale_status_requires_running_backend() {
+        let running = br#"{"BackendState":"Running","Self":{"DNSName":"box.tail1234.ts.net."}}"#;
+        assert_eq!(
+            tailscale::parse_status(running).unwrap(),
+            "box.tail1234.ts.net"
+        );
+
+        let logged_out = br#"{"BackendState":"NeedsLogin","Self":{"DNSName":""}}"#;
+        let err = tailscale::parse_status(logged_out).unwrap_err();
+        assert!(err.to_string().contains("tailscale up"), "{err}");
+
+        let stopped = br#"{"BackendState":"Stopped"}"#;
+        assert!(tailscale::parse_status(stopped).is_err());
+        assert!(tailscale::parse_status(b"not json").is_err());
+    }
+
+    #[test]
+    fn tailscale_serve_url_is_parsed_from_output() {
+        assert_eq!(
+            tailscale::parse_serve_url("https://box.tail1234.ts.net/").as_deref(),
+            Some("https://box.tail1234.ts.net")
+        );
+        assert_eq!(
+            tailscale::parse_serve_url("Available on the internet: https://box.ts.net:8443/ ")
+                .as_deref(),
+            Some("https://box.ts.net:8443")
+        );
+        assert!(tailscale::parse_serve_url("|-- / proxy http://127.0.0.1:3000").is_none());
+        assert!(tailscale::parse_serve_url("Available within your tailnet:").is_none());
+    }
+
+    #[test]
+    fn ngrok_url_is_parsed_from_logfmt() {
+        let line = r#"t=2026-01-01 lvl=info msg="started tunnel" obj=tunnels name=command_line addr=http://localhost:3000 url=https://ab12.ngrok-free.app"#;
+        assert_eq!(
+            ngrok::parse_ngrok_url(line).as_deref(),
+            Some("https://ab12.ngrok-free.app")
+        );
+        assert!(ngrok::parse_ngrok_url("lvl=info msg=\"client session established\"").is_none());
+    }
+
+    #[test]
+    fn custom_command_substitutes_and_splits() {
+        let parts =
+            custom::build_command("ssh -R '80:{host}:{port}' serveo.net", "127.0.0.1", 3000)
+                .unwrap();
+        assert_eq!(parts, ["ssh", "-R", "80:127.0.0.1:3000", "serveo.net"]);
+        assert!(custom::build_command("   ", "h", 1).is_err());
+        assert!(custom::build_command("bore 'local", "h", 1).is_err());
+    }
+
+    #[test]
+    fn custom_url_pattern_uses_capture_group() {
+        let re = custom::url_regex(Some(r"listening at (bore\.pub:\d+)")).unwrap();
+        assert_eq!(
+            custom::match_url(&re, "INFO listening at bore.pub:40123").as_deref(),
+            Some("bore.pub:40123")
+        );
+        assert!(custom::match_url(&re, "connecting...").is_none());
+
+        let default = custom::url_regex(None).unwrap();
+        assert_eq!(
+            custom::match_url(
+                &default,
+                "Forwarding HTTP traffic from https://x.serveo.net\""
+            )
+            .as_deref(),
+            Some("https://x.serveo.net")
+        );
+        assert!(custom::url_regex(Some("(unclosed")).is_err());
+    }
+
+    #[tokio::test]
+    async fn capture_url_finds_url_or_reports_exit() {
+        let output = std::io::Cursor::new(b"starting\nurl: https://a.example\nmore\n".to_vec());
+        let url = capture_url(output, "test", Duration::from_secs(1), |line| {
+            line.strip_prefix("url: ").map(str::to_string)
+        })
+        .await
+        .unwrap();
+        assert_eq!(url.as_deref(), Some("https://a.example"));
+
+        let output = std::io::Cursor::new(b"no url here\n".to_vec());
+        let err = capture_url(output, "test", Duration::from_secs(1), |_| None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("已退出"), "{err}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn shared_process_reports_exit() {
+        let proc = new_shared_process();
+        assert!(!shared_is_running(&proc).await);
+
+        let child = tokio::process::Command::new("sleep")
+            .arg("5")
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        *proc.lock().await = Some(TunnelProcess {
+            child,
+            public_url: "https://example.test".into(),
+        });
+        assert!(shared_is_running(&proc).await);
+
+        proc.lock()
+            .await
+            .as_mut()
+            .unwrap()
+            .child
+            .kill()
+            .await
+            .unwrap();
+        assert!(!shared_is_running(&proc).await);
+    }
+
     #[test]
     fn ngrok_tunnel_name() {
         let t = NgrokTunnel::new("tok".into(), None);
@@ -1,8 +1,14 @@
-use super::{kill_shared, new_shared_process, SharedProcess, Tunnel, TunnelProcess};
+use super::{
+    capture_url, exit_error, kill_shared, new_shared_process, shared_is_running, spawn_error,
+    SharedProcess, Tunnel, TunnelProcess,
+};
 use anyhow::{bail, Result};
-use tokio::io::AsyncBufReadExt;
+use std::time::Duration;
 use tokio::process::Command;
 
+/// How long to wait for ngrok to report its public URL.
+const URL_WAIT_SECS: u64 = 15;
+
 /// ngrok Tunnel — wraps the `ngrok` binary.
 ///
 /// Requires `ngrok` installed. Optionally set a custom domain
@@ -21,6 +27,26 @@ impl NgrokTunnel {
             proc: new_shared_process(),
         }
     }
+
+    /// Arguments for `ngrok http <port> [--domain <domain>]`, logging to
+    /// stdout in logfmt so the URL can be read back.
+    fn args(&self, local_port: u16) -> Vec<String> {
+        let mut args = vec!["http".to_string(), local_port.to_string()];
+        if let Some(ref domain) = self.domain {
+            args.push("--domain".into());
+            args.push(domain.clone());
+        }
+        args.extend(["--log", "stdout", "--log-format", "logfmt"].map(String::from));
+        args
+    }
+}
+
+/// Pick the public URL out of an ngrok logfmt line
+/// (`... msg="started tunnel" ... url=https://xxxx.ngrok-free.app`).
+pub(crate) fn parse_ngrok_url(line: &str) -> Option<String> {
+    let start = line.find("url=https://")? + "url=".len();
+    let url = line[start..].split_whitespace().next()?.trim_matches('"');
+    Some(url.to_string())
 }
 
 #[async_trait::async_trait]
@@ -30,69 +56,37 @@ impl Tunnel for NgrokTunnel {
     }
 
     async fn start(&self, _local_host: &str, local_port: u16) -> Result<String> {
-        // Set auth token
-        Command::new("ngrok")
-            .args(["config", "add-authtoken", &self.auth_token])
-            .output()
-            .await?;
-
-        // Build command: ngrok http <port> [--domain <domain>]
-        let mut args = vec!["http".to_string(), local_port.to_string()];
-        if let Some(ref domain) = self.domain {
-            args.push("--domain".into());
-            args.push(domain.clone());
-        }
-        // Output log to stdout for URL extraction
-        args.push("--log".into());
-        args.push("stdout".into());
-        args.push("--log-format".into());
-        args.push("logfmt".into());
-
+        // The token goes in the environment rather than ngrok's global config
+        // or the process arguments
         let mut child = Command::new("ngrok")
-            .args(&args)
+            .args(self.args(local_port))
+            .env("NGROK_AUTHTOKEN", &self.auth_token)
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
             .kill_on_drop(true)
-            .spawn()?;
+            .spawn()
+            .map_err(|e| spawn_error(e, "ngrok"))?;
 
         let stdout = child
             .stdout
             .take()
-            .ok_or_else(|| anyhow::anyhow!("Failed to capture ngrok stdout"))?;
-
-        let mut reader = tokio::io::BufReader::new(stdout).lines();
-        let mut public_url = String::new();
-
-        // Wait up to 15s for the tunnel URL
-        let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_secs(15);
-        while tokio::time::Instant::now() < deadline {
-            let line =
-                tokio::time::timeout(tokio::time::Duration::from_secs(3), reader.next_line()).await;
-
-            match line {
-                Ok(Ok(Some(l))) => {
-                    tracing::debug!("ngrok: {l}");
-                    // ngrok logfmt: url=https://xxxx.ngrok-free.app
-                    if let Some(idx) = l.find("url=https://") {
-                        let url_start = idx + 4; // skip "url="
-                        let url_part = &l[url_start..];
-                        let end = url_part
-                            .find(|c: char| c.is_whitespace())
-                            .unwrap_or(url_part.len());
-                        public_url = url_part[..end].to_string();
-                        break;
-                    }
-                }
-                Ok(Ok(None)) => break,
-                Ok(Err(e)) => bail!("Error reading ngrok output: {e}"),
-                Err(_) => {}
+            .ok_or_else(|| anyhow::anyhow!("无法读取 ngrok 输出"))?;
+
+        let public_url = match capture_url(
+            stdout,
+            "ngrok",
+            Duration::from_secs(URL_WAIT_SECS),
+            parse_ngrok_url,
+        )
+        .await
+        {
+            Ok(Some(url)) => url,
+            Ok(None) => {
+                child.kill().await.ok();
+                bail!("ngrok 在 {URL_WAIT_SECS} 秒内没有给出公网地址，请检查 auth token 是否有效");
             }
-        }
-
-        if public_url.is_empty() {
-            child.kill().await.ok();
-            bail!("ngrok did not produce a public URL within 15s. Is the auth token valid?");
-        }
+            Err(_) => return Err(exit_error(child, "ngrok").await),
+        };
 
         let mut guard = self.proc.lock().await;
         *guard = Some(TunnelProcess {
@@ -108,8 +102,7 @@ impl Tunnel for NgrokTunnel {
     }
 
     async fn health_check(&self) -> bool {
-        let guard = self.proc.lock().await;
-        guard.as_ref().is_some_and(|tp| tp.child.id().is_some())
+        shared_is_running(&self.proc).await
     }
 
     fn public_url(&self) -> Option<String> {
@@ -1,7 +1,14 @@
-use super::{kill_shared, new_shared_process, SharedProcess, Tunnel, TunnelProcess};
-use anyhow::{bail, Result};
+use super::{
+    capture_url, exit_error, kill_shared, new_shared_process, shared_is_running, SharedProcess,
+    Tunnel, TunnelProcess,
+};
+use anyhow::{bail, Context, Result};
+use std::time::Duration;
 use tokio::process::Command;
 
+/// How long to wait for `tailscale serve|funnel` to print its URL.
+const URL_WAIT_SECS: u64 = 10;
+
 /// Tailscale Tunnel — uses `tailscale serve` (tailnet-only) or
 /// `tailscale funnel` (public internet).
 ///
@@ -20,6 +27,82 @@ impl TailscaleTunnel {
             proc: new_shared_process(),
         }
     }
+
+    fn subcommand(&self) -> &'static str {
+        if self.funnel {
+            "funnel"
+        } else {
+            "serve"
+        }
+    }
+}
+
+/// Check that tailscale is installed and logged in, returning this node's
+/// tailnet DNS name. Blocking; used by `jarvis doctor`.
+pub fn check_ready() -> Result<String> {
+    interpret_status(
+        std::process::Command::new("tailscale")
+            .args(["status", "--json"])
+            .output(),
+    )
+}
+
+/// Turn the outcome of `tailscale status --json` into the node's DNS name,
+/// or an error saying why it can't serve.
+fn interpret_status(output: std::io::Result<std::process::Output>) -> Result<String> {
+    let output = match output {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            bail!("未找到 tailscale 命令，请先安装 Tailscale（https://tailscale.com/download）")
+        }
+        Err(e) => return Err(e).context("运行 tailscale status 失败"),
+    };
+    // `tailscale status` exits non-zero when logged out but still prints JSON
+    match parse_status(&output.stdout) {
+        Ok(hostname) => Ok(hostname),
+        Err(e) if output.status.success() => Err(e),
+        Err(e) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.trim().is_empty() {
+                Err(e)
+            } else {
+                bail!("{e}（tailscale status：{}）", stderr.trim())
+            }
+        }
+    }
+}
+
+/// Parse `tailscale status --json`: the node must be logged in and running.
+pub(crate) fn parse_status(raw: &[u8]) -> Result<String> {
+    let status: serde_json::Value =
+        serde_json::from_slice(raw).context("无法解析 tailscale status 的输出")?;
+    match status["BackendState"].as_str().unwrap_or("") {
+        "Running" => {}
+        "NeedsLogin" | "NeedsMachineAuth" => {
+            bail!("tailscale 未登录，请先运行 `tailscale up` 完成登录")
+        }
+        "Stopped" => bail!("tailscale 已停止，请运行 `tailscale up`"),
+        other => bail!("tailscale 未就绪（BackendState = {other:?}）"),
+    }
+    let hostname = status["Self"]["DNSName"]
+        .as_str()
+        .unwrap_or("")
+        .trim_end_matches('.');
+    if hostname.is_empty() {
+        bail!("tailscale 未返回本机 DNS 名称，请确认已启用 MagicDNS");
+    }
+    Ok(hostname.to_string())
+}
+
+/// Pick the served URL out of a line of `tailscale serve|funnel` output,
+/// e.g. `https://box.tail1234.ts.net/`.
+pub(crate) fn parse_serve_url(line: &str) -> Option<String> {
+    let start = line.find("https://")?;
+    let url = line[start..]
+        .split_whitespace()
+        .next()?
+        .trim_end_matches('/');
+    (url.len() > "https://".len()).then(|| url.to_string())
 }
 
 #[async_trait::async_trait]
@@ -29,44 +112,39 @@ impl Tunnel for TailscaleTunnel {
     }
 
     async fn start(&self, _local_host: &str, local_port: u16) -> Result<String> {
-        let subcommand = if self.funnel { "funnel" } else { "serve" };
-
-        // Get the tailscale hostname for URL construction
-        let hostname = if let Some(ref h) = self.hostname {
-            h.clone()
-        } else {
-            // Query tailscale for the current hostname
-            let output = Command::new("tailscale")
-                .args(["status", "--json"])
-                .output()
-                .await?;
-
-            if !output.status.success() {
-                bail!(
-                    "tailscale status failed: {}",
-                    String::from_utf8_lossy(&output.stderr)
-                );
-            }
-
-            let status: serde_json::Value =
-                serde_json::from_slice(&output.stdout).unwrap_or_default();
-            status["Self"]["DNSName"]
-                .as_str()
-                .unwrap_or("localhost")
-                .trim_end_matches('.')
-                .to_string()
-        };
+        let status = Command::new("tailscale")
+            .args(["status", "--json"])
+            .output()
+            .await;
+        let node_name = interpret_status(status)?;
+        let hostname = self.hostname.clone().unwrap_or(node_name);
 
-        // tailscale serve|funnel <port>
-        let child = Command::new("tailscale")
-            .args([subcommand, &local_port.to_string()])
+        // tailscale serve|funnel <port> stays in the foreground while serving
+        let mut child = Command::new("tailscale")
+            .args([self.subcommand(), &local_port.to_string()])
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
             .kill_on_drop(true)
-            .spawn()?;
+            .spawn()
+            .context("启动 tailscale 失败")?;
 
-        let public_url = format!("https://{hostname}:{local_port}");
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("无法读取 tailscale 输出"))?;
+        // Older CLIs print nothing in the foreground, so a timeout is fine
+        let Ok(public_url) = capture_url(
+            stdout,
+            "tailscale",
+            Duration::from_secs(URL_WAIT_SECS),
+            parse_serve_url,
+        )
+        .await
+        else {
+            return Err(exit_error(child, "tailscale").await);
+        };
 
+        let public_url = public_url.unwrap_or_else(|| format!("https://{hostname}"));
         let mut guard = self.proc.lock().await;
         *guard = Some(TunnelProcess {
             child,
@@ -78,9 +156,8 @@ impl Tunnel for TailscaleTunnel {
 
     async fn stop(&self) -> Result<()> {
         // Also reset the tailscale serve/funnel
-        let subcommand = if self.funnel { "funnel" } else { "serve" };
         Command::new("tailscale")
-            .args([subcommand, "reset"])
+            .args([self.subcommand(), "reset"])
             .output()
             .await
             .ok();
@@ -89,8 +166,7 @@ impl Tunnel for TailscaleTunnel {
     }
 
     async fn health_check(&self) -> bool {
-        let guard = self.proc.lock().await;
-        guard.as_ref().is_some_and(|tp| tp.child.id().is_some())
+        shared_is_running(&self.proc).await
     }
 
     fn public_url(&self) -> Option<String> {
@@ -0,0 +1,362 @@
+//! Daily cost log: every completed agent turn appends its (estimated) token
+//! usage and cost to `workspace/state/usage/YYYY-MM-DD.jsonl`, one JSON
+//! object per line. Summed by provider and model with `jarvis usage`.
+
+use super::{TokenUsage, TurnUsage};
+use crate::config::Config;
+use crate::sessions::Transcript;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// One line of the daily log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub timestamp: DateTime<Utc>,
+    /// Transcript session the turn belongs to
+    pub session: String,
+    /// cli, tui, gateway, …
+    pub origin: String,
+    pub provider: String,
+    pub model: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// `None` for models without a known price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+/// Writer for the daily log. The default records nothing (tests).
+#[derive(Debug, Clone, Default)]
+pub struct UsageLog {
+    dir: Option<PathBuf>,
+    provider: String,
+    origin: String,
+    session: String,
+}
+
+impl UsageLog {
+    /// Log turns of `transcript`'s session, answered by `provider`.
+    pub fn new(workspace_dir: &Path, provider: &str, transcript: &Transcript) -> Self {
+        Self {
+            dir: Some(usage_dir(workspace_dir)),
+            provider: provider.to_string(),
+            origin: transcript.origin().to_string(),
+            session: transcript.id().to_string(),
+        }
+    }
+
+    /// Append a turn to today's file (best-effort: a failed write is logged,
+    /// never fatal). Turns that made no model call are skipped.
+    pub fn record(&self, model: &str, turn: &TurnUsage) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+        if turn.tokens == TokenUsage::default() {
+            return;
+        }
+        let record = UsageRecord {
+            timestamp: Utc::now(),
+            session: self.session.clone(),
+            origin: self.origin.clone(),
+            provider: self.provider.clone(),
+            model: model.to_string(),
+            prompt_tokens: turn.tokens.prompt_tokens,
+            completion_tokens: turn.tokens.completion_tokens,
+            cost_usd: turn.cost_usd,
+        };
+        let path = day_file(dir, Local::now().date_naive());
+        if let Err(e) = append(&path, &record) {
+            tracing::warn!("写入用量记录失败 {}: {e:#}", path.display());
+        }
+    }
+}
+
+fn usage_dir(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join("state").join("usage")
+}
+
+fn day_file(dir: &Path, date: NaiveDate) -> PathBuf {
+    dir.join(format!("{}.jsonl", date.format("%Y-%m-%d")))
+}
+
+fn append(path: &Path, record: &UsageRecord) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    // One write per line, so concurrent turns never interleave within a line
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Records of every day on or after `since` (all days when `None`), skipping
+/// lines that don't parse (e.g. a torn last write).
+fn read_since(workspace_dir: &Path, since: Option<NaiveDate>) -> Result<Vec<UsageRecord>> {
+    let dir = usage_dir(workspace_dir);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("读取 {} 失败", dir.display())),
+    };
+    let mut days: Vec<(NaiveDate, PathBuf)> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let stem = path.file_name()?.to_str()?.strip_suffix(".jsonl")?;
+            let date = NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok()?;
+            Some((date, path))
+        })
+        .filter(|(date, _)| since.is_none_or(|since| *date >= since))
+        .collect();
+    days.sort();
+
+    let mut records = Vec::new();
+    for (_, path) in days {
+        let raw =
+            fs::read_to_string(&path).with_context(|| format!("读取 {} 失败", path.display()))?;
+        records.extend(
+            raw.lines()
+                .filter(|line| !line.trim().is_empty())
+                .filter_map(|line| serde_json::from_str::<UsageRecord>(line).ok()),
+        );
+    }
+    Ok(records)
+}
+
+/// Totals for one provider and model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageTotal {
+    pub provider: String,
+    pub model: String,
+    pub turns: u64,
+    pub tokens: TokenUsage,
+    /// Sum over the turns with a known price
+    pub cost_usd: f64,
+    /// Turns whose model had no known price
+    pub unpriced_turns: u64,
+}
+
+/// Usage since `since` (inclusive), by provider and model, most expensive
+/// first.
+pub fn aggregate(workspace_dir: &Path, since: Option<NaiveDate>) -> Result<Vec<UsageTotal>> {
+    let mut totals: BTreeMap<(String, String), UsageTotal> = BTreeMap::new();
+    for record in read_since(workspace_dir, since)? {
+        let total = totals
+            .entry((record.provider.clone(), record.model.clone()))
+            .or_insert_with(|| UsageTotal {
+                provider: record.provider,
+                model: record.model,
+                ..UsageTotal::default()
+            });
+        total.turns += 1;
+        total.tokens.prompt_tokens += record.prompt_tokens;
+        total.tokens.completion_tokens += record.completion_tokens;
+        match record.cost_usd {
+            Some(cost) => total.cost_usd += cost,
+            None => total.unpriced_turns += 1,
+        }
+    }
+    let mut totals: Vec<UsageTotal> = totals.into_values().collect();
+    totals.sort_by(|a, b| b.cost_usd.total_cmp(&a.cost_usd));
+    Ok(totals)
+}
+
+/// `jarvis usage [--since DATE]`.
+pub fn run(config: &Config, since: Option<&str>) -> Result<()> {
+    let since = since
+        .map(|raw| {
+            NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d")
+                .with_context(|| format!("日期格式无效：{raw}（应为 YYYY-MM-DD）"))
+        })
+        .transpose()?;
+    let totals = aggregate(&config.workspace_dir, since)?;
+
+    let range = since.map_or_else(|| "全部记录".to_string(), |d| format!("自 {d} 起"));
+    if totals.is_empty() {
+        println!("没有用量记录（{range}）。");
+        return Ok(());
+    }
+
+    println!("💰 Token 用量与估算费用（{range}）");
+    println!();
+    println!(
+        "  {:<14} {:<36} {:>6} {:>12} {:>12} {:>10}",
+        "Provider", "模型", "轮次", "输入 token", "输出 token", "费用 (USD)"
+    );
+    let mut sum = UsageTotal::default();
+    for total in &totals {
+        println!(
+            "  {:<14} {:<36} {:>6} {:>12} {:>12} {:>10}",
+            total.provider,
+            total.model,
+            total.turns,
+            total.tokens.prompt_tokens,
+            total.tokens.completion_tokens,
+            cost_text(total),
+        );
+        sum.turns += total.turns;
+        sum.tokens.prompt_tokens += total.tokens.prompt_tokens;
+        sum.tokens.completion_tokens += total.tokens.completion_tokens;
+        sum.cost_usd += total.cost_usd;
+        sum.unpriced_turns += total.unpriced_turns;
+    }
+    println!(
+        "  {:<14} {:<36} {:>6} {:>12} {:>12} {:>10}",
+        "合计",
+        "",
+        sum.turns,
+        sum.tokens.prompt_tokens,
+        sum.tokens.completion_tokens,
+        cost_text(&sum),
+    );
+    println!();
+    println!("  Token 数按文本长度估算，费用按内置价格表计算，仅供参考。");
+    if sum.unpriced_turns > 0 {
+        println!(
+            "  标记 + 的费用不含 {} 轮未知价格模型的用量。",
+            sum.unpriced_turns
+        );
+    }
+    Ok(())
+}
+
+/// `$0.0512`, `$0.0512+` when some turns are unpriced, `?` when all are.
+fn cost_text(total: &UsageTotal) -> String {
+    if total.unpriced_turns == total.turns {
+        "?".to_string()
+    } else if total.unpriced_turns > 0 {
+        format!("${:.4}+", total.cost_usd)
+    } else {
+        format!("${:.4}", total.cost_usd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(provider: &str, model: &str, tokens: (u64, u64), cost: Option<f64>) -> UsageRecord {
+        UsageRecord {
+            timestamp: Utc::now(),
+            session: "s".into(),
+            origin: "cli".into(),
+            provider: provider.into(),
+            model: model.into(),
+            prompt_tokens: tokens.0,
+            completion_tokens: tokens.1,
+            cost_usd: cost,
+        }
+    }
+
+    fn write_day(workspace: &Path, day: &str, records: &[UsageRecord]) {
+        let date = NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap();
+        let path = day_file(&usage_dir(workspace), date);
+        for r in records {
+            append(&path, r).unwrap();
+        }
+    }
+
+    #[test]
+    fn aggregates_fixture_days_by_provider_and_model() {
+        let tmp = TempDir::new().unwrap();
+        write_day(
+            tmp.path(),
+            "2026-10-15",
+            &[
+                record("openrouter", "openai/gpt-4o", (1000, 200), Some(0.01)),
+                record("ollama", "llama3.2", (500, 50), None),
+            ],
+        );
+        write_day(
+            tmp.path(),
+            "2026-10-16",
+            &[
+                record("openrouter", "openai/gpt-4o", (3000, 400), Some(0.02)),
+                record("anthropic", "claude-sonnet-4", (100, 10), Some(0.005)),
+            ],
+        );
+        // Torn write and a stray file are ignored
+        let dir = usage_dir(tmp.path());
+        fs::write(dir.join("2026-10-17.jsonl"), "{\"timestamp\":").unwrap();
+        fs::write(dir.join("notes.txt"), "x").unwrap();
+
+        let totals = aggregate(tmp.path(), None).unwrap();
+        assert_eq!(totals.len(), 3);
+        let gpt = &totals[0];
+        assert_eq!(
+            (gpt.provider.as_str(), gpt.model.as_str()),
+            ("openrouter", "openai/gpt-4o")
+        );
+        assert_eq!(gpt.turns, 2);
+        assert_eq!(
+            gpt.tokens,
+            TokenUsage {
+                prompt_tokens: 4000,
+                completion_tokens: 600
+            }
+        );
+        assert!((gpt.cost_usd - 0.03).abs() < 1e-9);
+        assert_eq!(cost_text(gpt), "$0.0300");
+        assert_eq!(totals[1].model, "claude-sonnet-4");
+        let local = &totals[2];
+        assert_eq!((local.unpriced_turns, local.cost_usd), (1, 0.0));
+        assert_eq!(cost_text(local), "?");
+
+        let since = NaiveDate::from_ymd_opt(2026, 10, 16);
+        let recent = aggregate(tmp.path(), since).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].turns, 1);
+        assert_eq!(recent[0].tokens.prompt_tokens, 3000);
+    }
+
+    #[test]
+    fn log_appends_turns_to_todays_file() {
+        let tmp = TempDir::new().unwrap();
+        let transcript = Transcript::start(tmp.path(), "tui");
+        let log = UsageLog::new(tmp.path(), "openai", &transcript);
+        let turn = TurnUsage {
+            tokens: TokenUsage {
+                prompt_tokens: 1_000_000,
+                completion_tokens: 0,
+            },
+            cost_usd: Some(2.5),
+            ..TurnUsage::default()
+        };
+        log.record("gpt-4o", &turn);
+        log.record("gpt-4o", &TurnUsage::default());
+        UsageLog::default().record("gpt-4o", &turn);
+
+        let records = read_since(tmp.path(), None).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].session, transcript.id());
+        assert_eq!(records[0].origin, "tui");
+        assert_eq!(records[0].provider, "openai");
+        assert_eq!(records[0].cost_usd, Some(2.5));
+        assert!(day_file(&usage_dir(tmp.path()), Local::now().date_naive()).exists());
+    }
+
+    #[test]
+    fn missing_log_dir_is_empty() {
+        let tmp = TempDir::new().unwrap();
+        assert!(aggregate(tmp.path(), None).unwrap().is_empty());
+        assert_eq!(
+            cost_text(&UsageTotal {
+                turns: 2,
+                cost_usd: 0.5,
+                unpriced_turns: 1,
+                ..UsageTotal::default()
+            }),
+            "$0.5000+"
+        );
+    }
+}
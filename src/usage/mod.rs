@@ -0,0 +1,374 @@
+//! Token and cost estimates, shared by the TUI status bar and the daily
+//! cost log in [`ledger`].
+//!
+//! Providers don't report usage through `chat_with_tools`, so counts are
+//! estimated from the text sent and received (~4 chars per token for
+//! Latin text, ~1 per CJK character). Good enough to see a conversation
+//! growing; not a billing statement.
+
+pub mod ledger;
+
+use crate::providers::traits::{ChatMessage, ToolDefinition};
+
+/// Fixed per-message overhead (role markers etc.)
+const MESSAGE_OVERHEAD_TOKENS: u64 = 4;
+
+/// USD per million input/output tokens, matched by substring of the model
+/// name. More specific names come first.
+const PRICING: &[(&str, f64, f64)] = &[
+    ("claude-opus-4", 15.0, 75.0),
+    ("claude-3-opus", 15.0, 75.0),
+    ("claude-sonnet-4", 3.0, 15.0),
+    ("claude-3-7-sonnet", 3.0, 15.0),
+    ("claude-3-5-sonnet", 3.0, 15.0),
+    ("claude-3-5-haiku", 0.8, 4.0),
+    ("claude-3-haiku", 0.25, 1.25),
+    ("gpt-4o-mini", 0.15, 0.6),
+    ("gpt-4o", 2.5, 10.0),
+    ("gpt-4.1-nano", 0.1, 0.4),
+    ("gpt-4.1-mini", 0.4, 1.6),
+    ("gpt-4.1", 2.0, 8.0),
+    ("o3-mini", 1.1, 4.4),
+    ("o4-mini", 1.1, 4.4),
+    ("deepseek-chat", 0.27, 1.1),
+    ("deepseek-reasoner", 0.55, 2.19),
+    ("gemini-2.5-pro", 1.25, 10.0),
+    ("gemini-2.5-flash", 0.3, 2.5),
+    ("gemini-2.0-flash", 0.1, 0.4),
+    ("gemini-1.5-pro", 1.25, 5.0),
+    ("gemini-1.5-flash", 0.075, 0.3),
+];
+
+/// Rough token count of `text`.
+pub fn estimate_tokens(text: &str) -> u64 {
+    let (ascii, other) = text.chars().fold((0u64, 0u64), |(ascii, other), c| {
+        if c.is_ascii() {
+            (ascii + 1, other)
+        } else {
+            (ascii, other + 1)
+        }
+    });
+    ascii.div_ceil(4) + other
+}
+
+fn message_tokens(message: &ChatMessage) -> u64 {
+    let body = match message {
+        ChatMessage::System { content }
+        | ChatMessage::User { content }
+        | ChatMessage::Tool { content, .. } => estimate_tokens(content),
+        ChatMessage::Assistant {
+            content,
+            tool_calls,
+        } => {
+            let text = content.as_deref().map_or(0, estimate_tokens);
+            let calls: u64 = tool_calls
+                .iter()
+                .flatten()
+                .map(|call| {
+                    estimate_tokens(&call.function.name) + estimate_tokens(&call.function.arguments)
+                })
+                .sum();
+            text + calls
+        }
+    };
+    body + MESSAGE_OVERHEAD_TOKENS
+}
+
+/// Price per million input/output tokens for `model`, if known.
+pub fn pricing(model: &str) -> Option<(f64, f64)> {
+    let model = model.to_lowercase();
+    PRICING
+        .iter()
+        .find(|(name, _, _)| model.contains(name))
+        .map(|&(_, input, output)| (input, output))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Estimated cost in USD, `None` for models without a known price.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn cost_usd(&self, model: &str) -> Option<f64> {
+        let (input, output) = pricing(model)?;
+        Some(
+            (self.prompt_tokens as f64 * input + self.completion_tokens as f64 * output)
+                / 1_000_000.0,
+        )
+    }
+}
+
+/// Size of the conversation history that will be sent with the next message.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistorySize {
+    pub turns: usize,
+    pub tokens: u64,
+}
+
+impl HistorySize {
+    pub fn of(history: &[ChatMessage]) -> Self {
+        Self {
+            turns: history
+                .iter()
+                .filter(|m| matches!(m, ChatMessage::User { .. }))
+                .count(),
+            tokens: history.iter().map(message_tokens).sum(),
+        }
+    }
+}
+
+/// Usage of one user turn, sent to the UI along with the reply.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TurnUsage {
+    pub tokens: TokenUsage,
+    pub cost_usd: Option<f64>,
+    pub history: HistorySize,
+}
+
+impl TurnUsage {
+    /// Usage of the model calls that produced `history[start..]`.
+    ///
+    /// Every assistant message there is one provider call, whose prompt was
+    /// the whole history before it plus the tool definitions.
+    pub fn of_turn(
+        history: &[ChatMessage],
+        start: usize,
+        tool_definitions: &[ToolDefinition],
+        model: &str,
+    ) -> Self {
+        let tools = serde_json::to_string(tool_definitions).map_or(0, |s| estimate_tokens(&s));
+        let mut tokens = TokenUsage::default();
+        let mut prompt_so_far: u64 = history[..start.min(history.len())]
+            .iter()
+            .map(message_tokens)
+            .sum();
+        for message in history.iter().skip(start) {
+            let size = message_tokens(message);
+            if matches!(message, ChatMessage::Assistant { .. }) {
+                tokens.prompt_tokens += prompt_so_far + tools;
+                tokens.completion_tokens += size;
+            }
+            prompt_so_far += size;
+        }
+        Self {
+            tokens,
+            cost_usd: tokens.cost_usd(model),
+            history: HistorySize::of(history),
+        }
+    }
+}
+
+/// Running totals since the conversation started (or was cleared).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageMeter {
+    pub tokens: TokenUsage,
+    pub cost_usd: f64,
+    /// Some turns used a model without a known price
+    pub unpriced: bool,
+    pub history: HistorySize,
+}
+
+impl UsageMeter {
+    pub fn add(&mut self, turn: &TurnUsage) {
+        self.tokens.prompt_tokens += turn.tokens.prompt_tokens;
+        self.tokens.completion_tokens += turn.tokens.completion_tokens;
+        match turn.cost_usd {
+            Some(cost) => self.cost_usd += cost,
+            None => self.unpriced = true,
+        }
+        self.history = turn.history;
+    }
+
+    /// Status bar text, e.g. `↑12.3k ↓850 ~$0.0512 | 4 turns ~3.1k ctx`.
+    pub fn status_text(&self) -> String {
+        let cost = if self.unpriced && self.cost_usd == 0.0 {
+            "$?".to_string()
+        } else {
+            let plus = if self.unpriced { "+" } else { "" };
+            format!("~${:.4}{plus}", self.cost_usd)
+        };
+        format!(
+            "↑{} ↓{} {cost} | {} turns ~{} ctx",
+            compact_count(self.tokens.prompt_tokens),
+            compact_count(self.tokens.completion_tokens),
+            self.history.turns,
+            compact_count(self.history.tokens),
+        )
+    }
+
+    /// `/cost` reply: this session's token counts and estimated spend.
+    pub fn cost_report(&self) -> String {
+        let TokenUsage {
+            prompt_tokens,
+            completion_tokens,
+        } = self.tokens;
+        if prompt_tokens == 0 && completion_tokens == 0 {
+            return "No model calls yet this session.".to_string();
+        }
+        let cost = if self.unpriced && self.cost_usd == 0.0 {
+            "unknown (no price for this model)".to_string()
+        } else if self.unpriced {
+            format!(
+                "~${:.4} (plus turns on models without a known price)",
+                self.cost_usd
+            )
+        } else {
+            format!("~${:.4}", self.cost_usd)
+        };
+        format!(
+            "Session usage: {prompt_tokens} prompt + {completion_tokens} completion tokens \
+             (estimated)\nCost: {cost}\nDaily totals: `jarvis usage`"
+        )
+    }
+}
+
+/// `950`, `12.3k`, `1.2M`.
+#[allow(clippy::cast_precision_loss)]
+fn compact_count(n: u64) -> String {
+    match n {
+        0..1_000 => n.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", n as f64 / 1_000.0),
+        _ => format!("{:.1}M", n as f64 / 1_000_000.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::traits::{FunctionCall, ToolCall};
+
+    fn user(text: &str) -> ChatMessage {
+        ChatMessage::User {
+            content: text.into(),
+        }
+    }
+
+    fn assistant(text: &str) -> ChatMessage {
+        ChatMessage::Assistant {
+            content: Some(text.into()),
+            tool_calls: None,
+        }
+    }
+
+    #[test]
+    fn estimates_latin_and_cjk_text() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        assert_eq!(estimate_tokens("你好"), 2);
+    }
+
+    #[test]
+    fn looks_up_pricing_by_model_name() {
+        assert_eq!(pricing("gpt-4o-mini"), Some((0.15, 0.6)));
+        assert_eq!(pricing("openai/gpt-4o"), Some((2.5, 10.0)));
+        assert_eq!(
+            pricing("anthropic/claude-sonnet-4-20250514"),
+            Some((3.0, 15.0))
+        );
+        assert_eq!(pricing("llama3.2"), None);
+
+        let usage = TokenUsage {
+            prompt_tokens: 1_000_000,
+            completion_tokens: 100_000,
+        };
+        let cost = usage.cost_usd("gpt-4o").unwrap();
+        assert!((cost - 3.5).abs() < 1e-9);
+        assert_eq!(usage.cost_usd("llama3.2"), None);
+    }
+
+    #[test]
+    fn turn_usage_counts_every_model_call() {
+        let history = vec![
+            ChatMessage::System {
+                content: "s".repeat(40),
+            },
+            user(&"u".repeat(40)),
+            ChatMessage::Assistant {
+                content: None,
+                tool_calls: Some(vec![ToolCall {
+                    id: "1".into(),
+                    function: FunctionCall {
+                        name: "shell".into(),
+                        arguments: "{}".into(),
+                    },
+                }]),
+            },
+            ChatMessage::Tool {
+                tool_call_id: "1".into(),
+                content: "t".repeat(40),
+            },
+            assistant(&"a".repeat(40)),
+        ];
+        // System, user, tool result and reply are 14 each, the tool call 7,
+        // and the empty tool list (`[]`) 1 per call
+        let turn = TurnUsage::of_turn(&history, 1, &[], "gpt-4o");
+        assert_eq!(turn.tokens.prompt_tokens, (28 + 1) + (28 + 7 + 14 + 1));
+        assert_eq!(turn.tokens.completion_tokens, 7 + 14);
+        assert!(turn.cost_usd.is_some());
+        assert_eq!(
+            turn.history,
+            HistorySize {
+                turns: 1,
+                tokens: 63
+            }
+        );
+    }
+
+    #[test]
+    fn meter_accumulates_and_formats() {
+        let mut meter = UsageMeter::default();
+        assert_eq!(meter.status_text(), "↑0 ↓0 ~$0.0000 | 0 turns ~0 ctx");
+
+        let turn = TurnUsage {
+            tokens: TokenUsage {
+                prompt_tokens: 12_340,
+                completion_tokens: 850,
+            },
+            cost_usd: Some(0.05),
+            history: HistorySize {
+                turns: 4,
+                tokens: 3_100,
+            },
+        };
+        meter.add(&turn);
+        meter.add(&TurnUsage {
+            cost_usd: None,
+            ..turn
+        });
+        assert_eq!(
+            meter.status_text(),
+            "↑24.7k ↓1.7k ~$0.0500+ | 4 turns ~3.1k ctx"
+        );
+    }
+
+    #[test]
+    fn cost_report_describes_session_spend() {
+        let mut meter = UsageMeter::default();
+        assert_eq!(meter.cost_report(), "No model calls yet this session.");
+
+        meter.add(&TurnUsage {
+            tokens: TokenUsage {
+                prompt_tokens: 1_200,
+                completion_tokens: 80,
+            },
+            cost_usd: Some(0.0123),
+            ..TurnUsage::default()
+        });
+        assert_eq!(
+            meter.cost_report(),
+            "Session usage: 1200 prompt + 80 completion tokens (estimated)\n\
+             Cost: ~$0.0123\nDaily totals: `jarvis usage`"
+        );
+
+        meter.unpriced = true;
+        assert!(meter
+            .cost_report()
+            .contains("~$0.0123 (plus turns on models"));
+        meter.cost_usd = 0.0;
+        assert!(meter.cost_report().contains("Cost: unknown"));
+    }
+}
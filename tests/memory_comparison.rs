@@ -145,8 +145,8 @@ async fn compare_recall_quality() {
     println!("RECALL QUALITY (10 entries seeded):\n");
 
     for (query, desc) in &queries {
-        let sq_results = sq.recall(query, 10).await.unwrap();
-        let md_results = md.recall(query, 10).await.unwrap();
+        let sq_results = sq.recall(query, 10, &[]).await.unwrap();
+        let md_results = md.recall(query, 10, &[]).await.unwrap();
 
         println!("  Query: \"{query}\" — {desc}");
         println!("    SQLite:   {} results", sq_results.len());
@@ -200,11 +200,11 @@ async fn compare_recall_speed() {
 
     // Benchmark recall
     let start = Instant::now();
-    let sq_results = sq.recall("Rust systems", 10).await.unwrap();
+    let sq_results = sq.recall("Rust systems", 10, &[]).await.unwrap();
     let sq_dur = start.elapsed();
 
     let start = Instant::now();
-    let md_results = md.recall("Rust systems", 10).await.unwrap();
+    let md_results = md.recall("Rust systems", 10, &[]).await.unwrap();
     let md_dur = start.elapsed();
 
     println!("\n============================================================");
@@ -300,7 +300,7 @@ async fn compare_upsert() {
     let md_count = md.count().await.unwrap();
 
     let sq_entry = sq.get("pref").await.unwrap();
-    let md_results = md.recall("loves Rust", 5).await.unwrap();
+    let md_results = md.recall("loves Rust", 5, &[]).await.unwrap();
 
     println!("\n============================================================");
     println!("UPSERT (store same key twice):");